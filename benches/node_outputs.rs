@@ -0,0 +1,106 @@
+//! Throughput of storing node outputs on wide fan-outs, where every running
+//! node holds a snapshot of the outputs while its siblings store theirs
+//!
+//! Run with `cargo bench --bench node_outputs`; pass `--save-baseline <name>`
+//! and `--baseline <name>` to compare two revisions.
+
+// `criterion_group!` generates an undocumented `pub fn`
+#![allow(missing_docs)]
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::{NodeOutputs, Secrets},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::Arc;
+
+const WIDTHS: [usize; 3] = [64, 512, 4096];
+
+/// Store the outputs of `width` sibling nodes, each taking a snapshot when it
+/// starts and keeping it until all of them are done
+fn fan_out_writes(c: &mut Criterion) {
+    let payload = Arc::new(json!({"id": "report", "text": "x".repeat(1024)}));
+    let mut group = c.benchmark_group("node_outputs/fan_out_writes");
+    for width in WIDTHS {
+        group.throughput(Throughput::Elements(width as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(width), &width, |b, &width| {
+            b.iter(|| {
+                let mut outputs = NodeOutputs::new();
+                let mut snapshots = Vec::with_capacity(width);
+                for i in 0..width {
+                    snapshots.push(outputs.clone());
+                    // Outputs are stored under both the node id and name
+                    outputs.insert(format!("id-{i}"), Arc::clone(&payload));
+                    outputs.insert(format!("branch_{i}"), Arc::clone(&payload));
+                }
+                black_box((outputs, snapshots))
+            });
+        });
+    }
+    group.finish();
+}
+
+/// `source` fans a payload out to `width` transforms reading it
+fn wide_workflow(width: usize) -> Workflow {
+    let mut workflow = Workflow::new("wide", "");
+    let source = WorkflowNode::new(
+        "source",
+        "",
+        NodeType::Transform {
+            transformation: "vars.payload".to_string(),
+        },
+    );
+    let source_id = source.id.clone();
+    workflow.add_node(source).expect("source node");
+    for i in 0..width {
+        let branch = WorkflowNode::new(
+            format!("branch_{i}"),
+            "",
+            NodeType::Transform {
+                transformation: "input.id".to_string(),
+            },
+        );
+        let branch_id = branch.id.clone();
+        workflow.add_node(branch).expect("branch node");
+        workflow
+            .connect_nodes(source_id.clone(), branch_id, WorkflowEdge::data_flow())
+            .expect("fan-out edge");
+    }
+    workflow
+}
+
+/// Execute a workflow fanning one payload out to `width` nodes
+fn wide_workflow_execution(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let payload = json!({"id": "report", "text": "x".repeat(64 * 1024)});
+    let mut group = c.benchmark_group("node_outputs/wide_workflow");
+    group.sample_size(10);
+    for width in WIDTHS.into_iter().take(2) {
+        group.throughput(Throughput::Elements(width as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(width), &width, |b, &width| {
+            b.iter(|| {
+                let context = runtime
+                    .block_on(
+                        WorkflowExecutor::new()
+                            .without_retries()
+                            .execute_with_inputs(
+                                wide_workflow(width),
+                                None,
+                                HashMap::from([("payload".to_string(), payload.clone())]),
+                                Secrets::default(),
+                            ),
+                    )
+                    .expect("wide workflow runs");
+                black_box(context)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, fan_out_writes, wide_workflow_execution);
+criterion_main!(benches);
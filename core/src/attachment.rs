@@ -0,0 +1,261 @@
+//! Binary attachments passed between nodes
+//!
+//! Node outputs are JSON, so files, images and audio travel beside them as
+//! [`Attachment`]s, kept in the [`WorkflowContext`](crate::types::WorkflowContext)
+//! under the name of the node that produced them. The document loader node
+//! attaches the file it loaded and the HTTP request node attaches binary
+//! response bodies. Templates reference them by position:
+//!
+//! ```text
+//! {{nodes.loader.attachments[0]}}
+//! ```
+//!
+//! Agent prompts send referenced images to vision-capable models as image
+//! inputs, the `multipart` fields of HTTP request nodes upload referenced
+//! attachments as files, and any other template renders the reference as the
+//! path of a local copy that is removed when the execution finishes.
+//!
+//! Attachments larger than the threshold of the execution's
+//! [`OutputSpill`] are written to its output store instead of the context.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::output_store::OutputSpill;
+
+/// Default size limit of a single attachment: 25 MiB
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 25 * 1024 * 1024;
+
+/// File, image or audio clip produced by a node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Unique ID of the attachment
+    pub id: String,
+    /// MIME type of the content, e.g. `image/png`
+    pub mime_type: String,
+    /// Original file name, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Size of the content in bytes
+    pub size_bytes: u64,
+    /// SHA-256 hex digest of the content
+    pub sha256: String,
+    /// Where the content is kept
+    pub data: AttachmentData,
+}
+
+/// Where the content of an [`Attachment`] is kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "storage", rename_all = "snake_case")]
+pub enum AttachmentData {
+    /// In the context, base64-encoded when serialized
+    Inline {
+        /// The content
+        #[serde(with = "base64_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// In the execution's output store, under the attachment's SHA-256
+    Stored,
+    /// In a file on disk, such as the document a loader node read
+    File {
+        /// Path of the file
+        path: PathBuf,
+    },
+}
+
+impl Attachment {
+    /// Attachment holding `bytes` of type `mime_type`
+    pub fn from_bytes(bytes: Vec<u8>, mime_type: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            mime_type: mime_type.into(),
+            filename: None,
+            size_bytes: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&bytes)),
+            data: AttachmentData::Inline { bytes },
+        }
+    }
+
+    /// Attachment referring to the file at `path`, typed from its extension.
+    ///
+    /// The file is read once for its digest; reading the attachment later
+    /// fails if the file changed in between.
+    pub fn from_file(path: impl Into<PathBuf>) -> GraphBitResult<Self> {
+        let path = path.into();
+        let bytes = std::fs::read(&path)?;
+        Ok(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            mime_type: mime_type_for_path(&path).to_string(),
+            filename: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            size_bytes: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&bytes)),
+            data: AttachmentData::File { path },
+        })
+    }
+
+    /// Set the original file name
+    #[must_use]
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Whether the content is an image
+    #[must_use]
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+
+    /// The content, read from the output store of `spill` when it was stored there
+    pub fn read(&self, spill: Option<&OutputSpill>) -> GraphBitResult<Vec<u8>> {
+        match &self.data {
+            AttachmentData::Inline { bytes } => Ok(bytes.clone()),
+            AttachmentData::Stored => spill
+                .ok_or_else(|| {
+                    GraphBitError::workflow_execution(format!(
+                        "Attachment {} is in an output store, but no output spill is attached",
+                        self.id
+                    ))
+                })?
+                .load_bytes(&self.sha256),
+            AttachmentData::File { path } => {
+                let bytes = std::fs::read(path)?;
+                if format!("{:x}", Sha256::digest(&bytes)) != self.sha256 {
+                    return Err(GraphBitError::workflow_execution(format!(
+                        "Attachment {}: file {} changed since it was attached",
+                        self.id,
+                        path.display()
+                    )));
+                }
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Write the content to `path`
+    pub fn save(&self, path: &Path, spill: Option<&OutputSpill>) -> GraphBitResult<()> {
+        Ok(std::fs::write(path, self.read(spill)?)?)
+    }
+
+    /// The content, base64-encoded
+    pub fn read_base64(&self, spill: Option<&OutputSpill>) -> GraphBitResult<String> {
+        Ok(BASE64.encode(self.read(spill)?))
+    }
+
+    /// Name of the local copy of the attachment: its ID followed by its file
+    /// name, or by an extension matching its type
+    #[must_use]
+    pub fn local_file_name(&self) -> String {
+        let filename = self
+            .filename
+            .as_deref()
+            .and_then(|name| Path::new(name).file_name())
+            .map(|name| name.to_string_lossy().into_owned());
+        filename.map_or_else(
+            || format!("{}.{}", self.id, extension_for_mime_type(&self.mime_type)),
+            |filename| format!("{}-{filename}", self.id),
+        )
+    }
+}
+
+/// MIME type of a file from its extension, `application/octet-stream` when unknown
+#[must_use]
+pub fn mime_type_for_path(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    MIME_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map_or("application/octet-stream", |(_, mime_type)| mime_type)
+}
+
+/// File extension for a MIME type, `bin` when unknown
+#[must_use]
+pub fn extension_for_mime_type(mime_type: &str) -> &'static str {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    MIME_TYPES
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(essence))
+        .map_or("bin", |(ext, _)| ext)
+}
+
+/// Extensions and MIME types of common file types, preferred extension first
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("json", "application/json"),
+    ("csv", "text/csv"),
+    ("xml", "application/xml"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    ("xls", "application/vnd.ms-excel"),
+    (
+        "xlsb",
+        "application/vnd.ms-excel.sheet.binary.macroenabled.12",
+    ),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("ogg", "audio/ogg"),
+    ("m4a", "audio/mp4"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("zip", "application/zip"),
+];
+
+/// Directory holding the local copies of the attachments of an execution
+#[must_use]
+pub fn temp_dir(execution_id: uuid::Uuid) -> PathBuf {
+    std::env::temp_dir().join(format!("graphbit-attachments-{execution_id}"))
+}
+
+/// Remove a directory of local copies made by [`temp_dir`]
+pub fn remove_temp_dir(dir: &Path) {
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!(
+            "Failed to remove attachment files in {}: {e}",
+            dir.display()
+        ),
+    }
+}
+
+/// Serialize bytes as a base64 string
+mod base64_bytes {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        BASE64.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
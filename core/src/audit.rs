@@ -0,0 +1,614 @@
+//! Audit log of provider calls
+//!
+//! An [`AuditLog`] registered with
+//! [`WorkflowExecutor::with_audit_log`](crate::workflow::WorkflowExecutor::with_audit_log)
+//! receives one [`AuditRecord`] for every LLM call, tool call from the tool
+//! registry and HTTP request node of a run, and for every embedding request
+//! made inside [`AuditLog::scope`]. Prompts and completions are stored in full,
+//! hashed or redacted according to the [`AuditPolicy`].
+//!
+//! Records are chained: each one carries the hash of the record before it, so
+//! [`verify_chain`] detects records edited, removed or reordered after they
+//! were written. Records are handed to an [`AuditSink`], such as the
+//! [`JsonlAuditSink`], by a background task, so a slow sink never holds up
+//! execution; [`AuditLog::flush`] waits until everything is written.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::{LlmProviderTrait, LlmRequest, LlmResponse, LlmUsage, RedactionMiddleware};
+
+tokio::task_local! {
+    static CURRENT: (AuditLog, AuditContext);
+}
+
+/// Kind of call an [`AuditRecord`] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditCallKind {
+    /// Completion request to an LLM provider
+    Llm,
+    /// Embedding request
+    Embedding,
+    /// Tool invocation
+    Tool,
+    /// Request sent by an HTTP request node
+    Http,
+    /// Checks run by a guardrail node
+    Guardrail,
+}
+
+/// How an audited call ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The call succeeded
+    Success,
+    /// The call failed
+    Error {
+        /// Error the call failed with
+        message: String,
+    },
+}
+
+/// What an [`AuditRecord`] keeps of prompts and completions
+#[derive(Debug, Clone, Default)]
+pub enum AuditPolicy {
+    /// The text as sent and received
+    Full,
+    /// Only the SHA-256 hash of the text, as `sha256:<hex>`
+    #[default]
+    Hash,
+    /// The text with matches of the redaction patterns replaced
+    Redact(RedactionMiddleware),
+}
+
+impl AuditPolicy {
+    /// Redaction of email addresses, API keys, bearer tokens and card numbers
+    #[must_use]
+    pub fn redact() -> Self {
+        Self::Redact(RedactionMiddleware::new())
+    }
+
+    /// Apply the policy to `text`
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Self::Full => text.to_string(),
+            Self::Hash => sha256(text),
+            Self::Redact(redaction) => redaction.redact(text),
+        }
+    }
+}
+
+/// Execution and node an audited call was made for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditContext {
+    /// Execution making the call
+    pub execution_id: Option<String>,
+    /// Node making the call
+    pub node_id: Option<String>,
+    /// Name of the node making the call
+    pub node_name: Option<String>,
+}
+
+/// A call to be audited, before the policy is applied
+#[derive(Debug, Clone)]
+pub struct AuditCall {
+    /// Kind of call
+    pub kind: AuditCallKind,
+    /// Provider, tool runtime or protocol handling the call
+    pub provider: String,
+    /// Model, tool or endpoint called
+    pub model: Option<String>,
+    /// When the call started
+    pub started_at: DateTime<Utc>,
+    /// How long the call took
+    pub latency_ms: u64,
+    /// Text sent
+    pub prompt: String,
+    /// Text received, if the call succeeded
+    pub completion: Option<String>,
+    /// Tokens used, for LLM and embedding calls
+    pub usage: Option<LlmUsage>,
+    /// How the call ended
+    pub outcome: AuditOutcome,
+}
+
+/// One audited call, as written to an [`AuditSink`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position of the record in the log, starting at 0
+    pub sequence: u64,
+    /// When the call started
+    pub started_at: DateTime<Utc>,
+    /// When the call ended
+    pub finished_at: DateTime<Utc>,
+    /// How long the call took
+    pub latency_ms: u64,
+    /// Execution making the call
+    pub execution_id: Option<String>,
+    /// Node making the call
+    pub node_id: Option<String>,
+    /// Name of the node making the call
+    pub node_name: Option<String>,
+    /// Kind of call
+    pub kind: AuditCallKind,
+    /// Provider, tool runtime or protocol handling the call
+    pub provider: String,
+    /// Model, tool or endpoint called
+    pub model: Option<String>,
+    /// Text sent, after the policy was applied
+    pub prompt: String,
+    /// Text received, after the policy was applied
+    pub completion: Option<String>,
+    /// Tokens used, for LLM and embedding calls
+    pub usage: Option<LlmUsage>,
+    /// How the call ended
+    pub outcome: AuditOutcome,
+    /// Hash of the previous record, `None` for the first record of a log
+    pub previous_hash: Option<String>,
+    /// Hash of this record, computed with `hash` left empty
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// Hash of the record's contents, computed with `hash` left empty
+    #[must_use]
+    pub fn compute_hash(&self) -> String {
+        let unhashed = Self {
+            hash: String::new(),
+            ..self.clone()
+        };
+        sha256(&serde_json::to_string(&unhashed).unwrap_or_default())
+    }
+}
+
+/// Check that `records`, consecutive records of a log, are unaltered: each
+/// record's hash matches its contents and names the record before it
+pub fn verify_chain(records: &[AuditRecord]) -> GraphBitResult<()> {
+    let mut previous: Option<&AuditRecord> = None;
+    for record in records {
+        if record.hash != record.compute_hash() {
+            return Err(GraphBitError::validation(
+                "audit_log",
+                format!("Audit record {} does not match its hash", record.sequence),
+            ));
+        }
+        if let Some(previous) = previous {
+            if record.sequence != previous.sequence + 1
+                || record.previous_hash.as_deref() != Some(previous.hash.as_str())
+            {
+                return Err(GraphBitError::validation(
+                    "audit_log",
+                    format!(
+                        "Audit record {} does not follow record {}",
+                        record.sequence, previous.sequence
+                    ),
+                ));
+            }
+        }
+        previous = Some(record);
+    }
+    Ok(())
+}
+
+/// Destination of audit records
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Store `record`
+    async fn write(&self, record: &AuditRecord) -> GraphBitResult<()>;
+
+    /// Make sure every record written so far is stored durably
+    async fn flush(&self) -> GraphBitResult<()> {
+        Ok(())
+    }
+
+    /// Last record stored by an earlier log, which the chain continues from
+    async fn last_record(&self) -> GraphBitResult<Option<AuditRecord>> {
+        Ok(None)
+    }
+}
+
+/// Open file and its size
+type OpenFile = (tokio::fs::File, u64);
+
+/// Writes records as JSON lines to a file, rotating it by size.
+///
+/// When a record would take the file past the size limit, `audit.jsonl` is
+/// renamed to `audit.jsonl.1`, `audit.jsonl.1` to `audit.jsonl.2` and so on,
+/// dropping files beyond the configured count, and a new file is started.
+#[derive(Debug)]
+pub struct JsonlAuditSink {
+    path: PathBuf,
+    max_bytes: Option<u64>,
+    max_files: usize,
+    file: tokio::sync::Mutex<Option<OpenFile>>,
+}
+
+impl JsonlAuditSink {
+    /// Append records to the file at `path`, without rotation
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: None,
+            max_files: 5,
+            file: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Rotate the file once it would grow past `max_bytes`
+    #[must_use]
+    pub const fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Keep at most `max_files` rotated files besides the current one (5 by default)
+    #[must_use]
+    pub const fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Path of the file records are appended to
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the `index`th rotated file
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    async fn open(&self) -> GraphBitResult<OpenFile> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        let size = file.metadata().await?.len();
+        Ok((file, size))
+    }
+
+    async fn rotate(&self) -> GraphBitResult<()> {
+        if self.max_files == 0 {
+            tokio::fs::remove_file(&self.path).await?;
+            return Ok(());
+        }
+        let oldest = self.rotated_path(self.max_files);
+        if tokio::fs::try_exists(&oldest).await? {
+            tokio::fs::remove_file(&oldest).await?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if tokio::fs::try_exists(&from).await? {
+                tokio::fs::rename(&from, self.rotated_path(index + 1)).await?;
+            }
+        }
+        tokio::fs::rename(&self.path, self.rotated_path(1)).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonlAuditSink {
+    async fn write(&self, record: &AuditRecord) -> GraphBitResult<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let line_len = line.len() as u64;
+
+        let mut file = self.file.lock().await;
+        if let (Some(max_bytes), Some((_, size))) = (self.max_bytes, file.as_ref()) {
+            if *size > 0 && size + line_len > max_bytes {
+                *file = None;
+                self.rotate().await?;
+            }
+        }
+        let (handle, size) = match file.as_mut() {
+            Some(open) => open,
+            None => file.insert(self.open().await?),
+        };
+        handle.write_all(line.as_bytes()).await?;
+        handle.flush().await?;
+        *size += line_len;
+        drop(file);
+        Ok(())
+    }
+
+    async fn flush(&self) -> GraphBitResult<()> {
+        if let Some((handle, _)) = self.file.lock().await.as_mut() {
+            handle.sync_data().await?;
+        }
+        Ok(())
+    }
+
+    async fn last_record(&self) -> GraphBitResult<Option<AuditRecord>> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        contents
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(Into::into)
+    }
+}
+
+enum Command {
+    Record(Box<(AuditContext, AuditCall)>),
+    Flush(oneshot::Sender<GraphBitResult<()>>),
+}
+
+struct Inner {
+    sink: Arc<dyn AuditSink>,
+    policy: AuditPolicy,
+    /// Channel to the writer task, started with the first record
+    sender: OnceLock<mpsc::UnboundedSender<Command>>,
+}
+
+/// Handle to an audit log. Clones share the same log.
+#[derive(Clone)]
+pub struct AuditLog {
+    inner: Arc<Inner>,
+}
+
+impl AuditLog {
+    /// Log calls to `sink`, applying `policy` to prompts and completions
+    pub fn new(sink: impl AuditSink + 'static, policy: AuditPolicy) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                sink: Arc::new(sink),
+                policy,
+                sender: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Policy applied to prompts and completions
+    #[must_use]
+    pub fn policy(&self) -> &AuditPolicy {
+        &self.inner.policy
+    }
+
+    /// Queue `call`, made for `context`, to be written
+    pub fn record(&self, context: AuditContext, call: AuditCall) {
+        // The writer only stops once every handle is dropped
+        let _ = self
+            .sender()
+            .send(Command::Record(Box::new((context, call))));
+    }
+
+    /// Wait until every call recorded so far is written and flushed
+    pub async fn flush(&self) -> GraphBitResult<()> {
+        let Some(sender) = self.inner.sender.get() else {
+            return Ok(());
+        };
+        let (done, written) = oneshot::channel();
+        if sender.send(Command::Flush(done)).is_err() {
+            return Ok(());
+        }
+        written.await.unwrap_or(Ok(()))
+    }
+
+    /// Run `future`, recording the calls it makes for `context`
+    pub async fn scope<F: Future>(&self, context: AuditContext, future: F) -> F::Output {
+        CURRENT.scope((self.clone(), context), future).await
+    }
+
+    fn sender(&self) -> &mpsc::UnboundedSender<Command> {
+        self.inner.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            tokio::spawn(write_records(
+                self.inner.sink.clone(),
+                self.inner.policy.clone(),
+                receiver,
+            ));
+            sender
+        })
+    }
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("policy", &self.inner.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Writer task: applies the policy, chains the records and writes them in order
+async fn write_records(
+    sink: Arc<dyn AuditSink>,
+    policy: AuditPolicy,
+    mut receiver: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut previous = sink.last_record().await.unwrap_or_else(|e| {
+        tracing::error!("Failed to read the last audit record, starting a new chain: {e}");
+        None
+    });
+    let mut failure = None;
+    while let Some(command) = receiver.recv().await {
+        match command {
+            Command::Record(entry) => {
+                let (context, call) = *entry;
+                let mut record = AuditRecord {
+                    sequence: previous.as_ref().map_or(0, |record| record.sequence + 1),
+                    started_at: call.started_at,
+                    finished_at: call.started_at
+                        + chrono::Duration::milliseconds(
+                            i64::try_from(call.latency_ms).unwrap_or(i64::MAX),
+                        ),
+                    latency_ms: call.latency_ms,
+                    execution_id: context.execution_id,
+                    node_id: context.node_id,
+                    node_name: context.node_name,
+                    kind: call.kind,
+                    provider: call.provider,
+                    model: call.model,
+                    prompt: policy.apply(&call.prompt),
+                    completion: call.completion.as_deref().map(|text| policy.apply(text)),
+                    usage: call.usage,
+                    outcome: call.outcome,
+                    previous_hash: previous.as_ref().map(|record| record.hash.clone()),
+                    hash: String::new(),
+                };
+                record.hash = record.compute_hash();
+                match sink.write(&record).await {
+                    Ok(()) => previous = Some(record),
+                    Err(e) => {
+                        tracing::error!(
+                            sequence = record.sequence,
+                            "Failed to write audit record: {e}"
+                        );
+                        failure = Some(e);
+                    }
+                }
+            }
+            Command::Flush(done) => {
+                let result = match failure.take() {
+                    Some(e) => Err(e),
+                    None => sink.flush().await,
+                };
+                let _ = done.send(result);
+            }
+        }
+    }
+}
+
+/// Record `call` in the audit log of the current scope, if any
+pub fn record(call: AuditCall) {
+    let _ = CURRENT.try_with(|(log, context)| log.record(context.clone(), call));
+}
+
+/// Whether calls made now are audited
+fn is_active() -> bool {
+    CURRENT.try_with(|_| ()).is_ok()
+}
+
+fn sha256(text: &str) -> String {
+    format!("sha256:{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// Run `future`, the execution of a node, recording its calls in `log`
+pub(crate) async fn run_node<F: Future>(
+    log: Option<AuditLog>,
+    context: AuditContext,
+    future: F,
+) -> F::Output {
+    match log {
+        Some(log) => log.scope(context, future).await,
+        None => future.await,
+    }
+}
+
+/// Run `call` with `input` and, inside a scope, record the call together with
+/// `prompt`, the text of the input, and what `describe` makes of the output:
+/// the completion text and token usage
+pub(crate) async fn observe<I, T, Fut>(
+    kind: AuditCallKind,
+    provider: &str,
+    model: Option<&str>,
+    input: I,
+    prompt: impl FnOnce(&I) -> String,
+    call: impl FnOnce(I) -> Fut,
+    describe: impl FnOnce(&T) -> (String, Option<LlmUsage>),
+) -> GraphBitResult<T>
+where
+    Fut: Future<Output = GraphBitResult<T>>,
+{
+    if !is_active() {
+        return call(input).await;
+    }
+    let prompt = prompt(&input);
+    let started_at = Utc::now();
+    let start = Instant::now();
+    let result = call(input).await;
+    let latency_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let (completion, usage, outcome) = match &result {
+        Ok(output) => {
+            let (completion, usage) = describe(output);
+            (Some(completion), usage, AuditOutcome::Success)
+        }
+        Err(e) => (
+            None,
+            None,
+            AuditOutcome::Error {
+                message: e.to_string(),
+            },
+        ),
+    };
+    record(AuditCall {
+        kind,
+        provider: provider.to_string(),
+        model: model.map(str::to_string),
+        started_at,
+        latency_ms,
+        prompt,
+        completion,
+        usage,
+        outcome,
+    });
+    result
+}
+
+/// Send `request` to `provider` through `call`, recording the call
+pub(crate) async fn llm_call<F, Fut>(
+    provider: &dyn LlmProviderTrait,
+    request: LlmRequest,
+    call: F,
+) -> GraphBitResult<LlmResponse>
+where
+    F: FnOnce(LlmRequest) -> Fut,
+    Fut: Future<Output = GraphBitResult<LlmResponse>>,
+{
+    observe(
+        AuditCallKind::Llm,
+        provider.provider_name(),
+        Some(provider.model_name()),
+        request,
+        |request| {
+            request
+                .messages
+                .iter()
+                .map(|message| {
+                    let role = serde_json::to_value(&message.role)
+                        .ok()
+                        .and_then(|role| role.as_str().map(str::to_string))
+                        .unwrap_or_default();
+                    format!("{role}: {}", message.content)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
+        call,
+        |response: &LlmResponse| {
+            let mut completion = response.content.clone();
+            if !response.tool_calls.is_empty() {
+                if let Ok(tool_calls) = serde_json::to_string(&response.tool_calls) {
+                    completion.push_str(&tool_calls);
+                }
+            }
+            (completion, Some(response.usage.clone()))
+        },
+    )
+    .await
+}
@@ -0,0 +1,187 @@
+//! Token and cost budgets of a workflow execution
+//!
+//! A [`BudgetTracker`] runs as LLM middleware for one execution: it sums the
+//! usage and cost of every LLM response and, once the budget is used up,
+//! refuses further LLM calls except from nodes marked `budget_exempt`. The
+//! executor then stops scheduling nodes and fails the run with
+//! [`FailureReason::BudgetExceeded`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::{LlmMiddleware, LlmRequest, LlmResponse, LlmUsage};
+use crate::types::FailureReason;
+
+tokio::task_local! {
+    static BUDGET_EXEMPT: bool;
+}
+
+/// What happens to nodes still running when the budget runs out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPolicy {
+    /// Let running nodes finish; their LLM calls after the budget ran out are refused
+    #[default]
+    Finish,
+    /// Stop running nodes, except budget-exempt ones
+    Cancel,
+}
+
+/// Limits on the LLM usage of one workflow execution
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionBudget {
+    /// Tokens, prompt and completion, the execution may use
+    pub max_total_tokens: Option<u64>,
+    /// Cost in USD the execution may incur, priced with the providers' costs per token
+    pub max_cost_usd: Option<f64>,
+    /// What happens to running nodes once the budget runs out
+    pub policy: BudgetPolicy,
+}
+
+impl ExecutionBudget {
+    /// Whether any limit is set
+    #[must_use]
+    pub const fn is_limited(&self) -> bool {
+        self.max_total_tokens.is_some() || self.max_cost_usd.is_some()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Spent {
+    usage: LlmUsage,
+    cost_usd: f64,
+}
+
+/// Usage of one execution against its [`ExecutionBudget`]
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: ExecutionBudget,
+    /// Input and output cost per token, by model
+    prices: HashMap<String, (f64, f64)>,
+    spent: Arc<Mutex<Spent>>,
+}
+
+impl BudgetTracker {
+    /// Track usage against `budget`, pricing responses with `prices`, the
+    /// input and output cost per token by model name
+    #[must_use]
+    pub fn new(budget: ExecutionBudget, prices: HashMap<String, (f64, f64)>) -> Self {
+        Self {
+            budget,
+            prices,
+            spent: Arc::default(),
+        }
+    }
+
+    /// Tokens used so far
+    #[must_use]
+    pub fn usage(&self) -> LlmUsage {
+        self.spent().usage.clone()
+    }
+
+    /// Cost in USD incurred so far
+    #[must_use]
+    pub fn cost_usd(&self) -> f64 {
+        self.spent().cost_usd
+    }
+
+    /// Whether usage has reached a limit of the budget
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        let spent = self.spent();
+        self.budget
+            .max_total_tokens
+            .is_some_and(|max| u64::from(spent.usage.total_tokens) >= max)
+            || self
+                .budget
+                .max_cost_usd
+                .is_some_and(|max| spent.cost_usd >= max)
+    }
+
+    /// What happens to running nodes once the budget runs out
+    #[must_use]
+    pub const fn policy(&self) -> BudgetPolicy {
+        self.budget.policy
+    }
+
+    /// Reason a run stopped by this budget failed, with the usage so far
+    #[must_use]
+    pub fn failure_reason(&self) -> FailureReason {
+        let spent = self.spent();
+        FailureReason::BudgetExceeded {
+            usage: spent.usage.clone(),
+            cost_usd: spent.cost_usd,
+        }
+    }
+
+    /// Message describing how the budget ran out
+    #[must_use]
+    pub fn exhausted_message(&self) -> String {
+        let (tokens, cost_usd) = {
+            let spent = self.spent();
+            (spent.usage.total_tokens, spent.cost_usd)
+        };
+        let mut limits = Vec::new();
+        if let Some(max) = self.budget.max_total_tokens {
+            limits.push(format!("{tokens} of {max} tokens"));
+        }
+        if let Some(max) = self.budget.max_cost_usd {
+            limits.push(format!("${cost_usd:.4} of ${max:.4}"));
+        }
+        format!("Execution budget exhausted: used {}", limits.join(" and "))
+    }
+
+    fn spent(&self) -> std::sync::MutexGuard<'_, Spent> {
+        self.spent
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl LlmMiddleware for BudgetTracker {
+    fn name(&self) -> &str {
+        "budget"
+    }
+
+    async fn before_request(&self, _request: &mut LlmRequest) -> GraphBitResult<()> {
+        if self.is_exhausted() && !BUDGET_EXEMPT.try_with(|exempt| *exempt).unwrap_or(false) {
+            return Err(GraphBitError::workflow_execution(format!(
+                "LLM call refused: {}",
+                self.exhausted_message()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        _request: &LlmRequest,
+        response: &mut LlmResponse,
+    ) -> GraphBitResult<()> {
+        let cost = crate::usage::price(&self.prices, &response.model, &response.usage);
+        if cost.is_none() && self.budget.max_cost_usd.is_some() {
+            tracing::warn!(
+                model = %response.model,
+                "No price known for model; its calls do not count toward the cost budget"
+            );
+        }
+        {
+            let mut spent = self.spent();
+            spent.usage.add(&response.usage);
+            spent.cost_usd += cost.unwrap_or(0.0);
+        }
+        Ok(())
+    }
+}
+
+/// Run `future`, the execution of a node that is budget-exempt or not. The LLM
+/// calls of exempt nodes are made even after the budget ran out.
+pub(crate) async fn run_node<F: Future>(exempt: bool, future: F) -> F::Output {
+    BUDGET_EXEMPT.scope(exempt, future).await
+}
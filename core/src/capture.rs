@@ -0,0 +1,276 @@
+//! Capture of rendered prompts and raw provider payloads
+//!
+//! When an executor runs with
+//! [`WorkflowExecutor::with_capture_payloads`](crate::workflow::WorkflowExecutor::with_capture_payloads),
+//! each node runs inside a capture scope. The prompt the node sends after
+//! template resolution, and every request an LLM provider sends through
+//! [`send`] together with the raw response body, are recorded into a
+//! [`NodeDebugCapture`] attached to the node's result.
+//!
+//! Credentials are redacted before anything is recorded: the values of
+//! authorization, cookie and key, token or secret headers and query
+//! parameters are replaced with [`Secrets::REDACTED`], including where they
+//! reappear in the request body. Captured prompts and payloads are cut to the
+//! configured length.
+//!
+//! The follow-up requests of an agent's tool loop also record the conversation
+//! they send, after the node's history and context windows were applied. Nodes
+//! with [post-processors](crate::post_process) record their output from before
+//! post-processing.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::GraphBitResult;
+use crate::llm::LlmMessage;
+use crate::memory::processor::transcript_line;
+use crate::types::{
+    NodeDebugCapture, NodeExecutionResult, PayloadCaptureConfig, ProviderExchange, Secrets,
+    SentHistory,
+};
+
+tokio::task_local! {
+    static CURRENT: Recorder;
+}
+
+/// Capture of the node running in the current scope
+#[derive(Clone)]
+struct Recorder {
+    config: PayloadCaptureConfig,
+    capture: Arc<Mutex<NodeDebugCapture>>,
+}
+
+impl Recorder {
+    fn update(&self, update: impl FnOnce(&mut NodeDebugCapture)) {
+        if let Ok(mut capture) = self.capture.lock() {
+            update(&mut capture);
+        }
+    }
+}
+
+/// Run `future`, the execution of a node, capturing its prompt and provider
+/// payloads into the result when `config` is set
+pub(crate) async fn run_node<F>(
+    config: Option<PayloadCaptureConfig>,
+    future: F,
+) -> GraphBitResult<NodeExecutionResult>
+where
+    F: Future<Output = GraphBitResult<NodeExecutionResult>>,
+{
+    let Some(config) = config else {
+        return future.await;
+    };
+    let recorder = Recorder {
+        config,
+        capture: Arc::new(Mutex::new(NodeDebugCapture::default())),
+    };
+    let capture = recorder.capture.clone();
+    let result = CURRENT.scope(recorder, future).await?;
+    let capture = capture
+        .lock()
+        .map(|capture| capture.clone())
+        .unwrap_or_default();
+    Ok(if capture.is_empty() {
+        result
+    } else {
+        result.with_debug(Some(capture))
+    })
+}
+
+/// Record the system prompt and prompt a node sends to its LLM
+pub(crate) fn record_prompt(system_prompt: Option<&str>, prompt: &str) {
+    let _ = CURRENT.try_with(|recorder| {
+        let system_prompt = system_prompt.map(|text| recorder.config.truncate(text.to_string()).0);
+        let prompt = recorder.config.truncate(prompt.to_string()).0;
+        recorder.update(|capture| {
+            capture.system_prompt = system_prompt;
+            capture.rendered_prompt = Some(prompt);
+        });
+    });
+}
+
+/// Record a node's output from before its post-processors ran
+pub(crate) fn record_raw_output(output: &serde_json::Value) {
+    let _ = CURRENT.try_with(|recorder| {
+        let raw = match output {
+            serde_json::Value::String(text) => {
+                serde_json::Value::String(recorder.config.truncate(text.clone()).0)
+            }
+            other => other.clone(),
+        };
+        recorder.update(|capture| capture.raw = Some(raw));
+    });
+}
+
+/// Record the conversation a node's tool loop sends in `iteration`, of which
+/// `dropped_exchanges` leading tool exchanges were left out and
+/// `summarized_exchanges` replaced by a summary note
+pub(crate) fn record_history(
+    iteration: u32,
+    messages: &[LlmMessage],
+    dropped_exchanges: usize,
+    summarized_exchanges: usize,
+) {
+    let _ = CURRENT.try_with(|recorder| {
+        let messages = messages
+            .iter()
+            .map(|message| recorder.config.truncate(transcript_line(message)).0)
+            .collect();
+        recorder.update(|capture| {
+            capture.histories.push(SentHistory {
+                iteration,
+                messages,
+                dropped_exchanges,
+                summarized_exchanges,
+            });
+        });
+    });
+}
+
+/// Send `request` on behalf of `provider`, recording it and its response
+/// body when the current node's payloads are captured.
+///
+/// The response body is read completely before it is returned, so use this
+/// for complete, non-streaming requests only.
+pub(crate) async fn send(
+    provider: &str,
+    request: reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    let Ok(recorder) = CURRENT.try_with(Recorder::clone) else {
+        return request.send().await;
+    };
+    let (client, request) = request.build_split();
+    let request = request?;
+    let mut exchange = describe_request(provider, &request, recorder.config);
+
+    let response = match client.execute(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            exchange.error = Some(e.to_string());
+            recorder.update(|capture| capture.exchanges.push(exchange));
+            return Err(e);
+        }
+    };
+
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let body = match response.bytes().await {
+        Ok(body) => body,
+        Err(e) => {
+            exchange.status = Some(status.as_u16());
+            exchange.error = Some(e.to_string());
+            recorder.update(|capture| capture.exchanges.push(exchange));
+            return Err(e);
+        }
+    };
+    let (response_body, truncated) = recorder
+        .config
+        .truncate(String::from_utf8_lossy(&body).into_owned());
+    exchange.status = Some(status.as_u16());
+    exchange.response_body = Some(response_body);
+    exchange.truncated |= truncated;
+    recorder.update(|capture| capture.exchanges.push(exchange));
+
+    // Hand the provider a response equivalent to the one consumed above
+    let mut rebuilt = http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.version_mut() = version;
+    *rebuilt.headers_mut() = headers;
+    Ok(reqwest::Response::from(rebuilt))
+}
+
+/// Record of `request` with its credentials redacted
+fn describe_request(
+    provider: &str,
+    request: &reqwest::Request,
+    config: PayloadCaptureConfig,
+) -> ProviderExchange {
+    let mut secrets = Vec::new();
+
+    let mut request_headers = BTreeMap::new();
+    for (name, value) in request.headers() {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        let value = if is_secret_header(name.as_str()) {
+            secrets.push(credential(&value).to_string());
+            Secrets::REDACTED.to_string()
+        } else {
+            value
+        };
+        request_headers
+            .entry(name.as_str().to_string())
+            .and_modify(|existing: &mut String| {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            })
+            .or_insert(value);
+    }
+
+    let mut url = request.url().clone();
+    if url
+        .query_pairs()
+        .any(|(name, _)| is_secret_name(&name.to_ascii_lowercase()))
+    {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                if is_secret_name(&name.to_ascii_lowercase()) {
+                    secrets.push(value.to_string());
+                    (name.into_owned(), Secrets::REDACTED.to_string())
+                } else {
+                    (name.into_owned(), value.into_owned())
+                }
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    let body = request
+        .body()
+        .and_then(reqwest::Body::as_bytes)
+        .map(|body| String::from_utf8_lossy(body).into_owned())
+        .unwrap_or_default();
+    let body = secrets
+        .iter()
+        .filter(|secret| secret.len() >= MIN_SECRET_LEN)
+        .fold(body, |body, secret| {
+            body.replace(secret.as_str(), Secrets::REDACTED)
+        });
+    let (request_body, truncated) = config.truncate(body);
+
+    ProviderExchange {
+        provider: provider.to_string(),
+        method: request.method().to_string(),
+        url: url.to_string(),
+        request_headers,
+        request_body,
+        status: None,
+        response_body: None,
+        error: None,
+        truncated,
+    }
+}
+
+/// Shortest credential also redacted where it reappears in the request body
+const MIN_SECRET_LEN: usize = 8;
+
+fn is_secret_header(name: &str) -> bool {
+    matches!(
+        name,
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie"
+    ) || is_secret_name(name)
+}
+
+fn is_secret_name(name: &str) -> bool {
+    ["key", "token", "secret", "password"]
+        .iter()
+        .any(|part| name.contains(part))
+}
+
+/// The credential of an authorization header value, without its scheme
+fn credential(value: &str) -> &str {
+    value
+        .split_once(' ')
+        .map_or(value, |(_, credential)| credential.trim())
+}
@@ -0,0 +1,185 @@
+//! Time source of the executor and its time-based policies
+//!
+//! Retry backoff, delay nodes, pacing, circuit breakers and rate limiting read
+//! the time and wait through a [`Clock`] instead of calling `tokio::time` or
+//! `Utc::now` directly. [`SystemClock`] is the real clock and the default of
+//! every public constructor; [`TestClock`] lets tests control time.
+//!
+//! The executor runs each node with its clock as the current clock of the
+//! task, read with [`now`], [`instant`] and [`sleep`]. Outside of an execution
+//! these fall back to the system clock.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static CURRENT: Arc<dyn Clock>;
+}
+
+/// Source of timestamps, monotonic instants and sleeps
+#[async_trait]
+pub trait Clock: Send + Sync + Debug {
+    /// Current wall-clock time, for timestamps
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current monotonic time, for measuring durations and deadlines
+    fn instant(&self) -> Instant;
+
+    /// Wait for `duration` to pass on this clock
+    async fn sleep(&self, duration: Duration);
+}
+
+/// The real clock: `Utc::now` and the Tokio clock
+///
+/// Follows `tokio::time::pause` like any Tokio timer does.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// A clock controlled by tests
+///
+/// A [frozen](Self::frozen) clock stands still until it is advanced; sleeping
+/// on it advances it by the sleep and returns at once, so waits of any length
+/// take no real time. A [paused](Self::paused) clock follows the Tokio clock
+/// of a runtime under `tokio::time::pause`, which jumps ahead whenever every
+/// task is waiting on a timer.
+#[derive(Debug)]
+pub struct TestClock {
+    /// Wall-clock time at `origin`
+    start: DateTime<Utc>,
+    origin: Instant,
+    /// Time a frozen clock moved on since `origin`; `None` for a paused clock
+    frozen_elapsed: Option<Mutex<Duration>>,
+}
+
+impl TestClock {
+    /// Clock standing still at `start`, moved only by sleeps and
+    /// [`advance`](Self::advance)
+    #[must_use]
+    pub fn frozen(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            origin: Instant::now(),
+            frozen_elapsed: Some(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Clock reading `start` now and following the Tokio clock from there;
+    /// meant for runtimes started with `start_paused = true`
+    #[must_use]
+    pub fn paused(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            origin: Instant::now(),
+            frozen_elapsed: None,
+        }
+    }
+
+    /// Time this clock moved on since it was created
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.frozen_elapsed.as_ref().map_or_else(
+            || Instant::now().saturating_duration_since(self.origin),
+            |elapsed| {
+                *elapsed
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+            },
+        )
+    }
+
+    /// Move the clock forward by `duration`
+    ///
+    /// A paused clock lets `duration` pass on the paused Tokio clock, running
+    /// the timers that fall due on the way.
+    pub async fn advance(&self, duration: Duration) {
+        match &self.frozen_elapsed {
+            Some(elapsed) => {
+                *elapsed
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner) += duration;
+            }
+            None => tokio::time::sleep(duration).await,
+        }
+    }
+}
+
+#[async_trait]
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = chrono::Duration::from_std(self.elapsed()).unwrap_or(chrono::Duration::MAX);
+        self.start + elapsed
+    }
+
+    fn instant(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        if self.frozen_elapsed.is_some() {
+            self.advance(duration).await;
+            // Still give other tasks a turn, as a real sleep would
+            tokio::task::yield_now().await;
+        } else {
+            tokio::time::sleep(duration).await;
+        }
+    }
+}
+
+/// The system clock, as a shared [`Clock`]
+#[must_use]
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// The clock of the running execution, or the system clock outside of one
+#[must_use]
+pub fn current() -> Arc<dyn Clock> {
+    CURRENT.try_with(Arc::clone).unwrap_or_else(|_| system())
+}
+
+/// Current wall-clock time on the [current](current) clock
+#[must_use]
+pub fn now() -> DateTime<Utc> {
+    CURRENT
+        .try_with(|clock| clock.now())
+        .unwrap_or_else(|_| Utc::now())
+}
+
+/// Current monotonic time on the [current](current) clock
+#[must_use]
+pub fn instant() -> Instant {
+    CURRENT
+        .try_with(|clock| clock.instant())
+        .unwrap_or_else(|_| Instant::now())
+}
+
+/// Wait for `duration` to pass on the [current](current) clock
+pub async fn sleep(duration: Duration) {
+    current().sleep(duration).await;
+}
+
+/// Run `future` with `clock` as its current clock
+pub(crate) async fn scope<F: Future>(clock: Arc<dyn Clock>, future: F) -> F::Output {
+    CURRENT.scope(clock, future).await
+}
@@ -0,0 +1,517 @@
+//! Comparison of two workflow runs
+//!
+//! [`WorkflowContext::compare`] diffs a run against an expected one, typically
+//! a stored golden run, node by node: status changes, output differences,
+//! duration and token deltas, and their totals over the run. Nodes are matched
+//! by name, so the runs may come from different executions of the same
+//! workflow. [`CompareOptions`] leaves volatile values out and tolerates small
+//! numeric differences, and [`RunComparison::violations`] checks a comparison
+//! against [`CompareThresholds`] for use as a CI assertion.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::LlmUsage;
+use crate::report::{NodeReport, ReportConfig};
+use crate::types::WorkflowContext;
+
+/// Status of a node that ran in only one of the compared runs
+pub const NOT_RUN: &str = "not_run";
+
+/// Line diffs of texts whose line counts multiply to more than this replace
+/// the whole text instead of aligning lines
+const MAX_LINE_DIFF_CELLS: usize = 1_000_000;
+
+/// What a [`WorkflowContext::compare`] leaves out or tolerates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompareOptions {
+    /// Leave duration deltas out and treat any two RFC 3339 timestamps in
+    /// outputs as equal
+    pub ignore_timings: bool,
+    /// Leave token deltas out
+    pub ignore_tokens: bool,
+    /// Leave object keys named `id` or ending in `_id` out of output diffs
+    pub ignore_ids: bool,
+    /// Further object keys left out of output diffs, at any depth
+    pub ignore_keys: Vec<String>,
+    /// Numbers in outputs differing by at most this much are equal
+    pub numeric_tolerance: f64,
+}
+
+impl CompareOptions {
+    /// Also ignore `field`: `timings`, `tokens` or `ids`
+    pub fn ignore(mut self, field: &str) -> GraphBitResult<Self> {
+        match field {
+            "timings" => self.ignore_timings = true,
+            "tokens" => self.ignore_tokens = true,
+            "ids" => self.ignore_ids = true,
+            _ => {
+                return Err(GraphBitError::validation(
+                    "ignore",
+                    format!("Unknown field to ignore '{field}'; expected timings, tokens or ids"),
+                ));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Leave the values of object `key` out of output diffs
+    #[must_use]
+    pub fn with_ignore_key(mut self, key: impl Into<String>) -> Self {
+        self.ignore_keys.push(key.into());
+        self
+    }
+
+    /// Treat numbers in outputs differing by at most `tolerance` as equal
+    #[must_use]
+    pub const fn with_numeric_tolerance(mut self, tolerance: f64) -> Self {
+        self.numeric_tolerance = tolerance;
+        self
+    }
+
+    fn ignores_key(&self, key: &str) -> bool {
+        (self.ignore_ids && (key == "id" || key.ends_with("_id")))
+            || self.ignore_keys.iter().any(|ignored| ignored == key)
+    }
+}
+
+/// Status of a node or run in the expected run and in the compared one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusChange {
+    /// Status in the expected run
+    pub expected: String,
+    /// Status in the actual run
+    pub actual: String,
+}
+
+/// A value that differs between the expected and the actual output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    /// JSON Pointer to the value within the output; empty for the whole output
+    pub path: String,
+    /// Expected value, `None` when only the actual output has it
+    pub expected: Option<serde_json::Value>,
+    /// Actual value, `None` when only the expected output has it
+    pub actual: Option<serde_json::Value>,
+}
+
+/// Duration in the expected and in the actual run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DurationDelta {
+    /// Duration of the expected run in milliseconds
+    pub expected_ms: u64,
+    /// Duration of the actual run in milliseconds
+    pub actual_ms: u64,
+    /// `actual_ms - expected_ms`
+    pub delta_ms: i64,
+}
+
+impl DurationDelta {
+    fn new(expected_ms: u64, actual_ms: u64) -> Self {
+        Self {
+            expected_ms,
+            actual_ms,
+            delta_ms: signed(actual_ms) - signed(expected_ms),
+        }
+    }
+}
+
+/// Change of token usage, actual minus expected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenDelta {
+    /// Change of the prompt tokens
+    pub prompt_tokens: i64,
+    /// Change of the completion tokens
+    pub completion_tokens: i64,
+    /// Change of the total tokens
+    pub total_tokens: i64,
+}
+
+impl TokenDelta {
+    fn new(expected: &LlmUsage, actual: &LlmUsage) -> Self {
+        Self {
+            prompt_tokens: i64::from(actual.prompt_tokens) - i64::from(expected.prompt_tokens),
+            completion_tokens: i64::from(actual.completion_tokens)
+                - i64::from(expected.completion_tokens),
+            total_tokens: i64::from(actual.total_tokens) - i64::from(expected.total_tokens),
+        }
+    }
+}
+
+/// Differences of one node between two runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeComparison {
+    /// Name of the node, which matches it between the runs
+    pub node_name: String,
+    /// Status in each run when it changed: `succeeded`, `failed` or [`NOT_RUN`]
+    pub status: Option<StatusChange>,
+    /// Whether the outputs differ beyond the ignored values and tolerances
+    pub output_changed: bool,
+    /// Differing values of the outputs; outputs that are JSON text are
+    /// compared as JSON
+    pub output_diff: Vec<ValueChange>,
+    /// Line diff of changed text outputs, lines prefixed with `-`, `+` or a space
+    pub text_diff: Option<Vec<String>>,
+    /// Durations, when the node ran in both runs and timings are not ignored
+    pub duration: Option<DurationDelta>,
+    /// Token usage change, when either run used tokens and they are not ignored
+    pub token_delta: Option<TokenDelta>,
+}
+
+impl NodeComparison {
+    /// Whether the node's status or output changed
+    pub fn is_changed(&self) -> bool {
+        self.status.is_some() || self.output_changed
+    }
+}
+
+/// Differences between a run and an expected run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunComparison {
+    /// Final state of each run when it changed
+    pub status: Option<StatusChange>,
+    /// Nodes that ran in either run: those of the actual run in execution
+    /// order, then those only the expected run executed
+    pub nodes: Vec<NodeComparison>,
+    /// Number of nodes whose output changed
+    pub changed_outputs: usize,
+    /// Number of nodes whose status changed
+    pub status_changes: usize,
+    /// Run durations, unless timings are ignored
+    pub duration: Option<DurationDelta>,
+    /// Change of the run's total token usage, unless tokens are ignored
+    pub token_delta: Option<TokenDelta>,
+}
+
+/// Limits a [`RunComparison`] must stay within to pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompareThresholds {
+    /// Most nodes whose output may change
+    pub max_changed_outputs: usize,
+    /// Accept changes of the run's and the nodes' status
+    pub allow_status_changes: bool,
+    /// Largest accepted increase of the run duration in milliseconds
+    pub max_duration_increase_ms: Option<u64>,
+    /// Largest accepted increase of the run duration in percent of the expected one
+    pub max_duration_increase_pct: Option<f64>,
+    /// Largest accepted increase of the run's total tokens
+    pub max_token_increase: Option<u64>,
+}
+
+impl RunComparison {
+    /// Ways the comparison exceeds `thresholds`, empty when it passes
+    pub fn violations(&self, thresholds: &CompareThresholds) -> Vec<String> {
+        let mut violations = Vec::new();
+        if !thresholds.allow_status_changes {
+            if let Some(status) = &self.status {
+                violations.push(format!(
+                    "Run status changed from {} to {}",
+                    status.expected, status.actual
+                ));
+            }
+            for node in &self.nodes {
+                if let Some(status) = &node.status {
+                    violations.push(format!(
+                        "Node '{}' status changed from {} to {}",
+                        node.node_name, status.expected, status.actual
+                    ));
+                }
+            }
+        }
+        if self.changed_outputs > thresholds.max_changed_outputs {
+            let names: Vec<&str> = self
+                .nodes
+                .iter()
+                .filter(|node| node.output_changed)
+                .map(|node| node.node_name.as_str())
+                .collect();
+            violations.push(format!(
+                "{} node outputs changed, at most {} allowed: {}",
+                self.changed_outputs,
+                thresholds.max_changed_outputs,
+                names.join(", ")
+            ));
+        }
+        if let Some(duration) = &self.duration {
+            if let Some(max) = thresholds.max_duration_increase_ms {
+                if duration.delta_ms > signed(max) {
+                    violations.push(format!(
+                        "Run duration increased by {} ms, at most {max} ms allowed",
+                        duration.delta_ms
+                    ));
+                }
+            }
+            if let Some(max) = thresholds.max_duration_increase_pct {
+                if duration.delta_ms > 0 {
+                    let pct = if duration.expected_ms == 0 {
+                        f64::INFINITY
+                    } else {
+                        duration.delta_ms as f64 * 100.0 / duration.expected_ms as f64
+                    };
+                    if pct > max {
+                        violations.push(format!(
+                            "Run duration increased by {pct:.1}%, at most {max}% allowed"
+                        ));
+                    }
+                }
+            }
+        }
+        if let (Some(max), Some(tokens)) = (thresholds.max_token_increase, &self.token_delta) {
+            if tokens.total_tokens > signed(max) {
+                violations.push(format!(
+                    "Total tokens increased by {}, at most {max} allowed",
+                    tokens.total_tokens
+                ));
+            }
+        }
+        violations
+    }
+
+    /// Whether the comparison stays within `thresholds`
+    pub fn passes(&self, thresholds: &CompareThresholds) -> bool {
+        self.violations(thresholds).is_empty()
+    }
+}
+
+impl WorkflowContext {
+    /// Compare this run against `expected`, node by node
+    #[must_use]
+    pub fn compare(&self, expected: &Self, options: &CompareOptions) -> RunComparison {
+        let config = ReportConfig {
+            max_value_chars: None,
+            ..ReportConfig::default()
+        };
+        let actual = self.to_report_with(&config);
+        let expected = expected.to_report_with(&config);
+        let actual_nodes = last_by_name(&actual.nodes);
+        let expected_nodes = last_by_name(&expected.nodes);
+
+        let mut names: Vec<&str> = Vec::new();
+        let mut seen = BTreeSet::new();
+        for node in actual.nodes.iter().chain(&expected.nodes) {
+            if seen.insert(node.node_name.as_str()) {
+                names.push(&node.node_name);
+            }
+        }
+        let nodes: Vec<NodeComparison> = names
+            .into_iter()
+            .map(|name| {
+                compare_nodes(
+                    name,
+                    expected_nodes.get(name).copied(),
+                    actual_nodes.get(name).copied(),
+                    options,
+                )
+            })
+            .collect();
+
+        RunComparison {
+            status: (actual.status != expected.status).then(|| StatusChange {
+                expected: expected.status,
+                actual: actual.status,
+            }),
+            changed_outputs: nodes.iter().filter(|node| node.output_changed).count(),
+            status_changes: nodes.iter().filter(|node| node.status.is_some()).count(),
+            nodes,
+            duration: match (expected.duration_ms, actual.duration_ms) {
+                (Some(expected), Some(actual)) if !options.ignore_timings => {
+                    Some(DurationDelta::new(expected, actual))
+                }
+                _ => None,
+            },
+            token_delta: (!options.ignore_tokens)
+                .then(|| TokenDelta::new(&expected.token_usage, &actual.token_usage)),
+        }
+    }
+}
+
+/// Reports of the nodes by name, the last execution of a node that ran more than once
+fn last_by_name(nodes: &[NodeReport]) -> HashMap<&str, &NodeReport> {
+    nodes
+        .iter()
+        .map(|node| (node.node_name.as_str(), node))
+        .collect()
+}
+
+fn compare_nodes(
+    name: &str,
+    expected: Option<&NodeReport>,
+    actual: Option<&NodeReport>,
+    options: &CompareOptions,
+) -> NodeComparison {
+    let null = serde_json::Value::Null;
+    let expected_output = expected.map_or(&null, |node| &node.output);
+    let actual_output = actual.map_or(&null, |node| &node.output);
+
+    let expected_output = structured(expected_output);
+    let actual_output = structured(actual_output);
+    let mut output_diff = Vec::new();
+    diff_values(
+        Some(&expected_output),
+        Some(&actual_output),
+        &mut String::new(),
+        options,
+        &mut output_diff,
+    );
+    let text_diff = match (expected_output.as_ref(), actual_output.as_ref()) {
+        (serde_json::Value::String(expected), serde_json::Value::String(actual))
+            if !output_diff.is_empty() =>
+        {
+            Some(line_diff(expected, actual))
+        }
+        _ => None,
+    };
+
+    let usage = |node: Option<&NodeReport>| node.and_then(|node| node.token_usage.clone());
+    NodeComparison {
+        node_name: name.to_string(),
+        status: (status(expected) != status(actual)).then(|| StatusChange {
+            expected: status(expected).to_string(),
+            actual: status(actual).to_string(),
+        }),
+        output_changed: !output_diff.is_empty(),
+        output_diff,
+        text_diff,
+        duration: match (expected, actual) {
+            (Some(expected), Some(actual)) if !options.ignore_timings => {
+                Some(DurationDelta::new(expected.duration_ms, actual.duration_ms))
+            }
+            _ => None,
+        },
+        token_delta: match (usage(expected), usage(actual)) {
+            (None, None) => None,
+            _ if options.ignore_tokens => None,
+            (expected, actual) => Some(TokenDelta::new(
+                &expected.unwrap_or_else(LlmUsage::empty),
+                &actual.unwrap_or_else(LlmUsage::empty),
+            )),
+        },
+    }
+}
+
+fn status(node: Option<&NodeReport>) -> &str {
+    node.map_or(NOT_RUN, |node| node.status.as_str())
+}
+
+/// Text holding a JSON object or array as that JSON, so agent outputs are
+/// compared value by value
+fn structured(value: &serde_json::Value) -> Cow<'_, serde_json::Value> {
+    if let serde_json::Value::String(text) = value {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            if let Ok(parsed) = serde_json::from_str(text) {
+                return Cow::Owned(parsed);
+            }
+        }
+    }
+    Cow::Borrowed(value)
+}
+
+/// Append the differences between `expected` and `actual` at `path` to `changes`
+fn diff_values(
+    expected: Option<&serde_json::Value>,
+    actual: Option<&serde_json::Value>,
+    path: &mut String,
+    options: &CompareOptions,
+    changes: &mut Vec<ValueChange>,
+) {
+    use serde_json::Value;
+
+    let mut descend =
+        |path: &mut String, segment: &str, expected: Option<&Value>, actual: Option<&Value>| {
+            let len = path.len();
+            path.push('/');
+            path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+            diff_values(expected, actual, path, options, changes);
+            path.truncate(len);
+        };
+    match (expected, actual) {
+        (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+            let keys: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+            for key in keys.into_iter().filter(|key| !options.ignores_key(key)) {
+                descend(path, key, expected.get(key), actual.get(key));
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+            for index in 0..expected.len().max(actual.len()) {
+                descend(
+                    path,
+                    &index.to_string(),
+                    expected.get(index),
+                    actual.get(index),
+                );
+            }
+        }
+        (Some(Value::Number(expected)), Some(Value::Number(actual)))
+            if expected
+                .as_f64()
+                .zip(actual.as_f64())
+                .is_some_and(|(expected, actual)| {
+                    (expected - actual).abs() <= options.numeric_tolerance
+                }) => {}
+        (Some(Value::String(expected)), Some(Value::String(actual)))
+            if options.ignore_timings && is_timestamp(expected) && is_timestamp(actual) => {}
+        (expected, actual) if expected == actual => {}
+        (expected, actual) => changes.push(ValueChange {
+            path: path.clone(),
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        }),
+    }
+}
+
+fn is_timestamp(text: &str) -> bool {
+    chrono::DateTime::parse_from_rfc3339(text).is_ok()
+}
+
+/// Diff of the lines of two texts, aligned on their longest common subsequence
+fn line_diff(expected: &str, actual: &str) -> Vec<String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let removed = |line: &&str| format!("-{line}");
+    let added = |line: &&str| format!("+{line}");
+    if expected.len().saturating_mul(actual.len()) > MAX_LINE_DIFF_CELLS {
+        return expected
+            .iter()
+            .map(removed)
+            .chain(actual.iter().map(added))
+            .collect();
+    }
+
+    // common[i][j]: length of the longest common subsequence of expected[i..] and actual[j..]
+    let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push(format!(" {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1])
+        {
+            diff.push(removed(&expected[i]));
+            i += 1;
+        } else {
+            diff.push(added(&actual[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+fn signed(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
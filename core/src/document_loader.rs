@@ -3,17 +3,32 @@
 //! This module provides utilities for loading and extracting content from various
 //! document formats including PDF, TXT, Word, JSON, CSV, XML, and HTML.
 
+mod cache;
+mod download;
+mod fetch;
+
+pub use cache::CACHE_HIT_KEY;
+pub use download::{DownloadProgress, DownloadProgressCallback};
+
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::shared_client;
+use cache::{ExtractionCache, Validators, flag_cache_hit};
 use calamine::Data;
 use csv::ReaderBuilder;
-use quick_xml::events::Event;
+use download::Downloader;
+use fetch::UrlFetcher;
+use futures::StreamExt;
 use quick_xml::Reader;
+use quick_xml::events::Event;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::io::Cursor;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
 /// Document loader configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +41,66 @@ pub struct DocumentLoaderConfig {
     pub preserve_formatting: bool,
     /// Document-specific extraction settings
     pub extraction_settings: HashMap<String, serde_json::Value>,
+    /// Directory caching extracted documents, so that loading an unchanged
+    /// document again skips extraction; no caching when `None`
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// Maximum concurrent requests to one host
+    #[serde(default = "default_max_requests_per_host")]
+    pub max_requests_per_host: usize,
+    /// Maximum requests per second across all hosts; no cap when `None`
+    #[serde(default)]
+    pub requests_per_second: Option<f64>,
+    /// Retries of a URL request answered with `429` or `5xx`, or failing to
+    /// connect
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubling with every further retry; a
+    /// `Retry-After` header takes precedence
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Time limit of a whole [`DocumentLoader::load_batch`] call; sources not
+    /// loaded by then fail
+    #[serde(default)]
+    pub batch_timeout_ms: Option<u64>,
+    /// Largest URL download, in bytes; `max_file_size` when `None`
+    #[serde(default)]
+    pub max_download_size: Option<usize>,
+    /// Times a URL download whose connection drops is resumed with a `Range`
+    /// request, or started over when the server does not accept ranges
+    #[serde(default = "default_max_download_retries")]
+    pub max_download_retries: u32,
+    /// Called as URL downloads progress
+    #[serde(skip)]
+    pub download_progress: Option<DownloadProgressCallback>,
+    /// Documents parsed at the same time on the blocking thread pool; the
+    /// number of CPUs when `None`
+    #[serde(default)]
+    pub max_parallel_parses: Option<usize>,
+    /// Bytes of documents parsed at the same time, bounding peak memory; a
+    /// larger document is parsed on its own
+    #[serde(default = "default_max_parse_bytes")]
+    pub max_parse_bytes: usize,
+}
+
+const fn default_max_requests_per_host() -> usize {
+    4
+}
+
+const fn default_max_retries() -> u32 {
+    3
+}
+
+const fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+const fn default_max_download_retries() -> u32 {
+    3
+}
+
+const fn default_max_parse_bytes() -> usize {
+    64 * 1024 * 1024
 }
 
 impl Default for DocumentLoaderConfig {
@@ -35,6 +110,17 @@ impl Default for DocumentLoaderConfig {
             default_encoding: "utf-8".to_string(),
             preserve_formatting: false,
             extraction_settings: HashMap::new(),
+            cache_dir: None,
+            max_requests_per_host: default_max_requests_per_host(),
+            requests_per_second: None,
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            batch_timeout_ms: None,
+            max_download_size: None,
+            max_download_retries: default_max_download_retries(),
+            download_progress: None,
+            max_parallel_parses: None,
+            max_parse_bytes: default_max_parse_bytes(),
         }
     }
 }
@@ -56,22 +142,119 @@ pub struct DocumentContent {
     pub extracted_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Outcome of loading one source of [`DocumentLoader::load_batch`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchLoadResult {
+    /// File path or URL, as given
+    pub source: String,
+    /// Loaded document, unless loading failed
+    pub document: Option<DocumentContent>,
+    /// Why loading failed
+    pub error: Option<String>,
+}
+
+/// Documents of a directory, see [`DocumentLoader::load_directory_with_stats`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryLoadResult {
+    /// Loaded documents, in path order
+    pub documents: Vec<DocumentContent>,
+    /// How the documents were loaded
+    pub stats: DirectoryLoadStats,
+}
+
+/// Statistics of loading a directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryLoadStats {
+    /// One entry per loaded file, in path order
+    pub files: Vec<FileLoadStats>,
+    /// Bytes of all loaded files
+    pub total_bytes: usize,
+    /// Time spent parsing, summed over the files
+    pub total_parse_ms: f64,
+    /// Wall time of the whole load
+    pub elapsed_ms: f64,
+}
+
+/// How one file of a directory was loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLoadStats {
+    /// File path
+    pub source: String,
+    /// Detected document type
+    pub document_type: String,
+    /// File size in bytes
+    pub file_size: usize,
+    /// When parsing started, from the start of the load; `None` when the
+    /// document came from the cache
+    pub started_ms: Option<f64>,
+    /// Time spent parsing; `None` when the document came from the cache
+    pub parse_ms: Option<f64>,
+}
+
+/// When and for how long a document was parsed
+#[derive(Debug, Clone, Copy)]
+struct ParseTiming {
+    started: Instant,
+    duration: Duration,
+}
+
+/// Sources of a batch loaded at the same time; URL requests are further
+/// limited per host
+const BATCH_CONCURRENCY: usize = 16;
+
 /// Document loader for processing various file formats
+///
+/// With a `cache_dir` configured, extracted documents are cached on disk. A
+/// cached file is reused while its size and modification time are unchanged,
+/// and a cached URL while the server answers its `ETag` or `Last-Modified`
+/// validator with `304 Not Modified`. Every document then carries a
+/// [`CACHE_HIT_KEY`] metadata flag telling whether it came from the cache.
+///
+/// URL requests are limited to `max_requests_per_host` concurrent requests per
+/// host and `requests_per_second` overall, and retried with backoff on `429`
+/// and `5xx` responses, honoring `Retry-After`. A loaded URL records its number
+/// of requests under the `fetch_attempts` metadata key.
+///
+/// URL bodies are streamed to a temporary file of at most `max_download_size`
+/// bytes. A download whose connection drops is resumed with a `Range` request
+/// when the server accepts ranges, up to `max_download_retries` times, and
+/// records its number of resumes under the `download_retries` metadata key.
+///
+/// Documents are parsed on the blocking thread pool, so that parsing large
+/// PDFs does not stall other tasks of the runtime. At most
+/// `max_parallel_parses` documents, of at most `max_parse_bytes` bytes
+/// together, are parsed at the same time.
 pub struct DocumentLoader {
     config: DocumentLoaderConfig,
+    cache: Option<ExtractionCache>,
+    fetcher: UrlFetcher,
+    parallel_parses: usize,
+    parse_slots: Arc<Semaphore>,
+    parse_bytes: Arc<Semaphore>,
 }
 
 impl DocumentLoader {
     /// Create a new document loader with default configuration
     pub fn new() -> Self {
-        Self {
-            config: DocumentLoaderConfig::default(),
-        }
+        Self::with_config(DocumentLoaderConfig::default())
     }
 
     /// Create a new document loader with custom configuration
     pub fn with_config(config: DocumentLoaderConfig) -> Self {
-        Self { config }
+        let cache = config.cache_dir.clone().map(ExtractionCache::new);
+        let fetcher = UrlFetcher::new(&config);
+        let parallel_parses = config
+            .max_parallel_parses
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, usize::from))
+            .max(1);
+        Self {
+            parallel_parses,
+            parse_slots: Arc::new(Semaphore::new(parallel_parses)),
+            parse_bytes: Arc::new(Semaphore::new(parse_byte_budget(&config) as usize)),
+            config,
+            cache,
+            fetcher,
+        }
     }
 
     /// Load and extract content from a document
@@ -103,18 +286,163 @@ impl DocumentLoader {
                 ),
             ));
         } else {
-            self.load_from_file(source_path, document_type).await?
+            self.load_from_file(source_path, document_type).await?.0
         };
 
         Ok(content)
     }
 
-    /// Load document from file path
+    /// Load every supported document in a directory, detecting each file's type
+    /// from its extension. Files with unsupported extensions are skipped and
+    /// documents are returned in path order.
+    pub async fn load_directory(
+        &self,
+        directory: &str,
+        recursive: bool,
+    ) -> GraphBitResult<Vec<DocumentContent>> {
+        Ok(self
+            .load_directory_with_stats(directory, recursive)
+            .await?
+            .documents)
+    }
+
+    /// Load a directory like [`Self::load_directory`], also reporting how long
+    /// each file took to parse
+    ///
+    /// Files are parsed concurrently, largest first, so that a large file does
+    /// not start last and hold up the whole load. The first file failing to
+    /// load, in path order, fails the load.
+    pub async fn load_directory_with_stats(
+        &self,
+        directory: &str,
+        recursive: bool,
+    ) -> GraphBitResult<DirectoryLoadResult> {
+        let load_started = Instant::now();
+        let root = Path::new(directory);
+        if !root.is_dir() {
+            return Err(GraphBitError::validation(
+                "document_loader",
+                format!("Directory not found: {directory}"),
+            ));
+        }
+
+        let mut files = Vec::new();
+        collect_files(root, recursive, &mut files)?;
+        files.sort();
+
+        let mut jobs = Vec::with_capacity(files.len());
+        for path in files {
+            let path = path.to_string_lossy().into_owned();
+            if let Some(document_type) = detect_document_type(&path) {
+                let size = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+                jobs.push((jobs.len(), path, document_type, size));
+            }
+        }
+        // Largest first; the sort is stable, so equal sizes keep path order
+        jobs.sort_by_key(|&(_, _, _, size)| std::cmp::Reverse(size));
+
+        let mut loaded: Vec<_> = futures::stream::iter(jobs)
+            .map(|(index, path, document_type, _)| async move {
+                let result = self.load_from_file(&path, &document_type).await;
+                (index, result)
+            })
+            .buffer_unordered(self.parallel_parses)
+            .collect()
+            .await;
+        loaded.sort_by_key(|&(index, _)| index);
+
+        let mut documents = Vec::with_capacity(loaded.len());
+        let mut stats = DirectoryLoadStats::default();
+        for (_, result) in loaded {
+            let (document, timing) = result?;
+            let parse_ms = timing.map(|timing| timing.duration.as_secs_f64() * 1000.0);
+            stats.total_bytes += document.file_size;
+            stats.total_parse_ms += parse_ms.unwrap_or_default();
+            stats.files.push(FileLoadStats {
+                source: document.source.clone(),
+                document_type: document.document_type.clone(),
+                file_size: document.file_size,
+                started_ms: timing.map(|timing| {
+                    timing
+                        .started
+                        .saturating_duration_since(load_started)
+                        .as_secs_f64()
+                        * 1000.0
+                }),
+                parse_ms,
+            });
+            documents.push(document);
+        }
+        stats.elapsed_ms = load_started.elapsed().as_secs_f64() * 1000.0;
+        Ok(DirectoryLoadResult { documents, stats })
+    }
+
+    /// Load files and URLs, detecting each source's type from its extension
+    ///
+    /// Sources load concurrently and results come back in source order. A
+    /// source failing to load, after retries for URLs, is reported in its
+    /// result without aborting the others; so are sources still loading when
+    /// `batch_timeout_ms` runs out.
+    pub async fn load_batch(&self, sources: &[String]) -> Vec<BatchLoadResult> {
+        let deadline = self
+            .config
+            .batch_timeout_ms
+            .map(|ms| tokio::time::Instant::now() + std::time::Duration::from_millis(ms));
+
+        futures::stream::iter(sources.iter().cloned())
+            .map(|source| self.load_batch_source(source, deadline))
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Load one source of a batch, failing once `deadline` passes
+    async fn load_batch_source(
+        &self,
+        source: String,
+        deadline: Option<tokio::time::Instant>,
+    ) -> BatchLoadResult {
+        let load = async {
+            let document_type = detect_document_type(&source).ok_or_else(|| {
+                GraphBitError::validation(
+                    "document_loader",
+                    format!("Cannot detect the document type of {source}"),
+                )
+            })?;
+            self.load_document(&source, &document_type).await
+        };
+        let result = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, load)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(GraphBitError::validation(
+                        "document_loader",
+                        format!("Batch timed out before {source} was loaded"),
+                    ))
+                }),
+            None => load.await,
+        };
+        match result {
+            Ok(document) => BatchLoadResult {
+                source,
+                document: Some(document),
+                error: None,
+            },
+            Err(e) => BatchLoadResult {
+                source,
+                document: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Load document from file path, along with when and for how long it was
+    /// parsed unless it came from the cache
     async fn load_from_file(
         &self,
         file_path: &str,
         document_type: &str,
-    ) -> GraphBitResult<DocumentContent> {
+    ) -> GraphBitResult<(DocumentContent, Option<ParseTiming>)> {
         let path = Path::new(file_path);
 
         // Check if file exists
@@ -144,26 +472,23 @@ impl DocumentLoader {
             ));
         }
 
-        // Extract content based on document type
-        let content = match document_type.to_lowercase().as_str() {
-            "txt" => Self::extract_text_content(file_path).await?,
-            "pdf" => Self::extract_pdf_content(file_path).await?,
-            "docx" => Self::extract_docx_content(file_path).await?,
-            "json" => Self::extract_json_content(file_path).await?,
-            "csv" => Self::extract_csv_content(file_path).await?,
-            "xml" => Self::extract_xml_content(file_path).await?,
-            "html" => Self::extract_html_content(file_path).await?,
-            "xlsb" | "xlsx" | "xls" => Self::extract_excel_content(file_path).await?,
-            _ => {
-                return Err(GraphBitError::validation(
-                    "document_loader",
-                    format!(
-                        "Unsupported document type: {document_type}. Supported types: {:?}",
-                        Self::supported_types()
-                    ),
-                ))
+        let cache_key = self
+            .cache
+            .as_ref()
+            .map(|_| ExtractionCache::file_key(path, &metadata, document_type, &self.config));
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            if let Some(entry) = cache.get(key).await {
+                let mut document = entry.document;
+                flag_cache_hit(&mut document, true);
+                return Ok((document, None));
             }
-        };
+        }
+
+        // Extract content based on document type
+        let (path, kind) = (file_path.to_string(), document_type.to_lowercase());
+        let (content, timing) = self
+            .parse_blocking(file_size, move || Self::extract_file_content(&path, &kind))
+            .await?;
 
         let mut doc_metadata = HashMap::new();
         doc_metadata.insert(
@@ -175,14 +500,78 @@ impl DocumentLoader {
             serde_json::Value::String(file_path.to_string()),
         );
 
-        Ok(DocumentContent {
+        let mut document = DocumentContent {
             source: file_path.to_string(),
             document_type: document_type.to_string(),
             content,
             metadata: doc_metadata,
             file_size,
             extracted_at: chrono::Utc::now(),
+        };
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            flag_cache_hit(&mut document, false);
+            cache.store(key, &document, Validators::default()).await;
+        }
+        Ok((document, Some(timing)))
+    }
+
+    /// Run `parse` on the blocking thread pool once a parse slot and the
+    /// bytes of a document of `size` bytes are free, timing it
+    async fn parse_blocking<T: Send + 'static>(
+        &self,
+        size: usize,
+        parse: impl FnOnce() -> GraphBitResult<T> + Send + 'static,
+    ) -> GraphBitResult<(T, ParseTiming)> {
+        let unavailable = |e: tokio::sync::AcquireError| {
+            GraphBitError::validation("document_loader", format!("Parsing unavailable: {e}"))
+        };
+        // Always in this order, so loads waiting for one another cannot deadlock
+        let slot = Arc::clone(&self.parse_slots)
+            .acquire_owned()
+            .await
+            .map_err(unavailable)?;
+        let bytes = u32::try_from(size)
+            .unwrap_or(u32::MAX)
+            .clamp(1, parse_byte_budget(&self.config));
+        let bytes = Arc::clone(&self.parse_bytes)
+            .acquire_many_owned(bytes)
+            .await
+            .map_err(unavailable)?;
+
+        tokio::task::spawn_blocking(move || {
+            // Held until parsing ends, even when the load is abandoned
+            let _permits = (slot, bytes);
+            let started = Instant::now();
+            parse().map(|output| {
+                let duration = started.elapsed();
+                (output, ParseTiming { started, duration })
+            })
         })
+        .await
+        .map_err(|e| {
+            GraphBitError::validation("document_loader", format!("Parsing task failed: {e}"))
+        })?
+    }
+
+    /// Extract the content of a file of a lowercase document type
+    fn extract_file_content(file_path: &str, document_type: &str) -> GraphBitResult<String> {
+        match document_type {
+            "txt" => Self::extract_text_content(file_path),
+            "pdf" => Self::extract_pdf_content(file_path),
+            "docx" => Self::extract_docx_content(file_path),
+            "json" => Self::extract_json_content(file_path),
+            "csv" => Self::extract_csv_content(file_path),
+            "xml" => Self::extract_xml_content(file_path),
+            "html" => Self::extract_html_content(file_path),
+            "xlsb" | "xlsx" | "xls" => Self::extract_excel_content(file_path),
+            _ => Err(GraphBitError::validation(
+                "document_loader",
+                format!(
+                    "Unsupported document type: {document_type}. Supported types: {:?}",
+                    Self::supported_types()
+                ),
+            )),
+        }
     }
 
     /// Load document from URL
@@ -199,39 +588,78 @@ impl DocumentLoader {
             ));
         }
 
-        // Create HTTP client with timeout and user agent
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent("GraphBit Document Loader/1.0")
-            .build()
-            .map_err(|e| {
-                GraphBitError::validation(
-                    "document_loader",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
-
-        // Fetch the document
-        let response = client.get(url).send().await.map_err(|e| {
-            GraphBitError::validation("document_loader", format!("Failed to fetch URL {url}: {e}"))
+        // Shared client with the loader's default timeout and user agent
+        let client = shared_client(
+            "document_loader",
+            Some(std::time::Duration::from_secs(30)),
+            Some("GraphBit Document Loader/1.0"),
+        )
+        .map_err(|e| {
+            GraphBitError::validation(
+                "document_loader",
+                format!("Failed to create HTTP client: {e}"),
+            )
         })?;
 
+        // Revalidate a cached copy with its validators
+        let cache_key = self
+            .cache
+            .as_ref()
+            .map(|_| ExtractionCache::url_key(url, document_type, &self.config));
+        let cached = match (&self.cache, &cache_key) {
+            (Some(cache), Some(key)) => cache.get(key).await,
+            _ => None,
+        };
+        let mut request = client.get(url);
+        if let Some(validators) = cached.as_ref().map(|entry| &entry.validators) {
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        // Fetch the document, keeping the host's connection slot until it is read
+        let fetch::Fetched {
+            response,
+            attempts,
+            permit,
+        } = self.fetcher.send(url, request).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                let mut document = entry.document;
+                flag_cache_hit(&mut document, true);
+                return Ok(document);
+            }
+        }
+        let validators = Validators::from_headers(response.headers());
+
         // Check response status
         if !response.status().is_success() {
+            let after = if attempts > 1 {
+                format!(" after {attempts} attempts")
+            } else {
+                String::new()
+            };
             return Err(GraphBitError::validation(
                 "document_loader",
-                format!("HTTP error {}: {url}", response.status()),
+                format!("HTTP error {}{after}: {url}", response.status()),
             ));
         }
 
         // Check content length
+        let max_size = self
+            .config
+            .max_download_size
+            .unwrap_or(self.config.max_file_size);
         if let Some(content_length) = response.content_length() {
-            if content_length as usize > self.config.max_file_size {
+            if content_length > max_size as u64 {
                 return Err(GraphBitError::validation(
                     "document_loader",
                     format!(
-                        "Remote file size ({content_length} bytes) exceeds maximum allowed size ({} bytes)",
-                        self.config.max_file_size
+                        "Remote file size ({content_length} bytes) exceeds maximum allowed size ({max_size} bytes)"
                     ),
                 ));
             }
@@ -245,40 +673,43 @@ impl DocumentLoader {
             .unwrap_or("")
             .to_lowercase();
 
-        // Download the content
-        let content_bytes = response.bytes().await.map_err(|e| {
-            GraphBitError::validation(
-                "document_loader",
-                format!("Failed to read response body: {e}"),
-            )
-        })?;
-
-        // Check actual size
-        if content_bytes.len() > self.config.max_file_size {
-            return Err(GraphBitError::validation(
-                "document_loader",
-                format!(
-                    "Downloaded file size ({} bytes) exceeds maximum allowed size ({} bytes)",
-                    content_bytes.len(),
-                    self.config.max_file_size
-                ),
-            ));
+        // Download the content to a temporary file, resuming it when the connection drops
+        let download = Downloader {
+            fetcher: &self.fetcher,
+            client: &client,
+            url,
+            max_size,
+            max_retries: self.config.max_download_retries,
+            progress: self.config.download_progress.as_ref(),
         }
+        .download(response, permit)
+        .await?;
+        let download_path = download.file.path().to_string_lossy().into_owned();
 
-        // Convert bytes to string based on document type
+        // Convert the download to text based on document type
         let content = match document_type.to_lowercase().as_str() {
-            "txt" | "json" | "csv" | "xml" | "html" => String::from_utf8(content_bytes.to_vec())
-                .map_err(|e| {
+            "txt" | "json" | "csv" | "xml" | "html" => {
+                let bytes = tokio::fs::read(&download_path).await.map_err(|e| {
+                    GraphBitError::validation(
+                        "document_loader",
+                        format!("Failed to read downloaded file: {e}"),
+                    )
+                })?;
+                String::from_utf8(bytes).map_err(|e| {
                     GraphBitError::validation(
                         "document_loader",
                         format!("Failed to decode text content: {e}"),
                     )
-                })?,
-            "pdf" | "docx" => {
-                return Err(GraphBitError::validation(
-                    "document_loader",
-                    format!("URL loading for {document_type} documents is not yet supported"),
-                ));
+                })?
+            }
+            kind @ ("pdf" | "docx") => {
+                let kind = kind.to_string();
+                let size = usize::try_from(download.size).unwrap_or(usize::MAX);
+                self.parse_blocking(size, move || {
+                    Self::extract_file_content(&download_path, &kind)
+                })
+                .await?
+                .0
             }
             _ => {
                 return Err(GraphBitError::validation(
@@ -313,7 +744,7 @@ impl DocumentLoader {
         let mut metadata = HashMap::new();
         metadata.insert(
             "file_size".to_string(),
-            serde_json::Value::Number(content_bytes.len().into()),
+            serde_json::Value::Number(download.size.into()),
         );
         metadata.insert(
             "url".to_string(),
@@ -323,19 +754,35 @@ impl DocumentLoader {
             "content_type".to_string(),
             serde_json::Value::String(content_type),
         );
+        metadata.insert(
+            "fetch_attempts".to_string(),
+            serde_json::Value::Number(attempts.into()),
+        );
+        metadata.insert(
+            "download_retries".to_string(),
+            serde_json::Value::Number(download.retries.into()),
+        );
 
-        Ok(DocumentContent {
+        let mut document = DocumentContent {
             source: url.to_string(),
             document_type: document_type.to_string(),
             content: processed_content,
             metadata,
-            file_size: content_bytes.len(),
+            file_size: usize::try_from(download.size).unwrap_or(usize::MAX),
             extracted_at: chrono::Utc::now(),
-        })
+        };
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            flag_cache_hit(&mut document, false);
+            // Without validators the copy could never be revalidated
+            if let Some(validators) = validators {
+                cache.store(key, &document, validators).await;
+            }
+        }
+        Ok(document)
     }
 
     /// Extract content from plain text files
-    async fn extract_text_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_text_content(file_path: &str) -> GraphBitResult<String> {
         let content = std::fs::read_to_string(file_path).map_err(|e| {
             GraphBitError::validation("document_loader", format!("Failed to read text file: {e}"))
         })?;
@@ -343,7 +790,7 @@ impl DocumentLoader {
     }
 
     /// Extract content from JSON files
-    async fn extract_json_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_json_content(file_path: &str) -> GraphBitResult<String> {
         let content = std::fs::read_to_string(file_path).map_err(|e| {
             GraphBitError::validation("document_loader", format!("Failed to read JSON file: {e}"))
         })?;
@@ -360,7 +807,7 @@ impl DocumentLoader {
     }
 
     /// Extract content from CSV files
-    async fn extract_csv_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_csv_content(file_path: &str) -> GraphBitResult<String> {
         let content = std::fs::read_to_string(file_path).map_err(|e| {
             GraphBitError::validation("document_loader", format!("Failed to read CSV file: {e}"))
         })?;
@@ -432,7 +879,7 @@ impl DocumentLoader {
     }
 
     /// Extract content from XML files
-    async fn extract_xml_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_xml_content(file_path: &str) -> GraphBitResult<String> {
         let content = std::fs::read_to_string(file_path).map_err(|e| {
             GraphBitError::validation("document_loader", format!("Failed to read XML file: {e}"))
         })?;
@@ -514,7 +961,7 @@ impl DocumentLoader {
     }
 
     /// Extract content from HTML files
-    async fn extract_html_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_html_content(file_path: &str) -> GraphBitResult<String> {
         let content = std::fs::read_to_string(file_path).map_err(|e| {
             GraphBitError::validation("document_loader", format!("Failed to read HTML file: {e}"))
         })?;
@@ -530,7 +977,7 @@ impl DocumentLoader {
     }
 
     /// Parse HTML content into structured, readable text format
-    fn parse_html_to_structured_text(
+    pub(crate) fn parse_html_to_structured_text(
         html_content: &str,
     ) -> Result<String, Box<dyn std::error::Error>> {
         let document = Html::parse_document(html_content);
@@ -681,7 +1128,7 @@ impl DocumentLoader {
     }
 
     /// Extract content from PDF files
-    async fn extract_pdf_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_pdf_content(file_path: &str) -> GraphBitResult<String> {
         // Read the PDF file into memory
         let bytes = std::fs::read(file_path).map_err(|e| {
             GraphBitError::validation("document_loader", format!("Failed to read PDF file: {e}"))
@@ -706,7 +1153,7 @@ impl DocumentLoader {
     }
 
     /// Extract content from DOCX files
-    async fn extract_docx_content(file_path: &str) -> GraphBitResult<String> {
+    fn extract_docx_content(file_path: &str) -> GraphBitResult<String> {
         use std::fs::File;
         use std::io::Read;
 
@@ -752,8 +1199,8 @@ impl DocumentLoader {
     }
 
     /// Extract content from Excel (XLSB, XLSX, XLS,etc.) files
-    async fn extract_excel_content(file_path: &str) -> GraphBitResult<String> {
-        use calamine::{open_workbook_auto, Reader};
+    fn extract_excel_content(file_path: &str) -> GraphBitResult<String> {
+        use calamine::{Reader, open_workbook_auto};
 
         let mut workbook = open_workbook_auto(file_path).map_err(|e| {
             GraphBitError::validation(
@@ -820,10 +1267,54 @@ impl Default for DocumentLoader {
     }
 }
 
-/// Helper function to determine document type from file extension
+/// Bytes of documents parsed at the same time, as semaphore permits
+fn parse_byte_budget(config: &DocumentLoaderConfig) -> u32 {
+    u32::try_from(config.max_parse_bytes)
+        .unwrap_or(u32::MAX)
+        .max(1)
+}
+
+/// Collect the files in `directory`, descending into subdirectories when `recursive`
+fn collect_files(
+    directory: &Path,
+    recursive: bool,
+    files: &mut Vec<std::path::PathBuf>,
+) -> GraphBitResult<()> {
+    let entries = std::fs::read_dir(directory).map_err(|e| {
+        GraphBitError::validation(
+            "document_loader",
+            format!("Failed to read directory {}: {e}", directory.display()),
+        )
+    })?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| {
+                GraphBitError::validation(
+                    "document_loader",
+                    format!("Failed to read directory entry: {e}"),
+                )
+            })?
+            .path();
+        if path.is_dir() {
+            if recursive {
+                collect_files(&path, recursive, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Helper function to determine document type from file extension; for a URL,
+/// from the extension of its path
 pub fn detect_document_type(file_path: &str) -> Option<String> {
     let supported_types = DocumentLoader::supported_types();
-    Path::new(file_path)
+    let url = (file_path.starts_with("http://") || file_path.starts_with("https://"))
+        .then(|| reqwest::Url::parse(file_path).ok())
+        .flatten();
+    let path = url.as_ref().map_or(file_path, reqwest::Url::path);
+    Path::new(path)
         .extension()
         .and_then(|ext| ext.to_str())
         .map(str::to_lowercase)
@@ -0,0 +1,172 @@
+//! On-disk cache of extracted documents
+//!
+//! Entries are JSON files named after the SHA-256 of their key. A file is keyed
+//! by its path, size and modification time, so editing it misses the cache; a
+//! URL is keyed by the URL alone and revalidated with the `ETag` and
+//! `Last-Modified` validators stored alongside the document. Both keys include
+//! the document type and the loader configuration, so loading with different
+//! extraction settings never reuses an entry.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{DocumentContent, DocumentLoaderConfig};
+use crate::errors::GraphBitResult;
+
+/// Metadata key flagging whether a document was served from the cache
+pub const CACHE_HIT_KEY: &str = "cache_hit";
+
+/// HTTP validators of a cached URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct Validators {
+    pub(super) etag: Option<String>,
+    pub(super) last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Validators of a response, `None` when it has neither
+    pub(super) fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        let header = |name: reqwest::header::HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        };
+        (validators.etag.is_some() || validators.last_modified.is_some()).then_some(validators)
+    }
+}
+
+/// A cached document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct CacheEntry {
+    #[serde(default)]
+    pub(super) validators: Validators,
+    pub(super) document: DocumentContent,
+}
+
+/// Directory of cached documents
+pub(super) struct ExtractionCache {
+    dir: PathBuf,
+}
+
+impl ExtractionCache {
+    pub(super) fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Key of a local file in its current state
+    pub(super) fn file_key(
+        path: &Path,
+        metadata: &std::fs::Metadata,
+        document_type: &str,
+        config: &DocumentLoaderConfig,
+    ) -> String {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_nanos());
+        Self::key(&[
+            "file",
+            &path.to_string_lossy(),
+            &metadata.len().to_string(),
+            &mtime.to_string(),
+            document_type,
+            &config_fingerprint(config),
+        ])
+    }
+
+    /// Key of a URL, whatever its current content
+    pub(super) fn url_key(url: &str, document_type: &str, config: &DocumentLoaderConfig) -> String {
+        Self::key(&["url", url, document_type, &config_fingerprint(config)])
+    }
+
+    fn key(parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// The entry stored under `key`; unreadable entries count as missing
+    pub(super) async fn get(&self, key: &str) -> Option<CacheEntry> {
+        let bytes = tokio::fs::read(self.path_for(key)).await.ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable document cache entry {key}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Store `entry` under `key`, replacing any previous entry
+    pub(super) async fn put(&self, key: &str, entry: &CacheEntry) -> GraphBitResult<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let bytes = serde_json::to_vec(entry)?;
+        // Write under a unique name and rename, so readers never see a partial file
+        let partial = self
+            .dir
+            .join(format!("{key}.{}.partial", uuid::Uuid::new_v4()));
+        tokio::fs::write(&partial, bytes).await?;
+        if let Err(e) = tokio::fs::rename(&partial, self.path_for(key)).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Store `document`, logging rather than failing the load when that fails
+    pub(super) async fn store(
+        &self,
+        key: &str,
+        document: &DocumentContent,
+        validators: Validators,
+    ) {
+        let entry = CacheEntry {
+            validators,
+            document: document.clone(),
+        };
+        if let Err(e) = self.put(key, &entry).await {
+            tracing::warn!(
+                "Failed to cache extracted document {}: {e}",
+                document.source
+            );
+        }
+    }
+}
+
+/// Hash of the configuration settings that affect extraction
+fn config_fingerprint(config: &DocumentLoaderConfig) -> String {
+    // Sorted, so that the settings hash the same whatever their `HashMap` order
+    let extraction_settings: BTreeMap<_, _> = config.extraction_settings.iter().collect();
+    let settings = serde_json::json!([
+        config.max_file_size,
+        config.default_encoding,
+        config.preserve_formatting,
+        extraction_settings,
+    ]);
+    format!("{:x}", Sha256::digest(settings.to_string().as_bytes()))
+}
+
+/// Mark whether `document` was served from the cache
+pub(super) fn flag_cache_hit(document: &mut DocumentContent, hit: bool) {
+    document
+        .metadata
+        .insert(CACHE_HIT_KEY.to_string(), serde_json::Value::Bool(hit));
+}
@@ -0,0 +1,320 @@
+//! Resumable URL downloads
+//!
+//! A URL body is streamed to a temporary file instead of being held in memory.
+//! When the connection drops before the body is complete, the download is
+//! resumed with a `Range` request if the server advertised
+//! `Accept-Ranges: bytes`, and started over otherwise. `If-Range` makes a
+//! server whose document changed in between answer with the whole new
+//! document, and the finished file is checked against the expected size
+//! before it is parsed.
+
+use std::fmt;
+use std::io::SeekFrom;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use reqwest::StatusCode;
+use reqwest::header::{
+    ACCEPT_RANGES, CONTENT_RANGE, ETAG, HeaderMap, HeaderName, IF_RANGE, LAST_MODIFIED, RANGE,
+};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::OwnedSemaphorePermit;
+
+use super::fetch::{Fetched, UrlFetcher};
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Bytes downloaded between two progress reports
+const PROGRESS_INTERVAL: u64 = 256 * 1024;
+
+/// Progress of a URL download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// URL being downloaded
+    pub url: String,
+    /// Bytes downloaded so far
+    pub downloaded: u64,
+    /// Size of the document, when the server announced it
+    pub total: Option<u64>,
+}
+
+/// Callback reporting the [`DownloadProgress`] of URL downloads
+///
+/// It is called every 256 KiB and once a download completes, from the task
+/// running the download, so it should return quickly.
+#[derive(Clone)]
+pub struct DownloadProgressCallback(Arc<dyn Fn(&DownloadProgress) + Send + Sync>);
+
+impl DownloadProgressCallback {
+    /// Wrap `callback`
+    pub fn new(callback: impl Fn(&DownloadProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for DownloadProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DownloadProgressCallback")
+    }
+}
+
+/// A complete download
+pub(super) struct Download {
+    /// Temporary file holding the body, deleted when dropped
+    pub(super) file: tempfile::NamedTempFile,
+    pub(super) size: u64,
+    /// Times the download was resumed or started over
+    pub(super) retries: u32,
+}
+
+/// What is known about the document being downloaded
+struct Transfer {
+    /// Whether the server advertised `Accept-Ranges: bytes`
+    accepts_ranges: bool,
+    etag: Option<String>,
+    /// Validator sent as `If-Range` when resuming
+    if_range: Option<String>,
+    total: Option<u64>,
+    /// Bytes written to the download file
+    written: u64,
+}
+
+impl Transfer {
+    /// A download starting with a response carrying `headers`
+    fn new(headers: &HeaderMap, total: Option<u64>) -> Self {
+        Self {
+            accepts_ranges: header(headers, ACCEPT_RANGES)
+                .is_some_and(|units| units.eq_ignore_ascii_case("bytes")),
+            etag: header(headers, ETAG),
+            if_range: if_range_validator(headers),
+            total,
+            written: 0,
+        }
+    }
+}
+
+/// Why reading a body stopped early
+enum BodyError {
+    /// The connection dropped; the download can be resumed
+    Interrupted(String),
+    /// The download cannot go on
+    Fatal(GraphBitError),
+}
+
+/// Downloads one URL
+pub(super) struct Downloader<'a> {
+    pub(super) fetcher: &'a UrlFetcher,
+    pub(super) client: &'a reqwest::Client,
+    pub(super) url: &'a str,
+    pub(super) max_size: usize,
+    pub(super) max_retries: u32,
+    pub(super) progress: Option<&'a DownloadProgressCallback>,
+}
+
+impl Downloader<'_> {
+    /// Stream the body of `response`, a successful answer to a GET of the
+    /// URL, to a temporary file, resuming it when the connection drops
+    pub(super) async fn download(
+        &self,
+        response: reqwest::Response,
+        permit: OwnedSemaphorePermit,
+    ) -> GraphBitResult<Download> {
+        let file = tempfile::NamedTempFile::new().map_err(|e| {
+            GraphBitError::validation(
+                "document_loader",
+                format!("Failed to create download file: {e}"),
+            )
+        })?;
+        let mut out = file
+            .as_file()
+            .try_clone()
+            .map(tokio::fs::File::from_std)
+            .map_err(|e| {
+                GraphBitError::validation(
+                    "document_loader",
+                    format!("Failed to open download file: {e}"),
+                )
+            })?;
+
+        let mut transfer = Transfer::new(response.headers(), response.content_length());
+        let mut response = response;
+        let mut permit = permit;
+        let mut retries = 0;
+
+        loop {
+            let outcome = self.stream_body(response, &mut out, &mut transfer).await;
+            drop(permit);
+            match outcome {
+                Ok(()) => break,
+                Err(BodyError::Fatal(e)) => return Err(e),
+                Err(BodyError::Interrupted(e)) if retries >= self.max_retries => {
+                    let after = if retries > 0 {
+                        format!(" after {} attempts", retries + 1)
+                    } else {
+                        String::new()
+                    };
+                    return Err(GraphBitError::validation(
+                        "document_loader",
+                        format!("Failed to read response body{after}: {e}"),
+                    ));
+                }
+                Err(BodyError::Interrupted(e)) => {
+                    tracing::debug!(
+                        url = self.url,
+                        written = transfer.written,
+                        error = %e,
+                        "Download interrupted"
+                    );
+                }
+            }
+            retries += 1;
+            (response, permit) = self.request_rest(&mut transfer, &mut out).await?;
+        }
+
+        let Transfer { written, total, .. } = transfer;
+        out.flush().await.map_err(|e| write_error(&e))?;
+        if let Some(total) = total.filter(|&total| total != written) {
+            return Err(GraphBitError::validation(
+                "document_loader",
+                format!(
+                    "Downloaded {written} bytes of {}, expected {total}",
+                    self.url
+                ),
+            ));
+        }
+        self.report(written, total);
+        Ok(Download {
+            file,
+            size: written,
+            retries,
+        })
+    }
+
+    /// Ask for the rest of the document when the server accepts ranges, and
+    /// for the whole document otherwise, emptying `out` when it comes whole
+    async fn request_rest(
+        &self,
+        transfer: &mut Transfer,
+        out: &mut tokio::fs::File,
+    ) -> GraphBitResult<(reqwest::Response, OwnedSemaphorePermit)> {
+        let resume = transfer.accepts_ranges && transfer.written > 0;
+        let mut request = self.client.get(self.url);
+        if resume {
+            request = request.header(RANGE, format!("bytes={}-", transfer.written));
+            if let Some(validator) = &transfer.if_range {
+                request = request.header(IF_RANGE, validator);
+            }
+        }
+        let Fetched {
+            response, permit, ..
+        } = self.fetcher.send(self.url, request).await?;
+
+        let status = response.status();
+        if resume && status == StatusCode::PARTIAL_CONTENT {
+            let range = content_range(response.headers());
+            let etag = header(response.headers(), ETAG);
+            if range.map(|(start, _)| start) != Some(transfer.written)
+                || (transfer.etag.is_some() && etag != transfer.etag)
+            {
+                return Err(GraphBitError::validation(
+                    "document_loader",
+                    format!("Document at {} changed while downloading", self.url),
+                ));
+            }
+            transfer.total = transfer.total.or_else(|| range.and_then(|(_, size)| size));
+        } else if status.is_success() {
+            out.set_len(0).await.map_err(|e| write_error(&e))?;
+            out.seek(SeekFrom::Start(0))
+                .await
+                .map_err(|e| write_error(&e))?;
+            *transfer = Transfer::new(response.headers(), response.content_length());
+        } else {
+            return Err(GraphBitError::validation(
+                "document_loader",
+                format!(
+                    "HTTP error {status} resuming download after {} bytes: {}",
+                    transfer.written, self.url
+                ),
+            ));
+        }
+        Ok((response, permit))
+    }
+
+    /// Append the body of `response` to `out`, counting its bytes
+    async fn stream_body(
+        &self,
+        response: reqwest::Response,
+        out: &mut tokio::fs::File,
+        transfer: &mut Transfer,
+    ) -> Result<(), BodyError> {
+        let mut reported = transfer.written;
+        let mut body = response.bytes_stream();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| BodyError::Interrupted(e.to_string()))?;
+            let size = transfer.written + chunk.len() as u64;
+            if size > self.max_size as u64 {
+                return Err(BodyError::Fatal(GraphBitError::validation(
+                    "document_loader",
+                    format!(
+                        "Downloaded file size ({size} bytes) exceeds maximum allowed size ({} bytes)",
+                        self.max_size
+                    ),
+                )));
+            }
+            out.write_all(&chunk)
+                .await
+                .map_err(|e| BodyError::Fatal(write_error(&e)))?;
+            transfer.written = size;
+            if size - reported >= PROGRESS_INTERVAL {
+                self.report(size, transfer.total);
+                reported = size;
+            }
+        }
+        match transfer.total {
+            Some(total) if transfer.written < total => Err(BodyError::Interrupted(format!(
+                "body ended after {} of {total} bytes",
+                transfer.written
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn report(&self, downloaded: u64, total: Option<u64>) {
+        if let Some(progress) = self.progress {
+            (progress.0)(&DownloadProgress {
+                url: self.url.to_string(),
+                downloaded,
+                total,
+            });
+        }
+    }
+}
+
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Validator for `If-Range`: a strong `ETag`, else `Last-Modified`
+fn if_range_validator(headers: &HeaderMap) -> Option<String> {
+    header(headers, ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(headers, LAST_MODIFIED))
+}
+
+/// First byte and document size, unless unknown, of a
+/// `Content-Range: bytes <start>-<end>/<size>` answer
+fn content_range(headers: &HeaderMap) -> Option<(u64, Option<u64>)> {
+    let range = header(headers, CONTENT_RANGE)?;
+    let (span, size) = range.strip_prefix("bytes ")?.split_once('/')?;
+    let start = span.split_once('-')?.0.trim().parse().ok()?;
+    Some((start, size.trim().parse().ok()))
+}
+
+fn write_error(e: &std::io::Error) -> GraphBitError {
+    GraphBitError::validation(
+        "document_loader",
+        format!("Failed to write download file: {e}"),
+    )
+}
@@ -0,0 +1,140 @@
+//! Polite URL fetching
+//!
+//! Requests to one host share a limited number of concurrent connections, and
+//! all requests of a loader share an optional requests-per-second cap. Requests
+//! answered with `429 Too Many Requests` or a `5xx` status, or failing to
+//! connect or time out, are retried with exponential backoff; a `Retry-After`
+//! header replaces the backoff delay.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::DocumentLoaderConfig;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::retry_after_seconds;
+use crate::rate_limit::TokenBucket;
+
+/// Longest backoff between two attempts, unless `Retry-After` asks for more
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A response with the connection slot of its host
+pub(super) struct Fetched {
+    pub(super) response: reqwest::Response,
+    /// Requests sent, retries included
+    pub(super) attempts: u32,
+    /// Connection slot of the host, to release once the body is read
+    pub(super) permit: OwnedSemaphorePermit,
+}
+
+/// Sends the URL requests of a document loader
+pub(super) struct UrlFetcher {
+    max_requests_per_host: usize,
+    max_retries: u32,
+    retry_backoff: Duration,
+    rate_limiter: Option<TokenBucket>,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl UrlFetcher {
+    pub(super) fn new(config: &DocumentLoaderConfig) -> Self {
+        Self {
+            max_requests_per_host: config.max_requests_per_host.max(1),
+            max_retries: config.max_retries,
+            retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+            rate_limiter: config
+                .requests_per_second
+                .and_then(|rate| TokenBucket::new(rate, 1)),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connection slots of the host of `url`
+    fn host_slots(&self, url: &str) -> Arc<Semaphore> {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .map(|host| format!("{host}:{}", url.port_or_known_default().unwrap_or(0)))
+            })
+            .unwrap_or_default();
+        let mut hosts = self.hosts.lock().unwrap_or_else(PoisonError::into_inner);
+        hosts
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_requests_per_host)))
+            .clone()
+    }
+
+    /// Delay before attempt `attempt + 1`
+    fn backoff(&self, attempt: u32, retry_after: Option<u64>) -> Duration {
+        retry_after.map_or_else(
+            || {
+                self.retry_backoff
+                    .saturating_mul(2_u32.saturating_pow(attempt - 1))
+                    .min(MAX_BACKOFF)
+            },
+            Duration::from_secs,
+        )
+    }
+
+    /// Send `request`, retrying it while it fails with a retryable status or
+    /// error. Once retries run out, the last response is returned whatever its
+    /// status.
+    pub(super) async fn send(
+        &self,
+        url: &str,
+        request: reqwest::RequestBuilder,
+    ) -> GraphBitResult<Fetched> {
+        let slots = self.host_slots(url);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let permit = slots
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|e| GraphBitError::concurrency(e.to_string()))?;
+            let attempt_request = request.try_clone().ok_or_else(|| {
+                GraphBitError::validation("document_loader", "Request cannot be retried")
+            })?;
+
+            let retry_after = match attempt_request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    let retryable = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        || status.is_server_error();
+                    if !retryable || attempt > self.max_retries {
+                        return Ok(Fetched {
+                            response,
+                            attempts: attempt,
+                            permit,
+                        });
+                    }
+                    retry_after_seconds(response.headers())
+                }
+                Err(e) if (e.is_connect() || e.is_timeout()) && attempt <= self.max_retries => None,
+                Err(e) => {
+                    let after = if attempt > 1 {
+                        format!(" after {attempt} attempts")
+                    } else {
+                        String::new()
+                    };
+                    return Err(GraphBitError::validation(
+                        "document_loader",
+                        format!("Failed to fetch URL {url}{after}: {e}"),
+                    ));
+                }
+            };
+            drop(permit);
+
+            let delay = self.backoff(attempt, retry_after);
+            tracing::debug!(url, attempt, ?delay, "Retrying URL fetch");
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
@@ -1,17 +1,37 @@
 //! Embeddings support for `GraphBit`
 //!
 //! This module provides a unified interface for working with different
-//! embedding providers including `HuggingFace` and `OpenAI`.
+//! embedding providers including `HuggingFace`, `OpenAI`, `Voyage AI` and
+//! `Jina AI`, and with the `local-embeddings` feature, models run locally.
 
+pub mod jina;
+#[cfg(feature = "local-embeddings")]
+pub mod local;
 pub mod python_bridge;
+pub mod similarity;
+pub mod voyage;
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, retry_after_seconds, shared_client_with_timeouts};
+use crate::llm::LlmUsage;
+use crate::llm::context_window::estimate_tokens;
+use crate::llm::health::{self, ProviderHealth};
+use crate::secret_ref::{debug_config, resolve_api_key};
+use crate::types::RetryConfig;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
 
+pub use jina::JinaEmbeddingProvider;
+#[cfg(feature = "local-embeddings")]
+pub use local::LocalEmbeddingProvider;
 pub use python_bridge::PythonBridgeEmbeddingProvider;
+pub use similarity::{
+    SimilarityMetric, cosine_similarity, dot_product, euclidean_distance, mmr, top_k,
+};
+pub use voyage::VoyageEmbeddingProvider;
 
 #[cfg(feature = "python")]
 fn default_python_instance() -> Option<Arc<pyo3::PyObject>> {
@@ -21,11 +41,12 @@ fn default_python_instance() -> Option<Arc<pyo3::PyObject>> {
 }
 
 /// Configuration for embedding providers
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
     /// Provider type (e.g., "openai", "huggingface", "pythonbridge")
     pub provider: EmbeddingProvider,
-    /// API key for the provider
+    /// API key for the provider, or a reference to it such as
+    /// `env:OPENAI_API_KEY`, see [`crate::secret_ref`]
     pub api_key: String,
     /// Model name to use for embeddings
     pub model: String,
@@ -33,16 +54,110 @@ pub struct EmbeddingConfig {
     pub base_url: Option<String>,
     /// Request timeout in seconds
     pub timeout_seconds: Option<u64>,
+    /// Request and connection timeouts; a `timeout_ms` set here takes
+    /// precedence over `timeout_seconds`
+    #[serde(flatten)]
+    pub timeouts: RequestTimeouts,
     /// Maximum batch size for processing multiple texts
     pub max_batch_size: Option<usize>,
     /// Additional provider-specific parameters
     pub extra_params: HashMap<String, serde_json::Value>,
+    /// Retries of a failed request in [`EmbeddingService::process_batch`];
+    /// `None` sends every request once
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
     /// Python object instance for PythonBridge provider
     #[cfg(feature = "python")]
     #[serde(skip, default = "default_python_instance")]
     pub python_instance: Option<Arc<pyo3::PyObject>>,
 }
 
+impl std::fmt::Debug for EmbeddingConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        debug_config(f, "EmbeddingConfig", self)
+    }
+}
+
+impl EmbeddingConfig {
+    /// Configuration for `provider` with the defaults of every other setting
+    fn for_provider(
+        provider: EmbeddingProvider,
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider,
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: None,
+            timeout_seconds: None,
+            timeouts: RequestTimeouts::default(),
+            max_batch_size: None,
+            extra_params: HashMap::new(),
+            retry: None,
+            #[cfg(feature = "python")]
+            python_instance: None,
+        }
+    }
+
+    /// Create `OpenAI` configuration
+    pub fn openai(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::for_provider(EmbeddingProvider::OpenAI, api_key, model)
+    }
+
+    /// Create `Voyage AI` configuration
+    pub fn voyage(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::for_provider(EmbeddingProvider::Voyage, api_key, model)
+    }
+
+    /// Create `Jina AI` configuration
+    pub fn jina(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self::for_provider(EmbeddingProvider::Jina, api_key, model)
+    }
+
+    /// Create configuration of the local `sentence-transformers` model in
+    /// `model_dir`, run by [`LocalEmbeddingProvider`]
+    #[cfg(feature = "local-embeddings")]
+    pub fn local(model_dir: impl Into<String>) -> Self {
+        Self::for_provider(EmbeddingProvider::Local, String::new(), model_dir)
+    }
+
+    /// Run a local model on `threads` CPU threads instead of one per core
+    #[cfg(feature = "local-embeddings")]
+    #[must_use]
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.extra_params
+            .insert(local::THREADS_PARAM.to_string(), threads.into());
+        self
+    }
+
+    /// Use `base_url` instead of the provider's public API
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Embed texts as `input_type`, [`INPUT_TYPE_QUERY`] or
+    /// [`INPUT_TYPE_DOCUMENT`], unless a request says otherwise
+    #[must_use]
+    pub fn with_input_type(mut self, input_type: impl Into<String>) -> Self {
+        self.extra_params.insert(
+            INPUT_TYPE_PARAM.to_string(),
+            serde_json::Value::String(input_type.into()),
+        );
+        self
+    }
+
+    /// Ask for embeddings of `dimensions` from models that can shorten them
+    #[must_use]
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.extra_params
+            .insert(DIMENSIONS_PARAM.to_string(), dimensions.into());
+        self
+    }
+}
+
 /// Supported embedding providers
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -54,6 +169,13 @@ pub enum EmbeddingProvider {
     Azure,
     /// `HuggingFace` embedding provider
     HuggingFace,
+    /// `Voyage AI` embedding provider
+    Voyage,
+    /// `Jina AI` embedding provider
+    Jina,
+    /// Local `sentence-transformers` model run on the CPU
+    #[cfg(feature = "local-embeddings")]
+    Local,
     /// Python bridge provider for calling Python embedding implementations
     #[cfg(feature = "python")]
     PythonBridge,
@@ -163,6 +285,12 @@ pub struct EmbeddingBatchStats {
     pub total_embeddings: usize,
     /// Total tokens processed
     pub total_tokens: u32,
+    /// Provider calls made, counting every retry
+    #[serde(default)]
+    pub total_attempts: usize,
+    /// Number of requests that needed more than one attempt
+    #[serde(default)]
+    pub retried_requests: usize,
 }
 
 /// Trait for embedding providers
@@ -197,6 +325,172 @@ pub trait EmbeddingProviderTrait: Send + Sync {
     fn validate_config(&self) -> GraphBitResult<()> {
         Ok(())
     }
+
+    /// Check that the provider is reachable, accepts the credentials and
+    /// serves the model. The default embeds a single word.
+    async fn health_check(&self) -> ProviderHealth {
+        let request = EmbeddingRequest {
+            input: EmbeddingInput::Single("ping".to_string()),
+            user: None,
+            params: HashMap::new(),
+        };
+        health::probe(
+            self.provider_name(),
+            self.model_name(),
+            self.generate_embeddings(request),
+        )
+        .await
+    }
+}
+
+/// Request parameter telling retrieval models whether they embed a search
+/// query or a document to search; `Voyage AI` and `Jina AI` embed the two
+/// differently
+pub const INPUT_TYPE_PARAM: &str = "input_type";
+/// [`INPUT_TYPE_PARAM`] value for search queries
+pub const INPUT_TYPE_QUERY: &str = "query";
+/// [`INPUT_TYPE_PARAM`] value for documents to search
+pub const INPUT_TYPE_DOCUMENT: &str = "document";
+/// Request parameter asking for shortened embeddings of this many dimensions
+pub const DIMENSIONS_PARAM: &str = "dimensions";
+
+/// Parameters of `request` over the defaults in `config.extra_params`
+pub(crate) fn request_params(
+    config: &EmbeddingConfig,
+    request: &EmbeddingRequest,
+) -> HashMap<String, serde_json::Value> {
+    let mut params = config.extra_params.clone();
+    params.extend(request.params.clone());
+    params
+}
+
+/// Whether `params` ask for query (`true`) or document (`false`) embeddings,
+/// if they say
+pub(crate) fn input_type_is_query(
+    params: &HashMap<String, serde_json::Value>,
+) -> GraphBitResult<Option<bool>> {
+    match params.get(INPUT_TYPE_PARAM) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => match value.as_str() {
+            Some(INPUT_TYPE_QUERY) => Ok(Some(true)),
+            Some(INPUT_TYPE_DOCUMENT) => Ok(Some(false)),
+            _ => Err(GraphBitError::validation(
+                INPUT_TYPE_PARAM,
+                format!("must be \"{INPUT_TYPE_QUERY}\" or \"{INPUT_TYPE_DOCUMENT}\", got {value}"),
+            )),
+        },
+    }
+}
+
+/// Split `texts` into consecutive batches of at most `max_texts` texts and,
+/// when given, at most `max_tokens` estimated tokens. A text above the token
+/// limit on its own makes a batch by itself, for the provider to truncate or
+/// reject.
+pub(crate) fn batch_ranges(
+    texts: &[&str],
+    max_texts: usize,
+    max_tokens: Option<usize>,
+) -> Vec<Range<usize>> {
+    let max_texts = max_texts.max(1);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (index, text) in texts.iter().enumerate() {
+        let text_tokens = estimate_tokens(text);
+        let full = index - start == max_texts
+            || max_tokens.is_some_and(|max| index > start && tokens + text_tokens > max);
+        if full {
+            ranges.push(start..index);
+            start = index;
+            tokens = 0;
+        }
+        tokens += text_tokens;
+    }
+    if start < texts.len() {
+        ranges.push(start..texts.len());
+    }
+    ranges
+}
+
+/// `OpenAI` style embeddings response, as `Voyage AI` and `Jina AI` send it
+#[derive(Debug, Deserialize)]
+struct DataResponse {
+    data: Vec<DataEmbedding>,
+    model: Option<String>,
+    #[serde(default)]
+    usage: DataUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataEmbedding {
+    embedding: Vec<f32>,
+    index: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DataUsage {
+    prompt_tokens: Option<u32>,
+    total_tokens: Option<u32>,
+}
+
+/// Embed `texts` with an `OpenAI` style embeddings endpoint at `url`, one
+/// request per batch of `ranges` with the body `body` builds for its texts.
+/// Returns the embeddings in the order of `texts`, the summed usage and the
+/// model the provider reported.
+pub(crate) async fn embed_in_batches(
+    client: &reqwest::Client,
+    provider: &str,
+    url: &str,
+    api_key: &str,
+    texts: &[&str],
+    ranges: Vec<Range<usize>>,
+    body: impl Fn(&[&str]) -> serde_json::Value,
+) -> GraphBitResult<(Vec<Vec<f32>>, EmbeddingUsage, Option<String>)> {
+    let mut embeddings = Vec::with_capacity(texts.len());
+    let mut usage = EmbeddingUsage {
+        prompt_tokens: 0,
+        total_tokens: 0,
+    };
+    let mut model = None;
+    for range in ranges {
+        let expected = range.len();
+        let response = client
+            .post(url)
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header("Content-Type", "application/json")
+            .json(&body(&texts[range]))
+            .send()
+            .await
+            .map_err(|e| GraphBitError::from_request_error(provider, &e))?;
+        if !response.status().is_success() {
+            return Err(crate::llm::catalog::error_from_response(provider, response).await);
+        }
+        let mut response: DataResponse = response.json().await.map_err(|e| {
+            GraphBitError::llm_provider(provider, format!("Failed to parse response: {e}"))
+        })?;
+        if response.data.len() != expected {
+            return Err(GraphBitError::llm_provider(
+                provider,
+                format!(
+                    "Expected {expected} embeddings, got {}",
+                    response.data.len()
+                ),
+            ));
+        }
+        response.data.sort_by_key(|item| item.index);
+        embeddings.extend(response.data.into_iter().map(|item| item.embedding));
+
+        // Usage may give only the total, or only the prompt tokens
+        let prompt_tokens = response
+            .usage
+            .prompt_tokens
+            .or(response.usage.total_tokens)
+            .unwrap_or(0);
+        usage.prompt_tokens += prompt_tokens;
+        usage.total_tokens += response.usage.total_tokens.unwrap_or(prompt_tokens);
+        model = model.or(response.model);
+    }
+    Ok((embeddings, usage, model))
 }
 
 /// `OpenAI` embedding provider
@@ -215,12 +509,15 @@ impl OpenAIEmbeddingProvider {
             ));
         }
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(
+        config.timeouts.validate()?;
+        let client = shared_client_with_timeouts(
+            "embeddings.openai",
+            Some(std::time::Duration::from_secs(
                 config.timeout_seconds.unwrap_or(30),
-            ))
-            .build()
-            .map_err(|e| GraphBitError::llm(format!("Failed to create HTTP client: {e}")))?;
+            )),
+            None,
+            config.timeouts,
+        )?;
 
         Ok(Self { config, client })
     }
@@ -275,16 +572,21 @@ impl EmbeddingProviderTrait for OpenAIEmbeddingProvider {
             .json(&body)
             .send()
             .await
-            .map_err(|e| GraphBitError::llm(format!("Failed to send request to OpenAI: {e}")))?;
+            .map_err(|e| GraphBitError::from_request_error("openai", &e))?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let retry_after = retry_after_seconds(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(GraphBitError::llm(format!(
-                "OpenAI API error: {error_text}"
-            )));
+            return Err(GraphBitError::from_http_status(
+                "openai",
+                status,
+                retry_after,
+                error_text,
+            ));
         }
 
         let response_json: serde_json::Value = response
@@ -408,12 +710,15 @@ impl AzureEmbeddingProvider {
             .unwrap_or("2024-02-01")
             .to_string();
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(
+        config.timeouts.validate()?;
+        let client = shared_client_with_timeouts(
+            "embeddings.azure",
+            Some(std::time::Duration::from_secs(
                 config.timeout_seconds.unwrap_or(30),
-            ))
-            .build()
-            .map_err(|e| GraphBitError::llm(format!("Failed to create HTTP client: {e}")))?;
+            )),
+            None,
+            config.timeouts,
+        )?;
 
         Ok(Self {
             config,
@@ -474,7 +779,7 @@ impl EmbeddingProviderTrait for AzureEmbeddingProvider {
             .json(&body)
             .send()
             .await
-            .map_err(|e| GraphBitError::llm(format!("Failed to send request to Azure: {e}")))?;
+            .map_err(|e| GraphBitError::from_request_error("azure", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -579,12 +884,15 @@ impl HuggingFaceEmbeddingProvider {
             ));
         }
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(
+        config.timeouts.validate()?;
+        let client = shared_client_with_timeouts(
+            "embeddings.huggingface",
+            Some(std::time::Duration::from_secs(
                 config.timeout_seconds.unwrap_or(60),
-            ))
-            .build()
-            .map_err(|e| GraphBitError::llm(format!("Failed to create HTTP client: {e}")))?;
+            )),
+            None,
+            config.timeouts,
+        )?;
 
         Ok(Self { config, client })
     }
@@ -636,9 +944,7 @@ impl EmbeddingProviderTrait for HuggingFaceEmbeddingProvider {
             .json(&body)
             .send()
             .await
-            .map_err(|e| {
-                GraphBitError::llm(format!("Failed to send request to HuggingFace: {e}"))
-            })?;
+            .map_err(|e| GraphBitError::from_request_error("huggingface", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -730,10 +1036,12 @@ impl EmbeddingProviderTrait for HuggingFaceEmbeddingProvider {
 pub struct EmbeddingProviderFactory;
 
 impl EmbeddingProviderFactory {
-    /// Create an embedding provider from configuration
+    /// Create an embedding provider from configuration, resolving an
+    /// `api_key` reference
     pub fn create_provider(
-        config: EmbeddingConfig,
+        mut config: EmbeddingConfig,
     ) -> GraphBitResult<Box<dyn EmbeddingProviderTrait>> {
+        config.api_key = resolve_api_key(&config.api_key)?;
         match config.provider {
             EmbeddingProvider::OpenAI => {
                 let provider = OpenAIEmbeddingProvider::new(config)?;
@@ -747,6 +1055,19 @@ impl EmbeddingProviderFactory {
                 let provider = HuggingFaceEmbeddingProvider::new(config)?;
                 Ok(Box::new(provider))
             }
+            EmbeddingProvider::Voyage => {
+                let provider = VoyageEmbeddingProvider::new(config)?;
+                Ok(Box::new(provider))
+            }
+            EmbeddingProvider::Jina => {
+                let provider = JinaEmbeddingProvider::new(config)?;
+                Ok(Box::new(provider))
+            }
+            #[cfg(feature = "local-embeddings")]
+            EmbeddingProvider::Local => {
+                let provider = LocalEmbeddingProvider::new(config)?;
+                Ok(Box::new(provider))
+            }
             #[cfg(feature = "python")]
             EmbeddingProvider::PythonBridge => {
                 let provider = PythonBridgeEmbeddingProvider::new(config)?;
@@ -761,7 +1082,6 @@ pub struct EmbeddingService {
     provider: Box<dyn EmbeddingProviderTrait>,
     config: EmbeddingConfig,
     max_concurrency: usize,
-    current_requests: Arc<std::sync::atomic::AtomicUsize>,
 }
 
 impl EmbeddingService {
@@ -774,7 +1094,6 @@ impl EmbeddingService {
             provider,
             config,
             max_concurrency,
-            current_requests: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
         })
     }
 
@@ -786,7 +1105,7 @@ impl EmbeddingService {
             params: HashMap::new(),
         };
 
-        let response = self.provider.generate_embeddings(request).await?;
+        let response = self.generate(request).await?;
 
         response
             .embeddings
@@ -803,54 +1122,72 @@ impl EmbeddingService {
             params: HashMap::new(),
         };
 
-        let response = self.provider.generate_embeddings(request).await?;
+        let response = self.generate(request).await?;
         Ok(response.embeddings)
     }
 
-    /// Process a batch of embedding requests with lock-free concurrency control
+    /// Generate embeddings for `request`, keeping the provider's usage and
+    /// metadata
+    pub async fn embed(&self, request: EmbeddingRequest) -> GraphBitResult<EmbeddingResponse> {
+        self.generate(request).await
+    }
+
+    /// Send `request` to the provider, recording the call in the audit log of
+    /// the current scope
+    async fn generate(&self, request: EmbeddingRequest) -> GraphBitResult<EmbeddingResponse> {
+        crate::audit::observe(
+            crate::audit::AuditCallKind::Embedding,
+            self.provider.provider_name(),
+            Some(self.provider.model_name()),
+            request,
+            |request| match &request.input {
+                EmbeddingInput::Single(text) => text.clone(),
+                EmbeddingInput::Multiple(texts) => texts.join("\n"),
+            },
+            |request| self.provider.generate_embeddings(request),
+            |response| {
+                (
+                    format!("{} embeddings", response.embeddings.len()),
+                    Some(LlmUsage::new(response.usage.prompt_tokens, 0)),
+                )
+            },
+        )
+        .await
+    }
+
+    /// Process a batch of embedding requests, at most `max_concurrency` at a
+    /// time. With a [`RetryConfig`] configured, a failed request is retried
+    /// with backoff according to it, waiting at least the provider's
+    /// `Retry-After` on rate limits; a request sleeping before its retry does
+    /// not hold a concurrency slot. Without one every request is sent once.
     pub async fn process_batch(
         &self,
         batch: EmbeddingBatchRequest,
     ) -> GraphBitResult<EmbeddingBatchResponse> {
         let start_time = std::time::Instant::now();
         let max_concurrency = batch.max_concurrency.unwrap_or(self.max_concurrency);
+        let slots = Arc::new(tokio::sync::Semaphore::new(max_concurrency.max(1)));
+        let retry = Arc::new(
+            self.config
+                .retry
+                .clone()
+                .unwrap_or_else(|| RetryConfig::new(0)),
+        );
 
         let mut tasks = Vec::with_capacity(batch.requests.len());
-        let current_requests = Arc::clone(&self.current_requests);
 
         for request in batch.requests {
             let config = self.config.clone();
-            let current_requests = Arc::clone(&current_requests);
+            let slots = Arc::clone(&slots);
+            let retry = Arc::clone(&retry);
 
             let task = tokio::spawn(async move {
-                // Wait for available slot using atomic operations (no semaphore bottleneck)
-                loop {
-                    let current = current_requests.load(std::sync::atomic::Ordering::Acquire);
-                    if current < max_concurrency {
-                        match current_requests.compare_exchange(
-                            current,
-                            current + 1,
-                            std::sync::atomic::Ordering::AcqRel,
-                            std::sync::atomic::Ordering::Acquire,
-                        ) {
-                            Ok(_) => break,     // Successfully acquired slot
-                            Err(_) => continue, // Retry
-                        }
+                match EmbeddingProviderFactory::create_provider(config) {
+                    Ok(provider) => {
+                        Self::generate_with_retry(provider.as_ref(), request, &retry, &slots).await
                     }
-                    tokio::task::yield_now().await;
+                    Err(e) => (Err(e), 0),
                 }
-
-                // Execute the request
-                let result = async {
-                    let provider = EmbeddingProviderFactory::create_provider(config)?;
-                    provider.generate_embeddings(request).await
-                }
-                .await;
-
-                // Release slot
-                current_requests.fetch_sub(1, std::sync::atomic::Ordering::AcqRel);
-
-                result
             });
 
             tasks.push(task);
@@ -872,22 +1209,30 @@ impl EmbeddingService {
         let mut failed = 0;
         let mut total_embeddings = 0;
         let mut total_tokens = 0;
+        let mut total_attempts = 0;
+        let mut retried_requests = 0;
 
         let final_responses: Vec<Result<EmbeddingResponse, GraphBitError>> = responses
             .into_iter()
             .map(|task_result| match task_result {
-                Ok(embedding_result) => match embedding_result {
-                    Ok(response) => {
-                        successful += 1;
-                        total_embeddings += response.embeddings.len();
-                        total_tokens += response.usage.total_tokens;
-                        Ok(response)
+                Ok((embedding_result, attempts)) => {
+                    total_attempts += attempts;
+                    if attempts > 1 {
+                        retried_requests += 1;
                     }
-                    Err(e) => {
-                        failed += 1;
-                        Err(e)
+                    match embedding_result {
+                        Ok(response) => {
+                            successful += 1;
+                            total_embeddings += response.embeddings.len();
+                            total_tokens += response.usage.total_tokens;
+                            Ok(response)
+                        }
+                        Err(e) => {
+                            failed += 1;
+                            Err(e)
+                        }
                     }
-                },
+                }
                 Err(e) => {
                     failed += 1;
                     Err(GraphBitError::llm(format!("Task execution failed: {e}")))
@@ -911,34 +1256,68 @@ impl EmbeddingService {
                 avg_response_time_ms,
                 total_embeddings,
                 total_tokens,
+                total_attempts,
+                retried_requests,
             },
         })
     }
 
-    /// Calculate cosine similarity between two embeddings
-    pub fn cosine_similarity(a: &[f32], b: &[f32]) -> GraphBitResult<f32> {
-        if a.len() != b.len() {
-            return Err(GraphBitError::validation(
-                "dimensions".to_string(),
-                "Embedding dimensions must match".to_string(),
-            ));
-        }
-
-        let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-        let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-        let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
-
-        if norm_a == 0.0 || norm_b == 0.0 {
-            return Ok(0.0);
+    /// Send `request` until it succeeds or `retry` gives up, holding a slot of
+    /// `slots` only while a call is in flight. Rate limits are retried while
+    /// attempts remain, whatever the retryable error types. Returns the result
+    /// and the number of calls made.
+    async fn generate_with_retry(
+        provider: &dyn EmbeddingProviderTrait,
+        request: EmbeddingRequest,
+        retry: &RetryConfig,
+        slots: &tokio::sync::Semaphore,
+    ) -> (GraphBitResult<EmbeddingResponse>, usize) {
+        let mut attempt = 0;
+        loop {
+            let result = match slots.acquire().await {
+                Ok(_permit) => provider.generate_embeddings(request.clone()).await,
+                Err(e) => Err(GraphBitError::concurrency(format!(
+                    "Failed to acquire a batch slot: {e}"
+                ))),
+            };
+            let error = match result {
+                Err(error @ GraphBitError::RateLimit { .. }) if attempt < retry.max_attempts => {
+                    error
+                }
+                Err(error) if retry.should_retry(&error, attempt) => error,
+                result => return (result, attempt as usize + 1),
+            };
+
+            attempt += 1;
+            let mut delay_ms = retry.calculate_delay(attempt);
+            if let GraphBitError::RateLimit {
+                retry_after_seconds,
+                ..
+            } = error
+            {
+                delay_ms = delay_ms.max(retry_after_seconds.saturating_mul(1000));
+            }
+            if delay_ms > 0 {
+                crate::clock::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            }
         }
+    }
 
-        Ok(dot_product / (norm_a * norm_b))
+    /// Calculate cosine similarity between two embeddings
+    pub fn cosine_similarity(a: &[f32], b: &[f32]) -> GraphBitResult<f32> {
+        similarity::cosine_similarity(a, b)
     }
 
     /// Get embedding dimensions for the current provider
     pub async fn get_dimensions(&self) -> GraphBitResult<usize> {
         self.provider.get_embedding_dimensions().await
     }
+
+    /// Check that the provider is reachable, accepts the credentials and
+    /// serves the model
+    pub async fn health_check(&self) -> ProviderHealth {
+        self.provider.health_check().await
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,171 @@
+//! `Jina AI` embedding provider
+//!
+//! Jina's v3 and later models take a `task` telling them what the embeddings
+//! are for; the `input_type` request parameter (`"query"` or `"document"`)
+//! selects the `retrieval.query` or `retrieval.passage` task, and a `task`
+//! parameter is passed on as given. Requests hold at most 2,048 texts, so
+//! larger inputs are sent in batches.
+
+use crate::embeddings::{
+    DIMENSIONS_PARAM, EmbeddingConfig, EmbeddingInput, EmbeddingProvider, EmbeddingProviderTrait,
+    EmbeddingRequest, EmbeddingResponse, INPUT_TYPE_PARAM, batch_ranges, embed_in_batches,
+    input_type_is_query, request_params,
+};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::shared_client_with_timeouts;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Most texts `Jina AI` accepts in one request
+pub const MAX_BATCH_TEXTS: usize = 2048;
+
+/// `Jina AI` embedding provider
+#[derive(Debug, Clone)]
+pub struct JinaEmbeddingProvider {
+    config: EmbeddingConfig,
+    client: reqwest::Client,
+}
+
+impl JinaEmbeddingProvider {
+    /// Create a new `Jina AI` embedding provider
+    pub fn new(config: EmbeddingConfig) -> GraphBitResult<Self> {
+        if config.provider != EmbeddingProvider::Jina {
+            return Err(GraphBitError::config(
+                "Invalid provider type for Jina".to_string(),
+            ));
+        }
+
+        config.timeouts.validate()?;
+        let client = shared_client_with_timeouts(
+            "embeddings.jina",
+            Some(std::time::Duration::from_secs(
+                config.timeout_seconds.unwrap_or(60),
+            )),
+            None,
+            config.timeouts,
+        )?;
+
+        Ok(Self { config, client })
+    }
+
+    /// Get the API base URL
+    fn base_url(&self) -> &str {
+        self.config
+            .base_url
+            .as_deref()
+            .unwrap_or("https://api.jina.ai/v1")
+    }
+
+    /// Default embedding dimensions of a `Jina AI` model
+    #[must_use]
+    pub fn model_dimensions(model: &str) -> Option<usize> {
+        match model {
+            "jina-embeddings-v4" => Some(2048),
+            "jina-embeddings-v3" | "jina-clip-v2" => Some(1024),
+            "jina-clip-v1" => Some(768),
+            "jina-embeddings-v2-small-en" => Some(512),
+            m if m.starts_with("jina-embeddings-v2-base-") => Some(768),
+            _ => None,
+        }
+    }
+
+    /// Whether `model` takes a `task`; the v2 models and CLIP v1 do not
+    fn supports_task(model: &str) -> bool {
+        !model.starts_with("jina-embeddings-v2-") && model != "jina-clip-v1"
+    }
+}
+
+#[async_trait]
+impl EmbeddingProviderTrait for JinaEmbeddingProvider {
+    async fn generate_embeddings(
+        &self,
+        request: EmbeddingRequest,
+    ) -> GraphBitResult<EmbeddingResponse> {
+        let url = format!("{}/embeddings", self.base_url());
+        let mut params = request_params(&self.config, &request);
+        let input_type = input_type_is_query(&params)?;
+        params.remove(INPUT_TYPE_PARAM);
+        if let Some(query) = input_type {
+            if Self::supports_task(&self.config.model) {
+                let task = if query {
+                    "retrieval.query"
+                } else {
+                    "retrieval.passage"
+                };
+                params
+                    .entry("task".to_string())
+                    .or_insert_with(|| task.into());
+            }
+        }
+
+        let texts = request.input.as_texts();
+        let ranges = batch_ranges(&texts, self.max_batch_size(), None);
+        let batches = ranges.len();
+        let (embeddings, usage, model) = embed_in_batches(
+            &self.client,
+            "jina",
+            &url,
+            &self.config.api_key,
+            &texts,
+            ranges,
+            |batch| {
+                let mut body = serde_json::json!({
+                    "model": self.config.model,
+                    "input": batch,
+                });
+                for (key, value) in &params {
+                    body[key] = value.clone();
+                }
+                body
+            },
+        )
+        .await?;
+
+        let mut metadata = HashMap::from([("batches".to_string(), batches.into())]);
+        if let Some(task) = params.get("task") {
+            metadata.insert("task".to_string(), task.clone());
+        }
+        Ok(EmbeddingResponse {
+            embeddings,
+            model: model.unwrap_or_else(|| self.config.model.clone()),
+            usage,
+            metadata,
+        })
+    }
+
+    fn provider_name(&self) -> &str {
+        "jina"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn get_embedding_dimensions(&self) -> GraphBitResult<usize> {
+        let configured = self
+            .config
+            .extra_params
+            .get(DIMENSIONS_PARAM)
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|dimensions| usize::try_from(dimensions).ok());
+        if let Some(dimensions) = configured.or_else(|| Self::model_dimensions(&self.config.model))
+        {
+            return Ok(dimensions);
+        }
+
+        // Make a test request to determine dimensions
+        let test_request = EmbeddingRequest {
+            input: EmbeddingInput::Single("test".to_string()),
+            user: None,
+            params: HashMap::new(),
+        };
+        let response = self.generate_embeddings(test_request).await?;
+        Ok(response.embeddings.first().map_or(1024, Vec::len))
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.config
+            .max_batch_size
+            .map_or(MAX_BATCH_TEXTS, |size| size.min(MAX_BATCH_TEXTS))
+    }
+}
@@ -0,0 +1,281 @@
+//! Local embedding provider
+//!
+//! Runs a `sentence-transformers` model with a BERT architecture on the CPU,
+//! so embeddings need no network access. The model directory holds the
+//! `config.json`, `tokenizer.json` and `model.safetensors` of the exported
+//! model; an optional `sentence_bert_config.json` sets the longest input.
+//! Token embeddings are mean pooled over the attention mask and L2
+//! normalized, as the `Pooling` and `Normalize` modules of
+//! `sentence-transformers` do. Models are never downloaded: the directory
+//! must already exist.
+
+use crate::embeddings::{
+    EmbeddingConfig, EmbeddingProvider, EmbeddingProviderTrait, EmbeddingRequest,
+    EmbeddingResponse, EmbeddingUsage,
+};
+use crate::errors::{GraphBitError, GraphBitResult};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+/// [`EmbeddingConfig::extra_params`] key of the number of CPU threads running
+/// the model
+pub const THREADS_PARAM: &str = "threads";
+
+/// Texts run through the model at once unless `max_batch_size` says otherwise
+const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// Embedding provider running a local `sentence-transformers` model
+pub struct LocalEmbeddingProvider {
+    model: Arc<LocalModel>,
+    model_dir: String,
+    max_batch_size: usize,
+}
+
+/// A loaded model with its tokenizer
+struct LocalModel {
+    bert: BertModel,
+    tokenizer: Tokenizer,
+    hidden_size: usize,
+    /// Threads running the model, `None` for one per core
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl LocalEmbeddingProvider {
+    /// Load the model in the directory named by `config.model`
+    pub fn new(config: EmbeddingConfig) -> GraphBitResult<Self> {
+        if config.provider != EmbeddingProvider::Local {
+            return Err(GraphBitError::config(
+                "Invalid provider type for local embeddings".to_string(),
+            ));
+        }
+
+        let dir = Path::new(&config.model);
+        if !dir.is_dir() {
+            return Err(GraphBitError::config(format!(
+                "Local embedding model directory '{}' does not exist",
+                dir.display()
+            )));
+        }
+
+        let threads = match config.extra_params.get(THREADS_PARAM) {
+            None | Some(serde_json::Value::Null) => None,
+            Some(value) => Some(
+                value
+                    .as_u64()
+                    .and_then(|threads| usize::try_from(threads).ok())
+                    .filter(|threads| *threads > 0)
+                    .ok_or_else(|| {
+                        GraphBitError::validation(
+                            THREADS_PARAM,
+                            format!("must be a positive integer, got {value}"),
+                        )
+                    })?,
+            ),
+        };
+        let pool = threads
+            .map(|threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|index| format!("graphbit-embed-{index}"))
+                    .build()
+            })
+            .transpose()
+            .map_err(|e| {
+                GraphBitError::config(format!("Failed to start embedding threads: {e}"))
+            })?;
+
+        let model = LocalModel::load(dir, pool)?;
+        Ok(Self {
+            model: Arc::new(model),
+            model_dir: config.model,
+            max_batch_size: config.max_batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1),
+        })
+    }
+}
+
+impl LocalModel {
+    fn load(dir: &Path, pool: Option<rayon::ThreadPool>) -> GraphBitResult<Self> {
+        let config: BertConfig = serde_json::from_slice(&read(&dir.join("config.json"))?)
+            .map_err(|e| GraphBitError::config(format!("Invalid model config.json: {e}")))?;
+
+        // `max_seq_length` of sentence-transformers is often below what the
+        // position embeddings allow
+        let max_length = match std::fs::read(dir.join("sentence_bert_config.json")) {
+            Ok(data) => serde_json::from_slice::<serde_json::Value>(&data)
+                .map_err(|e| {
+                    GraphBitError::config(format!("Invalid sentence_bert_config.json: {e}"))
+                })?
+                .get("max_seq_length")
+                .and_then(serde_json::Value::as_u64)
+                .and_then(|length| usize::try_from(length).ok()),
+            Err(_) => None,
+        }
+        .map_or(config.max_position_embeddings, |length| {
+            length.min(config.max_position_embeddings)
+        });
+
+        let mut tokenizer = Tokenizer::from_bytes(read(&dir.join("tokenizer.json"))?)
+            .map_err(|e| GraphBitError::config(format!("Invalid model tokenizer.json: {e}")))?;
+        let pad_id = u32::try_from(config.pad_token_id).unwrap_or_default();
+        let pad_token = tokenizer
+            .id_to_token(pad_id)
+            .unwrap_or_else(|| "[PAD]".to_string());
+        tokenizer
+            .with_padding(Some(PaddingParams {
+                pad_id,
+                pad_token,
+                ..PaddingParams::default()
+            }))
+            .with_truncation(Some(TruncationParams {
+                max_length,
+                ..TruncationParams::default()
+            }))
+            .map_err(|e| GraphBitError::config(format!("Invalid model tokenizer.json: {e}")))?;
+
+        let weights = read(&dir.join("model.safetensors"))?;
+        let vb = VarBuilder::from_buffered_safetensors(weights, DTYPE, &Device::Cpu)
+            .map_err(|e| GraphBitError::config(format!("Invalid model.safetensors: {e}")))?;
+        let bert = BertModel::load(vb, &config)
+            .map_err(|e| GraphBitError::config(format!("Failed to load the model: {e}")))?;
+
+        Ok(Self {
+            bert,
+            tokenizer,
+            hidden_size: config.hidden_size,
+            pool,
+        })
+    }
+
+    /// Embed `texts`, returning their embeddings and the number of tokens
+    /// they took
+    fn embed(&self, texts: &[String]) -> GraphBitResult<(Vec<Vec<f32>>, u32)> {
+        self.pool
+            .as_ref()
+            .map_or_else(|| self.run(texts), |pool| pool.install(|| self.run(texts)))
+    }
+
+    fn run(&self, texts: &[String]) -> GraphBitResult<(Vec<Vec<f32>>, u32)> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| model_error(format!("Tokenization failed: {e}")))?;
+        let tokens: usize = encodings
+            .iter()
+            .map(|encoding| encoding.get_attention_mask().iter().sum::<u32>() as usize)
+            .sum();
+
+        let stack = |rows: Vec<&[u32]>| -> candle_core::Result<Tensor> {
+            let rows = rows
+                .into_iter()
+                .map(|row| Tensor::new(row, &Device::Cpu))
+                .collect::<candle_core::Result<Vec<_>>>()?;
+            Tensor::stack(&rows, 0)
+        };
+        let embeddings = (|| {
+            let input_ids = stack(
+                encodings
+                    .iter()
+                    .map(tokenizers::Encoding::get_ids)
+                    .collect(),
+            )?;
+            let attention_mask = stack(
+                encodings
+                    .iter()
+                    .map(tokenizers::Encoding::get_attention_mask)
+                    .collect(),
+            )?;
+            let token_type_ids = input_ids.zeros_like()?;
+            let hidden = self
+                .bert
+                .forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+
+            // Mean of the token embeddings, leaving out padding
+            let mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+            let pooled = hidden
+                .broadcast_mul(&mask)?
+                .sum(1)?
+                .broadcast_div(&mask.sum(1)?)?;
+            let norms = pooled.sqr()?.sum_keepdim(1)?.sqrt()?.maximum(1e-12)?;
+            pooled.broadcast_div(&norms)?.to_vec2::<f32>()
+        })()
+        .map_err(|e| model_error(format!("Inference failed: {e}")))?;
+
+        Ok((embeddings, u32::try_from(tokens).unwrap_or(u32::MAX)))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProviderTrait for LocalEmbeddingProvider {
+    async fn generate_embeddings(
+        &self,
+        request: EmbeddingRequest,
+    ) -> GraphBitResult<EmbeddingResponse> {
+        let texts: Vec<String> = request
+            .input
+            .as_texts()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let model = Arc::clone(&self.model);
+        let max_batch_size = self.max_batch_size;
+
+        // Inference is CPU bound; keep it off the async workers
+        let (embeddings, tokens) = tokio::task::spawn_blocking(move || {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            let mut tokens = 0u32;
+            for batch in texts.chunks(max_batch_size) {
+                let (batch_embeddings, batch_tokens) = model.embed(batch)?;
+                embeddings.extend(batch_embeddings);
+                tokens = tokens.saturating_add(batch_tokens);
+            }
+            Ok::<_, GraphBitError>((embeddings, tokens))
+        })
+        .await
+        .map_err(|e| model_error(format!("Embedding task failed: {e}")))??;
+
+        Ok(EmbeddingResponse {
+            embeddings,
+            model: self.model_dir.clone(),
+            usage: EmbeddingUsage {
+                prompt_tokens: tokens,
+                total_tokens: tokens,
+            },
+            metadata: HashMap::new(),
+        })
+    }
+
+    fn provider_name(&self) -> &str {
+        "local"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_dir
+    }
+
+    async fn get_embedding_dimensions(&self) -> GraphBitResult<usize> {
+        Ok(self.model.hidden_size)
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+}
+
+fn read(path: &Path) -> GraphBitResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        GraphBitError::config(format!(
+            "Failed to read local embedding model file '{}': {e}",
+            path.display()
+        ))
+    })
+}
+
+fn model_error(message: String) -> GraphBitError {
+    GraphBitError::llm_provider("local", message)
+}
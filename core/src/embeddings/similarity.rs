@@ -0,0 +1,251 @@
+//! Similarity functions over embeddings
+//!
+//! Scores, nearest-neighbour selection and maximal marginal relevance (MMR)
+//! re-ranking over embeddings that have already been computed. Every function
+//! fails with a validation error when the dimensions of its vectors differ.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// How the similarity of two embeddings is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityMetric {
+    /// Cosine of the angle between the embeddings; higher is more similar
+    #[default]
+    Cosine,
+    /// Dot product of the embeddings; higher is more similar
+    DotProduct,
+    /// Euclidean distance between the embeddings; lower is more similar
+    Euclidean,
+}
+
+impl SimilarityMetric {
+    /// Name of the metric, as accepted by [`FromStr`]
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Cosine => "cosine",
+            Self::DotProduct => "dot_product",
+            Self::Euclidean => "euclidean",
+        }
+    }
+
+    /// Whether a higher score means the embeddings are more similar
+    #[must_use]
+    pub const fn higher_is_better(self) -> bool {
+        !matches!(self, Self::Euclidean)
+    }
+
+    /// Score of `a` against `b` under this metric
+    pub fn score(self, a: &[f32], b: &[f32]) -> GraphBitResult<f32> {
+        match self {
+            Self::Cosine => cosine_similarity(a, b),
+            Self::DotProduct => dot_product(a, b),
+            Self::Euclidean => euclidean_distance(a, b),
+        }
+    }
+}
+
+impl fmt::Display for SimilarityMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SimilarityMetric {
+    type Err = GraphBitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cosine" => Ok(Self::Cosine),
+            "dot_product" | "dot" => Ok(Self::DotProduct),
+            "euclidean" => Ok(Self::Euclidean),
+            other => Err(GraphBitError::validation(
+                "metric",
+                format!(
+                    "Unknown similarity metric '{other}', expected cosine, dot_product or euclidean"
+                ),
+            )),
+        }
+    }
+}
+
+fn check_dimensions(a: &[f32], b: &[f32]) -> GraphBitResult<()> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(GraphBitError::validation(
+            "dimensions",
+            format!(
+                "Embedding dimensions must match, got {} and {}",
+                a.len(),
+                b.len()
+            ),
+        ))
+    }
+}
+
+/// Dot product of two embeddings
+pub fn dot_product(a: &[f32], b: &[f32]) -> GraphBitResult<f32> {
+    check_dimensions(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+/// Cosine similarity of two embeddings, 0 when either is the zero vector
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> GraphBitResult<f32> {
+    let dot_product = dot_product(a, b)?;
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return Ok(0.0);
+    }
+    Ok(dot_product / (norm_a * norm_b))
+}
+
+/// Euclidean distance between two embeddings
+pub fn euclidean_distance(a: &[f32], b: &[f32]) -> GraphBitResult<f32> {
+    check_dimensions(a, b)?;
+    Ok(a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f32>()
+        .sqrt())
+}
+
+/// A corpus entry ordered so that the greatest entry is the most similar one
+#[derive(Debug, Clone, Copy)]
+struct Ranked {
+    /// Score, negated for metrics where lower is better
+    key: f32,
+    index: usize,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Earlier entries rank above later ones with the same score
+        self.key
+            .total_cmp(&other.key)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// The `k` entries of `corpus` most similar to `query`, as `(index, score)`
+/// pairs from most to least similar. Entries with equal scores keep their
+/// corpus order.
+///
+/// Only `k` entries are held at a time, so selecting a few neighbours from a
+/// large corpus does not allocate a score per entry.
+pub fn top_k<V: AsRef<[f32]>>(
+    query: &[f32],
+    corpus: &[V],
+    k: usize,
+    metric: SimilarityMetric,
+) -> GraphBitResult<Vec<(usize, f32)>> {
+    if k == 0 {
+        return Ok(Vec::new());
+    }
+
+    let sign = if metric.higher_is_better() { 1.0 } else { -1.0 };
+    let mut heap = BinaryHeap::with_capacity(k.min(corpus.len()) + 1);
+    for (index, embedding) in corpus.iter().enumerate() {
+        let score = metric.score(query, embedding.as_ref())?;
+        heap.push(Reverse(Ranked {
+            key: sign * score,
+            index,
+        }));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    Ok(heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| (ranked.index, sign * ranked.key))
+        .collect())
+}
+
+/// Re-rank `candidates` by maximal marginal relevance, returning the indices of
+/// up to `k` of them in selection order.
+///
+/// Each step picks the candidate maximising
+/// `lambda * sim(query, c) - (1 - lambda) * max(sim(c, s) for selected s)`,
+/// with cosine similarity, so `lambda` 1 ranks by relevance alone and `lambda`
+/// 0 by diversity alone.
+pub fn mmr<V: AsRef<[f32]>>(
+    query: &[f32],
+    candidates: &[V],
+    lambda: f32,
+    k: usize,
+) -> GraphBitResult<Vec<usize>> {
+    if !(0.0..=1.0).contains(&lambda) {
+        return Err(GraphBitError::validation(
+            "lambda",
+            format!("MMR lambda must be between 0 and 1, got {lambda}"),
+        ));
+    }
+
+    let relevance = candidates
+        .iter()
+        .map(|candidate| cosine_similarity(query, candidate.as_ref()))
+        .collect::<GraphBitResult<Vec<f32>>>()?;
+    let mut redundancy = vec![f32::NEG_INFINITY; candidates.len()];
+    let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+    let mut selected = Vec::with_capacity(k.min(candidates.len()));
+
+    while selected.len() < k {
+        let marginal = |index: usize| {
+            let penalty = if selected.is_empty() {
+                0.0
+            } else {
+                redundancy[index]
+            };
+            lambda.mul_add(relevance[index], -(1.0 - lambda) * penalty)
+        };
+        let best = remaining.iter().enumerate().fold(
+            None,
+            |best: Option<(usize, f32)>, (position, &index)| {
+                let score = marginal(index);
+                match best {
+                    Some((_, best_score)) if best_score >= score => best,
+                    _ => Some((position, score)),
+                }
+            },
+        );
+        let Some((position, _)) = best else {
+            break;
+        };
+        let chosen = remaining.remove(position);
+        selected.push(chosen);
+
+        for &index in &remaining {
+            let similarity =
+                cosine_similarity(candidates[chosen].as_ref(), candidates[index].as_ref())?;
+            redundancy[index] = redundancy[index].max(similarity);
+        }
+    }
+
+    Ok(selected)
+}
@@ -0,0 +1,186 @@
+//! `Voyage AI` embedding provider
+//!
+//! Voyage's retrieval models embed search queries and the documents searched
+//! differently; the `input_type` request parameter (`"query"` or
+//! `"document"`) selects which. Requests hold at most 1,000 texts and a
+//! model-dependent number of tokens, so larger inputs are sent in batches.
+
+use crate::embeddings::{
+    DIMENSIONS_PARAM, EmbeddingConfig, EmbeddingInput, EmbeddingProvider, EmbeddingProviderTrait,
+    EmbeddingRequest, EmbeddingResponse, INPUT_TYPE_DOCUMENT, INPUT_TYPE_PARAM, INPUT_TYPE_QUERY,
+    batch_ranges, embed_in_batches, input_type_is_query, request_params,
+};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::shared_client_with_timeouts;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Most texts `Voyage AI` accepts in one request
+pub const MAX_BATCH_TEXTS: usize = 1000;
+
+/// `Voyage AI` embedding provider
+#[derive(Debug, Clone)]
+pub struct VoyageEmbeddingProvider {
+    config: EmbeddingConfig,
+    client: reqwest::Client,
+}
+
+impl VoyageEmbeddingProvider {
+    /// Create a new `Voyage AI` embedding provider
+    pub fn new(config: EmbeddingConfig) -> GraphBitResult<Self> {
+        if config.provider != EmbeddingProvider::Voyage {
+            return Err(GraphBitError::config(
+                "Invalid provider type for Voyage".to_string(),
+            ));
+        }
+
+        config.timeouts.validate()?;
+        let client = shared_client_with_timeouts(
+            "embeddings.voyage",
+            Some(std::time::Duration::from_secs(
+                config.timeout_seconds.unwrap_or(60),
+            )),
+            None,
+            config.timeouts,
+        )?;
+
+        Ok(Self { config, client })
+    }
+
+    /// Get the API base URL
+    fn base_url(&self) -> &str {
+        self.config
+            .base_url
+            .as_deref()
+            .unwrap_or("https://api.voyageai.com/v1")
+    }
+
+    /// Default embedding dimensions of a `Voyage AI` model
+    #[must_use]
+    pub fn model_dimensions(model: &str) -> Option<usize> {
+        match model {
+            "voyage-3.5"
+            | "voyage-3.5-lite"
+            | "voyage-3-large"
+            | "voyage-3"
+            | "voyage-code-3"
+            | "voyage-finance-2"
+            | "voyage-law-2"
+            | "voyage-multilingual-2"
+            | "voyage-2" => Some(1024),
+            "voyage-3-lite" => Some(512),
+            "voyage-code-2" | "voyage-large-2" | "voyage-large-2-instruct" => Some(1536),
+            _ => None,
+        }
+    }
+
+    /// Most tokens `Voyage AI` accepts in one request to `model`
+    #[must_use]
+    pub fn max_batch_tokens(model: &str) -> usize {
+        match model {
+            "voyage-3.5-lite" | "voyage-3-lite" => 1_000_000,
+            "voyage-3.5" | "voyage-3" | "voyage-2" => 320_000,
+            _ => 120_000,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProviderTrait for VoyageEmbeddingProvider {
+    async fn generate_embeddings(
+        &self,
+        request: EmbeddingRequest,
+    ) -> GraphBitResult<EmbeddingResponse> {
+        let url = format!("{}/embeddings", self.base_url());
+        let mut params = request_params(&self.config, &request);
+        let input_type = input_type_is_query(&params)?.map(|query| {
+            if query {
+                INPUT_TYPE_QUERY
+            } else {
+                INPUT_TYPE_DOCUMENT
+            }
+        });
+        params.remove(INPUT_TYPE_PARAM);
+        // Voyage calls shortened embeddings `output_dimension`
+        if let Some(dimensions) = params.remove(DIMENSIONS_PARAM) {
+            params.insert("output_dimension".to_string(), dimensions);
+        }
+
+        let texts = request.input.as_texts();
+        let ranges = batch_ranges(
+            &texts,
+            self.max_batch_size(),
+            Some(Self::max_batch_tokens(&self.config.model)),
+        );
+        let batches = ranges.len();
+        let (embeddings, usage, model) = embed_in_batches(
+            &self.client,
+            "voyage",
+            &url,
+            &self.config.api_key,
+            &texts,
+            ranges,
+            |batch| {
+                let mut body = serde_json::json!({
+                    "model": self.config.model,
+                    "input": batch,
+                });
+                if let Some(input_type) = input_type {
+                    body["input_type"] = input_type.into();
+                }
+                for (key, value) in &params {
+                    body[key] = value.clone();
+                }
+                body
+            },
+        )
+        .await?;
+
+        let mut metadata = HashMap::from([("batches".to_string(), batches.into())]);
+        if let Some(input_type) = input_type {
+            metadata.insert("input_type".to_string(), input_type.into());
+        }
+        Ok(EmbeddingResponse {
+            embeddings,
+            model: model.unwrap_or_else(|| self.config.model.clone()),
+            usage,
+            metadata,
+        })
+    }
+
+    fn provider_name(&self) -> &str {
+        "voyage"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn get_embedding_dimensions(&self) -> GraphBitResult<usize> {
+        let configured = self
+            .config
+            .extra_params
+            .get(DIMENSIONS_PARAM)
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|dimensions| usize::try_from(dimensions).ok());
+        if let Some(dimensions) = configured.or_else(|| Self::model_dimensions(&self.config.model))
+        {
+            return Ok(dimensions);
+        }
+
+        // Make a test request to determine dimensions
+        let test_request = EmbeddingRequest {
+            input: EmbeddingInput::Single("test".to_string()),
+            user: None,
+            params: HashMap::new(),
+        };
+        let response = self.generate_embeddings(test_request).await?;
+        Ok(response.embeddings.first().map_or(1024, Vec::len))
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.config
+            .max_batch_size
+            .map_or(MAX_BATCH_TEXTS, |size| size.min(MAX_BATCH_TEXTS))
+    }
+}
@@ -0,0 +1,582 @@
+//! Configuration from environment variables
+//!
+//! [`EnvConfig`] reads the `GRAPHBIT_*` variables of the process, which
+//! [`LlmConfig::from_env`], [`EmbeddingConfig::from_env`] and
+//! [`WorkflowExecutor::from_env`](crate::workflow::WorkflowExecutor::from_env)
+//! build their configuration from. Settings made in code after that always
+//! take precedence over the variables.
+//!
+//! Variables are named `GRAPHBIT_<SETTING>`, and provider settings
+//! `GRAPHBIT_<PROVIDER>_<SETTING>` with the provider as returned by
+//! [`LlmConfig::provider_name`], upper-cased:
+//!
+//! | Variable | Setting |
+//! |---|---|
+//! | `GRAPHBIT_DEFAULT_PROVIDER` | LLM provider used when none is named |
+//! | `GRAPHBIT_DEFAULT_MODEL` | Model of providers without `GRAPHBIT_<PROVIDER>_MODEL` |
+//! | `GRAPHBIT_<PROVIDER>_API_KEY` | API key, or a [reference](crate::secret_ref) to it |
+//! | `GRAPHBIT_<PROVIDER>_MODEL` | Model, or deployment name for `azurellm` |
+//! | `GRAPHBIT_<PROVIDER>_BASE_URL` | Endpoint replacing the provider's public API |
+//! | `GRAPHBIT_AZURELLM_ENDPOINT` | Endpoint of the Azure deployment |
+//! | `GRAPHBIT_AZURELLM_API_VERSION` | Azure API version |
+//! | `GRAPHBIT_EMBEDDING_PROVIDER` | `openai` (default), `azure`, `voyage` or `jina` |
+//! | `GRAPHBIT_EMBEDDING_MODEL` | Embedding model |
+//! | `GRAPHBIT_EMBEDDING_API_KEY` | API key, by default the provider's `GRAPHBIT_<PROVIDER>_API_KEY` |
+//! | `GRAPHBIT_EMBEDDING_BASE_URL` | Endpoint, the Azure endpoint for `azure` |
+//! | `GRAPHBIT_EMBEDDING_DEPLOYMENT_NAME` | Deployment name for `azure` |
+//! | `GRAPHBIT_MAX_CONCURRENCY` | Nodes running at once |
+//! | `GRAPHBIT_FAIL_FAST` | Whether the first failing node fails the run |
+//! | `GRAPHBIT_MAX_NODE_EXECUTION_TIME_MS` | Time limit of each node |
+//! | `GRAPHBIT_MAX_TOTAL_TOKENS` | Token budget of each run |
+//! | `GRAPHBIT_MAX_COST_USD` | Cost budget of each run |
+//!
+//! Empty variables count as unset. [`EnvConfig::doctor`] lists which
+//! variables were found, which are missing and which are not read at all.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::embeddings::{EmbeddingConfig, EmbeddingProvider};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::LlmConfig;
+use crate::secret_ref::REDACTED;
+
+/// Prefix of the variables read
+pub const ENV_PREFIX: &str = "GRAPHBIT_";
+
+/// Providers [`LlmConfig::from_env`] can configure
+pub const LLM_PROVIDERS: &[&str] = &[
+    "openai",
+    "anthropic",
+    "azurellm",
+    "bytedance",
+    "deepseek",
+    "huggingface",
+    "ollama",
+    "perplexity",
+    "openrouter",
+    "fireworks",
+    "replicate",
+    "togetherai",
+    "xai",
+    "ai21",
+    "mistralai",
+    "gemini",
+];
+
+/// Providers [`EmbeddingConfig::from_env`] can configure
+pub const EMBEDDING_PROVIDERS: &[&str] = &["openai", "azure", "voyage", "jina"];
+
+/// API version of Azure deployments without `GRAPHBIT_AZURELLM_API_VERSION`
+const AZURELLM_API_VERSION: &str = "2024-10-21";
+
+/// API version of Azure embedding deployments
+const AZURE_EMBEDDING_API_VERSION: &str = "2024-02-01";
+
+/// Executor settings, each with its variable and description
+const EXECUTOR_VARS: &[(&str, &str)] = &[
+    ("MAX_CONCURRENCY", "nodes running at once"),
+    ("FAIL_FAST", "whether the first failing node fails the run"),
+    ("MAX_NODE_EXECUTION_TIME_MS", "time limit of each node"),
+    ("MAX_TOTAL_TOKENS", "token budget of each run"),
+    ("MAX_COST_USD", "cost budget of each run"),
+];
+
+/// Name of the variable of `setting`, e.g. `GRAPHBIT_MAX_CONCURRENCY`
+#[must_use]
+pub fn var_name(setting: &str) -> String {
+    format!("{ENV_PREFIX}{setting}")
+}
+
+/// Name of the variable of a provider's `setting`, e.g.
+/// `GRAPHBIT_OPENAI_API_KEY`
+#[must_use]
+pub fn provider_var_name(provider: &str, setting: &str) -> String {
+    format!("{ENV_PREFIX}{}_{setting}", provider.to_ascii_uppercase())
+}
+
+/// Snapshot of the `GRAPHBIT_*` variables
+#[derive(Clone, Default)]
+pub struct EnvConfig {
+    vars: BTreeMap<String, String>,
+}
+
+impl std::fmt::Debug for EnvConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.vars
+                    .iter()
+                    .map(|(name, value)| (name, display_value(name, value))),
+            )
+            .finish()
+    }
+}
+
+/// Executor settings read from the environment, `None` where no variable is set
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExecutorEnvDefaults {
+    /// `GRAPHBIT_MAX_CONCURRENCY`
+    pub max_concurrency: Option<usize>,
+    /// `GRAPHBIT_FAIL_FAST`
+    pub fail_fast: Option<bool>,
+    /// `GRAPHBIT_MAX_NODE_EXECUTION_TIME_MS`
+    pub max_node_execution_time_ms: Option<u64>,
+    /// `GRAPHBIT_MAX_TOTAL_TOKENS`
+    pub max_total_tokens: Option<u64>,
+    /// `GRAPHBIT_MAX_COST_USD`
+    pub max_cost_usd: Option<f64>,
+}
+
+/// What [`EnvConfig::doctor`] found for a variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "detail", rename_all = "snake_case")]
+pub enum EnvVarStatus {
+    /// Set to a usable value, shown redacted for API keys
+    Found(String),
+    /// Not set
+    Missing,
+    /// Set to a value that cannot be used, with the reason
+    Invalid(String),
+    /// Set, but not read by `GraphBit`
+    Unrecognized,
+}
+
+/// A variable checked by [`EnvConfig::doctor`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvVarCheck {
+    /// Name of the variable
+    pub name: String,
+    /// Setting the variable holds
+    pub description: String,
+    /// Whether the configuration in use cannot be built without it
+    pub required: bool,
+    /// What was found
+    pub status: EnvVarStatus,
+}
+
+impl EnvVarCheck {
+    /// Whether the variable is invalid, or required and missing
+    #[must_use]
+    pub const fn is_problem(&self) -> bool {
+        match self.status {
+            EnvVarStatus::Invalid(_) => true,
+            EnvVarStatus::Missing => self.required,
+            EnvVarStatus::Found(_) | EnvVarStatus::Unrecognized => false,
+        }
+    }
+}
+
+impl EnvConfig {
+    /// Read the `GRAPHBIT_*` variables of the process; variables that are
+    /// not valid Unicode are skipped
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::from_vars(std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        }))
+    }
+
+    /// Take the `GRAPHBIT_*` variables out of `vars`
+    pub fn from_vars<I, K, V>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        vars.into_iter()
+            .fold(Self::default(), |config, (name, value)| {
+                config.with_var(name, value)
+            })
+    }
+
+    /// Set the variable `name`, or unset it when `value` is empty; names
+    /// without the `GRAPHBIT_` prefix are ignored
+    #[must_use]
+    pub fn with_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let (name, value) = (name.into(), value.into());
+        if name.starts_with(ENV_PREFIX) {
+            if value.trim().is_empty() {
+                self.vars.remove(&name);
+            } else {
+                self.vars.insert(name, value);
+            }
+        }
+        self
+    }
+
+    /// Value of the variable `name`, e.g. `GRAPHBIT_DEFAULT_MODEL`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    /// `GRAPHBIT_DEFAULT_PROVIDER`
+    #[must_use]
+    pub fn default_provider(&self) -> Option<&str> {
+        self.get(&var_name("DEFAULT_PROVIDER"))
+    }
+
+    /// Configuration of `provider` from its `GRAPHBIT_<PROVIDER>_*` variables
+    pub fn llm_config(&self, provider: &str) -> GraphBitResult<LlmConfig> {
+        let provider = provider.trim().to_ascii_lowercase();
+        if !LLM_PROVIDERS.contains(&provider.as_str()) {
+            return Err(GraphBitError::config(format!(
+                "Unknown LLM provider '{provider}', expected one of: {}",
+                LLM_PROVIDERS.join(", ")
+            )));
+        }
+        let var = |setting: &str| provider_var_name(&provider, setting);
+        let model = self
+            .get(&var("MODEL"))
+            .or_else(|| self.get(&var_name("DEFAULT_MODEL")))
+            .ok_or_else(|| {
+                GraphBitError::config(format!(
+                    "Neither {} nor {} is set",
+                    var("MODEL"),
+                    var_name("DEFAULT_MODEL")
+                ))
+            })?
+            .to_string();
+        if provider == "ollama" {
+            let config = LlmConfig::ollama(model);
+            return Ok(match self.get(&var("BASE_URL")) {
+                Some(base_url) => config.with_base_url(base_url),
+                None => config,
+            });
+        }
+
+        let api_key = self.require(&var("API_KEY"))?.to_string();
+        let config = match provider.as_str() {
+            "openai" => LlmConfig::openai(api_key, model),
+            "anthropic" => LlmConfig::anthropic(api_key, model),
+            "azurellm" => LlmConfig::azurellm(
+                api_key,
+                model,
+                self.require(&var("ENDPOINT"))?,
+                self.get(&var("API_VERSION"))
+                    .unwrap_or(AZURELLM_API_VERSION),
+            ),
+            "bytedance" => LlmConfig::bytedance(api_key, model),
+            "deepseek" => LlmConfig::deepseek(api_key, model),
+            "huggingface" => LlmConfig::huggingface(api_key, model),
+            "perplexity" => LlmConfig::perplexity(api_key, model),
+            "openrouter" => LlmConfig::openrouter(api_key, model),
+            "fireworks" => LlmConfig::fireworks(api_key, model),
+            "replicate" => LlmConfig::replicate(api_key, model),
+            "togetherai" => LlmConfig::togetherai(api_key, model),
+            "xai" => LlmConfig::xai(api_key, model),
+            "ai21" => LlmConfig::ai21(api_key, model),
+            "mistralai" => LlmConfig::mistralai(api_key, model),
+            _ => LlmConfig::gemini(api_key, model),
+        };
+        Ok(match self.get(&var("BASE_URL")) {
+            Some(base_url) => config.with_base_url(base_url),
+            None => config,
+        })
+    }
+
+    /// Embedding configuration from the `GRAPHBIT_EMBEDDING_*` variables
+    pub fn embedding_config(&self) -> GraphBitResult<EmbeddingConfig> {
+        let provider = self.embedding_provider()?;
+        let var = |setting: &str| provider_var_name("EMBEDDING", setting);
+        let model = self.require(&var("MODEL"))?;
+        let api_key = self
+            .get(&var("API_KEY"))
+            .or_else(|| self.get(&embedding_key_fallback(provider)))
+            .ok_or_else(|| {
+                GraphBitError::config(format!(
+                    "Neither {} nor {} is set",
+                    var("API_KEY"),
+                    embedding_key_fallback(provider)
+                ))
+            })?;
+        let base_url = self.get(&var("BASE_URL"));
+        let config = match provider {
+            "openai" => EmbeddingConfig::openai(api_key, model),
+            "azure" => {
+                let endpoint = base_url.ok_or_else(|| {
+                    GraphBitError::config(format!(
+                        "{} must be set to the Azure endpoint",
+                        var("BASE_URL")
+                    ))
+                })?;
+                let mut config = EmbeddingConfig::openai(api_key, model);
+                config.provider = EmbeddingProvider::Azure;
+                config.extra_params.extend([
+                    (
+                        "deployment_name".to_string(),
+                        self.require(&var("DEPLOYMENT_NAME"))?.into(),
+                    ),
+                    ("endpoint".to_string(), endpoint.into()),
+                    (
+                        "api_version".to_string(),
+                        AZURE_EMBEDDING_API_VERSION.into(),
+                    ),
+                ]);
+                return Ok(config);
+            }
+            "voyage" => EmbeddingConfig::voyage(api_key, model),
+            _ => EmbeddingConfig::jina(api_key, model),
+        };
+        Ok(match base_url {
+            Some(base_url) => config.with_base_url(base_url),
+            None => config,
+        })
+    }
+
+    /// Executor settings from `GRAPHBIT_MAX_CONCURRENCY`, `GRAPHBIT_FAIL_FAST`,
+    /// `GRAPHBIT_MAX_NODE_EXECUTION_TIME_MS`, `GRAPHBIT_MAX_TOTAL_TOKENS` and
+    /// `GRAPHBIT_MAX_COST_USD`
+    pub fn executor_defaults(&self) -> GraphBitResult<ExecutorEnvDefaults> {
+        let positive = |setting: &str| -> GraphBitResult<Option<u64>> {
+            match self.parse::<u64>(setting, "a whole number")? {
+                Some(0) => Err(invalid(setting, "0", "greater than 0")),
+                value => Ok(value),
+            }
+        };
+        let max_cost_usd = self.parse::<f64>("MAX_COST_USD", "a number")?;
+        if let Some(cost) = max_cost_usd.filter(|cost| !(cost.is_finite() && *cost > 0.0)) {
+            return Err(invalid(
+                "MAX_COST_USD",
+                &cost.to_string(),
+                "a positive number",
+            ));
+        }
+        Ok(ExecutorEnvDefaults {
+            max_concurrency: positive("MAX_CONCURRENCY")?
+                .map(|value| usize::try_from(value).unwrap_or(usize::MAX)),
+            fail_fast: self
+                .get(&var_name("FAIL_FAST"))
+                .map(|value| {
+                    parse_bool(value).ok_or_else(|| invalid("FAIL_FAST", value, "true or false"))
+                })
+                .transpose()?,
+            max_node_execution_time_ms: positive("MAX_NODE_EXECUTION_TIME_MS")?,
+            max_total_tokens: positive("MAX_TOTAL_TOKENS")?,
+            max_cost_usd,
+        })
+    }
+
+    /// Check the variables: the general and executor ones, those of the
+    /// providers in use, that is the default provider and every provider
+    /// with a variable set, and of the embedding configuration when one of
+    /// its variables is set. Set variables `GraphBit` does not read are
+    /// listed last, as [`EnvVarStatus::Unrecognized`].
+    #[must_use]
+    pub fn doctor(&self) -> Vec<EnvVarCheck> {
+        let mut checks = Vec::new();
+        let mut check = |name: String, description: &str, required: bool, status| {
+            checks.push(EnvVarCheck {
+                name,
+                description: description.to_string(),
+                required,
+                status,
+            });
+        };
+
+        let default_provider = self.default_provider().map(str::to_ascii_lowercase);
+        let provider_status = |name: &str, providers: &[&str]| match self.get(name) {
+            None => EnvVarStatus::Missing,
+            Some(value) if providers.contains(&value.to_ascii_lowercase().as_str()) => {
+                EnvVarStatus::Found(value.to_string())
+            }
+            Some(value) => EnvVarStatus::Invalid(format!(
+                "unknown provider '{value}', expected one of: {}",
+                providers.join(", ")
+            )),
+        };
+        let name = var_name("DEFAULT_PROVIDER");
+        let status = provider_status(&name, LLM_PROVIDERS);
+        check(name, "LLM provider used when none is named", false, status);
+        let name = var_name("DEFAULT_MODEL");
+        let status = self.found(&name);
+        check(name, "model of providers without their own", false, status);
+
+        for (setting, description) in EXECUTOR_VARS {
+            let name = var_name(setting);
+            let status = self.get(&name).map_or(EnvVarStatus::Missing, |value| {
+                let single = Self::default().with_var(name.clone(), value);
+                match single.executor_defaults() {
+                    Ok(_) => EnvVarStatus::Found(value.to_string()),
+                    Err(error) => EnvVarStatus::Invalid(error.to_string()),
+                }
+            });
+            check(name, description, false, status);
+        }
+
+        let providers_in_use = LLM_PROVIDERS.iter().filter(|provider| {
+            default_provider.as_deref() == Some(**provider)
+                || self.vars.keys().any(|name| is_provider_var(name, provider))
+        });
+        for provider in providers_in_use {
+            let var = |setting: &str| provider_var_name(provider, setting);
+            if *provider != "ollama" {
+                let name = var("API_KEY");
+                let status = self.found(&name);
+                check(name, "API key", true, status);
+            }
+            let name = var("MODEL");
+            let status = self.found(&name);
+            let required = self.get(&var_name("DEFAULT_MODEL")).is_none();
+            check(name, "model", required, status);
+            if *provider == "azurellm" {
+                let name = var("ENDPOINT");
+                let status = self.found(&name);
+                check(name, "endpoint of the deployment", true, status);
+                let name = var("API_VERSION");
+                let status = self.found(&name);
+                check(name, "API version", false, status);
+            } else {
+                let name = var("BASE_URL");
+                let status = self.found(&name);
+                check(name, "endpoint replacing the public API", false, status);
+            }
+        }
+
+        if self
+            .vars
+            .keys()
+            .any(|name| is_provider_var(name, "embedding"))
+        {
+            let var = |setting: &str| provider_var_name("embedding", setting);
+            let name = var("PROVIDER");
+            let status = provider_status(&name, EMBEDDING_PROVIDERS);
+            check(name, "embedding provider, openai by default", false, status);
+            let name = var("MODEL");
+            let status = self.found(&name);
+            check(name, "embedding model", true, status);
+            let fallback = self.embedding_provider().ok().map(embedding_key_fallback);
+            let name = var("API_KEY");
+            let status = self.found(&name);
+            let required = !fallback
+                .as_ref()
+                .is_some_and(|fallback| self.get(fallback).is_some());
+            let description = fallback.map_or_else(
+                || "API key".to_string(),
+                |fallback| format!("API key, by default {fallback}"),
+            );
+            check(name, &description, required, status);
+            let azure = self.embedding_provider().ok() == Some("azure");
+            let name = var("BASE_URL");
+            let status = self.found(&name);
+            check(name, "endpoint replacing the public API", azure, status);
+            if azure {
+                let name = var("DEPLOYMENT_NAME");
+                let status = self.found(&name);
+                check(name, "deployment name", true, status);
+            }
+        }
+
+        let checked: Vec<String> = checks.iter().map(|check| check.name.clone()).collect();
+        for name in self.vars.keys() {
+            if !checked.contains(name) {
+                checks.push(EnvVarCheck {
+                    name: name.clone(),
+                    description: "not read by GraphBit".to_string(),
+                    required: false,
+                    status: EnvVarStatus::Unrecognized,
+                });
+            }
+        }
+        checks
+    }
+
+    fn require(&self, name: &str) -> GraphBitResult<&str> {
+        self.get(name)
+            .ok_or_else(|| GraphBitError::config(format!("{name} is not set")))
+    }
+
+    fn found(&self, name: &str) -> EnvVarStatus {
+        self.get(name).map_or(EnvVarStatus::Missing, |value| {
+            EnvVarStatus::Found(display_value(name, value))
+        })
+    }
+
+    fn parse<T: FromStr>(&self, setting: &str, expected: &str) -> GraphBitResult<Option<T>> {
+        self.get(&var_name(setting))
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid(setting, value, expected))
+            })
+            .transpose()
+    }
+
+    /// `GRAPHBIT_EMBEDDING_PROVIDER`, `openai` when unset
+    fn embedding_provider(&self) -> GraphBitResult<&'static str> {
+        let Some(value) = self.get(&var_name("EMBEDDING_PROVIDER")) else {
+            return Ok("openai");
+        };
+        let value = value.trim().to_ascii_lowercase();
+        EMBEDDING_PROVIDERS
+            .iter()
+            .find(|provider| **provider == value)
+            .copied()
+            .ok_or_else(|| {
+                GraphBitError::config(format!(
+                    "Unknown embedding provider '{value}', expected one of: {}",
+                    EMBEDDING_PROVIDERS.join(", ")
+                ))
+            })
+    }
+}
+
+impl LlmConfig {
+    /// Configuration of `provider`, e.g. `"openai"`, from the
+    /// `GRAPHBIT_<PROVIDER>_*` variables, see [`crate::env_config`]
+    pub fn from_env(provider: &str) -> GraphBitResult<Self> {
+        EnvConfig::from_env().llm_config(provider)
+    }
+}
+
+impl EmbeddingConfig {
+    /// Configuration from the `GRAPHBIT_EMBEDDING_*` variables, see
+    /// [`crate::env_config`]
+    pub fn from_env() -> GraphBitResult<Self> {
+        EnvConfig::from_env().embedding_config()
+    }
+}
+
+/// Variable holding the key of embedding `provider` when
+/// `GRAPHBIT_EMBEDDING_API_KEY` is not set
+fn embedding_key_fallback(provider: &str) -> String {
+    let provider = if provider == "azure" {
+        "azurellm"
+    } else {
+        provider
+    };
+    provider_var_name(provider, "API_KEY")
+}
+
+fn is_provider_var(name: &str, provider: &str) -> bool {
+    name.strip_prefix(&provider_var_name(provider, ""))
+        .is_some_and(|setting| !setting.is_empty())
+}
+
+/// `value` as shown by [`EnvConfig::doctor`] and `Debug`: API keys are redacted
+fn display_value(name: &str, value: &str) -> String {
+    if name.ends_with("_API_KEY") {
+        REDACTED.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn invalid(setting: &str, value: &str, expected: &str) -> GraphBitError {
+    GraphBitError::config(format!(
+        "{} must be {expected}, got '{value}'",
+        var_name(setting)
+    ))
+}
@@ -6,6 +6,8 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::secret_ref::redact;
+
 /// Result type alias for `GraphBit` operations
 pub type GraphBitResult<T> = Result<T, GraphBitError>;
 
@@ -106,6 +108,26 @@ pub enum GraphBitError {
         retry_after_seconds: u64,
     },
 
+    /// Request to a provider that exceeded its request or connection timeout
+    #[error("Request timed out: {provider} - {message}")]
+    Timeout {
+        /// Provider name
+        provider: String,
+        /// Error message
+        message: String,
+    },
+
+    /// Provider refused to produce content for safety reasons
+    #[error("Content blocked: {provider} - {reason}")]
+    ContentBlocked {
+        /// Provider name
+        provider: String,
+        /// Why the provider blocked the content
+        reason: String,
+        /// Harm categories the provider flagged
+        categories: Vec<String>,
+    },
+
     /// Generic internal errors
     #[error("Internal error: {message}")]
     Internal {
@@ -139,22 +161,23 @@ impl GraphBitError {
     /// Create a new configuration error
     pub fn config(message: impl Into<String>) -> Self {
         Self::Configuration {
-            message: message.into(),
+            message: redact(&message.into()),
         }
     }
 
-    /// Create a new LLM provider error
+    /// Create a new LLM provider error; API keys echoed in `message` are
+    /// redacted, see [`crate::secret_ref::redact`]
     pub fn llm_provider(provider: impl Into<String>, message: impl Into<String>) -> Self {
         Self::LlmProvider {
             provider: provider.into(),
-            message: message.into(),
+            message: redact(&message.into()),
         }
     }
 
     /// Create a new generic LLM error
     pub fn llm(message: impl Into<String>) -> Self {
         Self::Llm {
-            message: message.into(),
+            message: redact(&message.into()),
         }
     }
 
@@ -199,7 +222,7 @@ impl GraphBitError {
     pub fn authentication(provider: impl Into<String>, message: impl Into<String>) -> Self {
         Self::Authentication {
             provider: provider.into(),
-            message: message.into(),
+            message: redact(&message.into()),
         }
     }
 
@@ -211,6 +234,71 @@ impl GraphBitError {
         }
     }
 
+    /// Create a new content blocked error
+    pub fn content_blocked(
+        provider: impl Into<String>,
+        reason: impl Into<String>,
+        categories: Vec<String>,
+    ) -> Self {
+        Self::ContentBlocked {
+            provider: provider.into(),
+            reason: reason.into(),
+            categories,
+        }
+    }
+
+    /// Create a new timeout error
+    pub fn timeout(provider: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Timeout {
+            provider: provider.into(),
+            message: redact(&message.into()),
+        }
+    }
+
+    /// Create an error from a request to a provider that failed without a
+    /// response: a timeout error if the request or connection timed out, and a
+    /// provider error otherwise
+    pub fn from_request_error(provider: impl Into<String>, error: &reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::timeout(provider, error.to_string())
+        } else {
+            Self::llm_provider(provider, format!("Request failed: {error}"))
+        }
+    }
+
+    /// Create an error from a non-success HTTP response returned by a provider
+    ///
+    /// `429` becomes a rate limit error (waiting `retry_after_seconds`, or one
+    /// second when the provider did not say), `401`/`403` an authentication
+    /// error, and anything else a provider error that records the status code.
+    pub fn from_http_status(
+        provider: impl Into<String>,
+        status: u16,
+        retry_after_seconds: Option<u64>,
+        body: impl AsRef<str>,
+    ) -> Self {
+        let body = body.as_ref();
+        match status {
+            429 => Self::rate_limit(provider, retry_after_seconds.unwrap_or(1)),
+            401 | 403 => {
+                Self::authentication(provider, format!("API error (HTTP {status}): {body}"))
+            }
+            _ => Self::llm_provider(provider, format!("API error (HTTP {status}): {body}")),
+        }
+    }
+
+    /// HTTP status code of the provider response that caused this error, if known
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            Self::RateLimit { .. } => Some(429),
+            Self::LlmProvider { message, .. } | Self::Authentication { message, .. } => message
+                .split_once("(HTTP ")
+                .and_then(|(_, rest)| rest.split_once(')'))
+                .and_then(|(code, _)| code.parse().ok()),
+            _ => None,
+        }
+    }
+
     /// Create a new concurrency error
     pub fn concurrency(message: impl Into<String>) -> Self {
         Self::Concurrency {
@@ -230,6 +318,7 @@ impl GraphBitError {
         matches!(
             self,
             Self::Network { .. }
+                | Self::Timeout { .. }
                 | Self::RateLimit { .. }
                 | Self::LlmProvider { .. }
                 | Self::Llm { .. }
@@ -243,7 +332,7 @@ impl GraphBitError {
                 retry_after_seconds,
                 ..
             } => Some(*retry_after_seconds),
-            Self::Network { .. } => Some(1),
+            Self::Network { .. } | Self::Timeout { .. } => Some(1),
             Self::LlmProvider { .. } => Some(2),
             Self::Llm { .. } => Some(1),
             _ => None,
@@ -255,7 +344,7 @@ impl GraphBitError {
 impl From<reqwest::Error> for GraphBitError {
     fn from(error: reqwest::Error) -> Self {
         Self::Network {
-            message: error.to_string(),
+            message: redact(&error.to_string()),
         }
     }
 }
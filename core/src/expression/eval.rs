@@ -0,0 +1,592 @@
+//! Tree-walking evaluator
+
+use super::parser::{BinaryOp, Expr, Function, Node, UnaryOp};
+use super::{ExpressionError, ExpressionErrorKind, ExpressionScope};
+use crate::validation::json_equal;
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+
+pub(super) use crate::validation::json_type as type_name;
+
+/// Evaluate `node` against `scope`
+pub(super) fn evaluate(node: &Node, scope: &ExpressionScope) -> Result<Value, ExpressionError> {
+    match &node.expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Name(name) => resolve_name(name, node.position, scope),
+        Expr::Member(target, field) => {
+            let target = evaluate(target, scope)?;
+            member(&target, field, node.position)
+        }
+        Expr::Index(target, index) => {
+            let target = evaluate(target, scope)?;
+            let index = evaluate(index, scope)?;
+            index_value(&target, &index, node.position)
+        }
+        Expr::Unary(op, operand) => {
+            let value = evaluate(operand, scope)?;
+            match op {
+                UnaryOp::Not => Ok(Value::Bool(!expect_bool(&value, "not", node.position)?)),
+                UnaryOp::Negate => match &value {
+                    Value::Number(n) => match n.as_i64().and_then(i64::checked_neg) {
+                        Some(negated) => Ok(Value::from(negated)),
+                        None => float(-n.as_f64().unwrap_or_default(), node.position),
+                    },
+                    other => Err(mismatch(
+                        format!("Cannot negate {}", type_name(other)),
+                        node.position,
+                    )),
+                },
+            }
+        }
+        Expr::Binary(BinaryOp::And, left, right) => {
+            if !expect_bool(&evaluate(left, scope)?, "and", node.position)? {
+                return Ok(Value::Bool(false));
+            }
+            let right = evaluate(right, scope)?;
+            Ok(Value::Bool(expect_bool(&right, "and", node.position)?))
+        }
+        Expr::Binary(BinaryOp::Or, left, right) => {
+            if expect_bool(&evaluate(left, scope)?, "or", node.position)? {
+                return Ok(Value::Bool(true));
+            }
+            let right = evaluate(right, scope)?;
+            Ok(Value::Bool(expect_bool(&right, "or", node.position)?))
+        }
+        Expr::Binary(op, left, right) => {
+            let left = evaluate(left, scope)?;
+            let right = evaluate(right, scope)?;
+            binary(*op, &left, &right, node.position)
+        }
+        Expr::Call(function, args) => {
+            let args = args
+                .iter()
+                .map(|arg| evaluate(arg, scope))
+                .collect::<Result<Vec<_>, _>>()?;
+            call(*function, &args, node.position)
+        }
+        Expr::List(items) => items
+            .iter()
+            .map(|item| evaluate(item, scope))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Expr::Object(entries) => {
+            let mut object = Map::new();
+            for (key, value) in entries {
+                object.insert(key.clone(), evaluate(value, scope)?);
+            }
+            Ok(Value::Object(object))
+        }
+    }
+}
+
+fn mismatch(message: impl Into<String>, position: usize) -> ExpressionError {
+    ExpressionError::new(ExpressionErrorKind::TypeMismatch, message, position)
+}
+
+fn invalid(message: impl Into<String>, position: usize) -> ExpressionError {
+    ExpressionError::new(ExpressionErrorKind::InvalidValue, message, position)
+}
+
+/// Wrap a float, rejecting NaN and infinities which JSON cannot represent
+fn float(value: f64, position: usize) -> Result<Value, ExpressionError> {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .ok_or_else(|| invalid(format!("Result {value} is not a finite number"), position))
+}
+
+fn expect_bool(value: &Value, operator: &str, position: usize) -> Result<bool, ExpressionError> {
+    value.as_bool().ok_or_else(|| {
+        mismatch(
+            format!("'{operator}' expects booleans, got {}", type_name(value)),
+            position,
+        )
+    })
+}
+
+/// Agent outputs are often JSON documents held in a string; treat those as
+/// the object or array they contain when reading fields
+fn structured(value: &Value) -> Option<Value> {
+    match value {
+        Value::String(text) => serde_json::from_str::<Value>(text.trim())
+            .ok()
+            .filter(|parsed| parsed.is_object() || parsed.is_array()),
+        _ => None,
+    }
+}
+
+fn resolve_name(
+    name: &str,
+    position: usize,
+    scope: &ExpressionScope,
+) -> Result<Value, ExpressionError> {
+    match name {
+        "input" => return Ok(scope.input.clone()),
+        "nodes" => {
+            return Ok(Value::Object(
+                scope
+                    .nodes
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ));
+        }
+        "vars" => {
+            return Ok(Value::Object(
+                scope
+                    .variables
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ));
+        }
+        _ => {}
+    }
+
+    let field = match &scope.input {
+        Value::Object(object) => object.get(name).cloned(),
+        other => structured(other).and_then(|parsed| parsed.get(name).cloned()),
+    };
+    field
+        .or_else(|| scope.variables.get(name).cloned())
+        .ok_or_else(|| {
+            ExpressionError::new(
+                ExpressionErrorKind::MissingVariable,
+                format!("Unknown name '{name}': not a field of the input or a workflow variable"),
+                position,
+            )
+        })
+}
+
+fn member(target: &Value, field: &str, position: usize) -> Result<Value, ExpressionError> {
+    match target {
+        Value::Null => Ok(Value::Null),
+        Value::Object(object) => Ok(object.get(field).cloned().unwrap_or(Value::Null)),
+        other => match structured(other) {
+            Some(parsed) => member(&parsed, field, position),
+            None => Err(mismatch(
+                format!("Cannot read field '{field}' of {}", type_name(other)),
+                position,
+            )),
+        },
+    }
+}
+
+fn index_value(target: &Value, index: &Value, position: usize) -> Result<Value, ExpressionError> {
+    match (target, index) {
+        (Value::Null, _) => Ok(Value::Null),
+        (Value::Object(object), Value::String(key)) => {
+            Ok(object.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(items), Value::Number(n)) => {
+            let i = integer_index(n, position)?;
+            Ok(resolve_index(i, items.len()).map_or(Value::Null, |i| items[i].clone()))
+        }
+        (Value::String(text), Value::Number(n)) if structured(target).is_none() => {
+            let i = integer_index(n, position)?;
+            let chars: Vec<char> = text.chars().collect();
+            Ok(resolve_index(i, chars.len())
+                .map_or(Value::Null, |i| Value::String(chars[i].to_string())))
+        }
+        (Value::String(_), _) => match structured(target) {
+            Some(parsed) => index_value(&parsed, index, position),
+            None => Err(mismatch(
+                format!("Cannot index string with {}", type_name(index)),
+                position,
+            )),
+        },
+        (target, index) => Err(mismatch(
+            format!(
+                "Cannot index {} with {}",
+                type_name(target),
+                type_name(index)
+            ),
+            position,
+        )),
+    }
+}
+
+fn integer_index(n: &Number, position: usize) -> Result<i64, ExpressionError> {
+    n.as_i64()
+        .ok_or_else(|| invalid(format!("Index {n} is not an integer"), position))
+}
+
+/// Map a possibly negative index onto `0..len`
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let len = i64::try_from(len).ok()?;
+    let index = if index < 0 { len + index } else { index };
+    (0..len)
+        .contains(&index)
+        .then(|| usize::try_from(index).ok())?
+}
+
+fn binary(
+    op: BinaryOp,
+    left: &Value,
+    right: &Value,
+    position: usize,
+) -> Result<Value, ExpressionError> {
+    match op {
+        BinaryOp::Eq => Ok(Value::Bool(json_equal(left, right))),
+        BinaryOp::Ne => Ok(Value::Bool(!json_equal(left, right))),
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            let ordering = compare(left, right, op.symbol(), position)?;
+            Ok(Value::Bool(match op {
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }))
+        }
+        BinaryOp::In => contains(right, left, "in", position).map(Value::Bool),
+        BinaryOp::Add => match (left, right) {
+            (Value::String(l), Value::String(r)) => Ok(Value::String(format!("{l}{r}"))),
+            (Value::Array(l), Value::Array(r)) => {
+                Ok(Value::Array(l.iter().chain(r).cloned().collect()))
+            }
+            _ => arithmetic(op, left, right, position),
+        },
+        _ => arithmetic(op, left, right, position),
+    }
+}
+
+fn compare(
+    left: &Value,
+    right: &Value,
+    operator: &str,
+    position: usize,
+) -> Result<Ordering, ExpressionError> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => match (l.as_i64(), r.as_i64()) {
+            (Some(l), Some(r)) => Ok(l.cmp(&r)),
+            _ => l
+                .as_f64()
+                .partial_cmp(&r.as_f64())
+                .ok_or_else(|| invalid("Numbers cannot be compared", position)),
+        },
+        (Value::String(l), Value::String(r)) => Ok(l.cmp(r)),
+        _ => Err(mismatch(
+            format!(
+                "Cannot compare {} {operator} {}",
+                type_name(left),
+                type_name(right)
+            ),
+            position,
+        )),
+    }
+}
+
+fn arithmetic(
+    op: BinaryOp,
+    left: &Value,
+    right: &Value,
+    position: usize,
+) -> Result<Value, ExpressionError> {
+    let (Value::Number(l), Value::Number(r)) = (left, right) else {
+        return Err(mismatch(
+            format!(
+                "Cannot apply '{}' to {} and {}",
+                op.symbol(),
+                type_name(left),
+                type_name(right)
+            ),
+            position,
+        ));
+    };
+
+    if matches!(op, BinaryOp::Div | BinaryOp::Rem) && r.as_f64() == Some(0.0) {
+        return Err(ExpressionError::new(
+            ExpressionErrorKind::DivisionByZero,
+            format!("Cannot apply '{}' with a zero divisor", op.symbol()),
+            position,
+        ));
+    }
+
+    if let (Some(l), Some(r)) = (l.as_i64(), r.as_i64()) {
+        let exact = match op {
+            BinaryOp::Add => l.checked_add(r),
+            BinaryOp::Sub => l.checked_sub(r),
+            BinaryOp::Mul => l.checked_mul(r),
+            BinaryOp::Div => l
+                .checked_rem(r)
+                .filter(|rem| *rem == 0)
+                .and_then(|_| l.checked_div(r)),
+            BinaryOp::Rem => l.checked_rem(r),
+            _ => None,
+        };
+        if let Some(result) = exact {
+            return Ok(Value::from(result));
+        }
+    }
+
+    let (l, r) = (
+        l.as_f64().unwrap_or_default(),
+        r.as_f64().unwrap_or_default(),
+    );
+    let result = match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div => l / r,
+        _ => l % r,
+    };
+    float(result, position)
+}
+
+/// Whether `haystack` holds `needle` as a substring, array item or object key
+fn contains(
+    haystack: &Value,
+    needle: &Value,
+    operator: &str,
+    position: usize,
+) -> Result<bool, ExpressionError> {
+    match (haystack, needle) {
+        (Value::String(text), Value::String(part)) => Ok(text.contains(part.as_str())),
+        (Value::Array(items), needle) => Ok(items.iter().any(|item| json_equal(item, needle))),
+        (Value::Object(object), Value::String(key)) => Ok(object.contains_key(key)),
+        _ => Err(mismatch(
+            format!(
+                "'{operator}' cannot look for {} in {}",
+                type_name(needle),
+                type_name(haystack)
+            ),
+            position,
+        )),
+    }
+}
+
+fn string_arg(function: Function, value: &Value, position: usize) -> Result<&str, ExpressionError> {
+    value.as_str().ok_or_else(|| {
+        mismatch(
+            format!(
+                "'{}' expects a string, got {}",
+                function.name(),
+                type_name(value)
+            ),
+            position,
+        )
+    })
+}
+
+fn number_arg(
+    function: Function,
+    value: &Value,
+    position: usize,
+) -> Result<Number, ExpressionError> {
+    match value {
+        Value::Number(n) => Ok(n.clone()),
+        other => Err(mismatch(
+            format!(
+                "'{}' expects a number, got {}",
+                function.name(),
+                type_name(other)
+            ),
+            position,
+        )),
+    }
+}
+
+/// Parse text as a number the way the lexer reads number literals
+fn parse_number(text: &str, position: usize) -> Result<Number, ExpressionError> {
+    let trimmed = text.trim();
+    trimmed
+        .parse::<i64>()
+        .ok()
+        .map(Number::from)
+        .or_else(|| trimmed.parse::<f64>().ok().and_then(Number::from_f64))
+        .ok_or_else(|| invalid(format!("'{text}' is not a number"), position))
+}
+
+/// Apply a rounding function, keeping integers as they are
+fn rounded(
+    function: Function,
+    value: &Value,
+    position: usize,
+    round: fn(f64) -> f64,
+) -> Result<Value, ExpressionError> {
+    let n = number_arg(function, value, position)?;
+    if n.is_i64() || n.is_u64() {
+        return Ok(Value::Number(n));
+    }
+    let result = round(n.as_f64().unwrap_or_default());
+    integral(result).map_or_else(|| float(result, position), |i| Ok(Value::from(i)))
+}
+
+/// The `i64` equal to `value`, if there is one
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn integral(value: f64) -> Option<i64> {
+    (value.fract() == 0.0 && value.abs() < i64::MAX as f64).then_some(value as i64)
+}
+
+fn call(function: Function, args: &[Value], position: usize) -> Result<Value, ExpressionError> {
+    match function {
+        Function::Len => match &args[0] {
+            Value::String(s) => Ok(Value::from(s.chars().count())),
+            Value::Array(items) => Ok(Value::from(items.len())),
+            Value::Object(object) => Ok(Value::from(object.len())),
+            other => Err(mismatch(
+                format!(
+                    "'len' expects a string, array or object, got {}",
+                    type_name(other)
+                ),
+                position,
+            )),
+        },
+        Function::Lower => Ok(Value::String(
+            string_arg(function, &args[0], position)?.to_lowercase(),
+        )),
+        Function::Upper => Ok(Value::String(
+            string_arg(function, &args[0], position)?.to_uppercase(),
+        )),
+        Function::Trim => Ok(Value::String(
+            string_arg(function, &args[0], position)?.trim().to_string(),
+        )),
+        Function::Contains => contains(&args[0], &args[1], "contains", position).map(Value::Bool),
+        Function::StartsWith => {
+            let text = string_arg(function, &args[0], position)?;
+            let prefix = string_arg(function, &args[1], position)?;
+            Ok(Value::Bool(text.starts_with(prefix)))
+        }
+        Function::EndsWith => {
+            let text = string_arg(function, &args[0], position)?;
+            let suffix = string_arg(function, &args[1], position)?;
+            Ok(Value::Bool(text.ends_with(suffix)))
+        }
+        Function::Replace => {
+            let text = string_arg(function, &args[0], position)?;
+            let from = string_arg(function, &args[1], position)?;
+            let to = string_arg(function, &args[2], position)?;
+            if from.is_empty() {
+                return Err(invalid(
+                    "'replace' cannot replace an empty string",
+                    position,
+                ));
+            }
+            Ok(Value::String(text.replace(from, to)))
+        }
+        Function::Split => {
+            let text = string_arg(function, &args[0], position)?;
+            let separator = string_arg(function, &args[1], position)?;
+            if separator.is_empty() {
+                return Err(invalid("'split' needs a non-empty separator", position));
+            }
+            Ok(Value::Array(
+                text.split(separator)
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            ))
+        }
+        Function::Join => {
+            let Value::Array(items) = &args[0] else {
+                return Err(mismatch(
+                    format!("'join' expects an array, got {}", type_name(&args[0])),
+                    position,
+                ));
+            };
+            let separator = string_arg(function, &args[1], position)?;
+            let parts = items
+                .iter()
+                .map(|item| string_arg(function, item, position))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::String(parts.join(separator)))
+        }
+        Function::Keys | Function::Values => {
+            let Value::Object(object) = &args[0] else {
+                return Err(mismatch(
+                    format!(
+                        "'{}' expects an object, got {}",
+                        function.name(),
+                        type_name(&args[0])
+                    ),
+                    position,
+                ));
+            };
+            Ok(Value::Array(if function == Function::Keys {
+                object.keys().cloned().map(Value::String).collect()
+            } else {
+                object.values().cloned().collect()
+            }))
+        }
+        Function::Type => Ok(Value::String(type_name(&args[0]).to_string())),
+        Function::Str => Ok(Value::String(match &args[0] {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })),
+        Function::Num => match &args[0] {
+            Value::Number(n) => Ok(Value::Number(n.clone())),
+            Value::String(s) => parse_number(s, position).map(Value::Number),
+            other => Err(mismatch(
+                format!("'num' expects a number or string, got {}", type_name(other)),
+                position,
+            )),
+        },
+        Function::Int => {
+            let n = match &args[0] {
+                Value::Number(n) => n.clone(),
+                Value::String(s) => parse_number(s, position)?,
+                other => {
+                    return Err(mismatch(
+                        format!("'int' expects a number or string, got {}", type_name(other)),
+                        position,
+                    ));
+                }
+            };
+            rounded(function, &Value::Number(n), position, f64::trunc)
+        }
+        Function::Abs => {
+            let n = number_arg(function, &args[0], position)?;
+            match n.as_i64().and_then(i64::checked_abs) {
+                Some(i) => Ok(Value::from(i)),
+                None => float(n.as_f64().unwrap_or_default().abs(), position),
+            }
+        }
+        Function::Round => rounded(function, &args[0], position, f64::round),
+        Function::Floor => rounded(function, &args[0], position, f64::floor),
+        Function::Ceil => rounded(function, &args[0], position, f64::ceil),
+        Function::Min | Function::Max => {
+            let values = match args {
+                [Value::Array(items)] => items.as_slice(),
+                _ => args,
+            };
+            let Some((first, rest)) = values.split_first() else {
+                return Err(invalid(
+                    format!("'{}' needs at least one value", function.name()),
+                    position,
+                ));
+            };
+            let mut best = first;
+            for value in rest {
+                let ordering = compare(value, best, function.name(), position)?;
+                let better = if function == Function::Min {
+                    ordering == Ordering::Less
+                } else {
+                    ordering == Ordering::Greater
+                };
+                if better {
+                    best = value;
+                }
+            }
+            if rest.is_empty() && !matches!(best, Value::Number(_) | Value::String(_)) {
+                return Err(mismatch(
+                    format!(
+                        "'{}' expects numbers or strings, got {}",
+                        function.name(),
+                        type_name(best)
+                    ),
+                    position,
+                ));
+            }
+            Ok(best.clone())
+        }
+        Function::Default => Ok(if args[0].is_null() {
+            args[1].clone()
+        } else {
+            args[0].clone()
+        }),
+        Function::JsonParse => {
+            let text = string_arg(function, &args[0], position)?;
+            serde_json::from_str(text)
+                .map_err(|e| invalid(format!("'json_parse' got invalid JSON: {e}"), position))
+        }
+        Function::JsonStringify => Ok(Value::String(args[0].to_string())),
+    }
+}
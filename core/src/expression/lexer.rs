@@ -0,0 +1,299 @@
+//! Tokenizer for the expression language
+
+use super::{ExpressionError, ExpressionErrorKind};
+use serde_json::Number;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum TokenKind {
+    Number(Number),
+    Str(String),
+    Name(String),
+    True,
+    False,
+    Null,
+    And,
+    Or,
+    Not,
+    In,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Comma,
+    Dot,
+    Colon,
+    End,
+}
+
+impl TokenKind {
+    /// How the token is shown in syntax errors
+    pub(super) fn describe(&self) -> String {
+        match self {
+            Self::Number(n) => format!("number {n}"),
+            Self::Str(s) => format!("string {s:?}"),
+            Self::Name(name) => format!("name '{name}'"),
+            Self::End => "end of expression".to_string(),
+            other => format!("'{}'", other.symbol()),
+        }
+    }
+
+    const fn symbol(&self) -> &'static str {
+        match self {
+            Self::True => "true",
+            Self::False => "false",
+            Self::Null => "null",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Not => "not",
+            Self::In => "in",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::Percent => "%",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::LBracket => "[",
+            Self::RBracket => "]",
+            Self::LBrace => "{",
+            Self::RBrace => "}",
+            Self::Comma => ",",
+            Self::Dot => ".",
+            Self::Colon => ":",
+            Self::Number(_) | Self::Str(_) | Self::Name(_) | Self::End => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Token {
+    pub(super) kind: TokenKind,
+    /// Character offset of the token's first character
+    pub(super) position: usize,
+}
+
+/// Split `source` into tokens, ending with [`TokenKind::End`]
+pub(super) fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let next = chars.get(pos + 1).copied();
+        let (kind, len) = match (c, next) {
+            ('=', Some('=')) => (TokenKind::Eq, 2),
+            ('!', Some('=')) => (TokenKind::Ne, 2),
+            ('<', Some('=')) => (TokenKind::Le, 2),
+            ('>', Some('=')) => (TokenKind::Ge, 2),
+            ('&', Some('&')) => (TokenKind::And, 2),
+            ('|', Some('|')) => (TokenKind::Or, 2),
+            ('!', _) => (TokenKind::Not, 1),
+            ('<', _) => (TokenKind::Lt, 1),
+            ('>', _) => (TokenKind::Gt, 1),
+            ('+', _) => (TokenKind::Plus, 1),
+            ('-', _) => (TokenKind::Minus, 1),
+            ('*', _) => (TokenKind::Star, 1),
+            ('/', _) => (TokenKind::Slash, 1),
+            ('%', _) => (TokenKind::Percent, 1),
+            ('(', _) => (TokenKind::LParen, 1),
+            (')', _) => (TokenKind::RParen, 1),
+            ('[', _) => (TokenKind::LBracket, 1),
+            (']', _) => (TokenKind::RBracket, 1),
+            ('{', _) => (TokenKind::LBrace, 1),
+            ('}', _) => (TokenKind::RBrace, 1),
+            (',', _) => (TokenKind::Comma, 1),
+            (':', _) => (TokenKind::Colon, 1),
+            ('.', Some(d)) if d.is_ascii_digit() => {
+                let (number, end) = read_number(&chars, pos)?;
+                tokens.push(Token {
+                    kind: TokenKind::Number(number),
+                    position: start,
+                });
+                pos = end;
+                continue;
+            }
+            ('.', _) => (TokenKind::Dot, 1),
+            ('"' | '\'', _) => {
+                let (text, end) = read_string(&chars, pos)?;
+                tokens.push(Token {
+                    kind: TokenKind::Str(text),
+                    position: start,
+                });
+                pos = end;
+                continue;
+            }
+            (d, _) if d.is_ascii_digit() => {
+                let (number, end) = read_number(&chars, pos)?;
+                tokens.push(Token {
+                    kind: TokenKind::Number(number),
+                    position: start,
+                });
+                pos = end;
+                continue;
+            }
+            (a, _) if a.is_alphabetic() || a == '_' => {
+                let mut end = pos;
+                while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                let word: String = chars[pos..end].iter().collect();
+                let kind = match word.as_str() {
+                    "true" => TokenKind::True,
+                    "false" => TokenKind::False,
+                    "null" => TokenKind::Null,
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    "in" => TokenKind::In,
+                    _ => TokenKind::Name(word),
+                };
+                tokens.push(Token {
+                    kind,
+                    position: start,
+                });
+                pos = end;
+                continue;
+            }
+            ('=', _) => {
+                return Err(ExpressionError::new(
+                    ExpressionErrorKind::Syntax,
+                    "Unexpected '='; use '==' to compare",
+                    start,
+                ));
+            }
+            (other, _) => {
+                return Err(ExpressionError::new(
+                    ExpressionErrorKind::Syntax,
+                    format!("Unexpected character '{other}'"),
+                    start,
+                ));
+            }
+        };
+        tokens.push(Token {
+            kind,
+            position: start,
+        });
+        pos += len;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        position: chars.len(),
+    });
+    Ok(tokens)
+}
+
+/// Read an integer or decimal number with an optional exponent
+fn read_number(chars: &[char], start: usize) -> Result<(Number, usize), ExpressionError> {
+    let mut end = start;
+    let mut is_float = false;
+    while end < chars.len() && chars[end].is_ascii_digit() {
+        end += 1;
+    }
+    if end < chars.len()
+        && chars[end] == '.'
+        && chars.get(end + 1).is_some_and(char::is_ascii_digit)
+    {
+        is_float = true;
+        end += 1;
+        while end < chars.len() && chars[end].is_ascii_digit() {
+            end += 1;
+        }
+    }
+    if end < chars.len() && matches!(chars[end], 'e' | 'E') {
+        let mut exponent_end = end + 1;
+        if exponent_end < chars.len() && matches!(chars[exponent_end], '+' | '-') {
+            exponent_end += 1;
+        }
+        if chars.get(exponent_end).is_some_and(char::is_ascii_digit) {
+            is_float = true;
+            end = exponent_end;
+            while end < chars.len() && chars[end].is_ascii_digit() {
+                end += 1;
+            }
+        }
+    }
+
+    let text: String = chars[start..end].iter().collect();
+    let number = if is_float {
+        text.parse::<f64>().ok().and_then(Number::from_f64)
+    } else {
+        text.parse::<i64>()
+            .ok()
+            .map(Number::from)
+            .or_else(|| text.parse::<f64>().ok().and_then(Number::from_f64))
+    };
+    number.map(|n| (n, end)).ok_or_else(|| {
+        ExpressionError::new(
+            ExpressionErrorKind::Syntax,
+            format!("Invalid number '{text}'"),
+            start,
+        )
+    })
+}
+
+/// Read a quoted string, returning its unescaped text and the offset past the
+/// closing quote
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize), ExpressionError> {
+    let quote = chars[start];
+    let mut text = String::new();
+    let mut pos = start + 1;
+    while pos < chars.len() {
+        match chars[pos] {
+            c if c == quote => return Ok((text, pos + 1)),
+            '\\' => {
+                let escaped = match chars.get(pos + 1) {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some(c @ ('\\' | '\'' | '"')) => *c,
+                    Some(other) => {
+                        return Err(ExpressionError::new(
+                            ExpressionErrorKind::Syntax,
+                            format!("Unknown escape sequence '\\{other}'"),
+                            pos,
+                        ));
+                    }
+                    None => break,
+                };
+                text.push(escaped);
+                pos += 2;
+            }
+            c => {
+                text.push(c);
+                pos += 1;
+            }
+        }
+    }
+    Err(ExpressionError::new(
+        ExpressionErrorKind::Syntax,
+        "Unterminated string",
+        start,
+    ))
+}
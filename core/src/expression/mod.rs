@@ -0,0 +1,214 @@
+//! Expression language for condition nodes, conditional edges and edge
+//! transforms
+//!
+//! Expressions are small, side-effect free formulas evaluated against the
+//! data flowing through a workflow. They are parsed when a workflow is
+//! validated, so syntax errors and unknown functions surface before anything
+//! runs, and evaluated when the node or edge is reached.
+//!
+//! # Grammar
+//!
+//! ```text
+//! expression  := or
+//! or          := and (("or" | "||") and)*
+//! and         := not (("and" | "&&") not)*
+//! not         := ("not" | "!") not | comparison
+//! comparison  := additive (("==" | "!=" | "<" | "<=" | ">" | ">=" | "in") additive)?
+//! additive    := multiplicative (("+" | "-") multiplicative)*
+//! multiplicative := unary (("*" | "/" | "%") unary)*
+//! unary       := "-" unary | postfix
+//! postfix     := primary ("." NAME | "[" expression "]")*
+//! primary     := NUMBER | STRING | "true" | "false" | "null"
+//!              | NAME | NAME "(" (expression ("," expression)*)? ")"
+//!              | "[" (expression ("," expression)*)? "]"
+//!              | "{" ((STRING | NAME) ":" expression ("," ...)*)? "}"
+//!              | "(" expression ")"
+//! ```
+//!
+//! Strings use single or double quotes and support `\n`, `\t`, `\r`, `\\`,
+//! `\'` and `\"` escapes.
+//!
+//! # Names
+//!
+//! - `input`: the value being evaluated, e.g. the parent output of a
+//!   condition node or the value flowing through an edge
+//! - `nodes`: outputs of completed nodes, keyed by node name (`nodes.fetch.body`)
+//! - `vars`: workflow variables
+//!
+//! Any other name reads a field of `input` (also when `input` is a string
+//! holding a JSON object, as agent outputs often are), then a workflow
+//! variable. A name that resolves to neither is a
+//! [`ExpressionErrorKind::MissingVariable`] error, while a missing field
+//! (`input.absent`) or an out-of-range index evaluates to `null`.
+//!
+//! # Types
+//!
+//! Operators do not convert between types: `1 + "1"` and `"a" < 1` are
+//! [`ExpressionErrorKind::TypeMismatch`] errors, and `and`, `or` and `not`
+//! require booleans. `+` adds numbers and concatenates strings or arrays.
+//! `==` compares any two values, treating `1` and `1.0` as equal. `in` tests
+//! substrings, array membership and object keys.
+//!
+//! # Functions
+//!
+//! Only these functions exist; calling anything else fails at parse time:
+//! `len`, `lower`, `upper`, `trim`, `contains`, `starts_with`, `ends_with`,
+//! `replace`, `split`, `join`, `keys`, `values`, `type`, `str`, `num`, `int`,
+//! `abs`, `round`, `floor`, `ceil`, `min`, `max`, `default`, `json_parse` and
+//! `json_stringify`.
+
+mod eval;
+mod lexer;
+mod parser;
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+pub use parser::Function;
+
+/// Category of an [`ExpressionError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpressionErrorKind {
+    /// The text is not a valid expression
+    Syntax,
+    /// A call names a function outside the whitelist
+    UnknownFunction,
+    /// A function was called with the wrong number of arguments
+    ArgumentCount,
+    /// An operator or function received a value of the wrong type
+    TypeMismatch,
+    /// A name resolves to no input field or workflow variable
+    MissingVariable,
+    /// Division or remainder by zero
+    DivisionByZero,
+    /// A value of the right type could not be used, e.g. `num("abc")`
+    InvalidValue,
+}
+
+/// Error raised while parsing or evaluating an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionError {
+    /// Error category
+    pub kind: ExpressionErrorKind,
+    /// Human-readable description
+    pub message: String,
+    /// Character offset in the expression source where the error occurred
+    pub position: usize,
+}
+
+impl ExpressionError {
+    pub(crate) fn new(
+        kind: ExpressionErrorKind,
+        message: impl Into<String>,
+        position: usize,
+    ) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Values an expression can read by name
+#[derive(Debug, Clone, Default)]
+pub struct ExpressionScope {
+    /// Value bound to `input`
+    pub input: Value,
+    /// Node outputs bound to `nodes`
+    pub nodes: HashMap<String, Value>,
+    /// Workflow variables bound to `vars`
+    pub variables: HashMap<String, Value>,
+}
+
+impl ExpressionScope {
+    /// Create a scope whose `input` is `input`
+    pub fn new(input: Value) -> Self {
+        Self {
+            input,
+            ..Self::default()
+        }
+    }
+
+    /// Bind node outputs to `nodes`
+    #[must_use]
+    pub fn with_nodes(mut self, nodes: HashMap<String, Value>) -> Self {
+        self.nodes = nodes;
+        self
+    }
+
+    /// Bind workflow variables to `vars`
+    #[must_use]
+    pub fn with_variables(mut self, variables: HashMap<String, Value>) -> Self {
+        self.variables = variables;
+        self
+    }
+}
+
+/// A parsed expression, ready to be evaluated any number of times
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    root: parser::Node,
+}
+
+impl Expression {
+    /// Parse `source`, checking syntax, function names and argument counts
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let tokens = lexer::tokenize(source)?;
+        let root = parser::parse(&tokens)?;
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// The text the expression was parsed from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluate against `scope`
+    pub fn evaluate(&self, scope: &ExpressionScope) -> Result<Value, ExpressionError> {
+        eval::evaluate(&self.root, scope)
+    }
+
+    /// Evaluate against `scope` and require a boolean result
+    pub fn evaluate_bool(&self, scope: &ExpressionScope) -> Result<bool, ExpressionError> {
+        match self.evaluate(scope)? {
+            Value::Bool(result) => Ok(result),
+            other => Err(ExpressionError::new(
+                ExpressionErrorKind::TypeMismatch,
+                format!(
+                    "Expected the expression to produce a boolean, got {}",
+                    eval::type_name(&other)
+                ),
+                0,
+            )),
+        }
+    }
+}
+
+/// Expression for a transform node's `transformation`, mapping the built-in
+/// transformation names that predate expressions onto their equivalents
+pub(crate) fn transformation_source(transformation: &str) -> &str {
+    let transformation = transformation.trim();
+    match transformation.to_lowercase().as_str() {
+        "identity" => "input",
+        "uppercase" => "upper(input)",
+        "lowercase" => "lower(input)",
+        "trim" => "trim(input)",
+        "json_parse" => "json_parse(input)",
+        "json_stringify" => "json_stringify(input)",
+        _ => transformation,
+    }
+}
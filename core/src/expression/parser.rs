@@ -0,0 +1,554 @@
+//! Recursive-descent parser producing the expression syntax tree
+
+use super::lexer::{Token, TokenKind};
+use super::{ExpressionError, ExpressionErrorKind};
+use serde_json::Value;
+
+/// Deepest nesting of parentheses, brackets and operators accepted, so
+/// hostile input cannot overflow the stack
+const MAX_DEPTH: usize = 64;
+
+/// A function an expression may call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    /// `len(value)`: characters of a string, items of an array or keys of an object
+    Len,
+    /// `lower(text)`
+    Lower,
+    /// `upper(text)`
+    Upper,
+    /// `trim(text)`
+    Trim,
+    /// `contains(haystack, needle)`: substring, array item or object key
+    Contains,
+    /// `starts_with(text, prefix)`
+    StartsWith,
+    /// `ends_with(text, suffix)`
+    EndsWith,
+    /// `replace(text, from, to)`: replaces every occurrence
+    Replace,
+    /// `split(text, separator)`
+    Split,
+    /// `join(array, separator)`: array of strings
+    Join,
+    /// `keys(object)`
+    Keys,
+    /// `values(object)`
+    Values,
+    /// `type(value)`: `null`, `boolean`, `number`, `string`, `array` or `object`
+    Type,
+    /// `str(value)`: strings unchanged, anything else as JSON text
+    Str,
+    /// `num(value)`: numbers unchanged, strings parsed
+    Num,
+    /// `int(value)`: like `num`, truncated towards zero
+    Int,
+    /// `abs(number)`
+    Abs,
+    /// `round(number)`
+    Round,
+    /// `floor(number)`
+    Floor,
+    /// `ceil(number)`
+    Ceil,
+    /// `min(a, b, ...)` or `min(array)`
+    Min,
+    /// `max(a, b, ...)` or `max(array)`
+    Max,
+    /// `default(value, fallback)`: `fallback` when `value` is null
+    Default,
+    /// `json_parse(text)`
+    JsonParse,
+    /// `json_stringify(value)`
+    JsonStringify,
+}
+
+impl Function {
+    /// Every function, in the order they are listed in errors
+    pub const ALL: [Self; 25] = [
+        Self::Len,
+        Self::Lower,
+        Self::Upper,
+        Self::Trim,
+        Self::Contains,
+        Self::StartsWith,
+        Self::EndsWith,
+        Self::Replace,
+        Self::Split,
+        Self::Join,
+        Self::Keys,
+        Self::Values,
+        Self::Type,
+        Self::Str,
+        Self::Num,
+        Self::Int,
+        Self::Abs,
+        Self::Round,
+        Self::Floor,
+        Self::Ceil,
+        Self::Min,
+        Self::Max,
+        Self::Default,
+        Self::JsonParse,
+        Self::JsonStringify,
+    ];
+
+    /// Name used to call the function
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Len => "len",
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Trim => "trim",
+            Self::Contains => "contains",
+            Self::StartsWith => "starts_with",
+            Self::EndsWith => "ends_with",
+            Self::Replace => "replace",
+            Self::Split => "split",
+            Self::Join => "join",
+            Self::Keys => "keys",
+            Self::Values => "values",
+            Self::Type => "type",
+            Self::Str => "str",
+            Self::Num => "num",
+            Self::Int => "int",
+            Self::Abs => "abs",
+            Self::Round => "round",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Default => "default",
+            Self::JsonParse => "json_parse",
+            Self::JsonStringify => "json_stringify",
+        }
+    }
+
+    /// Look a function up by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Smallest and largest accepted argument counts
+    const fn arity(self) -> (usize, usize) {
+        match self {
+            Self::Contains
+            | Self::StartsWith
+            | Self::EndsWith
+            | Self::Split
+            | Self::Join
+            | Self::Default => (2, 2),
+            Self::Replace => (3, 3),
+            Self::Min | Self::Max => (1, usize::MAX),
+            _ => (1, 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+    Not,
+    Negate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    And,
+    Or,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    pub(super) const fn symbol(self) -> &'static str {
+        match self {
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::In => "in",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Expr {
+    Literal(Value),
+    Name(String),
+    Member(Box<Node>, String),
+    Index(Box<Node>, Box<Node>),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+    List(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+/// A syntax tree node and the character offset it starts at
+#[derive(Debug, Clone)]
+pub(super) struct Node {
+    pub(super) expr: Expr,
+    pub(super) position: usize,
+}
+
+impl Node {
+    const fn new(expr: Expr, position: usize) -> Self {
+        Self { expr, position }
+    }
+}
+
+/// Parse a token stream produced by the lexer
+pub(super) fn parse(tokens: &[Token]) -> Result<Node, ExpressionError> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    if parser.peek().kind == TokenKind::End {
+        return Err(ExpressionError::new(
+            ExpressionErrorKind::Syntax,
+            "Expression is empty",
+            0,
+        ));
+    }
+    let node = parser.expression()?;
+    let trailing = parser.peek();
+    if trailing.kind != TokenKind::End {
+        return Err(ExpressionError::new(
+            ExpressionErrorKind::Syntax,
+            format!(
+                "Unexpected {} after the expression",
+                trailing.kind.describe()
+            ),
+            trailing.position,
+        ));
+    }
+    Ok(node)
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        // The lexer always ends the stream with `End`
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> &Token {
+        let token = &self.tokens[self.pos.min(self.tokens.len() - 1)];
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if &self.peek().kind == kind {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind, what: &str) -> Result<(), ExpressionError> {
+        if self.eat(kind) {
+            Ok(())
+        } else {
+            let found = self.peek();
+            Err(ExpressionError::new(
+                ExpressionErrorKind::Syntax,
+                format!("Expected {what}, found {}", found.kind.describe()),
+                found.position,
+            ))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ExpressionError::new(
+                ExpressionErrorKind::Syntax,
+                format!("Expression nests more than {MAX_DEPTH} levels deep"),
+                self.peek().position,
+            ));
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<Node, ExpressionError> {
+        self.enter()?;
+        let node = self.or();
+        self.depth -= 1;
+        node
+    }
+
+    fn or(&mut self) -> Result<Node, ExpressionError> {
+        let mut left = self.and()?;
+        while self.peek().kind == TokenKind::Or {
+            let position = self.advance().position;
+            let right = self.and()?;
+            left = Node::new(
+                Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right)),
+                position,
+            );
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Node, ExpressionError> {
+        let mut left = self.not()?;
+        while self.peek().kind == TokenKind::And {
+            let position = self.advance().position;
+            let right = self.not()?;
+            left = Node::new(
+                Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right)),
+                position,
+            );
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Node, ExpressionError> {
+        if self.peek().kind == TokenKind::Not {
+            let position = self.advance().position;
+            self.enter()?;
+            let operand = self.not();
+            self.depth -= 1;
+            return Ok(Node::new(
+                Expr::Unary(UnaryOp::Not, Box::new(operand?)),
+                position,
+            ));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, ExpressionError> {
+        let left = self.additive()?;
+        let op = match self.peek().kind {
+            TokenKind::Eq => BinaryOp::Eq,
+            TokenKind::Ne => BinaryOp::Ne,
+            TokenKind::Lt => BinaryOp::Lt,
+            TokenKind::Le => BinaryOp::Le,
+            TokenKind::Gt => BinaryOp::Gt,
+            TokenKind::Ge => BinaryOp::Ge,
+            TokenKind::In => BinaryOp::In,
+            _ => return Ok(left),
+        };
+        let position = self.advance().position;
+        let right = self.additive()?;
+        Ok(Node::new(
+            Expr::Binary(op, Box::new(left), Box::new(right)),
+            position,
+        ))
+    }
+
+    fn additive(&mut self) -> Result<Node, ExpressionError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Plus => BinaryOp::Add,
+                TokenKind::Minus => BinaryOp::Sub,
+                _ => return Ok(left),
+            };
+            let position = self.advance().position;
+            let right = self.multiplicative()?;
+            left = Node::new(Expr::Binary(op, Box::new(left), Box::new(right)), position);
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Node, ExpressionError> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Star => BinaryOp::Mul,
+                TokenKind::Slash => BinaryOp::Div,
+                TokenKind::Percent => BinaryOp::Rem,
+                _ => return Ok(left),
+            };
+            let position = self.advance().position;
+            let right = self.unary()?;
+            left = Node::new(Expr::Binary(op, Box::new(left), Box::new(right)), position);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.peek().kind == TokenKind::Minus {
+            let position = self.advance().position;
+            self.enter()?;
+            let operand = self.unary();
+            self.depth -= 1;
+            return Ok(Node::new(
+                Expr::Unary(UnaryOp::Negate, Box::new(operand?)),
+                position,
+            ));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.primary()?;
+        loop {
+            match self.peek().kind {
+                TokenKind::Dot => {
+                    self.advance();
+                    let token = self.advance().clone();
+                    let field = match token.kind {
+                        TokenKind::Name(name) => name,
+                        other => {
+                            return Err(ExpressionError::new(
+                                ExpressionErrorKind::Syntax,
+                                format!(
+                                    "Expected a field name after '.', found {}",
+                                    other.describe()
+                                ),
+                                token.position,
+                            ));
+                        }
+                    };
+                    let position = node.position;
+                    node = Node::new(Expr::Member(Box::new(node), field), position);
+                }
+                TokenKind::LBracket => {
+                    self.advance();
+                    let index = self.expression()?;
+                    self.expect(&TokenKind::RBracket, "']'")?;
+                    let position = node.position;
+                    node = Node::new(Expr::Index(Box::new(node), Box::new(index)), position);
+                }
+                _ => return Ok(node),
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, ExpressionError> {
+        let token = self.advance().clone();
+        let position = token.position;
+        let expr = match token.kind {
+            TokenKind::Number(n) => Expr::Literal(Value::Number(n)),
+            TokenKind::Str(s) => Expr::Literal(Value::String(s)),
+            TokenKind::True => Expr::Literal(Value::Bool(true)),
+            TokenKind::False => Expr::Literal(Value::Bool(false)),
+            TokenKind::Null => Expr::Literal(Value::Null),
+            TokenKind::Name(name) if self.peek().kind == TokenKind::LParen => {
+                self.advance();
+                return self.call(&name, position);
+            }
+            TokenKind::Name(name) => Expr::Name(name),
+            TokenKind::LParen => {
+                let inner = self.expression()?;
+                self.expect(&TokenKind::RParen, "')'")?;
+                return Ok(inner);
+            }
+            TokenKind::LBracket => Expr::List(self.items(&TokenKind::RBracket, "']'")?),
+            TokenKind::LBrace => Expr::Object(self.entries()?),
+            other => {
+                return Err(ExpressionError::new(
+                    ExpressionErrorKind::Syntax,
+                    format!("Expected a value, found {}", other.describe()),
+                    position,
+                ));
+            }
+        };
+        Ok(Node::new(expr, position))
+    }
+
+    fn call(&mut self, name: &str, position: usize) -> Result<Node, ExpressionError> {
+        let function = Function::from_name(name).ok_or_else(|| {
+            let known: Vec<&str> = Function::ALL.iter().map(|f| f.name()).collect();
+            ExpressionError::new(
+                ExpressionErrorKind::UnknownFunction,
+                format!(
+                    "Unknown function '{name}' (available: {})",
+                    known.join(", ")
+                ),
+                position,
+            )
+        })?;
+        let args = self.items(&TokenKind::RParen, "')'")?;
+        let (min, max) = function.arity();
+        if args.len() < min || args.len() > max {
+            let expected = if min == max {
+                min.to_string()
+            } else if max == usize::MAX {
+                format!("at least {min}")
+            } else {
+                format!("{min} to {max}")
+            };
+            return Err(ExpressionError::new(
+                ExpressionErrorKind::ArgumentCount,
+                format!(
+                    "Function '{name}' takes {expected} argument(s), got {}",
+                    args.len()
+                ),
+                position,
+            ));
+        }
+        Ok(Node::new(Expr::Call(function, args), position))
+    }
+
+    /// Comma-separated expressions up to `close`, which is consumed
+    fn items(&mut self, close: &TokenKind, what: &str) -> Result<Vec<Node>, ExpressionError> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.expression()?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(&TokenKind::Comma, &format!("',' or {what}"))?;
+        }
+    }
+
+    /// `key: value` pairs up to the closing brace, which is consumed
+    fn entries(&mut self) -> Result<Vec<(String, Node)>, ExpressionError> {
+        let mut entries = Vec::new();
+        if self.eat(&TokenKind::RBrace) {
+            return Ok(entries);
+        }
+        loop {
+            let token = self.advance().clone();
+            let key = match token.kind {
+                TokenKind::Str(key) | TokenKind::Name(key) => key,
+                other => {
+                    return Err(ExpressionError::new(
+                        ExpressionErrorKind::Syntax,
+                        format!("Expected an object key, found {}", other.describe()),
+                        token.position,
+                    ));
+                }
+            };
+            self.expect(&TokenKind::Colon, "':'")?;
+            entries.push((key, self.expression()?));
+            if self.eat(&TokenKind::RBrace) {
+                return Ok(entries);
+            }
+            self.expect(&TokenKind::Comma, "',' or '}'")?;
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Execution of `ExternalEvent` nodes
+//!
+//! An external event node suspends its branch of the workflow until another
+//! system delivers the named event to the execution, e.g. a webhook confirming
+//! a payment. [`ExternalEvents`] keeps a mailbox per running execution: a
+//! delivered payload wakes the node waiting for it, or is kept until the node
+//! starts waiting. The payload becomes the node's output. A node that waits
+//! longer than its timeout follows its timeout edges, or fails without any.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+#[derive(Default)]
+struct Mailbox {
+    /// Payloads delivered before a node waited for them, by event name
+    delivered: HashMap<String, serde_json::Value>,
+    /// Nodes waiting for an event, by event name
+    waiting: HashMap<String, oneshot::Sender<serde_json::Value>>,
+}
+
+/// Mailboxes of the running executions of an executor
+///
+/// Cloning is cheap; clones share the mailboxes.
+#[derive(Clone, Default)]
+pub struct ExternalEvents {
+    mailboxes: Arc<Mutex<HashMap<Uuid, Mailbox>>>,
+}
+
+impl std::fmt::Debug for ExternalEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalEvents")
+            .field("executions", &self.lock().len())
+            .finish()
+    }
+}
+
+/// Open mailbox of one execution, closed when dropped
+pub(crate) struct MailboxGuard {
+    events: ExternalEvents,
+    execution_id: Uuid,
+}
+
+impl Drop for MailboxGuard {
+    fn drop(&mut self) {
+        self.events.lock().remove(&self.execution_id);
+    }
+}
+
+impl ExternalEvents {
+    /// Create an empty set of mailboxes
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver `event_name` with `payload` to the running execution `execution_id`
+    ///
+    /// Fails if the execution is not running or already holds an undelivered
+    /// payload for the event.
+    pub fn deliver(
+        &self,
+        execution_id: &str,
+        event_name: &str,
+        payload: serde_json::Value,
+    ) -> GraphBitResult<()> {
+        let id = parse_execution_id(execution_id)?;
+        let mut mailboxes = self.lock();
+        let mailbox = mailboxes.get_mut(&id).ok_or_else(|| {
+            GraphBitError::validation(
+                "execution_id",
+                format!("No running execution '{execution_id}'"),
+            )
+        })?;
+        let payload = match mailbox.waiting.remove(event_name) {
+            // The waiting node was stopped; keep the payload for another one
+            Some(waiter) => match waiter.send(payload) {
+                Ok(()) => return Ok(()),
+                Err(payload) => payload,
+            },
+            None => payload,
+        };
+        if mailbox.delivered.contains_key(event_name) {
+            return Err(GraphBitError::validation(
+                "event_name",
+                format!("Event '{event_name}' was already delivered to execution '{execution_id}'"),
+            ));
+        }
+        mailbox.delivered.insert(event_name.to_string(), payload);
+        drop(mailboxes);
+        Ok(())
+    }
+
+    /// Names of the events nodes of `execution_id` are waiting for, sorted
+    #[must_use]
+    pub fn waiting(&self, execution_id: &str) -> Vec<String> {
+        let Ok(id) = parse_execution_id(execution_id) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = self
+            .lock()
+            .get(&id)
+            .map(|mailbox| mailbox.waiting.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Open the mailbox of `execution_id` for as long as the guard lives
+    pub(crate) fn open(&self, execution_id: Uuid) -> MailboxGuard {
+        self.lock().entry(execution_id).or_default();
+        MailboxGuard {
+            events: self.clone(),
+            execution_id,
+        }
+    }
+
+    /// Wait for `event_name` to be delivered to `execution_id`, returning its
+    /// payload, or `None` once `timeout` has passed
+    pub(crate) async fn wait(
+        &self,
+        execution_id: Uuid,
+        event_name: &str,
+        timeout: Option<Duration>,
+    ) -> GraphBitResult<Option<serde_json::Value>> {
+        let mut receiver = {
+            let mut mailboxes = self.lock();
+            let mailbox = mailboxes.get_mut(&execution_id).ok_or_else(|| {
+                GraphBitError::workflow_execution(format!(
+                    "Execution '{execution_id}' cannot receive external events"
+                ))
+            })?;
+            if let Some(payload) = mailbox.delivered.remove(event_name) {
+                return Ok(Some(payload));
+            }
+            if mailbox.waiting.contains_key(event_name) {
+                return Err(GraphBitError::workflow_execution(format!(
+                    "Another node is already waiting for event '{event_name}'"
+                )));
+            }
+            let (sender, receiver) = oneshot::channel();
+            mailbox.waiting.insert(event_name.to_string(), sender);
+            drop(mailboxes);
+            receiver
+        };
+        let payload = if let Some(timeout) = timeout {
+            let Ok(payload) = tokio::time::timeout(timeout, &mut receiver).await else {
+                // Stop accepting the event, then take one delivered meanwhile
+                if let Some(mailbox) = self.lock().get_mut(&execution_id) {
+                    mailbox.waiting.remove(event_name);
+                }
+                return Ok(receiver.try_recv().ok());
+            };
+            payload
+        } else {
+            receiver.await
+        };
+        payload.map(Some).map_err(|_| {
+            GraphBitError::workflow_execution(format!(
+                "Execution '{execution_id}' stopped receiving external events"
+            ))
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, Mailbox>> {
+        self.mailboxes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn parse_execution_id(execution_id: &str) -> GraphBitResult<Uuid> {
+    Uuid::parse_str(execution_id).map_err(|_| {
+        GraphBitError::validation(
+            "execution_id",
+            format!("No running execution '{execution_id}'"),
+        )
+    })
+}
@@ -45,6 +45,59 @@ impl WorkflowEdge {
         }
     }
 
+    /// Create an edge a guardrail node with the `route` action follows only
+    /// when its input violates a check
+    pub fn violation() -> Self {
+        Self {
+            edge_type: EdgeType::Violation,
+            condition: None,
+            transform: None,
+            metadata: HashMap::with_capacity(4), // Pre-allocate for metadata
+        }
+    }
+
+    /// Create an edge an external event node follows only when its event did
+    /// not arrive before the timeout
+    pub fn timeout() -> Self {
+        Self {
+            edge_type: EdgeType::Timeout,
+            condition: None,
+            transform: None,
+            metadata: HashMap::with_capacity(4), // Pre-allocate for metadata
+        }
+    }
+
+    /// Create an edge followed only when its source node fails, leading to a
+    /// compensation node that cleans up after it
+    pub fn on_failure() -> Self {
+        Self {
+            edge_type: EdgeType::ErrorHandling,
+            condition: None,
+            transform: None,
+            metadata: HashMap::with_capacity(4), // Pre-allocate for metadata
+        }
+    }
+
+    /// Create an edge a condition node follows when its expression holds
+    pub fn on_true() -> Self {
+        Self {
+            edge_type: EdgeType::Branch(true),
+            condition: None,
+            transform: None,
+            metadata: HashMap::with_capacity(4), // Pre-allocate for metadata
+        }
+    }
+
+    /// Create an edge a condition node follows when its expression does not hold
+    pub fn on_false() -> Self {
+        Self {
+            edge_type: EdgeType::Branch(false),
+            condition: None,
+            transform: None,
+            metadata: HashMap::with_capacity(4), // Pre-allocate for metadata
+        }
+    }
+
     /// Add a transformation to the edge
     pub fn with_transform(mut self, transform: impl Into<String>) -> Self {
         self.transform = Some(transform.into());
@@ -67,6 +120,16 @@ pub enum EdgeType {
     ControlFlow,
     /// Conditional edge (only traversed if condition is true)
     Conditional,
-    /// Error handling edge
+    /// Followed only when its source node fails, to a compensation node; its
+    /// value is an object with the failed `node` and its `error`
     ErrorHandling,
+    /// Followed by a routing guardrail node instead of its other edges when
+    /// its input violates a check
+    Violation,
+    /// Followed by an external event node instead of its other edges when its
+    /// event did not arrive before the timeout
+    Timeout,
+    /// Followed by a condition node when its boolean expression evaluates to
+    /// the label of the edge
+    Branch(bool),
 }
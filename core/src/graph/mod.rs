@@ -8,5 +8,8 @@ mod node;
 pub mod workflow_graph;
 
 pub use workflow_graph::WorkflowGraph;
-pub use node::{AgentNodeConfig, NodeType, WorkflowNode};
+pub use node::{
+    AgentNodeConfig, JoinPolicy, NodeType, NodeTypeKind, PendingBranches, WorkflowNode,
+};
+pub(crate) use node::resolve_node_llm_config;
 pub use edge::{EdgeType, WorkflowEdge};
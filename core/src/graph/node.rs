@@ -1,7 +1,13 @@
 use crate::errors::{GraphBitError, GraphBitResult};
-use crate::types::{AgentId, RetryConfig};
+use crate::expression::{Expression, transformation_source};
+use crate::guardrail_node::{GuardrailAction, GuardrailCheck};
+use crate::llm::{ContextStrategy, HistoryWindow, LlmConfig};
+use crate::pacing::NodePacing;
+use crate::post_process::{POST_PROCESS_KEY, PostProcessor};
+use crate::types::{AgentId, PASSTHROUGH_ON_SKIP_TAG, RetryConfig};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 
 /// A node in the workflow graph representing a single execution unit
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +85,270 @@ impl WorkflowNode {
         self
     }
 
+    /// Set the names of the tools an agent node may call, resolved from the
+    /// executor's tool registry
+    pub fn with_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = tools
+            .into_iter()
+            .map(|name| serde_json::Value::String(name.into()))
+            .collect();
+        self.config
+            .insert("tools".to_string(), serde_json::Value::Array(names));
+        self
+    }
+
+    /// Set the agent nodes this node may delegate to through the built-in
+    /// `handoff` tool. Handoff targets only run when handed a task.
+    pub fn with_handoff_targets<I, S>(mut self, targets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = targets
+            .into_iter()
+            .map(|name| serde_json::Value::String(name.into()))
+            .collect();
+        self.config
+            .insert("handoff_targets".to_string(), serde_json::Value::Array(names));
+        self
+    }
+
+    /// Names of the agent nodes this node may hand off to
+    #[must_use]
+    pub fn handoff_targets(&self) -> Vec<String> {
+        self.config
+            .get("handoff_targets")
+            .and_then(serde_json::Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| name.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Mark this agent node as referencing an agent registered with the executor.
+    ///
+    /// Such nodes carry no agent configuration of their own, so several of them
+    /// may share one agent id; the executor fails instead of auto-creating an
+    /// agent when the referenced agent has not been registered.
+    pub fn with_registered_agent(mut self) -> Self {
+        self.config
+            .insert("registered_agent".to_string(), serde_json::Value::Bool(true));
+        self
+    }
+
+    /// Whether this node references an agent registered with the executor
+    #[must_use]
+    pub fn uses_registered_agent(&self) -> bool {
+        self.config
+            .get("registered_agent")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// Let this node run and call LLMs after the execution budget ran out, e.g.
+    /// for cleanup nodes
+    #[must_use]
+    pub fn with_budget_exempt(mut self, exempt: bool) -> Self {
+        self.config
+            .insert("budget_exempt".to_string(), serde_json::Value::Bool(exempt));
+        self
+    }
+
+    /// Whether this node runs after the execution budget ran out
+    #[must_use]
+    pub fn is_budget_exempt(&self) -> bool {
+        self.config
+            .get("budget_exempt")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// Never run this node at the same time as other nodes with the same
+    /// concurrency `key`, e.g. nodes writing to one external resource, even
+    /// where the graph would run them in parallel
+    #[must_use]
+    pub fn with_concurrency_key(mut self, key: impl Into<String>) -> Self {
+        self.config.insert(
+            "concurrency_key".to_string(),
+            serde_json::Value::String(key.into()),
+        );
+        self
+    }
+
+    /// Concurrency key serializing this node with the other nodes sharing it
+    #[must_use]
+    pub fn concurrency_key(&self) -> Option<&str> {
+        self.config
+            .get("concurrency_key")
+            .and_then(serde_json::Value::as_str)
+    }
+
+    /// Wait `pre_execution_delay_ms` before and `post_execution_delay_ms`
+    /// after this node runs, each non-zero delay extended by up to
+    /// `jitter_ms` at random. The waits are not part of the node's duration.
+    #[must_use]
+    pub fn with_pacing(mut self, pacing: NodePacing) -> Self {
+        for (key, value) in [
+            ("pre_execution_delay_ms", pacing.pre_execution_delay_ms),
+            ("post_execution_delay_ms", pacing.post_execution_delay_ms),
+            ("delay_jitter_ms", pacing.jitter_ms),
+        ] {
+            if value == 0 {
+                self.config.remove(key);
+            } else {
+                self.config
+                    .insert(key.to_string(), serde_json::Value::from(value));
+            }
+        }
+        self
+    }
+
+    /// Delays of this node around its execution; invalid values count as none
+    #[must_use]
+    pub fn pacing(&self) -> Option<NodePacing> {
+        NodePacing::from_node_config(&self.config).ok().flatten()
+    }
+
+    /// Set global template variables for this node, referenced as
+    /// `{{globals.NAME}}` and taking precedence over the executor's globals
+    #[must_use]
+    pub fn with_globals(mut self, globals: HashMap<String, serde_json::Value>) -> Self {
+        self.config.insert(
+            "globals".to_string(),
+            serde_json::Value::Object(globals.into_iter().collect()),
+        );
+        self
+    }
+
+    /// Global template variables set on this node
+    #[must_use]
+    pub fn globals(&self) -> HashMap<String, serde_json::Value> {
+        self.config
+            .get("globals")
+            .and_then(serde_json::Value::as_object)
+            .map(|globals| {
+                globals
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Set the system prompt of an agent node, overriding the agent's own
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.config.insert(
+            "system_prompt".to_string(),
+            serde_json::Value::String(system_prompt.into()),
+        );
+        self
+    }
+
+    /// Set the sampling temperature of an agent node, overriding the agent's own
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.config
+            .insert("temperature".to_string(), serde_json::json!(temperature));
+        self
+    }
+
+    /// Set the maximum tokens of an agent node, overriding the agent's own
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.config
+            .insert("max_tokens".to_string(), serde_json::json!(max_tokens));
+        self
+    }
+
+    /// Set the model used by an agent node, keeping the provider of the resolved
+    /// LLM configuration
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.config
+            .insert("model".to_string(), serde_json::Value::String(model.into()));
+        self
+    }
+
+    /// Set the full LLM configuration used by an agent node
+    pub fn with_llm_config(mut self, llm_config: &LlmConfig) -> Self {
+        if let Ok(value) = serde_json::to_value(llm_config) {
+            self.config.insert("llm_config".to_string(), value);
+        }
+        self
+    }
+
+    /// Resolve the LLM configuration for this node.
+    ///
+    /// The node's `llm_config` takes priority over `fallback` (the agent or executor
+    /// configuration); the node's `model`, if set, is applied on top. Returns `None`
+    /// when neither the node nor `fallback` provides a configuration.
+    #[must_use]
+    pub fn resolve_llm_config(&self, fallback: Option<&LlmConfig>) -> Option<LlmConfig> {
+        resolve_node_llm_config(&self.config, fallback)
+    }
+
+    /// Set how an agent node handles requests larger than the model's context window
+    pub fn with_context_strategy(mut self, strategy: ContextStrategy) -> Self {
+        self.config.insert(
+            "context_strategy".to_string(),
+            serde_json::Value::String(strategy.as_str().to_string()),
+        );
+        self
+    }
+
+    /// Set the context window size in tokens used by the node's context strategy,
+    /// for models whose context length is unknown or should be capped
+    pub fn with_context_window(mut self, context_window: u32) -> Self {
+        self.config.insert(
+            "context_window".to_string(),
+            serde_json::json!(context_window),
+        );
+        self
+    }
+
+    /// Limit the tool-loop history an agent node replays to the model
+    #[must_use]
+    pub fn with_history_window(mut self, window: HistoryWindow) -> Self {
+        if let Some(max_exchanges) = window.max_exchanges() {
+            self.config.insert(
+                "history_window".to_string(),
+                serde_json::json!(max_exchanges),
+            );
+        }
+        if let Some(tokens) = window.token_budget() {
+            self.config.insert(
+                "history_token_budget".to_string(),
+                serde_json::json!(tokens),
+            );
+        }
+        self.config.insert(
+            "summarize_overflow".to_string(),
+            serde_json::Value::Bool(window.summarize_overflow()),
+        );
+        self
+    }
+
+    /// Set how many parents a join node waits for
+    pub fn with_join_policy(mut self, policy: JoinPolicy) -> Self {
+        self.config
+            .insert("join_policy".to_string(), serde_json::json!(policy));
+        self
+    }
+
+    /// Set what happens to a join node's unfinished parent branches once its
+    /// policy is met
+    pub fn with_pending_branches(mut self, pending_branches: PendingBranches) -> Self {
+        self.config.insert(
+            "pending_branches".to_string(),
+            serde_json::Value::String(pending_branches.as_str().to_string()),
+        );
+        self
+    }
+
     /// Set input schema
     pub fn with_input_schema(mut self, schema: serde_json::Value) -> Self {
         self.input_schema = Some(schema);
@@ -91,6 +361,51 @@ impl WorkflowNode {
         self
     }
 
+    /// Set how many times an agent node asks the model to fix output that fails
+    /// its `output_schema` before the node fails (default 2)
+    pub fn with_max_repair_attempts(mut self, max_repair_attempts: u32) -> Self {
+        self.config.insert(
+            "max_repair_attempts".to_string(),
+            serde_json::json!(max_repair_attempts),
+        );
+        self
+    }
+
+    /// Set the operations applied in order to this node's output before it
+    /// is stored, see [`crate::post_process`]
+    #[must_use]
+    pub fn with_post_process<I>(mut self, processors: I) -> Self
+    where
+        I: IntoIterator<Item = PostProcessor>,
+    {
+        let processors: Vec<serde_json::Value> =
+            processors.into_iter().map(|p| p.to_value()).collect();
+        if processors.is_empty() {
+            self.config.remove(POST_PROCESS_KEY);
+        } else {
+            self.config.insert(
+                POST_PROCESS_KEY.to_string(),
+                serde_json::Value::Array(processors),
+            );
+        }
+        self
+    }
+
+    /// Operations applied to this node's output; invalid lists count as none
+    #[must_use]
+    pub fn post_processors(&self) -> Vec<PostProcessor> {
+        PostProcessor::from_node_config(&self.config).unwrap_or_default()
+    }
+
+    /// Validate an agent node's output against its `output_schema` as returned,
+    /// without first recovering JSON from fenced or malformed output
+    #[must_use]
+    pub fn with_strict_json(mut self, strict_json: bool) -> Self {
+        self.config
+            .insert("strict_json".to_string(), serde_json::json!(strict_json));
+        self
+    }
+
     /// Set retry configuration
     pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
         self.retry_config = retry_config;
@@ -109,11 +424,18 @@ impl WorkflowNode {
         self
     }
 
+    /// Whether this node passes its input on as its output when the tag filter
+    /// of an execution excludes it
+    #[must_use]
+    pub fn passes_through_on_skip(&self) -> bool {
+        self.tags.iter().any(|tag| tag == PASSTHROUGH_ON_SKIP_TAG)
+    }
+
     /// Validate the node configuration
     pub fn validate(&self) -> GraphBitResult<()> {
         // Validate node type specific requirements
         match &self.node_type {
-            NodeType::Agent { config } => {
+            NodeType::Agent { config } | NodeType::ModelRouter { config, .. } => {
                 if config.agent_id.to_string().is_empty() {
                     return Err(GraphBitError::graph(
                         "Agent node must have a valid agent_id",
@@ -128,20 +450,124 @@ impl WorkflowNode {
                 if let Some(sys) = &config.system_prompt_override {
                     Self::validate_template_syntax(sys, "system_prompt_override")?;
                 }
+                ContextStrategy::from_node_config(&self.config)?;
+                HistoryWindow::from_node_config(&self.config)?;
+                if let NodeType::ModelRouter {
+                    candidates,
+                    escalation_expression,
+                    ..
+                } = &self.node_type
+                {
+                    if candidates.is_empty() {
+                        return Err(GraphBitError::graph(format!(
+                            "Model router node '{}' must have at least one candidate model",
+                            self.name
+                        )));
+                    }
+                    Expression::parse(escalation_expression).map_err(|e| {
+                        GraphBitError::graph(format!(
+                            "Model router node '{}' has an invalid escalation expression: {e}",
+                            self.name
+                        ))
+                    })?;
+                }
             }
-            NodeType::Condition { handler_id } => {
-                if handler_id.trim().is_empty() {
+            NodeType::Condition {
+                handler_id,
+                expression,
+            } => match expression {
+                Some(expression) => {
+                    Expression::parse(expression).map_err(|e| {
+                        GraphBitError::graph(format!(
+                            "Condition node '{}' has an invalid expression: {e}",
+                            self.name
+                        ))
+                    })?;
+                }
+                None if handler_id.trim().is_empty() => {
                     return Err(GraphBitError::graph(
-                        "Condition node must have a non-empty handler_id",
+                        "Condition node must have a non-empty handler_id or an expression",
                     ));
                 }
-            }
+                None => {}
+            },
             NodeType::Transform { transformation } => {
                 if transformation.is_empty() {
                     return Err(GraphBitError::graph(
                         "Transform node must have a transformation",
                     ));
                 }
+                Expression::parse(transformation_source(transformation)).map_err(|e| {
+                    GraphBitError::graph(format!(
+                        "Transform node '{}' has an invalid transformation: {e}",
+                        self.name
+                    ))
+                })?;
+            }
+            NodeType::PythonCode {
+                code_template,
+                timeout_seconds,
+                memory_limit_mb,
+                ..
+            } => {
+                if cfg!(not(feature = "python")) {
+                    return Err(GraphBitError::graph(format!(
+                        "Python code node '{}' requires GraphBit built with the `python` feature",
+                        self.name
+                    )));
+                }
+                if code_template.trim().is_empty() {
+                    return Err(GraphBitError::graph(format!(
+                        "Python code node '{}' must have code",
+                        self.name
+                    )));
+                }
+                if *timeout_seconds == 0 {
+                    return Err(GraphBitError::graph(format!(
+                        "Python code node '{}' must have a timeout of at least 1 second",
+                        self.name
+                    )));
+                }
+                if *memory_limit_mb == 0 {
+                    return Err(GraphBitError::graph(format!(
+                        "Python code node '{}' must have a memory limit of at least 1 MiB",
+                        self.name
+                    )));
+                }
+            }
+            NodeType::ShellCommand {
+                command_template,
+                timeout_seconds,
+                ..
+            } => {
+                if command_template.trim().is_empty() {
+                    return Err(GraphBitError::graph(format!(
+                        "Shell command node '{}' must have a command",
+                        self.name
+                    )));
+                }
+                if *timeout_seconds == 0 {
+                    return Err(GraphBitError::graph(format!(
+                        "Shell command node '{}' must have a timeout of at least 1 second",
+                        self.name
+                    )));
+                }
+                crate::shell_command::check_command_template(&self.name, command_template)?;
+            }
+            NodeType::Guardrail { checks, .. } => {
+                crate::guardrail_node::validate_checks(&self.name, checks)?;
+            }
+            NodeType::ExternalEvent { event_name, .. } => {
+                if event_name.trim().is_empty() {
+                    return Err(GraphBitError::graph(format!(
+                        "External event node '{}' must name its event",
+                        self.name
+                    )));
+                }
+            }
+            NodeType::Join => {
+                JoinPolicy::from_node_config(&self.config)?;
+                PendingBranches::from_node_config(&self.config)?;
             }
             NodeType::DocumentLoader {
                 document_type,
@@ -168,6 +594,23 @@ impl WorkflowNode {
             }
             _ => {}
         }
+        NodePacing::from_node_config(&self.config)?;
+        PostProcessor::from_node_config(&self.config).map_err(|e| {
+            GraphBitError::graph(format!(
+                "Node '{}' has an invalid post_process list: {e}",
+                self.name
+            ))
+        })?;
+        match self.config.get("concurrency_key") {
+            None => {}
+            Some(serde_json::Value::String(key)) if !key.trim().is_empty() => {}
+            Some(_) => {
+                return Err(GraphBitError::graph(format!(
+                    "Node '{}' has an invalid concurrency_key: expected a non-empty string",
+                    self.name
+                )));
+            }
+        }
 
         Ok(())
     }
@@ -205,6 +648,29 @@ impl WorkflowNode {
     }
 }
 
+/// Resolve an agent node's LLM configuration from its config map, see
+/// [`WorkflowNode::resolve_llm_config`]
+pub(crate) fn resolve_node_llm_config(
+    config: &HashMap<String, serde_json::Value>,
+    fallback: Option<&LlmConfig>,
+) -> Option<LlmConfig> {
+    let node_llm_config = config.get("llm_config").and_then(|value| {
+        match serde_json::from_value::<LlmConfig>(value.clone()) {
+            Ok(llm_config) => Some(llm_config),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid node-level LLM config: {e}");
+                None
+            }
+        }
+    });
+    let resolved = node_llm_config.or_else(|| fallback.cloned())?;
+
+    Some(match config.get("model").and_then(|v| v.as_str()) {
+        Some(model) if !model.trim().is_empty() => resolved.with_model(model),
+        _ => resolved,
+    })
+}
+
 /// Configuration for an Agent execution node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentNodeConfig {
@@ -218,6 +684,102 @@ pub struct AgentNodeConfig {
     pub system_prompt_override: Option<String>,
 }
 
+/// How many parents a join node waits for before it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinPolicy {
+    /// Wait for every parent
+    #[default]
+    All,
+    /// Run once any parent succeeds
+    Any,
+    /// Run once the given number of parents succeed
+    Quorum(usize),
+}
+
+impl JoinPolicy {
+    /// Number of parents that must succeed before the join runs early, or
+    /// `None` when it waits for all of them
+    #[must_use]
+    pub const fn required(self) -> Option<usize> {
+        match self {
+            Self::All => None,
+            Self::Any => Some(1),
+            Self::Quorum(n) => Some(n),
+        }
+    }
+
+    /// The `join_policy` of a node configuration, [`JoinPolicy::All`] when unset
+    pub fn from_node_config(config: &HashMap<String, serde_json::Value>) -> GraphBitResult<Self> {
+        let policy = match config.get("join_policy") {
+            None | Some(serde_json::Value::Null) => return Ok(Self::All),
+            Some(value) => serde_json::from_value(value.clone()).map_err(|_| {
+                GraphBitError::validation(
+                    "join_policy",
+                    format!("Expected \"all\", \"any\" or {{\"quorum\": n}}, got {value}"),
+                )
+            })?,
+        };
+        if policy == Self::Quorum(0) {
+            return Err(GraphBitError::validation(
+                "join_policy",
+                "A quorum must be at least 1",
+            ));
+        }
+        Ok(policy)
+    }
+}
+
+/// What happens to a join node's unfinished parent branches once its policy is met
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingBranches {
+    /// Stop running branch nodes and skip the ones that have not started
+    #[default]
+    Cancel,
+    /// Leave running branch nodes to finish in the background, discarding their
+    /// outputs, and skip the ones that have not started
+    Detach,
+}
+
+impl PendingBranches {
+    /// Name used in node configurations
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Cancel => "cancel",
+            Self::Detach => "detach",
+        }
+    }
+
+    /// The `pending_branches` of a node configuration, [`PendingBranches::Cancel`] when unset
+    pub fn from_node_config(config: &HashMap<String, serde_json::Value>) -> GraphBitResult<Self> {
+        match config.get("pending_branches") {
+            None | Some(serde_json::Value::Null) => Ok(Self::Cancel),
+            Some(serde_json::Value::String(name)) => name.parse(),
+            Some(other) => Err(GraphBitError::validation(
+                "pending_branches",
+                format!("Expected \"cancel\" or \"detach\", got {other}"),
+            )),
+        }
+    }
+}
+
+impl FromStr for PendingBranches {
+    type Err = GraphBitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cancel" => Ok(Self::Cancel),
+            "detach" => Ok(Self::Detach),
+            other => Err(GraphBitError::validation(
+                "pending_branches",
+                format!("Unknown pending branch handling '{other}', expected cancel or detach"),
+            )),
+        }
+    }
+}
+
 /// Types of workflow nodes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -228,19 +790,45 @@ pub enum NodeType {
         #[serde(flatten)]
         config: AgentNodeConfig,
     },
-    /// Conditional branch: parent output and workflow snapshot are passed to a runtime-registered handler; handler returns the next node name (string).
+    /// Agent execution trying `candidates` in order, escalating to the next model
+    /// while `escalation_expression` holds for an answer
+    ModelRouter {
+        /// Flatten into the outer object like [`NodeType::Agent`]
+        #[serde(flatten)]
+        config: AgentNodeConfig,
+        /// LLM configurations to try, usually cheapest first
+        candidates: Vec<LlmConfig>,
+        /// Expression (see [`crate::expression`]) over `output`, `finish_reason`,
+        /// `confidence`, `schema_valid`, `model` and `attempt`; when it holds, the
+        /// answer is discarded and the next candidate is asked
+        escalation_expression: String,
+    },
+    /// Conditional branch: routes to the successor named by `expression`, or by the
+    /// runtime-registered handler when no expression is set.
     Condition {
         /// Opaque id matching an entry in `WorkflowExecutor`'s conditional handler map.
+        #[serde(default)]
         handler_id: String,
+        /// Expression (see [`crate::expression`]) evaluated against the parent output.
+        /// A string result names the next node; a boolean follows the node's
+        /// [`on_true`](super::WorkflowEdge::on_true) or
+        /// [`on_false`](super::WorkflowEdge::on_false) edge, running none of its
+        /// branches when that edge is missing. Without such edges a boolean picks
+        /// the first (`true`) or second (`false`) connected successor.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expression: Option<String>,
     },
     /// Data transformation node
     Transform {
-        /// Transformation logic to apply
+        /// Expression (see [`crate::expression`]) evaluated with the node input as
+        /// `input`, or one of the names `identity`, `uppercase`, `lowercase`,
+        /// `trim`, `json_parse` and `json_stringify`
         transformation: String,
     },
     /// Parallel execution splitter
     Split,
-    /// Parallel execution joiner
+    /// Parallel execution joiner; its `join_policy` and `pending_branches`
+    /// configuration set how many parents it waits for
     Join,
     /// Delay/wait node
     Delay {
@@ -261,6 +849,58 @@ pub enum NodeType {
         /// Name of the custom function to execute
         function_name: String,
     },
+    /// Python code run in a sandboxed subprocess by executors that allow Python
+    /// code nodes (see [`crate::python_code`]); only available when `GraphBit`
+    /// is built with the `python` feature
+    PythonCode {
+        /// Code to run; `{{node.X}}` and `{{input.X}}` references are substituted first.
+        /// The value of a trailing expression is captured.
+        code_template: String,
+        /// Wall-clock and CPU time limit in seconds
+        #[serde(default = "default_python_code_timeout")]
+        timeout_seconds: u64,
+        /// Address space limit in MiB
+        #[serde(default = "default_python_code_memory_limit")]
+        memory_limit_mb: u64,
+        /// Top-level modules the code may import
+        #[serde(default)]
+        allowed_imports: Vec<String>,
+        /// Whether the code may open network connections
+        #[serde(default)]
+        allow_network: bool,
+    },
+    /// Shell command run by executors that allow shell nodes (see [`crate::shell_command`])
+    ShellCommand {
+        /// Command to run; values of `{{node.X}}` and `{{input.X}}` references,
+        /// which must not be inside quotes, are passed as environment variables
+        /// (see [`crate::shell_command::CommandValues`])
+        command_template: String,
+        /// Directory to run in, the current directory if unset
+        #[serde(default)]
+        working_dir: Option<String>,
+        /// Variables added to the inherited environment
+        #[serde(default)]
+        env: HashMap<String, String>,
+        /// Time limit in seconds, after which the command is killed
+        #[serde(default = "default_shell_command_timeout")]
+        timeout_seconds: u64,
+    },
+    /// Content check for personal data and unwanted text (see [`crate::guardrail_node`])
+    Guardrail {
+        /// Checks run in order on the node input
+        checks: Vec<GuardrailCheck>,
+        /// What a violation does
+        #[serde(default)]
+        on_violation: GuardrailAction,
+    },
+    /// Wait for an event delivered to the execution (see [`crate::external_event`])
+    ExternalEvent {
+        /// Name the event is delivered under
+        event_name: String,
+        /// Seconds to wait before following the timeout edges; unlimited when unset
+        #[serde(default)]
+        timeout_seconds: Option<u64>,
+    },
     /// Document loading node
     DocumentLoader {
         /// Type of document to load
@@ -271,3 +911,156 @@ pub enum NodeType {
         encoding: Option<String>,
     },
 }
+
+impl NodeType {
+    /// Snake case name of the node type, e.g. `http_request`
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        self.type_kind().as_str()
+    }
+
+    /// Variant of the node type, without its configuration
+    #[must_use]
+    pub const fn type_kind(&self) -> NodeTypeKind {
+        match self {
+            Self::Agent { .. } => NodeTypeKind::Agent,
+            Self::ModelRouter { .. } => NodeTypeKind::ModelRouter,
+            Self::Condition { .. } => NodeTypeKind::Condition,
+            Self::Transform { .. } => NodeTypeKind::Transform,
+            Self::Split => NodeTypeKind::Split,
+            Self::Join => NodeTypeKind::Join,
+            Self::Delay { .. } => NodeTypeKind::Delay,
+            Self::HttpRequest { .. } => NodeTypeKind::HttpRequest,
+            Self::Custom { .. } => NodeTypeKind::Custom,
+            Self::PythonCode { .. } => NodeTypeKind::PythonCode,
+            Self::ShellCommand { .. } => NodeTypeKind::ShellCommand,
+            Self::Guardrail { .. } => NodeTypeKind::Guardrail,
+            Self::ExternalEvent { .. } => NodeTypeKind::ExternalEvent,
+            Self::DocumentLoader { .. } => NodeTypeKind::DocumentLoader,
+        }
+    }
+
+    /// Agent configuration of agent and model router nodes
+    #[must_use]
+    pub const fn agent_config(&self) -> Option<&AgentNodeConfig> {
+        match self {
+            Self::Agent { config } | Self::ModelRouter { config, .. } => Some(config),
+            _ => None,
+        }
+    }
+
+    /// Mutable agent configuration of agent and model router nodes
+    pub fn agent_config_mut(&mut self) -> Option<&mut AgentNodeConfig> {
+        match self {
+            Self::Agent { config } | Self::ModelRouter { config, .. } => Some(config),
+            _ => None,
+        }
+    }
+}
+
+/// Variants of [`NodeType`] without their configuration, for finding nodes by type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeTypeKind {
+    /// [`NodeType::Agent`]
+    Agent,
+    /// [`NodeType::ModelRouter`]
+    ModelRouter,
+    /// [`NodeType::Condition`]
+    Condition,
+    /// [`NodeType::Transform`]
+    Transform,
+    /// [`NodeType::Split`]
+    Split,
+    /// [`NodeType::Join`]
+    Join,
+    /// [`NodeType::Delay`]
+    Delay,
+    /// [`NodeType::HttpRequest`]
+    HttpRequest,
+    /// [`NodeType::Custom`]
+    Custom,
+    /// [`NodeType::PythonCode`]
+    PythonCode,
+    /// [`NodeType::ShellCommand`]
+    ShellCommand,
+    /// [`NodeType::Guardrail`]
+    Guardrail,
+    /// [`NodeType::ExternalEvent`]
+    ExternalEvent,
+    /// [`NodeType::DocumentLoader`]
+    DocumentLoader,
+}
+
+impl NodeTypeKind {
+    /// Every node type
+    pub const ALL: [Self; 14] = [
+        Self::Agent,
+        Self::ModelRouter,
+        Self::Condition,
+        Self::Transform,
+        Self::Split,
+        Self::Join,
+        Self::Delay,
+        Self::HttpRequest,
+        Self::Custom,
+        Self::PythonCode,
+        Self::ShellCommand,
+        Self::Guardrail,
+        Self::ExternalEvent,
+        Self::DocumentLoader,
+    ];
+
+    /// Snake case name, e.g. `http_request`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Agent => "agent",
+            Self::ModelRouter => "model_router",
+            Self::Condition => "condition",
+            Self::Transform => "transform",
+            Self::Split => "split",
+            Self::Join => "join",
+            Self::Delay => "delay",
+            Self::HttpRequest => "http_request",
+            Self::Custom => "custom",
+            Self::PythonCode => "python_code",
+            Self::ShellCommand => "shell_command",
+            Self::Guardrail => "guardrail",
+            Self::ExternalEvent => "external_event",
+            Self::DocumentLoader => "document_loader",
+        }
+    }
+}
+
+impl FromStr for NodeTypeKind {
+    type Err = GraphBitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|kind| kind.as_str()).collect();
+                GraphBitError::validation(
+                    "node_type",
+                    format!(
+                        "Unknown node type '{s}', expected one of {}",
+                        names.join(", ")
+                    ),
+                )
+            })
+    }
+}
+
+const fn default_python_code_timeout() -> u64 {
+    30
+}
+
+const fn default_python_code_memory_limit() -> u64 {
+    512
+}
+
+const fn default_shell_command_timeout() -> u64 {
+    60
+}
@@ -3,7 +3,9 @@
 //! Contains the graph structure and adjacency management for workflow execution.
 
 use crate::errors::{GraphBitError, GraphBitResult};
-use crate::types::NodeId;
+use crate::expression::Expression;
+use crate::guardrail_node::GuardrailAction;
+use crate::types::{AgentId, NodeId};
 use petgraph::{
     Direction,
     algo::{is_cyclic_directed, toposort},
@@ -11,8 +13,16 @@ use petgraph::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 
-use super::{NodeType, WorkflowEdge, WorkflowNode};
+use super::{EdgeType, JoinPolicy, NodeType, NodeTypeKind, WorkflowEdge, WorkflowNode};
+
+/// Dependency levels computed by [`WorkflowGraph::node_levels`]
+struct NodeLevels {
+    order: Vec<NodeId>,
+    levels: HashMap<NodeId, usize>,
+    deepest_parent: HashMap<NodeId, NodeId>,
+}
 
 /// A workflow graph that defines the structure and execution flow
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +61,11 @@ pub struct WorkflowGraph {
     root_nodes_cache: Option<Vec<NodeId>>,
     #[serde(skip)]
     leaf_nodes_cache: Option<Vec<NodeId>>,
+    /// Cached transitive dependencies and dependents
+    #[serde(skip)]
+    ancestors_cache: HashMap<NodeId, HashSet<NodeId>>,
+    #[serde(skip)]
+    descendants_cache: HashMap<NodeId, HashSet<NodeId>>,
 }
 
 impl WorkflowGraph {
@@ -70,6 +85,8 @@ impl WorkflowGraph {
             name_to_id: HashMap::with_capacity(16),
             root_nodes_cache: None,
             leaf_nodes_cache: None,
+            ancestors_cache: HashMap::new(),
+            descendants_cache: HashMap::new(),
         }
     }
 
@@ -79,6 +96,8 @@ impl WorkflowGraph {
         self.dependents_cache.clear();
         self.root_nodes_cache = None;
         self.leaf_nodes_cache = None;
+        self.ancestors_cache.clear();
+        self.descendants_cache.clear();
     }
 
     /// Refresh outgoing/incoming adjacency maps from canonical `edges`.
@@ -155,21 +174,29 @@ impl WorkflowGraph {
         Ok(())
     }
 
-    /// Add a node to the graph
-    pub fn add_node(&mut self, node: WorkflowNode) -> GraphBitResult<()> {
-        let node_id = node.id.clone();
-
-        if self.nodes.contains_key(&node_id) {
-            let incoming_name = node.name.clone();
-            let existing_name = self
-                .nodes
-                .get(&node_id)
-                .map(|n| n.name.clone())
-                .unwrap_or_else(|| "<unknown>".to_string());
+    /// Fail when a node with the id of `node` is already in the graph
+    fn ensure_node_is_new(&self, node: &WorkflowNode) -> GraphBitResult<()> {
+        if let Some(existing) = self.nodes.get(&node.id) {
             return Err(GraphBitError::graph(format!(
-                "Node already exists: id={node_id} (existing name='{existing_name}', incoming name='{incoming_name}'). Hint: create a fresh Node instance; do not add the same Node object twice."
+                "Node already exists: id={} (existing name='{}', incoming name='{}'). Hint: create a fresh Node instance; do not add the same Node object twice.",
+                node.id, existing.name, node.name
             )));
         }
+        Ok(())
+    }
+
+    /// Position in `edges` of the first edge from `from` to `to`
+    fn edge_position(&self, from: &NodeId, to: &NodeId) -> GraphBitResult<usize> {
+        self.edges
+            .iter()
+            .position(|(f, t, _)| f == from && t == to)
+            .ok_or_else(|| GraphBitError::graph(format!("Edge {from} -> {to} not found")))
+    }
+
+    /// Add a node to the graph
+    pub fn add_node(&mut self, node: WorkflowNode) -> GraphBitResult<()> {
+        self.ensure_node_is_new(&node)?;
+        let node_id = node.id.clone();
 
         let graph_index = self.graph.add_node(node.clone());
         self.node_map.insert(node_id.clone(), graph_index);
@@ -254,6 +281,148 @@ impl WorkflowGraph {
         Ok(())
     }
 
+    /// Replace the node `node_id` with `new_node`, keeping its incoming and
+    /// outgoing edges
+    ///
+    /// The edges are re-pointed at the id of `new_node`, which may be
+    /// `node_id` itself to update the node in place.
+    pub fn replace_node(&mut self, node_id: &NodeId, new_node: WorkflowNode) -> GraphBitResult<()> {
+        if !self.nodes.contains_key(node_id) {
+            return Err(GraphBitError::graph(format!("Node {node_id} not found")));
+        }
+        if &new_node.id != node_id {
+            self.ensure_node_is_new(&new_node)?;
+        }
+
+        let new_id = new_node.id.clone();
+        for (from, to, _) in &mut self.edges {
+            if from == node_id {
+                *from = new_id.clone();
+            }
+            if to == node_id {
+                *to = new_id.clone();
+            }
+        }
+        self.nodes.remove(node_id);
+        self.nodes.insert(new_id, new_node);
+
+        self.rebuild_graph()
+    }
+
+    /// Split the edge from `from` to `to` by inserting `node` into it
+    ///
+    /// The original edge, with its condition and transform, now leads from
+    /// `from` to `node`, and a data flow edge leads from `node` to `to`. When
+    /// several edges join the two nodes, the first one is split.
+    pub fn insert_between(
+        &mut self,
+        from: &NodeId,
+        to: &NodeId,
+        node: WorkflowNode,
+    ) -> GraphBitResult<()> {
+        let position = self.edge_position(from, to)?;
+        self.ensure_node_is_new(&node)?;
+
+        let node_id = node.id.clone();
+        self.nodes.insert(node_id.clone(), node);
+        // Keep both halves where the original edge was, so serialized edges keep their order
+        self.edges[position].1 = node_id.clone();
+        self.edges.insert(
+            position + 1,
+            (node_id, to.clone(), WorkflowEdge::data_flow()),
+        );
+
+        self.rebuild_graph()
+    }
+
+    /// Replace the first edge from `from` to `to` with `new_edge`
+    pub fn update_edge(
+        &mut self,
+        from: &NodeId,
+        to: &NodeId,
+        new_edge: WorkflowEdge,
+    ) -> GraphBitResult<()> {
+        let position = self.edge_position(from, to)?;
+        self.edges[position].2 = new_edge;
+        self.rebuild_graph()
+    }
+
+    /// Copy the nodes and edges of `other` into this graph, prepending
+    /// `prefix` to the name of every copied node
+    ///
+    /// Copied nodes keep their ids unless the id is already taken here, in
+    /// which case they get a fresh one; agent nodes whose agent id is taken
+    /// get a fresh agent id, and handoff targets are renamed along with their
+    /// nodes. Fails without changing the graph when a prefixed name is already
+    /// used by a node of this graph. Graph metadata is not copied.
+    ///
+    /// Returns the id in this graph of every node of `other`.
+    pub fn merge(
+        &mut self,
+        other: &Self,
+        prefix: &str,
+    ) -> GraphBitResult<HashMap<NodeId, NodeId>> {
+        let names: HashSet<&str> = self.nodes.values().map(|n| n.name.as_str()).collect();
+        let agent_ids: HashSet<AgentId> = self
+            .nodes
+            .values()
+            .filter(|node| !node.uses_registered_agent())
+            .filter_map(|node| node.node_type.agent_config())
+            .map(|config| config.agent_id.clone())
+            .collect();
+
+        let mut ids = HashMap::with_capacity(other.nodes.len());
+        let mut merged = Vec::with_capacity(other.nodes.len());
+        for (old_id, node) in &other.nodes {
+            let mut node = node.clone();
+            node.name = format!("{prefix}{}", node.name);
+            if names.contains(node.name.as_str()) {
+                return Err(GraphBitError::graph(format!(
+                    "Cannot merge node '{}': a node with that name already exists. Hint: use a different prefix.",
+                    node.name
+                )));
+            }
+            if self.nodes.contains_key(&node.id) {
+                node.id = NodeId::new();
+            }
+            if !node.uses_registered_agent() {
+                if let Some(config) = node.node_type.agent_config_mut() {
+                    if agent_ids.contains(&config.agent_id) {
+                        config.agent_id = AgentId::new();
+                    }
+                }
+            }
+            let targets = node.handoff_targets();
+            if !targets.is_empty() {
+                node = node.with_handoff_targets(
+                    targets
+                        .into_iter()
+                        .map(|target| format!("{prefix}{target}")),
+                );
+            }
+            ids.insert(old_id.clone(), node.id.clone());
+            merged.push(node);
+        }
+
+        let edges = other
+            .edges
+            .iter()
+            .map(|(from, to, edge)| match (ids.get(from), ids.get(to)) {
+                (Some(from), Some(to)) => Ok((from.clone(), to.clone(), edge.clone())),
+                _ => Err(GraphBitError::graph(format!(
+                    "Edge references non-existent node: {from} -> {to}"
+                ))),
+            })
+            .collect::<GraphBitResult<Vec<_>>>()?;
+
+        self.nodes
+            .extend(merged.into_iter().map(|node| (node.id.clone(), node)));
+        self.edges.extend(edges);
+        self.rebuild_graph()?;
+
+        Ok(ids)
+    }
+
     /// Get a node by ID
     #[inline]
     pub fn get_node(&self, node_id: &NodeId) -> Option<&WorkflowNode> {
@@ -266,12 +435,38 @@ impl WorkflowGraph {
         &self.nodes
     }
 
+    /// Apply `update` to every node, keeping the graph's copies of the nodes in sync
+    pub fn update_nodes<F>(&mut self, mut update: F) -> GraphBitResult<()>
+    where
+        F: FnMut(&mut WorkflowNode) -> GraphBitResult<()>,
+    {
+        for (node_id, node) in &mut self.nodes {
+            update(node)?;
+            if let Some(weight) = self
+                .node_map
+                .get(node_id)
+                .and_then(|index| self.graph.node_weight_mut(*index))
+            {
+                *weight = node.clone();
+            }
+        }
+        Ok(())
+    }
+
     /// Get all edges
     #[inline]
     pub fn get_edges(&self) -> &[(NodeId, NodeId, WorkflowEdge)] {
         &self.edges
     }
 
+    /// Get the first edge from `from` to `to`
+    pub fn get_edge(&self, from: &NodeId, to: &NodeId) -> Option<&WorkflowEdge> {
+        self.edges
+            .iter()
+            .find(|(f, t, _)| f == from && t == to)
+            .map(|(_, _, edge)| edge)
+    }
+
     /// Check if the graph contains cycles
     pub fn has_cycles(&self) -> bool {
         is_cyclic_directed(&self.graph)
@@ -298,6 +493,195 @@ impl WorkflowGraph {
         Ok(sorted_nodes)
     }
 
+    /// Level of every node: 1 for root nodes, otherwise one more than the
+    /// deepest dependency. Nodes on the same level can run in parallel.
+    ///
+    /// Returns the nodes in topological order along with their levels and the
+    /// dependency each node's deepest chain runs through.
+    fn node_levels(&self) -> GraphBitResult<NodeLevels> {
+        let order = self.topological_sort()?;
+        let mut levels: HashMap<NodeId, usize> = HashMap::with_capacity(order.len());
+        let mut deepest_parent: HashMap<NodeId, NodeId> = HashMap::with_capacity(order.len());
+        for node_id in &order {
+            let mut level = 1;
+            let mut parent: Option<&NodeId> = None;
+            for dependency in self.incoming.get(node_id).into_iter().flatten() {
+                let candidate = levels[dependency] + 1;
+                // Ties go to the dependency with the smallest name, so the result is stable
+                if candidate > level
+                    || (candidate == level
+                        && parent.is_some_and(|p| self.node_name(dependency) < self.node_name(p)))
+                {
+                    level = candidate;
+                    parent = Some(dependency);
+                }
+            }
+            if let Some(parent) = parent {
+                deepest_parent.insert(node_id.clone(), parent.clone());
+            }
+            levels.insert(node_id.clone(), level);
+        }
+        Ok(NodeLevels {
+            order,
+            levels,
+            deepest_parent,
+        })
+    }
+
+    fn node_name(&self, node_id: &NodeId) -> &str {
+        self.nodes
+            .get(node_id)
+            .map_or("", |node| node.name.as_str())
+    }
+
+    /// Number of nodes on the longest dependency chain; 0 for an empty graph
+    pub fn depth(&self) -> GraphBitResult<usize> {
+        Ok(self.node_levels()?.levels.into_values().max().unwrap_or(0))
+    }
+
+    /// Nodes on the longest dependency chain, from a root node to a leaf node
+    ///
+    /// When several chains are equally long, the one through the nodes with the
+    /// smallest names is returned.
+    pub fn critical_path(&self) -> GraphBitResult<Vec<NodeId>> {
+        let NodeLevels {
+            order,
+            levels,
+            deepest_parent,
+        } = self.node_levels()?;
+        let Some(mut current) = order
+            .iter()
+            .min_by(|a, b| {
+                levels[*b]
+                    .cmp(&levels[*a])
+                    .then_with(|| self.node_name(a).cmp(self.node_name(b)))
+            })
+            .cloned()
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut path = vec![current.clone()];
+        while let Some(parent) = deepest_parent.get(&current) {
+            path.push(parent.clone());
+            current = parent.clone();
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Largest number of nodes that can run at the same time, i.e. the largest
+    /// number of nodes sharing a dependency level
+    pub fn max_parallel_width(&self) -> GraphBitResult<usize> {
+        let mut width: HashMap<usize, usize> = HashMap::new();
+        for level in self.node_levels()?.levels.into_values() {
+            *width.entry(level).or_default() += 1;
+        }
+        Ok(width.into_values().max().unwrap_or(0))
+    }
+
+    /// Nodes ordered by dependency level and then by name, or by name alone
+    /// when the graph has cycles, with the short ids (`n0`, `n1`, ...) the
+    /// exporters give them
+    fn export_ids(&self) -> (Vec<&WorkflowNode>, HashMap<&NodeId, String>) {
+        let levels = self.node_levels().map(|levels| levels.levels).ok();
+        let mut nodes: Vec<&WorkflowNode> = self.nodes.values().collect();
+        nodes.sort_by_cached_key(|node| {
+            let level = levels
+                .as_ref()
+                .and_then(|levels| levels.get(&node.id).copied());
+            (level, node.name.clone(), node.id.to_string())
+        });
+        let ids = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (&node.id, format!("n{index}")))
+            .collect();
+        (nodes, ids)
+    }
+
+    /// Mermaid flowchart of the graph: condition nodes are drawn as diamonds,
+    /// agent nodes with rounded corners and edge conditions as edge labels
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let (nodes, ids) = self.export_ids();
+        let mut mermaid = String::from("flowchart TD\n");
+        for node in &nodes {
+            let label = node.name.replace('"', "#quot;");
+            let shape = match node.node_type {
+                NodeType::Condition { .. } => format!("{{\"{label}\"}}"),
+                NodeType::Agent { .. } | NodeType::ModelRouter { .. } => {
+                    format!("(\"{label}\")")
+                }
+                _ => format!("[\"{label}\"]"),
+            };
+            let _ = writeln!(mermaid, "    {}{shape}", ids[&node.id]);
+        }
+        for (from, to, edge) in &self.edges {
+            let (Some(from), Some(to)) = (ids.get(from), ids.get(to)) else {
+                continue;
+            };
+            let arrow = if matches!(edge.edge_type, EdgeType::ControlFlow) {
+                "-.->"
+            } else {
+                "-->"
+            };
+            match &edge.condition {
+                Some(condition) => {
+                    let condition = condition.replace('"', "#quot;");
+                    let _ = writeln!(mermaid, "    {from} {arrow}|\"{condition}\"| {to}");
+                }
+                None => {
+                    let _ = writeln!(mermaid, "    {from} {arrow} {to}");
+                }
+            }
+        }
+        mermaid
+    }
+
+    /// Graphviz DOT description of the graph, drawn like [`Self::to_mermaid`]
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        fn quote(text: &str) -> String {
+            format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+
+        let (nodes, ids) = self.export_ids();
+        let mut dot = String::from("digraph workflow {\n    rankdir=TB;\n");
+        for node in &nodes {
+            let shape = match node.node_type {
+                NodeType::Condition { .. } => "shape=diamond",
+                NodeType::Agent { .. } | NodeType::ModelRouter { .. } => "shape=box, style=rounded",
+                _ => "shape=box",
+            };
+            let _ = writeln!(
+                dot,
+                "    {} [label={}, {shape}];",
+                ids[&node.id],
+                quote(&node.name)
+            );
+        }
+        for (from, to, edge) in &self.edges {
+            let (Some(from), Some(to)) = (ids.get(from), ids.get(to)) else {
+                continue;
+            };
+            let mut attributes = Vec::new();
+            if let Some(condition) = &edge.condition {
+                attributes.push(format!("label={}", quote(condition)));
+            }
+            if matches!(edge.edge_type, EdgeType::ControlFlow) {
+                attributes.push("style=dashed".to_string());
+            }
+            if attributes.is_empty() {
+                let _ = writeln!(dot, "    {from} -> {to};");
+            } else {
+                let _ = writeln!(dot, "    {from} -> {to} [{}];", attributes.join(", "));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
     /// Get dependencies (incoming edges) for a node with caching
     pub fn get_dependencies(&mut self, node_id: &NodeId) -> Vec<NodeId> {
         // Check cache first
@@ -384,6 +768,45 @@ impl WorkflowGraph {
         leaves
     }
 
+    /// Nodes `node_id` depends on, directly or through other nodes, with caching
+    pub fn ancestors(&mut self, node_id: &NodeId) -> HashSet<NodeId> {
+        if let Some(ancestors) = self.ancestors_cache.get(node_id) {
+            return ancestors.clone();
+        }
+        let ancestors = self.transitive(node_id, Self::get_dependencies);
+        self.ancestors_cache
+            .insert(node_id.clone(), ancestors.clone());
+        ancestors
+    }
+
+    /// Nodes depending on `node_id`, directly or through other nodes, with caching
+    pub fn descendants(&mut self, node_id: &NodeId) -> HashSet<NodeId> {
+        if let Some(descendants) = self.descendants_cache.get(node_id) {
+            return descendants.clone();
+        }
+        let descendants = self.transitive(node_id, Self::get_dependents);
+        self.descendants_cache
+            .insert(node_id.clone(), descendants.clone());
+        descendants
+    }
+
+    /// Nodes reached from `node_id` by repeated `step`s, not counting `node_id`
+    /// unless it is on a cycle
+    fn transitive(
+        &mut self,
+        node_id: &NodeId,
+        step: fn(&mut Self, &NodeId) -> Vec<NodeId>,
+    ) -> HashSet<NodeId> {
+        let mut reached = HashSet::new();
+        let mut stack = step(self, node_id);
+        while let Some(id) = stack.pop() {
+            if reached.insert(id.clone()) {
+                stack.extend(step(self, &id));
+            }
+        }
+        reached
+    }
+
     /// Check if a node is ready to execute (all dependencies completed)
     pub fn is_node_ready(
         &mut self,
@@ -420,6 +843,34 @@ impl WorkflowGraph {
 
     /// Validate the graph structure
     pub fn validate(&self) -> GraphBitResult<()> {
+        // Compensation subtrees run after a failure and end there: they hold no
+        // cycle, and only on-failure edges lead into them
+        let compensation = self.compensation_nodes();
+        for id in &compensation {
+            if self
+                .direct_successors(id)
+                .iter()
+                .any(|successor| self.forward_reachable_from(successor).contains(id))
+            {
+                return Err(GraphBitError::graph(format!(
+                    "Compensation node '{}' is part of a cycle",
+                    self.node_name(id)
+                )));
+            }
+        }
+        for (from, to, edge) in &self.edges {
+            if compensation.contains(to)
+                && !compensation.contains(from)
+                && !matches!(edge.edge_type, EdgeType::ErrorHandling)
+            {
+                return Err(GraphBitError::graph(format!(
+                    "Node '{}' leads to compensation node '{}' by an edge that is not an on-failure edge",
+                    self.node_name(from),
+                    self.node_name(to)
+                )));
+            }
+        }
+
         // Check for cycles
         if self.has_cycles() {
             return Err(GraphBitError::graph("Workflow graph contains cycles"));
@@ -444,6 +895,43 @@ impl WorkflowGraph {
             node.validate()?;
         }
 
+        // Edge conditions and transforms must parse
+        for (from, to, edge) in &self.edges {
+            let expressions = [
+                ("condition", &edge.condition),
+                ("transform", &edge.transform),
+            ];
+            for (kind, source) in expressions {
+                let Some(source) = source else { continue };
+                if let Err(e) = Expression::parse(source) {
+                    return Err(GraphBitError::graph(format!(
+                        "Edge '{}' -> '{}' has an invalid {kind}: {e}",
+                        self.nodes[from].name, self.nodes[to].name
+                    )));
+                }
+            }
+        }
+
+        // A join's quorum cannot exceed its number of parents
+        for node in self.nodes.values() {
+            if !matches!(node.node_type, NodeType::Join) {
+                continue;
+            }
+            if let Some(required) = JoinPolicy::from_node_config(&node.config)?.required() {
+                let parents = self
+                    .edges
+                    .iter()
+                    .filter(|(_, to, _)| to == &node.id)
+                    .count();
+                if required > parents {
+                    return Err(GraphBitError::graph(format!(
+                        "Join node '{}' has {parents} parents, fewer than its policy requires ({required})",
+                        node.name
+                    )));
+                }
+            }
+        }
+
         // Condition nodes: direct successors must have unique names (routing by name)
         for node in self.nodes.values() {
             if matches!(node.node_type, NodeType::Condition { .. }) {
@@ -463,12 +951,144 @@ impl WorkflowGraph {
             }
         }
 
-        // Enforce unique agent IDs across all agent nodes
+        // True/false edges leave condition nodes, at most one of each and no
+        // other edges besides on-failure ones
+        for node in self.nodes.values() {
+            let outgoing = self.edges.iter().filter(|(from, _, edge)| {
+                from == &node.id && !matches!(edge.edge_type, EdgeType::ErrorHandling)
+            });
+            let (branches, others): (Vec<_>, Vec<_>) =
+                outgoing.partition(|(_, _, edge)| matches!(edge.edge_type, EdgeType::Branch(_)));
+            if branches.is_empty() {
+                continue;
+            }
+            if !matches!(node.node_type, NodeType::Condition { .. }) {
+                return Err(GraphBitError::graph(format!(
+                    "Node '{}' has true/false edges but is not a condition node",
+                    node.name
+                )));
+            }
+            if !others.is_empty() {
+                return Err(GraphBitError::graph(format!(
+                    "Condition node '{}' has true/false edges and other outgoing edges",
+                    node.name
+                )));
+            }
+            for outcome in [true, false] {
+                let count = branches
+                    .iter()
+                    .filter(|(_, _, edge)| {
+                        matches!(edge.edge_type, EdgeType::Branch(label) if label == outcome)
+                    })
+                    .count();
+                if count > 1 {
+                    return Err(GraphBitError::graph(format!(
+                        "Condition node '{}' has {count} {outcome} edges, at most one is allowed",
+                        node.name
+                    )));
+                }
+            }
+        }
+        for warning in self.warnings() {
+            tracing::warn!("{warning}");
+        }
+
+        // Violation edges leave routing guardrails, which need at least one
+        for node in self.nodes.values() {
+            let routes = matches!(
+                node.node_type,
+                NodeType::Guardrail {
+                    on_violation: GuardrailAction::Route,
+                    ..
+                }
+            );
+            let violation_edges = self
+                .edges
+                .iter()
+                .filter(|(from, _, edge)| {
+                    from == &node.id && matches!(edge.edge_type, EdgeType::Violation)
+                })
+                .count();
+            if routes && violation_edges == 0 {
+                return Err(GraphBitError::graph(format!(
+                    "Guardrail node '{}' routes violations but has no violation edge",
+                    node.name
+                )));
+            }
+            if !routes && violation_edges > 0 {
+                return Err(GraphBitError::graph(format!(
+                    "Node '{}' has violation edges but is not a guardrail node with the route action",
+                    node.name
+                )));
+            }
+        }
+
+        // Timeout edges leave external event nodes that stop waiting
+        for node in self.nodes.values() {
+            let has_timeout_edges = self.edges.iter().any(|(from, _, edge)| {
+                from == &node.id && matches!(edge.edge_type, EdgeType::Timeout)
+            });
+            if !has_timeout_edges {
+                continue;
+            }
+            match node.node_type {
+                NodeType::ExternalEvent {
+                    timeout_seconds: Some(_),
+                    ..
+                } => {}
+                NodeType::ExternalEvent { .. } => {
+                    return Err(GraphBitError::graph(format!(
+                        "External event node '{}' has timeout edges but no timeout",
+                        node.name
+                    )));
+                }
+                _ => {
+                    return Err(GraphBitError::graph(format!(
+                        "Node '{}' has timeout edges but is not an external event node",
+                        node.name
+                    )));
+                }
+            }
+        }
+
+        // Handoff targets must name other agent nodes
+        for node in self.nodes.values() {
+            for target in node.handoff_targets() {
+                let target_node = self.nodes.values().find(|n| n.name == target);
+                match target_node {
+                    None => {
+                        return Err(GraphBitError::graph(format!(
+                            "Node '{}' hands off to unknown node '{target}'",
+                            node.name
+                        )));
+                    }
+                    Some(t) if !matches!(t.node_type, NodeType::Agent { .. }) => {
+                        return Err(GraphBitError::graph(format!(
+                            "Handoff target '{target}' of node '{}' is not an agent node",
+                            node.name
+                        )));
+                    }
+                    Some(t) if t.id == node.id => {
+                        return Err(GraphBitError::graph(format!(
+                            "Node '{}' cannot hand off to itself",
+                            node.name
+                        )));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        // Enforce unique agent IDs across all agent nodes; nodes referencing an agent
+        // registered with the executor may share it
         {
             use std::collections::HashMap;
             let mut agent_index: HashMap<String, Vec<(NodeId, String)>> = HashMap::new();
             for node in self.nodes.values() {
-                if let NodeType::Agent { config } = &node.node_type {
+                if node.uses_registered_agent() {
+                    continue;
+                }
+                if let Some(config) = node.node_type.agent_config() {
                     agent_index
                         .entry(config.agent_id.to_string())
                         .or_default()
@@ -540,6 +1160,33 @@ impl WorkflowGraph {
         Ok(())
     }
 
+    /// Problems of a valid graph worth a look, sorted: condition nodes with a
+    /// true edge but no false edge or the other way around, which run none of
+    /// their branches when their expression takes the missing one
+    #[must_use]
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for node in self.nodes.values() {
+            let labels: HashSet<bool> = self
+                .edges
+                .iter()
+                .filter_map(|(from, _, edge)| match edge.edge_type {
+                    EdgeType::Branch(label) if from == &node.id => Some(label),
+                    _ => None,
+                })
+                .collect();
+            if labels.len() == 1 {
+                let missing = !labels.contains(&true);
+                warnings.push(format!(
+                    "Condition node '{}' has no {missing} edge; nothing after it runs when its expression is {missing}",
+                    node.name
+                ));
+            }
+        }
+        warnings.sort();
+        warnings
+    }
+
     /// Set metadata
     pub fn set_metadata(&mut self, key: String, value: serde_json::Value) {
         self.metadata.insert(key, value);
@@ -570,6 +1217,54 @@ impl WorkflowGraph {
         self.name_to_id.get(name).cloned()
     }
 
+    /// Ids of the nodes `predicate` holds for, ordered by name
+    pub fn find_nodes<F>(&self, predicate: F) -> Vec<NodeId>
+    where
+        F: Fn(&WorkflowNode) -> bool,
+    {
+        let mut found: Vec<&WorkflowNode> =
+            self.nodes.values().filter(|node| predicate(node)).collect();
+        found.sort_by_cached_key(|node| (node.name.clone(), node.id.to_string()));
+        found.into_iter().map(|node| node.id.clone()).collect()
+    }
+
+    /// Ids of the nodes of type `kind`, ordered by name
+    pub fn find_nodes_by_type(&self, kind: NodeTypeKind) -> Vec<NodeId> {
+        self.find_nodes(|node| node.node_type.type_kind() == kind)
+    }
+
+    /// Ids of the nodes tagged `tag`, ordered by name
+    pub fn find_nodes_by_tag(&self, tag: &str) -> Vec<NodeId> {
+        self.find_nodes(|node| node.tags.iter().any(|t| t == tag))
+    }
+
+    /// Standalone graph of the nodes `node_ids` and the edges between them
+    ///
+    /// Nodes keep their ids, and the graph metadata is copied. Fails when a
+    /// node is not in this graph.
+    pub fn subgraph(&self, node_ids: &[NodeId]) -> GraphBitResult<Self> {
+        let mut subgraph = Self::new();
+        for node_id in node_ids {
+            let node = self
+                .nodes
+                .get(node_id)
+                .ok_or_else(|| GraphBitError::graph(format!("Node {node_id} not found")))?;
+            subgraph.nodes.insert(node_id.clone(), node.clone());
+        }
+        subgraph.edges = self
+            .edges
+            .iter()
+            .filter(|(from, to, _)| {
+                subgraph.nodes.contains_key(from) && subgraph.nodes.contains_key(to)
+            })
+            .cloned()
+            .collect();
+        subgraph.metadata = self.metadata.clone();
+        subgraph.rebuild_graph()?;
+        subgraph.validate()?;
+        Ok(subgraph)
+    }
+
     /// Direct successors along control/data edges (`from` → `to`).
     pub fn direct_successors(&self, from: &NodeId) -> Vec<NodeId> {
         self.outgoing.get(from).cloned().unwrap_or_default()
@@ -589,6 +1284,86 @@ impl WorkflowGraph {
         }
         visited
     }
+
+    /// Compensation nodes: the targets of on-failure edges and every node
+    /// reachable from them
+    #[must_use]
+    pub fn compensation_nodes(&self) -> HashSet<NodeId> {
+        self.edges
+            .iter()
+            .filter(|(_, _, edge)| matches!(edge.edge_type, EdgeType::ErrorHandling))
+            .flat_map(|(_, to, _)| self.forward_reachable_from(to))
+            .collect()
+    }
+
+    /// Compensation nodes reached through the on-failure edges of `node_id`
+    #[must_use]
+    pub fn compensation_nodes_of(&self, node_id: &NodeId) -> HashSet<NodeId> {
+        self.edges
+            .iter()
+            .filter(|(from, _, edge)| {
+                from == node_id && matches!(edge.edge_type, EdgeType::ErrorHandling)
+            })
+            .flat_map(|(_, to, _)| self.forward_reachable_from(to))
+            .collect()
+    }
+
+    /// Branch of a split fan-out each node runs in: the split node and the
+    /// position of the split's child the branch starts at, in edge order.
+    ///
+    /// A branch runs from a child of the split up to, but not including, the
+    /// first join node. Nodes reachable from several branches of a split are
+    /// in none of its branches, and nodes in branches of nested splits are in
+    /// the branch of the innermost one. Graphs with cycles have no branches.
+    #[must_use]
+    pub fn split_branches(&self) -> HashMap<NodeId, (NodeId, usize)> {
+        let mut branches = HashMap::new();
+        let Ok(order) = self.topological_sort() else {
+            return branches;
+        };
+        // Outer splits come first in topological order, so inner ones overwrite them
+        for split in order.iter().filter(|id| {
+            self.nodes
+                .get(id)
+                .is_some_and(|node| matches!(node.node_type, NodeType::Split))
+        }) {
+            let mut children = self.direct_successors(split);
+            let mut seen = HashSet::new();
+            children.retain(|child| seen.insert(child.clone()));
+            let mut reached: HashMap<NodeId, Option<usize>> = HashMap::new();
+            for (index, child) in children.iter().enumerate() {
+                let mut stack = vec![child.clone()];
+                let mut visited = HashSet::new();
+                while let Some(id) = stack.pop() {
+                    let is_join = self
+                        .nodes
+                        .get(&id)
+                        .is_some_and(|node| matches!(node.node_type, NodeType::Join));
+                    if is_join || !visited.insert(id.clone()) {
+                        continue;
+                    }
+                    reached
+                        .entry(id.clone())
+                        .and_modify(|branch| {
+                            if *branch != Some(index) {
+                                *branch = None;
+                            }
+                        })
+                        .or_insert(Some(index));
+                    stack.extend(self.direct_successors(&id));
+                }
+            }
+            // A nested split only claims nodes in the same branch as itself
+            let enclosing = branches.get(split).cloned();
+            for (id, index) in reached {
+                let Some(index) = index else { continue };
+                if branches.get(&id) == enclosing.as_ref() {
+                    branches.insert(id, (split.clone(), index));
+                }
+            }
+        }
+        branches
+    }
 }
 
 impl Default for WorkflowGraph {
@@ -0,0 +1,366 @@
+//! Execution of `Guardrail` nodes
+//!
+//! A guardrail node checks the text it receives for personal data and other
+//! unwanted content. Pattern checks run locally; the classifier check asks an
+//! LLM. The node's [`GuardrailAction`] decides what a violation does: redact
+//! the matches, fail the node, or route to the node's violation edges. Every
+//! run is recorded in the audit log as an [`AuditCallKind::Guardrail`] call.
+
+use crate::audit::AuditCallKind;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::{
+    LlmConfig, LlmMessage, LlmMiddlewareStack, LlmProvider, LlmProviderFactory, LlmRequest,
+};
+use crate::pii::{CREDIT_CARD_PATTERN, EMAIL_PATTERN, PHONE_PATTERN};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Check run by a guardrail node
+///
+/// Serialized as a name (`"pii"`, `"email"`, `"phone"`, `"credit_card"`) or a
+/// single-key object (`{"regex": ...}`, `{"deny_list": [...]}`,
+/// `{"classifier": {"instructions": ...}}`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailCheck {
+    /// Email addresses, phone numbers and credit card numbers
+    Pii,
+    /// Email addresses
+    Email,
+    /// Phone numbers
+    Phone,
+    /// Credit card numbers
+    CreditCard,
+    /// Text matching a regular expression
+    Regex(String),
+    /// Any of the given words or phrases, matched as whole words ignoring case
+    DenyList(Vec<String>),
+    /// An LLM deciding whether the text violates `instructions`
+    Classifier {
+        /// Policy the text is checked against
+        instructions: String,
+        /// LLM to ask, the node's LLM configuration if unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        llm_config: Option<LlmConfig>,
+    },
+}
+
+impl GuardrailCheck {
+    /// Patterns the check looks for, by the name their violations are reported
+    /// under; empty for the classifier. Fails with what is wrong with the check.
+    fn patterns(&self) -> Result<Vec<(&'static str, Regex)>, String> {
+        let builtin = |name, pattern: &LazyLock<Regex>| Ok(vec![(name, Regex::clone(pattern))]);
+        match self {
+            Self::Pii => Ok([Self::Email, Self::CreditCard, Self::Phone]
+                .iter()
+                .map(Self::patterns)
+                .collect::<Result<Vec<_>, _>>()?
+                .concat()),
+            Self::Email => builtin("email", &EMAIL_PATTERN),
+            Self::Phone => builtin("phone", &PHONE_PATTERN),
+            Self::CreditCard => builtin("credit_card", &CREDIT_CARD_PATTERN),
+            Self::Regex(pattern) => {
+                let regex =
+                    Regex::new(pattern).map_err(|e| format!("has an invalid regex check: {e}"))?;
+                Ok(vec![("regex", regex)])
+            }
+            Self::DenyList(terms) => {
+                let alternatives: Vec<String> = terms
+                    .iter()
+                    .filter(|term| !term.trim().is_empty())
+                    .map(|term| regex::escape(term.trim()))
+                    .collect();
+                if alternatives.is_empty() {
+                    return Err("has a deny list check without terms".to_string());
+                }
+                let pattern = format!(r"(?i)\b(?:{})\b", alternatives.join("|"));
+                let regex =
+                    Regex::new(&pattern).map_err(|e| format!("has an invalid deny list: {e}"))?;
+                Ok(vec![("deny_list", regex)])
+            }
+            Self::Classifier { instructions, .. } if instructions.trim().is_empty() => {
+                Err("has a classifier check without instructions".to_string())
+            }
+            Self::Classifier { .. } => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Check that the guardrail node `node_name` can run `checks`: there is at
+/// least one, patterns compile, deny lists have terms and classifiers have
+/// instructions
+pub fn validate_checks(node_name: &str, checks: &[GuardrailCheck]) -> GraphBitResult<()> {
+    if checks.is_empty() {
+        return Err(GraphBitError::graph(format!(
+            "Guardrail node '{node_name}' must have at least one check"
+        )));
+    }
+    for check in checks {
+        check
+            .patterns()
+            .map_err(|e| GraphBitError::graph(format!("Guardrail node '{node_name}' {e}")))?;
+    }
+    Ok(())
+}
+
+/// What a guardrail node does when a check finds a violation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailAction {
+    /// Replace matches with `[REDACTED_<CHECK>]` and continue with the masked
+    /// text; a classifier violation replaces the whole text
+    #[default]
+    Redact,
+    /// Fail the node
+    Block,
+    /// Pass the text on unchanged along the node's violation edges instead of
+    /// its other edges
+    Route,
+}
+
+impl GuardrailAction {
+    /// Name of the action
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Redact => "redact",
+            Self::Block => "block",
+            Self::Route => "route",
+        }
+    }
+}
+
+impl FromStr for GuardrailAction {
+    type Err = GraphBitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "redact" => Ok(Self::Redact),
+            "block" => Ok(Self::Block),
+            "route" => Ok(Self::Route),
+            other => Err(GraphBitError::validation(
+                "on_violation",
+                format!("Unknown guardrail action '{other}', expected redact, block or route"),
+            )),
+        }
+    }
+}
+
+/// Violations one check found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailViolation {
+    /// Check that found them: `email`, `phone`, `credit_card`, `regex`,
+    /// `deny_list` or `classifier`
+    pub check: String,
+    /// Number of matches; 1 for a classifier violation
+    pub matches: usize,
+    /// Explanation given by the classifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Outcome of a guardrail node, recorded on the
+/// [`WorkflowContext`](crate::types::WorkflowContext) and the node's result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailReport {
+    /// Action the node takes on a violation
+    pub action: GuardrailAction,
+    /// Violations found, one entry per check
+    pub violations: Vec<GuardrailViolation>,
+    /// Number of replacements made in the node output
+    pub redactions: usize,
+}
+
+impl GuardrailReport {
+    /// Whether no check found a violation
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Error failing the node `node_name`, when its action is block and the
+    /// input violated a check
+    #[must_use]
+    pub fn block_error(&self, node_name: &str) -> Option<GraphBitError> {
+        if self.action != GuardrailAction::Block || self.is_clean() {
+            return None;
+        }
+        let found: Vec<String> = self
+            .violations
+            .iter()
+            .map(|violation| format!("{} ({})", violation.check, violation.matches))
+            .collect();
+        Some(GraphBitError::workflow_execution(format!(
+            "Guardrail node '{node_name}' blocked its input: {}",
+            found.join(", ")
+        )))
+    }
+
+    fn add(&mut self, check: &str, matches: usize, reason: Option<String>) {
+        match self.violations.iter_mut().find(|v| v.check == check) {
+            Some(violation) => violation.matches += matches,
+            None => self.violations.push(GuardrailViolation {
+                check: check.to_string(),
+                matches,
+                reason,
+            }),
+        }
+    }
+}
+
+/// Run `checks` of the node `node_name` on `input`, in order, each on the text
+/// earlier checks redacted.
+///
+/// Every string in `input` is checked, object keys aside. Returns the node
+/// output, `input` with matches masked when `action` is redact and unchanged
+/// otherwise, with the report. Violations do not fail the call; see
+/// [`GuardrailReport::block_error`]. Classifier checks without an LLM
+/// configuration of their own use `llm_config`.
+pub async fn run_guardrail(
+    node_name: &str,
+    checks: &[GuardrailCheck],
+    action: GuardrailAction,
+    input: serde_json::Value,
+    llm_config: Option<&LlmConfig>,
+    middleware: &LlmMiddlewareStack,
+) -> GraphBitResult<(serde_json::Value, GuardrailReport)> {
+    crate::audit::observe(
+        AuditCallKind::Guardrail,
+        "guardrail",
+        Some(node_name),
+        input,
+        text_of,
+        |mut value| async move {
+            let mut report = GuardrailReport {
+                action,
+                violations: Vec::new(),
+                redactions: 0,
+            };
+            let redact = action == GuardrailAction::Redact;
+            for check in checks {
+                if let GuardrailCheck::Classifier {
+                    instructions,
+                    llm_config: own_config,
+                } = check
+                {
+                    let config = own_config.as_ref().or(llm_config).ok_or_else(|| {
+                        GraphBitError::config(format!(
+                            "Guardrail node '{node_name}' has a classifier check but no LLM configuration"
+                        ))
+                    })?;
+                    let text = text_of(&value);
+                    if let Some(reason) =
+                        classify(node_name, instructions, &text, config, middleware).await?
+                    {
+                        report.add("classifier", 1, Some(reason));
+                        if redact {
+                            value = serde_json::Value::String("[REDACTED_CLASSIFIER]".into());
+                            report.redactions += 1;
+                        }
+                    }
+                    continue;
+                }
+                let patterns = check.patterns().map_err(|e| {
+                    GraphBitError::workflow_execution(format!(
+                        "Guardrail node '{node_name}' {e}"
+                    ))
+                })?;
+                for (name, regex) in patterns {
+                    let label = format!("[REDACTED_{}]", name.to_uppercase());
+                    let matches = scan(&mut value, &regex, redact.then_some(label.as_str()));
+                    if matches > 0 {
+                        report.add(name, matches, None);
+                        if redact {
+                            report.redactions += matches;
+                        }
+                    }
+                }
+            }
+            Ok((value, report))
+        },
+        |(_, report)| (serde_json::to_string(report).unwrap_or_default(), None),
+    )
+    .await
+}
+
+/// The text of a node input: strings as they are, other values as JSON
+fn text_of(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Count the matches of `regex` in the strings of `value`, replacing them with
+/// `label` if given
+fn scan(value: &mut serde_json::Value, regex: &Regex, label: Option<&str>) -> usize {
+    match value {
+        serde_json::Value::String(text) => {
+            let matches = regex.find_iter(text).count();
+            if let (Some(label), true) = (label, matches > 0) {
+                *text = regex.replace_all(text, regex::NoExpand(label)).into_owned();
+            }
+            matches
+        }
+        serde_json::Value::Array(items) => {
+            items.iter_mut().map(|item| scan(item, regex, label)).sum()
+        }
+        serde_json::Value::Object(fields) => fields
+            .values_mut()
+            .map(|field| scan(field, regex, label))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Ask the LLM of `config` whether `text` violates `instructions`, returning
+/// its reason if it does
+async fn classify(
+    node_name: &str,
+    instructions: &str,
+    text: &str,
+    config: &LlmConfig,
+    middleware: &LlmMiddlewareStack,
+) -> GraphBitResult<Option<String>> {
+    let provider = LlmProvider::new(
+        LlmProviderFactory::create_provider(config.clone())?,
+        config.clone(),
+    );
+    let request = LlmRequest::with_messages(vec![
+        LlmMessage::system(format!(
+            "You check text against a content policy. Reply with only a JSON object \
+             {{\"violation\": true or false, \"reason\": \"<one sentence>\"}}.\n\n\
+             Policy:\n{instructions}"
+        )),
+        LlmMessage::user(text),
+    ])
+    .with_temperature(0.0);
+    let response = provider.complete_with(middleware, request).await?;
+
+    let content = response.content.trim();
+    let verdict = content
+        .find('{')
+        .zip(content.rfind('}'))
+        .and_then(|(start, end)| {
+            serde_json::from_str::<serde_json::Value>(content.get(start..=end)?).ok()
+        });
+    let Some(violation) = verdict
+        .as_ref()
+        .and_then(|verdict| verdict.get("violation"))
+        .and_then(serde_json::Value::as_bool)
+    else {
+        return Err(GraphBitError::workflow_execution(format!(
+            "Guardrail node '{node_name}': classifier reply is not a verdict: {content}"
+        )));
+    };
+    Ok(violation.then(|| {
+        verdict
+            .as_ref()
+            .and_then(|verdict| verdict.get("reason"))
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    }))
+}
@@ -0,0 +1,500 @@
+//! Shared HTTP clients
+//!
+//! Every `reqwest::Client` owns a connection pool, so building one per provider
+//! (or per request) keeps many pools alive and renegotiates TLS for each of
+//! them. Components obtain their clients from an [`HttpClientFactory`] instead,
+//! which builds one client per distinct [`HttpSettings`] and hands out clones of
+//! it; clones share the pool, so components with the same settings reuse
+//! connections.
+//!
+//! Settings are resolved per component. LLM providers use their provider name
+//! (`openai`, `anthropic`, ...), embedding providers `embeddings.<provider>`
+//! (`embeddings.openai`, `embeddings.azure`, `embeddings.huggingface`), and the
+//! document loader, the `http_request` tool and HTTP request nodes use
+//! `document_loader`, `http_tool` and `http_node`. A component uses its
+//! override if one is set and the factory's settings otherwise; a timeout or
+//! user agent left unset there falls back to the component's own default, and
+//! an unset connection timeout to [`DEFAULT_CONNECT_TIMEOUT`]. The
+//! `http_request` tool builds a client of its own from its resolved settings,
+//! so that it can check every redirect against its host lists. The
+//! [`RequestTimeouts`] of an LLM or embedding configuration take precedence
+//! over all of these for the providers built from it.
+//!
+//! Without an explicit proxy, clients honor the `HTTP_PROXY`, `HTTPS_PROXY`,
+//! `ALL_PROXY` and `NO_PROXY` environment variables. Extra root certificates
+//! for TLS-intercepting proxies or private CAs can be loaded from a PEM bundle
+//! with [`HttpSettings::with_ca_bundle`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Connection timeout of components whose settings leave it unset
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+static GLOBAL_FACTORY: LazyLock<HttpClientFactory> =
+    LazyLock::new(|| HttpClientFactory::new(HttpSettings::default()));
+
+/// Request timeouts set on an LLM or embedding configuration, overriding the
+/// timeouts of the component's HTTP settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestTimeouts {
+    /// Total time a request may take, response body included, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Time allowed for establishing a connection, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_ms: Option<u64>,
+}
+
+impl RequestTimeouts {
+    /// No timeouts set, leaving them to the HTTP settings
+    #[must_use]
+    pub const fn none() -> Self {
+        Self {
+            timeout_ms: None,
+            connect_timeout_ms: None,
+        }
+    }
+
+    /// Set the total request timeout
+    #[must_use]
+    pub const fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Set the connection timeout
+    #[must_use]
+    pub const fn with_connect_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.connect_timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Whether neither timeout is set
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.timeout_ms.is_none() && self.connect_timeout_ms.is_none()
+    }
+
+    /// Check that the timeouts set are not zero
+    pub fn validate(self) -> GraphBitResult<()> {
+        for (field, value) in [
+            ("timeout_ms", self.timeout_ms),
+            ("connect_timeout_ms", self.connect_timeout_ms),
+        ] {
+            if value == Some(0) {
+                return Err(GraphBitError::validation(
+                    field,
+                    "must be greater than zero",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the timeouts of `settings` with the ones set here
+    pub fn apply(self, settings: &mut HttpSettings) {
+        if let Some(timeout_ms) = self.timeout_ms {
+            settings.timeout = Some(Duration::from_millis(timeout_ms));
+        }
+        if let Some(timeout_ms) = self.connect_timeout_ms {
+            settings.connect_timeout = Some(Duration::from_millis(timeout_ms));
+        }
+    }
+}
+
+/// Settings of a shared HTTP client; clients are shared between components
+/// whose resolved settings are equal
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpSettings {
+    /// Total request timeout. `None` uses the component's default.
+    pub timeout: Option<Duration>,
+    /// Timeout for establishing a connection
+    pub connect_timeout: Option<Duration>,
+    /// Proxy URL for all requests (e.g. `http://proxy.internal:3128`)
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy` (e.g. `localhost,.internal`).
+    /// `None` uses the `NO_PROXY` environment variable.
+    pub no_proxy: Option<String>,
+    /// Honor the proxy environment variables when `proxy` is unset
+    pub env_proxy: bool,
+    /// PEM bundle of root certificates trusted in addition to the system ones
+    pub ca_bundle: Option<PathBuf>,
+    /// Headers sent with every request
+    pub default_headers: BTreeMap<String, String>,
+    /// `User-Agent` header. `None` uses the component's default.
+    pub user_agent: Option<String>,
+    /// Maximum idle connections kept per host
+    pub pool_max_idle_per_host: usize,
+    /// How long idle connections are kept in the pool
+    pub pool_idle_timeout: Option<Duration>,
+    /// TCP keep-alive interval
+    pub tcp_keepalive: Option<Duration>,
+    /// Only use HTTP/1.1
+    pub http1_only: bool,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            no_proxy: None,
+            env_proxy: true,
+            ca_bundle: None,
+            default_headers: BTreeMap::new(),
+            user_agent: None,
+            pool_max_idle_per_host: 10,
+            pool_idle_timeout: Some(Duration::from_secs(30)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http1_only: false,
+        }
+    }
+}
+
+impl HttpSettings {
+    /// Set the total request timeout
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the connection timeout
+    #[must_use]
+    pub const fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send all requests through a proxy
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Hosts that bypass the proxy, as a comma-separated list
+    #[must_use]
+    pub fn with_no_proxy(mut self, no_proxy: impl Into<String>) -> Self {
+        self.no_proxy = Some(no_proxy.into());
+        self
+    }
+
+    /// Honor or ignore the proxy environment variables when no proxy is set
+    #[must_use]
+    pub const fn with_env_proxy(mut self, env_proxy: bool) -> Self {
+        self.env_proxy = env_proxy;
+        self
+    }
+
+    /// Trust the root certificates in a PEM bundle in addition to the system ones
+    #[must_use]
+    pub fn with_ca_bundle(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_bundle = Some(path.into());
+        self
+    }
+
+    /// Add a header sent with every request
+    #[must_use]
+    pub fn with_default_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.default_headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the `User-Agent` header
+    #[must_use]
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the maximum idle connections kept per host
+    #[must_use]
+    pub const fn with_pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = max_idle;
+        self
+    }
+
+    /// Set how long idle connections are kept, `None` to keep them indefinitely
+    #[must_use]
+    pub const fn with_pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// Set the TCP keep-alive interval, `None` to disable it
+    #[must_use]
+    pub const fn with_tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        self
+    }
+
+    /// Only use HTTP/1.1
+    #[must_use]
+    pub const fn with_http1_only(mut self, http1_only: bool) -> Self {
+        self.http1_only = http1_only;
+        self
+    }
+
+    /// Check that a client can be built with these settings, e.g. that the
+    /// proxy URL parses and the CA bundle loads
+    pub fn validate(&self) -> GraphBitResult<()> {
+        self.build_client().map(drop)
+    }
+
+    /// Build a client with these settings
+    fn build_client(&self) -> GraphBitResult<Client> {
+        self.client_builder()?
+            .build()
+            .map_err(|e| GraphBitError::config(format!("Failed to create HTTP client: {e}")))
+    }
+
+    /// Client builder configured with these settings, for components that need
+    /// a client of their own (e.g. with a custom redirect policy)
+    pub(crate) fn client_builder(&self) -> GraphBitResult<ClientBuilder> {
+        let mut builder = Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        match &self.proxy {
+            Some(proxy) => {
+                let no_proxy = self
+                    .no_proxy
+                    .as_deref()
+                    .map_or_else(NoProxy::from_env, NoProxy::from_string);
+                let proxy = Proxy::all(proxy).map_err(|e| {
+                    GraphBitError::config(format!("Invalid HTTP proxy '{proxy}': {e}"))
+                })?;
+                builder = builder.proxy(proxy.no_proxy(no_proxy));
+            }
+            // reqwest reads the proxy environment variables unless told otherwise
+            None if !self.env_proxy => builder = builder.no_proxy(),
+            None => {}
+        }
+        if let Some(path) = &self.ca_bundle {
+            builder = builder.tls_certs_merge(load_ca_bundle(path)?);
+        }
+        if !self.default_headers.is_empty() {
+            let mut headers = HeaderMap::new();
+            for (name, value) in &self.default_headers {
+                let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                    GraphBitError::config(format!("Invalid default header name '{name}': {e}"))
+                })?;
+                let value = HeaderValue::from_str(value).map_err(|e| {
+                    GraphBitError::config(format!("Invalid value for default header '{name}': {e}"))
+                })?;
+                headers.insert(name, value);
+            }
+            builder = builder.default_headers(headers);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        Ok(builder)
+    }
+}
+
+/// Read the certificates of a PEM bundle
+fn load_ca_bundle(path: &Path) -> GraphBitResult<Vec<Certificate>> {
+    let pem = std::fs::read(path).map_err(|e| {
+        GraphBitError::config(format!(
+            "Failed to read CA bundle '{}': {e}",
+            path.display()
+        ))
+    })?;
+    let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+        GraphBitError::config(format!("Invalid CA bundle '{}': {e}", path.display()))
+    })?;
+    if certs.is_empty() {
+        return Err(GraphBitError::config(format!(
+            "CA bundle '{}' contains no PEM certificates",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+/// Hands out HTTP clients shared between all components with the same settings
+#[derive(Debug)]
+pub struct HttpClientFactory {
+    settings: RwLock<HttpSettings>,
+    overrides: RwLock<HashMap<String, HttpSettings>>,
+    clients: Mutex<HashMap<HttpSettings, Client>>,
+    clients_built: AtomicUsize,
+}
+
+impl HttpClientFactory {
+    /// Create a factory using `settings` for components without an override
+    #[must_use]
+    pub fn new(settings: HttpSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            overrides: RwLock::new(HashMap::new()),
+            clients: Mutex::new(HashMap::new()),
+            clients_built: AtomicUsize::new(0),
+        }
+    }
+
+    /// The process-wide factory used by providers, loaders, tools and HTTP nodes
+    #[must_use]
+    pub fn global() -> &'static Self {
+        &GLOBAL_FACTORY
+    }
+
+    /// Settings used by components without an override
+    pub fn settings(&self) -> HttpSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the settings used by components without an override. Clients
+    /// handed out before keep their settings.
+    pub fn set_settings(&self, settings: HttpSettings) {
+        *self
+            .settings
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = settings;
+    }
+
+    /// Use `settings` for one component (e.g. `openai` or `http_node`)
+    pub fn set_provider_settings(&self, component: impl Into<String>, settings: HttpSettings) {
+        self.overrides
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(component.into(), settings);
+    }
+
+    /// Remove a component's override, returning whether one was set
+    pub fn clear_provider_settings(&self, component: &str) -> bool {
+        self.overrides
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(component)
+            .is_some()
+    }
+
+    /// Settings a component's client is built with, given the component's own
+    /// default timeout and user agent
+    pub fn settings_for(
+        &self,
+        component: &str,
+        default_timeout: Option<Duration>,
+        default_user_agent: Option<&str>,
+    ) -> HttpSettings {
+        let mut settings = self
+            .overrides
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(component)
+            .cloned()
+            .unwrap_or_else(|| self.settings());
+        if settings.timeout.is_none() {
+            settings.timeout = default_timeout;
+        }
+        if settings.connect_timeout.is_none() {
+            settings.connect_timeout = Some(DEFAULT_CONNECT_TIMEOUT);
+        }
+        if settings.user_agent.is_none() {
+            settings.user_agent = default_user_agent.map(str::to_string);
+        }
+        settings
+    }
+
+    /// Client for a component; see [`HttpClientFactory::settings_for`]
+    pub fn client(
+        &self,
+        component: &str,
+        default_timeout: Option<Duration>,
+        default_user_agent: Option<&str>,
+    ) -> GraphBitResult<Client> {
+        self.client_with(&self.settings_for(component, default_timeout, default_user_agent))
+    }
+
+    /// Client with exactly `settings`, built on first use and shared afterwards
+    pub fn client_with(&self, settings: &HttpSettings) -> GraphBitResult<Client> {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(settings) {
+            return Ok(client.clone());
+        }
+        let client = settings.build_client()?;
+        self.clients_built.fetch_add(1, Ordering::Relaxed);
+        clients.insert(settings.clone(), client.clone());
+        drop(clients);
+        Ok(client)
+    }
+
+    /// Number of clients built so far
+    pub fn clients_built(&self) -> usize {
+        self.clients_built.load(Ordering::Relaxed)
+    }
+}
+
+/// Client for a component from the global factory, see [`HttpClientFactory::client`]
+pub fn shared_client(
+    component: &str,
+    default_timeout: Option<Duration>,
+    default_user_agent: Option<&str>,
+) -> GraphBitResult<Client> {
+    shared_client_with_timeouts(
+        component,
+        default_timeout,
+        default_user_agent,
+        RequestTimeouts::none(),
+    )
+}
+
+/// Client for a component from the global factory whose timeouts are replaced
+/// by the ones set in `timeouts`
+pub fn shared_client_with_timeouts(
+    component: &str,
+    default_timeout: Option<Duration>,
+    default_user_agent: Option<&str>,
+    timeouts: RequestTimeouts,
+) -> GraphBitResult<Client> {
+    let factory = HttpClientFactory::global();
+    let mut settings = factory.settings_for(component, default_timeout, default_user_agent);
+    timeouts.apply(&mut settings);
+    factory.client_with(&settings)
+}
+
+/// Seconds to wait before retrying, from the `Retry-After` header of a
+/// rate-limited or unavailable response: either a number of seconds or an HTTP
+/// date, which counts as zero seconds once past
+pub(crate) fn retry_after_seconds(headers: &HeaderMap) -> Option<u64> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    value.parse().ok().or_else(|| {
+        let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        let seconds = (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_seconds();
+        Some(u64::try_from(seconds).unwrap_or(0))
+    })
+}
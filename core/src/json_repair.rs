@@ -0,0 +1,226 @@
+//! Recovery of JSON from almost-JSON model output
+//!
+//! Models asked for JSON often wrap it in a Markdown code fence, put a sentence
+//! before or after it, leave a trailing comma or stop mid-value when they run
+//! out of tokens. [`repair_json`] recovers the intended value from such text and
+//! lists the [`JsonRepair`]s it made. Agent nodes with an `output_schema` apply
+//! it to output that does not parse, unless the node sets `strict_json`.
+//!
+//! The repairs are heuristic: they never change text that already parses, but a
+//! repaired value may differ from what the model meant, for example when a
+//! truncated array is closed after its last complete element.
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of `{` / `[` positions tried as the start of the value
+const MAX_CANDIDATES: usize = 32;
+
+/// A repair applied by [`repair_json`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonRepair {
+    /// Removed a Markdown code fence around the value
+    StrippedCodeFence,
+    /// Took the value out of the prose around it
+    ExtractedFromProse,
+    /// Removed commas before a closing brace or bracket
+    RemovedTrailingCommas,
+    /// Closed a string left open at the end of the text
+    ClosedString,
+    /// Closed objects and arrays left open at the end of the text
+    ClosedBrackets,
+}
+
+impl JsonRepair {
+    /// Name of the repair, as serialized
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::StrippedCodeFence => "stripped_code_fence",
+            Self::ExtractedFromProse => "extracted_from_prose",
+            Self::RemovedTrailingCommas => "removed_trailing_commas",
+            Self::ClosedString => "closed_string",
+            Self::ClosedBrackets => "closed_brackets",
+        }
+    }
+}
+
+/// A value recovered by [`repair_json`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairedJson {
+    /// The recovered value
+    pub value: serde_json::Value,
+    /// Repairs made to recover it, in declaration order of [`JsonRepair`];
+    /// empty when the text was valid JSON
+    pub repairs: Vec<JsonRepair>,
+}
+
+/// Recover a JSON value from model output.
+///
+/// Text that parses as JSON is returned unchanged. Otherwise a Markdown code
+/// fence is stripped, and the first object or array in the remaining text that
+/// can be parsed after removing trailing commas and closing an unterminated
+/// string and open brackets is returned. Returns `None` when no such value is
+/// found.
+#[must_use]
+pub fn repair_json(text: &str) -> Option<RepairedJson> {
+    if let Ok(value) = serde_json::from_str(text) {
+        return Some(RepairedJson {
+            value,
+            repairs: Vec::new(),
+        });
+    }
+
+    let fenced = strip_code_fence(text).and_then(|inner| {
+        let mut repaired = repair_unfenced(inner)?;
+        repaired.repairs.insert(0, JsonRepair::StrippedCodeFence);
+        Some(repaired)
+    });
+    fenced.or_else(|| repair_unfenced(text))
+}
+
+/// Repair text that is not wrapped in a code fence
+fn repair_unfenced(text: &str) -> Option<RepairedJson> {
+    let text = text.trim();
+    if let Ok(value) = serde_json::from_str(text) {
+        return Some(RepairedJson {
+            value,
+            repairs: Vec::new(),
+        });
+    }
+
+    text.char_indices()
+        .filter(|(_, c)| matches!(c, '{' | '['))
+        .take(MAX_CANDIDATES)
+        .find_map(|(start, _)| {
+            let end = value_end(&text[start..]).map_or(text.len(), |len| start + len);
+            let mut repairs = Vec::new();
+            let candidate = close_and_clean(&text[start..end], &mut repairs);
+            let value = serde_json::from_str(&candidate).ok()?;
+            if start > 0 || !text[end..].trim().is_empty() {
+                repairs.push(JsonRepair::ExtractedFromProse);
+            }
+            repairs.sort_unstable();
+            Some(RepairedJson { value, repairs })
+        })
+}
+
+/// Contents of the first Markdown code fence in `text`, if it has one. A fence
+/// left open runs to the end of the text.
+fn strip_code_fence(text: &str) -> Option<&str> {
+    let open = text.find("```")?;
+    let after_open = &text[open + 3..];
+    // Skip the info string (`json`, `JSON`, ...) up to the end of the line
+    let body_start = after_open.find('\n').map_or(0, |newline| newline + 1);
+    let body = &after_open[body_start..];
+    Some(body.find("```").map_or(body, |close| &body[..close]))
+}
+
+/// Length of the object or array starting `text`, up to and including its
+/// closing bracket, or `None` if it is never closed
+fn value_end(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Remove trailing commas from `fragment` and close a string and brackets left
+/// open at its end, recording the repairs made
+fn close_and_clean(fragment: &str, repairs: &mut Vec<JsonRepair>) -> String {
+    let mut out = String::with_capacity(fragment.len() + 8);
+    let mut closers = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string_is_key = false;
+    let mut removed_commas = false;
+
+    for c in fragment.chars() {
+        if in_string {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                string_is_key = closers.last() == Some(&'}')
+                    && matches!(out.trim_end().chars().last(), Some('{' | ','));
+            }
+            '{' => closers.push('}'),
+            '[' => closers.push(']'),
+            '}' | ']' => {
+                removed_commas |= remove_trailing_comma(&mut out);
+                if closers.last() == Some(&c) {
+                    closers.pop();
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+
+    if in_string {
+        if escaped {
+            out.pop();
+        }
+        out.push('"');
+        if string_is_key {
+            out.push(':');
+        }
+        repairs.push(JsonRepair::ClosedString);
+    }
+    if !closers.is_empty() {
+        // The text stopped after a comma or a key: drop the comma, give the key a value
+        removed_commas |= remove_trailing_comma(&mut out);
+        if out.trim_end().ends_with(':') {
+            out.push_str(" null");
+        }
+        out.extend(closers.iter().rev());
+        repairs.push(JsonRepair::ClosedBrackets);
+    }
+    if removed_commas {
+        repairs.push(JsonRepair::RemovedTrailingCommas);
+    }
+    out
+}
+
+/// Remove a comma, and the whitespace after it, from the end of `out`
+fn remove_trailing_comma(out: &mut String) -> bool {
+    let trimmed = out.trim_end();
+    if trimmed.ends_with(',') {
+        out.truncate(trimmed.len() - 1);
+        true
+    } else {
+        false
+    }
+}
@@ -31,42 +31,98 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 pub mod agents;
+pub mod attachment;
+pub mod audit;
+pub mod budget;
+pub mod capture;
+pub mod clock;
+pub mod compare;
 pub mod document_loader;
 pub mod embeddings;
+pub mod env_config;
 pub mod errors;
+pub mod expression;
+pub mod external_event;
 pub mod graph;
+pub mod guardrail_node;
+pub mod http_client;
+pub mod json_repair;
 pub mod llm;
 pub mod memory;
+pub mod output_store;
+pub mod pacing;
+pub(crate) mod pii;
+pub mod post_process;
+#[cfg(feature = "python")]
+pub mod python_code;
+pub mod rate_limit;
+pub mod report;
+pub mod result_sink;
+pub mod run_history;
+pub mod secret_ref;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shell_command;
+pub mod shutdown;
 pub mod stream;
+pub mod telemetry;
+pub mod template;
 pub mod text_splitter;
+pub mod tools;
+pub mod transcript;
 pub mod types;
+pub mod usage;
 pub mod validation;
 pub mod workflow;
 
 // Re-export important types for convenience - only keep what's actually used
 pub use agents::{Agent, AgentBuilder, AgentConfig, AgentTrait};
+pub use attachment::{Attachment, AttachmentData};
+pub use audit::{AuditLog, AuditPolicy, AuditSink, JsonlAuditSink};
+pub use budget::{BudgetPolicy, ExecutionBudget};
+pub use compare::{CompareOptions, CompareThresholds, NodeComparison, RunComparison};
 pub use document_loader::{DocumentContent, DocumentLoader, DocumentLoaderConfig};
 pub use embeddings::{
     EmbeddingConfig, EmbeddingProvider, EmbeddingRequest, EmbeddingResponse, EmbeddingService,
 };
+pub use env_config::EnvConfig;
 pub use errors::{GraphBitError, GraphBitResult};
-pub use graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowGraph, WorkflowNode};
-pub use llm::{LlmConfig, LlmProvider, LlmResponse};
+pub use expression::{Expression, ExpressionError, ExpressionErrorKind, ExpressionScope};
+pub use external_event::ExternalEvents;
+pub use graph::{
+    AgentNodeConfig, NodeType, NodeTypeKind, WorkflowEdge, WorkflowGraph, WorkflowNode,
+};
+pub use guardrail_node::{GuardrailAction, GuardrailCheck, GuardrailReport, GuardrailViolation};
+pub use http_client::{HttpClientFactory, HttpSettings, RequestTimeouts};
+pub use json_repair::{JsonRepair, RepairedJson, repair_json};
+pub use llm::{LlmConfig, LlmProvider, LlmResponse, ProviderHealth};
 pub use memory::{
     Memory, MemoryConfig, MemoryHistory, MemoryId, MemoryScope, MemoryService, ScoredMemory,
 };
+pub use output_store::{FileOutputStore, OutputRef, OutputSpill, OutputStore};
+pub use report::{ExecutionReport, NodeReport, ReportConfig, ReportEdge};
+pub use result_sink::{ResultRecord, ResultSink};
+pub use run_history::{RunHistory, RunQuery, RunRecord, RunRetention, RunState};
+pub use secret_ref::SecretRef;
+pub use shutdown::{InterruptedExecution, ShutdownHandle, ShutdownSummary};
 pub use stream::{StreamEvent, StreamMode, error_type_from_graphbit_error, error_type_from_string};
+pub use template::{ParameterType, TemplateParameter, TemplateRegistry, WorkflowTemplate};
+pub use transcript::NodeTranscript;
 pub use text_splitter::{
-    CharacterSplitter, RecursiveSplitter, SentenceSplitter, SplitterStrategy, TextChunk,
-    TextSplitterConfig, TextSplitterFactory, TextSplitterTrait, TokenSplitter,
+    CharacterSplitter, CodeSplitter, DocumentChunkConfig, MarkdownSplitter, RecursiveSplitter,
+    SentenceSplitter, SplitterStrategy, TextChunk, TextSplitterConfig, TextSplitterFactory,
+    TextSplitterTrait, TokenSplitter, chunk_document,
 };
 pub use types::{
-    AgentCapability, AgentId, AgentMessage, MessageContent, NodeExecutionResult, NodeId,
-    WorkflowContext, WorkflowExecutionStats, WorkflowId, WorkflowState,
+    AgentCapability, AgentId, AgentMessage, FailureReason, MessageContent, NodeExecutionRecord,
+    NodeExecutionResult, NodeId, OutputValidationReport, PromptDefaults, Secrets, ToolCallRecord,
+    ToolCallTraceConfig, WorkflowContext, WorkflowExecutionStats, WorkflowId, WorkflowState,
 };
+pub use usage::{UsageBreakdown, UsageCost, UsageSummary};
 pub use validation::ValidationResult;
 pub use workflow::{
-    ConditionRoutingInput, ConditionalRouteFn, Workflow, WorkflowBuilder, WorkflowExecutor,
+    ConditionRoutingInput, ConditionalRouteFn, CostEstimate, DryRunReport, NodeCostEstimate,
+    ProviderWarmUp, RenderedPrompt, WarmUpReport, Workflow, WorkflowBuilder, WorkflowExecutor,
 };
 
 // Re-export guardrail types (from prebuilt libguardrail_ffi.a via guardrail_ffi crate)
@@ -1,6 +1,7 @@
 //! `AI21` LLM provider implementation (Jamba / Chat + function calling)
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -24,15 +25,7 @@ pub struct Ai21Provider {
 impl Ai21Provider {
     /// Create a new `AI21` Provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("ai21", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         // Base URL for AI21 chat API (Jamba)
         let base_url = "https://api.ai21.com/studio/v1".to_string();
         Ok(Self {
@@ -46,15 +39,7 @@ impl Ai21Provider {
 
     /// Create a new `AI21` provider with custom base url
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("ai21", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         Ok(Self {
             client,
             api_key,
@@ -64,6 +49,17 @@ impl Ai21Provider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("ai21", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Create a new `AI21` provider with custom organization
     pub fn with_organization(mut self, org: String) -> Self {
         self.organization = Some(org);
@@ -236,10 +232,9 @@ impl LlmProviderTrait for Ai21Provider {
             builder = builder.header("Ai21-Organization", org);
         }
 
-        let resp = builder
-            .send()
+        let resp = crate::capture::send("ai21", builder)
             .await
-            .map_err(|e| GraphBitError::llm_provider("ai21", format!("Request failed: {e}")))?;
+            .map_err(|e| GraphBitError::from_request_error("ai21", &e))?;
 
         if !resp.status().is_success() {
             let text = resp
@@ -322,7 +317,7 @@ impl LlmProviderTrait for Ai21Provider {
         let response = timeout(CONNECTION_TIMEOUT, builder.send())
             .await
             .map_err(|_| {
-                GraphBitError::llm_provider(
+                GraphBitError::timeout(
                     "ai21",
                     format!(
                         "Connection timeout after {:?} - AI21 did not respond. \
@@ -331,7 +326,7 @@ impl LlmProviderTrait for Ai21Provider {
                     ),
                 )
             })?
-            .map_err(|e| GraphBitError::llm_provider("ai21", format!("Request failed: {e}")))?;
+            .map_err(|e| GraphBitError::from_request_error("ai21", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -475,7 +470,7 @@ impl LlmProviderTrait for Ai21Provider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "ai21",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
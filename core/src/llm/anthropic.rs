@@ -1,7 +1,9 @@
 //! `Anthropic` `Claude` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
+use crate::llm::tool_schema::native_parameters;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
 };
@@ -23,7 +25,7 @@ pub struct AnthropicProvider {
 impl AnthropicProvider {
     /// Create a new `Anthropic` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        let client = Client::new();
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.anthropic.com/v1".to_string();
 
         Ok(Self {
@@ -34,6 +36,50 @@ impl AnthropicProvider {
         })
     }
 
+    /// Create a new `Anthropic` provider with a custom base URL
+    pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
+        let client = Self::http_client(RequestTimeouts::none())?;
+
+        Ok(Self {
+            client,
+            api_key,
+            model,
+            base_url,
+        })
+    }
+
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("anthropic", None, None, timeouts)
+    }
+
+    /// Largest number of output tokens the model accepts
+    ///
+    /// `Anthropic` requires `max_tokens` on every request; it defaults to this
+    /// limit when the request leaves it unset.
+    fn max_output_tokens(&self) -> u32 {
+        let model = self.model.as_str();
+        if model.starts_with("claude-opus-4-5")
+            || model.starts_with("claude-sonnet-4")
+            || model.starts_with("claude-haiku-4")
+            || model.starts_with("claude-3-7-sonnet")
+        {
+            64_000
+        } else if model.starts_with("claude-opus-4") {
+            32_000
+        } else if model.starts_with("claude-3-5") {
+            8_192
+        } else {
+            4_096
+        }
+    }
+
     /// Convert `GraphBit` tool to `Anthropic` tool format.
     /// When `with_cache` is `true`, adds a `cache_control` breakpoint so Anthropic
     /// caches the entire tool-definition block up to this point.
@@ -41,7 +87,7 @@ impl AnthropicProvider {
         AnthropicTool {
             name: tool.name.clone(),
             description: tool.description.clone(),
-            input_schema: tool.parameters.clone(),
+            input_schema: native_parameters("anthropic", tool),
             cache_control: if with_cache {
                 Some(CacheControl::ephemeral())
             } else {
@@ -55,6 +101,7 @@ impl AnthropicProvider {
     /// Anthropic uses structured content blocks for tool interactions:
     /// - Assistant tool calls → `tool_use` content blocks
     /// - Tool results → `user` message with `tool_result` content blocks
+    /// - User messages with images → `image` content blocks followed by a `text` block
     ///
     /// When `enable_caching` is `true`, the system prompt is returned as a JSON
     /// content-block array with a `cache_control` breakpoint so Anthropic caches it.
@@ -70,12 +117,37 @@ impl AnthropicProvider {
                 LlmRole::System => {
                     system_text = Some(message.content.clone());
                 }
-                LlmRole::User => {
+                LlmRole::User if message.images.is_empty() => {
                     anthropic_messages.push(AnthropicMessage {
                         role: "user".to_string(),
                         content: serde_json::Value::String(message.content.clone()),
                     });
                 }
+                LlmRole::User => {
+                    // Images go in content blocks before the text
+                    let mut content_blocks: Vec<serde_json::Value> = message
+                        .images
+                        .iter()
+                        .map(|image| {
+                            serde_json::json!({
+                                "type": "image",
+                                "source": {
+                                    "type": "base64",
+                                    "media_type": image.mime_type,
+                                    "data": image.data
+                                }
+                            })
+                        })
+                        .collect();
+                    content_blocks.push(serde_json::json!({
+                        "type": "text",
+                        "text": message.content
+                    }));
+                    anthropic_messages.push(AnthropicMessage {
+                        role: "user".to_string(),
+                        content: serde_json::Value::Array(content_blocks),
+                    });
+                }
                 LlmRole::Assistant => {
                     if message.tool_calls.is_empty() {
                         // Plain assistant message
@@ -115,16 +187,27 @@ impl AnthropicProvider {
                     // Tool result → user message with tool_result content block
                     let tool_use_id = message.tool_call_id.clone().unwrap_or_default();
 
-                    let content_block = serde_json::json!([{
+                    let content_block = serde_json::json!({
                         "type": "tool_result",
                         "tool_use_id": tool_use_id,
                         "content": message.content
-                    }]);
-
-                    anthropic_messages.push(AnthropicMessage {
-                        role: "user".to_string(),
-                        content: content_block,
                     });
+
+                    // The results of parallel tool calls go back in a single user message
+                    match anthropic_messages.last_mut() {
+                        Some(AnthropicMessage {
+                            role,
+                            content: serde_json::Value::Array(blocks),
+                        }) if role == "user"
+                            && blocks.iter().all(|block| block["type"] == "tool_result") =>
+                        {
+                            blocks.push(content_block);
+                        }
+                        _ => anthropic_messages.push(AnthropicMessage {
+                            role: "user".to_string(),
+                            content: serde_json::Value::Array(vec![content_block]),
+                        }),
+                    }
                 }
             }
         }
@@ -179,13 +262,7 @@ impl AnthropicProvider {
             }
         }
 
-        let finish_reason = match response.stop_reason.as_deref() {
-            Some("end_turn" | "stop_sequence") => FinishReason::Stop,
-            Some("max_tokens") => FinishReason::Length,
-            Some("tool_use") => FinishReason::Other("tool_use".into()),
-            Some(other) => FinishReason::Other(other.to_string()),
-            None => FinishReason::Stop,
-        };
+        let finish_reason = finish_reason(response.stop_reason.as_deref());
 
         let usage = LlmUsage::new_with_cache(
             response.usage.input_tokens,
@@ -258,7 +335,9 @@ impl LlmProviderTrait for AnthropicProvider {
 
         let body = AnthropicRequest {
             model: self.model.clone(),
-            max_tokens: request.max_tokens.unwrap_or(4096),
+            max_tokens: request
+                .max_tokens
+                .unwrap_or_else(|| self.max_output_tokens()),
             messages,
             system: system_prompt,
             temperature: request.temperature,
@@ -284,9 +363,9 @@ impl LlmProviderTrait for AnthropicProvider {
                 req_builder.header("anthropic-beta", "prompt-caching-2024-07-31");
         }
 
-        let response = req_builder.json(&body).send().await.map_err(|e| {
-            GraphBitError::llm_provider("anthropic", format!("Request failed: {e}"))
-        })?;
+        let response = crate::capture::send("anthropic", req_builder.json(&body))
+            .await
+            .map_err(|e| GraphBitError::from_request_error("anthropic", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -345,7 +424,9 @@ impl LlmProviderTrait for AnthropicProvider {
 
         let body = AnthropicStreamRequest {
             model: self.model.clone(),
-            max_tokens: request.max_tokens.unwrap_or(4096),
+            max_tokens: request
+                .max_tokens
+                .unwrap_or_else(|| self.max_output_tokens()),
             messages,
             system: system_prompt,
             temperature: request.temperature,
@@ -375,7 +456,7 @@ impl LlmProviderTrait for AnthropicProvider {
         let response = timeout(CONNECTION_TIMEOUT, stream_req.json(&body).send())
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "anthropic",
                 format!(
                     "Connection timeout after {:?} - Anthropic did not respond. \
@@ -384,7 +465,7 @@ impl LlmProviderTrait for AnthropicProvider {
                 ),
             )
         })?
-        .map_err(|e| GraphBitError::llm_provider("anthropic", format!("Request failed: {e}")))?;
+        .map_err(|e| GraphBitError::from_request_error("anthropic", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -407,88 +488,35 @@ impl LlmProviderTrait for AnthropicProvider {
         let model = self.model.clone();
         let byte_stream = response.bytes_stream();
 
-        // State: (byte_stream, buffer, timeout_occurred, consecutive_parse_errors, total_parse_errors)
+        // State: (byte_stream, buffer, done, consecutive_parse_errors, total_parse_errors, tool_use)
         const MAX_CONSECUTIVE_PARSE_ERRORS: u32 = 5;
 
         let stream = futures::stream::unfold(
-            (byte_stream, String::new(), false, 0u32, 0u32),
+            (
+                byte_stream,
+                String::new(),
+                false,
+                0u32,
+                0u32,
+                StreamedToolUse::default(),
+            ),
             move |(
                 mut byte_stream,
                 mut buffer,
-                timeout_occurred,
+                done,
                 mut consecutive_parse_errors,
                 mut total_parse_errors,
+                mut tool_use,
             )| {
                 let model = model.clone();
                 async move {
-                    // If we already had a timeout, don't continue
-                    if timeout_occurred {
+                    // Stop after a timeout, an error event or the final tool call chunk
+                    if done {
                         return None;
                     }
 
                     loop {
-                        // Apply timeout to each chunk read
-                        let chunk_result = match timeout(CHUNK_TIMEOUT, byte_stream.next()).await {
-                            Ok(Some(result)) => result,
-                            Ok(None) => {
-                                // Stream naturally ended
-                                if total_parse_errors > 0 {
-                                    tracing::warn!(
-                                        "Stream ended with {} total parse errors. Some data may have been lost.",
-                                        total_parse_errors
-                                    );
-                                }
-                                return None;
-                            }
-                            Err(_) => {
-                                // Timeout occurred
-                                tracing::warn!(
-                                    "Stream chunk timeout after {:?} - Anthropic stopped responding. \
-                                     Response may be incomplete.",
-                                    CHUNK_TIMEOUT
-                                );
-                                return Some((
-                                    Err(GraphBitError::llm_provider(
-                                        "anthropic",
-                                        format!(
-                                            "Stream timeout after {:?} - response may be incomplete",
-                                            CHUNK_TIMEOUT
-                                        ),
-                                    )),
-                                    (
-                                        byte_stream,
-                                        buffer,
-                                        true,
-                                        consecutive_parse_errors,
-                                        total_parse_errors,
-                                    ),
-                                ));
-                            }
-                        };
-
-                        let chunk = match chunk_result {
-                            Ok(c) => c,
-                            Err(e) => {
-                                return Some((
-                                    Err(GraphBitError::llm_provider(
-                                        "anthropic",
-                                        format!("Stream error: {e}"),
-                                    )),
-                                    (
-                                        byte_stream,
-                                        buffer,
-                                        false,
-                                        consecutive_parse_errors,
-                                        total_parse_errors,
-                                    ),
-                                ));
-                            }
-                        };
-
-                        // Append new data to buffer
-                        buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                        // Process complete lines
+                        // Process the complete lines already buffered, then read more
                         while let Some(newline_pos) = buffer.find('\n') {
                             let line: String = buffer.drain(..=newline_pos).collect();
                             let line = line.trim();
@@ -499,7 +527,8 @@ impl LlmProviderTrait for AnthropicProvider {
                             }
 
                             // Anthropic SSE format: "event: <event_type>" followed by "data: <json>"
-                            // We primarily care about content_block_delta events with text deltas
+                            // Text deltas are yielded as they arrive; tool_use blocks are collected
+                            // and yielded as one final chunk when the message stops
                             if let Some(data) = line.strip_prefix("data: ") {
                                 // Parse the JSON data
                                 match serde_json::from_str::<AnthropicStreamEvent>(data) {
@@ -507,10 +536,28 @@ impl LlmProviderTrait for AnthropicProvider {
                                         consecutive_parse_errors = 0;
 
                                         match event.r#type.as_str() {
+                                            "content_block_start" => {
+                                                if let Some(block) = &event.content_block {
+                                                    tool_use.start_block(block);
+                                                }
+                                            }
+                                            "message_delta" => {
+                                                if let Some(delta) = &event.delta {
+                                                    if delta.stop_reason.is_some() {
+                                                        tool_use
+                                                            .stop_reason
+                                                            .clone_from(&delta.stop_reason);
+                                                    }
+                                                }
+                                            }
                                             "content_block_delta" => {
                                                 // Extract text from delta
                                                 if let Some(delta) = &event.delta {
-                                                    if delta.r#type == "text_delta" {
+                                                    if delta.r#type == "input_json_delta" {
+                                                        if let Some(json) = &delta.partial_json {
+                                                            tool_use.push_input(json);
+                                                        }
+                                                    } else if delta.r#type == "text_delta" {
                                                         if let Some(text) = &delta.text {
                                                             if !text.is_empty() {
                                                                 let response = LlmResponse::new(
@@ -525,6 +572,7 @@ impl LlmProviderTrait for AnthropicProvider {
                                                                         false,
                                                                         consecutive_parse_errors,
                                                                         total_parse_errors,
+                                                                        tool_use,
                                                                     ),
                                                                 ));
                                                             }
@@ -540,7 +588,19 @@ impl LlmProviderTrait for AnthropicProvider {
                                                         total_parse_errors
                                                     );
                                                 }
-                                                return None;
+                                                let response = std::mem::take(&mut tool_use)
+                                                    .into_response(&model)?;
+                                                return Some((
+                                                    Ok(response),
+                                                    (
+                                                        byte_stream,
+                                                        buffer,
+                                                        true,
+                                                        consecutive_parse_errors,
+                                                        total_parse_errors,
+                                                        tool_use,
+                                                    ),
+                                                ));
                                             }
                                             "error" => {
                                                 // Handle error event
@@ -560,11 +620,11 @@ impl LlmProviderTrait for AnthropicProvider {
                                                         true,
                                                         consecutive_parse_errors,
                                                         total_parse_errors,
+                                                        tool_use,
                                                     ),
                                                 ));
                                             }
-                                            // message_start, content_block_start, content_block_stop,
-                                            // message_delta, ping - ignore these
+                                            // message_start, content_block_stop, ping - ignore these
                                             _ => {}
                                         }
                                     }
@@ -597,6 +657,7 @@ impl LlmProviderTrait for AnthropicProvider {
                                                     true,
                                                     consecutive_parse_errors,
                                                     total_parse_errors,
+                                                    tool_use,
                                                 ),
                                             ));
                                         }
@@ -604,6 +665,69 @@ impl LlmProviderTrait for AnthropicProvider {
                                 }
                             }
                         }
+
+                        // Apply timeout to each chunk read
+                        let chunk_result = match timeout(CHUNK_TIMEOUT, byte_stream.next()).await {
+                            Ok(Some(result)) => result,
+                            Ok(None) => {
+                                // Stream naturally ended
+                                if total_parse_errors > 0 {
+                                    tracing::warn!(
+                                        "Stream ended with {} total parse errors. Some data may have been lost.",
+                                        total_parse_errors
+                                    );
+                                }
+                                return None;
+                            }
+                            Err(_) => {
+                                // Timeout occurred
+                                tracing::warn!(
+                                    "Stream chunk timeout after {:?} - Anthropic stopped responding. \
+                                     Response may be incomplete.",
+                                    CHUNK_TIMEOUT
+                                );
+                                return Some((
+                                    Err(GraphBitError::timeout(
+                                        "anthropic",
+                                        format!(
+                                            "Stream timeout after {:?} - response may be incomplete",
+                                            CHUNK_TIMEOUT
+                                        ),
+                                    )),
+                                    (
+                                        byte_stream,
+                                        buffer,
+                                        true,
+                                        consecutive_parse_errors,
+                                        total_parse_errors,
+                                        tool_use,
+                                    ),
+                                ));
+                            }
+                        };
+
+                        let chunk = match chunk_result {
+                            Ok(c) => c,
+                            Err(e) => {
+                                return Some((
+                                    Err(GraphBitError::llm_provider(
+                                        "anthropic",
+                                        format!("Stream error: {e}"),
+                                    )),
+                                    (
+                                        byte_stream,
+                                        buffer,
+                                        false,
+                                        consecutive_parse_errors,
+                                        total_parse_errors,
+                                        tool_use,
+                                    ),
+                                ));
+                            }
+                        };
+
+                        // Append new data to buffer
+                        buffer.push_str(&String::from_utf8_lossy(&chunk));
                     }
                 }
             },
@@ -615,6 +739,20 @@ impl LlmProviderTrait for AnthropicProvider {
     fn supports_streaming(&self) -> bool {
         true // Anthropic supports streaming
     }
+
+    fn supports_image_inputs(&self) -> bool {
+        true
+    }
+}
+
+/// Map an `Anthropic` `stop_reason` to a [`FinishReason`]
+fn finish_reason(stop_reason: Option<&str>) -> FinishReason {
+    match stop_reason {
+        Some("end_turn" | "stop_sequence") | None => FinishReason::Stop,
+        Some("max_tokens") => FinishReason::Length,
+        Some("tool_use") => FinishReason::ToolCalls,
+        Some(other) => FinishReason::Other(other.to_string()),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -718,22 +856,87 @@ struct AnthropicStreamRequest {
 struct AnthropicStreamEvent {
     #[serde(rename = "type")]
     r#type: String,
-    /// Present in content_block_delta events
+    /// Present in `content_block_delta` and `message_delta` events
     #[serde(default)]
     delta: Option<StreamDelta>,
+    /// Present in `content_block_start` events
+    #[serde(default)]
+    content_block: Option<ContentBlock>,
     /// Present in error events
     #[serde(default)]
     error: Option<StreamError>,
 }
 
-/// Delta content in a content_block_delta event
+/// Delta content in a `content_block_delta` or `message_delta` event
 #[derive(Debug, Deserialize)]
 struct StreamDelta {
-    #[serde(rename = "type")]
-    r#type: String, // "text_delta", "input_json_delta", etc.
+    /// `text_delta`, `input_json_delta`, etc.; absent in `message_delta` events
+    #[serde(rename = "type", default)]
+    r#type: String,
     /// Text content (present when type == "text_delta")
     #[serde(default)]
     text: Option<String>,
+    /// Fragment of a tool call's input JSON (present when type == `input_json_delta`)
+    #[serde(default)]
+    partial_json: Option<String>,
+    /// Why the message stopped (present in `message_delta` events)
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+/// `tool_use` blocks collected over a stream, with their input JSON as received
+#[derive(Debug, Default)]
+struct StreamedToolUse {
+    /// (id, name, input JSON) of each `tool_use` block started so far
+    calls: Vec<(String, String, String)>,
+    stop_reason: Option<String>,
+}
+
+impl StreamedToolUse {
+    fn start_block(&mut self, block: &ContentBlock) {
+        if block.r#type == "tool_use" {
+            self.calls.push((
+                block.id.clone().unwrap_or_default(),
+                block.name.clone().unwrap_or_default(),
+                String::new(),
+            ));
+        }
+    }
+
+    /// Append an `input_json_delta` fragment to the latest `tool_use` block
+    fn push_input(&mut self, fragment: &str) {
+        if let Some((_, _, input)) = self.calls.last_mut() {
+            input.push_str(fragment);
+        }
+    }
+
+    /// Final chunk carrying the collected tool calls, or `None` without any
+    fn into_response(self, model: &str) -> Option<LlmResponse> {
+        if self.calls.is_empty() {
+            return None;
+        }
+        let tool_calls = self
+            .calls
+            .into_iter()
+            .map(|(id, name, input)| LlmToolCall {
+                id,
+                name,
+                // A tool without parameters streams no input at all
+                parameters: if input.trim().is_empty() {
+                    serde_json::json!({})
+                } else {
+                    serde_json::from_str(&input).unwrap_or(serde_json::Value::String(input))
+                },
+            })
+            .collect();
+        Some(
+            LlmResponse::new(String::new(), model)
+                .with_tool_calls(tool_calls)
+                .with_finish_reason(finish_reason(
+                    self.stop_reason.as_deref().or(Some("tool_use")),
+                )),
+        )
+    }
 }
 
 /// Error information in an error event
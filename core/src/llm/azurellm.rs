@@ -5,6 +5,7 @@
 //! This provider supports all Azure-deployed models,not just OpenAI models.
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -12,6 +13,7 @@ use crate::llm::{
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::time::Duration;
 
 /// `Azure LLM` API provider
 pub struct AzureLlmProvider {
@@ -30,19 +32,7 @@ impl AzureLlmProvider {
         endpoint: String,
         api_version: String,
     ) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(120)) // Increased timeout for Azure LLM
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "azurellm",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -53,6 +43,17 @@ impl AzureLlmProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("azurellm", Some(Duration::from_secs(120)), None, timeouts)
+    }
+
     /// Create a new `Azure LLM` provider with default API version
     pub fn with_defaults(
         api_key: String,
@@ -205,15 +206,16 @@ impl AzureLlmProvider {
         tracing::debug!("Responses API request URL: {}", url);
         tracing::debug!("Responses API request body: {}", body);
 
-        let response = self
-            .client
-            .post(&url)
-            .header("api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| GraphBitError::llm_provider("azurellm", format!("Request failed: {e}")))?;
+        let response = crate::capture::send(
+            "azurellm",
+            self.client
+                .post(&url)
+                .header("api-key", &self.api_key)
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("azurellm", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -501,15 +503,16 @@ impl LlmProviderTrait for AzureLlmProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("api-key", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| GraphBitError::llm_provider("azurellm", format!("Request failed: {e}")))?;
+        let response = crate::capture::send(
+            "azurellm",
+            self.client
+                .post(&url)
+                .header("api-key", &self.api_key)
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("azurellm", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -726,6 +729,7 @@ mod tests {
             content: "Hello, world!".to_string(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         };
 
         let azure_message = AzureLlmProvider::convert_message(&message);
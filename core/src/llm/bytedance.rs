@@ -4,6 +4,7 @@
 //! It uses an OpenAI-compatible API format for easy integration.
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -11,6 +12,7 @@ use crate::llm::{
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// `ByteDance ModelArk` API provider
 pub struct ByteDanceProvider {
@@ -23,19 +25,7 @@ pub struct ByteDanceProvider {
 impl ByteDanceProvider {
     /// Create a new `ByteDance ModelArk` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "bytedance",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://ark.ap-southeast.bytepluses.com/api/v3".to_string();
 
         Ok(Self {
@@ -48,19 +38,7 @@ impl ByteDanceProvider {
 
     /// Create a new `ByteDance ModelArk` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "bytedance",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -70,6 +48,17 @@ impl ByteDanceProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("bytedance", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `ByteDance` message format
     fn convert_message(message: &LlmMessage) -> ByteDanceMessage {
         ByteDanceMessage {
@@ -200,17 +189,16 @@ impl LlmProviderTrait for ByteDanceProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("bytedance", format!("Request failed: {e}"))
-            })?;
+        let response = crate::capture::send(
+            "bytedance",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("bytedance", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -384,6 +372,7 @@ mod tests {
             content: "Hello, world!".to_string(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         };
 
         let bytedance_message = ByteDanceProvider::convert_message(&message);
@@ -399,6 +388,7 @@ mod tests {
             content: "Hello! How can I help you?".to_string(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         };
 
         let bytedance_message = ByteDanceProvider::convert_message(&message);
@@ -414,6 +404,7 @@ mod tests {
             content: "You are a helpful assistant.".to_string(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         };
 
         let bytedance_message = ByteDanceProvider::convert_message(&message);
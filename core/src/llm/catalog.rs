@@ -0,0 +1,162 @@
+//! Model catalogs and error responses of OpenAI-compatible model hosts
+//!
+//! Hosts such as `Fireworks AI` and `TogetherAI` serve the `OpenAI` chat
+//! completions API, but list their models and report errors each in their own
+//! shape: a `{"data": [...]}` list or a bare array of models, and errors as an
+//! `OpenAI` style `{"error": {...}}` object, a plain `{"error": "..."}` string,
+//! or a top-level `message` or `detail`. The helpers here read all of them.
+
+use reqwest::{Client, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::retry_after_seconds;
+
+/// A model listed in a provider's catalog
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// ID to configure the provider with
+    pub id: String,
+    /// Human readable name, when the provider gives one
+    pub display_name: Option<String>,
+    /// Organization publishing or serving the model
+    pub organization: Option<String>,
+    /// Kind of model, such as `chat`, `language` or `embedding`
+    pub model_type: Option<String>,
+    /// Context window in tokens
+    pub context_length: Option<u32>,
+    /// Whether the model accepts tools, when the provider says
+    pub supports_tools: Option<bool>,
+}
+
+/// Catalog entry as either provider sends it
+#[derive(Debug, Deserialize)]
+struct RawModel {
+    id: String,
+    display_name: Option<String>,
+    organization: Option<String>,
+    owned_by: Option<String>,
+    #[serde(rename = "type")]
+    model_type: Option<String>,
+    kind: Option<String>,
+    context_length: Option<u64>,
+    supports_tools: Option<bool>,
+}
+
+impl From<RawModel> for ModelInfo {
+    fn from(raw: RawModel) -> Self {
+        Self {
+            id: raw.id,
+            display_name: raw.display_name,
+            organization: raw.organization.or(raw.owned_by),
+            model_type: raw.model_type.or(raw.kind),
+            context_length: raw
+                .context_length
+                .and_then(|length| u32::try_from(length).ok()),
+            supports_tools: raw.supports_tools,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Catalog {
+    List { data: Vec<RawModel> },
+    Models(Vec<RawModel>),
+}
+
+/// Parse a models endpoint response
+pub(crate) fn parse_catalog(provider: &str, body: &str) -> GraphBitResult<Vec<ModelInfo>> {
+    let catalog: Catalog = serde_json::from_str(body).map_err(|e| {
+        GraphBitError::llm_provider(provider, format!("Failed to parse models response: {e}"))
+    })?;
+    let models = match catalog {
+        Catalog::List { data } => data,
+        Catalog::Models(models) => models,
+    };
+    Ok(models.into_iter().map(ModelInfo::from).collect())
+}
+
+/// List the models of `provider` at `url`, its models endpoint
+pub(crate) async fn fetch_catalog(
+    client: &Client,
+    provider: &str,
+    url: &str,
+    api_key: &str,
+) -> GraphBitResult<Vec<ModelInfo>> {
+    let response = client
+        .get(url)
+        .header("Authorization", format!("Bearer {api_key}"))
+        .send()
+        .await
+        .map_err(|e| GraphBitError::from_request_error(provider, &e))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(provider, response).await);
+    }
+    let body = response.text().await.map_err(|e| {
+        GraphBitError::llm_provider(provider, format!("Failed to read models response: {e}"))
+    })?;
+    parse_catalog(provider, &body)
+}
+
+/// The entry for `model` in `models`, or an error naming the model when the
+/// catalog does not list it
+pub(crate) fn find_model(
+    models: Vec<ModelInfo>,
+    provider: &str,
+    model: &str,
+) -> GraphBitResult<ModelInfo> {
+    models
+        .into_iter()
+        .find(|info| info.id == model)
+        .ok_or_else(|| {
+            GraphBitError::llm_provider(
+                provider,
+                format!("Model '{model}' is not in the provider's model catalog"),
+            )
+        })
+}
+
+/// Error for a non-success response, with the message taken from its body
+pub(crate) async fn error_from_response(provider: &str, response: Response) -> GraphBitError {
+    let status = response.status().as_u16();
+    let retry_after = retry_after_seconds(response.headers());
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    GraphBitError::from_http_status(provider, status, retry_after, error_message(&body))
+}
+
+/// Message of an error response body, followed by its code or type in
+/// parentheses when there is one; the body itself when it has no known shape
+pub(crate) fn error_message(body: &str) -> String {
+    let body = body.trim();
+    let Ok(serde_json::Value::Object(object)) = serde_json::from_str(body) else {
+        return if body.is_empty() {
+            "Unknown error".to_string()
+        } else {
+            body.to_string()
+        };
+    };
+
+    let text = |value: Option<&serde_json::Value>| {
+        value
+            .and_then(serde_json::Value::as_str)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let message = match object.get("error") {
+        Some(serde_json::Value::Object(error)) => text(error.get("message")).map(|message| {
+            match text(error.get("code")).or_else(|| text(error.get("type"))) {
+                Some(code) => format!("{message} ({code})"),
+                None => message,
+            }
+        }),
+        error => text(error),
+    };
+    message
+        .or_else(|| text(object.get("message")))
+        .or_else(|| text(object.get("detail")))
+        .unwrap_or_else(|| body.to_string())
+}
@@ -0,0 +1,330 @@
+//! Context-window management for LLM requests
+//!
+//! Before an agent node's request is dispatched, its size is estimated and
+//! compared with the model's context window (the provider's
+//! [`max_context_length`](super::LlmProviderTrait::max_context_length) or the
+//! node's `context_window` override) minus the tokens reserved for the
+//! completion. When it does not fit, the node's [`ContextStrategy`] either
+//! truncates the request or fails with the exact overflow instead of letting
+//! the provider reject it.
+//!
+//! Token counts are estimates: about four ASCII characters per token, one token
+//! per other character, plus a small per-message overhead. They err on the high
+//! side for English text.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{LlmMessage, LlmProviderTrait, LlmRequest, LlmRole};
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Tokens counted for the role and separators of every message
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Tokens counted for priming the reply
+const REPLY_OVERHEAD_TOKENS: usize = 3;
+
+/// Estimate the number of tokens in `text`
+#[must_use]
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) = text.chars().fold((0, 0), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    (ascii + 3) / 4 + other
+}
+
+/// Estimate the number of prompt tokens of `request`: its messages, their tool
+/// calls and the tool schemas
+#[must_use]
+pub fn estimate_request_tokens(request: &LlmRequest) -> usize {
+    let messages: usize = request.messages.iter().map(estimate_message_tokens).sum();
+    let tools: usize = request
+        .tools
+        .iter()
+        .map(|tool| estimate_tokens(&serde_json::to_string(tool).unwrap_or_default()))
+        .sum();
+    messages + tools + REPLY_OVERHEAD_TOKENS
+}
+
+pub(super) fn estimate_message_tokens(message: &LlmMessage) -> usize {
+    let tool_calls: usize = message
+        .tool_calls
+        .iter()
+        .map(|call| estimate_tokens(&call.name) + estimate_tokens(&call.parameters.to_string()))
+        .sum();
+    MESSAGE_OVERHEAD_TOKENS + estimate_tokens(&message.content) + tool_calls
+}
+
+/// What to do with a request that does not fit the context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextStrategy {
+    /// Drop the oldest conversation messages, keeping system messages, the latest
+    /// user message and the latest tool exchange
+    TruncateHistory,
+    /// Cut the middle out of the largest messages, where parent outputs and tool
+    /// results are injected, keeping their beginning and end
+    TruncateDocuments,
+    /// Fail the node with an error stating the overflow
+    Fail,
+}
+
+impl ContextStrategy {
+    /// Name used in node configurations
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::TruncateHistory => "truncate_history",
+            Self::TruncateDocuments => "truncate_documents",
+            Self::Fail => "fail",
+        }
+    }
+
+    /// The `context_strategy` of a node configuration, if set
+    pub fn from_node_config(
+        config: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<Option<Self>> {
+        match config.get("context_strategy") {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(serde_json::Value::String(name)) => name.parse().map(Some),
+            Some(other) => Err(GraphBitError::validation(
+                "context_strategy",
+                format!("Expected a strategy name, got {other}"),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ContextStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContextStrategy {
+    type Err = GraphBitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "truncate_history" => Ok(Self::TruncateHistory),
+            "truncate_documents" => Ok(Self::TruncateDocuments),
+            "fail" => Ok(Self::Fail),
+            other => Err(GraphBitError::validation(
+                "context_strategy",
+                format!(
+                    "Unknown context strategy '{other}', expected truncate_history, truncate_documents or fail"
+                ),
+            )),
+        }
+    }
+}
+
+/// Outcome of fitting a request into a context window, recorded in the node metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextFit {
+    /// Strategy applied
+    pub strategy: ContextStrategy,
+    /// Context window of the model, in tokens
+    pub context_window: u32,
+    /// Tokens reserved for the completion (the request's `max_tokens`)
+    pub reserved_tokens: u32,
+    /// Estimated prompt tokens before truncation
+    pub estimated_tokens: usize,
+    /// Estimated prompt tokens sent
+    pub final_tokens: usize,
+    /// Whether the request was truncated
+    pub truncated: bool,
+    /// Number of messages dropped
+    pub removed_messages: usize,
+}
+
+/// Context window of a model together with the strategy used to respect it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextWindow {
+    limit: u32,
+    strategy: ContextStrategy,
+}
+
+impl ContextWindow {
+    /// Context window of `limit` tokens
+    #[must_use]
+    pub const fn new(limit: u32, strategy: ContextStrategy) -> Self {
+        Self { limit, strategy }
+    }
+
+    /// Context window of an agent node: its `context_strategy` applied to its
+    /// `context_window` override or the model's context length. `None` when the
+    /// node sets no strategy or the context length of the model is unknown.
+    pub fn for_node(
+        config: &HashMap<String, serde_json::Value>,
+        provider: &dyn LlmProviderTrait,
+    ) -> GraphBitResult<Option<Self>> {
+        let Some(strategy) = ContextStrategy::from_node_config(config)? else {
+            return Ok(None);
+        };
+        let limit = match config.get("context_window") {
+            Some(value) => Some(
+                value
+                    .as_u64()
+                    .and_then(|limit| u32::try_from(limit).ok())
+                    .filter(|limit| *limit > 0)
+                    .ok_or_else(|| {
+                        GraphBitError::validation(
+                            "context_window",
+                            format!("Expected a positive number of tokens, got {value}"),
+                        )
+                    })?,
+            ),
+            None => provider.max_context_length(),
+        };
+        if limit.is_none() {
+            tracing::warn!(
+                "Context window of model '{}' is unknown; set context_window on the node to apply context_strategy",
+                provider.model_name()
+            );
+        }
+        Ok(limit.map(|limit| Self::new(limit, strategy)))
+    }
+
+    /// Context window size in tokens
+    #[must_use]
+    pub const fn limit(self) -> u32 {
+        self.limit
+    }
+
+    /// Strategy applied to oversized requests
+    #[must_use]
+    pub const fn strategy(self) -> ContextStrategy {
+        self.strategy
+    }
+
+    /// Make `request` fit the window, truncating it as the strategy allows
+    pub fn fit(self, request: &mut LlmRequest) -> GraphBitResult<ContextFit> {
+        let reserved = request.max_tokens.unwrap_or(0);
+        let budget = self.limit.saturating_sub(reserved) as usize;
+        let estimated = estimate_request_tokens(request);
+        let mut fit = ContextFit {
+            strategy: self.strategy,
+            context_window: self.limit,
+            reserved_tokens: reserved,
+            estimated_tokens: estimated,
+            final_tokens: estimated,
+            truncated: false,
+            removed_messages: 0,
+        };
+        if estimated <= budget {
+            return Ok(fit);
+        }
+
+        match self.strategy {
+            ContextStrategy::Fail => {}
+            ContextStrategy::TruncateHistory => {
+                while fit.final_tokens > budget {
+                    let Some(range) = oldest_history(&request.messages) else {
+                        break;
+                    };
+                    fit.removed_messages += range.len();
+                    request.messages.drain(range);
+                    fit.final_tokens = estimate_request_tokens(request);
+                }
+            }
+            ContextStrategy::TruncateDocuments => {
+                let mut attempts = request.messages.len() * 2;
+                while fit.final_tokens > budget && attempts > 0 {
+                    attempts -= 1;
+                    let over = fit.final_tokens - budget;
+                    let Some(message) = request
+                        .messages
+                        .iter_mut()
+                        .filter(|message| message.role != LlmRole::System)
+                        .max_by_key(|message| estimate_tokens(&message.content))
+                    else {
+                        break;
+                    };
+                    let current = estimate_tokens(&message.content);
+                    match truncate_middle(&message.content, current.saturating_sub(over)) {
+                        Some(truncated) => message.content = truncated,
+                        None => break,
+                    }
+                    fit.final_tokens = estimate_request_tokens(request);
+                }
+            }
+        }
+        fit.truncated = fit.final_tokens < estimated;
+
+        if fit.final_tokens > budget {
+            let truncation = if fit.truncated {
+                format!(" after truncating to {} tokens", fit.final_tokens)
+            } else {
+                String::new()
+            };
+            return Err(GraphBitError::validation(
+                "context_window",
+                format!(
+                    "Request needs an estimated {estimated} prompt tokens but only {budget} fit \
+                     ({} token context window, {reserved} reserved for the completion): \
+                     {} tokens over{truncation} with strategy {}",
+                    self.limit,
+                    fit.final_tokens - budget,
+                    self.strategy
+                ),
+            ));
+        }
+        Ok(fit)
+    }
+}
+
+/// The oldest droppable messages: a message, or an assistant message with its tool
+/// results. System messages, the latest user message and the latest exchange are kept.
+fn oldest_history(messages: &[LlmMessage]) -> Option<std::ops::Range<usize>> {
+    let last_user = messages.iter().rposition(|m| m.role == LlmRole::User);
+    // The exchange the model answers next: the final message, with the assistant
+    // message whose tool calls the trailing tool results answer
+    let mut tail = messages.len().checked_sub(1)?;
+    while tail > 0 && messages[tail].role == LlmRole::Tool {
+        tail -= 1;
+    }
+    let start = (0..tail).find(|&i| messages[i].role != LlmRole::System && Some(i) != last_user)?;
+    let mut end = start + 1;
+    if !messages[start].tool_calls.is_empty() {
+        while end < tail && messages[end].role == LlmRole::Tool {
+            end += 1;
+        }
+    }
+    Some(start..end)
+}
+
+/// Keep the beginning and end of `text` within `max_tokens`, marking the cut. When
+/// not even the marker fits, only the marker is kept; `None` when that would not
+/// shorten `text`.
+fn truncate_middle(text: &str, max_tokens: usize) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let total = estimate_tokens(text);
+    let build = |keep: usize| {
+        let head: String = chars[..(keep + 1) / 2].iter().collect();
+        let tail: String = chars[chars.len() - keep / 2..].iter().collect();
+        let removed = total.saturating_sub(estimate_tokens(&head) + estimate_tokens(&tail));
+        format!("{head}\n\n[... {removed} tokens truncated ...]\n\n{tail}")
+    };
+    let marker_only = build(0);
+    if estimate_tokens(&marker_only) > max_tokens {
+        return (estimate_tokens(&marker_only) < total).then_some(marker_only);
+    }
+    // Largest number of kept characters whose result fits
+    let (mut low, mut high) = (0, chars.len().saturating_sub(1));
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if estimate_tokens(&build(mid)) <= max_tokens {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(build(low))
+}
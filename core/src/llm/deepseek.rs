@@ -1,6 +1,7 @@
 //! `DeepSeek` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -23,19 +24,7 @@ pub struct DeepSeekProvider {
 impl DeepSeekProvider {
     /// Create a new `DeepSeek` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "deepseek",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.deepseek.com/v1".to_string();
 
         Ok(Self {
@@ -48,19 +37,7 @@ impl DeepSeekProvider {
 
     /// Create a new `DeepSeek` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "deepseek",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -70,6 +47,17 @@ impl DeepSeekProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("deepseek", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `DeepSeek` message format
     fn convert_message(message: &LlmMessage) -> DeepSeekMessage {
         DeepSeekMessage {
@@ -201,15 +189,16 @@ impl LlmProviderTrait for DeepSeekProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| GraphBitError::llm_provider("deepseek", format!("Request failed: {e}")))?;
+        let response = crate::capture::send(
+            "deepseek",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("deepseek", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -320,7 +309,7 @@ impl LlmProviderTrait for DeepSeekProvider {
         )
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "deepseek",
                 format!(
                     "Connection timeout after {:?} - DeepSeek did not respond. \
@@ -329,7 +318,7 @@ impl LlmProviderTrait for DeepSeekProvider {
                 ),
             )
         })?
-        .map_err(|e| GraphBitError::llm_provider("deepseek", format!("Request failed: {e}")))?;
+        .map_err(|e| GraphBitError::from_request_error("deepseek", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -403,7 +392,7 @@ impl LlmProviderTrait for DeepSeekProvider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "deepseek",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
@@ -1,6 +1,9 @@
 //! `Fireworks AI` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
+use crate::llm::catalog::{self, ModelInfo};
+use crate::llm::health::{self, ProviderHealth};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -8,6 +11,8 @@ use crate::llm::{
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
 
 /// `Fireworks AI` API provider
 pub struct FireworksProvider {
@@ -15,58 +20,49 @@ pub struct FireworksProvider {
     api_key: String,
     model: String,
     base_url: String,
+    /// Context window of the model, once read from the catalog
+    catalog_context_length: OnceLock<u32>,
 }
 
 impl FireworksProvider {
     /// Create a new `Fireworks AI` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "fireworks",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.fireworks.ai/inference/v1".to_string();
 
         Ok(Self {
             client,
             api_key,
-            model,
+            model: Self::model_id(model),
             base_url,
+            catalog_context_length: OnceLock::new(),
         })
     }
 
     /// Create a new `Fireworks AI` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "fireworks",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
             api_key,
-            model,
+            model: Self::model_id(model),
             base_url,
+            catalog_context_length: OnceLock::new(),
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("fireworks", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `Fireworks AI` message format
     fn convert_message(message: &LlmMessage) -> FireworksMessage {
         FireworksMessage {
@@ -111,6 +107,30 @@ impl FireworksProvider {
         }
     }
 
+    /// Full model ID: bare names such as `llama-v3p1-8b-instruct` refer to the
+    /// models `Fireworks AI` serves itself, under `accounts/fireworks/models/`
+    fn model_id(model: String) -> String {
+        if model.contains('/') {
+            model
+        } else {
+            format!("accounts/fireworks/models/{model}")
+        }
+    }
+
+    /// List the models in the `Fireworks AI` catalog
+    pub async fn list_models(&self) -> GraphBitResult<Vec<ModelInfo>> {
+        let url = format!("{}/models", self.base_url);
+        let models = catalog::fetch_catalog(&self.client, "fireworks", &url, &self.api_key).await?;
+        if let Some(length) = models
+            .iter()
+            .find(|info| info.id == self.model)
+            .and_then(|info| info.context_length)
+        {
+            let _ = self.catalog_context_length.set(length);
+        }
+        Ok(models)
+    }
+
     /// Parse `Fireworks AI` response to `GraphBit` response
     fn parse_response(&self, response: FireworksResponse) -> GraphBitResult<LlmResponse> {
         let choice =
@@ -228,27 +248,19 @@ impl LlmProviderTrait for FireworksProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("fireworks", format!("Request failed: {e}"))
-            })?;
+        let response = crate::capture::send(
+            "fireworks",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("fireworks", &e))?;
 
         if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(GraphBitError::llm_provider(
-                "fireworks",
-                format!("API error: {error_text}"),
-            ));
+            return Err(catalog::error_from_response("fireworks", response).await);
         }
 
         let fireworks_response: FireworksResponse = response.json().await.map_err(|e| {
@@ -264,6 +276,9 @@ impl LlmProviderTrait for FireworksProvider {
     }
 
     fn max_context_length(&self) -> Option<u32> {
+        if let Some(length) = self.catalog_context_length.get() {
+            return Some(*length);
+        }
         // Common context lengths for popular Fireworks AI models
         match self.model.as_str() {
             m if m.contains("llama-v3p1-8b") => Some(131_072),
@@ -289,6 +304,15 @@ impl LlmProviderTrait for FireworksProvider {
             _ => None, // Unknown model pricing
         }
     }
+
+    /// Look the model up in the catalog, which costs no tokens and catches
+    /// misspelled model names before a run
+    async fn health_check(&self) -> ProviderHealth {
+        health::probe("fireworks", &self.model, async {
+            catalog::find_model(self.list_models().await?, "fireworks", &self.model)
+        })
+        .await
+    }
 }
 
 // `Fireworks AI` API types
@@ -1,7 +1,9 @@
 //! `Google Gemini` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
+use crate::llm::tool_schema::native_parameters;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
 };
@@ -9,6 +11,7 @@ use async_trait::async_trait;
 use futures::stream::{Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::time::timeout;
 
@@ -18,20 +21,13 @@ pub struct GeminiProvider {
     api_key: String,
     model: String,
     base_url: String,
+    safety_settings: Vec<GeminiSafetySetting>,
 }
 
 impl GeminiProvider {
     /// Create a new `Gemini` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("gemini", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://generativelanguage.googleapis.com/v1beta".to_string();
 
         Ok(Self {
@@ -39,29 +35,49 @@ impl GeminiProvider {
             api_key,
             model,
             base_url,
+            safety_settings: Vec::new(),
         })
     }
 
     /// Create a new `Gemini` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("gemini", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
             api_key,
             model,
             base_url,
+            safety_settings: Vec::new(),
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("gemini", Some(Duration::from_secs(120)), None, timeouts)
+    }
+
+    /// Send these block thresholds by harm category with every request
+    #[must_use]
+    pub fn with_safety_settings(mut self, safety_settings: HashMap<String, String>) -> Self {
+        let mut safety_settings: Vec<_> = safety_settings
+            .into_iter()
+            .map(|(category, threshold)| GeminiSafetySetting {
+                category,
+                threshold,
+            })
+            .collect();
+        safety_settings.sort_by(|a, b| a.category.cmp(&b.category));
+        self.safety_settings = safety_settings;
+        self
+    }
+
     /// Convert `GraphBit` messages to `Gemini` content format
     ///
     /// Gemini API uses a different format than OpenAI:
@@ -186,7 +202,7 @@ impl GeminiProvider {
             .map(|tool| GeminiFunctionDeclaration {
                 name: tool.name.clone(),
                 description: tool.description.clone(),
-                parameters: tool.parameters.clone(),
+                parameters: native_parameters("gemini", tool),
             })
             .collect();
 
@@ -195,40 +211,75 @@ impl GeminiProvider {
         }]
     }
 
+    /// Collect the text and function calls of a candidate's parts
+    fn collect_parts(content: GeminiResponseContent) -> (String, Vec<LlmToolCall>) {
+        let mut text_content = String::new();
+        let mut tool_calls: Vec<LlmToolCall> = Vec::new();
+
+        for part in content.parts.unwrap_or_default() {
+            match part {
+                GeminiResponsePart::Text { text } => {
+                    text_content.push_str(&text);
+                }
+                GeminiResponsePart::FunctionCall { function_call } => {
+                    tool_calls.push(LlmToolCall {
+                        // Gemini doesn't provide tool call IDs, generate one
+                        id: format!("call_{}", uuid::Uuid::new_v4()),
+                        name: function_call.name,
+                        parameters: function_call.args,
+                    });
+                }
+                GeminiResponsePart::Other(_) => {} // Ignore other part types
+            }
+        }
+
+        (text_content, tool_calls)
+    }
+
+    /// Error for a response `Gemini` blocked, either the prompt itself
+    /// (`promptFeedback.blockReason`) or the candidate (`finishReason: SAFETY`)
+    fn blocked_error(response: &GeminiResponse) -> Option<GraphBitError> {
+        if let Some(feedback) = &response.prompt_feedback {
+            if let Some(block_reason) = &feedback.block_reason {
+                return Some(GraphBitError::content_blocked(
+                    "gemini",
+                    format!("prompt blocked ({block_reason})"),
+                    flagged_categories(&feedback.safety_ratings),
+                ));
+            }
+        }
+
+        let candidate = response.candidates.as_ref()?.first()?;
+        match candidate.finish_reason.as_deref() {
+            Some(reason @ ("SAFETY" | "PROHIBITED_CONTENT" | "BLOCKLIST" | "SPII")) => {
+                Some(GraphBitError::content_blocked(
+                    "gemini",
+                    format!("response blocked ({reason})"),
+                    flagged_categories(&candidate.safety_ratings),
+                ))
+            }
+            _ => None,
+        }
+    }
+
     /// Parse `Gemini` response to `GraphBit` response
     fn parse_response(&self, response: GeminiResponse) -> GraphBitResult<LlmResponse> {
+        if let Some(error) = Self::blocked_error(&response) {
+            return Err(error);
+        }
+
         let candidate = response
             .candidates
             .and_then(|c| c.into_iter().next())
             .ok_or_else(|| GraphBitError::llm_provider("gemini", "No candidates in response"))?;
 
         // Extract text content and tool calls from parts
-        let mut content = String::new();
-        let mut tool_calls: Vec<LlmToolCall> = Vec::new();
-
-        if let Some(parts) = candidate.content.parts {
-            for part in parts {
-                match part {
-                    GeminiResponsePart::Text { text } => {
-                        content.push_str(&text);
-                    }
-                    GeminiResponsePart::FunctionCall { function_call } => {
-                        tool_calls.push(LlmToolCall {
-                            // Gemini doesn't provide tool call IDs, generate one
-                            id: format!("call_{}", uuid::Uuid::new_v4()),
-                            name: function_call.name,
-                            parameters: function_call.args,
-                        });
-                    }
-                    _ => {} // Ignore other part types
-                }
-            }
-        }
+        let (content, tool_calls) = Self::collect_parts(candidate.content);
 
         let finish_reason = match candidate.finish_reason.as_deref() {
+            _ if !tool_calls.is_empty() => FinishReason::ToolCalls,
             Some("STOP") => FinishReason::Stop,
             Some("MAX_TOKENS") => FinishReason::Length,
-            Some("SAFETY") => FinishReason::ContentFilter,
             Some("RECITATION") => FinishReason::ContentFilter,
             Some("MALFORMED_FUNCTION_CALL") => FinishReason::Error,
             Some(other) => FinishReason::Other(other.to_string()),
@@ -253,6 +304,49 @@ impl GeminiProvider {
             .with_finish_reason(finish_reason)
             .with_id(response_id))
     }
+
+    /// Turn one streamed `Gemini` chunk into a response, skipping chunks
+    /// without text or function calls
+    fn parse_stream_chunk(
+        chunk: GeminiResponse,
+        model: &str,
+    ) -> Option<GraphBitResult<LlmResponse>> {
+        if let Some(error) = Self::blocked_error(&chunk) {
+            return Some(Err(error));
+        }
+
+        let candidate = chunk.candidates?.into_iter().next()?;
+        let (content, tool_calls) = Self::collect_parts(candidate.content);
+        if content.is_empty() && tool_calls.is_empty() {
+            return None;
+        }
+
+        let mut response =
+            LlmResponse::new(content, model).with_id(chunk.response_id.unwrap_or_default());
+        if !tool_calls.is_empty() {
+            response = response
+                .with_tool_calls(tool_calls)
+                .with_finish_reason(FinishReason::ToolCalls);
+        }
+        Some(Ok(response))
+    }
+}
+
+/// Harm categories `Gemini` blocked, or rated at least medium when none is marked blocked
+fn flagged_categories(ratings: &[GeminiSafetyRating]) -> Vec<String> {
+    let blocked: Vec<String> = ratings
+        .iter()
+        .filter(|rating| rating.blocked)
+        .map(|rating| rating.category.clone())
+        .collect();
+    if !blocked.is_empty() {
+        return blocked;
+    }
+    ratings
+        .iter()
+        .filter(|rating| matches!(rating.probability.as_deref(), Some("MEDIUM" | "HIGH")))
+        .map(|rating| rating.category.clone())
+        .collect()
 }
 
 #[async_trait]
@@ -285,6 +379,7 @@ impl LlmProviderTrait for GeminiProvider {
             contents,
             system_instruction,
             tools: if tools.is_empty() { None } else { Some(tools) },
+            safety_settings: self.safety_settings.clone(),
             generation_config: Some(generation_config),
         };
 
@@ -296,14 +391,15 @@ impl LlmProviderTrait for GeminiProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| GraphBitError::llm_provider("gemini", format!("Request failed: {e}")))?;
+        let response = crate::capture::send(
+            "gemini",
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("gemini", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -350,6 +446,7 @@ impl LlmProviderTrait for GeminiProvider {
             contents,
             system_instruction,
             tools: if tools.is_empty() { None } else { Some(tools) },
+            safety_settings: self.safety_settings.clone(),
             generation_config: Some(generation_config),
         };
 
@@ -375,7 +472,7 @@ impl LlmProviderTrait for GeminiProvider {
         )
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "gemini",
                 format!(
                     "Connection timeout after {:?} - Gemini did not respond. \
@@ -384,7 +481,7 @@ impl LlmProviderTrait for GeminiProvider {
                 ),
             )
         })?
-        .map_err(|e| GraphBitError::llm_provider("gemini", format!("Request failed: {e}")))?;
+        .map_err(|e| GraphBitError::from_request_error("gemini", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -414,17 +511,87 @@ impl LlmProviderTrait for GeminiProvider {
             move |(
                 mut byte_stream,
                 mut buffer,
-                timeout_occurred,
+                done,
                 mut consecutive_parse_errors,
                 mut total_parse_errors,
             )| {
                 let model = model.clone();
                 async move {
-                    if timeout_occurred {
+                    if done {
                         return None;
                     }
 
                     loop {
+                        // Process complete SSE lines before reading more data
+                        while let Some(newline_pos) = buffer.find('\n') {
+                            let line: String = buffer.drain(..=newline_pos).collect();
+                            let line = line.trim();
+
+                            if line.is_empty() || line.starts_with(':') {
+                                continue;
+                            }
+
+                            // Gemini SSE format: "data: <json>"
+                            let Some(data) = line.strip_prefix("data: ") else {
+                                continue;
+                            };
+
+                            match serde_json::from_str::<GeminiResponse>(data) {
+                                Ok(gemini_chunk) => {
+                                    consecutive_parse_errors = 0;
+
+                                    if let Some(result) =
+                                        Self::parse_stream_chunk(gemini_chunk, &model)
+                                    {
+                                        // A blocked response ends the stream
+                                        let done = result.is_err();
+                                        return Some((
+                                            result,
+                                            (
+                                                byte_stream,
+                                                buffer,
+                                                done,
+                                                consecutive_parse_errors,
+                                                total_parse_errors,
+                                            ),
+                                        ));
+                                    }
+                                }
+                                Err(e) => {
+                                    consecutive_parse_errors += 1;
+                                    total_parse_errors += 1;
+
+                                    tracing::warn!(
+                                        "Failed to parse Gemini stream chunk (consecutive: {}, total: {}): {}, data: {}",
+                                        consecutive_parse_errors,
+                                        total_parse_errors,
+                                        e,
+                                        if data.len() > 200 { &data[..200] } else { data }
+                                    );
+
+                                    if consecutive_parse_errors >= MAX_CONSECUTIVE_PARSE_ERRORS {
+                                        return Some((
+                                            Err(GraphBitError::llm_provider(
+                                                "gemini",
+                                                format!(
+                                                    "Stream corrupted: {} consecutive parse errors. \
+                                                     Last error: {}. Data may be incomplete.",
+                                                    consecutive_parse_errors, e
+                                                ),
+                                            )),
+                                            (
+                                                byte_stream,
+                                                buffer,
+                                                true,
+                                                consecutive_parse_errors,
+                                                total_parse_errors,
+                                            ),
+                                        ));
+                                    }
+                                }
+                            }
+                        }
+
                         let chunk_result = match timeout(CHUNK_TIMEOUT, byte_stream.next()).await {
                             Ok(Some(result)) => result,
                             Ok(None) => {
@@ -443,7 +610,7 @@ impl LlmProviderTrait for GeminiProvider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "gemini",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
@@ -481,99 +648,10 @@ impl LlmProviderTrait for GeminiProvider {
                         };
 
                         buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                        // Process complete SSE lines
-                        while let Some(newline_pos) = buffer.find('\n') {
-                            let line: String = buffer.drain(..=newline_pos).collect();
-                            let line = line.trim();
-
-                            if line.is_empty() || line.starts_with(':') {
-                                continue;
-                            }
-
-                            // Gemini SSE format: "data: <json>"
-                            if let Some(data) = line.strip_prefix("data: ") {
-                                // Parse the Gemini streaming response chunk
-                                match serde_json::from_str::<GeminiResponse>(data) {
-                                    Ok(gemini_chunk) => {
-                                        consecutive_parse_errors = 0;
-
-                                        // Extract text from candidates
-                                        if let Some(candidates) = &gemini_chunk.candidates {
-                                            if let Some(candidate) = candidates.first() {
-                                                if let Some(parts) = &candidate.content.parts {
-                                                    for part in parts {
-                                                        if let GeminiResponsePart::Text { text } =
-                                                            part
-                                                        {
-                                                            if !text.is_empty() {
-                                                                let response_id = gemini_chunk
-                                                                    .response_id
-                                                                    .clone()
-                                                                    .unwrap_or_default();
-                                                                let response = LlmResponse::new(
-                                                                    text.clone(),
-                                                                    &model,
-                                                                )
-                                                                .with_id(response_id);
-                                                                return Some((
-                                                                    Ok(response),
-                                                                    (
-                                                                        byte_stream,
-                                                                        buffer,
-                                                                        false,
-                                                                        consecutive_parse_errors,
-                                                                        total_parse_errors,
-                                                                    ),
-                                                                ));
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        consecutive_parse_errors += 1;
-                                        total_parse_errors += 1;
-
-                                        tracing::warn!(
-                                            "Failed to parse Gemini stream chunk (consecutive: {}, total: {}): {}, data: {}",
-                                            consecutive_parse_errors,
-                                            total_parse_errors,
-                                            e,
-                                            if data.len() > 200 { &data[..200] } else { data }
-                                        );
-
-                                        if consecutive_parse_errors >= MAX_CONSECUTIVE_PARSE_ERRORS
-                                        {
-                                            return Some((
-                                                Err(GraphBitError::llm_provider(
-                                                    "gemini",
-                                                    format!(
-                                                        "Stream corrupted: {} consecutive parse errors. \
-                                                         Last error: {}. Data may be incomplete.",
-                                                        consecutive_parse_errors, e
-                                                    ),
-                                                )),
-                                                (
-                                                    byte_stream,
-                                                    buffer,
-                                                    true,
-                                                    consecutive_parse_errors,
-                                                    total_parse_errors,
-                                                ),
-                                            ));
-                                        }
-                                    }
-                                }
-                            }
-                        }
                     }
                 }
             },
         );
-
         Ok(Box::new(Box::pin(stream)))
     }
 
@@ -636,10 +714,19 @@ struct GeminiRequest {
     system_instruction: Option<GeminiContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<GeminiTool>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    safety_settings: Vec<GeminiSafetySetting>,
     #[serde(skip_serializing_if = "Option::is_none")]
     generation_config: Option<GeminiGenerationConfig>,
 }
 
+/// Block threshold for one harm category
+#[derive(Debug, Clone, Serialize)]
+struct GeminiSafetySetting {
+    category: String,
+    threshold: String,
+}
+
 /// Content object containing role and parts
 #[derive(Debug, Serialize)]
 struct GeminiContent {
@@ -722,19 +809,45 @@ struct GeminiResponse {
     model_version: Option<String>,
     #[serde(default)]
     response_id: Option<String>,
+    #[serde(default)]
+    prompt_feedback: Option<GeminiPromptFeedback>,
+}
+
+/// Feedback on the prompt, set when `Gemini` blocked it
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+    #[serde(default)]
+    safety_ratings: Vec<GeminiSafetyRating>,
+}
+
+/// Safety rating of a prompt or candidate for one harm category
+#[derive(Debug, Deserialize)]
+struct GeminiSafetyRating {
+    category: String,
+    #[serde(default)]
+    probability: Option<String>,
+    #[serde(default)]
+    blocked: bool,
 }
 
 /// Candidate response
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiCandidate {
+    // Blocked candidates come without content
+    #[serde(default)]
     content: GeminiResponseContent,
     #[serde(default)]
     finish_reason: Option<String>,
+    #[serde(default)]
+    safety_ratings: Vec<GeminiSafetyRating>,
 }
 
 /// Content in a candidate response
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct GeminiResponseContent {
     #[serde(default)]
     parts: Option<Vec<GeminiResponsePart>>,
@@ -760,6 +873,8 @@ enum GeminiResponsePart {
 #[derive(Debug, Deserialize)]
 struct GeminiFunctionCallResponse {
     name: String,
+    // Functions without parameters are called without `args`
+    #[serde(default = "empty_args")]
     args: serde_json::Value,
 }
 
@@ -774,3 +889,7 @@ struct GeminiUsageMetadata {
     #[serde(default)]
     total_token_count: Option<u32>,
 }
+
+fn empty_args() -> serde_json::Value {
+    serde_json::json!({})
+}
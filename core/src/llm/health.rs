@@ -0,0 +1,100 @@
+//! Health checks of LLM and embedding providers
+//!
+//! A health check makes the smallest request that proves a provider can be
+//! used: a lookup of the model on the provider's models endpoint where there is
+//! one, a one-token completion or a one-word embedding otherwise. Its outcome
+//! is reported as a [`ProviderHealth`] rather than an error, so that checks of
+//! several providers can be collected into one report.
+
+use std::future::Future;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Outcome of a provider health check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    /// Provider checked
+    pub provider: String,
+    /// Model checked
+    pub model: String,
+    /// Whether the provider answered at all
+    pub reachable: bool,
+    /// Whether the provider accepted the credentials
+    pub authenticated: bool,
+    /// Whether the provider serves the model
+    pub model_available: bool,
+    /// Time the probe took
+    pub latency_ms: u64,
+    /// Error the probe failed with
+    pub error: Option<String>,
+}
+
+impl ProviderHealth {
+    /// Whether the provider can be used with this model
+    #[must_use]
+    pub const fn is_healthy(&self) -> bool {
+        self.reachable && self.authenticated && self.model_available
+    }
+
+    /// Health of `model` at `provider` judged from the outcome of a probe
+    #[must_use]
+    pub fn from_probe(
+        provider: impl Into<String>,
+        model: impl Into<String>,
+        latency_ms: u64,
+        outcome: &GraphBitResult<()>,
+    ) -> Self {
+        let (reachable, authenticated, model_available) = match outcome {
+            Ok(()) => (true, true, true),
+            Err(error) => classify(error),
+        };
+        Self {
+            provider: provider.into(),
+            model: model.into(),
+            reachable,
+            authenticated,
+            model_available,
+            latency_ms,
+            error: outcome.as_ref().err().map(ToString::to_string),
+        }
+    }
+}
+
+/// Reachable, authenticated and model available, as far as `error` tells
+fn classify(error: &GraphBitError) -> (bool, bool, bool) {
+    match error {
+        GraphBitError::Network { .. } => (false, false, false),
+        GraphBitError::Authentication { .. } => (true, false, false),
+        // Throttled requests were authenticated, but say nothing about the model
+        GraphBitError::RateLimit { .. } => (true, true, false),
+        _ if error.http_status().is_some() => (true, true, false),
+        _ => {
+            let message = error.to_string().to_lowercase();
+            let unreachable = [
+                "request failed",
+                "error sending request",
+                "connection",
+                "timed out",
+                "timeout",
+            ]
+            .iter()
+            .any(|pattern| message.contains(pattern));
+            (!unreachable, !unreachable, false)
+        }
+    }
+}
+
+/// Run `probe`, measuring how long it takes, and judge the health of `model`
+/// at `provider` from its outcome
+pub async fn probe<T, Fut>(provider: &str, model: &str, probe: Fut) -> ProviderHealth
+where
+    Fut: Future<Output = GraphBitResult<T>>,
+{
+    let start = Instant::now();
+    let outcome = probe.await.map(|_| ());
+    let latency_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+    ProviderHealth::from_probe(provider, model, latency_ms, &outcome)
+}
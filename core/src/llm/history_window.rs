@@ -0,0 +1,269 @@
+//! Conversation history windows for agent tool loops
+//!
+//! Every round of an agent's tool-calling loop appends an exchange to the
+//! conversation: the assistant message requesting tools together with the tool
+//! results answering it. A node's [`HistoryWindow`] limits which exchanges are
+//! replayed to the model: at most `history_window` of the latest exchanges, and
+//! only as many as fit `history_token_budget` estimated prompt tokens. The
+//! system messages and the prompt before the first exchange, and the latest
+//! exchange, are always sent.
+//!
+//! With `summarize_overflow`, the exchanges left out are summarized by the LLM
+//! into a single system note placed between the prompt and the exchanges kept.
+//! The summary is extended as further exchanges drop out of the window, so no
+//! exchange is summarized twice.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::context_window::{estimate_message_tokens, estimate_request_tokens};
+use super::{LlmMessage, LlmMiddlewareStack, LlmProvider, LlmRequest, LlmRole};
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Limits on the tool-loop history an agent node replays to the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryWindow {
+    max_exchanges: Option<usize>,
+    token_budget: Option<usize>,
+    summarize_overflow: bool,
+}
+
+/// Summary of the earliest exchanges of a conversation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistorySummary {
+    /// Number of leading exchanges the summary covers
+    pub exchanges: usize,
+    /// Summary text
+    pub text: String,
+}
+
+/// History sent with one request, recorded in the node metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryTrim {
+    /// Exchanges in the conversation
+    pub exchanges: usize,
+    /// Leading exchanges left out of the request
+    pub dropped_exchanges: usize,
+    /// Leading exchanges replaced by the summary note
+    pub summarized_exchanges: usize,
+    /// Estimated prompt tokens of the request sent
+    pub estimated_tokens: usize,
+}
+
+impl HistoryWindow {
+    /// Replay at most the latest `max_exchanges` exchanges
+    #[must_use]
+    pub const fn with_max_exchanges(mut self, max_exchanges: usize) -> Self {
+        self.max_exchanges = Some(max_exchanges);
+        self
+    }
+
+    /// Replay only as many of the latest exchanges as fit `tokens` estimated prompt tokens
+    #[must_use]
+    pub const fn with_token_budget(mut self, tokens: usize) -> Self {
+        self.token_budget = Some(tokens);
+        self
+    }
+
+    /// Summarize the exchanges left out into a system note
+    #[must_use]
+    pub const fn with_summarize_overflow(mut self, summarize: bool) -> Self {
+        self.summarize_overflow = summarize;
+        self
+    }
+
+    /// Maximum number of exchanges replayed
+    #[must_use]
+    pub const fn max_exchanges(self) -> Option<usize> {
+        self.max_exchanges
+    }
+
+    /// Budget of estimated prompt tokens
+    #[must_use]
+    pub const fn token_budget(self) -> Option<usize> {
+        self.token_budget
+    }
+
+    /// Whether the exchanges left out are summarized
+    #[must_use]
+    pub const fn summarize_overflow(self) -> bool {
+        self.summarize_overflow
+    }
+
+    /// The history window of a node configuration: its `history_window`,
+    /// `history_token_budget` and `summarize_overflow`. `None` when the node
+    /// limits neither the exchanges nor the tokens replayed.
+    pub fn from_node_config(
+        config: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<Option<Self>> {
+        let positive = |key: &str| -> GraphBitResult<Option<usize>> {
+            match config.get(key) {
+                None | Some(serde_json::Value::Null) => Ok(None),
+                Some(value) => value
+                    .as_u64()
+                    .and_then(|n| usize::try_from(n).ok())
+                    .filter(|n| *n > 0)
+                    .map(Some)
+                    .ok_or_else(|| {
+                        GraphBitError::validation(
+                            key,
+                            format!("Expected a positive number, got {value}"),
+                        )
+                    }),
+            }
+        };
+        let max_exchanges = positive("history_window")?;
+        let token_budget = positive("history_token_budget")?;
+        let summarize_overflow = match config.get("summarize_overflow") {
+            None | Some(serde_json::Value::Null) => false,
+            Some(serde_json::Value::Bool(summarize)) => *summarize,
+            Some(other) => {
+                return Err(GraphBitError::validation(
+                    "summarize_overflow",
+                    format!("Expected a boolean, got {other}"),
+                ));
+            }
+        };
+        if max_exchanges.is_none() && token_budget.is_none() {
+            if summarize_overflow {
+                return Err(GraphBitError::validation(
+                    "summarize_overflow",
+                    "summarize_overflow requires history_window or history_token_budget",
+                ));
+            }
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            max_exchanges,
+            token_budget,
+            summarize_overflow,
+        }))
+    }
+
+    /// Number of leading exchanges of `request` to leave out, given the
+    /// summary the request would carry. Never fewer than the summary covers,
+    /// and never the latest exchange.
+    #[must_use]
+    pub fn dropped_exchanges(
+        self,
+        request: &LlmRequest,
+        summary: Option<&HistorySummary>,
+    ) -> usize {
+        let (prefix, exchanges) = split_exchanges(&request.messages);
+        let total = exchanges.len();
+        let covered = summary.map_or(0, |summary| summary.exchanges.min(total));
+        let mut dropped = self
+            .max_exchanges
+            .map_or(0, |max| total.saturating_sub(max))
+            .max(covered);
+        if let Some(budget) = self.token_budget {
+            let mut fixed = request.clone();
+            fixed.messages.truncate(prefix);
+            let mut tokens = estimate_request_tokens(&fixed)
+                + summary.map_or(0, |summary| estimate_message_tokens(&summary_note(summary)));
+            let sizes: Vec<usize> = exchanges
+                .iter()
+                .map(|range| {
+                    request.messages[range.clone()]
+                        .iter()
+                        .map(estimate_message_tokens)
+                        .sum()
+                })
+                .collect();
+            tokens += sizes[dropped.min(total)..].iter().sum::<usize>();
+            while tokens > budget && dropped + 1 < total {
+                tokens -= sizes[dropped];
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    /// Fit the history of `request` into the window, first extending `summary`
+    /// with the LLM when the window summarizes exchanges it leaves out
+    pub async fn fit(
+        self,
+        request: &mut LlmRequest,
+        summary: &mut Option<HistorySummary>,
+        provider: &LlmProvider,
+        middleware: &LlmMiddlewareStack,
+    ) -> GraphBitResult<HistoryTrim> {
+        let mut dropped = self.dropped_exchanges(request, summary.as_ref());
+        // A longer summary may push further exchanges out; each round summarizes
+        // at least one more exchange, so this ends before the latest one
+        while self.summarize_overflow
+            && dropped > summary.as_ref().map_or(0, |summary| summary.exchanges)
+        {
+            let (_, exchanges) = split_exchanges(&request.messages);
+            let covered = summary.as_ref().map_or(0, |summary| summary.exchanges);
+            let range = exchanges[covered].start..exchanges[dropped - 1].end;
+            let summary_request = crate::memory::processor::summary_request(
+                summary.as_ref().map(|summary| summary.text.as_str()),
+                &request.messages[range],
+            );
+            let response = provider
+                .complete_with(middleware, summary_request)
+                .await
+                .map_err(|e| {
+                    GraphBitError::workflow_execution(format!(
+                        "Failed to summarize conversation history: {e}"
+                    ))
+                })?;
+            *summary = Some(HistorySummary {
+                exchanges: dropped,
+                text: response.content.trim().to_string(),
+            });
+            dropped = self.dropped_exchanges(request, summary.as_ref());
+        }
+        let summary = summary.as_ref().filter(|_| self.summarize_overflow);
+        Ok(trim(request, dropped, summary))
+    }
+}
+
+/// Leave the leading `dropped` exchanges out of `request`, inserting the
+/// summary note after the prompt when a summary is given
+fn trim(request: &mut LlmRequest, dropped: usize, summary: Option<&HistorySummary>) -> HistoryTrim {
+    let (prefix, exchanges) = split_exchanges(&request.messages);
+    let dropped = dropped.min(exchanges.len());
+    if let Some(end) = exchanges[..dropped].last().map(|range| range.end) {
+        request.messages.drain(prefix..end);
+    }
+    let summarized = summary.map_or(0, |summary| summary.exchanges.min(dropped));
+    if let Some(summary) = summary.filter(|_| summarized > 0) {
+        request.messages.insert(prefix, summary_note(summary));
+    }
+    HistoryTrim {
+        exchanges: exchanges.len(),
+        dropped_exchanges: dropped,
+        summarized_exchanges: summarized,
+        estimated_tokens: estimate_request_tokens(request),
+    }
+}
+
+/// System note standing in for the exchanges `summary` covers
+fn summary_note(summary: &HistorySummary) -> LlmMessage {
+    LlmMessage::system(format!(
+        "Summary of the {} earlier tool exchanges of this conversation:\n{}",
+        summary.exchanges, summary.text
+    ))
+}
+
+/// Length of the prompt before the first exchange, and the message ranges of
+/// the exchanges: an assistant message with the messages up to the next one
+fn split_exchanges(messages: &[LlmMessage]) -> (usize, Vec<Range<usize>>) {
+    let starts: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| message.role == LlmRole::Assistant)
+        .map(|(i, _)| i)
+        .collect();
+    let prefix = starts.first().copied().unwrap_or(messages.len());
+    let exchanges = starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| start..starts.get(n + 1).copied().unwrap_or(messages.len()))
+        .collect();
+    (prefix, exchanges)
+}
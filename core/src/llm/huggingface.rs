@@ -1,13 +1,27 @@
 //! `HuggingFace` LLM provider implementation
+//!
+//! Requests go to the Inference Providers router, whose chat-completions
+//! endpoint (`https://router.huggingface.co/v1/chat/completions`) is
+//! OpenAI-compatible. An optional inference provider hint such as `hf-inference`
+//! or `together` is passed in the router's `model:provider` syntax.
+//!
+//! Self-hosted Text Generation Inference (TGI) servers can instead be called
+//! with raw text-generation requests by enabling `text_generation` and pointing
+//! `base_url` at the server.
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
-use crate::llm::{FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmUsage};
+use crate::llm::{
+    FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
+};
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Write;
+use std::time::Duration;
+use tokio::sync::OnceCell;
 
 /// `HuggingFace` API provider
 pub struct HuggingFaceProvider {
@@ -15,35 +29,208 @@ pub struct HuggingFaceProvider {
     api_key: String,
     model: String,
     base_url: String,
+    inference_provider: Option<String>,
+    text_generation: bool,
+    hub_url: String,
+    context_length: OnceCell<Option<u32>>,
 }
 
 impl HuggingFaceProvider {
-    /// Create a new `HuggingFace` provider
-    pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        let client = Client::new();
-        let base_url = "https://api-inference.huggingface.co/models".to_string();
+    /// Base URL of the Inference Providers router
+    pub const DEFAULT_BASE_URL: &'static str = "https://router.huggingface.co/v1";
 
-        Ok(Self {
-            client,
-            api_key,
-            model,
-            base_url,
-        })
+    /// Base URL of the `HuggingFace` Hub, used to read model cards
+    pub const DEFAULT_HUB_URL: &'static str = "https://huggingface.co";
+
+    /// Create a new `HuggingFace` provider using the Inference Providers router
+    pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
+        Self::with_base_url(api_key, model, Self::DEFAULT_BASE_URL.to_string())
     }
 
     /// Create a new `HuggingFace` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        let client = Client::new();
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
             api_key,
             model,
-            base_url,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            inference_provider: None,
+            text_generation: false,
+            hub_url: Self::DEFAULT_HUB_URL.to_string(),
+            context_length: OnceCell::new(),
         })
     }
 
-    /// Convert `GraphBit` messages to `HuggingFace` chat format
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts(
+            "huggingface",
+            Some(Duration::from_secs(120)),
+            None,
+            timeouts,
+        )
+    }
+
+    /// Route requests to a specific inference provider (e.g. `hf-inference`, `together`)
+    #[must_use]
+    pub fn with_inference_provider(mut self, inference_provider: impl Into<String>) -> Self {
+        self.inference_provider = Some(inference_provider.into());
+        self
+    }
+
+    /// Send raw text-generation requests to a self-hosted TGI server at the base URL
+    /// instead of chat-completion requests
+    #[must_use]
+    pub const fn with_text_generation(mut self, text_generation: bool) -> Self {
+        self.text_generation = text_generation;
+        self
+    }
+
+    /// Read model cards from a different Hub URL
+    #[must_use]
+    pub fn with_hub_url(mut self, hub_url: impl Into<String>) -> Self {
+        self.hub_url = hub_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Model name sent to the router, with the inference provider hint appended
+    fn routed_model(&self) -> String {
+        match &self.inference_provider {
+            Some(provider) if !self.model.contains(':') => format!("{}:{provider}", self.model),
+            _ => self.model.clone(),
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        }
+    }
+
+    /// Context length of the model, read once from the model card's `config.json`
+    /// (or from `/info` of a TGI server). `None` when it is not available.
+    pub async fn load_context_length(&self) -> Option<u32> {
+        *self
+            .context_length
+            .get_or_init(|| async {
+                let url = if self.text_generation {
+                    format!("{}/info", self.base_url)
+                } else {
+                    let model = self.model.split(':').next().unwrap_or(&self.model);
+                    format!("{}/{model}/resolve/main/config.json", self.hub_url)
+                };
+                let response = self
+                    .authorize(self.client.get(&url))
+                    .timeout(Duration::from_secs(10))
+                    .send()
+                    .await
+                    .ok()
+                    .filter(|response| response.status().is_success())?;
+                let card: serde_json::Value = response.json().await.ok()?;
+                let context_length = context_length_from_card(&card);
+                if context_length.is_none() {
+                    tracing::debug!("No context length found in {url}");
+                }
+                context_length
+            })
+            .await
+    }
+
+    /// Build the error for an unsuccessful response, explaining models that are
+    /// not served for the requested task
+    fn api_error(&self, status: StatusCode, body: &str) -> GraphBitError {
+        let (message, code) = parse_error_body(body);
+        let lowered = message.to_lowercase();
+        let unsupported = code.as_deref() == Some("model_not_supported")
+            || lowered.contains("not supported")
+            || lowered.contains("not a chat model")
+            || (status == StatusCode::NOT_FOUND
+                && !self.text_generation
+                && lowered.contains("model"));
+        if !unsupported {
+            return GraphBitError::llm_provider(
+                "huggingface",
+                format!("API error ({status}): {message}"),
+            );
+        }
+
+        let default_task = if self.text_generation {
+            "text-generation"
+        } else {
+            "conversational"
+        };
+        let task = task_from_message(&message).unwrap_or_else(|| default_task.to_string());
+        let provider = self.inference_provider.as_deref().map_or_else(
+            || "any enabled inference provider".to_string(),
+            |p| format!("inference provider '{p}'"),
+        );
+        GraphBitError::llm_provider(
+            "huggingface",
+            format!(
+                "Model '{}' is not supported for task '{task}' by {provider} ({message}). \
+                 Choose a model whose model card lists '{task}' under Inference Providers, \
+                 pick another inference provider, or serve the model with TGI and enable \
+                 text_generation with its base_url",
+                self.model
+            ),
+        )
+    }
+
+    /// Convert `GraphBit` message to the OpenAI-compatible message format
+    fn convert_message(message: &LlmMessage) -> HuggingFaceMessage {
+        HuggingFaceMessage {
+            role: match message.role {
+                LlmRole::User => "user".to_string(),
+                LlmRole::Assistant => "assistant".to_string(),
+                LlmRole::System => "system".to_string(),
+                LlmRole::Tool => "tool".to_string(),
+            },
+            content: Some(message.content.clone()),
+            tool_calls: if message.tool_calls.is_empty() {
+                None
+            } else {
+                Some(
+                    message
+                        .tool_calls
+                        .iter()
+                        .map(|tc| HuggingFaceToolCall {
+                            id: tc.id.clone(),
+                            r#type: "function".to_string(),
+                            function: HuggingFaceFunction {
+                                name: tc.name.clone(),
+                                arguments: tc.parameters.to_string(),
+                            },
+                        })
+                        .collect(),
+                )
+            },
+            tool_call_id: message.tool_call_id.clone(),
+        }
+    }
+
+    /// Convert `GraphBit` tool to the OpenAI-compatible tool format
+    fn convert_tool(tool: &LlmTool) -> HuggingFaceTool {
+        HuggingFaceTool {
+            r#type: "function".to_string(),
+            function: HuggingFaceFunctionDef {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                parameters: tool.parameters.clone(),
+            },
+        }
+    }
+
+    /// Convert `GraphBit` messages to a text-generation prompt
     fn format_messages_for_chat(messages: &[LlmMessage]) -> String {
         let mut formatted = String::new();
 
@@ -69,15 +256,78 @@ impl HuggingFaceProvider {
         formatted
     }
 
-    /// Parse `HuggingFace` response to `GraphBit` response
-    fn parse_response(&self, response: HuggingFaceResponse) -> GraphBitResult<LlmResponse> {
-        let generated_text = response
+    /// Parse a chat-completions response to `GraphBit` response
+    fn parse_chat_response(
+        &self,
+        response: HuggingFaceChatResponse,
+    ) -> GraphBitResult<LlmResponse> {
+        let choice =
+            response.choices.into_iter().next().ok_or_else(|| {
+                GraphBitError::llm_provider("huggingface", "No choices in response")
+            })?;
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .unwrap_or_default()
             .into_iter()
-            .next()
-            .ok_or_else(|| {
-                GraphBitError::llm_provider("huggingface", "No generated text in response")
-            })?
-            .generated_text;
+            .map(|tc| {
+                let parameters = if tc.function.arguments.trim().is_empty() {
+                    serde_json::Value::Object(serde_json::Map::new())
+                } else {
+                    serde_json::from_str(&tc.function.arguments).unwrap_or_else(|e| {
+                        tracing::warn!(
+                            "Failed to parse tool call arguments for {}: {e}",
+                            tc.function.name
+                        );
+                        serde_json::json!({ "raw_arguments": tc.function.arguments })
+                    })
+                };
+                LlmToolCall {
+                    id: tc.id,
+                    name: tc.function.name,
+                    parameters,
+                }
+            })
+            .collect();
+
+        let finish_reason = match choice.finish_reason.as_deref() {
+            Some("stop" | "eos_token" | "stop_sequence") | None => FinishReason::Stop,
+            Some("length") => FinishReason::Length,
+            Some("tool_calls") => FinishReason::ToolCalls,
+            Some("content_filter") => FinishReason::ContentFilter,
+            Some(other) => FinishReason::Other(other.to_string()),
+        };
+
+        let mut llm_response =
+            LlmResponse::new(choice.message.content.unwrap_or_default(), &self.model)
+                .with_tool_calls(tool_calls)
+                .with_finish_reason(finish_reason);
+        if let Some(usage) = response.usage {
+            llm_response = llm_response
+                .with_usage(LlmUsage::new(usage.prompt_tokens, usage.completion_tokens));
+        }
+        if let Some(id) = response.id {
+            llm_response = llm_response.with_id(id);
+        }
+        Ok(llm_response)
+    }
+
+    /// Parse a TGI text-generation response to `GraphBit` response
+    fn parse_text_generation_response(
+        &self,
+        inputs: &str,
+        response: TextGenerationResponse,
+    ) -> GraphBitResult<LlmResponse> {
+        let output = match response {
+            TextGenerationResponse::Single(output) => output,
+            TextGenerationResponse::Batch(outputs) => {
+                outputs.into_iter().next().ok_or_else(|| {
+                    GraphBitError::llm_provider("huggingface", "No generated text in response")
+                })?
+            }
+        };
+        let generated_text = output.generated_text;
 
         // Extract only the assistant's response (after the last "Assistant: ")
         let content = if let Some(last_assistant) = generated_text.rfind("Assistant: ") {
@@ -88,114 +338,166 @@ impl HuggingFaceProvider {
             generated_text.trim().to_string()
         };
 
-        // `HuggingFace` doesn't provide usage stats in the same way, so we estimate
+        // TGI reports generated tokens in its details; otherwise estimate at 4 chars per token
+        let details = output.details.unwrap_or_default();
         let usage = LlmUsage::new(
-            (content.len() / 4) as u32, // Rough estimate: 4 chars per token
-            (content.len() / 4) as u32,
+            (inputs.len() / 4) as u32,
+            details
+                .generated_tokens
+                .unwrap_or((content.len() / 4) as u32),
         );
+        let finish_reason = match details.finish_reason.as_deref() {
+            Some("length") => FinishReason::Length,
+            _ => FinishReason::Stop,
+        };
 
         Ok(LlmResponse::new(content, &self.model)
             .with_usage(usage)
-            .with_finish_reason(FinishReason::Stop))
+            .with_finish_reason(finish_reason))
     }
-}
 
-#[async_trait]
-impl LlmProviderTrait for HuggingFaceProvider {
-    fn provider_name(&self) -> &str {
-        "huggingface"
-    }
+    async fn complete_chat(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
+        let url = format!("{}/chat/completions", self.base_url);
 
-    fn model_name(&self) -> &str {
-        &self.model
+        let tools: Option<Vec<HuggingFaceTool>> = if request.tools.is_empty() {
+            None
+        } else {
+            Some(request.tools.iter().map(Self::convert_tool).collect())
+        };
+        let body = HuggingFaceChatRequest {
+            model: self.routed_model(),
+            messages: request.messages.iter().map(Self::convert_message).collect(),
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+        };
+
+        // Add extra parameters
+        let mut request_json = serde_json::to_value(&body)?;
+        if let serde_json::Value::Object(ref mut map) = request_json {
+            for (key, value) in request.extra_params {
+                map.insert(key, value);
+            }
+        }
+
+        let response = crate::capture::send(
+            "huggingface",
+            self.authorize(self.client.post(&url))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("huggingface", &e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(self.api_error(status, &error_text));
+        }
+
+        let chat_response: HuggingFaceChatResponse = response.json().await.map_err(|e| {
+            GraphBitError::llm_provider("huggingface", format!("Failed to parse response: {e}"))
+        })?;
+
+        self.parse_chat_response(chat_response)
     }
 
-    async fn complete(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
-        let url = format!("{}/{}", self.base_url, self.model);
+    async fn complete_text_generation(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
+        let url = format!("{}/generate", self.base_url);
 
-        // Format messages for `HuggingFace`
         let inputs = Self::format_messages_for_chat(&request.messages);
 
         let mut parameters = HashMap::new();
         if let Some(max_tokens) = request.max_tokens {
-            parameters.insert(
-                "max_new_tokens".to_string(),
-                serde_json::Value::Number(max_tokens.into()),
-            );
+            parameters.insert("max_new_tokens".to_string(), serde_json::json!(max_tokens));
         }
         if let Some(temperature) = request.temperature {
-            parameters.insert(
-                "temperature".to_string(),
-                serde_json::Value::Number(
-                    serde_json::Number::from_f64(temperature as f64).unwrap(),
-                ),
-            );
+            parameters.insert("temperature".to_string(), serde_json::json!(temperature));
         }
         if let Some(top_p) = request.top_p {
-            parameters.insert(
-                "top_p".to_string(),
-                serde_json::Value::Number(serde_json::Number::from_f64(top_p as f64).unwrap()),
-            );
+            parameters.insert("top_p".to_string(), serde_json::json!(top_p));
         }
+        parameters.insert("return_full_text".to_string(), serde_json::json!(false));
+        parameters.insert("details".to_string(), serde_json::json!(true));
 
         // Add extra parameters
         for (key, value) in request.extra_params {
             parameters.insert(key, value);
         }
 
-        let body = HuggingFaceRequest {
-            inputs,
-            parameters: if parameters.is_empty() {
-                None
-            } else {
-                Some(parameters)
-            },
-            options: Some(HuggingFaceOptions {
-                wait_for_model: true,
-                use_cache: false,
-            }),
+        let body = TextGenerationRequest {
+            inputs: inputs.clone(),
+            parameters,
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("huggingface", format!("Request failed: {e}"))
-            })?;
-
-        if !response.status().is_success() {
+        let response = crate::capture::send(
+            "huggingface",
+            self.authorize(self.client.post(&url))
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("huggingface", &e))?;
+
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(GraphBitError::llm_provider(
-                "huggingface",
-                format!("API error: {error_text}"),
-            ));
+            return Err(self.api_error(status, &error_text));
         }
 
-        let hf_response: HuggingFaceResponse = response.json().await.map_err(|e| {
+        let tgi_response: TextGenerationResponse = response.json().await.map_err(|e| {
             GraphBitError::llm_provider("huggingface", format!("Failed to parse response: {e}"))
         })?;
 
-        self.parse_response(hf_response)
+        self.parse_text_generation_response(&inputs, tgi_response)
+    }
+}
+
+#[async_trait]
+impl LlmProviderTrait for HuggingFaceProvider {
+    fn provider_name(&self) -> &str {
+        "huggingface"
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
+        // The model card is read alongside the first request, so it adds no latency
+        if self.text_generation {
+            let (_, response) = tokio::join!(
+                self.load_context_length(),
+                self.complete_text_generation(request)
+            );
+            response
+        } else {
+            let (_, response) =
+                tokio::join!(self.load_context_length(), self.complete_chat(request));
+            response
+        }
     }
 
     fn supports_function_calling(&self) -> bool {
-        false // Most `HuggingFace` models don't support function calling out of the box
+        // The router passes tools through to providers that support them
+        !self.text_generation
     }
 
     fn supports_streaming(&self) -> bool {
-        false // Streaming would require different endpoint
+        false
     }
 
     fn max_context_length(&self) -> Option<u32> {
-        // Common context lengths for popular models, can be model-specific
-        if self.model.contains("llama") {
+        if let Some(Some(context_length)) = self.context_length.get() {
+            return Some(*context_length);
+        }
+        // Common context lengths for popular models until the model card is read
+        let model = self.model.to_lowercase();
+        if model.contains("llama") {
             Some(4096)
-        } else if self.model.contains("mistral") {
+        } else if model.contains("mistral") {
             Some(8192)
         } else {
             Some(2048) // Conservative default
@@ -207,26 +509,156 @@ impl LlmProviderTrait for HuggingFaceProvider {
         // This would need to be updated based on actual pricing
         Some((0.0001, 0.0001)) // Placeholder values
     }
+
+    fn has_cold_start(&self) -> bool {
+        // Models not kept warm by the inference provider load on first request
+        true
+    }
+}
+
+/// Read the context length from a model card `config.json` or a TGI `/info` response
+fn context_length_from_card(card: &serde_json::Value) -> Option<u32> {
+    const KEYS: [&str; 6] = [
+        "max_total_tokens",
+        "max_position_embeddings",
+        "n_positions",
+        "max_sequence_length",
+        "seq_length",
+        "n_ctx",
+    ];
+    let lookup = |value: &serde_json::Value| {
+        KEYS.iter()
+            .find_map(|key| value.get(key).and_then(serde_json::Value::as_u64))
+    };
+    // Multimodal models nest the language model's configuration
+    lookup(card)
+        .or_else(|| card.get("text_config").and_then(lookup))
+        .and_then(|length| u32::try_from(length).ok())
+}
+
+/// Error message and code of an error response, which the router and TGI send
+/// as `{"error": "..."}` or `{"error": {"message": "...", "code": "..."}}`
+fn parse_error_body(body: &str) -> (String, Option<String>) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return (body.trim().to_string(), None);
+    };
+    let error = value.get("error").unwrap_or(&value);
+    let message = error
+        .as_str()
+        .or_else(|| error.get("message").and_then(serde_json::Value::as_str))
+        .map_or_else(|| body.trim().to_string(), str::to_string);
+    let code = error
+        .get("code")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string);
+    (message, code)
+}
+
+/// The task named in an error such as "not supported for task text-generation"
+fn task_from_message(message: &str) -> Option<String> {
+    let lowered = message.to_lowercase();
+    let start = lowered.find("task")? + "task".len();
+    let task: String = lowered[start..]
+        .trim_start_matches(|c: char| c.is_whitespace() || matches!(c, ':' | '\'' | '"' | '`'))
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    (!task.is_empty()).then_some(task)
 }
 
 #[derive(Debug, Serialize)]
-struct HuggingFaceRequest {
-    inputs: String,
+struct HuggingFaceChatRequest {
+    model: String,
+    messages: Vec<HuggingFaceMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    parameters: Option<HashMap<String, serde_json::Value>>,
+    tools: Option<Vec<HuggingFaceTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    options: Option<HuggingFaceOptions>,
+    tool_choice: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HuggingFaceMessage {
+    role: String,
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<HuggingFaceToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HuggingFaceToolCall {
+    id: String,
+    r#type: String,
+    function: HuggingFaceFunction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HuggingFaceFunction {
+    name: String,
+    arguments: String,
+}
+
+#[derive(Debug, Serialize)]
+struct HuggingFaceTool {
+    r#type: String,
+    function: HuggingFaceFunctionDef,
 }
 
 #[derive(Debug, Serialize)]
-struct HuggingFaceOptions {
-    wait_for_model: bool,
-    use_cache: bool,
+struct HuggingFaceFunctionDef {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct HuggingFaceChatResponse {
+    id: Option<String>,
+    choices: Vec<HuggingFaceChoice>,
+    usage: Option<HuggingFaceUsage>,
 }
 
 #[derive(Debug, Deserialize)]
-struct HuggingFaceResponseItem {
+struct HuggingFaceChoice {
+    message: HuggingFaceMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HuggingFaceUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct TextGenerationRequest {
+    inputs: String,
+    parameters: HashMap<String, serde_json::Value>,
+}
+
+/// TGI's `/generate` returns one output; the legacy serverless API returned a list
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TextGenerationResponse {
+    Single(TextGenerationOutput),
+    Batch(Vec<TextGenerationOutput>),
+}
+
+#[derive(Debug, Deserialize)]
+struct TextGenerationOutput {
     generated_text: String,
+    details: Option<TextGenerationDetails>,
 }
 
-type HuggingFaceResponse = Vec<HuggingFaceResponseItem>;
+#[derive(Debug, Default, Deserialize)]
+struct TextGenerationDetails {
+    finish_reason: Option<String>,
+    generated_tokens: Option<u32>,
+}
@@ -0,0 +1,360 @@
+//! Middleware around LLM calls
+//!
+//! An [`LlmMiddleware`] sees every request before it reaches the provider and
+//! every response before it reaches the caller, e.g. to add headers through
+//! `extra_params`, rewrite model parameters or audit what is sent. Middleware
+//! is stacked on a provider with
+//! [`LlmProviderFactory::create_provider_with_middleware`](super::LlmProviderFactory::create_provider_with_middleware)
+//! or on every LLM call of a workflow with
+//! [`WorkflowExecutor::with_llm_middleware`](crate::workflow::WorkflowExecutor::with_llm_middleware).
+//!
+//! Every hook of a stack runs in registration order.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::health::ProviderHealth;
+use super::{LlmProviderTrait, LlmRequest, LlmResponse};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::pii;
+
+/// Hooks run around an LLM call
+#[async_trait]
+pub trait LlmMiddleware: Send + Sync {
+    /// Name used in logs and `Debug` output
+    fn name(&self) -> &str;
+
+    /// Inspect or modify a request before it is sent. An error aborts the call.
+    async fn before_request(&self, _request: &mut LlmRequest) -> GraphBitResult<()> {
+        Ok(())
+    }
+
+    /// Inspect or modify a response before it is returned. An error fails the call.
+    async fn after_response(
+        &self,
+        _request: &LlmRequest,
+        _response: &mut LlmResponse,
+    ) -> GraphBitResult<()> {
+        Ok(())
+    }
+
+    /// Observe an error failing the call, whether it came from the provider or
+    /// from a middleware
+    async fn on_error(&self, _request: &LlmRequest, _error: &GraphBitError) {}
+}
+
+/// Middleware applied in registration order
+#[derive(Clone, Default)]
+pub struct LlmMiddlewareStack {
+    layers: Vec<Arc<dyn LlmMiddleware>>,
+}
+
+impl LlmMiddlewareStack {
+    /// Create an empty stack
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a middleware after the ones already registered
+    pub fn push(&mut self, middleware: Arc<dyn LlmMiddleware>) {
+        self.layers.push(middleware);
+    }
+
+    /// Add a middleware after the ones already registered
+    #[must_use]
+    pub fn with(mut self, middleware: Arc<dyn LlmMiddleware>) -> Self {
+        self.push(middleware);
+        self
+    }
+
+    /// Number of middleware in the stack
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Whether the stack has no middleware
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Send `request` to `provider` through the stack
+    pub async fn complete(
+        &self,
+        provider: &dyn LlmProviderTrait,
+        request: LlmRequest,
+    ) -> GraphBitResult<LlmResponse> {
+        self.wrap(request, |request| provider.complete(request))
+            .await
+    }
+
+    /// Run `call` with the request prepared by the stack, passing its response
+    /// or error through the stack
+    pub async fn wrap<F, Fut>(
+        &self,
+        mut request: LlmRequest,
+        call: F,
+    ) -> GraphBitResult<LlmResponse>
+    where
+        F: FnOnce(LlmRequest) -> Fut + Send,
+        Fut: Future<Output = GraphBitResult<LlmResponse>> + Send,
+    {
+        if self.is_empty() {
+            return call(request).await;
+        }
+        let result = match self.before_request(&mut request).await {
+            Ok(()) => match call(request.clone()).await {
+                Ok(mut response) => self
+                    .after_response(&request, &mut response)
+                    .await
+                    .map(|()| response),
+                Err(error) => Err(error),
+            },
+            Err(error) => Err(error),
+        };
+        if let Err(error) = &result {
+            self.on_error(&request, error).await;
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl LlmMiddleware for LlmMiddlewareStack {
+    fn name(&self) -> &str {
+        "stack"
+    }
+
+    async fn before_request(&self, request: &mut LlmRequest) -> GraphBitResult<()> {
+        for layer in &self.layers {
+            layer.before_request(request).await?;
+        }
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        request: &LlmRequest,
+        response: &mut LlmResponse,
+    ) -> GraphBitResult<()> {
+        for layer in &self.layers {
+            layer.after_response(request, response).await?;
+        }
+        Ok(())
+    }
+
+    async fn on_error(&self, request: &LlmRequest, error: &GraphBitError) {
+        for layer in &self.layers {
+            layer.on_error(request, error).await;
+        }
+    }
+}
+
+impl fmt::Debug for LlmMiddlewareStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.layers.iter().map(|layer| layer.name()))
+            .finish()
+    }
+}
+
+impl FromIterator<Arc<dyn LlmMiddleware>> for LlmMiddlewareStack {
+    fn from_iter<I: IntoIterator<Item = Arc<dyn LlmMiddleware>>>(iter: I) -> Self {
+        Self {
+            layers: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Provider running its calls through a middleware stack.
+///
+/// Streams get `before_request` and, when the stream cannot be opened,
+/// `on_error`; their chunks do not pass through `after_response`.
+pub struct MiddlewareProvider {
+    inner: Box<dyn LlmProviderTrait>,
+    middleware: LlmMiddlewareStack,
+}
+
+impl MiddlewareProvider {
+    /// Wrap `inner` in `middleware`
+    #[must_use]
+    pub fn new(inner: Box<dyn LlmProviderTrait>, middleware: LlmMiddlewareStack) -> Self {
+        Self { inner, middleware }
+    }
+
+    /// The middleware applied to every call
+    #[must_use]
+    pub const fn middleware(&self) -> &LlmMiddlewareStack {
+        &self.middleware
+    }
+}
+
+#[async_trait]
+impl LlmProviderTrait for MiddlewareProvider {
+    fn provider_name(&self) -> &str {
+        self.inner.provider_name()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    async fn complete(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
+        self.middleware.complete(self.inner.as_ref(), request).await
+    }
+
+    async fn stream(
+        &self,
+        mut request: LlmRequest,
+    ) -> GraphBitResult<Box<dyn futures::Stream<Item = GraphBitResult<LlmResponse>> + Unpin + Send>>
+    {
+        let result = match self.middleware.before_request(&mut request).await {
+            Ok(()) => self.inner.stream(request.clone()).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = &result {
+            self.middleware.on_error(&request, error).await;
+        }
+        result
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_function_calling(&self) -> bool {
+        self.inner.supports_function_calling()
+    }
+
+    fn supports_image_inputs(&self) -> bool {
+        self.inner.supports_image_inputs()
+    }
+
+    fn max_context_length(&self) -> Option<u32> {
+        self.inner.max_context_length()
+    }
+
+    fn cost_per_token(&self) -> Option<(f64, f64)> {
+        self.inner.cost_per_token()
+    }
+
+    fn has_cold_start(&self) -> bool {
+        self.inner.has_cold_start()
+    }
+
+    async fn health_check(&self) -> ProviderHealth {
+        self.inner.health_check().await
+    }
+}
+
+/// Replaces sensitive text in message contents with `[REDACTED_<NAME>]`.
+///
+/// [`RedactionMiddleware::new`] covers email addresses, API keys, bearer
+/// tokens and card numbers; more patterns can be added by name. Responses are
+/// left untouched unless [`RedactionMiddleware::with_response_redaction`] is set.
+#[derive(Debug, Clone)]
+pub struct RedactionMiddleware {
+    rules: Vec<(String, Regex)>,
+    redact_responses: bool,
+}
+
+impl RedactionMiddleware {
+    /// Redaction with the built-in patterns
+    #[must_use]
+    pub fn new() -> Self {
+        const DEFAULT_RULES: [(&str, &str); 4] = [
+            ("EMAIL", pii::EMAIL),
+            (
+                "API_KEY",
+                r"\b(?:sk-[A-Za-z0-9_-]{16,}|hf_[A-Za-z0-9]{16,}|AKIA[0-9A-Z]{16})\b",
+            ),
+            ("BEARER_TOKEN", r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]{8,}=*"),
+            ("CARD_NUMBER", pii::CREDIT_CARD),
+        ];
+        let rules = DEFAULT_RULES
+            .iter()
+            .map(|(name, pattern)| ((*name).to_string(), Regex::new(pattern).unwrap()))
+            .collect();
+        Self {
+            rules,
+            redact_responses: false,
+        }
+    }
+
+    /// Redaction with no patterns, to be added with [`RedactionMiddleware::with_pattern`]
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            redact_responses: false,
+        }
+    }
+
+    /// Also redact text matching `pattern`, replacing it with `[REDACTED_<NAME>]`
+    pub fn with_pattern(mut self, name: &str, pattern: &str) -> GraphBitResult<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            GraphBitError::validation("pattern", format!("Invalid redaction pattern: {e}"))
+        })?;
+        self.rules.push((name.to_uppercase(), regex));
+        Ok(self)
+    }
+
+    /// Also redact response contents
+    #[must_use]
+    pub const fn with_response_redaction(mut self, redact_responses: bool) -> Self {
+        self.redact_responses = redact_responses;
+        self
+    }
+
+    /// Apply every pattern to `text`
+    #[must_use]
+    pub fn redact(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for (name, regex) in &self.rules {
+            if regex.is_match(&redacted) {
+                let label = format!("[REDACTED_{name}]");
+                redacted = regex
+                    .replace_all(&redacted, regex::NoExpand(&label))
+                    .into_owned();
+            }
+        }
+        redacted
+    }
+}
+
+impl Default for RedactionMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LlmMiddleware for RedactionMiddleware {
+    fn name(&self) -> &str {
+        "redaction"
+    }
+
+    async fn before_request(&self, request: &mut LlmRequest) -> GraphBitResult<()> {
+        for message in &mut request.messages {
+            message.content = self.redact(&message.content);
+        }
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        _request: &LlmRequest,
+        response: &mut LlmResponse,
+    ) -> GraphBitResult<()> {
+        if self.redact_responses {
+            response.content = self.redact(&response.content);
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! `MistralAI` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -23,19 +24,7 @@ pub struct MistralAiProvider {
 impl MistralAiProvider {
     /// Create a new `MistralAI` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "mistralai",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.mistral.ai/v1".to_string();
 
         Ok(Self {
@@ -48,19 +37,7 @@ impl MistralAiProvider {
 
     /// Create a new `MistralAI` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "mistralai",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -70,6 +47,17 @@ impl MistralAiProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("mistralai", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `MistralAI` message format
     fn convert_message(message: &LlmMessage) -> MistralAiMessage {
         MistralAiMessage {
@@ -230,17 +218,16 @@ impl LlmProviderTrait for MistralAiProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("mistralai", format!("Request failed: {e}"))
-            })?;
+        let response = crate::capture::send(
+            "mistralai",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("mistralai", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -339,7 +326,7 @@ impl LlmProviderTrait for MistralAiProvider {
         )
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "mistralai",
                 format!(
                     "Connection timeout after {:?} - MistralAI did not respond. \
@@ -348,7 +335,7 @@ impl LlmProviderTrait for MistralAiProvider {
                 ),
             )
         })?
-        .map_err(|e| GraphBitError::llm_provider("mistralai", format!("Request failed: {e}")))?;
+        .map_err(|e| GraphBitError::from_request_error("mistralai", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -495,7 +482,7 @@ impl LlmProviderTrait for MistralAiProvider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "mistralai",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
@@ -7,26 +7,45 @@ pub mod ai21;
 pub mod anthropic;
 pub mod azurellm;
 pub mod bytedance;
+pub mod catalog;
+pub mod context_window;
 pub mod deepseek;
 pub mod fireworks;
 pub mod gemini;
+pub mod health;
+pub mod history_window;
 pub mod huggingface;
+pub mod middleware;
 pub mod mistralai;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
 pub mod perplexity;
+pub mod profile;
 pub mod providers;
 pub mod python_bridge;
 pub mod replicate;
+pub mod rerank;
 pub mod response;
 pub mod togetherai;
+pub mod tool_schema;
 pub mod xai;
 
+pub use catalog::ModelInfo;
+pub use context_window::{ContextFit, ContextStrategy, ContextWindow};
+pub use health::ProviderHealth;
+pub use history_window::{HistorySummary, HistoryTrim, HistoryWindow};
+pub use middleware::{LlmMiddleware, LlmMiddlewareStack, MiddlewareProvider, RedactionMiddleware};
+pub use profile::{ConfigProfile, LlmOverride};
+pub use rerank::{RerankConfig, RerankedDocument, Reranking};
 pub use providers::{LlmConfig, LlmProvider, LlmProviderTrait};
 pub use response::{FinishReason, LlmResponse, LlmUsage};
+pub use tool_schema::{
+    SchemaMode, SchemaWarning, ToolDialect, ToolSchemaTranslator, TranslatedTools,
+};
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::RequestTimeouts;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -158,6 +177,9 @@ pub struct LlmMessage {
     /// Optional tool call ID this message responds to (for tool result messages)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Images sent with the message to vision-capable models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<LlmImage>,
 }
 
 impl LlmMessage {
@@ -168,6 +190,7 @@ impl LlmMessage {
             content: content.into(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         }
     }
 
@@ -178,6 +201,7 @@ impl LlmMessage {
             content: content.into(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         }
     }
 
@@ -188,6 +212,7 @@ impl LlmMessage {
             content: content.into(),
             tool_calls: Vec::new(),
             tool_call_id: None,
+            images: Vec::new(),
         }
     }
 
@@ -199,6 +224,7 @@ impl LlmMessage {
             content: result.into(),
             tool_calls: Vec::new(),
             tool_call_id: Some(id),
+            images: Vec::new(),
         }
     }
 
@@ -209,6 +235,14 @@ impl LlmMessage {
         self
     }
 
+    /// Send images with the message
+    #[inline]
+    #[must_use]
+    pub fn with_images(mut self, images: Vec<LlmImage>) -> Self {
+        self.images = images;
+        self
+    }
+
     /// Get content length for performance estimation
     #[inline]
     pub fn content_length(&self) -> usize {
@@ -216,6 +250,23 @@ impl LlmMessage {
     }
 }
 
+/// Image sent with a message to a vision-capable model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmImage {
+    /// MIME type of the image, e.g. `image/png`
+    pub mime_type: String,
+    /// Base64-encoded image
+    pub data: String,
+}
+
+impl LlmImage {
+    /// The image as a base64 `data:` URL
+    #[must_use]
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+}
+
 /// Role of a message sender
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -272,13 +323,56 @@ pub struct LlmProviderFactory;
 
 impl LlmProviderFactory {
     /// Create a new LLM provider from configuration
+    ///
+    /// The configuration's [`RequestTimeouts`](crate::http_client::RequestTimeouts)
+    /// replace the timeouts the provider's HTTP client would otherwise use, and
+    /// an `api_key` reference is resolved, see [`crate::secret_ref`].
     pub fn create_provider(config: LlmConfig) -> GraphBitResult<Box<dyn LlmProviderTrait>> {
+        let config = config.with_resolved_api_key()?;
+        let timeouts = config.timeouts();
+        timeouts.validate()?;
+        Self::build_provider(config, timeouts)
+    }
+
+    fn build_provider(
+        config: LlmConfig,
+        timeouts: RequestTimeouts,
+    ) -> GraphBitResult<Box<dyn LlmProviderTrait>> {
         match config {
-            LlmConfig::OpenAI { api_key, model, .. } => {
-                Ok(Box::new(openai::OpenAiProvider::new(api_key, model)?))
+            LlmConfig::OpenAI {
+                api_key,
+                model,
+                base_url,
+                ..
+            } => {
+                if let Some(base_url) = base_url {
+                    Ok(Box::new(
+                        openai::OpenAiProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
+                } else {
+                    Ok(Box::new(
+                        openai::OpenAiProvider::new(api_key, model)?.with_timeouts(timeouts)?,
+                    ))
+                }
             }
-            LlmConfig::Anthropic { api_key, model, .. } => {
-                Ok(Box::new(anthropic::AnthropicProvider::new(api_key, model)?))
+            LlmConfig::Anthropic {
+                api_key,
+                model,
+                base_url,
+                ..
+            } => {
+                if let Some(base_url) = base_url {
+                    Ok(Box::new(
+                        anthropic::AnthropicProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
+                } else {
+                    Ok(Box::new(
+                        anthropic::AnthropicProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
+                }
             }
             LlmConfig::AzureLlm {
                 api_key,
@@ -286,12 +380,10 @@ impl LlmProviderFactory {
                 endpoint,
                 api_version,
                 ..
-            } => Ok(Box::new(azurellm::AzureLlmProvider::new(
-                api_key,
-                deployment_name,
-                endpoint,
-                api_version,
-            )?)),
+            } => Ok(Box::new(
+                azurellm::AzureLlmProvider::new(api_key, deployment_name, endpoint, api_version)?
+                    .with_timeouts(timeouts)?,
+            )),
             LlmConfig::ByteDance {
                 api_key,
                 model,
@@ -299,11 +391,15 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(bytedance::ByteDanceProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        bytedance::ByteDanceProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(bytedance::ByteDanceProvider::new(api_key, model)?))
+                    Ok(Box::new(
+                        bytedance::ByteDanceProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::DeepSeek {
@@ -313,38 +409,54 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(deepseek::DeepSeekProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        deepseek::DeepSeekProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(deepseek::DeepSeekProvider::new(api_key, model)?))
+                    Ok(Box::new(
+                        deepseek::DeepSeekProvider::new(api_key, model)?.with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::HuggingFace {
                 api_key,
                 model,
                 base_url,
+                inference_provider,
+                text_generation,
                 ..
             } => {
-                if let Some(base_url) = base_url {
-                    Ok(Box::new(huggingface::HuggingFaceProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
-                } else {
-                    Ok(Box::new(huggingface::HuggingFaceProvider::new(
-                        api_key, model,
-                    )?))
+                let mut provider = match base_url {
+                    Some(base_url) => {
+                        huggingface::HuggingFaceProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?
+                    }
+                    None if text_generation => {
+                        return Err(GraphBitError::config(
+                            "HuggingFace text_generation requires a base_url pointing at a TGI endpoint",
+                        ));
+                    }
+                    None => huggingface::HuggingFaceProvider::new(api_key, model)?
+                        .with_timeouts(timeouts)?,
+                };
+                if let Some(inference_provider) = inference_provider {
+                    provider = provider.with_inference_provider(inference_provider);
                 }
+                Ok(Box::new(provider.with_text_generation(text_generation)))
             }
             LlmConfig::Ollama {
                 model, base_url, ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(ollama::OllamaProvider::with_base_url(
-                        model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        ollama::OllamaProvider::with_base_url(model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(ollama::OllamaProvider::new(model)?))
+                    Ok(Box::new(
+                        ollama::OllamaProvider::new(model)?.with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::Perplexity {
@@ -354,13 +466,15 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(perplexity::PerplexityProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        perplexity::PerplexityProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(perplexity::PerplexityProvider::new(
-                        api_key, model,
-                    )?))
+                    Ok(Box::new(
+                        perplexity::PerplexityProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::OpenRouter {
@@ -372,17 +486,22 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(openrouter::OpenRouterProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        openrouter::OpenRouterProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else if site_url.is_some() || site_name.is_some() {
-                    Ok(Box::new(openrouter::OpenRouterProvider::with_site_info(
-                        api_key, model, site_url, site_name,
-                    )?))
+                    Ok(Box::new(
+                        openrouter::OpenRouterProvider::with_site_info(
+                            api_key, model, site_url, site_name,
+                        )?
+                        .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(openrouter::OpenRouterProvider::new(
-                        api_key, model,
-                    )?))
+                    Ok(Box::new(
+                        openrouter::OpenRouterProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::Fireworks {
@@ -392,11 +511,15 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(fireworks::FireworksProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        fireworks::FireworksProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(fireworks::FireworksProvider::new(api_key, model)?))
+                    Ok(Box::new(
+                        fireworks::FireworksProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::Replicate {
@@ -408,8 +531,9 @@ impl LlmProviderFactory {
             } => {
                 let mut provider = if let Some(base_url) = base_url {
                     replicate::ReplicateProvider::with_base_url(api_key, model, base_url)?
+                        .with_timeouts(timeouts)?
                 } else {
-                    replicate::ReplicateProvider::new(api_key, model)?
+                    replicate::ReplicateProvider::new(api_key, model)?.with_timeouts(timeouts)?
                 };
 
                 if let Some(version) = version {
@@ -425,13 +549,15 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(togetherai::TogetherAiProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        togetherai::TogetherAiProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(togetherai::TogetherAiProvider::new(
-                        api_key, model,
-                    )?))
+                    Ok(Box::new(
+                        togetherai::TogetherAiProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::Xai {
@@ -441,11 +567,14 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(xai::XaiProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        xai::XaiProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(xai::XaiProvider::new(api_key, model)?))
+                    Ok(Box::new(
+                        xai::XaiProvider::new(api_key, model)?.with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::Ai21 {
@@ -455,11 +584,14 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(ai21::Ai21Provider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        ai21::Ai21Provider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(ai21::Ai21Provider::new(api_key, model)?))
+                    Ok(Box::new(
+                        ai21::Ai21Provider::new(api_key, model)?.with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::MistralAI {
@@ -469,26 +601,31 @@ impl LlmProviderFactory {
                 ..
             } => {
                 if let Some(base_url) = base_url {
-                    Ok(Box::new(mistralai::MistralAiProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                    Ok(Box::new(
+                        mistralai::MistralAiProvider::with_base_url(api_key, model, base_url)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 } else {
-                    Ok(Box::new(mistralai::MistralAiProvider::new(api_key, model)?))
+                    Ok(Box::new(
+                        mistralai::MistralAiProvider::new(api_key, model)?
+                            .with_timeouts(timeouts)?,
+                    ))
                 }
             }
             LlmConfig::Gemini {
                 api_key,
                 model,
                 base_url,
+                safety_settings,
                 ..
             } => {
-                if let Some(base_url) = base_url {
-                    Ok(Box::new(gemini::GeminiProvider::with_base_url(
-                        api_key, model, base_url,
-                    )?))
+                let provider = if let Some(base_url) = base_url {
+                    gemini::GeminiProvider::with_base_url(api_key, model, base_url)?
+                        .with_timeouts(timeouts)?
                 } else {
-                    Ok(Box::new(gemini::GeminiProvider::new(api_key, model)?))
-                }
+                    gemini::GeminiProvider::new(api_key, model)?.with_timeouts(timeouts)?
+                };
+                Ok(Box::new(provider.with_safety_settings(safety_settings)))
             }
             #[cfg(feature = "python")]
             LlmConfig::PythonBridge {
@@ -526,6 +663,18 @@ impl LlmProviderFactory {
         }
     }
 
+    /// Create a provider whose calls run through `middleware`
+    pub fn create_provider_with_middleware(
+        config: LlmConfig,
+        middleware: LlmMiddlewareStack,
+    ) -> GraphBitResult<Box<dyn LlmProviderTrait>> {
+        let provider = Self::create_provider(config)?;
+        if middleware.is_empty() {
+            return Ok(provider);
+        }
+        Ok(Box::new(MiddlewareProvider::new(provider, middleware)))
+    }
+
     /// Create multiple providers for load balancing
     pub fn create_providers(
         configs: Vec<LlmConfig>,
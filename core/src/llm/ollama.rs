@@ -1,6 +1,7 @@
 //! `Ollama` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -26,12 +27,7 @@ pub struct OllamaProvider {
 impl OllamaProvider {
     /// Create a new `Ollama` provider
     pub fn new(model: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(120)) // Reasonable timeout
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("ollama", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "http://localhost:11434".to_string();
 
         Ok(Self {
@@ -44,12 +40,7 @@ impl OllamaProvider {
 
     /// Create a new `Ollama` provider with custom base URL
     pub fn with_base_url(model: String, base_url: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("ollama", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -59,6 +50,17 @@ impl OllamaProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("ollama", Some(Duration::from_secs(120)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `Ollama` message format
     fn convert_message(message: &LlmMessage) -> OllamaMessage {
         OllamaMessage {
@@ -295,22 +297,26 @@ impl LlmProviderTrait for OllamaProvider {
             },
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "ollama",
-                    format!(
-                        "Request failed: {e}. Make sure Ollama is running on {}",
-                        self.base_url
-                    ),
-                )
-            })?;
+        let response = crate::capture::send(
+            "ollama",
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                return GraphBitError::from_request_error("ollama", &e);
+            }
+            GraphBitError::llm_provider(
+                "ollama",
+                format!(
+                    "Request failed: {e}. Make sure Ollama is running on {}",
+                    self.base_url
+                ),
+            )
+        })?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -410,7 +416,7 @@ impl LlmProviderTrait for OllamaProvider {
         )
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "ollama",
                 format!(
                     "Connection timeout after {:?} - Ollama did not respond. \
@@ -420,6 +426,9 @@ impl LlmProviderTrait for OllamaProvider {
             )
         })?
         .map_err(|e| {
+            if e.is_timeout() {
+                return GraphBitError::from_request_error("ollama", &e);
+            }
             GraphBitError::llm_provider(
                 "ollama",
                 format!(
@@ -568,7 +577,7 @@ impl LlmProviderTrait for OllamaProvider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "ollama",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
@@ -631,6 +640,11 @@ impl LlmProviderTrait for OllamaProvider {
         // `Ollama` is typically free for local usage
         Some((0.0, 0.0))
     }
+
+    fn has_cold_start(&self) -> bool {
+        // The model is loaded into memory, and pulled if missing, on first use
+        true
+    }
 }
 
 // `Ollama` API request structures
@@ -1,7 +1,10 @@
 //! `OpenAI` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, retry_after_seconds, shared_client_with_timeouts};
+use crate::llm::health::{self, ProviderHealth};
 use crate::llm::providers::LlmProviderTrait;
+use crate::llm::tool_schema::native_parameters;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
 };
@@ -25,16 +28,7 @@ pub struct OpenAiProvider {
 impl OpenAiProvider {
     /// Create a new `OpenAI` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("openai", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.openai.com/v1".to_string();
 
         Ok(Self {
@@ -48,16 +42,7 @@ impl OpenAiProvider {
 
     /// Create a new `OpenAI` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("openai", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -68,6 +53,17 @@ impl OpenAiProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("openai", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Set organization ID
     pub fn with_organization(mut self, organization: String) -> Self {
         self.organization = Some(organization);
@@ -83,7 +79,19 @@ impl OpenAiProvider {
                 LlmRole::System => "system".to_string(),
                 LlmRole::Tool => "tool".to_string(),
             },
-            content: message.content.clone(),
+            content: if message.images.is_empty() {
+                OpenAiContent::Text(message.content.clone())
+            } else {
+                // Images go in content parts after the text
+                let text = serde_json::json!({ "type": "text", "text": message.content });
+                let images = message.images.iter().map(|image| {
+                    serde_json::json!({
+                        "type": "image_url",
+                        "image_url": { "url": image.data_url() }
+                    })
+                });
+                OpenAiContent::Parts(std::iter::once(text).chain(images).collect())
+            },
             tool_calls: if message.tool_calls.is_empty() {
                 None
             } else {
@@ -113,7 +121,7 @@ impl OpenAiProvider {
             function: OpenAiFunctionDef {
                 name: tool.name.clone(),
                 description: tool.description.clone(),
-                parameters: tool.parameters.clone(),
+                parameters: native_parameters("openai", tool),
             },
         }
     }
@@ -126,7 +134,7 @@ impl OpenAiProvider {
             .next()
             .ok_or_else(|| GraphBitError::llm_provider("openai", "No choices in response"))?;
 
-        let mut content = choice.message.content;
+        let mut content = choice.message.content.into_text();
         if content.trim().is_empty()
             && !choice
                 .message
@@ -241,19 +249,22 @@ impl LlmProviderTrait for OpenAiProvider {
             req_builder = req_builder.header("OpenAI-Organization", org);
         }
 
-        let response = req_builder
-            .send()
+        let response = crate::capture::send("openai", req_builder)
             .await
-            .map_err(|e| GraphBitError::llm_provider("openai", format!("Request failed: {e}")))?;
+            .map_err(|e| GraphBitError::from_request_error("openai", &e))?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let retry_after = retry_after_seconds(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(GraphBitError::llm_provider(
+            return Err(GraphBitError::from_http_status(
                 "openai",
-                format!("API error: {error_text}"),
+                status,
+                retry_after,
+                error_text,
             ));
         }
 
@@ -264,6 +275,10 @@ impl LlmProviderTrait for OpenAiProvider {
         self.parse_response(openai_response)
     }
 
+    fn supports_image_inputs(&self) -> bool {
+        true
+    }
+
     fn supports_function_calling(&self) -> bool {
         // Most `OpenAI` models support function calling
         matches!(
@@ -341,7 +356,7 @@ impl LlmProviderTrait for OpenAiProvider {
         let response = timeout(CONNECTION_TIMEOUT, req_builder.send())
             .await
             .map_err(|_| {
-                GraphBitError::llm_provider(
+                GraphBitError::timeout(
                     "openai",
                     format!(
                         "Connection timeout after {:?} - OpenAI did not respond. \
@@ -350,9 +365,11 @@ impl LlmProviderTrait for OpenAiProvider {
                     ),
                 )
             })?
-            .map_err(|e| GraphBitError::llm_provider("openai", format!("Request failed: {e}")))?;
+            .map_err(|e| GraphBitError::from_request_error("openai", &e))?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let retry_after = retry_after_seconds(response.headers());
             // Apply timeout to reading error body to prevent hanging on malformed responses
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
                 .await
@@ -364,9 +381,11 @@ impl LlmProviderTrait for OpenAiProvider {
                 })
                 .unwrap_or_else(|_| "Unknown error (failed to read body)".to_string());
 
-            return Err(GraphBitError::llm_provider(
+            return Err(GraphBitError::from_http_status(
                 "openai",
-                format!("API error: {error_text}"),
+                status,
+                retry_after,
+                error_text,
             ));
         }
 
@@ -439,7 +458,7 @@ impl LlmProviderTrait for OpenAiProvider {
                                     );
                                     // Return error to notify user, then end stream
                                     return Some((
-                                        Err(GraphBitError::llm_provider(
+                                        Err(GraphBitError::timeout(
                                             "openai",
                                             format!(
                                                 "Stream timeout after {:?} - response may be incomplete",
@@ -753,6 +772,41 @@ impl LlmProviderTrait for OpenAiProvider {
             _ => None,
         }
     }
+
+    async fn health_check(&self) -> ProviderHealth {
+        health::probe("openai", &self.model, self.retrieve_model()).await
+    }
+}
+
+impl OpenAiProvider {
+    /// Look the model up on the models endpoint, which costs no tokens
+    async fn retrieve_model(&self) -> GraphBitResult<()> {
+        let url = format!("{}/models/{}", self.base_url, self.model);
+        let mut req_builder = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key));
+        if let Some(org) = &self.organization {
+            req_builder = req_builder.header("OpenAI-Organization", org);
+        }
+
+        let response = req_builder.send().await?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let retry_after = retry_after_seconds(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(GraphBitError::from_http_status(
+                "openai",
+                status,
+                retry_after,
+                error_text,
+            ));
+        }
+        Ok(())
+    }
 }
 
 // `OpenAI` API types
@@ -785,7 +839,7 @@ struct OpenAiStreamOptions {
 struct OpenAiMessage {
     role: String,
     #[serde(deserialize_with = "deserialize_nullable_content")]
-    content: String,
+    content: OpenAiContent,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAiToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -837,14 +891,35 @@ struct OpenAiUsage {
     completion_tokens: u32,
 }
 
+/// Message content: text, or content parts when the message carries images
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum OpenAiContent {
+    Text(String),
+    Parts(Vec<serde_json::Value>),
+}
+
+impl OpenAiContent {
+    /// The text of the content, ignoring any images
+    fn into_text(self) -> String {
+        match self {
+            Self::Text(text) => text,
+            Self::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(serde_json::Value::as_str))
+                .collect(),
+        }
+    }
+}
+
 /// Custom deserializer for nullable content field
 /// `OpenAI` returns null for content when tool calls are made
-fn deserialize_nullable_content<'de, D>(deserializer: D) -> Result<String, D::Error>
+fn deserialize_nullable_content<'de, D>(deserializer: D) -> Result<OpenAiContent, D::Error>
 where
     D: Deserializer<'de>,
 {
     let opt: Option<String> = Option::deserialize(deserializer)?;
-    Ok(opt.unwrap_or_default())
+    Ok(OpenAiContent::Text(opt.unwrap_or_default()))
 }
 
 // Streaming-specific types
@@ -1076,7 +1151,7 @@ mod tests {
             model: "gpt-4o-mini".to_string(),
             messages: vec![OpenAiMessage {
                 role: "user".to_string(),
-                content: "hello".to_string(),
+                content: OpenAiContent::Text("hello".to_string()),
                 tool_calls: None,
                 tool_call_id: None,
             }],
@@ -5,6 +5,7 @@
 //! and provider preferences.
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -12,6 +13,7 @@ use crate::llm::{
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// `OpenRouter` API provider
 pub struct OpenRouterProvider {
@@ -26,19 +28,7 @@ pub struct OpenRouterProvider {
 impl OpenRouterProvider {
     /// Create a new `OpenRouter` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "openrouter",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://openrouter.ai/api/v1".to_string();
 
         Ok(Self {
@@ -53,19 +43,7 @@ impl OpenRouterProvider {
 
     /// Create a new `OpenRouter` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "openrouter",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -77,6 +55,17 @@ impl OpenRouterProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("openrouter", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Create a new `OpenRouter` provider with site information for rankings
     pub fn with_site_info(
         api_key: String,
@@ -235,13 +224,9 @@ impl LlmProviderTrait for OpenRouterProvider {
             request_builder = request_builder.header("X-Title", site_name);
         }
 
-        let response = request_builder
-            .json(&request_json)
-            .send()
+        let response = crate::capture::send("openrouter", request_builder.json(&request_json))
             .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("openrouter", format!("Request failed: {e}"))
-            })?;
+            .map_err(|e| GraphBitError::from_request_error("openrouter", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -1,6 +1,7 @@
 //! `Perplexity` AI LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -23,19 +24,7 @@ pub struct PerplexityProvider {
 impl PerplexityProvider {
     /// Create a new `Perplexity` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "perplexity",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.perplexity.ai".to_string();
 
         Ok(Self {
@@ -48,18 +37,7 @@ impl PerplexityProvider {
 
     /// Create a new `Perplexity` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "perplexity",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -69,6 +47,17 @@ impl PerplexityProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("perplexity", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `Perplexity` message format (`OpenAI`-compatible)
     fn convert_message(message: &LlmMessage) -> PerplexityMessage {
         PerplexityMessage {
@@ -199,18 +188,17 @@ impl LlmProviderTrait for PerplexityProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("perplexity", format!("Request failed: {e}"))
-            })?;
+        let response = crate::capture::send(
+            "perplexity",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("perplexity", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -328,7 +316,7 @@ impl LlmProviderTrait for PerplexityProvider {
         )
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "perplexity",
                 format!(
                     "Connection timeout after {:?} - Perplexity did not respond. \
@@ -337,7 +325,7 @@ impl LlmProviderTrait for PerplexityProvider {
                 ),
             )
         })?
-        .map_err(|e| GraphBitError::llm_provider("perplexity", format!("Request failed: {e}")))?;
+        .map_err(|e| GraphBitError::from_request_error("perplexity", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -411,7 +399,7 @@ impl LlmProviderTrait for PerplexityProvider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "perplexity",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
@@ -0,0 +1,164 @@
+//! Named LLM configuration profiles
+//!
+//! A profile lets one workflow run on different models per environment, e.g. a
+//! hosted model in production and a local one in development, without editing
+//! node configurations. An executor holds any number of named profiles and one
+//! is selected per execution.
+//!
+//! For an agent node, the profile's defaults apply first, then the overrides
+//! of the node's tags in tag order, then the override for the node's name. A
+//! node's own `llm_config` and `model` still take precedence over the profile,
+//! and the executor-level configuration applies where the profile sets nothing.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::LlmConfig;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::graph::WorkflowNode;
+
+/// LLM configuration and model a profile applies to agent nodes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmOverride {
+    /// Configuration replacing the one the node would otherwise use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_config: Option<LlmConfig>,
+    /// Model applied on top of the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl LlmOverride {
+    /// Override replacing the LLM configuration
+    #[must_use]
+    pub const fn config(llm_config: LlmConfig) -> Self {
+        Self {
+            llm_config: Some(llm_config),
+            model: None,
+        }
+    }
+
+    /// Override keeping the provider and changing the model
+    #[must_use]
+    pub fn model(model: impl Into<String>) -> Self {
+        Self {
+            llm_config: None,
+            model: Some(model.into()),
+        }
+    }
+
+    /// Whether the override changes nothing
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.llm_config.is_none() && self.model.is_none()
+    }
+
+    /// Apply the override to `base`
+    fn apply(&self, base: Option<LlmConfig>) -> Option<LlmConfig> {
+        let config = self.llm_config.clone().or(base)?;
+        Some(match self.model.as_deref() {
+            Some(model) if !model.trim().is_empty() => config.with_model(model),
+            _ => config,
+        })
+    }
+}
+
+/// Named set of LLM settings selected at execution time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigProfile {
+    /// Settings for every agent node
+    #[serde(flatten)]
+    pub defaults: LlmOverride,
+    /// Settings for agent nodes with a tag, keyed by tag
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tags: HashMap<String, LlmOverride>,
+    /// Settings for individual agent nodes, keyed by node name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub nodes: HashMap<String, LlmOverride>,
+}
+
+impl ConfigProfile {
+    /// Profile giving every agent node `llm_config`
+    #[must_use]
+    pub fn new(llm_config: LlmConfig) -> Self {
+        Self {
+            defaults: LlmOverride::config(llm_config),
+            ..Self::default()
+        }
+    }
+
+    /// Set the model of every agent node
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.defaults.model = Some(model.into());
+        self
+    }
+
+    /// Override the settings of agent nodes tagged `tag`
+    #[must_use]
+    pub fn with_tag_override(mut self, tag: impl Into<String>, settings: LlmOverride) -> Self {
+        self.tags.insert(tag.into(), settings);
+        self
+    }
+
+    /// Override the settings of the agent node named `name`
+    #[must_use]
+    pub fn with_node_override(mut self, name: impl Into<String>, settings: LlmOverride) -> Self {
+        self.nodes.insert(name.into(), settings);
+        self
+    }
+
+    /// LLM configuration the profile gives `node`, starting from `fallback`,
+    /// the executor-level configuration; `None` when the profile sets nothing
+    /// for the node
+    #[must_use]
+    pub fn resolve(&self, node: &WorkflowNode, fallback: Option<&LlmConfig>) -> Option<LlmConfig> {
+        let overrides = std::iter::once(&self.defaults)
+            .chain(node.tags.iter().filter_map(|tag| self.tags.get(tag)))
+            .chain(self.nodes.get(&node.name))
+            .filter(|settings| !settings.is_empty());
+
+        let mut resolved = None;
+        for settings in overrides {
+            resolved = settings.apply(resolved.or_else(|| fallback.cloned()));
+        }
+        resolved
+    }
+}
+
+/// Parse profiles keyed by name, e.g.
+///
+/// ```json
+/// {
+///   "dev": {
+///     "llm_config": {"provider": "Ollama", "model": "llama3.2"},
+///     "tags": {"fast": {"model": "llama3.2:1b"}}
+///   },
+///   "prod": {
+///     "llm_config": {"provider": "OpenAI", "api_key": "{{secret.OPENAI_API_KEY}}", "model": "gpt-4o"},
+///     "nodes": {"Summarize": {"model": "gpt-4o-mini"}}
+///   }
+/// }
+/// ```
+pub fn profiles_from_value(
+    value: serde_json::Value,
+) -> GraphBitResult<HashMap<String, ConfigProfile>> {
+    serde_json::from_value(value)
+        .map_err(|e| GraphBitError::config(format!("Invalid LLM profiles: {e}")))
+}
+
+/// Load profiles keyed by name from a JSON file, see [`profiles_from_value`]
+pub fn profiles_from_file(
+    path: impl AsRef<Path>,
+) -> GraphBitResult<HashMap<String, ConfigProfile>> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path).map_err(|e| {
+        GraphBitError::config(format!(
+            "Failed to read LLM profiles {}: {e}",
+            path.display()
+        ))
+    })?;
+    profiles_from_value(serde_json::from_str(&json)?)
+}
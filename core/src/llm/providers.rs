@@ -1,7 +1,10 @@
 //! LLM provider abstraction and configuration
 
 use crate::errors::GraphBitResult;
-use crate::llm::{LlmRequest, LlmResponse};
+use crate::http_client::RequestTimeouts;
+use crate::llm::health::{self, ProviderHealth};
+use crate::llm::{LlmMiddlewareStack, LlmRequest, LlmResponse};
+use crate::secret_ref::{debug_config, resolve_api_key};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -52,7 +55,11 @@ pub fn unregister_python_instance(id: &str) -> bool {
 }
 
 /// Configuration for different LLM providers
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// An `api_key` may be a reference such as `env:OPENAI_API_KEY`, resolved
+/// when a provider is created, see [`crate::secret_ref`]. `Debug` output
+/// shows references as written and literal keys redacted.
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(tag = "provider")]
 pub enum LlmConfig {
     /// `OpenAI` LLM provider configuration
@@ -65,6 +72,10 @@ pub enum LlmConfig {
         base_url: Option<String>,
         /// Optional organization ID
         organization: Option<String>,
+        /// Timeouts of requests to `OpenAI`, replacing those of the `openai` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Anthropic` LLM provider configuration
     Anthropic {
@@ -74,6 +85,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `Anthropic`, replacing those of the `anthropic` HTTP
+        /// settings, which set no total request timeout by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Azure LLM` provider configuration for Azure-deployed models
     AzureLlm {
@@ -85,6 +100,10 @@ pub enum LlmConfig {
         endpoint: String,
         /// API version to use
         api_version: String,
+        /// Timeouts of requests to the Azure deployment, replacing those of the `azurellm` HTTP
+        /// settings, which let a request take 120 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `ByteDance ModelArk` LLM provider configuration
     ByteDance {
@@ -94,6 +113,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `ByteDance`, replacing those of the `bytedance` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `DeepSeek` LLM provider configuration
     DeepSeek {
@@ -103,6 +126,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `DeepSeek`, replacing those of the `deepseek` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `HuggingFace` LLM provider configuration
     HuggingFace {
@@ -112,6 +139,16 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Inference provider the router should use (e.g. "hf-inference", "together")
+        #[serde(default)]
+        inference_provider: Option<String>,
+        /// Send raw text-generation requests to a self-hosted TGI server at `base_url`
+        #[serde(default)]
+        text_generation: bool,
+        /// Timeouts of requests to `HuggingFace`, replacing those of the `huggingface` HTTP
+        /// settings, which let a request take 120 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Ollama` LLM provider configuration
     Ollama {
@@ -119,6 +156,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to the `Ollama` server, replacing those of the `ollama` HTTP
+        /// settings, which let a request take 120 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Perplexity` LLM provider configuration
     Perplexity {
@@ -128,6 +169,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `Perplexity`, replacing those of the `perplexity` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `OpenRouter` LLM provider configuration
     OpenRouter {
@@ -141,6 +186,10 @@ pub enum LlmConfig {
         site_url: Option<String>,
         /// Optional site name for `OpenRouter` rankings
         site_name: Option<String>,
+        /// Timeouts of requests to `OpenRouter`, replacing those of the `openrouter` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Fireworks AI` LLM provider configuration
     Fireworks {
@@ -150,6 +199,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `Fireworks`, replacing those of the `fireworks` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Replicate` LLM provider configuration
     Replicate {
@@ -161,6 +214,10 @@ pub enum LlmConfig {
         base_url: Option<String>,
         /// Optional model version (if not specified, uses latest)
         version: Option<String>,
+        /// Timeouts of requests to `Replicate`, replacing those of the `replicate` HTTP
+        /// settings, which let a request take 300 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `TogetherAI` LLM provider configuration
     TogetherAi {
@@ -170,6 +227,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `Together AI`, replacing those of the `togetherai` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `xAI` LLM provider configuration for Grok models
     Xai {
@@ -179,6 +240,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `xAI`, replacing those of the `xai` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `AI21` LLM provider configuration
     Ai21 {
@@ -190,6 +255,10 @@ pub enum LlmConfig {
         base_url: Option<String>,
         /// Optional custom organization
         organization: Option<String>,
+        /// Timeouts of requests to `AI21`, replacing those of the `ai21` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `MistralAI` LLM provider configuration
     MistralAI {
@@ -199,6 +268,10 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Timeouts of requests to `Mistral AI`, replacing those of the `mistralai` HTTP
+        /// settings, which let a request take 60 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// `Google Gemini` LLM provider configuration
     Gemini {
@@ -208,6 +281,14 @@ pub enum LlmConfig {
         model: String,
         /// Optional custom base URL
         base_url: Option<String>,
+        /// Block threshold by harm category (e.g. `HARM_CATEGORY_DANGEROUS_CONTENT` to
+        /// `BLOCK_ONLY_HIGH`), sent unchanged as `safetySettings`
+        #[serde(default)]
+        safety_settings: HashMap<String, String>,
+        /// Timeouts of requests to `Gemini`, replacing those of the `gemini` HTTP
+        /// settings, which let a request take 120 seconds by default
+        #[serde(flatten)]
+        timeouts: RequestTimeouts,
     },
     /// Python bridge provider configuration for calling Python LLM implementations
     #[cfg(feature = "python")]
@@ -243,6 +324,22 @@ impl LlmConfig {
             model: model.into(),
             base_url: None,
             organization: None,
+            timeouts: RequestTimeouts::default(),
+        }
+    }
+
+    /// Create `OpenAI` configuration with custom base URL
+    pub fn openai_with_base_url(
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self::OpenAI {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: Some(base_url.into()),
+            organization: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -252,6 +349,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -267,6 +365,7 @@ impl LlmConfig {
             deployment_name: deployment_name.into(),
             endpoint: endpoint.into(),
             api_version: api_version.into(),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -281,6 +380,7 @@ impl LlmConfig {
             deployment_name: deployment_name.into(),
             endpoint: endpoint.into(),
             api_version: "2024-10-21".to_string(),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -290,6 +390,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -303,6 +404,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: Some(base_url.into()),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -312,6 +414,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -321,6 +424,41 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            inference_provider: None,
+            text_generation: false,
+            timeouts: RequestTimeouts::default(),
+        }
+    }
+
+    /// Create `HuggingFace` configuration routed to a specific inference provider
+    pub fn huggingface_with_provider(
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+        inference_provider: impl Into<String>,
+    ) -> Self {
+        Self::HuggingFace {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: None,
+            inference_provider: Some(inference_provider.into()),
+            text_generation: false,
+            timeouts: RequestTimeouts::default(),
+        }
+    }
+
+    /// Create `HuggingFace` configuration for a self-hosted TGI text-generation endpoint
+    pub fn huggingface_tgi(
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self::HuggingFace {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: Some(base_url.into()),
+            inference_provider: None,
+            text_generation: true,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -330,6 +468,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -341,6 +480,7 @@ impl LlmConfig {
             base_url: None,
             site_url: None,
             site_name: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -357,6 +497,7 @@ impl LlmConfig {
             base_url: None,
             site_url,
             site_name,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -366,6 +507,21 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
+        }
+    }
+
+    /// Create `Fireworks AI` configuration with custom base URL
+    pub fn fireworks_with_base_url(
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self::Fireworks {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: Some(base_url.into()),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -376,6 +532,7 @@ impl LlmConfig {
             model: model.into(),
             base_url: None,
             version: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -390,6 +547,7 @@ impl LlmConfig {
             model: model.into(),
             base_url: None,
             version: Some(version.into()),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -399,6 +557,21 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
+        }
+    }
+
+    /// Create `TogetherAI` configuration with custom base URL
+    pub fn togetherai_with_base_url(
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self::TogetherAi {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: Some(base_url.into()),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -408,6 +581,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -418,6 +592,7 @@ impl LlmConfig {
             model: model.into(),
             base_url: None,
             organization: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -432,6 +607,7 @@ impl LlmConfig {
             model: model.into(),
             base_url: None,
             organization: Some(organization.into()),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -441,6 +617,7 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -450,6 +627,23 @@ impl LlmConfig {
             api_key: api_key.into(),
             model: model.into(),
             base_url: None,
+            safety_settings: HashMap::new(),
+            timeouts: RequestTimeouts::default(),
+        }
+    }
+
+    /// Create `Google Gemini` configuration with safety thresholds by harm category
+    pub fn gemini_with_safety_settings(
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+        safety_settings: HashMap<String, String>,
+    ) -> Self {
+        Self::Gemini {
+            api_key: api_key.into(),
+            model: model.into(),
+            base_url: None,
+            safety_settings,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -458,6 +652,7 @@ impl LlmConfig {
         Self::Ollama {
             model: model.into(),
             base_url: None,
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -466,6 +661,7 @@ impl LlmConfig {
         Self::Ollama {
             model: model.into(),
             base_url: Some(base_url.into()),
+            timeouts: RequestTimeouts::default(),
         }
     }
 
@@ -526,6 +722,169 @@ impl LlmConfig {
         }
     }
 
+    /// Return this configuration with its model replaced
+    ///
+    /// For `Azure LLM` the deployment name is replaced; unconfigured configurations
+    /// are returned unchanged.
+    #[must_use]
+    pub fn with_model(mut self, new_model: impl Into<String>) -> Self {
+        let new_model = new_model.into();
+        match &mut self {
+            Self::OpenAI { model, .. }
+            | Self::Anthropic { model, .. }
+            | Self::ByteDance { model, .. }
+            | Self::DeepSeek { model, .. }
+            | Self::HuggingFace { model, .. }
+            | Self::Ollama { model, .. }
+            | Self::Perplexity { model, .. }
+            | Self::OpenRouter { model, .. }
+            | Self::Fireworks { model, .. }
+            | Self::Replicate { model, .. }
+            | Self::TogetherAi { model, .. }
+            | Self::Xai { model, .. }
+            | Self::Ai21 { model, .. }
+            | Self::MistralAI { model, .. }
+            | Self::Gemini { model, .. } => *model = new_model,
+            #[cfg(feature = "python")]
+            Self::PythonBridge { model, .. } => *model = new_model,
+            Self::AzureLlm {
+                deployment_name, ..
+            } => *deployment_name = new_model,
+            Self::Custom { config, .. } => {
+                config.insert("model".to_string(), serde_json::Value::String(new_model));
+            }
+            Self::Unconfigured { .. } => {}
+        }
+        self
+    }
+
+    /// Return this configuration sending its requests to `new_base_url`
+    /// instead of the provider's public API. `Azure LLM`, which has an
+    /// endpoint instead, and providers without a base URL are returned
+    /// unchanged.
+    #[must_use]
+    pub fn with_base_url(mut self, new_base_url: impl Into<String>) -> Self {
+        match &mut self {
+            Self::OpenAI { base_url, .. }
+            | Self::Anthropic { base_url, .. }
+            | Self::ByteDance { base_url, .. }
+            | Self::DeepSeek { base_url, .. }
+            | Self::HuggingFace { base_url, .. }
+            | Self::Ollama { base_url, .. }
+            | Self::Perplexity { base_url, .. }
+            | Self::OpenRouter { base_url, .. }
+            | Self::Fireworks { base_url, .. }
+            | Self::Replicate { base_url, .. }
+            | Self::TogetherAi { base_url, .. }
+            | Self::Xai { base_url, .. }
+            | Self::Ai21 { base_url, .. }
+            | Self::MistralAI { base_url, .. }
+            | Self::Gemini { base_url, .. } => *base_url = Some(new_base_url.into()),
+            _ => {}
+        }
+        self
+    }
+
+    /// Request and connection timeouts of the provider's HTTP client; empty
+    /// for providers that do not make HTTP requests themselves
+    #[must_use]
+    pub fn timeouts(&self) -> RequestTimeouts {
+        match self {
+            Self::OpenAI { timeouts, .. }
+            | Self::Anthropic { timeouts, .. }
+            | Self::AzureLlm { timeouts, .. }
+            | Self::ByteDance { timeouts, .. }
+            | Self::DeepSeek { timeouts, .. }
+            | Self::HuggingFace { timeouts, .. }
+            | Self::Ollama { timeouts, .. }
+            | Self::Perplexity { timeouts, .. }
+            | Self::OpenRouter { timeouts, .. }
+            | Self::Fireworks { timeouts, .. }
+            | Self::Replicate { timeouts, .. }
+            | Self::TogetherAi { timeouts, .. }
+            | Self::Xai { timeouts, .. }
+            | Self::Ai21 { timeouts, .. }
+            | Self::MistralAI { timeouts, .. }
+            | Self::Gemini { timeouts, .. } => *timeouts,
+            _ => RequestTimeouts::default(),
+        }
+    }
+
+    /// Return this configuration with its request and connection timeouts
+    /// replaced. Providers that do not make HTTP requests themselves are
+    /// returned unchanged.
+    #[must_use]
+    pub fn with_timeouts(mut self, new_timeouts: RequestTimeouts) -> Self {
+        match &mut self {
+            Self::OpenAI { timeouts, .. }
+            | Self::Anthropic { timeouts, .. }
+            | Self::AzureLlm { timeouts, .. }
+            | Self::ByteDance { timeouts, .. }
+            | Self::DeepSeek { timeouts, .. }
+            | Self::HuggingFace { timeouts, .. }
+            | Self::Ollama { timeouts, .. }
+            | Self::Perplexity { timeouts, .. }
+            | Self::OpenRouter { timeouts, .. }
+            | Self::Fireworks { timeouts, .. }
+            | Self::Replicate { timeouts, .. }
+            | Self::TogetherAi { timeouts, .. }
+            | Self::Xai { timeouts, .. }
+            | Self::Ai21 { timeouts, .. }
+            | Self::MistralAI { timeouts, .. }
+            | Self::Gemini { timeouts, .. } => *timeouts = new_timeouts,
+            _ => {}
+        }
+        self
+    }
+
+    /// The `api_key` setting, a key or a reference to one; `None` for
+    /// providers without a key
+    #[must_use]
+    pub fn api_key(&self) -> Option<&str> {
+        match self {
+            Self::OpenAI { api_key, .. }
+            | Self::Anthropic { api_key, .. }
+            | Self::AzureLlm { api_key, .. }
+            | Self::ByteDance { api_key, .. }
+            | Self::DeepSeek { api_key, .. }
+            | Self::HuggingFace { api_key, .. }
+            | Self::Perplexity { api_key, .. }
+            | Self::OpenRouter { api_key, .. }
+            | Self::Fireworks { api_key, .. }
+            | Self::Replicate { api_key, .. }
+            | Self::TogetherAi { api_key, .. }
+            | Self::Xai { api_key, .. }
+            | Self::Ai21 { api_key, .. }
+            | Self::MistralAI { api_key, .. }
+            | Self::Gemini { api_key, .. } => Some(api_key),
+            _ => None,
+        }
+    }
+
+    /// Return this configuration with its `api_key` reference replaced by the
+    /// key it refers to
+    pub fn with_resolved_api_key(mut self) -> GraphBitResult<Self> {
+        match &mut self {
+            Self::OpenAI { api_key, .. }
+            | Self::Anthropic { api_key, .. }
+            | Self::AzureLlm { api_key, .. }
+            | Self::ByteDance { api_key, .. }
+            | Self::DeepSeek { api_key, .. }
+            | Self::HuggingFace { api_key, .. }
+            | Self::Perplexity { api_key, .. }
+            | Self::OpenRouter { api_key, .. }
+            | Self::Fireworks { api_key, .. }
+            | Self::Replicate { api_key, .. }
+            | Self::TogetherAi { api_key, .. }
+            | Self::Xai { api_key, .. }
+            | Self::Ai21 { api_key, .. }
+            | Self::MistralAI { api_key, .. }
+            | Self::Gemini { api_key, .. } => *api_key = resolve_api_key(api_key)?,
+            _ => {}
+        }
+        Ok(self)
+    }
+
     /// A stable fingerprint used to dedupe LLM config validation across a workflow.
     ///
     /// Returns `None` for configs that should be skipped (e.g. Python bridge).
@@ -577,6 +936,12 @@ impl LlmConfig {
     }
 }
 
+impl std::fmt::Debug for LlmConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        debug_config(f, "LlmConfig", self)
+    }
+}
+
 impl Default for LlmConfig {
     /// Default configuration requires explicit setup - no hardcoded provider defaults
     fn default() -> Self {
@@ -619,6 +984,11 @@ pub trait LlmProviderTrait: Send + Sync {
         false
     }
 
+    /// Check if the provider accepts images sent with messages
+    fn supports_image_inputs(&self) -> bool {
+        false
+    }
+
     /// Get the maximum context length for this provider/model
     fn max_context_length(&self) -> Option<u32> {
         None
@@ -628,6 +998,24 @@ pub trait LlmProviderTrait: Send + Sync {
     fn cost_per_token(&self) -> Option<(f64, f64)> {
         None // (input_cost, output_cost)
     }
+
+    /// Whether the first request to the model may wait for it to be loaded,
+    /// which a warm-up primes with a one-token completion
+    fn has_cold_start(&self) -> bool {
+        false
+    }
+
+    /// Check that the provider is reachable, accepts the credentials and
+    /// serves the model. The default sends a one-token completion.
+    async fn health_check(&self) -> ProviderHealth {
+        let request = LlmRequest::new("ping").with_max_tokens(1);
+        health::probe(
+            self.provider_name(),
+            self.model_name(),
+            self.complete(request),
+        )
+        .await
+    }
 }
 
 /// Wrapper for LLM providers
@@ -657,13 +1045,35 @@ impl LlmProvider {
 
     /// Send a request to the LLM
     pub async fn complete(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
+        self.complete_with(&LlmMiddlewareStack::default(), request)
+            .await
+    }
+
+    /// Send a request to the LLM through `middleware`
+    pub async fn complete_with(
+        &self,
+        middleware: &LlmMiddlewareStack,
+        request: LlmRequest,
+    ) -> GraphBitResult<LlmResponse> {
         tracing::info!(
             "LlmProvider wrapper: Forwarding request with {} tools to {} provider\nRequest: {:?}",
             request.tools.len(),
             self.config.provider_name(),
             request
         );
-        self.inner.complete(request).await
+        middleware
+            .wrap(request, |request| {
+                crate::audit::llm_call(self.inner.as_ref(), request, |request| {
+                    crate::telemetry::llm_call(self.inner.as_ref(), self.inner.complete(request))
+                })
+            })
+            .await
+    }
+
+    /// Check that the provider is reachable, accepts the credentials and
+    /// serves the model
+    pub async fn health_check(&self) -> ProviderHealth {
+        self.inner.health_check().await
     }
 
     /// Stream a response from the LLM
@@ -1,6 +1,7 @@
 //! `Replicate` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -22,18 +23,8 @@ pub struct ReplicateProvider {
 impl ReplicateProvider {
     /// Create a new `Replicate` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(300)) // Longer timeout for Replicate predictions
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(Duration::from_secs(30))
-            .tcp_keepalive(Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "replicate",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        // Longer timeout for Replicate predictions
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.replicate.com/v1".to_string();
 
         Ok(Self {
@@ -47,18 +38,7 @@ impl ReplicateProvider {
 
     /// Create a new `Replicate` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(300))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(Duration::from_secs(30))
-            .tcp_keepalive(Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "replicate",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -69,6 +49,17 @@ impl ReplicateProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("replicate", Some(Duration::from_secs(300)), None, timeouts)
+    }
+
     /// Set model version
     pub fn with_version(mut self, version: String) -> Self {
         self.version = Some(version);
@@ -288,17 +279,16 @@ impl LlmProviderTrait for ReplicateProvider {
 
         // Create prediction
         let url = format!("{}/predictions", self.base_url);
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Token {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&prediction_request)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("replicate", format!("Request failed: {e}"))
-            })?;
+        let response = crate::capture::send(
+            "replicate",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Token {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&prediction_request),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("replicate", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -345,6 +335,11 @@ impl LlmProviderTrait for ReplicateProvider {
             Some(4096) // Default fallback
         }
     }
+
+    fn has_cold_start(&self) -> bool {
+        // Models without traffic are booted on a fresh instance first
+        true
+    }
 }
 
 impl ReplicateProvider {
@@ -361,15 +356,16 @@ impl ReplicateProvider {
             tokio::time::sleep(Duration::from_secs(5)).await;
             attempts += 1;
 
-            let response = self
-                .client
-                .get(&url)
-                .header("Authorization", format!("Token {}", self.api_key))
-                .send()
-                .await
-                .map_err(|e| {
-                    GraphBitError::llm_provider("replicate", format!("Polling request failed: {e}"))
-                })?;
+            let response = crate::capture::send(
+                "replicate",
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Token {}", self.api_key)),
+            )
+            .await
+            .map_err(|e| {
+                GraphBitError::llm_provider("replicate", format!("Polling request failed: {e}"))
+            })?;
 
             if !response.status().is_success() {
                 let error_text = response
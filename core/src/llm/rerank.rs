@@ -0,0 +1,233 @@
+//! Re-ranking of retrieved documents by LLM-judged relevance
+//!
+//! Embedding similarity finds candidates cheaply but orders them poorly. A
+//! re-rank asks the model to score each candidate against the query, a few
+//! documents per prompt, and orders the candidates by those scores. The
+//! scoring calls go through the provider's middleware, so their token usage
+//! reaches a [`BudgetTracker`](crate::budget::BudgetTracker) like any other
+//! LLM call.
+
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    LlmMessage, LlmMiddlewareStack, LlmProvider, LlmProviderFactory, LlmRequest, LlmUsage,
+};
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Highest score the model is asked to give
+const MAX_SCORE: f64 = 10.0;
+
+const SCORING_INSTRUCTIONS: &str = "You judge how relevant documents are to a search query. \
+Score each numbered document from 0 (unrelated) to 10 (fully answers the query). \
+Reply with only a JSON object mapping each document number to its score, like {\"1\": 7, \"2\": 0}.";
+
+/// How documents are re-ranked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RerankConfig {
+    /// Documents to keep, all when unset
+    pub top_k: Option<usize>,
+    /// Model scoring the documents instead of the provider's own
+    pub model: Option<String>,
+    /// Documents scored per prompt
+    pub batch_size: usize,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self {
+            top_k: None,
+            model: None,
+            batch_size: 10,
+        }
+    }
+}
+
+impl RerankConfig {
+    /// Keep the `top_k` best documents
+    #[must_use]
+    pub const fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Score documents with `model`
+    #[must_use]
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Score `batch_size` documents per prompt
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+/// Document with its relevance to the query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RerankedDocument {
+    /// Position of the document in the input
+    pub index: usize,
+    /// Text of the document
+    pub document: String,
+    /// Relevance from 0.0 to 1.0
+    pub score: f64,
+}
+
+/// Documents ordered by relevance, most relevant first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Reranking {
+    /// Kept documents, most relevant first
+    pub documents: Vec<RerankedDocument>,
+    /// Tokens used by the scoring prompts
+    pub usage: LlmUsage,
+}
+
+impl LlmProvider {
+    /// Order `documents` by their relevance to `query`
+    pub async fn rerank(
+        &self,
+        query: &str,
+        documents: &[String],
+        config: &RerankConfig,
+    ) -> GraphBitResult<Reranking> {
+        self.rerank_with(&LlmMiddlewareStack::default(), query, documents, config)
+            .await
+    }
+
+    /// Order `documents` by their relevance to `query`, scoring them through
+    /// `middleware`
+    pub async fn rerank_with(
+        &self,
+        middleware: &LlmMiddlewareStack,
+        query: &str,
+        documents: &[String],
+        config: &RerankConfig,
+    ) -> GraphBitResult<Reranking> {
+        if config.batch_size == 0 {
+            return Err(GraphBitError::validation(
+                "batch_size",
+                "Rerank batch size must be at least 1",
+            ));
+        }
+        if let Some(model) = config
+            .model
+            .as_deref()
+            .filter(|m| *m != self.config().model_name())
+        {
+            let llm_config = self.config().clone().with_model(model);
+            let provider = Self::new(
+                LlmProviderFactory::create_provider(llm_config.clone())?,
+                llm_config,
+            );
+            return provider
+                .score_documents(middleware, query, documents, config)
+                .await;
+        }
+        self.score_documents(middleware, query, documents, config)
+            .await
+    }
+
+    async fn score_documents(
+        &self,
+        middleware: &LlmMiddlewareStack,
+        query: &str,
+        documents: &[String],
+        config: &RerankConfig,
+    ) -> GraphBitResult<Reranking> {
+        let batches = documents
+            .chunks(config.batch_size)
+            .enumerate()
+            .map(|(batch, chunk)| {
+                self.score_batch(middleware, query, chunk, batch * config.batch_size)
+            });
+
+        let mut reranking = Reranking::default();
+        for (scored, usage) in try_join_all(batches).await? {
+            reranking.documents.extend(scored);
+            reranking.usage.add(&usage);
+        }
+        // Stable, so equally relevant documents keep their retrieval order
+        reranking
+            .documents
+            .sort_by(|a, b| b.score.total_cmp(&a.score));
+        if let Some(top_k) = config.top_k {
+            reranking.documents.truncate(top_k);
+        }
+        Ok(reranking)
+    }
+
+    /// Score the documents of one prompt, the first of which is at `offset`
+    /// in the input
+    async fn score_batch(
+        &self,
+        middleware: &LlmMiddlewareStack,
+        query: &str,
+        documents: &[String],
+        offset: usize,
+    ) -> GraphBitResult<(Vec<RerankedDocument>, LlmUsage)> {
+        let response = self
+            .complete_with(middleware, scoring_request(query, documents))
+            .await?;
+        let scores = parse_scores(&response.content, documents.len())?;
+        let ranked = documents
+            .iter()
+            .zip(scores)
+            .enumerate()
+            .map(|(i, (document, score))| RerankedDocument {
+                index: offset + i,
+                document: document.clone(),
+                score,
+            })
+            .collect();
+        Ok((ranked, response.usage))
+    }
+}
+
+fn scoring_request(query: &str, documents: &[String]) -> LlmRequest {
+    let numbered: Vec<String> = documents
+        .iter()
+        .enumerate()
+        .map(|(i, document)| format!("[{}] {document}", i + 1))
+        .collect();
+    LlmRequest::with_messages(vec![
+        LlmMessage::system(SCORING_INSTRUCTIONS),
+        LlmMessage::user(format!(
+            "Query: {query}\n\nDocuments:\n{}",
+            numbered.join("\n\n")
+        )),
+    ])
+    .with_temperature(0.0)
+}
+
+/// Scores of `count` documents from the model's reply, normalized to 0.0..=1.0
+fn parse_scores(reply: &str, count: usize) -> GraphBitResult<Vec<f64>> {
+    let verdict = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .filter(|(start, end)| start < end)
+        .and_then(|(start, end)| {
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&reply[start..=end])
+                .ok()
+        })
+        .ok_or_else(|| {
+            GraphBitError::llm(format!("Reranker reply is not a score object: {reply}"))
+        })?;
+    (1..=count)
+        .map(|n| {
+            let score = verdict.get(&n.to_string()).and_then(|score| match score {
+                serde_json::Value::String(score) => score.trim().parse().ok(),
+                score => score.as_f64(),
+            });
+            score
+                .map(|score| (score / MAX_SCORE).clamp(0.0, 1.0))
+                .ok_or_else(|| {
+                    GraphBitError::llm(format!("Reranker reply has no score for document {n}"))
+                })
+        })
+        .collect()
+}
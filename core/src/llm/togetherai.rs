@@ -1,6 +1,9 @@
 //! `TogetherAI` LLM provider implementation
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
+use crate::llm::catalog::{self, ModelInfo};
+use crate::llm::health::{self, ProviderHealth};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -8,6 +11,8 @@ use crate::llm::{
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
 
 /// `TogetherAI` API provider
 pub struct TogetherAiProvider {
@@ -15,24 +20,14 @@ pub struct TogetherAiProvider {
     api_key: String,
     model: String,
     base_url: String,
+    /// Context window of the model, once read from the catalog
+    catalog_context_length: OnceLock<u32>,
 }
 
 impl TogetherAiProvider {
     /// Create a new `TogetherAI` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "togetherai",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.together.ai/v1".to_string();
 
         Ok(Self {
@@ -40,33 +35,34 @@ impl TogetherAiProvider {
             api_key,
             model,
             base_url,
+            catalog_context_length: OnceLock::new(),
         })
     }
 
     /// Create a new `TogetherAI` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider(
-                    "togetherai",
-                    format!("Failed to create HTTP client: {e}"),
-                )
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
             api_key,
             model,
             base_url,
+            catalog_context_length: OnceLock::new(),
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("togetherai", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `TogetherAI` message format
     fn convert_message(&self, message: &LlmMessage) -> TogetherAiMessage {
         TogetherAiMessage {
@@ -111,6 +107,21 @@ impl TogetherAiProvider {
         }
     }
 
+    /// List the models in the `TogetherAI` catalog
+    pub async fn list_models(&self) -> GraphBitResult<Vec<ModelInfo>> {
+        let url = format!("{}/models", self.base_url);
+        let models =
+            catalog::fetch_catalog(&self.client, "togetherai", &url, &self.api_key).await?;
+        if let Some(length) = models
+            .iter()
+            .find(|info| info.id == self.model)
+            .and_then(|info| info.context_length)
+        {
+            let _ = self.catalog_context_length.set(length);
+        }
+        Ok(models)
+    }
+
     /// Parse `TogetherAI` response to `GraphBit` response
     fn parse_response(&self, response: TogetherAiResponse) -> GraphBitResult<LlmResponse> {
         let choice =
@@ -231,27 +242,19 @@ impl LlmProviderTrait for TogetherAiProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| {
-                GraphBitError::llm_provider("togetherai", format!("Request failed: {e}"))
-            })?;
+        let response = crate::capture::send(
+            "togetherai",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("togetherai", &e))?;
 
         if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(GraphBitError::llm_provider(
-                "togetherai",
-                format!("API error: {error_text}"),
-            ));
+            return Err(catalog::error_from_response("togetherai", response).await);
         }
 
         let togetherai_response: TogetherAiResponse = response.json().await.map_err(|e| {
@@ -267,6 +270,9 @@ impl LlmProviderTrait for TogetherAiProvider {
     }
 
     fn max_context_length(&self) -> Option<u32> {
+        if let Some(length) = self.catalog_context_length.get() {
+            return Some(*length);
+        }
         // Context lengths for supported TogetherAI models
         match self.model.as_str() {
             "openai/gpt-oss-20b" => Some(8192),
@@ -286,6 +292,15 @@ impl LlmProviderTrait for TogetherAiProvider {
             _ => None, // Unknown model pricing
         }
     }
+
+    /// Look the model up in the catalog, which costs no tokens and catches
+    /// misspelled model names before a run
+    async fn health_check(&self) -> ProviderHealth {
+        health::probe("togetherai", &self.model, async {
+            catalog::find_model(self.list_models().await?, "togetherai", &self.model)
+        })
+        .await
+    }
 }
 
 // `TogetherAI` API types
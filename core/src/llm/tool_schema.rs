@@ -0,0 +1,526 @@
+//! Translation of tool definitions to and from provider formats
+//!
+//! Tools are defined once as [`LlmTool`]s whose parameters are a JSON Schema.
+//! Each provider expects them in its own envelope: `OpenAI` (and the providers
+//! compatible with it) as `function` tools, `Anthropic` with an `input_schema`
+//! and `Gemini` as function declarations. `Gemini` also accepts only a subset
+//! of JSON Schema. In [`SchemaMode::Lenient`] the keywords it rejects are
+//! downgraded to supported ones where possible (`oneOf` to `anyOf`, `const` to
+//! a single-value `enum`, type lists to `nullable` or `anyOf`) and dropped
+//! otherwise, each change recorded as a [`SchemaWarning`]. In
+//! [`SchemaMode::Strict`] the first such keyword is an error.
+//!
+//! Tool calls in a provider's response are parsed back into [`LlmToolCall`]s.
+
+use serde::{Deserialize, Serialize};
+
+use super::{LlmTool, LlmToolCall};
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// JSON Schema keywords `Gemini` accepts in function parameters
+const GEMINI_KEYWORDS: &[&str] = &[
+    "type",
+    "format",
+    "title",
+    "description",
+    "nullable",
+    "enum",
+    "default",
+    "example",
+    "properties",
+    "required",
+    "propertyOrdering",
+    "minProperties",
+    "maxProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+    "anyOf",
+];
+
+/// Native tool format of a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolDialect {
+    /// `OpenAI` chat completions `function` tools, used by the providers
+    /// compatible with `OpenAI`
+    OpenAi,
+    /// `Anthropic` messages tools with an `input_schema`
+    Anthropic,
+    /// `Gemini` function declarations
+    Gemini,
+}
+
+impl ToolDialect {
+    /// Tool format of the provider named `provider`
+    #[must_use]
+    pub fn for_provider(provider: &str) -> Self {
+        match provider {
+            "anthropic" => Self::Anthropic,
+            "gemini" => Self::Gemini,
+            _ => Self::OpenAi,
+        }
+    }
+}
+
+/// What happens to tool schemas using keywords a provider rejects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaMode {
+    /// Downgrade or drop the keywords, recording a warning for each
+    #[default]
+    Lenient,
+    /// Fail the translation
+    Strict,
+}
+
+/// Change made to a tool schema to fit a provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaWarning {
+    /// Name of the tool
+    pub tool: String,
+    /// JSON pointer of the changed keyword within the tool's parameters
+    pub path: String,
+    /// What was changed
+    pub message: String,
+}
+
+/// Tools in a provider's native format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslatedTools {
+    /// The `tools` value of the provider's request
+    pub tools: serde_json::Value,
+    /// Changes made to the schemas, in lenient mode
+    pub warnings: Vec<SchemaWarning>,
+}
+
+/// Translates tool definitions to a provider's format and its tool calls back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolSchemaTranslator {
+    dialect: ToolDialect,
+    mode: SchemaMode,
+}
+
+impl ToolSchemaTranslator {
+    /// Lenient translator for `dialect`
+    #[must_use]
+    pub const fn new(dialect: ToolDialect) -> Self {
+        Self {
+            dialect,
+            mode: SchemaMode::Lenient,
+        }
+    }
+
+    /// Set what happens to keywords the provider rejects
+    #[must_use]
+    pub const fn with_mode(mut self, mode: SchemaMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Tool format translated to
+    #[must_use]
+    pub const fn dialect(self) -> ToolDialect {
+        self.dialect
+    }
+
+    /// What happens to keywords the provider rejects
+    #[must_use]
+    pub const fn mode(self) -> SchemaMode {
+        self.mode
+    }
+
+    /// `tools` in the provider's native format
+    pub fn translate(self, tools: &[LlmTool]) -> GraphBitResult<TranslatedTools> {
+        let mut warnings = Vec::new();
+        let mut translated = Vec::with_capacity(tools.len());
+        for tool in tools {
+            let parameters = self.parameters(tool, &mut warnings)?;
+            translated.push(match self.dialect {
+                ToolDialect::OpenAi => serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": parameters,
+                    },
+                }),
+                ToolDialect::Anthropic => serde_json::json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": parameters,
+                }),
+                ToolDialect::Gemini => serde_json::json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": parameters,
+                }),
+            });
+        }
+        let tools = match self.dialect {
+            ToolDialect::Gemini if !translated.is_empty() => {
+                serde_json::json!([{ "functionDeclarations": translated }])
+            }
+            _ => serde_json::Value::Array(translated),
+        };
+        Ok(TranslatedTools { tools, warnings })
+    }
+
+    /// Parameters schema of `tool` in the form the provider accepts, adding
+    /// the changes made to `warnings`
+    ///
+    /// A tool without parameters gets an empty object schema. The parameters
+    /// must describe an object.
+    pub fn parameters(
+        self,
+        tool: &LlmTool,
+        warnings: &mut Vec<SchemaWarning>,
+    ) -> GraphBitResult<serde_json::Value> {
+        let mut schema = match &tool.parameters {
+            serde_json::Value::Null => serde_json::json!({ "type": "object", "properties": {} }),
+            serde_json::Value::Object(map) if map.is_empty() => {
+                serde_json::json!({ "type": "object", "properties": {} })
+            }
+            serde_json::Value::Object(_) => tool.parameters.clone(),
+            other => {
+                return Err(GraphBitError::validation(
+                    format!("tools.{}.parameters", tool.name),
+                    format!("Expected a JSON Schema object, got {other}"),
+                ));
+            }
+        };
+        match schema.get("type") {
+            Some(serde_json::Value::String(kind)) if kind == "object" => {}
+            None if schema.get("properties").is_some() => {
+                schema["type"] = serde_json::json!("object");
+            }
+            _ => {
+                return Err(GraphBitError::validation(
+                    format!("tools.{}.parameters", tool.name),
+                    "Tool parameters must be an object schema",
+                ));
+            }
+        }
+        if self.dialect == ToolDialect::Gemini {
+            let mut fit = GeminiFit {
+                tool: &tool.name,
+                mode: self.mode,
+                warnings,
+            };
+            fit.schema(&mut schema, "")?;
+        }
+        Ok(schema)
+    }
+
+    /// Tool calls in the provider's response body
+    ///
+    /// `OpenAI` arguments that are not valid JSON are kept as
+    /// `{"raw_arguments": ...}` in lenient mode and are an error in strict mode.
+    /// `Gemini` calls without an id are given one.
+    pub fn parse_tool_calls(
+        self,
+        response: &serde_json::Value,
+    ) -> GraphBitResult<Vec<LlmToolCall>> {
+        match self.dialect {
+            ToolDialect::OpenAi => openai_tool_calls(response, self.mode),
+            ToolDialect::Anthropic => anthropic_tool_calls(response),
+            ToolDialect::Gemini => gemini_tool_calls(response),
+        }
+    }
+}
+
+/// Error for a tool call of `provider` lacking `field`
+fn missing(provider: &str, field: &str) -> GraphBitError {
+    GraphBitError::llm_provider(provider, format!("Tool call without {field}"))
+}
+
+/// Tool calls of an `OpenAI` chat completion
+fn openai_tool_calls(
+    response: &serde_json::Value,
+    mode: SchemaMode,
+) -> GraphBitResult<Vec<LlmToolCall>> {
+    let tool_calls = response
+        .pointer("/choices/0/message/tool_calls")
+        .and_then(serde_json::Value::as_array);
+    let mut calls = Vec::new();
+    for call in tool_calls.into_iter().flatten() {
+        let id = call
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| missing("openai", "an id"))?;
+        let name = call
+            .pointer("/function/name")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| missing("openai", "a function name"))?;
+        let arguments = call
+            .pointer("/function/arguments")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        let parameters = if arguments.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            match serde_json::from_str(arguments) {
+                Ok(parameters) => parameters,
+                Err(e) if mode == SchemaMode::Strict => {
+                    return Err(GraphBitError::llm_provider(
+                        "openai",
+                        format!("Invalid arguments for tool '{name}': {e}"),
+                    ));
+                }
+                Err(_) => serde_json::json!({ "raw_arguments": arguments }),
+            }
+        };
+        calls.push(LlmToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            parameters,
+        });
+    }
+    Ok(calls)
+}
+
+/// Tool calls of an `Anthropic` message: its `tool_use` content blocks
+fn anthropic_tool_calls(response: &serde_json::Value) -> GraphBitResult<Vec<LlmToolCall>> {
+    let blocks = response
+        .get("content")
+        .and_then(serde_json::Value::as_array);
+    let mut calls = Vec::new();
+    for block in blocks.into_iter().flatten() {
+        if block.get("type").and_then(serde_json::Value::as_str) != Some("tool_use") {
+            continue;
+        }
+        let field = |key: &str| {
+            block
+                .get(key)
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| missing("anthropic", &format!("a {key}")))
+        };
+        calls.push(LlmToolCall {
+            id: field("id")?,
+            name: field("name")?,
+            parameters: block
+                .get("input")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({})),
+        });
+    }
+    Ok(calls)
+}
+
+/// Tool calls of a `Gemini` response: the function calls of its first
+/// candidate, given an id when they have none
+fn gemini_tool_calls(response: &serde_json::Value) -> GraphBitResult<Vec<LlmToolCall>> {
+    let parts = response
+        .pointer("/candidates/0/content/parts")
+        .and_then(serde_json::Value::as_array);
+    let mut calls = Vec::new();
+    for call in parts
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part.get("functionCall"))
+    {
+        let name = call
+            .get("name")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| missing("gemini", "a name"))?;
+        let id = call
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .map_or_else(|| format!("call_{}", uuid::Uuid::new_v4()), str::to_string);
+        calls.push(LlmToolCall {
+            id,
+            name: name.to_string(),
+            parameters: call
+                .get("args")
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!({})),
+        });
+    }
+    Ok(calls)
+}
+
+/// Parameters of `tool` translated leniently for the provider named
+/// `provider`, logging the changes made; the parameters as given when they
+/// cannot be translated
+#[must_use]
+pub fn native_parameters(provider: &str, tool: &LlmTool) -> serde_json::Value {
+    let mut warnings = Vec::new();
+    match ToolSchemaTranslator::new(ToolDialect::for_provider(provider))
+        .parameters(tool, &mut warnings)
+    {
+        Ok(parameters) => {
+            for warning in warnings {
+                tracing::warn!(
+                    provider,
+                    tool = %warning.tool,
+                    path = %warning.path,
+                    "Tool schema changed for the provider: {}",
+                    warning.message
+                );
+            }
+            parameters
+        }
+        Err(e) => {
+            tracing::warn!(provider, tool = %tool.name, "Sending tool schema untranslated: {e}");
+            tool.parameters.clone()
+        }
+    }
+}
+
+/// Fits a schema into the JSON Schema subset `Gemini` accepts
+struct GeminiFit<'a> {
+    tool: &'a str,
+    mode: SchemaMode,
+    warnings: &'a mut Vec<SchemaWarning>,
+}
+
+impl GeminiFit<'_> {
+    /// Record the change of `keyword` of the schema at `path`, or fail in
+    /// strict mode naming the `unsupported` construct
+    fn change(
+        &mut self,
+        path: &str,
+        keyword: &str,
+        unsupported: &str,
+        message: String,
+    ) -> GraphBitResult<()> {
+        let path = format!("{path}/{keyword}");
+        if self.mode == SchemaMode::Strict {
+            return Err(GraphBitError::validation(
+                format!("tools.{}.parameters", self.tool),
+                format!("Gemini does not support {unsupported} (at {path})"),
+            ));
+        }
+        self.warnings.push(SchemaWarning {
+            tool: self.tool.to_string(),
+            path,
+            message,
+        });
+        Ok(())
+    }
+
+    /// Replace a list of types by a single type or `anyOf`, with `nullable`
+    /// when the list holds `null`
+    fn type_list(
+        &mut self,
+        map: &mut serde_json::Map<String, serde_json::Value>,
+        path: &str,
+    ) -> GraphBitResult<()> {
+        let Some(types) = map
+            .get("type")
+            .and_then(serde_json::Value::as_array)
+            .cloned()
+        else {
+            return Ok(());
+        };
+        self.change(
+            path,
+            "type",
+            "type lists",
+            "Type list replaced by nullable or anyOf".to_string(),
+        )?;
+        let nullable = types.iter().any(|kind| kind == "null");
+        let kinds: Vec<serde_json::Value> =
+            types.into_iter().filter(|kind| kind != "null").collect();
+        map.remove("type");
+        match kinds.as_slice() {
+            [] => {}
+            [kind] => {
+                map.insert("type".to_string(), kind.clone());
+            }
+            _ => {
+                let options = kinds
+                    .into_iter()
+                    .map(|kind| serde_json::json!({ "type": kind }))
+                    .collect();
+                map.insert("anyOf".to_string(), serde_json::Value::Array(options));
+            }
+        }
+        if nullable {
+            map.insert("nullable".to_string(), serde_json::Value::Bool(true));
+        }
+        Ok(())
+    }
+
+    /// Fit the schema at `path` and the schemas nested in it
+    fn schema(&mut self, schema: &mut serde_json::Value, path: &str) -> GraphBitResult<()> {
+        let Some(map) = schema.as_object_mut() else {
+            return Ok(());
+        };
+        self.type_list(map, path)?;
+        if let Some(options) = map.remove("oneOf") {
+            self.change(
+                path,
+                "oneOf",
+                "`oneOf`",
+                "oneOf downgraded to anyOf".to_string(),
+            )?;
+            if let serde_json::Value::Array(mut options) = options {
+                if let Some(serde_json::Value::Array(existing)) = map.remove("anyOf") {
+                    options.extend(existing);
+                }
+                map.insert("anyOf".to_string(), serde_json::Value::Array(options));
+            }
+        }
+        if let Some(value) = map.remove("const") {
+            self.change(
+                path,
+                "const",
+                "`const`",
+                "const replaced by a single-value enum".to_string(),
+            )?;
+            map.insert("enum".to_string(), serde_json::json!([value]));
+        }
+        for (exclusive, inclusive) in [
+            ("exclusiveMinimum", "minimum"),
+            ("exclusiveMaximum", "maximum"),
+        ] {
+            if let Some(bound) = map.remove(exclusive) {
+                self.change(
+                    path,
+                    exclusive,
+                    &format!("`{exclusive}`"),
+                    format!("{exclusive} replaced by the inclusive {inclusive}"),
+                )?;
+                if bound.is_number() {
+                    map.entry(inclusive.to_string()).or_insert(bound);
+                }
+            }
+        }
+        let unsupported: Vec<String> = map
+            .keys()
+            .filter(|keyword| !GEMINI_KEYWORDS.contains(&keyword.as_str()))
+            .cloned()
+            .collect();
+        for keyword in unsupported {
+            self.change(
+                path,
+                &keyword,
+                &format!("`{keyword}`"),
+                format!("Unsupported keyword {keyword} dropped"),
+            )?;
+            map.remove(&keyword);
+        }
+
+        if let Some(serde_json::Value::Object(properties)) = map.get_mut("properties") {
+            for (name, property) in properties.iter_mut() {
+                self.schema(property, &format!("{path}/properties/{name}"))?;
+            }
+        }
+        if let Some(items) = map.get_mut("items") {
+            self.schema(items, &format!("{path}/items"))?;
+        }
+        if let Some(serde_json::Value::Array(options)) = map.get_mut("anyOf") {
+            for (i, option) in options.iter_mut().enumerate() {
+                self.schema(option, &format!("{path}/anyOf/{i}"))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! `xAI` LLM provider implementation for Grok models
 
 use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::{RequestTimeouts, shared_client_with_timeouts};
 use crate::llm::providers::LlmProviderTrait;
 use crate::llm::{
     FinishReason, LlmMessage, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall, LlmUsage,
@@ -23,16 +24,7 @@ pub struct XaiProvider {
 impl XaiProvider {
     /// Create a new `xAI` provider
     pub fn new(api_key: String, model: String) -> GraphBitResult<Self> {
-        // Optimized client with connection pooling for better performance
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10) // Increased connection pool size
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("xai", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
         let base_url = "https://api.x.ai/v1".to_string();
 
         Ok(Self {
@@ -45,16 +37,7 @@ impl XaiProvider {
 
     /// Create a new `xAI` provider with custom base URL
     pub fn with_base_url(api_key: String, model: String, base_url: String) -> GraphBitResult<Self> {
-        // Use same optimized client settings
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .pool_idle_timeout(std::time::Duration::from_secs(30))
-            .tcp_keepalive(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| {
-                GraphBitError::llm_provider("xai", format!("Failed to create HTTP client: {e}"))
-            })?;
+        let client = Self::http_client(RequestTimeouts::none())?;
 
         Ok(Self {
             client,
@@ -64,6 +47,17 @@ impl XaiProvider {
         })
     }
 
+    /// Use `timeouts` for the requests of this provider, replacing the
+    /// timeouts of its HTTP settings
+    pub fn with_timeouts(mut self, timeouts: RequestTimeouts) -> GraphBitResult<Self> {
+        self.client = Self::http_client(timeouts)?;
+        Ok(self)
+    }
+
+    fn http_client(timeouts: RequestTimeouts) -> GraphBitResult<Client> {
+        shared_client_with_timeouts("xai", Some(Duration::from_secs(60)), None, timeouts)
+    }
+
     /// Convert `GraphBit` message to `xAI` message format
     fn convert_message(message: &LlmMessage) -> XaiMessage {
         XaiMessage {
@@ -228,15 +222,16 @@ impl LlmProviderTrait for XaiProvider {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request_json)
-            .send()
-            .await
-            .map_err(|e| GraphBitError::llm_provider("xai", format!("Request failed: {e}")))?;
+        let response = crate::capture::send(
+            "xai",
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request_json),
+        )
+        .await
+        .map_err(|e| GraphBitError::from_request_error("xai", &e))?;
 
         if !response.status().is_success() {
             let error_text = response
@@ -341,7 +336,7 @@ impl LlmProviderTrait for XaiProvider {
         )
         .await
         .map_err(|_| {
-            GraphBitError::llm_provider(
+            GraphBitError::timeout(
                 "xai",
                 format!(
                     "Connection timeout after {:?} - xAI did not respond. \
@@ -350,7 +345,7 @@ impl LlmProviderTrait for XaiProvider {
                 ),
             )
         })?
-        .map_err(|e| GraphBitError::llm_provider("xai", format!("Request failed: {e}")))?;
+        .map_err(|e| GraphBitError::from_request_error("xai", &e))?;
 
         if !response.status().is_success() {
             let error_text = timeout(ERROR_BODY_TIMEOUT, response.text())
@@ -414,7 +409,7 @@ impl LlmProviderTrait for XaiProvider {
                                     CHUNK_TIMEOUT
                                 );
                                 return Some((
-                                    Err(GraphBitError::llm_provider(
+                                    Err(GraphBitError::timeout(
                                         "xai",
                                         format!(
                                             "Stream timeout after {:?} - response may be incomplete",
@@ -12,5 +12,5 @@ pub mod vector;
 pub use service::MemoryService;
 pub use types::{
     Memory, MemoryAction, MemoryConfig, MemoryDecision, MemoryHistory, MemoryId, MemoryScope,
-    ScoredMemory,
+    ReembedProgress, ScoredMemory,
 };
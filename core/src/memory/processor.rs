@@ -1,5 +1,7 @@
 //! LLM-driven fact extraction and consolidation logic.
 
+use std::fmt::Write as _;
+
 use crate::errors::{GraphBitError, GraphBitResult};
 use crate::llm::{LlmMessage, LlmProviderTrait, LlmRequest};
 
@@ -35,11 +37,7 @@ impl MemoryProcessor {
             return Ok(Vec::new());
         }
 
-        let conversation = messages
-            .iter()
-            .map(|m| format!("{}: {}", role_label(&m.role), &m.content))
-            .collect::<Vec<_>>()
-            .join("\n");
+        let conversation = transcript(messages);
 
         let system_prompt = concat!(
             "You are a memory extraction assistant. Your task is to extract important facts, ",
@@ -129,6 +127,60 @@ impl MemoryProcessor {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Render `messages` as a `Role: content` transcript, one line per message, with
+/// the tool calls of assistant messages
+pub(crate) fn transcript(messages: &[LlmMessage]) -> String {
+    messages
+        .iter()
+        .map(transcript_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Transcript line of `message`
+pub(crate) fn transcript_line(message: &LlmMessage) -> String {
+    let mut line = format!("{}: {}", role_label(&message.role), message.content);
+    for call in &message.tool_calls {
+        let _ = write!(line, " [calls {}({})]", call.name, call.parameters);
+    }
+    line
+}
+
+/// Request asking the LLM to summarize `messages`, folding in the summary of
+/// the conversation before them when there is one
+pub(crate) fn summary_request(
+    previous_summary: Option<&str>,
+    messages: &[LlmMessage],
+) -> LlmRequest {
+    let system_prompt = concat!(
+        "You are a conversation summarization assistant. Summarize the conversation so that ",
+        "it can replace the original messages in further work on the same task.\n\n",
+        "Rules:\n",
+        "- Keep facts, decisions, tool results and open questions needed to continue.\n",
+        "- Drop greetings, filler and repeated content.\n",
+        "- If an earlier summary is given, merge it with the new messages into one summary.\n\n",
+        "Return only the summary text.",
+    );
+    let mut prompt = String::new();
+    if let Some(previous) = previous_summary {
+        let _ = write!(prompt, "Earlier summary:\n{previous}\n\n");
+    }
+    let _ = write!(
+        prompt,
+        "Summarize this conversation:\n\n{}",
+        transcript(messages)
+    );
+    LlmRequest::with_messages(vec![
+        LlmMessage::system(system_prompt),
+        LlmMessage::user(prompt),
+    ])
+    .with_max_tokens(SUMMARY_MAX_TOKENS)
+    .with_temperature(0.0)
+}
+
+/// Completion tokens allowed for a conversation summary
+const SUMMARY_MAX_TOKENS: u32 = 512;
+
 fn role_label(role: &crate::llm::LlmRole) -> &'static str {
     match role {
         crate::llm::LlmRole::User => "User",
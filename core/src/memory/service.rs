@@ -3,17 +3,20 @@
 //! deduplication, and persistent storage.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use chrono::Utc;
+use tokio::sync::RwLock;
 
-use crate::embeddings::EmbeddingService;
+use crate::embeddings::{EmbeddingConfig, EmbeddingService};
 use crate::errors::{GraphBitError, GraphBitResult};
-use crate::llm::{LlmMessage, LlmProviderFactory};
+use crate::llm::{LlmMessage, LlmProvider, LlmProviderFactory, RerankConfig};
 
 use super::processor::MemoryProcessor;
 use super::store::MetadataStore;
 use super::types::{
-    Memory, MemoryAction, MemoryConfig, MemoryHistory, MemoryId, MemoryScope, ScoredMemory,
+    Memory, MemoryAction, MemoryConfig, MemoryHistory, MemoryId, MemoryScope, ReembedProgress,
+    ScoredMemory,
 };
 use super::vector::VectorIndex;
 
@@ -21,20 +24,38 @@ use super::vector::VectorIndex;
 pub struct MemoryService {
     store: MetadataStore,
     vector_index: VectorIndex,
-    embedding_service: EmbeddingService,
+    embedding: RwLock<Arc<ActiveEmbedding>>,
     processor: MemoryProcessor,
     config: MemoryConfig,
 }
 
+/// The embedding model new embeddings are made with.
+struct ActiveEmbedding {
+    service: EmbeddingService,
+    model: String,
+}
+
+impl ActiveEmbedding {
+    fn new(config: EmbeddingConfig) -> GraphBitResult<Self> {
+        Ok(Self {
+            model: config.model.clone(),
+            service: EmbeddingService::new(config)?,
+        })
+    }
+}
+
 impl MemoryService {
     /// Build a new `MemoryService` from the provided configuration.
     ///
     /// This creates the SQLite store, vector index, embedding service, and
-    /// LLM processor, then loads any existing memories into the vector index.
+    /// LLM processor, then loads the stored embeddings into the vector index.
+    /// Memories stored without an embedding are embedded. When the stored
+    /// embeddings were made with another model, nothing is loaded and
+    /// searching fails until they are re-embedded with [`Self::reembed_all`].
     pub async fn new(config: MemoryConfig) -> GraphBitResult<Self> {
         let store = MetadataStore::new(&config.db_path)?;
         let vector_index = VectorIndex::new();
-        let embedding_service = EmbeddingService::new(config.embedding_config.clone())?;
+        let embedding = ActiveEmbedding::new(config.embedding_config.clone())?;
         let llm_provider = LlmProviderFactory::create_provider(config.llm_config.clone())?;
         let processor = MemoryProcessor::new(
             llm_provider,
@@ -45,7 +66,7 @@ impl MemoryService {
         let service = Self {
             store,
             vector_index,
-            embedding_service,
+            embedding: RwLock::new(Arc::new(embedding)),
             processor,
             config,
         };
@@ -64,6 +85,8 @@ impl MemoryService {
         messages: &[LlmMessage],
         scope: &MemoryScope,
     ) -> GraphBitResult<Vec<Memory>> {
+        let embedding = self.compatible_embedding().await?;
+
         // Phase 1: extract facts.
         let facts = self.processor.extract_facts(messages).await?;
         if facts.is_empty() {
@@ -80,7 +103,7 @@ impl MemoryService {
             match decision.action {
                 MemoryAction::Add => {
                     let memory = self
-                        .create_memory(&decision.fact, scope.clone())
+                        .create_memory(&embedding, &decision.fact, scope.clone())
                         .await?;
                     result_memories.push(memory);
                 }
@@ -90,6 +113,7 @@ impl MemoryService {
                             if let Some(old_memory) = self.store.get_memory(&target_id).await? {
                                 let updated = self
                                     .update_memory_internal(
+                                        &embedding,
                                         &target_id,
                                         &decision.fact,
                                         &old_memory.content,
@@ -118,13 +142,18 @@ impl MemoryService {
     }
 
     /// Embed a query and search for the most similar memories within a scope.
+    ///
+    /// With `rerank`, the `top_k` memories found are re-ranked by the memory
+    /// LLM and their scores are its relevance scores.
     pub async fn search(
         &self,
         query: &str,
         scope: &MemoryScope,
         top_k: usize,
+        rerank: Option<&RerankConfig>,
     ) -> GraphBitResult<Vec<ScoredMemory>> {
-        let query_embedding = self.embedding_service.embed_text(query).await?;
+        let embedding = self.compatible_embedding().await?;
+        let query_embedding = embedding.service.embed_text(query).await?;
         let results = self
             .vector_index
             .search(&query_embedding, top_k, self.config.similarity_threshold)
@@ -139,7 +168,36 @@ impl MemoryService {
             }
         }
 
-        Ok(scored)
+        match rerank {
+            Some(config) => self.rerank(query, scored, config).await,
+            None => Ok(scored),
+        }
+    }
+
+    /// Order `candidates` by the memory LLM's judgement of their relevance
+    async fn rerank(
+        &self,
+        query: &str,
+        candidates: Vec<ScoredMemory>,
+        config: &RerankConfig,
+    ) -> GraphBitResult<Vec<ScoredMemory>> {
+        let provider = LlmProvider::new(
+            LlmProviderFactory::create_provider(self.config.llm_config.clone())?,
+            self.config.llm_config.clone(),
+        );
+        let contents: Vec<String> = candidates
+            .iter()
+            .map(|candidate| candidate.memory.content.clone())
+            .collect();
+        let reranking = provider.rerank(query, &contents, config).await?;
+        Ok(reranking
+            .documents
+            .into_iter()
+            .map(|document| ScoredMemory {
+                memory: candidates[document.index].memory.clone(),
+                score: document.score,
+            })
+            .collect())
     }
 
     /// Get a single memory by its ID.
@@ -154,13 +212,14 @@ impl MemoryService {
 
     /// Update a memory's content by ID.
     pub async fn update(&self, memory_id: &MemoryId, content: &str) -> GraphBitResult<Memory> {
+        let embedding = self.compatible_embedding().await?;
         let old = self
             .store
             .get_memory(memory_id)
             .await?
             .ok_or_else(|| GraphBitError::memory(format!("Memory not found: {memory_id}")))?;
 
-        self.update_memory_internal(memory_id, content, &old.content)
+        self.update_memory_internal(&embedding, memory_id, content, &old.content)
             .await
     }
 
@@ -186,25 +245,154 @@ impl MemoryService {
         self.store.get_history(memory_id).await
     }
 
+    /// Get the name of the embedding model new embeddings are made with.
+    pub async fn embedding_model(&self) -> String {
+        self.embedding.read().await.model.clone()
+    }
+
+    /// Re-embed every stored memory with `embedding_config` and switch the
+    /// service to it.
+    ///
+    /// Memories are embedded `batch_size` at a time in ID order. Each batch
+    /// is written in one transaction together with a checkpoint, and
+    /// `progress` is called after it; an error returned by `progress` stops
+    /// the re-embedding. Until every memory is re-embedded, adding, updating
+    /// and searching memories fail; calling this again with the same model
+    /// resumes after the last written batch. Returns the number of memories
+    /// embedded by this call.
+    pub async fn reembed_all(
+        &self,
+        embedding_config: EmbeddingConfig,
+        batch_size: usize,
+        mut progress: impl FnMut(ReembedProgress) -> GraphBitResult<()>,
+    ) -> GraphBitResult<usize> {
+        if batch_size == 0 {
+            return Err(GraphBitError::validation(
+                "batch_size",
+                "Re-embedding batch size must be greater than 0",
+            ));
+        }
+        let embedding = ActiveEmbedding::new(embedding_config)?;
+
+        let mut after = match self.store.get_reembed_checkpoint().await? {
+            Some(checkpoint) if checkpoint.model == embedding.model => checkpoint.last_id,
+            _ => {
+                self.store.start_reembed(&embedding.model).await?;
+                None
+            }
+        };
+        let total = self.store.count_memories(None).await?;
+        let mut processed = match &after {
+            Some(last_id) => self.store.count_memories(Some(last_id)).await?,
+            None => 0,
+        };
+
+        let mut embedded = 0;
+        loop {
+            let batch = self
+                .store
+                .get_memories_after(after.as_ref(), batch_size)
+                .await?;
+            let Some(last_id) = batch.last().map(|memory| memory.id.clone()) else {
+                break;
+            };
+            let contents: Vec<String> = batch.iter().map(|m| m.content.clone()).collect();
+            let vectors = embedding.service.embed_texts(&contents).await?;
+            if vectors.len() != batch.len() {
+                return Err(GraphBitError::memory(format!(
+                    "Embedding provider returned {} embeddings for {} memories",
+                    vectors.len(),
+                    batch.len()
+                )));
+            }
+            let vectors: Vec<(MemoryId, Vec<f32>)> =
+                batch.into_iter().map(|m| m.id).zip(vectors).collect();
+            self.store
+                .write_reembed_batch(&embedding.model, &vectors, &last_id)
+                .await?;
+
+            embedded += vectors.len();
+            processed += vectors.len();
+            progress(ReembedProgress {
+                processed,
+                total: total.max(processed),
+            })?;
+            after = Some(last_id);
+        }
+
+        self.store.finish_reembed(&embedding.model).await?;
+        self.index_embeddings(&embedding.model).await?;
+        *self.embedding.write().await = Arc::new(embedding);
+        Ok(embedded)
+    }
+
     // -----------------------------------------------------------------------
     // Internal helpers
     // -----------------------------------------------------------------------
 
-    /// Load all existing memories from the store into the vector index.
+    /// Load the stored embeddings into the vector index, embedding memories
+    /// stored without one. Nothing is loaded when the stored embeddings were
+    /// made with another model or are being re-embedded.
     async fn load_existing_memories(&self) -> GraphBitResult<()> {
-        let all_memories = self.store.get_all_memories(&MemoryScope::default()).await?;
-        for memory in &all_memories {
-            let embedding = self.embedding_service.embed_text(&memory.content).await?;
-            self.vector_index
-                .insert(memory.id.clone(), embedding)
-                .await;
+        let embedding = Arc::clone(&*self.embedding.read().await);
+        if self.store.get_reembed_checkpoint().await?.is_some() {
+            return Ok(());
+        }
+        match self.store.get_embedding_model().await? {
+            Some(model) if model != embedding.model => return Ok(()),
+            Some(_) => {}
+            None => self.store.set_embedding_model(&embedding.model).await?,
+        }
+
+        self.index_embeddings(&embedding.model).await?;
+        let missing = self
+            .store
+            .get_memories_without_embedding(&embedding.model)
+            .await?;
+        for memory in missing {
+            let vector = embedding.service.embed_text(&memory.content).await?;
+            self.store
+                .put_embedding(&memory.id, &embedding.model, &vector)
+                .await?;
+            self.vector_index.insert(memory.id, vector).await;
+        }
+        Ok(())
+    }
+
+    /// Replace the vector index with the stored embeddings made with `model`.
+    async fn index_embeddings(&self, model: &str) -> GraphBitResult<()> {
+        let embeddings = self.store.get_embeddings(model).await?;
+        self.vector_index.clear().await;
+        for (memory_id, vector) in embeddings {
+            self.vector_index.insert(memory_id, vector).await;
         }
         Ok(())
     }
 
+    /// Get the active embedding model, refusing it when the stored
+    /// embeddings were made with another model or are being re-embedded, as
+    /// their vectors cannot be compared with the ones it makes.
+    async fn compatible_embedding(&self) -> GraphBitResult<Arc<ActiveEmbedding>> {
+        let embedding = Arc::clone(&*self.embedding.read().await);
+        if let Some(checkpoint) = self.store.get_reembed_checkpoint().await? {
+            return Err(GraphBitError::memory(format!(
+                "Re-embedding the memories with '{}' is unfinished; call reembed_all again to resume it",
+                checkpoint.model
+            )));
+        }
+        match self.store.get_embedding_model().await? {
+            Some(model) if model != embedding.model => Err(GraphBitError::memory(format!(
+                "Stored memory embeddings were made with '{model}', not '{}'; re-embed them with reembed_all first",
+                embedding.model
+            ))),
+            _ => Ok(embedding),
+        }
+    }
+
     /// Create a brand-new memory: hash, embed, store, index, record history.
     async fn create_memory(
         &self,
+        embedding: &ActiveEmbedding,
         content: &str,
         scope: MemoryScope,
     ) -> GraphBitResult<Memory> {
@@ -222,10 +410,12 @@ impl MemoryService {
             hash,
         };
 
+        let vector = embedding.service.embed_text(content).await?;
         self.store.insert_memory(&memory).await?;
-
-        let embedding = self.embedding_service.embed_text(content).await?;
-        self.vector_index.insert(id.clone(), embedding).await;
+        self.store
+            .put_embedding(&id, &embedding.model, &vector)
+            .await?;
+        self.vector_index.insert(id.clone(), vector).await;
 
         self.store
             .insert_history(&MemoryHistory {
@@ -243,17 +433,20 @@ impl MemoryService {
     /// Update an existing memory: re-hash, re-embed, persist, record history.
     async fn update_memory_internal(
         &self,
+        embedding: &ActiveEmbedding,
         memory_id: &MemoryId,
         new_content: &str,
         old_content: &str,
     ) -> GraphBitResult<Memory> {
         let hash = simple_hash(new_content);
+        let vector = embedding.service.embed_text(new_content).await?;
         self.store
             .update_memory(memory_id, new_content, &hash)
             .await?;
-
-        let embedding = self.embedding_service.embed_text(new_content).await?;
-        self.vector_index.update(memory_id, embedding).await;
+        self.store
+            .put_embedding(memory_id, &embedding.model, &vector)
+            .await?;
+        self.vector_index.update(memory_id, vector).await;
 
         self.store
             .insert_history(&MemoryHistory {
@@ -61,6 +61,16 @@ impl MetadataStore {
                 timestamp   TEXT NOT NULL,
                 FOREIGN KEY (memory_id) REFERENCES memories(id) ON DELETE CASCADE
             );
+            CREATE TABLE IF NOT EXISTS memory_embeddings (
+                memory_id   TEXT PRIMARY KEY,
+                model       TEXT NOT NULL,
+                embedding   BLOB NOT NULL,
+                FOREIGN KEY (memory_id) REFERENCES memories(id) ON DELETE CASCADE
+            );
+            CREATE TABLE IF NOT EXISTS memory_settings (
+                key         TEXT PRIMARY KEY,
+                value       TEXT NOT NULL
+            );
             CREATE INDEX IF NOT EXISTS idx_memories_user_id  ON memories(user_id);
             CREATE INDEX IF NOT EXISTS idx_memories_agent_id ON memories(agent_id);
             CREATE INDEX IF NOT EXISTS idx_memories_run_id   ON memories(run_id);
@@ -252,12 +262,281 @@ impl MetadataStore {
         .await
         .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
     }
+
+    /// Store the embedding of a memory, made with `model`.
+    pub async fn put_embedding(
+        &self,
+        memory_id: &MemoryId,
+        model: &str,
+        embedding: &[f32],
+    ) -> GraphBitResult<()> {
+        let id = memory_id.to_string();
+        let model = model.to_string();
+        let blob = embedding_to_blob(embedding);
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<()> {
+            let conn = conn_arc.blocking_lock();
+            conn.execute(
+                "INSERT OR REPLACE INTO memory_embeddings (memory_id, model, embedding) VALUES (?1, ?2, ?3)",
+                rusqlite::params![id, model, blob],
+            )?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+
+    /// Get the stored embeddings made with `model`.
+    pub async fn get_embeddings(&self, model: &str) -> GraphBitResult<Vec<(MemoryId, Vec<f32>)>> {
+        let model = model.to_string();
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<Vec<(MemoryId, Vec<f32>)>> {
+            let conn = conn_arc.blocking_lock();
+            let mut stmt = conn
+                .prepare("SELECT memory_id, embedding FROM memory_embeddings WHERE model = ?1")?;
+            let mut rows = stmt.query(rusqlite::params![model])?;
+            let mut embeddings = Vec::new();
+            while let Some(row) = rows.next()? {
+                let id: String = row.get(0)?;
+                let blob: Vec<u8> = row.get(1)?;
+                let id = MemoryId(Uuid::parse_str(&id).map_err(|e| {
+                    GraphBitError::memory(format!("Invalid UUID in embeddings: {e}"))
+                })?);
+                embeddings.push((id, blob_to_embedding(&blob)));
+            }
+            Ok(embeddings)
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+
+    /// Get the memories without an embedding made with `model`.
+    pub async fn get_memories_without_embedding(&self, model: &str) -> GraphBitResult<Vec<Memory>> {
+        let model = model.to_string();
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<Vec<Memory>> {
+            let conn = conn_arc.blocking_lock();
+            let mut stmt = conn.prepare(
+                "SELECT id, content, user_id, agent_id, run_id, hash, metadata, created_at, updated_at FROM memories m
+                 WHERE NOT EXISTS (SELECT 1 FROM memory_embeddings e WHERE e.memory_id = m.id AND e.model = ?1)",
+            )?;
+            let mut rows = stmt.query(rusqlite::params![model])?;
+            let mut memories = Vec::new();
+            while let Some(row) = rows.next()? {
+                memories.push(row_to_memory(row)?);
+            }
+            Ok(memories)
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+
+    /// Get up to `limit` memories in ID order, starting after `after`.
+    pub async fn get_memories_after(
+        &self,
+        after: Option<&MemoryId>,
+        limit: usize,
+    ) -> GraphBitResult<Vec<Memory>> {
+        let after = after.map(ToString::to_string).unwrap_or_default();
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<Vec<Memory>> {
+            let conn = conn_arc.blocking_lock();
+            let mut stmt = conn.prepare(
+                "SELECT id, content, user_id, agent_id, run_id, hash, metadata, created_at, updated_at FROM memories
+                 WHERE id > ?1 ORDER BY id LIMIT ?2",
+            )?;
+            let mut rows = stmt.query(rusqlite::params![after, limit])?;
+            let mut memories = Vec::new();
+            while let Some(row) = rows.next()? {
+                memories.push(row_to_memory(row)?);
+            }
+            Ok(memories)
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+
+    /// Count the memories, or those with an ID up to `through`.
+    pub async fn count_memories(&self, through: Option<&MemoryId>) -> GraphBitResult<usize> {
+        let through = through.map(ToString::to_string);
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<usize> {
+            let conn = conn_arc.blocking_lock();
+            let count: i64 = match through {
+                Some(id) => conn.query_row(
+                    "SELECT COUNT(*) FROM memories WHERE id <= ?1",
+                    rusqlite::params![id],
+                    |row| row.get(0),
+                )?,
+                None => conn.query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))?,
+            };
+            Ok(usize::try_from(count).unwrap_or_default())
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+
+    /// Get the embedding model the stored embeddings were made with.
+    pub async fn get_embedding_model(&self) -> GraphBitResult<Option<String>> {
+        self.get_setting(EMBEDDING_MODEL_KEY).await
+    }
+
+    /// Record the embedding model the stored embeddings are made with.
+    pub async fn set_embedding_model(&self, model: &str) -> GraphBitResult<()> {
+        let model = model.to_string();
+        self.transaction(move |tx| set_setting(tx, EMBEDDING_MODEL_KEY, &model))
+            .await
+    }
+
+    /// Get the re-embedding in progress, if any.
+    pub async fn get_reembed_checkpoint(&self) -> GraphBitResult<Option<ReembedCheckpoint>> {
+        let Some(model) = self.get_setting(REEMBED_MODEL_KEY).await? else {
+            return Ok(None);
+        };
+        let last_id = match self.get_setting(REEMBED_LAST_ID_KEY).await? {
+            Some(id) => Some(MemoryId(Uuid::parse_str(&id).map_err(|e| {
+                GraphBitError::memory(format!("Invalid UUID in re-embedding checkpoint: {e}"))
+            })?)),
+            None => None,
+        };
+        Ok(Some(ReembedCheckpoint { model, last_id }))
+    }
+
+    /// Start re-embedding every memory with `model`, discarding the
+    /// checkpoint of any earlier re-embedding.
+    pub async fn start_reembed(&self, model: &str) -> GraphBitResult<()> {
+        let model = model.to_string();
+        self.transaction(move |tx| {
+            set_setting(tx, REEMBED_MODEL_KEY, &model)?;
+            tx.execute(
+                "DELETE FROM memory_settings WHERE key = ?1",
+                rusqlite::params![REEMBED_LAST_ID_KEY],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Store a batch of re-embedded memories and move the re-embedding
+    /// checkpoint to `last_id`, in one transaction.
+    pub async fn write_reembed_batch(
+        &self,
+        model: &str,
+        embeddings: &[(MemoryId, Vec<f32>)],
+        last_id: &MemoryId,
+    ) -> GraphBitResult<()> {
+        let model = model.to_string();
+        let rows: Vec<(String, Vec<u8>)> = embeddings
+            .iter()
+            .map(|(id, embedding)| (id.to_string(), embedding_to_blob(embedding)))
+            .collect();
+        let last_id = last_id.to_string();
+
+        self.transaction(move |tx| {
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT OR REPLACE INTO memory_embeddings (memory_id, model, embedding) VALUES (?1, ?2, ?3)",
+                )?;
+                for (id, blob) in &rows {
+                    stmt.execute(rusqlite::params![id, model, blob])?;
+                }
+            }
+            set_setting(tx, REEMBED_LAST_ID_KEY, &last_id)
+        })
+        .await
+    }
+
+    /// Finish the re-embedding: record `model` as the embedding model and
+    /// drop the checkpoint, in one transaction.
+    pub async fn finish_reembed(&self, model: &str) -> GraphBitResult<()> {
+        let model = model.to_string();
+        self.transaction(move |tx| {
+            set_setting(tx, EMBEDDING_MODEL_KEY, &model)?;
+            tx.execute(
+                "DELETE FROM memory_settings WHERE key IN (?1, ?2)",
+                rusqlite::params![REEMBED_MODEL_KEY, REEMBED_LAST_ID_KEY],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_setting(&self, key: &'static str) -> GraphBitResult<Option<String>> {
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<Option<String>> {
+            let conn = conn_arc.blocking_lock();
+            let mut stmt = conn.prepare("SELECT value FROM memory_settings WHERE key = ?1")?;
+            let mut rows = stmt.query(rusqlite::params![key])?;
+            match rows.next()? {
+                Some(row) => Ok(Some(row.get(0)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+
+    /// Run `apply` in a transaction, committing it when `apply` succeeds.
+    async fn transaction<F>(&self, apply: F) -> GraphBitResult<()>
+    where
+        F: FnOnce(&rusqlite::Transaction<'_>) -> GraphBitResult<()> + Send + 'static,
+    {
+        let conn_arc = Arc::clone(&self.conn);
+
+        tokio::task::spawn_blocking(move || -> GraphBitResult<()> {
+            let mut conn = conn_arc.blocking_lock();
+            let tx = conn.transaction()?;
+            apply(&tx)?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| GraphBitError::memory(format!("Join error: {e}")))?
+    }
+}
+
+/// Position of an interrupted re-embedding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReembedCheckpoint {
+    /// Embedding model the memories are being re-embedded with.
+    pub model: String,
+    /// Last memory re-embedded, in ID order; `None` before the first batch.
+    pub last_id: Option<MemoryId>,
 }
 
+const EMBEDDING_MODEL_KEY: &str = "embedding_model";
+const REEMBED_MODEL_KEY: &str = "reembed_model";
+const REEMBED_LAST_ID_KEY: &str = "reembed_last_id";
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
+fn set_setting(tx: &rusqlite::Transaction<'_>, key: &str, value: &str) -> GraphBitResult<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO memory_settings (key, value) VALUES (?1, ?2)",
+        rusqlite::params![key, value],
+    )?;
+    Ok(())
+}
+
+fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn blob_to_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
 fn row_to_memory(row: &rusqlite::Row<'_>) -> GraphBitResult<Memory> {
     let id_str: String = row.get(0)?;
     let content: String = row.get(1)?;
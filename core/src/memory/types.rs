@@ -84,7 +84,8 @@ pub struct Memory {
 pub struct ScoredMemory {
     /// The memory.
     pub memory: Memory,
-    /// Cosine similarity score (0.0 .. 1.0).
+    /// Cosine similarity score (0.0 .. 1.0), or the relevance score when
+    /// the search re-ranked its results.
     pub score: f64,
 }
 
@@ -150,6 +151,16 @@ pub struct MemoryDecision {
     pub target_memory_id: Option<String>,
 }
 
+/// Progress of re-embedding the stored memories with a new embedding model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReembedProgress {
+    /// Memories re-embedded so far, including those of an interrupted run
+    /// that was resumed.
+    pub processed: usize,
+    /// Memories to re-embed in total.
+    pub total: usize,
+}
+
 /// Configuration for the memory subsystem.
 #[derive(Debug, Clone)]
 pub struct MemoryConfig {
@@ -262,8 +273,10 @@ mod tests {
             model: "text-embedding-3-small".to_string(),
             base_url: None,
             timeout_seconds: None,
+            timeouts: Default::default(),
             max_batch_size: None,
             extra_params: HashMap::new(),
+            retry: None,
             #[cfg(feature = "python")]
             python_instance: None,
         };
@@ -0,0 +1,198 @@
+//! Content-addressed storage for large node outputs
+//!
+//! Node outputs live in the [`WorkflowContext`](crate::types::WorkflowContext), so a
+//! node returning megabytes of text makes every context clone and every serialized
+//! context just as large. With an [`OutputSpill`] attached, outputs whose JSON
+//! encoding exceeds a size threshold are written to an [`OutputStore`] keyed by
+//! the SHA-256 of that encoding, and the context keeps only an [`OutputRef`]:
+//!
+//! ```json
+//! {"$output_ref": {"sha256": "9f86d0…", "size_bytes": 83886080}}
+//! ```
+//!
+//! Identical outputs share one stored copy, across nodes and across runs using
+//! the same store. The executor dereferences spilled outputs when it renders
+//! templates, evaluates expressions and feeds downstream nodes.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Storage backend for spilled outputs, keyed by the SHA-256 hex digest of the content
+pub trait OutputStore: Send + Sync {
+    /// Store `bytes` under `key`. Storing a key that already exists is a no-op.
+    fn put(&self, key: &str, bytes: &[u8]) -> GraphBitResult<()>;
+
+    /// Read the bytes stored under `key`
+    fn get(&self, key: &str) -> GraphBitResult<Vec<u8>>;
+}
+
+/// [`OutputStore`] keeping one file per output under a directory
+#[derive(Debug, Clone)]
+pub struct FileOutputStore {
+    root: PathBuf,
+}
+
+impl FileOutputStore {
+    /// Create a store in `root`, creating the directory if needed
+    pub fn new(root: impl Into<PathBuf>) -> GraphBitResult<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Directory holding the stored outputs
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file holding the output stored under `key`
+    #[must_use]
+    pub fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(format!("{key}.json"))
+    }
+}
+
+impl OutputStore for FileOutputStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> GraphBitResult<()> {
+        let path = self.path_for(key);
+        if path.exists() {
+            return Ok(());
+        }
+        // Write under a unique name and rename, so readers never see a partial file
+        let partial = self
+            .root
+            .join(format!("{key}.{}.partial", uuid::Uuid::new_v4()));
+        std::fs::write(&partial, bytes)?;
+        if let Err(e) = std::fs::rename(&partial, &path) {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> GraphBitResult<Vec<u8>> {
+        Ok(std::fs::read(self.path_for(key))?)
+    }
+}
+
+/// Reference to a spilled output, stored in the context in place of the output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputRef {
+    /// SHA-256 hex digest of the output's JSON encoding
+    pub sha256: String,
+    /// Size of the output's JSON encoding in bytes
+    pub size_bytes: u64,
+}
+
+impl OutputRef {
+    /// Key of the single-entry object standing in for a spilled output
+    pub const KEY: &'static str = "$output_ref";
+
+    /// The JSON value stored in the context in place of the output
+    #[must_use]
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::json!({ Self::KEY: self })
+    }
+
+    /// Read a reference from a value stored in the context, if it is one
+    #[must_use]
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        let object = value.as_object()?;
+        if object.len() != 1 {
+            return None;
+        }
+        serde_json::from_value(object.get(Self::KEY)?.clone()).ok()
+    }
+}
+
+/// Spills node outputs larger than a threshold to an [`OutputStore`]
+#[derive(Clone)]
+pub struct OutputSpill {
+    store: Arc<dyn OutputStore>,
+    threshold_bytes: usize,
+}
+
+impl OutputSpill {
+    /// Default spill threshold: 1 MiB of JSON
+    pub const DEFAULT_THRESHOLD_BYTES: usize = 1024 * 1024;
+
+    /// Spill outputs whose JSON encoding is larger than `threshold_bytes` to `store`
+    pub fn new(store: Arc<dyn OutputStore>, threshold_bytes: usize) -> Self {
+        Self {
+            store,
+            threshold_bytes,
+        }
+    }
+
+    /// Spill outputs larger than [`Self::DEFAULT_THRESHOLD_BYTES`] to files under `root`
+    pub fn to_directory(root: impl Into<PathBuf>) -> GraphBitResult<Self> {
+        Ok(Self::new(
+            Arc::new(FileOutputStore::new(root)?),
+            Self::DEFAULT_THRESHOLD_BYTES,
+        ))
+    }
+
+    /// Size above which outputs are spilled
+    #[must_use]
+    pub const fn threshold_bytes(&self) -> usize {
+        self.threshold_bytes
+    }
+
+    /// Spill `output` if its JSON encoding is larger than the threshold, returning
+    /// the reference to keep in its place; `None` when it stays in memory
+    pub fn spill(&self, output: &serde_json::Value) -> GraphBitResult<Option<OutputRef>> {
+        if OutputRef::from_value(output).is_some() {
+            return Ok(None);
+        }
+        let bytes = serde_json::to_vec(output)?;
+        if bytes.len() <= self.threshold_bytes {
+            return Ok(None);
+        }
+        let output_ref = OutputRef {
+            sha256: format!("{:x}", Sha256::digest(&bytes)),
+            size_bytes: bytes.len() as u64,
+        };
+        self.store.put(&output_ref.sha256, &bytes)?;
+        Ok(Some(output_ref))
+    }
+
+    /// Read a spilled output back from the store
+    pub fn load(&self, output_ref: &OutputRef) -> GraphBitResult<serde_json::Value> {
+        Ok(serde_json::from_slice(
+            &self.load_bytes(&output_ref.sha256)?,
+        )?)
+    }
+
+    /// Write raw `bytes` to the store regardless of the threshold, returning
+    /// the SHA-256 hex digest they are stored under
+    pub fn store_bytes(&self, bytes: &[u8]) -> GraphBitResult<String> {
+        let sha256 = format!("{:x}", Sha256::digest(bytes));
+        self.store.put(&sha256, bytes)?;
+        Ok(sha256)
+    }
+
+    /// Read the bytes stored under `sha256`, checking them against the digest
+    pub fn load_bytes(&self, sha256: &str) -> GraphBitResult<Vec<u8>> {
+        let bytes = self.store.get(sha256)?;
+        if format!("{:x}", Sha256::digest(&bytes)) != sha256 {
+            return Err(GraphBitError::workflow_execution(format!(
+                "Spilled output {sha256} does not match its content hash"
+            )));
+        }
+        Ok(bytes)
+    }
+}
+
+impl fmt::Debug for OutputSpill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutputSpill")
+            .field("threshold_bytes", &self.threshold_bytes)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,197 @@
+//! Deliberate pacing of node executions and LLM calls
+//!
+//! Nodes may wait before and after they run, configured with the node config
+//! keys `pre_execution_delay_ms`, `post_execution_delay_ms` and
+//! `delay_jitter_ms` (see [`NodePacing`]). The executor waits outside the
+//! node's measured duration: the pre-execution delay before the node takes
+//! its concurrency permits, the post-execution delay after it released them.
+//!
+//! An [`LlmCallSpacing`] runs as LLM middleware shared by every execution of an
+//! executor and starts its LLM calls at least a minimum interval apart. It
+//! waits before the request is sent, so provider rate limits and concurrency
+//! permits apply on top of the spacing.
+//!
+//! All waits use the [current clock](crate::clock), so tests can run them in
+//! paused or frozen time.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::{LlmMiddleware, LlmRequest};
+
+tokio::task_local! {
+    static LLM_SPACING_WAIT_MS: Arc<AtomicU64>;
+}
+
+/// Delays of a node around its execution, from its node config
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodePacing {
+    /// Milliseconds to wait before the node runs
+    pub pre_execution_delay_ms: u64,
+    /// Milliseconds to wait after the node ran, before its dependents run
+    pub post_execution_delay_ms: u64,
+    /// Up to this many milliseconds, chosen at random, are added to each
+    /// non-zero delay
+    pub jitter_ms: u64,
+}
+
+impl NodePacing {
+    /// The pacing of a node configuration: its `pre_execution_delay_ms`,
+    /// `post_execution_delay_ms` and `delay_jitter_ms`. `None` when the node
+    /// waits neither before nor after it runs.
+    pub fn from_node_config(
+        config: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<Option<Self>> {
+        let millis = |key: &str| -> GraphBitResult<u64> {
+            match config.get(key) {
+                None | Some(serde_json::Value::Null) => Ok(0),
+                Some(value) => value.as_u64().ok_or_else(|| {
+                    GraphBitError::validation(
+                        key,
+                        format!("Expected a non-negative number of milliseconds, got {value}"),
+                    )
+                }),
+            }
+        };
+        let pacing = Self {
+            pre_execution_delay_ms: millis("pre_execution_delay_ms")?,
+            post_execution_delay_ms: millis("post_execution_delay_ms")?,
+            jitter_ms: millis("delay_jitter_ms")?,
+        };
+        if pacing.pre_execution_delay_ms == 0 && pacing.post_execution_delay_ms == 0 {
+            if pacing.jitter_ms > 0 {
+                return Err(GraphBitError::validation(
+                    "delay_jitter_ms",
+                    "delay_jitter_ms requires pre_execution_delay_ms or post_execution_delay_ms",
+                ));
+            }
+            return Ok(None);
+        }
+        Ok(Some(pacing))
+    }
+
+    /// Delay to wait before the node runs, jitter included
+    #[must_use]
+    pub fn pre_execution_delay(self) -> Duration {
+        self.jittered(self.pre_execution_delay_ms)
+    }
+
+    /// Delay to wait after the node ran, jitter included
+    #[must_use]
+    pub fn post_execution_delay(self) -> Duration {
+        self.jittered(self.post_execution_delay_ms)
+    }
+
+    fn jittered(self, delay_ms: u64) -> Duration {
+        if delay_ms == 0 {
+            return Duration::ZERO;
+        }
+        let jitter = if self.jitter_ms == 0 {
+            0
+        } else {
+            rand::rng().random_range(0..=self.jitter_ms)
+        };
+        Duration::from_millis(delay_ms.saturating_add(jitter))
+    }
+}
+
+/// Time a node spent waiting for pacing, outside of and within its execution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacingDelays {
+    /// Milliseconds waited before the node ran, outside its duration
+    pub pre_execution_ms: u64,
+    /// Milliseconds waited after the node ran, outside its duration
+    pub post_execution_ms: u64,
+    /// Milliseconds the node's LLM calls waited for their turn, within its duration
+    pub llm_spacing_ms: u64,
+}
+
+impl PacingDelays {
+    /// Whether the node did not wait at all
+    #[must_use]
+    pub const fn is_zero(self) -> bool {
+        self.pre_execution_ms == 0 && self.post_execution_ms == 0 && self.llm_spacing_ms == 0
+    }
+
+    /// Milliseconds waited in total
+    #[must_use]
+    pub const fn total_ms(self) -> u64 {
+        self.pre_execution_ms
+            .saturating_add(self.post_execution_ms)
+            .saturating_add(self.llm_spacing_ms)
+    }
+}
+
+/// LLM middleware starting LLM calls at least a minimum interval apart
+#[derive(Debug)]
+pub struct LlmCallSpacing {
+    interval: Duration,
+    /// Earliest start of the next call
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl LlmCallSpacing {
+    /// Space LLM calls `interval_ms` milliseconds apart
+    #[must_use]
+    pub const fn new(interval_ms: u64) -> Self {
+        Self {
+            interval: Duration::from_millis(interval_ms),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Minimum interval between the starts of two LLM calls
+    #[must_use]
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Reserve the next free slot and return when it starts. Calls waiting
+    /// concurrently get consecutive slots, in the order they asked.
+    fn reserve(&self) -> Instant {
+        let mut next_slot = self
+            .next_slot
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = crate::clock::instant();
+        let slot = next_slot.map_or(now, |next| next.max(now));
+        *next_slot = Some(slot + self.interval);
+        slot
+    }
+}
+
+#[async_trait]
+impl LlmMiddleware for LlmCallSpacing {
+    fn name(&self) -> &str {
+        "llm_call_spacing"
+    }
+
+    async fn before_request(&self, _request: &mut LlmRequest) -> GraphBitResult<()> {
+        let slot = self.reserve();
+        let wait = slot.saturating_duration_since(crate::clock::instant());
+        if !wait.is_zero() {
+            crate::clock::sleep(wait).await;
+            let waited_ms = u64::try_from(wait.as_millis()).unwrap_or(u64::MAX);
+            let _ =
+                LLM_SPACING_WAIT_MS.try_with(|total| total.fetch_add(waited_ms, Ordering::Relaxed));
+        }
+        Ok(())
+    }
+}
+
+/// Run `future`, the execution of a node, and return its output with the
+/// milliseconds its LLM calls waited for an [`LlmCallSpacing`]
+pub(crate) async fn run_node<F: Future>(future: F) -> (F::Output, u64) {
+    let waited = Arc::new(AtomicU64::new(0));
+    let output = LLM_SPACING_WAIT_MS.scope(waited.clone(), future).await;
+    (output, waited.load(Ordering::Relaxed))
+}
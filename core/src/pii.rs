@@ -0,0 +1,22 @@
+//! Patterns for personal data, shared by the `Guardrail` node checks and the
+//! redaction middleware so both recognise the same text
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Email addresses
+pub(crate) const EMAIL: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+
+/// Phone numbers, optionally with a country code
+pub(crate) const PHONE: &str =
+    r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\)[ .-]?|\b\d{3}[ .-])\d{3}[ .-]\d{4}\b";
+
+/// Credit card numbers of 13 to 19 digits, optionally grouped by spaces or dashes
+pub(crate) const CREDIT_CARD: &str = r"\b\d{4}(?:[ -]?\d{4}){2}[ -]?\d{1,7}\b";
+
+pub(crate) static EMAIL_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(EMAIL).unwrap());
+
+pub(crate) static PHONE_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(PHONE).unwrap());
+
+pub(crate) static CREDIT_CARD_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(CREDIT_CARD).unwrap());
@@ -0,0 +1,501 @@
+//! Post-processing of node outputs
+//!
+//! A node's `post_process` config key holds an ordered list of operations the
+//! executor applies to the node's output before storing it in the context, so
+//! small reshaping between nodes needs neither a transform node nor an LLM
+//! call:
+//!
+//! - `{"json_path": "$.summary"}` selects part of a JSON output; text outputs
+//!   are parsed first, recovering JSON from fenced or chatty model output
+//! - `{"regex_extract": {"pattern": "...", "group": 1}}` keeps a capture group
+//!   (a number or a name, the whole match by default) of the first match
+//! - `{"truncate": 2000}` keeps the first characters of the text
+//! - `"strip_markdown"` reduces Markdown to plain text
+//! - `"to_lowercase"` lowercases the text
+//! - `{"template": "Summary: {{output}}"}` renders a template in which
+//!   `{{output}}` is the value so far, next to the usual node and input
+//!   references
+//!
+//! Operations working on text turn JSON values into their JSON text first. An
+//! operation that cannot apply, such as a path or pattern that matches
+//! nothing, fails the node. When payloads are captured the output from before
+//! post-processing is kept in the node's debug capture as `raw`.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::json_repair::repair_json;
+
+/// Node config key holding the post-processing operations
+pub const POST_PROCESS_KEY: &str = "post_process";
+
+/// Placeholder for the value so far in a `template` operation
+pub const OUTPUT_PLACEHOLDER: &str = "{{output}}";
+
+/// Names of the operations, as written in a node's config
+pub const OPERATIONS: &[&str] = &[
+    "json_path",
+    "regex_extract",
+    "truncate",
+    "strip_markdown",
+    "to_lowercase",
+    "template",
+];
+
+/// One operation of a node's `post_process` list
+#[derive(Debug, Clone)]
+pub enum PostProcessor {
+    /// Select part of a JSON value
+    JsonPath(JsonPath),
+    /// Keep a capture group of the first match of a pattern
+    RegexExtract {
+        /// Pattern to search the text for
+        regex: Regex,
+        /// Capture group to keep
+        group: RegexGroup,
+    },
+    /// Keep at most this many characters
+    Truncate(usize),
+    /// Reduce Markdown to plain text
+    StripMarkdown,
+    /// Lowercase the text
+    ToLowercase,
+    /// Render a template around the value so far
+    Template(String),
+}
+
+/// Capture group kept by [`PostProcessor::RegexExtract`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexGroup {
+    /// Group by position, 0 being the whole match
+    Index(usize),
+    /// Named group
+    Name(String),
+}
+
+/// A parsed JSON path such as `$.items[0].title` or `$['key with spaces']`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    /// Array index, counted from the end when negative
+    Index(i64),
+}
+
+impl JsonPath {
+    /// Parse a path starting at the root `$`, followed by `.key`, `['key']`
+    /// and `[index]` segments
+    pub fn parse(path: &str) -> GraphBitResult<Self> {
+        let invalid = |message: &str| {
+            GraphBitError::validation("json_path", format!("Invalid path '{path}': {message}"))
+        };
+        let rest = path
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with '$'"))?;
+
+        let mut segments = Vec::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    let mut key = String::new();
+                    while let Some(&next) = chars.peek() {
+                        if next == '.' || next == '[' {
+                            break;
+                        }
+                        key.push(next);
+                        chars.next();
+                    }
+                    if key.is_empty() {
+                        return Err(invalid("empty key after '.'"));
+                    }
+                    segments.push(PathSegment::Key(key));
+                }
+                '[' => {
+                    let mut inner = String::new();
+                    let mut closed = false;
+                    let quote = chars.peek().copied().filter(|q| *q == '\'' || *q == '"');
+                    if let Some(quote) = quote {
+                        chars.next();
+                        for next in chars.by_ref() {
+                            if next == quote {
+                                closed = chars.next() == Some(']');
+                                break;
+                            }
+                            inner.push(next);
+                        }
+                        if !closed {
+                            return Err(invalid("unterminated quoted key"));
+                        }
+                        segments.push(PathSegment::Key(inner));
+                    } else {
+                        for next in chars.by_ref() {
+                            if next == ']' {
+                                closed = true;
+                                break;
+                            }
+                            inner.push(next);
+                        }
+                        if !closed {
+                            return Err(invalid("missing ']'"));
+                        }
+                        let index = inner
+                            .trim()
+                            .parse()
+                            .map_err(|_| invalid("expected an array index or a quoted key"))?;
+                        segments.push(PathSegment::Index(index));
+                    }
+                }
+                other => {
+                    return Err(invalid(&format!("unexpected '{other}'")));
+                }
+            }
+        }
+
+        Ok(Self {
+            source: path.trim().to_string(),
+            segments,
+        })
+    }
+
+    /// The path as written
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The value the path selects in `value`, if there is one
+    #[must_use]
+    pub fn select<'a>(&self, value: &'a serde_json::Value) -> Option<&'a serde_json::Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |value, segment| match segment {
+                PathSegment::Key(key) => value.get(key),
+                PathSegment::Index(index) => {
+                    let items = value.as_array()?;
+                    let index = if *index < 0 {
+                        items
+                            .len()
+                            .checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+                    } else {
+                        usize::try_from(*index).ok()?
+                    };
+                    items.get(index)
+                }
+            })
+    }
+}
+
+impl PostProcessor {
+    /// A `json_path` operation
+    pub fn json_path(path: &str) -> GraphBitResult<Self> {
+        JsonPath::parse(path).map(Self::JsonPath)
+    }
+
+    /// A `regex_extract` operation keeping capture group `group`
+    pub fn regex_extract(pattern: &str, group: RegexGroup) -> GraphBitResult<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            GraphBitError::validation("regex_extract", format!("Invalid pattern: {e}"))
+        })?;
+        let known = match &group {
+            RegexGroup::Index(index) => *index < regex.captures_len(),
+            RegexGroup::Name(name) => regex.capture_names().flatten().any(|n| n == name),
+        };
+        if !known {
+            return Err(GraphBitError::validation(
+                "regex_extract",
+                format!("Pattern '{pattern}' has no group {group:?}"),
+            ));
+        }
+        Ok(Self::RegexExtract { regex, group })
+    }
+
+    /// A `truncate` operation keeping at most `max_chars` characters
+    pub fn truncate(max_chars: usize) -> GraphBitResult<Self> {
+        if max_chars == 0 {
+            return Err(GraphBitError::validation(
+                "truncate",
+                "max_chars must be greater than 0",
+            ));
+        }
+        Ok(Self::Truncate(max_chars))
+    }
+
+    /// A `template` operation
+    #[must_use]
+    pub fn template(template: impl Into<String>) -> Self {
+        Self::Template(template.into())
+    }
+
+    /// Name of the operation, as written in a node's config
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::JsonPath(_) => "json_path",
+            Self::RegexExtract { .. } => "regex_extract",
+            Self::Truncate(_) => "truncate",
+            Self::StripMarkdown => "strip_markdown",
+            Self::ToLowercase => "to_lowercase",
+            Self::Template(_) => "template",
+        }
+    }
+
+    /// Parse one operation: the name of an operation without arguments, or
+    /// an object with the operation's name as its only key
+    pub fn from_value(value: &serde_json::Value) -> GraphBitResult<Self> {
+        let invalid = |message: String| GraphBitError::validation(POST_PROCESS_KEY, message);
+        let (name, argument) = match value {
+            serde_json::Value::String(name) => (name.as_str(), &serde_json::Value::Null),
+            serde_json::Value::Object(operation) if operation.len() == 1 => operation
+                .iter()
+                .next()
+                .map(|(name, argument)| (name.as_str(), argument))
+                .ok_or_else(|| invalid("empty operation".to_string()))?,
+            other => {
+                return Err(invalid(format!(
+                    "Expected an operation name or an object with one operation, got {other}"
+                )));
+            }
+        };
+        let text = |argument: &serde_json::Value| {
+            argument
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| invalid(format!("'{name}' expects a string, got {argument}")))
+        };
+
+        match name {
+            "json_path" => Self::json_path(&text(argument)?),
+            "regex_extract" => {
+                let (pattern, group) = match argument {
+                    serde_json::Value::Object(options) => (
+                        options.get("pattern").unwrap_or(&serde_json::Value::Null),
+                        options.get("group").unwrap_or(&serde_json::Value::Null),
+                    ),
+                    pattern => (pattern, &serde_json::Value::Null),
+                };
+                let group = match group {
+                    serde_json::Value::Null => RegexGroup::Index(0),
+                    serde_json::Value::String(name) => RegexGroup::Name(name.clone()),
+                    index => RegexGroup::Index(
+                        index
+                            .as_u64()
+                            .and_then(|index| usize::try_from(index).ok())
+                            .ok_or_else(|| {
+                                invalid(format!(
+                                    "'regex_extract' group must be a number or a name, got {index}"
+                                ))
+                            })?,
+                    ),
+                };
+                Self::regex_extract(&text(pattern)?, group)
+            }
+            "truncate" => Self::truncate(
+                argument
+                    .as_u64()
+                    .and_then(|max_chars| usize::try_from(max_chars).ok())
+                    .ok_or_else(|| {
+                        invalid(format!(
+                            "'truncate' expects a number of characters, got {argument}"
+                        ))
+                    })?,
+            ),
+            "strip_markdown" | "to_lowercase" => match argument {
+                serde_json::Value::Null | serde_json::Value::Bool(true) => {
+                    Ok(if name == "strip_markdown" {
+                        Self::StripMarkdown
+                    } else {
+                        Self::ToLowercase
+                    })
+                }
+                other => Err(invalid(format!("'{name}' takes no arguments, got {other}"))),
+            },
+            "template" => Ok(Self::template(text(argument)?)),
+            unknown => Err(invalid(format!(
+                "Unknown operation '{unknown}'; expected one of {}",
+                OPERATIONS.join(", ")
+            ))),
+        }
+    }
+
+    /// The operation as written in a node's config
+    #[must_use]
+    pub fn to_value(&self) -> serde_json::Value {
+        match self {
+            Self::JsonPath(path) => serde_json::json!({ "json_path": path.as_str() }),
+            Self::RegexExtract { regex, group } => {
+                let group = match group {
+                    RegexGroup::Index(index) => serde_json::json!(index),
+                    RegexGroup::Name(name) => serde_json::json!(name),
+                };
+                serde_json::json!({
+                    "regex_extract": { "pattern": regex.as_str(), "group": group }
+                })
+            }
+            Self::Truncate(max_chars) => serde_json::json!({ "truncate": max_chars }),
+            Self::StripMarkdown | Self::ToLowercase => serde_json::json!(self.name()),
+            Self::Template(template) => serde_json::json!({ "template": template }),
+        }
+    }
+
+    /// The operations of a node config's `post_process` list, empty when it
+    /// has none
+    pub fn from_node_config(
+        config: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<Vec<Self>> {
+        match config.get(POST_PROCESS_KEY) {
+            None | Some(serde_json::Value::Null) => Ok(Vec::new()),
+            Some(serde_json::Value::Array(operations)) => operations
+                .iter()
+                .enumerate()
+                .map(|(index, operation)| {
+                    Self::from_value(operation).map_err(|e| match e {
+                        GraphBitError::Validation { field, message } => GraphBitError::validation(
+                            field,
+                            format!("operation {index}: {message}"),
+                        ),
+                        other => other,
+                    })
+                })
+                .collect(),
+            Some(other) => Err(GraphBitError::validation(
+                POST_PROCESS_KEY,
+                format!("Expected a list of operations, got {other}"),
+            )),
+        }
+    }
+
+    /// Apply the operation to `value`. `resolve` renders the node and input
+    /// references of a template.
+    pub fn apply(
+        &self,
+        value: &serde_json::Value,
+        resolve: &dyn Fn(&str) -> String,
+    ) -> GraphBitResult<serde_json::Value> {
+        let failed = |message: String| GraphBitError::validation(self.name(), message);
+        match self {
+            Self::JsonPath(path) => {
+                let parsed;
+                let document = match value {
+                    serde_json::Value::String(text) => {
+                        parsed = repair_json(text)
+                            .ok_or_else(|| failed("the output is not JSON".to_string()))?
+                            .value;
+                        &parsed
+                    }
+                    value => value,
+                };
+                path.select(document)
+                    .cloned()
+                    .ok_or_else(|| failed(format!("'{}' matched nothing", path.as_str())))
+            }
+            Self::RegexExtract { regex, group } => {
+                let text = as_text(value);
+                let captures = regex
+                    .captures(&text)
+                    .ok_or_else(|| failed(format!("'{}' matched nothing", regex.as_str())))?;
+                let matched = match group {
+                    RegexGroup::Index(index) => captures.get(*index),
+                    RegexGroup::Name(name) => captures.name(name),
+                };
+                Ok(serde_json::Value::String(
+                    matched.map_or_else(String::new, |m| m.as_str().to_string()),
+                ))
+            }
+            Self::Truncate(max_chars) => Ok(serde_json::Value::String(
+                as_text(value).chars().take(*max_chars).collect(),
+            )),
+            Self::StripMarkdown => Ok(serde_json::Value::String(strip_markdown(&as_text(value)))),
+            Self::ToLowercase => Ok(serde_json::Value::String(as_text(value).to_lowercase())),
+            Self::Template(template) => Ok(serde_json::Value::String(
+                resolve(template).replace(OUTPUT_PLACEHOLDER, &as_text(value)),
+            )),
+        }
+    }
+}
+
+/// Apply `processors` in order to a node's output
+pub fn apply_all(
+    processors: &[PostProcessor],
+    output: serde_json::Value,
+    resolve: &dyn Fn(&str) -> String,
+) -> GraphBitResult<serde_json::Value> {
+    processors
+        .iter()
+        .try_fold(output, |value, processor| processor.apply(&value, resolve))
+}
+
+/// A value as text: strings as they are, anything else as JSON
+fn as_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+static HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s{0,3}#{1,6}\s+").expect("valid heading pattern"));
+static CLOSING_HASHES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s+#+\s*$").expect("valid closing hashes pattern"));
+static BLOCKQUOTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:>\s?)+").expect("valid blockquote pattern"));
+static BULLET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)[-*+]\s+").expect("valid bullet pattern"));
+static RULE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:[-*_]\s*){3,}$").expect("valid rule pattern"));
+static IMAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!\[([^\]]*)\]\([^)]*\)").expect("valid image pattern"));
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\([^)]*\)").expect("valid link pattern"));
+static EMPHASIS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\*\*([^*\n]+)\*\*|__([^_\n]+)__|~~([^~\n]+)~~|\*([^*\n]+)\*|\b_([^_\n]+)_\b")
+        .expect("valid emphasis pattern")
+});
+static INLINE_CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`([^`\n]+)`").expect("valid inline code pattern"));
+
+/// Markdown reduced to plain text: fences, heading and quote markers, list
+/// bullets, rules, emphasis and link targets are removed; code inside fences
+/// is kept as it is
+fn strip_markdown(text: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            lines.push(line.to_string());
+            continue;
+        }
+        if RULE.is_match(line) {
+            continue;
+        }
+
+        let line = BLOCKQUOTE.replace(line, "");
+        let line = if HEADING.is_match(&line) {
+            CLOSING_HASHES
+                .replace(&HEADING.replace(&line, ""), "")
+                .into_owned()
+        } else {
+            BULLET.replace(&line, "$1").into_owned()
+        };
+        let line = IMAGE.replace_all(&line, "$1");
+        let line = LINK.replace_all(&line, "$1");
+        let line = INLINE_CODE.replace_all(&line, "$1");
+        let line = EMPHASIS.replace_all(&line, "$1$2$3$4$5");
+        lines.push(line.into_owned());
+    }
+    lines.join("\n").trim().to_string()
+}
@@ -0,0 +1,149 @@
+//! Execution of `PythonCode` nodes in a Python subprocess
+//!
+//! The code runs in a separate interpreter with CPU time and memory limits, an
+//! allow-list of importable modules and, unless allowed, no network access.
+//! These limits guard against runaway and accidental misuse of generated code;
+//! they are not a security boundary. The interpreter runs as the same user as
+//! the executor, with its file system access, and the import allow-list is
+//! enforced inside the interpreter, where determined code can get around it.
+//! Executors therefore only run `PythonCode` nodes when built with
+//! `allow_python_code_nodes`, so an untrusted workflow file cannot run code
+//! silently.
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use serde::Deserialize;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Script that applies the limits and runs the code, see `runner.py`
+const RUNNER: &str = include_str!("runner.py");
+
+static PYTHON_EXECUTABLE: OnceLock<String> = OnceLock::new();
+
+/// Run `PythonCode` nodes with `executable`. The Python bindings set it to the
+/// interpreter that imported them; otherwise `python3` on the `PATH` is used.
+pub fn set_python_executable(executable: impl Into<String>) {
+    let _ = PYTHON_EXECUTABLE.set(executable.into());
+}
+
+fn python_executable() -> &'static str {
+    PYTHON_EXECUTABLE.get().map_or("python3", String::as_str)
+}
+
+/// What the code may do
+#[derive(Debug, Clone)]
+pub struct PythonCodeLimits {
+    /// Wall-clock and CPU time limit in seconds
+    pub timeout_seconds: u64,
+    /// Address space limit in MiB
+    pub memory_limit_mb: u64,
+    /// Top-level modules the code may import
+    pub allowed_imports: Vec<String>,
+    /// Whether the code may open network connections
+    pub allow_network: bool,
+}
+
+/// Line the runner writes after the code finishes
+#[derive(Deserialize)]
+struct RunnerResult {
+    stdout: String,
+    stderr: String,
+    value: serde_json::Value,
+    error: Option<String>,
+}
+
+/// Run `code` of the node `node_name` within `limits`.
+///
+/// Returns `{"stdout", "stderr", "value", "exit_status"}`, where `value` is the
+/// value of the code's trailing expression, if any, as JSON (or its `repr` when
+/// it is not JSON-serializable). Fails when the code raises, exceeds a limit or
+/// runs past its timeout, in which case the process is killed.
+pub async fn run_python_code(
+    node_name: &str,
+    code: &str,
+    limits: &PythonCodeLimits,
+) -> GraphBitResult<serde_json::Value> {
+    let spec = serde_json::json!({
+        "code": code,
+        "cpu_seconds": limits.timeout_seconds.max(1),
+        "memory_bytes": limits.memory_limit_mb.saturating_mul(1024 * 1024),
+        "allowed_imports": limits.allowed_imports,
+        "allow_network": limits.allow_network,
+    });
+
+    // The code sees none of the environment, which may hold API keys
+    let mut command = Command::new(python_executable());
+    command
+        .args(["-I", "-c", RUNNER])
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for name in ["PATH", "HOME", "PYENV_ROOT", "LANG"] {
+        if let Some(value) = std::env::var_os(name) {
+            command.env(name, value);
+        }
+    }
+    let mut child = command.spawn().map_err(|e| {
+        GraphBitError::workflow_execution(format!(
+            "Python code node '{node_name}' could not start interpreter '{}': {e}",
+            python_executable()
+        ))
+    })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(spec.to_string().as_bytes())
+            .await
+            .map_err(|e| {
+                GraphBitError::workflow_execution(format!(
+                    "Python code node '{node_name}' could not send its code: {e}"
+                ))
+            })?;
+    }
+
+    // Dropping the child on timeout kills it
+    let timeout = Duration::from_secs(limits.timeout_seconds);
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            GraphBitError::workflow_execution(format!(
+                "Python code node '{node_name}' timed out after {}s and was killed",
+                limits.timeout_seconds
+            ))
+        })?
+        .map_err(|e| {
+            GraphBitError::workflow_execution(format!("Python code node '{node_name}' failed: {e}"))
+        })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let result = stdout
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| serde_json::from_str::<RunnerResult>(line).ok());
+    let Some(result) = result else {
+        // Killed before reporting, e.g. for exceeding the CPU time limit
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GraphBitError::workflow_execution(format!(
+            "Python code node '{node_name}' exited with {}: {}",
+            output.status,
+            stderr.trim()
+        )));
+    };
+    if let Some(error) = result.error {
+        return Err(GraphBitError::workflow_execution(format!(
+            "Python code node '{node_name}' raised {error}\n{}",
+            result.stderr.trim_end()
+        )));
+    }
+    Ok(serde_json::json!({
+        "stdout": result.stdout,
+        "stderr": result.stderr,
+        "value": result.value,
+        "exit_status": output.status.code(),
+    }))
+}
@@ -0,0 +1,92 @@
+"""Run the code of a GraphBit PythonCode node.
+
+Reads a JSON spec from stdin, applies the node's resource limits and
+permissions, runs the code and writes one JSON line with the captured stdout,
+stderr, the value of a trailing expression and the error, if any.
+"""
+
+import ast
+import builtins
+import contextlib
+import io
+import json
+import resource
+import socket
+import sys
+import traceback
+
+CODE_MODULE = "__graphbit_code__"
+
+
+def limit(kind, value):
+    try:
+        resource.setrlimit(kind, (value, value))
+    except (ValueError, OSError):
+        pass
+
+
+def block_network():
+    def blocked(*args, **kwargs):
+        raise PermissionError("network access is disabled for this node")
+
+    for name in ("connect", "connect_ex", "bind", "sendto"):
+        setattr(socket.socket, name, blocked)
+    socket.getaddrinfo = blocked
+    socket.create_connection = blocked
+
+
+def guard_imports(allowed):
+    real_import = builtins.__import__
+
+    def guarded_import(name, globals=None, locals=None, fromlist=(), level=0):
+        # Only the node's own imports are checked; allowed modules import freely
+        if globals is not None and globals.get("__name__") == CODE_MODULE:
+            if level or name.partition(".")[0] not in allowed:
+                raise ImportError(f"import of '{name}' is not allowed")
+        return real_import(name, globals, locals, fromlist, level)
+
+    builtins.__import__ = guarded_import
+
+
+def run(code):
+    tree = ast.parse(code, "<node>", "exec")
+    last = tree.body.pop() if tree.body and isinstance(tree.body[-1], ast.Expr) else None
+    namespace = {"__name__": CODE_MODULE}
+    exec(compile(tree, "<node>", "exec"), namespace)
+    if last is None:
+        return None
+    expression = ast.Expression(last.value)
+    return eval(compile(expression, "<node>", "eval"), namespace)
+
+
+def main():
+    spec = json.loads(sys.stdin.read())
+    limit(resource.RLIMIT_CPU, spec["cpu_seconds"])
+    limit(resource.RLIMIT_AS, spec["memory_bytes"])
+    if not spec["allow_network"]:
+        block_network()
+    guard_imports(set(spec["allowed_imports"]))
+
+    stdout, stderr = io.StringIO(), io.StringIO()
+    value, error = None, None
+    with contextlib.redirect_stdout(stdout), contextlib.redirect_stderr(stderr):
+        try:
+            value = run(spec["code"])
+        except SystemExit as e:
+            if e.code not in (None, 0):
+                error = f"SystemExit: {e.code}"
+        except BaseException as e:
+            traceback.print_exc()
+            error = "".join(traceback.format_exception_only(type(e), e)).strip()
+
+    try:
+        value = json.loads(json.dumps(value))
+    except (TypeError, ValueError):
+        value = repr(value)
+    result = {"stdout": stdout.getvalue(), "stderr": stderr.getvalue(), "value": value, "error": error}
+    sys.__stdout__.write("\n" + json.dumps(result) + "\n")
+    sys.__stdout__.flush()
+    sys.exit(1 if error else 0)
+
+
+main()
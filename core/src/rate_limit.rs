@@ -0,0 +1,99 @@
+//! Request rate limiting
+//!
+//! A [`TokenBucket`] refills at a fixed rate up to its capacity; every request
+//! takes one token, waiting for the next one when the bucket is empty. The
+//! capacity is the burst allowed after an idle period.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::clock::Clock;
+
+/// Token bucket limiting requests to a rate per second
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate_per_second: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Bucket allowing `rate_per_second` requests, with bursts of up to
+    /// `capacity` requests; starts full
+    ///
+    /// Returns `None` unless the rate is a positive number.
+    #[must_use]
+    pub fn new(rate_per_second: f64, capacity: u32) -> Option<Self> {
+        if !(rate_per_second.is_finite() && rate_per_second > 0.0) {
+            return None;
+        }
+        let capacity = f64::from(capacity.max(1));
+        let clock = crate::clock::system();
+        Some(Self {
+            rate_per_second,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: clock.instant(),
+            }),
+            clock,
+        })
+    }
+
+    /// Refill the bucket and wait for tokens on `clock` instead of the system
+    /// clock; the bucket starts full again
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.state = Mutex::new(BucketState {
+            tokens: self.capacity,
+            refilled_at: clock.instant(),
+        });
+        self.clock = clock;
+        self
+    }
+
+    /// Requests allowed per second
+    #[must_use]
+    pub const fn rate_per_second(&self) -> f64 {
+        self.rate_per_second
+    }
+
+    /// Take a token without waiting; on an empty bucket, the time until the
+    /// next token
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = self.clock.instant();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = elapsed
+            .mul_add(self.rate_per_second, state.tokens)
+            .min(self.capacity);
+        state.refilled_at = now;
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - state.tokens) / self.rate_per_second,
+            ))
+        }
+    }
+
+    /// Take a token, waiting until one is available
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            self.clock.sleep(wait).await;
+        }
+    }
+}
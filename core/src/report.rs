@@ -0,0 +1,887 @@
+//! Execution reports
+//!
+//! [`WorkflowContext::to_report`] turns a finished run into an [`ExecutionReport`]:
+//! which nodes ran and in what order, with their prompts, outputs, tool calls,
+//! token usage, durations, retries and errors. The report serializes to a
+//! versioned JSON structure and renders to a single HTML file with a collapsible
+//! view per node, a Mermaid graph of the execution and a tab with the
+//! [transcript](crate::transcript) of the agent nodes.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+use crate::llm::LlmUsage;
+use crate::output_store::OutputRef;
+use crate::pacing::PacingDelays;
+use crate::transcript::{COLLAPSE_AFTER_CHARS, NodeTranscript};
+use crate::types::{
+    NodeDebugCapture, OutputValidationReport, ToolCallRecord, WorkflowContext,
+    WorkflowExecutionStats, WorkflowState,
+};
+use crate::usage::UsageCost;
+
+/// Version of the report structure, bumped on incompatible changes
+pub const REPORT_VERSION: u32 = 1;
+
+/// Controls redaction and truncation of the values captured in an [`ExecutionReport`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportConfig {
+    /// Object keys, matched case-insensitively, whose values are replaced with
+    /// [`ReportConfig::REDACTED`] wherever they occur in prompts, outputs and tool calls
+    pub redact_keys: Vec<String>,
+    /// Truncate strings longer than this many characters
+    pub max_value_chars: Option<usize>,
+    /// Include the rendered prompts and provider payloads captured for nodes
+    #[serde(default)]
+    pub include_payloads: bool,
+}
+
+impl ReportConfig {
+    /// Placeholder stored in place of redacted values
+    pub const REDACTED: &'static str = "[REDACTED]";
+
+    /// Default string length limit
+    pub const DEFAULT_MAX_VALUE_CHARS: usize = 10_000;
+
+    /// Keys redacted by default
+    pub const DEFAULT_REDACT_KEYS: &'static [&'static str] = &[
+        "api_key",
+        "apikey",
+        "x-api-key",
+        "authorization",
+        "password",
+        "secret",
+        "access_token",
+        "refresh_token",
+    ];
+
+    /// Also redact the values of `key`
+    #[must_use]
+    pub fn with_redact_key(mut self, key: impl Into<String>) -> Self {
+        self.redact_keys.push(key.into());
+        self
+    }
+
+    /// Truncate strings to at most `max_chars` characters, or keep them whole with `None`
+    #[must_use]
+    pub const fn with_max_value_chars(mut self, max_chars: Option<usize>) -> Self {
+        self.max_value_chars = max_chars;
+        self
+    }
+
+    /// Include the prompts and provider payloads captured with
+    /// [`WorkflowExecutor::with_capture_payloads`](crate::workflow::WorkflowExecutor::with_capture_payloads)
+    #[must_use]
+    pub const fn with_payloads(mut self, include: bool) -> Self {
+        self.include_payloads = include;
+        self
+    }
+
+    fn is_redacted_key(&self, key: &str) -> bool {
+        self.redact_keys
+            .iter()
+            .any(|redacted| redacted.eq_ignore_ascii_case(key))
+    }
+
+    pub(crate) fn capture_text(&self, text: &str) -> String {
+        let Some(max_chars) = self.max_value_chars else {
+            return text.to_string();
+        };
+        let total_chars = text.chars().count();
+        if total_chars <= max_chars {
+            return text.to_string();
+        }
+        let truncated: String = text.chars().take(max_chars).collect();
+        format!(
+            "{truncated}... [truncated {} chars]",
+            total_chars - max_chars
+        )
+    }
+
+    pub(crate) fn capture(&self, value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            // Spilled outputs keep their reference intact
+            _ if OutputRef::from_value(value).is_some() => value.clone(),
+            serde_json::Value::String(text) => serde_json::Value::String(self.capture_text(text)),
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(|item| self.capture(item)).collect())
+            }
+            serde_json::Value::Object(entries) => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, item)| {
+                        let item = if self.is_redacted_key(key) {
+                            serde_json::Value::String(Self::REDACTED.to_string())
+                        } else {
+                            self.capture(item)
+                        };
+                        (key.clone(), item)
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            redact_keys: Self::DEFAULT_REDACT_KEYS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            max_value_chars: Some(Self::DEFAULT_MAX_VALUE_CHARS),
+            include_payloads: false,
+        }
+    }
+}
+
+/// Report of one workflow execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    /// Version of the report structure, see [`REPORT_VERSION`]
+    pub report_version: u32,
+    /// ID of the executed workflow
+    pub workflow_id: String,
+    /// Final state: `pending`, `running`, `paused`, `completed`, `failed` or `cancelled`
+    pub status: String,
+    /// Error that failed the workflow
+    pub error: Option<String>,
+    /// When the execution started
+    pub started_at: DateTime<Utc>,
+    /// When the execution finished
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Total execution time in milliseconds
+    pub duration_ms: Option<u64>,
+    /// Execution statistics
+    pub stats: Option<WorkflowExecutionStats>,
+    /// Token usage summed over all nodes
+    pub token_usage: LlmUsage,
+    /// Executed nodes in the order they finished
+    pub nodes: Vec<NodeReport>,
+    /// Names of the workflow nodes that did not run, e.g. untaken branches
+    pub skipped_nodes: Vec<String>,
+    /// Edges of the workflow graph, by node name
+    pub edges: Vec<ReportEdge>,
+    /// Conversations of the agent nodes, in execution order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transcript: Vec<NodeTranscript>,
+}
+
+/// One executed node in an [`ExecutionReport`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeReport {
+    /// 1-based position in the execution order
+    pub sequence: usize,
+    /// 1-based scheduling step; nodes in the same step ran concurrently
+    pub step: usize,
+    /// Node ID
+    pub node_id: String,
+    /// Node name
+    pub node_name: String,
+    /// `succeeded` or `failed`
+    pub status: String,
+    /// When the node started
+    pub started_at: DateTime<Utc>,
+    /// Execution duration in milliseconds, retries included
+    pub duration_ms: u64,
+    /// Number of attempts, retries included
+    pub attempts: u32,
+    /// Error message if the node failed
+    pub error: Option<String>,
+    /// Model that answered, for agent nodes
+    pub model: Option<String>,
+    /// Prompt sent to the model, for agent nodes
+    pub prompt: Option<String>,
+    /// Node output
+    pub output: serde_json::Value,
+    /// Token usage of the node's LLM calls, for agent nodes
+    pub token_usage: Option<LlmUsage>,
+    /// Tool calls made by the node, in call order
+    pub tool_calls: Vec<ToolCallRecord>,
+    /// Output schema validation outcome, for nodes with an output schema
+    pub output_validation: Option<OutputValidationReport>,
+    /// Captured prompt and provider payloads, when the report includes them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<NodeDebugCapture>,
+    /// Time the node waited for pacing; the waits before and after the node
+    /// are not part of `duration_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pacing: Option<PacingDelays>,
+}
+
+/// Edge of the workflow graph in an [`ExecutionReport`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ReportEdge {
+    /// Name of the source node
+    pub from: String,
+    /// Name of the target node
+    pub to: String,
+}
+
+impl WorkflowContext {
+    /// Build an execution report with the default [`ReportConfig`]
+    #[must_use]
+    pub fn to_report(&self) -> ExecutionReport {
+        self.to_report_with(&ReportConfig::default())
+    }
+
+    /// Build an execution report, redacting and truncating captured values per `config`.
+    ///
+    /// Secret values still attached to the context are redacted as well.
+    #[must_use]
+    pub fn to_report_with(&self, config: &ReportConfig) -> ExecutionReport {
+        let capture = |value: &serde_json::Value| {
+            let mut value = config.capture(value);
+            self.secrets.redact_value(&mut value);
+            value
+        };
+        let capture_text = |text: &str| self.secrets.redact(&config.capture_text(text));
+
+        let (status, error) = match &self.state {
+            WorkflowState::Pending => ("pending", None),
+            WorkflowState::Running { .. } => ("running", None),
+            WorkflowState::Paused { reason, .. } => ("paused", Some(reason.as_str())),
+            WorkflowState::Completed => ("completed", None),
+            WorkflowState::Failed { error, .. } => ("failed", Some(error.as_str())),
+            WorkflowState::Cancelled => ("cancelled", None),
+            WorkflowState::PartiallyCompleted { .. } => ("partially_completed", None),
+        };
+
+        let mut token_usage = LlmUsage::empty();
+        let nodes: Vec<NodeReport> = self
+            .node_executions
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let response = self
+                    .metadata
+                    .get(&format!("node_response_{}", record.node_id));
+                let usage = response.and_then(node_token_usage);
+                if let Some(usage) = &usage {
+                    token_usage.add(usage);
+                }
+                NodeReport {
+                    sequence: index + 1,
+                    step: record.step,
+                    node_id: record.node_id.to_string(),
+                    node_name: record.node_name.clone(),
+                    status: if record.success {
+                        "succeeded"
+                    } else {
+                        "failed"
+                    }
+                    .to_string(),
+                    started_at: record.started_at,
+                    duration_ms: record.duration_ms,
+                    attempts: record.retry_count + 1,
+                    error: record
+                        .error
+                        .as_deref()
+                        .map(|error| self.secrets.redact(error)),
+                    model: response.and_then(node_model),
+                    prompt: response
+                        .and_then(|response| response.get("user_input"))
+                        .and_then(serde_json::Value::as_str)
+                        .map(capture_text),
+                    output: self
+                        .load_node_output(&record.node_id.to_string())
+                        .map_or(serde_json::Value::Null, |output| capture(&output)),
+                    token_usage: usage,
+                    tool_calls: self
+                        .get_tool_calls(&record.node_name)
+                        .iter()
+                        .map(|call| ToolCallRecord {
+                            arguments: capture(&call.arguments),
+                            output: capture(&call.output),
+                            ..call.clone()
+                        })
+                        .collect(),
+                    output_validation: self.get_output_validation(&record.node_name).cloned(),
+                    debug: config
+                        .include_payloads
+                        .then(|| self.get_node_debug(&record.node_name))
+                        .flatten()
+                        .map(|debug| capture_debug(debug, capture_text)),
+                    pacing: record.pacing,
+                }
+            })
+            .collect();
+
+        let names = self.node_names();
+        let executed: BTreeSet<&str> = nodes.iter().map(|node| node.node_name.as_str()).collect();
+        let skipped_nodes = names
+            .values()
+            .filter(|name| !executed.contains(name.as_str()))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        ExecutionReport {
+            report_version: REPORT_VERSION,
+            workflow_id: self.workflow_id.to_string(),
+            status: status.to_string(),
+            error: error.map(|error| self.secrets.redact(error)),
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            duration_ms: self.execution_duration_ms(),
+            stats: self.stats.clone(),
+            token_usage,
+            nodes,
+            skipped_nodes,
+            edges: self.report_edges(&names),
+            transcript: self.transcripts(config),
+        }
+    }
+
+    /// Node names keyed by node ID, as recorded at the start of the run
+    fn node_names(&self) -> HashMap<String, String> {
+        self.metadata
+            .get("node_id_to_name")
+            .and_then(serde_json::Value::as_object)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|(id, name)| Some((id.clone(), name.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Graph edges by node name, from the dependencies recorded at the start of the run
+    fn report_edges(&self, names: &HashMap<String, String>) -> Vec<ReportEdge> {
+        let Some(dependencies) = self
+            .metadata
+            .get("node_dependencies")
+            .and_then(serde_json::Value::as_object)
+        else {
+            return Vec::new();
+        };
+        let name = |id: &str| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+        let edges: BTreeSet<ReportEdge> = dependencies
+            .iter()
+            .flat_map(|(to, parents)| {
+                parents
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(move |from| (from, to))
+            })
+            .map(|(from, to)| ReportEdge {
+                from: name(from),
+                to: name(to),
+            })
+            .collect();
+        edges.into_iter().collect()
+    }
+}
+
+/// Token usage of a node summed over its LLM calls, falling back to the node total
+pub(crate) fn node_token_usage(response: &serde_json::Value) -> Option<LlmUsage> {
+    let calls: Vec<LlmUsage> = response
+        .get("executions")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|execution| {
+            execution.get("type").and_then(serde_json::Value::as_str) == Some("llm_call")
+        })
+        .filter_map(|execution| serde_json::from_value(execution.get("usage")?.clone()).ok())
+        .collect();
+    if calls.is_empty() {
+        return serde_json::from_value(response.get("total_usage")?.clone()).ok();
+    }
+    Some(
+        calls
+            .into_iter()
+            .fold(LlmUsage::empty(), |total, usage| total + usage),
+    )
+}
+
+/// Model that answered for a model router node, or of a node's first LLM call
+pub(crate) fn node_model(response: &serde_json::Value) -> Option<String> {
+    if let Some(model) = response
+        .pointer("/model_router/answered_by")
+        .and_then(serde_json::Value::as_str)
+    {
+        return Some(model.to_string());
+    }
+    response
+        .get("executions")?
+        .as_array()?
+        .iter()
+        .find_map(|execution| execution.get("model")?.as_str())
+        .map(str::to_string)
+}
+
+impl ExecutionReport {
+    /// Serialize the report as pretty-printed JSON
+    pub fn to_json(&self) -> crate::errors::GraphBitResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write the report to `path`: HTML for `.html` and `.htm` files, JSON otherwise
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> crate::errors::GraphBitResult<()> {
+        let path = path.as_ref();
+        let is_html = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm")
+            });
+        let contents = if is_html {
+            self.to_html()
+        } else {
+            self.to_json()?
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Mermaid flowchart of the workflow graph, with nodes styled by outcome
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let mut names: Vec<&str> = self
+            .nodes
+            .iter()
+            .map(|node| node.node_name.as_str())
+            .chain(self.skipped_nodes.iter().map(String::as_str))
+            .chain(
+                self.edges
+                    .iter()
+                    .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()]),
+            )
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let ids: HashMap<&str, String> = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (*name, format!("n{index}")))
+            .collect();
+
+        let mut mermaid = String::from("flowchart TD\n");
+        for name in &names {
+            let (class, label) = match self.nodes.iter().find(|node| node.node_name == *name) {
+                Some(node) if node.status == "succeeded" => {
+                    ("succeeded", format!("{}. {name}", node.sequence))
+                }
+                Some(node) => ("failed", format!("{}. {name}", node.sequence)),
+                None => ("skipped", (*name).to_string()),
+            };
+            let _ = writeln!(
+                mermaid,
+                "    {}[\"{}\"]:::{class}",
+                ids[name],
+                label.replace('"', "#quot;")
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                mermaid,
+                "    {} --> {}",
+                ids[edge.from.as_str()],
+                ids[edge.to.as_str()]
+            );
+        }
+        mermaid.push_str(
+            "    classDef succeeded fill:#dcfce7,stroke:#16a34a\n\
+             \x20   classDef failed fill:#fee2e2,stroke:#dc2626\n\
+             \x20   classDef skipped fill:#f3f4f6,stroke:#9ca3af,stroke-dasharray: 4 4\n",
+        );
+        mermaid
+    }
+
+    /// Render the report as a single HTML page.
+    ///
+    /// The page has no dependencies apart from the Mermaid script, loaded from a
+    /// CDN to draw the execution graph; offline, the graph is shown as Mermaid source.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Workflow run {id}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+             <h1>Workflow run <code>{id}</code></h1>\n",
+            id = escape_html(&self.workflow_id)
+        );
+
+        let _ = write!(
+            html,
+            "<table class=\"summary\">\n<tr><th>Status</th><td><span class=\"status {status}\">{status}</span></td></tr>\n\
+             <tr><th>Started</th><td>{started}</td></tr>\n<tr><th>Duration</th><td>{duration}</td></tr>\n\
+             <tr><th>Nodes</th><td>{executed} executed, {failed} failed, {skipped} skipped</td></tr>\n\
+             <tr><th>Tokens</th><td>{prompt} prompt + {completion} completion = {total}</td></tr>\n",
+            status = escape_html(&self.status),
+            started = self.started_at.to_rfc3339(),
+            duration = self
+                .duration_ms
+                .map_or_else(|| "-".to_string(), |ms| format!("{ms} ms")),
+            executed = self.nodes.len(),
+            failed = self
+                .nodes
+                .iter()
+                .filter(|node| node.status != "succeeded")
+                .count(),
+            skipped = self.skipped_nodes.len(),
+            prompt = self.token_usage.prompt_tokens,
+            completion = self.token_usage.completion_tokens,
+            total = self.token_usage.total_tokens,
+        );
+        if let Some(error) = &self.error {
+            let _ = writeln!(
+                html,
+                "<tr><th>Error</th><td><pre class=\"error\">{}</pre></td></tr>",
+                escape_html(error)
+            );
+        }
+        let usage = self.stats.as_ref().map(|stats| &stats.usage);
+        if let Some(cost_usd) = usage
+            .map(|usage| usage.total.cost_usd)
+            .filter(|cost_usd| *cost_usd > 0.0)
+        {
+            let _ = writeln!(html, "<tr><th>Cost</th><td>${cost_usd:.4}</td></tr>");
+        }
+        html.push_str("</table>\n");
+        if let Some(usage) = usage {
+            if !usage.by_tag.is_empty() || !usage.by_metadata.is_empty() {
+                html.push_str("<h2>Usage</h2>\n");
+                render_usage(&mut html, "Tag", &usage.by_tag);
+                for (key, by_value) in &usage.by_metadata {
+                    render_usage(&mut html, key, by_value);
+                }
+            }
+        }
+
+        let _ = write!(
+            html,
+            "<h2>Execution graph</h2>\n<pre class=\"mermaid\">\n{}</pre>\n\
+             <div class=\"tabs\">\n\
+             <input type=\"radio\" name=\"tab\" id=\"tab-nodes\" checked><label for=\"tab-nodes\">Nodes</label>\n\
+             <input type=\"radio\" name=\"tab\" id=\"tab-transcript\"><label for=\"tab-transcript\">Transcript</label>\n\
+             <section class=\"tab nodes\">\n",
+            escape_html(&self.to_mermaid())
+        );
+        for node in &self.nodes {
+            render_node(&mut html, node);
+        }
+        if !self.skipped_nodes.is_empty() {
+            let _ = writeln!(
+                html,
+                "<p class=\"skipped\">Skipped: {}</p>",
+                escape_html(&self.skipped_nodes.join(", "))
+            );
+        }
+        html.push_str("</section>\n<section class=\"tab transcript\">\n");
+        if self.transcript.is_empty() {
+            html.push_str("<p class=\"skipped\">No agent nodes ran.</p>\n");
+        }
+        for node in &self.transcript {
+            render_transcript(&mut html, node);
+        }
+        html.push_str("</section>\n</div>\n");
+
+        html.push_str(
+            "<script type=\"module\">\n\
+             import mermaid from \"https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs\";\n\
+             mermaid.initialize({ startOnLoad: true });\n\
+             </script>\n</body>\n</html>\n",
+        );
+        html
+    }
+}
+
+/// Render the usage attributed to the values of `label` as a table
+fn render_usage(html: &mut String, label: &str, usage: &BTreeMap<String, UsageCost>) {
+    let _ = writeln!(
+        html,
+        "<table class=\"usage\">\n<tr><th>{}</th><th>Prompt</th><th>Completion</th><th>Total</th><th>Cost</th></tr>",
+        escape_html(label)
+    );
+    for (value, usage) in usage {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>${:.4}</td></tr>",
+            escape_html(value),
+            usage.usage.prompt_tokens,
+            usage.usage.completion_tokens,
+            usage.usage.total_tokens,
+            usage.cost_usd
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+/// Render one node as a collapsible section
+fn render_node(html: &mut String, node: &NodeReport) {
+    let mut meta = format!(
+        "{} ms · {} attempt{}",
+        node.duration_ms,
+        node.attempts,
+        if node.attempts == 1 { "" } else { "s" }
+    );
+    if let Some(usage) = &node.token_usage {
+        let _ = write!(meta, " · {} tokens", usage.total_tokens);
+    }
+    if !node.tool_calls.is_empty() {
+        let _ = write!(meta, " · {} tool calls", node.tool_calls.len());
+    }
+    if let Some(pacing) = &node.pacing {
+        let waits: Vec<String> = [
+            (pacing.pre_execution_ms, "before"),
+            (pacing.post_execution_ms, "after"),
+            (pacing.llm_spacing_ms, "for LLM call spacing"),
+        ]
+        .into_iter()
+        .filter(|(ms, _)| *ms > 0)
+        .map(|(ms, when)| format!("{ms} ms {when}"))
+        .collect();
+        let _ = write!(meta, " · waited {}", waits.join(", "));
+    }
+    let _ = write!(
+        html,
+        "<details class=\"node {status}\"{open}>\n<summary><span class=\"seq\">{sequence}</span> \
+         <strong>{name}</strong> <span class=\"status {status}\">{status}</span> \
+         <span class=\"meta\">step {step} · {meta}</span></summary>\n",
+        status = escape_html(&node.status),
+        open = if node.status == "succeeded" {
+            ""
+        } else {
+            " open"
+        },
+        sequence = node.sequence,
+        name = escape_html(&node.node_name),
+        step = node.step,
+        meta = escape_html(&meta),
+    );
+    if let Some(error) = &node.error {
+        section(html, "Error", error);
+    }
+    if let Some(model) = &node.model {
+        section(html, "Model", model);
+    }
+    if let Some(prompt) = &node.prompt {
+        section(html, "Prompt", prompt);
+    }
+    section(html, "Output", &pretty(&node.output));
+    for call in &node.tool_calls {
+        section(
+            html,
+            &format!(
+                "Tool call: {} ({}, {:.0} ms, attempt {})",
+                call.tool_name,
+                if call.success { "ok" } else { "failed" },
+                call.duration_ms,
+                call.attempt
+            ),
+            &format!(
+                "arguments: {}\noutput: {}",
+                pretty(&call.arguments),
+                pretty(&call.output)
+            ),
+        );
+    }
+    if let Some(validation) = &node.output_validation {
+        section(
+            html,
+            "Output validation",
+            &serde_json::to_string_pretty(validation).unwrap_or_default(),
+        );
+    }
+    if let Some(debug) = &node.debug {
+        render_debug(html, debug);
+    }
+    html.push_str("</details>\n");
+}
+
+/// Render the conversation of one agent node
+fn render_transcript(html: &mut String, node: &NodeTranscript) {
+    let _ = writeln!(html, "<h3>{}</h3>", escape_html(&node.node_name));
+    if let Some(model) = &node.model {
+        section(html, "Model", model);
+    }
+    if let Some(system_prompt) = &node.system_prompt {
+        section(html, "System prompt", system_prompt);
+    }
+    if let Some(prompt) = &node.prompt {
+        section(html, "Prompt", prompt);
+    }
+    for iteration in &node.iterations {
+        if let Some(reply) = &iteration.reply {
+            section(html, &format!("Iteration {}", iteration.iteration), reply);
+        } else {
+            let _ = writeln!(html, "<h4>Iteration {}</h4>", iteration.iteration);
+        }
+        for call in &iteration.tool_calls {
+            section(
+                html,
+                &format!(
+                    "Tool call: {}{}",
+                    call.tool_name,
+                    if call.success { "" } else { " (failed)" }
+                ),
+                &pretty(&call.arguments),
+            );
+            let label = if call.success { "Result" } else { "Error" };
+            let chars = call.output.chars().count();
+            if chars > COLLAPSE_AFTER_CHARS {
+                let _ = write!(
+                    html,
+                    "<details class=\"debug\">\n<summary>{label} ({chars} characters)</summary>\n<pre>{}</pre>\n</details>\n",
+                    escape_html(&call.output)
+                );
+            } else {
+                section(html, label, &call.output);
+            }
+        }
+    }
+    if let Some(answer) = &node.answer {
+        section(html, "Final answer", answer);
+    }
+    if let Some(error) = &node.error {
+        section(html, "Error", error);
+    }
+}
+
+/// Render captured payloads as a section collapsed by default
+fn render_debug(html: &mut String, debug: &NodeDebugCapture) {
+    html.push_str("<details class=\"debug\">\n<summary>Debug payloads</summary>\n");
+    if let Some(system_prompt) = &debug.system_prompt {
+        section(html, "Rendered system prompt", system_prompt);
+    }
+    if let Some(prompt) = &debug.rendered_prompt {
+        section(html, "Rendered prompt", prompt);
+    }
+    for (index, exchange) in debug.exchanges.iter().enumerate() {
+        let mut headers = String::new();
+        for (name, value) in &exchange.request_headers {
+            let _ = writeln!(headers, "{name}: {value}");
+        }
+        section(
+            html,
+            &format!(
+                "Request {}: {} {} ({})",
+                index + 1,
+                exchange.method,
+                exchange.url,
+                exchange.provider
+            ),
+            &format!("{headers}\n{}", exchange.request_body),
+        );
+        let (title, body) = match (&exchange.response_body, &exchange.error) {
+            (Some(body), _) => (
+                format!(
+                    "Response {}: {}",
+                    index + 1,
+                    exchange.status.unwrap_or_default()
+                ),
+                body.as_str(),
+            ),
+            (None, Some(error)) => (format!("Request {} failed", index + 1), error.as_str()),
+            (None, None) => continue,
+        };
+        section(html, &title, body);
+    }
+    for history in &debug.histories {
+        section(
+            html,
+            &format!(
+                "Conversation sent in iteration {} ({} exchanges left out, {} summarized)",
+                history.iteration, history.dropped_exchanges, history.summarized_exchanges
+            ),
+            &history.messages.join("\n"),
+        );
+    }
+    html.push_str("</details>\n");
+}
+
+/// `debug` with its texts truncated and redacted by `capture_text`
+fn capture_debug(
+    debug: &NodeDebugCapture,
+    capture_text: impl Fn(&str) -> String,
+) -> NodeDebugCapture {
+    let mut debug = debug.clone();
+    for text in [&mut debug.system_prompt, &mut debug.rendered_prompt]
+        .into_iter()
+        .flatten()
+    {
+        *text = capture_text(text);
+    }
+    for exchange in &mut debug.exchanges {
+        exchange.request_body = capture_text(&exchange.request_body);
+        if let Some(body) = &mut exchange.response_body {
+            *body = capture_text(body);
+        }
+    }
+    for message in debug
+        .histories
+        .iter_mut()
+        .flat_map(|history| &mut history.messages)
+    {
+        *message = capture_text(message);
+    }
+    debug
+}
+
+fn section(html: &mut String, title: &str, body: &str) {
+    let _ = write!(
+        html,
+        "<h4>{}</h4>\n<pre>{}</pre>\n",
+        escape_html(title),
+        escape_html(body)
+    );
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:1100px;padding:0 1rem;color:#111827}\
+table.summary{border-collapse:collapse;margin-bottom:1.5rem}\
+table.summary th{text-align:left;padding:.25rem 1rem .25rem 0;color:#6b7280;font-weight:500}\
+table.usage{border-collapse:collapse;margin-bottom:1rem}\
+table.usage th,table.usage td{text-align:right;padding:.25rem .75rem;border-bottom:1px solid #e5e7eb}\
+table.usage th:first-child,table.usage td:first-child{text-align:left}\
+pre{background:#f9fafb;border:1px solid #e5e7eb;border-radius:4px;padding:.5rem;white-space:pre-wrap;word-break:break-word}\
+pre.mermaid{background:none;border:none}\
+details.node{border:1px solid #e5e7eb;border-radius:6px;margin:.5rem 0;padding:.5rem .75rem}\
+details.node.failed{border-color:#fca5a5}\
+details.debug{margin-top:.5rem;color:#374151}\
+summary{cursor:pointer}\
+.seq{display:inline-block;min-width:1.5rem;color:#6b7280}\
+.status{border-radius:4px;padding:0 .4rem;font-size:.85em}\
+.status.succeeded,.status.completed{background:#dcfce7;color:#166534}\
+.status.failed{background:#fee2e2;color:#991b1b}\
+.meta{color:#6b7280;font-size:.85em;margin-left:.5rem}\
+.skipped{color:#6b7280}\
+.tabs>input{display:none}\
+.tabs>label{display:inline-block;padding:.4rem .9rem;margin-right:.25rem;border:1px solid #e5e7eb;border-bottom:none;border-radius:6px 6px 0 0;cursor:pointer;color:#6b7280}\
+.tabs>input:checked+label{color:#111827;font-weight:600;background:#f9fafb}\
+.tab{display:none;border-top:1px solid #e5e7eb;padding-top:.5rem}\
+#tab-nodes:checked~.nodes,#tab-transcript:checked~.transcript{display:block}\
+h4{margin:.75rem 0 .25rem}";
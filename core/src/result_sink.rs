@@ -0,0 +1,298 @@
+//! Durable JSON lines of execution results
+//!
+//! A [`ResultSink`] appends one [`ResultRecord`] per line to a file as results
+//! complete: per finished node when it is attached to a run with
+//! [`WorkflowContext::with_result_sink`], plus one for the workflow when a
+//! [shutdown](crate::shutdown) cancels the run, and per finished workflow when
+//! it is passed to [`WorkflowExecutor::execute_batch`](crate::workflow::WorkflowExecutor::execute_batch).
+//! Each line is written with a single append and synced to disk before the
+//! write returns, so the results of a long run survive the process crashing.
+//!
+//! A crash during a write can leave the last line incomplete. The sink cuts
+//! such a line off when it reopens the file, and [`read_results`] skips it.
+//! A resumed execution keeps its execution id, so the nodes it repeats are
+//! deduplicated by [`read_results`], which keeps the last record of each node
+//! of each execution.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::errors::GraphBitResult;
+use crate::llm::LlmUsage;
+use crate::types::{NodeExecutionRecord, WorkflowContext, WorkflowState};
+
+/// One completed node or workflow, as written to a [`ResultSink`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultRecord {
+    /// Execution the result belongs to
+    pub execution_id: String,
+    /// Position of the execution's inputs in a batch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// ID of the node, for node results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    /// Name of the node, for node results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_name: Option<String>,
+    /// Whether the node or workflow succeeded
+    pub success: bool,
+    /// Output of the node, or the outputs of a workflow's nodes keyed by name
+    pub output: serde_json::Value,
+    /// Error the node or workflow failed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Tokens used by the node's LLM calls, or by all nodes of a workflow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<LlmUsage>,
+    /// When the result was recorded
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl ResultRecord {
+    /// Result of the node `record` describes, with its output and usage read
+    /// from `context` and secrets redacted
+    #[must_use]
+    pub fn for_node(context: &WorkflowContext, record: &NodeExecutionRecord) -> Self {
+        let mut output = context
+            .load_node_output(&record.node_id.to_string())
+            .map_or(serde_json::Value::Null, std::borrow::Cow::into_owned);
+        context.secrets.redact_value(&mut output);
+        Self {
+            execution_id: context.execution_id.to_string(),
+            index: None,
+            node_id: Some(record.node_id.to_string()),
+            node_name: Some(record.node_name.clone()),
+            success: record.success,
+            output,
+            error: record
+                .error
+                .as_deref()
+                .map(|error| context.secrets.redact(error)),
+            usage: node_usage(context, record),
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Result of the workflow run in `context`: the outputs of its executed
+    /// nodes keyed by name and the tokens they used, with secrets redacted
+    #[must_use]
+    pub fn for_workflow(context: &WorkflowContext) -> Self {
+        let mut outputs = serde_json::Map::new();
+        let mut usage: Option<LlmUsage> = None;
+        for record in &context.node_executions {
+            if let Some(output) = context.load_node_output(&record.node_id.to_string()) {
+                outputs.insert(record.node_name.clone(), output.into_owned());
+            }
+            if let Some(node_usage) = node_usage(context, record) {
+                usage.get_or_insert_with(LlmUsage::empty).add(&node_usage);
+            }
+        }
+        let mut output = serde_json::Value::Object(outputs);
+        context.secrets.redact_value(&mut output);
+        let error = match &context.state {
+            WorkflowState::Failed { error, .. } => Some(context.secrets.redact(error)),
+            WorkflowState::Cancelled => Some("Execution cancelled".to_string()),
+            _ => None,
+        };
+        Self {
+            execution_id: context.execution_id.to_string(),
+            index: None,
+            node_id: None,
+            node_name: None,
+            success: error.is_none(),
+            output,
+            error,
+            usage,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Result of an execution that failed before it produced a context
+    #[must_use]
+    pub fn for_error(execution_id: uuid::Uuid, error: impl Into<String>) -> Self {
+        Self {
+            execution_id: execution_id.to_string(),
+            index: None,
+            node_id: None,
+            node_name: None,
+            success: false,
+            output: serde_json::Value::Null,
+            error: Some(error.into()),
+            usage: None,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Set the position of the execution's inputs in a batch
+    #[must_use]
+    pub const fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+}
+
+/// Tokens used by the LLM calls of the node `record` describes
+fn node_usage(context: &WorkflowContext, record: &NodeExecutionRecord) -> Option<LlmUsage> {
+    context
+        .metadata
+        .get(&format!("node_response_{}", record.node_id))
+        .and_then(crate::report::node_token_usage)
+}
+
+/// Open file and its size
+type OpenFile = (tokio::fs::File, u64);
+
+/// Appends [`ResultRecord`]s as JSON lines to a file, rotating it by size.
+///
+/// Every line is synced to disk before [`append`](Self::append) returns. When
+/// a line would take the file past the size limit, `results.jsonl` is renamed
+/// to the first free name of `results.jsonl.1`, `results.jsonl.2` and so on,
+/// so rotated files are never renamed again, and a new file is started.
+#[derive(Debug)]
+pub struct ResultSink {
+    path: PathBuf,
+    max_bytes: Option<u64>,
+    file: tokio::sync::Mutex<Option<OpenFile>>,
+}
+
+impl ResultSink {
+    /// Append results to the file at `path`, without rotation
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: None,
+            file: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Rotate the file once it would grow past `max_bytes`
+    #[must_use]
+    pub const fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Path of the file results are appended to
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `record` as one line and sync it to disk
+    pub async fn append(&self, record: &ResultRecord) -> GraphBitResult<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let line_len = line.len() as u64;
+
+        let mut file = self.file.lock().await;
+        if file.is_none() {
+            *file = Some(open_repaired(&self.path).await?);
+        }
+        if let (Some(max_bytes), Some((_, size))) = (self.max_bytes, file.as_ref()) {
+            if *size > 0 && size + line_len > max_bytes {
+                *file = None;
+                tokio::fs::rename(&self.path, next_rotated_path(&self.path).await?).await?;
+                *file = Some(open_repaired(&self.path).await?);
+            }
+        }
+        let Some((handle, size)) = file.as_mut() else {
+            unreachable!("the file was opened above");
+        };
+        handle.write_all(line.as_bytes()).await?;
+        handle.sync_data().await?;
+        *size += line_len;
+        drop(file);
+        Ok(())
+    }
+}
+
+/// Path of the `index`th rotated file of `path`
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.to_path_buf().into_os_string();
+    rotated.push(format!(".{index}"));
+    rotated.into()
+}
+
+/// First rotated file name of `path` not taken yet
+async fn next_rotated_path(path: &Path) -> GraphBitResult<PathBuf> {
+    let mut index = 1;
+    while tokio::fs::try_exists(rotated_path(path, index)).await? {
+        index += 1;
+    }
+    Ok(rotated_path(path, index))
+}
+
+/// Open `path` for appending, cutting off an incomplete last line left by a
+/// crash so the next line starts on its own
+async fn open_repaired(path: &Path) -> GraphBitResult<OpenFile> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .await?;
+    let mut size = file.metadata().await?.len();
+    if size > 0 {
+        let mut contents = Vec::with_capacity(usize::try_from(size).unwrap_or_default());
+        file.seek(std::io::SeekFrom::Start(0)).await?;
+        file.read_to_end(&mut contents).await?;
+        if contents.last() != Some(&b'\n') {
+            let complete = contents
+                .iter()
+                .rposition(|byte| *byte == b'\n')
+                .map_or(0, |newline| newline + 1);
+            tracing::warn!(
+                path = %path.display(),
+                "Dropping an incomplete result line left by an interrupted write"
+            );
+            size = complete as u64;
+            file.set_len(size).await?;
+            file.sync_data().await?;
+        }
+    }
+    Ok((file, size))
+}
+
+/// Records of the result file at `path` and its rotated files, oldest first.
+///
+/// Only the last record of each node of each execution, and of each
+/// execution's workflow result, is kept. Incomplete lines are skipped.
+pub async fn read_results(path: impl AsRef<Path>) -> GraphBitResult<Vec<ResultRecord>> {
+    let path = path.as_ref();
+    let mut files = Vec::new();
+    let mut index = 1;
+    while tokio::fs::try_exists(rotated_path(path, index)).await? {
+        files.push(rotated_path(path, index));
+        index += 1;
+    }
+    files.push(path.to_path_buf());
+
+    let mut records: Vec<ResultRecord> = Vec::new();
+    let mut positions: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for file in files {
+        let contents = match tokio::fs::read_to_string(&file).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for line in contents.split_terminator('\n') {
+            let Ok(record) = serde_json::from_str::<ResultRecord>(line) else {
+                continue;
+            };
+            let key = (record.execution_id.clone(), record.node_id.clone());
+            if let Some(&position) = positions.get(&key) {
+                records[position] = record;
+            } else {
+                positions.insert(key, records.len());
+                records.push(record);
+            }
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,524 @@
+//! Persistent history of workflow executions
+//!
+//! A [`RunHistory`] keeps one [`RunRecord`] per execution in a SQLite
+//! database. An executor configured with
+//! [`WorkflowExecutor::with_run_history`](crate::workflow::WorkflowExecutor::with_run_history)
+//! records each execution when it starts and again when it finishes, so a
+//! service can list recent runs, including those of earlier processes, and
+//! re-open them. With a report directory, the finished run is archived there
+//! as [`WorkflowContext::to_json`] and can be loaded again with
+//! [`WorkflowContext::from_json`].
+//!
+//! Records are queried with a [`RunQuery`] and removed with a
+//! [`RunRetention`], either on demand or after every finished execution.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::types::{WorkflowContext, WorkflowExecutionStats, WorkflowState};
+use crate::usage::UsageSummary;
+use crate::workflow::Workflow;
+
+/// Workflow metadata key holding the workflow version recorded in the history
+pub const WORKFLOW_VERSION_KEY: &str = "version";
+
+/// Outcome of a recorded execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// The execution has started and not finished yet
+    Running,
+    /// Every node completed
+    Completed,
+    /// Some nodes failed without failing the workflow
+    PartiallyCompleted,
+    /// The execution failed
+    Failed,
+    /// The execution was cancelled
+    Cancelled,
+}
+
+impl RunState {
+    /// Name of the state as stored and serialized
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::PartiallyCompleted => "partially_completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// State named `name`, as returned by [`Self::as_str`]
+    #[must_use]
+    pub fn from_str_opt(name: &str) -> Option<Self> {
+        match name {
+            "running" => Some(Self::Running),
+            "completed" => Some(Self::Completed),
+            "partially_completed" => Some(Self::PartiallyCompleted),
+            "failed" => Some(Self::Failed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+impl From<&WorkflowState> for RunState {
+    fn from(state: &WorkflowState) -> Self {
+        match state {
+            WorkflowState::Pending
+            | WorkflowState::Running { .. }
+            | WorkflowState::Paused { .. } => Self::Running,
+            WorkflowState::Completed => Self::Completed,
+            WorkflowState::PartiallyCompleted { .. } => Self::PartiallyCompleted,
+            WorkflowState::Failed { .. } => Self::Failed,
+            WorkflowState::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+/// One execution in a [`RunHistory`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Execution ID
+    pub execution_id: String,
+    /// ID of the executed workflow
+    pub workflow_id: String,
+    /// Name of the executed workflow
+    pub workflow_name: String,
+    /// Version of the workflow, from its [`WORKFLOW_VERSION_KEY`] metadata
+    pub workflow_version: Option<String>,
+    /// Current state
+    pub state: RunState,
+    /// Error that failed the execution
+    pub error: Option<String>,
+    /// When the execution started
+    pub started_at: DateTime<Utc>,
+    /// When the execution finished
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Execution statistics, once the execution has finished
+    pub stats: Option<WorkflowExecutionStats>,
+    /// File the finished run was archived to with [`WorkflowContext::to_json`]
+    pub report_path: Option<PathBuf>,
+}
+
+impl RunRecord {
+    /// Record of `workflow` starting to run in `context`
+    #[must_use]
+    pub fn started(workflow: &Workflow, context: &WorkflowContext) -> Self {
+        let workflow_version =
+            workflow
+                .metadata
+                .get(WORKFLOW_VERSION_KEY)
+                .map(|version| match version {
+                    serde_json::Value::String(version) => version.clone(),
+                    version => version.to_string(),
+                });
+        Self {
+            execution_id: context.execution_id.to_string(),
+            workflow_id: workflow.id.to_string(),
+            workflow_name: workflow.name.clone(),
+            workflow_version,
+            state: RunState::Running,
+            error: None,
+            started_at: context.started_at,
+            completed_at: None,
+            stats: None,
+            report_path: None,
+        }
+    }
+
+    /// Update the record with the outcome of the execution
+    pub fn finish(&mut self, result: &GraphBitResult<WorkflowContext>) {
+        match result {
+            Ok(context) => {
+                self.state = RunState::from(&context.state);
+                self.error = match &context.state {
+                    WorkflowState::Failed { error, .. } => Some(context.secrets.redact(error)),
+                    _ => None,
+                };
+                self.completed_at = Some(context.completed_at.unwrap_or_else(Utc::now));
+                self.stats.clone_from(&context.stats);
+            }
+            Err(e) => {
+                self.state = RunState::Failed;
+                self.error = Some(e.to_string());
+                self.completed_at = Some(Utc::now());
+            }
+        }
+    }
+}
+
+/// Filter of [`RunHistory::list`]; runs are returned newest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunQuery {
+    /// Only runs of the workflow with this name
+    pub workflow_name: Option<String>,
+    /// Only runs in this state
+    pub state: Option<RunState>,
+    /// Only runs started at or after this time
+    pub started_after: Option<DateTime<Utc>>,
+    /// Only runs started before this time
+    pub started_before: Option<DateTime<Utc>>,
+    /// Maximum number of runs returned
+    pub limit: Option<usize>,
+}
+
+impl RunQuery {
+    /// Query matching every run
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only runs of the workflow named `name`
+    #[must_use]
+    pub fn with_workflow_name(mut self, name: impl Into<String>) -> Self {
+        self.workflow_name = Some(name.into());
+        self
+    }
+
+    /// Only runs in `state`
+    #[must_use]
+    pub const fn with_state(mut self, state: RunState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Only runs started in `[from, to)`; either bound may be left open
+    #[must_use]
+    pub const fn started_between(
+        mut self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.started_after = from;
+        self.started_before = to;
+        self
+    }
+
+    /// Return at most `limit` runs
+    #[must_use]
+    pub const fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Which runs [`RunHistory::prune`] keeps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunRetention {
+    /// Remove runs started longer ago than this
+    pub max_age: Option<Duration>,
+    /// Keep only this many of the most recently started runs
+    pub max_runs: Option<usize>,
+}
+
+/// SQLite store of [`RunRecord`]s
+///
+/// Cloning is cheap; clones share the connection.
+#[derive(Clone)]
+pub struct RunHistory {
+    conn: Arc<Mutex<rusqlite::Connection>>,
+    report_dir: Option<PathBuf>,
+    retention: Option<RunRetention>,
+}
+
+impl std::fmt::Debug for RunHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunHistory")
+            .field("report_dir", &self.report_dir)
+            .field("retention", &self.retention)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RunHistory {
+    /// Open (or create) the history database at `path`
+    pub fn open(path: impl AsRef<Path>) -> GraphBitResult<Self> {
+        Self::with_connection(rusqlite::Connection::open(path)?)
+    }
+
+    /// History kept in memory for the lifetime of the value and its clones
+    pub fn in_memory() -> GraphBitResult<Self> {
+        Self::with_connection(rusqlite::Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: rusqlite::Connection) -> GraphBitResult<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                execution_id     TEXT PRIMARY KEY,
+                workflow_id      TEXT NOT NULL,
+                workflow_name    TEXT NOT NULL,
+                workflow_version TEXT,
+                state            TEXT NOT NULL,
+                error            TEXT,
+                started_at       TEXT NOT NULL,
+                completed_at     TEXT,
+                stats            TEXT,
+                report_path      TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_runs_started_at    ON runs(started_at);
+            CREATE INDEX IF NOT EXISTS idx_runs_workflow_name ON runs(workflow_name);",
+        )?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            report_dir: None,
+            retention: None,
+        })
+    }
+
+    /// Archive every finished run as `<execution id>.json` in `dir`, which is
+    /// created when needed
+    #[must_use]
+    pub fn with_report_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.report_dir = Some(dir.into());
+        self
+    }
+
+    /// Prune the history with `retention` after every finished execution
+    #[must_use]
+    pub const fn with_retention(mut self, retention: RunRetention) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Directory finished runs are archived to
+    #[must_use]
+    pub fn report_dir(&self) -> Option<&Path> {
+        self.report_dir.as_deref()
+    }
+
+    /// Run `query` on the connection outside the async runtime
+    async fn with_conn<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&rusqlite::Connection) -> GraphBitResult<T> + Send + 'static,
+    ) -> GraphBitResult<T> {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || query(&conn.blocking_lock()))
+            .await
+            .map_err(|e| GraphBitError::Internal {
+                message: format!("Run history task failed: {e}"),
+            })?
+    }
+
+    /// Insert `record`, replacing the record of the same execution
+    pub async fn record(&self, record: &RunRecord) -> GraphBitResult<()> {
+        let stats = record
+            .stats
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let record = record.clone();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO runs (execution_id, workflow_id, workflow_name, workflow_version, state, error, started_at, completed_at, stats, report_path)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                rusqlite::params![
+                    record.execution_id,
+                    record.workflow_id,
+                    record.workflow_name,
+                    record.workflow_version,
+                    record.state.as_str(),
+                    record.error,
+                    timestamp(record.started_at),
+                    record.completed_at.map(timestamp),
+                    stats,
+                    record
+                        .report_path
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().into_owned()),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Record the outcome of the execution `record` describes, archiving the
+    /// run when a report directory is set and applying the retention
+    pub async fn record_finished(
+        &self,
+        mut record: RunRecord,
+        result: &GraphBitResult<WorkflowContext>,
+    ) -> GraphBitResult<RunRecord> {
+        record.finish(result);
+        if let (Some(dir), Ok(context)) = (&self.report_dir, result) {
+            tokio::fs::create_dir_all(dir).await?;
+            let path = dir.join(format!("{}.json", record.execution_id));
+            tokio::fs::write(&path, context.to_json(None)?).await?;
+            record.report_path = Some(path);
+        }
+        self.record(&record).await?;
+        if let Some(retention) = self.retention {
+            self.prune(retention).await?;
+        }
+        Ok(record)
+    }
+
+    /// Record of the execution `execution_id`
+    pub async fn get(&self, execution_id: &str) -> GraphBitResult<Option<RunRecord>> {
+        let execution_id = execution_id.to_string();
+        self.with_conn(move |conn| {
+            let mut statement = conn.prepare(&format!(
+                "SELECT {COLUMNS} FROM runs WHERE execution_id = ?1"
+            ))?;
+            let mut rows = statement.query(rusqlite::params![execution_id])?;
+            rows.next()?.map(row_to_record).transpose()
+        })
+        .await
+    }
+
+    /// Runs matching `query`, most recently started first
+    pub async fn list(&self, query: &RunQuery) -> GraphBitResult<Vec<RunRecord>> {
+        let mut conditions = Vec::new();
+        let mut params: Vec<String> = Vec::new();
+        if let Some(name) = &query.workflow_name {
+            params.push(name.clone());
+            conditions.push(format!("workflow_name = ?{}", params.len()));
+        }
+        if let Some(state) = query.state {
+            params.push(state.as_str().to_string());
+            conditions.push(format!("state = ?{}", params.len()));
+        }
+        if let Some(after) = query.started_after {
+            params.push(timestamp(after));
+            conditions.push(format!("started_at >= ?{}", params.len()));
+        }
+        if let Some(before) = query.started_before {
+            params.push(timestamp(before));
+            conditions.push(format!("started_at < ?{}", params.len()));
+        }
+        let mut sql = format!("SELECT {COLUMNS} FROM runs");
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY started_at DESC, rowid DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(" LIMIT ");
+            sql.push_str(&limit.to_string());
+        }
+
+        self.with_conn(move |conn| {
+            let mut statement = conn.prepare(&sql)?;
+            let mut rows = statement.query(rusqlite::params_from_iter(params))?;
+            let mut records = Vec::new();
+            while let Some(row) = rows.next()? {
+                records.push(row_to_record(row)?);
+            }
+            Ok(records)
+        })
+        .await
+    }
+
+    /// Usage of the runs started at or after `since`, added up by node tag and
+    /// by attributed workflow metadata value
+    pub async fn usage_summary(&self, since: DateTime<Utc>) -> GraphBitResult<UsageSummary> {
+        let runs = self
+            .list(&RunQuery::new().started_between(Some(since), None))
+            .await?;
+        Ok(UsageSummary::from_runs(since, &runs))
+    }
+
+    /// The `limit` most recently started runs
+    pub async fn recent(&self, limit: usize) -> GraphBitResult<Vec<RunRecord>> {
+        self.list(&RunQuery::new().with_limit(limit)).await
+    }
+
+    /// Remove the runs `retention` does not keep, and their archived reports,
+    /// returning the number of runs removed
+    pub async fn prune(&self, retention: RunRetention) -> GraphBitResult<usize> {
+        let cutoff = retention
+            .max_age
+            .and_then(|max_age| chrono::Duration::from_std(max_age).ok())
+            .map(|max_age| timestamp(Utc::now() - max_age));
+        let max_runs = retention.max_runs;
+        let removed = self
+            .with_conn(move |conn| {
+                let mut statement =
+                    conn.prepare("SELECT execution_id, started_at, report_path FROM runs ORDER BY started_at DESC, rowid DESC")?;
+                let runs = statement
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                let removed: Vec<(String, Option<String>)> = runs
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(position, (_, started_at, _))| {
+                        max_runs.is_some_and(|max_runs| *position >= max_runs)
+                            || cutoff.as_ref().is_some_and(|cutoff| started_at < cutoff)
+                    })
+                    .map(|(_, (execution_id, _, report_path))| (execution_id, report_path))
+                    .collect();
+                let mut delete = conn.prepare("DELETE FROM runs WHERE execution_id = ?1")?;
+                for (execution_id, _) in &removed {
+                    delete.execute(rusqlite::params![execution_id])?;
+                }
+                Ok(removed)
+            })
+            .await?;
+
+        for report_path in removed.iter().filter_map(|(_, path)| path.as_ref()) {
+            if let Err(e) = tokio::fs::remove_file(report_path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to remove archived run {report_path}: {e}");
+                }
+            }
+        }
+        Ok(removed.len())
+    }
+}
+
+/// Columns read by [`row_to_record`], in order
+const COLUMNS: &str = "execution_id, workflow_id, workflow_name, workflow_version, state, error, started_at, completed_at, stats, report_path";
+
+/// Fixed-width UTC timestamp, so stored times sort as text
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_timestamp(text: &str) -> GraphBitResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| GraphBitError::Internal {
+            message: format!("Invalid timestamp '{text}' in run history: {e}"),
+        })
+}
+
+fn row_to_record(row: &rusqlite::Row<'_>) -> GraphBitResult<RunRecord> {
+    let state: String = row.get(4)?;
+    let started_at: String = row.get(6)?;
+    let completed_at: Option<String> = row.get(7)?;
+    let stats_json: Option<String> = row.get(8)?;
+    Ok(RunRecord {
+        execution_id: row.get(0)?,
+        workflow_id: row.get(1)?,
+        workflow_name: row.get(2)?,
+        workflow_version: row.get(3)?,
+        state: RunState::from_str_opt(&state).ok_or_else(|| GraphBitError::Internal {
+            message: format!("Invalid run state '{state}' in run history"),
+        })?,
+        error: row.get(5)?,
+        started_at: parse_timestamp(&started_at)?,
+        completed_at: completed_at.as_deref().map(parse_timestamp).transpose()?,
+        stats: stats_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()?,
+        report_path: row.get::<_, Option<String>>(9)?.map(PathBuf::from),
+    })
+}
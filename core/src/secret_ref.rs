@@ -0,0 +1,312 @@
+//! References to provider API keys
+//!
+//! The `api_key` of an [`LlmConfig`](crate::llm::LlmConfig) or
+//! [`EmbeddingConfig`](crate::embeddings::EmbeddingConfig) is either the key
+//! itself or a reference to where the key is kept:
+//!
+//! - `env:OPENAI_API_KEY` reads an environment variable
+//! - `file:/run/secrets/openai` reads a file, without surrounding whitespace
+//! - `keyring:service/user` reads the system keyring, through `security` on
+//!   macOS and `secret-tool` elsewhere
+//!
+//! References are resolved when a provider is created from the configuration,
+//! so the configuration, and any workflow or context serialized with it,
+//! only ever holds the reference. Every key resolved this way, literal or not,
+//! is remembered and replaced with [`REDACTED`] by [`redact`], which provider
+//! errors and the `Debug` output of configurations go through.
+//!
+//! Keys read from the keyring are reused for [`KEYRING_CACHE_TTL`] instead of
+//! running the keyring tool for every provider created. After rotating a key,
+//! [`forget_api_key`] drops the cached and remembered key of a setting.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{LazyLock, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Placeholder shown in place of a key
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Keys shorter than this are not redacted, so short placeholder keys do not
+/// mangle unrelated text
+const MIN_REDACTED_LEN: usize = 12;
+
+/// How long a key read from the keyring is reused before the keyring is read
+/// again
+pub const KEYRING_CACHE_TTL: Duration = Duration::from_secs(300);
+
+static RESOLVED_KEYS: LazyLock<RwLock<Vec<String>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Keys read from the keyring by service and user, with when they were read
+type KeyringCache = HashMap<(String, String), (String, Instant)>;
+
+static KEYRING_CACHE: LazyLock<Mutex<KeyringCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Where an API key is kept
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    /// The key itself
+    Literal(String),
+    /// An environment variable holding the key
+    Env(String),
+    /// A file holding the key
+    File(PathBuf),
+    /// An entry of the system keyring
+    Keyring {
+        /// Service the key is stored under
+        service: String,
+        /// Account the key is stored under
+        user: String,
+    },
+}
+
+impl SecretRef {
+    /// Parse an `api_key` setting; anything without a reference prefix is a
+    /// literal key
+    pub fn parse(value: &str) -> GraphBitResult<Self> {
+        let invalid = |message: &str| {
+            GraphBitError::config(format!("Invalid API key reference '{value}': {message}"))
+        };
+        if let Some(name) = value.strip_prefix("env:") {
+            if name.trim().is_empty() {
+                return Err(invalid("missing the variable name"));
+            }
+            Ok(Self::Env(name.trim().to_string()))
+        } else if let Some(path) = value.strip_prefix("file:") {
+            if path.trim().is_empty() {
+                return Err(invalid("missing the file path"));
+            }
+            Ok(Self::File(PathBuf::from(path.trim())))
+        } else if let Some(entry) = value.strip_prefix("keyring:") {
+            match entry.split_once('/') {
+                Some((service, user)) if !service.is_empty() && !user.is_empty() => {
+                    Ok(Self::Keyring {
+                        service: service.to_string(),
+                        user: user.to_string(),
+                    })
+                }
+                _ => Err(invalid("expected 'keyring:service/user'")),
+            }
+        } else {
+            Ok(Self::Literal(value.to_string()))
+        }
+    }
+
+    /// Whether this refers to a key kept elsewhere
+    #[must_use]
+    pub const fn is_reference(&self) -> bool {
+        !matches!(self, Self::Literal(_))
+    }
+
+    /// Read the key, remembering it for [`redact`]
+    pub fn resolve(&self) -> GraphBitResult<String> {
+        let key = self.read()?;
+        remember(&key);
+        Ok(key)
+    }
+
+    fn read(&self) -> GraphBitResult<String> {
+        Ok(match self {
+            Self::Literal(key) => key.clone(),
+            Self::Env(name) => std::env::var(name)
+                .ok()
+                .filter(|key| !key.trim().is_empty())
+                .ok_or_else(|| {
+                    GraphBitError::config(format!(
+                        "API key environment variable '{name}' is not set"
+                    ))
+                })?
+                .trim()
+                .to_string(),
+            Self::File(path) => {
+                let key = std::fs::read_to_string(path).map_err(|e| {
+                    GraphBitError::config(format!(
+                        "Cannot read API key file '{}': {e}",
+                        path.display()
+                    ))
+                })?;
+                if key.trim().is_empty() {
+                    return Err(GraphBitError::config(format!(
+                        "API key file '{}' is empty",
+                        path.display()
+                    )));
+                }
+                key.trim().to_string()
+            }
+            Self::Keyring { service, user } => read_keyring(service, user)?,
+        })
+    }
+}
+
+/// Resolve an `api_key` setting, see [`SecretRef`]
+pub fn resolve_api_key(value: &str) -> GraphBitResult<String> {
+    SecretRef::parse(value)?.resolve()
+}
+
+impl fmt::Display for SecretRef {
+    /// The reference as written, or [`REDACTED`] for a literal key
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(_) => f.write_str(REDACTED),
+            Self::Env(name) => write!(f, "env:{name}"),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Keyring { service, user } => write!(f, "keyring:{service}/{user}"),
+        }
+    }
+}
+
+impl fmt::Debug for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretRef({self})")
+    }
+}
+
+/// An `api_key` setting as it may be shown: references as written, literal
+/// keys as [`REDACTED`]
+#[must_use]
+pub fn display_api_key(value: &str) -> String {
+    SecretRef::parse(value).map_or_else(|_| REDACTED.to_string(), |secret| secret.to_string())
+}
+
+/// Forget the key an `api_key` setting resolved to, e.g. after rotating it
+///
+/// A keyring entry is read again by the next resolution instead of being
+/// taken from the cache, and the key is no longer redacted unless it is
+/// resolved again.
+pub fn forget_api_key(value: &str) {
+    let key = match SecretRef::parse(value) {
+        Ok(SecretRef::Literal(key)) => Some(key),
+        Ok(SecretRef::Keyring { service, user }) => KEYRING_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(service, user))
+            .map(|(key, _)| key),
+        // Environment variables and files are read on every resolution
+        Ok(secret) => secret.read().ok(),
+        Err(_) => None,
+    };
+    if let Some(key) = key {
+        RESOLVED_KEYS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|known| *known != key);
+    }
+}
+
+/// Replace every key resolved so far that occurs in `text` with [`REDACTED`]
+#[must_use]
+pub fn redact(text: &str) -> String {
+    RESOLVED_KEYS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .fold(text.to_string(), |redacted, key| {
+            if redacted.contains(key.as_str()) {
+                redacted.replace(key.as_str(), REDACTED)
+            } else {
+                redacted
+            }
+        })
+}
+
+/// Replace the `api_key` settings inside a serialized configuration with how
+/// they may be shown, see [`display_api_key`]
+pub(crate) fn redact_config_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(entries) => {
+            for (key, item) in entries.iter_mut() {
+                match item {
+                    serde_json::Value::String(text) if key == "api_key" => {
+                        *text = display_api_key(text);
+                    }
+                    _ => redact_config_value(item),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_config_value),
+        _ => {}
+    }
+}
+
+/// Write a configuration for `Debug` as its serialized form, with its keys
+/// redacted
+pub(crate) fn debug_config(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    config: &impl serde::Serialize,
+) -> fmt::Result {
+    let mut value = serde_json::to_value(config).map_err(|_| fmt::Error)?;
+    redact_config_value(&mut value);
+    write!(f, "{name} {value}")
+}
+
+fn remember(key: &str) {
+    if key.len() < MIN_REDACTED_LEN {
+        return;
+    }
+    let mut keys = RESOLVED_KEYS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    if !keys.iter().any(|known| known == key) {
+        keys.push(key.to_string());
+        // Longer keys first, so a key containing another is redacted whole
+        keys.sort_by_key(|key| std::cmp::Reverse(key.len()));
+    }
+}
+
+/// Key of a keyring entry, from the cache while it is fresh
+fn read_keyring(service: &str, user: &str) -> GraphBitResult<String> {
+    let entry = (service.to_string(), user.to_string());
+    if let Some((key, read_at)) = KEYRING_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&entry)
+    {
+        if read_at.elapsed() < KEYRING_CACHE_TTL {
+            return Ok(key.clone());
+        }
+    }
+    // Providers are created inside async tasks, so keep the keyring tool from
+    // stalling the other tasks of the worker thread while it runs
+    let key = match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| query_keyring(service, user))
+        }
+        _ => query_keyring(service, user),
+    }?;
+    KEYRING_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(entry, (key.clone(), Instant::now()));
+    Ok(key)
+}
+
+/// Run the system keyring tool for an entry
+fn query_keyring(service: &str, user: &str) -> GraphBitResult<String> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", service, "-a", user, "-w"]);
+        command
+    } else {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service, "user", user]);
+        command
+    };
+    let failed = |message: String| {
+        GraphBitError::config(format!(
+            "Cannot read API key 'keyring:{service}/{user}': {message}"
+        ))
+    };
+    let output = command
+        .output()
+        .map_err(|e| failed(format!("the keyring tool is unavailable ({e})")))?;
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || key.is_empty() {
+        return Err(failed("no such entry".to_string()));
+    }
+    Ok(key)
+}
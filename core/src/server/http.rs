@@ -0,0 +1,609 @@
+//! HTTP front end of the execution service
+//!
+//! Routes are served with axum over hyper's HTTP/1.1 connections. A
+//! [`ServerConfig`] bounds how long a client may take to send the headers and
+//! the body of a request and how large the body may be, and can require a
+//! bearer token on every request.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Json;
+use axum::Router;
+use axum::body::{Body, Bytes};
+use axum::extract::{DefaultBodyLimit, Path, Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode, Uri, header};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use chrono::{DateTime, Utc};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use hyper_util::rt::{TokioIo, TokioTimer};
+use hyper_util::service::TowerToHyperService;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::{ExecutionEvents, ExecutionService};
+use crate::run_history::{RunQuery, RunState};
+use crate::shutdown::ShutdownSummary;
+use crate::stream::StreamMode;
+use crate::workflow::Workflow;
+
+/// Limit on the request line and headers
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Runs listed by `GET /executions` without a `limit`
+const DEFAULT_RUN_LIMIT: usize = 20;
+/// Media types of workflows submitted as YAML
+const YAML_MEDIA_TYPES: [&str; 4] = [
+    "application/yaml",
+    "application/x-yaml",
+    "text/yaml",
+    "text/x-yaml",
+];
+
+/// Limits and authentication of the HTTP front end
+#[derive(Clone)]
+pub struct ServerConfig {
+    /// Time a client has to send the request line and headers, 10 seconds by
+    /// default; the connection is closed when it runs out
+    pub header_read_timeout: Duration,
+    /// Time a client has to send the request body once the headers are read,
+    /// 30 seconds by default; the request fails with status 408 when it runs out
+    pub body_read_timeout: Duration,
+    /// Largest request body, 16 MiB by default; larger ones fail with status 413
+    pub max_body_bytes: usize,
+    /// Token every request must carry as `Authorization: Bearer <token>`;
+    /// requests are not authenticated when unset
+    pub auth_token: Option<String>,
+}
+
+impl ServerConfig {
+    /// Configuration with the default limits and no authentication
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time a client has to send the request line and headers
+    #[must_use]
+    pub const fn with_header_read_timeout(mut self, timeout: Duration) -> Self {
+        self.header_read_timeout = timeout;
+        self
+    }
+
+    /// Set the time a client has to send the request body
+    #[must_use]
+    pub const fn with_body_read_timeout(mut self, timeout: Duration) -> Self {
+        self.body_read_timeout = timeout;
+        self
+    }
+
+    /// Set the largest request body
+    #[must_use]
+    pub const fn with_max_body_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_body_bytes = max_bytes;
+        self
+    }
+
+    /// Require `token` as a bearer token on every request
+    #[must_use]
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            header_read_timeout: Duration::from_secs(10),
+            body_read_timeout: Duration::from_secs(30),
+            max_body_bytes: 16 * 1024 * 1024,
+            auth_token: None,
+        }
+    }
+}
+
+impl fmt::Debug for ServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerConfig")
+            .field("header_read_timeout", &self.header_read_timeout)
+            .field("body_read_timeout", &self.body_read_timeout)
+            .field("max_body_bytes", &self.max_body_bytes)
+            .field(
+                "auth_token",
+                &self
+                    .auth_token
+                    .as_ref()
+                    .map(|_| crate::secret_ref::REDACTED),
+            )
+            .finish()
+    }
+}
+
+/// Pause before accepting again after running out of file descriptors, so
+/// connections being closed can free some
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Serve `service` over HTTP on `listener` with the default [`ServerConfig`]
+/// until the returned future is dropped
+pub async fn serve(listener: TcpListener, service: ExecutionService) {
+    serve_with_config(listener, service, ServerConfig::default()).await;
+}
+
+/// Serve `service` over HTTP on `listener` with `config` until the returned
+/// future is dropped
+///
+/// A failed accept is logged and the server keeps accepting connections.
+pub async fn serve_with_config(
+    listener: TcpListener,
+    service: ExecutionService,
+    config: ServerConfig,
+) {
+    accept_loop(listener, service, config).await;
+}
+
+/// Serve `service` over HTTP on `listener` with `config` until `signal`
+/// completes, then stop accepting connections and
+/// [shut the service down](ExecutionService::shutdown) with `grace_period`
+pub async fn serve_with_shutdown(
+    listener: TcpListener,
+    service: ExecutionService,
+    config: ServerConfig,
+    signal: impl Future<Output = ()>,
+    grace_period: Duration,
+) -> ShutdownSummary {
+    tokio::select! {
+        () = serve_with_config(listener, service.clone(), config) => {}
+        () = signal => {}
+    }
+    tracing::info!("Execution service stopped accepting connections; shutting down");
+    service.shutdown(grace_period).await
+}
+
+/// Serve `service` over HTTP on `listener` with `config` until the process
+/// receives SIGTERM or Ctrl-C, see [`serve_with_shutdown`]
+pub async fn serve_until_terminated(
+    listener: TcpListener,
+    service: ExecutionService,
+    config: ServerConfig,
+    grace_period: Duration,
+) -> std::io::Result<ShutdownSummary> {
+    Ok(serve_with_shutdown(listener, service, config, terminated()?, grace_period).await)
+}
+
+/// Future completing when the process receives SIGTERM or Ctrl-C
+#[cfg(unix)]
+fn terminated() -> std::io::Result<impl Future<Output = ()>> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut sigterm = signal(SignalKind::terminate())?;
+    Ok(async move {
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    })
+}
+
+/// Future completing when the process receives Ctrl-C
+#[cfg(not(unix))]
+fn terminated() -> std::io::Result<impl Future<Output = ()>> {
+    Ok(async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+}
+
+/// Source of client connections for [`accept_loop`]
+trait Accept {
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+
+    fn accept(&self) -> impl Future<Output = std::io::Result<(Self::Stream, SocketAddr)>> + Send;
+}
+
+impl Accept for TcpListener {
+    type Stream = TcpStream;
+
+    fn accept(&self) -> impl Future<Output = std::io::Result<(Self::Stream, SocketAddr)>> + Send {
+        // Resolves to the inherent `TcpListener::accept`
+        Self::accept(self)
+    }
+}
+
+/// Serve every connection `listener` accepts; never returns
+async fn accept_loop(listener: impl Accept, service: ExecutionService, config: ServerConfig) {
+    let mut http = hyper::server::conn::http1::Builder::new();
+    http.timer(TokioTimer::new())
+        .header_read_timeout(config.header_read_timeout)
+        .max_buf_size(MAX_HEAD_BYTES);
+    let app = router(service, config);
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tracing::warn!("Execution service failed to accept a connection: {e}");
+                if is_fd_exhaustion(&e) {
+                    tokio::time::sleep(ACCEPT_BACKOFF).await;
+                }
+                continue;
+            }
+        };
+        let connection =
+            http.serve_connection(TokioIo::new(stream), TowerToHyperService::new(app.clone()));
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::debug!("Execution service connection from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+/// Whether `error` means the process or the system ran out of file descriptors
+#[cfg(unix)]
+fn is_fd_exhaustion(error: &std::io::Error) -> bool {
+    matches!(error.raw_os_error(), Some(libc::EMFILE | libc::ENFILE))
+}
+
+/// Whether `error` means the process ran out of sockets
+#[cfg(not(unix))]
+fn is_fd_exhaustion(error: &std::io::Error) -> bool {
+    // WSAEMFILE
+    error.raw_os_error() == Some(10024)
+}
+
+fn router(service: ExecutionService, config: ServerConfig) -> Router {
+    Router::new()
+        .route("/workflows", post(submit_workflow))
+        .route("/executions", post(start_execution).get(list_runs))
+        .route("/executions/{id}", get(execution))
+        .route(
+            "/executions/{id}/events",
+            get(execution_events).post(deliver_event),
+        )
+        .fallback(no_route)
+        .method_not_allowed_fallback(method_not_allowed)
+        // `guard` enforces the configured limit while reading the body
+        .layer(DefaultBodyLimit::disable())
+        .layer(middleware::from_fn_with_state(Arc::new(config), guard))
+        .with_state(service)
+}
+
+fn error(status: StatusCode, message: impl fmt::Display) -> Response {
+    (status, Json(json!({ "error": message.to_string() }))).into_response()
+}
+
+/// Check the bearer token, then read the body within the configured time and
+/// size limits
+async fn guard(State(config): State<Arc<ServerConfig>>, request: Request, next: Next) -> Response {
+    if let Some(token) = &config.auth_token {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if !presented.is_some_and(|presented| tokens_match(presented, token)) {
+            let mut response = error(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token");
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            return response;
+        }
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = Limited::new(body, config.max_body_bytes).collect();
+    let body = match tokio::time::timeout(config.body_read_timeout, body).await {
+        Ok(Ok(body)) => body.to_bytes(),
+        Ok(Err(e)) if e.is::<LengthLimitError>() => {
+            return error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Request body exceeds {} bytes", config.max_body_bytes),
+            );
+        }
+        Ok(Err(e)) => return error(StatusCode::BAD_REQUEST, format!("Failed to read body: {e}")),
+        Err(_) => {
+            // The rest of the body may still arrive, so the connection cannot be reused
+            let mut response = error(
+                StatusCode::REQUEST_TIMEOUT,
+                format!(
+                    "Request body was not received within {}s",
+                    config.body_read_timeout.as_secs_f64()
+                ),
+            );
+            response
+                .headers_mut()
+                .insert(header::CONNECTION, HeaderValue::from_static("close"));
+            return response;
+        }
+    };
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+/// Compare tokens in a time that does not depend on where they differ
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+async fn no_route(uri: Uri) -> Response {
+    error(
+        StatusCode::NOT_FOUND,
+        format!("No route for {}", uri.path()),
+    )
+}
+
+async fn method_not_allowed(method: Method) -> Response {
+    error(
+        StatusCode::METHOD_NOT_ALLOWED,
+        format!("Method {method} is not allowed"),
+    )
+}
+
+/// Body of `POST /executions`
+#[derive(Deserialize)]
+struct ExecutionRequest {
+    workflow_id: String,
+    #[serde(default)]
+    inputs: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    stream_mode: Option<String>,
+}
+
+/// Body of `POST /executions/{id}/events`
+#[derive(Deserialize)]
+struct EventDelivery {
+    name: String,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+/// Whether the request body is declared to be YAML
+fn is_yaml(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| {
+            YAML_MEDIA_TYPES
+                .iter()
+                .any(|yaml| media_type.trim().eq_ignore_ascii_case(yaml))
+        })
+}
+
+async fn submit_workflow(
+    State(service): State<ExecutionService>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Ok(text) = std::str::from_utf8(&body) else {
+        return error(StatusCode::BAD_REQUEST, "Workflow must be UTF-8");
+    };
+    let workflow = if is_yaml(&headers) {
+        Workflow::from_yaml(text)
+    } else {
+        Workflow::from_json(text)
+    };
+    let workflow = match workflow {
+        Ok(workflow) => workflow,
+        Err(e) => return error(StatusCode::BAD_REQUEST, format!("Invalid workflow: {e}")),
+    };
+    let name = workflow.name.clone();
+    let node_count = workflow.graph.node_count();
+    match service.submit_workflow(workflow).await {
+        Ok(id) => (
+            StatusCode::CREATED,
+            Json(json!({ "id": id, "name": name, "node_count": node_count })),
+        )
+            .into_response(),
+        Err(e) => error(StatusCode::BAD_REQUEST, format!("Invalid workflow: {e}")),
+    }
+}
+
+async fn execution(State(service): State<ExecutionService>, Path(id): Path<String>) -> Response {
+    service.execution(&id).await.map_or_else(
+        || error(StatusCode::NOT_FOUND, format!("Unknown execution '{id}'")),
+        |snapshot| Json(json!(snapshot)).into_response(),
+    )
+}
+
+async fn execution_events(
+    State(service): State<ExecutionService>,
+    Path(id): Path<String>,
+) -> Response {
+    service.events(&id).await.map_or_else(
+        || error(StatusCode::NOT_FOUND, format!("Unknown execution '{id}'")),
+        |events| Sse::new(event_stream(events)).into_response(),
+    )
+}
+
+/// Server-sent events of an execution, ending when the execution finishes
+fn event_stream(events: ExecutionEvents) -> impl futures::Stream<Item = Result<Event, Infallible>> {
+    futures::stream::unfold(events, |mut events| async move {
+        let event = events.next().await?;
+        let data = serde_json::to_value(&event)
+            .unwrap_or_else(|e| json!({ "event": "error", "error": e.to_string() }));
+        let name = data["event"].as_str().unwrap_or("message").to_string();
+        Some((
+            Ok(Event::default().event(name).data(data.to_string())),
+            events,
+        ))
+    })
+}
+
+async fn deliver_event(
+    State(service): State<ExecutionService>,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Response {
+    let delivery: EventDelivery = match serde_json::from_slice(&body) {
+        Ok(delivery) => delivery,
+        Err(e) => return error(StatusCode::BAD_REQUEST, format!("Invalid event: {e}")),
+    };
+    match service
+        .deliver_event(&id, &delivery.name, delivery.payload)
+        .await
+    {
+        Some(Ok(())) => (
+            StatusCode::ACCEPTED,
+            Json(json!({ "id": id, "event": delivery.name })),
+        )
+            .into_response(),
+        Some(Err(e)) => error(StatusCode::CONFLICT, e),
+        None => error(StatusCode::NOT_FOUND, format!("Unknown execution '{id}'")),
+    }
+}
+
+async fn start_execution(State(service): State<ExecutionService>, body: Bytes) -> Response {
+    let request: ExecutionRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid execution request: {e}"),
+            );
+        }
+    };
+    let stream_mode = match request.stream_mode.as_deref() {
+        None => StreamMode::Updates,
+        Some(mode) => match StreamMode::from_str_opt(mode) {
+            Some(mode) => mode,
+            None => {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid stream_mode '{mode}', expected updates, messages or all"),
+                );
+            }
+        },
+    };
+    match service
+        .execute_stored(&request.workflow_id, request.inputs, stream_mode)
+        .await
+    {
+        Some(id) => (
+            StatusCode::ACCEPTED,
+            Json(json!({ "id": id, "status": "running" })),
+        )
+            .into_response(),
+        None => error(
+            StatusCode::NOT_FOUND,
+            format!("Unknown workflow '{}'", request.workflow_id),
+        ),
+    }
+}
+
+async fn list_runs(
+    State(service): State<ExecutionService>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let mut filter = RunQuery::new().with_limit(DEFAULT_RUN_LIMIT);
+    if let Some(name) = query.get("workflow_name") {
+        filter = filter.with_workflow_name(name.clone());
+    }
+    if let Some(state) = query.get("state") {
+        let Some(state) = RunState::from_str_opt(state) else {
+            return error(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid state '{state}', expected running, completed, partially_completed, \
+                     failed or cancelled"
+                ),
+            );
+        };
+        filter = filter.with_state(state);
+    }
+    for (parameter, bound) in [
+        ("since", &mut filter.started_after),
+        ("until", &mut filter.started_before),
+    ] {
+        if let Some(time) = query.get(parameter) {
+            match DateTime::parse_from_rfc3339(time) {
+                Ok(time) => *bound = Some(time.with_timezone(&Utc)),
+                Err(e) => {
+                    return error(
+                        StatusCode::BAD_REQUEST,
+                        format!("Invalid {parameter} '{time}': {e}"),
+                    );
+                }
+            }
+        }
+    }
+    if let Some(limit) = query.get("limit") {
+        let Ok(limit) = limit.parse() else {
+            return error(StatusCode::BAD_REQUEST, format!("Invalid limit '{limit}'"));
+        };
+        filter = filter.with_limit(limit);
+    }
+    match service.runs(&filter).await {
+        Ok(runs) => Json(json!({ "runs": runs })).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow::WorkflowExecutor;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Listener whose accepts fail with `failures` before it accepts for real
+    struct FlakyListener {
+        inner: TcpListener,
+        failures: Mutex<Vec<std::io::Error>>,
+    }
+
+    impl Accept for FlakyListener {
+        type Stream = TcpStream;
+
+        fn accept(
+            &self,
+        ) -> impl Future<Output = std::io::Result<(Self::Stream, SocketAddr)>> + Send {
+            let failure = self.failures.lock().unwrap().pop();
+            async move {
+                match failure {
+                    Some(e) => Err(e),
+                    None => self.inner.accept().await,
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_keeps_serving_after_accept_failures() {
+        let inner = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = inner.local_addr().unwrap();
+        let listener = FlakyListener {
+            inner,
+            failures: Mutex::new(vec![
+                std::io::Error::from(std::io::ErrorKind::ConnectionAborted),
+                #[cfg(unix)]
+                std::io::Error::from_raw_os_error(libc::EMFILE),
+            ]),
+        };
+        let service = ExecutionService::new(WorkflowExecutor::new());
+        let server = tokio::spawn(accept_loop(listener, service, ServerConfig::default()));
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"GET /executions HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(!server.is_finished());
+        server.abort();
+    }
+}
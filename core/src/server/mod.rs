@@ -0,0 +1,359 @@
+//! Execution service for remote workflow submission
+//!
+//! [`ExecutionService`] stores submitted workflows and runs them in the
+//! background on a shared [`WorkflowExecutor`]. Every execution keeps the
+//! [`StreamEvent`]s it emits, so they can be replayed and followed while it
+//! runs, and its [`ExecutionReport`] once it finishes. [`serve`] exposes the
+//! service over HTTP:
+//!
+//! | Route                          | Description                                                          |
+//! |--------------------------------|----------------------------------------------------------------------|
+//! | `POST /workflows`              | Validate and store a workflow in the [`Workflow::to_json`] format   |
+//! | `POST /executions`             | Run a stored workflow with `inputs`, returning the execution id     |
+//! | `GET /executions`              | Recent runs from the run history, filtered by the query parameters   |
+//! | `GET /executions/{id}`         | Status, error and, once finished, the execution report              |
+//! | `GET /executions/{id}/events`  | Server-sent events of the execution, replayed from the start        |
+//! | `POST /executions/{id}/events` | Deliver a `name`d event with a `payload` to its external event nodes |
+//!
+//! Workflows are posted as JSON, or as YAML with a `Content-Type` of
+//! `application/yaml`. A [`ServerConfig`] sets how long clients may take to
+//! send a request's headers and body, the largest body accepted and an
+//! optional bearer token every request must carry.
+//!
+//! Executions are kept in memory for the lifetime of the service. Every run is
+//! also recorded in the executor's [`RunHistory`], or in an in-memory one when
+//! the executor has none, which `GET /executions` lists: with a history
+//! database, this includes the runs of earlier processes. It takes the
+//! `workflow_name`, `state`, `since`, `until` (RFC 3339 times) and `limit`
+//! (default 20) query parameters.
+//!
+//! [`serve_until_terminated`] serves until the process receives SIGTERM or
+//! Ctrl-C and then [shuts the executor down](ExecutionService::shutdown):
+//! executions still running after the grace period end `cancelled`.
+//!
+//! Enabled by the `server` feature.
+
+mod http;
+
+pub use http::{
+    ServerConfig, serve, serve_until_terminated, serve_with_config, serve_with_shutdown,
+};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, watch};
+
+use crate::errors::GraphBitResult;
+use crate::report::ExecutionReport;
+use crate::run_history::{RunHistory, RunQuery, RunRecord};
+use crate::shutdown::ShutdownSummary;
+use crate::stream::{StreamEvent, StreamMode};
+use crate::types::{WorkflowContext, WorkflowState};
+use crate::workflow::{Workflow, WorkflowExecutor};
+
+/// Events buffered between the executor and an execution's event log
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// State of a submitted execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    /// The workflow is running
+    Running,
+    /// The workflow completed
+    Completed,
+    /// The workflow failed or could not be started
+    Failed,
+    /// The service shut down before the workflow finished
+    Cancelled,
+}
+
+/// Point-in-time view of an execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionSnapshot {
+    /// Execution ID
+    pub id: String,
+    /// ID of the executed workflow
+    pub workflow_id: String,
+    /// Current status
+    pub status: ExecutionStatus,
+    /// Error that failed the execution
+    pub error: Option<String>,
+    /// When the execution was submitted
+    pub submitted_at: DateTime<Utc>,
+    /// Number of events emitted so far
+    pub event_count: usize,
+    /// External events the execution is waiting for, sorted
+    #[serde(default)]
+    pub waiting_for: Vec<String>,
+    /// Statistics and per-node results, once the execution has finished
+    pub report: Option<ExecutionReport>,
+}
+
+struct ExecutionRecord {
+    workflow_id: String,
+    submitted_at: DateTime<Utc>,
+    status: ExecutionStatus,
+    error: Option<String>,
+    events: Vec<StreamEvent>,
+    report: Option<ExecutionReport>,
+}
+
+/// An execution and the notifications sent on every change to it
+struct Execution {
+    record: Mutex<ExecutionRecord>,
+    changes: watch::Sender<u64>,
+}
+
+impl Execution {
+    fn update(&self, apply: impl FnOnce(&mut ExecutionRecord)) {
+        apply(&mut self.record.lock().unwrap_or_else(PoisonError::into_inner));
+        self.changes.send_modify(|version| *version += 1);
+    }
+
+    fn snapshot(&self, id: &str, waiting_for: Vec<String>) -> ExecutionSnapshot {
+        let record = self.record.lock().unwrap_or_else(PoisonError::into_inner);
+        ExecutionSnapshot {
+            id: id.to_string(),
+            workflow_id: record.workflow_id.clone(),
+            status: record.status,
+            error: record.error.clone(),
+            submitted_at: record.submitted_at,
+            event_count: record.events.len(),
+            waiting_for,
+            report: record.report.clone(),
+        }
+    }
+
+    fn finish(&self, result: GraphBitResult<WorkflowContext>) {
+        self.update(|record| match result {
+            Ok(context) => {
+                let report = context.to_report();
+                if matches!(context.state, WorkflowState::Failed { .. }) {
+                    record.status = ExecutionStatus::Failed;
+                    record.error.clone_from(&report.error);
+                } else if context.state.is_cancelled() {
+                    record.status = ExecutionStatus::Cancelled;
+                } else {
+                    record.status = ExecutionStatus::Completed;
+                }
+                record.report = Some(report);
+            }
+            Err(e) => {
+                record.status = ExecutionStatus::Failed;
+                record.error = Some(e.to_string());
+            }
+        });
+    }
+}
+
+/// Events of one execution, in the order they were emitted
+///
+/// Starts with the events emitted before it was created, then waits for new
+/// ones while the execution runs.
+pub struct ExecutionEvents {
+    execution: Arc<Execution>,
+    changes: watch::Receiver<u64>,
+    next: usize,
+}
+
+impl ExecutionEvents {
+    /// The next event, or `None` once the execution has finished and every event was returned
+    pub async fn next(&mut self) -> Option<StreamEvent> {
+        loop {
+            {
+                let record = self
+                    .execution
+                    .record
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if let Some(event) = record.events.get(self.next) {
+                    self.next += 1;
+                    return Some(event.clone());
+                }
+                if record.status != ExecutionStatus::Running {
+                    return None;
+                }
+            }
+            // The execution holds the sender, so this only waits for the next change
+            let _ = self.changes.changed().await;
+        }
+    }
+}
+
+struct ServiceState {
+    executor: WorkflowExecutor,
+    workflows: RwLock<HashMap<String, Workflow>>,
+    executions: RwLock<HashMap<String, Arc<Execution>>>,
+}
+
+/// Stores workflows and runs them in the background
+///
+/// Cloning is cheap; clones share the stored workflows and executions.
+#[derive(Clone)]
+pub struct ExecutionService {
+    state: Arc<ServiceState>,
+}
+
+impl ExecutionService {
+    /// Create a service running workflows on `executor`
+    ///
+    /// Agents, tools and the default LLM configuration used by submitted
+    /// workflows are those registered on the executor.
+    pub fn new(mut executor: WorkflowExecutor) -> Self {
+        if executor.run_history().is_none() {
+            match RunHistory::in_memory() {
+                Ok(history) => executor = executor.with_run_history(history),
+                Err(e) => tracing::warn!("Execution service runs without a run history: {e}"),
+            }
+        }
+        Self {
+            state: Arc::new(ServiceState {
+                executor,
+                workflows: RwLock::new(HashMap::new()),
+                executions: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Validate and store a workflow, returning its ID
+    ///
+    /// A workflow submitted again with the same ID replaces the stored one.
+    pub async fn submit_workflow(&self, workflow: Workflow) -> GraphBitResult<String> {
+        workflow.validate()?;
+        let id = workflow.id.to_string();
+        self.state
+            .workflows
+            .write()
+            .await
+            .insert(id.clone(), workflow);
+        Ok(id)
+    }
+
+    /// The stored workflow with the given ID
+    pub async fn workflow(&self, id: &str) -> Option<Workflow> {
+        self.state.workflows.read().await.get(id).cloned()
+    }
+
+    /// Start running `workflow` with `inputs`, returning the execution ID
+    ///
+    /// `stream_mode` selects the events recorded for the execution.
+    pub async fn execute(
+        &self,
+        workflow: Workflow,
+        inputs: HashMap<String, serde_json::Value>,
+        stream_mode: StreamMode,
+    ) -> String {
+        let execution_id = uuid::Uuid::new_v4();
+        let id = execution_id.to_string();
+        let execution = Arc::new(Execution {
+            record: Mutex::new(ExecutionRecord {
+                workflow_id: workflow.id.to_string(),
+                submitted_at: Utc::now(),
+                status: ExecutionStatus::Running,
+                error: None,
+                events: Vec::new(),
+                report: None,
+            }),
+            changes: watch::channel(0).0,
+        });
+        self.state
+            .executions
+            .write()
+            .await
+            .insert(id.clone(), execution.clone());
+
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(EVENT_CHANNEL_CAPACITY);
+            let mut context = WorkflowContext::new(workflow.id.clone()).with_inputs(inputs);
+            context.execution_id = execution_id;
+            let run = state.executor.execute_with_context(
+                workflow,
+                None,
+                Some(event_tx),
+                stream_mode,
+                context,
+            );
+            let record_events = async {
+                while let Some(event) = event_rx.recv().await {
+                    execution.update(|record| record.events.push(event));
+                }
+            };
+            let (result, ()) = tokio::join!(run, record_events);
+            execution.finish(result);
+        });
+        id
+    }
+
+    /// Start running the stored workflow `workflow_id`, or return `None` if there is no such workflow
+    pub async fn execute_stored(
+        &self,
+        workflow_id: &str,
+        inputs: HashMap<String, serde_json::Value>,
+        stream_mode: StreamMode,
+    ) -> Option<String> {
+        let workflow = self.workflow(workflow_id).await?;
+        Some(self.execute(workflow, inputs, stream_mode).await)
+    }
+
+    /// Current view of an execution
+    pub async fn execution(&self, id: &str) -> Option<ExecutionSnapshot> {
+        let execution = self.state.executions.read().await.get(id).cloned()?;
+        let waiting_for = self.state.executor.external_events().waiting(id);
+        Some(execution.snapshot(id, waiting_for))
+    }
+
+    /// Runs recorded in the executor's run history that match `query`, most
+    /// recently started first
+    pub async fn runs(&self, query: &RunQuery) -> GraphBitResult<Vec<RunRecord>> {
+        match self.state.executor.run_history() {
+            Some(history) => history.list(query).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Deliver `event_name` with `payload` to an execution's external event
+    /// nodes, or return `None` if there is no such execution
+    ///
+    /// Fails if the execution has finished or already holds an undelivered
+    /// payload for the event.
+    pub async fn deliver_event(
+        &self,
+        id: &str,
+        event_name: &str,
+        payload: serde_json::Value,
+    ) -> Option<GraphBitResult<()>> {
+        self.state.executions.read().await.get(id)?;
+        Some(self.state.executor.deliver_event(id, event_name, payload))
+    }
+
+    /// Events of an execution, from the first one on
+    pub async fn events(&self, id: &str) -> Option<ExecutionEvents> {
+        let execution = self.state.executions.read().await.get(id).cloned()?;
+        let changes = execution.changes.subscribe();
+        Some(ExecutionEvents {
+            execution,
+            changes,
+            next: 0,
+        })
+    }
+
+    /// Wait for an execution to finish and return its final view
+    pub async fn wait(&self, id: &str) -> Option<ExecutionSnapshot> {
+        let mut events = self.events(id).await?;
+        while events.next().await.is_some() {}
+        self.execution(id).await
+    }
+
+    /// Shut the executor down, see [`WorkflowExecutor::shutdown`]: executions
+    /// submitted afterwards fail, and those still running after `grace_period`
+    /// are cancelled
+    pub async fn shutdown(&self, grace_period: Duration) -> ShutdownSummary {
+        self.state.executor.shutdown(grace_period).await
+    }
+}
@@ -0,0 +1,223 @@
+//! Execution of `ShellCommand` nodes
+//!
+//! Commands run with `sh -c` (`cmd /V:ON /C` on Windows). Values of the
+//! template references are never spliced into the command text: each is passed
+//! to the command in an environment variable, bound by [`CommandValues`], and
+//! the reference is replaced by a quoted expansion of that variable. The shell
+//! expands variables after parsing the command, so context data cannot inject
+//! shell syntax. The expansion is only a single word outside of quotes, so
+//! [`check_command_template`] rejects references inside quotes. On Windows the
+//! variables are read with delayed expansion (`!NAME!`), which leaves `%`, `^`
+//! and `&` in values alone but makes `!` special in the command itself.
+//!
+//! Executors only run shell nodes when built with `allow_shell_nodes`, so an
+//! untrusted workflow file cannot run commands silently.
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use regex::Regex;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+/// Bytes of stdout and of stderr kept in the node output; the rest is discarded
+pub const OUTPUT_LIMIT_BYTES: usize = 1024 * 1024;
+
+/// Prefix of the environment variables holding substituted values
+pub const VALUE_VAR_PREFIX: &str = "GRAPHBIT_VALUE_";
+
+static REFERENCE_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{[^}]*\}\}").unwrap());
+
+/// Values substituted into a command, passed to it as environment variables
+#[derive(Debug, Clone, Default)]
+pub struct CommandValues {
+    values: Vec<String>,
+}
+
+impl CommandValues {
+    /// Bind `value` to the next variable, returning the quoted expansion of the
+    /// variable that takes the place of the reference in the command
+    pub fn bind(&mut self, value: &str) -> String {
+        let name = format!("{VALUE_VAR_PREFIX}{}", self.values.len());
+        self.values.push(value.to_string());
+        if cfg!(windows) {
+            format!("\"!{name}!\"")
+        } else {
+            format!("\"${name}\"")
+        }
+    }
+
+    /// The bound variables, by name
+    pub fn env(&self) -> impl Iterator<Item = (String, &str)> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (format!("{VALUE_VAR_PREFIX}{index}"), value.as_str()))
+    }
+}
+
+/// Check that no `{{...}}` reference in the command of the node `node_name`
+/// is inside quotes, where the expansion substituted for it would not be a
+/// single word: within single quotes it is not expanded and within double
+/// quotes it ends the quotes
+pub fn check_command_template(node_name: &str, template: &str) -> GraphBitResult<()> {
+    let quoted = quoted_ranges(template);
+    for reference in REFERENCE_PATTERN.find_iter(template) {
+        if quoted
+            .iter()
+            .any(|range| range.start < reference.end() && reference.start() < range.end)
+        {
+            return Err(GraphBitError::graph(format!(
+                "Shell command node '{node_name}' has the reference '{}' inside quotes; \
+                 substituted values are quoted already",
+                reference.as_str()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Byte ranges of `command` inside quotes, as the shell running it reads them
+fn quoted_ranges(command: &str) -> Vec<std::ops::Range<usize>> {
+    let (escape, single_quotes) = if cfg!(windows) {
+        ('^', false)
+    } else {
+        ('\\', true)
+    };
+    let mut ranges = Vec::new();
+    let mut open: Option<(char, usize)> = None;
+    let mut chars = command.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match open {
+            // Nothing is special within single quotes
+            Some(('\'', start)) if c == '\'' => {
+                ranges.push(start..index + 1);
+                open = None;
+            }
+            Some(('\'', _)) => {}
+            Some((_, start)) if c == '"' => {
+                ranges.push(start..index + 1);
+                open = None;
+            }
+            Some(_) if c == '\\' && !cfg!(windows) => {
+                chars.next();
+            }
+            Some(_) => {}
+            None if c == escape => {
+                chars.next();
+            }
+            None if c == '"' || (c == '\'' && single_quotes) => open = Some((c, index)),
+            None => {}
+        }
+    }
+    if let Some((_, start)) = open {
+        ranges.push(start..command.len());
+    }
+    ranges
+}
+
+/// How a command is run
+#[derive(Debug, Clone, Default)]
+pub struct ShellCommandOptions {
+    /// Directory to run in, the current directory if unset
+    pub working_dir: Option<String>,
+    /// Variables added to the inherited environment
+    pub env: HashMap<String, String>,
+    /// Time limit in seconds, after which the command is killed
+    pub timeout_seconds: u64,
+}
+
+/// Run `command` of the node `node_name`.
+///
+/// Returns `{"stdout", "stderr", "exit_status", "truncated"}`, where each stream
+/// is capped at [`OUTPUT_LIMIT_BYTES`] and `truncated` tells whether either was
+/// cut. Fails when the command exits with a nonzero status or runs past its
+/// timeout, in which case it is killed.
+pub async fn run_shell_command(
+    node_name: &str,
+    command: &str,
+    options: &ShellCommandOptions,
+) -> GraphBitResult<serde_json::Value> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.args(["/V:ON", "/C", command]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    process
+        .envs(&options.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = &options.working_dir {
+        process.current_dir(dir);
+    }
+
+    let mut child = process.spawn().map_err(|e| {
+        GraphBitError::workflow_execution(format!(
+            "Shell command node '{node_name}' could not start: {e}"
+        ))
+    })?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    // Dropping the child on timeout kills it
+    let run = async {
+        let (status, stdout, stderr) =
+            tokio::join!(child.wait(), read_capped(stdout), read_capped(stderr));
+        status.map(|status| (status, stdout, stderr))
+    };
+    let (status, (stdout, stdout_truncated), (stderr, stderr_truncated)) =
+        tokio::time::timeout(Duration::from_secs(options.timeout_seconds), run)
+            .await
+            .map_err(|_| {
+                GraphBitError::workflow_execution(format!(
+                    "Shell command node '{node_name}' timed out after {}s and was killed",
+                    options.timeout_seconds
+                ))
+            })?
+            .map_err(|e| {
+                GraphBitError::workflow_execution(format!(
+                    "Shell command node '{node_name}' failed: {e}"
+                ))
+            })?;
+
+    if !status.success() {
+        return Err(GraphBitError::workflow_execution(format!(
+            "Shell command node '{node_name}' exited with {status}: {}",
+            stderr.trim()
+        )));
+    }
+    Ok(serde_json::json!({
+        "stdout": stdout,
+        "stderr": stderr,
+        "exit_status": status.code(),
+        "truncated": stdout_truncated || stderr_truncated,
+    }))
+}
+
+/// Read `reader` to the end, keeping the first [`OUTPUT_LIMIT_BYTES`]. Reading
+/// past the limit keeps the pipe from filling up and blocking the command.
+async fn read_capped(reader: Option<impl AsyncRead + Unpin>) -> (String, bool) {
+    let Some(mut reader) = reader else {
+        return (String::new(), false);
+    };
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buffer = vec![0u8; 8192];
+    while let Ok(read) = reader.read(&mut buffer).await {
+        if read == 0 {
+            break;
+        }
+        let room = OUTPUT_LIMIT_BYTES - kept.len();
+        kept.extend_from_slice(&buffer[..read.min(room)]);
+        truncated |= read > room;
+    }
+    (String::from_utf8_lossy(&kept).into_owned(), truncated)
+}
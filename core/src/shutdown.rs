@@ -0,0 +1,274 @@
+//! Graceful shutdown of an executor
+//!
+//! [`WorkflowExecutor::shutdown`](crate::workflow::WorkflowExecutor::shutdown)
+//! stops the executor from starting executions and waits up to a grace period
+//! for the running ones to finish. Executions still running after that are
+//! cancelled: their running nodes are stopped and recorded as
+//! [`AbandonedNode::Cancelled`], the nodes that had not started are skipped
+//! with [`SkipReason::Shutdown`], and the execution ends in
+//! [`WorkflowState::Cancelled`].
+//!
+//! A cancelled execution is recorded like any finished one: in the run
+//! history, where a report directory keeps its context as a checkpoint to
+//! resume it from with
+//! [`WorkflowExecutor::reexecute_from`](crate::workflow::WorkflowExecutor::reexecute_from),
+//! and in the result sink of its context, which receives a workflow record.
+//! Once no execution is left, the audit log is flushed and, with the
+//! `metrics` feature, the metrics are pushed to the gateway set with
+//! `with_metrics_push_url`. The returned [`ShutdownSummary`] lists the
+//! executions that were interrupted.
+//!
+//! Executors sharing a [`ShutdownHandle`], such as the executors a Python
+//! `Executor` builds for its runs, shut down together.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::types::{AbandonedNode, SkipReason, WorkflowContext};
+
+/// How far a shutdown has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Phase {
+    /// Executions may start
+    #[default]
+    Open,
+    /// No execution may start; the running ones may finish
+    Draining,
+    /// The grace period ran out; the running executions are being cancelled
+    Cancelling,
+}
+
+#[derive(Default)]
+struct State {
+    phase: Phase,
+    /// Running executions, with the name of their workflow
+    running: HashMap<Uuid, String>,
+    /// Executions that finished on their own after the shutdown began
+    completed: usize,
+    /// Executions the shutdown cancelled
+    interrupted: Vec<InterruptedExecution>,
+}
+
+/// Running executions of an executor and how far its shutdown has got
+///
+/// Executors given clones with
+/// [`WorkflowExecutor::with_shutdown_handle`](crate::workflow::WorkflowExecutor::with_shutdown_handle)
+/// shut down together. Cloning is cheap; clones share the state.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    state: Arc<watch::Sender<State>>,
+}
+
+impl Default for ShutdownHandle {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(State::default())),
+        }
+    }
+}
+
+impl std::fmt::Debug for ShutdownHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("ShutdownHandle")
+            .field("phase", &state.phase)
+            .field("running", &state.running.len())
+            .finish()
+    }
+}
+
+/// Registration of a running execution, removed when dropped
+pub(crate) struct RunningExecution {
+    handle: ShutdownHandle,
+    execution_id: Uuid,
+    interrupted: Option<InterruptedExecution>,
+}
+
+impl RunningExecution {
+    /// Record that the shutdown cancelled the execution that ran in `context`,
+    /// archived to `checkpoint` by the run history
+    pub(crate) fn interrupted(&mut self, context: &WorkflowContext, checkpoint: Option<PathBuf>) {
+        let mut cancelled_nodes = Vec::new();
+        let mut skipped_nodes = Vec::new();
+        for (name, state) in &context.abandoned_nodes {
+            match state {
+                AbandonedNode::Cancelled => cancelled_nodes.push(name.clone()),
+                AbandonedNode::Skipped {
+                    reason: SkipReason::Shutdown,
+                } => skipped_nodes.push(name.clone()),
+                _ => {}
+            }
+        }
+        cancelled_nodes.sort();
+        skipped_nodes.sort();
+        let workflow_name = self
+            .handle
+            .state
+            .borrow()
+            .running
+            .get(&self.execution_id)
+            .cloned()
+            .unwrap_or_default();
+        self.interrupted = Some(InterruptedExecution {
+            execution_id: self.execution_id.to_string(),
+            workflow_name,
+            cancelled_nodes,
+            skipped_nodes,
+            checkpoint,
+        });
+    }
+}
+
+impl Drop for RunningExecution {
+    fn drop(&mut self) {
+        let interrupted = self.interrupted.take();
+        self.handle.state.send_modify(|state| {
+            state.running.remove(&self.execution_id);
+            match interrupted {
+                Some(interrupted) => state.interrupted.push(interrupted),
+                None if state.phase != Phase::Open => state.completed += 1,
+                None => {}
+            }
+        });
+    }
+}
+
+impl ShutdownHandle {
+    /// Create the state of an executor that was not shut down
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a shutdown has begun, so no execution may start
+    #[must_use]
+    pub fn is_shutting_down(&self) -> bool {
+        self.state.borrow().phase != Phase::Open
+    }
+
+    /// Number of running executions
+    #[must_use]
+    pub fn running(&self) -> usize {
+        self.state.borrow().running.len()
+    }
+
+    /// Register the execution `execution_id` of `workflow_name` as running
+    ///
+    /// Fails once a shutdown has begun.
+    pub(crate) fn begin(
+        &self,
+        execution_id: Uuid,
+        workflow_name: &str,
+    ) -> GraphBitResult<RunningExecution> {
+        let started = self.state.send_if_modified(|state| {
+            if state.phase != Phase::Open {
+                return false;
+            }
+            state
+                .running
+                .insert(execution_id, workflow_name.to_string());
+            true
+        });
+        if !started {
+            return Err(GraphBitError::workflow_execution(
+                "The executor is shutting down and does not start new executions",
+            ));
+        }
+        Ok(RunningExecution {
+            handle: self.clone(),
+            execution_id,
+            interrupted: None,
+        })
+    }
+
+    /// Whether the running executions are being cancelled
+    pub(crate) fn is_cancelling(&self) -> bool {
+        self.state.borrow().phase == Phase::Cancelling
+    }
+
+    /// Wait until the running executions are to be cancelled
+    pub(crate) async fn cancelled(&self) {
+        let mut changes = self.state.subscribe();
+        // The sender lives as long as `self`, so this only returns once cancelling
+        let _ = changes
+            .wait_for(|state| state.phase == Phase::Cancelling)
+            .await;
+    }
+
+    /// Stop executions from starting, wait up to `grace_period` for the running
+    /// ones to finish and cancel the rest, returning the number of executions
+    /// that finished on their own and those that were cancelled
+    pub(crate) async fn drain(&self, grace_period: Duration) -> (usize, Vec<InterruptedExecution>) {
+        self.state.send_if_modified(|state| {
+            let open = state.phase == Phase::Open;
+            if open {
+                state.phase = Phase::Draining;
+            }
+            open
+        });
+        let mut changes = self.state.subscribe();
+        let finished = tokio::time::timeout(
+            grace_period,
+            changes.wait_for(|state| state.running.is_empty()),
+        )
+        .await
+        .is_ok();
+        if !finished {
+            tracing::info!(
+                running = self.running(),
+                "Shutdown grace period of {}ms ran out; cancelling the running executions",
+                grace_period.as_millis()
+            );
+            self.state
+                .send_modify(|state| state.phase = Phase::Cancelling);
+            let _ = changes.wait_for(|state| state.running.is_empty()).await;
+        }
+        let mut outcome = (0, Vec::new());
+        self.state.send_modify(|state| {
+            outcome = (
+                std::mem::take(&mut state.completed),
+                std::mem::take(&mut state.interrupted),
+            );
+        });
+        outcome
+    }
+}
+
+/// An execution a shutdown cancelled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterruptedExecution {
+    /// Execution ID
+    pub execution_id: String,
+    /// Name of the executed workflow
+    pub workflow_name: String,
+    /// Nodes stopped while running, sorted
+    pub cancelled_nodes: Vec<String>,
+    /// Nodes that had not started, sorted
+    pub skipped_nodes: Vec<String>,
+    /// File the run history archived the execution to, to resume it from
+    pub checkpoint: Option<PathBuf>,
+}
+
+/// Outcome of [`WorkflowExecutor::shutdown`](crate::workflow::WorkflowExecutor::shutdown)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownSummary {
+    /// Executions that finished within the grace period
+    pub completed: usize,
+    /// Executions cancelled once the grace period ran out
+    pub interrupted: Vec<InterruptedExecution>,
+    /// Whether the audit log was flushed
+    pub audit_log_flushed: bool,
+    /// Whether the metrics were pushed
+    pub metrics_pushed: bool,
+    /// Errors of the flushes; a failed flush does not stop the others
+    pub errors: Vec<String>,
+    /// How long the shutdown took in milliseconds
+    pub duration_ms: u64,
+}
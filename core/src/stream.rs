@@ -6,7 +6,7 @@
 //! # Stream Modes
 //!
 //! - `Updates` — all non-token events (workflow/node + LLM/tool lifecycle)
-//! - `Messages` — real-time LLM tokens + terminal workflow event
+//! - `Messages` — real-time LLM tokens and node output deltas + terminal workflow event
 //! - `All` — all event types (`Updates` + `Messages`)
 //!
 //! # Event Types
@@ -14,7 +14,7 @@
 //! Events are divided into three categories:
 //! 1. **Workflow/Node lifecycle** (`Updates`/`All`): `WorkflowStarted`, `NodeStarted`,
 //!    `NodeCompleted`, `NodeFailed`, `WorkflowCompleted`, `WorkflowFailed`
-//! 2. **Token-level** (`Messages`/`All`): `Token`
+//! 2. **Token-level** (`Messages`/`All`): `Token`, `NodeOutputDelta`
 //! 3. **Execution-level** (`Updates`/`All`): `LlmCallStarted`, `LlmCallCompleted`, `ToolCallStarted`,
 //!    `ToolCallCompleted`, `ToolCallFailed`
 
@@ -31,7 +31,7 @@ use serde::{Deserialize, Serialize};
 pub enum StreamMode {
     /// All non-token events (workflow/node + LLM/tool lifecycle) (default).
     Updates,
-    /// Real-time LLM tokens + terminal workflow event.
+    /// Real-time LLM tokens and node output deltas + terminal workflow event.
     Messages,
     /// All events (`Updates` + `Messages`).
     All,
@@ -44,7 +44,7 @@ impl StreamMode {
         matches!(self, Self::Updates | Self::All)
     }
 
-    /// Whether this mode emits `Token` events (real-time LLM output).
+    /// Whether this mode emits `Token` and `NodeOutputDelta` events (real-time LLM output).
     #[inline]
     pub fn emits_tokens(&self) -> bool {
         matches!(self, Self::Messages | Self::All)
@@ -169,6 +169,20 @@ pub enum StreamEvent {
         content: String,
     },
 
+    /// A piece of an agent node's output text, as it is generated.
+    ///
+    /// Unlike `Token`, tool-call fragments are never sent as deltas; they are
+    /// buffered until the full response is assembled.
+    #[serde(rename = "node_output_delta")]
+    NodeOutputDelta {
+        /// Node UUID producing the output
+        node_id: String,
+        /// Human-readable node name
+        node_name: String,
+        /// Newly generated output text
+        delta: String,
+    },
+
     // ── LLM/tool lifecycle events (Updates / All) ────────────────────────
     /// An LLM call started within an agent node execution.
     #[serde(rename = "llm_call_started")]
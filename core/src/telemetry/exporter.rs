@@ -0,0 +1,89 @@
+//! Prometheus scrape endpoint and push client
+
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::MetricsRecorder;
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Content type of the Prometheus text exposition format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// Limit on the request line and headers
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+
+/// Serve the metrics of `recorder` at `GET /metrics` on `listener` until the
+/// returned future is dropped
+///
+/// Fails only if accepting a connection fails.
+pub async fn serve(listener: TcpListener, recorder: MetricsRecorder) -> std::io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let recorder = recorder.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &recorder).await {
+                tracing::debug!("Metrics connection from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, recorder: &MetricsRecorder) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut head = (&mut stream).take(MAX_HEAD_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line).await?;
+    // Skip the headers; the request has no body
+    loop {
+        let mut line = String::new();
+        if head.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next();
+    let path = parts.next().and_then(|target| target.split('?').next());
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", CONTENT_TYPE, recorder.render_prometheus()),
+        (Some(_), Some("/metrics")) => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Only GET is supported\n".to_string(),
+        ),
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+    };
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.flush().await?;
+    stream.get_mut().shutdown().await
+}
+
+/// Push the metrics of `recorder` to `url`, e.g. the job URL of a Prometheus
+/// push gateway (`http://gateway:9091/metrics/job/graphbit`)
+pub async fn push(url: &str, recorder: &MetricsRecorder) -> GraphBitResult<()> {
+    let client = crate::http_client::shared_client("metrics", Some(Duration::from_secs(10)), None)?;
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, CONTENT_TYPE)
+        .body(recorder.render_prometheus())
+        .send()
+        .await
+        .map_err(|e| GraphBitError::Network {
+            message: format!("Failed to push metrics to {url}: {e}"),
+        })?;
+    if !response.status().is_success() {
+        return Err(GraphBitError::Network {
+            message: format!(
+                "Metrics push to {url} failed with status {}",
+                response.status()
+            ),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,182 @@
+//! Metrics of executor internals
+//!
+//! With the `metrics` feature the executor reports node executions, LLM calls,
+//! concurrency permits, retries and circuit breaker transitions through the
+//! [`metrics`](https://docs.rs/metrics) facade, so any recorder installed by
+//! the application receives them. [`install`] sets up the built-in
+//! [`MetricsRecorder`], which keeps the values in memory for
+//! [`MetricsRecorder::snapshot`] and renders them in the Prometheus text format
+//! for [`serve`] (scraping) or [`push`] (a push gateway).
+//!
+//! Without the feature the reporting functions do nothing.
+
+#[cfg(feature = "metrics")]
+mod exporter;
+#[cfg(feature = "metrics")]
+mod recorder;
+
+#[cfg(feature = "metrics")]
+pub use exporter::{push, serve};
+#[cfg(feature = "metrics")]
+pub use recorder::{
+    HistogramSummary, MetricSample, MetricsRecorder, MetricsSnapshot, install, installed,
+};
+
+use std::time::Duration;
+
+use crate::errors::GraphBitResult;
+use crate::llm::{LlmProviderTrait, LlmResponse};
+use crate::types::{AgentId, CircuitBreakerState};
+
+/// Node executions, labelled `node_type` and `outcome` (`success` or `failure`)
+pub const NODE_EXECUTIONS: &str = "graphbit_node_executions_total";
+/// Node execution time in seconds, labelled `node_type`
+pub const NODE_DURATION: &str = "graphbit_node_duration_seconds";
+/// Node retries, labelled `node_type`
+pub const NODE_RETRIES: &str = "graphbit_node_retries_total";
+/// LLM requests, labelled `provider`, `model` and `outcome`
+pub const LLM_REQUESTS: &str = "graphbit_llm_requests_total";
+/// LLM request latency in seconds, labelled `provider` and `model`
+pub const LLM_DURATION: &str = "graphbit_llm_request_duration_seconds";
+/// LLM tokens, labelled `provider`, `model` and `kind` (`prompt` or `completion`)
+pub const LLM_TOKENS: &str = "graphbit_llm_tokens_total";
+/// Time spent waiting for concurrency permits in seconds, labelled `node_type`
+pub const PERMIT_WAIT: &str = "graphbit_permit_wait_seconds";
+/// Tasks holding concurrency permits
+pub const ACTIVE_TASKS: &str = "graphbit_active_tasks";
+/// Tasks waiting for concurrency permits
+pub const QUEUED_TASKS: &str = "graphbit_queued_tasks";
+/// Circuit breaker transitions, labelled `agent` and `to` (the new state)
+pub const BREAKER_TRANSITIONS: &str = "graphbit_circuit_breaker_transitions_total";
+/// Circuit breaker state by `agent`: 0 closed, 1 half-open, 2 open
+pub const BREAKER_STATE: &str = "graphbit_circuit_breaker_state";
+
+#[cfg(feature = "metrics")]
+const fn outcome(success: bool) -> &'static str {
+    if success { "success" } else { "failure" }
+}
+
+/// Report a finished node execution, with its duration when known
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn node_executed(node_type: &'static str, success: bool, duration: Option<Duration>) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(NODE_EXECUTIONS, "node_type" => node_type, "outcome" => outcome(success))
+            .increment(1);
+        if let Some(duration) = duration {
+            metrics::histogram!(NODE_DURATION, "node_type" => node_type)
+                .record(duration.as_secs_f64());
+        }
+    }
+}
+
+/// Report a node attempt that failed and will be retried
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn node_retried(node_type: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(NODE_RETRIES, "node_type" => node_type).increment(1);
+}
+
+/// Run the LLM `call` to `provider`, reporting its latency, outcome and tokens
+pub(crate) async fn llm_call<Fut>(
+    provider: &dyn LlmProviderTrait,
+    call: Fut,
+) -> GraphBitResult<LlmResponse>
+where
+    Fut: Future<Output = GraphBitResult<LlmResponse>>,
+{
+    #[cfg(feature = "metrics")]
+    {
+        let provider_name = provider.provider_name().to_string();
+        let model = provider.model_name().to_string();
+        let start = std::time::Instant::now();
+        let result = call.await;
+        metrics::histogram!(LLM_DURATION, "provider" => provider_name.clone(), "model" => model.clone())
+            .record(start.elapsed().as_secs_f64());
+        metrics::counter!(
+            LLM_REQUESTS,
+            "provider" => provider_name.clone(),
+            "model" => model.clone(),
+            "outcome" => outcome(result.is_ok())
+        )
+        .increment(1);
+        if let Ok(response) = &result {
+            for (kind, tokens) in [
+                ("prompt", response.usage.prompt_tokens),
+                ("completion", response.usage.completion_tokens),
+            ] {
+                metrics::counter!(
+                    LLM_TOKENS,
+                    "provider" => provider_name.clone(),
+                    "model" => model.clone(),
+                    "kind" => kind
+                )
+                .increment(u64::from(tokens));
+            }
+        }
+        result
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = provider;
+        call.await
+    }
+}
+
+/// Report a task that got its concurrency permits after waiting `wait`
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn permits_acquired(node_type: &str, wait: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::histogram!(PERMIT_WAIT, "node_type" => node_type.to_string())
+            .record(wait.as_secs_f64());
+        metrics::gauge!(ACTIVE_TASKS).increment(1.0);
+    }
+}
+
+/// Report a task that released its concurrency permits
+pub(crate) fn permits_released() {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(ACTIVE_TASKS).decrement(1.0);
+}
+
+/// Task waiting for concurrency permits, counted until dropped
+pub(crate) struct QueuedTask(());
+
+impl QueuedTask {
+    pub(crate) fn new() -> Self {
+        #[cfg(feature = "metrics")]
+        metrics::gauge!(QUEUED_TASKS).increment(1.0);
+        Self(())
+    }
+}
+
+impl Drop for QueuedTask {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        metrics::gauge!(QUEUED_TASKS).decrement(1.0);
+    }
+}
+
+/// Report the circuit breaker of `agent` moving from `from` to `to`, if it did
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn breaker_transition(
+    agent: &AgentId,
+    from: &CircuitBreakerState,
+    to: &CircuitBreakerState,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        if std::mem::discriminant(from) == std::mem::discriminant(to) {
+            return;
+        }
+        let (name, level) = match to {
+            CircuitBreakerState::Closed => ("closed", 0.0),
+            CircuitBreakerState::HalfOpen => ("half_open", 1.0),
+            CircuitBreakerState::Open { .. } => ("open", 2.0),
+        };
+        let agent = agent.to_string();
+        metrics::counter!(BREAKER_TRANSITIONS, "agent" => agent.clone(), "to" => name).increment(1);
+        metrics::gauge!(BREAKER_STATE, "agent" => agent).set(level);
+    }
+}
@@ -0,0 +1,438 @@
+//! In-memory recorder for the `metrics` facade
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use metrics::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+/// Upper bounds of the histogram buckets, in seconds
+const BUCKETS: [f64; 16] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+/// Histogram with the fixed [`BUCKETS`]
+#[derive(Default)]
+struct Buckets {
+    /// Observations per bucket, the last one past the largest bound
+    counts: [AtomicU64; BUCKETS.len() + 1],
+    /// Sum of the observations, as `f64` bits
+    sum: AtomicU64,
+}
+
+impl HistogramFn for Buckets {
+    fn record(&self, value: f64) {
+        let bucket = BUCKETS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(BUCKETS.len());
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+}
+
+impl Buckets {
+    fn summary(&self) -> HistogramSummary {
+        let mut cumulative = 0;
+        let buckets: Vec<(f64, u64)> = BUCKETS
+            .iter()
+            .copied()
+            .chain([f64::INFINITY])
+            .zip(&self.counts)
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (bound, cumulative)
+            })
+            .collect();
+        HistogramSummary {
+            count: cumulative,
+            sum: f64::from_bits(self.sum.load(Ordering::Relaxed)),
+            p50: quantile(&buckets, 0.5),
+            p90: quantile(&buckets, 0.9),
+            p99: quantile(&buckets, 0.99),
+            buckets,
+        }
+    }
+}
+
+/// Estimate the `q` quantile from cumulative `buckets`, interpolating linearly
+/// within the bucket it falls in; observations past the largest bound count as
+/// the largest bound
+#[allow(clippy::cast_precision_loss)]
+fn quantile(buckets: &[(f64, u64)], q: f64) -> f64 {
+    let Some(&(_, total)) = buckets.last() else {
+        return 0.0;
+    };
+    if total == 0 {
+        return 0.0;
+    }
+    let rank = q * total as f64;
+    let mut lower = (0.0, 0);
+    for &(bound, cumulative) in buckets {
+        if cumulative as f64 >= rank {
+            if bound.is_infinite() {
+                return lower.0;
+            }
+            let in_bucket = (cumulative - lower.1) as f64;
+            return lower.0 + (bound - lower.0) * (rank - lower.1 as f64) / in_bucket;
+        }
+        lower = (bound, cumulative);
+    }
+    lower.0
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: Mutex<HashMap<Key, Arc<AtomicU64>>>,
+    /// Gauge values as `f64` bits
+    gauges: Mutex<HashMap<Key, Arc<AtomicU64>>>,
+    histograms: Mutex<HashMap<Key, Arc<Buckets>>>,
+    descriptions: Mutex<HashMap<String, String>>,
+}
+
+/// Recorder keeping metric values in memory
+///
+/// Cloning is cheap; clones share the values.
+#[derive(Clone, Default)]
+pub struct MetricsRecorder {
+    registry: Arc<Registry>,
+}
+
+impl std::fmt::Debug for MetricsRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsRecorder")
+            .field("counters", &lock(&self.registry.counters).len())
+            .field("gauges", &lock(&self.registry.gauges).len())
+            .field("histograms", &lock(&self.registry.histograms).len())
+            .finish()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn handle<T: Default>(handles: &Mutex<HashMap<Key, Arc<T>>>, key: &Key) -> Arc<T> {
+    Arc::clone(lock(handles).entry(key.clone()).or_default())
+}
+
+impl Recorder for MetricsRecorder {
+    fn describe_counter(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(&key, &description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(&key, &description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(&key, &description);
+    }
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(handle(&self.registry.counters, key))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(handle(&self.registry.gauges, key))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(handle(&self.registry.histograms, key))
+    }
+}
+
+impl MetricsRecorder {
+    /// Create a recorder with no values
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn describe(&self, name: &KeyName, description: &str) {
+        lock(&self.registry.descriptions)
+            .insert(name.as_str().to_string(), description.to_string());
+    }
+
+    /// Current values of every metric, sorted by name and labels
+    #[must_use]
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        fn samples<H, T>(
+            handles: &Mutex<HashMap<Key, Arc<H>>>,
+            value: impl Fn(&H) -> T,
+        ) -> Vec<MetricSample<T>> {
+            let mut samples: Vec<MetricSample<T>> = lock(handles)
+                .iter()
+                .map(|(key, handle)| MetricSample {
+                    name: key.name().to_string(),
+                    labels: key
+                        .labels()
+                        .map(|label| (label.key().to_string(), label.value().to_string()))
+                        .collect(),
+                    value: value(handle),
+                })
+                .collect();
+            samples.sort_by(|a, b| (&a.name, &a.labels).cmp(&(&b.name, &b.labels)));
+            samples
+        }
+
+        MetricsSnapshot {
+            counters: samples(&self.registry.counters, |counter| {
+                counter.load(Ordering::Relaxed)
+            }),
+            gauges: samples(&self.registry.gauges, |gauge| {
+                f64::from_bits(gauge.load(Ordering::Relaxed))
+            }),
+            histograms: samples(&self.registry.histograms, Buckets::summary),
+        }
+    }
+
+    /// Current values in the Prometheus text exposition format
+    #[must_use]
+    pub fn render_prometheus(&self) -> String {
+        let snapshot = self.snapshot();
+        let descriptions = lock(&self.registry.descriptions).clone();
+        let mut text = String::new();
+        render_family(
+            &mut text,
+            &descriptions,
+            &snapshot.counters,
+            "counter",
+            |text, sample| {
+                let _ = writeln!(
+                    text,
+                    "{}{} {}",
+                    sample.name,
+                    labels(&sample.labels, None),
+                    sample.value
+                );
+            },
+        );
+        render_family(
+            &mut text,
+            &descriptions,
+            &snapshot.gauges,
+            "gauge",
+            |text, sample| {
+                let _ = writeln!(
+                    text,
+                    "{}{} {}",
+                    sample.name,
+                    labels(&sample.labels, None),
+                    sample.value
+                );
+            },
+        );
+        render_family(
+            &mut text,
+            &descriptions,
+            &snapshot.histograms,
+            "histogram",
+            |text, sample| {
+                let name = &sample.name;
+                for (bound, cumulative) in &sample.value.buckets {
+                    let le = if bound.is_infinite() {
+                        "+Inf".to_string()
+                    } else {
+                        bound.to_string()
+                    };
+                    let _ = writeln!(
+                        text,
+                        "{name}_bucket{} {cumulative}",
+                        labels(&sample.labels, Some(&le))
+                    );
+                }
+                let labels = labels(&sample.labels, None);
+                let _ = writeln!(text, "{name}_sum{labels} {}", sample.value.sum);
+                let _ = writeln!(text, "{name}_count{labels} {}", sample.value.count);
+            },
+        );
+        text
+    }
+}
+
+/// Write `samples`, sorted by name, with the `HELP` and `TYPE` lines of each metric
+fn render_family<T>(
+    text: &mut String,
+    descriptions: &HashMap<String, String>,
+    samples: &[MetricSample<T>],
+    kind: &str,
+    write: impl Fn(&mut String, &MetricSample<T>),
+) {
+    for (i, sample) in samples.iter().enumerate() {
+        if i == 0 || samples[i - 1].name != sample.name {
+            let name = &sample.name;
+            if let Some(help) = descriptions.get(name) {
+                let _ = writeln!(text, "# HELP {name} {}", escape(help, false));
+            }
+            let _ = writeln!(text, "# TYPE {name} {kind}");
+        }
+        write(text, sample);
+    }
+}
+
+/// `{a="1",b="2"}` for `labels` and the bucket bound `le`, empty without any
+fn labels(labels: &BTreeMap<String, String>, le: Option<&str>) -> String {
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value, true)))
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(text: &str, quotes: bool) -> String {
+    let text = text.replace('\\', "\\\\").replace('\n', "\\n");
+    if quotes {
+        text.replace('"', "\\\"")
+    } else {
+        text
+    }
+}
+
+/// Value of one metric for one set of labels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricSample<T> {
+    /// Metric name
+    pub name: String,
+    /// Label values by label name
+    pub labels: BTreeMap<String, String>,
+    /// Current value
+    pub value: T,
+}
+
+/// Observations of a histogram
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramSummary {
+    /// Number of observations
+    pub count: u64,
+    /// Sum of the observations
+    pub sum: f64,
+    /// Median, estimated from the buckets
+    pub p50: f64,
+    /// 90th percentile, estimated from the buckets
+    pub p90: f64,
+    /// 99th percentile, estimated from the buckets
+    pub p99: f64,
+    /// Cumulative observation counts by bucket upper bound, ending with infinity
+    pub buckets: Vec<(f64, u64)>,
+}
+
+/// Values of the metrics at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Counters
+    pub counters: Vec<MetricSample<u64>>,
+    /// Gauges
+    pub gauges: Vec<MetricSample<f64>>,
+    /// Histograms
+    pub histograms: Vec<MetricSample<HistogramSummary>>,
+}
+
+fn matches<T>(sample: &MetricSample<T>, name: &str, labels: &[(&str, &str)]) -> bool {
+    sample.name == name
+        && labels
+            .iter()
+            .all(|(label, value)| sample.labels.get(*label).map(String::as_str) == Some(*value))
+}
+
+impl MetricsSnapshot {
+    /// Sum of the counter `name` over the samples carrying all of `labels`
+    #[must_use]
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        self.counters
+            .iter()
+            .filter(|sample| matches(sample, name, labels))
+            .map(|sample| sample.value)
+            .sum()
+    }
+
+    /// Value of the gauge `name` for the first sample carrying all of `labels`
+    #[must_use]
+    pub fn gauge(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        self.gauges
+            .iter()
+            .find(|sample| matches(sample, name, labels))
+            .map(|sample| sample.value)
+    }
+
+    /// Histogram `name` for the first sample carrying all of `labels`
+    #[must_use]
+    pub fn histogram(&self, name: &str, labels: &[(&str, &str)]) -> Option<&HistogramSummary> {
+        self.histograms
+            .iter()
+            .find(|sample| matches(sample, name, labels))
+            .map(|sample| &sample.value)
+    }
+}
+
+static INSTALLED: OnceLock<MetricsRecorder> = OnceLock::new();
+
+/// Install a [`MetricsRecorder`] as the global recorder of the `metrics`
+/// facade, returning it. Installing again returns the same recorder.
+///
+/// Fails if the application already installed another recorder; the executor
+/// metrics then go to that recorder.
+pub fn install() -> GraphBitResult<MetricsRecorder> {
+    static INSTALL: Mutex<()> = Mutex::new(());
+    let _installing = lock(&INSTALL);
+    if let Some(recorder) = INSTALLED.get() {
+        return Ok(recorder.clone());
+    }
+    let recorder = MetricsRecorder::new();
+    metrics::set_global_recorder(recorder.clone())
+        .map_err(|_| GraphBitError::config("Another metrics recorder is already installed"))?;
+    describe();
+    let _ = INSTALLED.set(recorder.clone());
+    Ok(recorder)
+}
+
+/// Recorder set up by [`install`], if any
+#[must_use]
+pub fn installed() -> Option<MetricsRecorder> {
+    INSTALLED.get().cloned()
+}
+
+fn describe() {
+    use super::{
+        ACTIVE_TASKS, BREAKER_STATE, BREAKER_TRANSITIONS, LLM_DURATION, LLM_REQUESTS, LLM_TOKENS,
+        NODE_DURATION, NODE_EXECUTIONS, NODE_RETRIES, PERMIT_WAIT, QUEUED_TASKS,
+    };
+
+    metrics::describe_counter!(NODE_EXECUTIONS, "Node executions by node type and outcome");
+    metrics::describe_histogram!(NODE_DURATION, Unit::Seconds, "Node execution time");
+    metrics::describe_counter!(NODE_RETRIES, "Node attempts retried after a failure");
+    metrics::describe_counter!(LLM_REQUESTS, "LLM requests by provider, model and outcome");
+    metrics::describe_histogram!(LLM_DURATION, Unit::Seconds, "LLM request latency");
+    metrics::describe_counter!(LLM_TOKENS, "LLM prompt and completion tokens");
+    metrics::describe_histogram!(
+        PERMIT_WAIT,
+        Unit::Seconds,
+        "Time spent waiting for concurrency permits"
+    );
+    metrics::describe_gauge!(ACTIVE_TASKS, "Tasks holding concurrency permits");
+    metrics::describe_gauge!(QUEUED_TASKS, "Tasks waiting for concurrency permits");
+    metrics::describe_counter!(
+        BREAKER_TRANSITIONS,
+        "Circuit breaker transitions by agent and new state"
+    );
+    metrics::describe_gauge!(
+        BREAKER_STATE,
+        "Circuit breaker state by agent: 0 closed, 1 half-open, 2 open"
+    );
+}
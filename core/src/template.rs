@@ -0,0 +1,485 @@
+//! Parameterized workflow templates
+//!
+//! A [`WorkflowTemplate`] is a workflow definition, in the format written by
+//! [`Workflow::to_json`], together with declared parameters. Any string in the
+//! definition (prompts, node configurations, names) can reference a parameter as
+//! `{{params.NAME}}`. [`WorkflowTemplate::instantiate`] checks the supplied values,
+//! fills in defaults, substitutes the references and returns a validated
+//! [`Workflow`]. A string consisting of a single reference takes the parameter's
+//! value with its type, so `"{{params.max_tokens}}"` can become a number.
+//!
+//! Templates are read from JSON or YAML. A [`TemplateRegistry`] keeps templates
+//! by name.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::types::WorkflowId;
+use crate::workflow::Workflow;
+
+static PARAM_REF_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{params\.([a-zA-Z0-9_]+)\}\}").unwrap());
+
+static PARAM_NAME_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_]+$").unwrap());
+
+/// Type of a template parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterType {
+    /// Any string
+    String,
+    /// Any number
+    Number,
+    /// A whole number
+    Integer,
+    /// `true` or `false`
+    Boolean,
+    /// A JSON array
+    Array,
+    /// A JSON object
+    Object,
+}
+
+impl ParameterType {
+    /// Whether `value` has this type
+    #[must_use]
+    pub fn matches(self, value: &serde_json::Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Boolean => value.is_boolean(),
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+        }
+    }
+}
+
+impl fmt::Display for ParameterType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::Boolean => "boolean",
+            Self::Array => "array",
+            Self::Object => "object",
+        })
+    }
+}
+
+/// A declared template parameter. Parameters without a default are required.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateParameter {
+    /// Type of the parameter's value
+    #[serde(rename = "type")]
+    pub param_type: ParameterType,
+    /// Value used when the parameter is not supplied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    /// What the parameter controls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl TemplateParameter {
+    /// Required parameter of type `param_type`
+    #[must_use]
+    pub const fn new(param_type: ParameterType) -> Self {
+        Self {
+            param_type,
+            default: None,
+            description: None,
+        }
+    }
+
+    /// Make the parameter optional with `default` as its value
+    #[must_use]
+    pub fn with_default(mut self, default: impl Into<serde_json::Value>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Set the description
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Whether the parameter must be supplied
+    #[must_use]
+    pub const fn is_required(&self) -> bool {
+        self.default.is_none()
+    }
+}
+
+/// A workflow definition with declared parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowTemplate {
+    /// Name the template is registered and instantiated under
+    pub name: String,
+    /// What the template builds
+    #[serde(default)]
+    pub description: String,
+    /// Declared parameters by name
+    #[serde(default)]
+    pub parameters: BTreeMap<String, TemplateParameter>,
+    /// Workflow definition in the format written by [`Workflow::to_json`]
+    pub workflow: serde_json::Value,
+}
+
+impl WorkflowTemplate {
+    /// Template from an existing workflow whose strings reference parameters as
+    /// `{{params.NAME}}`; declare them with [`WorkflowTemplate::with_parameter`]
+    pub fn from_workflow(name: impl Into<String>, workflow: &Workflow) -> GraphBitResult<Self> {
+        Ok(Self {
+            name: name.into(),
+            description: workflow.description.clone(),
+            parameters: BTreeMap::new(),
+            workflow: serde_json::from_str(&workflow.to_json()?)?,
+        })
+    }
+
+    /// Declare a parameter
+    #[must_use]
+    pub fn with_parameter(mut self, name: impl Into<String>, parameter: TemplateParameter) -> Self {
+        self.parameters.insert(name.into(), parameter);
+        self
+    }
+
+    /// Set the description
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Load and validate a template from a JSON value with `name`, `description`,
+    /// `parameters` and `workflow` fields
+    pub fn from_value(value: serde_json::Value) -> GraphBitResult<Self> {
+        let template: Self = serde_json::from_value(value)?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Load and validate a template from JSON
+    pub fn from_json(json: &str) -> GraphBitResult<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// Load and validate a template from YAML
+    pub fn from_yaml(yaml: &str) -> GraphBitResult<Self> {
+        Self::from_value(parse_yaml(yaml, "workflow template")?)
+    }
+
+    /// Load and validate a template from a file: YAML for `.yaml` and `.yml`
+    /// files, JSON otherwise
+    pub fn from_file(path: impl AsRef<Path>) -> GraphBitResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            GraphBitError::config(format!(
+                "Failed to read workflow template {}: {e}",
+                path.display()
+            ))
+        })?;
+        if is_yaml_file(path) {
+            Self::from_yaml(&text)
+        } else {
+            Self::from_json(&text)
+        }
+    }
+
+    /// Serialize the template to JSON
+    pub fn to_json(&self) -> GraphBitResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Check the parameter names and defaults, and that every `{{params.NAME}}`
+    /// reference in the workflow is declared
+    pub fn validate(&self) -> GraphBitResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(GraphBitError::validation(
+                "name",
+                "Workflow template name cannot be empty",
+            ));
+        }
+        for (name, parameter) in &self.parameters {
+            if !PARAM_NAME_PATTERN.is_match(name) {
+                return Err(self.error(format!(
+                    "Parameter name '{name}' may only contain letters, digits and underscores"
+                )));
+            }
+            if let Some(default) = &parameter.default {
+                if !parameter.param_type.matches(default) {
+                    return Err(self.error(format!(
+                        "Default of parameter '{name}' must be of type {}, got {default}",
+                        parameter.param_type
+                    )));
+                }
+            }
+        }
+        let mut referenced = BTreeSet::new();
+        collect_references(&self.workflow, &mut referenced);
+        if let Some(undeclared) = referenced
+            .iter()
+            .find(|name| !self.parameters.contains_key(*name))
+        {
+            return Err(self.error(format!(
+                "Workflow references undeclared parameter '{{{{params.{undeclared}}}}}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Build a workflow from the template with `params`, falling back to the
+    /// declared defaults. Unknown, missing and mistyped parameters are errors,
+    /// and the resulting workflow is validated. Every instance gets a new
+    /// workflow ID and records the template name and resolved parameters under
+    /// its `template` metadata.
+    pub fn instantiate(
+        &self,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<Workflow> {
+        let mut unknown: Vec<&str> = params
+            .keys()
+            .filter(|name| !self.parameters.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            return Err(self.error(format!(
+                "Unknown parameters: {} (declared: {})",
+                unknown.join(", "),
+                self.parameters
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        let mut resolved = serde_json::Map::new();
+        let mut missing = Vec::new();
+        for (name, parameter) in &self.parameters {
+            let Some(value) = params.get(name).or(parameter.default.as_ref()) else {
+                missing.push(name.as_str());
+                continue;
+            };
+            if !parameter.param_type.matches(value) {
+                return Err(self.error(format!(
+                    "Parameter '{name}' must be of type {}, got {value}",
+                    parameter.param_type
+                )));
+            }
+            resolved.insert(name.clone(), value.clone());
+        }
+        if !missing.is_empty() {
+            return Err(self.error(format!(
+                "Missing required parameters: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let mut definition = self.workflow.clone();
+        substitute(&mut definition, &resolved)?;
+        let mut workflow = Workflow::from_json(&definition.to_string())
+            .map_err(|e| self.error(format!("Invalid workflow definition: {e}")))?;
+        workflow.id = WorkflowId::new();
+        workflow.set_metadata(
+            "template".to_string(),
+            serde_json::json!({ "name": self.name, "parameters": resolved }),
+        );
+        workflow.validate()?;
+        Ok(workflow)
+    }
+
+    fn error(&self, message: impl fmt::Display) -> GraphBitError {
+        GraphBitError::validation("params", format!("Template '{}': {message}", self.name))
+    }
+}
+
+/// Whether `path` names a YAML file
+#[must_use]
+pub fn is_yaml_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"))
+}
+
+/// Parse YAML into the JSON value it describes; `what` names the document in
+/// errors
+pub fn parse_yaml(yaml: &str, what: &str) -> GraphBitResult<serde_json::Value> {
+    serde_yaml::from_str(yaml).map_err(|e| GraphBitError::config(format!("Invalid {what}: {e}")))
+}
+
+/// Names of the parameters referenced by the strings in `value`
+fn collect_references(value: &serde_json::Value, names: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::String(text) => {
+            for cap in PARAM_REF_PATTERN.captures_iter(text) {
+                names.insert(cap[1].to_string());
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_references(item, names);
+            }
+        }
+        serde_json::Value::Object(entries) => {
+            for item in entries.values() {
+                collect_references(item, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace the `{{params.NAME}}` references in the strings of `value`
+fn substitute(
+    value: &mut serde_json::Value,
+    params: &serde_json::Map<String, serde_json::Value>,
+) -> GraphBitResult<()> {
+    match value {
+        serde_json::Value::String(text) => {
+            if !text.contains("{{params.") {
+                return Ok(());
+            }
+            // A lone reference keeps the parameter's type
+            if let Some(cap) = PARAM_REF_PATTERN.captures(text) {
+                if cap[0].len() == text.len() {
+                    if let Some(param) = params.get(&cap[1]) {
+                        *value = param.clone();
+                        return Ok(());
+                    }
+                }
+            }
+            let mut missing = None;
+            let substituted = PARAM_REF_PATTERN.replace_all(text, |cap: &regex::Captures<'_>| {
+                match params.get(&cap[1]) {
+                    Some(serde_json::Value::String(param)) => param.clone(),
+                    Some(param) => param.to_string(),
+                    None => {
+                        missing.get_or_insert_with(|| cap[1].to_string());
+                        String::new()
+                    }
+                }
+            });
+            if let Some(name) = missing {
+                return Err(GraphBitError::validation(
+                    "params",
+                    format!("Parameter '{name}' is referenced but not declared"),
+                ));
+            }
+            *text = substituted.into_owned();
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                substitute(item, params)?;
+            }
+        }
+        serde_json::Value::Object(entries) => {
+            for item in entries.values_mut() {
+                substitute(item, params)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Workflow templates by name
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: HashMap<String, WorkflowTemplate>,
+}
+
+impl TemplateRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate and register a template under its name. A template with the same
+    /// name must be removed first.
+    pub fn register(&mut self, template: WorkflowTemplate) -> GraphBitResult<()> {
+        template.validate()?;
+        if self.templates.contains_key(&template.name) {
+            return Err(GraphBitError::validation(
+                "name",
+                format!(
+                    "Workflow template '{}' is already registered",
+                    template.name
+                ),
+            ));
+        }
+        self.templates.insert(template.name.clone(), template);
+        Ok(())
+    }
+
+    /// Load a template from a YAML or JSON file and register it, returning its
+    /// name
+    pub fn register_file(&mut self, path: impl AsRef<Path>) -> GraphBitResult<String> {
+        let template = WorkflowTemplate::from_file(path)?;
+        let name = template.name.clone();
+        self.register(template)?;
+        Ok(name)
+    }
+
+    /// Remove a template
+    pub fn remove(&mut self, name: &str) -> Option<WorkflowTemplate> {
+        self.templates.remove(name)
+    }
+
+    /// Get a template
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&WorkflowTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Names of the registered templates, sorted
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.templates.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Number of registered templates
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Whether no templates are registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Instantiate the template registered as `name`; see [`WorkflowTemplate::instantiate`]
+    pub fn instantiate(
+        &self,
+        name: &str,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<Workflow> {
+        self.get(name)
+            .ok_or_else(|| {
+                GraphBitError::validation(
+                    "name",
+                    format!(
+                        "Unknown workflow template '{name}' (registered: {})",
+                        self.names().join(", ")
+                    ),
+                )
+            })?
+            .instantiate(params)
+    }
+}
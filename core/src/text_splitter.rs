@@ -3,10 +3,12 @@
 //! This module provides various text splitting strategies for processing
 //! large documents into manageable chunks while maintaining context.
 
+use crate::document_loader::DocumentContent;
 use crate::errors::{GraphBitError, GraphBitResult};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Configuration for text splitting operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +68,14 @@ pub enum SplitterStrategy {
         chunk_overlap: usize,
         /// Optional custom sentence ending patterns
         sentence_endings: Option<Vec<String>>,
+        /// Abbreviations a period does not end a sentence after, added to
+        /// [`DEFAULT_ABBREVIATIONS`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        abbreviations: Option<Vec<String>>,
+        /// Find boundaries with Unicode sentence segmentation (UAX #29)
+        /// instead of the ending patterns
+        #[serde(default)]
+        unicode_segmentation: bool,
     },
     /// Recursive splitting with multiple separators
     Recursive {
@@ -121,6 +131,24 @@ pub enum SplitterStrategy {
     },
 }
 
+impl SplitterStrategy {
+    /// Lowercase name of the strategy, such as `"markdown"`
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Character { .. } => "character",
+            Self::Token { .. } => "token",
+            Self::Sentence { .. } => "sentence",
+            Self::Recursive { .. } => "recursive",
+            Self::Paragraph { .. } => "paragraph",
+            Self::Semantic { .. } => "semantic",
+            Self::Markdown { .. } => "markdown",
+            Self::Code { .. } => "code",
+            Self::Regex { .. } => "regex",
+        }
+    }
+}
+
 /// A text chunk with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextChunk {
@@ -449,19 +477,51 @@ impl TextSplitterTrait for TokenSplitter {
     }
 }
 
+/// English titles, Latin, citation, month and dosing abbreviations a period
+/// does not end a sentence after, lowercase and without their final period
+pub const DEFAULT_ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "rev", "hon", "gen", "col", "capt", "lt",
+    "sgt", "gov", "sen", "rep", "esq", "ph.d", "m.d", "e.g", "i.e", "etc", "cf", "vs", "v", "viz",
+    "al", "ibid", "op", "cit", "approx", "ca", "fig", "figs", "no", "nos", "vol", "vols", "p",
+    "pp", "ch", "sec", "art", "para", "ed", "eds", "inc", "ltd", "co", "corp", "dept", "univ",
+    "ave", "blvd", "rd", "u.s", "u.k", "mt", "jan", "feb", "mar", "apr", "jun", "jul", "aug",
+    "sep", "sept", "oct", "nov", "dec", "dx", "hx", "rx", "tx", "b.i.d", "t.i.d", "q.i.d", "q.d",
+    "p.r.n",
+];
+
+/// Opening and closing brackets and quotes a sentence does not end inside
+const ENCLOSING_PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('“', '”'),
+    ('「', '」'),
+    ('『', '』'),
+    ('（', '）'),
+];
+
+/// Characters closing a quote or bracket after a sentence's final punctuation
+const CLOSING_CHARS: &[char] = &['"', '\'', ')', ']', '”', '’', '」', '』', '）'];
+
 /// Sentence-based text splitter
+///
+/// Boundaries come from the ending patterns, or from Unicode sentence
+/// segmentation when enabled. Either way a boundary is skipped after an
+/// abbreviation or initial, before a lowercase word, inside a number and
+/// inside brackets or quotes; line breaks always end a sentence.
 pub struct SentenceSplitter {
     config: TextSplitterConfig,
     chunk_size: usize,
     chunk_overlap: usize,
     sentence_pattern: Regex,
+    abbreviations: HashSet<String>,
+    unicode_segmentation: bool,
 }
 
 impl SentenceSplitter {
     /// Create a new sentence splitter
     pub fn new(chunk_size: usize, chunk_overlap: usize) -> GraphBitResult<Self> {
         let default_endings = vec![
-            r"[.!?]+[\s\n]+",
+            r#"[.!?]+["'”’)\]]*[\s\n]+"#,
             r"[。！？]+[\s\n]*", // Chinese/Japanese
             r"[\n\r]+",          // Newlines as sentence boundaries
         ];
@@ -492,6 +552,8 @@ impl SentenceSplitter {
                 chunk_size,
                 chunk_overlap,
                 sentence_endings: Some(endings.iter().map(ToString::to_string).collect()),
+                abbreviations: None,
+                unicode_segmentation: false,
             },
             ..Default::default()
         };
@@ -501,22 +563,75 @@ impl SentenceSplitter {
             chunk_size,
             chunk_overlap,
             sentence_pattern,
+            abbreviations: DEFAULT_ABBREVIATIONS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            unicode_segmentation: false,
         })
     }
-}
 
-impl TextSplitterTrait for SentenceSplitter {
-    fn split_text(&self, text: &str) -> GraphBitResult<Vec<TextChunk>> {
-        if text.is_empty() {
-            return Ok(Vec::new());
+    /// Add abbreviations a period does not end a sentence after, such as
+    /// `"Art"` or `"cl."`, to the defaults
+    #[must_use]
+    pub fn with_abbreviations<I, S>(mut self, abbreviations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let added: Vec<String> = abbreviations.into_iter().map(Into::into).collect();
+        self.abbreviations
+            .extend(added.iter().map(|a| normalize_abbreviation(a)));
+        if let SplitterStrategy::Sentence {
+            abbreviations: configured,
+            ..
+        } = &mut self.config.strategy
+        {
+            configured.get_or_insert_with(Vec::new).extend(added);
         }
+        self
+    }
+
+    /// Find boundaries with Unicode sentence segmentation (UAX #29) instead
+    /// of the ending patterns, for scripts the patterns do not cover
+    #[must_use]
+    pub fn with_unicode_segmentation(mut self, enabled: bool) -> Self {
+        self.unicode_segmentation = enabled;
+        if let SplitterStrategy::Sentence {
+            unicode_segmentation,
+            ..
+        } = &mut self.config.strategy
+        {
+            *unicode_segmentation = enabled;
+        }
+        self
+    }
+
+    /// Split `text` into `(sentence, start, end)` spans covering it
+    fn sentences<'a>(&self, text: &'a str) -> Vec<(&'a str, usize, usize)> {
+        // Candidate boundaries as the span of the ending they follow
+        let candidates: Vec<(usize, usize)> = if self.unicode_segmentation {
+            text.split_sentence_bound_indices()
+                .map(|(start, piece)| {
+                    let end = start + piece.len();
+                    (start + ending_start(piece), end)
+                })
+                .collect()
+        } else {
+            self.sentence_pattern
+                .find_iter(text)
+                .map(|m| (m.start(), m.end()))
+                .collect()
+        };
 
-        // Split text into sentences
         let mut sentences = Vec::new();
         let mut last_end = 0;
-
-        for mat in self.sentence_pattern.find_iter(text) {
-            let sentence_end = mat.end();
+        for (ending_start, sentence_end) in candidates {
+            if sentence_end <= last_end
+                || !self.is_boundary(text, last_end, ending_start, sentence_end)
+            {
+                continue;
+            }
             let sentence = &text[last_end..sentence_end];
             if !sentence.trim().is_empty() {
                 sentences.push((sentence, last_end, sentence_end));
@@ -531,6 +646,90 @@ impl TextSplitterTrait for SentenceSplitter {
                 sentences.push((sentence, last_end, text.len()));
             }
         }
+        sentences
+    }
+
+    /// Whether the ending at `ending_start..ending_end` closes the sentence
+    /// begun at `sentence_start`
+    fn is_boundary(
+        &self,
+        text: &str,
+        sentence_start: usize,
+        ending_start: usize,
+        ending_end: usize,
+    ) -> bool {
+        let ending = &text[ending_start..ending_end];
+        if ending.is_empty() || ending.contains(['\n', '\r']) {
+            return true;
+        }
+        if enclosing_open(&text[sentence_start..ending_end]) {
+            return false;
+        }
+        if !ending.starts_with('.') {
+            return true;
+        }
+
+        let before = &text[sentence_start..ending_start];
+        let after = &text[ending_start + 1..];
+        // Decimal numbers such as 3.14
+        if before.ends_with(|c: char| c.is_ascii_digit())
+            && after.starts_with(|c: char| c.is_ascii_digit())
+        {
+            return false;
+        }
+        // Abbreviations and initials such as "Dr." or "J."
+        let word = before
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(|c: char| !c.is_alphanumeric());
+        let mut letters = word.chars();
+        let initial =
+            matches!((letters.next(), letters.next()), (Some(c), None) if c.is_uppercase());
+        if initial || self.abbreviations.contains(&word.to_lowercase()) {
+            return false;
+        }
+        // A sentence does not continue in lowercase
+        !text[ending_end..]
+            .trim_start()
+            .starts_with(char::is_lowercase)
+    }
+}
+
+/// Lowercase `abbreviation` without its final period
+fn normalize_abbreviation(abbreviation: &str) -> String {
+    abbreviation.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Offset in `piece` of its final punctuation, closing quotes and whitespace
+fn ending_start(piece: &str) -> usize {
+    let content = piece.trim_end().trim_end_matches(CLOSING_CHARS);
+    let body = content.trim_end_matches(|c: char| {
+        !c.is_alphanumeric() && !c.is_whitespace() && !CLOSING_CHARS.contains(&c)
+    });
+    if body.len() == content.len() {
+        piece.trim_end().len()
+    } else {
+        body.len()
+    }
+}
+
+/// Whether `text` leaves a bracket or quote open
+fn enclosing_open(text: &str) -> bool {
+    text.matches('"').count() % 2 == 1
+        || ENCLOSING_PAIRS
+            .iter()
+            .any(|&(open, close)| text.matches(open).count() > text.matches(close).count())
+}
+
+impl TextSplitterTrait for SentenceSplitter {
+    fn split_text(&self, text: &str) -> GraphBitResult<Vec<TextChunk>> {
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Split text into sentences
+        let sentences = self.sentences(text);
 
         // Group sentences into chunks
         let mut chunks = Vec::new();
@@ -825,6 +1024,500 @@ impl TextSplitterTrait for RecursiveSplitter {
     }
 }
 
+/// Places a structure-aware splitter may cut text, most preferred first.
+/// Text is cut at the start of capture group 1 when the pattern has one,
+/// otherwise right after the match.
+type Boundaries = Vec<Regex>;
+
+fn boundaries(patterns: &[&str]) -> Boundaries {
+    patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("valid boundary pattern"))
+        .collect()
+}
+
+/// Boundaries every structure-aware splitter falls back to
+const TEXT_BOUNDARIES: &[&str] = &[r"\n[ \t]*\n", r"\n", r"[.!?][ \t]+", r"[ \t]+"];
+
+/// Cut `text[start..end]` into ranges of at most `chunk_size` bytes, cutting
+/// at the first level of `levels` that occurs in it and merging neighbouring
+/// pieces while they fit
+fn boundary_ranges(
+    text: &str,
+    start: usize,
+    end: usize,
+    levels: &[Regex],
+    chunk_size: usize,
+    ranges: &mut Vec<(usize, usize)>,
+) {
+    if end - start <= chunk_size {
+        ranges.push((start, end));
+        return;
+    }
+
+    let Some((level, finer)) = levels.split_first() else {
+        // No boundary left: cut at character boundaries
+        let mut cut_start = start;
+        while cut_start < end {
+            let mut cut_end = (cut_start + chunk_size).min(end);
+            while !text.is_char_boundary(cut_end) {
+                cut_end -= 1;
+            }
+            if cut_end == cut_start {
+                cut_end = text[cut_start..]
+                    .chars()
+                    .next()
+                    .map_or(end, |c| cut_start + c.len_utf8());
+            }
+            ranges.push((cut_start, cut_end));
+            cut_start = cut_end;
+        }
+        return;
+    };
+
+    let cuts: Vec<usize> = level
+        .captures_iter(&text[start..end])
+        .filter_map(|captures| {
+            let cut = match captures.get(1) {
+                Some(group) => group.start(),
+                None => captures.get(0)?.end(),
+            };
+            Some(start + cut)
+        })
+        .collect();
+    merge_pieces(text, start, end, cuts, finer, chunk_size, ranges);
+}
+
+/// Cut `text[start..end]` at `cuts`, merging neighbouring pieces while they
+/// fit in `chunk_size` bytes and cutting larger pieces at the `finer` levels
+fn merge_pieces(
+    text: &str,
+    start: usize,
+    end: usize,
+    mut cuts: Vec<usize>,
+    finer: &[Regex],
+    chunk_size: usize,
+    ranges: &mut Vec<(usize, usize)>,
+) {
+    cuts.retain(|cut| *cut > start && *cut < end);
+    cuts.dedup();
+    if cuts.is_empty() {
+        boundary_ranges(text, start, end, finer, chunk_size, ranges);
+        return;
+    }
+
+    let mut chunk_start = start;
+    let mut piece_start = start;
+    for cut in cuts.into_iter().chain(std::iter::once(end)) {
+        if cut - chunk_start > chunk_size {
+            if piece_start > chunk_start {
+                ranges.push((chunk_start, piece_start));
+                chunk_start = piece_start;
+            }
+            if cut - chunk_start > chunk_size {
+                boundary_ranges(text, chunk_start, cut, finer, chunk_size, ranges);
+                chunk_start = cut;
+            }
+        }
+        piece_start = cut;
+    }
+    if chunk_start < end {
+        ranges.push((chunk_start, end));
+    }
+}
+
+/// Turn byte ranges of `text` into chunks numbered from `first_index`, each
+/// starting up to `chunk_overlap` bytes into the chunk before it
+fn chunks_from_ranges(
+    text: &str,
+    ranges: &[(usize, usize)],
+    chunk_overlap: usize,
+    trim_whitespace: bool,
+    first_index: usize,
+) -> Vec<TextChunk> {
+    let mut chunks: Vec<TextChunk> = Vec::with_capacity(ranges.len());
+    for &(range_start, range_end) in ranges {
+        let (mut start, mut end) = (range_start, range_end);
+        if trim_whitespace {
+            let content = &text[start..end];
+            start += content.len() - content.trim_start().len();
+            end -= content.len() - content.trim_end().len();
+        }
+        if start >= end {
+            continue;
+        }
+
+        if chunk_overlap > 0 {
+            if let Some(previous) = chunks.last() {
+                let mut overlap_start = start
+                    .saturating_sub(chunk_overlap)
+                    .max(previous.start_index);
+                while !text.is_char_boundary(overlap_start) {
+                    overlap_start += 1;
+                }
+                if trim_whitespace {
+                    let overlap = &text[overlap_start..start];
+                    overlap_start += overlap.len() - overlap.trim_start().len();
+                }
+                start = overlap_start;
+            }
+        }
+
+        chunks.push(TextChunk::new(
+            text[start..end].to_string(),
+            start,
+            end,
+            first_index + chunks.len(),
+        ));
+    }
+    chunks
+}
+
+/// Markdown splitter that keeps sections, fenced code and paragraphs
+/// together where it can
+pub struct MarkdownSplitter {
+    config: TextSplitterConfig,
+    chunk_size: usize,
+    chunk_overlap: usize,
+    split_by_headers: bool,
+    boundaries: Boundaries,
+}
+
+impl MarkdownSplitter {
+    /// Create a new Markdown splitter. With `split_by_headers` every section
+    /// starts a new chunk and its chunks carry the section's `heading` in
+    /// their metadata.
+    pub fn new(
+        chunk_size: usize,
+        chunk_overlap: usize,
+        split_by_headers: bool,
+    ) -> GraphBitResult<Self> {
+        if chunk_size == 0 {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk size must be greater than 0",
+            ));
+        }
+
+        if chunk_overlap >= chunk_size {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk overlap must be less than chunk size",
+            ));
+        }
+
+        let config = TextSplitterConfig {
+            strategy: SplitterStrategy::Markdown {
+                chunk_size,
+                chunk_overlap,
+                split_by_headers,
+            },
+            ..Default::default()
+        };
+
+        let mut patterns = vec![r"\n(```|~~~)"];
+        patterns.extend_from_slice(TEXT_BOUNDARIES);
+
+        Ok(Self {
+            config,
+            chunk_size,
+            chunk_overlap,
+            split_by_headers,
+            boundaries: boundaries(&patterns),
+        })
+    }
+
+    /// Byte offsets where sections start, with their heading text; headers
+    /// inside fenced code do not count
+    fn sections(text: &str) -> Vec<(usize, Option<String>)> {
+        let mut sections = vec![(0, None)];
+        let mut in_fence = false;
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim_end();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            } else if !in_fence {
+                let level = trimmed.chars().take_while(|c| *c == '#').count();
+                let rest = &trimmed[level..];
+                if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])) {
+                    let heading = rest.trim().to_string();
+                    if offset == 0 {
+                        sections[0].1 = Some(heading);
+                    } else {
+                        sections.push((offset, Some(heading)));
+                    }
+                }
+            }
+            offset += line.len();
+        }
+
+        sections
+    }
+}
+
+impl TextSplitterTrait for MarkdownSplitter {
+    fn split_text(&self, text: &str) -> GraphBitResult<Vec<TextChunk>> {
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sections = Self::sections(text);
+        if !self.split_by_headers {
+            // Sections are only the preferred places to cut
+            let mut ranges = Vec::new();
+            merge_pieces(
+                text,
+                0,
+                text.len(),
+                sections.iter().map(|(start, _)| *start).collect(),
+                &self.boundaries,
+                self.chunk_size,
+                &mut ranges,
+            );
+            return Ok(chunks_from_ranges(
+                text,
+                &ranges,
+                self.chunk_overlap,
+                self.config.trim_whitespace,
+                0,
+            ));
+        }
+
+        let mut chunks = Vec::new();
+        for (index, (start, heading)) in sections.iter().enumerate() {
+            let end = sections.get(index + 1).map_or(text.len(), |next| next.0);
+            let mut ranges = Vec::new();
+            boundary_ranges(
+                text,
+                *start,
+                end,
+                &self.boundaries,
+                self.chunk_size,
+                &mut ranges,
+            );
+
+            let section_chunks = chunks_from_ranges(
+                text,
+                &ranges,
+                self.chunk_overlap,
+                self.config.trim_whitespace,
+                chunks.len(),
+            );
+            chunks.extend(section_chunks.into_iter().map(|chunk| match heading {
+                Some(heading) => chunk.with_metadata(
+                    "heading".to_string(),
+                    serde_json::Value::String(heading.clone()),
+                ),
+                None => chunk,
+            }));
+        }
+
+        Ok(chunks)
+    }
+
+    fn config(&self) -> &TextSplitterConfig {
+        &self.config
+    }
+
+    fn validate_config(&self) -> GraphBitResult<()> {
+        if self.chunk_size == 0 {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk size must be greater than 0",
+            ));
+        }
+        if self.chunk_overlap >= self.chunk_size {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk overlap must be less than chunk size",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Leading doc comments, attributes and decorators that belong to the item
+/// after them
+const ITEM_PREFIX: &str = r"(?:[ \t]*(?:///|//!|#\[|@).*\n)*";
+
+/// Source splitter that cuts between top-level items before it cuts inside
+/// them
+pub struct CodeSplitter {
+    config: TextSplitterConfig,
+    chunk_size: usize,
+    chunk_overlap: usize,
+    language: Option<String>,
+    boundaries: Boundaries,
+}
+
+impl CodeSplitter {
+    /// Languages with syntax-aware boundaries
+    pub const LANGUAGES: &'static [&'static str] = &[
+        "rust",
+        "python",
+        "javascript",
+        "typescript",
+        "go",
+        "java",
+        "c",
+        "cpp",
+    ];
+
+    /// Create a new code splitter. Languages outside [`Self::LANGUAGES`]
+    /// split between blank lines, then lines.
+    pub fn new(
+        chunk_size: usize,
+        chunk_overlap: usize,
+        language: Option<String>,
+    ) -> GraphBitResult<Self> {
+        if chunk_size == 0 {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk size must be greater than 0",
+            ));
+        }
+
+        if chunk_overlap >= chunk_size {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk overlap must be less than chunk size",
+            ));
+        }
+
+        let language = language.map(|language| language.to_lowercase());
+        let config = TextSplitterConfig {
+            strategy: SplitterStrategy::Code {
+                chunk_size,
+                chunk_overlap,
+                language: language.clone(),
+            },
+            trim_whitespace: false,
+            ..Default::default()
+        };
+
+        let items = match language.as_deref() {
+            Some("rust") => vec![
+                format!(
+                    r"\n({ITEM_PREFIX}(?:pub(?:\([^)\n]*\))? )?(?:async |const |unsafe )*(?:fn|impl|struct|enum|trait|mod|type|static|const|macro_rules!)\b)"
+                ),
+                format!(
+                    r"\n({ITEM_PREFIX}[ \t]+(?:pub(?:\([^)\n]*\))? )?(?:async |const |unsafe )*fn\b)"
+                ),
+            ],
+            Some("python") => vec![
+                r"\n((?:@.*\n)*(?:async def|def|class)\b)".to_string(),
+                r"\n((?:[ \t]+@.*\n)*[ \t]+(?:async def|def)\b)".to_string(),
+            ],
+            Some("javascript" | "typescript") => vec![format!(
+                r"\n({ITEM_PREFIX}(?:export (?:default )?)?(?:declare )?(?:async )?(?:function\b|class\b|interface\b|enum\b|type\b|const\b|let\b|var\b))"
+            )],
+            Some("go") => vec![r"\n((?://.*\n)*(?:func|type|var|const)\b)".to_string()],
+            Some("java") => vec![
+                format!(
+                    r"\n({ITEM_PREFIX}(?:(?:public|protected|private|abstract|final|sealed|static) )*(?:class|interface|enum|record)\b)"
+                ),
+                format!(
+                    r"\n({ITEM_PREFIX}[ \t]+(?:(?:public|protected|private|static|final|abstract|synchronized) )+[^\n;=]*\()"
+                ),
+            ],
+            Some("c" | "cpp") => vec![r"\n\}[ \t]*;?[ \t]*\n".to_string()],
+            _ => Vec::new(),
+        };
+        let mut patterns: Vec<&str> = items.iter().map(String::as_str).collect();
+        patterns.extend_from_slice(TEXT_BOUNDARIES);
+
+        Ok(Self {
+            config,
+            chunk_size,
+            chunk_overlap,
+            language,
+            boundaries: boundaries(&patterns),
+        })
+    }
+
+    /// Language of a source file extension such as `rs` or `.py`, if it is
+    /// one of [`Self::LANGUAGES`]
+    #[must_use]
+    pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+        match extension
+            .trim()
+            .trim_start_matches('.')
+            .to_lowercase()
+            .as_str()
+        {
+            "rs" => Some("rust"),
+            "py" | "pyi" => Some("python"),
+            "js" | "jsx" | "mjs" | "cjs" => Some("javascript"),
+            "ts" | "tsx" | "mts" | "cts" => Some("typescript"),
+            "go" => Some("go"),
+            "java" => Some("java"),
+            "c" | "h" => Some("c"),
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => Some("cpp"),
+            _ => None,
+        }
+    }
+}
+
+impl TextSplitterTrait for CodeSplitter {
+    fn split_text(&self, text: &str) -> GraphBitResult<Vec<TextChunk>> {
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut ranges = Vec::new();
+        boundary_ranges(
+            text,
+            0,
+            text.len(),
+            &self.boundaries,
+            self.chunk_size,
+            &mut ranges,
+        );
+
+        let chunks = chunks_from_ranges(
+            text,
+            &ranges,
+            self.chunk_overlap,
+            self.config.trim_whitespace,
+            0,
+        );
+        Ok(match &self.language {
+            Some(language) => chunks
+                .into_iter()
+                .map(|chunk| {
+                    chunk.with_metadata(
+                        "language".to_string(),
+                        serde_json::Value::String(language.clone()),
+                    )
+                })
+                .collect(),
+            None => chunks,
+        })
+    }
+
+    fn config(&self) -> &TextSplitterConfig {
+        &self.config
+    }
+
+    fn validate_config(&self) -> GraphBitResult<()> {
+        if self.chunk_size == 0 {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk size must be greater than 0",
+            ));
+        }
+        if self.chunk_overlap >= self.chunk_size {
+            return Err(GraphBitError::validation(
+                "text_splitter",
+                "Chunk overlap must be less than chunk size",
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Factory for creating text splitters
 pub struct TextSplitterFactory;
 
@@ -860,20 +1553,20 @@ impl TextSplitterFactory {
                 chunk_size,
                 chunk_overlap,
                 sentence_endings,
+                abbreviations,
+                unicode_segmentation,
             } => {
-                if let Some(endings) = sentence_endings {
+                let splitter = if let Some(endings) = sentence_endings {
                     let endings_refs: Vec<&str> = endings.iter().map(String::as_str).collect();
-                    Ok(Box::new(SentenceSplitter::with_endings(
-                        *chunk_size,
-                        *chunk_overlap,
-                        endings_refs,
-                    )?))
+                    SentenceSplitter::with_endings(*chunk_size, *chunk_overlap, endings_refs)?
                 } else {
-                    Ok(Box::new(SentenceSplitter::new(
-                        *chunk_size,
-                        *chunk_overlap,
-                    )?))
-                }
+                    SentenceSplitter::new(*chunk_size, *chunk_overlap)?
+                };
+                Ok(Box::new(
+                    splitter
+                        .with_abbreviations(abbreviations.iter().flatten().cloned())
+                        .with_unicode_segmentation(*unicode_segmentation),
+                ))
             }
             SplitterStrategy::Recursive {
                 chunk_size,
@@ -893,12 +1586,130 @@ impl TextSplitterFactory {
                     )?))
                 }
             }
+            SplitterStrategy::Markdown {
+                chunk_size,
+                chunk_overlap,
+                split_by_headers,
+            } => Ok(Box::new(MarkdownSplitter::new(
+                *chunk_size,
+                *chunk_overlap,
+                *split_by_headers,
+            )?)),
+            SplitterStrategy::Code {
+                chunk_size,
+                chunk_overlap,
+                language,
+            } => Ok(Box::new(CodeSplitter::new(
+                *chunk_size,
+                *chunk_overlap,
+                language.clone(),
+            )?)),
             _ => Err(GraphBitError::validation(
                 "text_splitter",
                 format!("Unsupported splitter strategy: {:?}", config.strategy),
             )),
         }
     }
+
+    /// Create the splitter suited to a document type or file extension:
+    /// Markdown for `md`, code for the extensions of
+    /// [`CodeSplitter::LANGUAGES`], sentences for prose (`pdf`, `docx`,
+    /// `txt`) and recursive splitting for anything else. Prose chunks
+    /// overlap by one sentence when `chunk_overlap` is not zero.
+    pub fn for_document(
+        document_type: &str,
+        chunk_size: usize,
+        chunk_overlap: usize,
+    ) -> GraphBitResult<Box<dyn TextSplitterTrait>> {
+        let document_type = document_type.trim().trim_start_matches('.').to_lowercase();
+        let strategy = match document_type.as_str() {
+            "md" | "markdown" => SplitterStrategy::Markdown {
+                chunk_size,
+                chunk_overlap,
+                split_by_headers: true,
+            },
+            // The sentence splitter overlaps whole sentences
+            "pdf" | "docx" | "txt" => SplitterStrategy::Sentence {
+                chunk_size,
+                chunk_overlap: usize::from(chunk_overlap > 0),
+                sentence_endings: None,
+                abbreviations: None,
+                unicode_segmentation: false,
+            },
+            other => CodeSplitter::language_for_extension(other).map_or(
+                SplitterStrategy::Recursive {
+                    chunk_size,
+                    chunk_overlap,
+                    separators: None,
+                },
+                |language| SplitterStrategy::Code {
+                    chunk_size,
+                    chunk_overlap,
+                    language: Some(language.to_string()),
+                },
+            ),
+        };
+
+        Self::create_splitter(TextSplitterConfig {
+            strategy,
+            ..Default::default()
+        })
+    }
+}
+
+/// Chunk sizes for [`chunk_document`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChunkConfig {
+    /// Maximum size of each chunk in characters
+    pub chunk_size: usize,
+    /// Number of characters to overlap between chunks
+    pub chunk_overlap: usize,
+}
+
+impl Default for DocumentChunkConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1000,
+            chunk_overlap: 200,
+        }
+    }
+}
+
+/// Split a loaded document with the splitter suited to its type
+///
+/// The splitter comes from [`TextSplitterFactory::for_document`]. Every chunk
+/// carries the document's `source` and `document_type` and the `splitter`
+/// strategy that made it in its metadata.
+pub fn chunk_document(
+    document: &DocumentContent,
+    config: &DocumentChunkConfig,
+) -> GraphBitResult<Vec<TextChunk>> {
+    let splitter = TextSplitterFactory::for_document(
+        &document.document_type,
+        config.chunk_size,
+        config.chunk_overlap,
+    )?;
+    let strategy = splitter.config().strategy.name();
+
+    Ok(splitter
+        .split_text(&document.content)?
+        .into_iter()
+        .map(|chunk| {
+            chunk
+                .with_metadata(
+                    "source".to_string(),
+                    serde_json::Value::String(document.source.clone()),
+                )
+                .with_metadata(
+                    "document_type".to_string(),
+                    serde_json::Value::String(document.document_type.clone()),
+                )
+                .with_metadata(
+                    "splitter".to_string(),
+                    serde_json::Value::String(strategy.to_string()),
+                )
+        })
+        .collect())
 }
 
 #[cfg(test)]
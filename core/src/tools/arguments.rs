@@ -0,0 +1,115 @@
+//! Validation of model-supplied tool arguments
+//!
+//! Models regularly invent argument names or send a string where a number is
+//! expected. Arguments are checked against the tool's declared parameter
+//! schema before the tool runs; failures are reported back to the model as a
+//! structured tool message so it can correct the call on its next turn.
+
+use crate::errors::GraphBitError;
+use crate::validation::{TypeValidator, ValidationError};
+use std::fmt;
+
+/// Arguments of a tool call did not match the tool's parameter schema
+#[derive(Debug, Clone)]
+pub struct ToolArgumentError {
+    /// Name of the tool that was called
+    pub tool_name: String,
+    /// Individual schema violations
+    pub errors: Vec<ValidationError>,
+}
+
+impl ToolArgumentError {
+    /// Structured JSON error returned to the model in place of the tool output
+    #[must_use]
+    pub fn to_tool_message(&self) -> String {
+        let details: Vec<serde_json::Value> = self
+            .errors
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "argument": argument_name(&e.field_path),
+                    "problem": e.message,
+                    "expected": e.expected,
+                    "received": e.actual,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "error": "invalid_tool_arguments",
+            "tool": self.tool_name,
+            "message": format!(
+                "The arguments for tool '{}' do not match its parameter schema. \
+                 Fix the arguments listed in 'details' and call the tool again.",
+                self.tool_name
+            ),
+            "details": details,
+        })
+        .to_string()
+    }
+}
+
+impl fmt::Display for ToolArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", argument_name(&e.field_path), e.message))
+            .collect();
+        write!(
+            f,
+            "Invalid arguments for tool '{}': {}",
+            self.tool_name,
+            problems.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for ToolArgumentError {}
+
+impl From<ToolArgumentError> for GraphBitError {
+    fn from(err: ToolArgumentError) -> Self {
+        Self::validation("tool_arguments", err.to_string())
+    }
+}
+
+/// Validate tool call arguments against the tool's JSON parameter schema.
+///
+/// Arguments not declared in the schema's `properties` follow
+/// `additionalProperties`: allowed when it is absent or `true`, rejected when
+/// it is `false`, and validated against it when it is a schema.
+pub fn validate_tool_arguments(
+    tool_name: &str,
+    schema: &serde_json::Value,
+    arguments: &serde_json::Value,
+) -> Result<(), ToolArgumentError> {
+    let mut result = TypeValidator::new().validate_value(arguments, schema);
+
+    // Name the declared arguments on rejected extras so the model can pick one
+    if let Some(declared) = schema.get("properties").and_then(|p| p.as_object()) {
+        let expected = declared.keys().cloned().collect::<Vec<_>>().join(", ");
+        for error in &mut result.errors {
+            let name = argument_name(&error.field_path);
+            if error.error_code == "UNKNOWN_PROPERTY" && !name.contains('.') {
+                error.message = format!("Unknown argument '{name}'");
+                error.expected = Some(expected.clone());
+            }
+        }
+    }
+
+    if result.is_valid {
+        Ok(())
+    } else {
+        Err(ToolArgumentError {
+            tool_name: tool_name.to_string(),
+            errors: result.errors,
+        })
+    }
+}
+
+fn argument_name(field_path: &str) -> &str {
+    match field_path {
+        "root" => "(arguments)",
+        path => path.strip_prefix("root.").unwrap_or(path),
+    }
+}
@@ -0,0 +1,183 @@
+//! Context of the node a tool is called from
+//!
+//! While an agent node runs registered tools, [`ToolCallContext::current`]
+//! returns the node's idempotency key, so tools with external side effects (a
+//! payment, a database write) can dedupe the repeated calls a node retry makes.
+//!
+//! Tools registered with [`crate::tools::ToolRegistry::register_context_tool`]
+//! also receive a [`ToolContext`]: a snapshot of the workflow's node outputs
+//! and variables, and a scratch space whose writes later nodes read in their
+//! prompts as `{{scratch.NAME}}`.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::types::{NodeOutputs, WorkflowContext};
+
+/// Namespace of the keys tools write with [`ToolContext::set`], and the
+/// [`WorkflowContext`] metadata entry holding the written values
+pub const SCRATCH_NAMESPACE: &str = "scratch";
+
+tokio::task_local! {
+    static TOOL_CALL_CONTEXT: ToolCallContext;
+}
+
+/// Node-level context of a tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCallContext {
+    /// Name of the agent node calling the tool
+    pub node_name: String,
+    /// Idempotency key of the node, the same across its retries and different
+    /// for other nodes and executions
+    pub idempotency_key: String,
+    /// Number of earlier attempts of the node; 0 on the first attempt
+    pub attempt: u32,
+    /// Key an earlier attempt of the node already used, if this is a retry
+    pub previous_attempt_key: Option<String>,
+}
+
+impl ToolCallContext {
+    /// Context for attempt `attempt` (0-based) of the node `node_name`
+    #[must_use]
+    pub fn new(
+        node_name: impl Into<String>,
+        idempotency_key: impl Into<String>,
+        attempt: u32,
+    ) -> Self {
+        let idempotency_key = idempotency_key.into();
+        Self {
+            node_name: node_name.into(),
+            previous_attempt_key: (attempt > 0).then(|| idempotency_key.clone()),
+            idempotency_key,
+            attempt,
+        }
+    }
+
+    /// Context of the tool call being executed, when called from a tool
+    /// handler run by the workflow executor
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        TOOL_CALL_CONTEXT.try_with(Clone::clone).ok()
+    }
+
+    /// Run `future` with this context as the current one
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        TOOL_CALL_CONTEXT.scope(self, future).await
+    }
+}
+
+/// Workflow state a tool reads and writes while an agent node calls it
+///
+/// Reads see the workflow as it was when the tool was called. Writes are
+/// buffered and merged into the [`WorkflowContext`] once the tool succeeds; a
+/// failing tool's writes are dropped. Writes from a node's tool calls merge in
+/// the order the model requested the calls, and calls from parallel nodes in
+/// the order they finish: the last write of a key wins, and overwriting a value
+/// another tool call wrote logs a warning.
+#[derive(Debug, Clone, Default)]
+pub struct ToolContext {
+    node_name: String,
+    node_outputs: NodeOutputs,
+    variables: Arc<HashMap<String, serde_json::Value>>,
+    scratch: Arc<serde_json::Map<String, serde_json::Value>>,
+    writes: Arc<Mutex<Vec<(String, serde_json::Value)>>>,
+}
+
+impl ToolContext {
+    /// Context for a tool called by the node `node_name` of the workflow
+    /// executing in `context`
+    #[must_use]
+    pub fn new(context: &WorkflowContext, node_name: impl Into<String>) -> Self {
+        let node_name = node_name.into();
+        Self {
+            scratch: Arc::new(context.node_scratch(&node_name)),
+            node_name,
+            node_outputs: context.snapshot_node_outputs(),
+            variables: Arc::new(context.variables.clone()),
+            writes: Arc::default(),
+        }
+    }
+
+    /// Name of the agent node calling the tool; empty outside a workflow
+    #[must_use]
+    pub fn node_name(&self) -> &str {
+        &self.node_name
+    }
+
+    /// Output of a node, by node id or name
+    #[must_use]
+    pub fn node_output(&self, node: &str) -> Option<&serde_json::Value> {
+        self.node_outputs.get(node)
+    }
+
+    /// Outputs of the nodes completed so far, keyed by node id and name
+    #[must_use]
+    pub const fn node_outputs(&self) -> &NodeOutputs {
+        &self.node_outputs
+    }
+
+    /// Workflow variable, including run-time inputs
+    #[must_use]
+    pub fn variable(&self, name: &str) -> Option<&serde_json::Value> {
+        self.variables.get(name)
+    }
+
+    /// All workflow variables
+    #[must_use]
+    pub fn variables(&self) -> &HashMap<String, serde_json::Value> {
+        &self.variables
+    }
+
+    /// Value of a namespaced key such as `scratch.summary`, including this
+    /// tool call's own writes
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<serde_json::Value> {
+        let name = scratch_name(key).ok()?;
+        let written = self.writes.lock().ok().and_then(|writes| {
+            writes
+                .iter()
+                .find(|(written, _)| written == name)
+                .map(|(_, value)| value.clone())
+        });
+        written.or_else(|| self.scratch.get(name).cloned())
+    }
+
+    /// Write a namespaced key such as `scratch.summary`, read by later nodes'
+    /// prompts as `{{scratch.summary}}`
+    pub fn set(&self, key: &str, value: serde_json::Value) -> GraphBitResult<()> {
+        let name = scratch_name(key)?.to_string();
+        let mut writes = self
+            .writes
+            .lock()
+            .map_err(|e| GraphBitError::concurrency(format!("Tool context lock poisoned: {e}")))?;
+        writes.retain(|(written, _)| *written != name);
+        writes.push((name, value));
+        drop(writes);
+        Ok(())
+    }
+
+    /// Take the writes made so far, keyed by their name within the scratch
+    /// namespace, in the order they were made
+    #[must_use]
+    pub fn take_writes(&self) -> Vec<(String, serde_json::Value)> {
+        self.writes
+            .lock()
+            .map(|mut writes| std::mem::take(&mut *writes))
+            .unwrap_or_default()
+    }
+}
+
+/// Name of `key` within the scratch namespace
+fn scratch_name(key: &str) -> GraphBitResult<&str> {
+    key.strip_prefix(SCRATCH_NAMESPACE)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| {
+            GraphBitError::validation(
+                "key",
+                format!("Tool context keys must look like '{SCRATCH_NAMESPACE}.NAME', got '{key}'"),
+            )
+        })
+}
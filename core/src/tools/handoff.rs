@@ -0,0 +1,83 @@
+//! Built-in `handoff` tool for supervisor/worker agent patterns
+//!
+//! An agent node listing `handoff_targets` is offered a `handoff` tool. When the
+//! model calls it, the executor runs the chosen target agent node with the
+//! conversation so far and returns the worker's final answer as the tool result.
+
+use super::ToolMetadata;
+use crate::llm::{LlmMessage, LlmRole};
+
+/// Name of the tool injected into agent nodes with handoff targets
+pub const HANDOFF_TOOL_NAME: &str = "handoff";
+
+/// Describe the `handoff` tool offered to a node delegating to `targets`
+#[must_use]
+pub fn handoff_tool_metadata(targets: &[String]) -> ToolMetadata {
+    ToolMetadata::new(
+        HANDOFF_TOOL_NAME,
+        format!(
+            "Delegate the current task to another agent and receive its final answer. \
+             Available agents: {}",
+            targets.join(", ")
+        ),
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "target": {
+                    "type": "string",
+                    "enum": targets,
+                    "description": "Name of the agent to hand the task to"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Instructions for the agent, including everything it needs to know"
+                }
+            },
+            "required": ["target", "message"]
+        }),
+    )
+}
+
+/// Arguments of a `handoff` tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandoffRequest {
+    /// Name of the target agent node
+    pub target: String,
+    /// Instructions passed to the target agent
+    pub message: String,
+}
+
+impl HandoffRequest {
+    /// Parse the arguments of a `handoff` tool call
+    pub fn from_arguments(arguments: &serde_json::Value) -> Result<Self, String> {
+        let field = |name: &str| {
+            arguments
+                .get(name)
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("handoff requires a string '{name}' argument"))
+        };
+        Ok(Self {
+            target: field("target")?,
+            message: field("message")?,
+        })
+    }
+}
+
+/// The part of a supervisor's conversation carried over to a handoff target:
+/// user messages and assistant text, without system prompts or tool traffic
+#[must_use]
+pub fn handoff_transcript(messages: &[LlmMessage]) -> Vec<LlmMessage> {
+    messages
+        .iter()
+        .filter(|m| match m.role {
+            LlmRole::User => true,
+            LlmRole::Assistant => !m.content.trim().is_empty(),
+            LlmRole::System | LlmRole::Tool => false,
+        })
+        .map(|m| match m.role {
+            LlmRole::Assistant => LlmMessage::assistant(m.content.clone()),
+            _ => LlmMessage::user(m.content.clone()),
+        })
+        .collect()
+}
@@ -0,0 +1,357 @@
+//! Built-in `http_request` tool
+//!
+//! Lets an agent issue GET/POST requests with optional headers and a JSON body.
+//! HTML pages are converted to readable text using the document loader's HTML
+//! extraction, and response bodies are cut at the last character boundary
+//! within a configurable number of bytes.
+//! Hosts can be restricted with an allowlist and/or a denylist, which every
+//! redirect is checked against as well.
+
+use super::ToolMetadata;
+use crate::document_loader::DocumentLoader;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::http_client::HttpClientFactory;
+use reqwest::{Method, Url, redirect};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Redirects followed before a request fails
+const MAX_REDIRECTS: usize = 10;
+
+/// Name under which the tool is exposed to the model
+pub const HTTP_TOOL_NAME: &str = "http_request";
+
+/// Configuration for [`HttpRequestTool`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpToolConfig {
+    /// Maximum number of response body bytes returned to the model
+    pub max_response_bytes: usize,
+    /// Hosts the tool may contact. Empty means any host not denied.
+    /// Entries match the host itself and all of its subdomains.
+    pub allowed_hosts: Vec<String>,
+    /// Hosts the tool must never contact (takes precedence over the allowlist)
+    pub denied_hosts: Vec<String>,
+    /// Request timeout in seconds
+    pub timeout_seconds: u64,
+    /// Convert HTML responses to plain text before returning them
+    pub extract_html_text: bool,
+}
+
+impl Default for HttpToolConfig {
+    fn default() -> Self {
+        Self {
+            max_response_bytes: 64 * 1024,
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            timeout_seconds: 30,
+            extract_html_text: true,
+        }
+    }
+}
+
+impl HttpToolConfig {
+    /// Set the most bytes of response body kept; the body is cut at the last
+    /// character boundary within them
+    #[must_use]
+    pub const fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Restrict requests to the given hosts
+    #[must_use]
+    pub fn with_allowed_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.allowed_hosts = hosts;
+        self
+    }
+
+    /// Block requests to the given hosts
+    #[must_use]
+    pub fn with_denied_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.denied_hosts = hosts;
+        self
+    }
+
+    /// Set the request timeout in seconds
+    #[must_use]
+    pub const fn with_timeout_seconds(mut self, timeout_seconds: u64) -> Self {
+        self.timeout_seconds = timeout_seconds;
+        self
+    }
+
+    /// Enable or disable HTML-to-text extraction
+    #[must_use]
+    pub const fn with_html_extraction(mut self, enabled: bool) -> Self {
+        self.extract_html_text = enabled;
+        self
+    }
+}
+
+/// Arguments accepted by the `http_request` tool
+#[derive(Debug, Clone, Deserialize)]
+struct HttpToolArgs {
+    url: String,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    headers: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    body: Option<serde_json::Value>,
+}
+
+/// Rust-native HTTP fetch tool
+#[derive(Debug, Clone)]
+pub struct HttpRequestTool {
+    config: HttpToolConfig,
+    client: reqwest::Client,
+}
+
+impl HttpRequestTool {
+    /// Create a new HTTP tool with the given configuration
+    #[must_use]
+    pub fn new(config: HttpToolConfig) -> Self {
+        let settings = HttpClientFactory::global().settings_for(
+            "http_tool",
+            Some(Duration::from_secs(config.timeout_seconds)),
+            Some("GraphBit HTTP Tool/1.0"),
+        );
+        // The client is not shared: its redirect policy depends on the host lists
+        let hosts = config.clone();
+        let policy = redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error(GraphBitError::validation(
+                    "url",
+                    format!("Too many redirects (more than {MAX_REDIRECTS})"),
+                ));
+            }
+            match check_host(&hosts, attempt.url()) {
+                Ok(()) => attempt.follow(),
+                Err(e) => attempt.error(e),
+            }
+        });
+        let client = settings
+            .client_builder()
+            .and_then(|builder| {
+                builder.redirect(policy).build().map_err(|e| {
+                    GraphBitError::config(format!("Failed to create HTTP client: {e}"))
+                })
+            })
+            // Unusable settings leave a client that cannot follow any redirect
+            .unwrap_or_else(|_| {
+                reqwest::Client::builder()
+                    .redirect(redirect::Policy::none())
+                    .build()
+                    .unwrap_or_default()
+            });
+        Self { config, client }
+    }
+
+    /// Tool configuration
+    #[must_use]
+    pub const fn config(&self) -> &HttpToolConfig {
+        &self.config
+    }
+
+    /// Metadata (name, description, parameter schema) advertised to the model.
+    ///
+    /// The schema sticks to the subset of JSON schema every supported
+    /// provider accepts (explicit `type` on every property, no `oneOf`).
+    #[must_use]
+    pub fn metadata(&self) -> ToolMetadata {
+        let mut description = String::from(
+            "Fetch a URL over HTTP(S). Supports GET and POST with optional headers and a JSON \
+             body. Returns the status code, content type and (possibly truncated) response body; \
+             HTML pages are converted to plain text.",
+        );
+        if !self.config.allowed_hosts.is_empty() {
+            description.push_str(" Only these hosts may be requested: ");
+            description.push_str(&self.config.allowed_hosts.join(", "));
+            description.push('.');
+        }
+
+        ToolMetadata::new(
+            HTTP_TOOL_NAME,
+            description,
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "Absolute http:// or https:// URL to request"
+                    },
+                    "method": {
+                        "type": "string",
+                        "enum": ["GET", "POST"],
+                        "description": "HTTP method, defaults to GET"
+                    },
+                    "headers": {
+                        "type": "object",
+                        "description": "Optional request headers as name/value string pairs"
+                    },
+                    "body": {
+                        "type": "object",
+                        "description": "Optional JSON body sent with POST requests"
+                    }
+                },
+                "required": ["url"]
+            }),
+        )
+    }
+
+    /// Check a URL against the scheme and host restrictions
+    pub fn check_url(&self, url: &str) -> GraphBitResult<Url> {
+        let parsed = Url::parse(url)
+            .map_err(|e| GraphBitError::validation("url", format!("Invalid URL '{url}': {e}")))?;
+        check_host(&self.config, &parsed)?;
+        Ok(parsed)
+    }
+
+    /// Execute the tool with JSON arguments matching [`Self::metadata`]
+    pub async fn execute(&self, args: serde_json::Value) -> GraphBitResult<serde_json::Value> {
+        let args: HttpToolArgs = serde_json::from_value(args).map_err(|e| {
+            GraphBitError::validation("arguments", format!("Invalid http_request arguments: {e}"))
+        })?;
+        let url = self.check_url(&args.url)?;
+
+        let method = match args.method.as_deref().map(str::to_uppercase).as_deref() {
+            None | Some("GET") => Method::GET,
+            Some("POST") => Method::POST,
+            Some(other) => {
+                return Err(GraphBitError::validation(
+                    "method",
+                    format!("Unsupported HTTP method '{other}', expected GET or POST"),
+                ));
+            }
+        };
+
+        let mut request = self.client.request(method.clone(), url);
+        for (name, value) in args.headers.unwrap_or_default() {
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            };
+            request = request.header(name.as_str(), value);
+        }
+        if let Some(body) = args.body {
+            if method == Method::POST {
+                request = request.json(&body);
+            }
+        }
+
+        let mut response = request.send().await.map_err(|e| {
+            // Surface a redirect rejected by the host lists as that rejection
+            std::error::Error::source(&e)
+                .and_then(|source| source.downcast_ref::<GraphBitError>())
+                .cloned()
+                .unwrap_or_else(|| e.into())
+        })?;
+        let status = response.status().as_u16();
+        let final_url = response.url().to_string();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+
+        // Stop reading once the body passes the limit, so we know truncation happened
+        let limit = self.config.max_response_bytes;
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > limit {
+                truncated = true;
+                break;
+            }
+        }
+
+        let mut body = String::from_utf8_lossy(&bytes).into_owned();
+        if self.config.extract_html_text && is_html(&content_type, &body) {
+            if let Ok(text) = DocumentLoader::parse_html_to_structured_text(&body) {
+                body = text;
+            }
+        }
+        // Keep at most `limit` bytes without splitting a UTF-8 character
+        if body.len() > limit {
+            truncated = true;
+            body.truncate(floor_char_boundary(&body, limit));
+        }
+
+        Ok(serde_json::json!({
+            "status": status,
+            "url": final_url,
+            "content_type": content_type,
+            "body": body,
+            "truncated": truncated,
+        }))
+    }
+}
+
+/// Check the scheme and host of `url`, the first one or a redirect, against
+/// the host lists of `config`
+fn check_host(config: &HttpToolConfig, url: &Url) -> GraphBitResult<()> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(GraphBitError::validation(
+            "url",
+            format!("Unsupported URL scheme '{}'", url.scheme()),
+        ));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| GraphBitError::validation("url", format!("URL '{url}' has no host")))?
+        .to_lowercase();
+
+    if config
+        .denied_hosts
+        .iter()
+        .any(|pattern| host_matches(&host, pattern))
+    {
+        return Err(GraphBitError::validation(
+            "url",
+            format!("Host '{host}' is denied for the http_request tool"),
+        ));
+    }
+
+    if !config.allowed_hosts.is_empty()
+        && !config
+            .allowed_hosts
+            .iter()
+            .any(|pattern| host_matches(&host, pattern))
+    {
+        return Err(GraphBitError::validation(
+            "url",
+            format!("Host '{host}' is not in the http_request tool allowlist"),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `host` equals `pattern` or is a subdomain of it
+fn host_matches(host: &str, pattern: &str) -> bool {
+    let pattern = pattern.trim().trim_start_matches("*.").to_lowercase();
+    !pattern.is_empty()
+        && (host == pattern
+            || host
+                .strip_suffix(pattern.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+fn is_html(content_type: &str, body: &str) -> bool {
+    if content_type.to_lowercase().contains("html") {
+        return true;
+    }
+    let head = body
+        .trim_start()
+        .get(..15)
+        .unwrap_or_default()
+        .to_lowercase();
+    head.starts_with("<!doctype html") || head.starts_with("<html")
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len()))
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
@@ -0,0 +1,72 @@
+//! Tool support for `GraphBit` agents
+//!
+//! Contains the registry through which Rust code exposes tools to agent nodes,
+//! the built-in tools implemented natively in Rust, so that agents can opt into
+//! common capabilities (such as fetching a URL) without every project writing
+//! its own wrapper function, and validation of the arguments models pass to
+//! tools. The `handoff` tool lets a supervisor agent delegate to worker agent
+//! nodes. Tools read the idempotency key of the calling node from the
+//! [`ToolCallContext`], and context tools read and write workflow state through
+//! a [`ToolContext`].
+
+pub mod arguments;
+pub mod context;
+pub mod handoff;
+pub mod http;
+pub mod registry;
+
+pub use arguments::{ToolArgumentError, validate_tool_arguments};
+pub use context::{SCRATCH_NAMESPACE, ToolCallContext, ToolContext};
+pub use handoff::{HANDOFF_TOOL_NAME, HandoffRequest};
+pub use http::{HttpRequestTool, HttpToolConfig};
+pub use registry::{ContextToolHandler, ToolHandler, ToolRegistry};
+
+use crate::llm::LlmTool;
+use serde::{Deserialize, Serialize};
+
+/// Name, description and JSON parameter schema describing a tool to the LLM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolMetadata {
+    /// Unique tool name exposed to the model
+    pub name: String,
+    /// Human-readable description of what the tool does
+    pub description: String,
+    /// JSON schema for the tool arguments
+    pub parameters: serde_json::Value,
+}
+
+impl ToolMetadata {
+    /// Create new tool metadata
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+
+    /// Convert into the provider-agnostic tool definition sent with LLM requests
+    #[must_use]
+    pub fn to_llm_tool(&self) -> LlmTool {
+        LlmTool::new(
+            self.name.clone(),
+            self.description.clone(),
+            self.parameters.clone(),
+        )
+    }
+}
+
+/// Factory for the tools shipped with `GraphBit`
+pub struct BuiltinTools;
+
+impl BuiltinTools {
+    /// HTTP fetch tool (`http_request`) configured with the given options
+    #[must_use]
+    pub fn http(config: HttpToolConfig) -> HttpRequestTool {
+        HttpRequestTool::new(config)
+    }
+}
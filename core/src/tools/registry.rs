@@ -0,0 +1,209 @@
+//! Registry of Rust-native tool handlers
+//!
+//! Tools registered here are resolved by agent nodes when the registry is attached
+//! to a [`crate::workflow::WorkflowExecutor`] via `with_tool_registry`. The executor
+//! then runs the tool-calling loop itself: requested tools are invoked, their
+//! results appended to the conversation and the model called again until it
+//! produces a final answer.
+//!
+//! Tools registered with [`ToolRegistry::register_context_tool`] also receive
+//! the [`ToolContext`] of the calling node.
+
+use super::{ToolContext, ToolMetadata};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::LlmTool;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Async function invoked with the model-supplied JSON arguments of a tool call
+pub type ToolHandler = Arc<
+    dyn Fn(serde_json::Value) -> BoxFuture<'static, GraphBitResult<serde_json::Value>>
+        + Send
+        + Sync,
+>;
+
+/// Async function invoked with the model-supplied JSON arguments of a tool call
+/// and the [`ToolContext`] of the calling node
+pub type ContextToolHandler = Arc<
+    dyn Fn(serde_json::Value, ToolContext) -> BoxFuture<'static, GraphBitResult<serde_json::Value>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+struct RegisteredTool {
+    metadata: ToolMetadata,
+    handler: ContextToolHandler,
+}
+
+/// Thread-safe registry mapping tool names to their metadata and handler.
+///
+/// Cloning is cheap and clones share the same underlying tool table, so a
+/// registry handed to an executor still sees tools registered afterwards.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Arc<RwLock<HashMap<String, RegisteredTool>>>,
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a tool, replacing any existing tool with the same name
+    pub fn register_tool(
+        &self,
+        metadata: ToolMetadata,
+        handler: ToolHandler,
+    ) -> GraphBitResult<()> {
+        self.register_context_tool(metadata, Arc::new(move |arguments, _| handler(arguments)))
+    }
+
+    /// Register a tool reading or writing workflow state through its
+    /// [`ToolContext`], replacing any existing tool with the same name
+    pub fn register_context_tool(
+        &self,
+        metadata: ToolMetadata,
+        handler: ContextToolHandler,
+    ) -> GraphBitResult<()> {
+        if metadata.name.trim().is_empty() {
+            return Err(GraphBitError::validation(
+                "name",
+                "Tool name cannot be empty",
+            ));
+        }
+
+        self.tools
+            .write()
+            .map_err(|e| GraphBitError::concurrency(format!("Tool registry lock poisoned: {e}")))?
+            .insert(metadata.name.clone(), RegisteredTool { metadata, handler });
+        Ok(())
+    }
+
+    /// Copy every tool of `other` into this registry, replacing tools with the
+    /// same name
+    pub fn extend(&self, other: &Self) -> GraphBitResult<()> {
+        let tools: Vec<RegisteredTool> = other
+            .tools
+            .read()
+            .map_err(|e| GraphBitError::concurrency(format!("Tool registry lock poisoned: {e}")))?
+            .values()
+            .cloned()
+            .collect();
+        self.tools
+            .write()
+            .map_err(|e| GraphBitError::concurrency(format!("Tool registry lock poisoned: {e}")))?
+            .extend(
+                tools
+                    .into_iter()
+                    .map(|tool| (tool.metadata.name.clone(), tool)),
+            );
+        Ok(())
+    }
+
+    /// Remove a tool, returning whether it was registered
+    pub fn unregister_tool(&self, name: &str) -> bool {
+        self.tools
+            .write()
+            .is_ok_and(|mut tools| tools.remove(name).is_some())
+    }
+
+    /// Check whether a tool is registered
+    #[must_use]
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.tools
+            .read()
+            .is_ok_and(|tools| tools.contains_key(name))
+    }
+
+    /// Names of all registered tools
+    #[must_use]
+    pub fn tool_names(&self) -> Vec<String> {
+        self.tools
+            .read()
+            .map(|tools| tools.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Metadata of a registered tool
+    #[must_use]
+    pub fn metadata(&self, name: &str) -> Option<ToolMetadata> {
+        self.tools
+            .read()
+            .ok()
+            .and_then(|tools| tools.get(name).map(|tool| tool.metadata.clone()))
+    }
+
+    /// LLM tool definitions for the given tool names, skipping unknown names
+    #[must_use]
+    pub fn llm_tools(&self, names: &[String]) -> Vec<LlmTool> {
+        names
+            .iter()
+            .filter_map(|name| self.metadata(name))
+            .map(|metadata| metadata.to_llm_tool())
+            .collect()
+    }
+
+    /// Number of registered tools
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tools.read().map_or(0, |tools| tools.len())
+    }
+
+    /// Whether no tools are registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Invoke a registered tool with the given arguments, outside of any
+    /// workflow: its context is empty and its writes are discarded
+    pub async fn execute(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+    ) -> GraphBitResult<serde_json::Value> {
+        self.execute_with_context(name, arguments, ToolContext::default())
+            .await
+    }
+
+    /// Invoke a registered tool with the given arguments and context
+    pub async fn execute_with_context(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+        context: ToolContext,
+    ) -> GraphBitResult<serde_json::Value> {
+        let handler = self
+            .tools
+            .read()
+            .map_err(|e| GraphBitError::concurrency(format!("Tool registry lock poisoned: {e}")))?
+            .get(name)
+            .map(|tool| tool.handler.clone())
+            .ok_or_else(|| {
+                GraphBitError::workflow_execution(format!("Tool '{name}' is not registered"))
+            })?;
+
+        crate::audit::observe(
+            crate::audit::AuditCallKind::Tool,
+            "tool_registry",
+            Some(name),
+            arguments,
+            serde_json::Value::to_string,
+            |arguments| handler(arguments, context),
+            |output| (output.to_string(), None),
+        )
+        .await
+    }
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.tool_names())
+            .finish()
+    }
+}
@@ -0,0 +1,291 @@
+//! Session transcripts
+//!
+//! [`WorkflowContext::to_transcript_markdown`] renders what the agent nodes of a
+//! run said as a markdown document meant to be read rather than parsed: per
+//! agent node, its system prompt and prompt, every tool-loop iteration with the
+//! tool calls the model asked for and their results, and the final answer.
+//!
+//! Values are redacted and truncated like in an
+//! [`ExecutionReport`](crate::report::ExecutionReport): keys listed in the
+//! [`ReportConfig`] are redacted in tool arguments, and execution secrets and
+//! resolved API keys wherever they occur. Tool results longer than
+//! [`COLLAPSE_AFTER_CHARS`] are collapsed into a `<details>` block.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+use crate::report::ReportConfig;
+use crate::types::WorkflowContext;
+
+/// Tool results longer than this many characters are collapsed
+pub const COLLAPSE_AFTER_CHARS: usize = 600;
+
+/// Conversation of one agent node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeTranscript {
+    /// Node name
+    pub node_name: String,
+    /// Model that answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// System prompt sent along with the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Prompt sent to the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Tool-loop iterations, in order
+    #[serde(default)]
+    pub iterations: Vec<TranscriptIteration>,
+    /// Final answer of the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    /// Error the node failed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One model reply asking for tool calls, and the calls' results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptIteration {
+    /// 1-based iteration of the tool loop
+    pub iteration: u32,
+    /// Text the model replied with along with the tool calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply: Option<String>,
+    /// Tool calls, in call order
+    pub tool_calls: Vec<TranscriptToolCall>,
+}
+
+/// A tool call of a [`TranscriptIteration`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptToolCall {
+    /// Name of the tool
+    pub tool_name: String,
+    /// Arguments supplied by the model
+    pub arguments: serde_json::Value,
+    /// Tool result, or the error message when the call failed
+    pub output: String,
+    /// Whether the call succeeded
+    pub success: bool,
+}
+
+impl WorkflowContext {
+    /// Conversations of the agent nodes, in execution order, redacted and
+    /// truncated per `config`
+    #[must_use]
+    pub fn transcripts(&self, config: &ReportConfig) -> Vec<NodeTranscript> {
+        let capture = |value: &serde_json::Value| {
+            let mut value = config.capture(value);
+            self.secrets.redact_value(&mut value);
+            redact_api_keys(&mut value);
+            value
+        };
+        let capture_text = |text: &str| {
+            crate::secret_ref::redact(&self.secrets.redact(&config.capture_text(text)))
+        };
+        let text = |value: Option<&serde_json::Value>| {
+            value
+                .and_then(serde_json::Value::as_str)
+                .filter(|text| !text.trim().is_empty())
+                .map(capture_text)
+        };
+
+        self.node_executions
+            .iter()
+            .filter_map(|record| {
+                let response = self
+                    .metadata
+                    .get(&format!("node_response_{}", record.node_id))
+                    .filter(|response| {
+                        response
+                            .get("node_type")
+                            .and_then(serde_json::Value::as_str)
+                            == Some("Agent")
+                    })?;
+                let executions = response
+                    .get("executions")
+                    .and_then(serde_json::Value::as_array)
+                    .map_or(&[][..], Vec::as_slice);
+
+                let mut iterations: Vec<TranscriptIteration> = Vec::new();
+                for (call, execution) in executions
+                    .iter()
+                    .filter(|execution| execution_type(execution) == Some("llm_call"))
+                    .enumerate()
+                {
+                    // The first call has no iteration; follow-ups carry the
+                    // iteration whose tool results they answer
+                    let iteration = u32::try_from(call + 1).unwrap_or(u32::MAX);
+                    let tool_calls: Vec<TranscriptToolCall> = executions
+                        .iter()
+                        .filter(|execution| {
+                            execution_type(execution) == Some("tool_call")
+                                && execution
+                                    .get("iteration")
+                                    .and_then(serde_json::Value::as_u64)
+                                    == Some(u64::from(iteration))
+                        })
+                        .map(|execution| {
+                            let success = execution
+                                .get("success")
+                                .and_then(serde_json::Value::as_bool)
+                                .unwrap_or(true);
+                            let output = execution
+                                .get(if success { "output" } else { "error" })
+                                .map_or_else(String::new, |output| match output {
+                                    serde_json::Value::String(text) => text.clone(),
+                                    serde_json::Value::Null => String::new(),
+                                    other => other.to_string(),
+                                });
+                            TranscriptToolCall {
+                                tool_name: execution
+                                    .get("name")
+                                    .or_else(|| execution.get("tool_name"))
+                                    .and_then(serde_json::Value::as_str)
+                                    .unwrap_or_default()
+                                    .to_string(),
+                                arguments: execution
+                                    .get("parameters")
+                                    .map_or(serde_json::Value::Null, &capture),
+                                output: capture_text(&output),
+                                success,
+                            }
+                        })
+                        .collect();
+                    if !tool_calls.is_empty() {
+                        iterations.push(TranscriptIteration {
+                            iteration,
+                            reply: text(execution.get("output")),
+                            tool_calls,
+                        });
+                    }
+                }
+
+                Some(NodeTranscript {
+                    node_name: record.node_name.clone(),
+                    model: executions
+                        .iter()
+                        .find_map(|execution| execution.get("model")?.as_str())
+                        .map(str::to_string),
+                    system_prompt: text(response.get("system_prompt")),
+                    prompt: text(response.get("user_input")),
+                    iterations,
+                    answer: record
+                        .success
+                        .then(|| text(response.get("final_output")))
+                        .flatten(),
+                    error: record.error.as_deref().map(capture_text),
+                })
+            })
+            .collect()
+    }
+
+    /// Transcript of the agent nodes as markdown, with the default [`ReportConfig`]
+    #[must_use]
+    pub fn to_transcript_markdown(&self) -> String {
+        self.to_transcript_markdown_with(&ReportConfig::default())
+    }
+
+    /// Transcript of the agent nodes as markdown, redacted and truncated per `config`
+    #[must_use]
+    pub fn to_transcript_markdown_with(&self, config: &ReportConfig) -> String {
+        transcript_markdown(&self.workflow_id.to_string(), &self.transcripts(config))
+    }
+}
+
+/// Render `transcripts` of the run `workflow_id` as markdown
+#[must_use]
+pub fn transcript_markdown(workflow_id: &str, transcripts: &[NodeTranscript]) -> String {
+    let mut markdown = format!("# Transcript of workflow run `{workflow_id}`\n");
+    if transcripts.is_empty() {
+        markdown.push_str("\nNo agent nodes ran.\n");
+    }
+    for (index, node) in transcripts.iter().enumerate() {
+        let _ = write!(markdown, "\n## {}. {}\n", index + 1, node.node_name);
+        if let Some(model) = &node.model {
+            let _ = write!(markdown, "\nModel: `{model}`\n");
+        }
+        if let Some(system_prompt) = &node.system_prompt {
+            let _ = write!(markdown, "\n### System prompt\n\n{}", quote(system_prompt));
+        }
+        if let Some(prompt) = &node.prompt {
+            let _ = write!(markdown, "\n### Prompt\n\n{}", quote(prompt));
+        }
+        for iteration in &node.iterations {
+            let _ = write!(markdown, "\n### Iteration {}\n", iteration.iteration);
+            if let Some(reply) = &iteration.reply {
+                let _ = write!(markdown, "\n{}", quote(reply));
+            }
+            for call in &iteration.tool_calls {
+                let _ = write!(
+                    markdown,
+                    "\n#### Tool call: `{}`{}\n\nArguments:\n\n{}",
+                    call.tool_name,
+                    if call.success { "" } else { " (failed)" },
+                    fence(&pretty(&call.arguments), "json")
+                );
+                let label = if call.success { "Result" } else { "Error" };
+                let chars = call.output.chars().count();
+                if chars > COLLAPSE_AFTER_CHARS {
+                    let _ = write!(
+                        markdown,
+                        "\n<details>\n<summary>{label} ({chars} characters)</summary>\n\n{}\n</details>\n",
+                        fence(&call.output, "")
+                    );
+                } else {
+                    let _ = write!(markdown, "\n{label}:\n\n{}", fence(&call.output, ""));
+                }
+            }
+        }
+        if let Some(answer) = &node.answer {
+            let _ = write!(markdown, "\n### Final answer\n\n{}", quote(answer));
+        }
+        if let Some(error) = &node.error {
+            let _ = write!(markdown, "\n### Error\n\n{}", quote(error));
+        }
+    }
+    markdown
+}
+
+fn execution_type(execution: &serde_json::Value) -> Option<&str> {
+    execution.get("type").and_then(serde_json::Value::as_str)
+}
+
+/// Redact resolved API keys in the strings of `value`
+fn redact_api_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => *text = crate::secret_ref::redact(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_api_keys),
+        serde_json::Value::Object(entries) => entries.values_mut().for_each(redact_api_keys),
+        _ => {}
+    }
+}
+
+/// `text` as a markdown block quote
+fn quote(text: &str) -> String {
+    let mut quoted = String::new();
+    for line in text.trim_end().lines() {
+        if line.is_empty() {
+            quoted.push_str(">\n");
+        } else {
+            let _ = writeln!(quoted, "> {line}");
+        }
+    }
+    quoted
+}
+
+/// `text` as a fenced code block, with a fence longer than any backtick run in it
+fn fence(text: &str, language: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", text.trim_end())
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -1,8 +1,12 @@
 //! Circuit breaker types
 
+use std::sync::Arc;
+
 use chrono;
 use serde::{Deserialize, Serialize};
 
+use crate::clock::Clock;
+
 use super::ids::{DEFAULT_FAILURE_WINDOW_MS, DEFAULT_RECOVERY_TIMEOUT_MS};
 
 /// Circuit breaker configuration for error recovery
@@ -56,6 +60,9 @@ pub struct CircuitBreaker {
     pub success_count: u32,
     /// Last failure time
     pub last_failure: Option<chrono::DateTime<chrono::Utc>>,
+    /// Clock timing the recovery timeout
+    #[serde(skip, default = "crate::clock::system")]
+    clock: Arc<dyn Clock>,
 }
 
 impl CircuitBreaker {
@@ -67,15 +74,23 @@ impl CircuitBreaker {
             failure_count: 0,
             success_count: 0,
             last_failure: None,
+            clock: crate::clock::system(),
         }
     }
 
+    /// Time the recovery timeout on `clock` instead of the system clock
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Check if a request should be allowed
     pub fn should_allow_request(&mut self) -> bool {
         match self.state {
             CircuitBreakerState::Closed => true,
             CircuitBreakerState::Open { opened_at } => {
-                let now = chrono::Utc::now();
+                let now = self.clock.now();
                 let elapsed = now.signed_duration_since(opened_at).num_milliseconds() as u64;
 
                 if elapsed >= self.config.recovery_timeout_ms {
@@ -116,21 +131,18 @@ impl CircuitBreaker {
     /// Record a failed operation
     #[inline]
     pub fn record_failure(&mut self) {
-        self.last_failure = Some(chrono::Utc::now());
+        let now = self.clock.now();
+        self.last_failure = Some(now);
 
         match self.state {
             CircuitBreakerState::Closed => {
                 self.failure_count += 1;
                 if self.failure_count >= self.config.failure_threshold {
-                    self.state = CircuitBreakerState::Open {
-                        opened_at: chrono::Utc::now(),
-                    };
+                    self.state = CircuitBreakerState::Open { opened_at: now };
                 }
             }
             CircuitBreakerState::HalfOpen => {
-                self.state = CircuitBreakerState::Open {
-                    opened_at: chrono::Utc::now(),
-                };
+                self.state = CircuitBreakerState::Open { opened_at: now };
                 self.failure_count = 1;
                 self.success_count = 0;
             }
@@ -2,11 +2,11 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::{OwnedMutexGuard, OwnedSemaphorePermit, RwLock, Semaphore};
 
 use super::ids::NodeId;
-use crate::errors::GraphBitResult;
+use crate::errors::{GraphBitError, GraphBitResult};
 
 /// Simplified configuration for basic concurrency control
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,7 +24,7 @@ impl Default for ConcurrencyConfig {
         node_type_limits.insert("http_request".to_string(), 8);
         node_type_limits.insert("transform".to_string(), 16);
         node_type_limits.insert("condition".to_string(), 32);
-        node_type_limits.insert("delay".to_string(), 1);
+        node_type_limits.insert("delay".to_string(), 16);
 
         Self {
             global_max_concurrency: 16,
@@ -34,6 +34,37 @@ impl Default for ConcurrencyConfig {
 }
 
 impl ConcurrencyConfig {
+    /// Limits for running many independent workflows or wide graphs, scaled to
+    /// the number of available CPUs
+    pub fn high_throughput() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(4, usize::from);
+        let global_max_concurrency = (cpus * 4).max(32);
+        let mut node_type_limits = HashMap::with_capacity(8);
+        node_type_limits.insert("agent".to_string(), global_max_concurrency / 2);
+        for node_type in ["http_request", "transform", "condition", "delay"] {
+            node_type_limits.insert(node_type.to_string(), global_max_concurrency);
+        }
+
+        Self {
+            global_max_concurrency,
+            node_type_limits,
+        }
+    }
+
+    /// Small limits that keep few node outputs and requests in flight at once
+    pub fn memory_optimized() -> Self {
+        let mut node_type_limits = HashMap::with_capacity(8);
+        node_type_limits.insert("agent".to_string(), 2);
+        for node_type in ["http_request", "transform", "condition", "delay"] {
+            node_type_limits.insert(node_type.to_string(), 4);
+        }
+
+        Self {
+            global_max_concurrency: 4,
+            node_type_limits,
+        }
+    }
+
     /// Get concurrency limit for a specific node type
     pub fn get_node_type_limit(&self, node_type: &str) -> usize {
         self.node_type_limits
@@ -49,8 +80,14 @@ pub struct ConcurrencyManager {
     node_type_limits: Arc<RwLock<HashMap<String, NodeTypeConcurrency>>>,
     /// Configuration
     config: Arc<RwLock<ConcurrencyConfig>>,
-    /// Performance statistics
-    stats: Arc<RwLock<ConcurrencyStats>>,
+    /// Bounds the number of tasks running at once across all node types
+    global_permits: Arc<Semaphore>,
+    /// Performance statistics, locked only briefly and never across an await
+    /// so that dropped permits can update them
+    stats: Arc<Mutex<ConcurrencyStats>>,
+    /// Locks of the concurrency keys in use, so nodes sharing a key run one
+    /// at a time
+    keyed_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
 }
 
 /// Atomic concurrency tracking per node type
@@ -97,20 +134,41 @@ impl ConcurrencyManager {
 
         Self {
             node_type_limits: Arc::new(RwLock::new(node_type_limits)),
+            global_permits: Arc::new(Semaphore::new(config.global_max_concurrency.max(1))),
             config: Arc::new(RwLock::new(config)),
-            stats: Arc::new(RwLock::new(ConcurrencyStats::default())),
+            stats: Arc::new(Mutex::new(ConcurrencyStats::default())),
+            keyed_locks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Acquire permits for executing a task (no global semaphore bottleneck)
+    /// Acquire permits for executing a task.
+    ///
+    /// The node type permit is taken first, so tasks waiting on a busy node type
+    /// do not hold one of the `global_max_concurrency` slots.
     pub async fn acquire_permits(
         &self,
         task_info: &TaskInfo,
     ) -> GraphBitResult<ConcurrencyPermits> {
         let start_time = std::time::Instant::now();
-
-        // Get or create node type concurrency tracking
-        let (current_count, wait_queue, max_concurrent) = {
+        let queued = crate::telemetry::QueuedTask::new();
+
+        // Get or create node type concurrency tracking; the write lock is only
+        // taken for a node type seen for the first time
+        let known = self
+            .node_type_limits
+            .read()
+            .await
+            .get(&task_info.node_type)
+            .map(|node_concurrency| {
+                (
+                    Arc::clone(&node_concurrency.current_count),
+                    Arc::clone(&node_concurrency.wait_queue),
+                    node_concurrency.max_concurrent,
+                )
+            });
+        let (current_count, wait_queue, max_concurrent) = if let Some(known) = known {
+            known
+        } else {
             let config = self.config.read().await;
             let mut limits = self.node_type_limits.write().await;
 
@@ -134,6 +192,12 @@ impl ConcurrencyManager {
 
         // Fast path: try to acquire without waiting
         loop {
+            // Register for wake-ups before checking, so a permit released between the
+            // check and the await is not missed
+            let notified = wait_queue.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
             let current = current_count.load(std::sync::atomic::Ordering::Acquire);
             if current < max_concurrent {
                 // Try to increment atomically
@@ -147,28 +211,68 @@ impl ConcurrencyManager {
                     Err(_) => continue, // Retry - another thread modified the count
                 }
             }
-            wait_queue.notified().await;
+            notified.await;
         }
 
+        // Dropping `permits` releases the node type slot if the global slot cannot be taken
+        let mut permits = ConcurrencyPermits {
+            stats: Arc::clone(&self.stats),
+            current_count,
+            wait_queue,
+            global_permit: None,
+        };
+        permits.global_permit = Some(
+            Arc::clone(&self.global_permits)
+                .acquire_owned()
+                .await
+                .map_err(|e| GraphBitError::concurrency(format!("Failed to acquire permit: {e}")))?,
+        );
+
+        drop(queued);
+        crate::telemetry::permits_acquired(&task_info.node_type, start_time.elapsed());
+
         // Update statistics
         {
-            let mut stats = self.stats.write().await;
+            let mut stats = lock_stats(&self.stats);
             stats.total_permit_acquisitions += 1;
             stats.total_wait_time_ms += start_time.elapsed().as_millis() as u64;
             stats.current_active_tasks += 1;
             stats.peak_active_tasks = stats.peak_active_tasks.max(stats.current_active_tasks);
         }
 
-        Ok(ConcurrencyPermits {
-            stats: Arc::clone(&self.stats),
-            current_count,
-            wait_queue,
-        })
+        Ok(permits)
+    }
+
+    /// Take the lock of concurrency key `key`, waiting while another task
+    /// holds it. The lock is released when the returned guard is dropped.
+    ///
+    /// Take it before [`Self::acquire_permits`], so tasks waiting on a key do
+    /// not hold concurrency slots.
+    pub async fn acquire_key(&self, key: &str) -> KeyedLock {
+        let start_time = std::time::Instant::now();
+        let lock = Arc::clone(
+            lock_keyed(&self.keyed_locks)
+                .entry(key.to_string())
+                .or_default(),
+        );
+        let guard = lock.lock_owned().await;
+
+        {
+            let mut stats = lock_stats(&self.stats);
+            stats.keyed_lock_acquisitions += 1;
+            stats.keyed_lock_wait_time_ms += start_time.elapsed().as_millis() as u64;
+        }
+
+        KeyedLock {
+            key: key.to_string(),
+            locks: Arc::clone(&self.keyed_locks),
+            guard: Some(guard),
+        }
     }
 
     /// Get current statistics
     pub async fn get_stats(&self) -> ConcurrencyStats {
-        self.stats.read().await.clone()
+        lock_stats(&self.stats).clone()
     }
 
     /// Get available permits for debugging
@@ -242,7 +346,7 @@ impl TaskInfo {
     pub fn from_node_type(node_type: &crate::graph::NodeType, task_id: &NodeId) -> Self {
         use crate::graph::NodeType;
         let type_str = match node_type {
-            NodeType::Agent { .. } => "agent",
+            NodeType::Agent { .. } | NodeType::ModelRouter { .. } => "agent",
             NodeType::HttpRequest { .. } => "http_request",
             NodeType::Transform { .. } => "transform",
             NodeType::Condition { .. } => "condition",
@@ -258,11 +362,51 @@ impl TaskInfo {
     }
 }
 
+/// Lock the statistics, even if an earlier holder panicked
+fn lock_stats(stats: &Mutex<ConcurrencyStats>) -> MutexGuard<'_, ConcurrencyStats> {
+    stats
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Lock the map of concurrency key locks, even if an earlier holder panicked
+fn lock_keyed(
+    locks: &Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+) -> MutexGuard<'_, HashMap<String, Arc<tokio::sync::Mutex<()>>>> {
+    locks
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Held lock of a concurrency key, from [`ConcurrencyManager::acquire_key`]
+pub struct KeyedLock {
+    key: String,
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for KeyedLock {
+    fn drop(&mut self) {
+        drop(self.guard.take());
+        // Forget the key once no other task holds or waits for its lock; the
+        // map is the only other owner then
+        let mut locks = lock_keyed(&self.locks);
+        if locks
+            .get(&self.key)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.key);
+        }
+    }
+}
+
 /// Enhanced permits with atomic cleanup
 pub struct ConcurrencyPermits {
-    stats: Arc<RwLock<ConcurrencyStats>>,
+    stats: Arc<Mutex<ConcurrencyStats>>,
     current_count: Arc<std::sync::atomic::AtomicUsize>,
     wait_queue: Arc<tokio::sync::Notify>,
+    /// Slot of the global limit, released when the permits are dropped
+    global_permit: Option<OwnedSemaphorePermit>,
 }
 
 impl Drop for ConcurrencyPermits {
@@ -274,15 +418,17 @@ impl Drop for ConcurrencyPermits {
         // Notify waiting tasks
         self.wait_queue.notify_one();
 
-        // Update statistics
-        if let Ok(mut stats) = self.stats.try_write() {
+        // Only permits that took their global slot were counted as active
+        if self.global_permit.is_some() {
+            crate::telemetry::permits_released();
+            let mut stats = lock_stats(&self.stats);
             stats.current_active_tasks = stats.current_active_tasks.saturating_sub(1);
         }
     }
 }
 
 /// Simplified statistics for concurrency management
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ConcurrencyStats {
     /// Total number of permit acquisitions
     pub total_permit_acquisitions: u64,
@@ -296,6 +442,11 @@ pub struct ConcurrencyStats {
     pub permit_failures: u64,
     /// Average wait time per permit acquisition
     pub avg_wait_time_ms: f64,
+    /// Number of concurrency key locks taken
+    pub keyed_lock_acquisitions: u64,
+    /// Total time spent waiting for concurrency key locks (milliseconds),
+    /// not included in `total_wait_time_ms`
+    pub keyed_lock_wait_time_ms: u64,
 }
 
 impl ConcurrencyStats {
@@ -2,22 +2,52 @@
 
 use chrono;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::ids::{NodeId, WorkflowId};
-use super::execution::WorkflowExecutionStats;
+use super::execution::{
+    AbandonedNode, NodeDebugCapture, NodeDebugCaptures, NodeExecutionRecord,
+    OutputValidationReport, TagFilter, ToolCallRecord, WorkflowExecutionStats,
+};
+use super::node_outputs::NodeOutputs;
+use super::prompt::PromptDefaults;
+use super::secrets::Secrets;
+use crate::attachment::{Attachment, AttachmentData, DEFAULT_MAX_ATTACHMENT_BYTES};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::external_event::ExternalEvents;
+use crate::guardrail_node::GuardrailReport;
+use crate::llm::{LlmConfig, LlmMiddlewareStack, LlmUsage};
+use crate::output_store::{OutputRef, OutputSpill};
+use crate::report::ReportConfig;
+use crate::result_sink::ResultSink;
+use crate::tools::SCRATCH_NAMESPACE;
+use crate::workflow::WarmUpReport;
 
 /// Workflow execution context
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowContext {
     /// Workflow ID
     pub workflow_id: WorkflowId,
+    /// ID of this execution of the workflow, from which node idempotency keys derive
+    #[serde(default = "uuid::Uuid::new_v4")]
+    pub execution_id: uuid::Uuid,
     /// Current execution state
     pub state: WorkflowState,
     /// Shared variables accessible by all agents
     pub variables: HashMap<String, serde_json::Value>,
-    /// Node outputs for automatic data flow
-    pub node_outputs: HashMap<String, serde_json::Value>,
+    /// Node outputs for automatic data flow, shared with snapshots of the context
+    pub node_outputs: NodeOutputs,
+    /// Values of edges with a transform, keyed by target node id and then by
+    /// source node id and name; the source's entry in `node_outputs` keeps the
+    /// untransformed output
+    #[serde(default)]
+    pub edge_outputs: HashMap<String, HashMap<String, serde_json::Value>>,
+    /// Files, images and audio produced by nodes, keyed by node name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attachments: HashMap<String, Vec<Attachment>>,
     /// Execution metadata
     pub metadata: HashMap<String, serde_json::Value>,
     /// Start time of the workflow execution
@@ -26,6 +56,85 @@ pub struct WorkflowContext {
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     /// Execution statistics
     pub stats: Option<WorkflowExecutionStats>,
+    /// Tool calls made by agent nodes, keyed by node name in call order
+    #[serde(default)]
+    pub tool_calls: HashMap<String, Vec<ToolCallRecord>>,
+    /// Output schema validation outcomes for agent nodes, keyed by node name
+    #[serde(default)]
+    pub output_validations: HashMap<String, OutputValidationReport>,
+    /// Violations and redactions of guardrail nodes, keyed by node name
+    #[serde(default)]
+    pub guardrail_reports: HashMap<String, GuardrailReport>,
+    /// External event nodes whose event did not arrive before the timeout, by name
+    #[serde(default)]
+    pub event_timeouts: HashSet<String>,
+    /// Number of attempts each executed node needed, retries included, keyed by node name
+    #[serde(default)]
+    pub node_attempts: HashMap<String, u32>,
+    /// Executed nodes in the order they finished
+    #[serde(default)]
+    pub node_executions: Vec<NodeExecutionRecord>,
+    /// Nodes whose output was carried over from an earlier execution by
+    /// `WorkflowExecutor::reexecute_from` instead of running, by name
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub reused_nodes: HashSet<String>,
+    /// Nodes on parent branches a join with an `any` or `quorum` policy stopped
+    /// waiting for, nodes the budget stopped, nodes the tag filter excluded and
+    /// nodes blocked by a failed dependency, keyed by node name
+    #[serde(default)]
+    pub abandoned_nodes: HashMap<String, AbandonedNode>,
+    /// Rendered prompts and provider payloads captured for nodes in debug
+    /// mode; only serialized when the capture was configured to persist
+    #[serde(default, skip_serializing_if = "NodeDebugCaptures::is_transient")]
+    pub node_debug: NodeDebugCaptures,
+    /// Secrets available to this execution; never serialized
+    #[serde(skip)]
+    pub secrets: Secrets,
+    /// Where outputs over the spill threshold are stored; never serialized
+    #[serde(skip)]
+    pub output_spill: Option<OutputSpill>,
+    /// Size limit of a single attachment in bytes; never serialized
+    #[serde(skip, default = "default_max_attachment_bytes")]
+    pub max_attachment_bytes: u64,
+    /// Middleware run around every LLM call of the execution; never serialized
+    #[serde(skip)]
+    pub llm_middleware: LlmMiddlewareStack,
+    /// Mailboxes external events are delivered to; never serialized
+    #[serde(skip)]
+    pub external_events: ExternalEvents,
+    /// Prompt preamble, suffix and global template variables of the executor;
+    /// never serialized
+    #[serde(skip)]
+    pub prompt_defaults: PromptDefaults,
+    /// Global template variables set on individual nodes, keyed by node id;
+    /// never serialized
+    #[serde(skip)]
+    pub node_globals: HashMap<NodeId, HashMap<String, serde_json::Value>>,
+    /// Split branch each node in a split fan-out runs in, keyed by node name;
+    /// never serialized
+    #[serde(skip)]
+    pub node_branches: HashMap<String, BranchScope>,
+    /// Name of the executor's LLM profile selected for this execution
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Node tags selecting which nodes run in this execution
+    #[serde(default, skip_serializing_if = "TagFilter::is_empty")]
+    pub tag_filter: TagFilter,
+    /// LLM configurations the selected profile gives agent nodes and the
+    /// configurations guardrail classifiers fall back to, keyed by node id;
+    /// never serialized
+    #[serde(skip)]
+    pub node_llm_configs: HashMap<NodeId, LlmConfig>,
+    /// File each completed node's result is appended to; never serialized
+    #[serde(skip)]
+    pub result_sink: Option<Arc<ResultSink>>,
+    /// Whether the execution warms up its providers before the first node
+    /// runs; never serialized
+    #[serde(skip)]
+    pub warm_up: bool,
+    /// Outcome of the warm-up of the execution, when it warmed up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_up_report: Option<WarmUpReport>,
 }
 
 impl WorkflowContext {
@@ -33,16 +142,97 @@ impl WorkflowContext {
     pub fn new(workflow_id: WorkflowId) -> Self {
         Self {
             workflow_id,
+            execution_id: uuid::Uuid::new_v4(),
             state: WorkflowState::Pending,
             variables: HashMap::with_capacity(8),
-            node_outputs: HashMap::with_capacity(8),
+            node_outputs: NodeOutputs::new(),
+            edge_outputs: HashMap::new(),
+            attachments: HashMap::new(),
             metadata: HashMap::with_capacity(4),
             started_at: chrono::Utc::now(),
             completed_at: None,
             stats: None,
+            tool_calls: HashMap::new(),
+            output_validations: HashMap::new(),
+            guardrail_reports: HashMap::new(),
+            event_timeouts: HashSet::new(),
+            node_attempts: HashMap::new(),
+            node_executions: Vec::new(),
+            reused_nodes: HashSet::new(),
+            abandoned_nodes: HashMap::new(),
+            node_debug: NodeDebugCaptures::default(),
+            secrets: Secrets::default(),
+            output_spill: None,
+            max_attachment_bytes: DEFAULT_MAX_ATTACHMENT_BYTES,
+            llm_middleware: LlmMiddlewareStack::default(),
+            external_events: ExternalEvents::default(),
+            prompt_defaults: PromptDefaults::default(),
+            node_globals: HashMap::new(),
+            node_branches: HashMap::new(),
+            profile: None,
+            tag_filter: TagFilter::default(),
+            node_llm_configs: HashMap::new(),
+            result_sink: None,
+            warm_up: false,
+            warm_up_report: None,
         }
     }
 
+    /// Seed the context with run-time inputs.
+    ///
+    /// Inputs become workflow variables, referenced as `{{input.NAME}}` in prompt
+    /// templates and as `vars.NAME` in expressions.
+    #[must_use]
+    pub fn with_inputs(mut self, inputs: HashMap<String, serde_json::Value>) -> Self {
+        self.variables.extend(inputs);
+        self
+    }
+
+    /// Set the secrets available to this execution
+    #[must_use]
+    pub fn with_secrets(mut self, secrets: impl Into<Secrets>) -> Self {
+        self.secrets = secrets.into();
+        self
+    }
+
+    /// Select the executor's LLM profile named `profile` for this execution
+    #[must_use]
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Run only the nodes of this execution that `filter` selects
+    #[must_use]
+    pub fn with_tag_filter(mut self, filter: TagFilter) -> Self {
+        self.tag_filter = filter;
+        self
+    }
+
+    /// Warm up the providers of the workflow before its first node runs, see
+    /// [`WorkflowExecutor::warm_up`](crate::workflow::WorkflowExecutor::warm_up)
+    #[must_use]
+    pub const fn with_warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    /// Append the result of every node this execution completes to `sink`
+    #[must_use]
+    pub fn with_result_sink(mut self, sink: Arc<ResultSink>) -> Self {
+        self.result_sink = Some(sink);
+        self
+    }
+
+    /// Spill node outputs over the threshold of `spill` out of the context.
+    ///
+    /// Attach the same spill to a deserialized context to read its spilled outputs.
+    #[must_use]
+    pub fn with_output_spill(mut self, spill: OutputSpill) -> Self {
+        self.output_spill = Some(spill);
+        self
+    }
+
     /// Set a variable in the context
     #[inline]
     pub fn set_variable(&mut self, key: String, value: serde_json::Value) {
@@ -68,10 +258,35 @@ impl WorkflowContext {
         self.completed_at = Some(chrono::Utc::now());
     }
 
+    /// Mark workflow as finished with the given nodes failed
+    pub fn partially_complete(&mut self, failed_nodes: Vec<String>) {
+        self.state = WorkflowState::PartiallyCompleted { failed_nodes };
+        self.completed_at = Some(chrono::Utc::now());
+    }
+
     /// Mark workflow as failed
     #[inline]
     pub fn fail(&mut self, error: String) {
-        self.state = WorkflowState::Failed { error };
+        self.state = WorkflowState::Failed {
+            error,
+            reason: None,
+        };
+        self.completed_at = Some(chrono::Utc::now());
+    }
+
+    /// Mark workflow as cancelled
+    #[inline]
+    pub fn cancel(&mut self) {
+        self.state = WorkflowState::Cancelled;
+        self.completed_at = Some(chrono::Utc::now());
+    }
+
+    /// Mark workflow as failed for a reason callers can act on
+    pub fn fail_with_reason(&mut self, error: String, reason: FailureReason) {
+        self.state = WorkflowState::Failed {
+            error,
+            reason: Some(reason),
+        };
         self.completed_at = Some(chrono::Utc::now());
     }
 
@@ -87,6 +302,170 @@ impl WorkflowContext {
         self.stats.as_ref()
     }
 
+    /// Serialize the context to JSON, readable again with [`Self::from_json`].
+    ///
+    /// Spilled outputs stay references to their output store. With
+    /// `max_value_chars`, strings in node outputs, edge outputs and metadata
+    /// longer than that are truncated.
+    pub fn to_json(&self, max_value_chars: Option<usize>) -> GraphBitResult<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let (Some(max_chars), Some(object)) = (max_value_chars, value.as_object_mut()) {
+            let truncation = ReportConfig {
+                redact_keys: Vec::new(),
+                max_value_chars: Some(max_chars),
+                include_payloads: false,
+            };
+            for key in ["node_outputs", "edge_outputs", "metadata"] {
+                if let Some(entry) = object.get_mut(key) {
+                    *entry = truncation.capture(entry);
+                }
+            }
+        }
+        Ok(serde_json::to_string(&value)?)
+    }
+
+    /// Deserialize a context produced by [`Self::to_json`].
+    ///
+    /// Attach the spill the run used with [`Self::with_output_spill`] to read
+    /// its spilled outputs.
+    pub fn from_json(json: &str) -> GraphBitResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Record a tool call made by an agent node.
+    ///
+    /// The record's `attempt` is set from the number of earlier calls to the same
+    /// tool within the node, and the tool call counts in the execution statistics
+    /// are kept up to date when statistics are present.
+    pub fn record_tool_call(&mut self, node_name: &str, mut record: ToolCallRecord) {
+        let records = self.tool_calls.entry(node_name.to_string()).or_default();
+        let previous = records
+            .iter()
+            .filter(|r| r.tool_name == record.tool_name)
+            .count();
+        record.attempt = u32::try_from(previous)
+            .unwrap_or(u32::MAX)
+            .saturating_add(1);
+
+        if let Some(stats) = self.stats.as_mut() {
+            stats.total_tool_calls += 1;
+            if !record.success {
+                stats.failed_tool_calls += 1;
+            }
+        }
+        records.push(record);
+    }
+
+    /// Get the tool calls made by a node, in call order
+    #[must_use]
+    pub fn get_tool_calls(&self, node_name: &str) -> &[ToolCallRecord] {
+        self.tool_calls.get(node_name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Total and failed tool call counts across all nodes
+    #[must_use]
+    pub fn tool_call_counts(&self) -> (usize, usize) {
+        self.tool_calls
+            .values()
+            .flatten()
+            .fold((0, 0), |(total, failed), record| {
+                (total + 1, failed + usize::from(!record.success))
+            })
+    }
+
+    /// Record the output schema validation outcome of a node
+    pub fn record_output_validation(&mut self, node_name: &str, report: OutputValidationReport) {
+        self.output_validations
+            .insert(node_name.to_string(), report);
+    }
+
+    /// Get the output schema validation outcome of a node
+    #[must_use]
+    pub fn get_output_validation(&self, node_name: &str) -> Option<&OutputValidationReport> {
+        self.output_validations.get(node_name)
+    }
+
+    /// Record the report of a guardrail node
+    pub fn record_guardrail_report(&mut self, node_name: &str, report: GuardrailReport) {
+        self.guardrail_reports.insert(node_name.to_string(), report);
+    }
+
+    /// Get the report of a guardrail node
+    #[must_use]
+    pub fn get_guardrail_report(&self, node_name: &str) -> Option<&GuardrailReport> {
+        self.guardrail_reports.get(node_name)
+    }
+
+    /// Record that the event of an external event node did not arrive in time
+    pub fn record_event_timeout(&mut self, node_name: &str) {
+        self.event_timeouts.insert(node_name.to_string());
+    }
+
+    /// Whether the event of an external event node did not arrive in time
+    #[must_use]
+    pub fn event_timed_out(&self, node_name: &str) -> bool {
+        self.event_timeouts.contains(node_name)
+    }
+
+    /// Record the prompt and provider payloads captured for a node
+    pub fn record_node_debug(&mut self, node_name: &str, capture: NodeDebugCapture) {
+        self.node_debug.nodes.insert(node_name.to_string(), capture);
+    }
+
+    /// Get the prompt and provider payloads captured for a node
+    #[must_use]
+    pub fn get_node_debug(&self, node_name: &str) -> Option<&NodeDebugCapture> {
+        self.node_debug.nodes.get(node_name)
+    }
+
+    /// Record how many attempts a node needed, retries included
+    pub fn record_node_attempts(&mut self, node_name: &str, attempts: u32) {
+        self.node_attempts.insert(node_name.to_string(), attempts);
+    }
+
+    /// Get the number of attempts a node needed, if it was executed
+    #[must_use]
+    pub fn get_node_attempts(&self, node_name: &str) -> Option<u32> {
+        self.node_attempts.get(node_name).copied()
+    }
+
+    /// Idempotency key of a node in this execution: the same for every attempt
+    /// of the node and different for other nodes and other executions
+    #[must_use]
+    pub fn idempotency_key(&self, node_id: &NodeId) -> String {
+        uuid::Uuid::new_v5(&self.execution_id, node_id.as_uuid().as_bytes()).to_string()
+    }
+
+    /// Record a node that finished executing
+    pub fn record_node_execution(&mut self, record: NodeExecutionRecord) {
+        self.node_executions.push(record);
+    }
+
+    /// Executed nodes in the order they finished
+    #[must_use]
+    pub fn get_node_executions(&self) -> &[NodeExecutionRecord] {
+        &self.node_executions
+    }
+
+    /// Whether the output of the node `node_name` was carried over from an
+    /// earlier execution instead of running
+    #[must_use]
+    pub fn is_reused(&self, node_name: &str) -> bool {
+        self.reused_nodes.contains(node_name)
+    }
+
+    /// Record a node the executor abandoned, because a join stopped waiting for
+    /// it, the budget ran out, the tag filter excluded it or a node it depends
+    /// on failed, dropping anything it stored as its output
+    pub fn abandon_node(&mut self, node_id: &NodeId, node_name: &str, state: AbandonedNode) {
+        for key in [node_id.to_string(), node_name.to_string()] {
+            self.node_outputs.remove(&key);
+            self.variables.remove(&key);
+        }
+        self.node_attempts.remove(node_name);
+        self.abandoned_nodes.insert(node_name.to_string(), state);
+    }
+
     /// Calculate and return execution duration in milliseconds
     pub fn execution_duration_ms(&self) -> Option<u64> {
         if let Some(completed_at) = self.completed_at {
@@ -102,21 +481,464 @@ impl WorkflowContext {
     /// Store a node's output in the context for automatic data flow
     #[inline]
     pub fn set_node_output(&mut self, node_id: &NodeId, output: serde_json::Value) {
+        let output = self.spill_output(output);
         self.node_outputs.insert(node_id.to_string(), output);
     }
 
     /// Store a node's output in the context for automatic data flow using the node name as key
     #[inline]
     pub fn set_node_output_by_name(&mut self, node_name: &str, output: serde_json::Value) {
+        let output = self.spill_output(output);
         self.node_outputs.insert(node_name.to_string(), output);
     }
 
+    /// Store the transformed value that flowed from `source` into the node `target`
+    pub fn set_edge_output(
+        &mut self,
+        source_id: &NodeId,
+        source_name: &str,
+        target: &NodeId,
+        value: serde_json::Value,
+    ) {
+        let outputs = self.edge_outputs.entry(target.to_string()).or_default();
+        outputs.insert(source_id.to_string(), value.clone());
+        outputs.insert(source_name.to_string(), value);
+    }
+
+    /// Drop the transformed value stored for the edge `source -> target`
+    pub fn remove_edge_output(&mut self, source_id: &NodeId, source_name: &str, target: &NodeId) {
+        if let Some(outputs) = self.edge_outputs.get_mut(&target.to_string()) {
+            outputs.remove(&source_id.to_string());
+            outputs.remove(source_name);
+        }
+    }
+
+    /// Transformed value that flowed from `source` (a node id or name) into the node `target`
+    #[must_use]
+    pub fn get_edge_output(&self, source: &str, target: &NodeId) -> Option<&serde_json::Value> {
+        self.edge_outputs.get(&target.to_string())?.get(source)
+    }
+
+    /// Like [`Self::get_nested_output`] for the transformed values flowing into `target`
+    #[must_use]
+    pub fn get_nested_edge_output(
+        &self,
+        reference: &str,
+        target: &NodeId,
+    ) -> Option<&serde_json::Value> {
+        let mut parts = reference.split('.');
+        let mut current = self.get_edge_output(parts.next()?, target)?;
+        for part in parts {
+            current = current.get(part)?;
+        }
+        Some(current)
+    }
+
+    /// The value to keep in the context for `output`: an [`OutputRef`] when an output
+    /// spill is attached and the output is over its threshold, otherwise the output.
+    ///
+    /// An output that cannot be spilled stays in memory.
+    #[must_use]
+    pub fn spill_output(&self, output: serde_json::Value) -> serde_json::Value {
+        let Some(spill) = &self.output_spill else {
+            return output;
+        };
+        match spill.spill(&output) {
+            Ok(Some(output_ref)) => output_ref.to_value(),
+            Ok(None) => output,
+            Err(e) => {
+                tracing::warn!("Keeping node output in memory; spilling it failed: {e}");
+                output
+            }
+        }
+    }
+
+    /// Read `value` back from the output store when it is an [`OutputRef`]
+    #[must_use]
+    pub fn resolve_output<'a>(&self, value: &'a serde_json::Value) -> Cow<'a, serde_json::Value> {
+        let (Some(spill), Some(output_ref)) = (&self.output_spill, OutputRef::from_value(value))
+        else {
+            return Cow::Borrowed(value);
+        };
+        match spill.load(&output_ref) {
+            Ok(output) => Cow::Owned(output),
+            Err(e) => {
+                tracing::warn!("Failed to load spilled output {}: {e}", output_ref.sha256);
+                Cow::Borrowed(value)
+            }
+        }
+    }
+
+    /// Get a node's output, reading it back from the output store if it was spilled
+    #[must_use]
+    pub fn load_node_output(&self, node_id: &str) -> Option<Cow<'_, serde_json::Value>> {
+        self.get_node_output(node_id)
+            .map(|output| self.resolve_output(output))
+    }
+
+    /// All node outputs, with spilled outputs read back from the output store
+    #[must_use]
+    pub fn load_node_outputs(&self) -> HashMap<String, serde_json::Value> {
+        // Outputs are stored under both the node id and name; read each spilled one once
+        let mut loaded: HashMap<String, serde_json::Value> = HashMap::new();
+        self.node_outputs
+            .iter()
+            .map(|(key, output)| {
+                let output = match OutputRef::from_value(output) {
+                    Some(output_ref) => loaded
+                        .entry(output_ref.sha256)
+                        .or_insert_with(|| self.resolve_output(output).into_owned())
+                        .clone(),
+                    None => output.clone(),
+                };
+                (key.clone(), output)
+            })
+            .collect()
+    }
+
+    /// Snapshot of the node outputs, with spilled outputs read back from the output store.
+    ///
+    /// Unlike [`Self::load_node_outputs`], outputs kept in memory are shared
+    /// with the context rather than copied.
+    #[must_use]
+    pub fn snapshot_node_outputs(&self) -> NodeOutputs {
+        let mut snapshot = self.node_outputs.clone();
+        if self.output_spill.is_none() {
+            return snapshot;
+        }
+        // Outputs are stored under both the node id and name; read each spilled one once
+        let mut loaded: HashMap<String, Arc<serde_json::Value>> = HashMap::new();
+        for (key, output) in &self.node_outputs {
+            if let Some(output_ref) = OutputRef::from_value(output) {
+                let output = loaded
+                    .entry(output_ref.sha256)
+                    .or_insert_with(|| Arc::new(self.resolve_output(output).into_owned()));
+                snapshot.insert(key.clone(), Arc::clone(output));
+            }
+        }
+        snapshot
+    }
+
+    /// Number and total size in bytes of the distinct spilled node outputs
+    #[must_use]
+    pub fn spilled_output_totals(&self) -> (usize, u64) {
+        let mut seen = HashSet::new();
+        self.node_outputs
+            .values()
+            .filter_map(OutputRef::from_value)
+            .filter(|output_ref| seen.insert(output_ref.sha256.clone()))
+            .fold((0, 0), |(count, bytes), output_ref| {
+                (count + 1, bytes + output_ref.size_bytes)
+            })
+    }
+
+    /// Set the attachments of a node, replacing those of an earlier attempt.
+    ///
+    /// Fails when an attachment is over [`Self::max_attachment_bytes`]. With an
+    /// output spill attached, inline attachments over its threshold are moved
+    /// to its output store.
+    pub fn set_attachments(
+        &mut self,
+        node_name: &str,
+        mut attachments: Vec<Attachment>,
+    ) -> GraphBitResult<()> {
+        for attachment in &mut attachments {
+            if attachment.size_bytes > self.max_attachment_bytes {
+                return Err(GraphBitError::workflow_execution(format!(
+                    "Attachment of node '{node_name}' is {} bytes, over the {} byte limit",
+                    attachment.size_bytes, self.max_attachment_bytes
+                )));
+            }
+            let Some(spill) = &self.output_spill else {
+                continue;
+            };
+            if let AttachmentData::Inline { bytes } = &attachment.data {
+                if bytes.len() > spill.threshold_bytes() {
+                    spill.store_bytes(bytes)?;
+                    attachment.data = AttachmentData::Stored;
+                }
+            }
+        }
+        self.attachments.insert(node_name.to_string(), attachments);
+        Ok(())
+    }
+
+    /// Get the attachments of a node, in the order it produced them
+    #[must_use]
+    pub fn get_attachments(&self, node_name: &str) -> &[Attachment] {
+        self.attachments.get(node_name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Read the content of an attachment, from the output store if it was stored there
+    pub fn read_attachment(&self, attachment: &Attachment) -> GraphBitResult<Vec<u8>> {
+        attachment.read(self.output_spill.as_ref())
+    }
+
+    /// Path of a local file holding the attachment: its own file, or a copy in
+    /// the execution's temporary directory, which is removed when it finishes
+    pub fn attachment_path(&self, attachment: &Attachment) -> GraphBitResult<PathBuf> {
+        if let AttachmentData::File { path } = &attachment.data {
+            return Ok(path.clone());
+        }
+        let dir = crate::attachment::temp_dir(self.execution_id);
+        let path = dir.join(attachment.local_file_name());
+        if !path.exists() {
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(&path, self.read_attachment(attachment)?)?;
+        }
+        Ok(path)
+    }
+
     /// Get a node's output from the context
     #[inline]
     pub fn get_node_output(&self, node_id: &str) -> Option<&serde_json::Value> {
         self.node_outputs.get(node_id)
     }
 
+    /// Get a nested value from a variable using dot notation
+    #[must_use]
+    pub fn get_nested_variable(&self, reference: &str) -> Option<&serde_json::Value> {
+        let mut parts = reference.split('.');
+        let mut current = self.get_variable(parts.next()?)?;
+        for part in parts {
+            current = current.get(part)?;
+        }
+        Some(current)
+    }
+
+    /// Get a nested global template variable using dot notation, as seen by the
+    /// node `target`: the node's own globals take precedence over the executor's
+    #[must_use]
+    pub fn get_nested_global(
+        &self,
+        reference: &str,
+        target: Option<&NodeId>,
+    ) -> Option<&serde_json::Value> {
+        let mut parts = reference.split('.');
+        let name = parts.next()?;
+        let mut current = target
+            .and_then(|target| self.node_globals.get(target))
+            .and_then(|globals| globals.get(name))
+            .or_else(|| self.prompt_defaults.globals.get(name))?;
+        for part in parts {
+            current = current.get(part)?;
+        }
+        Some(current)
+    }
+
+    /// Get a value tools wrote to the scratch space, by its name within the
+    /// namespace, using dot notation into the value when no key matches exactly
+    #[must_use]
+    pub fn get_scratch(&self, reference: &str) -> Option<&serde_json::Value> {
+        lookup_scratch(self.metadata.get(SCRATCH_NAMESPACE)?.as_object()?, reference)
+    }
+
+    /// Like [`Self::get_scratch`] for the node `node_name`, reading the writes
+    /// of its split branch before the shared scratch space
+    #[must_use]
+    pub fn get_node_scratch(&self, node_name: &str, reference: &str) -> Option<&serde_json::Value> {
+        self.node_branch(node_name)
+            .and_then(|branch| self.branch_scratch(branch))
+            .and_then(|scratch| lookup_scratch(scratch, reference))
+            .or_else(|| self.get_scratch(reference))
+    }
+
+    /// Scratch space seen by the tools of the node `node_name`: the shared one
+    /// with the writes of the node's split branch on top
+    #[must_use]
+    pub fn node_scratch(&self, node_name: &str) -> serde_json::Map<String, serde_json::Value> {
+        let mut scratch = self
+            .metadata
+            .get(SCRATCH_NAMESPACE)
+            .and_then(serde_json::Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        if let Some(branch) = self
+            .node_branch(node_name)
+            .and_then(|branch| self.branch_scratch(branch))
+        {
+            scratch.extend(branch.clone());
+        }
+        scratch
+    }
+
+    /// Merge the scratch writes of the tool call `source`, in order.
+    ///
+    /// The last write of a key wins; replacing a different value another tool
+    /// call wrote logs a warning.
+    pub fn merge_scratch(&mut self, source: &str, writes: Vec<(String, serde_json::Value)>) {
+        if writes.is_empty() {
+            return;
+        }
+        let scratch = self
+            .metadata
+            .entry(SCRATCH_NAMESPACE.to_string())
+            .or_insert(serde_json::Value::Null);
+        merge_writes(as_object_entry(scratch), source, writes);
+    }
+
+    /// Merge the scratch writes of the tool call `source` of the node
+    /// `node_name`: into the scratch space of the node's split branch when it
+    /// runs in one, so parallel branches never see each other's writes, and
+    /// into the shared scratch space otherwise
+    pub fn merge_node_scratch(
+        &mut self,
+        node_name: &str,
+        source: &str,
+        writes: Vec<(String, serde_json::Value)>,
+    ) {
+        let Some(branch) = self.node_branch(node_name).cloned() else {
+            self.merge_scratch(source, writes);
+            return;
+        };
+        if writes.is_empty() {
+            return;
+        }
+        let splits = self
+            .metadata
+            .entry(BRANCH_SCRATCH_METADATA.to_string())
+            .or_insert(serde_json::Value::Null);
+        let branches = as_object_entry(splits)
+            .entry(branch.split)
+            .or_insert(serde_json::Value::Null);
+        let scratch = as_object_entry(branches)
+            .entry(branch.index.to_string())
+            .or_insert(serde_json::Value::Null);
+        merge_writes(as_object_entry(scratch), source, writes);
+    }
+
+    /// Name of the node `node_id` of the executing workflow
+    #[must_use]
+    pub fn node_name(&self, node_id: &NodeId) -> Option<&str> {
+        self.metadata
+            .get("node_id_to_name")?
+            .get(node_id.to_string())?
+            .as_str()
+    }
+
+    /// Split branch the node `node_name` runs in, if any
+    #[must_use]
+    pub fn node_branch(&self, node_name: &str) -> Option<&BranchScope> {
+        self.node_branches.get(node_name)
+    }
+
+    /// Scratch space of a split branch, if its tools wrote to it
+    fn branch_scratch(
+        &self,
+        branch: &BranchScope,
+    ) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.metadata
+            .get(BRANCH_SCRATCH_METADATA)?
+            .get(&branch.split)?
+            .get(branch.index.to_string())?
+            .as_object()
+    }
+
+    /// Item a split branch works on: the element of the split node's output at
+    /// the branch's index when that output is an array, the whole output otherwise
+    #[must_use]
+    pub fn branch_item(&self, branch: &BranchScope) -> Option<serde_json::Value> {
+        let output = self.load_node_output(&branch.split)?;
+        match output.as_ref() {
+            serde_json::Value::Array(items) => items.get(branch.index).cloned(),
+            other => Some(other.clone()),
+        }
+    }
+
+    /// Collect the scratch spaces of the split branches of `parents` into the
+    /// shared scratch space as `scratch.<join_name>`: an array with one object
+    /// of writes per branch, ordered by split and branch index
+    pub fn collect_branch_scratch(&mut self, join_name: &str, parents: &[String]) {
+        let mut branches: Vec<BranchScope> = parents
+            .iter()
+            .filter_map(|parent| self.node_branch(parent).cloned())
+            .collect();
+        if branches.is_empty() {
+            return;
+        }
+        branches.sort_by(|a, b| (&a.split, a.index).cmp(&(&b.split, b.index)));
+        branches.dedup();
+        let collected = branches
+            .iter()
+            .map(|branch| {
+                serde_json::Value::Object(self.branch_scratch(branch).cloned().unwrap_or_default())
+            })
+            .collect();
+        self.merge_scratch(
+            &format!("of join node '{join_name}'"),
+            vec![(join_name.to_string(), serde_json::Value::Array(collected))],
+        );
+    }
+
+    /// Replace secret values with [`Secrets::REDACTED`] wherever they occur in the
+    /// context: variables, node and edge outputs, metadata, tool calls and the failure message
+    pub fn redact_secrets(&mut self) {
+        if self.secrets.is_empty() {
+            return;
+        }
+        let secrets = &self.secrets;
+        for value in self
+            .variables
+            .values_mut()
+            .chain(self.edge_outputs.values_mut().flat_map(HashMap::values_mut))
+            .chain(self.metadata.values_mut())
+        {
+            secrets.redact_value(value);
+        }
+        self.node_outputs
+            .for_each_value_mut(|value| secrets.redact_value(value));
+        for record in self.tool_calls.values_mut().flatten() {
+            secrets.redact_value(&mut record.arguments);
+            secrets.redact_value(&mut record.output);
+        }
+        for record in &mut self.node_executions {
+            if let Some(error) = &mut record.error {
+                *error = secrets.redact(error);
+            }
+        }
+        for capture in self.node_debug.nodes.values_mut() {
+            for prompt in [&mut capture.system_prompt, &mut capture.rendered_prompt]
+                .into_iter()
+                .flatten()
+            {
+                *prompt = secrets.redact(prompt);
+            }
+            for exchange in &mut capture.exchanges {
+                exchange.request_body = secrets.redact(&exchange.request_body);
+                if let Some(body) = &mut exchange.response_body {
+                    *body = secrets.redact(body);
+                }
+            }
+        }
+        if let WorkflowState::Failed { error, .. } = &mut self.state {
+            *error = secrets.redact(error);
+        }
+    }
+
+    /// Like [`Self::get_nested_output`], reading spilled outputs back from the output store
+    #[must_use]
+    pub fn load_nested_output(&self, reference: &str) -> Option<Cow<'_, serde_json::Value>> {
+        let (node, path) = match reference.split_once('.') {
+            Some((node, path)) => (node, Some(path)),
+            None => (reference, None),
+        };
+        let output = self.load_node_output(node)?;
+        let Some(path) = path else {
+            return Some(output);
+        };
+        match output {
+            Cow::Borrowed(output) => path
+                .split('.')
+                .try_fold(output, |value, part| value.get(part))
+                .map(Cow::Borrowed),
+            Cow::Owned(output) => path
+                .split('.')
+                .try_fold(&output, |value, part| value.get(part))
+                .cloned()
+                .map(Cow::Owned),
+        }
+    }
+
     /// Get a nested value from a node's output using dot notation
     pub fn get_nested_output(&self, reference: &str) -> Option<&serde_json::Value> {
         let parts: Vec<&str> = reference.split('.').collect();
@@ -141,13 +963,39 @@ impl Default for WorkflowContext {
     fn default() -> Self {
         Self {
             workflow_id: WorkflowId::default(),
+            execution_id: uuid::Uuid::new_v4(),
             state: WorkflowState::Pending,
             variables: HashMap::with_capacity(16),
-            node_outputs: HashMap::with_capacity(16),
+            node_outputs: NodeOutputs::new(),
+            edge_outputs: HashMap::new(),
+            attachments: HashMap::new(),
             metadata: HashMap::with_capacity(8),
             started_at: chrono::Utc::now(),
             completed_at: None,
             stats: None,
+            tool_calls: HashMap::new(),
+            output_validations: HashMap::new(),
+            guardrail_reports: HashMap::new(),
+            event_timeouts: HashSet::new(),
+            node_attempts: HashMap::new(),
+            node_executions: Vec::new(),
+            reused_nodes: HashSet::new(),
+            abandoned_nodes: HashMap::new(),
+            node_debug: NodeDebugCaptures::default(),
+            secrets: Secrets::default(),
+            output_spill: None,
+            max_attachment_bytes: DEFAULT_MAX_ATTACHMENT_BYTES,
+            llm_middleware: LlmMiddlewareStack::default(),
+            external_events: ExternalEvents::default(),
+            prompt_defaults: PromptDefaults::default(),
+            node_globals: HashMap::new(),
+            node_branches: HashMap::new(),
+            profile: None,
+            tag_filter: TagFilter::default(),
+            node_llm_configs: HashMap::new(),
+            result_sink: None,
+            warm_up: false,
+            warm_up_report: None,
         }
     }
 }
@@ -176,9 +1024,31 @@ pub enum WorkflowState {
     Failed {
         /// Error message describing the failure
         error: String,
+        /// Cause of the failure, when callers can act on it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<FailureReason>,
     },
     /// Workflow was cancelled
     Cancelled,
+    /// Workflow finished without failing fast although some nodes failed: the
+    /// nodes depending on them were skipped and every other node ran
+    PartiallyCompleted {
+        /// Names of the failed nodes, in the order they failed
+        failed_nodes: Vec<String>,
+    },
+}
+
+/// Cause of a workflow failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailureReason {
+    /// The execution's token or cost budget ran out
+    BudgetExceeded {
+        /// Tokens used by the execution
+        usage: LlmUsage,
+        /// Cost in USD of the execution's LLM calls
+        cost_usd: f64,
+    },
 }
 
 impl WorkflowState {
@@ -187,10 +1057,25 @@ impl WorkflowState {
     pub fn is_terminal(&self) -> bool {
         matches!(
             self,
-            Self::Completed | Self::Failed { .. } | Self::Cancelled
+            Self::Completed
+                | Self::Failed { .. }
+                | Self::Cancelled
+                | Self::PartiallyCompleted { .. }
         )
     }
 
+    /// Check if the workflow finished with some nodes failed
+    #[inline]
+    pub fn is_partially_completed(&self) -> bool {
+        matches!(self, Self::PartiallyCompleted { .. })
+    }
+
+    /// Check if the workflow was cancelled
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
     /// Check if the workflow is currently running
     #[inline]
     pub fn is_running(&self) -> bool {
@@ -202,4 +1087,75 @@ impl WorkflowState {
     pub fn is_paused(&self) -> bool {
         matches!(self, Self::Paused { .. })
     }
-}
\ No newline at end of file
+}
+
+/// Branch of a split fan-out a node runs in.
+///
+/// Nodes in a branch read `{{index}}` and `{{item}}` in their templates, and
+/// the scratch writes of their tools stay in the branch until a join node
+/// collects them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchScope {
+    /// Name of the split node
+    pub split: String,
+    /// Position of the branch among the split node's children, from 0
+    pub index: usize,
+}
+
+/// Metadata key of the scratch spaces of split branches, keyed by split node
+/// name and then by branch index
+const BRANCH_SCRATCH_METADATA: &str = "branch_scratch";
+
+/// Value in a scratch space by its name, using dot notation into objects and
+/// arrays when no key matches exactly
+fn lookup_scratch<'a>(
+    scratch: &'a serde_json::Map<String, serde_json::Value>,
+    reference: &str,
+) -> Option<&'a serde_json::Value> {
+    if let Some(value) = scratch.get(reference) {
+        return Some(value);
+    }
+    let mut parts = reference.split('.');
+    let mut current = scratch.get(parts.next()?)?;
+    for part in parts {
+        current = match current {
+            serde_json::Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => current.get(part)?,
+        };
+    }
+    Some(current)
+}
+
+/// Object held by `value`, replacing any other value
+fn as_object_entry(
+    value: &mut serde_json::Value,
+) -> &mut serde_json::Map<String, serde_json::Value> {
+    if !value.is_object() {
+        *value = serde_json::Value::Object(serde_json::Map::new());
+    }
+    match value {
+        serde_json::Value::Object(map) => map,
+        _ => unreachable!("value was just made an object"),
+    }
+}
+
+/// Merge the scratch writes of the tool call `source` into `scratch`, in
+/// order, logging a warning when one replaces a different value
+fn merge_writes(
+    scratch: &mut serde_json::Map<String, serde_json::Value>,
+    source: &str,
+    writes: Vec<(String, serde_json::Value)>,
+) {
+    for (name, value) in writes {
+        if let Some(previous) = scratch.get(&name).filter(|previous| **previous != value) {
+            tracing::warn!(
+                "Tool call {source} overwrote {SCRATCH_NAMESPACE}.{name} (was {previous})"
+            );
+        }
+        scratch.insert(name, value);
+    }
+}
+
+fn default_max_attachment_bytes() -> u64 {
+    DEFAULT_MAX_ATTACHMENT_BYTES
+}
@@ -2,9 +2,14 @@
 
 use chrono;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::ids::NodeId;
+use crate::guardrail_node::GuardrailReport;
+use crate::json_repair::JsonRepair;
+use crate::pacing::PacingDelays;
+use crate::usage::UsageBreakdown;
+use crate::validation::ValidationResult;
 
 /// Node execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +32,21 @@ pub struct NodeExecutionResult {
     pub retry_count: u32,
     /// ID of the node that was executed
     pub node_id: NodeId,
+    /// Output schema validation outcome, for nodes with an `output_schema`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_validation: Option<OutputValidationReport>,
+    /// Idempotency key shared by all attempts of the node in this execution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Rendered prompt and provider payloads, when the executor captures them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<NodeDebugCapture>,
+    /// Violations and redactions, for guardrail nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guardrail: Option<GuardrailReport>,
+    /// Time the node waited for pacing, for paced nodes and spaced LLM calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pacing: Option<PacingDelays>,
 }
 
 impl NodeExecutionResult {
@@ -42,6 +62,11 @@ impl NodeExecutionResult {
             completed_at: None,
             retry_count: 0,
             node_id,
+            output_validation: None,
+            idempotency_key: None,
+            debug: None,
+            guardrail: None,
+            pacing: None,
         }
     }
 
@@ -57,6 +82,11 @@ impl NodeExecutionResult {
             completed_at: None,
             retry_count: 0,
             node_id,
+            output_validation: None,
+            idempotency_key: None,
+            debug: None,
+            guardrail: None,
+            pacing: None,
         }
     }
 
@@ -78,6 +108,49 @@ impl NodeExecutionResult {
         self
     }
 
+    /// Attach the output schema validation outcome
+    pub fn with_output_validation(mut self, report: Option<OutputValidationReport>) -> Self {
+        self.output_validation = report;
+        self
+    }
+
+    /// Set the idempotency key the node's attempts used
+    #[must_use]
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Attach the prompt and provider payloads captured for the node
+    #[must_use]
+    pub fn with_debug(mut self, debug: Option<NodeDebugCapture>) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Attach the report of a guardrail node
+    #[must_use]
+    pub fn with_guardrail(mut self, report: Option<GuardrailReport>) -> Self {
+        self.guardrail = report;
+        self
+    }
+
+    /// Attach the time the node waited for pacing, unless it did not wait
+    #[must_use]
+    pub fn with_pacing(mut self, pacing: PacingDelays) -> Self {
+        self.pacing = (!pacing.is_zero()).then_some(pacing);
+        self
+    }
+
+    /// Idempotency key an earlier attempt of the node already used, if the node
+    /// was retried. Side effects made under this key may already have happened.
+    #[must_use]
+    pub fn previous_attempt_key(&self) -> Option<&str> {
+        self.idempotency_key
+            .as_deref()
+            .filter(|_| self.retry_count > 0)
+    }
+
     /// Mark the result as completed
     #[inline]
     pub fn mark_completed(mut self) -> Self {
@@ -98,10 +171,197 @@ impl Default for NodeExecutionResult {
             completed_at: None,
             retry_count: 0,
             node_id: NodeId::new(),
+            output_validation: None,
+            idempotency_key: None,
+            debug: None,
+            guardrail: None,
+            pacing: None,
+        }
+    }
+}
+
+/// What happened to a node the executor abandoned, because a join stopped
+/// waiting for it, the budget ran out, the tag filter excluded it, a node it
+/// depends on failed, fail-fast stopped the run, the executor shut down or a
+/// condition node took another branch, recorded on the [`WorkflowContext`](super::WorkflowContext)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbandonedNode {
+    /// The node had not started and never ran
+    Skipped {
+        /// Why the node was skipped
+        reason: SkipReason,
+    },
+    /// The node was stopped while running
+    Cancelled,
+    /// The node was left running in the background; its output is discarded
+    Detached,
+    /// The node had not started and never ran because nodes it depends on,
+    /// directly or through other blocked nodes, failed
+    Blocked {
+        /// Names of the failed nodes
+        blocked_by: Vec<String>,
+    },
+}
+
+/// Why the executor skipped a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// A join with an `any` or `quorum` policy stopped waiting for the node's branch
+    Join,
+    /// The budget ran out before the node started
+    Budget,
+    /// The tag filter of the execution excluded the node, or a node it depends on
+    Tag,
+    /// A node failed with fail-fast on, and only its compensation nodes still ran
+    FailFast,
+    /// The executor shut down and cancelled the execution
+    Shutdown,
+    /// A condition node took a branch the node is not on
+    Condition,
+}
+
+/// Node tags selecting which nodes of a workflow run, set per execution with
+/// [`WorkflowContext::with_tag_filter`](super::WorkflowContext::with_tag_filter)
+///
+/// A node is excluded when it carries one of the `skip` tags, or when `only`
+/// is not empty and the node carries none of its tags. The dependents of an
+/// excluded node are skipped too, unless the excluded node is tagged
+/// [`PASSTHROUGH_ON_SKIP_TAG`]: its input is then passed on as its output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagFilter {
+    /// Run only nodes carrying one of these tags; every node when empty
+    #[serde(default)]
+    pub only: Vec<String>,
+    /// Skip nodes carrying any of these tags
+    #[serde(default)]
+    pub skip: Vec<String>,
+}
+
+/// Tag of nodes that pass their input on as their output when the tag filter
+/// excludes them, instead of skipping their dependents
+pub const PASSTHROUGH_ON_SKIP_TAG: &str = "passthrough_on_skip";
+
+impl TagFilter {
+    /// Run only nodes carrying one of `tags`
+    #[must_use]
+    pub fn with_only_tags(mut self, tags: Vec<String>) -> Self {
+        self.only = tags;
+        self
+    }
+
+    /// Skip nodes carrying any of `tags`
+    #[must_use]
+    pub fn with_skip_tags(mut self, tags: Vec<String>) -> Self {
+        self.skip = tags;
+        self
+    }
+
+    /// Whether the filter selects every node
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.skip.is_empty()
+    }
+
+    /// Whether the filter excludes a node carrying `tags`
+    #[must_use]
+    pub fn excludes(&self, tags: &[String]) -> bool {
+        tags.iter().any(|tag| self.skip.contains(tag))
+            || (!self.only.is_empty() && !tags.iter().any(|tag| self.only.contains(tag)))
+    }
+}
+
+/// Summary of one executed node, recorded on the
+/// [`WorkflowContext`](super::WorkflowContext) in the order nodes finished
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeExecutionRecord {
+    /// ID of the executed node
+    pub node_id: NodeId,
+    /// Name of the executed node
+    pub node_name: String,
+    /// 1-based scheduling step; nodes in the same step ran concurrently
+    pub step: usize,
+    /// Whether the node succeeded
+    pub success: bool,
+    /// Error message if the node failed
+    pub error: Option<String>,
+    /// When the node started, its first attempt included
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Execution duration in milliseconds, retries included
+    pub duration_ms: u64,
+    /// Number of retries after the first attempt
+    pub retry_count: u32,
+    /// Idempotency key shared by all attempts of the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Time the node waited for pacing, outside of and within its duration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pacing: Option<PacingDelays>,
+}
+
+impl NodeExecutionRecord {
+    /// Record a node result produced at scheduling `step`
+    #[must_use]
+    pub fn from_result(result: &NodeExecutionResult, node_name: &str, step: usize) -> Self {
+        let completed_at = result.completed_at.unwrap_or_else(chrono::Utc::now);
+        let duration =
+            chrono::Duration::milliseconds(i64::try_from(result.duration_ms).unwrap_or(i64::MAX));
+        Self {
+            node_id: result.node_id.clone(),
+            node_name: node_name.to_string(),
+            step,
+            success: result.success,
+            error: result.error.clone(),
+            started_at: completed_at - duration,
+            duration_ms: result.duration_ms,
+            retry_count: result.retry_count,
+            idempotency_key: result.idempotency_key.clone(),
+            pacing: result.pacing,
         }
     }
 }
 
+/// Outcome of validating an agent node's output against its `output_schema`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputValidationReport {
+    /// Number of repair requests sent to the model after a failed validation
+    pub repair_attempts: u32,
+    /// Validation result for the final output
+    pub validation: ValidationResult,
+    /// Repairs made to recover JSON from the final output when it did not parse
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub json_repairs: Vec<JsonRepair>,
+}
+
+impl OutputValidationReport {
+    /// Default number of repair attempts before an invalid output fails the node
+    pub const DEFAULT_MAX_REPAIR_ATTEMPTS: u32 = 2;
+
+    /// Create a report for the final validation after `repair_attempts` repairs
+    #[must_use]
+    pub const fn new(repair_attempts: u32, validation: ValidationResult) -> Self {
+        Self {
+            repair_attempts,
+            validation,
+            json_repairs: Vec::new(),
+        }
+    }
+
+    /// Record the JSON repairs made to the final output
+    #[must_use]
+    pub fn with_json_repairs(mut self, json_repairs: Vec<JsonRepair>) -> Self {
+        self.json_repairs = json_repairs;
+        self
+    }
+
+    /// Whether the final output satisfied the schema
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        self.validation.is_valid
+    }
+}
+
 /// Workflow execution statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowExecutionStats {
@@ -111,6 +371,11 @@ pub struct WorkflowExecutionStats {
     pub successful_nodes: usize,
     /// Number of nodes that failed
     pub failed_nodes: usize,
+    /// Number of nodes that never ran: branches a condition did not choose,
+    /// nodes a join or the budget stopped waiting for and nodes blocked by a
+    /// failed dependency
+    #[serde(default)]
+    pub skipped_nodes: usize,
     /// Average execution time per node in milliseconds
     pub avg_execution_time_ms: f64,
     /// Maximum concurrent nodes executed at once
@@ -123,4 +388,282 @@ pub struct WorkflowExecutionStats {
     pub semaphore_acquisitions: u64,
     /// Average wait time for semaphore acquisition
     pub avg_semaphore_wait_ms: f64,
-}
\ No newline at end of file
+    /// Total number of tool calls made by agent nodes
+    #[serde(default)]
+    pub total_tool_calls: usize,
+    /// Number of tool calls that failed
+    #[serde(default)]
+    pub failed_tool_calls: usize,
+    /// Number of distinct node outputs spilled to the output store
+    #[serde(default)]
+    pub spilled_outputs: usize,
+    /// Total size in bytes of the distinct node outputs spilled to the output store
+    #[serde(default)]
+    pub spilled_bytes: u64,
+    /// Tokens and estimated cost of the LLM calls, in total, by node tag and by
+    /// attributed workflow metadata value
+    #[serde(default)]
+    pub usage: UsageBreakdown,
+}
+/// A single tool invocation made by an agent node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    /// Name of the invoked tool
+    pub tool_name: String,
+    /// Arguments supplied by the model
+    pub arguments: serde_json::Value,
+    /// Tool output, or the error message when the call failed
+    pub output: serde_json::Value,
+    /// Whether the tool call succeeded
+    pub success: bool,
+    /// Execution duration in milliseconds
+    pub duration_ms: f64,
+    /// 1-based count of calls to this tool within the node, so a retried call has `attempt > 1`
+    pub attempt: u32,
+}
+
+impl ToolCallRecord {
+    /// Create a record for a tool call; `attempt` is assigned when it is recorded
+    /// on the [`WorkflowContext`](super::WorkflowContext)
+    pub fn new(
+        tool_name: impl Into<String>,
+        arguments: serde_json::Value,
+        output: serde_json::Value,
+        success: bool,
+        duration_ms: f64,
+    ) -> Self {
+        Self {
+            tool_name: tool_name.into(),
+            arguments,
+            output,
+            success,
+            duration_ms,
+            attempt: 1,
+        }
+    }
+}
+
+/// Controls how tool call arguments and outputs are captured in [`ToolCallRecord`]s
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCallTraceConfig {
+    /// Replace recorded arguments and outputs with a placeholder
+    pub redact_payloads: bool,
+    /// Truncate recorded arguments and outputs longer than this many characters
+    pub max_payload_chars: Option<usize>,
+}
+
+impl ToolCallTraceConfig {
+    /// Placeholder stored in place of redacted payloads
+    pub const REDACTED: &'static str = "[REDACTED]";
+
+    /// Redact all recorded payloads
+    #[must_use]
+    pub const fn redacted() -> Self {
+        Self {
+            redact_payloads: true,
+            max_payload_chars: None,
+        }
+    }
+
+    /// Truncate recorded payloads to at most `max_chars` characters
+    #[must_use]
+    pub const fn truncated(max_chars: usize) -> Self {
+        Self {
+            redact_payloads: false,
+            max_payload_chars: Some(max_chars),
+        }
+    }
+
+    /// Apply redaction and truncation to a record's payloads
+    #[must_use]
+    pub fn apply(&self, mut record: ToolCallRecord) -> ToolCallRecord {
+        record.arguments = self.capture(record.arguments);
+        record.output = self.capture(record.output);
+        record
+    }
+
+    fn capture(&self, value: serde_json::Value) -> serde_json::Value {
+        if self.redact_payloads {
+            return serde_json::Value::String(Self::REDACTED.to_string());
+        }
+        let Some(max_chars) = self.max_payload_chars else {
+            return value;
+        };
+
+        let text = match value {
+            serde_json::Value::String(text) => text,
+            other => {
+                let text = other.to_string();
+                if text.chars().count() <= max_chars {
+                    return other;
+                }
+                text
+            }
+        };
+        let total_chars = text.chars().count();
+        if total_chars <= max_chars {
+            return serde_json::Value::String(text);
+        }
+        let truncated: String = text.chars().take(max_chars).collect();
+        serde_json::Value::String(format!(
+            "{truncated}... [truncated {} chars]",
+            total_chars - max_chars
+        ))
+    }
+}
+
+/// Controls the prompts and provider payloads captured for each node when an
+/// executor runs with payload capture, see [`NodeDebugCapture`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadCaptureConfig {
+    /// Truncate captured prompts and payloads longer than this many characters
+    pub max_payload_chars: usize,
+    /// Keep the captures when the workflow context is serialized
+    pub persist: bool,
+}
+
+impl PayloadCaptureConfig {
+    /// Default limit on the length of a captured prompt or payload
+    pub const DEFAULT_MAX_PAYLOAD_CHARS: usize = 64 * 1024;
+
+    /// Truncate captured prompts and payloads longer than `max_chars` characters
+    #[must_use]
+    pub const fn with_max_payload_chars(mut self, max_chars: usize) -> Self {
+        self.max_payload_chars = max_chars;
+        self
+    }
+
+    /// Keep the captures when the workflow context is serialized
+    #[must_use]
+    pub const fn with_persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    /// `text` cut to the length limit, and whether it was cut
+    #[must_use]
+    pub fn truncate(self, text: String) -> (String, bool) {
+        let total_chars = text.chars().count();
+        if total_chars <= self.max_payload_chars {
+            return (text, false);
+        }
+        let truncated: String = text.chars().take(self.max_payload_chars).collect();
+        (
+            format!(
+                "{truncated}... [truncated {} chars]",
+                total_chars - self.max_payload_chars
+            ),
+            true,
+        )
+    }
+}
+
+impl Default for PayloadCaptureConfig {
+    fn default() -> Self {
+        Self {
+            max_payload_chars: Self::DEFAULT_MAX_PAYLOAD_CHARS,
+            persist: false,
+        }
+    }
+}
+
+/// A request sent to an LLM provider and the response it returned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderExchange {
+    /// Provider the request was sent to
+    pub provider: String,
+    /// HTTP method of the request
+    pub method: String,
+    /// Request URL, with the values of secret query parameters redacted
+    pub url: String,
+    /// Request headers, with credentials redacted
+    pub request_headers: BTreeMap<String, String>,
+    /// Serialized request body, with credentials redacted
+    pub request_body: String,
+    /// HTTP status of the response, if one was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Raw response body, if one was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_body: Option<String>,
+    /// Error the request failed with before a response was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the request or response body was cut to the length limit
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Rendered prompt and raw provider payloads of one node, captured when the
+/// executor runs with payload capture
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeDebugCapture {
+    /// System prompt sent along with the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Prompt after template resolution, as sent to the provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered_prompt: Option<String>,
+    /// Provider requests and responses in the order they were made, retries included
+    #[serde(default)]
+    pub exchanges: Vec<ProviderExchange>,
+    /// Conversations sent by the follow-up requests of an agent's tool loop
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub histories: Vec<SentHistory>,
+    /// Output of a node with post-processors, from before they ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+}
+
+impl NodeDebugCapture {
+    /// Whether nothing was captured
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.system_prompt.is_none()
+            && self.rendered_prompt.is_none()
+            && self.exchanges.is_empty()
+            && self.histories.is_empty()
+            && self.raw.is_none()
+    }
+}
+
+/// Conversation sent with a follow-up request of an agent's tool loop, after
+/// the node's history window and context window were applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SentHistory {
+    /// Tool-loop iteration the request was sent in
+    pub iteration: u32,
+    /// Messages sent, as `Role: content` lines cut to the length limit
+    pub messages: Vec<String>,
+    /// Leading tool exchanges the history window left out
+    #[serde(default)]
+    pub dropped_exchanges: usize,
+    /// Leading tool exchanges replaced by a summary note
+    #[serde(default)]
+    pub summarized_exchanges: usize,
+}
+
+/// Payload captures of the nodes of an execution, recorded on the
+/// [`WorkflowContext`](super::WorkflowContext)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeDebugCaptures {
+    /// Whether the captures are serialized with the context; captures read
+    /// from a serialized context were persisted
+    #[serde(skip_serializing, default = "persisted")]
+    pub persist: bool,
+    /// Captures keyed by node name
+    #[serde(default)]
+    pub nodes: HashMap<String, NodeDebugCapture>,
+}
+
+impl NodeDebugCaptures {
+    /// Whether the captures are left out when the context is serialized
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        !self.persist || self.nodes.is_empty()
+    }
+}
+
+const fn persisted() -> bool {
+    true
+}
@@ -6,15 +6,21 @@
 pub mod ids;
 pub mod message;
 pub mod context;
+pub mod node_outputs;
 pub mod execution;
 pub mod retry;
 pub mod circuit_breaker;
 pub mod concurrency;
+pub mod secrets;
+pub mod prompt;
 
 pub use ids::*;
 pub use message::*;
 pub use context::*;
+pub use node_outputs::NodeOutputs;
 pub use execution::*;
 pub use retry::*;
 pub use circuit_breaker::*;
-pub use concurrency::*;
\ No newline at end of file
+pub use concurrency::*;
+pub use secrets::*;
+pub use prompt::PromptDefaults;
\ No newline at end of file
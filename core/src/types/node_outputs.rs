@@ -0,0 +1,260 @@
+//! Node outputs shared between a workflow context and its snapshots
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Number of shards the outputs are split over, and of buckets per shard
+const SHARD_COUNT: usize = 64;
+
+type Bucket = HashMap<String, Arc<serde_json::Value>>;
+
+/// Buckets of one shard, created on their first output
+type Shard = [Option<Arc<Bucket>>; SHARD_COUNT];
+
+/// Node outputs keyed by node id and name, behaving like a
+/// `HashMap<String, serde_json::Value>`.
+///
+/// Outputs are immutable once stored and shared through `Arc`s, in shards
+/// picked by key hash and split into buckets, all shared between clones.
+/// Cloning copies one pointer per shard, so a node can take a snapshot of the
+/// outputs while holding the context lock and read it after releasing the
+/// lock. Storing an output copies the bucket pointers of its shard and the
+/// keys of its bucket, and only while a snapshot still shares them; output
+/// values are never copied. A write under the context lock therefore costs
+/// about the same however many outputs are stored, and wide fan-outs whose
+/// running nodes each hold a snapshot don't grow quadratic to write to.
+#[derive(Clone)]
+pub struct NodeOutputs {
+    /// Shards, created on their first output
+    shards: [Option<Arc<Shard>>; SHARD_COUNT],
+    len: usize,
+}
+
+impl Default for NodeOutputs {
+    fn default() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+}
+
+impl NodeOutputs {
+    /// Create an empty set of node outputs
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shard and bucket of `key`
+    fn slot(key: &str) -> (usize, usize) {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish().to_le_bytes();
+        (
+            usize::from(hash[0]) % SHARD_COUNT,
+            usize::from(hash[1]) % SHARD_COUNT,
+        )
+    }
+
+    fn bucket(&self, key: &str) -> Option<&Bucket> {
+        let (shard, bucket) = Self::slot(key);
+        self.shards[shard].as_deref()?[bucket].as_deref()
+    }
+
+    fn buckets(&self) -> impl Iterator<Item = &Bucket> {
+        self.shards
+            .iter()
+            .flatten()
+            .flat_map(|shard| shard.iter().flatten())
+            .map(AsRef::as_ref)
+    }
+
+    /// Number of stored outputs
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no output is stored
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether an output is stored under `key`
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.bucket(key)
+            .is_some_and(|bucket| bucket.contains_key(key))
+    }
+
+    /// Output stored under `key`
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.bucket(key)?.get(key).map(AsRef::as_ref)
+    }
+
+    /// Output stored under `key`, sharing it rather than borrowing it
+    #[must_use]
+    pub fn get_shared(&self, key: &str) -> Option<Arc<serde_json::Value>> {
+        self.bucket(key)?.get(key).cloned()
+    }
+
+    /// Number of shards holding outputs that `other` does not share with this
+    /// set of outputs: none right after either was cloned from the other, then
+    /// one more for each shard a write has copied since
+    #[must_use]
+    pub fn unshared_shards(&self, other: &Self) -> usize {
+        self.shards
+            .iter()
+            .zip(&other.shards)
+            .filter(|(shard, other)| match (shard, other) {
+                (Some(shard), Some(other)) => !Arc::ptr_eq(shard, other),
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .count()
+    }
+
+    /// Store `value` under `key`, returning the output it replaces
+    pub fn insert(
+        &mut self,
+        key: String,
+        value: impl Into<Arc<serde_json::Value>>,
+    ) -> Option<Arc<serde_json::Value>> {
+        let (shard, bucket) = Self::slot(&key);
+        let shard =
+            self.shards[shard].get_or_insert_with(|| Arc::new(std::array::from_fn(|_| None)));
+        let bucket = Arc::make_mut(shard)[bucket].get_or_insert_with(Arc::default);
+        let previous = Arc::make_mut(bucket).insert(key, value.into());
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Remove the output stored under `key`
+    pub fn remove(&mut self, key: &str) -> Option<Arc<serde_json::Value>> {
+        // Leave a shard shared with snapshots alone when there is nothing to remove
+        if !self.contains_key(key) {
+            return None;
+        }
+        let (shard, bucket) = Self::slot(key);
+        let shard = Arc::make_mut(self.shards[shard].as_mut()?);
+        let removed = Arc::make_mut(shard[bucket].as_mut()?).remove(key);
+        self.len -= 1;
+        removed
+    }
+
+    /// Remove every output
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Keys of the stored outputs, in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.buckets().flat_map(HashMap::keys)
+    }
+
+    /// Stored outputs, in no particular order
+    pub fn values(&self) -> impl Iterator<Item = &serde_json::Value> {
+        self.buckets().flat_map(HashMap::values).map(AsRef::as_ref)
+    }
+
+    /// Keys and outputs, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &serde_json::Value)> {
+        self.buckets()
+            .flat_map(HashMap::iter)
+            .map(|(key, value)| (key, value.as_ref()))
+    }
+
+    /// Call `f` on every output, copying the outputs snapshots still share first
+    pub fn for_each_value_mut(&mut self, mut f: impl FnMut(&mut serde_json::Value)) {
+        if self.is_empty() {
+            return;
+        }
+        for shard in self.shards.iter_mut().flatten() {
+            for bucket in Arc::make_mut(shard).iter_mut().flatten() {
+                for value in Arc::make_mut(bucket).values_mut() {
+                    f(Arc::make_mut(value));
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for NodeOutputs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a NodeOutputs {
+    type Item = (&'a String, &'a serde_json::Value);
+    type IntoIter = Box<dyn Iterator<Item = Self::Item> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl IntoIterator for NodeOutputs {
+    type Item = (String, serde_json::Value);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.shards
+            .into_iter()
+            .flatten()
+            .flat_map(|shard| Arc::try_unwrap(shard).unwrap_or_else(|shared| (*shared).clone()))
+            .flatten()
+            .flat_map(|bucket| Arc::try_unwrap(bucket).unwrap_or_else(|shared| (*shared).clone()))
+            .map(|(key, value)| {
+                (
+                    key,
+                    Arc::try_unwrap(value).unwrap_or_else(|shared| (*shared).clone()),
+                )
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl Extend<(String, serde_json::Value)> for NodeOutputs {
+    fn extend<I: IntoIterator<Item = (String, serde_json::Value)>>(&mut self, outputs: I) {
+        for (key, value) in outputs {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl FromIterator<(String, serde_json::Value)> for NodeOutputs {
+    fn from_iter<I: IntoIterator<Item = (String, serde_json::Value)>>(outputs: I) -> Self {
+        let mut node_outputs = Self::new();
+        node_outputs.extend(outputs);
+        node_outputs
+    }
+}
+
+impl From<HashMap<String, serde_json::Value>> for NodeOutputs {
+    fn from(outputs: HashMap<String, serde_json::Value>) -> Self {
+        outputs.into_iter().collect()
+    }
+}
+
+impl Serialize for NodeOutputs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeOutputs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::<String, serde_json::Value>::deserialize(deserializer).map(Self::from)
+    }
+}
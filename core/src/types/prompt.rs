@@ -0,0 +1,42 @@
+//! Prompt settings an executor applies to every agent node
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Text added to the prompts of every agent node an executor runs, and
+/// template variables shared by every node.
+///
+/// Globals are referenced as `{{globals.NAME}}` in prompt, context, code and
+/// command templates, and in the preamble and suffix themselves. A node's own
+/// `globals` configuration takes precedence over the executor's.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptDefaults {
+    /// Text placed before every agent node's system prompt
+    pub system_preamble: Option<String>,
+    /// Text appended to every agent node's prompt
+    pub prompt_suffix: Option<String>,
+    /// Template variables available to every node
+    pub globals: HashMap<String, serde_json::Value>,
+}
+
+impl PromptDefaults {
+    /// Whether no preamble, suffix or global is set
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.system_preamble.is_none() && self.prompt_suffix.is_none() && self.globals.is_empty()
+    }
+}
+
+/// Join two optional prompt sections with a blank line, skipping empty ones
+pub(crate) fn join_prompt_sections(
+    first: Option<String>,
+    second: Option<String>,
+) -> Option<String> {
+    match (
+        first.filter(|s| !s.is_empty()),
+        second.filter(|s| !s.is_empty()),
+    ) {
+        (Some(first), Some(second)) => Some(format!("{first}\n\n{second}")),
+        (first, second) => first.or(second),
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use super::ids::DEFAULT_TIMEOUT_MS;
 
 /// Retry configuration for node execution
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RetryConfig {
     /// Maximum number of retry attempts (0 means no retries)
     pub max_attempts: u32,
@@ -143,6 +143,10 @@ pub enum RetryableErrorType {
 impl RetryableErrorType {
     /// Determine retry type from error
     pub fn from_error(error: &crate::errors::GraphBitError) -> Self {
+        if matches!(error, crate::errors::GraphBitError::Timeout { .. }) {
+            return Self::TimeoutError;
+        }
+
         // Simple error type classification based on error message
         let error_str = error.to_string().to_lowercase();
 
@@ -0,0 +1,176 @@
+//! Secret values supplied to a single workflow execution
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::LazyLock;
+
+use crate::errors::{GraphBitError, GraphBitResult};
+
+static SECRET_REF_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{secret\.([a-zA-Z0-9_\-]+)\}\}").unwrap());
+
+/// Named secret values for one workflow execution.
+///
+/// Secrets are referenced as `{{secret.NAME}}` in HTTP request node URLs and
+/// headers and in LLM configurations. They are never serialized, their `Debug`
+/// output lists names only, and their values are replaced with
+/// [`Secrets::REDACTED`] in node outputs, errors and the returned context.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secrets {
+    values: HashMap<String, String>,
+}
+
+impl Secrets {
+    /// Placeholder stored in place of a secret value
+    pub const REDACTED: &'static str = "[REDACTED]";
+
+    /// Create an empty set of secrets
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a secret
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Get the value of a secret
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Names of the secrets, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Number of secrets
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no secrets
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Replace every `{{secret.NAME}}` reference in `text` with the secret's value.
+    ///
+    /// A reference to a secret that was not supplied is a configuration error.
+    pub fn inject(&self, text: &str) -> GraphBitResult<String> {
+        if !text.contains("{{secret.") {
+            return Ok(text.to_string());
+        }
+        let mut missing = None;
+        let injected = SECRET_REF_PATTERN.replace_all(text, |cap: &regex::Captures<'_>| {
+            self.get(&cap[1]).map_or_else(
+                || {
+                    missing.get_or_insert_with(|| cap[1].to_string());
+                    String::new()
+                },
+                str::to_string,
+            )
+        });
+        missing.map_or_else(
+            || Ok(injected.into_owned()),
+            |name| {
+                Err(GraphBitError::config(format!(
+                    "Secret '{name}' is referenced but was not supplied"
+                )))
+            },
+        )
+    }
+
+    /// Apply [`Secrets::inject`] to every string inside a JSON value
+    pub fn inject_value(&self, value: &mut serde_json::Value) -> GraphBitResult<()> {
+        match value {
+            serde_json::Value::String(text) => *text = self.inject(text)?,
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.inject_value(item)?;
+                }
+            }
+            serde_json::Value::Object(entries) => {
+                for item in entries.values_mut() {
+                    self.inject_value(item)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Replace every secret value occurring in `text` with [`Secrets::REDACTED`]
+    #[must_use]
+    pub fn redact(&self, text: &str) -> String {
+        // Longer values first, so a secret containing another is redacted whole
+        let mut values: Vec<&str> = self
+            .values
+            .values()
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+            .collect();
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+
+        let mut redacted = text.to_string();
+        for value in values {
+            if redacted.contains(value) {
+                redacted = redacted.replace(value, Self::REDACTED);
+            }
+        }
+        redacted
+    }
+
+    /// Apply [`Secrets::redact`] to every string inside a JSON value
+    pub fn redact_value(&self, value: &mut serde_json::Value) {
+        if self.is_empty() {
+            return;
+        }
+        match value {
+            serde_json::Value::String(text) => *text = self.redact(text),
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.redact_value(item);
+                }
+            }
+            serde_json::Value::Object(entries) => {
+                for item in entries.values_mut() {
+                    self.redact_value(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&str> = self.names().collect();
+        names.sort_unstable();
+        f.debug_map()
+            .entries(names.into_iter().map(|name| (name, Self::REDACTED)))
+            .finish()
+    }
+}
+
+impl From<HashMap<String, String>> for Secrets {
+    fn from(values: HashMap<String, String>) -> Self {
+        Self { values }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Secrets {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            values: iter
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+}
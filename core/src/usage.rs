@@ -0,0 +1,263 @@
+//! Token usage and estimated cost attributed to node tags and workflow metadata
+//!
+//! Every execution sums the tokens of its LLM calls into the
+//! [`UsageBreakdown`] of its [`WorkflowExecutionStats`]: in total, by node tag,
+//! counting a node with several tags toward each, and by the value the workflow
+//! has for each metadata key the executor attributes usage to, `team` unless
+//! set with
+//! [`WorkflowExecutor::with_usage_metadata_keys`](crate::workflow::WorkflowExecutor::with_usage_metadata_keys).
+//! Calls are priced like the execution budget prices them, with the providers'
+//! costs per token; the tokens of calls to models without a price count at no
+//! cost.
+//!
+//! With a run history,
+//! [`WorkflowExecutor::usage_summary`](crate::workflow::WorkflowExecutor::usage_summary)
+//! adds up the breakdowns of the runs started in a period, to charge spend back
+//! to the teams that incurred it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::llm::LlmUsage;
+use crate::run_history::RunRecord;
+use crate::types::{WorkflowContext, WorkflowExecutionStats};
+use crate::workflow::Workflow;
+
+/// Tokens and estimated cost of LLM calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageCost {
+    /// Tokens used
+    pub usage: LlmUsage,
+    /// Estimated cost in USD
+    pub cost_usd: f64,
+}
+
+impl Default for UsageCost {
+    fn default() -> Self {
+        Self {
+            usage: LlmUsage::empty(),
+            cost_usd: 0.0,
+        }
+    }
+}
+
+impl UsageCost {
+    /// Add the tokens and cost of `other`
+    pub fn add(&mut self, other: &Self) {
+        self.usage.add(&other.usage);
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// Tokens and estimated cost of one or more runs, in total and attributed to
+/// node tags and workflow metadata values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageBreakdown {
+    /// Usage of every LLM call
+    pub total: UsageCost,
+    /// Usage by node tag; a node with several tags counts toward each and an
+    /// untagged node toward none
+    #[serde(default)]
+    pub by_tag: BTreeMap<String, UsageCost>,
+    /// Usage by attributed workflow metadata key, then by the value of the key
+    #[serde(default)]
+    pub by_metadata: BTreeMap<String, BTreeMap<String, UsageCost>>,
+}
+
+impl UsageBreakdown {
+    /// Whether no tokens were used
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.total.usage.total_tokens == 0
+    }
+
+    /// Add the usage of `other`
+    pub fn merge(&mut self, other: &Self) {
+        self.total.add(&other.total);
+        for (tag, usage) in &other.by_tag {
+            self.by_tag.entry(tag.clone()).or_default().add(usage);
+        }
+        for (key, values) in &other.by_metadata {
+            let by_value = self.by_metadata.entry(key.clone()).or_default();
+            for (value, usage) in values {
+                by_value.entry(value.clone()).or_default().add(usage);
+            }
+        }
+    }
+}
+
+/// Usage of the runs a run history recorded over a period, from
+/// [`WorkflowExecutor::usage_summary`](crate::workflow::WorkflowExecutor::usage_summary)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSummary {
+    /// Start of the period
+    pub since: DateTime<Utc>,
+    /// Number of runs started in the period
+    pub runs: usize,
+    /// Their usage added up
+    #[serde(flatten)]
+    pub usage: UsageBreakdown,
+}
+
+impl UsageSummary {
+    /// Add up the usage of `runs`, started at or after `since`
+    #[must_use]
+    pub fn from_runs(since: DateTime<Utc>, runs: &[RunRecord]) -> Self {
+        let mut usage = UsageBreakdown::default();
+        for stats in runs.iter().filter_map(|run| run.stats.as_ref()) {
+            usage.merge(&stats.usage);
+        }
+        Self {
+            since,
+            runs: runs.len(),
+            usage,
+        }
+    }
+}
+
+/// Prices, node tags and metadata values an execution attributes its usage with
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UsageAttribution {
+    /// Input and output cost per token, by model
+    prices: HashMap<String, (f64, f64)>,
+    /// Tags of every node, by node ID
+    nodes: Vec<(String, Vec<String>)>,
+    /// Attributed metadata keys the workflow has, with their values
+    metadata: Vec<(String, String)>,
+}
+
+impl UsageAttribution {
+    /// Attribute the usage of `workflow` to its node tags and the values it has
+    /// for `metadata_keys`, pricing it with `prices`
+    pub(crate) fn new(
+        workflow: &Workflow,
+        prices: HashMap<String, (f64, f64)>,
+        metadata_keys: &[String],
+    ) -> Self {
+        let nodes = workflow
+            .graph
+            .get_nodes()
+            .values()
+            .map(|node| (node.id.to_string(), node.tags.clone()))
+            .collect();
+        let metadata = metadata_keys
+            .iter()
+            .filter_map(|key| {
+                let value = match workflow.metadata.get(key)? {
+                    serde_json::Value::Null => return None,
+                    serde_json::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                Some((key.clone(), value))
+            })
+            .collect();
+        Self {
+            prices,
+            nodes,
+            metadata,
+        }
+    }
+
+    /// Input and output cost per token, by model
+    pub(crate) const fn prices(&self) -> &HashMap<String, (f64, f64)> {
+        &self.prices
+    }
+
+    /// Usage of the LLM calls recorded in `context`
+    pub(crate) fn breakdown(&self, context: &WorkflowContext) -> UsageBreakdown {
+        let mut breakdown = UsageBreakdown::default();
+        for (node_id, tags) in &self.nodes {
+            let Some(response) = context.metadata.get(&format!("node_response_{node_id}")) else {
+                continue;
+            };
+            let mut node = UsageCost::default();
+            for (model, usage) in node_calls(response) {
+                node.cost_usd += model
+                    .and_then(|model| price(&self.prices, &model, &usage))
+                    .unwrap_or(0.0);
+                node.usage.add(&usage);
+            }
+            for tag in tags {
+                breakdown.by_tag.entry(tag.clone()).or_default().add(&node);
+            }
+            breakdown.total.add(&node);
+        }
+        if !breakdown.is_empty() {
+            for (key, value) in &self.metadata {
+                breakdown
+                    .by_metadata
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(value.clone(), breakdown.total.clone());
+            }
+        }
+        breakdown
+    }
+}
+
+/// LLM calls of a node, as the model that answered and the tokens it used,
+/// falling back to the node total and the model of the node
+fn node_calls(response: &serde_json::Value) -> Vec<(Option<String>, LlmUsage)> {
+    let calls: Vec<(Option<String>, LlmUsage)> = response
+        .get("executions")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|execution| {
+            execution.get("type").and_then(serde_json::Value::as_str) == Some("llm_call")
+        })
+        .filter_map(|execution| {
+            let usage = serde_json::from_value(execution.get("usage")?.clone()).ok()?;
+            let model = execution
+                .get("model")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string);
+            Some((model, usage))
+        })
+        .collect();
+    if !calls.is_empty() {
+        return calls;
+    }
+    crate::report::node_token_usage(response)
+        .map(|usage| (crate::report::node_model(response), usage))
+        .into_iter()
+        .collect()
+}
+
+/// Price of `usage` for `model` with `prices`: the price of the model or,
+/// failing that, of the longest priced model name it starts with (for dated
+/// snapshots)
+pub(crate) fn price(
+    prices: &HashMap<String, (f64, f64)>,
+    model: &str,
+    usage: &LlmUsage,
+) -> Option<f64> {
+    let (input_cost, output_cost) = prices.get(model).copied().or_else(|| {
+        prices
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, price)| *price)
+    })?;
+    Some(
+        f64::from(usage.prompt_tokens)
+            .mul_add(input_cost, f64::from(usage.completion_tokens) * output_cost),
+    )
+}
+
+impl WorkflowExecutionStats {
+    /// Tokens and estimated cost of the run by node tag
+    #[must_use]
+    pub const fn usage_by_tag(&self) -> &BTreeMap<String, UsageCost> {
+        &self.usage.by_tag
+    }
+
+    /// Tokens and estimated cost of the run by the value of the workflow
+    /// metadata `key`, if usage was attributed to it
+    #[must_use]
+    pub fn usage_by_metadata(&self, key: &str) -> Option<&BTreeMap<String, UsageCost>> {
+        self.usage.by_metadata.get(key)
+    }
+}
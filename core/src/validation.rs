@@ -2,10 +2,17 @@
 //!
 //! This module provides comprehensive validation of LLM responses and data
 //! against JSON schemas and custom validation rules.
+//!
+//! Schemas are checked against JSON Schema draft-07. `$ref` resolves within
+//! the schema document only (`#` and JSON pointers such as
+//! `#/definitions/address`); `format` is treated as an annotation. Keywords
+//! from later drafts are reported as `UNSUPPORTED_KEYWORD` errors rather than
+//! silently ignored.
 
 use crate::errors::GraphBitResult;
 use regex;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 
 /// Result of a validation operation
@@ -195,229 +202,738 @@ impl TypeValidator {
         self.validate_json_against_schema(&json_data, schema, "root")
     }
 
+    /// Validate an already-parsed JSON value against a JSON schema
+    #[must_use]
+    pub fn validate_value(
+        &self,
+        data: &serde_json::Value,
+        schema: &serde_json::Value,
+    ) -> ValidationResult {
+        self.validate_json_against_schema(data, schema, "root")
+    }
+
     /// Validate JSON value against schema
-    #[allow(clippy::only_used_in_recursion)]
     fn validate_json_against_schema(
         &self,
         data: &serde_json::Value,
         schema: &serde_json::Value,
         path: &str,
     ) -> ValidationResult {
+        SchemaValidator::new(schema).validate(data, schema, path)
+    }
+
+    /// Validate data against a validation rule
+    pub fn validate_against_rule(&self, data: &str, rule: &ValidationRule) -> ValidationResult {
         let mut result = ValidationResult::success();
 
-        // Extract schema properties
-        let schema_obj = match schema.as_object() {
-            Some(obj) => obj,
-            None => {
+        // Schema validation
+        if let Some(schema) = &rule.schema {
+            let schema_result = self.validate_against_schema(data, schema);
+            result.merge(schema_result);
+        }
+
+        // Custom validator
+        if let Some(validator_name) = &rule.validator_function {
+            if let Some(validator) = self.custom_validators.get(validator_name) {
+                match validator.validate(data, &rule.config) {
+                    Ok(custom_result) => result.merge(custom_result),
+                    Err(e) => {
+                        result.add_error(ValidationError::new(
+                            "custom_validator",
+                            format!("Custom validation failed: {e}"),
+                            "CUSTOM_VALIDATION_ERROR",
+                        ));
+                    }
+                }
+            } else {
+                result.add_error(ValidationError::new(
+                    "validator",
+                    format!("Unknown validator: {validator_name}"),
+                    "UNKNOWN_VALIDATOR",
+                ));
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for TypeValidator {
+    fn default() -> Self {
+        Self {
+            custom_validators: HashMap::with_capacity(8),
+        }
+    }
+}
+
+/// Trait for custom validators
+pub trait CustomValidator: Send + Sync {
+    /// Validate data with custom logic
+    fn validate(
+        &self,
+        data: &str,
+        config: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<ValidationResult>;
+
+    /// Get validator name
+    fn name(&self) -> &str;
+
+    /// Get validator description
+    fn description(&self) -> &str;
+}
+
+/// Keywords of later JSON Schema drafts that the draft-07 validator does not
+/// implement; schemas using them are rejected instead of being half-checked
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "$anchor",
+    "$dynamicAnchor",
+    "$dynamicRef",
+    "$recursiveAnchor",
+    "$recursiveRef",
+    "dependentRequired",
+    "dependentSchemas",
+    "maxContains",
+    "minContains",
+    "prefixItems",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// JSON Schema draft-07 validator bound to one root schema document
+struct SchemaValidator<'s> {
+    /// Document that `$ref` pointers resolve against
+    root: &'s Value,
+    /// `$ref` targets being applied, paired with the instance they apply to,
+    /// so a reference cycle that consumes no data is reported, not followed
+    active_refs: Vec<(*const Value, *const Value)>,
+}
+
+impl<'s> SchemaValidator<'s> {
+    const fn new(root: &'s Value) -> Self {
+        Self {
+            root,
+            active_refs: Vec::new(),
+        }
+    }
+
+    fn validate(&mut self, data: &Value, schema: &'s Value, path: &str) -> ValidationResult {
+        match schema {
+            Value::Bool(true) => ValidationResult::success(),
+            Value::Bool(false) => ValidationResult::failure(vec![ValidationError::new(
+                path,
+                "No value is allowed here",
+                "FALSE_SCHEMA",
+            )]),
+            Value::Object(schema_obj) => self.validate_with_object(data, schema_obj, path),
+            _ => ValidationResult::failure(vec![ValidationError::new(
+                path,
+                "Schema must be an object or a boolean",
+                "INVALID_SCHEMA",
+            )]),
+        }
+    }
+
+    fn validate_with_object(
+        &mut self,
+        data: &Value,
+        schema_obj: &'s Map<String, Value>,
+        path: &str,
+    ) -> ValidationResult {
+        let mut result = ValidationResult::success();
+
+        for keyword in UNSUPPORTED_KEYWORDS {
+            if schema_obj.contains_key(*keyword) {
                 result.add_error(ValidationError::new(
                     path,
-                    "Schema must be an object",
-                    "INVALID_SCHEMA",
+                    format!(
+                        "Unsupported JSON Schema keyword '{keyword}' (only draft-07 is supported)"
+                    ),
+                    "UNSUPPORTED_KEYWORD",
                 ));
-                return result;
             }
-        };
+        }
+        if !result.is_valid {
+            return result;
+        }
+
+        // In draft-07 a `$ref` replaces every other keyword next to it
+        if let Some(reference) = schema_obj.get("$ref") {
+            return self.validate_ref(data, reference, path);
+        }
 
         // Check type
-        if let Some(expected_type) = schema_obj.get("type").and_then(|t| t.as_str()) {
-            let actual_type = match data {
-                serde_json::Value::Null => "null",
-                serde_json::Value::Bool(_) => "boolean",
-                serde_json::Value::Number(_) => "number",
-                serde_json::Value::String(_) => "string",
-                serde_json::Value::Array(_) => "array",
-                serde_json::Value::Object(_) => "object",
+        if let Some(expected) = schema_obj.get("type") {
+            let expected: Vec<&str> = match expected {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
             };
-
-            if expected_type != actual_type {
+            if expected.is_empty() {
+                result.add_error(ValidationError::new(
+                    path,
+                    "'type' must be a type name or an array of type names",
+                    "INVALID_SCHEMA",
+                ));
+                return result;
+            }
+            if !expected.iter().any(|name| type_matches(name, data)) {
                 result.add_error(
                     ValidationError::new(path, "Type mismatch".to_string(), "TYPE_MISMATCH")
-                        .with_expected(expected_type)
-                        .with_actual(actual_type),
+                        .with_expected(expected.join(" or "))
+                        .with_actual(json_type(data)),
                 );
                 return result;
             }
         }
 
-        // Validate object properties
-        if let (Some(data_obj), Some(properties)) = (
-            data.as_object(),
-            schema_obj.get("properties").and_then(|p| p.as_object()),
-        ) {
-            // Check required properties
-            if let Some(required) = schema_obj.get("required").and_then(|r| r.as_array()) {
-                for req_prop in required {
-                    if let Some(prop_name) = req_prop.as_str() {
-                        if !data_obj.contains_key(prop_name) {
-                            result.add_error(ValidationError::new(
-                                format!("{path}.{prop_name}"),
-                                format!("Required property '{prop_name}' is missing"),
-                                "MISSING_REQUIRED_PROPERTY",
-                            ));
-                        }
-                    }
-                }
+        // Check enumerated and constant values
+        if let Some(allowed) = schema_obj.get("enum").and_then(Value::as_array) {
+            if !allowed.iter().any(|value| json_equal(value, data)) {
+                result.add_error(
+                    ValidationError::new(
+                        path,
+                        "Value is not one of the allowed values",
+                        "ENUM_MISMATCH",
+                    )
+                    .with_expected(Value::Array(allowed.clone()).to_string())
+                    .with_actual(data.to_string()),
+                );
             }
+        }
+        if let Some(constant) = schema_obj.get("const") {
+            if !json_equal(constant, data) {
+                result.add_error(
+                    ValidationError::new(
+                        path,
+                        "Value does not equal the constant",
+                        "CONST_MISMATCH",
+                    )
+                    .with_expected(constant.to_string())
+                    .with_actual(data.to_string()),
+                );
+            }
+        }
 
-            // Validate each property
-            for (prop_name, prop_schema) in properties {
-                if let Some(prop_value) = data_obj.get(prop_name) {
-                    let prop_path = format!("{path}.{prop_name}");
-                    let prop_result =
-                        self.validate_json_against_schema(prop_value, prop_schema, &prop_path);
-                    result.merge(prop_result);
-                }
+        self.validate_combinators(data, schema_obj, path, &mut result);
+
+        match data {
+            Value::Object(data_obj) => {
+                self.validate_object(data, data_obj, schema_obj, path, &mut result);
+            }
+            Value::Array(items) => self.validate_array(items, schema_obj, path, &mut result),
+            Value::String(data_str) => validate_string(data_str, schema_obj, path, &mut result),
+            Value::Number(_) => validate_number(data, schema_obj, path, &mut result),
+            Value::Null | Value::Bool(_) => {}
+        }
+
+        result
+    }
+
+    /// Resolve a `$ref` within the root document and validate against it
+    fn validate_ref(&mut self, data: &Value, reference: &Value, path: &str) -> ValidationResult {
+        let Some(reference) = reference.as_str() else {
+            return ValidationResult::failure(vec![ValidationError::new(
+                path,
+                "'$ref' must be a string",
+                "INVALID_SCHEMA",
+            )]);
+        };
+        let Some(pointer) = reference.strip_prefix('#') else {
+            return ValidationResult::failure(vec![ValidationError::new(
+                path,
+                format!(
+                    "Unsupported reference '{reference}': only references within the schema ('#...') are resolved"
+                ),
+                "UNSUPPORTED_REF",
+            )]);
+        };
+        let Some(target) = self.root.pointer(&percent_decode(pointer)) else {
+            return ValidationResult::failure(vec![ValidationError::new(
+                path,
+                format!("Reference '{reference}' does not resolve to a schema"),
+                "UNRESOLVED_REF",
+            )]);
+        };
+
+        let key: (*const Value, *const Value) = (target, data);
+        if self.active_refs.contains(&key) {
+            return ValidationResult::failure(vec![ValidationError::new(
+                path,
+                format!("Reference '{reference}' loops back to itself without consuming data"),
+                "REF_CYCLE",
+            )]);
+        }
+        self.active_refs.push(key);
+        let result = self.validate(data, target, path);
+        self.active_refs.pop();
+        result
+    }
+
+    /// Apply `allOf`, `anyOf`, `oneOf`, `not` and `if`/`then`/`else`
+    fn validate_combinators(
+        &mut self,
+        data: &Value,
+        schema_obj: &'s Map<String, Value>,
+        path: &str,
+        result: &mut ValidationResult,
+    ) {
+        if let Some(schemas) = schema_obj.get("allOf").and_then(Value::as_array) {
+            for schema in schemas {
+                result.merge(self.validate(data, schema, path));
             }
         }
 
-        // Validate array items
-        if let (Some(data_array), Some(items_schema)) = (data.as_array(), schema_obj.get("items")) {
-            for (index, item) in data_array.iter().enumerate() {
-                let item_path = format!("{path}[{index}]");
-                let item_result = self.validate_json_against_schema(item, items_schema, &item_path);
-                result.merge(item_result);
+        if let Some(schemas) = schema_obj.get("anyOf").and_then(Value::as_array) {
+            let branches: Vec<ValidationResult> = schemas
+                .iter()
+                .map(|schema| self.validate(data, schema, path))
+                .collect();
+            if !branches.iter().any(|branch| branch.is_valid) {
+                result.add_error(ValidationError::new(
+                    path,
+                    format!(
+                        "Value does not match any schema in anyOf ({})",
+                        summarize_branches(&branches)
+                    ),
+                    "ANY_OF_MISMATCH",
+                ));
             }
         }
 
-        // Validate string constraints
-        if let Some(data_str) = data.as_str() {
-            if let Some(min_length) = schema_obj
-                .get("minLength")
-                .and_then(serde_json::Value::as_u64)
-            {
-                if (data_str.len() as u64) < min_length {
+        if let Some(schemas) = schema_obj.get("oneOf").and_then(Value::as_array) {
+            let branches: Vec<ValidationResult> = schemas
+                .iter()
+                .map(|schema| self.validate(data, schema, path))
+                .collect();
+            match branches.iter().filter(|branch| branch.is_valid).count() {
+                1 => {}
+                0 => result.add_error(ValidationError::new(
+                    path,
+                    format!(
+                        "Value does not match any schema in oneOf ({})",
+                        summarize_branches(&branches)
+                    ),
+                    "ONE_OF_MISMATCH",
+                )),
+                matched => result.add_error(ValidationError::new(
+                    path,
+                    format!("Value matches {matched} schemas in oneOf, expected exactly one"),
+                    "ONE_OF_MISMATCH",
+                )),
+            }
+        }
+
+        if let Some(schema) = schema_obj.get("not") {
+            if self.validate(data, schema, path).is_valid {
+                result.add_error(ValidationError::new(
+                    path,
+                    "Value must not match the schema in 'not'",
+                    "NOT_MISMATCH",
+                ));
+            }
+        }
+
+        if let Some(condition) = schema_obj.get("if") {
+            let branch = if self.validate(data, condition, path).is_valid {
+                schema_obj.get("then")
+            } else {
+                schema_obj.get("else")
+            };
+            if let Some(branch) = branch {
+                result.merge(self.validate(data, branch, path));
+            }
+        }
+    }
+
+    fn validate_object(
+        &mut self,
+        data: &Value,
+        data_obj: &Map<String, Value>,
+        schema_obj: &'s Map<String, Value>,
+        path: &str,
+        result: &mut ValidationResult,
+    ) {
+        let count = data_obj.len() as u64;
+        if let Some(min) = schema_obj.get("minProperties").and_then(Value::as_u64) {
+            if count < min {
+                result.add_error(ValidationError::new(
+                    path,
+                    format!("Too few properties (min: {min})"),
+                    "TOO_FEW_PROPERTIES",
+                ));
+            }
+        }
+        if let Some(max) = schema_obj.get("maxProperties").and_then(Value::as_u64) {
+            if count > max {
+                result.add_error(ValidationError::new(
+                    path,
+                    format!("Too many properties (max: {max})"),
+                    "TOO_MANY_PROPERTIES",
+                ));
+            }
+        }
+
+        // Check required properties
+        if let Some(required) = schema_obj.get("required").and_then(Value::as_array) {
+            for prop_name in required.iter().filter_map(Value::as_str) {
+                if !data_obj.contains_key(prop_name) {
                     result.add_error(ValidationError::new(
-                        path,
-                        format!("String too short (min: {min_length})"),
-                        "STRING_TOO_SHORT",
+                        format!("{path}.{prop_name}"),
+                        format!("Required property '{prop_name}' is missing"),
+                        "MISSING_REQUIRED_PROPERTY",
                     ));
                 }
             }
+        }
 
-            if let Some(max_length) = schema_obj
-                .get("maxLength")
-                .and_then(serde_json::Value::as_u64)
-            {
-                if (data_str.len() as u64) > max_length {
-                    result.add_error(ValidationError::new(
+        let properties = schema_obj.get("properties").and_then(Value::as_object);
+        let mut patterns = Vec::new();
+        if let Some(pattern_properties) = schema_obj
+            .get("patternProperties")
+            .and_then(Value::as_object)
+        {
+            for (pattern, schema) in pattern_properties {
+                match regex::Regex::new(pattern) {
+                    Ok(re) => patterns.push((re, schema)),
+                    Err(e) => result.add_error(ValidationError::new(
                         path,
-                        format!("String too long (max: {max_length})"),
-                        "STRING_TOO_LONG",
-                    ));
+                        format!("Invalid regex pattern {pattern}: {e}"),
+                        "INVALID_REGEX_PATTERN",
+                    )),
                 }
             }
+        }
+        let additional = schema_obj.get("additionalProperties");
+        let property_names = schema_obj.get("propertyNames");
 
-            if let Some(pattern) = schema_obj.get("pattern").and_then(|p| p.as_str()) {
-                // Use regex for proper pattern matching
-                match regex::Regex::new(pattern) {
-                    Ok(re) => {
-                        if !re.is_match(data_str) {
-                            result.add_error(ValidationError::new(
-                                path,
-                                format!("String does not match pattern: {pattern}"),
-                                "PATTERN_MISMATCH",
-                            ));
+        for (prop_name, prop_value) in data_obj {
+            let prop_path = format!("{path}.{prop_name}");
+
+            if let Some(schema) = property_names {
+                let name = Value::String(prop_name.clone());
+                result.merge(self.validate(&name, schema, &prop_path));
+            }
+
+            let mut declared = false;
+            if let Some(schema) = properties.and_then(|p| p.get(prop_name)) {
+                declared = true;
+                result.merge(self.validate(prop_value, schema, &prop_path));
+            }
+            for (re, schema) in &patterns {
+                if re.is_match(prop_name) {
+                    declared = true;
+                    result.merge(self.validate(prop_value, schema, &prop_path));
+                }
+            }
+
+            match additional {
+                _ if declared => {}
+                None => {}
+                // Reject unknown properties when the schema forbids them
+                Some(Value::Bool(false)) => result.add_error(ValidationError::new(
+                    prop_path,
+                    format!("Unknown property '{prop_name}'"),
+                    "UNKNOWN_PROPERTY",
+                )),
+                Some(schema) => result.merge(self.validate(prop_value, schema, &prop_path)),
+            }
+        }
+
+        if let Some(dependencies) = schema_obj.get("dependencies").and_then(Value::as_object) {
+            for (prop_name, dependency) in dependencies {
+                if !data_obj.contains_key(prop_name) {
+                    continue;
+                }
+                match dependency {
+                    Value::Array(needed) => {
+                        for needed in needed.iter().filter_map(Value::as_str) {
+                            if !data_obj.contains_key(needed) {
+                                result.add_error(ValidationError::new(
+                                    format!("{path}.{needed}"),
+                                    format!(
+                                        "Property '{needed}' is required when '{prop_name}' is present"
+                                    ),
+                                    "MISSING_DEPENDENCY",
+                                ));
+                            }
                         }
                     }
-                    Err(e) => {
-                        result.add_error(ValidationError::new(
-                            path,
-                            format!("Invalid regex pattern {pattern}: {e}"),
-                            "INVALID_REGEX_PATTERN",
-                        ));
-                    }
+                    schema => result.merge(self.validate(data, schema, path)),
                 }
             }
         }
+    }
 
-        // Validate number constraints
-        if let Some(data_num) = data.as_f64() {
-            if let Some(minimum) = schema_obj
-                .get("minimum")
-                .and_then(serde_json::Value::as_f64)
-            {
-                if data_num < minimum {
-                    result.add_error(ValidationError::new(
-                        path,
-                        format!("Number too small (min: {minimum})"),
-                        "NUMBER_TOO_SMALL",
-                    ));
-                }
+    fn validate_array(
+        &mut self,
+        items: &[Value],
+        schema_obj: &'s Map<String, Value>,
+        path: &str,
+        result: &mut ValidationResult,
+    ) {
+        let count = items.len() as u64;
+        if let Some(min) = schema_obj.get("minItems").and_then(Value::as_u64) {
+            if count < min {
+                result.add_error(ValidationError::new(
+                    path,
+                    format!("Array too short (min items: {min})"),
+                    "ARRAY_TOO_SHORT",
+                ));
             }
+        }
+        if let Some(max) = schema_obj.get("maxItems").and_then(Value::as_u64) {
+            if count > max {
+                result.add_error(ValidationError::new(
+                    path,
+                    format!("Array too long (max items: {max})"),
+                    "ARRAY_TOO_LONG",
+                ));
+            }
+        }
 
-            if let Some(maximum) = schema_obj
-                .get("maximum")
-                .and_then(serde_json::Value::as_f64)
-            {
-                if data_num > maximum {
-                    result.add_error(ValidationError::new(
-                        path,
-                        format!("Number too large (max: {maximum})"),
-                        "NUMBER_TOO_LARGE",
-                    ));
+        if schema_obj.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = items.iter().enumerate().find_map(|(index, item)| {
+                items[..index]
+                    .iter()
+                    .position(|earlier| json_equal(earlier, item))
+                    .map(|earlier| (earlier, index))
+            });
+            if let Some((earlier, index)) = duplicate {
+                result.add_error(ValidationError::new(
+                    format!("{path}[{index}]"),
+                    format!("Item duplicates item {earlier}"),
+                    "DUPLICATE_ITEMS",
+                ));
+            }
+        }
+
+        // Validate array items, positionally when `items` is a tuple
+        match schema_obj.get("items") {
+            Some(Value::Array(tuple)) => {
+                for (index, item) in items.iter().enumerate() {
+                    let item_path = format!("{path}[{index}]");
+                    match (tuple.get(index), schema_obj.get("additionalItems")) {
+                        (Some(schema), _) => result.merge(self.validate(item, schema, &item_path)),
+                        (None, None) => {}
+                        (None, Some(Value::Bool(false))) => {
+                            result.add_error(ValidationError::new(
+                                item_path,
+                                format!("Additional item not allowed (max items: {})", tuple.len()),
+                                "ADDITIONAL_ITEMS",
+                            ));
+                        }
+                        (None, Some(schema)) => {
+                            result.merge(self.validate(item, schema, &item_path));
+                        }
+                    }
+                }
+            }
+            Some(schema) => {
+                for (index, item) in items.iter().enumerate() {
+                    let item_path = format!("{path}[{index}]");
+                    result.merge(self.validate(item, schema, &item_path));
                 }
             }
+            None => {}
         }
 
-        result
+        if let Some(schema) = schema_obj.get("contains") {
+            if !items
+                .iter()
+                .any(|item| self.validate(item, schema, path).is_valid)
+            {
+                result.add_error(ValidationError::new(
+                    path,
+                    "No item matches the 'contains' schema",
+                    "CONTAINS_MISMATCH",
+                ));
+            }
+        }
     }
+}
 
-    /// Validate data against a validation rule
-    pub fn validate_against_rule(&self, data: &str, rule: &ValidationRule) -> ValidationResult {
-        let mut result = ValidationResult::success();
+/// Validate string length and pattern constraints
+fn validate_string(
+    data_str: &str,
+    schema_obj: &Map<String, Value>,
+    path: &str,
+    result: &mut ValidationResult,
+) {
+    // Lengths count Unicode code points, as JSON Schema specifies
+    let length = data_str.chars().count() as u64;
+    if let Some(min_length) = schema_obj.get("minLength").and_then(Value::as_u64) {
+        if length < min_length {
+            result.add_error(ValidationError::new(
+                path,
+                format!("String too short (min: {min_length})"),
+                "STRING_TOO_SHORT",
+            ));
+        }
+    }
 
-        // Schema validation
-        if let Some(schema) = &rule.schema {
-            let schema_result = self.validate_against_schema(data, schema);
-            result.merge(schema_result);
+    if let Some(max_length) = schema_obj.get("maxLength").and_then(Value::as_u64) {
+        if length > max_length {
+            result.add_error(ValidationError::new(
+                path,
+                format!("String too long (max: {max_length})"),
+                "STRING_TOO_LONG",
+            ));
         }
+    }
 
-        // Custom validator
-        if let Some(validator_name) = &rule.validator_function {
-            if let Some(validator) = self.custom_validators.get(validator_name) {
-                match validator.validate(data, &rule.config) {
-                    Ok(custom_result) => result.merge(custom_result),
-                    Err(e) => {
-                        result.add_error(ValidationError::new(
-                            "custom_validator",
-                            format!("Custom validation failed: {e}"),
-                            "CUSTOM_VALIDATION_ERROR",
-                        ));
-                    }
+    if let Some(pattern) = schema_obj.get("pattern").and_then(Value::as_str) {
+        match regex::Regex::new(pattern) {
+            Ok(re) => {
+                if !re.is_match(data_str) {
+                    result.add_error(ValidationError::new(
+                        path,
+                        format!("String does not match pattern: {pattern}"),
+                        "PATTERN_MISMATCH",
+                    ));
                 }
-            } else {
+            }
+            Err(e) => {
                 result.add_error(ValidationError::new(
-                    "validator",
-                    format!("Unknown validator: {validator_name}"),
-                    "UNKNOWN_VALIDATOR",
+                    path,
+                    format!("Invalid regex pattern {pattern}: {e}"),
+                    "INVALID_REGEX_PATTERN",
                 ));
             }
         }
-
-        result
     }
 }
 
-impl Default for TypeValidator {
-    fn default() -> Self {
-        Self {
-            custom_validators: HashMap::with_capacity(8),
+/// Validate range and `multipleOf` constraints
+fn validate_number(
+    data: &Value,
+    schema_obj: &Map<String, Value>,
+    path: &str,
+    result: &mut ValidationResult,
+) {
+    let Some(data_num) = data.as_f64() else {
+        return;
+    };
+    let bound = |keyword: &str| schema_obj.get(keyword).and_then(Value::as_f64);
+
+    if let Some(minimum) = bound("minimum") {
+        if data_num < minimum {
+            result.add_error(ValidationError::new(
+                path,
+                format!("Number too small (min: {minimum})"),
+                "NUMBER_TOO_SMALL",
+            ));
+        }
+    }
+    if let Some(minimum) = bound("exclusiveMinimum") {
+        if data_num <= minimum {
+            result.add_error(ValidationError::new(
+                path,
+                format!("Number too small (exclusive min: {minimum})"),
+                "NUMBER_TOO_SMALL",
+            ));
+        }
+    }
+    if let Some(maximum) = bound("maximum") {
+        if data_num > maximum {
+            result.add_error(ValidationError::new(
+                path,
+                format!("Number too large (max: {maximum})"),
+                "NUMBER_TOO_LARGE",
+            ));
+        }
+    }
+    if let Some(maximum) = bound("exclusiveMaximum") {
+        if data_num >= maximum {
+            result.add_error(ValidationError::new(
+                path,
+                format!("Number too large (exclusive max: {maximum})"),
+                "NUMBER_TOO_LARGE",
+            ));
+        }
+    }
+    if let Some(divisor) = bound("multipleOf").filter(|d| *d > 0.0) {
+        let quotient = data_num / divisor;
+        // Tolerate binary rounding, so 0.3 counts as a multiple of 0.1
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            result.add_error(ValidationError::new(
+                path,
+                format!("Number is not a multiple of {divisor}"),
+                "NOT_MULTIPLE_OF",
+            ));
         }
     }
 }
 
-/// Trait for custom validators
-pub trait CustomValidator: Send + Sync {
-    /// Validate data with custom logic
-    fn validate(
-        &self,
-        data: &str,
-        config: &HashMap<String, serde_json::Value>,
-    ) -> GraphBitResult<ValidationResult>;
+/// JSON schema type name of a value
+pub(crate) const fn json_type(data: &Value) -> &'static str {
+    match data {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
 
-    /// Get validator name
-    fn name(&self) -> &str;
+fn type_matches(expected: &str, data: &Value) -> bool {
+    // JSON schema "integer" is a number without a fractional part
+    expected == json_type(data)
+        || (expected == "integer"
+            && (data.is_i64() || data.is_u64() || data.as_f64().is_some_and(|n| n.fract() == 0.0)))
+}
 
-    /// Get validator description
-    fn description(&self) -> &str;
+/// Equality as JSON Schema defines it, where `1` and `1.0` are equal
+pub(crate) fn json_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => match (l.as_i64(), r.as_i64()) {
+            (Some(l), Some(r)) => l == r,
+            _ => l.as_f64() == r.as_f64(),
+        },
+        (Value::Array(l), Value::Array(r)) => {
+            l.len() == r.len() && l.iter().zip(r).all(|(l, r)| json_equal(l, r))
+        }
+        (Value::Object(l), Value::Object(r)) => {
+            l.len() == r.len()
+                && l.iter()
+                    .all(|(key, l)| r.get(key).is_some_and(|r| json_equal(l, r)))
+        }
+        _ => left == right,
+    }
+}
+
+/// First error of every failed branch, for `anyOf`/`oneOf` messages
+fn summarize_branches(branches: &[ValidationResult]) -> String {
+    branches
+        .iter()
+        .enumerate()
+        .filter_map(|(index, branch)| {
+            branch
+                .errors
+                .first()
+                .map(|e| format!("#{index}: {}: {}", e.field_path, e.message))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Decode `%XX` escapes in a `$ref` fragment into a JSON pointer
+fn percent_decode(fragment: &str) -> String {
+    let bytes = fragment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| fragment.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
 }
@@ -3,26 +3,84 @@
 //! This module provides the main workflow execution capabilities,
 //! orchestrating agents and managing the execution flow.
 
+mod attachments;
+mod compensation;
+mod handoff;
+mod http_request;
+mod model_router;
+mod output_validation;
+mod reexecution;
+mod tool_registry;
+mod warm_up;
+
+pub use warm_up::{ProviderWarmUp, WarmUpReport};
+
 use crate::agents::AgentTrait;
+use crate::attachment::Attachment;
+use crate::audit::{AuditContext, AuditLog};
+use crate::budget::{BudgetPolicy, BudgetTracker, ExecutionBudget};
+use crate::clock::Clock;
 use crate::document_loader::DocumentLoader;
+use crate::env_config::{EnvConfig, ExecutorEnvDefaults};
 use crate::errors::{GraphBitError, GraphBitResult};
-use crate::graph::{AgentNodeConfig, NodeType, WorkflowGraph, WorkflowNode};
+use crate::expression::{Expression, ExpressionScope, transformation_source};
+use crate::external_event::ExternalEvents;
+use crate::graph::{
+    AgentNodeConfig, EdgeType, JoinPolicy, NodeType, PendingBranches, WorkflowGraph, WorkflowNode,
+    resolve_node_llm_config,
+};
+use crate::guardrail_node::{GuardrailAction, GuardrailCheck, GuardrailReport};
+use crate::http_client::{HttpClientFactory, HttpSettings};
+use crate::llm::{
+    ConfigProfile, ContextWindow, HistoryWindow, LlmImage, LlmMiddleware, LlmMiddlewareStack,
+    LlmUsage, ProviderHealth,
+};
+use crate::output_store::{OutputRef, OutputSpill};
+use crate::pacing::{LlmCallSpacing, PacingDelays};
+use crate::post_process::PostProcessor;
+use crate::run_history::{RunHistory, RunRecord};
+use crate::shutdown::{ShutdownHandle, ShutdownSummary};
+use crate::tools::{ToolCallContext, ToolRegistry};
 use crate::types::{
-    AgentId, AgentMessage, CircuitBreaker, CircuitBreakerConfig, ConcurrencyConfig,
-    ConcurrencyManager, ConcurrencyStats, MessageContent, NodeExecutionResult, NodeId, RetryConfig,
-    TaskInfo, WorkflowContext, WorkflowExecutionStats, WorkflowId, WorkflowState,
+    AbandonedNode, AgentId, AgentMessage, BranchScope, CircuitBreaker, CircuitBreakerConfig,
+    CircuitBreakerState, ConcurrencyConfig, ConcurrencyManager, ConcurrencyStats, MessageContent,
+    NodeExecutionRecord, NodeExecutionResult, NodeId, PayloadCaptureConfig, PromptDefaults,
+    RetryConfig, Secrets, SkipReason, TagFilter, TaskInfo, ToolCallTraceConfig, WorkflowContext,
+    WorkflowExecutionStats, WorkflowId, WorkflowState, prompt::join_prompt_sections,
 };
+use crate::usage::{UsageAttribution, UsageSummary};
 use crate::{DecodeContext, EncodeContext, Enforcer};
 use futures::future::join_all;
+use futures::stream::FuturesUnordered;
+use futures::{FutureExt, StreamExt};
+use handoff::HandoffScope;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, LazyLock};
 use tokio::sync::{Mutex, RwLock};
+use tool_registry::{NativeToolRuntime, ToolLoopScope};
 
 static NODE_REF_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\{\{node\.([a-zA-Z0-9_\-\.]+)\}\}").unwrap());
 
+static INPUT_REF_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{input\.([a-zA-Z0-9_\-\.]+)\}\}").unwrap());
+
+static GLOBALS_REF_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{globals\.([a-zA-Z0-9_\-\.]+)\}\}").unwrap());
+
+static SCRATCH_REF_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{scratch\.([a-zA-Z0-9_\-\.]+)\}\}").unwrap());
+
+static ITEM_REF_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{item((?:\.[a-zA-Z0-9_\-]+)*)\}\}").unwrap());
+
+static ATTACHMENT_REF_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{nodes?\.([a-zA-Z0-9_\-]+)\.attachments\[(\d+)\]\}\}").unwrap()
+});
+
 /// Snapshot passed to condition handlers: parent output plus shared workflow maps for routing.
 #[derive(Debug, Clone)]
 pub struct ConditionRoutingInput {
@@ -42,6 +100,80 @@ pub struct ConditionRoutingInput {
 pub type ConditionalRouteFn =
     Arc<dyn Fn(ConditionRoutingInput) -> GraphBitResult<String> + Send + Sync>;
 
+/// Header carrying the idempotency key of HTTP request nodes unless their
+/// `idempotency_header` config names another one
+pub const DEFAULT_IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Version of the JSON format written by [`Workflow::to_json`]
+pub const WORKFLOW_SCHEMA_VERSION: u32 = 1;
+
+/// On-failure edges a chain of failing compensation nodes follows at most,
+/// unless [`WorkflowExecutor::with_max_compensation_depth`] sets another limit
+pub const DEFAULT_MAX_COMPENSATION_DEPTH: usize = 3;
+
+/// Expected cost of running a workflow, from [`Workflow::estimate_cost`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// Agent nodes in topological order
+    pub nodes: Vec<NodeCostEstimate>,
+    /// Expected tokens summed over all agent nodes
+    pub token_usage: LlmUsage,
+    /// Expected cost summed over the agent nodes whose model has a price
+    pub total_cost: f64,
+    /// Names of the agent nodes without a model or whose model has no price
+    pub unpriced_nodes: Vec<String>,
+}
+
+/// One agent node in a [`CostEstimate`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeCostEstimate {
+    /// Node name
+    pub node_name: String,
+    /// Model configured on the node
+    pub model: Option<String>,
+    /// Expected cost, when the model has a price
+    pub cost: Option<f64>,
+}
+
+/// Outcome of [`WorkflowExecutor::dry_run`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    /// Why the workflow would not start, if it would not
+    pub validation_error: Option<String>,
+    /// Health of every distinct provider and model used by an agent node
+    pub providers: Vec<ProviderHealth>,
+    /// Prompts of the agent nodes in execution order, rendered without inputs
+    /// or upstream outputs
+    #[serde(default)]
+    pub prompts: Vec<RenderedPrompt>,
+    /// JSON Schema the run-time inputs must match, as declared by the workflow
+    #[serde(default)]
+    pub input_schema: Option<serde_json::Value>,
+}
+
+/// Prompts an agent node would send, as rendered by [`WorkflowExecutor::dry_run`]
+///
+/// Executor and node globals are substituted; references to inputs and to
+/// upstream outputs are left in place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedPrompt {
+    /// Name of the agent node
+    pub node: String,
+    /// System prompt, preamble included, unless the node's agent is not
+    /// registered and the node sets none
+    pub system_prompt: Option<String>,
+    /// Prompt, suffix included
+    pub prompt: String,
+}
+
+impl DryRunReport {
+    /// Whether the workflow is valid and every provider is healthy
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.validation_error.is_none() && self.providers.iter().all(ProviderHealth::is_healthy)
+    }
+}
+
 /// A complete workflow definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Workflow {
@@ -55,6 +187,9 @@ pub struct Workflow {
     pub graph: WorkflowGraph,
     /// Workflow metadata
     pub metadata: HashMap<String, serde_json::Value>,
+    /// JSON Schema the run-time inputs must match, checked before any node runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
 }
 
 impl Workflow {
@@ -66,6 +201,7 @@ impl Workflow {
             description: description.into(),
             graph: WorkflowGraph::new(),
             metadata: HashMap::with_capacity(4),
+            input_schema: None,
         }
     }
 
@@ -86,16 +222,242 @@ impl Workflow {
         self.graph.add_edge(from, to, edge)
     }
 
+    /// Problems of a valid workflow worth a look, see [`WorkflowGraph::warnings`]
+    #[must_use]
+    pub fn warnings(&self) -> Vec<String> {
+        self.graph.warnings()
+    }
+
     /// Validate the workflow
     pub fn validate(&self) -> GraphBitResult<()> {
         // tracing::debug!("Workflow '{:#?}' validated successfully", self.graph);
+        if let Some(schema) = &self.input_schema {
+            check_input_schema(schema)?;
+        }
         self.graph.validate()
     }
 
+    /// Declare the JSON Schema the run-time inputs must match.
+    ///
+    /// The inputs are checked as one object, so the schema lists them under
+    /// `properties`, the ones that must be given under `required`, and rejects
+    /// undeclared inputs with `"additionalProperties": false`.
+    pub fn set_input_schema(&mut self, schema: serde_json::Value) -> GraphBitResult<()> {
+        check_input_schema(&schema)?;
+        self.input_schema = Some(schema);
+        Ok(())
+    }
+
+    /// Check `inputs` against the declared input schema, reporting every
+    /// missing and invalid input at once
+    pub fn validate_inputs(
+        &self,
+        inputs: &HashMap<String, serde_json::Value>,
+    ) -> GraphBitResult<()> {
+        let Some(schema) = &self.input_schema else {
+            return Ok(());
+        };
+        let inputs = serde_json::Value::Object(
+            inputs
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        );
+        let result = crate::validation::TypeValidator::new().validate_value(&inputs, schema);
+        if result.is_valid {
+            return Ok(());
+        }
+        let problems: Vec<String> = result
+            .errors
+            .iter()
+            .map(|error| {
+                let input = match error.field_path.as_str() {
+                    "root" => "(inputs)",
+                    path => path.strip_prefix("root.").unwrap_or(path),
+                };
+                match (&error.expected, &error.actual) {
+                    (Some(expected), Some(actual)) => format!(
+                        "{input}: {} (expected {expected}, got {actual})",
+                        error.message
+                    ),
+                    _ => format!("{input}: {}", error.message),
+                }
+            })
+            .collect();
+        Err(GraphBitError::validation(
+            "inputs",
+            format!(
+                "Inputs of workflow '{}' do not match its input schema: {}",
+                self.name,
+                problems.join("; ")
+            ),
+        ))
+    }
+
     /// Set workflow metadata
     pub fn set_metadata(&mut self, key: String, value: serde_json::Value) {
         self.metadata.insert(key, value);
     }
+
+    /// Replace `{{secret.NAME}}` references in node-level LLM configurations and in
+    /// HTTP request node URLs and headers with the values from `secrets`
+    pub fn inject_secrets(&mut self, secrets: &Secrets) -> GraphBitResult<()> {
+        self.graph.update_nodes(|node| {
+            let in_node =
+                |e: GraphBitError| GraphBitError::config(format!("Node '{}': {e}", node.name));
+            if let Some(llm_config) = node.config.get_mut("llm_config") {
+                secrets.inject_value(llm_config).map_err(in_node)?;
+            }
+            if let NodeType::HttpRequest { url, headers, .. } = &mut node.node_type {
+                *url = secrets.inject(url).map_err(in_node)?;
+                for value in headers.values_mut() {
+                    *value = secrets.inject(value).map_err(in_node)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Estimate the cost of running every agent node once, without executing anything
+    ///
+    /// `prices` maps model names to `(input_cost_per_token, output_cost_per_token)`,
+    /// as returned by [`LlmProviderTrait::cost_per_token`], and each agent node is
+    /// expected to use `avg_tokens_per_node`. The model is taken from the node's
+    /// `model` or `llm_config` setting, so nodes that rely on their agent's model
+    /// are listed as unpriced.
+    ///
+    /// [`LlmProviderTrait::cost_per_token`]: crate::llm::LlmProviderTrait::cost_per_token
+    pub fn estimate_cost(
+        &self,
+        prices: &HashMap<String, (f64, f64)>,
+        avg_tokens_per_node: &LlmUsage,
+    ) -> GraphBitResult<CostEstimate> {
+        let mut nodes = Vec::new();
+        let mut unpriced_nodes = Vec::new();
+        let mut total_cost = 0.0;
+        for node_id in self.graph.topological_sort()? {
+            let Some(node) = self.graph.get_node(&node_id) else {
+                continue;
+            };
+            if !matches!(node.node_type, NodeType::Agent { .. }) {
+                continue;
+            }
+            let model = resolve_node_llm_config(&node.config, None)
+                .map(|llm_config| llm_config.model_name().to_string())
+                .or_else(|| {
+                    node.config
+                        .get("model")
+                        .and_then(|model| model.as_str())
+                        .filter(|model| !model.trim().is_empty())
+                        .map(str::to_string)
+                });
+            let cost = model.as_ref().and_then(|model| prices.get(model)).map(
+                |&(input_cost, output_cost)| {
+                    f64::from(avg_tokens_per_node.prompt_tokens).mul_add(
+                        input_cost,
+                        f64::from(avg_tokens_per_node.completion_tokens) * output_cost,
+                    )
+                },
+            );
+            match cost {
+                Some(cost) => total_cost += cost,
+                None => unpriced_nodes.push(node.name.clone()),
+            }
+            nodes.push(NodeCostEstimate {
+                node_name: node.name.clone(),
+                model,
+                cost,
+            });
+        }
+
+        let count = u32::try_from(nodes.len()).unwrap_or(u32::MAX);
+        Ok(CostEstimate {
+            token_usage: LlmUsage::new(
+                avg_tokens_per_node.prompt_tokens.saturating_mul(count),
+                avg_tokens_per_node.completion_tokens.saturating_mul(count),
+            ),
+            nodes,
+            total_cost,
+            unpriced_nodes,
+        })
+    }
+
+    /// Serialize the workflow to JSON, tagged with [`WORKFLOW_SCHEMA_VERSION`]
+    pub fn to_json(&self) -> GraphBitResult<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(object) = value.as_object_mut() {
+            object.insert(
+                "schema_version".to_string(),
+                serde_json::Value::from(WORKFLOW_SCHEMA_VERSION),
+            );
+        }
+        Ok(serde_json::to_string(&value)?)
+    }
+
+    /// Deserialize a workflow produced by [`Workflow::to_json`] and rebuild its
+    /// graph, so edges and caches are usable straight away. JSON without a
+    /// `schema_version` is read as version 1; newer versions are rejected.
+    pub fn from_json(json: &str) -> GraphBitResult<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// Deserialize a workflow from YAML holding the fields of
+    /// [`Workflow::to_json`], see [`Workflow::from_json`]
+    pub fn from_yaml(yaml: &str) -> GraphBitResult<Self> {
+        Self::from_value(crate::template::parse_yaml(yaml, "workflow")?)
+    }
+
+    /// Deserialize a workflow from the JSON value of [`Workflow::to_json`], see
+    /// [`Workflow::from_json`]
+    pub fn from_value(mut value: serde_json::Value) -> GraphBitResult<Self> {
+        let schema_version = match value
+            .as_object_mut()
+            .and_then(|object| object.remove("schema_version"))
+        {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .filter(|&version| version > 0)
+                .ok_or_else(|| {
+                    GraphBitError::validation(
+                        "schema_version",
+                        format!("schema_version must be a positive integer, got {version}"),
+                    )
+                })?,
+        };
+        if schema_version > u64::from(WORKFLOW_SCHEMA_VERSION) {
+            return Err(GraphBitError::validation(
+                "schema_version",
+                format!(
+                    "Workflow JSON has schema_version {schema_version}, but this version of GraphBit \
+                     supports up to {WORKFLOW_SCHEMA_VERSION}; upgrade GraphBit to load it"
+                ),
+            ));
+        }
+
+        let mut workflow: Self = serde_json::from_value(value)?;
+        workflow.graph.rebuild_graph()?;
+        Ok(workflow)
+    }
+}
+
+/// Check that `schema` can describe the inputs of a workflow: an object
+/// schema, whose `type` is `object` when it is given
+fn check_input_schema(schema: &serde_json::Value) -> GraphBitResult<()> {
+    let Some(object) = schema.as_object() else {
+        return Err(GraphBitError::validation(
+            "input_schema",
+            format!("Input schema must be a JSON object, got {schema}"),
+        ));
+    };
+    match object.get("type") {
+        None => Ok(()),
+        Some(serde_json::Value::String(kind)) if kind == "object" => Ok(()),
+        Some(kind) => Err(GraphBitError::validation(
+            "input_schema",
+            format!("Input schema must have type \"object\", got {kind}"),
+        )),
+    }
 }
 
 /// Builder for creating workflows with fluent API
@@ -140,6 +502,14 @@ impl WorkflowBuilder {
         self
     }
 
+    /// Declare the JSON Schema the run-time inputs must match; an invalid
+    /// schema fails [`build`](Self::build)
+    #[must_use]
+    pub fn input_schema(mut self, schema: serde_json::Value) -> Self {
+        self.workflow.input_schema = Some(schema);
+        self
+    }
+
     /// Build the workflow
     pub fn build(self) -> GraphBitResult<Workflow> {
         self.workflow.validate()?;
@@ -157,6 +527,8 @@ pub struct WorkflowExecutor {
     max_node_execution_time_ms: Option<u64>,
     /// Whether to fail fast on first error or continue with other nodes
     fail_fast: bool,
+    /// On-failure edges a chain of failing compensation nodes follows at most
+    max_compensation_depth: usize,
     /// Default retry configuration for all nodes
     default_retry_config: Option<RetryConfig>,
     /// Circuit breakers per agent to prevent cascading failures - use `RwLock` for better performance
@@ -167,6 +539,183 @@ pub struct WorkflowExecutor {
     default_llm_config: Option<crate::llm::LlmConfig>,
     /// Runtime handlers for [`NodeType::Condition`] nodes (`handler_id` → callback).
     conditional_handlers: Arc<HashMap<String, ConditionalRouteFn>>,
+    /// Rust-native tools resolved by agent nodes; when set, tool calls are executed in-process
+    tool_registry: Option<ToolRegistry>,
+    /// Fail the node on tool arguments that do not match the tool schema instead of
+    /// returning the validation error to the model
+    strict_tool_args: bool,
+    /// Redaction and truncation applied to recorded tool call payloads
+    tool_call_trace: ToolCallTraceConfig,
+    /// Where node outputs over the spill threshold are stored instead of the context
+    output_spill: Option<OutputSpill>,
+    /// Size limit of a single attachment produced by a node
+    max_attachment_bytes: u64,
+    /// Middleware run around every LLM call made by workflow nodes
+    llm_middleware: LlmMiddlewareStack,
+    /// Whether workflows containing [`NodeType::ShellCommand`] nodes may run
+    allow_shell_nodes: bool,
+    /// Whether workflows containing [`NodeType::PythonCode`] nodes may run
+    allow_python_code_nodes: bool,
+    /// Limits on the LLM usage of each execution
+    budget: ExecutionBudget,
+    /// Log receiving a record of every provider, tool and HTTP call
+    audit_log: Option<AuditLog>,
+    /// Capture of each node's rendered prompt and raw provider payloads
+    capture_payloads: Option<PayloadCaptureConfig>,
+    /// Preamble, suffix and global template variables applied to every node
+    prompt_defaults: PromptDefaults,
+    /// LLM profiles an execution may select, keyed by name
+    profiles: HashMap<String, ConfigProfile>,
+    /// Mailboxes of running executions for external event nodes
+    external_events: ExternalEvents,
+    /// Spacing of the LLM calls of all executions
+    llm_call_spacing: Option<Arc<LlmCallSpacing>>,
+    /// Clock timing retries, delays, pacing and circuit breakers
+    clock: Arc<dyn Clock>,
+    /// Store recording every execution when it starts and finishes
+    run_history: Option<RunHistory>,
+    /// Workflow metadata keys whose values executions attribute their usage to
+    usage_metadata_keys: Vec<String>,
+    /// Whether warm-ups prime providers with cold starts with a one-token completion
+    warm_up_priming: bool,
+    /// Running executions and whether they are being shut down
+    shutdown: ShutdownHandle,
+    /// Push gateway the metrics are pushed to on shutdown
+    #[cfg(feature = "metrics")]
+    metrics_push_url: Option<String>,
+}
+
+/// What the nodes of a run share, handed to each node task
+struct NodeRuntime {
+    /// Registered agents
+    agents: Arc<RwLock<HashMap<crate::types::AgentId, Arc<dyn AgentTrait>>>>,
+    /// Circuit breakers per agent
+    circuit_breakers: Arc<RwLock<HashMap<crate::types::AgentId, CircuitBreaker>>>,
+    /// Configuration of new circuit breakers
+    circuit_breaker_config: CircuitBreakerConfig,
+    /// Masks prompts before LLM calls and unmasks the responses
+    guardrail_enforcer: Option<Arc<Enforcer>>,
+    /// Parents of each node, from the edges of the graph
+    node_parents: Arc<HashMap<NodeId, Vec<NodeId>>>,
+    /// Condition handlers by id
+    conditional_handlers: Arc<HashMap<String, ConditionalRouteFn>>,
+    /// Graph of the workflow being run
+    workflow_graph: Arc<WorkflowGraph>,
+    /// Channel stream events are sent on, when streaming
+    event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+    /// Which events are streamed
+    stream_mode: crate::stream::StreamMode,
+    /// Tools of the executor's registry, when it has one
+    tool_runtime: Option<NativeToolRuntime>,
+}
+
+/// What a run settles once before its first batch
+struct RunPlan {
+    /// What the nodes share
+    runtime: Arc<NodeRuntime>,
+    /// Supervisors of each handoff target. Handoff targets only run when a
+    /// supervisor hands them a task; they are settled together with their
+    /// supervisors so their successors wait for them.
+    handoff_sources: HashMap<NodeId, Vec<NodeId>>,
+    /// Joins that may run before all their parents finish, with the number of
+    /// parents that must succeed and what happens to the others
+    early_joins: Vec<(NodeId, usize, PendingBranches)>,
+    /// Tags selecting the nodes that run
+    tag_filter: TagFilter,
+    /// Tracks spending against the executor's budget, when it has one
+    budget: Option<BudgetTracker>,
+    /// Attributes usage to nodes, agents and metadata
+    attribution: UsageAttribution,
+    /// Secrets redacted from streamed outputs and errors
+    secrets: Secrets,
+    /// Id of the execution
+    execution_id: uuid::Uuid,
+    /// When the run started
+    start_time: std::time::Instant,
+}
+
+impl RunPlan {
+    /// Nodes not skipped when a parent fails: they settle on their own terms
+    fn unblockable(&self) -> HashSet<&NodeId> {
+        self.early_joins
+            .iter()
+            .map(|(join_id, _, _)| join_id)
+            .chain(self.handoff_sources.keys())
+            .collect()
+    }
+}
+
+/// Progress of a run across its batches
+#[derive(Default)]
+struct RunState {
+    /// Nodes that settled by running or being reused
+    resolved: HashSet<NodeId>,
+    /// Nodes that settled without running
+    skipped: HashSet<NodeId>,
+    /// Conditional edges whose condition evaluated to false
+    disabled_edges: HashSet<(NodeId, NodeId)>,
+    /// Nodes that succeeded
+    succeeded: HashSet<NodeId>,
+    /// Nodes that failed without failing the workflow, in the order they failed
+    failed: Vec<NodeId>,
+    /// Streaming-only coordination: nodes that emitted `tool_calls_required` are
+    /// treated as pending until the Python streaming layer resolves them and
+    /// performs a downstream rerun
+    pending_tool_resolution: HashSet<NodeId>,
+    /// Nodes the tag filter skipped, whose dependents are skipped in turn
+    tag_skipped: HashSet<NodeId>,
+    /// Failure of a fail-fast run still running the compensation nodes of the
+    /// node that failed
+    compensating: Option<String>,
+    /// Nodes that ran
+    total_executed: usize,
+    /// Nodes that ran and succeeded
+    total_successful: usize,
+    /// Number of the current batch
+    step: usize,
+    /// Whether the executor shut down and cancelled the execution
+    shut_down: bool,
+}
+
+impl RunState {
+    /// Whether `node_id` settled
+    fn is_done(&self, node_id: &NodeId) -> bool {
+        self.resolved.contains(node_id) || self.skipped.contains(node_id)
+    }
+
+    /// Whether every node of `graph` settled
+    fn is_settled(&self, graph: &WorkflowGraph) -> bool {
+        self.resolved.len() + self.skipped.len() >= graph.node_count()
+    }
+}
+
+/// Nodes of a run executing concurrently
+struct Batch {
+    /// Context shared by the node tasks
+    context: Arc<Mutex<WorkflowContext>>,
+    /// Batch nodes still awaited, by id
+    running: HashMap<NodeId, tokio::task::AbortHandle>,
+    /// Batch nodes a join or the budget stopped waiting for; their results are ignored
+    abandoned: HashMap<NodeId, AbandonedNode>,
+    /// Failure that stops the run
+    failure: Option<RunFailure>,
+    /// Ids of the nodes the batch started with
+    ids: Vec<String>,
+}
+
+impl Batch {
+    /// Stop the run with `message`, caused by the failure of `node` if any
+    fn fail(&mut self, node: Option<NodeId>, message: String) {
+        self.failure = Some(RunFailure { node, message });
+    }
+}
+
+/// Failure that stops a run
+struct RunFailure {
+    /// Node whose failure stopped the run
+    node: Option<NodeId>,
+    /// Why the run stopped
+    message: String,
 }
 
 impl WorkflowExecutor {
@@ -180,12 +729,89 @@ impl WorkflowExecutor {
             concurrency_manager,
             max_node_execution_time_ms: None,
             fail_fast: false,
+            max_compensation_depth: DEFAULT_MAX_COMPENSATION_DEPTH,
             default_retry_config: Some(RetryConfig::default()),
             circuit_breakers: Arc::new(RwLock::new(HashMap::with_capacity(8))),
             circuit_breaker_config: CircuitBreakerConfig::default(),
             default_llm_config: None,
             conditional_handlers: Arc::new(HashMap::new()),
+            tool_registry: None,
+            strict_tool_args: false,
+            tool_call_trace: ToolCallTraceConfig::default(),
+            output_spill: None,
+            max_attachment_bytes: crate::attachment::DEFAULT_MAX_ATTACHMENT_BYTES,
+            llm_middleware: LlmMiddlewareStack::default(),
+            allow_shell_nodes: false,
+            allow_python_code_nodes: false,
+            budget: ExecutionBudget::default(),
+            audit_log: None,
+            capture_payloads: None,
+            prompt_defaults: PromptDefaults::default(),
+            profiles: HashMap::new(),
+            external_events: ExternalEvents::new(),
+            llm_call_spacing: None,
+            clock: crate::clock::system(),
+            run_history: None,
+            usage_metadata_keys: vec!["team".to_string()],
+            warm_up_priming: true,
+            shutdown: ShutdownHandle::new(),
+            #[cfg(feature = "metrics")]
+            metrics_push_url: None,
+        }
+    }
+
+    /// Create an executor tuned for throughput, with concurrency limits scaled to
+    /// the number of available CPUs
+    pub fn new_high_throughput() -> Self {
+        Self::new().with_concurrency_config(ConcurrencyConfig::high_throughput())
+    }
+
+    /// Create an executor tuned for latency, which reports failures right away
+    /// instead of retrying them
+    pub fn new_low_latency() -> Self {
+        Self::new().without_retries()
+    }
+
+    /// Create an executor that keeps few nodes in flight at once
+    pub fn new_memory_optimized() -> Self {
+        Self::new().with_concurrency_config(ConcurrencyConfig::memory_optimized())
+    }
+
+    /// Create an executor with the settings of the `GRAPHBIT_*` variables, see
+    /// [`crate::env_config`], and with `GRAPHBIT_DEFAULT_PROVIDER` set, the LLM
+    /// configuration of that provider for auto-generated agents. Settings made
+    /// with the builder methods afterwards take precedence.
+    pub fn from_env() -> GraphBitResult<Self> {
+        let env = EnvConfig::from_env();
+        let executor = Self::new().with_env_defaults(&env.executor_defaults()?);
+        Ok(match env.default_provider() {
+            Some(provider) => executor.with_default_llm_config(env.llm_config(provider)?),
+            None => executor,
+        })
+    }
+
+    /// Apply the settings of `defaults` that were read from a variable
+    #[must_use]
+    pub fn with_env_defaults(mut self, defaults: &ExecutorEnvDefaults) -> Self {
+        if let Some(max_concurrency) = defaults.max_concurrency {
+            self = self.with_concurrency_config(ConcurrencyConfig {
+                global_max_concurrency: max_concurrency,
+                ..ConcurrencyConfig::default()
+            });
+        }
+        if let Some(fail_fast) = defaults.fail_fast {
+            self.fail_fast = fail_fast;
+        }
+        if let Some(timeout_ms) = defaults.max_node_execution_time_ms {
+            self.max_node_execution_time_ms = Some(timeout_ms);
         }
+        if let Some(max_tokens) = defaults.max_total_tokens {
+            self.budget.max_total_tokens = Some(max_tokens);
+        }
+        if let Some(max_cost_usd) = defaults.max_cost_usd {
+            self.budget.max_cost_usd = Some(max_cost_usd);
+        }
+        self
     }
 
     /// Register an agent with the executor
@@ -194,6 +820,25 @@ impl WorkflowExecutor {
         self.agents.write().await.insert(agent_id, agent);
     }
 
+    /// The agent registered, or created for an agent node, under `agent_id`
+    pub async fn agent(&self, agent_id: &AgentId) -> Option<Arc<dyn AgentTrait>> {
+        self.agents.read().await.get(agent_id).cloned()
+    }
+
+    /// Register agents while building the executor, e.g. agents shared by several
+    /// nodes through [`WorkflowNode::with_registered_agent`]
+    pub fn with_agents<I>(self, agents: I) -> Self
+    where
+        I: IntoIterator<Item = Arc<dyn AgentTrait>>,
+    {
+        if let Ok(mut registered) = self.agents.try_write() {
+            for agent in agents {
+                registered.insert(agent.id().clone(), agent);
+            }
+        }
+        self
+    }
+
     /// Set maximum execution time per node
     pub fn with_max_node_execution_time(mut self, timeout_ms: u64) -> Self {
         self.max_node_execution_time_ms = Some(timeout_ms);
@@ -206,6 +851,14 @@ impl WorkflowExecutor {
         self
     }
 
+    /// Limit how many on-failure edges a chain of failing compensation nodes
+    /// follows: a compensation node reached through more of them never runs.
+    /// With 0 no compensation node runs.
+    pub fn with_max_compensation_depth(mut self, depth: usize) -> Self {
+        self.max_compensation_depth = depth;
+        self
+    }
+
     /// Set retry configuration
     pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
         self.default_retry_config = Some(retry_config);
@@ -233,137 +886,886 @@ impl WorkflowExecutor {
         self
     }
 
-    /// Disable retries
-    pub fn without_retries(mut self) -> Self {
-        self.default_retry_config = None;
+    /// Resolve agent node tools from `registry` and execute their calls in-process.
+    ///
+    /// Agent nodes list the tools they may use under the `tools` config key (or
+    /// provide full `tool_schemas`); any tool found in the registry is invoked by the
+    /// executor, which feeds the result back to the model until it answers.
+    pub fn with_tool_registry(mut self, registry: ToolRegistry) -> Self {
+        self.tool_registry = Some(registry);
         self
     }
 
-    /// Get concurrency statistics
-    pub async fn get_concurrency_stats(&self) -> ConcurrencyStats {
-        self.concurrency_manager.get_stats().await
+    /// Make tool argument validation failures fatal for the node
+    pub fn with_strict_tool_args(mut self, strict: bool) -> Self {
+        self.strict_tool_args = strict;
+        self
     }
 
-    /// Resolve LLM configuration for a node with hierarchical priority
-    /// Priority: Node-level config > Executor-level config > ERROR (no defaults)
-    fn resolve_llm_config_for_node(
-        &self,
-        node_config: &std::collections::HashMap<String, serde_json::Value>,
-    ) -> crate::llm::LlmConfig {
-        // 1. Check for node-level LLM config first (highest priority)
-        if let Some(node_llm_config) = node_config.get("llm_config") {
-            if let Ok(config) =
-                serde_json::from_value::<crate::llm::LlmConfig>(node_llm_config.clone())
-            {
-                tracing::debug!(
-                    "Using node-level LLM configuration: {:?}",
-                    config.provider_name()
-                );
-                return config;
-            }
-            tracing::warn!(
-                "Failed to deserialize node-level LLM config, falling back to executor config"
-            );
-        }
+    /// Set how tool call arguments and outputs are captured in the recorded tool calls
+    pub fn with_tool_call_trace(mut self, config: ToolCallTraceConfig) -> Self {
+        self.tool_call_trace = config;
+        self
+    }
 
-        // 2. Fall back to executor-level config (medium priority)
-        if let Some(executor_config) = &self.default_llm_config {
-            tracing::debug!(
-                "Using executor-level LLM configuration: {:?}",
-                executor_config.provider_name()
-            );
-            return executor_config.clone();
-        }
+    /// Spill node outputs over the spill threshold to its output store, keeping
+    /// only a reference in the context. Contexts that already have an output
+    /// spill attached keep theirs.
+    #[must_use]
+    pub fn with_output_spill(mut self, spill: OutputSpill) -> Self {
+        self.output_spill = Some(spill);
+        self
+    }
 
-        // 3. No default fallback - require explicit configuration as requested by user
-        tracing::error!(
-            "No LLM configuration found - neither node-level nor executor-level config provided. System requires explicit configuration."
-        );
-        crate::llm::LlmConfig::Unconfigured {
-            message: "No LLM configuration provided. The system requires explicit configuration from program or user input rather than hardcoded defaults.".to_string()
-        }
+    /// Fail nodes producing an attachment larger than `max_bytes`, instead of
+    /// the default limit of 25 MiB
+    #[must_use]
+    pub fn with_max_attachment_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_attachment_bytes = max_bytes;
+        self
     }
 
-    /// Get or create circuit breaker for an agent
-    async fn get_circuit_breaker(&self, agent_id: &crate::types::AgentId) -> CircuitBreaker {
-        // Try to read first (more efficient for existing breakers)
-        {
-            let breakers = self.circuit_breakers.read().await;
-            if let Some(breaker) = breakers.get(agent_id) {
-                return breaker.clone();
-            }
-        }
+    /// Run every LLM call made by workflow nodes through `middleware`, after
+    /// the middleware registered before it
+    #[must_use]
+    pub fn with_llm_middleware(mut self, middleware: Arc<dyn LlmMiddleware>) -> Self {
+        self.llm_middleware.push(middleware);
+        self
+    }
 
-        // If not found, acquire write lock and create
-        let mut breakers = self.circuit_breakers.write().await;
-        breakers
-            .entry(agent_id.clone())
-            .or_insert_with(|| CircuitBreaker::new(self.circuit_breaker_config.clone()))
-            .clone()
+    /// Start the LLM calls of workflow nodes at least `interval_ms`
+    /// milliseconds apart, across all executions of this executor; `0` lifts
+    /// the spacing. Calls wait for their turn within their node's duration.
+    #[must_use]
+    pub fn with_min_interval_between_llm_calls(mut self, interval_ms: u64) -> Self {
+        self.llm_call_spacing =
+            (interval_ms > 0).then(|| Arc::new(LlmCallSpacing::new(interval_ms)));
+        self
     }
 
-    /// Get current concurrency limit
-    pub async fn max_concurrency(&self) -> usize {
-        // Get the global max concurrency from the concurrency manager
-        let _stats = self.concurrency_manager.get_stats().await;
-        let permits = self.concurrency_manager.get_available_permits().await;
-        permits.get("global").copied().unwrap_or(16) // Default fallback
+    /// Space the LLM calls of workflow nodes with `spacing`, which may be
+    /// shared with other executors so that their calls are spaced together
+    #[must_use]
+    pub fn with_llm_call_spacing(mut self, spacing: Arc<LlmCallSpacing>) -> Self {
+        self.llm_call_spacing = Some(spacing);
+        self
     }
 
-    /// Get available permits in semaphore
-    pub async fn available_permits(&self) -> HashMap<String, usize> {
-        self.concurrency_manager.get_available_permits().await
+    /// Time retry backoff, delay nodes, pacing and circuit breakers on `clock`
+    /// instead of the system clock, e.g. a [`crate::clock::TestClock`]
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 
-    /// Execute a workflow with enhanced performance monitoring.
-    ///
-    /// When `guardrail_enforcer` is `Some`, PII is encoded before each LLM call and
-    /// decoded on LLM output; tool-call boundaries are handled by the executor layer.
-    ///
-    /// This is the non-streaming entry point. It delegates to [`execute_internal`]
-    /// with no event channel, so zero overhead is added to the hot path.
-    pub async fn execute(
-        &self,
-        workflow: Workflow,
-        guardrail_enforcer: Option<Arc<Enforcer>>,
-    ) -> GraphBitResult<WorkflowContext> {
-        let context = WorkflowContext::new(workflow.id.clone());
-        self.execute_internal(
-            workflow,
-            guardrail_enforcer,
-            None,
-            crate::stream::StreamMode::Updates,
-            context,
-        )
-        .await
+    /// Place `preamble` before the system prompt of every agent node, separated
+    /// by a blank line; it may reference `{{globals.NAME}}`
+    #[must_use]
+    pub fn with_system_preamble(mut self, preamble: impl Into<String>) -> Self {
+        self.prompt_defaults.system_preamble = Some(preamble.into());
+        self
     }
 
-    pub async fn execute_with_context(
-        &self,
-        workflow: Workflow,
-        guardrail_enforcer: Option<Arc<Enforcer>>,
-        event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
-        stream_mode: crate::stream::StreamMode,
-        mut context: WorkflowContext,
-    ) -> GraphBitResult<WorkflowContext> {
-        self.execute_internal(
-            workflow,
-            guardrail_enforcer,
-            event_tx,
-            stream_mode,
-            context,
-        )
-        .await
+    /// Append `suffix` to the prompt of every agent node, separated by a blank
+    /// line; it may reference `{{globals.NAME}}` and any other template variable
+    #[must_use]
+    pub fn with_prompt_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.prompt_defaults.prompt_suffix = Some(suffix.into());
+        self
     }
 
-    /// Execute a workflow with real-time streaming events.
-    ///
-    /// Identical to [`execute`] but emits [`StreamEvent`]s through `event_tx`
-    /// at every node boundary. The caller consumes events from the corresponding
-    /// `mpsc::Receiver` while this future runs (typically in a spawned task).
-    ///
-    /// # Arguments
-    /// * `workflow` — the workflow to execute
+    /// Add template variables referenced as `{{globals.NAME}}` by every node;
+    /// globals set on a node with [`WorkflowNode::with_globals`] take precedence
+    #[must_use]
+    pub fn with_globals(mut self, globals: HashMap<String, serde_json::Value>) -> Self {
+        self.prompt_defaults.globals.extend(globals);
+        self
+    }
+
+    /// Preamble, suffix and global template variables applied to every node
+    #[must_use]
+    pub const fn prompt_defaults(&self) -> &PromptDefaults {
+        &self.prompt_defaults
+    }
+
+    /// Add an LLM profile that executions select by `name`, e.g. with
+    /// [`execute_with_profile`](Self::execute_with_profile). The profile's
+    /// configuration applies to agent nodes below their own `llm_config` and
+    /// `model` and above the executor-level configuration.
+    #[must_use]
+    pub fn with_profile(mut self, name: impl Into<String>, profile: ConfigProfile) -> Self {
+        self.profiles.insert(name.into(), profile);
+        self
+    }
+
+    /// Add several LLM profiles keyed by name, e.g. loaded with
+    /// [`profiles_from_file`](crate::llm::profile::profiles_from_file)
+    #[must_use]
+    pub fn with_profiles(mut self, profiles: HashMap<String, ConfigProfile>) -> Self {
+        self.profiles.extend(profiles);
+        self
+    }
+
+    /// LLM profiles executions may select, keyed by name
+    #[must_use]
+    pub const fn profiles(&self) -> &HashMap<String, ConfigProfile> {
+        &self.profiles
+    }
+
+    /// Receive external events through `events`, e.g. to share mailboxes with
+    /// the server delivering them
+    #[must_use]
+    pub fn with_external_events(mut self, events: ExternalEvents) -> Self {
+        self.external_events = events;
+        self
+    }
+
+    /// Mailboxes of the executions this executor is running
+    #[must_use]
+    pub const fn external_events(&self) -> &ExternalEvents {
+        &self.external_events
+    }
+
+    /// Deliver `event_name` with `payload` to the running execution
+    /// `execution_id`, resuming the external event node waiting for it
+    pub fn deliver_event(
+        &self,
+        execution_id: &str,
+        event_name: &str,
+        payload: serde_json::Value,
+    ) -> GraphBitResult<()> {
+        self.external_events
+            .deliver(execution_id, event_name, payload)
+    }
+
+    /// Settings for the HTTP clients of LLM providers, embedding providers, the
+    /// document loader, the `http_request` tool and HTTP request nodes.
+    ///
+    /// Clients are shared process-wide, so this configures the global
+    /// [`HttpClientFactory`] and applies to clients created from now on,
+    /// including those of agents created when this executor runs a workflow.
+    #[must_use]
+    pub fn with_http_settings(self, settings: HttpSettings) -> Self {
+        HttpClientFactory::global().set_settings(settings);
+        self
+    }
+
+    /// Settings for the HTTP client of one provider or component (e.g. `openai`,
+    /// `embeddings.openai` or `http_node`), taking precedence over
+    /// [`Self::with_http_settings`]. Applies process-wide like those settings.
+    #[must_use]
+    pub fn with_provider_http_settings(
+        self,
+        component: impl Into<String>,
+        settings: HttpSettings,
+    ) -> Self {
+        HttpClientFactory::global().set_provider_settings(component, settings);
+        self
+    }
+
+    /// Allow running workflows with shell command nodes. Off by default, so a
+    /// workflow loaded from an untrusted file cannot run commands unnoticed.
+    #[must_use]
+    pub fn with_allow_shell_nodes(mut self, allow: bool) -> Self {
+        self.allow_shell_nodes = allow;
+        self
+    }
+
+    /// Allow running workflows with Python code nodes. Off by default, so a
+    /// workflow loaded from an untrusted file cannot run code unnoticed; the
+    /// node's sandbox limits mistakes, it does not contain hostile code.
+    #[must_use]
+    pub const fn with_allow_python_code_nodes(mut self, allow: bool) -> Self {
+        self.allow_python_code_nodes = allow;
+        self
+    }
+
+    /// Stop an execution once its LLM calls used `max_tokens` tokens. Nodes not
+    /// started yet are skipped, except those with `budget_exempt: true` in their
+    /// config, and the run fails with
+    /// [`FailureReason::BudgetExceeded`](crate::types::FailureReason::BudgetExceeded).
+    #[must_use]
+    pub const fn with_max_total_tokens(mut self, max_tokens: u64) -> Self {
+        self.budget.max_total_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Stop an execution once its LLM calls cost `max_cost_usd`, like
+    /// [`Self::with_max_total_tokens`]. Calls are priced with the cost per token
+    /// of the providers of the registered agents.
+    #[must_use]
+    pub const fn with_max_cost_usd(mut self, max_cost_usd: f64) -> Self {
+        self.budget.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
+    /// What happens to nodes still running when the budget runs out
+    #[must_use]
+    pub const fn with_budget_policy(mut self, policy: BudgetPolicy) -> Self {
+        self.budget.policy = policy;
+        self
+    }
+
+    /// Record every LLM call, registry tool call and HTTP request node of each
+    /// execution in `audit_log`. Records are written in the background; call
+    /// [`AuditLog::flush`] to wait for them.
+    #[must_use]
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Record each node's prompt after template resolution and the raw
+    /// requests and responses of its LLM provider calls, available from
+    /// [`WorkflowContext::get_node_debug`]. Credentials are redacted and
+    /// payloads are cut to [`PayloadCaptureConfig::DEFAULT_MAX_PAYLOAD_CHARS`];
+    /// the captures are left out when the context is serialized.
+    #[must_use]
+    pub fn with_capture_payloads(mut self, enabled: bool) -> Self {
+        self.capture_payloads = enabled.then(PayloadCaptureConfig::default);
+        self
+    }
+
+    /// Capture payloads like [`Self::with_capture_payloads`], with the length
+    /// limit and persistence of `config`
+    #[must_use]
+    pub const fn with_payload_capture_config(mut self, config: PayloadCaptureConfig) -> Self {
+        self.capture_payloads = Some(config);
+        self
+    }
+
+    /// Record every execution in `history` when it starts and when it
+    /// finishes
+    #[must_use]
+    pub fn with_run_history(mut self, history: RunHistory) -> Self {
+        self.run_history = Some(history);
+        self
+    }
+
+    /// History the executions of this executor are recorded in
+    #[must_use]
+    pub const fn run_history(&self) -> Option<&RunHistory> {
+        self.run_history.as_ref()
+    }
+
+    /// Attribute the usage of each execution to the values its workflow has
+    /// for the metadata `keys`, in
+    /// [`UsageBreakdown::by_metadata`](crate::usage::UsageBreakdown::by_metadata);
+    /// `team` by default
+    #[must_use]
+    pub fn with_usage_metadata_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.usage_metadata_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add up the usage of the runs the run history recorded as started in the
+    /// last `period`, by node tag and by attributed metadata value
+    pub async fn usage_summary(&self, period: std::time::Duration) -> GraphBitResult<UsageSummary> {
+        let history = self.run_history.as_ref().ok_or_else(|| {
+            GraphBitError::config("Usage summaries need an executor with a run history")
+        })?;
+        let period = chrono::Duration::from_std(period)
+            .map_err(|e| GraphBitError::config(format!("Invalid usage summary period: {e}")))?;
+        history.usage_summary(chrono::Utc::now() - period).await
+    }
+
+    /// Share `handle` with other executors, so that shutting down any of them
+    /// shuts down the executions of all
+    #[must_use]
+    pub fn with_shutdown_handle(mut self, handle: ShutdownHandle) -> Self {
+        self.shutdown = handle;
+        self
+    }
+
+    /// Running executions of this executor and whether it is shutting down
+    #[must_use]
+    pub const fn shutdown_handle(&self) -> &ShutdownHandle {
+        &self.shutdown
+    }
+
+    /// Push the metrics to `url`, the job URL of a Prometheus push gateway, when
+    /// the executor [shuts down](Self::shutdown)
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn with_metrics_push_url(mut self, url: impl Into<String>) -> Self {
+        self.metrics_push_url = Some(url.into());
+        self
+    }
+
+    /// Shut the executor down, see [`crate::shutdown`]: stop starting
+    /// executions, wait up to `grace_period` for the running ones to finish and
+    /// cancel the rest, then flush the audit log and push the metrics.
+    ///
+    /// Executions started afterwards fail. Flushes that fail are listed in the
+    /// summary's `errors` and do not stop the others.
+    pub async fn shutdown(&self, grace_period: std::time::Duration) -> ShutdownSummary {
+        let start = std::time::Instant::now();
+        let (completed, interrupted) = self.shutdown.drain(grace_period).await;
+        let mut summary = ShutdownSummary {
+            completed,
+            interrupted,
+            ..ShutdownSummary::default()
+        };
+        if let Some(audit_log) = &self.audit_log {
+            match audit_log.flush().await {
+                Ok(()) => summary.audit_log_flushed = true,
+                Err(e) => summary
+                    .errors
+                    .push(format!("Failed to flush the audit log: {e}")),
+            }
+        }
+        #[cfg(feature = "metrics")]
+        if let (Some(url), Some(recorder)) = (&self.metrics_push_url, crate::telemetry::installed())
+        {
+            match crate::telemetry::push(url, &recorder).await {
+                Ok(()) => summary.metrics_pushed = true,
+                Err(e) => summary.errors.push(e.to_string()),
+            }
+        }
+        summary.duration_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        tracing::info!(
+            completed = summary.completed,
+            interrupted = summary.interrupted.len(),
+            "Executor shut down"
+        );
+        summary
+    }
+
+    /// Disable retries
+    pub fn without_retries(mut self) -> Self {
+        self.default_retry_config = None;
+        self
+    }
+
+    /// Set whether [`warm_up`](Self::warm_up) primes providers with cold starts
+    /// with a one-token completion, which it does by default, instead of only
+    /// health-checking them
+    #[must_use]
+    pub const fn with_warm_up_priming(mut self, prime: bool) -> Self {
+        self.warm_up_priming = prime;
+        self
+    }
+
+    /// Fail for a workflow with shell command or Python code nodes unless they
+    /// are allowed
+    fn check_code_nodes_allowed(&self, workflow: &Workflow) -> GraphBitResult<()> {
+        for node in workflow.graph.get_nodes().values() {
+            match node.node_type {
+                NodeType::ShellCommand { .. } if !self.allow_shell_nodes => {
+                    return Err(GraphBitError::workflow_execution(format!(
+                        "Workflow contains shell command node '{}', but this executor does not \
+                         allow shell nodes; enable them with `with_allow_shell_nodes(true)`",
+                        node.name
+                    )));
+                }
+                NodeType::PythonCode { .. } if !self.allow_python_code_nodes => {
+                    return Err(GraphBitError::workflow_execution(format!(
+                        "Workflow contains Python code node '{}', but this executor does not \
+                         allow Python code nodes; enable them with \
+                         `with_allow_python_code_nodes(true)`",
+                        node.name
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a workflow without running it: validate it, then health-check every
+    /// distinct provider and model its agent nodes would call.
+    ///
+    /// A node uses its registered agent's provider, unless the node overrides the
+    /// agent's `llm_config` or `model`; nodes without a registered agent use the
+    /// configuration an auto-registered agent would get. Providers are not probed
+    /// when validation fails.
+    pub async fn dry_run(&self, workflow: &Workflow) -> GraphBitResult<DryRunReport> {
+        if let Err(e) = workflow
+            .validate()
+            .and_then(|()| self.check_code_nodes_allowed(workflow))
+        {
+            return Ok(DryRunReport {
+                validation_error: Some(e.to_string()),
+                providers: Vec::new(),
+                prompts: Vec::new(),
+                input_schema: workflow.input_schema.clone(),
+            });
+        }
+
+        let mut context = WorkflowContext::new(workflow.id.clone());
+        self.attach_prompt_defaults(&mut context, workflow);
+
+        let mut seen = HashSet::new();
+        let mut providers = Vec::new();
+        let mut prompts = Vec::new();
+        for node_id in workflow.graph.topological_sort()? {
+            let Some(node) = workflow.graph.get_node(&node_id) else {
+                continue;
+            };
+            let Some(config) = node.node_type.agent_config() else {
+                continue;
+            };
+            let agent = self.agents.read().await.get(&config.agent_id).cloned();
+            prompts.push(RenderedPrompt {
+                node: node.name.clone(),
+                system_prompt: Self::resolve_node_system_prompt(
+                    config,
+                    &node.config,
+                    agent.as_deref(),
+                    &context,
+                    &node.id,
+                ),
+                prompt: Self::resolve_node_template_variables(
+                    &Self::suffixed_prompt_template(&config.prompt_template, &context),
+                    &context,
+                    Some(&node.id),
+                ),
+            });
+
+            let llm_config = agent.map_or_else(
+                || {
+                    Self::resolve_llm_config_for_node(
+                        &node.config,
+                        None,
+                        self.default_llm_config.as_ref(),
+                    )
+                },
+                |agent| {
+                    let agent_llm_config = agent.llm_provider().config();
+                    resolve_node_llm_config(&node.config, Some(agent_llm_config))
+                        .unwrap_or_else(|| agent_llm_config.clone())
+                },
+            );
+            let key = serde_json::to_string(&llm_config)?;
+            if !seen.insert(key) {
+                continue;
+            }
+
+            let health = match crate::llm::LlmProviderFactory::create_provider(llm_config.clone()) {
+                Ok(provider) => provider.health_check().await,
+                Err(e) => ProviderHealth::from_probe(
+                    llm_config.provider_name(),
+                    llm_config.model_name(),
+                    0,
+                    &Err(e),
+                ),
+            };
+            providers.push(health);
+        }
+
+        Ok(DryRunReport {
+            validation_error: None,
+            providers,
+            prompts,
+            input_schema: workflow.input_schema.clone(),
+        })
+    }
+
+    /// Get concurrency statistics
+    pub async fn get_concurrency_stats(&self) -> ConcurrencyStats {
+        self.concurrency_manager.get_stats().await
+    }
+
+    /// Current values of the executor metrics (see [`crate::telemetry`]), empty
+    /// until [`crate::telemetry::install`] has set up the recorder
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics_snapshot(&self) -> crate::telemetry::MetricsSnapshot {
+        crate::telemetry::installed()
+            .map(|recorder| recorder.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Resolve LLM configuration for a node with hierarchical priority
+    /// Priority: Node-level config > Profile config > Executor-level config > ERROR (no defaults)
+    fn resolve_llm_config_for_node(
+        node_config: &std::collections::HashMap<String, serde_json::Value>,
+        profile_llm_config: Option<&crate::llm::LlmConfig>,
+        default_llm_config: Option<&crate::llm::LlmConfig>,
+    ) -> crate::llm::LlmConfig {
+        // 1. Node-level config (highest priority), falling back to the selected
+        //    profile's config and then the executor-level config; a node-level
+        //    `model` is applied on top of any of them
+        let fallback = profile_llm_config.or(default_llm_config);
+        if let Some(config) = resolve_node_llm_config(node_config, fallback) {
+            tracing::debug!(
+                "Using LLM configuration: {:?} / {}",
+                config.provider_name(),
+                config.model_name()
+            );
+            return config;
+        }
+
+        // 2. No default fallback - require explicit configuration as requested by user
+        tracing::error!(
+            "No LLM configuration found - neither node-level nor executor-level config provided. System requires explicit configuration."
+        );
+        crate::llm::LlmConfig::Unconfigured {
+            message: "No LLM configuration provided. The system requires explicit configuration from program or user input rather than hardcoded defaults.".to_string()
+        }
+    }
+
+    /// The executor-level LLM configuration with `{{secret.NAME}}` references resolved
+    fn default_llm_config_with_secrets(
+        &self,
+        secrets: &Secrets,
+    ) -> GraphBitResult<Option<crate::llm::LlmConfig>> {
+        self.default_llm_config
+            .as_ref()
+            .map(|config| Self::llm_config_with_secrets(config, secrets))
+            .transpose()
+    }
+
+    /// `config` with `{{secret.NAME}}` references resolved
+    fn llm_config_with_secrets(
+        config: &crate::llm::LlmConfig,
+        secrets: &Secrets,
+    ) -> GraphBitResult<crate::llm::LlmConfig> {
+        let mut value = serde_json::to_value(config)?;
+        if !value.to_string().contains("{{secret.") {
+            return Ok(config.clone());
+        }
+        secrets.inject_value(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Give `context` the LLM configurations its selected profile gives the
+    /// agent nodes of `workflow`, with secret references resolved
+    fn attach_profile(
+        &self,
+        context: &mut WorkflowContext,
+        workflow: &Workflow,
+        default_llm_config: Option<&crate::llm::LlmConfig>,
+    ) -> GraphBitResult<()> {
+        let Some(name) = context.profile.as_deref() else {
+            return Ok(());
+        };
+        let Some(profile) = self.profiles.get(name) else {
+            let mut available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            return Err(GraphBitError::validation(
+                "profile",
+                format!("Unknown profile '{name}'. Available profiles: {available:?}"),
+            ));
+        };
+
+        let mut node_llm_configs = HashMap::new();
+        for node in workflow.graph.get_nodes().values() {
+            if !matches!(node.node_type, NodeType::Agent { .. }) {
+                continue;
+            }
+            if let Some(config) = profile.resolve(node, default_llm_config) {
+                let config = Self::llm_config_with_secrets(&config, &context.secrets)?;
+                node_llm_configs.insert(node.id.clone(), config);
+            }
+        }
+        context.node_llm_configs = node_llm_configs;
+        Ok(())
+    }
+
+    /// Give `context` the LLM configurations the classifier checks of the
+    /// guardrail nodes of `workflow` fall back to
+    fn attach_guardrail_llm_configs(
+        context: &mut WorkflowContext,
+        workflow: &Workflow,
+        default_llm_config: Option<&crate::llm::LlmConfig>,
+    ) {
+        for node in workflow.graph.get_nodes().values() {
+            let NodeType::Guardrail { checks, .. } = &node.node_type else {
+                continue;
+            };
+            if !checks
+                .iter()
+                .any(|check| matches!(check, GuardrailCheck::Classifier { .. }))
+            {
+                continue;
+            }
+            let config = Self::resolve_llm_config_for_node(
+                &node.config,
+                context.node_llm_configs.get(&node.id),
+                default_llm_config,
+            );
+            context.node_llm_configs.insert(node.id.clone(), config);
+        }
+    }
+
+    /// Give `context` the executor's prompt defaults and the globals of the
+    /// nodes of `workflow`
+    fn attach_prompt_defaults(&self, context: &mut WorkflowContext, workflow: &Workflow) {
+        context.prompt_defaults.clone_from(&self.prompt_defaults);
+        context.node_globals = workflow
+            .graph
+            .get_nodes()
+            .values()
+            .map(|node| (node.id.clone(), node.globals()))
+            .filter(|(_, globals)| !globals.is_empty())
+            .collect();
+    }
+
+    /// Resolve the system prompt for an agent node, preceded by the executor's
+    /// system preamble
+    /// Priority: `AgentNodeConfig` override > node `system_prompt` > agent config
+    fn resolve_node_system_prompt(
+        agent_node_config: &AgentNodeConfig,
+        node_config: &HashMap<String, serde_json::Value>,
+        agent: Option<&dyn AgentTrait>,
+        context: &WorkflowContext,
+        node_id: &NodeId,
+    ) -> Option<String> {
+        let system_prompt = agent_node_config
+            .system_prompt_override
+            .clone()
+            .or_else(|| {
+                node_config
+                    .get("system_prompt")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            })
+            .or_else(|| agent.map(|agent| agent.config().system_prompt.clone()));
+        let preamble = context
+            .prompt_defaults
+            .system_preamble
+            .as_deref()
+            .map(|preamble| {
+                Self::resolve_node_template_variables(preamble, context, Some(node_id))
+            });
+        join_prompt_sections(preamble, system_prompt)
+    }
+
+    /// System prompt of an agent node, resolved against the current context
+    async fn node_system_prompt(
+        node: &WorkflowNode,
+        agent_node_config: &AgentNodeConfig,
+        agent: &dyn AgentTrait,
+        context: &Mutex<WorkflowContext>,
+    ) -> Option<String> {
+        let ctx = context.lock().await;
+        Self::resolve_node_system_prompt(
+            agent_node_config,
+            &node.config,
+            Some(agent),
+            &ctx,
+            &node.id,
+        )
+    }
+
+    /// `prompt_template` followed by the executor's prompt suffix
+    fn suffixed_prompt_template<'a>(
+        prompt_template: &'a str,
+        context: &WorkflowContext,
+    ) -> Cow<'a, str> {
+        match context.prompt_defaults.prompt_suffix.as_deref() {
+            Some(suffix) if !suffix.is_empty() => {
+                Cow::Owned(format!("{prompt_template}\n\n{suffix}"))
+            }
+            _ => Cow::Borrowed(prompt_template),
+        }
+    }
+
+    /// Apply sampling settings to an agent node's request
+    /// Priority: node config > agent config
+    fn apply_node_sampling_overrides(
+        mut request: crate::llm::LlmRequest,
+        node_config: &HashMap<String, serde_json::Value>,
+        agent: &dyn AgentTrait,
+    ) -> crate::llm::LlmRequest {
+        let temperature = node_config
+            .get("temperature")
+            .and_then(serde_json::Value::as_f64)
+            .map(|t| t as f32)
+            .or_else(|| agent.config().temperature);
+        if let Some(temperature) = temperature {
+            request = request.with_temperature(temperature);
+        }
+
+        let max_tokens = node_config
+            .get("max_tokens")
+            .and_then(serde_json::Value::as_u64)
+            .map(|t| u32::try_from(t).unwrap_or(u32::MAX))
+            .or_else(|| agent.config().max_tokens);
+        if let Some(max_tokens) = max_tokens {
+            request = request.with_max_tokens(max_tokens);
+        }
+
+        if let Some(top_p) = node_config.get("top_p").and_then(serde_json::Value::as_f64) {
+            request = request.with_top_p(top_p as f32);
+        }
+        request
+    }
+
+    /// Provider for an agent node whose `llm_config` or `model`, or the configuration
+    /// the selected profile gives it, differs from the configuration of the agent's own
+    /// provider; `None` when the agent's provider applies
+    async fn node_llm_provider_override(
+        node_id: &NodeId,
+        node_config: &HashMap<String, serde_json::Value>,
+        agent: &dyn AgentTrait,
+        context: &Mutex<WorkflowContext>,
+    ) -> GraphBitResult<Option<crate::llm::LlmProvider>> {
+        let profile_llm_config = context.lock().await.node_llm_configs.get(node_id).cloned();
+        if profile_llm_config.is_none()
+            && !node_config.contains_key("llm_config")
+            && !node_config.contains_key("model")
+        {
+            return Ok(None);
+        }
+
+        let agent_llm_config = agent.llm_provider().config();
+        let fallback = profile_llm_config.as_ref().unwrap_or(agent_llm_config);
+        let Some(resolved) = resolve_node_llm_config(node_config, Some(fallback)) else {
+            return Ok(None);
+        };
+        if serde_json::to_value(&resolved).ok() == serde_json::to_value(agent_llm_config).ok() {
+            return Ok(None);
+        }
+
+        tracing::debug!(
+            "Using node-level LLM override: {} / {}",
+            resolved.provider_name(),
+            resolved.model_name()
+        );
+        let provider = crate::llm::LlmProviderFactory::create_provider(resolved.clone())?;
+        Ok(Some(crate::llm::LlmProvider::new(provider, resolved)))
+    }
+
+    /// Get or create circuit breaker for an agent
+    async fn get_circuit_breaker(&self, agent_id: &crate::types::AgentId) -> CircuitBreaker {
+        // Try to read first (more efficient for existing breakers)
+        {
+            let breakers = self.circuit_breakers.read().await;
+            if let Some(breaker) = breakers.get(agent_id) {
+                return breaker.clone();
+            }
+        }
+
+        // If not found, acquire write lock and create
+        let mut breakers = self.circuit_breakers.write().await;
+        breakers
+            .entry(agent_id.clone())
+            .or_insert_with(|| {
+                CircuitBreaker::new(self.circuit_breaker_config.clone())
+                    .with_clock(self.clock.clone())
+            })
+            .clone()
+    }
+
+    /// Get current concurrency limit
+    pub async fn max_concurrency(&self) -> usize {
+        // Get the global max concurrency from the concurrency manager
+        let _stats = self.concurrency_manager.get_stats().await;
+        let permits = self.concurrency_manager.get_available_permits().await;
+        permits.get("global").copied().unwrap_or(16) // Default fallback
+    }
+
+    /// Get available permits in semaphore
+    pub async fn available_permits(&self) -> HashMap<String, usize> {
+        self.concurrency_manager.get_available_permits().await
+    }
+
+    /// Execute a workflow with enhanced performance monitoring.
+    ///
+    /// When `guardrail_enforcer` is `Some`, PII is encoded before each LLM call and
+    /// decoded on LLM output; tool-call boundaries are handled by the executor layer.
+    ///
+    /// This is the non-streaming entry point. It delegates to [`execute_internal`]
+    /// with no event channel, so zero overhead is added to the hot path.
+    pub async fn execute(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+    ) -> GraphBitResult<WorkflowContext> {
+        let context = WorkflowContext::new(workflow.id.clone());
+        self.execute_internal(
+            workflow,
+            guardrail_enforcer,
+            None,
+            crate::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+    }
+
+    /// Execute a workflow with run-time inputs and secrets.
+    ///
+    /// `inputs` seed the workflow variables and are referenced as `{{input.NAME}}` in
+    /// prompt templates and as `vars.NAME` in expressions. `secrets` are referenced as
+    /// `{{secret.NAME}}` in LLM configurations and HTTP request node URLs and headers;
+    /// their values are redacted from the returned context.
+    pub async fn execute_with_inputs(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        inputs: HashMap<String, serde_json::Value>,
+        secrets: impl Into<Secrets>,
+    ) -> GraphBitResult<WorkflowContext> {
+        let context = WorkflowContext::new(workflow.id.clone())
+            .with_inputs(inputs)
+            .with_secrets(secrets);
+        self.execute_internal(
+            workflow,
+            guardrail_enforcer,
+            None,
+            crate::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+    }
+
+    /// Execute a workflow with the LLM profile named `profile`, added with
+    /// [`with_profile`](Self::with_profile).
+    ///
+    /// Fails before any node runs when the executor has no such profile. To combine a
+    /// profile with inputs or secrets, select it with [`WorkflowContext::with_profile`]
+    /// and run [`execute_with_context`](Self::execute_with_context).
+    pub async fn execute_with_profile(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        profile: &str,
+    ) -> GraphBitResult<WorkflowContext> {
+        let context = WorkflowContext::new(workflow.id.clone()).with_profile(profile);
+        self.execute_internal(
+            workflow,
+            guardrail_enforcer,
+            None,
+            crate::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+    }
+
+    /// Execute a workflow in a context prepared by the caller.
+    ///
+    /// Runs like [`execute_streaming`](Self::execute_streaming) when `event_tx`
+    /// is set and like [`execute`](Self::execute) otherwise, but starts from
+    /// `context` instead of a new one, so the inputs, secrets, profile and tag
+    /// filter set on it with the `WorkflowContext::with_*` builders all apply
+    /// to the run. `context` should be created for `workflow.id`.
+    pub async fn execute_with_context(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        stream_mode: crate::stream::StreamMode,
+        context: WorkflowContext,
+    ) -> GraphBitResult<WorkflowContext> {
+        self.execute_internal(workflow, guardrail_enforcer, event_tx, stream_mode, context)
+            .await
+    }
+
+    /// Execute a workflow with real-time streaming events.
+    ///
+    /// Identical to [`execute`] but emits [`StreamEvent`]s through `event_tx`
+    /// at every node boundary. The caller consumes events from the corresponding
+    /// `mpsc::Receiver` while this future runs (typically in a spawned task).
+    ///
+    /// # Arguments
+    /// * `workflow` — the workflow to execute
     /// * `guardrail_enforcer` — optional PII masking enforcer
     /// * `event_tx` — sender half of an `mpsc` channel for streaming events
     /// * `stream_mode` — controls which event categories are emitted
@@ -373,727 +1775,1802 @@ impl WorkflowExecutor {
     /// send failures are silently ignored — the workflow still runs to completion.
     pub async fn execute_streaming(
         &self,
-        workflow: Workflow,
-        guardrail_enforcer: Option<Arc<Enforcer>>,
-        event_tx: tokio::sync::mpsc::Sender<crate::stream::StreamEvent>,
-        stream_mode: crate::stream::StreamMode,
-    ) -> GraphBitResult<WorkflowContext> {
-        let context = WorkflowContext::new(workflow.id.clone());
-        self.execute_internal(workflow, guardrail_enforcer, Some(event_tx), stream_mode, context)
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        event_tx: tokio::sync::mpsc::Sender<crate::stream::StreamEvent>,
+        stream_mode: crate::stream::StreamMode,
+    ) -> GraphBitResult<WorkflowContext> {
+        let context = WorkflowContext::new(workflow.id.clone());
+        self.execute_internal(workflow, guardrail_enforcer, Some(event_tx), stream_mode, context)
+            .await
+    }
+
+    /// Execute a workflow once for each set of run-time inputs.
+    ///
+    /// Up to [`max_concurrency`](Self::max_concurrency) executions run at a time.
+    /// When `sink` is `Some`, one [`ResultRecord`](crate::result_sink::ResultRecord)
+    /// per execution is appended to it as the execution completes, carrying the
+    /// position of its inputs, so the results finished before a crash can be read
+    /// back with [`read_results`](crate::result_sink::read_results). Results are
+    /// returned in the order of `inputs`.
+    pub async fn execute_batch(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        inputs: Vec<HashMap<String, serde_json::Value>>,
+        sink: Option<Arc<crate::result_sink::ResultSink>>,
+    ) -> Vec<GraphBitResult<WorkflowContext>> {
+        use crate::result_sink::ResultRecord;
+
+        let limit = self.max_concurrency().await.max(1);
+        futures::stream::iter(inputs.into_iter().enumerate())
+            .map(|(index, inputs)| {
+                let workflow = workflow.clone();
+                let guardrail_enforcer = guardrail_enforcer.clone();
+                let sink = sink.clone();
+                async move {
+                    let context = WorkflowContext::new(workflow.id.clone()).with_inputs(inputs);
+                    let execution_id = context.execution_id;
+                    let result = self
+                        .execute_internal(
+                            workflow,
+                            guardrail_enforcer,
+                            None,
+                            crate::stream::StreamMode::Updates,
+                            context,
+                        )
+                        .await;
+                    if let Some(sink) = sink {
+                        let record = match &result {
+                            Ok(context) => ResultRecord::for_workflow(context),
+                            Err(e) => ResultRecord::for_error(execution_id, e.to_string()),
+                        };
+                        if let Err(e) = sink.append(&record.with_index(index)).await {
+                            tracing::error!(
+                                index,
+                                "Failed to append execution result to {}: {e}",
+                                sink.path().display()
+                            );
+                        }
+                    }
+                    result
+                }
+            })
+            .buffered(limit)
+            .collect()
+            .await
+    }
+
+    /// Run [`execute_run`](Self::execute_run), recording the execution in the
+    /// run history when one is set. Failing to write the history is logged and
+    /// does not fail the execution.
+    ///
+    /// The execution counts as running until it is recorded, so a shutdown
+    /// waits for the record; fails when the executor is shutting down.
+    async fn execute_internal(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        stream_mode: crate::stream::StreamMode,
+        context: WorkflowContext,
+    ) -> GraphBitResult<WorkflowContext> {
+        let mut running = self.shutdown.begin(context.execution_id, &workflow.name)?;
+        // Local copies of attachments only live as long as the execution
+        let attachment_dir = crate::attachment::temp_dir(context.execution_id);
+        let record = self
+            .run_history
+            .as_ref()
+            .map(|_| RunRecord::started(&workflow, &context));
+        if let (Some(history), Some(record)) = (&self.run_history, &record) {
+            if let Err(e) = history.record(record).await {
+                tracing::error!(
+                    execution_id = %record.execution_id,
+                    "Failed to record the start of the execution in the run history: {e}"
+                );
+            }
+        }
+        let result = self
+            .execute_run(workflow, guardrail_enforcer, event_tx, stream_mode, context)
+            .await;
+        crate::attachment::remove_temp_dir(&attachment_dir);
+        let mut checkpoint = None;
+        if let (Some(history), Some(record)) = (&self.run_history, record) {
+            let execution_id = record.execution_id.clone();
+            match history.record_finished(record, &result).await {
+                Ok(record) => checkpoint = record.report_path,
+                Err(e) => tracing::error!(
+                    %execution_id,
+                    "Failed to record the end of the execution in the run history: {e}"
+                ),
+            }
+        }
+        if let Some(context) = result.as_ref().ok().filter(|c| c.state.is_cancelled()) {
+            if let Some(sink) = &context.result_sink {
+                let record = crate::result_sink::ResultRecord::for_workflow(context);
+                if let Err(e) = sink.append(&record).await {
+                    tracing::error!(
+                        execution_id = %context.execution_id,
+                        "Failed to append the cancelled execution to {}: {e}",
+                        sink.path().display()
+                    );
+                }
+            }
+            running.interrupted(context, checkpoint);
+        }
+        result
+    }
+
+    /// Create and register an agent for every agent node of `workflow` whose
+    /// agent is not registered yet, configured from the node, and pre-warm the
+    /// circuit breakers of all of them. Returns the ids of the created agents.
+    async fn register_workflow_agents(
+        &self,
+        workflow: &Workflow,
+        context: &WorkflowContext,
+        default_llm_config: Option<&crate::llm::LlmConfig>,
+    ) -> GraphBitResult<Vec<AgentId>> {
+        let mut created = Vec::new();
+        for agent_id_str in &extract_agent_ids_from_workflow(workflow) {
+            if let Ok(agent_id) = AgentId::from_string(agent_id_str) {
+                // Check if agent is already registered
+                let agent_exists = {
+                    let agents_guard = self.agents.read().await;
+                    agents_guard.contains_key(&agent_id)
+                };
+
+                // Nodes referencing a registered agent must not get an auto-created one
+                if !agent_exists
+                    && workflow.graph.get_nodes().values().any(|node| {
+                        node.uses_registered_agent()
+                            && node
+                                .node_type
+                                .agent_config()
+                                .is_some_and(|config| config.agent_id == agent_id)
+                    })
+                {
+                    return Err(GraphBitError::workflow_execution(format!(
+                        "Agent '{agent_id_str}' is referenced as a registered agent but was not registered with the executor",
+                    )));
+                }
+
+                // If agent doesn't exist, create and register a default agent
+                if !agent_exists {
+                    let default_config = Self::auto_agent_config(
+                        workflow,
+                        context,
+                        &agent_id,
+                        agent_id_str,
+                        default_llm_config,
+                    );
+
+                    // Try to create agent - if it fails due to config issues, fail the workflow
+                    match crate::agents::Agent::new(default_config).await {
+                        Ok(agent) => {
+                            // Concurrent executions of this executor may have
+                            // registered the agent meanwhile; theirs is kept
+                            let mut agents_guard = self.agents.write().await;
+                            if let std::collections::hash_map::Entry::Vacant(entry) =
+                                agents_guard.entry(agent_id.clone())
+                            {
+                                entry.insert(Arc::new(agent));
+                                created.push(agent_id.clone());
+                                tracing::debug!("Auto-registered agent: {agent_id}");
+                            }
+                        }
+                        Err(e) => {
+                            return Err(GraphBitError::workflow_execution(format!(
+                                "Failed to create agent '{agent_id_str}': {e}. This may be due to invalid API key or configuration.",
+                            )));
+                        }
+                    }
+                }
+
+                // Pre-warm circuit breakers for all agents
+                let _ = self.get_circuit_breaker(&agent_id).await;
+            }
+        }
+        Ok(created)
+    }
+
+    /// Configuration of the agent created for the nodes of `workflow` calling
+    /// `agent_id`, taken from the first of them
+    fn auto_agent_config(
+        workflow: &Workflow,
+        context: &WorkflowContext,
+        agent_id: &AgentId,
+        agent_id_str: &str,
+        default_llm_config: Option<&crate::llm::LlmConfig>,
+    ) -> crate::agents::AgentConfig {
+        // Find the node configuration for this agent to extract system_prompt, temperature, max_tokens, and LLM config
+        let mut system_prompt = String::new();
+        let mut temperature: Option<f32> = None;
+        let mut max_tokens: Option<u32> = None;
+        let mut resolved_llm_config = default_llm_config.cloned()
+            .unwrap_or_else(|| crate::llm::LlmConfig::Unconfigured {
+                message: "No LLM configuration provided for agent creation. Please explicitly configure an LLM provider.".to_string()
+            });
+
+        for node in workflow.graph.get_nodes().values() {
+            if let Some(config) = node.node_type.agent_config() {
+                if &config.agent_id == agent_id {
+                    // Extract system_prompt: Priority is AgentNodeConfig.system_prompt_override > node.config["system_prompt"]
+                    if let Some(sys_override) = &config.system_prompt_override {
+                        system_prompt = sys_override.clone();
+                    } else if let Some(prompt_value) = node.config.get("system_prompt") {
+                        if let Some(prompt_str) = prompt_value.as_str() {
+                            system_prompt = prompt_str.to_string();
+                        }
+                    }
+
+                    // Extract temperature from node config if available
+                    if let Some(temp_value) = node.config.get("temperature") {
+                        if let Some(temp_num) = temp_value.as_f64() {
+                            temperature = Some(temp_num as f32);
+                        }
+                    }
+
+                    // Extract max_tokens from node config if available
+                    if let Some(max_tokens_value) = node.config.get("max_tokens") {
+                        if let Some(max_tokens_num) = max_tokens_value.as_u64() {
+                            max_tokens = Some(max_tokens_num as u32);
+                        }
+                    }
+
+                    // Resolve LLM configuration with hierarchical priority:
+                    // 1. Node-level config > 2. Profile config > 3. Executor-level config
+                    // Model routers start with their first candidate
+                    resolved_llm_config = match &node.node_type {
+                        NodeType::ModelRouter { candidates, .. } if !candidates.is_empty() => {
+                            candidates[0].clone()
+                        }
+                        _ => Self::resolve_llm_config_for_node(
+                            &node.config,
+                            context.node_llm_configs.get(&node.id),
+                            default_llm_config,
+                        ),
+                    };
+                    break;
+                }
+            }
+        }
+
+        // Create default agent configuration for this workflow
+        let mut default_config = crate::agents::AgentConfig::new(
+            format!("Agent_{agent_id_str}"),
+            "Auto-generated agent for workflow execution",
+            resolved_llm_config,
+        )
+        .with_id(agent_id.clone());
+
+        // Set system prompt if found in node configuration
+        if !system_prompt.is_empty() {
+            default_config = default_config.with_system_prompt(system_prompt);
+        }
+
+        // Set temperature if found in node configuration
+        if let Some(temp) = temperature {
+            default_config = default_config.with_temperature(temp);
+        }
+
+        // Set max_tokens if found in node configuration
+        if let Some(tokens) = max_tokens {
+            default_config = default_config.with_max_tokens(tokens);
+        }
+
+        default_config
+    }
+
+    /// Shared execution engine used by both [`execute`] and [`execute_streaming`].
+    ///
+    /// When `event_tx` is `Some`, node-level [`StreamEvent`]s are emitted at every
+    /// milestone. When `None` (non-streaming path) no channel operations occur, so
+    /// performance is identical to the original `execute()`.
+    async fn execute_run(
+        &self,
+        mut workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        stream_mode: crate::stream::StreamMode,
+        mut context: WorkflowContext,
+    ) -> GraphBitResult<WorkflowContext> {
+        let start_time = std::time::Instant::now();
+        self.attach_run_settings(&mut context, &workflow);
+        // Events can be delivered to the execution until it finishes
+        let _mailbox = self.external_events.open(context.execution_id);
+        if let Err(e) = self.prepare_run(&mut workflow, &mut context).await {
+            Self::report_run_error(event_tx.as_ref(), &e).await;
+            return Err(e);
+        }
+
+        // Each execution tracks its own usage, priced with the costs of the
+        // providers of the agents it may call and of the models routers may pick
+        let prices = self.usage_prices(&workflow).await;
+        let attribution = UsageAttribution::new(&workflow, prices, &self.usage_metadata_keys);
+        let budget = self.track_budget(&attribution, &mut context);
+        Self::record_node_graph(&workflow.graph, &mut context);
+
+        let nodes = Self::collect_executable_nodes(&workflow.graph)?;
+        if nodes.is_empty() {
+            context.complete();
+            context.redact_secrets();
+            // Streaming: emit WorkflowCompleted even for an empty workflow
+            Self::report_run_completed(event_tx.as_ref(), &context).await;
+            return Ok(context);
+        }
+
+        Self::report_run_started(event_tx.as_ref(), &workflow, nodes.len()).await;
+
+        let plan = RunPlan {
+            runtime: Arc::new(self.node_runtime(
+                &workflow.graph,
+                guardrail_enforcer,
+                event_tx,
+                stream_mode,
+            )),
+            handoff_sources: Self::handoff_sources(&workflow.graph),
+            early_joins: Self::early_joins(&workflow.graph),
+            tag_filter: context.tag_filter.clone(),
+            budget,
+            attribution,
+            secrets: context.secrets.clone(),
+            execution_id: context.execution_id,
+            start_time,
+        };
+        let unblockable = plan.unblockable();
+        let mut state = RunState::default();
+        // On-failure edges past the compensation depth are never followed
+        state
+            .disabled_edges
+            .extend(Self::failure_edges_beyond_depth(
+                &workflow.graph,
+                self.max_compensation_depth,
+            ));
+
+        while !state.is_settled(&workflow.graph) {
+            if self.shutdown.is_cancelling() {
+                state.shut_down = true;
+                break;
+            }
+            Self::settle_pending_nodes(&plan, &unblockable, &mut state, &mut context);
+            if state.is_settled(&workflow.graph) {
+                break;
+            }
+
+            let ready = Self::ready_nodes(&plan, &state);
+            if ready.is_empty() {
+                Self::stop_stalled_run(&plan, &state).await?;
+                break;
+            }
+            let ready = match Self::settle_unrunnable_nodes(
+                ready,
+                &plan,
+                &unblockable,
+                &mut state,
+                &mut context,
+            ) {
+                Ok(ready) => ready,
+                Err(e) => {
+                    Self::report_run_error(plan.runtime.event_tx.as_ref(), &e).await;
+                    return Err(e);
+                }
+            };
+            if ready.is_empty() {
+                continue;
+            }
+
+            let mut batch = self.run_batch(ready, context, &plan, &mut state).await;
+            let failure = batch.failure.take();
+            if state.shut_down {
+                context =
+                    Self::take_batch_context(batch.context, &workflow.graph, batch.abandoned).await;
+                break;
+            }
+            if let Some(failure) = failure {
+                // A node with on-failure edges still runs its compensation
+                // nodes; every other node is skipped
+                if let Err(failure) =
+                    Self::begin_compensation(&workflow.graph, failure, &mut batch, &mut state).await
+                {
+                    let context =
+                        Self::take_batch_context(batch.context, &workflow.graph, batch.abandoned)
+                            .await;
+                    return Ok(self.fail_run(context, &plan, &mut state, failure).await);
+                }
+            }
+            context =
+                Self::take_batch_context(batch.context, &workflow.graph, batch.abandoned).await;
+        }
+
+        Ok(self
+            .finish_run(context, &plan, state, &workflow.graph)
+            .await)
+    }
+
+    /// Put the executor's settings for a run on its context and mark it running
+    fn attach_run_settings(&self, context: &mut WorkflowContext, workflow: &Workflow) {
+        context.state = WorkflowState::Running {
+            current_node: NodeId::new(),
+        };
+        if context.output_spill.is_none() {
+            context.output_spill.clone_from(&self.output_spill);
+        }
+        context.max_attachment_bytes = self.max_attachment_bytes;
+        context.llm_middleware.clone_from(&self.llm_middleware);
+        if let Some(spacing) = &self.llm_call_spacing {
+            context.llm_middleware.push(spacing.clone());
+        }
+        context.external_events.clone_from(&self.external_events);
+        context.node_debug.persist = self.capture_payloads.is_some_and(|capture| capture.persist);
+        self.attach_prompt_defaults(context, workflow);
+    }
+
+    /// Validate a workflow and its context before its first batch, resolve its
+    /// secrets and LLM configurations, and create its agents
+    async fn prepare_run(
+        &self,
+        workflow: &mut Workflow,
+        context: &mut WorkflowContext,
+    ) -> GraphBitResult<()> {
+        // Validate workflow before execution. Inputs are checked only before the
+        // first node runs; a re-entered context holds variables nodes have set.
+        workflow.validate()?;
+        self.check_code_nodes_allowed(workflow)?;
+        if context.node_executions.is_empty() {
+            workflow.validate_inputs(&context.variables)?;
+        }
+
+        // Substitute secret references before any node configuration is read; a
+        // reference to a secret that was not supplied fails the run here
+        let secrets = context.secrets.clone();
+        workflow.inject_secrets(&secrets)?;
+        let default_llm_config = self.default_llm_config_with_secrets(&secrets)?;
+        self.attach_profile(context, workflow, default_llm_config.as_ref())?;
+        Self::attach_guardrail_llm_configs(context, workflow, default_llm_config.as_ref());
+        Self::validate_llm_configs(workflow, context, default_llm_config.as_ref()).await?;
+
+        if workflow.graph.node_count() == 0 {
+            return Err(GraphBitError::validation(
+                "workflow",
+                "No nodes found in workflow",
+            ));
+        }
+
+        // Auto-register missing agents to prevent lookup failures
+        let agents_created = self
+            .register_workflow_agents(workflow, context, default_llm_config.as_ref())
+            .await?;
+
+        // A re-entered context keeps the report of its first warm-up
+        if context.warm_up && context.warm_up_report.is_none() {
+            context.warm_up_report = Some(
+                self.warm_up_providers(workflow, agents_created, std::time::Instant::now())
+                    .await,
+            );
+        }
+        Ok(())
+    }
+
+    /// Validate the unique LLM configurations of the agent nodes once per
+    /// workflow (deduped by config fingerprint), so that agent creation does
+    /// not validate the same key and provider repeatedly
+    async fn validate_llm_configs(
+        workflow: &Workflow,
+        context: &mut WorkflowContext,
+        default_llm_config: Option<&crate::llm::LlmConfig>,
+    ) -> GraphBitResult<()> {
+        use crate::llm::{LlmProvider, LlmProviderFactory, LlmRequest};
+
+        // If we are re-entering execution (e.g. tool-resolution downstream reruns), avoid
+        // re-validating LLM configs again. This flag is workflow-run scoped and does not
+        // persist outside the current execution context.
+        let already_validated = context
+            .metadata
+            .get("workflow_llm_configs_validated")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if already_validated {
+            tracing::info!("Workflow LLM validation: already validated; skipping");
+            return Ok(());
+        }
+
+        let mut unique: HashMap<String, crate::llm::LlmConfig> = HashMap::with_capacity(4);
+        for node in workflow.graph.get_nodes().values() {
+            if matches!(node.node_type, NodeType::Agent { .. }) {
+                let resolved = Self::resolve_llm_config_for_node(
+                    &node.config,
+                    context.node_llm_configs.get(&node.id),
+                    default_llm_config,
+                );
+                if let Some(fp) = resolved.validation_fingerprint() {
+                    unique.entry(fp).or_insert(resolved);
+                }
+            }
+        }
+
+        tracing::info!(
+            "Workflow LLM validation: {} unique LLM config(s) across {} node(s)",
+            unique.len(),
+            workflow.graph.node_count()
+        );
+
+        // If there is only one unique LLM configuration across the workflow, skip proactive
+        // validation entirely (fail fast on the first real LLM call instead).
+        //
+        // If there are multiple unique configurations, validate them concurrently before any
+        // node execution to avoid N sequential round trips.
+        if unique.len() <= 1 {
+            tracing::info!(
+                "Workflow LLM validation: {} unique config; skipping proactive validation (fail-fast mode)",
+                unique.len()
+            );
+        } else {
+            tracing::info!(
+                "Workflow LLM validation: {} unique configs detected; validating concurrently",
+                unique.len()
+            );
+            let configs: Vec<crate::llm::LlmConfig> = unique.into_values().collect();
+            let validations = configs.into_iter().map(|cfg| async move {
+                let provider_name = cfg.provider_name().to_string();
+                let model_name = cfg.model_name().to_string();
+
+                tracing::info!(
+                    "Workflow LLM validation: validating provider={} model={}",
+                    provider_name,
+                    model_name
+                );
+
+                let provider = LlmProviderFactory::create_provider(cfg.clone())?;
+                let llm_provider = LlmProvider::new(provider, cfg);
+                let test_request = LlmRequest::new("Hello");
+                llm_provider.complete(test_request).await?;
+
+                Ok::<(), GraphBitError>(())
+            });
+
+            let results = futures::future::join_all(validations).await;
+            let mut failures: Vec<String> = Vec::new();
+            for r in results {
+                if let Err(e) = r {
+                    // Do not include raw API keys here; provider errors already mask keys.
+                    failures.push(e.to_string());
+                }
+            }
+
+            if !failures.is_empty() {
+                return Err(GraphBitError::config(format!(
+                    "LLM configuration validation failed for {} config(s): {}",
+                    failures.len(),
+                    failures.join(" | ")
+                )));
+            }
+        }
+
+        context.set_metadata(
+            "workflow_llm_configs_validated".to_string(),
+            serde_json::Value::Bool(true),
+        );
+        Ok(())
+    }
+
+    /// Tracker of the run's spending against the executor's budget, when it has
+    /// one. The tracker sees every LLM call of the run as a middleware.
+    fn track_budget(
+        &self,
+        attribution: &UsageAttribution,
+        context: &mut WorkflowContext,
+    ) -> Option<BudgetTracker> {
+        if !self.budget.is_limited() {
+            return None;
+        }
+        let tracker = BudgetTracker::new(self.budget.clone(), attribution.prices().clone());
+        context.llm_middleware.push(Arc::new(tracker.clone()));
+        Some(tracker)
+    }
+
+    /// Costs per token of the models a run may call, by model name: those of
+    /// the registered agents and of the candidates of its model routers
+    async fn usage_prices(&self, workflow: &Workflow) -> HashMap<String, (f64, f64)> {
+        let mut prices: HashMap<String, (f64, f64)> = self
+            .agents
+            .read()
             .await
+            .values()
+            .filter_map(|agent| {
+                let provider = agent.llm_provider().provider();
+                Some((
+                    provider.model_name().to_string(),
+                    provider.cost_per_token()?,
+                ))
+            })
+            .collect();
+        prices.extend(Self::candidate_prices(&workflow.graph));
+        prices
     }
 
-    /// Shared execution engine used by both [`execute`] and [`execute_streaming`].
+    /// Store the parents and names of the nodes of `graph` in the context
+    /// metadata, and the split branch each node runs in
+    fn record_node_graph(graph: &WorkflowGraph, context: &mut WorkflowContext) {
+        // Build dependency map: node_id -> [parent_node_ids...]
+        let mut deps_map: HashMap<String, Vec<String>> = HashMap::new();
+        // Build id->name map for better labeling
+        let mut id_name_map: HashMap<String, String> = HashMap::new();
+
+        for (nid, node) in graph.get_nodes() {
+            id_name_map.insert(nid.to_string(), node.name.clone());
+        }
+
+        // We need a mutable graph to call get_dependencies (it caches)
+        let mut graph_clone = graph.clone();
+        for nid in id_name_map.keys() {
+            // Convert back to NodeId via from_string (deterministic for UUIDs)
+            if let Ok(node_id) = NodeId::from_string(nid) {
+                let parents = graph_clone
+                    .get_dependencies(&node_id)
+                    .into_iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>();
+                deps_map.insert(nid.clone(), parents);
+            }
+        }
+
+        context.set_metadata(
+            "node_dependencies".to_string(),
+            serde_json::to_value(deps_map).unwrap_or(serde_json::json!({})),
+        );
+        context.set_metadata(
+            "node_id_to_name".to_string(),
+            serde_json::to_value(id_name_map).unwrap_or(serde_json::json!({})),
+        );
+
+        let nodes = graph.get_nodes();
+        context.node_branches = graph
+            .split_branches()
+            .into_iter()
+            .filter_map(|(id, (split, index))| {
+                let branch = BranchScope {
+                    split: nodes.get(&split)?.name.clone(),
+                    index,
+                };
+                Some((nodes.get(&id)?.name.clone(), branch))
+            })
+            .collect();
+    }
+
+    /// Parents of each node of `graph`.
     ///
-    /// When `event_tx` is `Some`, node-level [`StreamEvent`]s are emitted at every
-    /// milestone. When `None` (non-streaming path) no channel operations occur, so
-    /// performance is identical to the original `execute()`.
-    async fn execute_internal(
+    /// Taken from the canonical `edges` list (same source Python `connect` uses).
+    /// Using `get_dependencies` + petgraph/cache here has regressed to empty dependency lists
+    /// for some graphs, which makes `parents.iter().all(...)` vacuously true and schedules
+    /// condition successors (e.g. all advisor branches) in the same batch as the intake node.
+    fn node_parents(graph: &WorkflowGraph) -> HashMap<NodeId, Vec<NodeId>> {
+        graph
+            .get_nodes()
+            .keys()
+            .map(|nid| {
+                let deps: Vec<NodeId> = graph
+                    .get_edges()
+                    .iter()
+                    .filter_map(|(from, to, _)| (to == nid).then_some(from.clone()))
+                    .collect();
+                (nid.clone(), deps)
+            })
+            .collect()
+    }
+
+    /// Joins of `graph` that may run before all their parents finish, with the
+    /// number of parents that must succeed and what happens to the others
+    fn early_joins(graph: &WorkflowGraph) -> Vec<(NodeId, usize, PendingBranches)> {
+        graph
+            .get_nodes()
+            .values()
+            .filter(|node| matches!(node.node_type, NodeType::Join))
+            .filter_map(|node| {
+                let required = JoinPolicy::from_node_config(&node.config)
+                    .ok()?
+                    .required()?;
+                let pending = PendingBranches::from_node_config(&node.config).unwrap_or_default();
+                Some((node.id.clone(), required, pending))
+            })
+            .collect()
+    }
+
+    /// What the nodes of a run on `graph` share
+    fn node_runtime(
         &self,
-        workflow: Workflow,
+        graph: &WorkflowGraph,
         guardrail_enforcer: Option<Arc<Enforcer>>,
         event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
         stream_mode: crate::stream::StreamMode,
-        mut context: WorkflowContext,
-    ) -> GraphBitResult<WorkflowContext> {
-        use crate::stream::{StreamEvent, error_type_from_graphbit_error, error_type_from_string};
+    ) -> NodeRuntime {
+        NodeRuntime {
+            agents: self.agents.clone(),
+            circuit_breakers: self.circuit_breakers.clone(),
+            circuit_breaker_config: self.circuit_breaker_config.clone(),
+            guardrail_enforcer,
+            node_parents: Arc::new(Self::node_parents(graph)),
+            conditional_handlers: self.conditional_handlers.clone(),
+            workflow_graph: Arc::new(graph.clone()),
+            event_tx,
+            stream_mode,
+            tool_runtime: self
+                .tool_registry
+                .clone()
+                .map(|registry| NativeToolRuntime {
+                    registry,
+                    strict_tool_args: self.strict_tool_args,
+                    trace: self.tool_call_trace.clone(),
+                }),
+        }
+    }
 
-        let start_time = std::time::Instant::now();
+    /// Settle the nodes that wait on others without running: handoff targets
+    /// whose supervisors settled, dependents of failed nodes, and nodes only
+    /// reached through disabled edges
+    fn settle_pending_nodes(
+        plan: &RunPlan,
+        unblockable: &HashSet<&NodeId>,
+        state: &mut RunState,
+        context: &mut WorkflowContext,
+    ) {
+        Self::settle_handoff_targets(&plan.handoff_sources, state);
+        if !state.failed.is_empty() {
+            Self::block_failed_dependents(
+                &plan.runtime.workflow_graph,
+                &plan.runtime.node_parents,
+                &state.failed,
+                unblockable,
+                &state.resolved,
+                &mut state.skipped,
+                context,
+            );
+        }
+        if !state.disabled_edges.is_empty() {
+            Self::expand_skips_from_disabled_edges(
+                &plan.runtime.node_parents,
+                &state.disabled_edges,
+                &state.resolved,
+                &mut state.skipped,
+            );
+        }
+    }
 
-        // Set initial workflow state
-        context.state = WorkflowState::Running {
-            current_node: NodeId::new(),
-        };
+    /// Unsettled nodes whose parents have all settled
+    fn ready_nodes(plan: &RunPlan, state: &RunState) -> Vec<WorkflowNode> {
+        plan.runtime
+            .workflow_graph
+            .get_nodes()
+            .iter()
+            .filter(|(id, _)| {
+                !state.resolved.contains(*id)
+                    && !state.skipped.contains(*id)
+                    && !state.pending_tool_resolution.contains(*id)
+                    && !plan.handoff_sources.contains_key(*id)
+            })
+            .filter(|(id, _)| {
+                plan.runtime
+                    .node_parents
+                    .get(*id)
+                    .map(|parents| parents.iter().all(|p| state.is_done(p)))
+                    .unwrap_or(false)
+            })
+            .map(|(_, n)| n.clone())
+            .collect()
+    }
 
-        // Validate workflow before execution
-        if let Err(e) = workflow.validate() {
-            if let Some(ref tx) = event_tx {
-                let _ = tx
-                    .send(StreamEvent::WorkflowFailed {
-                        error: e.to_string(),
-                        error_type: error_type_from_graphbit_error(&e),
-                    })
-                    .await;
-            }
-            return Err(e);
+    /// Stop a run with no node ready to run. A streaming run waiting on tool
+    /// calls stops to let the bindings resolve them; any other run fails.
+    async fn stop_stalled_run(plan: &RunPlan, state: &RunState) -> GraphBitResult<()> {
+        if plan.runtime.event_tx.is_some() && !state.pending_tool_resolution.is_empty() {
+            Self::defer_tool_resolution(plan, state);
+            return Ok(());
+        }
+        let err_msg =
+            "No runnable nodes but workflow not finished (cycle or invalid scheduling state)"
+                .to_string();
+        if let Some(ref tx) = plan.runtime.event_tx {
+            let _ = tx
+                .send(crate::stream::StreamEvent::WorkflowFailed {
+                    error: err_msg.clone(),
+                    error_type: "runtime_error".to_string(),
+                })
+                .await;
         }
+        Err(GraphBitError::workflow_execution(err_msg))
+    }
 
-        // Validate unique LLM configurations once per workflow (deduped by config fingerprint).
-        // This prevents validating the same key/provider repeatedly during agent creation.
-        {
-            // If we are re-entering execution (e.g. tool-resolution downstream reruns), avoid
-            // re-validating LLM configs again. This flag is workflow-run scoped and does not
-            // persist outside the current execution context.
-            let already_validated = context
-                .metadata
-                .get("workflow_llm_configs_validated")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-            if already_validated {
-                tracing::info!("Workflow LLM validation: already validated; skipping");
-            } else {
-            use crate::llm::{LlmProvider, LlmProviderFactory, LlmRequest};
-            use std::collections::HashMap;
-
-            let mut unique: HashMap<String, crate::llm::LlmConfig> = HashMap::with_capacity(4);
-            for node in workflow.graph.get_nodes().values() {
-                if matches!(node.node_type, NodeType::Agent { .. }) {
-                    let resolved = self.resolve_llm_config_for_node(&node.config);
-                    if let Some(fp) = resolved.validation_fingerprint() {
-                        unique.entry(fp).or_insert(resolved);
-                    }
+    /// Log the nodes left waiting on unresolved tool calls when a streaming
+    /// run stops to let the bindings resolve them
+    fn defer_tool_resolution(plan: &RunPlan, state: &RunState) {
+        let blocked_node_ids: Vec<String> = plan
+            .runtime
+            .workflow_graph
+            .get_nodes()
+            .keys()
+            .filter(|id| !state.is_done(id) && !state.pending_tool_resolution.contains(*id))
+            .map(ToString::to_string)
+            .collect();
+        tracing::info!(
+            blocked_node_ids = ?blocked_node_ids,
+            "Deferring downstream nodes blocked on unresolved tool calls"
+        );
+    }
+
+    /// Settle the ready nodes that do not run: those a re-execution reuses,
+    /// those the tag filter excludes or whose parent it skipped, and, once the
+    /// budget ran out, those not exempt from it. Returns the nodes to run.
+    fn settle_unrunnable_nodes(
+        ready: Vec<WorkflowNode>,
+        plan: &RunPlan,
+        unblockable: &HashSet<&NodeId>,
+        state: &mut RunState,
+        context: &mut WorkflowContext,
+    ) -> GraphBitResult<Vec<WorkflowNode>> {
+        let mut ready =
+            Self::settle_reused_nodes(ready, &plan.runtime.workflow_graph, state, context)?;
+
+        // Nodes the tag filter excludes, and the dependents of nodes it
+        // skipped, settle without running
+        if !plan.tag_filter.is_empty() || !state.tag_skipped.is_empty() {
+            let node_parents = &plan.runtime.node_parents;
+            let (excluded, runnable): (Vec<_>, Vec<_>) = ready.into_iter().partition(|node| {
+                plan.tag_filter.excludes(&node.tags)
+                    || (!unblockable.contains(&node.id)
+                        && node_parents.get(&node.id).is_some_and(|parents| {
+                            parents.iter().any(|p| state.tag_skipped.contains(p))
+                        }))
+            });
+            for node in &excluded {
+                Self::settle_excluded_node(
+                    node,
+                    node_parents,
+                    &plan.runtime.workflow_graph,
+                    context,
+                    &mut state.tag_skipped,
+                );
+                if state.tag_skipped.contains(&node.id) {
+                    state.skipped.insert(node.id.clone());
+                } else {
+                    state.resolved.insert(node.id.clone());
                 }
             }
+            ready = runnable;
+        }
 
-            tracing::info!(
-                "Workflow LLM validation: {} unique LLM config(s) across {} node(s)",
-                unique.len(),
-                workflow.graph.node_count()
-            );
-
-            // If there is only one unique LLM configuration across the workflow, skip proactive
-            // validation entirely (fail fast on the first real LLM call instead).
-            //
-            // If there are multiple unique configurations, validate them concurrently before any
-            // node execution to avoid N sequential round trips.
-            if unique.len() <= 1 {
-                tracing::info!(
-                    "Workflow LLM validation: {} unique config; skipping proactive validation (fail-fast mode)",
-                    unique.len()
-                );
-            } else {
-                tracing::info!(
-                    "Workflow LLM validation: {} unique configs detected; validating concurrently",
-                    unique.len()
+        // Once the budget ran out only budget-exempt nodes still run
+        if plan
+            .budget
+            .as_ref()
+            .is_some_and(BudgetTracker::is_exhausted)
+        {
+            let (exempt, stopped): (Vec<_>, Vec<_>) =
+                ready.into_iter().partition(WorkflowNode::is_budget_exempt);
+            for node in &stopped {
+                state.skipped.insert(node.id.clone());
+                context.abandon_node(
+                    &node.id,
+                    &node.name,
+                    AbandonedNode::Skipped {
+                        reason: SkipReason::Budget,
+                    },
                 );
-                let configs: Vec<crate::llm::LlmConfig> = unique.into_values().collect();
-                let validations = configs.into_iter().map(|cfg| async move {
-                    let provider_name = cfg.provider_name().to_string();
-                    let model_name = cfg.model_name().to_string();
-
-                    tracing::info!(
-                        "Workflow LLM validation: validating provider={} model={}",
-                        provider_name,
-                        model_name
-                    );
-
-                    let provider = LlmProviderFactory::create_provider(cfg.clone())?;
-                    let llm_provider = LlmProvider::new(provider, cfg);
-                    let test_request = LlmRequest::new("Hello");
-                    llm_provider.complete(test_request).await?;
+            }
+            ready = exempt;
+        }
+        Ok(ready)
+    }
 
-                    Ok::<(), GraphBitError>(())
-                });
+    /// Run a batch of ready nodes concurrently, merging their results into
+    /// `state` as they finish, until all finished, the run fails or the
+    /// executor shuts down
+    async fn run_batch(
+        &self,
+        ready: Vec<WorkflowNode>,
+        context: WorkflowContext,
+        plan: &RunPlan,
+        state: &mut RunState,
+    ) -> Batch {
+        let batch_size = ready.len();
+        state.step += 1;
+        let batch_ids: Vec<String> = ready.iter().map(|n| n.id.to_string()).collect();
+        tracing::info!(batch_size, batch_node_ids = ?batch_ids, "Executing dynamic batch");
+
+        // ── Streaming: emit NodeStarted for each node about to run ────────────
+        Self::report_nodes_started(plan, &ready).await;
+
+        let mut batch = Batch {
+            context: Arc::new(Mutex::new(context)),
+            running: HashMap::with_capacity(batch_size),
+            abandoned: HashMap::new(),
+            failure: None,
+            ids: batch_ids,
+        };
+        let mut tasks = FuturesUnordered::new();
+        for node in ready {
+            let task_node_id = node.id.clone();
+            let task = self.spawn_node(node, &batch.context, plan);
+            batch
+                .running
+                .insert(task_node_id.clone(), task.abort_handle());
+            tasks.push(task.map(move |result| (task_node_id, result)));
+        }
 
-                let results = futures::future::join_all(validations).await;
-                let mut failures: Vec<String> = Vec::new();
-                for r in results {
-                    if let Err(e) = r {
-                        // Do not include raw API keys here; provider errors already mask keys.
-                        failures.push(e.to_string());
+        while !batch.running.is_empty() {
+            let next = tokio::select! {
+                next = tasks.next() => next,
+                () = self.shutdown.cancelled() => {
+                    // The executor shut down: stop the running nodes
+                    for (node_id, task) in batch.running.drain() {
+                        task.abort();
+                        if let std::collections::hash_map::Entry::Vacant(entry) =
+                            batch.abandoned.entry(node_id.clone())
+                        {
+                            state.skipped.insert(node_id);
+                            entry.insert(AbandonedNode::Cancelled);
+                        }
                     }
+                    state.shut_down = true;
+                    break;
                 }
-
-                if !failures.is_empty() {
-                    let err = GraphBitError::config(format!(
-                        "LLM configuration validation failed for {} config(s): {}",
-                        failures.len(),
-                        failures.join(" | ")
-                    ));
-                    if let Some(ref tx) = event_tx {
-                        let _ = tx
-                            .send(StreamEvent::WorkflowFailed {
-                                error: err.to_string(),
-                                error_type: error_type_from_graphbit_error(&err),
-                            })
-                            .await;
-                    }
-                    return Err(err);
+            };
+            let Some((task_node_id, task_result)) = next else {
+                break;
+            };
+            if batch.running.remove(&task_node_id).is_none()
+                || batch.abandoned.contains_key(&task_node_id)
+            {
+                continue;
+            }
+            match task_result {
+                Ok(Ok(node_result)) => {
+                    self.merge_node_result(node_result, plan, state, &mut batch)
+                        .await;
+                }
+                Ok(Err(e)) => {
+                    let stops_run = Self::is_auth_error(&e) || self.fail_fast;
+                    Self::merge_task_failure(
+                        &plan.runtime.workflow_graph,
+                        task_node_id,
+                        e.to_string(),
+                        stops_run,
+                        state,
+                        &mut batch,
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    Self::merge_task_failure(
+                        &plan.runtime.workflow_graph,
+                        task_node_id,
+                        format!("Task execution failed: {e}"),
+                        self.fail_fast,
+                        state,
+                        &mut batch,
+                    )
+                    .await;
                 }
             }
+            if batch.failure.is_some() {
+                break;
+            }
+            Self::cancel_over_budget_nodes(plan, state, &mut batch);
+            Self::stop_waiting_for_branches(plan, state, &mut batch);
+        }
 
-            context.set_metadata(
-                "workflow_llm_configs_validated".to_string(),
-                serde_json::Value::Bool(true),
-            );
+        // After a fail-fast error, let the rest of the batch finish as before
+        while !batch.running.is_empty() {
+            match tasks.next().await {
+                Some((task_node_id, _)) => {
+                    batch.running.remove(&task_node_id);
+                }
+                None => break,
             }
         }
+        batch
+    }
 
+    /// Emit `NodeStarted` for the nodes of a batch about to run
+    async fn report_nodes_started(plan: &RunPlan, nodes: &[WorkflowNode]) {
+        let Some(ref tx) = plan.runtime.event_tx else {
+            return;
+        };
+        for node in nodes {
+            let _ = tx
+                .send(crate::stream::StreamEvent::NodeStarted {
+                    node_id: node.id.to_string(),
+                    node_name: node.name.clone(),
+                })
+                .await;
+        }
+    }
 
-        // PERFORMANCE FIX: Auto-register agents for all agent nodes found in workflow
-        let agent_ids = extract_agent_ids_from_workflow(&workflow);
-        if agent_ids.is_empty() {
-            let err = GraphBitError::validation("workflow", "No agents found in workflow");
-            if let Some(ref tx) = event_tx {
-                let _ = tx
-                    .send(StreamEvent::WorkflowFailed {
-                        error: err.to_string(),
-                        error_type: error_type_from_graphbit_error(&err),
+    /// Spawn the task running `node` in a batch: it waits out the node's pacing
+    /// and concurrency limits, then runs the node, retries included, within
+    /// its time limit
+    fn spawn_node(
+        &self,
+        node: WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        plan: &RunPlan,
+    ) -> tokio::task::JoinHandle<GraphBitResult<NodeExecutionResult>> {
+        let context = context.clone();
+        let runtime = plan.runtime.clone();
+        // A retry configuration set on the node overrides the executor default
+        let retry_config = if node.retry_config == RetryConfig::default() {
+            self.default_retry_config.clone()
+        } else {
+            Some(node.retry_config.clone())
+        };
+        let concurrency_manager = self.concurrency_manager.clone();
+        let max_node_execution_time = self.max_node_execution_time_ms;
+        let execution_id = plan.execution_id;
+        let audit_log = self.audit_log.clone();
+        let capture_payloads = self.capture_payloads;
+
+        tokio::spawn(crate::clock::scope(self.clock.clone(), async move {
+            let task_info = TaskInfo::from_node_type(&node.node_type, &node.id);
+
+            // Pacing delays are waited outside the node's duration, and
+            // without holding its concurrency permits
+            let pacing = node.pacing().unwrap_or_default();
+            let pre_execution_delay = pacing.pre_execution_delay();
+            if !pre_execution_delay.is_zero() {
+                crate::clock::sleep(pre_execution_delay).await;
+            }
+
+            // Nodes sharing a concurrency key wait for each other before
+            // taking their permits, and hold the key until they finish
+            let keyed_lock = match node.concurrency_key() {
+                Some(key) => Some(concurrency_manager.acquire_key(key).await),
+                None => None,
+            };
+            let permits = concurrency_manager
+                .acquire_permits(&task_info)
+                .await
+                .map_err(|e| {
+                    GraphBitError::workflow_execution(format!(
+                        "Failed to acquire permits for node {}: {e}",
+                        node.id
+                    ))
+                })?;
+
+            // A node timeout bounds the whole node execution, retries included,
+            // and falls back to the executor's maximum node execution time
+            let node_id = node.id.clone();
+            let node_name = node.name.clone();
+            // External event nodes wait as long as their event takes
+            let waits_for_event = matches!(node.node_type, NodeType::ExternalEvent { .. });
+            let time_limit_ms = node
+                .timeout_seconds
+                .map(|seconds| seconds.saturating_mul(1000))
+                .or_else(|| max_node_execution_time.filter(|_| !waits_for_event));
+            let budget_exempt = node.is_budget_exempt();
+            let audit_context = AuditContext {
+                execution_id: Some(execution_id.to_string()),
+                node_id: Some(node_id.to_string()),
+                node_name: Some(node_name.clone()),
+            };
+            let execution = Self::execute_node_with_retry(node, context, runtime, retry_config);
+            let execution = crate::budget::run_node(budget_exempt, Box::pin(execution));
+            let execution = crate::capture::run_node(capture_payloads, execution);
+            let execution = crate::audit::run_node(audit_log, audit_context, execution);
+            let (result, llm_spacing_ms) = crate::pacing::run_node(Self::within_time_limit(
+                execution,
+                time_limit_ms,
+                node_name,
+                node_id,
+            ))
+            .await;
+            drop(permits);
+            drop(keyed_lock);
+
+            let mut result = result?;
+            let post_execution_delay = pacing.post_execution_delay();
+            if !post_execution_delay.is_zero() {
+                if result.completed_at.is_none() {
+                    result = result.mark_completed();
+                }
+                crate::clock::sleep(post_execution_delay).await;
+            }
+            let millis =
+                |delay: std::time::Duration| u64::try_from(delay.as_millis()).unwrap_or(u64::MAX);
+            GraphBitResult::Ok(result.with_pacing(PacingDelays {
+                pre_execution_ms: millis(pre_execution_delay),
+                post_execution_ms: millis(post_execution_delay),
+                llm_spacing_ms,
+            }))
+        }))
+    }
+
+    /// Await a node's `execution`, failing the node once `time_limit_ms` passed
+    async fn within_time_limit(
+        execution: impl Future<Output = GraphBitResult<NodeExecutionResult>>,
+        time_limit_ms: Option<u64>,
+        node_name: String,
+        node_id: NodeId,
+    ) -> GraphBitResult<NodeExecutionResult> {
+        match time_limit_ms {
+            Some(limit_ms) => {
+                tokio::time::timeout(tokio::time::Duration::from_millis(limit_ms), execution)
+                    .await
+                    .unwrap_or_else(|_| {
+                        Ok(NodeExecutionResult::failure(
+                            format!("Node '{node_name}' timed out after {limit_ms}ms"),
+                            node_id,
+                        )
+                        .with_duration(limit_ms))
                     })
-                    .await;
             }
-            return Err(err);
+            None => execution.await,
         }
+    }
 
-        // Auto-register missing agents to prevent lookup failures
-        for agent_id_str in &agent_ids {
-            if let Ok(agent_id) = AgentId::from_string(agent_id_str) {
-                // Check if agent is already registered
-                let agent_exists = {
-                    let agents_guard = self.agents.read().await;
-                    agents_guard.contains_key(&agent_id)
-                };
+    /// Merge the result of a node of `batch` that ran: record it, route its
+    /// outgoing edges when it succeeded, and fail the run or block its
+    /// dependents when it failed
+    async fn merge_node_result(
+        &self,
+        node_result: NodeExecutionResult,
+        plan: &RunPlan,
+        state: &mut RunState,
+        batch: &mut Batch,
+    ) {
+        state.total_executed += 1;
+        if node_result.success {
+            state.total_successful += 1;
+            state.succeeded.insert(node_result.node_id.clone());
+        }
+        let node_requires_tool_resolution = plan.runtime.event_tx.is_some()
+            && Self::is_tool_calls_required_output(&node_result.output);
+        if node_requires_tool_resolution {
+            state
+                .pending_tool_resolution
+                .insert(node_result.node_id.clone());
+        } else {
+            state.resolved.insert(node_result.node_id.clone());
+        }
 
-                // If agent doesn't exist, create and register a default agent
-                if !agent_exists {
-                    // Find the node configuration for this agent to extract system_prompt, temperature, max_tokens, and LLM config
-                    let mut system_prompt = String::new();
-                    let mut temperature: Option<f32> = None;
-                    let mut max_tokens: Option<u32> = None;
-                    let mut resolved_llm_config = self.default_llm_config.clone()
-                        .unwrap_or_else(|| crate::llm::LlmConfig::Unconfigured {
-                            message: "No LLM configuration provided for agent creation. Please explicitly configure an LLM provider.".to_string()
-                        });
-
-                    for node in workflow.graph.get_nodes().values() {
-                        if let NodeType::Agent { config } = &node.node_type {
-                            if config.agent_id == agent_id {
-                                // Extract system_prompt: Priority is AgentNodeConfig.system_prompt_override > node.config["system_prompt"]
-                                if let Some(sys_override) = &config.system_prompt_override {
-                                    system_prompt = sys_override.clone();
-                                } else if let Some(prompt_value) = node.config.get("system_prompt")
-                                {
-                                    if let Some(prompt_str) = prompt_value.as_str() {
-                                        system_prompt = prompt_str.to_string();
-                                    }
-                                }
-
-                                // Extract temperature from node config if available
-                                if let Some(temp_value) = node.config.get("temperature") {
-                                    if let Some(temp_num) = temp_value.as_f64() {
-                                        temperature = Some(temp_num as f32);
-                                    }
-                                }
-
-                                // Extract max_tokens from node config if available
-                                if let Some(max_tokens_value) = node.config.get("max_tokens") {
-                                    if let Some(max_tokens_num) = max_tokens_value.as_u64() {
-                                        max_tokens = Some(max_tokens_num as u32);
-                                    }
-                                }
-
-                                // Resolve LLM configuration with hierarchical priority:
-                                // 1. Node-level config > 2. Executor-level config > 3. Default
-                                resolved_llm_config =
-                                    self.resolve_llm_config_for_node(&node.config);
-                                break;
-                            }
-                        }
-                    }
+        // ── Streaming: emit NodeCompleted or NodeFailed ───────────────────
+        Self::report_node_result(plan, &node_result).await;
+        let graph = plan.runtime.workflow_graph.as_ref();
+        Self::record_node_result(graph, &node_result, state, &batch.context).await;
 
-                    // Create default agent configuration for this workflow
-                    let mut default_config = crate::agents::AgentConfig::new(
-                        format!("Agent_{agent_id_str}"),
-                        "Auto-generated agent for workflow execution",
-                        resolved_llm_config,
-                    )
-                    .with_id(agent_id.clone());
+        let Some(node) = graph.get_node(&node_result.node_id) else {
+            if node_result.success {
+                tracing::warn!(
+                    node_id = %node_result.node_id,
+                    "Workflow batch merge: executed node id not found in graph"
+                );
+                batch.fail(
+                    None,
+                    format!(
+                        "Workflow batch merge: executed node id {} not found in graph",
+                        node_result.node_id
+                    ),
+                );
+            }
+            return;
+        };
+        if node_result.success {
+            let routing = {
+                let mut ctx = batch.context.lock().await;
+                Self::route_node_output(graph, node, &node_result, &mut ctx, state)
+            };
+            if let Err(e) = routing {
+                batch.fail(None, e);
+            }
+            return;
+        }
 
-                    // Set system prompt if found in node configuration
-                    if !system_prompt.is_empty() {
-                        default_config = default_config.with_system_prompt(system_prompt);
-                    }
+        Self::store_failure_edge_outputs(
+            graph,
+            node,
+            node_result.error.as_deref().unwrap_or("Unknown error"),
+            &mut *batch.context.lock().await,
+        );
+        // A failed condition leaves every successor "ready" (parent resolved) but
+        // never runs expand_skips_from_condition, so all branches would execute.
+        // Treat condition failure like an unrecoverable routing error.
+        if matches!(node.node_type, NodeType::Condition { .. }) {
+            let message = node_result.error.clone().unwrap_or_else(|| {
+                format!(
+                    "Condition '{}' failed (no branch chosen; successors were not skipped)",
+                    node.name
+                )
+            });
+            batch.fail(Some(node.id.clone()), message);
+        } else if self.fail_fast && state.compensating.is_none() {
+            let message = node_result
+                .error
+                .clone()
+                .unwrap_or_else(|| format!("Node '{}' failed", node.name));
+            batch.fail(Some(node.id.clone()), message);
+        } else if !node_requires_tool_resolution {
+            state.failed.push(node_result.node_id);
+        }
+    }
 
-                    // Set temperature if found in node configuration
-                    if let Some(temp) = temperature {
-                        default_config = default_config.with_temperature(temp);
-                    }
+    /// Emit `NodeCompleted` or `NodeFailed` for a node that ran, with secrets
+    /// redacted
+    async fn report_node_result(plan: &RunPlan, node_result: &NodeExecutionResult) {
+        use crate::stream::{StreamEvent, error_type_from_string};
 
-                    // Set max_tokens if found in node configuration
-                    if let Some(tokens) = max_tokens {
-                        default_config = default_config.with_max_tokens(tokens);
-                    }
+        let Some(ref tx) = plan.runtime.event_tx else {
+            return;
+        };
+        let node_name = plan
+            .runtime
+            .workflow_graph
+            .get_node(&node_result.node_id)
+            .map(|n| n.name.clone())
+            .unwrap_or_default();
+
+        if node_result.success {
+            let mut output = node_result.output.clone();
+            plan.secrets.redact_value(&mut output);
+            let _ = tx
+                .send(StreamEvent::NodeCompleted {
+                    node_id: node_result.node_id.to_string(),
+                    node_name,
+                    output,
+                })
+                .await;
+        } else {
+            let error_msg = plan
+                .secrets
+                .redact(node_result.error.as_deref().unwrap_or("Unknown error"));
+            let _ = tx
+                .send(StreamEvent::NodeFailed {
+                    node_id: node_result.node_id.to_string(),
+                    node_name,
+                    error: error_msg.clone(),
+                    error_type: error_type_from_string(&error_msg),
+                })
+                .await;
+        }
+    }
 
-                    // Try to create agent - if it fails due to config issues, fail the workflow
-                    match crate::agents::Agent::new(default_config).await {
-                        Ok(agent) => {
-                            let mut agents_guard = self.agents.write().await;
-                            agents_guard.insert(agent_id.clone(), Arc::new(agent));
-                            tracing::debug!("Auto-registered agent: {agent_id}");
-                        }
-                        Err(e) => {
-                            let err = GraphBitError::workflow_execution(format!(
-                                "Failed to create agent '{agent_id_str}': {e}. This may be due to invalid API key or configuration.",
-                            ));
-                            if let Some(ref tx) = event_tx {
-                                let _ = tx
-                                    .send(StreamEvent::WorkflowFailed {
-                                        error: err.to_string(),
-                                        error_type: error_type_from_graphbit_error(&err),
-                                    })
-                                    .await;
-                            }
-                            return Err(err);
-                        }
-                    }
+    /// Store the output of a node that ran, record its execution on the
+    /// context and append it to the result sink
+    async fn record_node_result(
+        graph: &WorkflowGraph,
+        node_result: &NodeExecutionResult,
+        state: &RunState,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) {
+        let sink_record = {
+            let mut ctx = context.lock().await;
+            let mut sink_record = None;
+            if let Some(node) = graph.get_node(&node_result.node_id) {
+                Self::store_node_output(&mut ctx, node, node_result.output.clone());
+                let record = NodeExecutionRecord::from_result(node_result, &node.name, state.step);
+                sink_record = ctx.result_sink.clone().map(|sink| {
+                    (
+                        sink,
+                        crate::result_sink::ResultRecord::for_node(&ctx, &record),
+                    )
+                });
+                ctx.record_node_execution(record);
+                crate::telemetry::node_executed(
+                    node.node_type.kind(),
+                    node_result.success,
+                    Some(std::time::Duration::from_millis(node_result.duration_ms)),
+                );
+                if let Some(debug) = &node_result.debug {
+                    ctx.record_node_debug(&node.name, debug.clone());
                 }
 
-                // Pre-warm circuit breakers for all agents
-                let _ = self.get_circuit_breaker(&agent_id).await;
+                let keys_now: Vec<String> = ctx.node_outputs.keys().cloned().collect();
+                tracing::debug!(
+                    stored_node_id = %node.id,
+                    stored_node_name = %node.name,
+                    node_output_keys_now = ?keys_now,
+                    "Stored node output in context.node_outputs"
+                );
+            } else if let Ok(output_str) = serde_json::to_string(&node_result.output) {
+                ctx.set_variable(
+                    format!("node_result_{}", state.total_executed),
+                    serde_json::Value::String(output_str),
+                );
+                tracing::debug!(
+                    executed_index = state.total_executed,
+                    "Stored output under generic variable name (node not found)"
+                );
+            }
+            sink_record
+        };
+        if let Some((sink, record)) = sink_record {
+            if let Err(e) = sink.append(&record).await {
+                tracing::error!(
+                    node_id = %node_result.node_id,
+                    "Failed to append node result to {}: {e}",
+                    sink.path().display()
+                );
+            }
+        }
+    }
+
+    /// Route the outgoing edges of a node that succeeded: disable the edges its
+    /// output, guardrail report or event timeout do not take, store the outputs
+    /// the others carry and skip the branches a condition did not choose.
+    /// Returns the last routing error, which fails the run.
+    fn route_node_output(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        node_result: &NodeExecutionResult,
+        ctx: &mut WorkflowContext,
+        state: &mut RunState,
+    ) -> Result<(), String> {
+        let mut routing = Self::evaluate_conditional_edges(
+            graph,
+            node,
+            &node_result.output,
+            ctx,
+            &mut state.disabled_edges,
+        )
+        .map_err(|e| e.to_string());
+        Self::route_violation_edges(
+            graph,
+            node,
+            node_result.guardrail.as_ref(),
+            &mut state.disabled_edges,
+        );
+        Self::route_timeout_edges(
+            graph,
+            node,
+            ctx.event_timed_out(&node.name),
+            &mut state.disabled_edges,
+        );
+        Self::disable_failure_edges(graph, node, &mut state.disabled_edges);
+        Self::store_edge_outputs(graph, node, &node_result.output, ctx, &state.disabled_edges);
+        if matches!(node.node_type, NodeType::Condition { .. }) {
+            if let Err(e) =
+                Self::skip_unchosen_branches(graph, node, &node_result.output, ctx, state)
+            {
+                routing = Err(e);
             }
         }
+        routing
+    }
 
-        // Pre-compute and store dependency map and id->name map into context metadata
-        {
-            // Build dependency map: node_id -> [parent_node_ids...]
-            let mut deps_map: HashMap<String, Vec<String>> = HashMap::new();
-            // Build id->name map for better labeling
-            let mut id_name_map: HashMap<String, String> = HashMap::new();
-
-            for (nid, node) in workflow.graph.get_nodes() {
-                id_name_map.insert(nid.to_string(), node.name.clone());
-            }
-
-            // We need a mutable graph to call get_dependencies (it caches)
-            let mut graph_clone = workflow.graph.clone();
-            for nid in id_name_map.keys() {
-                // Convert back to NodeId via from_string (deterministic for UUIDs)
-                if let Ok(node_id) = NodeId::from_string(nid) {
-                    let parents = graph_clone
-                        .get_dependencies(&node_id)
-                        .into_iter()
-                        .map(|p| p.to_string())
-                        .collect::<Vec<_>>();
-                    deps_map.insert(nid.clone(), parents);
+    /// Skip the branches of a condition node other than the one its `output`
+    /// chose: a boolean picks its true or false edge, a string names the
+    /// successor to run
+    fn skip_unchosen_branches(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        output: &serde_json::Value,
+        ctx: &mut WorkflowContext,
+        state: &mut RunState,
+    ) -> Result<(), String> {
+        let chosen_id = if let serde_json::Value::Bool(holds) = output {
+            Self::branch_target(graph, &node.id, *holds)
+        } else {
+            let Some(chosen_name) = Self::condition_output_branch_name(output) else {
+                return Err(format!(
+                    "Condition '{}': output must be a non-empty branch node name (String); got output={:?}",
+                    node.name, output
+                ));
+            };
+            match Self::resolve_condition_branch_target(graph, &node.id, &chosen_name) {
+                Ok(chosen_id) => Some(chosen_id),
+                Err(e) => {
+                    return Err(format!(
+                        "Condition '{}': handler chose branch {:?} but it does not match exactly one direct successor node name: {}",
+                        node.name, chosen_name, e
+                    ));
                 }
             }
+        };
+        Self::expand_skips_from_condition(
+            graph,
+            &node.id,
+            chosen_id.as_ref(),
+            &state.resolved,
+            &mut state.skipped,
+            ctx,
+        );
+        Ok(())
+    }
 
-            context.set_metadata(
-                "node_dependencies".to_string(),
-                serde_json::to_value(deps_map).unwrap_or(serde_json::json!({})),
-            );
-            context.set_metadata(
-                "node_id_to_name".to_string(),
-                serde_json::to_value(id_name_map).unwrap_or(serde_json::json!({})),
-            );
-        }
+    /// Whether a node error looks like an authentication or configuration
+    /// problem, which no other node would get past
+    fn is_auth_error(error: &GraphBitError) -> bool {
+        let error_msg = error.to_string().to_lowercase();
+        error_msg.contains("auth")
+            || error_msg.contains("key")
+            || error_msg.contains("invalid")
+            || error_msg.contains("unauthorized")
+            || error_msg.contains("permission")
+            || error_msg.contains("api error")
+    }
 
-        let nodes = Self::collect_executable_nodes(&workflow.graph)?;
-        if nodes.is_empty() {
-            context.complete();
-            // Streaming: emit WorkflowCompleted even for an empty workflow
-            if let Some(ref tx) = event_tx {
-                let _ = tx
-                    .send(StreamEvent::WorkflowCompleted {
-                        context: context.clone(),
-                    })
-                    .await;
-            }
-            return Ok(context);
+    /// Merge a node of `batch` whose task failed before producing a result.
+    /// When `stops_run`, the error fails the run unless compensation nodes
+    /// are already running.
+    async fn merge_task_failure(
+        graph: &WorkflowGraph,
+        node_id: NodeId,
+        error: String,
+        stops_run: bool,
+        state: &mut RunState,
+        batch: &mut Batch,
+    ) {
+        if stops_run && state.compensating.is_none() {
+            batch.fail(Some(node_id), error);
+            return;
         }
+        state.total_executed += 1;
+        Self::record_task_failure(&batch.context, graph, &node_id, error, state.step).await;
+        state.resolved.insert(node_id.clone());
+        state.failed.push(node_id);
+    }
 
-        // ── Streaming: emit WorkflowStarted ──────────────────────────────────────
-        if let Some(ref tx) = event_tx {
-            let _ = tx
-                .send(StreamEvent::WorkflowStarted {
-                    workflow_id: workflow.id.to_string(),
-                    workflow_name: workflow.name.clone(),
-                    total_nodes: nodes.len(),
-                })
-                .await;
+    /// Once the budget ran out, the cancel policy stops the running nodes of
+    /// `batch` that are not exempt from it
+    fn cancel_over_budget_nodes(plan: &RunPlan, state: &mut RunState, batch: &mut Batch) {
+        let Some(budget) = plan
+            .budget
+            .as_ref()
+            .filter(|b| b.policy() == BudgetPolicy::Cancel && b.is_exhausted())
+        else {
+            return;
+        };
+        let graph = &plan.runtime.workflow_graph;
+        let stopped: Vec<NodeId> = batch
+            .running
+            .keys()
+            .filter(|node_id| {
+                !batch.abandoned.contains_key(*node_id)
+                    && graph
+                        .get_node(node_id)
+                        .is_some_and(|node| !node.is_budget_exempt())
+            })
+            .cloned()
+            .collect();
+        for node_id in stopped {
+            if let Some(task) = batch.running.get(&node_id) {
+                task.abort();
+            }
+            tracing::info!(%node_id, "{}; cancelling node", budget.exhausted_message());
+            state.skipped.insert(node_id.clone());
+            batch.abandoned.insert(node_id, AbandonedNode::Cancelled);
         }
+    }
 
-        let mut total_executed = 0;
-        let mut total_successful = 0;
-
-        // Parent map from the canonical `edges` list (same source Python `connect` uses).
-        // Using `get_dependencies` + petgraph/cache here has regressed to empty dependency lists
-        // for some graphs, which makes `parents.iter().all(...)` vacuously true and schedules
-        // condition successors (e.g. all advisor branches) in the same batch as the intake node.
-        let node_ids: Vec<NodeId> = workflow.graph.get_nodes().keys().cloned().collect();
-        let mut node_parents: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
-        for nid in &node_ids {
-            let deps: Vec<NodeId> = workflow
-                .graph
-                .get_edges()
-                .iter()
-                .filter_map(|(from, to, _)| (to == nid).then_some(from.clone()))
-                .collect();
-            node_parents.insert(nid.clone(), deps);
-        }
-        let node_parents = Arc::new(node_parents);
-        let conditional_handlers = self.conditional_handlers.clone();
-        let workflow_graph = Arc::new(workflow.graph.clone());
-
-        let total_node_count = workflow.graph.node_count();
-        let mut resolved: HashSet<NodeId> = HashSet::new();
-        let mut skipped: HashSet<NodeId> = HashSet::new();
-        // Streaming-only coordination: nodes that emitted `tool_calls_required` are treated as
-        // pending until the Python streaming layer resolves them and performs a downstream rerun.
-        let mut pending_tool_resolution: HashSet<NodeId> = HashSet::new();
-
-        while resolved.len() + skipped.len() < total_node_count {
-            let ready: Vec<WorkflowNode> = workflow
-                .graph
-                .get_nodes()
-                .iter()
-                .filter(|(id, _)| {
-                    !resolved.contains(*id)
-                        && !skipped.contains(*id)
-                        && !pending_tool_resolution.contains(*id)
-                })
-                .filter(|(id, _)| {
-                    node_parents
-                        .get(*id)
-                        .map(|parents| {
-                            parents
-                                .iter()
-                                .all(|p| resolved.contains(p) || skipped.contains(p))
-                        })
-                        .unwrap_or(false)
-                })
-                .map(|(_, n)| n.clone())
-                .collect();
-
-            if ready.is_empty() {
-                if event_tx.is_some() && !pending_tool_resolution.is_empty() {
-                    let blocked_node_ids: Vec<String> = workflow
-                        .graph
-                        .get_nodes()
-                        .iter()
-                        .filter(|(id, _)| {
-                            !resolved.contains(*id)
-                                && !skipped.contains(*id)
-                                && !pending_tool_resolution.contains(*id)
-                        })
-                        .map(|(id, _)| id.to_string())
-                        .collect();
-                    tracing::info!(
-                        blocked_node_ids = ?blocked_node_ids,
-                        "Deferring downstream nodes blocked on unresolved tool calls"
-                    );
-                    break;
-                }
-                let err_msg = "No runnable nodes but workflow not finished (cycle or invalid scheduling state)".to_string();
-                if let Some(ref tx) = event_tx {
-                    let _ = tx
-                        .send(StreamEvent::WorkflowFailed {
-                            error: err_msg.clone(),
-                            error_type: "runtime_error".to_string(),
-                        })
-                        .await;
-                }
-                return Err(GraphBitError::workflow_execution(err_msg));
+    /// A join whose policy is now met stops waiting for its other parents
+    fn stop_waiting_for_branches(plan: &RunPlan, state: &mut RunState, batch: &mut Batch) {
+        let graph = &plan.runtime.workflow_graph;
+        for (join_id, required, pending) in &plan.early_joins {
+            if state.is_done(join_id) {
+                continue;
             }
-
-            let batch_size = ready.len();
-            let batch_ids: Vec<String> = ready.iter().map(|n| n.id.to_string()).collect();
-            tracing::info!(batch_size, batch_node_ids = ?batch_ids, "Executing dynamic batch");
-
-            // ── Streaming: emit NodeStarted for each node about to run ────────────
-            if let Some(ref tx) = event_tx {
-                for node in &ready {
-                    let _ = tx
-                        .send(StreamEvent::NodeStarted {
-                            node_id: node.id.to_string(),
-                            node_name: node.name.clone(),
-                        })
-                        .await;
-                }
+            let parents = plan
+                .runtime
+                .node_parents
+                .get(join_id)
+                .map_or(&[][..], Vec::as_slice);
+            if parents
+                .iter()
+                .filter(|p| state.succeeded.contains(*p))
+                .count()
+                < *required
+            {
+                continue;
             }
-
-            let shared_context = Arc::new(Mutex::new(context));
-            let mut tasks = Vec::with_capacity(batch_size);
-
-            for node in ready {
-                let context_clone = shared_context.clone();
-                let agents_clone = self.agents.clone();
-                let circuit_breakers_clone = self.circuit_breakers.clone();
-                let circuit_breaker_config = self.circuit_breaker_config.clone();
-                let retry_config = self.default_retry_config.clone();
-                let concurrency_manager = self.concurrency_manager.clone();
-                let guardrail_enforcer = guardrail_enforcer.clone();
-                let node_parents = node_parents.clone();
-                let conditional_handlers = conditional_handlers.clone();
-                let workflow_graph = workflow_graph.clone();
-                // Clone the event channel sender for this task (cheap Arc clone inside Sender)
-                let task_event_tx = event_tx.clone();
-                let task_stream_mode = stream_mode;
-
-                let task = tokio::spawn(async move {
-                    let task_info = TaskInfo::from_node_type(&node.node_type, &node.id);
-
-                    let _permits = if matches!(node.node_type, NodeType::Agent { .. }) {
-                        Some(
-                            concurrency_manager
-                                .acquire_permits(&task_info)
-                                .await
-                                .map_err(|e| {
-                                    GraphBitError::workflow_execution(format!(
-                                        "Failed to acquire permits for node {}: {e}",
-                                        node.id
-                                    ))
-                                })?,
-                        )
-                    } else {
-                        None
-                    };
-
-                    Self::execute_node_with_retry(
-                        node,
-                        context_clone,
-                        agents_clone,
-                        circuit_breakers_clone,
-                        circuit_breaker_config,
-                        retry_config,
-                        guardrail_enforcer,
-                        node_parents,
-                        conditional_handlers,
-                        workflow_graph,
-                        task_event_tx,
-                        task_stream_mode,
-                    )
-                    .await
-                });
-                tasks.push(task);
+            for node_id in
+                Self::pending_join_branches(graph, join_id, &state.resolved, &state.skipped)
+            {
+                let abandoned = match batch.running.get(&node_id) {
+                    None => AbandonedNode::Skipped {
+                        reason: SkipReason::Join,
+                    },
+                    Some(_) if *pending == PendingBranches::Detach => {
+                        batch.running.remove(&node_id);
+                        AbandonedNode::Detached
+                    }
+                    Some(task) => {
+                        task.abort();
+                        AbandonedNode::Cancelled
+                    }
+                };
+                state.skipped.insert(node_id.clone());
+                batch.abandoned.insert(node_id, abandoned);
             }
+        }
+    }
 
-            let results = join_all(tasks).await;
+    /// Fail a run with the failure that stopped it, or with the failure whose
+    /// compensation nodes were running
+    async fn fail_run(
+        &self,
+        mut context: WorkflowContext,
+        plan: &RunPlan,
+        state: &mut RunState,
+        failure: RunFailure,
+    ) -> WorkflowContext {
+        // The original failure outlives errors of its compensation nodes
+        let failure_message = state.compensating.take().unwrap_or(failure.message);
+        let run_stats = self.execution_stats(&context, plan, state).await;
+        context.set_stats(run_stats);
+        context.fail(failure_message.clone());
+        context.redact_secrets();
+        let failure_message = plan.secrets.redact(&failure_message);
+
+        // ── Streaming: emit WorkflowFailed on fail-fast ──────────────────
+        Self::report_run_failure(plan.runtime.event_tx.as_ref(), failure_message).await;
+        context
+    }
 
-            let mut should_fail_fast = false;
-            let mut failure_message = String::new();
+    /// Finish a run whose nodes all settled, or that the executor shut down
+    async fn finish_run(
+        &self,
+        mut context: WorkflowContext,
+        plan: &RunPlan,
+        mut state: RunState,
+        graph: &WorkflowGraph,
+    ) -> WorkflowContext {
+        let event_tx = plan.runtime.event_tx.as_ref();
+        if state.shut_down {
+            return self.cancel_run(context, plan, state, graph).await;
+        }
 
-            for task_result in results {
-                match task_result {
-                    Ok(Ok(node_result)) => {
-                        total_executed += 1;
-                        if node_result.success {
-                            total_successful += 1;
-                        }
-                        let node_requires_tool_resolution = event_tx.is_some()
-                            && Self::is_tool_calls_required_output(&node_result.output);
-                        if node_requires_tool_resolution {
-                            pending_tool_resolution.insert(node_result.node_id.clone());
-                        } else {
-                            resolved.insert(node_result.node_id.clone());
-                        }
+        // Set execution statistics
+        let run_stats = self.execution_stats(&context, plan, &state).await;
+        context.set_stats(run_stats);
+
+        // A fail-fast run that ran compensation nodes reports the original failure
+        if let Some(failure_message) = state.compensating.take() {
+            context.fail(failure_message.clone());
+            context.redact_secrets();
+            let failure_message = plan.secrets.redact(&failure_message);
+            Self::report_run_failure(event_tx, failure_message).await;
+            return context;
+        }
 
-                        // ── Streaming: emit NodeCompleted or NodeFailed ───────────────────
-                        if let Some(ref tx) = event_tx {
-                            let node_name = workflow
-                                .graph
-                                .get_node(&node_result.node_id)
-                                .map(|n| n.name.clone())
-                                .unwrap_or_default();
-
-                            if node_result.success {
-                                let _ = tx
-                                    .send(StreamEvent::NodeCompleted {
-                                        node_id: node_result.node_id.to_string(),
-                                        node_name,
-                                        output: node_result.output.clone(),
-                                    })
-                                    .await;
-                            } else {
-                                let error_msg = node_result
-                                    .error
-                                    .as_deref()
-                                    .unwrap_or("Unknown error")
-                                    .to_string();
-                                let _ = tx
-                                    .send(StreamEvent::NodeFailed {
-                                        node_id: node_result.node_id.to_string(),
-                                        node_name,
-                                        error: error_msg.clone(),
-                                        error_type: error_type_from_string(&error_msg),
-                                    })
-                                    .await;
-                            }
-                        }
+        if let Some(budget) = plan.budget.as_ref().filter(|b| b.is_exhausted()) {
+            let failure_message = budget.exhausted_message();
+            context.fail_with_reason(failure_message.clone(), budget.failure_reason());
+            context.redact_secrets();
+            Self::report_run_failure(event_tx, failure_message).await;
+            return context;
+        }
 
-                        {
-                            let mut ctx = shared_context.lock().await;
-                            if let Some(node) = workflow.graph.get_node(&node_result.node_id) {
-                                ctx.set_node_output(&node.id, node_result.output.clone());
-                                ctx.set_node_output_by_name(&node.name, node_result.output.clone());
-
-                                let keys_now: Vec<String> =
-                                    ctx.node_outputs.keys().cloned().collect();
-                                tracing::debug!(
-                                    stored_node_id = %node.id,
-                                    stored_node_name = %node.name,
-                                    node_output_keys_now = ?keys_now,
-                                    "Stored node output in context.node_outputs"
-                                );
-
-                                if let Ok(output_str) = serde_json::to_string(&node_result.output) {
-                                    ctx.set_variable(
-                                        node.name.clone(),
-                                        serde_json::Value::String(output_str.clone()),
-                                    );
-                                    ctx.set_variable(
-                                        node.id.to_string(),
-                                        serde_json::Value::String(output_str),
-                                    );
-                                }
-                            } else if let Ok(output_str) =
-                                serde_json::to_string(&node_result.output)
-                            {
-                                ctx.set_variable(
-                                    format!("node_result_{total_executed}"),
-                                    serde_json::Value::String(output_str),
-                                );
-                                tracing::debug!(
-                                    executed_index = total_executed,
-                                    "Stored output under generic variable name (node not found)"
-                                );
-                            }
-                        }
+        if state.failed.is_empty() {
+            context.complete();
+        } else {
+            let failed_nodes = state
+                .failed
+                .iter()
+                .map(|id| {
+                    graph
+                        .get_node(id)
+                        .map_or_else(|| id.to_string(), |node| node.name.clone())
+                })
+                .collect();
+            context.partially_complete(failed_nodes);
+        }
+        context.redact_secrets();
 
-                        if node_result.success {
-                            if let Some(node) = workflow.graph.get_node(&node_result.node_id) {
-                                if matches!(node.node_type, NodeType::Condition { .. }) {
-                                    match Self::condition_output_branch_name(&node_result.output) {
-                                        Some(chosen_name) => {
-                                            match Self::resolve_condition_branch_target(
-                                                workflow_graph.as_ref(),
-                                                &node.id,
-                                                &chosen_name,
-                                            ) {
-                                                Ok(chosen_id) => {
-                                                    Self::expand_skips_from_condition(
-                                                        workflow_graph.as_ref(),
-                                                        &node.id,
-                                                        &chosen_id,
-                                                        &resolved,
-                                                        &mut skipped,
-                                                    );
-                                                }
-                                                Err(e) => {
-                                                    should_fail_fast = true;
-                                                    failure_message = format!(
-                                                        "Condition '{}': handler chose branch {:?} but it does not match exactly one direct successor node name: {}",
-                                                        node.name, chosen_name, e
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            should_fail_fast = true;
-                                            failure_message = format!(
-                                                "Condition '{}': output must be a non-empty branch node name (String); got output={:?}",
-                                                node.name, node_result.output
-                                            );
-                                        }
-                                    }
-                                }
-                            } else {
-                                tracing::warn!(
-                                    node_id = %node_result.node_id,
-                                    "Workflow batch merge: executed node id not found in graph"
-                                );
-                                should_fail_fast = true;
-                                failure_message = format!(
-                                    "Workflow batch merge: executed node id {} not found in graph",
-                                    node_result.node_id
-                                );
-                            }
-                        } else if let Some(node) = workflow.graph.get_node(&node_result.node_id) {
-                            // A failed condition leaves every successor "ready" (parent resolved) but
-                            // never runs expand_skips_from_condition, so all branches would execute.
-                            // Treat condition failure like an unrecoverable routing error.
-                            if matches!(node.node_type, NodeType::Condition { .. }) {
-                                should_fail_fast = true;
-                                failure_message = node_result.error.clone().unwrap_or_else(|| {
-                                    format!(
-                                        "Condition '{}' failed (no branch chosen; successors were not skipped)",
-                                        node.name
-                                    )
-                                });
-                            }
-                        }
+        // ── Streaming: emit WorkflowCompleted ────────────────────────────────────
+        Self::report_run_completed(event_tx, &context).await;
+        context
+    }
 
-                        if should_fail_fast {
-                            break;
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        let error_msg = e.to_string().to_lowercase();
-                        let is_auth_error = error_msg.contains("auth")
-                            || error_msg.contains("key")
-                            || error_msg.contains("invalid")
-                            || error_msg.contains("unauthorized")
-                            || error_msg.contains("permission")
-                            || error_msg.contains("api error");
-
-                        if is_auth_error || self.fail_fast {
-                            should_fail_fast = true;
-                            failure_message = e.to_string();
-                            break;
-                        }
-                        total_executed += 1;
-                    }
-                    Err(e) => {
-                        if self.fail_fast {
-                            should_fail_fast = true;
-                            failure_message = format!("Task execution failed: {e}");
-                            break;
-                        }
-                        total_executed += 1;
-                    }
-                }
+    /// Cancel a run the executor shut down; the nodes that had not started
+    /// never will
+    async fn cancel_run(
+        &self,
+        mut context: WorkflowContext,
+        plan: &RunPlan,
+        mut state: RunState,
+        graph: &WorkflowGraph,
+    ) -> WorkflowContext {
+        for node in graph.get_nodes().values() {
+            if !state.resolved.contains(&node.id) && state.skipped.insert(node.id.clone()) {
+                context.abandon_node(
+                    &node.id,
+                    &node.name,
+                    AbandonedNode::Skipped {
+                        reason: SkipReason::Shutdown,
+                    },
+                );
             }
+        }
+        let run_stats = self.execution_stats(&context, plan, &state).await;
+        context.set_stats(run_stats);
+        context.cancel();
+        context.redact_secrets();
+        tracing::info!(
+            execution_id = %context.execution_id,
+            "Execution cancelled by the executor shutting down"
+        );
+        Self::report_run_completed(plan.runtime.event_tx.as_ref(), &context).await;
+        context
+    }
 
-            if should_fail_fast {
-                let mut ctx = shared_context.lock().await;
-                ctx.fail(failure_message.clone());
-                drop(ctx);
-                let final_ctx = Arc::try_unwrap(shared_context).unwrap().into_inner();
+    /// Emit `WorkflowFailed` for a run that failed before its first batch
+    async fn report_run_error(
+        event_tx: Option<&tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        error: &GraphBitError,
+    ) {
+        if let Some(tx) = event_tx {
+            let _ = tx
+                .send(crate::stream::StreamEvent::WorkflowFailed {
+                    error: error.to_string(),
+                    error_type: crate::stream::error_type_from_graphbit_error(error),
+                })
+                .await;
+        }
+    }
 
-                // ── Streaming: emit WorkflowFailed on fail-fast ──────────────────
-                if let Some(ref tx) = event_tx {
-                    let error_type = error_type_from_string(&failure_message);
-                    let _ = tx
-                        .send(StreamEvent::WorkflowFailed {
-                            error: failure_message,
-                            error_type,
-                        })
-                        .await;
-                }
+    /// Emit `WorkflowStarted` for a run of `total_nodes` nodes
+    async fn report_run_started(
+        event_tx: Option<&tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        workflow: &Workflow,
+        total_nodes: usize,
+    ) {
+        if let Some(tx) = event_tx {
+            let _ = tx
+                .send(crate::stream::StreamEvent::WorkflowStarted {
+                    workflow_id: workflow.id.to_string(),
+                    workflow_name: workflow.name.clone(),
+                    total_nodes,
+                })
+                .await;
+        }
+    }
 
-                return Ok(final_ctx);
-            }
+    /// Emit `WorkflowFailed` for a run that failed with `failure_message`
+    async fn report_run_failure(
+        event_tx: Option<&tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        failure_message: String,
+    ) {
+        if let Some(tx) = event_tx {
+            let error_type = crate::stream::error_type_from_string(&failure_message);
+            let _ = tx
+                .send(crate::stream::StreamEvent::WorkflowFailed {
+                    error: failure_message,
+                    error_type,
+                })
+                .await;
+        }
+    }
 
-            context = Arc::try_unwrap(shared_context).unwrap().into_inner();
+    /// Emit `WorkflowCompleted` for a run that finished
+    async fn report_run_completed(
+        event_tx: Option<&tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
+        context: &WorkflowContext,
+    ) {
+        if let Some(tx) = event_tx {
+            let _ = tx
+                .send(crate::stream::StreamEvent::WorkflowCompleted {
+                    context: context.clone(),
+                })
+                .await;
         }
+    }
 
-        // Set execution statistics
-        let total_time = start_time.elapsed();
-        let stats = WorkflowExecutionStats {
+    /// Statistics for a run following `plan` that got to `state`
+    async fn execution_stats(
+        &self,
+        context: &WorkflowContext,
+        plan: &RunPlan,
+        state: &RunState,
+    ) -> WorkflowExecutionStats {
+        let total_time = plan.start_time.elapsed();
+        let (total_executed, total_successful) = (state.total_executed, state.total_successful);
+        let (total_tool_calls, failed_tool_calls) = context.tool_call_counts();
+        let (spilled_outputs, spilled_bytes) = context.spilled_output_totals();
+        // Nodes stopped while running are in `skipped` too, but did start
+        let stopped = context
+            .abandoned_nodes
+            .values()
+            .filter(|state| matches!(state, AbandonedNode::Cancelled | AbandonedNode::Detached))
+            .count();
+        WorkflowExecutionStats {
             total_nodes: total_executed,
             successful_nodes: total_successful,
             failed_nodes: total_executed - total_successful,
+            skipped_nodes: state.skipped.len().saturating_sub(stopped),
             avg_execution_time_ms: total_time.as_millis() as f64 / total_executed.max(1) as f64,
             max_concurrent_nodes: self.max_concurrency().await,
             total_execution_time_ms: total_time.as_millis() as u64,
             peak_memory_usage_mb: None, // Could add memory tracking here
             semaphore_acquisitions: 0,  // Updated in the loop
             avg_semaphore_wait_ms: 0.0, // Updated in the loop
-        };
+            total_tool_calls,
+            failed_tool_calls,
+            spilled_outputs,
+            spilled_bytes,
+            usage: plan.attribution.breakdown(context),
+        }
+    }
 
-        context.set_stats(stats);
-        context.complete();
+    /// Report a change of state of the circuit breaker of an agent node
+    fn report_breaker_transition(
+        node: &WorkflowNode,
+        before: &CircuitBreakerState,
+        breaker: &CircuitBreaker,
+    ) {
+        if let Some(config) = node.node_type.agent_config() {
+            crate::telemetry::breaker_transition(&config.agent_id, before, &breaker.state);
+        }
+    }
 
-        // ── Streaming: emit WorkflowCompleted ────────────────────────────────────
-        if let Some(ref tx) = event_tx {
-            let _ = tx
-                .send(StreamEvent::WorkflowCompleted {
-                    context: context.clone(),
-                })
-                .await;
+    /// Record a node whose task failed before producing a result
+    async fn record_task_failure(
+        shared_context: &Arc<Mutex<WorkflowContext>>,
+        graph: &WorkflowGraph,
+        node_id: &NodeId,
+        error: String,
+        step: usize,
+    ) {
+        let node = graph.get_node(node_id);
+        let name = node.map_or_else(|| node_id.to_string(), |node| node.name.clone());
+        if let Some(node) = node {
+            crate::telemetry::node_executed(node.node_type.kind(), false, None);
+        }
+        let mut ctx = shared_context.lock().await;
+        if let Some(node) = node {
+            Self::store_failure_edge_outputs(graph, node, &error, &mut ctx);
+        }
+        let result = NodeExecutionResult::failure(error, node_id.clone());
+        ctx.record_node_execution(NodeExecutionRecord::from_result(&result, &name, step));
+    }
+
+    /// Take the context back from the tasks of a batch and record the nodes joins
+    /// or the budget abandoned. Detached nodes may still hold the shared context; they keep
+    /// writing to a copy the workflow no longer reads.
+    async fn take_batch_context(
+        shared_context: Arc<Mutex<WorkflowContext>>,
+        graph: &WorkflowGraph,
+        abandoned: HashMap<NodeId, AbandonedNode>,
+    ) -> WorkflowContext {
+        let mut context = match Arc::try_unwrap(shared_context) {
+            Ok(context) => context.into_inner(),
+            Err(shared_context) => shared_context.lock().await.clone(),
+        };
+        for (node_id, state) in abandoned {
+            let name = graph
+                .get_node(&node_id)
+                .map_or_else(|| node_id.to_string(), |node| node.name.clone());
+            context.abandon_node(&node_id, &name, state);
         }
+        context
+    }
 
-        Ok(context)
+    /// Unfinished nodes whose every path leads into the join `join_id`: the
+    /// parent branches it stops waiting for once its policy is met
+    fn pending_join_branches(
+        graph: &WorkflowGraph,
+        join_id: &NodeId,
+        resolved: &HashSet<NodeId>,
+        skipped: &HashSet<NodeId>,
+    ) -> Vec<NodeId> {
+        let mut successors: HashMap<&NodeId, Vec<&NodeId>> = HashMap::new();
+        for (from, to, _) in graph.get_edges() {
+            successors.entry(from).or_default().push(to);
+        }
+        let mut branches: HashSet<NodeId> = HashSet::new();
+        loop {
+            let found = successors
+                .iter()
+                .filter(|(node_id, next)| {
+                    **node_id != join_id
+                        && !resolved.contains(*node_id)
+                        && !skipped.contains(*node_id)
+                        && !branches.contains(*node_id)
+                        && next.iter().all(|n| *n == join_id || branches.contains(*n))
+                })
+                .map(|(node_id, _)| (*node_id).clone())
+                .collect::<Vec<_>>();
+            if found.is_empty() {
+                break;
+            }
+            branches.extend(found);
+        }
+        branches.into_iter().collect()
     }
 
     /// Validates `chosen_name` matches exactly one direct successor of the condition node.
@@ -1127,27 +3604,59 @@ impl WorkflowExecutor {
         }
     }
 
-    /// Mark nodes on non-chosen branches as skipped (diamond-join safe using `R_chosen`).
+    /// Mark nodes on non-chosen branches as skipped (diamond-join safe using `R_chosen`),
+    /// recording them on the context. Without a chosen branch every branch is skipped.
     fn expand_skips_from_condition(
         graph: &WorkflowGraph,
         condition_id: &NodeId,
-        chosen_id: &NodeId,
+        chosen_id: Option<&NodeId>,
         resolved: &HashSet<NodeId>,
         skipped: &mut HashSet<NodeId>,
+        context: &mut WorkflowContext,
     ) {
-        let r_chosen = graph.forward_reachable_from(chosen_id);
+        let r_chosen = chosen_id
+            .map(|chosen_id| graph.forward_reachable_from(chosen_id))
+            .unwrap_or_default();
         for bad_id in graph.direct_successors(condition_id) {
-            if &bad_id == chosen_id {
+            if Some(&bad_id) == chosen_id {
                 continue;
             }
             for v in graph.forward_reachable_from(&bad_id) {
-                if !r_chosen.contains(&v) && !resolved.contains(&v) {
-                    skipped.insert(v);
+                if !r_chosen.contains(&v) && !resolved.contains(&v) && skipped.insert(v.clone()) {
+                    if let Some(node) = graph.get_node(&v) {
+                        context.abandon_node(
+                            &v,
+                            &node.name,
+                            AbandonedNode::Skipped {
+                                reason: SkipReason::Condition,
+                            },
+                        );
+                    }
                 }
             }
         }
     }
 
+    /// Whether the condition node `condition_id` routes along true/false edges
+    fn has_branch_edges(graph: &WorkflowGraph, condition_id: &NodeId) -> bool {
+        graph.get_edges().iter().any(|(from, _, edge)| {
+            from == condition_id && matches!(edge.edge_type, EdgeType::Branch(_))
+        })
+    }
+
+    /// Target of the edge a condition node follows when its expression
+    /// evaluated to `holds`, if it has one
+    fn branch_target(graph: &WorkflowGraph, condition_id: &NodeId, holds: bool) -> Option<NodeId> {
+        graph
+            .get_edges()
+            .iter()
+            .find(|(from, _, edge)| {
+                from == condition_id
+                    && matches!(edge.edge_type, EdgeType::Branch(label) if label == holds)
+            })
+            .map(|(_, to, _)| to.clone())
+    }
+
     /// Stringify a parent node's output for `Condition` handler input.
     /// Extract the next-node name from a condition node's stored output value.
     fn condition_output_branch_name(output: &serde_json::Value) -> Option<String> {
@@ -1171,6 +3680,7 @@ impl WorkflowExecutor {
     async fn execute_condition_node(
         node: &WorkflowNode,
         handler_id: &str,
+        expression: Option<&str>,
         context: Arc<Mutex<WorkflowContext>>,
         parents_map: Arc<HashMap<NodeId, Vec<NodeId>>>,
         handlers: Arc<HashMap<String, ConditionalRouteFn>>,
@@ -1188,27 +3698,44 @@ impl WorkflowExecutor {
         let parent_key = parent_id.to_string();
         let routing_input = {
             let ctx = context.lock().await;
-            let parent_output = ctx.get_node_output(&parent_key).ok_or_else(|| {
+            let parent_output = ctx.load_node_output(&parent_key).ok_or_else(|| {
                 GraphBitError::workflow_execution(format!(
                     "Condition node '{}': parent output not found for {}",
                     node.name, parent_key
                 ))
             })?;
+            let parent_output = Self::apply_edge_transform(
+                &graph,
+                &ctx,
+                &parent_id,
+                &node.id,
+                parent_output.into_owned(),
+            )?;
             ConditionRoutingInput {
                 parent_node_id: parent_key.clone(),
-                parent_output: parent_output.clone(),
+                parent_output,
                 variables: ctx.variables.clone(),
-                node_outputs: ctx.node_outputs.clone(),
+                node_outputs: ctx.load_node_outputs(),
                 metadata: ctx.metadata.clone(),
             }
         };
-        let handler = handlers.get(handler_id).ok_or_else(|| {
-            GraphBitError::workflow_execution(format!(
-                "Condition node '{}': no handler registered for handler_id '{handler_id}'",
-                node.name
-            ))
-        })?;
-        let out = handler(routing_input)?;
+        let out = if let Some(expression) = expression {
+            let outcome =
+                Self::evaluate_condition_expression(node, expression, routing_input, &graph)?;
+            if outcome.is_boolean() {
+                // Routed along the true/false edges once the node settles
+                return Ok(outcome);
+            }
+            outcome.as_str().unwrap_or_default().to_string()
+        } else {
+            let handler = handlers.get(handler_id).ok_or_else(|| {
+                GraphBitError::workflow_execution(format!(
+                    "Condition node '{}': no handler registered for handler_id '{handler_id}'",
+                    node.name
+                ))
+            })?;
+            handler(routing_input)?
+        };
         let trimmed = out.trim().to_string();
         if trimmed.is_empty() {
             return Err(GraphBitError::workflow_execution(format!(
@@ -1220,6 +3747,55 @@ impl WorkflowExecutor {
         Ok(serde_json::Value::String(trimmed))
     }
 
+    /// Pick a condition node's branch with its expression: a string result is
+    /// the branch name. A boolean result is returned as is when the node has
+    /// true/false edges; otherwise `true` selects the first connected successor
+    /// and `false` the second.
+    fn evaluate_condition_expression(
+        node: &WorkflowNode,
+        expression: &str,
+        routing_input: ConditionRoutingInput,
+        graph: &WorkflowGraph,
+    ) -> GraphBitResult<serde_json::Value> {
+        let scope = ExpressionScope::new(routing_input.parent_output)
+            .with_nodes(routing_input.node_outputs)
+            .with_variables(routing_input.variables);
+        let result = Expression::parse(expression)
+            .and_then(|expression| expression.evaluate(&scope))
+            .map_err(|e| {
+                GraphBitError::workflow_execution(format!(
+                    "Condition node '{}': expression failed: {e}",
+                    node.name
+                ))
+            })?;
+
+        match result {
+            serde_json::Value::String(_) => Ok(result),
+            serde_json::Value::Bool(_) if Self::has_branch_edges(graph, &node.id) => Ok(result),
+            serde_json::Value::Bool(holds) => {
+                let successors = graph.direct_successors(&node.id);
+                if successors.len() != 2 {
+                    return Err(GraphBitError::workflow_execution(format!(
+                        "Condition node '{}': a boolean expression needs exactly two successors \
+                         (true branch first, false branch second), found {}",
+                        node.name,
+                        successors.len()
+                    )));
+                }
+                let chosen = &successors[usize::from(!holds)];
+                Ok(serde_json::Value::String(
+                    graph
+                        .get_node(chosen)
+                        .map_or_else(|| chosen.to_string(), |n| n.name.clone()),
+                ))
+            }
+            other => Err(GraphBitError::workflow_execution(format!(
+                "Condition node '{}': expression must produce a branch name or a boolean, got {other}",
+                node.name
+            ))),
+        }
+    }
+
     fn is_tool_calls_required_output(value: &serde_json::Value) -> bool {
         if let Some(obj) = value.as_object() {
             if let Some(ty) = obj.get("type").and_then(|v| v.as_str()) {
@@ -1234,109 +3810,71 @@ impl WorkflowExecutor {
         false
     }
 
+    /// Final output the context already holds for `node`, if any
+    async fn resolved_node_output(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> Option<serde_json::Value> {
+        let existing_output = context
+            .lock()
+            .await
+            .node_outputs
+            .get_shared(&node.id.to_string())?;
+        if Self::is_tool_calls_required_output(&existing_output) {
+            return None;
+        }
+        tracing::debug!(node_id = %node.id, node_name = %node.name, "Skipping already-resolved node execution");
+        Some(Arc::try_unwrap(existing_output).unwrap_or_else(|shared| (*shared).clone()))
+    }
+
     /// Execute a node with retry logic and circuit breaker
     async fn execute_node_with_retry(
         node: WorkflowNode,
         context: Arc<Mutex<WorkflowContext>>,
-        agents: Arc<RwLock<HashMap<crate::types::AgentId, Arc<dyn AgentTrait>>>>,
-        circuit_breakers: Arc<RwLock<HashMap<crate::types::AgentId, CircuitBreaker>>>,
-        circuit_breaker_config: CircuitBreakerConfig,
+        runtime: Arc<NodeRuntime>,
         retry_config: Option<RetryConfig>,
-        guardrail_enforcer: Option<Arc<Enforcer>>,
-        node_parents: Arc<HashMap<NodeId, Vec<NodeId>>>,
-        conditional_handlers: Arc<HashMap<String, ConditionalRouteFn>>,
-        workflow_graph: Arc<WorkflowGraph>,
-        event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
-        stream_mode: crate::stream::StreamMode,
     ) -> GraphBitResult<NodeExecutionResult> {
-        let start_time = std::time::Instant::now();
+        let start_time = crate::clock::instant();
+        let elapsed = || crate::clock::instant().saturating_duration_since(start_time);
         let mut attempt = 0;
 
         // Get circuit breaker for agent nodes
-        let mut circuit_breaker = if let NodeType::Agent { config } = &node.node_type {
-            let agent_id = &config.agent_id;
-            let mut breakers = circuit_breakers.write().await;
-            Some(
-                breakers
-                    .entry(agent_id.clone())
-                    .or_insert_with(|| CircuitBreaker::new(circuit_breaker_config.clone()))
-                    .clone(),
-            )
-        } else {
-            None
-        };
-
-        // Check whether this node already has a final resolved output in the provided context.
-        // This avoids rerunning unchanged resolved nodes during the downstream rerun pass.
-        if let Some(existing_output) = {
-            let ctx = context.lock().await;
-            ctx.get_node_output(&node.id.to_string()).cloned()
-        } {
-            if !Self::is_tool_calls_required_output(&existing_output) {
-                tracing::debug!(node_id = %node.id, node_name = %node.name, "Skipping already-resolved node execution");
-                return Ok(NodeExecutionResult::success(existing_output, node.id.clone())
-                    .with_duration(start_time.elapsed().as_millis() as u64)
-                    .with_retry_count(attempt));
-            }
+        let mut circuit_breaker = Self::node_circuit_breaker(&node, &runtime).await;
+
+        // Avoid rerunning unchanged resolved nodes during the downstream rerun pass
+        if let Some(existing_output) = Self::resolved_node_output(&node, &context).await {
+            return Ok(
+                NodeExecutionResult::success(existing_output, node.id.clone())
+                    .with_duration(elapsed().as_millis() as u64)
+                    .with_retry_count(attempt),
+            );
         }
 
+        // Shared by every attempt, so retried side effects can be deduplicated
+        let idempotency_key = context.lock().await.idempotency_key(&node.id);
+
         loop {
             // Check circuit breaker before attempting execution
-            if let Some(ref mut breaker) = circuit_breaker {
-                if !breaker.should_allow_request() {
-                    let error = GraphBitError::workflow_execution(
-                        "Circuit breaker is open - requests are being rejected".to_string(),
-                    );
-                    return Ok(
-                        NodeExecutionResult::failure(error.to_string(), node.id.clone())
-                            .with_duration(start_time.elapsed().as_millis() as u64)
-                            .with_retry_count(attempt),
-                    );
-                }
+            if !Self::breaker_allows_attempt(&node, circuit_breaker.as_mut()) {
+                let error = GraphBitError::workflow_execution(
+                    "Circuit breaker is open - requests are being rejected".to_string(),
+                );
+                return Ok(
+                    NodeExecutionResult::failure(error.to_string(), node.id.clone())
+                        .with_duration(elapsed().as_millis() as u64)
+                        .with_retry_count(attempt)
+                        .with_idempotency_key(idempotency_key),
+                );
             }
 
             // Attempt to execute the node
-            let result = match &node.node_type {
-                NodeType::Agent { config } => {
-                    Self::execute_agent_node_static(
-                        &node.id,
-                        config,
-                        &node.config,
-                        context.clone(),
-                        agents.clone(),
-                        guardrail_enforcer.clone(),
-                        event_tx.clone(),
-                        stream_mode,
-                    )
-                    .await
-                }
-                NodeType::DocumentLoader {
-                    document_type,
-                    source_path,
-                    ..
-                } => {
-                    Self::execute_document_loader_node_static(
-                        document_type,
-                        source_path,
-                        context.clone(),
-                    )
-                    .await
-                }
-                NodeType::Condition { handler_id } => {
-                    Self::execute_condition_node(
-                        &node,
-                        handler_id,
-                        context.clone(),
-                        node_parents.clone(),
-                        conditional_handlers.clone(),
-                        workflow_graph.clone(),
-                    )
-                    .await
+            let result =
+                Self::execute_node_once(&node, &context, &runtime, attempt, &idempotency_key).await;
+            let result = match result {
+                Ok(output) if !Self::is_tool_calls_required_output(&output) => {
+                    Self::post_process_output(&node, output, &context).await
                 }
-                _ => Err(GraphBitError::workflow_execution(format!(
-                    "Unsupported node type: {:?}",
-                    node.node_type
-                ))),
+                result => result,
             };
 
             match result {
@@ -1345,21 +3883,8 @@ impl WorkflowExecutor {
                     // Store using both NodeId and node name for flexible access
                     {
                         let mut ctx = context.lock().await;
-                        ctx.set_node_output(&node.id, output.clone());
-                        ctx.set_node_output_by_name(&node.name, output.clone());
-
-                        // PRODUCTION FIX: Also populate variables for backward compatibility
-                        // This ensures extract_output() functions work correctly
-                        if let Ok(output_str) = serde_json::to_string(&output) {
-                            ctx.set_variable(
-                                node.name.clone(),
-                                serde_json::Value::String(output_str.clone()),
-                            );
-                            ctx.set_variable(
-                                node.id.to_string(),
-                                serde_json::Value::String(output_str),
-                            );
-                        }
+                        Self::store_node_output(&mut ctx, &node, output.clone());
+                        ctx.record_node_attempts(&node.name, attempt + 1);
 
                         // Debug: confirm storage keys available after this node completes
                         let keys: Vec<String> = ctx.node_outputs.keys().cloned().collect();
@@ -1374,40 +3899,35 @@ impl WorkflowExecutor {
                     }
 
                     // Record success in circuit breaker
-                    if let Some(ref mut breaker) = circuit_breaker {
-                        breaker.record_success();
-                        if let NodeType::Agent { config } = &node.node_type {
-                            let agent_id = &config.agent_id;
-                            let mut breakers = circuit_breakers.write().await;
-                            breakers.insert(agent_id.clone(), breaker.clone());
-                        }
-                    }
+                    Self::record_breaker_outcome(&node, circuit_breaker.as_mut(), true, &runtime)
+                        .await;
 
-                    let duration = start_time.elapsed();
-                    return Ok(NodeExecutionResult::success(output, node.id.clone())
-                        .with_duration(duration.as_millis() as u64)
-                        .with_retry_count(attempt));
+                    let result = NodeExecutionResult::success(output, node.id.clone())
+                        .with_duration(elapsed().as_millis() as u64);
+                    return Ok(Self::with_node_reports(
+                        result,
+                        &node,
+                        &context,
+                        attempt,
+                        idempotency_key,
+                    )
+                    .await);
                 }
                 Err(error) => {
                     // Record failure in circuit breaker
-                    if let Some(ref mut breaker) = circuit_breaker {
-                        breaker.record_failure();
-                        if let NodeType::Agent { config } = &node.node_type {
-                            let agent_id = &config.agent_id;
-                            let mut breakers = circuit_breakers.write().await;
-                            breakers.insert(agent_id.clone(), breaker.clone());
-                        }
-                    }
+                    Self::record_breaker_outcome(&node, circuit_breaker.as_mut(), false, &runtime)
+                        .await;
 
                     // Check if we should retry
                     if let Some(ref config) = retry_config {
                         if config.should_retry(&error, attempt) {
                             attempt += 1;
+                            crate::telemetry::node_retried(node.node_type.kind());
 
                             // Calculate delay for this attempt
                             let delay_ms = config.calculate_delay(attempt);
                             if delay_ms > 0 {
-                                tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms))
+                                crate::clock::sleep(std::time::Duration::from_millis(delay_ms))
                                     .await;
                             }
 
@@ -1416,32 +3936,435 @@ impl WorkflowExecutor {
                     }
 
                     // No more retries, return the error
-                    let duration = start_time.elapsed();
-                    return Ok(
-                        NodeExecutionResult::failure(error.to_string(), node.id.clone())
-                            .with_duration(duration.as_millis() as u64)
-                            .with_retry_count(attempt),
-                    );
+                    let duration = elapsed();
+                    context
+                        .lock()
+                        .await
+                        .record_node_attempts(&node.name, attempt + 1);
+                    let result = NodeExecutionResult::failure(error.to_string(), node.id.clone())
+                        .with_duration(duration.as_millis() as u64);
+                    return Ok(Self::with_node_reports(
+                        result,
+                        &node,
+                        &context,
+                        attempt,
+                        idempotency_key,
+                    )
+                    .await);
                 }
             }
         }
     }
 
+    /// Circuit breaker of the agent an agent node calls, created on first use
+    async fn node_circuit_breaker(
+        node: &WorkflowNode,
+        runtime: &NodeRuntime,
+    ) -> Option<CircuitBreaker> {
+        let config = node.node_type.agent_config()?;
+        let mut breakers = runtime.circuit_breakers.write().await;
+        Some(
+            breakers
+                .entry(config.agent_id.clone())
+                .or_insert_with(|| {
+                    CircuitBreaker::new(runtime.circuit_breaker_config.clone())
+                        .with_clock(crate::clock::current())
+                })
+                .clone(),
+        )
+    }
+
+    /// Whether the node's circuit breaker, if any, lets an attempt through
+    fn breaker_allows_attempt(node: &WorkflowNode, breaker: Option<&mut CircuitBreaker>) -> bool {
+        let Some(breaker) = breaker else {
+            return true;
+        };
+        let before = breaker.state.clone();
+        let allowed = breaker.should_allow_request();
+        Self::report_breaker_transition(node, &before, breaker);
+        allowed
+    }
+
+    /// Record the outcome of an attempt in the node's circuit breaker, if it
+    /// has one, and share the breaker with the other nodes calling its agent
+    async fn record_breaker_outcome(
+        node: &WorkflowNode,
+        breaker: Option<&mut CircuitBreaker>,
+        succeeded: bool,
+        runtime: &NodeRuntime,
+    ) {
+        let Some(breaker) = breaker else {
+            return;
+        };
+        let before = breaker.state.clone();
+        if succeeded {
+            breaker.record_success();
+        } else {
+            breaker.record_failure();
+        }
+        Self::report_breaker_transition(node, &before, breaker);
+        if let Some(config) = node.node_type.agent_config() {
+            let mut breakers = runtime.circuit_breakers.write().await;
+            breakers.insert(config.agent_id.clone(), breaker.clone());
+        }
+    }
+
+    /// Complete the result of a node's last attempt with its retry count,
+    /// idempotency key and the reports recorded for it
+    async fn with_node_reports(
+        result: NodeExecutionResult,
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        attempt: u32,
+        idempotency_key: String,
+    ) -> NodeExecutionResult {
+        let output_validation = Self::node_output_validation(node, context).await;
+        let guardrail = Self::node_guardrail_report(node, context).await;
+        result
+            .with_retry_count(attempt)
+            .with_output_validation(output_validation)
+            .with_guardrail(guardrail)
+            .with_idempotency_key(idempotency_key)
+    }
+
+    /// Run one attempt of a node, dispatching on its kind
+    async fn execute_node_once(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        runtime: &NodeRuntime,
+        attempt: u32,
+        idempotency_key: &str,
+    ) -> GraphBitResult<serde_json::Value> {
+        let node_parents = &runtime.node_parents;
+        let workflow_graph = &runtime.workflow_graph;
+        match &node.node_type {
+            NodeType::Agent { config } => {
+                let output = ToolCallContext::new(&node.name, idempotency_key, attempt)
+                    .scope(Box::pin(Self::execute_agent_node_static(
+                        &node.id,
+                        config,
+                        &node.config,
+                        context.clone(),
+                        runtime,
+                    )))
+                    .await?;
+
+                // Enforce the output schema once the agent has produced its final output
+                Self::enforce_agent_output_schema(node, config, output, context.clone(), runtime)
+                    .await
+            }
+            NodeType::ModelRouter {
+                config,
+                candidates,
+                escalation_expression,
+            } => {
+                ToolCallContext::new(&node.name, idempotency_key, attempt)
+                    .scope(Box::pin(Self::execute_model_router_node(
+                        node,
+                        config,
+                        candidates,
+                        escalation_expression,
+                        context.clone(),
+                        runtime,
+                    )))
+                    .await
+            }
+            NodeType::DocumentLoader {
+                document_type,
+                source_path,
+                ..
+            } => {
+                Self::execute_document_loader_node_static(
+                    &node.name,
+                    document_type,
+                    source_path,
+                    context.clone(),
+                )
+                .await
+            }
+            NodeType::Condition {
+                handler_id,
+                expression,
+            } => {
+                Self::execute_condition_node(
+                    node,
+                    handler_id,
+                    expression.as_deref(),
+                    context.clone(),
+                    node_parents.clone(),
+                    runtime.conditional_handlers.clone(),
+                    workflow_graph.clone(),
+                )
+                .await
+            }
+            NodeType::Transform { transformation } => {
+                let input = Self::node_input(node, context, node_parents, workflow_graph).await?;
+                let ctx = context.lock().await;
+                Self::apply_transformation(&node.name, transformation, input, &ctx)
+            }
+            NodeType::Split => Self::node_input(node, context, node_parents, workflow_graph).await,
+            NodeType::Join => Self::execute_join_node(node, context, runtime).await,
+            NodeType::Delay { duration_seconds } => {
+                crate::clock::sleep(std::time::Duration::from_secs(*duration_seconds)).await;
+                Self::node_input(node, context, node_parents, workflow_graph).await
+            }
+            _ => Self::execute_external_node(node, context, runtime, idempotency_key).await,
+        }
+    }
+
+    /// Run one attempt of a node reaching outside the workflow: Python code,
+    /// shell commands, HTTP requests, guardrail checks and external events
+    async fn execute_external_node(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        runtime: &NodeRuntime,
+        idempotency_key: &str,
+    ) -> GraphBitResult<serde_json::Value> {
+        let node_parents = &runtime.node_parents;
+        let workflow_graph = &runtime.workflow_graph;
+        match &node.node_type {
+            #[cfg(feature = "python")]
+            NodeType::PythonCode {
+                code_template,
+                timeout_seconds,
+                memory_limit_mb,
+                allowed_imports,
+                allow_network,
+            } => {
+                let limits = crate::python_code::PythonCodeLimits {
+                    timeout_seconds: *timeout_seconds,
+                    memory_limit_mb: *memory_limit_mb,
+                    allowed_imports: allowed_imports.clone(),
+                    allow_network: *allow_network,
+                };
+                Self::execute_python_code_node(node, code_template, &limits, context).await
+            }
+            NodeType::ShellCommand {
+                command_template,
+                working_dir,
+                env,
+                timeout_seconds,
+            } => {
+                let options = crate::shell_command::ShellCommandOptions {
+                    working_dir: working_dir.clone(),
+                    env: env.clone(),
+                    timeout_seconds: *timeout_seconds,
+                };
+                Self::execute_shell_command_node(node, command_template, options, context).await
+            }
+            NodeType::HttpRequest {
+                url,
+                method,
+                headers,
+            } => {
+                Self::run_http_request_node(node, url, method, headers, context, idempotency_key)
+                    .await
+            }
+            NodeType::Guardrail {
+                checks,
+                on_violation,
+            } => {
+                Self::execute_guardrail_node(
+                    node,
+                    checks,
+                    *on_violation,
+                    context,
+                    node_parents,
+                    workflow_graph,
+                )
+                .await
+            }
+            NodeType::ExternalEvent {
+                event_name,
+                timeout_seconds,
+            } => {
+                Self::execute_external_event_node(
+                    node,
+                    event_name,
+                    *timeout_seconds,
+                    context,
+                    workflow_graph,
+                )
+                .await
+            }
+            _ => Err(GraphBitError::workflow_execution(format!(
+                "Unsupported node type: {:?}",
+                node.node_type
+            ))),
+        }
+    }
+
+    /// Merge the outputs of a join node's parents, collecting the scratch
+    /// values their branches wrote
+    async fn execute_join_node(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        runtime: &NodeRuntime,
+    ) -> GraphBitResult<serde_json::Value> {
+        let output = Self::join_output(
+            node,
+            context,
+            &runtime.node_parents,
+            &runtime.workflow_graph,
+        )
+        .await?;
+        let parents: Vec<String> = runtime
+            .node_parents
+            .get(&node.id)
+            .into_iter()
+            .flatten()
+            .filter_map(|parent| runtime.workflow_graph.get_node(parent))
+            .map(|parent| parent.name.clone())
+            .collect();
+        context
+            .lock()
+            .await
+            .collect_branch_scratch(&node.name, &parents);
+        Ok(output)
+    }
+
+    /// Run the code of a Python code node, its template resolved, within `limits`
+    #[cfg(feature = "python")]
+    async fn execute_python_code_node(
+        node: &WorkflowNode,
+        code_template: &str,
+        limits: &crate::python_code::PythonCodeLimits,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> GraphBitResult<serde_json::Value> {
+        let code = {
+            let ctx = context.lock().await;
+            Self::resolve_node_template_variables(code_template, &ctx, Some(&node.id))
+        };
+        crate::python_code::run_python_code(&node.name, &code, limits).await
+    }
+
+    /// Run the command of a shell command node. The values its template
+    /// references reach the command through environment variables, never
+    /// through the command line.
+    async fn execute_shell_command_node(
+        node: &WorkflowNode,
+        command_template: &str,
+        mut options: crate::shell_command::ShellCommandOptions,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> GraphBitResult<serde_json::Value> {
+        let mut values = crate::shell_command::CommandValues::default();
+        let command = {
+            let ctx = context.lock().await;
+            Self::resolve_escaped_template_variables(
+                command_template,
+                &ctx,
+                Some(&node.id),
+                &mut |value| values.bind(value),
+            )
+        };
+        options
+            .env
+            .extend(values.env().map(|(name, value)| (name, value.to_string())));
+        crate::shell_command::run_shell_command(&node.name, &command, &options).await
+    }
+
+    /// Run the checks of a guardrail node on its input, recording the report
+    /// before a blocked input fails the node
+    async fn execute_guardrail_node(
+        node: &WorkflowNode,
+        checks: &[GuardrailCheck],
+        on_violation: GuardrailAction,
+        context: &Arc<Mutex<WorkflowContext>>,
+        node_parents: &HashMap<NodeId, Vec<NodeId>>,
+        workflow_graph: &WorkflowGraph,
+    ) -> GraphBitResult<serde_json::Value> {
+        let input = Self::node_input(node, context, node_parents, workflow_graph).await?;
+        let (llm_config, middleware) = {
+            let ctx = context.lock().await;
+            (
+                ctx.node_llm_configs.get(&node.id).cloned(),
+                ctx.llm_middleware.clone(),
+            )
+        };
+        let (output, report) = crate::guardrail_node::run_guardrail(
+            &node.name,
+            checks,
+            on_violation,
+            input,
+            llm_config.as_ref(),
+            &middleware,
+        )
+        .await?;
+        let blocked = report.block_error(&node.name);
+        context
+            .lock()
+            .await
+            .record_guardrail_report(&node.name, report);
+        blocked.map_or(Ok(output), Err)
+    }
+
+    /// Wait for the event of an external event node and output its payload.
+    /// Past the timeout the node follows its timeout edges with a null output,
+    /// or fails when it has none.
+    async fn execute_external_event_node(
+        node: &WorkflowNode,
+        event_name: &str,
+        timeout_seconds: Option<u64>,
+        context: &Arc<Mutex<WorkflowContext>>,
+        workflow_graph: &WorkflowGraph,
+    ) -> GraphBitResult<serde_json::Value> {
+        let (events, execution_id) = {
+            let ctx = context.lock().await;
+            (ctx.external_events.clone(), ctx.execution_id)
+        };
+        let timeout = timeout_seconds.map(std::time::Duration::from_secs);
+        if let Some(payload) = events.wait(execution_id, event_name, timeout).await? {
+            return Ok(payload);
+        }
+        let has_timeout_edges = workflow_graph
+            .get_edges()
+            .iter()
+            .any(|(from, _, edge)| from == &node.id && matches!(edge.edge_type, EdgeType::Timeout));
+        if !has_timeout_edges {
+            return Err(GraphBitError::workflow_execution(format!(
+                "External event node '{}' received no '{event_name}' event within {}s",
+                node.name,
+                timeout_seconds.unwrap_or_default()
+            )));
+        }
+        context.lock().await.record_event_timeout(&node.name);
+        Ok(serde_json::Value::Null)
+    }
+
+    /// Report recorded for a node, if it is a guardrail node
+    async fn node_guardrail_report(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> Option<GuardrailReport> {
+        if !matches!(node.node_type, NodeType::Guardrail { .. }) {
+            return None;
+        }
+        context
+            .lock()
+            .await
+            .get_guardrail_report(&node.name)
+            .cloned()
+    }
+
     /// Execute an agent node (static version).
-    /// When `guardrail_enforcer` is `Some`, encodes prompt before LLM and decodes response after.
+    /// When the run has a guardrail enforcer, encodes prompt before LLM and decodes response after.
     /// When `stream_mode.emits_tokens()` and the provider supports streaming, emits `Token`
-    /// events per chunk via `event_tx` and accumulates into a full response. Falls back to
+    /// and `NodeOutputDelta` events per chunk on the run's event channel and accumulates into a full
+    /// response. Falls back to
     /// `complete()` if the provider does not support streaming.
     async fn execute_agent_node_static(
         current_node_id: &NodeId,
         agent_node_config: &AgentNodeConfig,
         node_config: &std::collections::HashMap<String, serde_json::Value>,
         context: Arc<Mutex<WorkflowContext>>,
-        agents: Arc<RwLock<HashMap<crate::types::AgentId, Arc<dyn AgentTrait>>>>,
-        guardrail_enforcer: Option<Arc<Enforcer>>,
-        event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
-        stream_mode: crate::stream::StreamMode,
+        runtime: &NodeRuntime,
     ) -> GraphBitResult<serde_json::Value> {
+        let agents = &runtime.agents;
+        let guardrail_enforcer = runtime.guardrail_enforcer.clone();
+        let event_tx = runtime.event_tx.clone();
+        let stream_mode = runtime.stream_mode;
+        let tool_runtime = runtime.tool_runtime.as_ref();
+        let workflow_graph = runtime.workflow_graph.as_ref();
         let agent_id = &agent_node_config.agent_id;
         let prompt_template = &agent_node_config.prompt_template;
         let conversational_context = agent_node_config.conversational_context.as_deref();
@@ -1454,7 +4377,7 @@ impl WorkflowExecutor {
         drop(agents_guard); // Release the lock early
 
         // Build implicit preamble from upstream (parent) node outputs, then resolve templates
-        let (resolved_prompt, resolved_context, metadata_input_raw) = {
+        let (resolved_prompt, resolved_context, metadata_input_raw, images) = {
             let ctx = context.lock().await;
 
             // Extract dependency map and name map from metadata
@@ -1503,14 +4426,33 @@ impl WorkflowExecutor {
             // Preserve order by iterating parent_ids, using id->name for titles, and fetching outputs by key
             for pid in &parent_ids {
                 // Prefer fetching by id first, then by name key
-                let val_opt = ctx.node_outputs.get(pid).or_else(|| {
-                    id_name_obj
-                        .and_then(|m| m.get(pid))
-                        .and_then(|v| v.as_str())
-                        .and_then(|name| ctx.node_outputs.get(name))
-                });
+                let val_opt = ctx
+                    .node_outputs
+                    .get(pid)
+                    .or_else(|| {
+                        id_name_obj
+                            .and_then(|m| m.get(pid))
+                            .and_then(|v| v.as_str())
+                            .and_then(|name| ctx.node_outputs.get(name))
+                    })
+                    .map(|value| ctx.resolve_output(value));
 
                 if let Some(value) = val_opt {
+                    let value = match workflow_graph
+                        .get_edges()
+                        .iter()
+                        .find(|(from, to, _)| &from.to_string() == pid && to == current_node_id)
+                    {
+                        Some((from, to, _)) => Self::apply_edge_transform(
+                            workflow_graph,
+                            &ctx,
+                            from,
+                            to,
+                            value.into_owned(),
+                        )?,
+                        None => value.into_owned(),
+                    };
+                    let value = &value;
                     let value_str = match value {
                         serde_json::Value::String(s) => s.clone(),
                         _ => value.to_string(),
@@ -1560,14 +4502,18 @@ impl WorkflowExecutor {
                 sections.join("\n\n") + "\n\n"
             };
 
+            let prompt_template = Self::suffixed_prompt_template(prompt_template, &ctx);
+            let (prompt_template, images) = Self::prompt_images(&prompt_template, &ctx)?;
             let combined_for_llm = format!("{implicit_preamble}{prompt_template}");
-            let resolved_prompt = Self::resolve_template_variables(&combined_for_llm, &ctx);
+            let resolve = |template: &str| {
+                Self::resolve_node_template_variables(template, &ctx, Some(current_node_id))
+            };
+            let resolved_prompt = resolve(&combined_for_llm);
 
-            let resolved_context = conversational_context
-                .map(|ctx_template| Self::resolve_template_variables(ctx_template, &ctx));
+            let resolved_context = conversational_context.map(resolve);
 
             // Use only the prompt part for cleaner metadata logging
-            let metadata_input_raw = Self::resolve_template_variables(prompt_template, &ctx);
+            let metadata_input_raw = resolve(&prompt_template);
 
             // Debug log the resolved prompt (trimmed)
             let preview: String = resolved_prompt.chars().take(400).collect();
@@ -1577,11 +4523,38 @@ impl WorkflowExecutor {
                 preview = %preview,
                 "Resolved prompt preview with implicit parent context"
             );
-            (resolved_prompt, resolved_context, metadata_input_raw)
+            (
+                resolved_prompt,
+                resolved_context,
+                metadata_input_raw,
+                images,
+            )
         };
 
-        // Check if this node has tools configured
-        let has_tools = node_config.contains_key("tool_schemas");
+        // Check if this node has tools configured, either as full schemas or as
+        // names resolved from the executor's tool registry, or can hand off to other agents
+        let handoff_targets: Vec<String> = node_config
+            .get("handoff_targets")
+            .and_then(|v| v.as_array())
+            .map(|targets| {
+                targets
+                    .iter()
+                    .filter_map(|t| t.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let handoff = (!handoff_targets.is_empty()).then_some(HandoffScope {
+            targets: &handoff_targets,
+            graph: workflow_graph,
+            agents: agents.as_ref(),
+        });
+        let has_tools = node_config.contains_key("tool_schemas")
+            || handoff.is_some()
+            || (tool_runtime.is_some()
+                && node_config
+                    .get("tools")
+                    .and_then(|v| v.as_array())
+                    .is_some_and(|tools| !tools.is_empty()));
 
         // DEBUG: Log tool detection
         tracing::info!(
@@ -1623,6 +4596,9 @@ impl WorkflowExecutor {
                 guardrail_enforcer.clone(),
                 event_tx.clone(),
                 stream_mode,
+                tool_runtime,
+                handoff,
+                images,
             )
             .await;
             tracing::info!("Agent with tools execution result: {:?}", result);
@@ -1713,42 +4689,51 @@ impl WorkflowExecutor {
             use crate::llm::{LlmMessage, LlmRequest};
 
             // Resolve system prompt: Priority is Node Override > Agent Default
-            let system_prompt =
-                if let Some(sys_override) = &agent_node_config.system_prompt_override {
-                    Some(sys_override.clone())
-                } else if !agent.config().system_prompt.is_empty() {
-                    Some(agent.config().system_prompt.clone())
-                } else {
-                    None
-                };
+            let system_prompt = Self::resolve_node_system_prompt(
+                agent_node_config,
+                node_config,
+                Some(agent.as_ref()),
+                &*context.lock().await,
+                current_node_id,
+            );
+            let provider_override = Self::node_llm_provider_override(
+                current_node_id,
+                node_config,
+                agent.as_ref(),
+                &context,
+            )
+            .await?;
+            let llm_provider = provider_override
+                .as_ref()
+                .unwrap_or_else(|| agent.llm_provider());
+            Self::check_image_inputs(llm_provider, &images)?;
+
+            crate::capture::record_prompt(system_prompt.as_deref(), &prompt_for_llm);
 
             // Build messages array
             let mut messages = Vec::with_capacity(2);
-            if let Some(content) = system_prompt {
+            if let Some(content) = system_prompt.clone() {
                 messages.push(LlmMessage::system(content));
             }
-            messages.push(LlmMessage::user(prompt_for_llm.clone()));
-
-            let mut request = LlmRequest::with_messages(messages);
+            messages.push(LlmMessage::user(prompt_for_llm.clone()).with_images(images));
 
             // Apply node-level configuration overrides (temperature, max_tokens, etc.)
-            if let Some(temp_value) = node_config.get("temperature") {
-                if let Some(temp_num) = temp_value.as_f64() {
-                    request = request.with_temperature(temp_num as f32);
-                }
-            }
-
-            if let Some(max_tokens_value) = node_config.get("max_tokens") {
-                if let Some(max_tokens_num) = max_tokens_value.as_u64() {
-                    request = request.with_max_tokens(max_tokens_num as u32);
-                }
-            }
+            let mut request = Self::apply_node_sampling_overrides(
+                LlmRequest::with_messages(messages),
+                node_config,
+                agent.as_ref(),
+            );
 
             // Enable Anthropic prompt caching when configured on the node
             if node_config.get("enable_prompt_caching").and_then(|v| v.as_bool()).unwrap_or(false) {
                 request = request.with_prompt_caching(true);
             }
 
+            // Fit the request into the model's context window per the node's strategy
+            let context_fit = ContextWindow::for_node(node_config, llm_provider.provider())?
+                .map(|window| window.fit(&mut request))
+                .transpose()?;
+
             // Measure LLM call duration and capture execution timestamp
             let execution_timestamp = chrono::Utc::now();
 
@@ -1770,7 +4755,7 @@ impl WorkflowExecutor {
                         },
                         llm_call_id: llm_call_id_hint.clone(),
                         iteration: 1,
-                        model: agent.llm_provider().config().model_name().to_string(),
+                        model: llm_provider.config().model_name().to_string(),
                     })
                     .await;
             }
@@ -1783,76 +4768,40 @@ impl WorkflowExecutor {
             // When the mode requests tokens AND the provider supports streaming,
             // drive the stream and emit a Token event per chunk, accumulating the
             // full content for metadata/context. Falls back to complete() transparently.
-            let llm_response = if stream_mode.emits_tokens()
-                && event_tx.is_some()
-                && agent.llm_provider().provider().supports_streaming()
-            {
-                use crate::stream::StreamEvent;
-                use futures::StreamExt;
-
-                // Get the node name for Token events (best-effort, doesn't block)
-                let token_node_name = {
-                    let ctx = context.lock().await;
-                    ctx.metadata
-                        .get("node_id_to_name")
-                        .and_then(|m| m.as_object())
-                        .and_then(|m| m.get(&current_node_id.to_string()))
-                        .and_then(|v| v.as_str())
-                        .map(str::to_string)
-                        .unwrap_or_else(|| "unknown".to_string())
-                };
-
-                // Drive the streaming response
-                // Pre-clone the request for the empty-stream fallback before moving into stream()
-                let fallback_request = request.clone();
-                let mut stream = agent.llm_provider().stream(request).await?;
-
-                // Accumulate content and keep the last full LlmResponse for metadata
-                let mut accumulated_content = String::new();
-                let mut last_response: Option<crate::llm::LlmResponse> = None;
-
-                while let Some(chunk_result) = stream.next().await {
-                    let chunk = chunk_result?;
-
-                    // Only emit non-empty content chunks as Token events
-                    if !chunk.content.is_empty() {
-                        if let Some(ref tx) = event_tx {
-                            let _ = tx
-                                .send(StreamEvent::Token {
-                                    node_id: current_node_id.to_string(),
-                                    node_name: token_node_name.clone(),
-                                    llm_call_id: llm_call_id_hint.clone(),
-                                    content: chunk.content.clone(),
-                                })
-                                .await;
-                        }
-                        accumulated_content.push_str(&chunk.content);
-                    }
-                    last_response = Some(chunk);
-                }
-
-                // Build a complete LlmResponse from the accumulated stream
-                match last_response {
-                    Some(mut final_resp) => {
-                        // Streamed text tokens; keep provider `content` when no deltas had text
-                        // (e.g. tool-only streams still set placeholder + tool_calls on the last chunk).
-                        if !accumulated_content.is_empty() {
-                            final_resp.content = accumulated_content;
-                        }
-                        final_resp
-                    }
-                    None => {
-                        // Empty stream — fall back to complete() to get a proper response
-                        tracing::warn!(
-                            node_id = %current_node_id,
-                            "LLM stream returned 0 chunks; falling back to complete()"
-                        );
-                        agent.llm_provider().complete(fallback_request).await?
-                    }
+            let middleware = context.lock().await.llm_middleware.clone();
+            let llm_response = match event_tx {
+                Some(ref tx)
+                    if stream_mode.emits_tokens()
+                        && llm_provider.provider().supports_streaming() =>
+                {
+                    // Get the node name for Token events (best-effort, doesn't block)
+                    let token_node_name = {
+                        let ctx = context.lock().await;
+                        ctx.metadata
+                            .get("node_id_to_name")
+                            .and_then(|m| m.as_object())
+                            .and_then(|m| m.get(&current_node_id.to_string()))
+                            .and_then(|v| v.as_str())
+                            .map_or_else(|| "unknown".to_string(), str::to_string)
+                    };
+                    let stream_node_id = current_node_id.to_string();
+                    middleware
+                        .wrap(request, |request| {
+                            crate::audit::llm_call(llm_provider.provider(), request, |request| {
+                                Self::stream_llm_response(
+                                    llm_provider,
+                                    request,
+                                    tx,
+                                    &stream_node_id,
+                                    &token_node_name,
+                                    &llm_call_id_hint,
+                                )
+                            })
+                        })
+                        .await?
                 }
-            } else {
                 // Standard (non-streaming) path — identical to previous behaviour
-                agent.llm_provider().complete(request).await?
+                _ => llm_provider.complete_with(&middleware, request).await?,
             };
 
             let llm_duration_ms = llm_start.elapsed().as_secs_f64() * 1000.0;
@@ -1885,7 +4834,7 @@ impl WorkflowExecutor {
             }
 
             // Get provider name for metadata
-            let provider_name = agent.llm_provider().config().provider_name().to_string();
+            let provider_name = llm_provider.config().provider_name().to_string();
 
             // Capture raw LLM content before decode for metadata
             let raw_llm_content = llm_response.content.clone();
@@ -1999,6 +4948,7 @@ impl WorkflowExecutor {
                     // When GR active: user_input = masked prompt, final_output = raw LLM content
                     // When GR inactive: user_input = original prompt, final_output = decoded content
                     "user_input": if guardrail_enforcer.is_some() { masked_input_for_meta.clone() } else { metadata_input_raw.clone() },
+                    "system_prompt": system_prompt,
                     "tools_available": [],
                     "total_tools_available": 0,
                     "start_time": execution_timestamp.to_rfc3339(),
@@ -2029,6 +4979,7 @@ impl WorkflowExecutor {
                     "total_tool_calls": 0,
                     "total_retries": 0,
                     "tools_used": [],
+                    "context_window": context_fit,
                     "executions": executions
                 });
 
@@ -2063,8 +5014,9 @@ impl WorkflowExecutor {
     ///
     /// When `stream_mode.emits_tokens()`, `event_tx` is `Some`, and the provider supports
     /// streaming, the initial tool-selection LLM call uses [`LlmProvider::stream`] and emits
-    /// [`crate::stream::StreamEvent::Token`] per chunk; otherwise that call uses
-    /// [`LlmProvider::complete`] (same as non-streaming `execute()`).
+    /// [`crate::stream::StreamEvent::Token`] per chunk, plus
+    /// [`crate::stream::StreamEvent::NodeOutputDelta`] for text outside tool calls; otherwise
+    /// that call uses [`LlmProvider::complete`] (same as non-streaming `execute()`).
     async fn execute_agent_with_tools(
         _agent_id: &crate::types::AgentId,
         agent_node_config: &AgentNodeConfig,
@@ -2079,6 +5031,9 @@ impl WorkflowExecutor {
         guardrail_enforcer: Option<Arc<Enforcer>>,
         event_tx: Option<tokio::sync::mpsc::Sender<crate::stream::StreamEvent>>,
         stream_mode: crate::stream::StreamMode,
+        tool_runtime: Option<&NativeToolRuntime>,
+        handoff: Option<HandoffScope<'_>>,
+        images: Vec<LlmImage>,
     ) -> GraphBitResult<serde_json::Value> {
         tracing::info!("Starting execute_agent_with_tools for agent: {_agent_id}");
         use crate::llm::{LlmMessage, LlmRequest, LlmTool};
@@ -2165,90 +5120,121 @@ impl WorkflowExecutor {
             }
         };
 
-        // Extract tool schemas from node config
-        let tool_schemas = node_config
-            .get("tool_schemas")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| GraphBitError::validation("node_config", "Missing tool_schemas"))?;
+        // Extract tool schemas from node config, falling back to the tool registry
+        // for nodes that only list tool names
+        let mut tools: Vec<LlmTool> = if let Some(tool_schemas) =
+            node_config.get("tool_schemas").and_then(|v| v.as_array())
+        {
+            tool_schemas
+                .iter()
+                .filter_map(|schema| {
+                    Some(LlmTool::new(
+                        schema.get("name")?.as_str()?,
+                        schema.get("description")?.as_str()?,
+                        schema.get("parameters")?.clone(),
+                    ))
+                })
+                .collect()
+        } else {
+            let names: Vec<String> = node_config
+                .get("tools")
+                .and_then(|v| v.as_array())
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(|n| n.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match tool_runtime.map(|runtime| &runtime.registry) {
+                Some(registry) => {
+                    if let Some(missing) = names.iter().find(|name| !registry.has_tool(name)) {
+                        return Err(GraphBitError::validation(
+                            "tools",
+                            format!(
+                                "Tool '{missing}' is not registered in the executor's tool registry"
+                            ),
+                        ));
+                    }
+                    registry.llm_tools(&names)
+                }
+                None if names.is_empty() && handoff.is_some() => Vec::new(),
+                None => {
+                    return Err(GraphBitError::validation(
+                        "node_config",
+                        "Missing tool_schemas",
+                    ));
+                }
+            }
+        };
 
-        tracing::info!("Found {} tool schemas", tool_schemas.len());
+        // Agents with handoff targets are offered the built-in handoff tool
+        if let Some(scope) = handoff {
+            tools.push(crate::tools::handoff::handoff_tool_metadata(scope.targets).to_llm_tool());
+        }
 
-        // Collect tool names for metadata
-        let tool_names: Vec<String> = tool_schemas
-            .iter()
-            .filter_map(|s| s.get("name").and_then(|v| v.as_str()).map(String::from))
-            .collect();
+        tracing::info!("Found {} tool schemas", tools.len());
 
-        // Convert tool schemas to LlmTool objects
-        let mut tools = Vec::new();
-        for schema in tool_schemas {
-            if let (Some(name), Some(description), Some(parameters)) = (
-                schema.get("name").and_then(|v| v.as_str()),
-                schema.get("description").and_then(|v| v.as_str()),
-                schema.get("parameters"),
-            ) {
-                tools.push(LlmTool::new(name, description, parameters.clone()));
-            }
-        }
+        // Collect tool names for metadata
+        let tool_names: Vec<String> = tools.iter().map(|tool| tool.name.clone()).collect();
 
         // Resolve system prompt: Priority is Node Override > Agent Default
-        let system_prompt = if let Some(sys_override) = &agent_node_config.system_prompt_override {
-            Some(sys_override.clone())
-        } else if !agent.config().system_prompt.is_empty() {
-            Some(agent.config().system_prompt.clone())
-        } else {
-            None
-        };
+        let system_prompt = Self::resolve_node_system_prompt(
+            agent_node_config,
+            node_config,
+            Some(agent.as_ref()),
+            &*context.lock().await,
+            node_id,
+        );
+        let provider_override =
+            Self::node_llm_provider_override(node_id, node_config, agent.as_ref(), &context)
+                .await?;
+        let llm_provider = provider_override
+            .as_ref()
+            .unwrap_or_else(|| agent.llm_provider());
+        Self::check_image_inputs(llm_provider, &images)?;
+
+        crate::capture::record_prompt(system_prompt.as_deref(), &prompt_for_llm);
 
         // Create initial LLM request with tools (using encoded prompt when guardrail is active)
         let mut messages = Vec::with_capacity(2);
-        if let Some(content) = system_prompt {
+        if let Some(content) = system_prompt.clone() {
             messages.push(LlmMessage::system(content));
         }
-        messages.push(LlmMessage::user(prompt_for_llm.clone()));
-
-        let mut request = LlmRequest::with_messages(messages);
-        for tool in &tools {
-            request = request.with_tool(tool.clone());
-        }
-
-        // Apply node-level configuration overrides (temperature, max_tokens, top_p)
-        // This ensures the initial tool selection LLM call respects node configuration
-        if let Some(temp_value) = node_config.get("temperature") {
-            if let Some(temp_num) = temp_value.as_f64() {
-                request = request.with_temperature(temp_num as f32);
-                tracing::debug!("Applied temperature={} to tool selection request", temp_num);
-            }
-        }
-
-        if let Some(max_tokens_value) = node_config.get("max_tokens") {
-            if let Some(max_tokens_num) = max_tokens_value.as_u64() {
-                request = request.with_max_tokens(max_tokens_num as u32);
-                tracing::debug!(
-                    "Applied max_tokens={} to tool selection request",
-                    max_tokens_num
-                );
-            }
-        }
+        messages.push(LlmMessage::user(prompt_for_llm.clone()).with_images(images));
 
-        if let Some(top_p_value) = node_config.get("top_p") {
-            if let Some(top_p_num) = top_p_value.as_f64() {
-                request = request.with_top_p(top_p_num as f32);
-                tracing::debug!("Applied top_p={} to tool selection request", top_p_num);
-            }
+        let mut request = LlmRequest::with_messages(messages);
+        for tool in &tools {
+            request = request.with_tool(tool.clone());
         }
 
+        // Apply node-level configuration overrides (temperature, max_tokens, top_p)
+        // This ensures the initial tool selection LLM call respects node configuration
+        request = Self::apply_node_sampling_overrides(request, node_config, agent.as_ref());
+
         // Enable Anthropic prompt caching when configured on the node
         if node_config.get("enable_prompt_caching").and_then(|v| v.as_bool()).unwrap_or(false) {
             request = request.with_prompt_caching(true);
             tracing::debug!("Applied enable_prompt_caching=true to tool selection request");
         }
 
+        // Fit the request into the model's context window per the node's strategy; the
+        // tool loop refits every follow-up request as the conversation grows
+        let context_window = ContextWindow::for_node(node_config, llm_provider.provider())?;
+        let context_fit = context_window
+            .map(|window| window.fit(&mut request))
+            .transpose()?;
+        let history_window = HistoryWindow::from_node_config(node_config)?;
+
         tracing::info!("Created LLM request with {} tools", request.tools.len());
         for (i, tool) in request.tools.iter().enumerate() {
             tracing::info!("Tool {i}: {} - {}", tool.name, tool.description);
         }
 
+        // Keep the conversation so far for the in-process tool loop
+        let base_request =
+            (tool_runtime.is_some() || handoff.is_some()).then(|| request.clone());
+
         // Execute LLM request directly to get tool calls
         tracing::info!(
             "About to call LLM provider with {} tools",
@@ -2264,7 +5250,7 @@ impl WorkflowExecutor {
                     node_name: node_name.to_string(),
                     llm_call_id: initial_llm_call_id.clone(),
                     iteration: 1,
-                    model: agent.llm_provider().config().model_name().to_string(),
+                    model: llm_provider.config().model_name().to_string(),
                 })
                 .await;
         }
@@ -2274,56 +5260,28 @@ impl WorkflowExecutor {
         let llm_start = std::time::Instant::now();
         // Token streaming only when explicitly requested and a stream channel exists — matches
         // the no-tools agent path. Non-streaming `execute()` uses `event_tx: None` / Updates mode.
-        let mut llm_response = if stream_mode.emits_tokens()
-            && event_tx.is_some()
-            && agent.llm_provider().provider().supports_streaming()
-        {
-            use crate::stream::StreamEvent;
-            use futures::StreamExt;
-
-            let token_node_name = node_name.to_string();
-            let fallback_request = request.clone();
-            let mut stream = agent.llm_provider().stream(request).await?;
-
-            let mut accumulated_content = String::new();
-            let mut last_response: Option<crate::llm::LlmResponse> = None;
-
-            while let Some(chunk_result) = stream.next().await {
-                let chunk = chunk_result?;
-
-                if !chunk.content.is_empty() {
-                    if let Some(ref tx) = event_tx {
-                        let _ = tx
-                            .send(StreamEvent::Token {
-                                node_id: node_id.to_string(),
-                                node_name: token_node_name.clone(),
-                                llm_call_id: initial_llm_call_id.clone(),
-                                content: chunk.content.clone(),
-                            })
-                            .await;
-                    }
-                    accumulated_content.push_str(&chunk.content);
-                }
-                last_response = Some(chunk);
-            }
-
-            match last_response {
-                Some(mut final_resp) => {
-                    if !accumulated_content.is_empty() {
-                        final_resp.content = accumulated_content;
-                    }
-                    final_resp
-                }
-                None => {
-                    tracing::warn!(
-                        node_id = %node_id,
-                        "LLM stream returned 0 chunks (tool path); falling back to complete()"
-                    );
-                    agent.llm_provider().complete(fallback_request).await?
-                }
+        let middleware = context.lock().await.llm_middleware.clone();
+        let mut llm_response = match event_tx {
+            Some(ref tx)
+                if stream_mode.emits_tokens() && llm_provider.provider().supports_streaming() =>
+            {
+                let stream_node_id = node_id.to_string();
+                middleware
+                    .wrap(request, |request| {
+                        crate::audit::llm_call(llm_provider.provider(), request, |request| {
+                            Self::stream_llm_response(
+                                llm_provider,
+                                request,
+                                tx,
+                                &stream_node_id,
+                                node_name,
+                                &initial_llm_call_id,
+                            )
+                        })
+                    })
+                    .await?
             }
-        } else {
-            agent.llm_provider().complete(request).await?
+            _ => llm_provider.complete_with(&middleware, request).await?,
         };
         let llm_duration_ms = llm_start.elapsed().as_secs_f64() * 1000.0;
         let llm_end_timestamp = chrono::Utc::now();
@@ -2346,7 +5304,7 @@ impl WorkflowExecutor {
         }
 
         // Get provider name for metadata
-        let provider_name = agent.llm_provider().config().provider_name().to_string();
+        let provider_name = llm_provider.config().provider_name().to_string();
 
         // Capture raw LLM content before decode for metadata
         let raw_llm_content = llm_response.content.clone();
@@ -2474,6 +5432,7 @@ impl WorkflowExecutor {
             // When GR active: user_input = masked prompt, final_output = raw LLM content
             // When GR inactive: user_input = original prompt, final_output = decoded content
             "user_input": if guardrail_enforcer.is_some() { masked_input_for_meta.clone() } else { metadata_input.to_string() },
+            "system_prompt": system_prompt,
             "tools_available": tool_names,
             "total_tools_available": tool_names.len(),
             "start_time": execution_timestamp.to_rfc3339(),
@@ -2504,6 +5463,7 @@ impl WorkflowExecutor {
             "total_tool_calls": 0,
             "total_retries": 0,
             "tools_used": [],
+            "context_window": context_fit,
             "executions": executions
         });
 
@@ -2566,6 +5526,37 @@ impl WorkflowExecutor {
                 }
             }
 
+            // Tools registered with the executor and handoffs run in-process; anything
+            // else is handed back to the Python layer below
+            if let Some(base_request) = base_request {
+                let handoff_only_runtime = NativeToolRuntime {
+                    registry: ToolRegistry::new(),
+                    strict_tool_args: false,
+                    trace: ToolCallTraceConfig::default(),
+                };
+                let runtime = tool_runtime.unwrap_or(&handoff_only_runtime);
+                if llm_response.tool_calls.iter().all(|tc| {
+                    runtime.registry.has_tool(&tc.name)
+                        || (handoff.is_some() && tc.name == crate::tools::HANDOFF_TOOL_NAME)
+                }) {
+                    let scope = ToolLoopScope {
+                        runtime,
+                        llm_provider,
+                        node_id,
+                        node_name,
+                        max_iterations,
+                        context_window,
+                        history_window,
+                        guardrail_enforcer: guardrail_enforcer.as_deref(),
+                        event_tx: event_tx.as_ref(),
+                        stream_mode,
+                        handoff,
+                    };
+                    return Self::run_native_tool_loop(&scope, base_request, llm_response, context)
+                        .await;
+                }
+            }
+
             // Instead of executing tools in Rust, return a structured response that indicates
             // tool calls need to be executed by the Python layer
             let tool_calls_json = serde_json::to_value(&llm_response.tool_calls).map_err(|e| {
@@ -2602,16 +5593,93 @@ impl WorkflowExecutor {
         }
     }
 
-    /// Execute a document loader node (static version)
+    /// Drive a streaming LLM call, forwarding its chunks as stream events.
+    ///
+    /// Every text chunk is sent as a [`crate::stream::StreamEvent::Token`]. Chunks that
+    /// carry no tool calls are also sent as [`crate::stream::StreamEvent::NodeOutputDelta`],
+    /// so tool-call fragments stay buffered until the assembled response is returned.
+    /// Falls back to `complete()` when the stream yields no chunks.
+    async fn stream_llm_response(
+        llm_provider: &crate::llm::LlmProvider,
+        request: crate::llm::LlmRequest,
+        event_tx: &tokio::sync::mpsc::Sender<crate::stream::StreamEvent>,
+        node_id: &str,
+        node_name: &str,
+        llm_call_id: &str,
+    ) -> GraphBitResult<crate::llm::LlmResponse> {
+        use crate::stream::StreamEvent;
+        use futures::StreamExt;
+
+        // Pre-clone the request for the empty-stream fallback before moving into stream()
+        let fallback_request = request.clone();
+        let mut stream = llm_provider.stream(request).await?;
+
+        // Accumulate content and keep the last full LlmResponse for metadata
+        let mut accumulated_content = String::new();
+        let mut last_response: Option<crate::llm::LlmResponse> = None;
+
+        while let Some(chunk_result) = stream.next().await {
+            let chunk = chunk_result?;
+
+            // Only emit non-empty content chunks
+            if !chunk.content.is_empty() {
+                let _ = event_tx
+                    .send(StreamEvent::Token {
+                        node_id: node_id.to_string(),
+                        node_name: node_name.to_string(),
+                        llm_call_id: llm_call_id.to_string(),
+                        content: chunk.content.clone(),
+                    })
+                    .await;
+                if chunk.tool_calls.is_empty() {
+                    let _ = event_tx
+                        .send(StreamEvent::NodeOutputDelta {
+                            node_id: node_id.to_string(),
+                            node_name: node_name.to_string(),
+                            delta: chunk.content.clone(),
+                        })
+                        .await;
+                }
+                accumulated_content.push_str(&chunk.content);
+            }
+            last_response = Some(chunk);
+        }
+
+        // Build a complete LlmResponse from the accumulated stream
+        let Some(mut final_resp) = last_response else {
+            tracing::warn!(
+                node_id = %node_id,
+                "LLM stream returned 0 chunks; falling back to complete()"
+            );
+            return llm_provider.complete(fallback_request).await;
+        };
+        // Streamed text tokens; keep provider `content` when no deltas had text
+        // (e.g. tool-only streams still set placeholder + tool_calls on the last chunk).
+        if !accumulated_content.is_empty() {
+            final_resp.content = accumulated_content;
+        }
+        Ok(final_resp)
+    }
+
+    /// Execute a document loader node (static version). A file loaded from
+    /// disk is also attached to the node as it is.
     async fn execute_document_loader_node_static(
+        node_name: &str,
         document_type: &str,
         source_path: &str,
-        _context: Arc<Mutex<WorkflowContext>>,
+        context: Arc<Mutex<WorkflowContext>>,
     ) -> GraphBitResult<serde_json::Value> {
         let loader = DocumentLoader::new();
 
         match loader.load_document(source_path, document_type).await {
             Ok(document_content) => {
+                if !source_path.contains("://") {
+                    let attachment = Attachment::from_file(source_path)?;
+                    context
+                        .lock()
+                        .await
+                        .set_attachments(node_name, vec![attachment])?;
+                }
                 // Return the document content as JSON
                 let content_json = serde_json::json!({
                     "source": document_content.source,
@@ -2629,6 +5697,462 @@ impl WorkflowExecutor {
         }
     }
 
+    /// Apply a node's post-processors to its output, keeping the output from
+    /// before them in the node's payload capture
+    async fn post_process_output(
+        node: &WorkflowNode,
+        output: serde_json::Value,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> GraphBitResult<serde_json::Value> {
+        let processors = PostProcessor::from_node_config(&node.config)?;
+        if processors.is_empty() {
+            return Ok(output);
+        }
+        crate::capture::record_raw_output(&output);
+        let ctx = context.lock().await;
+        let resolve =
+            |template: &str| Self::resolve_node_template_variables(template, &ctx, Some(&node.id));
+        crate::post_process::apply_all(&processors, output, &resolve).map_err(|e| {
+            let detail = match e {
+                GraphBitError::Validation { field, message } => format!("{field}: {message}"),
+                other => other.to_string(),
+            };
+            GraphBitError::workflow_execution(format!(
+                "Post-processing the output of node '{}' failed: {detail}",
+                node.name
+            ))
+        })
+    }
+
+    /// Store a node's output under its id and name, spilling it once if it is over
+    /// the spill threshold.
+    ///
+    /// The output is also stored as a JSON string in the variables named after
+    /// the node, for backward compatibility with `extract_output()`; a spilled
+    /// output is stored there as its reference instead.
+    fn store_node_output(
+        ctx: &mut WorkflowContext,
+        node: &WorkflowNode,
+        output: serde_json::Value,
+    ) {
+        let stored = ctx.spill_output(output);
+        let variable = if OutputRef::from_value(&stored).is_some() {
+            Some(stored.clone())
+        } else {
+            serde_json::to_string(&stored)
+                .ok()
+                .map(serde_json::Value::String)
+        };
+        if let Some(variable) = variable {
+            ctx.set_variable(node.name.clone(), variable.clone());
+            ctx.set_variable(node.id.to_string(), variable);
+        }
+        // Both keys share the one stored value
+        let stored = Arc::new(stored);
+        ctx.node_outputs
+            .insert(node.id.to_string(), Arc::clone(&stored));
+        ctx.node_outputs.insert(node.name.clone(), stored);
+    }
+
+    /// Outputs of a node's parents keyed by parent name, after any edge
+    /// transforms, skipping parents that produced no output (e.g. skipped branches)
+    async fn parent_outputs(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        graph: &WorkflowGraph,
+    ) -> GraphBitResult<Vec<(String, serde_json::Value)>> {
+        let ctx = context.lock().await;
+        Self::collect_parent_outputs(node, &ctx, parents_map, graph)
+    }
+
+    /// Outputs of a node's parents keyed by parent name, read from `ctx`
+    fn collect_parent_outputs(
+        node: &WorkflowNode,
+        ctx: &WorkflowContext,
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        graph: &WorkflowGraph,
+    ) -> GraphBitResult<Vec<(String, serde_json::Value)>> {
+        let parents = parents_map.get(&node.id).map_or(&[][..], Vec::as_slice);
+        let mut outputs = Vec::with_capacity(parents.len());
+        for parent_id in parents {
+            let output = match ctx.load_node_output(&parent_id.to_string()) {
+                Some(output) => Self::apply_edge_transform(
+                    graph,
+                    ctx,
+                    parent_id,
+                    &node.id,
+                    output.into_owned(),
+                )?,
+                // A parent whose task failed stored no output, but its
+                // on-failure edges carry the failure
+                None => match ctx.get_edge_output(&parent_id.to_string(), &node.id) {
+                    Some(failure) => failure.clone(),
+                    None => continue,
+                },
+            };
+            let name = graph
+                .get_node(parent_id)
+                .map_or_else(|| parent_id.to_string(), |parent| parent.name.clone());
+            outputs.push((name, output));
+        }
+        Ok(outputs)
+    }
+
+    /// Output of a join node: its parent outputs keyed by parent name. A join with
+    /// an `any` or `quorum` policy keeps only the parents that succeeded, and
+    /// fails when fewer succeeded than its policy requires.
+    async fn join_output(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        graph: &WorkflowGraph,
+    ) -> GraphBitResult<serde_json::Value> {
+        let outputs = Self::parent_outputs(node, context, parents_map, graph).await?;
+        let Some(required) = JoinPolicy::from_node_config(&node.config)?.required() else {
+            return Ok(serde_json::Value::Object(outputs.into_iter().collect()));
+        };
+        let ctx = context.lock().await;
+        let contributed: serde_json::Map<String, serde_json::Value> = outputs
+            .into_iter()
+            .filter(|(name, _)| {
+                ctx.node_executions
+                    .iter()
+                    .any(|record| &record.node_name == name && record.success)
+            })
+            .collect();
+        drop(ctx);
+        if contributed.len() < required {
+            return Err(GraphBitError::workflow_execution(format!(
+                "Join node '{}' needs {required} successful parents, {} succeeded",
+                node.name,
+                contributed.len()
+            )));
+        }
+        Ok(serde_json::Value::Object(contributed))
+    }
+
+    /// Input of a pass-through node: the output of its single parent, an object
+    /// of parent outputs keyed by name when it has several, or null for none
+    async fn node_input(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        graph: &WorkflowGraph,
+    ) -> GraphBitResult<serde_json::Value> {
+        let outputs = Self::parent_outputs(node, context, parents_map, graph).await?;
+        Ok(Self::combined_input(outputs))
+    }
+
+    /// Single parent output, an object of parent outputs keyed by name, or null
+    fn combined_input(mut outputs: Vec<(String, serde_json::Value)>) -> serde_json::Value {
+        match outputs.len() {
+            0 => serde_json::Value::Null,
+            1 => outputs.remove(0).1,
+            _ => serde_json::Value::Object(outputs.into_iter().collect()),
+        }
+    }
+
+    /// Scope for expressions evaluated during a run: `input` is the given
+    /// value, `nodes` the outputs so far and `vars` the workflow variables
+    fn expression_scope(ctx: &WorkflowContext, input: serde_json::Value) -> ExpressionScope {
+        ExpressionScope::new(input)
+            .with_nodes(ctx.load_node_outputs())
+            .with_variables(ctx.variables.clone())
+    }
+
+    /// Apply the transform of the edge `from -> to`, if it has one, to a value
+    /// flowing through it
+    ///
+    /// Uses the value stored when the source node completed, if there is one.
+    fn apply_edge_transform(
+        graph: &WorkflowGraph,
+        ctx: &WorkflowContext,
+        from: &NodeId,
+        to: &NodeId,
+        value: serde_json::Value,
+    ) -> GraphBitResult<serde_json::Value> {
+        if let Some(stored) = ctx.get_edge_output(&from.to_string(), to) {
+            return Ok(stored.clone());
+        }
+        Self::evaluate_edge_transform(graph, ctx, from, to, value)
+    }
+
+    /// Evaluate the transform of the edge `from -> to`, if it has one, on `value`
+    fn evaluate_edge_transform(
+        graph: &WorkflowGraph,
+        ctx: &WorkflowContext,
+        from: &NodeId,
+        to: &NodeId,
+        value: serde_json::Value,
+    ) -> GraphBitResult<serde_json::Value> {
+        let Some(transform) = graph
+            .get_edge(from, to)
+            .and_then(|e| e.transform.as_deref())
+        else {
+            return Ok(value);
+        };
+        Expression::parse(transform)
+            .and_then(|expression| expression.evaluate(&Self::expression_scope(ctx, value)))
+            .map_err(|e| {
+                let name = |id: &NodeId| {
+                    graph
+                        .get_node(id)
+                        .map_or_else(|| id.to_string(), |node| node.name.clone())
+                };
+                GraphBitError::workflow_execution(format!(
+                    "Edge '{}' -> '{}': transform failed: {e}",
+                    name(from),
+                    name(to)
+                ))
+            })
+    }
+
+    /// Apply the transforms of `node`'s enabled outgoing edges to its output and
+    /// store the results in the context, leaving its own output untransformed.
+    ///
+    /// A transform that fails stores nothing; the target node reports the error
+    /// when it reads its input.
+    fn store_edge_outputs(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        output: &serde_json::Value,
+        ctx: &mut WorkflowContext,
+        disabled_edges: &HashSet<(NodeId, NodeId)>,
+    ) {
+        for (from, to, edge) in graph.get_edges() {
+            if from != &node.id
+                || edge.transform.is_none()
+                || disabled_edges.contains(&(from.clone(), to.clone()))
+            {
+                continue;
+            }
+            match Self::evaluate_edge_transform(graph, ctx, from, to, output.clone()) {
+                Ok(value) => ctx.set_edge_output(from, &node.name, to, value),
+                Err(e) => {
+                    ctx.remove_edge_output(from, &node.name, to);
+                    tracing::debug!(error = %e, "Edge transform deferred to the target node");
+                }
+            }
+        }
+    }
+
+    /// Evaluate the conditions of `node`'s outgoing conditional edges against
+    /// its output, recording the edges whose condition is false
+    fn evaluate_conditional_edges(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        output: &serde_json::Value,
+        ctx: &WorkflowContext,
+        disabled_edges: &mut HashSet<(NodeId, NodeId)>,
+    ) -> GraphBitResult<()> {
+        for (from, to, edge) in graph.get_edges() {
+            if from != &node.id || !matches!(edge.edge_type, EdgeType::Conditional) {
+                continue;
+            }
+            let Some(condition) = edge.condition.as_deref() else {
+                continue;
+            };
+            let holds = Expression::parse(condition)
+                .and_then(|expression| {
+                    expression.evaluate_bool(&Self::expression_scope(ctx, output.clone()))
+                })
+                .map_err(|e| {
+                    let target = graph
+                        .get_node(to)
+                        .map_or_else(|| to.to_string(), |target| target.name.clone());
+                    GraphBitError::workflow_execution(format!(
+                        "Edge '{}' -> '{target}': condition failed: {e}",
+                        node.name
+                    ))
+                })?;
+            if !holds {
+                disabled_edges.insert((from.clone(), to.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Disable the violation edges of `node` unless it is a routing guardrail
+    /// whose `report` found a violation, in which case its other edges are
+    /// disabled instead
+    fn route_violation_edges(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        report: Option<&GuardrailReport>,
+        disabled_edges: &mut HashSet<(NodeId, NodeId)>,
+    ) {
+        let violated = report
+            .is_some_and(|report| report.action == GuardrailAction::Route && !report.is_clean());
+        for (from, to, edge) in graph.get_edges() {
+            if from != &node.id {
+                continue;
+            }
+            if matches!(edge.edge_type, EdgeType::Violation) != violated {
+                disabled_edges.insert((from.clone(), to.clone()));
+            }
+        }
+    }
+
+    /// Disable the timeout edges of `node` unless its event `timed_out`, in
+    /// which case its other edges are disabled instead
+    fn route_timeout_edges(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        timed_out: bool,
+        disabled_edges: &mut HashSet<(NodeId, NodeId)>,
+    ) {
+        for (from, to, edge) in graph.get_edges() {
+            if from == &node.id && matches!(edge.edge_type, EdgeType::Timeout) != timed_out {
+                disabled_edges.insert((from.clone(), to.clone()));
+            }
+        }
+    }
+
+    /// Skip nodes whose parents have all settled and which receive nothing:
+    /// every parent was skipped or reaches the node through a disabled edge
+    fn expand_skips_from_disabled_edges(
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        disabled_edges: &HashSet<(NodeId, NodeId)>,
+        resolved: &HashSet<NodeId>,
+        skipped: &mut HashSet<NodeId>,
+    ) {
+        loop {
+            let newly_skipped: Vec<NodeId> = parents_map
+                .iter()
+                .filter(|(id, parents)| {
+                    !parents.is_empty()
+                        && !resolved.contains(*id)
+                        && !skipped.contains(*id)
+                        && parents.iter().all(|p| {
+                            skipped.contains(p)
+                                || (resolved.contains(p)
+                                    && disabled_edges.contains(&(p.clone(), (*id).clone())))
+                        })
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            if newly_skipped.is_empty() {
+                return;
+            }
+            skipped.extend(newly_skipped);
+        }
+    }
+
+    /// Settle a node the tag filter excludes, or whose parent it skipped, without
+    /// running it. An excluded node tagged `passthrough_on_skip` whose parents
+    /// were not skipped by the filter stores its input as its output; any other
+    /// node is skipped and added to `tag_skipped`. Both are recorded on the
+    /// context as skipped by tag.
+    fn settle_excluded_node(
+        node: &WorkflowNode,
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        graph: &WorkflowGraph,
+        context: &mut WorkflowContext,
+        tag_skipped: &mut HashSet<NodeId>,
+    ) {
+        let upstream_skipped = parents_map
+            .get(&node.id)
+            .is_some_and(|parents| parents.iter().any(|p| tag_skipped.contains(p)));
+        let passthrough = if node.passes_through_on_skip() && !upstream_skipped {
+            match Self::collect_parent_outputs(node, context, parents_map, graph) {
+                Ok(outputs) => Some(Self::combined_input(outputs)),
+                Err(e) => {
+                    tracing::warn!(node_id = %node.id, "Skipping node whose input could not be passed through: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        context.abandon_node(
+            &node.id,
+            &node.name,
+            AbandonedNode::Skipped {
+                reason: SkipReason::Tag,
+            },
+        );
+        if let Some(input) = passthrough {
+            tracing::info!(node_id = %node.id, "Passing input through node excluded by tag");
+            Self::store_node_output(context, node, input);
+        } else {
+            tracing::info!(node_id = %node.id, "Skipping node excluded by tag");
+            tag_skipped.insert(node.id.clone());
+        }
+    }
+
+    /// Skip the unfinished nodes with a failed or blocked parent, recording on
+    /// the context the failed nodes blocking each of them. Joins with an `any`
+    /// or `quorum` policy are left to run with their other parents.
+    fn block_failed_dependents(
+        graph: &WorkflowGraph,
+        parents_map: &HashMap<NodeId, Vec<NodeId>>,
+        failed: &[NodeId],
+        exempt: &HashSet<&NodeId>,
+        resolved: &HashSet<NodeId>,
+        skipped: &mut HashSet<NodeId>,
+        context: &mut WorkflowContext,
+    ) {
+        let name = |id: &NodeId| {
+            graph
+                .get_node(id)
+                .map_or_else(|| id.to_string(), |node| node.name.clone())
+        };
+        loop {
+            let newly_blocked: Vec<(NodeId, Vec<String>)> = parents_map
+                .iter()
+                .filter(|(id, _)| {
+                    !resolved.contains(*id) && !skipped.contains(*id) && !exempt.contains(id)
+                })
+                .filter_map(|(id, parents)| {
+                    let mut blocked_by = std::collections::BTreeSet::new();
+                    for parent in parents {
+                        if failed.contains(parent) {
+                            // Compensation nodes run because their parent failed
+                            let compensates = graph.get_edge(parent, id).is_some_and(|edge| {
+                                matches!(edge.edge_type, EdgeType::ErrorHandling)
+                            });
+                            if !compensates {
+                                blocked_by.insert(name(parent));
+                            }
+                        } else if skipped.contains(parent) {
+                            if let Some(AbandonedNode::Blocked {
+                                blocked_by: blockers,
+                            }) = context.abandoned_nodes.get(&name(parent))
+                            {
+                                blocked_by.extend(blockers.iter().cloned());
+                            }
+                        }
+                    }
+                    (!blocked_by.is_empty()).then(|| (id.clone(), blocked_by.into_iter().collect()))
+                })
+                .collect();
+            if newly_blocked.is_empty() {
+                return;
+            }
+            for (id, blocked_by) in newly_blocked {
+                tracing::info!(node_id = %id, ?blocked_by, "Skipping node blocked by failed nodes");
+                context.abandon_node(&id, &name(&id), AbandonedNode::Blocked { blocked_by });
+                skipped.insert(id);
+            }
+        }
+    }
+
+    /// Evaluate a transform node's `transformation` expression with the node
+    /// input bound to `input`
+    fn apply_transformation(
+        node_name: &str,
+        transformation: &str,
+        input: serde_json::Value,
+        ctx: &WorkflowContext,
+    ) -> GraphBitResult<serde_json::Value> {
+        Expression::parse(transformation_source(transformation))
+            .and_then(|expression| expression.evaluate(&Self::expression_scope(ctx, input)))
+            .map_err(|e| {
+                GraphBitError::workflow_execution(format!("Transform node '{node_name}': {e}"))
+            })
+    }
+
     /// Execute concurrent tasks with retry logic
     pub async fn execute_concurrent_tasks_with_retry<T, F, R>(
         &self,
@@ -2658,8 +6182,9 @@ impl WorkflowExecutor {
                 let max_execution_time = self.max_node_execution_time_ms;
                 let retry_config = retry_config.clone();
                 let concurrency_manager = self.concurrency_manager.clone();
+                let clock = self.clock.clone();
 
-                tokio::spawn(async move {
+                tokio::spawn(crate::clock::scope(clock, async move {
                     // Create task info for generic concurrent tasks
                     let task_info = TaskInfo {
                         node_type: "concurrent_task".to_string(),
@@ -2679,7 +6204,7 @@ impl WorkflowExecutor {
                     // Execute task with retry logic
                     Self::execute_task_with_retry(task, task_fn, retry_config, max_execution_time)
                         .await
-                })
+                }))
             })
             .collect();
 
@@ -2748,7 +6273,7 @@ impl WorkflowExecutor {
                             // Calculate delay for this attempt
                             let delay_ms = config.calculate_delay(attempt - 1);
                             if delay_ms > 0 {
-                                tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms))
+                                crate::clock::sleep(std::time::Duration::from_millis(delay_ms))
                                     .await;
                             }
 
@@ -2888,6 +6413,13 @@ impl WorkflowExecutor {
         self.concurrency_manager = Arc::new(ConcurrencyManager::new(concurrency_config));
         self
     }
+
+    /// Share a concurrency manager with other executors, so its limits bound all
+    /// of their runs together and its statistics accumulate across them
+    pub fn with_concurrency_manager(mut self, concurrency_manager: Arc<ConcurrencyManager>) -> Self {
+        self.concurrency_manager = concurrency_manager;
+        self
+    }
 }
 
 impl Default for WorkflowExecutor {
@@ -2901,7 +6433,7 @@ fn extract_agent_ids_from_workflow(workflow: &Workflow) -> Vec<String> {
     let mut agent_ids = std::collections::HashSet::new();
 
     for node in workflow.graph.get_nodes().values() {
-        if let NodeType::Agent { config } = &node.node_type {
+        if let Some(config) = node.node_type.agent_config() {
             agent_ids.insert(config.agent_id.to_string());
         }
     }
@@ -2912,28 +6444,133 @@ fn extract_agent_ids_from_workflow(workflow: &Workflow) -> Vec<String> {
 impl WorkflowExecutor {
     /// Resolve template variables in a string, supporting both node references and regular variables
     pub fn resolve_template_variables(template: &str, context: &WorkflowContext) -> String {
+        Self::resolve_node_template_variables(template, context, None)
+    }
+
+    /// Like [`Self::resolve_template_variables`] for a template of the node
+    /// `target`, where references to a parent across a transformed edge resolve
+    /// to the transformed value
+    fn resolve_node_template_variables(
+        template: &str,
+        context: &WorkflowContext,
+        target: Option<&NodeId>,
+    ) -> String {
+        Self::resolve_escaped_template_variables(template, context, target, &mut str::to_string)
+    }
+
+    /// Like [`Self::resolve_node_template_variables`], passing every substituted
+    /// value through `escape`
+    fn resolve_escaped_template_variables(
+        template: &str,
+        context: &WorkflowContext,
+        target: Option<&NodeId>,
+        escape: &mut dyn FnMut(&str) -> String,
+    ) -> String {
         let mut result = template.to_string();
 
+        // Replace attachment references like {{nodes.loader.attachments[0]}} with
+        // the path of a local file holding the attachment
+        for cap in ATTACHMENT_REF_PATTERN.captures_iter(template) {
+            let Some(attachment) = Self::referenced_attachment(context, &cap) else {
+                continue;
+            };
+            match context.attachment_path(attachment) {
+                Ok(path) => {
+                    result = result.replace(&cap[0], &escape(&path.to_string_lossy()));
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to write attachment {} to a local file: {e}",
+                    attachment.id
+                ),
+            }
+        }
+
         // Replace node references like {{node.node_id}} or {{node.node_id.property}}
         for cap in NODE_REF_PATTERN.captures_iter(template) {
             if let Some(reference) = cap.get(1) {
                 let reference = reference.as_str();
-                if let Some(value) = context.get_nested_output(reference) {
-                    let value_str = match value {
+                let value = target
+                    .and_then(|target| context.get_nested_edge_output(reference, target))
+                    .map(Cow::Borrowed)
+                    .or_else(|| context.load_nested_output(reference));
+                if let Some(value) = value {
+                    let value_str = match value.as_ref() {
                         serde_json::Value::String(s) => s.clone(),
                         _ => value.to_string().trim_matches('"').to_string(),
                     };
-                    result = result.replace(&cap[0], &value_str);
+                    result = result.replace(&cap[0], &escape(&value_str));
+                }
+            }
+        }
+
+        // Replace run-time inputs like {{input.question}} or {{input.document.path}}
+        for cap in INPUT_REF_PATTERN.captures_iter(template) {
+            if let Some(value) = context.get_nested_variable(&cap[1]) {
+                let value_str = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    _ => value.to_string(),
+                };
+                result = result.replace(&cap[0], &escape(&value_str));
+            }
+        }
+
+        // Replace globals like {{globals.company_name}}, the node's own first
+        for cap in GLOBALS_REF_PATTERN.captures_iter(template) {
+            if let Some(value) = context.get_nested_global(&cap[1], target) {
+                let value_str = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    _ => value.to_string(),
+                };
+                result = result.replace(&cap[0], &escape(&value_str));
+            }
+        }
+
+        // Replace the {{index}} and {{item}} of the split branch the node runs in
+        let node_name = target.and_then(|target| context.node_name(target));
+        if let Some(branch) = node_name.and_then(|name| context.node_branch(name)) {
+            result = result.replace("{{index}}", &branch.index.to_string());
+            let item = context.branch_item(branch);
+            for cap in ITEM_REF_PATTERN.captures_iter(template) {
+                let parts = cap[1].split('.').filter(|part| !part.is_empty());
+                let value = parts.fold(item.as_ref(), |value, part| match value? {
+                    serde_json::Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+                    value => value.get(part),
+                });
+                if let Some(value) = value {
+                    let value_str = match value {
+                        serde_json::Value::String(s) => s.clone(),
+                        _ => value.to_string(),
+                    };
+                    result = result.replace(&cap[0], &escape(&value_str));
                 }
             }
         }
 
+        // Replace values tools wrote like {{scratch.summary}}, those of the
+        // node's split branch first
+        for cap in SCRATCH_REF_PATTERN.captures_iter(template) {
+            let value = node_name.map_or_else(
+                || context.get_scratch(&cap[1]),
+                |name| context.get_node_scratch(name, &cap[1]),
+            );
+            if let Some(value) = value {
+                let value_str = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    _ => value.to_string(),
+                };
+                result = result.replace(&cap[0], &escape(&value_str));
+            }
+        }
+
         // Replace simple variables for backward compatibility
         for (key, value) in &context.variables {
             let placeholder = format!("{{{key}}}");
-            if let Ok(value_str) = serde_json::to_string(value) {
+            if !result.contains(&placeholder) {
+                continue;
+            }
+            if let Ok(value_str) = serde_json::to_string(&context.resolve_output(value)) {
                 let value_str = value_str.trim_matches('"');
-                result = result.replace(&placeholder, value_str);
+                result = result.replace(&placeholder, &escape(value_str));
             }
         }
 
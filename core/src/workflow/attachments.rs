@@ -0,0 +1,178 @@
+//! Attachments of nodes referenced from prompts and HTTP request nodes
+//!
+//! A reference like `{{nodes.loader.attachments[0]}}` in an agent prompt sends
+//! the image it names with the prompt; in the `multipart` config of an HTTP
+//! request node it uploads the attachment as a file.
+
+use super::{ATTACHMENT_REF_PATTERN, WorkflowExecutor};
+use crate::attachment::Attachment;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::graph::WorkflowNode;
+use crate::llm::LlmImage;
+use crate::types::WorkflowContext;
+
+/// Field of the multipart body of an HTTP request node
+pub(super) enum MultipartField {
+    /// Text value
+    Text(String),
+    /// Upload of an attachment
+    File {
+        bytes: Vec<u8>,
+        mime_type: String,
+        filename: String,
+    },
+}
+
+impl WorkflowExecutor {
+    /// Fields of the multipart body of an HTTP request node, from its
+    /// `multipart` config entry: a value that is a reference like
+    /// `{{nodes.loader.attachments[0]}}` uploads the attachment as a file,
+    /// other strings are sent as they are and other values as JSON
+    pub(super) fn http_multipart_fields(
+        node: &WorkflowNode,
+        ctx: &WorkflowContext,
+    ) -> GraphBitResult<Option<Vec<(String, MultipartField)>>> {
+        let Some(fields) = node.config.get("multipart") else {
+            return Ok(None);
+        };
+        let invalid = |message: String| {
+            GraphBitError::workflow_execution(format!(
+                "HTTP request node '{}': {message}",
+                node.name
+            ))
+        };
+        let fields = fields
+            .as_object()
+            .ok_or_else(|| invalid("multipart must be an object of fields".to_string()))?;
+        if !matches!(
+            node.config.get("body"),
+            None | Some(serde_json::Value::Null)
+        ) {
+            return Err(invalid(
+                "set either body or multipart, not both".to_string(),
+            ));
+        }
+        let mut resolved = Vec::with_capacity(fields.len());
+        for (name, value) in fields {
+            let reference = value.as_str().and_then(|text| {
+                ATTACHMENT_REF_PATTERN
+                    .captures(text)
+                    .filter(|cap| &cap[0] == text.trim())
+            });
+            let field = match (reference, value) {
+                (Some(cap), _) => {
+                    let attachment = Self::referenced_attachment(ctx, &cap).ok_or_else(|| {
+                        invalid(format!(
+                            "multipart field '{name}' references {}, but node '{}' has no such attachment",
+                            &cap[0], &cap[1]
+                        ))
+                    })?;
+                    MultipartField::File {
+                        bytes: ctx.read_attachment(attachment)?,
+                        mime_type: attachment.mime_type.clone(),
+                        filename: attachment.local_file_name(),
+                    }
+                }
+                (None, serde_json::Value::String(text)) => MultipartField::Text(text.clone()),
+                (None, value) => MultipartField::Text(value.to_string()),
+            };
+            resolved.push((name.clone(), field));
+        }
+        Ok(Some(resolved))
+    }
+
+    /// Multipart form of the resolved `multipart` fields of an HTTP request node
+    pub(super) fn multipart_form(
+        node: &WorkflowNode,
+        fields: Vec<(String, MultipartField)>,
+    ) -> GraphBitResult<reqwest::multipart::Form> {
+        let mut form = reqwest::multipart::Form::new();
+        for (name, field) in fields {
+            form = match field {
+                MultipartField::Text(text) => form.text(name, text),
+                MultipartField::File {
+                    bytes,
+                    mime_type,
+                    filename,
+                } => {
+                    let part = reqwest::multipart::Part::bytes(bytes)
+                        .file_name(filename)
+                        .mime_str(&mime_type)
+                        .map_err(|e| {
+                            GraphBitError::workflow_execution(format!(
+                                "HTTP request node '{}': invalid MIME type '{mime_type}': {e}",
+                                node.name
+                            ))
+                        })?;
+                    form.part(name, part)
+                }
+            };
+        }
+        Ok(form)
+    }
+
+    /// The attachment an [`ATTACHMENT_REF_PATTERN`] match refers to, if the node produced it
+    pub(super) fn referenced_attachment<'a>(
+        context: &'a WorkflowContext,
+        cap: &regex::Captures<'_>,
+    ) -> Option<&'a Attachment> {
+        let index: usize = cap[2].parse().ok()?;
+        context.get_attachments(&cap[1]).get(index)
+    }
+
+    /// Take the attachment references out of an agent prompt template: each
+    /// referenced image is sent with the prompt, its reference replaced by a
+    /// marker naming it. Referencing a missing attachment or one that is not
+    /// an image fails.
+    pub(super) fn prompt_images(
+        template: &str,
+        context: &WorkflowContext,
+    ) -> GraphBitResult<(String, Vec<LlmImage>)> {
+        let mut result = template.to_string();
+        let mut images = Vec::new();
+        for cap in ATTACHMENT_REF_PATTERN.captures_iter(template) {
+            // Repeated references share the image sent for the first one
+            if !result.contains(&cap[0]) {
+                continue;
+            }
+            let attachment = Self::referenced_attachment(context, &cap).ok_or_else(|| {
+                GraphBitError::workflow_execution(format!(
+                    "Prompt references {}, but node '{}' has no such attachment",
+                    &cap[0], &cap[1]
+                ))
+            })?;
+            if !attachment.is_image() {
+                return Err(GraphBitError::workflow_execution(format!(
+                    "Prompt references {}, which is {} rather than an image",
+                    &cap[0], attachment.mime_type
+                )));
+            }
+            images.push(LlmImage {
+                mime_type: attachment.mime_type.clone(),
+                data: attachment.read_base64(context.output_spill.as_ref())?,
+            });
+            let name = attachment.filename.as_deref().unwrap_or(&attachment.id);
+            result = result.replace(&cap[0], &format!("[image {}: {name}]", images.len()));
+        }
+        Ok((result, images))
+    }
+
+    /// Fail when images are to be sent to a provider that does not accept them
+    pub(super) fn check_image_inputs(
+        provider: &crate::llm::LlmProvider,
+        images: &[LlmImage],
+    ) -> GraphBitResult<()> {
+        let provider = provider.provider();
+        if images.is_empty() || provider.supports_image_inputs() {
+            return Ok(());
+        }
+        Err(GraphBitError::llm_provider(
+            provider.provider_name(),
+            format!(
+                "model '{}' does not accept image inputs; the prompt references {} image attachment(s)",
+                provider.model_name(),
+                images.len()
+            ),
+        ))
+    }
+}
@@ -0,0 +1,143 @@
+//! On-failure edges, leading from a node to the compensation nodes run when it
+//! fails
+
+use super::{Batch, RunFailure, RunState, WorkflowExecutor};
+use crate::graph::{EdgeType, WorkflowGraph, WorkflowNode};
+use crate::types::{AbandonedNode, NodeId, SkipReason, WorkflowContext};
+use std::collections::{HashMap, HashSet};
+
+impl WorkflowExecutor {
+    /// Start running the compensation nodes of the node whose `failure` stopped
+    /// a fail-fast run, skipping every other unsettled node. Gives the failure
+    /// back when there is nothing to compensate: the node has no on-failure
+    /// edges to follow, or compensation nodes are already running.
+    pub(super) async fn begin_compensation(
+        graph: &WorkflowGraph,
+        failure: RunFailure,
+        batch: &mut Batch,
+        state: &mut RunState,
+    ) -> Result<(), RunFailure> {
+        let Some(failed_id) = failure.node.clone().filter(|id| {
+            state.compensating.is_none()
+                && Self::follows_failure_edges(graph, id, &state.disabled_edges)
+        }) else {
+            return Err(failure);
+        };
+        if !state.resolved.contains(&failed_id) {
+            state.total_executed += 1;
+            Self::record_task_failure(
+                &batch.context,
+                graph,
+                &failed_id,
+                failure.message.clone(),
+                state.step,
+            )
+            .await;
+            state.resolved.insert(failed_id.clone());
+        }
+        let compensation = graph.compensation_nodes_of(&failed_id);
+        for id in graph.get_nodes().keys() {
+            if state.is_done(id) || compensation.contains(id) {
+                continue;
+            }
+            state.skipped.insert(id.clone());
+            // Batch nodes still running when the node failed did run
+            if !batch.ids.contains(&id.to_string()) {
+                batch.abandoned.insert(
+                    id.clone(),
+                    AbandonedNode::Skipped {
+                        reason: SkipReason::FailFast,
+                    },
+                );
+            }
+        }
+        tracing::info!(node_id = %failed_id, "Running compensation nodes before failing");
+        state.failed.push(failed_id);
+        state.compensating = Some(failure.message);
+        Ok(())
+    }
+
+    /// Disable the on-failure edges of `node`, which succeeded
+    pub(super) fn disable_failure_edges(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        disabled_edges: &mut HashSet<(NodeId, NodeId)>,
+    ) {
+        for (from, to, edge) in graph.get_edges() {
+            if from == &node.id && matches!(edge.edge_type, EdgeType::ErrorHandling) {
+                disabled_edges.insert((from.clone(), to.clone()));
+            }
+        }
+    }
+
+    /// Whether `node_id` has on-failure edges that are followed
+    pub(super) fn follows_failure_edges(
+        graph: &WorkflowGraph,
+        node_id: &NodeId,
+        disabled_edges: &HashSet<(NodeId, NodeId)>,
+    ) -> bool {
+        graph.get_edges().iter().any(|(from, to, edge)| {
+            from == node_id
+                && matches!(edge.edge_type, EdgeType::ErrorHandling)
+                && !disabled_edges.contains(&(from.clone(), to.clone()))
+        })
+    }
+
+    /// On-failure edges past `max_depth`: those a chain of failing nodes would
+    /// reach only by following more than `max_depth` on-failure edges
+    pub(super) fn failure_edges_beyond_depth(
+        graph: &WorkflowGraph,
+        max_depth: usize,
+    ) -> Vec<(NodeId, NodeId)> {
+        let Ok(order) = graph.topological_sort() else {
+            return Vec::new();
+        };
+        // On-failure edges followed on the longest way into each node
+        let mut depths: HashMap<NodeId, usize> = HashMap::with_capacity(order.len());
+        let mut beyond = Vec::new();
+        for id in order {
+            let mut depth = 0;
+            for (from, _, edge) in graph.get_edges().iter().filter(|(_, to, _)| to == &id) {
+                let mut via = depths.get(from).copied().unwrap_or(0);
+                if matches!(edge.edge_type, EdgeType::ErrorHandling) {
+                    via += 1;
+                    if via > max_depth {
+                        beyond.push((from.clone(), id.clone()));
+                    }
+                }
+                depth = depth.max(via);
+            }
+            depths.insert(id, depth);
+        }
+        if !beyond.is_empty() {
+            tracing::warn!(
+                max_depth,
+                edges = beyond.len(),
+                "On-failure edges past the maximum compensation depth will not be followed"
+            );
+        }
+        beyond
+    }
+
+    /// Pass the failure of `node` along its on-failure edges, as an object with
+    /// the failed `node` and its `error`, after any edge transform
+    pub(super) fn store_failure_edge_outputs(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        error: &str,
+        ctx: &mut WorkflowContext,
+    ) {
+        for (from, to, edge) in graph.get_edges() {
+            if from != &node.id || !matches!(edge.edge_type, EdgeType::ErrorHandling) {
+                continue;
+            }
+            let failure = serde_json::json!({ "node": node.name, "error": error });
+            let value = Self::evaluate_edge_transform(graph, ctx, from, to, failure.clone())
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "On-failure edge transform failed; passing the failure on as is");
+                    failure
+                });
+            ctx.set_edge_output(from, &node.name, to, value);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Handoffs from supervisor agent nodes to the worker agent nodes they name in
+//! `handoff_targets`
+
+use super::{RunState, WorkflowExecutor};
+use crate::agents::AgentTrait;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::graph::{NodeType, WorkflowGraph};
+use crate::types::{NodeId, WorkflowContext};
+use crate::{DecodeContext, EncodeContext, Enforcer};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// Graph and agents a supervisor node needs to run its handoff targets
+#[derive(Clone, Copy)]
+pub(super) struct HandoffScope<'a> {
+    pub(super) targets: &'a [String],
+    pub(super) graph: &'a WorkflowGraph,
+    pub(super) agents: &'a RwLock<HashMap<crate::types::AgentId, Arc<dyn AgentTrait>>>,
+}
+
+impl WorkflowExecutor {
+    /// Supervisors of each handoff target of `graph`
+    pub(super) fn handoff_sources(graph: &WorkflowGraph) -> HashMap<NodeId, Vec<NodeId>> {
+        let mut handoff_sources: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for node in graph.get_nodes().values() {
+            for target in node.handoff_targets() {
+                if let Some(target_node) = graph.get_nodes().values().find(|n| n.name == target) {
+                    handoff_sources
+                        .entry(target_node.id.clone())
+                        .or_default()
+                        .push(node.id.clone());
+                }
+            }
+        }
+        handoff_sources
+    }
+
+    /// Settle the handoff targets whose supervisors all settled: resolved when
+    /// one of the supervisors ran, skipped otherwise
+    pub(super) fn settle_handoff_targets(
+        handoff_sources: &HashMap<NodeId, Vec<NodeId>>,
+        state: &mut RunState,
+    ) {
+        for (target, sources) in handoff_sources {
+            if state.is_done(target) {
+                continue;
+            }
+            if sources.iter().all(|s| state.is_done(s)) {
+                if sources.iter().any(|s| state.resolved.contains(s)) {
+                    state.resolved.insert(target.clone());
+                } else {
+                    state.skipped.insert(target.clone());
+                }
+            }
+        }
+    }
+
+    /// Run a handoff target for a supervisor's `handoff` tool call.
+    ///
+    /// The target agent receives the supervisor's conversation so far followed by its
+    /// own prompt and the handoff message. Its answer is stored as the target node's
+    /// output, the carried-over transcript is recorded in the context metadata under
+    /// `handoff_{target}`, and the answer is returned as the tool result.
+    pub(super) async fn run_handoff(
+        scope: HandoffScope<'_>,
+        supervisor_name: &str,
+        conversation: &[crate::llm::LlmMessage],
+        handoff: crate::tools::HandoffRequest,
+        context: &Arc<Mutex<WorkflowContext>>,
+        guardrail_enforcer: Option<&Enforcer>,
+    ) -> GraphBitResult<String> {
+        use crate::llm::{LlmMessage, LlmRequest};
+
+        let target = scope
+            .graph
+            .get_nodes()
+            .values()
+            .find(|n| n.name == handoff.target)
+            .ok_or_else(|| {
+                GraphBitError::workflow_execution(format!(
+                    "Handoff target '{}' not found",
+                    handoff.target
+                ))
+            })?;
+        let NodeType::Agent { config } = &target.node_type else {
+            return Err(GraphBitError::workflow_execution(format!(
+                "Handoff target '{}' is not an agent node",
+                target.name
+            )));
+        };
+        let agent = scope
+            .agents
+            .read()
+            .await
+            .get(&config.agent_id)
+            .cloned()
+            .ok_or_else(|| GraphBitError::agent_not_found(config.agent_id.to_string()))?;
+        let provider_override =
+            Self::node_llm_provider_override(&target.id, &target.config, agent.as_ref(), context)
+                .await?;
+        let llm_provider = provider_override
+            .as_ref()
+            .unwrap_or_else(|| agent.llm_provider());
+
+        // The supervisor's conversation is already masked when guardrails are active
+        let transcript = crate::tools::handoff::handoff_transcript(conversation);
+        let task_prompt = {
+            let ctx = context.lock().await;
+            Self::resolve_node_template_variables(
+                &Self::suffixed_prompt_template(&config.prompt_template, &ctx),
+                &ctx,
+                Some(&target.id),
+            )
+        };
+        let mut instruction = format!("{task_prompt}\n\n{}", handoff.message);
+        if let Some(enforcer) = guardrail_enforcer {
+            let enc = enforcer.encode(
+                serde_json::Value::String(instruction.clone()),
+                EncodeContext::Llm,
+            );
+            instruction = enc.payload.as_str().map_or(instruction, str::to_string);
+        }
+
+        let mut messages = Vec::with_capacity(transcript.len() + 2);
+        if let Some(content) =
+            Self::node_system_prompt(target, config, agent.as_ref(), context).await
+        {
+            messages.push(LlmMessage::system(content));
+        }
+        messages.extend(transcript.iter().cloned());
+        messages.push(LlmMessage::user(instruction));
+        let request = Self::apply_node_sampling_overrides(
+            LlmRequest::with_messages(messages),
+            &target.config,
+            agent.as_ref(),
+        );
+
+        tracing::debug!(
+            supervisor = %supervisor_name,
+            target = %target.name,
+            "Handing off to agent node"
+        );
+        let llm_start = std::time::Instant::now();
+        let middleware = context.lock().await.llm_middleware.clone();
+        let mut answer = llm_provider
+            .complete_with(&middleware, request)
+            .await?
+            .content;
+        if let Some(enforcer) = guardrail_enforcer {
+            let decoded = enforcer.decode(
+                serde_json::json!({ "content": answer }),
+                DecodeContext::LlmResponse,
+            );
+            if let Some(content) = decoded.payload.get("content").and_then(|v| v.as_str()) {
+                answer = content.to_string();
+            }
+        }
+
+        let output = serde_json::from_str::<serde_json::Value>(&answer)
+            .unwrap_or_else(|_| serde_json::Value::String(answer.clone()));
+        let mut ctx = context.lock().await;
+        Self::store_node_output(&mut ctx, target, output);
+        ctx.metadata.insert(
+            format!("handoff_{}", target.name),
+            serde_json::json!({
+                "from": supervisor_name,
+                "message": handoff.message,
+                "transcript": transcript,
+                "output": answer,
+                "duration_ms": llm_start.elapsed().as_secs_f64() * 1000.0,
+            }),
+        );
+        drop(ctx);
+
+        Ok(answer)
+    }
+}
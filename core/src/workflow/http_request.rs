@@ -0,0 +1,194 @@
+//! HTTP request nodes
+
+use super::attachments::MultipartField;
+use super::{DEFAULT_IDEMPOTENCY_HEADER, WorkflowExecutor};
+use crate::attachment::Attachment;
+use crate::audit::AuditCallKind;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::graph::WorkflowNode;
+use crate::http_client::shared_client;
+use crate::types::{Secrets, WorkflowContext};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+impl WorkflowExecutor {
+    /// Run an HTTP request node, recording the request in the audit log.
+    /// Headers are left out of the record as they usually carry credentials.
+    pub(super) async fn run_http_request_node(
+        node: &WorkflowNode,
+        url: &str,
+        method: &str,
+        headers: &HashMap<String, String>,
+        context: &Arc<Mutex<WorkflowContext>>,
+        idempotency_key: &str,
+    ) -> GraphBitResult<serde_json::Value> {
+        let (secrets, multipart) = {
+            let ctx = context.lock().await;
+            (
+                ctx.secrets.clone(),
+                Self::http_multipart_fields(node, &ctx)?,
+            )
+        };
+        crate::audit::observe(
+            AuditCallKind::Http,
+            "http",
+            Some(method),
+            multipart,
+            |multipart| {
+                let request = format!("{} {url}", method.to_uppercase());
+                let request = match (multipart, node.config.get("body")) {
+                    (Some(fields), _) => {
+                        let names: Vec<&str> =
+                            fields.iter().map(|(name, _)| name.as_str()).collect();
+                        format!("{request}\n\nmultipart: {}", names.join(", "))
+                    }
+                    (None, None | Some(serde_json::Value::Null)) => request,
+                    (None, Some(serde_json::Value::String(body))) => {
+                        format!("{request}\n\n{body}")
+                    }
+                    (None, Some(body)) => format!("{request}\n\n{body}"),
+                };
+                secrets.redact(&request)
+            },
+            |multipart| {
+                Self::execute_http_request_node(
+                    node,
+                    url,
+                    method,
+                    headers,
+                    multipart,
+                    context,
+                    &secrets,
+                    idempotency_key,
+                )
+            },
+            |output| (output.to_string(), None),
+        )
+        .await
+    }
+
+    /// Execute an HTTP request node. The request body is taken from the node's
+    /// `body` config entry: strings are sent as-is, other values as JSON; or
+    /// from its resolved `multipart` fields.
+    ///
+    /// A response with a binary content type, such as an image or a PDF, is
+    /// attached to the node instead of becoming its `body`. Secret values
+    /// echoed back in a text response or in errors are redacted.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn execute_http_request_node(
+        node: &WorkflowNode,
+        url: &str,
+        method: &str,
+        headers: &HashMap<String, String>,
+        multipart: Option<Vec<(String, MultipartField)>>,
+        context: &Arc<Mutex<WorkflowContext>>,
+        secrets: &Secrets,
+        idempotency_key: &str,
+    ) -> GraphBitResult<serde_json::Value> {
+        let method =
+            reqwest::Method::from_bytes(method.trim().to_uppercase().as_bytes()).map_err(|_| {
+                GraphBitError::workflow_execution(format!(
+                    "HTTP request node '{}': invalid method '{method}'",
+                    node.name
+                ))
+            })?;
+
+        let mut request = shared_client("http_node", None, None)?.request(method, url);
+        // The key goes in `Idempotency-Key` unless the `idempotency_header` config
+        // names another header or is null; explicit headers take precedence
+        let idempotency_header = match node.config.get("idempotency_header") {
+            None => Some(DEFAULT_IDEMPOTENCY_HEADER),
+            Some(serde_json::Value::String(name)) if !name.is_empty() => Some(name.as_str()),
+            Some(_) => None,
+        };
+        if let Some(name) = idempotency_header {
+            if !headers
+                .keys()
+                .any(|header| header.eq_ignore_ascii_case(name))
+            {
+                request = request.header(name, idempotency_key);
+            }
+        }
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        match (multipart, node.config.get("body")) {
+            (Some(fields), _) => request = request.multipart(Self::multipart_form(node, fields)?),
+            (None, None | Some(serde_json::Value::Null)) => {}
+            (None, Some(serde_json::Value::String(body))) => request = request.body(body.clone()),
+            (None, Some(body)) => request = request.json(body),
+        }
+
+        let redacted_network_error = |e: reqwest::Error| GraphBitError::Network {
+            message: secrets.redact(&e.to_string()),
+        };
+        let response = request.send().await.map_err(redacted_network_error)?;
+        let status = response.status();
+        let binary_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .filter(|content_type| !is_text_content_type(content_type))
+            .map(str::to_string);
+        if let Some(mime_type) = binary_type {
+            let bytes = response.bytes().await.map_err(redacted_network_error)?;
+            if !status.is_success() {
+                return Err(GraphBitError::workflow_execution(format!(
+                    "HTTP request node '{}' received status {}: {} bytes of {mime_type}",
+                    node.name,
+                    status.as_u16(),
+                    bytes.len()
+                )));
+            }
+            let attachment = Attachment::from_bytes(bytes.to_vec(), mime_type);
+            let summary = serde_json::json!({
+                "id": attachment.id,
+                "mime_type": attachment.mime_type,
+                "size_bytes": attachment.size_bytes,
+            });
+            context
+                .lock()
+                .await
+                .set_attachments(&node.name, vec![attachment])?;
+            return Ok(serde_json::json!({
+                "status": status.as_u16(),
+                "body": null,
+                "attachment": summary,
+            }));
+        }
+        let text = secrets.redact(&response.text().await.map_err(redacted_network_error)?);
+        if !status.is_success() {
+            return Err(GraphBitError::workflow_execution(format!(
+                "HTTP request node '{}' received status {}: {text}",
+                node.name,
+                status.as_u16()
+            )));
+        }
+
+        let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+        Ok(serde_json::json!({
+            "status": status.as_u16(),
+            "body": body,
+        }))
+    }
+}
+
+/// Whether an HTTP response of `content_type` is text, kept as the node's
+/// `body`, rather than binary content attached to the node
+fn is_text_content_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence.is_empty()
+        || essence.starts_with("text/")
+        || essence.ends_with("json")
+        || essence.ends_with("xml")
+        || matches!(
+            essence.as_str(),
+            "application/javascript" | "application/x-www-form-urlencoded"
+        )
+}
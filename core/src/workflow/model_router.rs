@@ -0,0 +1,255 @@
+//! Model router nodes, escalating from cheap to capable candidate models
+
+use super::{NodeRuntime, WorkflowExecutor};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::expression::{Expression, ExpressionScope};
+use crate::graph::{AgentNodeConfig, NodeType, WorkflowGraph, WorkflowNode};
+use crate::llm::LlmUsage;
+use crate::types::WorkflowContext;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+impl WorkflowExecutor {
+    /// Run a model router node: ask its candidates in order until one answers
+    /// without the escalation expression holding, or the last one answers.
+    ///
+    /// Each attempt runs like an agent node configured with the candidate as its
+    /// `llm_config`. A failed attempt, or one whose expression cannot be evaluated,
+    /// escalates as well. The LLM calls of every attempt stay in the node's
+    /// response metadata, tagged with their attempt, next to a `model_router`
+    /// entry holding each attempt's model, usage and cost, the model that
+    /// answered and the total cost.
+    pub(super) async fn execute_model_router_node(
+        node: &WorkflowNode,
+        agent_node_config: &AgentNodeConfig,
+        candidates: &[crate::llm::LlmConfig],
+        escalation_expression: &str,
+        context: Arc<Mutex<WorkflowContext>>,
+        runtime: &NodeRuntime,
+    ) -> GraphBitResult<serde_json::Value> {
+        let expression = Expression::parse(escalation_expression).map_err(|e| {
+            GraphBitError::workflow_execution(format!(
+                "Model router node '{}': invalid escalation expression: {e}",
+                node.name
+            ))
+        })?;
+        let response_key = format!("node_response_{}", node.id);
+
+        let mut executions = Vec::new();
+        let mut attempts = Vec::new();
+        let mut total_usage = LlmUsage::empty();
+        let mut total_cost = 0.0;
+        for (index, candidate) in candidates.iter().enumerate() {
+            let model = candidate.model_name().to_string();
+            let mut node_config = node.config.clone();
+            node_config.remove("model");
+            node_config.insert("llm_config".to_string(), serde_json::to_value(candidate)?);
+
+            let result = Self::execute_agent_node_static(
+                &node.id,
+                agent_node_config,
+                &node_config,
+                context.clone(),
+                runtime,
+            )
+            .await;
+
+            // Attribute the attempt's LLM calls to it
+            let mut response = Self::take_attempt_response(node, &response_key, &context).await;
+            let usage = crate::report::node_token_usage(&response).unwrap_or_else(LlmUsage::empty);
+            let cost = Self::attempt_cost(candidate, &usage);
+            total_usage.add(&usage);
+            total_cost += cost.unwrap_or(0.0);
+            executions.extend(Self::take_attempt_executions(&mut response, index));
+
+            let is_last = index + 1 == candidates.len();
+            let mut record = serde_json::json!({
+                "attempt": index,
+                "model": model,
+                "provider": candidate.provider_name(),
+                "usage": usage,
+                "cost_usd": cost,
+            });
+            let (output, escalate) = match result {
+                Ok(output) if Self::is_tool_calls_required_output(&output) => (Ok(output), false),
+                Ok(output) => {
+                    let finish_reason = response.get("exit_reason");
+                    let (output, escalate) = Self::judge_attempt(
+                        node,
+                        &expression,
+                        output,
+                        finish_reason,
+                        &model,
+                        index,
+                    );
+                    (Ok(output), escalate)
+                }
+                Err(e) => {
+                    record["error"] = serde_json::Value::String(e.to_string());
+                    (Err(e), true)
+                }
+            };
+            record["escalated"] = serde_json::Value::Bool(escalate && !is_last);
+            attempts.push(record);
+            if escalate && !is_last {
+                tracing::debug!(node_name = %node.name, model = %model, "Escalating to the next model");
+                continue;
+            }
+
+            let answered_by = output.as_ref().ok().map(|_| model);
+            if let Some(metadata) = response.as_object_mut() {
+                metadata.insert(
+                    "executions".to_string(),
+                    serde_json::Value::Array(std::mem::take(&mut executions)),
+                );
+                metadata.insert(
+                    "total_usage".to_string(),
+                    serde_json::to_value(&total_usage)?,
+                );
+                metadata.insert(
+                    "model_router".to_string(),
+                    serde_json::json!({
+                        "attempts": attempts,
+                        "answered_by": answered_by,
+                        "total_cost_usd": total_cost,
+                    }),
+                );
+            }
+            {
+                let mut ctx = context.lock().await;
+                ctx.metadata
+                    .insert(format!("node_response_{}", node.name), response.clone());
+                ctx.metadata.insert(response_key, response);
+            }
+            return output;
+        }
+
+        Err(GraphBitError::workflow_execution(format!(
+            "Model router node '{}' has no candidate models",
+            node.name
+        )))
+    }
+
+    /// Take the response metadata the attempt recorded under `response_key` off
+    /// the context, so the next attempt starts without it
+    async fn take_attempt_response(
+        node: &WorkflowNode,
+        response_key: &str,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> serde_json::Value {
+        context
+            .lock()
+            .await
+            .metadata
+            .remove(response_key)
+            .unwrap_or_else(|| {
+                serde_json::json!({
+                    "node_id": node.id.to_string(),
+                    "node_name": node.name,
+                })
+            })
+    }
+
+    /// Judge the output of an attempt by the escalation `expression`, with the
+    /// output recovered from the text as schema validation does. Returns the
+    /// output, recovered when it satisfies the node's schema, and whether to
+    /// escalate to the next candidate.
+    fn judge_attempt(
+        node: &WorkflowNode,
+        expression: &Expression,
+        output: serde_json::Value,
+        finish_reason: Option<&serde_json::Value>,
+        model: &str,
+        index: usize,
+    ) -> (serde_json::Value, bool) {
+        let parsed = match &output {
+            serde_json::Value::String(text) => crate::json_repair::repair_json(text)
+                .map_or_else(|| output.clone(), |repaired| repaired.value),
+            other => other.clone(),
+        };
+        let schema_validator = crate::validation::TypeValidator::new();
+        let schema_valid = node
+            .output_schema
+            .as_ref()
+            .map(|schema| schema_validator.validate_value(&parsed, schema).is_valid);
+        let scope = ExpressionScope::new(serde_json::json!({
+            "output": parsed,
+            "finish_reason": finish_reason,
+            "confidence": parsed.get("confidence"),
+            "schema_valid": schema_valid,
+            "model": model,
+            "attempt": index,
+        }));
+        let escalate = expression.evaluate_bool(&scope).unwrap_or_else(|e| {
+            tracing::warn!(
+                node_name = %node.name,
+                model = %model,
+                "Escalation expression failed, escalating: {e}"
+            );
+            true
+        });
+        let output = match schema_valid {
+            Some(true) => parsed,
+            _ => output,
+        };
+        (output, escalate)
+    }
+
+    /// Take the LLM calls out of the response metadata of attempt `index`,
+    /// tagged with the attempt
+    fn take_attempt_executions(
+        response: &mut serde_json::Value,
+        index: usize,
+    ) -> Vec<serde_json::Value> {
+        let Some(calls) = response
+            .get_mut("executions")
+            .and_then(serde_json::Value::as_array_mut)
+        else {
+            return Vec::new();
+        };
+        calls
+            .drain(..)
+            .map(|mut call| {
+                if let Some(call) = call.as_object_mut() {
+                    call.insert("attempt".to_string(), serde_json::Value::from(index));
+                }
+                call
+            })
+            .collect()
+    }
+
+    /// Cost of the `usage` of an attempt with `candidate`, when its price is known
+    fn attempt_cost(candidate: &crate::llm::LlmConfig, usage: &LlmUsage) -> Option<f64> {
+        Self::candidate_cost_per_token(candidate).map(|(input_cost, output_cost)| {
+            f64::from(usage.prompt_tokens)
+                .mul_add(input_cost, f64::from(usage.completion_tokens) * output_cost)
+        })
+    }
+
+    /// Costs per token of the candidate models of the model routers of `graph`,
+    /// by model name
+    pub(super) fn candidate_prices(graph: &WorkflowGraph) -> HashMap<String, (f64, f64)> {
+        let mut prices = HashMap::new();
+        for node in graph.get_nodes().values() {
+            let NodeType::ModelRouter { candidates, .. } = &node.node_type else {
+                continue;
+            };
+            for candidate in candidates {
+                if let Some(price) = Self::candidate_cost_per_token(candidate) {
+                    prices.insert(candidate.model_name().to_string(), price);
+                }
+            }
+        }
+        prices
+    }
+
+    /// `(input_cost_per_token, output_cost_per_token)` of a router candidate's model
+    pub(super) fn candidate_cost_per_token(
+        candidate: &crate::llm::LlmConfig,
+    ) -> Option<(f64, f64)> {
+        crate::llm::LlmProviderFactory::create_provider(candidate.clone())
+            .ok()?
+            .cost_per_token()
+    }
+}
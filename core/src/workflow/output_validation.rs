@@ -0,0 +1,271 @@
+//! Validation of agent output against the node's `output_schema`, with repair
+//! requests to the model
+
+use super::{NodeRuntime, WorkflowExecutor};
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::graph::{AgentNodeConfig, WorkflowNode};
+use crate::json_repair::JsonRepair;
+use crate::types::{OutputValidationReport, WorkflowContext};
+use crate::validation::{TypeValidator, ValidationResult};
+use crate::{DecodeContext, EncodeContext, Enforcer};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+impl WorkflowExecutor {
+    /// Validate an agent node's output against its `output_schema`, asking the model
+    /// to fix invalid output up to the node's `max_repair_attempts` (default 2).
+    ///
+    /// Output that is not valid JSON is first recovered with
+    /// [`crate::json_repair::repair_json`], unless the node sets `strict_json`.
+    /// The outcome is recorded on the context under the node name. Returns the first
+    /// output that satisfies the schema, or a validation error once repairs run out.
+    /// Output of a node without a schema, or still waiting on tool calls, is
+    /// returned as is.
+    pub(super) async fn enforce_agent_output_schema(
+        node: &WorkflowNode,
+        agent_node_config: &AgentNodeConfig,
+        mut output: serde_json::Value,
+        context: Arc<Mutex<WorkflowContext>>,
+        runtime: &NodeRuntime,
+    ) -> GraphBitResult<serde_json::Value> {
+        use crate::llm::{LlmMessage, LlmRequest};
+
+        let Some(schema) = node.output_schema.as_ref() else {
+            return Ok(output);
+        };
+        if Self::is_tool_calls_required_output(&output) {
+            return Ok(output);
+        }
+
+        let strict_json = strict_json(node);
+        let schema_validator = crate::validation::TypeValidator::new();
+        let validate = |output| validate_output(&schema_validator, schema, strict_json, output);
+
+        let (repaired, mut validation, mut json_repairs) = validate(output);
+        output = repaired;
+        if validation.is_valid {
+            context.lock().await.record_output_validation(
+                &node.name,
+                OutputValidationReport::new(0, validation).with_json_repairs(json_repairs),
+            );
+            return Ok(output);
+        }
+
+        let agent = runtime
+            .agents
+            .read()
+            .await
+            .get(&agent_node_config.agent_id)
+            .cloned()
+            .ok_or_else(|| {
+                GraphBitError::agent_not_found(agent_node_config.agent_id.to_string())
+            })?;
+        let provider_override =
+            Self::node_llm_provider_override(&node.id, &node.config, agent.as_ref(), &context)
+                .await?;
+        let llm_provider = provider_override
+            .as_ref()
+            .unwrap_or_else(|| agent.llm_provider());
+        let middleware = context.lock().await.llm_middleware.clone();
+        let system_prompt =
+            Self::node_system_prompt(node, agent_node_config, agent.as_ref(), &context).await;
+
+        let original_prompt = Self::recorded_node_prompt(node, agent_node_config, &context).await;
+        let enforcer = runtime.guardrail_enforcer.as_deref();
+        let encode = |text| mask_repair_text(enforcer, text);
+
+        let mut repair_attempts = 0;
+        let max_repair_attempts = max_repair_attempts(node);
+        while !validation.is_valid && repair_attempts < max_repair_attempts {
+            repair_attempts += 1;
+            tracing::debug!(
+                node_name = %node.name,
+                attempt = repair_attempts,
+                "Agent output failed output_schema validation; requesting a repair"
+            );
+
+            let previous_output = match &output {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            let instruction = Self::output_repair_instruction(&validation, schema);
+
+            let mut messages = Vec::with_capacity(4);
+            if let Some(content) = system_prompt.clone() {
+                messages.push(LlmMessage::system(content));
+            }
+            messages.push(LlmMessage::user(original_prompt.clone()));
+            messages.push(LlmMessage::assistant(encode(previous_output)));
+            messages.push(LlmMessage::user(encode(instruction)));
+            let request = Self::apply_node_sampling_overrides(
+                LlmRequest::with_messages(messages),
+                &node.config,
+                agent.as_ref(),
+            );
+
+            let repaired = unmask_repair_text(
+                enforcer,
+                llm_provider
+                    .complete_with(&middleware, request)
+                    .await?
+                    .content,
+            );
+            (output, validation, json_repairs) = validate(
+                serde_json::from_str::<serde_json::Value>(&repaired)
+                    .unwrap_or(serde_json::Value::String(repaired)),
+            );
+        }
+
+        let error = (!validation.is_valid).then(|| {
+            GraphBitError::validation(
+                "output_schema",
+                format!(
+                    "Output of node '{}' does not match its schema after {repair_attempts} repair attempt(s): {}",
+                    node.name,
+                    Self::describe_validation_errors(&validation, "; ")
+                ),
+            )
+        });
+        context.lock().await.record_output_validation(
+            &node.name,
+            OutputValidationReport::new(repair_attempts, validation)
+                .with_json_repairs(json_repairs),
+        );
+
+        error.map_or(Ok(output), Err)
+    }
+
+    /// Prompt recorded for an agent node, falling back to its resolved template.
+    /// The recorded prompt is already masked when guardrails are active.
+    pub(super) async fn recorded_node_prompt(
+        node: &WorkflowNode,
+        agent_node_config: &AgentNodeConfig,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> String {
+        let ctx = context.lock().await;
+        ctx.metadata
+            .get(&format!("node_response_{}", node.id))
+            .and_then(|meta| meta.get("user_input"))
+            .and_then(|v| v.as_str())
+            .map_or_else(
+                || {
+                    Self::resolve_node_template_variables(
+                        &Self::suffixed_prompt_template(&agent_node_config.prompt_template, &ctx),
+                        &ctx,
+                        Some(&node.id),
+                    )
+                },
+                str::to_string,
+            )
+    }
+
+    /// Instruction asking the model to fix output that failed schema validation
+    pub(super) fn output_repair_instruction(
+        validation: &crate::validation::ValidationResult,
+        schema: &serde_json::Value,
+    ) -> String {
+        format!(
+            "Your previous response does not match the required output schema.\n\n\
+             Validation errors:\n{}\n\nRequired JSON schema:\n{schema}\n\n\
+             Fix your output. Respond with only the corrected JSON, without explanations \
+             or code fences.",
+            Self::describe_validation_errors(validation, "\n")
+        )
+    }
+
+    /// Render validation errors as `path: message` entries joined by `separator`
+    pub(super) fn describe_validation_errors(
+        validation: &crate::validation::ValidationResult,
+        separator: &str,
+    ) -> String {
+        validation
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field_path, e.message))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// Output schema validation outcome recorded for a node, if it has an output schema
+    pub(super) async fn node_output_validation(
+        node: &WorkflowNode,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> Option<OutputValidationReport> {
+        node.output_schema.as_ref()?;
+        context
+            .lock()
+            .await
+            .get_output_validation(&node.name)
+            .cloned()
+    }
+}
+
+/// Whether the node turned off recovering invalid JSON output
+fn strict_json(node: &WorkflowNode) -> bool {
+    node.config
+        .get("strict_json")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Repair requests the node allows before failing validation
+fn max_repair_attempts(node: &WorkflowNode) -> u32 {
+    node.config
+        .get("max_repair_attempts")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(OutputValidationReport::DEFAULT_MAX_REPAIR_ATTEMPTS, |n| {
+            u32::try_from(n).unwrap_or(u32::MAX)
+        })
+}
+
+/// Validate `output` against `schema`. Text that fails validation is first
+/// recovered as JSON, unless `strict_json`. Returns the output, recovered if
+/// it was, its validation and the repairs made to recover it.
+fn validate_output(
+    schema_validator: &TypeValidator,
+    schema: &serde_json::Value,
+    strict_json: bool,
+    output: serde_json::Value,
+) -> (serde_json::Value, ValidationResult, Vec<JsonRepair>) {
+    let validation = schema_validator.validate_value(&output, schema);
+    match output {
+        serde_json::Value::String(ref text) if !validation.is_valid && !strict_json => {
+            match crate::json_repair::repair_json(text) {
+                Some(repaired) => {
+                    let validation = schema_validator.validate_value(&repaired.value, schema);
+                    (repaired.value, validation, repaired.repairs)
+                }
+                None => (output, validation, Vec::new()),
+            }
+        }
+        _ => (output, validation, Vec::new()),
+    }
+}
+
+/// Mask text sent to the model in a repair request
+fn mask_repair_text(enforcer: Option<&Enforcer>, text: String) -> String {
+    match enforcer {
+        Some(enforcer) => enforcer
+            .encode(serde_json::Value::String(text.clone()), EncodeContext::Llm)
+            .payload
+            .as_str()
+            .map_or(text, str::to_string),
+        None => text,
+    }
+}
+
+/// Unmask the model's answer to a repair request
+fn unmask_repair_text(enforcer: Option<&Enforcer>, text: String) -> String {
+    match enforcer {
+        Some(enforcer) => enforcer
+            .decode(
+                serde_json::json!({ "content": text }),
+                DecodeContext::LlmResponse,
+            )
+            .payload
+            .get("content")
+            .and_then(|v| v.as_str())
+            .map_or(text, str::to_string),
+        None => text,
+    }
+}
@@ -0,0 +1,292 @@
+//! Re-execution of the subgraph downstream of some nodes, reusing the outputs
+//! of a prior execution for the nodes upstream
+
+use super::{RunState, Workflow, WorkflowExecutor};
+use crate::Enforcer;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::graph::{NodeType, WorkflowGraph, WorkflowNode};
+use crate::types::{NodeId, WorkflowContext};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+impl WorkflowExecutor {
+    /// Re-execute the nodes `start_nodes`, given by name or id, and every node
+    /// downstream of them, reusing the outputs of a `prior` execution of the
+    /// workflow for the nodes upstream.
+    ///
+    /// A node outside the invalidated subgraph is reused when it has an output in
+    /// the prior execution and all its parents are reused; the others, e.g. nodes
+    /// that failed before, run again. Reused nodes are listed in
+    /// [`WorkflowContext::reused_nodes`] and route their outgoing edges as before,
+    /// but only the nodes that ran are recorded as executions. The prior
+    /// variables, inputs included, seed the new execution; secrets are carried
+    /// over only from a context still in memory, as they are never serialized.
+    ///
+    /// The workflow may be a rebuilt copy of the prior one: nodes are matched by
+    /// name, and every reused node must have the same parents as in the prior
+    /// execution. Start nodes should include every node whose definition changed.
+    pub async fn reexecute_from(
+        &self,
+        workflow: Workflow,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        prior: &WorkflowContext,
+        start_nodes: &[&str],
+    ) -> GraphBitResult<WorkflowContext> {
+        let context = Self::reexecution_context(&workflow, prior, start_nodes)?;
+        self.execute_internal(
+            workflow,
+            guardrail_enforcer,
+            None,
+            crate::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+    }
+
+    /// Context for [`reexecute_from`](Self::reexecute_from), seeded with the
+    /// prior outputs of the nodes it reuses; run it with
+    /// [`execute_with_context`](Self::execute_with_context)
+    pub fn reexecution_context(
+        workflow: &Workflow,
+        prior: &WorkflowContext,
+        start_nodes: &[&str],
+    ) -> GraphBitResult<WorkflowContext> {
+        let graph = &workflow.graph;
+        if start_nodes.is_empty() {
+            return Err(GraphBitError::validation(
+                "start_nodes",
+                "At least one start node is required",
+            ));
+        }
+        let mut invalidated = HashSet::new();
+        for start in start_nodes {
+            let id = graph
+                .get_node_id_by_name(start)
+                .or_else(|| {
+                    NodeId::from_string(start)
+                        .ok()
+                        .filter(|id| graph.get_node(id).is_some())
+                })
+                .ok_or_else(|| {
+                    GraphBitError::validation(
+                        "start_nodes",
+                        format!("Workflow has no node '{start}'"),
+                    )
+                })?;
+            invalidated.extend(graph.forward_reachable_from(&id));
+        }
+
+        let prior_names = Self::prior_node_names(prior)?;
+        let prior_parents = Self::prior_node_parents(prior, &prior_names)?;
+
+        // Variables prior nodes stored under their names and ids are replaced by
+        // the outputs of the reused nodes
+        let node_keys: HashSet<&str> = prior_names
+            .iter()
+            .flat_map(|(id, name)| [id.as_str(), name.as_str()])
+            .collect();
+        let mut context = WorkflowContext::new(workflow.id.clone())
+            .with_inputs(
+                prior
+                    .variables
+                    .iter()
+                    .filter(|(key, _)| !node_keys.contains(key.as_str()))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            )
+            .with_secrets(prior.secrets.clone());
+
+        let mut reused = HashSet::new();
+        for id in graph.topological_sort()? {
+            let Some(node) = graph.get_node(&id) else {
+                continue;
+            };
+            let parents: Vec<&WorkflowNode> = graph
+                .get_edges()
+                .iter()
+                .filter(|(_, to, _)| to == &id)
+                .filter_map(|(from, _, _)| graph.get_node(from))
+                .collect();
+            // Failed nodes keep a null output; they run again
+            let failed = prior
+                .node_executions
+                .iter()
+                .rev()
+                .find(|record| record.node_name == node.name)
+                .is_some_and(|record| !record.success);
+            let Some(output) = prior.node_outputs.get(&node.name).filter(|_| !failed) else {
+                continue;
+            };
+            if invalidated.contains(&id) || !parents.iter().all(|p| reused.contains(&p.id)) {
+                continue;
+            }
+            let parent_names: BTreeSet<String> = parents.iter().map(|p| p.name.clone()).collect();
+            if prior_parents.get(&node.name) != Some(&parent_names) {
+                return Err(GraphBitError::validation(
+                    "prior_context",
+                    format!(
+                        "Node '{}' has other parents than in the prior execution; re-execute it too",
+                        node.name
+                    ),
+                ));
+            }
+
+            Self::store_node_output(&mut context, node, output.clone());
+            if let Some(calls) = prior.tool_calls.get(&node.name) {
+                context.tool_calls.insert(node.name.clone(), calls.clone());
+            }
+            if let Some(attachments) = prior.attachments.get(&node.name) {
+                context
+                    .attachments
+                    .insert(node.name.clone(), attachments.clone());
+            }
+            if let Some(report) = prior.get_output_validation(&node.name) {
+                context.record_output_validation(&node.name, report.clone());
+            }
+            if let Some(report) = prior.get_guardrail_report(&node.name) {
+                context
+                    .guardrail_reports
+                    .insert(node.name.clone(), report.clone());
+            }
+            if prior.event_timed_out(&node.name) {
+                context.record_event_timeout(&node.name);
+            }
+            context.reused_nodes.insert(node.name.clone());
+            reused.insert(id);
+        }
+        Ok(context)
+    }
+
+    /// Names of the nodes of a prior execution by id, from its metadata
+    pub(super) fn prior_node_names(
+        prior: &WorkflowContext,
+    ) -> GraphBitResult<HashMap<String, String>> {
+        prior
+            .metadata
+            .get("node_id_to_name")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .ok_or_else(Self::unrecorded_prior_nodes)
+    }
+
+    /// Parent names of each node of a prior execution by name, from its metadata
+    pub(super) fn prior_node_parents(
+        prior: &WorkflowContext,
+        prior_names: &HashMap<String, String>,
+    ) -> GraphBitResult<HashMap<String, BTreeSet<String>>> {
+        let prior_dependencies: HashMap<String, Vec<String>> = prior
+            .metadata
+            .get("node_dependencies")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .ok_or_else(Self::unrecorded_prior_nodes)?;
+        Ok(prior_dependencies
+            .iter()
+            .filter_map(|(id, parents)| {
+                let parents = parents
+                    .iter()
+                    .filter_map(|parent| prior_names.get(parent).cloned())
+                    .collect();
+                Some((prior_names.get(id)?.clone(), parents))
+            })
+            .collect())
+    }
+
+    pub(super) fn unrecorded_prior_nodes() -> GraphBitError {
+        GraphBitError::validation(
+            "prior_context",
+            "The prior execution does not record the nodes of its workflow",
+        )
+    }
+
+    /// Settle the `ready` nodes a re-execution reuses with their prior output.
+    /// Returns the nodes to run.
+    pub(super) fn settle_reused_nodes(
+        ready: Vec<WorkflowNode>,
+        graph: &WorkflowGraph,
+        state: &mut RunState,
+        context: &mut WorkflowContext,
+    ) -> GraphBitResult<Vec<WorkflowNode>> {
+        if context.reused_nodes.is_empty() {
+            return Ok(ready);
+        }
+        let (reused, runnable): (Vec<_>, Vec<_>) = ready
+            .into_iter()
+            .partition(|node| context.is_reused(&node.name));
+        for node in &reused {
+            Self::settle_reused_node(
+                graph,
+                node,
+                context,
+                &state.resolved,
+                &mut state.skipped,
+                &mut state.disabled_edges,
+            )?;
+            state.resolved.insert(node.id.clone());
+            state.succeeded.insert(node.id.clone());
+        }
+        Ok(runnable)
+    }
+
+    /// Settle a node whose output a re-execution reused, routing its outgoing
+    /// edges from that output as when it ran
+    pub(super) fn settle_reused_node(
+        graph: &WorkflowGraph,
+        node: &WorkflowNode,
+        context: &mut WorkflowContext,
+        resolved: &HashSet<NodeId>,
+        skipped: &mut HashSet<NodeId>,
+        disabled_edges: &mut HashSet<(NodeId, NodeId)>,
+    ) -> GraphBitResult<()> {
+        tracing::info!(node_id = %node.id, "Reusing the output of a prior execution");
+        let output = context
+            .get_node_output(&node.name)
+            .cloned()
+            .unwrap_or_default();
+        Self::evaluate_conditional_edges(graph, node, &output, context, disabled_edges)?;
+        Self::route_violation_edges(
+            graph,
+            node,
+            context.get_guardrail_report(&node.name),
+            disabled_edges,
+        );
+        Self::route_timeout_edges(
+            graph,
+            node,
+            context.event_timed_out(&node.name),
+            disabled_edges,
+        );
+        Self::store_edge_outputs(graph, node, &output, context, disabled_edges);
+        if let (NodeType::Condition { .. }, serde_json::Value::Bool(holds)) =
+            (&node.node_type, &output)
+        {
+            let chosen_id = Self::branch_target(graph, &node.id, *holds);
+            Self::expand_skips_from_condition(
+                graph,
+                &node.id,
+                chosen_id.as_ref(),
+                resolved,
+                skipped,
+                context,
+            );
+        } else if matches!(node.node_type, NodeType::Condition { .. }) {
+            let chosen_id = Self::condition_output_branch_name(&output)
+                .ok_or_else(|| {
+                    GraphBitError::workflow_execution(format!(
+                        "Condition '{}': reused output is not a branch node name",
+                        node.name
+                    ))
+                })
+                .and_then(|chosen| {
+                    Self::resolve_condition_branch_target(graph, &node.id, &chosen)
+                })?;
+            Self::expand_skips_from_condition(
+                graph,
+                &node.id,
+                Some(&chosen_id),
+                resolved,
+                skipped,
+                context,
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,480 @@
+//! Tools registered with the executor, run in-process for agent nodes
+//!
+//! An agent node naming registered tools, or handing off to other agents, runs
+//! the tool calls of its model itself and sends the results back to the model
+//! until it answers without calling tools, instead of returning the calls to
+//! the bindings to run.
+
+use super::WorkflowExecutor;
+use super::handoff::HandoffScope;
+use crate::errors::{GraphBitError, GraphBitResult};
+use crate::llm::{
+    ContextFit, ContextWindow, HistorySummary, HistoryWindow, LlmMessage, LlmMiddlewareStack,
+};
+use crate::stream::StreamEvent;
+use crate::tools::{
+    HANDOFF_TOOL_NAME, HandoffRequest, ToolContext, ToolRegistry, validate_tool_arguments,
+};
+use crate::types::{NodeId, ToolCallRecord, ToolCallTraceConfig, WorkflowContext};
+use crate::{DecodeContext, EncodeContext, Enforcer};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Tool registry and settings threaded to agent nodes for in-process tool execution
+#[derive(Clone)]
+pub(super) struct NativeToolRuntime {
+    pub(super) registry: ToolRegistry,
+    pub(super) strict_tool_args: bool,
+    pub(super) trace: ToolCallTraceConfig,
+}
+
+/// Node an in-process tool loop runs for, and how it calls tools and the model
+pub(super) struct ToolLoopScope<'a> {
+    pub(super) runtime: &'a NativeToolRuntime,
+    pub(super) llm_provider: &'a crate::llm::LlmProvider,
+    pub(super) node_id: &'a NodeId,
+    pub(super) node_name: &'a str,
+    pub(super) max_iterations: u32,
+    pub(super) context_window: Option<ContextWindow>,
+    pub(super) history_window: Option<HistoryWindow>,
+    pub(super) guardrail_enforcer: Option<&'a Enforcer>,
+    pub(super) event_tx: Option<&'a tokio::sync::mpsc::Sender<StreamEvent>>,
+    pub(super) stream_mode: crate::stream::StreamMode,
+    pub(super) handoff: Option<HandoffScope<'a>>,
+}
+
+/// Tool and LLM calls a tool loop made so far
+#[derive(Default)]
+struct ToolLoopProgress {
+    /// Calls recorded in the node's response metadata
+    executions: Vec<serde_json::Value>,
+    /// Tool calls recorded on the context
+    records: Vec<ToolCallRecord>,
+    /// Names of the tools called, in the order first called
+    tools_used: Vec<String>,
+    /// Number of tool calls
+    total_tool_calls: usize,
+    /// Number of rounds of tool calls
+    iterations: u32,
+    /// Last fit of the conversation to the context window that truncated it
+    context_truncation: Option<ContextFit>,
+    /// Summary of the exchanges the history window left out
+    history_summary: Option<HistorySummary>,
+}
+
+impl WorkflowExecutor {
+    /// Run the tool-calling loop in-process for tools resolved from the executor's registry.
+    ///
+    /// Each requested tool is invoked through the registry and its result appended to the
+    /// conversation as a tool message; the model is then called again until it stops
+    /// requesting tools or `max_iterations` follow-up calls have been made. The node's
+    /// response metadata is extended with the tool and LLM calls made along the way.
+    /// Follow-up calls stream their output like the initial call when tokens are requested.
+    /// The node's history window limits the earlier exchanges each follow-up call replays.
+    pub(super) async fn run_native_tool_loop(
+        scope: &ToolLoopScope<'_>,
+        base_request: crate::llm::LlmRequest,
+        mut llm_response: crate::llm::LlmResponse,
+        context: Arc<Mutex<WorkflowContext>>,
+    ) -> GraphBitResult<serde_json::Value> {
+        let mut request = base_request;
+        let middleware = context.lock().await.llm_middleware.clone();
+        let mut progress = ToolLoopProgress::default();
+
+        while !llm_response.tool_calls.is_empty() && progress.iterations < scope.max_iterations {
+            progress.iterations += 1;
+            request.messages.push(
+                LlmMessage::assistant(llm_response.content.clone())
+                    .with_tool_calls(llm_response.tool_calls.clone()),
+            );
+            for tool_call in &llm_response.tool_calls {
+                let tool_message = Self::run_loop_tool_call(
+                    scope,
+                    tool_call,
+                    &request.messages,
+                    &context,
+                    &mut progress,
+                )
+                .await?;
+                request
+                    .messages
+                    .push(LlmMessage::tool(tool_call.id.clone(), tool_message));
+            }
+            llm_response =
+                Self::next_loop_response(scope, &request, &middleware, &mut progress).await?;
+        }
+
+        Self::record_tool_loop(scope, &llm_response, progress, &context).await;
+        Ok(serde_json::Value::String(llm_response.content))
+    }
+
+    /// Run one tool call of a tool loop and record it. Returns the tool message
+    /// sent back to the model, masked by the guardrail.
+    async fn run_loop_tool_call(
+        scope: &ToolLoopScope<'_>,
+        tool_call: &crate::llm::LlmToolCall,
+        messages: &[LlmMessage],
+        context: &Arc<Mutex<WorkflowContext>>,
+        progress: &mut ToolLoopProgress,
+    ) -> GraphBitResult<String> {
+        // The first round's ToolCallStarted events were emitted by the caller
+        if progress.iterations > 1 {
+            if let Some(tx) = scope.event_tx {
+                let _ = tx
+                    .send(StreamEvent::ToolCallStarted {
+                        node_id: scope.node_id.to_string(),
+                        node_name: scope.node_name.to_string(),
+                        tool_name: tool_call.name.clone(),
+                        tool_call_id: tool_call.id.clone(),
+                        parameters: tool_call.parameters.clone(),
+                    })
+                    .await;
+            }
+        }
+
+        let tool_start = std::time::Instant::now();
+        let handoff_scope = scope
+            .handoff
+            .filter(|_| tool_call.name == HANDOFF_TOOL_NAME);
+        let schema = handoff_scope.map_or_else(
+            || {
+                scope
+                    .runtime
+                    .registry
+                    .metadata(&tool_call.name)
+                    .map_or(serde_json::Value::Null, |metadata| metadata.parameters)
+            },
+            |handoff| crate::tools::handoff::handoff_tool_metadata(handoff.targets).parameters,
+        );
+        let outcome = match validate_tool_arguments(&tool_call.name, &schema, &tool_call.parameters)
+        {
+            Ok(()) => {
+                Self::invoke_loop_tool(scope, tool_call, handoff_scope, messages, context).await
+            }
+            Err(err) if scope.runtime.strict_tool_args => {
+                progress.records.push(ToolCallRecord::new(
+                    tool_call.name.clone(),
+                    tool_call.parameters.clone(),
+                    serde_json::Value::String(err.to_string()),
+                    false,
+                    tool_start.elapsed().as_secs_f64() * 1000.0,
+                ));
+                let mut ctx = context.lock().await;
+                for record in progress.records.drain(..) {
+                    ctx.record_tool_call(scope.node_name, scope.runtime.trace.apply(record));
+                }
+                drop(ctx);
+                return Err(err.into());
+            }
+            Err(err) => Err((err.to_tool_message(), "InvalidToolArguments")),
+        };
+        let duration_ms = tool_start.elapsed().as_secs_f64() * 1000.0;
+
+        progress.total_tool_calls += 1;
+        if !progress.tools_used.contains(&tool_call.name) {
+            progress.tools_used.push(tool_call.name.clone());
+        }
+
+        let (tool_message, success) =
+            Self::report_tool_outcome(scope, tool_call, outcome, duration_ms).await;
+        progress.records.push(ToolCallRecord::new(
+            tool_call.name.clone(),
+            tool_call.parameters.clone(),
+            serde_json::Value::String(tool_message.clone()),
+            success,
+            duration_ms,
+        ));
+        progress.executions.push(serde_json::json!({
+            "type": "tool_call",
+            "id": tool_call.id,
+            "name": tool_call.name,
+            "parameters": tool_call.parameters,
+            "output": if success { serde_json::Value::String(tool_message.clone()) } else { serde_json::Value::Null },
+            "error": if success { serde_json::Value::Null } else { serde_json::Value::String(tool_message.clone()) },
+            "success": success,
+            "duration_ms": duration_ms,
+            "iteration": progress.iterations
+        }));
+
+        // Guardrail: mask tool output before it is sent to the LLM
+        Ok(match scope.guardrail_enforcer {
+            Some(enforcer) => {
+                let enc = enforcer.encode(
+                    serde_json::Value::String(tool_message.clone()),
+                    EncodeContext::Llm,
+                );
+                enc.payload.as_str().map_or(tool_message, str::to_string)
+            }
+            None => tool_message,
+        })
+    }
+
+    /// Invoke a tool call whose arguments are valid: a handoff when
+    /// `handoff_scope` is set, a registered tool otherwise. Errors come with
+    /// the type reported to the stream.
+    async fn invoke_loop_tool(
+        scope: &ToolLoopScope<'_>,
+        tool_call: &crate::llm::LlmToolCall,
+        handoff_scope: Option<HandoffScope<'_>>,
+        messages: &[LlmMessage],
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> Result<serde_json::Value, (String, &'static str)> {
+        let Some(handoff_scope) = handoff_scope else {
+            return Self::run_registered_tool(
+                &scope.runtime.registry,
+                tool_call,
+                scope.node_name,
+                context,
+            )
+            .await
+            .map_err(|e| (format!("Error: {e}"), "ToolExecutionError"));
+        };
+        let handoff_request = HandoffRequest::from_arguments(&tool_call.parameters)
+            .map_err(GraphBitError::workflow_execution);
+        match handoff_request {
+            Ok(handoff_request) => Self::run_handoff(
+                handoff_scope,
+                scope.node_name,
+                messages,
+                handoff_request,
+                context,
+                scope.guardrail_enforcer,
+            )
+            .await
+            .map(serde_json::Value::String),
+            Err(e) => Err(e),
+        }
+        .map_err(|e| (format!("Error: {e}"), "HandoffError"))
+    }
+
+    /// Emit `ToolCallCompleted` or `ToolCallFailed` for a tool call of a tool
+    /// loop. Returns the tool message and whether the call succeeded.
+    async fn report_tool_outcome(
+        scope: &ToolLoopScope<'_>,
+        tool_call: &crate::llm::LlmToolCall,
+        outcome: Result<serde_json::Value, (String, &'static str)>,
+        duration_ms: f64,
+    ) -> (String, bool) {
+        match outcome {
+            Ok(output) => {
+                if let Some(tx) = scope.event_tx {
+                    let _ = tx
+                        .send(StreamEvent::ToolCallCompleted {
+                            node_id: scope.node_id.to_string(),
+                            node_name: scope.node_name.to_string(),
+                            tool_name: tool_call.name.clone(),
+                            tool_call_id: tool_call.id.clone(),
+                            output: output.clone(),
+                            duration_ms,
+                        })
+                        .await;
+                }
+                let text = match output {
+                    serde_json::Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                (text, true)
+            }
+            Err((error, error_type)) => {
+                if let Some(tx) = scope.event_tx {
+                    let _ = tx
+                        .send(StreamEvent::ToolCallFailed {
+                            node_id: scope.node_id.to_string(),
+                            node_name: scope.node_name.to_string(),
+                            tool_name: tool_call.name.clone(),
+                            tool_call_id: tool_call.id.clone(),
+                            error: error.clone(),
+                            error_type: error_type.to_string(),
+                        })
+                        .await;
+                }
+                (error, false)
+            }
+        }
+    }
+
+    /// Call the model again with the conversation of a tool loop, fitted to
+    /// the node's history and context windows, and record the call
+    async fn next_loop_response(
+        scope: &ToolLoopScope<'_>,
+        request: &crate::llm::LlmRequest,
+        middleware: &LlmMiddlewareStack,
+        progress: &mut ToolLoopProgress,
+    ) -> GraphBitResult<crate::llm::LlmResponse> {
+        let llm_provider = scope.llm_provider;
+        // Replay the history the node's window allows, then refit the conversation
+        // so any further history is dropped oldest first
+        let mut call_request = request.clone();
+        let history = match scope.history_window {
+            Some(window) => Some(
+                window
+                    .fit(
+                        &mut call_request,
+                        &mut progress.history_summary,
+                        llm_provider,
+                        middleware,
+                    )
+                    .await?,
+            ),
+            None => None,
+        };
+        let context_fit = scope
+            .context_window
+            .map(|window| window.fit(&mut call_request))
+            .transpose()?;
+        if context_fit.as_ref().is_some_and(|fit| fit.truncated) {
+            progress.context_truncation.clone_from(&context_fit);
+        }
+        crate::capture::record_history(
+            progress.iterations,
+            &call_request.messages,
+            history.as_ref().map_or(0, |trim| trim.dropped_exchanges),
+            history.as_ref().map_or(0, |trim| trim.summarized_exchanges),
+        );
+
+        let llm_start = std::time::Instant::now();
+        let mut llm_response = match scope.event_tx {
+            Some(tx)
+                if scope.stream_mode.emits_tokens()
+                    && llm_provider.provider().supports_streaming() =>
+            {
+                let stream_node_id = scope.node_id.to_string();
+                let llm_call_id = format!("{}-llm-{}", scope.node_id, progress.iterations + 1);
+                middleware
+                    .wrap(call_request, |request| {
+                        crate::audit::llm_call(llm_provider.provider(), request, |request| {
+                            Self::stream_llm_response(
+                                llm_provider,
+                                request,
+                                tx,
+                                &stream_node_id,
+                                scope.node_name,
+                                &llm_call_id,
+                            )
+                        })
+                    })
+                    .await?
+            }
+            _ => llm_provider.complete_with(middleware, call_request).await?,
+        };
+        if let Some(enforcer) = scope.guardrail_enforcer {
+            Self::unmask_tool_loop_response(enforcer, &mut llm_response);
+        }
+
+        progress.executions.push(serde_json::json!({
+            "type": "llm_call",
+            "id": llm_response.id.clone().unwrap_or_default(),
+            "model": llm_response.model,
+            "output": llm_response.content,
+            "finish_reason": format!("{}", llm_response.finish_reason),
+            "duration_ms": llm_start.elapsed().as_secs_f64() * 1000.0,
+            "usage": {
+                "prompt_tokens": llm_response.usage.prompt_tokens,
+                "completion_tokens": llm_response.usage.completion_tokens,
+                "total_tokens": llm_response.usage.total_tokens
+            },
+            "context_window": context_fit,
+            "history": history,
+            "iteration": progress.iterations
+        }));
+        Ok(llm_response)
+    }
+
+    /// Unmask the content and tool calls of a model response in a tool loop
+    fn unmask_tool_loop_response(enforcer: &Enforcer, llm_response: &mut crate::llm::LlmResponse) {
+        let decoded = enforcer.decode(
+            serde_json::json!({
+                "content": llm_response.content,
+                "tool_calls": llm_response.tool_calls
+            }),
+            DecodeContext::LlmResponse,
+        );
+        if let Some(c) = decoded.payload.get("content").and_then(|v| v.as_str()) {
+            llm_response.content = c.to_string();
+        }
+        if let Some(tc) = decoded.payload.get("tool_calls") {
+            if let Ok(parsed) = serde_json::from_value(tc.clone()) {
+                llm_response.tool_calls = parsed;
+            }
+        }
+    }
+
+    /// Record the tool calls of a finished tool loop on the context, and extend
+    /// the node metadata recorded for the initial LLM call
+    async fn record_tool_loop(
+        scope: &ToolLoopScope<'_>,
+        llm_response: &crate::llm::LlmResponse,
+        progress: ToolLoopProgress,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) {
+        let (node_id, node_name) = (scope.node_id, scope.node_name);
+        let exit_reason = if llm_response.tool_calls.is_empty() {
+            format!("{}", llm_response.finish_reason)
+        } else {
+            "max_iterations".to_string()
+        };
+
+        let mut ctx = context.lock().await;
+        for record in progress.records {
+            ctx.record_tool_call(node_name, scope.runtime.trace.apply(record));
+        }
+        for key in [
+            format!("node_response_{node_id}"),
+            format!("node_response_{node_name}"),
+        ] {
+            if let Some(obj) = ctx.metadata.get_mut(&key).and_then(|m| m.as_object_mut()) {
+                if let Some(list) = obj.get_mut("executions").and_then(|e| e.as_array_mut()) {
+                    list.extend(progress.executions.iter().cloned());
+                }
+                obj.insert(
+                    "final_output".to_string(),
+                    serde_json::json!(llm_response.content),
+                );
+                obj.insert(
+                    "total_iterations".to_string(),
+                    serde_json::json!(progress.iterations),
+                );
+                obj.insert(
+                    "total_tool_calls".to_string(),
+                    serde_json::json!(progress.total_tool_calls),
+                );
+                obj.insert(
+                    "tools_used".to_string(),
+                    serde_json::json!(progress.tools_used),
+                );
+                obj.insert("exit_reason".to_string(), serde_json::json!(exit_reason));
+                if let Some(fit) = &progress.context_truncation {
+                    obj.insert("context_window".to_string(), serde_json::json!(fit));
+                }
+                obj.insert(
+                    "end_time".to_string(),
+                    serde_json::json!(chrono::Utc::now().to_rfc3339()),
+                );
+            }
+        }
+    }
+
+    /// Run a registered tool for the node `node_name`, merging the scratch writes
+    /// of its [`ToolContext`] into the workflow context when it succeeds
+    pub(super) async fn run_registered_tool(
+        registry: &ToolRegistry,
+        tool_call: &crate::llm::LlmToolCall,
+        node_name: &str,
+        context: &Arc<Mutex<WorkflowContext>>,
+    ) -> GraphBitResult<serde_json::Value> {
+        let tool_context = ToolContext::new(&*context.lock().await, node_name);
+        let output = registry
+            .execute_with_context(
+                &tool_call.name,
+                tool_call.parameters.clone(),
+                tool_context.clone(),
+            )
+            .await?;
+        context.lock().await.merge_node_scratch(
+            node_name,
+            &format!("'{}' of node '{node_name}'", tool_call.name),
+            tool_context.take_writes(),
+        );
+        Ok(output)
+    }
+}
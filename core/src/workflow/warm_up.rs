@@ -0,0 +1,223 @@
+//! Warm-up of the providers a workflow calls, before or at the start of an
+//! execution
+
+use super::{Workflow, WorkflowExecutor};
+use crate::agents::AgentTrait;
+use crate::errors::GraphBitResult;
+use crate::graph::{NodeType, resolve_node_llm_config};
+use crate::llm::ProviderHealth;
+use crate::types::{AgentId, WorkflowContext};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Outcome of [`WorkflowExecutor::warm_up`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmUpReport {
+    /// Every distinct provider and model the agent nodes and model routers
+    /// would call, in the order of their first node
+    pub providers: Vec<ProviderWarmUp>,
+    /// Agents created for agent nodes and added to the executor's agents
+    pub agents_created: Vec<AgentId>,
+    /// Time the whole warm-up took
+    pub duration_ms: u64,
+}
+
+impl WarmUpReport {
+    /// Whether every provider was warmed up without error
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.providers
+            .iter()
+            .all(|warm_up| warm_up.health.error.is_none())
+    }
+}
+
+/// Warm-up of one provider and model in a [`WarmUpReport`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderWarmUp {
+    /// Outcome of the request made, with its latency
+    #[serde(flatten)]
+    pub health: ProviderHealth,
+    /// Whether the request was a one-token completion priming a provider with
+    /// cold starts, rather than a health check
+    pub primed: bool,
+    /// Names of the nodes calling the provider
+    pub nodes: Vec<String>,
+}
+
+impl WorkflowExecutor {
+    /// Warm up the providers a workflow calls before running it, so that its
+    /// first LLM calls do not pay for DNS lookups, TLS handshakes or model
+    /// loading.
+    ///
+    /// Agent nodes whose agent is not registered get one created and kept, as
+    /// an execution would create it. Then every distinct provider and model of
+    /// the agent nodes and model routers gets one request, concurrently: a
+    /// one-token completion for providers with cold starts (see
+    /// [`LlmProviderTrait::has_cold_start`](crate::llm::LlmProviderTrait::has_cold_start))
+    /// unless [`with_warm_up_priming`](Self::with_warm_up_priming) turned
+    /// priming off, and a health check otherwise. The connections these open
+    /// stay in the pools of the shared HTTP clients for the execution to use.
+    /// Failed requests are reported rather than returned as errors.
+    ///
+    /// An execution warms up by itself when its context is created with
+    /// [`WorkflowContext::with_warm_up`].
+    pub async fn warm_up(&self, workflow: &Workflow) -> GraphBitResult<WarmUpReport> {
+        self.warm_up_with_context(workflow, &WorkflowContext::new(workflow.id.clone()))
+            .await
+    }
+
+    /// [`warm_up`](Self::warm_up) with the secrets and LLM profile of `context`
+    pub async fn warm_up_with_context(
+        &self,
+        workflow: &Workflow,
+        context: &WorkflowContext,
+    ) -> GraphBitResult<WarmUpReport> {
+        let started = std::time::Instant::now();
+        workflow.validate()?;
+        let mut workflow = workflow.clone();
+        let mut context = context.clone();
+        let secrets = context.secrets.clone();
+        workflow.inject_secrets(&secrets)?;
+        let default_llm_config = self.default_llm_config_with_secrets(&secrets)?;
+        self.attach_profile(&mut context, &workflow, default_llm_config.as_ref())?;
+        let agents_created = self
+            .register_workflow_agents(&workflow, &context, default_llm_config.as_ref())
+            .await?;
+        Ok(self
+            .warm_up_providers(&workflow, agents_created, started)
+            .await)
+    }
+
+    /// Make one request to every distinct provider and model the registered
+    /// agents of the agent nodes of `workflow` and its model routers call
+    pub(super) async fn warm_up_providers(
+        &self,
+        workflow: &Workflow,
+        agents_created: Vec<AgentId>,
+        started: std::time::Instant,
+    ) -> WarmUpReport {
+        // Distinct configurations, keyed by their JSON, with the nodes calling
+        // them and the agent whose provider serves them, if any
+        struct Target {
+            config: crate::llm::LlmConfig,
+            nodes: Vec<String>,
+            agent: Option<Arc<dyn AgentTrait>>,
+        }
+        let mut targets: Vec<Target> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let agents = self.agents.read().await.clone();
+        // The workflow was validated, so it can be sorted
+        for node_id in workflow.graph.topological_sort().unwrap_or_default() {
+            let Some(node) = workflow.graph.get_node(&node_id) else {
+                continue;
+            };
+            let Some(agent) = node
+                .node_type
+                .agent_config()
+                .and_then(|config| agents.get(&config.agent_id))
+            else {
+                continue;
+            };
+            let agent_config = agent.llm_provider().config();
+            let configs = match &node.node_type {
+                NodeType::ModelRouter { candidates, .. } => candidates.clone(),
+                _ => vec![
+                    resolve_node_llm_config(&node.config, Some(agent_config))
+                        .unwrap_or_else(|| agent_config.clone()),
+                ],
+            };
+            let agent_key = serde_json::to_string(agent_config).unwrap_or_default();
+            for config in configs {
+                let key = serde_json::to_string(&config).unwrap_or_default();
+                if let Some(&index) = seen.get(&key) {
+                    targets[index].nodes.push(node.name.clone());
+                    continue;
+                }
+                let agent = (key == agent_key).then(|| agent.clone());
+                seen.insert(key, targets.len());
+                targets.push(Target {
+                    config,
+                    nodes: vec![node.name.clone()],
+                    agent,
+                });
+            }
+        }
+
+        let prime = self.warm_up_priming;
+        let providers = join_all(targets.into_iter().map(|target| async move {
+            let Target {
+                config,
+                nodes,
+                agent,
+            } = target;
+            let created;
+            let provider = match &agent {
+                Some(agent) => agent.llm_provider(),
+                None => match crate::llm::LlmProviderFactory::create_provider(config.clone()) {
+                    Ok(provider) => {
+                        created = crate::llm::LlmProvider::new(provider, config);
+                        &created
+                    }
+                    Err(e) => {
+                        return ProviderWarmUp {
+                            health: ProviderHealth::from_probe(
+                                config.provider_name(),
+                                config.model_name(),
+                                0,
+                                &Err(e),
+                            ),
+                            primed: false,
+                            nodes,
+                        };
+                    }
+                },
+            };
+            Self::warm_up_provider(provider, prime, nodes).await
+        }))
+        .await;
+
+        WarmUpReport {
+            providers,
+            agents_created,
+            duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Prime `provider` with a one-token completion when `prime` is set and it
+    /// has cold starts, and health-check it otherwise
+    pub(super) async fn warm_up_provider(
+        provider: &crate::llm::LlmProvider,
+        prime: bool,
+        nodes: Vec<String>,
+    ) -> ProviderWarmUp {
+        let inner = provider.provider();
+        let primed = prime && inner.has_cold_start();
+        let health = if primed {
+            let request = crate::llm::LlmRequest::new("ping").with_max_tokens(1);
+            crate::llm::health::probe(
+                inner.provider_name(),
+                inner.model_name(),
+                provider.complete(request),
+            )
+            .await
+        } else {
+            provider.health_check().await
+        };
+        tracing::debug!(
+            provider = %health.provider,
+            model = %health.model,
+            primed,
+            latency_ms = health.latency_ms,
+            error = ?health.error,
+            "Warmed up provider"
+        );
+        ProviderWarmUp {
+            health,
+            primed,
+            nodes,
+        }
+    }
+}
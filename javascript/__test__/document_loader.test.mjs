@@ -0,0 +1,132 @@
+import assert from 'node:assert/strict'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { mkdtempSync, utimesSync, writeFileSync } from 'node:fs'
+import { tmpdir } from 'node:os'
+import { dirname, join } from 'node:path'
+import { test } from 'node:test'
+import { fileURLToPath } from 'node:url'
+
+const require = createRequire(import.meta.url)
+const { JsDocumentLoader, JsTextSplitter } = require('../index.js')
+
+const fixtures = join(dirname(fileURLToPath(import.meta.url)), 'fixtures')
+
+test('load detects the document type from the extension', async () => {
+  const loader = new JsDocumentLoader()
+  const doc = await loader.load(join(fixtures, 'notes.txt'))
+
+  assert.equal(doc.documentType, 'txt')
+  assert.match(doc.content, /plain text/)
+  assert.ok(doc.fileSize > 0)
+  assert.ok(doc.extractedAt > 0)
+  assert.equal(typeof doc.metadata, 'object')
+})
+
+test('load rejects unknown types and missing files', async () => {
+  const loader = new JsDocumentLoader()
+  await assert.rejects(loader.load(join(fixtures, 'image.bin')), /Cannot detect document type/)
+  await assert.rejects(loader.load(join(fixtures, 'missing.txt')), /File not found/)
+  await assert.rejects(loader.load(join(fixtures, 'notes.txt'), 'exe'), /Unsupported document type/)
+})
+
+test('maxFileSize is enforced', async () => {
+  const loader = new JsDocumentLoader({ maxFileSize: 8 })
+  await assert.rejects(loader.load(join(fixtures, 'notes.txt')), /exceeds maximum allowed size/)
+  assert.throws(() => new JsDocumentLoader({ maxFileSize: 0 }), /maxFileSize/)
+})
+
+test('cacheDir serves unchanged files and re-parses touched ones', async () => {
+  const dir = mkdtempSync(join(tmpdir(), 'graphbit-cache-'))
+  const path = join(dir, 'doc.txt')
+  writeFileSync(path, 'first')
+  const loader = new JsDocumentLoader({ cacheDir: join(dir, 'cache') })
+
+  assert.equal((await loader.load(path)).metadata.cache_hit, false)
+  const cached = await loader.load(path)
+  assert.equal(cached.metadata.cache_hit, true)
+  assert.equal(cached.content, 'first')
+
+  writeFileSync(path, 'again')
+  const later = new Date(Date.now() + 5000)
+  utimesSync(path, later, later)
+  const reparsed = await loader.load(path)
+  assert.equal(reparsed.metadata.cache_hit, false)
+  assert.equal(reparsed.content, 'again')
+})
+
+test('loadDirectory skips unsupported files and only recurses on request', async () => {
+  const loader = new JsDocumentLoader()
+  const flat = await loader.loadDirectory(fixtures)
+  assert.deepEqual(
+    flat.map((doc) => doc.documentType),
+    ['json', 'txt', 'csv'],
+  )
+
+  const all = await loader.loadDirectory(fixtures, true)
+  assert.equal(all.length, 4)
+  assert.ok(all.some((doc) => doc.content.includes('nested file')))
+
+  await assert.rejects(loader.loadDirectory(join(fixtures, 'missing')), /Directory not found/)
+})
+
+test('loadBatch retries rate-limited URLs and reports failures per source', async () => {
+  // Every path answers 429 twice before serving JSON; /down always answers 503
+  const attempts = {}
+  const server = createServer((req, res) => {
+    attempts[req.url] = (attempts[req.url] ?? 0) + 1
+    if (req.url.startsWith('/down')) {
+      res.writeHead(503).end()
+    } else if (attempts[req.url] <= 2) {
+      res.writeHead(429, { 'Retry-After': '0' }).end()
+    } else {
+      res.writeHead(200, { 'Content-Type': 'application/json' }).end(JSON.stringify({ path: req.url }))
+    }
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  const base = `http://127.0.0.1:${server.address().port}`
+
+  try {
+    const loader = new JsDocumentLoader({ maxRequestsPerHost: 2, maxRetries: 2, retryBackoffMs: 10 })
+    const results = await loader.loadBatch([`${base}/a.json`, `${base}/down.json`, join(fixtures, 'missing.txt')])
+
+    assert.match(results[0].document.content, /a\.json/)
+    assert.equal(results[0].document.metadata.fetch_attempts, 3)
+    assert.match(results[1].error, /503.*after 3 attempts/)
+    assert.match(results[2].error, /File not found/)
+    assert.equal(attempts['/down.json'], 3)
+  } finally {
+    server.close()
+  }
+})
+
+test('supportedTypes and detectDocumentType', () => {
+  assert.ok(JsDocumentLoader.supportedTypes().includes('pdf'))
+  assert.equal(JsDocumentLoader.detectDocumentType('report.PDF'), 'pdf')
+  assert.equal(JsDocumentLoader.detectDocumentType('image.bin'), null)
+})
+
+test('text splitters return chunks with offsets and metadata', async () => {
+  const loader = new JsDocumentLoader()
+  const { content } = await loader.load(join(fixtures, 'notes.txt'))
+
+  for (const splitter of [
+    JsTextSplitter.character(20, 5),
+    JsTextSplitter.token(4, 1),
+    JsTextSplitter.sentence(40),
+    JsTextSplitter.recursive(30, 5),
+  ]) {
+    const chunks = await splitter.splitText(content)
+    assert.ok(chunks.length > 1, `${splitter.strategy} produced a single chunk`)
+    chunks.forEach((chunk, index) => {
+      assert.equal(chunk.chunkIndex, index)
+      assert.ok(chunk.endIndex > chunk.startIndex)
+      assert.equal(typeof chunk.metadata.length, 'number')
+    })
+  }
+})
+
+test('invalid splitter settings throw', () => {
+  assert.throws(() => JsTextSplitter.character(0), /Chunk size must be greater than 0/)
+  assert.throws(() => JsTextSplitter.character(10, 10), /Chunk overlap must be less than chunk size/)
+})
@@ -0,0 +1,151 @@
+import assert from 'node:assert/strict'
+import { randomUUID } from 'node:crypto'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { JsExecutor, JsLlmClient, JsWorkflow } = require('../index.js')
+
+// OpenAI-compatible models endpoint: the key "sk-bad" is rejected and only the
+// model "gpt-4o-mini" exists
+let server
+let baseUrl
+
+before(async () => {
+  server = createServer((req, res) => {
+    const reply = (status, body) => {
+      res.writeHead(status, { 'Content-Type': 'application/json' })
+      res.end(JSON.stringify(body))
+    }
+    if (req.headers.authorization !== 'Bearer sk-test') {
+      reply(401, { error: { message: 'Incorrect API key provided', code: 'invalid_api_key' } })
+    } else if (req.url.endsWith('/models/gpt-4o-mini')) {
+      reply(200, { id: 'gpt-4o-mini', object: 'model', owned_by: 'openai' })
+    } else {
+      reply(404, { error: { message: 'The model does not exist', code: 'model_not_found' } })
+    }
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  baseUrl = `http://127.0.0.1:${server.address().port}`
+})
+
+// Concurrent probes can open a connection that never carries a request, which
+// `close()` alone would wait for
+after(() => {
+  server.closeAllConnections()
+  server.close()
+})
+
+const client = (apiKey, model) => new JsLlmClient({ provider: 'openai', apiKey, model, baseUrl })
+
+test('healthCheck reports a healthy provider', async () => {
+  const health = await client('sk-test', 'gpt-4o-mini').healthCheck()
+  assert.equal(health.provider, 'openai')
+  assert.equal(health.model, 'gpt-4o-mini')
+  assert.ok(health.reachable && health.authenticated && health.modelAvailable)
+  assert.equal(health.error, undefined)
+})
+
+test('healthCheck reports rejected credentials', async () => {
+  const health = await client('sk-bad', 'gpt-4o-mini').healthCheck()
+  assert.ok(health.reachable)
+  assert.ok(!health.authenticated)
+  assert.match(health.error, /401/)
+})
+
+test('healthCheck reports a missing model', async () => {
+  const health = await client('sk-test', 'gpt-unknown').healthCheck()
+  assert.ok(health.reachable && health.authenticated)
+  assert.ok(!health.modelAvailable)
+})
+
+// Two agent nodes in sequence, configured with `model` on the mock endpoint
+function agentWorkflow(apiKey, models) {
+  const nodes = {}
+  const ids = models.map((model, index) => {
+    const id = randomUUID()
+    nodes[id] = {
+      id,
+      name: `agent ${index}`,
+      description: '',
+      node_type: {
+        type: 'Agent',
+        agent_id: randomUUID(),
+        prompt_template: 'Say hello',
+        conversational_context: null,
+        system_prompt_override: null,
+      },
+      config: {
+        llm_config: {
+          provider: 'OpenAI',
+          api_key: apiKey,
+          model,
+          base_url: baseUrl,
+          organization: null,
+        },
+      },
+      input_schema: null,
+      output_schema: null,
+      retry_config: {
+        max_attempts: 0,
+        initial_delay_ms: 0,
+        backoff_multiplier: 1.0,
+        max_delay_ms: 0,
+        jitter_factor: 0.0,
+        retryable_errors: [],
+      },
+      timeout_seconds: null,
+      tags: [],
+    }
+    return id
+  })
+  const edge = { edge_type: 'DataFlow', condition: null, transform: null, metadata: {} }
+  const edges = ids.slice(1).map((id, index) => [ids[index], id, edge])
+  return JsWorkflow.fromJSON(
+    JSON.stringify({
+      id: randomUUID(),
+      name: 'agents',
+      description: '',
+      graph: { nodes, edges, metadata: {} },
+      metadata: {},
+    }),
+  )
+}
+
+test('dryRun probes each distinct provider and model once', async () => {
+  const report = await new JsExecutor().dryRun(
+    agentWorkflow('sk-test', ['gpt-4o-mini', 'gpt-4o-mini', 'gpt-unknown']),
+  )
+  assert.ok(!report.ok)
+  assert.equal(report.validationError, undefined)
+  assert.deepEqual(
+    report.providers.map(({ model, modelAvailable }) => [model, modelAvailable]),
+    [
+      ['gpt-4o-mini', true],
+      ['gpt-unknown', false],
+    ],
+  )
+})
+
+test('dryRun passes a workflow whose providers are healthy', async () => {
+  const report = await new JsExecutor().dryRun(agentWorkflow('sk-test', ['gpt-4o-mini']))
+  assert.ok(report.ok)
+  assert.equal(report.providers.length, 1)
+})
+
+test('dryRun renders prompts with the executor preamble, suffix and globals', async () => {
+  const executor = new JsExecutor({
+    globals: { company: 'Acme' },
+    systemPreamble: 'You work for {{globals.company}}.',
+    promptSuffix: 'Sign as {{globals.company}}.',
+  })
+  const report = await executor.dryRun(agentWorkflow('sk-test', ['gpt-4o-mini']))
+  assert.deepEqual(report.prompts, [
+    {
+      node: 'agent 0',
+      systemPrompt: 'You work for Acme.',
+      prompt: 'Say hello\n\nSign as Acme.',
+    },
+  ])
+})
@@ -0,0 +1,74 @@
+import assert from 'node:assert/strict'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { JsLlmClient } = require('../index.js')
+
+// Chat completions endpoint answering with `reply`, and a models endpoint
+// listing only "openai/gpt-oss-20b"
+let server
+let baseUrl
+let reply
+const bodies = []
+
+before(async () => {
+  server = createServer((req, res) => {
+    let data = ''
+    req.on('data', (chunk) => (data += chunk))
+    req.on('end', () => {
+      const [status, body] = req.url.endsWith('/models')
+        ? [200, [{ id: 'openai/gpt-oss-20b', object: 'model', type: 'chat', context_length: 131072 }]]
+        : reply
+      if (data) bodies.push(JSON.parse(data))
+      res.writeHead(status, { 'Content-Type': 'application/json' })
+      res.end(JSON.stringify(body))
+    })
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  baseUrl = `http://127.0.0.1:${server.address().port}/v1`
+})
+
+after(() => server.close())
+
+const completion = (content) => [
+  200,
+  {
+    id: 'cmpl-1',
+    object: 'chat.completion',
+    choices: [{ index: 0, message: { role: 'assistant', content }, finish_reason: 'stop' }],
+    usage: { prompt_tokens: 5, completion_tokens: 3, total_tokens: 8 },
+  },
+]
+
+test('fireworks expands bare model names', async () => {
+  reply = completion('Hello from Fireworks')
+  const client = new JsLlmClient({ provider: 'fireworks', apiKey: 'fw-test', model: 'llama-v3p1-8b-instruct', baseUrl })
+  assert.equal(await client.complete('Hi'), 'Hello from Fireworks')
+  assert.equal(bodies.at(-1).model, 'accounts/fireworks/models/llama-v3p1-8b-instruct')
+})
+
+test('together answers completions', async () => {
+  reply = completion('Hello from Together')
+  const client = new JsLlmClient({ provider: 'together', apiKey: 'tg-test', model: 'openai/gpt-oss-20b', baseUrl })
+  assert.equal(await client.complete('Hi'), 'Hello from Together')
+})
+
+test('error envelopes surface their message', async () => {
+  reply = [400, { error: { message: 'max_tokens too large', type: 'invalid_request_error', code: 'invalid_parameter' } }]
+  const together = new JsLlmClient({ provider: 'together', apiKey: 'tg-test', model: 'openai/gpt-oss-20b', baseUrl })
+  await assert.rejects(together.complete('Hi'), /HTTP 400\): max_tokens too large \(invalid_parameter\)/)
+
+  reply = [404, { error: { object: 'error', type: 'invalid_request_error', message: 'Model not found' } }]
+  const fireworks = new JsLlmClient({ provider: 'fireworks', apiKey: 'fw-test', model: 'missing', baseUrl })
+  await assert.rejects(fireworks.complete('Hi'), /Model not found \(invalid_request_error\)/)
+})
+
+test('healthCheck looks the model up in the catalog', async () => {
+  const client = (model) => new JsLlmClient({ provider: 'together', apiKey: 'tg-test', model, baseUrl })
+  assert.ok((await client('openai/gpt-oss-20b').healthCheck()).modelAvailable)
+  const missing = await client('openai/gpt-oss-2b').healthCheck()
+  assert.ok(missing.reachable && missing.authenticated)
+  assert.ok(!missing.modelAvailable)
+})
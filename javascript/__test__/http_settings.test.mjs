@@ -0,0 +1,51 @@
+import assert from 'node:assert/strict'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { init, JsLlmClient } = require('../index.js')
+
+// Forward proxy answering every request with a chat completion itself and
+// recording the absolute URLs it was asked for
+const seen = []
+let proxy
+let proxyUrl
+
+before(async () => {
+  proxy = createServer((req, res) => {
+    req.resume()
+    req.on('end', () => {
+      seen.push(`${req.method} ${req.url}`)
+      res.writeHead(200, { 'Content-Type': 'application/json' })
+      res.end(
+        JSON.stringify({
+          id: 'chatcmpl-1',
+          object: 'chat.completion',
+          choices: [{ index: 0, message: { role: 'assistant', content: 'pong' }, finish_reason: 'stop' }],
+          usage: { prompt_tokens: 1, completion_tokens: 1, total_tokens: 2 },
+        }),
+      )
+    })
+  })
+  await new Promise((resolve) => proxy.listen(0, '127.0.0.1', resolve))
+  proxyUrl = `http://127.0.0.1:${proxy.address().port}`
+})
+
+after(() => proxy.close())
+
+test('init rejects an unreadable CA bundle', () => {
+  assert.throws(() => init({ caBundle: '/nonexistent/ca.pem' }), /Failed to read CA bundle/)
+})
+
+test('LLM requests go through the proxy passed to init', async () => {
+  init({ proxy: proxyUrl })
+  const client = new JsLlmClient({
+    provider: 'openai',
+    apiKey: 'sk-test',
+    model: 'gpt-4o-mini',
+    baseUrl: 'http://api.openai.invalid/v1',
+  })
+  assert.equal(await client.complete('ping'), 'pong')
+  assert.deepEqual(seen, ['POST http://api.openai.invalid/v1/chat/completions'])
+})
@@ -0,0 +1,48 @@
+import assert from 'node:assert/strict'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { JsLlmClient } = require('../index.js')
+
+// Server that accepts requests but never answers them
+let server
+let serverUrl
+
+before(async () => {
+  server = createServer(() => {})
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  serverUrl = `http://127.0.0.1:${server.address().port}/v1`
+})
+
+after(() => {
+  server.closeAllConnections()
+  server.close()
+})
+
+test('a stalled request fails once the configured timeout passes', async () => {
+  const client = new JsLlmClient({
+    provider: 'openai',
+    apiKey: 'sk-test',
+    model: 'gpt-4o-mini',
+    baseUrl: serverUrl,
+    timeoutMs: 200,
+  })
+  const started = Date.now()
+  await assert.rejects(client.complete('ping'), /timed out/)
+  assert.ok(Date.now() - started < 5000)
+})
+
+test('a zero timeout is rejected', () => {
+  assert.throws(
+    () =>
+      new JsLlmClient({
+        provider: 'openai',
+        apiKey: 'sk-test',
+        baseUrl: serverUrl,
+        connectTimeoutMs: 0,
+      }),
+    /greater than zero/,
+  )
+})
@@ -0,0 +1,105 @@
+import assert from 'node:assert/strict'
+import { execFileSync } from 'node:child_process'
+import { randomUUID } from 'node:crypto'
+import { mkdtempSync, writeFileSync } from 'node:fs'
+import { createRequire } from 'node:module'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+import { test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const indexPath = require.resolve('../index.js')
+const { JsExecutor, JsWorkflow, JsWorkflowResult } = require(indexPath)
+
+const transform = (transformation) => ({ type: 'Transform', transformation })
+
+// Chain of nodes, each one fed by the previous node's output
+function chainWorkflow(steps) {
+  const nodes = {}
+  const ids = steps.map(([name, nodeType]) => {
+    const id = randomUUID()
+    nodes[id] = {
+      id,
+      name,
+      description: '',
+      node_type: nodeType,
+      config: {},
+      input_schema: null,
+      output_schema: null,
+      retry_config: {
+        max_attempts: 0,
+        initial_delay_ms: 0,
+        backoff_multiplier: 1.0,
+        max_delay_ms: 0,
+        jitter_factor: 0.0,
+        retryable_errors: [],
+      },
+      timeout_seconds: null,
+      tags: [],
+    }
+    return id
+  })
+  const edge = { edge_type: 'DataFlow', condition: null, transform: null, metadata: {} }
+  const edges = ids.slice(1).map((id, index) => [ids[index], id, edge])
+  return JsWorkflow.fromJSON(
+    JSON.stringify({
+      id: randomUUID(),
+      name: 'archive',
+      description: '',
+      graph: { nodes, edges, metadata: {} },
+      metadata: {},
+    }),
+  )
+}
+
+const archiveWorkflow = () =>
+  chainWorkflow([
+    ['draft', transform("{'title': 'Graph archives', 'body': '" + 'lorem '.repeat(50) + "'}")],
+    ['measure', transform("{'title': input.title, 'length': len(input.body)}")],
+  ])
+
+// Reload an archived result in a fresh Node.js process and print what it exposes
+const RELOAD_SCRIPT = `
+const { readFileSync } = require('node:fs')
+const { JsWorkflowResult } = require(process.argv[1])
+const result = JsWorkflowResult.fromJSON(readFileSync(process.argv[2], 'utf8'))
+console.log(JSON.stringify({
+  success: result.isSuccess(),
+  draft: result.getNodeOutput('draft'),
+  measure: result.getNodeOutput('measure'),
+  stats: result.getStats(),
+}))
+`
+
+test('an archived result reloads in a fresh process', async () => {
+  const result = await new JsExecutor().execute(archiveWorkflow())
+  assert.ok(result.isSuccess())
+  const path = join(mkdtempSync(join(tmpdir(), 'graphbit-')), 'run.json')
+  writeFileSync(path, result.toJSON())
+
+  const output = execFileSync(process.execPath, ['-e', RELOAD_SCRIPT, indexPath, path], {
+    encoding: 'utf8',
+  })
+  const reloaded = JSON.parse(output)
+
+  assert.equal(reloaded.success, true)
+  assert.deepEqual(reloaded.draft, result.getNodeOutput('draft'))
+  assert.deepEqual(reloaded.measure, { title: 'Graph archives', length: 300 })
+  assert.deepEqual(reloaded.stats, result.getStats())
+  assert.equal(reloaded.stats.total_nodes, 2)
+})
+
+test('toJSON truncates long strings with maxValueChars', async () => {
+  const result = await new JsExecutor().execute(archiveWorkflow())
+
+  const archived = JsWorkflowResult.fromJSON(result.toJSON(20))
+
+  const { body } = archived.getNodeOutput('draft')
+  assert.ok(body.startsWith('lorem lorem lorem lo'), body)
+  assert.ok(body.endsWith('... [truncated 280 chars]'), body)
+  assert.equal(archived.getNodeOutput('measure').length, 300)
+})
+
+test('fromJSON rejects text that is not a serialized result', () => {
+  assert.throws(() => JsWorkflowResult.fromJSON('{"state": 3}'), /Invalid workflow result/)
+})
@@ -0,0 +1,170 @@
+import assert from 'node:assert/strict'
+import { randomUUID } from 'node:crypto'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { JsExecutor, JsLlmClient, JsWorkflow } = require('../index.js')
+
+const DELTAS = ['Hel', 'lo', ' world']
+
+// OpenAI-compatible endpoint streaming DELTAS. With the model "drop" it closes
+// the connection after the first delta.
+let server
+let baseUrl
+
+before(async () => {
+  server = createServer((req, res) => {
+    let body = ''
+    req.on('data', (chunk) => (body += chunk))
+    req.on('end', () => {
+      const { model } = JSON.parse(body)
+      res.writeHead(200, { 'Content-Type': 'text/event-stream' })
+      const send = (data) => res.write(`data: ${JSON.stringify(data)}\n\n`)
+      DELTAS.forEach((content, index) => {
+        setTimeout(() => {
+          if (model === 'drop' && index > 0) {
+            res.destroy()
+            return
+          }
+          send({ id: 'chunk', choices: [{ delta: { content } }] })
+          if (index === DELTAS.length - 1) {
+            send({ id: 'chunk', choices: [{ delta: {}, finish_reason: 'stop' }] })
+            res.end('data: [DONE]\n\n')
+          }
+        }, index * 10)
+      })
+    })
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  baseUrl = `http://127.0.0.1:${server.address().port}`
+})
+
+after(() => server.close())
+
+const client = (model) => new JsLlmClient({ provider: 'openai', apiKey: 'sk-test', model, baseUrl })
+
+test('completeStream delivers deltas in order and resolves to the full text', async () => {
+  const deltas = []
+  const text = await client('mock').completeStream('Say hello', (delta) => {
+    deltas.push(delta)
+  })
+  assert.deepEqual(deltas, DELTAS)
+  assert.equal(text, 'Hello world')
+})
+
+test('completeStream rejects when onDelta throws', async () => {
+  let calls = 0
+  await assert.rejects(
+    client('mock').completeStream('Say hello', () => {
+      calls += 1
+      throw new Error('consumer gave up')
+    }),
+    /consumer gave up/,
+  )
+  assert.equal(calls, 1)
+})
+
+test('completeStream rejects when the provider fails mid-stream', async () => {
+  const deltas = []
+  await assert.rejects(
+    client('drop').completeStream('Say hello', (delta) => deltas.push(delta)),
+    /Stream error/,
+  )
+  assert.deepEqual(deltas, ['Hel'])
+})
+
+const transform = (transformation) => ({ type: 'Transform', transformation })
+
+// Chain of nodes, each one fed by the previous node's output
+function chainWorkflow(steps) {
+  const nodes = {}
+  const ids = steps.map(([name, nodeType]) => {
+    const id = randomUUID()
+    nodes[id] = {
+      id,
+      name,
+      description: '',
+      node_type: nodeType,
+      config: {},
+      input_schema: null,
+      output_schema: null,
+      retry_config: {
+        max_attempts: 0,
+        initial_delay_ms: 0,
+        backoff_multiplier: 1.0,
+        max_delay_ms: 0,
+        jitter_factor: 0.0,
+        retryable_errors: [],
+      },
+      timeout_seconds: null,
+      tags: [],
+    }
+    return id
+  })
+  const edge = { edge_type: 'DataFlow', condition: null, transform: null, metadata: {} }
+  const edges = ids.slice(1).map((id, index) => [ids[index], id, edge])
+  return JsWorkflow.fromJSON(
+    JSON.stringify({
+      id: randomUUID(),
+      name: 'three steps',
+      description: '',
+      graph: { nodes, edges, metadata: {} },
+      metadata: {},
+    }),
+  )
+}
+
+test('executeStream yields node events in execution order', async () => {
+  const workflow = chainWorkflow([
+    ['stringify', transform('json_stringify')],
+    ['shout', transform('uppercase')],
+    ['whisper', transform('lowercase')],
+  ])
+  const stream = new JsExecutor().executeStream(workflow)
+
+  const events = []
+  for await (const event of stream) {
+    events.push(event)
+  }
+
+  assert.deepEqual(
+    events.map(({ event, node_name }) => (node_name ? `${event}:${node_name}` : event)),
+    [
+      'workflow_started',
+      'node_started:stringify',
+      'node_completed:stringify',
+      'node_started:shout',
+      'node_completed:shout',
+      'node_started:whisper',
+      'node_completed:whisper',
+      'workflow_completed',
+    ],
+  )
+  assert.equal(events[0].total_nodes, 3)
+  assert.equal(events[4].output, 'NULL')
+  assert.ok(stream.result().isSuccess())
+})
+
+test('executeStream rejects the iterator when the workflow fails', async () => {
+  // A condition without a registered handler fails the whole workflow
+  const workflow = chainWorkflow([
+    ['stringify', transform('json_stringify')],
+    ['shout', transform('uppercase')],
+    ['route', { type: 'Condition', handler_id: 'missing' }],
+  ])
+
+  const seen = []
+  await assert.rejects(async () => {
+    for await (const { event, node_name } of new JsExecutor().executeStream(workflow)) {
+      seen.push(node_name ? `${event}:${node_name}` : event)
+    }
+  }, /no handler registered/)
+  assert.deepEqual(seen.slice(-2), ['node_started:route', 'node_failed:route'])
+})
+
+test('executeStream validates the stream mode', () => {
+  const workflow = chainWorkflow([['only', transform('identity')]])
+  assert.throws(() => new JsExecutor().executeStream(workflow, 'tokens'), /Unknown stream mode/)
+})
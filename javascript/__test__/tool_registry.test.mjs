@@ -0,0 +1,207 @@
+import assert from 'node:assert/strict'
+import { randomUUID } from 'node:crypto'
+import { createServer } from 'node:http'
+import { createRequire } from 'node:module'
+import { after, before, test } from 'node:test'
+
+const require = createRequire(import.meta.url)
+const { JsExecutor, JsToolRegistry, JsWorkflow, createTool } = require('../index.js')
+
+// OpenAI-compatible chat endpoint: asks for `add` with 2 and 3 until the
+// conversation holds a tool result, then answers with that result
+let server
+let baseUrl
+
+before(async () => {
+  server = createServer((req, res) => {
+    let body = ''
+    req.on('data', (chunk) => (body += chunk))
+    req.on('end', () => {
+      const { messages } = JSON.parse(body)
+      const result = messages.find((message) => message.role === 'tool')
+      const message = result
+        ? { role: 'assistant', content: `The sum is ${result.content}` }
+        : {
+            role: 'assistant',
+            content: null,
+            tool_calls: [
+              {
+                id: 'call_1',
+                type: 'function',
+                function: { name: 'add', arguments: JSON.stringify({ a: 2, b: 3 }) },
+              },
+            ],
+          }
+      res.writeHead(200, { 'Content-Type': 'application/json' })
+      res.end(
+        JSON.stringify({
+          id: 'completion',
+          model: 'gpt-4o-mini',
+          choices: [
+            { index: 0, message, finish_reason: result ? 'stop' : 'tool_calls' },
+          ],
+          usage: { prompt_tokens: 10, completion_tokens: 5, total_tokens: 15 },
+        }),
+      )
+    })
+  })
+  await new Promise((resolve) => server.listen(0, '127.0.0.1', resolve))
+  baseUrl = `http://127.0.0.1:${server.address().port}`
+})
+
+after(() => server.close())
+
+// One agent node allowed to call the `add` tool
+function adderWorkflow() {
+  const id = randomUUID()
+  const nodes = {
+    [id]: {
+      id,
+      name: 'adder',
+      description: '',
+      node_type: {
+        type: 'Agent',
+        agent_id: randomUUID(),
+        prompt_template: 'What is 2 + 3?',
+        conversational_context: null,
+        system_prompt_override: null,
+      },
+      config: {
+        llm_config: {
+          provider: 'OpenAI',
+          api_key: 'sk-test',
+          model: 'gpt-4o-mini',
+          base_url: baseUrl,
+          organization: null,
+        },
+        tools: ['add'],
+      },
+      input_schema: null,
+      output_schema: null,
+      retry_config: {
+        max_attempts: 0,
+        initial_delay_ms: 0,
+        backoff_multiplier: 1.0,
+        max_delay_ms: 0,
+        jitter_factor: 0.0,
+        retryable_errors: [],
+      },
+      timeout_seconds: null,
+      tags: [],
+    },
+  }
+  return JsWorkflow.fromJSON(
+    JSON.stringify({
+      id: randomUUID(),
+      name: 'adder',
+      description: '',
+      graph: { nodes, edges: [], metadata: {} },
+      metadata: {},
+    }),
+  )
+}
+
+test('an agent node calls a JS tool from the executor registry', async () => {
+  const calls = []
+  const registry = new JsToolRegistry()
+  registry.register(
+    createTool({
+      name: 'add',
+      description: 'Add two numbers',
+      parameters: {
+        type: 'object',
+        properties: { a: { type: 'number' }, b: { type: 'number' } },
+        required: ['a', 'b'],
+      },
+      handler: async ({ a, b }) => {
+        calls.push([a, b])
+        return a + b
+      },
+    }),
+  )
+  const executor = new JsExecutor()
+  executor.setToolRegistry(registry)
+
+  const result = await executor.execute(adderWorkflow())
+  assert.ok(result.isSuccess())
+  assert.deepEqual(calls, [[2, 3]])
+  assert.equal(result.getNodeOutput('adder'), 'The sum is 5')
+  const [record] = result.getToolCalls('adder')
+  assert.equal(record.toolName, 'add')
+  assert.ok(record.success)
+})
+
+test('an agent node calls JS tools set on the node', async () => {
+  const workflow = adderWorkflow()
+  workflow.setNodeTools('adder', [
+    createTool({
+      name: 'add',
+      description: 'Add two numbers',
+      handler: ({ a, b }) => a + b,
+    }),
+  ])
+
+  const result = await new JsExecutor().execute(workflow)
+  assert.ok(result.isSuccess())
+  assert.equal(result.getNodeOutput('adder'), 'The sum is 5')
+  const { nodes } = JSON.parse(workflow.toJSON()).graph
+  assert.deepEqual(Object.values(nodes)[0].config.tools, ['add'])
+})
+
+test('setNodeTools rejects unknown nodes', () => {
+  const tool = createTool({ name: 'add', description: '', handler: () => 0 })
+  assert.throws(() => adderWorkflow().setNodeTools('missing', [tool]), /no node named 'missing'/)
+})
+
+test('a handler may return a plain value', async () => {
+  const registry = new JsToolRegistry()
+  registry.register(createTool({ name: 'answer', description: '', handler: () => ({ value: 42 }) }))
+  assert.deepEqual(JSON.parse(await registry.execute('answer', '{}')), { value: 42 })
+})
+
+test('a throwing handler fails the tool call', async () => {
+  const registry = new JsToolRegistry()
+  registry.register(
+    createTool({
+      name: 'boom',
+      description: '',
+      handler: () => {
+        throw new Error('kaboom')
+      },
+    }),
+  )
+  await assert.rejects(registry.execute('boom', '{}'), /Tool 'boom' failed: .*kaboom/)
+})
+
+test('a rejecting handler fails the tool call', async () => {
+  const registry = new JsToolRegistry()
+  registry.register(
+    createTool({
+      name: 'fizzle',
+      description: '',
+      handler: async () => {
+        throw new Error('fizzled out')
+      },
+    }),
+  )
+  await assert.rejects(registry.execute('fizzle', '{}'), /Tool 'fizzle' failed: .*fizzled out/)
+})
+
+test('an agent node reports a failing JS tool as a failed call', async () => {
+  const workflow = adderWorkflow()
+  workflow.setNodeTools('adder', [
+    createTool({
+      name: 'add',
+      description: 'Add two numbers',
+      handler: async () => {
+        throw new Error('adder is broken')
+      },
+    }),
+  ])
+
+  const result = await new JsExecutor().execute(workflow)
+  assert.ok(result.isSuccess())
+  const [record] = result.getToolCalls('adder')
+  assert.equal(record.toolName, 'add')
+  assert.equal(record.success, false)
+})
@@ -0,0 +1,78 @@
+import test from 'node:test'
+import assert from 'node:assert/strict'
+import { createRequire } from 'node:module'
+
+const require = createRequire(import.meta.url)
+const { validateJson } = require('../index.js')
+
+const failures = (result) => result.errors.map((e) => [e.fieldPath, e.errorCode])
+
+test('nested objects report the path of each failure', () => {
+  const schema = {
+    type: 'object',
+    properties: {
+      order: {
+        type: 'object',
+        properties: {
+          id: { type: 'integer' },
+          customer: {
+            type: 'object',
+            properties: { email: { type: 'string', pattern: '@' } },
+            required: ['email'],
+          },
+        },
+        required: ['id', 'customer'],
+      },
+    },
+  }
+
+  const ok = validateJson({ order: { id: 1, customer: { email: 'a@b.c' } } }, schema)
+  assert.equal(ok.isValid, true)
+  assert.deepEqual(ok.errors, [])
+
+  const result = validateJson({ order: { id: 1.5, customer: { email: 'nope' } } }, schema)
+  assert.equal(result.isValid, false)
+  assert.deepEqual(failures(result), [
+    ['root.order.customer.email', 'PATTERN_MISMATCH'],
+    ['root.order.id', 'TYPE_MISMATCH'],
+  ])
+  assert.equal(result.errors[1].expected, 'integer')
+  assert.equal(result.errors[1].actual, 'number')
+})
+
+test('array constraints are enforced per item and per array', () => {
+  const schema = {
+    type: 'array',
+    items: { type: 'number', maximum: 10 },
+    minItems: 1,
+    maxItems: 3,
+    uniqueItems: true,
+  }
+
+  assert.equal(validateJson([1, 2, 3], schema).isValid, true)
+  assert.deepEqual(failures(validateJson([], schema)), [['root', 'ARRAY_TOO_SHORT']])
+  assert.deepEqual(failures(validateJson([1, 11, 1, 2], schema)), [
+    ['root', 'ARRAY_TOO_LONG'],
+    ['root[2]', 'DUPLICATE_ITEMS'],
+    ['root[1]', 'NUMBER_TOO_LARGE'],
+  ])
+})
+
+test('local $ref and unsupported keywords', () => {
+  const schema = {
+    definitions: { point: { type: 'array', items: [{ type: 'number' }, { type: 'number' }], additionalItems: false } },
+    type: 'object',
+    properties: { path: { type: 'array', items: { $ref: '#/definitions/point' } } },
+  }
+  assert.equal(validateJson({ path: [[0, 0], [1, 2]] }, schema).isValid, true)
+  assert.deepEqual(failures(validateJson({ path: [[0, 0], [1, 'y', 3]] }, schema)), [
+    ['root.path[1][1]', 'TYPE_MISMATCH'],
+    ['root.path[1][2]', 'ADDITIONAL_ITEMS'],
+  ])
+
+  const unsupported = validateJson({}, { unevaluatedProperties: false })
+  assert.deepEqual(failures(unsupported), [['root', 'UNSUPPORTED_KEYWORD']])
+  assert.match(unsupported.errors[0].message, /unevaluatedProperties/)
+
+  assert.throws(() => validateJson({}, 'not a schema'), /Schema must be an object or a boolean/)
+})
@@ -1,4 +1,4 @@
-//! JavaScript / Node.js bindings for the `GraphBit` memory layer via NAPI-RS.
+//! JavaScript / Node.js bindings for the `GraphBit` memory layer and workflow results via NAPI-RS.
 
 #![allow(missing_docs)]
 
@@ -6,21 +6,194 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction};
+use napi::{Env, JsFunction, JsObject, JsUnknown, ValueType};
 use napi_derive::napi;
 use std::sync::OnceLock;
 
 // Re-use the tokio runtime across calls.
 static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
 
+// Worker thread count set by `configureRuntime()` before the runtime is built.
+static RUNTIME_WORKER_THREADS: OnceLock<usize> = OnceLock::new();
+
 fn get_runtime() -> &'static tokio::runtime::Runtime {
     RUNTIME.get_or_init(|| {
-        tokio::runtime::Builder::new_multi_thread()
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        if let Some(&threads) = RUNTIME_WORKER_THREADS.get() {
+            builder.worker_threads(threads);
+        }
+        builder
             .enable_all()
             .build()
             .expect("Failed to build tokio runtime for graphbit-js")
     })
 }
 
+// ---------------------------------------------------------------------------
+// System
+// ---------------------------------------------------------------------------
+
+// Set by `init()`, or by the first `healthCheck()`; the base of reported uptime.
+static INIT_TIME: OnceLock<std::time::Instant> = OnceLock::new();
+
+/// Options for `configureRuntime()`.
+#[napi(object)]
+pub struct JsRuntimeOptions {
+    /// Worker threads of the runtime that executes workflows.
+    pub max_threads: Option<u32>,
+}
+
+/// Outbound HTTP options for `init()`.
+///
+/// Without `proxy`, the `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` environment
+/// variables are honored.
+#[napi(object)]
+pub struct JsInitOptions {
+    /// Proxy URL for all outbound HTTP.
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy`.
+    pub no_proxy: Option<String>,
+    /// PEM file of root certificates trusted in addition to the system ones.
+    pub ca_bundle: Option<String>,
+}
+
+/// Host and runtime details returned by `getSystemInfo()`.
+#[napi(object)]
+pub struct JsSystemInfo {
+    pub version: String,
+    pub node_version: String,
+    pub os_name: String,
+    pub os_version: String,
+    pub kernel_version: String,
+    pub cpu_model: String,
+    pub cpu_count: u32,
+    pub total_memory_bytes: f64,
+    pub available_memory_bytes: f64,
+    pub runtime_initialized: bool,
+    pub runtime_worker_threads: Option<u32>,
+}
+
+/// Health report returned by `healthCheck()`.
+#[napi(object)]
+pub struct JsHealthCheck {
+    pub healthy: bool,
+    pub uptime_seconds: f64,
+    pub runtime_initialized: bool,
+    pub available_memory_bytes: f64,
+    pub memory_healthy: bool,
+}
+
+/// Total and available memory in bytes.
+fn memory_bytes() -> (u64, u64) {
+    use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+
+    let system = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()),
+    );
+    (system.total_memory(), system.available_memory())
+}
+
+/// Seconds since `init()`, or since the first call when `init()` was skipped.
+fn uptime_seconds() -> f64 {
+    INIT_TIME
+        .get_or_init(std::time::Instant::now)
+        .elapsed()
+        .as_secs_f64()
+}
+
+/// Initialize the library and start the uptime clock.
+///
+/// Proxy and CA bundle options apply to clients created afterwards.
+#[napi]
+pub fn init(options: Option<JsInitOptions>) -> Result<()> {
+    INIT_TIME.get_or_init(std::time::Instant::now);
+    if let Some(options) = options {
+        let factory = graphbit_core::HttpClientFactory::global();
+        let mut settings = factory.settings();
+        settings.proxy = options.proxy.or(settings.proxy);
+        settings.no_proxy = options.no_proxy.or(settings.no_proxy);
+        settings.ca_bundle = options.ca_bundle.map(Into::into).or(settings.ca_bundle);
+        settings.validate().map_err(to_napi_error)?;
+        factory.set_settings(settings);
+    }
+    graphbit_core::init().map_err(to_napi_error)
+}
+
+/// Configure the runtime that executes workflows.
+///
+/// Must be called before the first workflow runs; the runtime is built once.
+#[napi]
+pub fn configure_runtime(options: JsRuntimeOptions) -> Result<()> {
+    if RUNTIME.get().is_some() {
+        return Err(to_napi_error(
+            "configureRuntime() must be called before the first workflow runs",
+        ));
+    }
+    if let Some(max_threads) = options.max_threads {
+        if max_threads == 0 {
+            return Err(to_napi_error("maxThreads must be greater than 0"));
+        }
+        RUNTIME_WORKER_THREADS
+            .set(max_threads as usize)
+            .map_err(|_| to_napi_error("configureRuntime() can only set maxThreads once"))?;
+    }
+    Ok(())
+}
+
+/// Host, Node.js and runtime details.
+#[napi]
+pub fn get_system_info(env: Env) -> Result<JsSystemInfo> {
+    use sysinfo::{CpuRefreshKind, RefreshKind, System};
+
+    let node_version = env
+        .get_global()?
+        .get_named_property::<JsObject>("process")?
+        .get_named_property::<napi::JsString>("version")?
+        .into_utf8()?
+        .into_owned()?;
+    let system =
+        System::new_with_specifics(RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing()));
+    let cpu_model = system
+        .cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .filter(|brand| !brand.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let (total_memory, available_memory) = memory_bytes();
+
+    Ok(JsSystemInfo {
+        version: graphbit_core::VERSION.to_string(),
+        node_version,
+        os_name: System::name().unwrap_or_else(|| "unknown".to_string()),
+        os_version: System::long_os_version().unwrap_or_else(|| "unknown".to_string()),
+        kernel_version: System::kernel_version().unwrap_or_else(|| "unknown".to_string()),
+        cpu_model,
+        cpu_count: system.cpus().len() as u32,
+        total_memory_bytes: total_memory as f64,
+        available_memory_bytes: available_memory as f64,
+        runtime_initialized: RUNTIME.get().is_some(),
+        runtime_worker_threads: RUNTIME
+            .get()
+            .map(|runtime| runtime.metrics().num_workers() as u32),
+    })
+}
+
+/// Uptime, runtime and memory health.
+#[napi]
+pub fn health_check() -> JsHealthCheck {
+    let (_, available_memory) = memory_bytes();
+    // At least 100MB available
+    let memory_healthy = available_memory > 100 * 1024 * 1024;
+    JsHealthCheck {
+        healthy: memory_healthy,
+        uptime_seconds: uptime_seconds(),
+        runtime_initialized: RUNTIME.get().is_some(),
+        available_memory_bytes: available_memory as f64,
+        memory_healthy,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Data objects exposed to JS
 // ---------------------------------------------------------------------------
@@ -60,6 +233,17 @@ pub struct JsScoredMemory {
     pub score: f64,
 }
 
+/// A tool call made by an agent node during workflow execution.
+#[napi(object)]
+pub struct JsToolCallRecord {
+    pub tool_name: String,
+    pub arguments: String,
+    pub output: String,
+    pub success: bool,
+    pub duration_ms: f64,
+    pub attempt: u32,
+}
+
 /// A historical record of a memory mutation.
 #[napi(object)]
 pub struct JsMemoryHistory {
@@ -70,6 +254,35 @@ pub struct JsMemoryHistory {
     pub timestamp: String,
 }
 
+/// Outcome of a provider health check.
+#[napi(object)]
+pub struct JsProviderHealth {
+    pub provider: String,
+    pub model: String,
+    pub reachable: bool,
+    pub authenticated: bool,
+    pub model_available: bool,
+    pub latency_ms: f64,
+    pub error: Option<String>,
+}
+
+/// Prompts an agent node would send, without inputs or upstream outputs.
+#[napi(object)]
+pub struct JsRenderedPrompt {
+    pub node: String,
+    pub system_prompt: Option<String>,
+    pub prompt: String,
+}
+
+/// Outcome of checking a workflow without running it.
+#[napi(object)]
+pub struct JsDryRunReport {
+    pub ok: bool,
+    pub validation_error: Option<String>,
+    pub providers: Vec<JsProviderHealth>,
+    pub prompts: Vec<JsRenderedPrompt>,
+}
+
 // ---------------------------------------------------------------------------
 // Conversion helpers
 // ---------------------------------------------------------------------------
@@ -104,6 +317,29 @@ fn core_history_to_js(h: graphbit_core::memory::MemoryHistory) -> JsMemoryHistor
     }
 }
 
+fn core_health_to_js(h: graphbit_core::llm::ProviderHealth) -> JsProviderHealth {
+    JsProviderHealth {
+        provider: h.provider,
+        model: h.model,
+        reachable: h.reachable,
+        authenticated: h.authenticated,
+        model_available: h.model_available,
+        latency_ms: h.latency_ms as f64,
+        error: h.error,
+    }
+}
+
+fn core_tool_call_to_js(r: &graphbit_core::types::ToolCallRecord) -> JsToolCallRecord {
+    JsToolCallRecord {
+        tool_name: r.tool_name.clone(),
+        arguments: r.arguments.to_string(),
+        output: r.output.to_string(),
+        success: r.success,
+        duration_ms: r.duration_ms,
+        attempt: r.attempt,
+    }
+}
+
 fn js_scope_to_core(scope: Option<JsScope>) -> graphbit_core::memory::MemoryScope {
     match scope {
         Some(s) => graphbit_core::memory::MemoryScope {
@@ -136,9 +372,9 @@ impl JsMemoryConfig {
     /// `llm_provider` - one of "openai", "anthropic", etc.
     /// `llm_api_key` - API key for the LLM provider.
     /// `llm_model` - model name (e.g. "gpt-4o-mini").
-    /// `embedding_provider` - one of "openai", "huggingface".
     /// `embedding_api_key` - API key for the embedding provider.
     /// `embedding_model` - model name (e.g. "text-embedding-3-small").
+    /// `embedding_provider` - one of "openai" (default), "voyage", "jina".
     #[napi(constructor)]
     pub fn new(
         llm_provider: String,
@@ -148,6 +384,7 @@ impl JsMemoryConfig {
         embedding_model: Option<String>,
         db_path: Option<String>,
         similarity_threshold: Option<f64>,
+        embedding_provider: Option<String>,
     ) -> Result<Self> {
         let llm_config = build_llm_config(
             &llm_provider,
@@ -155,15 +392,11 @@ impl JsMemoryConfig {
             &llm_model.unwrap_or_else(|| "gpt-4o-mini".to_string()),
         )?;
 
-        let embedding_config = graphbit_core::embeddings::EmbeddingConfig {
-            provider: graphbit_core::embeddings::EmbeddingProvider::OpenAI,
-            api_key: embedding_api_key,
-            model: embedding_model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
-            base_url: None,
-            timeout_seconds: None,
-            max_batch_size: None,
-            extra_params: HashMap::new(),
-        };
+        let embedding_config = build_embedding_config(
+            embedding_provider.as_deref().unwrap_or("openai"),
+            embedding_api_key,
+            embedding_model,
+        )?;
 
         let mut config = graphbit_core::memory::MemoryConfig::new(llm_config, embedding_config);
 
@@ -178,6 +411,38 @@ impl JsMemoryConfig {
     }
 }
 
+fn build_embedding_config(
+    provider: &str,
+    api_key: String,
+    model: Option<String>,
+) -> Result<graphbit_core::embeddings::EmbeddingConfig> {
+    use graphbit_core::embeddings::{EmbeddingConfig, EmbeddingProvider};
+    match provider.to_lowercase().as_str() {
+        "openai" => Ok(EmbeddingConfig {
+            provider: EmbeddingProvider::OpenAI,
+            api_key,
+            model: model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
+            base_url: None,
+            timeout_seconds: None,
+            timeouts: graphbit_core::http_client::RequestTimeouts::default(),
+            max_batch_size: None,
+            extra_params: HashMap::new(),
+            retry: None,
+        }),
+        "voyage" => Ok(EmbeddingConfig::voyage(
+            api_key,
+            model.unwrap_or_else(|| "voyage-3.5".to_string()),
+        )),
+        "jina" => Ok(EmbeddingConfig::jina(
+            api_key,
+            model.unwrap_or_else(|| "jina-embeddings-v3".to_string()),
+        )),
+        other => Err(napi::Error::from_reason(format!(
+            "Unsupported embedding provider for JS bindings: {other}"
+        ))),
+    }
+}
+
 fn build_llm_config(
     provider: &str,
     api_key: &str,
@@ -188,6 +453,8 @@ fn build_llm_config(
         "openai" => Ok(LlmConfig::openai(api_key, model)),
         "anthropic" => Ok(LlmConfig::anthropic(api_key, model)),
         "deepseek" => Ok(LlmConfig::deepseek(api_key, model)),
+        "fireworks" => Ok(LlmConfig::fireworks(api_key, model)),
+        "together" | "togetherai" => Ok(LlmConfig::togetherai(api_key, model)),
         other => Err(napi::Error::from_reason(format!(
             "Unsupported LLM provider for JS bindings: {other}"
         ))),
@@ -255,7 +522,7 @@ impl JsMemoryClient {
         let core_scope = js_scope_to_core(scope);
         let results = self
             .service
-            .search(&query, &core_scope, top_k.unwrap_or(10) as usize)
+            .search(&query, &core_scope, top_k.unwrap_or(10) as usize, None)
             .await
             .map_err(to_napi_error)?;
 
@@ -327,3 +594,1381 @@ impl JsMemoryClient {
         Ok(entries.into_iter().map(core_history_to_js).collect())
     }
 }
+
+// ---------------------------------------------------------------------------
+// LLM client
+// ---------------------------------------------------------------------------
+
+/// Provider settings for `new JsLlmClient()`.
+#[napi(object)]
+pub struct JsLlmConfig {
+    /// One of "openai", "anthropic", "deepseek", "fireworks" or "together".
+    pub provider: String,
+    pub api_key: String,
+    pub model: Option<String>,
+    /// Endpoint to use instead of the provider's public API.
+    pub base_url: Option<String>,
+    /// Total time a request may take, in milliseconds.
+    pub timeout_ms: Option<u32>,
+    /// Time allowed for establishing a connection, in milliseconds.
+    pub connect_timeout_ms: Option<u32>,
+}
+
+/// Generation settings for a single completion.
+#[napi(object)]
+pub struct JsCompletionOptions {
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f64>,
+}
+
+fn build_llm_request(
+    prompt: String,
+    options: Option<JsCompletionOptions>,
+) -> graphbit_core::llm::LlmRequest {
+    let mut request = graphbit_core::llm::LlmRequest::new(prompt);
+    if let Some(options) = options {
+        if let Some(max_tokens) = options.max_tokens {
+            request = request.with_max_tokens(max_tokens);
+        }
+        if let Some(temperature) = options.temperature {
+            request = request.with_temperature(temperature as f32);
+        }
+    }
+    request
+}
+
+/// Client for one LLM provider and model.
+#[napi]
+pub struct JsLlmClient {
+    provider: Arc<dyn graphbit_core::llm::LlmProviderTrait>,
+}
+
+#[napi]
+impl JsLlmClient {
+    #[napi(constructor)]
+    pub fn new(config: JsLlmConfig) -> Result<Self> {
+        use graphbit_core::http_client::RequestTimeouts;
+        use graphbit_core::llm::{LlmConfig, LlmProviderFactory};
+
+        let mut llm_config = build_llm_config(
+            &config.provider,
+            &config.api_key,
+            config.model.as_deref().unwrap_or("gpt-4o-mini"),
+        )?;
+        if let Some(url) = config.base_url {
+            match &mut llm_config {
+                LlmConfig::OpenAI { base_url, .. }
+                | LlmConfig::Anthropic { base_url, .. }
+                | LlmConfig::DeepSeek { base_url, .. }
+                | LlmConfig::Fireworks { base_url, .. }
+                | LlmConfig::TogetherAi { base_url, .. } => *base_url = Some(url),
+                _ => {}
+            }
+        }
+        let llm_config = llm_config.with_timeouts(RequestTimeouts {
+            timeout_ms: config.timeout_ms.map(u64::from),
+            connect_timeout_ms: config.connect_timeout_ms.map(u64::from),
+        });
+        let provider = LlmProviderFactory::create_provider(llm_config).map_err(to_napi_error)?;
+        Ok(Self {
+            provider: Arc::from(provider),
+        })
+    }
+
+    /// Generate a completion and resolve to its text.
+    #[napi]
+    pub async fn complete(
+        &self,
+        prompt: String,
+        options: Option<JsCompletionOptions>,
+    ) -> Result<String> {
+        let provider = self.provider.clone();
+        let request = build_llm_request(prompt, options);
+        let response = get_runtime()
+            .spawn(async move { provider.complete(request).await })
+            .await
+            .map_err(to_napi_error)?
+            .map_err(to_napi_error)?;
+        Ok(response.content)
+    }
+
+    /// Check that the provider is reachable, accepts the credentials and
+    /// serves the model.
+    #[napi]
+    pub async fn health_check(&self) -> Result<JsProviderHealth> {
+        let provider = self.provider.clone();
+        let health = get_runtime()
+            .spawn(async move { provider.health_check().await })
+            .await
+            .map_err(to_napi_error)?;
+        Ok(core_health_to_js(health))
+    }
+
+    /// Stream a completion, calling `onDelta` with each piece of text as it
+    /// arrives, and resolve to the full text. The next piece is not read from
+    /// the provider until `onDelta` returns. The promise rejects when the
+    /// provider fails mid-stream or `onDelta` throws.
+    #[napi(
+        ts_args_type = "prompt: string, onDelta: (delta: string) => void, options?: JsCompletionOptions",
+        ts_return_type = "Promise<string>"
+    )]
+    pub fn complete_stream(
+        &self,
+        env: Env,
+        prompt: String,
+        on_delta: JsFunction,
+        options: Option<JsCompletionOptions>,
+    ) -> Result<JsObject> {
+        use futures::StreamExt;
+
+        // `CalleeHandled` reports a throwing callback back to Rust instead of
+        // aborting the process, but passes a leading error argument. Calling
+        // through `Function.prototype.call.bind(onDelta)` drops that argument.
+        let on_delta = on_delta.coerce_to_object()?;
+        let call = on_delta
+            .get_named_property::<JsFunction>("call")?
+            .coerce_to_object()?;
+        let bind: JsFunction = call.get_named_property("bind")?;
+        let callback = JsFunction::try_from(bind.call(Some(&call), &[on_delta])?)?;
+        let on_delta: ThreadsafeFunction<String, ErrorStrategy::CalleeHandled> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<String>| {
+                Ok(vec![ctx.value])
+            })?;
+
+        let provider = self.provider.clone();
+        let request = build_llm_request(prompt, options);
+        let stream = async move {
+            let mut stream = provider.stream(request).await.map_err(to_napi_error)?;
+            let mut content = String::new();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(to_napi_error)?;
+                if chunk.content.is_empty() {
+                    continue;
+                }
+                on_delta
+                    .call_async::<Option<serde_json::Value>>(Ok(chunk.content.clone()))
+                    .await?;
+                content.push_str(&chunk.content);
+            }
+            Ok(content)
+        };
+        env.execute_tokio_future(
+            async move { get_runtime().spawn(stream).await.map_err(to_napi_error)? },
+            |_, content: String| Ok(content),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Workflows
+// ---------------------------------------------------------------------------
+
+/// A workflow definition that can be saved to and loaded from versioned JSON.
+#[napi]
+pub struct JsWorkflow {
+    inner: graphbit_core::workflow::Workflow,
+    /// Tools given to agent nodes with `setNodeTools`
+    tools: graphbit_core::tools::ToolRegistry,
+}
+
+#[napi]
+impl JsWorkflow {
+    /// Load a workflow from JSON produced by `toJSON()`. Throws when the JSON
+    /// has a `schema_version` newer than this version of GraphBit supports.
+    #[napi(factory, js_name = "fromJSON")]
+    pub fn from_json(json: String) -> Result<Self> {
+        let inner = graphbit_core::workflow::Workflow::from_json(&json).map_err(to_napi_error)?;
+        Ok(Self {
+            inner,
+            tools: graphbit_core::tools::ToolRegistry::new(),
+        })
+    }
+
+    /// Serialize the workflow, including node IDs and `schema_version`.
+    #[napi(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<String> {
+        self.inner.to_json().map_err(to_napi_error)
+    }
+
+    /// Workflow name.
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    /// Number of nodes in the workflow graph.
+    #[napi]
+    pub fn node_count(&self) -> u32 {
+        self.inner.graph.node_count() as u32
+    }
+
+    /// Number of edges in the workflow graph.
+    #[napi]
+    pub fn edge_count(&self) -> u32 {
+        self.inner.graph.edge_count() as u32
+    }
+
+    /// Validate the workflow graph and node configuration.
+    #[napi]
+    pub fn validate(&self) -> Result<()> {
+        self.inner.validate().map_err(to_napi_error)
+    }
+
+    /// Let the agent node named `nodeName` call `tools`, replacing the tools it
+    /// lists. The tools run with the workflow, so no executor registry is
+    /// needed; they take precedence over executor tools with the same name.
+    #[napi(ts_args_type = "nodeName: string, tools: JsTool[]")]
+    pub fn set_node_tools(
+        &mut self,
+        node_name: String,
+        tools: Vec<ClassInstance<JsTool>>,
+    ) -> Result<()> {
+        use graphbit_core::graph::NodeType;
+
+        let graph = &mut self.inner.graph;
+        let node_id = graph
+            .get_node_id_by_name(&node_name)
+            .ok_or_else(|| to_napi_error(format!("Workflow has no node named '{node_name}'")))?;
+        let node = graph
+            .get_node(&node_id)
+            .cloned()
+            .ok_or_else(|| to_napi_error(format!("Workflow has no node named '{node_name}'")))?;
+        if !matches!(node.node_type, NodeType::Agent { .. }) {
+            return Err(to_napi_error(format!(
+                "Node '{node_name}' is not an agent node; only agent nodes call tools"
+            )));
+        }
+        for tool in &tools {
+            self.tools
+                .register_tool(tool.metadata.clone(), tool.handler.clone())
+                .map_err(to_napi_error)?;
+        }
+        let node = node.with_tools(tools.iter().map(|tool| tool.metadata.name.clone()));
+        graph.replace_node(&node_id, node).map_err(to_napi_error)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Executor
+// ---------------------------------------------------------------------------
+
+/// Retry policy for nodes without their own. `maxAttempts` counts every
+/// attempt, the first one included.
+#[napi(object)]
+pub struct JsRetryPolicy {
+    pub max_attempts: u32,
+    pub backoff_ms: Option<u32>,
+    pub exponential: Option<bool>,
+    pub max_backoff_ms: Option<u32>,
+    pub jitter: Option<f64>,
+}
+
+/// Circuit breaker settings for agent nodes; unset fields keep their defaults.
+#[napi(object)]
+pub struct JsCircuitBreakerOptions {
+    pub failure_threshold: Option<u32>,
+    pub recovery_timeout_ms: Option<u32>,
+    pub success_threshold: Option<u32>,
+    pub failure_window_ms: Option<u32>,
+}
+
+/// Options for `new Executor(options)`.
+///
+/// `mode` is one of "balanced" (default), "high_throughput", "low_latency" or
+/// "memory_optimized"; the other options override the mode's settings.
+/// `globals` are referenced as `{{globals.NAME}}` by every node;
+/// `systemPreamble` and `promptSuffix` surround every agent node's prompts.
+#[napi(object)]
+pub struct JsExecutorOptions {
+    pub mode: Option<String>,
+    pub max_concurrency: Option<u32>,
+    pub node_type_limits: Option<HashMap<String, u32>>,
+    pub fail_fast: Option<bool>,
+    pub max_node_execution_time_ms: Option<u32>,
+    pub retry: Option<JsRetryPolicy>,
+    pub circuit_breaker: Option<JsCircuitBreakerOptions>,
+    pub globals: Option<HashMap<String, serde_json::Value>>,
+    pub system_preamble: Option<String>,
+    pub prompt_suffix: Option<String>,
+}
+
+/// Concurrency statistics accumulated over every run of an executor.
+#[napi(object)]
+pub struct JsConcurrencyStats {
+    pub total_permit_acquisitions: f64,
+    pub total_wait_time_ms: f64,
+    pub current_active_tasks: u32,
+    pub peak_active_tasks: u32,
+    pub permit_failures: f64,
+    pub avg_wait_time_ms: f64,
+    pub keyed_lock_acquisitions: f64,
+    pub keyed_lock_wait_time_ms: f64,
+}
+
+/// Runs workflows with the configured presets and concurrency limits.
+#[napi]
+pub struct JsExecutor {
+    mode: String,
+    concurrency_manager: Arc<graphbit_core::types::ConcurrencyManager>,
+    fail_fast: bool,
+    max_node_execution_time_ms: Option<u64>,
+    retry: Option<graphbit_core::types::RetryConfig>,
+    circuit_breaker: Option<graphbit_core::types::CircuitBreakerConfig>,
+    prompt_defaults: graphbit_core::types::PromptDefaults,
+    tool_registry: Option<graphbit_core::tools::ToolRegistry>,
+}
+
+#[napi]
+impl JsExecutor {
+    #[napi(constructor)]
+    pub fn new(options: Option<JsExecutorOptions>) -> Result<Self> {
+        use graphbit_core::types::{
+            CircuitBreakerConfig, ConcurrencyConfig, ConcurrencyManager, PromptDefaults,
+            RetryConfig,
+        };
+
+        let options = options.unwrap_or(JsExecutorOptions {
+            mode: None,
+            max_concurrency: None,
+            node_type_limits: None,
+            fail_fast: None,
+            max_node_execution_time_ms: None,
+            retry: None,
+            circuit_breaker: None,
+            globals: None,
+            system_preamble: None,
+            prompt_suffix: None,
+        });
+        let mode = options.mode.unwrap_or_else(|| "balanced".to_string());
+        let mut concurrency = match mode.as_str() {
+            "balanced" | "low_latency" => ConcurrencyConfig::default(),
+            "high_throughput" => ConcurrencyConfig::high_throughput(),
+            "memory_optimized" => ConcurrencyConfig::memory_optimized(),
+            _ => {
+                return Err(to_napi_error(format!(
+                    "Unknown executor mode '{mode}': expected balanced, high_throughput, low_latency or memory_optimized"
+                )));
+            }
+        };
+        if let Some(max_concurrency) = options.max_concurrency {
+            if max_concurrency == 0 {
+                return Err(to_napi_error("maxConcurrency must be greater than 0"));
+            }
+            concurrency.global_max_concurrency = max_concurrency as usize;
+        }
+        for (node_type, limit) in options.node_type_limits.unwrap_or_default() {
+            if limit == 0 {
+                return Err(to_napi_error(format!(
+                    "nodeTypeLimits.{node_type} must be greater than 0"
+                )));
+            }
+            concurrency
+                .node_type_limits
+                .insert(node_type, limit as usize);
+        }
+        if options.max_node_execution_time_ms == Some(0) {
+            return Err(to_napi_error(
+                "maxNodeExecutionTimeMs must be greater than 0",
+            ));
+        }
+
+        let retry = match options.retry {
+            Some(policy) => {
+                if policy.max_attempts == 0 {
+                    return Err(to_napi_error("retry.maxAttempts must be at least 1"));
+                }
+                let defaults = RetryConfig::default();
+                let backoff_ms = policy
+                    .backoff_ms
+                    .map_or(defaults.initial_delay_ms, u64::from);
+                let multiplier = if policy.exponential.unwrap_or(true) {
+                    2.0
+                } else {
+                    1.0
+                };
+                Some(
+                    RetryConfig::new(policy.max_attempts - 1)
+                        .with_exponential_backoff(
+                            backoff_ms,
+                            multiplier,
+                            policy
+                                .max_backoff_ms
+                                .map_or(defaults.max_delay_ms, u64::from),
+                        )
+                        .with_jitter(policy.jitter.unwrap_or(defaults.jitter_factor)),
+                )
+            }
+            None => None,
+        };
+        let circuit_breaker = options.circuit_breaker.map(|options| {
+            let defaults = CircuitBreakerConfig::default();
+            CircuitBreakerConfig {
+                failure_threshold: options
+                    .failure_threshold
+                    .unwrap_or(defaults.failure_threshold),
+                recovery_timeout_ms: options
+                    .recovery_timeout_ms
+                    .map_or(defaults.recovery_timeout_ms, u64::from),
+                success_threshold: options
+                    .success_threshold
+                    .unwrap_or(defaults.success_threshold),
+                failure_window_ms: options
+                    .failure_window_ms
+                    .map_or(defaults.failure_window_ms, u64::from),
+            }
+        });
+
+        Ok(Self {
+            mode,
+            concurrency_manager: Arc::new(ConcurrencyManager::new(concurrency)),
+            fail_fast: options.fail_fast.unwrap_or(false),
+            max_node_execution_time_ms: options.max_node_execution_time_ms.map(u64::from),
+            retry,
+            circuit_breaker,
+            prompt_defaults: PromptDefaults {
+                system_preamble: options.system_preamble,
+                prompt_suffix: options.prompt_suffix,
+                globals: options.globals.unwrap_or_default(),
+            },
+            tool_registry: None,
+        })
+    }
+
+    /// Name of the executor mode.
+    #[napi(getter)]
+    pub fn mode(&self) -> String {
+        self.mode.clone()
+    }
+
+    /// Let agent nodes call the tools of `registry` that they list under
+    /// `tools`. Tools registered afterwards are available to later runs too.
+    #[napi]
+    pub fn set_tool_registry(&mut self, registry: &JsToolRegistry) {
+        self.tool_registry = Some(registry.inner.clone());
+    }
+
+    /// Execute a workflow, resolving to its result once every node has run.
+    #[napi]
+    pub async fn execute(&self, workflow: &JsWorkflow) -> Result<JsWorkflowResult> {
+        let executor = self.build_executor(workflow)?;
+        // Run on the module runtime so `configureRuntime()` bounds workflow threads
+        let workflow = workflow.inner.clone();
+        let inner = get_runtime()
+            .spawn(async move { executor.execute(workflow, None).await })
+            .await
+            .map_err(to_napi_error)?
+            .map_err(to_napi_error)?;
+        Ok(JsWorkflowResult { inner })
+    }
+
+    /// Check a workflow without running it: validate it and health-check
+    /// every distinct provider and model its agent nodes would call.
+    #[napi]
+    pub async fn dry_run(&self, workflow: &JsWorkflow) -> Result<JsDryRunReport> {
+        let executor = self.build_executor(workflow)?;
+        let workflow = workflow.inner.clone();
+        let report = get_runtime()
+            .spawn(async move { executor.dry_run(&workflow).await })
+            .await
+            .map_err(to_napi_error)?
+            .map_err(to_napi_error)?;
+        Ok(JsDryRunReport {
+            ok: report.is_ok(),
+            validation_error: report.validation_error,
+            providers: report
+                .providers
+                .into_iter()
+                .map(core_health_to_js)
+                .collect(),
+            prompts: report
+                .prompts
+                .into_iter()
+                .map(|prompt| JsRenderedPrompt {
+                    node: prompt.node,
+                    system_prompt: prompt.system_prompt,
+                    prompt: prompt.prompt,
+                })
+                .collect(),
+        })
+    }
+
+    /// Concurrency statistics accumulated over every run of this executor.
+    #[napi]
+    pub async fn stats(&self) -> JsConcurrencyStats {
+        let stats = self.concurrency_manager.get_stats().await;
+        JsConcurrencyStats {
+            total_permit_acquisitions: stats.total_permit_acquisitions as f64,
+            total_wait_time_ms: stats.total_wait_time_ms as f64,
+            current_active_tasks: stats.current_active_tasks as u32,
+            peak_active_tasks: stats.peak_active_tasks as u32,
+            permit_failures: stats.permit_failures as f64,
+            avg_wait_time_ms: stats.avg_wait_time_ms,
+            keyed_lock_acquisitions: stats.keyed_lock_acquisitions as f64,
+            keyed_lock_wait_time_ms: stats.keyed_lock_wait_time_ms as f64,
+        }
+    }
+
+    /// Execute a workflow and return an async iterator over its execution
+    /// events. `streamMode` is "updates" (default), "messages" or "all".
+    ///
+    /// Events are buffered in a bounded channel, so a consumer that stops
+    /// reading slows the workflow down instead of growing memory. The
+    /// iterator rejects with the workflow error when execution fails.
+    #[napi(ts_return_type = "JsExecutionStream & AsyncIterable<Record<string, any>>")]
+    pub fn execute_stream(
+        &self,
+        env: Env,
+        workflow: &JsWorkflow,
+        stream_mode: Option<String>,
+    ) -> Result<JsObject> {
+        use graphbit_core::stream::{StreamEvent, StreamMode, error_type_from_graphbit_error};
+
+        let mode = match stream_mode {
+            Some(name) => StreamMode::from_str_opt(&name).ok_or_else(|| {
+                to_napi_error(format!(
+                    "Unknown stream mode '{name}': expected updates, messages or all"
+                ))
+            })?,
+            None => StreamMode::Updates,
+        };
+        let executor = self.build_executor(workflow)?;
+        let workflow = workflow.inner.clone();
+        let workflow_name = workflow.name.clone();
+
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        get_runtime().spawn(async move {
+            // Failures during execution arrive as `workflow_failed` events;
+            // this covers errors raised before the workflow starts.
+            if let Err(e) = executor
+                .execute_streaming(workflow, None, event_tx.clone(), mode)
+                .await
+            {
+                let _ = event_tx
+                    .send(StreamEvent::WorkflowFailed {
+                        error: e.to_string(),
+                        error_type: error_type_from_graphbit_error(&e),
+                    })
+                    .await;
+            }
+        });
+
+        let stream = JsExecutionStream {
+            receiver: tokio::sync::Mutex::new(Some(event_rx)),
+            result: std::sync::Mutex::new(None),
+            workflow_name,
+        }
+        .into_instance(env)?;
+        let mut stream = stream.as_object(env);
+        let async_iterator: JsUnknown = env
+            .get_global()?
+            .get_named_property::<JsFunction>("Symbol")?
+            .coerce_to_object()?
+            .get_named_property("asyncIterator")?;
+        let iterator =
+            env.create_function_from_closure("asyncIterator", |ctx| ctx.this::<JsObject>())?;
+        stream.set_property(async_iterator, iterator)?;
+        Ok(stream)
+    }
+}
+
+impl JsExecutor {
+    fn build_executor(
+        &self,
+        workflow: &JsWorkflow,
+    ) -> Result<graphbit_core::workflow::WorkflowExecutor> {
+        use graphbit_core::tools::ToolRegistry;
+        use graphbit_core::workflow::WorkflowExecutor;
+
+        let mut executor = match self.mode.as_str() {
+            "high_throughput" => WorkflowExecutor::new_high_throughput(),
+            "low_latency" => WorkflowExecutor::new_low_latency(),
+            "memory_optimized" => WorkflowExecutor::new_memory_optimized(),
+            _ => WorkflowExecutor::new(),
+        }
+        .with_concurrency_manager(self.concurrency_manager.clone())
+        .with_fail_fast(self.fail_fast);
+        if let Some(timeout_ms) = self.max_node_execution_time_ms {
+            executor = executor.with_max_node_execution_time(timeout_ms);
+        }
+        if let Some(retry) = &self.retry {
+            executor = executor.with_retry_config(retry.clone());
+        }
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            executor = executor.with_circuit_breaker_config(circuit_breaker.clone());
+        }
+        if let Some(preamble) = &self.prompt_defaults.system_preamble {
+            executor = executor.with_system_preamble(preamble.clone());
+        }
+        if let Some(suffix) = &self.prompt_defaults.prompt_suffix {
+            executor = executor.with_prompt_suffix(suffix.clone());
+        }
+        if workflow.tools.is_empty() {
+            if let Some(registry) = &self.tool_registry {
+                executor = executor.with_tool_registry(registry.clone());
+            }
+        } else {
+            // Node tools shadow executor tools with the same name
+            let registry = ToolRegistry::new();
+            if let Some(executor_tools) = &self.tool_registry {
+                registry.extend(executor_tools).map_err(to_napi_error)?;
+            }
+            registry.extend(&workflow.tools).map_err(to_napi_error)?;
+            executor = executor.with_tool_registry(registry);
+        }
+
+        Ok(executor.with_globals(self.prompt_defaults.globals.clone()))
+    }
+}
+
+/// Events buffered between a streaming workflow and its JS consumer.
+const STREAM_CHANNEL_CAPACITY: usize = 256;
+
+/// Result of `JsExecutionStream.next()`, shaped like an iterator result.
+#[napi(object, object_from_js = false)]
+pub struct JsStreamIteratorResult {
+    pub done: bool,
+    pub value: Option<serde_json::Value>,
+}
+
+/// Execution events of a workflow started by `executeStream()`.
+///
+/// Each event is a plain object with an `event` field such as
+/// `"node_started"` or `"token"`; the rest of its fields match the Python
+/// streaming events. The final `"workflow_completed"` event carries the node
+/// `outputs`, and `result()` returns the full result afterwards.
+#[napi]
+pub struct JsExecutionStream {
+    receiver:
+        tokio::sync::Mutex<Option<tokio::sync::mpsc::Receiver<graphbit_core::stream::StreamEvent>>>,
+    result: std::sync::Mutex<Option<graphbit_core::types::WorkflowContext>>,
+    workflow_name: String,
+}
+
+#[napi]
+impl JsExecutionStream {
+    /// Wait for the next event. Rejects when the workflow fails.
+    #[napi]
+    pub async fn next(&self) -> Result<JsStreamIteratorResult> {
+        use graphbit_core::stream::StreamEvent;
+
+        let mut receiver = self.receiver.lock().await;
+        let event = match receiver.as_mut() {
+            Some(rx) => rx.recv().await,
+            None => None,
+        };
+        let value = match event {
+            Some(StreamEvent::WorkflowCompleted { mut context }) => {
+                receiver.take();
+                context.metadata.insert(
+                    "workflow_name".to_string(),
+                    serde_json::Value::String(self.workflow_name.clone()),
+                );
+                let outputs = serde_json::to_value(&context.node_outputs).map_err(to_napi_error)?;
+                *self.result.lock().map_err(to_napi_error)? = Some(context);
+                serde_json::json!({ "event": "workflow_completed", "outputs": outputs })
+            }
+            Some(StreamEvent::WorkflowFailed { error, .. }) => {
+                receiver.take();
+                return Err(to_napi_error(error));
+            }
+            Some(event) => serde_json::to_value(event).map_err(to_napi_error)?,
+            None => {
+                receiver.take();
+                return Ok(JsStreamIteratorResult {
+                    done: true,
+                    value: None,
+                });
+            }
+        };
+        Ok(JsStreamIteratorResult {
+            done: false,
+            value: Some(value),
+        })
+    }
+
+    /// Stop reading events. The workflow itself runs to completion.
+    #[napi(js_name = "return")]
+    pub async fn close(&self) -> JsStreamIteratorResult {
+        self.receiver.lock().await.take();
+        JsStreamIteratorResult {
+            done: true,
+            value: None,
+        }
+    }
+
+    /// Result of the workflow once the `"workflow_completed"` event was read.
+    #[napi]
+    pub fn result(&self) -> Result<Option<JsWorkflowResult>> {
+        let result = self.result.lock().map_err(to_napi_error)?;
+        Ok(result.clone().map(|inner| JsWorkflowResult { inner }))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Workflow results
+// ---------------------------------------------------------------------------
+
+/// Result of a workflow execution, built from a serialized `WorkflowContext`.
+#[napi]
+pub struct JsWorkflowResult {
+    inner: graphbit_core::types::WorkflowContext,
+}
+
+#[napi]
+impl JsWorkflowResult {
+    /// Load a workflow result from JSON produced by `toJSON()`.
+    #[napi(factory, js_name = "fromJSON")]
+    pub fn from_json(json: String) -> Result<Self> {
+        let inner = graphbit_core::types::WorkflowContext::from_json(&json)
+            .map_err(|e| to_napi_error(format!("Invalid workflow result: {e}")))?;
+        Ok(Self { inner })
+    }
+
+    /// Serialize the full result: state, node outputs, tool calls, stats and
+    /// metadata. Strings in node outputs and metadata longer than
+    /// `maxValueChars` are truncated.
+    #[napi(js_name = "toJSON")]
+    pub fn to_json(&self, max_value_chars: Option<u32>) -> Result<String> {
+        self.inner
+            .to_json(max_value_chars.map(|chars| chars as usize))
+            .map_err(to_napi_error)
+    }
+
+    /// Whether the workflow completed successfully.
+    #[napi]
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self.inner.state,
+            graphbit_core::types::WorkflowState::Completed
+        )
+    }
+
+    /// Output of a node by name or ID, or null when the node has none.
+    #[napi]
+    pub fn get_node_output(&self, node_name: String) -> Option<serde_json::Value> {
+        self.inner.get_node_output(&node_name).cloned()
+    }
+
+    /// Execution statistics, or null when unavailable.
+    #[napi]
+    pub fn get_stats(&self) -> Result<Option<serde_json::Value>> {
+        self.inner
+            .get_stats()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(to_napi_error)
+    }
+
+    /// Tool calls made by an agent node, in call order.
+    #[napi]
+    pub fn get_tool_calls(&self, node_name: String) -> Vec<JsToolCallRecord> {
+        self.inner
+            .get_tool_calls(&node_name)
+            .iter()
+            .map(core_tool_call_to_js)
+            .collect()
+    }
+
+    /// Output schema validation outcome of an agent node as a JSON string
+    /// (`repair_attempts` and `validation`), or null when the node has no schema.
+    #[napi]
+    pub fn get_output_validation(&self, node_name: String) -> Result<Option<String>> {
+        self.inner
+            .get_output_validation(&node_name)
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(to_napi_error)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tools
+// ---------------------------------------------------------------------------
+
+/// A JavaScript function exposed to agents as a tool.
+#[napi]
+pub struct JsTool {
+    metadata: graphbit_core::tools::ToolMetadata,
+    handler: graphbit_core::tools::ToolHandler,
+}
+
+#[napi]
+impl JsTool {
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.metadata.name.clone()
+    }
+
+    #[napi(getter)]
+    pub fn description(&self) -> String {
+        self.metadata.description.clone()
+    }
+
+    /// JSON schema of the tool arguments, as a JSON string.
+    #[napi(getter)]
+    pub fn parameters(&self) -> String {
+        self.metadata.parameters.to_string()
+    }
+}
+
+/// Create a tool from a JS function.
+///
+/// `options.parameters` is the JSON schema of the arguments object passed to
+/// `options.handler`; it defaults to an object schema without properties. The
+/// handler's return value, or the value its promise resolves to, is sent back
+/// to the model. A thrown error or rejected promise is reported to the model
+/// as a failed tool call.
+#[napi(
+    ts_args_type = "options: { name: string, description: string, parameters?: object, handler: (args: any) => any }"
+)]
+pub fn create_tool(env: Env, options: JsObject) -> Result<JsTool> {
+    let name: String = options
+        .get("name")?
+        .ok_or_else(|| to_napi_error("createTool: `name` is required"))?;
+    if name.trim().is_empty() {
+        return Err(to_napi_error("createTool: `name` cannot be empty"));
+    }
+    let description: String = options.get("description")?.unwrap_or_default();
+    let parameters: serde_json::Value = match options.get::<_, JsUnknown>("parameters")? {
+        Some(schema) if schema.get_type()? != ValueType::Undefined => env.from_js_value(schema)?,
+        _ => serde_json::json!({"type": "object", "properties": {}}),
+    };
+    if !parameters.is_object() {
+        return Err(to_napi_error(
+            "createTool: `parameters` must be a JSON schema object",
+        ));
+    }
+    let handler: JsFunction = options
+        .get("handler")?
+        .ok_or_else(|| to_napi_error("createTool: `handler` is required"))?;
+
+    // Calling through an async wrapper turns a plain return value into a
+    // resolved promise and a throw into a rejected one. It also drops the
+    // leading error argument `CalleeHandled` passes.
+    let wrap: JsFunction =
+        env.run_script("(handler) => async (_error, args) => (await handler(args)) ?? null")?;
+    let handler = JsFunction::try_from(wrap.call(None, &[handler])?)?;
+    let mut tsfn: ThreadsafeFunction<serde_json::Value, ErrorStrategy::CalleeHandled> = handler
+        .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<serde_json::Value>| {
+            ctx.env.to_js_value(&ctx.value).map(|args| vec![args])
+        })?;
+    // A tool must not keep Node running once nothing else is left to do
+    tsfn.unref(&env)?;
+
+    let tool_name = name.clone();
+    let handler: graphbit_core::tools::ToolHandler = Arc::new(move |args: serde_json::Value| {
+        let tsfn = tsfn.clone();
+        let tool_name = tool_name.clone();
+        Box::pin(async move {
+            let result = async {
+                let promise = tsfn
+                    .call_async::<Promise<serde_json::Value>>(Ok(args))
+                    .await?;
+                promise.await
+            }
+            .await;
+            result.map_err(|e| {
+                graphbit_core::GraphBitError::workflow_execution(format!(
+                    "Tool '{tool_name}' failed: {}",
+                    e.reason
+                ))
+            })
+        })
+    });
+
+    Ok(JsTool {
+        metadata: graphbit_core::tools::ToolMetadata::new(name, description, parameters),
+        handler,
+    })
+}
+
+/// Registry of tools that agent nodes may call.
+#[napi]
+pub struct JsToolRegistry {
+    inner: graphbit_core::tools::ToolRegistry,
+}
+
+#[napi]
+impl JsToolRegistry {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: graphbit_core::tools::ToolRegistry::new(),
+        }
+    }
+
+    /// Register a tool created with `createTool`, replacing any tool with the same name.
+    #[napi]
+    pub fn register(&self, tool: &JsTool) -> Result<()> {
+        self.inner
+            .register_tool(tool.metadata.clone(), tool.handler.clone())
+            .map_err(to_napi_error)
+    }
+
+    /// Remove a tool, returning whether it was registered.
+    #[napi]
+    pub fn unregister(&self, name: String) -> bool {
+        self.inner.unregister_tool(&name)
+    }
+
+    #[napi]
+    pub fn has_tool(&self, name: String) -> bool {
+        self.inner.has_tool(&name)
+    }
+
+    #[napi]
+    pub fn tool_names(&self) -> Vec<String> {
+        self.inner.tool_names()
+    }
+
+    /// Invoke a tool with JSON-encoded arguments, resolving to its JSON-encoded result.
+    #[napi]
+    pub async fn execute(&self, name: String, arguments: String) -> Result<String> {
+        let arguments: serde_json::Value = serde_json::from_str(&arguments)
+            .map_err(|e| to_napi_error(format!("Invalid tool arguments: {e}")))?;
+        let output = self
+            .inner
+            .execute(&name, arguments)
+            .await
+            .map_err(to_napi_error)?;
+        Ok(output.to_string())
+    }
+}
+
+impl Default for JsToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Document loader
+// ---------------------------------------------------------------------------
+
+/// Options for `new JsDocumentLoader()`. Unset fields keep their defaults.
+#[napi(object)]
+pub struct JsDocumentLoaderConfig {
+    pub max_file_size: Option<f64>,
+    pub default_encoding: Option<String>,
+    pub preserve_formatting: Option<bool>,
+    pub extraction_settings: Option<HashMap<String, serde_json::Value>>,
+    /// Directory caching extracted documents; unset disables caching.
+    pub cache_dir: Option<String>,
+    /// Maximum concurrent requests to one host.
+    pub max_requests_per_host: Option<u32>,
+    /// Maximum requests per second across all hosts; unset means no cap.
+    pub requests_per_second: Option<f64>,
+    /// Retries of a URL request answered with 429 or 5xx.
+    pub max_retries: Option<u32>,
+    /// Delay before the first retry, doubling with every further retry.
+    pub retry_backoff_ms: Option<u32>,
+    /// Time limit of a whole `loadBatch()` call.
+    pub batch_timeout_ms: Option<u32>,
+    /// Largest URL download in bytes; unset means `maxFileSize` applies.
+    pub max_download_size: Option<f64>,
+    /// Times a URL download whose connection drops is resumed or restarted.
+    pub max_download_retries: Option<u32>,
+}
+
+/// Outcome of loading one source of `loadBatch()`.
+#[napi(object)]
+pub struct JsBatchLoadResult {
+    pub source: String,
+    pub document: Option<JsDocumentContent>,
+    pub error: Option<String>,
+}
+
+/// Text and metadata extracted from a document.
+#[napi(object)]
+pub struct JsDocumentContent {
+    pub source: String,
+    pub document_type: String,
+    pub content: String,
+    pub metadata: HashMap<String, serde_json::Value>,
+    pub file_size: f64,
+    /// Extraction time as a Unix timestamp in seconds.
+    pub extracted_at: i64,
+}
+
+fn core_document_to_js(d: graphbit_core::document_loader::DocumentContent) -> JsDocumentContent {
+    JsDocumentContent {
+        source: d.source,
+        document_type: d.document_type,
+        content: d.content,
+        metadata: d.metadata,
+        file_size: d.file_size as f64,
+        extracted_at: d.extracted_at.timestamp(),
+    }
+}
+
+/// Loads text from PDF, DOCX, CSV, JSON, XML, HTML, Excel and plain text files.
+#[napi]
+pub struct JsDocumentLoader {
+    inner: Arc<graphbit_core::document_loader::DocumentLoader>,
+}
+
+#[napi]
+impl JsDocumentLoader {
+    #[napi(constructor)]
+    pub fn new(config: Option<JsDocumentLoaderConfig>) -> Result<Self> {
+        let mut cfg = graphbit_core::document_loader::DocumentLoaderConfig::default();
+        if let Some(config) = config {
+            if let Some(max_file_size) = config.max_file_size {
+                if max_file_size.is_nan() || max_file_size < 1.0 {
+                    return Err(to_napi_error("maxFileSize must be greater than 0"));
+                }
+                cfg.max_file_size = max_file_size as usize;
+            }
+            if let Some(encoding) = config.default_encoding {
+                if encoding.trim().is_empty() {
+                    return Err(to_napi_error("defaultEncoding cannot be empty"));
+                }
+                cfg.default_encoding = encoding;
+            }
+            if let Some(preserve) = config.preserve_formatting {
+                cfg.preserve_formatting = preserve;
+            }
+            if let Some(settings) = config.extraction_settings {
+                cfg.extraction_settings = settings;
+            }
+            cfg.cache_dir = config.cache_dir.map(std::path::PathBuf::from);
+            if let Some(limit) = config.max_requests_per_host {
+                if limit == 0 {
+                    return Err(to_napi_error("maxRequestsPerHost must be greater than 0"));
+                }
+                cfg.max_requests_per_host = limit as usize;
+            }
+            if let Some(rate) = config.requests_per_second {
+                if !(rate.is_finite() && rate > 0.0) {
+                    return Err(to_napi_error("requestsPerSecond must be a positive number"));
+                }
+                cfg.requests_per_second = Some(rate);
+            }
+            if let Some(retries) = config.max_retries {
+                cfg.max_retries = retries;
+            }
+            if let Some(backoff) = config.retry_backoff_ms {
+                cfg.retry_backoff_ms = u64::from(backoff);
+            }
+            if let Some(timeout) = config.batch_timeout_ms {
+                if timeout == 0 {
+                    return Err(to_napi_error("batchTimeoutMs must be greater than 0"));
+                }
+                cfg.batch_timeout_ms = Some(u64::from(timeout));
+            }
+            if let Some(max_download_size) = config.max_download_size {
+                if max_download_size.is_nan() || max_download_size < 1.0 {
+                    return Err(to_napi_error("maxDownloadSize must be greater than 0"));
+                }
+                cfg.max_download_size = Some(max_download_size as usize);
+            }
+            if let Some(retries) = config.max_download_retries {
+                cfg.max_download_retries = retries;
+            }
+        }
+        Ok(Self {
+            inner: Arc::new(graphbit_core::document_loader::DocumentLoader::with_config(
+                cfg,
+            )),
+        })
+    }
+
+    /// Load a file or HTTP(S) URL. The type is detected from the file
+    /// extension when `documentType` is omitted.
+    #[napi]
+    pub async fn load(
+        &self,
+        source_path: String,
+        document_type: Option<String>,
+    ) -> Result<JsDocumentContent> {
+        let document_type = resolve_document_type(&source_path, document_type)?;
+        let loader = self.inner.clone();
+        let document = get_runtime()
+            .spawn(async move { loader.load_document(&source_path, &document_type).await })
+            .await
+            .map_err(to_napi_error)?
+            .map_err(to_napi_error)?;
+        Ok(core_document_to_js(document))
+    }
+
+    /// Load every supported file in a directory in path order, skipping files
+    /// whose extension is not a supported document type.
+    #[napi]
+    pub async fn load_directory(
+        &self,
+        directory: String,
+        recursive: Option<bool>,
+    ) -> Result<Vec<JsDocumentContent>> {
+        let loader = self.inner.clone();
+        let recursive = recursive.unwrap_or(false);
+        let documents = get_runtime()
+            .spawn(async move { loader.load_directory(&directory, recursive).await })
+            .await
+            .map_err(to_napi_error)?
+            .map_err(to_napi_error)?;
+        Ok(documents.into_iter().map(core_document_to_js).collect())
+    }
+
+    /// Load files and URLs concurrently, detecting each type from its
+    /// extension. Results come back in source order; a failing source is
+    /// reported in its result without aborting the batch.
+    #[napi]
+    pub async fn load_batch(&self, sources: Vec<String>) -> Result<Vec<JsBatchLoadResult>> {
+        let loader = self.inner.clone();
+        let results = get_runtime()
+            .spawn(async move { loader.load_batch(&sources).await })
+            .await
+            .map_err(to_napi_error)?;
+        Ok(results
+            .into_iter()
+            .map(|result| JsBatchLoadResult {
+                source: result.source,
+                document: result.document.map(core_document_to_js),
+                error: result.error,
+            })
+            .collect())
+    }
+
+    /// Document types accepted by `load()`.
+    #[napi]
+    pub fn supported_types() -> Vec<String> {
+        graphbit_core::document_loader::DocumentLoader::supported_types()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Document type for a path's extension, or null when it is not supported.
+    #[napi]
+    pub fn detect_document_type(file_path: String) -> Option<String> {
+        graphbit_core::document_loader::detect_document_type(&file_path)
+    }
+}
+
+fn resolve_document_type(source_path: &str, document_type: Option<String>) -> Result<String> {
+    if source_path.trim().is_empty() {
+        return Err(to_napi_error("sourcePath cannot be empty"));
+    }
+    match document_type {
+        Some(document_type) if document_type.trim().is_empty() => {
+            Err(to_napi_error("documentType cannot be empty"))
+        }
+        Some(document_type) => Ok(document_type),
+        None => {
+            graphbit_core::document_loader::detect_document_type(source_path).ok_or_else(|| {
+                to_napi_error(format!(
+                    "Cannot detect document type of '{source_path}'; pass documentType explicitly"
+                ))
+            })
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Text splitter
+// ---------------------------------------------------------------------------
+
+/// A chunk returned by `splitText()`. Offsets are character positions in the
+/// original text.
+#[napi(object)]
+pub struct JsTextChunk {
+    pub content: String,
+    pub start_index: u32,
+    pub end_index: u32,
+    pub chunk_index: u32,
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Splits text into overlapping chunks. Create one with the `character`,
+/// `token`, `sentence` or `recursive` factories.
+#[napi]
+pub struct JsTextSplitter {
+    strategy: String,
+    inner: Arc<dyn graphbit_core::text_splitter::TextSplitterTrait>,
+}
+
+impl JsTextSplitter {
+    fn create(strategy: graphbit_core::text_splitter::SplitterStrategy) -> Result<Self> {
+        use graphbit_core::text_splitter::{
+            SplitterStrategy, TextSplitterConfig, TextSplitterFactory,
+        };
+
+        let name = match &strategy {
+            SplitterStrategy::Character { .. } => "character",
+            SplitterStrategy::Token { .. } => "token",
+            SplitterStrategy::Sentence { .. } => "sentence",
+            _ => "recursive",
+        };
+        let config = TextSplitterConfig {
+            strategy,
+            ..Default::default()
+        };
+        let inner = TextSplitterFactory::create_splitter(config).map_err(to_napi_error)?;
+        Ok(Self {
+            strategy: name.to_string(),
+            inner: Arc::from(inner),
+        })
+    }
+}
+
+#[napi]
+impl JsTextSplitter {
+    /// Split into chunks of at most `chunkSize` characters.
+    #[napi(factory)]
+    pub fn character(chunk_size: u32, chunk_overlap: Option<u32>) -> Result<Self> {
+        Self::create(graphbit_core::text_splitter::SplitterStrategy::Character {
+            chunk_size: chunk_size as usize,
+            chunk_overlap: chunk_overlap.unwrap_or(0) as usize,
+        })
+    }
+
+    /// Split into chunks of at most `chunkSize` tokens. Tokens are words and
+    /// punctuation unless `tokenPattern` gives a regex.
+    #[napi(factory)]
+    pub fn token(
+        chunk_size: u32,
+        chunk_overlap: Option<u32>,
+        token_pattern: Option<String>,
+    ) -> Result<Self> {
+        Self::create(graphbit_core::text_splitter::SplitterStrategy::Token {
+            chunk_size: chunk_size as usize,
+            chunk_overlap: chunk_overlap.unwrap_or(0) as usize,
+            token_pattern,
+        })
+    }
+
+    /// Split on sentence boundaries into chunks of at most `chunkSize` characters.
+    /// `abbreviations` adds to the English ones a period does not end a sentence
+    /// after; `unicodeSegmentation` finds boundaries with UAX #29 instead of
+    /// `sentenceEndings`.
+    #[napi(factory)]
+    pub fn sentence(
+        chunk_size: u32,
+        chunk_overlap: Option<u32>,
+        sentence_endings: Option<Vec<String>>,
+        abbreviations: Option<Vec<String>>,
+        unicode_segmentation: Option<bool>,
+    ) -> Result<Self> {
+        Self::create(graphbit_core::text_splitter::SplitterStrategy::Sentence {
+            chunk_size: chunk_size as usize,
+            chunk_overlap: chunk_overlap.unwrap_or(0) as usize,
+            sentence_endings,
+            abbreviations,
+            unicode_segmentation: unicode_segmentation.unwrap_or(false),
+        })
+    }
+
+    /// Split on the first of `separators` that yields chunks of at most
+    /// `chunkSize` characters, falling back to the next one for larger pieces.
+    #[napi(factory)]
+    pub fn recursive(
+        chunk_size: u32,
+        chunk_overlap: Option<u32>,
+        separators: Option<Vec<String>>,
+    ) -> Result<Self> {
+        Self::create(graphbit_core::text_splitter::SplitterStrategy::Recursive {
+            chunk_size: chunk_size as usize,
+            chunk_overlap: chunk_overlap.unwrap_or(0) as usize,
+            separators,
+        })
+    }
+
+    /// Splitting strategy: `character`, `token`, `sentence` or `recursive`.
+    #[napi(getter)]
+    pub fn strategy(&self) -> String {
+        self.strategy.clone()
+    }
+
+    /// Split text into chunks on a worker thread.
+    #[napi]
+    pub async fn split_text(&self, text: String) -> Result<Vec<JsTextChunk>> {
+        let splitter = self.inner.clone();
+        let chunks = get_runtime()
+            .spawn_blocking(move || splitter.split_text(&text))
+            .await
+            .map_err(to_napi_error)?
+            .map_err(to_napi_error)?;
+        Ok(chunks
+            .into_iter()
+            .map(|c| JsTextChunk {
+                content: c.content,
+                start_index: c.start_index as u32,
+                end_index: c.end_index as u32,
+                chunk_index: c.chunk_index as u32,
+                metadata: c.metadata,
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+/// One schema violation. `fieldPath` locates the value, e.g. `root.tags[1]`.
+#[napi(object)]
+pub struct JsValidationError {
+    pub field_path: String,
+    pub message: String,
+    pub error_code: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// Outcome of `validateJson()`.
+#[napi(object)]
+pub struct JsValidationResult {
+    pub is_valid: bool,
+    pub errors: Vec<JsValidationError>,
+}
+
+/// Validate a value against a JSON Schema (draft-07).
+///
+/// `$ref` resolves within the schema; keywords from later drafts are reported
+/// as `UNSUPPORTED_KEYWORD` errors. Strings are validated as strings, not parsed.
+#[napi]
+pub fn validate_json(
+    instance: serde_json::Value,
+    schema: serde_json::Value,
+) -> Result<JsValidationResult> {
+    if !schema.is_object() && !schema.is_boolean() {
+        return Err(to_napi_error("Schema must be an object or a boolean"));
+    }
+    let result = graphbit_core::validation::TypeValidator::new().validate_value(&instance, &schema);
+    Ok(JsValidationResult {
+        is_valid: result.is_valid,
+        errors: result
+            .errors
+            .into_iter()
+            .map(|e| JsValidationError {
+                field_path: e.field_path,
+                message: e.message,
+                error_code: e.error_code,
+                expected: e.expected,
+                actual: e.actual,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_is_reported() {
+        let (total, available) = memory_bytes();
+        assert!(total > 0);
+        assert!(available > 0);
+        assert!(available <= total);
+    }
+
+    #[test]
+    fn test_uptime_increases() {
+        let first = uptime_seconds();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let second = uptime_seconds();
+        assert!(second > first);
+        assert!(second - first >= 0.02);
+    }
+}
@@ -4,11 +4,18 @@
 # Core functions
 from .graphbit import (
     init,
+    set_log_level,
     version,
     get_system_info,
     health_check,
     configure_runtime,
+    runtime_info,
     shutdown,
+    enable_metrics,
+    render_metrics,
+    push_metrics,
+    repair_json,
+    forget_api_key,
 )
 # Document loader classes
 from .graphbit import (
@@ -21,6 +28,7 @@ from .graphbit import (
 from .graphbit import (
     LlmConfig,
     LlmClient,
+    LlmMiddleware,
     LlmUsage,
     FinishReason,
     LlmToolCall,
@@ -30,11 +38,19 @@ from .graphbit import (
 # Workflow classes
 from .graphbit import (
     GuardRailPolicyConfig,
+    Agent,
+    AgentBuilder,
     Node,
+    RetryPolicy,
     Workflow,
+    WorkflowTemplate,
+    TemplateRegistry,
     WorkflowContext,
     WorkflowResult,
+    ResultComparison,
+    Attachment,
     Executor,
+    ExecutionHandle,
 )
 
 # Embedding classes
@@ -63,14 +79,23 @@ from .graphbit import (
     TextSplitter,
 )
 
+# Validation
+from .graphbit import (
+    ValidationResult,
+    validate_json,
+)
+
 # Tool system classes
 from .graphbit import (
     ToolResult,
     ToolRegistry,
+    ToolContext,
     ToolDecorator,
     ToolExecutor,
     ExecutorConfig,
     ToolResultCollection,
+    BuiltinTools,
+    BuiltinTool,
 )
 
 # Tool functions
@@ -88,15 +113,25 @@ from .graphbit import (
 # Module metadata
 from .graphbit import __version__, __author__, __description__
 
+# Exception hierarchy
+from . import errors
+
 # Define __all__ for explicit exports
 __all__ = [
     # Core functions
     "init",
+    "set_log_level",
     "version",
     "get_system_info",
     "health_check",
     "configure_runtime",
+    "runtime_info",
     "shutdown",
+    "enable_metrics",
+    "render_metrics",
+    "push_metrics",
+    "repair_json",
+    "forget_api_key",
     # Document loader
     "DocumentLoaderConfig",
     "DocumentContent",
@@ -104,6 +139,7 @@ __all__ = [
     # LLM
     "LlmConfig",
     "LlmClient",
+    "LlmMiddleware",
     "LlmUsage",
     "FinishReason",
     "LlmToolCall",
@@ -111,11 +147,19 @@ __all__ = [
     # GuardRail
     "GuardRailPolicyConfig",
     # Workflow
+    "Agent",
+    "AgentBuilder",
     "Node",
+    "RetryPolicy",
     "Workflow",
+    "WorkflowTemplate",
+    "TemplateRegistry",
     "WorkflowContext",
     "WorkflowResult",
+    "ResultComparison",
+    "Attachment",
     "Executor",
+    "ExecutionHandle",
     # Embeddings
     "EmbeddingConfig",
     "EmbeddingClient",
@@ -133,13 +177,19 @@ __all__ = [
     "SentenceSplitter",
     "RecursiveSplitter",
     "TextSplitter",
+    # Validation
+    "ValidationResult",
+    "validate_json",
     # Tools
     "ToolResult",
     "ToolRegistry",
+    "ToolContext",
     "ToolDecorator",
     "ToolExecutor",
     "ExecutorConfig",
     "ToolResultCollection",
+    "BuiltinTools",
+    "BuiltinTool",
     "tool",
     "get_tool_registry",
     "clear_tools",
@@ -148,6 +198,8 @@ __all__ = [
     "execute_workflow_tool_calls",
     "execute_production_tool_calls",
     "sync_global_tools_to_workflow",
+    # Errors
+    "errors",
     # Metadata
     "__version__",
     "__author__",
@@ -0,0 +1,27 @@
+"""The ``graphbit`` command line interface.
+
+Installed as the ``graphbit`` console script; also runnable as
+``python -m graphbit.cli``. Run ``graphbit --help`` for the commands.
+"""
+
+import sys
+from typing import List, Optional
+
+from .graphbit import cli_main
+
+__all__ = ["main"]
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    """Run the ``graphbit`` command with ``argv`` (default ``sys.argv[1:]``) and return its exit code.
+
+    Ctrl+C, which stops ``graphbit run --watch``, exits with 130.
+    """
+    try:
+        return cli_main(list(sys.argv[1:] if argv is None else argv))
+    except KeyboardInterrupt:
+        return 130
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,117 @@
+"""Exception hierarchy raised by the GraphBit bindings.
+
+Every error raised by GraphBit derives from :class:`GraphBitError`, which is
+itself a :class:`RuntimeError` so existing ``except RuntimeError`` handlers keep
+working. Subclasses also derive from the matching builtin where one exists
+(``ValueError`` for validation and configuration problems, ``TimeoutError``
+for timeouts), and carry structured attributes describing the failure.
+"""
+
+import builtins
+from typing import List, Optional
+
+__all__ = [
+    "GraphBitError",
+    "ConfigurationError",
+    "ProviderError",
+    "RateLimitError",
+    "ContentBlockedError",
+    "ValidationError",
+    "WorkflowExecutionError",
+    "TimeoutError",
+    "CancelledError",
+]
+
+
+class GraphBitError(RuntimeError):
+    """Base class for all GraphBit errors."""
+
+    def __init__(self, message: str = "") -> None:
+        super().__init__(message)
+        self.message = message
+
+
+class ConfigurationError(GraphBitError, ValueError):
+    """Invalid or incomplete configuration."""
+
+
+class ProviderError(GraphBitError):
+    """An LLM or embedding provider rejected or failed a request.
+
+    Attributes:
+        provider: Name of the provider (e.g. ``"openai"``), if known.
+        status_code: HTTP status code returned by the provider, if any.
+    """
+
+    def __init__(self, message: str = "", provider: Optional[str] = None, status_code: Optional[int] = None) -> None:
+        super().__init__(message)
+        self.provider = provider
+        self.status_code = status_code
+
+
+class RateLimitError(ProviderError):
+    """The provider rate-limited the request (HTTP 429).
+
+    Attributes:
+        retry_after: Seconds the provider asked to wait before retrying, if given.
+    """
+
+    def __init__(
+        self,
+        message: str = "",
+        provider: Optional[str] = None,
+        status_code: Optional[int] = 429,
+        retry_after: Optional[int] = None,
+    ) -> None:
+        super().__init__(message, provider, status_code)
+        self.retry_after = retry_after
+
+
+class ContentBlockedError(ProviderError):
+    """The provider refused to produce content for safety reasons.
+
+    Attributes:
+        categories: Harm categories the provider flagged (e.g. ``"HARM_CATEGORY_DANGEROUS_CONTENT"``).
+    """
+
+    def __init__(
+        self,
+        message: str = "",
+        provider: Optional[str] = None,
+        status_code: Optional[int] = None,
+        categories: Optional[List[str]] = None,
+    ) -> None:
+        super().__init__(message, provider, status_code)
+        self.categories = list(categories or [])
+
+
+class ValidationError(GraphBitError, ValueError):
+    """Input failed validation.
+
+    Attributes:
+        field: Name of the offending field or argument, if known.
+    """
+
+    def __init__(self, message: str = "", field: Optional[str] = None) -> None:
+        super().__init__(message)
+        self.field = field
+
+
+class WorkflowExecutionError(GraphBitError):
+    """A workflow could not be executed.
+
+    Attributes:
+        node_name: Name of the node that caused the failure, if known.
+    """
+
+    def __init__(self, message: str = "", node_name: Optional[str] = None) -> None:
+        super().__init__(message)
+        self.node_name = node_name
+
+
+class TimeoutError(GraphBitError, builtins.TimeoutError):
+    """An operation exceeded its time limit."""
+
+
+class CancelledError(GraphBitError):
+    """An operation was cancelled before it completed."""
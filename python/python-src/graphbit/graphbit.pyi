@@ -0,0 +1,1448 @@
+"""Type stubs for the compiled GraphBit extension module.
+
+Keep in sync with ``python/src``: ``tests/python_unit_tests/tests_stubs.py``
+fails when a class, method or keyword argument is missing here.
+"""
+
+import builtins
+import datetime
+import os
+from typing import Any, AsyncIterator, Awaitable, Callable, Dict, Iterator, List, Literal, Optional, Tuple, TypeVar, Union, final
+
+_F = TypeVar("_F", bound=Callable[..., Any])
+
+__version__: str
+__author__: str
+__description__: str
+
+# Core functions
+
+def init(
+    log_level: Optional[str] = None,
+    enable_tracing: Optional[bool] = None,
+    debug: Optional[bool] = None,
+    log_format: Literal["text", "json"] = "text",
+    log_file: Optional[str] = None,
+    proxy: Optional[str] = None,
+    no_proxy: Optional[str] = None,
+    ca_bundle: Optional[Union[str, os.PathLike[str]]] = None,
+) -> None: ...
+def set_log_level(level: str) -> None: ...
+def version() -> str: ...
+def get_system_info() -> Dict[str, Any]: ...
+def health_check() -> Dict[str, Any]: ...
+def configure_runtime(
+    worker_threads: Optional[int] = None,
+    max_blocking_threads: Optional[int] = None,
+    thread_stack_size_mb: Optional[int] = None,
+    blocking_threads: Optional[int] = None,
+    thread_name_prefix: Optional[str] = None,
+) -> None: ...
+def runtime_info() -> Dict[str, Any]: ...
+def shutdown() -> None: ...
+def enable_metrics() -> None: ...
+def render_metrics() -> str: ...
+def push_metrics(url: str) -> None: ...
+def repair_json(text: str, return_repairs: bool = False) -> Any: ...
+def forget_api_key(api_key: str) -> None: ...
+def cli_main(argv: List[str]) -> int: ...
+
+# Document loading
+
+class DocumentLoaderConfig:
+    max_file_size: int
+    default_encoding: str
+    preserve_formatting: bool
+    extraction_settings: Dict[str, Any]
+    cache_dir: Optional[Union[str, os.PathLike[str]]]
+    max_requests_per_host: int
+    requests_per_second: Optional[float]
+    max_retries: int
+    retry_backoff_ms: int
+    batch_timeout_ms: Optional[int]
+    max_download_size: Optional[int]
+    max_download_retries: int
+    download_progress: Optional[Callable[[str, int, Optional[int]], Any]]
+    max_parallel_parses: Optional[int]
+    max_parse_bytes: int
+    def __init__(
+        self,
+        max_file_size: Optional[int] = None,
+        default_encoding: Optional[str] = None,
+        preserve_formatting: Optional[bool] = None,
+        cache_dir: Optional[Union[str, os.PathLike[str]]] = None,
+        max_requests_per_host: Optional[int] = None,
+        requests_per_second: Optional[float] = None,
+        max_retries: Optional[int] = None,
+        retry_backoff_ms: Optional[int] = None,
+        batch_timeout_ms: Optional[int] = None,
+        max_download_size: Optional[int] = None,
+        max_download_retries: Optional[int] = None,
+        download_progress: Optional[Callable[[str, int, Optional[int]], Any]] = None,
+        max_parallel_parses: Optional[int] = None,
+        max_parse_bytes: Optional[int] = None,
+    ) -> None: ...
+
+@final
+class DocumentContent:
+    @property
+    def source(self) -> str: ...
+    @property
+    def document_type(self) -> str: ...
+    @property
+    def content(self) -> str: ...
+    @property
+    def file_size(self) -> int: ...
+    @property
+    def extracted_at(self) -> int: ...
+    @property
+    def metadata(self) -> Dict[str, Any]: ...
+    def content_length(self) -> int: ...
+    def is_empty(self) -> bool: ...
+    def preview(self, max_length: int = 500) -> str: ...
+
+class DocumentLoader:
+    def __init__(self, config: Optional[DocumentLoaderConfig] = None) -> None: ...
+    def load_document(self, source_path: str, document_type: Optional[str] = None) -> DocumentContent: ...
+    def load_document_async(self, source_path: str, document_type: Optional[str] = None) -> Awaitable[DocumentContent]: ...
+    def load_directory(
+        self, directory: str, recursive: bool = False, return_stats: bool = False
+    ) -> Union[List[DocumentContent], Tuple[List[DocumentContent], Dict[str, Any]]]: ...
+    def load_directory_async(
+        self, directory: str, recursive: bool = False, return_stats: bool = False
+    ) -> Awaitable[Union[List[DocumentContent], Tuple[List[DocumentContent], Dict[str, Any]]]]: ...
+    def load_batch(self, sources: List[str]) -> List[Dict[str, Any]]: ...
+    def load_batch_async(self, sources: List[str]) -> Awaitable[List[Dict[str, Any]]]: ...
+    @staticmethod
+    def supported_types() -> List[str]: ...
+    @staticmethod
+    def detect_document_type(file_path: str) -> Optional[str]: ...
+    @staticmethod
+    def validate_document_source(source_path: str, document_type: str) -> None: ...
+
+# LLM
+
+class LlmConfig:
+    @staticmethod
+    def openai(api_key: str, model: Optional[str] = None, base_url: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def anthropic(api_key: str, model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def azurellm(api_key: str, deployment_name: str, endpoint: str, api_version: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def bytedance(api_key: str, model: Optional[str] = None, base_url: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def deepseek(api_key: str, model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def ollama(model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def perplexity(api_key: str, model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def openrouter(api_key: str, model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def openrouter_with_site(
+        api_key: str,
+        model: Optional[str] = None,
+        site_url: Optional[str] = None,
+        site_name: Optional[str] = None,
+        timeout_ms: Optional[int] = None,
+        connect_timeout_ms: Optional[int] = None,
+    ) -> LlmConfig: ...
+    @staticmethod
+    def fireworks(api_key: str, model: Optional[str] = None, base_url: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def ai21(api_key: str, model: Optional[str] = None, organization: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def replicate(api_key: str, model: Optional[str] = None, version: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def togetherai(api_key: str, model: Optional[str] = None, base_url: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def together(api_key: str, model: Optional[str] = None, base_url: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def xai(api_key: str, model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def mistralai(api_key: str, model: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None) -> LlmConfig: ...
+    @staticmethod
+    def gemini(
+        api_key: str, model: Optional[str] = None, safety_settings: Optional[Dict[str, str]] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None
+    ) -> LlmConfig: ...
+    @staticmethod
+    def huggingface(api_key: str, model: Optional[str] = None) -> LlmConfig: ...
+    @staticmethod
+    def litellm(api_key: Optional[str] = None, model: Optional[str] = None) -> LlmConfig: ...
+    @staticmethod
+    def from_env(provider: Optional[str] = None, model: Optional[str] = None, api_key: Optional[str] = None, base_url: Optional[str] = None) -> LlmConfig: ...
+    def provider(self) -> str: ...
+    def model(self) -> str: ...
+    def timeout_ms(self) -> Optional[int]: ...
+    def connect_timeout_ms(self) -> Optional[int]: ...
+    def redacted(self) -> LlmConfig: ...
+    def cleanup(self) -> bool: ...
+    def __enter__(self) -> LlmConfig: ...
+    def __exit__(self, _exc_type: Optional[Any] = None, _exc_val: Optional[Any] = None, _exc_tb: Optional[Any] = None) -> bool: ...
+
+@final
+class _StreamIterator(AsyncIterator[str]):
+    """Async iterator over streamed completion chunks (not importable at runtime)."""
+
+    def __aiter__(self) -> _StreamIterator: ...
+    def __anext__(self) -> Awaitable[str]: ...
+    def streaming_response(self) -> Awaitable[str]: ...
+
+class LlmMiddleware:
+    """Synchronous hooks run around every LLM call, in registration order.
+
+    Hooks run on GraphBit's runtime threads with the GIL held, so they should be
+    quick and must not be coroutine functions.
+    """
+
+    def __init__(
+        self,
+        before: Optional[Callable[[Dict[str, Any]], Optional[Dict[str, Any]]]] = None,
+        after: Optional[Callable[[Dict[str, Any], Dict[str, Any]], Optional[Dict[str, Any]]]] = None,
+        on_error: Optional[Callable[[Dict[str, Any], str], None]] = None,
+        name: Optional[str] = None,
+    ) -> None: ...
+    @staticmethod
+    def redaction(
+        patterns: Optional[Dict[str, str]] = None,
+        include_defaults: bool = True,
+        redact_responses: bool = False,
+    ) -> LlmMiddleware: ...
+    @property
+    def name(self) -> str: ...
+
+class LlmClient:
+    def __init__(
+        self,
+        config: LlmConfig,
+        debug: Optional[bool] = None,
+        middleware: Optional[List[LlmMiddleware]] = None,
+    ) -> None: ...
+    def complete(
+        self,
+        prompt: str,
+        max_tokens: Optional[int] = None,
+        temperature: Optional[float] = None,
+        enable_prompt_caching: bool = False,
+    ) -> str: ...
+    def complete_async(
+        self,
+        prompt: str,
+        max_tokens: Optional[int] = None,
+        temperature: Optional[float] = None,
+        enable_prompt_caching: bool = False,
+    ) -> Awaitable[str]: ...
+    def complete_full(
+        self,
+        prompt: str,
+        max_tokens: Optional[int] = None,
+        temperature: Optional[float] = None,
+        enable_prompt_caching: bool = False,
+    ) -> LlmResponse: ...
+    def complete_full_async(
+        self,
+        prompt: str,
+        max_tokens: Optional[int] = None,
+        temperature: Optional[float] = None,
+        enable_prompt_caching: bool = False,
+    ) -> Awaitable[LlmResponse]: ...
+    def complete_batch(
+        self,
+        prompts: List[str],
+        max_tokens: Optional[int] = None,
+        temperature: Optional[float] = None,
+        max_concurrency: Optional[int] = None,
+    ) -> Awaitable[List[str]]: ...
+    def chat_optimized(
+        self,
+        messages: List[Tuple[str, str]],
+        max_tokens: Optional[int] = None,
+        temperature: Optional[float] = None,
+        enable_prompt_caching: bool = False,
+    ) -> Awaitable[str]: ...
+    def stream(self, prompt: str, max_tokens: Optional[int] = None, temperature: Optional[float] = None) -> _StreamIterator: ...
+    def complete_stream(self, prompt: str, max_tokens: Optional[int] = None, temperature: Optional[float] = None) -> _StreamIterator: ...
+    def get_stats(self) -> Dict[str, Any]: ...
+    def warmup(self) -> Awaitable[None]: ...
+    def reset_stats(self) -> None: ...
+    def health_check(self) -> Dict[str, Any]: ...
+    def rerank(
+        self,
+        query: str,
+        documents: List[str],
+        top_k: Optional[int] = None,
+        model: Optional[str] = None,
+        batch_size: int = 10,
+    ) -> Dict[str, Any]: ...
+
+class LlmUsage:
+    def __init__(self, prompt_tokens: int, completion_tokens: int) -> None: ...
+    @staticmethod
+    def empty() -> LlmUsage: ...
+    @staticmethod
+    def from_dict(data: Dict[str, Any]) -> LlmUsage: ...
+    @property
+    def prompt_tokens(self) -> int: ...
+    @property
+    def completion_tokens(self) -> int: ...
+    @property
+    def total_tokens(self) -> int: ...
+    @property
+    def cache_read_tokens(self) -> Optional[int]: ...
+    @property
+    def cache_creation_tokens(self) -> Optional[int]: ...
+    def add(self, other: LlmUsage) -> None: ...
+    def __add__(self, other: LlmUsage) -> LlmUsage: ...
+    def __iadd__(self, other: LlmUsage) -> LlmUsage: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+
+class FinishReason:
+    @staticmethod
+    def stop() -> FinishReason: ...
+    @staticmethod
+    def length() -> FinishReason: ...
+    @staticmethod
+    def tool_calls() -> FinishReason: ...
+    @staticmethod
+    def content_filter() -> FinishReason: ...
+    @staticmethod
+    def error() -> FinishReason: ...
+    @staticmethod
+    def other(reason: str) -> FinishReason: ...
+    def is_natural_stop(self) -> bool: ...
+    def is_truncated(self) -> bool: ...
+    def is_error(self) -> bool: ...
+    def to_string(self) -> str: ...
+
+class LlmToolCall:
+    def __init__(self, id: str, name: str, parameters: str) -> None: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def parameters(self) -> str: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+
+class LlmResponse:
+    def __init__(self, content: str, model: str) -> None: ...
+    @property
+    def content(self) -> str: ...
+    @property
+    def tool_calls(self) -> List[LlmToolCall]: ...
+    @property
+    def usage(self) -> LlmUsage: ...
+    @property
+    def metadata(self) -> Dict[str, Any]: ...
+    @property
+    def finish_reason(self) -> FinishReason: ...
+    @property
+    def model(self) -> str: ...
+    @property
+    def id(self) -> Optional[str]: ...
+    def has_tool_calls(self) -> bool: ...
+    def is_truncated(self) -> bool: ...
+    def total_tokens(self) -> int: ...
+    def with_usage(self, usage: LlmUsage) -> None: ...
+    def with_tool_calls(self, tool_calls: List[LlmToolCall]) -> None: ...
+    def with_finish_reason(self, finish_reason: FinishReason) -> None: ...
+    def with_id(self, id: str) -> None: ...
+    def with_metadata(self, key: str, value: str) -> None: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+
+# Workflow
+
+class GuardRailPolicyConfig:
+    @staticmethod
+    def from_json(json_str: str) -> GuardRailPolicyConfig: ...
+    @staticmethod
+    def from_file(path: str) -> GuardRailPolicyConfig: ...
+    @staticmethod
+    def from_url(url: str) -> GuardRailPolicyConfig: ...
+    @staticmethod
+    def default_config() -> GuardRailPolicyConfig: ...
+    def policy_name(self) -> str: ...
+    def policy_version(self) -> str: ...
+    def is_active(self) -> bool: ...
+
+class Agent:
+    @staticmethod
+    def builder(name: str) -> AgentBuilder: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def description(self) -> str: ...
+    @property
+    def capabilities(self) -> List[str]: ...
+    @property
+    def system_prompt(self) -> Optional[str]: ...
+
+class AgentBuilder:
+    def description(self, description: str) -> AgentBuilder: ...
+    def llm(self, config: LlmConfig) -> AgentBuilder: ...
+    def capability(self, name: str) -> AgentBuilder: ...
+    def system_prompt(self, prompt: str) -> AgentBuilder: ...
+    def temperature(self, temperature: float) -> AgentBuilder: ...
+    def max_tokens(self, max_tokens: int) -> AgentBuilder: ...
+    def id(self, agent_id: str) -> AgentBuilder: ...
+    def build(self) -> Agent: ...
+
+class RetryPolicy:
+    def __init__(
+        self,
+        max_attempts: int = 3,
+        backoff_ms: int = 1000,
+        exponential: bool = True,
+        max_backoff_ms: Optional[int] = None,
+        jitter: float = 0.1,
+    ) -> None: ...
+    @property
+    def max_attempts(self) -> int: ...
+    @property
+    def backoff_ms(self) -> int: ...
+    @property
+    def exponential(self) -> bool: ...
+    @property
+    def max_backoff_ms(self) -> int: ...
+    @property
+    def jitter(self) -> float: ...
+
+class Node:
+    @staticmethod
+    def agent(
+        name: str,
+        prompt: str,
+        context: Optional[str] = None,
+        agent_id: Optional[str] = None,
+        output_name: Optional[str] = None,
+        tools: Optional[List[Any]] = None,
+        system_prompt: Optional[str] = None,
+        llm_config: Optional[LlmConfig] = None,
+        temperature: Optional[float] = None,
+        max_tokens: Optional[int] = None,
+        max_iterations: Optional[int] = None,
+        enable_prompt_caching: bool = False,
+        model: Optional[str] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        max_repair_attempts: Optional[int] = None,
+        strict_json: bool = False,
+        handoff_targets: Optional[List[str]] = None,
+        agent: Optional[Agent] = None,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        context_strategy: Optional[Literal["truncate_history", "truncate_documents", "fail"]] = None,
+        context_window: Optional[int] = None,
+        history_window: Optional[int] = None,
+        history_token_budget: Optional[int] = None,
+        summarize_overflow: bool = False,
+        globals: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def model_router(
+        name: str,
+        prompt: str,
+        models: List[LlmConfig],
+        escalate_if: str,
+        context: Optional[str] = None,
+        agent_id: Optional[str] = None,
+        output_name: Optional[str] = None,
+        system_prompt: Optional[str] = None,
+        temperature: Optional[float] = None,
+        max_tokens: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def transform(
+        name: str,
+        transformation: str,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def condition(
+        name: str,
+        handler: Optional[Union[Callable[[Dict[str, Any]], str], str]] = None,
+        expression: Optional[str] = None,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def http_request(
+        name: str,
+        url: str,
+        method: str = "GET",
+        headers: Optional[Dict[str, str]] = None,
+        body: Optional[Any] = None,
+        idempotency_header: Optional[str] = "Idempotency-Key",
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+        multipart: Optional[Dict[str, Any]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def delay(
+        name: str,
+        seconds: int,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def python_code(
+        name: str,
+        code: str,
+        timeout: int = 30,
+        allowed_imports: Optional[List[str]] = None,
+        allow_network: bool = False,
+        memory_limit_mb: int = 512,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def shell(
+        name: str,
+        command: str,
+        working_dir: Optional[str] = None,
+        env: Optional[Dict[str, str]] = None,
+        timeout: int = 60,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def guardrail(
+        name: str,
+        checks: List[Union[str, Dict[str, Any]]],
+        on_violation: str = "redact",
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def external_event(
+        name: str,
+        event_name: str,
+        event_timeout: Optional[int] = None,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def document_loader(
+        name: str,
+        source: str,
+        document_type: str,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def split(
+        name: str,
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    @staticmethod
+    def join(
+        name: str,
+        policy: str = "all",
+        quorum: Optional[int] = None,
+        pending_branches: str = "cancel",
+        retry: Optional[RetryPolicy] = None,
+        retry_config: Optional[Dict[str, Any]] = None,
+        timeout_seconds: Optional[int] = None,
+        tags: Optional[List[str]] = None,
+        budget_exempt: bool = False,
+        pre_execution_delay_ms: Optional[int] = None,
+        post_execution_delay_ms: Optional[int] = None,
+        delay_jitter_ms: Optional[int] = None,
+        output_schema: Optional[Dict[str, Any]] = None,
+        concurrency_key: Optional[str] = None,
+        post_process: Optional[List[Union[str, Dict[str, Any]]]] = None,
+    ) -> Node: ...
+    def id(self) -> str: ...
+    def name(self) -> str: ...
+    def has_tools(self) -> bool: ...
+    def get_tool_names(self) -> List[str]: ...
+    def create_tool_executor(self) -> ToolExecutor: ...
+    def execute_tools(self, tool_calls: List[Any]) -> str: ...
+    def get_config(self) -> str: ...
+
+class Workflow:
+    def __init__(self, name: str) -> None: ...
+    def add_node(self, node: Node) -> str: ...
+    def connect(
+        self,
+        from_id: str,
+        to_id: str,
+        condition: Optional[str] = None,
+        transform: Optional[str] = None,
+        on_violation: bool = False,
+        on_timeout: bool = False,
+        on: Optional[Literal["failure"]] = None,
+        branch: Optional[Literal["true", "false"]] = None,
+    ) -> None: ...
+    def replace_node(self, node_id: str, node: Node) -> str: ...
+    def insert_between(self, from_id: str, to_id: str, node: Node) -> str: ...
+    def validate(self) -> None: ...
+    def warnings(self) -> List[str]: ...
+    def name(self) -> str: ...
+    def set_input_schema(self, schema: Dict[str, Any]) -> None: ...
+    def input_schema(self) -> Optional[Dict[str, Any]]: ...
+    def node_count(self) -> int: ...
+    def edge_count(self) -> int: ...
+    def find_nodes(self, type: Optional[str] = None, tag: Optional[str] = None) -> List[str]: ...
+    def ancestors(self, node: str) -> List[str]: ...
+    def descendants(self, node: str) -> List[str]: ...
+    def analyze(
+        self,
+        prices: Optional[Dict[str, Tuple[float, float]]] = None,
+        avg_prompt_tokens: int = 1000,
+        avg_completion_tokens: int = 500,
+    ) -> Dict[str, Any]: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> Workflow: ...
+    def set_metadata(self, key: str, value: Any) -> None: ...
+    def set_graph_metadata(self, key: str, value: bool) -> None: ...
+
+class WorkflowTemplate:
+    def __init__(
+        self,
+        name: str,
+        workflow: Workflow,
+        parameters: Optional[Dict[str, Union[str, Dict[str, Any]]]] = None,
+        description: Optional[str] = None,
+    ) -> None: ...
+    @staticmethod
+    def from_file(path: str) -> WorkflowTemplate: ...
+    @staticmethod
+    def from_json(json: str) -> WorkflowTemplate: ...
+    def to_json(self) -> str: ...
+    def instantiate(self, **params: Any) -> Workflow: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def description(self) -> str: ...
+    @property
+    def parameters(self) -> Dict[str, Dict[str, Any]]: ...
+
+class TemplateRegistry:
+    def __init__(self) -> None: ...
+    def register(self, template: WorkflowTemplate) -> None: ...
+    def register_file(self, path: str) -> str: ...
+    def instantiate(self, name: str, /, **params: Any) -> Workflow: ...
+    def get(self, name: str) -> Optional[WorkflowTemplate]: ...
+    def remove(self, name: str) -> bool: ...
+    def names(self) -> List[str]: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, name: str) -> bool: ...
+
+class WorkflowContext:
+    def __init__(self) -> None: ...
+    def set_variable(self, key: str, value: Any) -> None: ...
+    def get_variable(self, key: str) -> Optional[Any]: ...
+    def get_node_output(self, node_id: str) -> Optional[Any]: ...
+    def get_nested_output(self, reference: str) -> Optional[Any]: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    def get_all_node_outputs(self) -> Dict[str, Any]: ...
+    def get_workflow_id(self) -> str: ...
+    def get_state(self) -> str: ...
+    def is_completed(self) -> bool: ...
+    def is_failed(self) -> bool: ...
+
+class Attachment:
+    @property
+    def id(self) -> str: ...
+    @property
+    def mime_type(self) -> str: ...
+    @property
+    def filename(self) -> Optional[str]: ...
+    @property
+    def size_bytes(self) -> int: ...
+    @property
+    def sha256(self) -> str: ...
+    def bytes(self) -> builtins.bytes: ...
+    def save(self, path: Union[str, os.PathLike[str]]) -> None: ...
+
+class ResultComparison:
+    def __getitem__(self, key: str) -> Any: ...
+    def __contains__(self, key: str) -> bool: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def keys(self) -> List[str]: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    def violations(self, thresholds: Optional[Dict[str, Any]] = None) -> List[str]: ...
+    def passes(self, thresholds: Optional[Dict[str, Any]] = None) -> bool: ...
+
+class WorkflowResult:
+    def is_success(self) -> bool: ...
+    def is_failed(self) -> bool: ...
+    def is_cancelled(self) -> bool: ...
+    def is_partial(self) -> bool: ...
+    def failed_nodes(self) -> List[str]: ...
+    def skipped_nodes(self) -> Dict[str, List[str]]: ...
+    def state(self) -> str: ...
+    def execution_time_ms(self) -> int: ...
+    def variables(self) -> List[Tuple[str, str]]: ...
+    def get_variable(self, key: str) -> Optional[str]: ...
+    def get_all_variables(self) -> Dict[str, str]: ...
+    def get_node_output(self, node_name: str) -> Optional[str]: ...
+    def get_all_node_outputs(self) -> Dict[str, str]: ...
+    def get_node_response_metadata(self, node_id: str) -> Optional[Dict[str, Any]]: ...
+    def get_all_node_response_metadata(self) -> Dict[str, Any]: ...
+    def get_tool_calls(self, node_name: str) -> List[Dict[str, Any]]: ...
+    def get_output_validation(self, node_name: str) -> Optional[Dict[str, Any]]: ...
+    def get_node_attempts(self, node_name: str) -> Optional[int]: ...
+    def get_node_debug(self, node_name: str) -> Optional[Dict[str, Any]]: ...
+    def get_guardrail_report(self, node_name: str) -> Optional[Dict[str, Any]]: ...
+    def get_abandoned_nodes(self) -> Dict[str, str]: ...
+    def get_reused_nodes(self) -> List[str]: ...
+    def get_node_attachments(self, node_name: str) -> List[Attachment]: ...
+    def get_failure_reason(self) -> Optional[Dict[str, Any]]: ...
+    def get_warm_up_report(self) -> Optional[Dict[str, Any]]: ...
+    def get_stats(self) -> Optional[Dict[str, Any]]: ...
+    def get_usage_by_tag(self) -> Dict[str, Dict[str, Any]]: ...
+    def get_usage_by_metadata(self, key: str) -> Optional[Dict[str, Dict[str, Any]]]: ...
+    def to_json(self, max_value_chars: Optional[int] = None) -> str: ...
+    @staticmethod
+    def from_json(json: str) -> WorkflowResult: ...
+    def compare(
+        self,
+        expected: WorkflowResult,
+        ignore: Optional[List[str]] = None,
+        ignore_keys: Optional[List[str]] = None,
+        numeric_tolerance: float = 0.0,
+    ) -> ResultComparison: ...
+    def to_report_dict(
+        self,
+        max_value_chars: Optional[int] = 10000,
+        redact_keys: Optional[List[str]] = None,
+        include_payloads: bool = False,
+    ) -> Dict[str, Any]: ...
+    def save_report(
+        self,
+        path: Union[str, os.PathLike[str]],
+        max_value_chars: Optional[int] = 10000,
+        redact_keys: Optional[List[str]] = None,
+        include_payloads: bool = False,
+    ) -> None: ...
+    def to_transcript_markdown(
+        self,
+        max_value_chars: Optional[int] = 10000,
+        redact_keys: Optional[List[str]] = None,
+    ) -> str: ...
+    def save_transcript(
+        self,
+        path: Union[str, os.PathLike[str]],
+        max_value_chars: Optional[int] = 10000,
+        redact_keys: Optional[List[str]] = None,
+    ) -> None: ...
+
+@final
+class WorkflowStreamIterator(Iterator[Dict[str, Any]], AsyncIterator[Dict[str, Any]]):
+    def __iter__(self) -> WorkflowStreamIterator: ...
+    def __next__(self) -> Dict[str, Any]: ...
+    def __aiter__(self) -> WorkflowStreamIterator: ...
+    def __anext__(self) -> Awaitable[Dict[str, Any]]: ...
+
+@final
+class ExecutionHandle:
+    @property
+    def execution_id(self) -> str: ...
+    def deliver_event(self, event_name: str, payload: Any) -> None: ...
+    def waiting_events(self) -> List[str]: ...
+    def is_done(self) -> bool: ...
+    def result(self, timeout_seconds: Optional[float] = None) -> WorkflowResult: ...
+
+class Executor:
+    def __init__(
+        self,
+        config: LlmConfig,
+        lightweight_mode: Optional[bool] = None,
+        timeout_seconds: Optional[int] = None,
+        debug: Optional[bool] = None,
+        strict_tool_args: bool = False,
+        redact_tool_calls: bool = False,
+        tool_call_max_chars: Optional[int] = None,
+        mode: Optional[Literal["balanced", "high_throughput", "low_latency", "memory_optimized"]] = None,
+        max_concurrency: Optional[int] = None,
+        node_type_limits: Optional[Dict[str, int]] = None,
+        fail_fast: bool = False,
+        max_node_execution_time_ms: Optional[int] = None,
+        retry: Optional[RetryPolicy] = None,
+        circuit_breaker: Optional[Dict[str, Any]] = None,
+        middleware: Optional[List[LlmMiddleware]] = None,
+        allow_shell_nodes: bool = False,
+        max_total_tokens: Optional[int] = None,
+        max_cost_usd: Optional[float] = None,
+        budget_policy: str = "finish",
+        audit_log: Optional[str] = None,
+        audit_policy: str = "hash",
+        globals: Optional[Dict[str, Any]] = None,
+        system_preamble: Optional[str] = None,
+        prompt_suffix: Optional[str] = None,
+        profiles: Optional[Union[Dict[str, Any], str, os.PathLike[str]]] = None,
+        capture_payloads: bool = False,
+        capture_payload_max_chars: Optional[int] = None,
+        min_interval_between_llm_calls_ms: Optional[int] = None,
+        run_history: Optional[Union[str, os.PathLike[str]]] = None,
+        run_history_report_dir: Optional[Union[str, os.PathLike[str]]] = None,
+        max_attachment_bytes: Optional[int] = None,
+        allow_python_code_nodes: bool = False,
+    ) -> None: ...
+    @classmethod
+    def from_env(cls, config: Optional[LlmConfig] = None, **kwargs: Any) -> Executor: ...
+    def execute(
+        self,
+        workflow: Workflow,
+        policy: Optional[GuardRailPolicyConfig] = None,
+        inputs: Optional[Dict[str, Any]] = None,
+        secrets: Optional[Dict[str, str]] = None,
+        profile: Optional[str] = None,
+        only_tags: Optional[List[str]] = None,
+        skip_tags: Optional[List[str]] = None,
+        warm_up: bool = False,
+    ) -> WorkflowResult: ...
+    def rerun(
+        self,
+        workflow: Workflow,
+        result: WorkflowResult,
+        from_nodes: List[str],
+        policy: Optional[GuardRailPolicyConfig] = None,
+        secrets: Optional[Dict[str, str]] = None,
+    ) -> WorkflowResult: ...
+    def run_async(
+        self,
+        workflow: Workflow,
+        policy: Optional[GuardRailPolicyConfig] = None,
+        inputs: Optional[Dict[str, Any]] = None,
+        secrets: Optional[Dict[str, str]] = None,
+        profile: Optional[str] = None,
+        only_tags: Optional[List[str]] = None,
+        skip_tags: Optional[List[str]] = None,
+        warm_up: bool = False,
+    ) -> Awaitable[WorkflowResult]: ...
+    def execute_async(
+        self,
+        workflow: Workflow,
+        policy: Optional[GuardRailPolicyConfig] = None,
+        inputs: Optional[Dict[str, Any]] = None,
+        secrets: Optional[Dict[str, str]] = None,
+        profile: Optional[str] = None,
+        only_tags: Optional[List[str]] = None,
+        skip_tags: Optional[List[str]] = None,
+        warm_up: bool = False,
+    ) -> ExecutionHandle: ...
+    def execute_batch(
+        self,
+        workflow: Workflow,
+        inputs: List[Dict[str, Any]],
+        output_jsonl: Optional[str] = None,
+        output_max_bytes: Optional[int] = None,
+        policy: Optional[GuardRailPolicyConfig] = None,
+        secrets: Optional[Dict[str, str]] = None,
+        profile: Optional[str] = None,
+        only_tags: Optional[List[str]] = None,
+        skip_tags: Optional[List[str]] = None,
+    ) -> List[WorkflowResult]: ...
+    def deliver_event(self, execution_id: str, event_name: str, payload: Any) -> None: ...
+    def execute_streaming(
+        self,
+        workflow: Workflow,
+        policy: Optional[GuardRailPolicyConfig] = None,
+        stream_mode: Optional[str] = None,
+        output_jsonl: Optional[str] = None,
+        output_max_bytes: Optional[int] = None,
+    ) -> WorkflowStreamIterator: ...
+    def configure(
+        self,
+        timeout_seconds: Optional[int] = None,
+        max_retries: Optional[int] = None,
+        enable_metrics: Optional[bool] = None,
+        debug: Optional[bool] = None,
+    ) -> None: ...
+    def register_agent(self, agent: Agent) -> None: ...
+    def metrics_snapshot(self) -> Dict[str, List[Dict[str, Any]]]: ...
+    def get_stats(self) -> Dict[str, Any]: ...
+    def stats(self) -> Dict[str, Any]: ...
+    def dry_run(self, workflow: Workflow) -> Dict[str, Any]: ...
+    def warm_up(
+        self,
+        workflow: Workflow,
+        secrets: Optional[Dict[str, str]] = None,
+        profile: Optional[str] = None,
+        prime: bool = True,
+    ) -> Dict[str, Any]: ...
+    def shutdown(self, timeout: float = 30.0, metrics_url: Optional[str] = None) -> Dict[str, Any]: ...
+    def shutdown_on_exit(self, timeout: float = 30.0) -> None: ...
+    def list_runs(
+        self,
+        limit: int = 20,
+        workflow_name: Optional[str] = None,
+        state: Optional[Literal["running", "completed", "partially_completed", "failed", "cancelled"]] = None,
+        since: Optional[Union[datetime.datetime, str]] = None,
+        until: Optional[Union[datetime.datetime, str]] = None,
+    ) -> List[Dict[str, Any]]: ...
+    def prune_runs(self, max_age_seconds: Optional[float] = None, max_runs: Optional[int] = None) -> int: ...
+    def usage_summary(self, period_seconds: float) -> Dict[str, Any]: ...
+    def reset_stats(self) -> None: ...
+    def get_execution_mode(self) -> str: ...
+    @staticmethod
+    def get_stream_event_schema() -> Dict[str, Any]: ...
+
+# Embeddings
+
+class EmbeddingConfig:
+    @staticmethod
+    def openai(
+        api_key: str, model: Optional[str] = None, base_url: Optional[str] = None, timeout_ms: Optional[int] = None, connect_timeout_ms: Optional[int] = None
+    ) -> EmbeddingConfig: ...
+    @staticmethod
+    def azure(
+        api_key: str,
+        deployment_name: str,
+        endpoint: str,
+        model: Optional[str] = None,
+        api_version: Optional[str] = None,
+        timeout_ms: Optional[int] = None,
+        connect_timeout_ms: Optional[int] = None,
+    ) -> EmbeddingConfig: ...
+    @staticmethod
+    def voyage(
+        api_key: str,
+        model: Optional[str] = None,
+        input_type: Optional[str] = None,
+        dimensions: Optional[int] = None,
+        base_url: Optional[str] = None,
+        timeout_ms: Optional[int] = None,
+        connect_timeout_ms: Optional[int] = None,
+    ) -> EmbeddingConfig: ...
+    @staticmethod
+    def jina(
+        api_key: str,
+        model: Optional[str] = None,
+        input_type: Optional[str] = None,
+        dimensions: Optional[int] = None,
+        base_url: Optional[str] = None,
+        timeout_ms: Optional[int] = None,
+        connect_timeout_ms: Optional[int] = None,
+    ) -> EmbeddingConfig: ...
+    @staticmethod
+    def local(model_dir: str, threads: Optional[int] = None, max_batch_size: Optional[int] = None) -> EmbeddingConfig: ...
+    @staticmethod
+    def huggingface(api_key: str, model: Optional[str] = None) -> EmbeddingConfig: ...
+    @staticmethod
+    def litellm(api_key: str, model: Optional[str] = None) -> EmbeddingConfig: ...
+    @staticmethod
+    def from_env() -> EmbeddingConfig: ...
+    @property
+    def timeout_ms(self) -> Optional[int]: ...
+    @property
+    def connect_timeout_ms(self) -> Optional[int]: ...
+    @property
+    def retry(self) -> Optional[RetryPolicy]: ...
+    @retry.setter
+    def retry(self, value: Optional[RetryPolicy]) -> None: ...
+
+class EmbeddingClient:
+    def __init__(self, config: EmbeddingConfig) -> None: ...
+    def embed(self, text: str, input_type: Optional[str] = None) -> List[float]: ...
+    def embed_many(self, texts: List[str], input_type: Optional[str] = None) -> List[List[float]]: ...
+    def embed_batch_parallel(
+        self,
+        texts_batch: List[List[str]],
+        max_concurrency: Optional[int] = None,
+        timeout_ms: Optional[int] = None,
+    ) -> Dict[str, Any]: ...
+    def health_check(self) -> Dict[str, Any]: ...
+    @staticmethod
+    def similarity(a: List[float], b: List[float]) -> float: ...
+    @staticmethod
+    def dot_product(a: List[float], b: List[float]) -> float: ...
+    @staticmethod
+    def euclidean_distance(a: List[float], b: List[float]) -> float: ...
+    @staticmethod
+    def top_k(query: List[float], corpus: List[List[float]], k: int, metric: str = "cosine") -> List[Tuple[int, float]]: ...
+    @staticmethod
+    def mmr(query: List[float], candidates: List[List[float]], lambda_mult: float = 0.5, k: int = 4) -> List[int]: ...
+
+# Memory
+
+class MemoryConfig:
+    def __init__(
+        self,
+        llm_config: LlmConfig,
+        embedding_config: EmbeddingConfig,
+        db_path: Optional[str] = None,
+        similarity_threshold: Optional[float] = None,
+    ) -> None: ...
+
+@final
+class PyMemory:
+    @property
+    def id(self) -> str: ...
+    @property
+    def content(self) -> str: ...
+    @property
+    def user_id(self) -> Optional[str]: ...
+    @property
+    def agent_id(self) -> Optional[str]: ...
+    @property
+    def run_id(self) -> Optional[str]: ...
+    @property
+    def created_at(self) -> str: ...
+    @property
+    def updated_at(self) -> str: ...
+    @property
+    def metadata(self) -> Dict[str, str]: ...
+
+@final
+class PyScoredMemory:
+    @property
+    def memory(self) -> PyMemory: ...
+    @property
+    def score(self) -> float: ...
+
+@final
+class PyMemoryHistory:
+    @property
+    def memory_id(self) -> str: ...
+    @property
+    def old_content(self) -> str: ...
+    @property
+    def new_content(self) -> str: ...
+    @property
+    def action(self) -> str: ...
+    @property
+    def timestamp(self) -> str: ...
+
+class MemoryClient:
+    def __init__(self, config: MemoryConfig) -> None: ...
+    def add(
+        self,
+        messages: List[Tuple[str, str]],
+        user_id: Optional[str] = None,
+        agent_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+    ) -> List[PyMemory]: ...
+    def search(
+        self,
+        query: str,
+        user_id: Optional[str] = None,
+        agent_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+        top_k: Optional[int] = None,
+        rerank: Optional[Dict[str, Any]] = None,
+    ) -> List[PyScoredMemory]: ...
+    def get(self, memory_id: str) -> Optional[PyMemory]: ...
+    def get_all(
+        self,
+        user_id: Optional[str] = None,
+        agent_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+    ) -> List[PyMemory]: ...
+    def update(self, memory_id: str, content: str) -> PyMemory: ...
+    def delete(self, memory_id: str) -> None: ...
+    def delete_all(
+        self,
+        user_id: Optional[str] = None,
+        agent_id: Optional[str] = None,
+        run_id: Optional[str] = None,
+    ) -> None: ...
+    def history(self, memory_id: str) -> List[PyMemoryHistory]: ...
+    def reembed(
+        self,
+        embedding_config: EmbeddingConfig,
+        batch_size: int = 256,
+        progress: Optional[Callable[[int, int], Any]] = None,
+    ) -> int: ...
+
+# Text splitting
+
+class TextSplitterConfig:
+    @staticmethod
+    def character(chunk_size: int, chunk_overlap: int = 0) -> TextSplitterConfig: ...
+    @staticmethod
+    def token(chunk_size: int, chunk_overlap: int = 0, token_pattern: Optional[str] = None) -> TextSplitterConfig: ...
+    @staticmethod
+    def sentence(
+        chunk_size: int, chunk_overlap: int = 0, sentence_endings: Optional[List[str]] = None, abbreviations: Optional[List[str]] = None, unicode_segmentation: bool = False
+    ) -> TextSplitterConfig: ...
+    @staticmethod
+    def recursive(chunk_size: int, chunk_overlap: int = 0, separators: Optional[List[str]] = None) -> TextSplitterConfig: ...
+    @staticmethod
+    def paragraph(chunk_size: int, chunk_overlap: int = 0, min_paragraph_length: Optional[int] = None) -> TextSplitterConfig: ...
+    @staticmethod
+    def markdown(chunk_size: int, chunk_overlap: int = 0, split_by_headers: bool = True) -> TextSplitterConfig: ...
+    @staticmethod
+    def code(chunk_size: int, chunk_overlap: int = 0, language: Optional[str] = None) -> TextSplitterConfig: ...
+    @staticmethod
+    def regex(pattern: str, chunk_size: int, chunk_overlap: int = 0) -> TextSplitterConfig: ...
+    def set_preserve_word_boundaries(self, preserve: bool) -> None: ...
+    def set_trim_whitespace(self, trim: bool) -> None: ...
+    def set_include_metadata(self, include: bool) -> None: ...
+    @property
+    def preserve_word_boundaries(self) -> bool: ...
+    @property
+    def trim_whitespace(self) -> bool: ...
+    @property
+    def include_metadata(self) -> bool: ...
+    @property
+    def strategy_type(self) -> str: ...
+    @property
+    def chunk_size(self) -> int: ...
+    @property
+    def chunk_overlap(self) -> int: ...
+
+@final
+class TextChunk:
+    @property
+    def content(self) -> str: ...
+    @property
+    def start_index(self) -> int: ...
+    @property
+    def end_index(self) -> int: ...
+    @property
+    def chunk_index(self) -> int: ...
+    @property
+    def metadata(self) -> Dict[str, Any]: ...
+
+class CharacterSplitter:
+    def __init__(self, chunk_size: int, chunk_overlap: int = 0) -> None: ...
+    def split_text(self, text: str) -> List[TextChunk]: ...
+    def split_texts(self, texts: List[str]) -> List[List[TextChunk]]: ...
+    @property
+    def chunk_size(self) -> int: ...
+    @property
+    def chunk_overlap(self) -> int: ...
+
+class TokenSplitter:
+    def __init__(self, chunk_size: int, chunk_overlap: int = 0, token_pattern: Optional[str] = None) -> None: ...
+    def split_text(self, text: str) -> List[TextChunk]: ...
+    def split_texts(self, texts: List[str]) -> List[List[TextChunk]]: ...
+    @property
+    def chunk_size(self) -> int: ...
+    @property
+    def chunk_overlap(self) -> int: ...
+
+class SentenceSplitter:
+    def __init__(
+        self, chunk_size: int, chunk_overlap: int = 0, sentence_endings: Optional[List[str]] = None, abbreviations: Optional[List[str]] = None, unicode_segmentation: bool = False
+    ) -> None: ...
+    def split_text(self, text: str) -> List[TextChunk]: ...
+    def split_texts(self, texts: List[str]) -> List[List[TextChunk]]: ...
+    @property
+    def chunk_size(self) -> int: ...
+    @property
+    def chunk_overlap(self) -> int: ...
+
+class RecursiveSplitter:
+    def __init__(self, chunk_size: int, chunk_overlap: int = 0, separators: Optional[List[str]] = None) -> None: ...
+    def split_text(self, text: str) -> List[TextChunk]: ...
+    def split_texts(self, texts: List[str]) -> List[List[TextChunk]]: ...
+    @property
+    def chunk_size(self) -> int: ...
+    @property
+    def chunk_overlap(self) -> int: ...
+    @property
+    def separators(self) -> List[str]: ...
+
+class TextSplitter:
+    def __init__(self, config: TextSplitterConfig) -> None: ...
+    @staticmethod
+    def character(chunk_size: int, chunk_overlap: int = 0) -> TextSplitter: ...
+    @staticmethod
+    def token(chunk_size: int, chunk_overlap: int = 0, token_pattern: Optional[str] = None) -> TextSplitter: ...
+    @staticmethod
+    def sentence(
+        chunk_size: int, chunk_overlap: int = 0, sentence_endings: Optional[List[str]] = None, abbreviations: Optional[List[str]] = None, unicode_segmentation: bool = False
+    ) -> TextSplitter: ...
+    @staticmethod
+    def recursive(chunk_size: int, chunk_overlap: int = 0, separators: Optional[List[str]] = None) -> TextSplitter: ...
+    @staticmethod
+    def auto(document: DocumentContent, chunk_size: int = 1000, chunk_overlap: int = 200) -> List[TextChunk]: ...
+    def split_text(self, text: str) -> List[TextChunk]: ...
+    def split_texts(self, texts: List[str]) -> List[List[TextChunk]]: ...
+    def split_document(self, document: DocumentContent) -> List[TextChunk]: ...
+    def create_documents(self, text: str) -> List[Dict[str, str]]: ...
+
+# Validation
+
+@final
+class ValidationResult:
+    @property
+    def is_valid(self) -> bool: ...
+    @property
+    def errors(self) -> List[Dict[str, Any]]: ...
+    @property
+    def metadata(self) -> Dict[str, Any]: ...
+    def __bool__(self) -> bool: ...
+
+def validate_json(instance: Any, schema: Union[Dict[str, Any], bool]) -> ValidationResult: ...
+
+# Tools
+
+class ToolResult:
+    def __init__(self, tool_name: str, input_params: str, output: str, duration_ms: int = 0) -> None: ...
+    @staticmethod
+    def failure(tool_name: str, input_params: str, error: str, duration_ms: int = 0) -> ToolResult: ...
+    @staticmethod
+    def from_json(json_str: str) -> ToolResult: ...
+    @property
+    def tool_name(self) -> str: ...
+    @property
+    def input_params(self) -> str: ...
+    @property
+    def output(self) -> str: ...
+    @property
+    def success(self) -> bool: ...
+    @property
+    def error(self) -> Optional[str]: ...
+    @property
+    def duration_ms(self) -> int: ...
+    @property
+    def timestamp(self) -> int: ...
+    def is_success(self) -> bool: ...
+    def is_failure(self) -> bool: ...
+    def get_error(self) -> Optional[str]: ...
+    def get_duration(self) -> float: ...
+    def add_metadata(self, key: str, value: Any) -> None: ...
+    def get_metadata(self, key: str) -> Optional[str]: ...
+    def to_json(self) -> str: ...
+
+class ToolResultCollection:
+    def __init__(self) -> None: ...
+    def add(self, result: ToolResult) -> None: ...
+    def get_all(self) -> List[ToolResult]: ...
+    def get_successful(self) -> List[ToolResult]: ...
+    def get_failed(self) -> List[ToolResult]: ...
+    def count(self) -> int: ...
+    def success_count(self) -> int: ...
+    def failure_count(self) -> int: ...
+    def success_rate(self) -> float: ...
+    def total_duration_ms(self) -> int: ...
+    def clear(self) -> None: ...
+
+class ToolContext:
+    def __init__(self) -> None: ...
+    @property
+    def node_name(self) -> str: ...
+    @property
+    def node_outputs(self) -> Dict[str, Any]: ...
+    @property
+    def variables(self) -> Dict[str, Any]: ...
+    def node_output(self, node: str) -> Optional[Any]: ...
+    def variable(self, name: str) -> Optional[Any]: ...
+    def get(self, key: str, default: Optional[Any] = None) -> Optional[Any]: ...
+    def set(self, key: str, value: Any) -> None: ...
+
+class ToolRegistry:
+    def __init__(self) -> None: ...
+    @staticmethod
+    def new_global_proxy() -> ToolRegistry: ...
+    def register_tool(
+        self,
+        name: str,
+        description: str,
+        function: Callable[..., Any],
+        parameters_schema: Dict[str, Any],
+        return_type: Optional[str],
+    ) -> None: ...
+    def unregister_tool(self, name: str) -> bool: ...
+    def has_tool(self, name: str) -> bool: ...
+    def list_tools(self) -> List[str]: ...
+    def get_tool_metadata(self, name: str) -> Optional[str]: ...
+    def get_llm_tools(self) -> List[str]: ...
+    def execute_tool(self, name: str, params: Dict[str, Any]) -> ToolResult: ...
+    def get_execution_history(self) -> List[ToolResult]: ...
+    def clear_history(self) -> None: ...
+    def get_stats(self) -> str: ...
+
+class ToolDecorator:
+    def __init__(self, registry: Optional[ToolRegistry] = None) -> None: ...
+    def __call__(
+        self,
+        description: Optional[str] = None,
+        name: Optional[str] = None,
+        return_type: Optional[str] = None,
+    ) -> Callable[[_F], _F]: ...
+    def get_registry(self) -> ToolRegistry: ...
+    def register(
+        self,
+        func: Callable[..., Any],
+        name: Optional[str],
+        description: Optional[str],
+        return_type: Optional[str],
+    ) -> None: ...
+    def list_tools(self) -> List[str]: ...
+    def get_tool_info(self, name: str) -> Optional[str]: ...
+
+class ExecutorConfig:
+    max_execution_time_ms: int
+    max_tool_calls: int
+    continue_on_error: bool
+    store_results: bool
+    enable_logging: bool
+    def __init__(
+        self,
+        max_execution_time_ms: int = 30000,
+        max_tool_calls: int = 10,
+        continue_on_error: bool = False,
+        store_results: bool = True,
+        enable_logging: bool = True,
+    ) -> None: ...
+    @staticmethod
+    def production() -> ExecutorConfig: ...
+    @staticmethod
+    def development() -> ExecutorConfig: ...
+
+class ToolExecutor:
+    def __init__(self, registry: Optional[ToolRegistry] = None, config: Optional[ExecutorConfig] = None) -> None: ...
+    def execute_tools(self, tool_calls: List[Any]) -> ToolResultCollection: ...
+    def get_results(self) -> ToolResultCollection: ...
+    def get_variables(self) -> str: ...
+    def set_variable(self, key: str, value: Any) -> None: ...
+    def get_variable(self, key: str) -> Optional[str]: ...
+    def clear_context(self) -> None: ...
+    def get_execution_stats(self) -> str: ...
+
+@final
+class BuiltinTool:
+    @property
+    def name(self) -> str: ...
+    @property
+    def description(self) -> str: ...
+    @property
+    def parameters_schema(self) -> Dict[str, Any]: ...
+    def __call__(self, **kwargs: Any) -> Any: ...
+
+class BuiltinTools:
+    @staticmethod
+    def http(
+        max_response_bytes: Optional[int] = None,
+        allowed_hosts: Optional[List[str]] = None,
+        denied_hosts: Optional[List[str]] = None,
+        timeout_seconds: Optional[int] = None,
+        extract_html_text: bool = True,
+    ) -> BuiltinTool: ...
+
+def tool(
+    _description: Optional[str] = None,
+    name: Optional[str] = None,
+    description: Optional[str] = None,
+    return_type: Optional[str] = None,
+    schema_override: Optional[Dict[str, Any]] = None,
+) -> Callable[[_F], _F]: ...
+def get_tool_registry() -> ToolRegistry: ...
+def clear_tools() -> None: ...
+def execute_tool(tool_name: str, args: List[Any]) -> Any: ...
+def get_registered_tools() -> List[str]: ...
+def sync_global_tools_to_workflow() -> None: ...
+def execute_workflow_tool_calls(tool_calls_json: str, node_tools: List[str]) -> str: ...
+def execute_production_tool_calls(tool_calls_json: str, node_tools: List[str]) -> str: ...
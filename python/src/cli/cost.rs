@@ -0,0 +1,75 @@
+//! `graphbit cost`: estimate what running a workflow's agent nodes costs
+
+use graphbit_core::llm::LlmUsage;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{Args, CliError, Outcome, load_document, load_workflow, write_out};
+
+pub(super) fn run(py: Python<'_>, args: &Args) -> Result<Outcome, CliError> {
+    let path = &args.positional[0];
+    let prices_path = args
+        .option("--model-prices")
+        .ok_or_else(|| CliError::Usage("cost needs --model-prices FILE".to_string()))?;
+    let tokens = LlmUsage::new(
+        args.number("--prompt-tokens", 1000)?,
+        args.number("--completion-tokens", 500)?,
+    );
+
+    let workflow = load_workflow(py, path)?;
+    let prices: HashMap<String, (f64, f64)> =
+        serde_json::from_value(load_document(py, prices_path)?).map_err(|e| {
+            CliError::Failed(format!(
+                "{prices_path} must map model names to \
+                 [input_cost_per_token, output_cost_per_token]: {e}"
+            ))
+        })?;
+    let estimate = workflow
+        .estimate_cost(&prices, &tokens)
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+
+    let mut text = format!(
+        "Estimated cost of '{}' ({} prompt + {} completion tokens per agent node)\n",
+        workflow.name, tokens.prompt_tokens, tokens.completion_tokens
+    );
+    let name_width = estimate
+        .nodes
+        .iter()
+        .map(|node| node.node_name.len())
+        .max()
+        .unwrap_or(0);
+    let model_width = estimate
+        .nodes
+        .iter()
+        .map(|node| node.model.as_deref().map_or(1, str::len))
+        .max()
+        .unwrap_or(0);
+    for node in &estimate.nodes {
+        let cost = node
+            .cost
+            .map_or_else(|| "unpriced".to_string(), |cost| format!("${cost:.6}"));
+        let _ = writeln!(
+            text,
+            "  {:name_width$}  {:model_width$}  {cost}",
+            node.node_name,
+            node.model.as_deref().unwrap_or("-"),
+        );
+    }
+    let _ = writeln!(
+        text,
+        "Total: ${:.6} for {} prompt + {} completion tokens",
+        estimate.total_cost,
+        estimate.token_usage.prompt_tokens,
+        estimate.token_usage.completion_tokens
+    );
+    if !estimate.unpriced_nodes.is_empty() {
+        let _ = writeln!(
+            text,
+            "Unpriced nodes: {}",
+            estimate.unpriced_nodes.join(", ")
+        );
+    }
+    write_out(py, &text)?;
+    Ok(Outcome::Success)
+}
@@ -0,0 +1,42 @@
+//! `graphbit doctor`: list the `GRAPHBIT_*` environment variables found and missing
+
+use graphbit_core::env_config::{EnvConfig, EnvVarStatus};
+use pyo3::prelude::*;
+use std::fmt::Write;
+
+use super::{Args, CliError, Outcome, write_out};
+
+pub(super) fn run(py: Python<'_>, _args: &Args) -> Result<Outcome, CliError> {
+    let checks = EnvConfig::from_env().doctor();
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut text = String::new();
+    for check in &checks {
+        let (status, detail) = match &check.status {
+            EnvVarStatus::Found(value) => ("found", value.clone()),
+            EnvVarStatus::Missing if check.required => {
+                ("missing", format!("{} (required)", check.description))
+            }
+            EnvVarStatus::Missing => ("missing", check.description.clone()),
+            EnvVarStatus::Invalid(reason) => ("invalid", reason.clone()),
+            EnvVarStatus::Unrecognized => ("unknown", check.description.clone()),
+        };
+        let _ = writeln!(text, "{:<width$}  {status:<8} {detail}", check.name);
+    }
+    let problems = checks.iter().filter(|check| check.is_problem()).count();
+    if problems == 0 {
+        text.push_str("\nenvironment: ok\n");
+    } else {
+        let _ = writeln!(text, "\nenvironment: {problems} problem(s)");
+    }
+    write_out(py, &text)?;
+    Ok(if problems == 0 {
+        Outcome::Success
+    } else {
+        Outcome::Failure
+    })
+}
@@ -0,0 +1,24 @@
+//! `graphbit graph`: export a workflow graph as Mermaid or Graphviz DOT
+
+use pyo3::prelude::*;
+
+use super::{Args, CliError, Outcome, load_workflow, write_out};
+
+pub(super) fn run(py: Python<'_>, args: &Args) -> Result<Outcome, CliError> {
+    let workflow = load_workflow(py, &args.positional[0])?;
+    let graph = match args.option("--format").unwrap_or("mermaid") {
+        "mermaid" => workflow.graph.to_mermaid(),
+        "dot" => workflow.graph.to_dot(),
+        other => {
+            return Err(CliError::Usage(format!(
+                "--format must be 'mermaid' or 'dot', got '{other}'"
+            )));
+        }
+    };
+    match args.option("--output") {
+        Some(output) => std::fs::write(output, graph)
+            .map_err(|e| CliError::Failed(format!("cannot write {output}: {e}")))?,
+        None => write_out(py, &graph)?,
+    }
+    Ok(Outcome::Success)
+}
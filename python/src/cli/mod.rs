@@ -0,0 +1,260 @@
+//! The `graphbit` command line interface
+//!
+//! The console script installed with the Python package calls [`cli_main`]
+//! with its arguments. Commands read a workflow file in the format written by
+//! `Workflow.to_json`, as JSON (`.json`) or YAML (any other extension), and
+//! exit with 0 on success, 1 when the check or command fails and 2 on usage
+//! errors.
+
+mod cost;
+mod doctor;
+mod graph;
+mod run;
+mod validate;
+
+use graphbit_core::workflow::Workflow;
+use pyo3::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const USAGE: &str = "\
+usage: graphbit <command> [options]
+
+commands:
+  validate <workflow> [--check-providers]
+      Check the workflow graph, its expressions and templates; with
+      --check-providers, also health-check every provider its agents call
+  graph <workflow> [--format mermaid|dot] [-o FILE]
+      Export the workflow graph as a Mermaid flowchart or Graphviz DOT
+  cost <workflow> --model-prices FILE [--prompt-tokens N] [--completion-tokens N]
+      Estimate the cost of running every agent node once; the prices file maps
+      model names to [input_cost_per_token, output_cost_per_token]
+  run <workflow> [--input KEY=VALUE]... [--input-file FILE] [--profile NAME]
+                 [--profiles FILE] [-o REPORT]
+                 [--watch [--watch-dir DIR] [--max-runs N]]
+      Run the workflow and print the status of every node; VALUE is read as
+      JSON when it parses, and as a string otherwise. --profile selects an LLM
+      profile from FILE, by default profiles.yaml next to the workflow. -o
+      writes the execution report, as HTML for .html files and JSON otherwise.
+      With --watch, run again whenever the workflow, its inputs, its profiles
+      or DIR change, until interrupted or after --max-runs N runs
+  doctor
+      List the GRAPHBIT_* environment variables configuring providers,
+      embeddings and executors: which were found, which are missing and which
+      are not read at all; fails when a required one is missing or invalid
+
+Workflow files are in the format written by Workflow.to_json, as JSON (.json)
+or YAML (any other extension).
+";
+
+/// Outcome of a command that ran
+enum Outcome {
+    /// The command succeeded
+    Success,
+    /// The command ran, but its check failed
+    Failure,
+}
+
+/// Run the `graphbit` command line with `argv`, the arguments after the
+/// program name, and return the exit code
+#[pyfunction]
+pub(crate) fn cli_main(py: Python<'_>, argv: Vec<String>) -> PyResult<i32> {
+    let mut argv = argv.into_iter();
+    let Some(command) = argv.next() else {
+        write_err(py, USAGE)?;
+        return Ok(2);
+    };
+    let arguments: Vec<String> = argv.collect();
+    let result = match command.as_str() {
+        "-h" | "--help" | "help" => {
+            write_out(py, USAGE)?;
+            return Ok(0);
+        }
+        "validate" => Args::parse(&arguments, &[], &["--check-providers"], 1)
+            .and_then(|args| validate::run(py, &args)),
+        "graph" => Args::parse(&arguments, &["--format", "--output"], &[], 1)
+            .and_then(|args| graph::run(py, &args)),
+        "cost" => Args::parse(
+            &arguments,
+            &["--model-prices", "--prompt-tokens", "--completion-tokens"],
+            &[],
+            1,
+        )
+        .and_then(|args| cost::run(py, &args)),
+        "doctor" => Args::parse(&arguments, &[], &[], 0).and_then(|args| doctor::run(py, &args)),
+        "run" => Args::parse(
+            &arguments,
+            &[
+                "--input",
+                "--input-file",
+                "--profile",
+                "--profiles",
+                "--output",
+                "--watch-dir",
+                "--max-runs",
+            ],
+            &["--watch"],
+            1,
+        )
+        .and_then(|args| run::run(py, &args)),
+        other => Err(CliError::Usage(format!("unknown command '{other}'"))),
+    };
+    match result {
+        Ok(Outcome::Success) => Ok(0),
+        Ok(Outcome::Failure) => Ok(1),
+        Err(CliError::Usage(message)) => {
+            write_err(py, &format!("graphbit: {message}\n\n{USAGE}"))?;
+            Ok(2)
+        }
+        Err(CliError::Failed(message)) => {
+            write_err(py, &format!("graphbit {command}: {message}\n"))?;
+            Ok(1)
+        }
+        Err(CliError::Python(e)) => Err(e),
+    }
+}
+
+/// Why a command did not run
+enum CliError {
+    /// The arguments are invalid
+    Usage(String),
+    /// A file could not be read, parsed or written
+    Failed(String),
+    /// Writing to Python's standard streams failed
+    Python(PyErr),
+}
+
+impl From<PyErr> for CliError {
+    fn from(error: PyErr) -> Self {
+        Self::Python(error)
+    }
+}
+
+/// Parsed arguments of a command: positional arguments, options taking a
+/// value (`--name value` or `--name=value`), possibly repeated, and flags
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Vec<String>>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    /// Parse `args` accepting the given options and flags and exactly
+    /// `positional` positional arguments; `-o` is short for `--output`
+    fn parse(
+        args: &[String],
+        options: &[&str],
+        flags: &[&str],
+        positional: usize,
+    ) -> Result<Self, CliError> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            options: HashMap::new(),
+            flags: HashSet::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let name = if name == "-o" { "--output" } else { name };
+            if flags.contains(&name) && inline_value.is_none() {
+                parsed.flags.insert(name.to_string());
+            } else if options.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| CliError::Usage(format!("{name} needs a value")))?,
+                };
+                parsed
+                    .options
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(value);
+            } else {
+                return Err(CliError::Usage(format!("unknown option '{arg}'")));
+            }
+        }
+        if parsed.positional.len() != positional {
+            return Err(CliError::Usage(format!(
+                "expected {positional} argument(s), got {}",
+                parsed.positional.len()
+            )));
+        }
+        Ok(parsed)
+    }
+
+    /// Value of `--name`, the last one if it is repeated
+    fn option(&self, name: &str) -> Option<&str> {
+        self.values(name).last().map(String::as_str)
+    }
+
+    /// Every value of `--name`, in the order given
+    fn values(&self, name: &str) -> &[String] {
+        self.options.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether the flag `--name` was given
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// Value of `--name` as a number, or `default` when it is not given
+    fn number(&self, name: &str, default: u32) -> Result<u32, CliError> {
+        self.option(name).map_or(Ok(default), |value| {
+            value.parse().map_err(|_| {
+                CliError::Usage(format!("{name} must be a whole number, got '{value}'"))
+            })
+        })
+    }
+}
+
+/// Read a JSON (`.json`) or YAML document
+fn load_document(py: Python<'_>, path: &str) -> Result<serde_json::Value, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Failed(format!("cannot read {path}: {e}")))?;
+    let is_json = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        return serde_json::from_str(&text)
+            .map_err(|e| CliError::Failed(format!("{path} is not valid JSON: {e}")));
+    }
+    let document = py
+        .import("yaml")
+        .and_then(|yaml| yaml.call_method1("safe_load", (text,)))
+        .map_err(|e| CliError::Failed(format!("{path} is not valid YAML: {e}")))?;
+    pythonize::depythonize(&document)
+        .map_err(|e| CliError::Failed(format!("{path} cannot be read as JSON data: {e}")))
+}
+
+/// Read a workflow file written in the format of `Workflow.to_json`
+fn load_workflow(py: Python<'_>, path: &str) -> Result<Workflow, CliError> {
+    let document = load_document(py, path)?;
+    Workflow::from_json(&document.to_string())
+        .map_err(|e| CliError::Failed(format!("{path} is not a valid workflow: {e}")))
+}
+
+/// Write `text` to Python's `sys.stdout`
+fn write_out(py: Python<'_>, text: &str) -> PyResult<()> {
+    write_stream(py, "stdout", text)
+}
+
+/// Write `text` to Python's `sys.stderr`
+fn write_err(py: Python<'_>, text: &str) -> PyResult<()> {
+    write_stream(py, "stderr", text)
+}
+
+fn write_stream(py: Python<'_>, stream: &str, text: &str) -> PyResult<()> {
+    py.import("sys")?
+        .getattr(stream)?
+        .call_method1("write", (text,))?;
+    Ok(())
+}
@@ -0,0 +1,344 @@
+//! `graphbit run`: execute a workflow, and with `--watch` again on every change
+
+use graphbit_core::graph::NodeType;
+use graphbit_core::llm::LlmConfig;
+use graphbit_core::llm::profile::profiles_from_value;
+use graphbit_core::report::ExecutionReport;
+use graphbit_core::types::{Secrets, TagFilter};
+use pyo3::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::{Args, CliError, Outcome, load_document, load_workflow, write_err, write_out};
+use crate::runtime::get_runtime;
+use crate::workflow::executor::{ExecutionConfig, Executor};
+
+/// How often watched files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long watched files must stay unchanged before a run starts, so that a
+/// burst of writes, such as an editor saving or a directory being copied,
+/// starts one run
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Profile files looked up next to the workflow when `--profile` is given
+/// without `--profiles`
+const PROFILE_FILES: [&str; 3] = ["profiles.yaml", "profiles.yml", "profiles.json"];
+
+pub(super) fn run(py: Python<'_>, args: &Args) -> Result<Outcome, CliError> {
+    let options = RunOptions::from_args(args)?;
+    if !args.flag("--watch") {
+        if args.option("--watch-dir").is_some() || args.option("--max-runs").is_some() {
+            return Err(CliError::Usage(
+                "--watch-dir and --max-runs need --watch".to_string(),
+            ));
+        }
+        return execute(py, &options, None);
+    }
+
+    let max_runs = args
+        .option("--max-runs")
+        .map(|_| args.number("--max-runs", 0))
+        .transpose()?;
+    let mut watched = options.files();
+    watched.extend(args.values("--watch-dir").iter().map(PathBuf::from));
+    let mut snapshot = Snapshot::take(&watched);
+    let mut run = 0;
+    loop {
+        run += 1;
+        // A broken edit must not stop the watch: report it and wait for the next one
+        let outcome = match execute(py, &options, Some(run)) {
+            Err(CliError::Failed(message)) => {
+                write_err(py, &format!("graphbit run: {message}\n"))?;
+                Outcome::Failure
+            }
+            result => result?,
+        };
+        if max_runs.is_some_and(|max_runs| run >= max_runs) {
+            return Ok(outcome);
+        }
+        write_out(py, "Watching for changes, press Ctrl+C to stop\n")?;
+        snapshot = snapshot.wait_for_change(py, &watched)?;
+    }
+}
+
+/// What to run, read from the arguments once for every run
+struct RunOptions {
+    workflow: PathBuf,
+    inputs: HashMap<String, serde_json::Value>,
+    input_file: Option<PathBuf>,
+    profile: Option<String>,
+    profiles: Option<PathBuf>,
+    output: Option<PathBuf>,
+}
+
+impl RunOptions {
+    fn from_args(args: &Args) -> Result<Self, CliError> {
+        let workflow = PathBuf::from(&args.positional[0]);
+        let mut inputs = HashMap::new();
+        for input in args.values("--input") {
+            let (name, value) = input.split_once('=').ok_or_else(|| {
+                CliError::Usage(format!("--input must be KEY=VALUE, got '{input}'"))
+            })?;
+            let value = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+            inputs.insert(name.to_string(), value);
+        }
+
+        let profile = args.option("--profile").map(str::to_string);
+        let profiles = match args.option("--profiles") {
+            Some(path) => Some(PathBuf::from(path)),
+            None if profile.is_some() => {
+                let directory = workflow.parent().unwrap_or_else(|| Path::new(""));
+                let found = PROFILE_FILES
+                    .iter()
+                    .map(|name| directory.join(name))
+                    .find(|path| path.is_file());
+                if found.is_none() {
+                    return Err(CliError::Usage(format!(
+                        "--profile needs --profiles FILE or a {} next to the workflow",
+                        PROFILE_FILES[0]
+                    )));
+                }
+                found
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            workflow,
+            inputs,
+            input_file: args.option("--input-file").map(PathBuf::from),
+            profile,
+            profiles,
+            output: args.option("--output").map(PathBuf::from),
+        })
+    }
+
+    /// Files read by every run
+    fn files(&self) -> Vec<PathBuf> {
+        std::iter::once(&self.workflow)
+            .chain(&self.input_file)
+            .chain(&self.profiles)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Run the workflow once, numbered `run` in watch mode, and print the status
+/// of its nodes
+fn execute(py: Python<'_>, options: &RunOptions, run: Option<u32>) -> Result<Outcome, CliError> {
+    let workflow = load_workflow(py, &options.workflow.to_string_lossy())?;
+    let mut inputs: HashMap<String, serde_json::Value> = match &options.input_file {
+        Some(path) => {
+            let path = path.to_string_lossy();
+            serde_json::from_value(load_document(py, &path)?).map_err(|e| {
+                CliError::Failed(format!("{path} must map input names to values: {e}"))
+            })?
+        }
+        None => HashMap::new(),
+    };
+    inputs.extend(options.inputs.clone());
+    let profiles = match &options.profiles {
+        Some(path) => {
+            let path = path.to_string_lossy();
+            profiles_from_value(load_document(py, &path)?)
+                .map_err(|e| CliError::Failed(format!("{path}: {e}")))?
+        }
+        None => HashMap::new(),
+    };
+
+    // Agent nodes take their LLM configuration from their own `llm_config` or
+    // from the selected profile; name the ones that have neither before
+    // anything runs
+    let profile = match &options.profile {
+        Some(name) => Some(profiles.get(name).ok_or_else(|| {
+            let mut available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            CliError::Failed(format!(
+                "unknown profile '{name}', available profiles: {}",
+                available.join(", ")
+            ))
+        })?),
+        None => None,
+    };
+    let mut unconfigured: Vec<&str> = workflow
+        .graph
+        .get_nodes()
+        .values()
+        .filter(|node| matches!(node.node_type, NodeType::Agent { .. }))
+        .filter(|node| {
+            node.resolve_llm_config(None).is_none()
+                && !profile.is_some_and(|profile| profile.resolve(node, None).is_some())
+        })
+        .map(|node| node.name.as_str())
+        .collect();
+    if !unconfigured.is_empty() {
+        unconfigured.sort_unstable();
+        return Err(CliError::Failed(format!(
+            "agent node(s) '{}' have no LLM configuration: give them an llm_config \
+             or select a profile with --profile",
+            unconfigured.join("', '")
+        )));
+    }
+    let llm_config = LlmConfig::Unconfigured {
+        message: "No LLM configuration".to_string(),
+    };
+    let config = ExecutionConfig {
+        profiles,
+        ..ExecutionConfig::default()
+    };
+    let name = workflow.name.clone();
+    let profile = options.profile.clone();
+    let context = py
+        .allow_threads(|| {
+            get_runtime().block_on(Executor::execute_workflow_internal(
+                llm_config,
+                workflow,
+                config,
+                None,
+                inputs,
+                Secrets::default(),
+                profile,
+                TagFilter::default(),
+                false,
+                uuid::Uuid::new_v4(),
+            ))
+        })
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+
+    let report = context.to_report();
+    if let Some(output) = &options.output {
+        report
+            .save(output)
+            .map_err(|e| CliError::Failed(format!("cannot write {}: {e}", output.display())))?;
+    }
+    write_out(py, &status_table(&name, &report, run))?;
+    match failure(&report) {
+        None => Ok(Outcome::Success),
+        Some(message) => {
+            write_err(py, &format!("graphbit run: {message}\n"))?;
+            Ok(Outcome::Failure)
+        }
+    }
+}
+
+/// One line for the run, then one per executed or skipped node with its
+/// status, duration and token usage
+fn status_table(name: &str, report: &ExecutionReport, run: Option<u32>) -> String {
+    let mut text = String::new();
+    if let Some(run) = run {
+        let _ = write!(text, "[run {run}] ");
+    }
+    let _ = writeln!(
+        text,
+        "'{name}' {} in {}",
+        report.status,
+        duration(report.duration_ms.unwrap_or(0))
+    );
+    let width = report
+        .nodes
+        .iter()
+        .map(|node| node.node_name.len())
+        .chain(report.skipped_nodes.iter().map(String::len))
+        .max()
+        .unwrap_or(0);
+    for node in &report.nodes {
+        let _ = write!(
+            text,
+            "  {:width$}  {:9}  {:>7}",
+            node.node_name,
+            node.status,
+            duration(node.duration_ms)
+        );
+        if let Some(usage) = &node.token_usage {
+            let _ = write!(text, "  {} tokens", usage.total_tokens);
+        }
+        text.push('\n');
+    }
+    for node in &report.skipped_nodes {
+        let _ = writeln!(text, "  {node:width$}  skipped");
+    }
+    text
+}
+
+fn duration(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else {
+        format!("{}.{:02}s", ms / 1000, ms % 1000 / 10)
+    }
+}
+
+/// Why the run failed, naming the failing node where there is one
+fn failure(report: &ExecutionReport) -> Option<String> {
+    if report.status == "completed" {
+        return None;
+    }
+    let error = report.error.as_deref().unwrap_or("no error was recorded");
+    let failed = report.nodes.iter().find(|node| node.status == "failed");
+    Some(failed.map_or_else(
+        || format!("workflow {}: {error}", report.status),
+        |node| {
+            format!(
+                "node '{}' failed: {}",
+                node.node_name,
+                node.error.as_deref().unwrap_or(error)
+            )
+        },
+    ))
+}
+
+/// Modification time and size of every file under the watched paths
+#[derive(PartialEq, Eq)]
+struct Snapshot(BTreeMap<PathBuf, (Option<SystemTime>, u64)>);
+
+impl Snapshot {
+    fn take(paths: &[PathBuf]) -> Self {
+        let mut files = BTreeMap::new();
+        let mut pending = paths.to_vec();
+        while let Some(path) = pending.pop() {
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                if let Ok(entries) = std::fs::read_dir(&path) {
+                    pending.extend(entries.flatten().map(|entry| entry.path()));
+                }
+            } else {
+                files.insert(path, (metadata.modified().ok(), metadata.len()));
+            }
+        }
+        Self(files)
+    }
+
+    /// Wait until a watched file is created, changed or removed and then for
+    /// the files to settle, returning their new snapshot. Fails with
+    /// `KeyboardInterrupt` on Ctrl+C.
+    fn wait_for_change(self, py: Python<'_>, paths: &[PathBuf]) -> PyResult<Self> {
+        let mut last = self;
+        loop {
+            sleep(py, POLL_INTERVAL)?;
+            let current = Self::take(paths);
+            if current != last {
+                last = current;
+                break;
+            }
+        }
+        loop {
+            sleep(py, DEBOUNCE)?;
+            let current = Self::take(paths);
+            if current == last {
+                return Ok(current);
+            }
+            last = current;
+        }
+    }
+}
+
+fn sleep(py: Python<'_>, duration: Duration) -> PyResult<()> {
+    py.allow_threads(|| std::thread::sleep(duration));
+    py.check_signals()
+}
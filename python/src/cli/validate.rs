@@ -0,0 +1,113 @@
+//! `graphbit validate`: check a workflow file without running it
+
+use graphbit_core::expression::Expression;
+use graphbit_core::workflow::{Workflow, WorkflowExecutor};
+use pyo3::prelude::*;
+use std::fmt::Write;
+
+use super::{Args, CliError, Outcome, load_workflow, write_out};
+use crate::runtime::get_runtime;
+
+pub(super) fn run(py: Python<'_>, args: &Args) -> Result<Outcome, CliError> {
+    let path = &args.positional[0];
+    let workflow = match load_workflow(py, path) {
+        Ok(workflow) => workflow,
+        Err(CliError::Failed(message)) => {
+            report(py, path, &[message])?;
+            return Ok(Outcome::Failure);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut problems = problems(&workflow);
+    if problems.is_empty() && args.flag("--check-providers") {
+        problems = provider_problems(py, &workflow);
+    }
+    report(py, path, &problems)?;
+    if problems.is_empty() {
+        Ok(Outcome::Success)
+    } else {
+        Ok(Outcome::Failure)
+    }
+}
+
+/// Everything wrong with the workflow: every node and edge expression that
+/// does not parse or node that is misconfigured, and otherwise the first
+/// problem with the graph as a whole, such as a cycle
+fn problems(workflow: &Workflow) -> Vec<String> {
+    let graph = &workflow.graph;
+    let mut problems = Vec::new();
+
+    let mut nodes: Vec<_> = graph.get_nodes().values().collect();
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+    for node in nodes {
+        if let Err(e) = node.validate() {
+            problems.push(format!("node '{}': {e}", node.name));
+        }
+    }
+
+    let name = |id| graph.get_node(id).map_or("?", |node| node.name.as_str());
+    for (from, to, edge) in graph.get_edges() {
+        for (kind, source) in [
+            ("condition", &edge.condition),
+            ("transform", &edge.transform),
+        ] {
+            let Some(source) = source else { continue };
+            if let Err(e) = Expression::parse(source) {
+                problems.push(format!(
+                    "edge '{}' -> '{}': invalid {kind}: {e}",
+                    name(from),
+                    name(to)
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        if let Err(e) = workflow.validate() {
+            problems.push(e.to_string());
+        }
+    }
+    problems
+}
+
+/// The providers and models of the workflow's agent nodes that fail their
+/// health check, as found by a dry run
+fn provider_problems(py: Python<'_>, workflow: &Workflow) -> Vec<String> {
+    let executor = WorkflowExecutor::new();
+    let report = py.allow_threads(|| get_runtime().block_on(executor.dry_run(workflow)));
+    match report {
+        Ok(report) => report
+            .validation_error
+            .into_iter()
+            .chain(
+                report
+                    .providers
+                    .into_iter()
+                    .filter(|health| !health.is_healthy())
+                    .map(|health| {
+                        format!(
+                            "provider {} (model {}): {}",
+                            health.provider,
+                            health.model,
+                            health
+                                .error
+                                .unwrap_or_else(|| "failed its health check".to_string())
+                        )
+                    }),
+            )
+            .collect(),
+        Err(e) => vec![e.to_string()],
+    }
+}
+
+fn report(py: Python<'_>, path: &str, problems: &[String]) -> PyResult<()> {
+    if problems.is_empty() {
+        return write_out(py, &format!("{path}: ok\n"));
+    }
+    let mut text = format!("{path}: {} problem(s)\n", problems.len());
+    for problem in problems {
+        let _ = writeln!(text, "  - {problem}");
+    }
+    write_out(py, &text)
+}
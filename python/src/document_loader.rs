@@ -6,13 +6,18 @@
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use graphbit_core::{
     GraphBitResult,
-    document_loader::{DocumentContent, DocumentLoader, DocumentLoaderConfig},
+    document_loader::{
+        BatchLoadResult, DirectoryLoadResult, DocumentContent, DocumentLoader,
+        DocumentLoaderConfig, DownloadProgressCallback,
+    },
 };
 
-use crate::errors::to_py_error;
+use crate::errors::{invalid_value, to_py_error};
 use crate::runtime::get_runtime;
 
 /// Python wrapper for DocumentLoaderConfig
@@ -20,6 +25,8 @@ use crate::runtime::get_runtime;
 #[derive(Clone)]
 pub struct PyDocumentLoaderConfig {
     pub(crate) inner: DocumentLoaderConfig,
+    /// Python callable behind `inner.download_progress`
+    download_progress: Option<Arc<PyObject>>,
 }
 
 #[pymethods]
@@ -29,29 +36,47 @@ impl PyDocumentLoaderConfig {
     #[pyo3(signature = (
         max_file_size=None,
         default_encoding=None,
-        preserve_formatting=None
+        preserve_formatting=None,
+        cache_dir=None,
+        max_requests_per_host=None,
+        requests_per_second=None,
+        max_retries=None,
+        retry_backoff_ms=None,
+        batch_timeout_ms=None,
+        max_download_size=None,
+        max_download_retries=None,
+        download_progress=None,
+        max_parallel_parses=None,
+        max_parse_bytes=None
     ))]
     fn new(
         max_file_size: Option<usize>,
         default_encoding: Option<String>,
         preserve_formatting: Option<bool>,
+        cache_dir: Option<PathBuf>,
+        max_requests_per_host: Option<usize>,
+        requests_per_second: Option<f64>,
+        max_retries: Option<u32>,
+        retry_backoff_ms: Option<u64>,
+        batch_timeout_ms: Option<u64>,
+        max_download_size: Option<usize>,
+        max_download_retries: Option<u32>,
+        download_progress: Option<PyObject>,
+        max_parallel_parses: Option<usize>,
+        max_parse_bytes: Option<usize>,
     ) -> PyResult<Self> {
         let mut config = DocumentLoaderConfig::default();
 
         if let Some(size) = max_file_size {
             if size == 0 {
-                return Err(pyo3::exceptions::PyValueError::new_err(
-                    "max_file_size must be greater than 0",
-                ));
+                return Err(invalid_value("max_file_size must be greater than 0"));
             }
             config.max_file_size = size;
         }
 
         if let Some(encoding) = default_encoding {
             if encoding.trim().is_empty() {
-                return Err(pyo3::exceptions::PyValueError::new_err(
-                    "default_encoding cannot be empty",
-                ));
+                return Err(invalid_value("default_encoding cannot be empty"));
             }
             config.default_encoding = encoding;
         }
@@ -60,7 +85,34 @@ impl PyDocumentLoaderConfig {
             config.preserve_formatting = preserve;
         }
 
-        Ok(Self { inner: config })
+        config.cache_dir = cache_dir;
+
+        let mut config = Self {
+            inner: config,
+            download_progress: None,
+        };
+        if let Some(limit) = max_requests_per_host {
+            config.set_max_requests_per_host(limit)?;
+        }
+        config.set_requests_per_second(requests_per_second)?;
+        if let Some(retries) = max_retries {
+            config.inner.max_retries = retries;
+        }
+        if let Some(backoff) = retry_backoff_ms {
+            config.inner.retry_backoff_ms = backoff;
+        }
+        config.set_batch_timeout_ms(batch_timeout_ms)?;
+        config.set_max_download_size(max_download_size)?;
+        if let Some(retries) = max_download_retries {
+            config.inner.max_download_retries = retries;
+        }
+        config.set_download_progress(download_progress);
+        config.set_max_parallel_parses(max_parallel_parses)?;
+        if let Some(bytes) = max_parse_bytes {
+            config.set_max_parse_bytes(bytes)?;
+        }
+
+        Ok(config)
     }
 
     /// Get the maximum file size
@@ -73,9 +125,7 @@ impl PyDocumentLoaderConfig {
     #[setter]
     fn set_max_file_size(&mut self, size: usize) -> PyResult<()> {
         if size == 0 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "max_file_size must be greater than 0",
-            ));
+            return Err(invalid_value("max_file_size must be greater than 0"));
         }
         self.inner.max_file_size = size;
         Ok(())
@@ -91,9 +141,7 @@ impl PyDocumentLoaderConfig {
     #[setter]
     fn set_default_encoding(&mut self, encoding: String) -> PyResult<()> {
         if encoding.trim().is_empty() {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "default_encoding cannot be empty",
-            ));
+            return Err(invalid_value("default_encoding cannot be empty"));
         }
         self.inner.default_encoding = encoding;
         Ok(())
@@ -111,6 +159,181 @@ impl PyDocumentLoaderConfig {
         self.inner.preserve_formatting = preserve;
     }
 
+    /// Get the extraction cache directory, `None` when caching is disabled
+    #[getter]
+    fn cache_dir(&self) -> Option<PathBuf> {
+        self.inner.cache_dir.clone()
+    }
+
+    /// Set the extraction cache directory, `None` to disable caching
+    #[setter]
+    fn set_cache_dir(&mut self, cache_dir: Option<PathBuf>) {
+        self.inner.cache_dir = cache_dir;
+    }
+
+    /// Get the maximum concurrent requests to one host
+    #[getter]
+    fn max_requests_per_host(&self) -> usize {
+        self.inner.max_requests_per_host
+    }
+
+    /// Set the maximum concurrent requests to one host
+    #[setter]
+    fn set_max_requests_per_host(&mut self, limit: usize) -> PyResult<()> {
+        if limit == 0 {
+            return Err(invalid_value(
+                "max_requests_per_host must be greater than 0",
+            ));
+        }
+        self.inner.max_requests_per_host = limit;
+        Ok(())
+    }
+
+    /// Get the maximum requests per second, `None` when uncapped
+    #[getter]
+    fn requests_per_second(&self) -> Option<f64> {
+        self.inner.requests_per_second
+    }
+
+    /// Set the maximum requests per second, `None` to remove the cap
+    #[setter]
+    fn set_requests_per_second(&mut self, rate: Option<f64>) -> PyResult<()> {
+        if rate.is_some_and(|rate| !(rate.is_finite() && rate > 0.0)) {
+            return Err(invalid_value(
+                "requests_per_second must be a positive number",
+            ));
+        }
+        self.inner.requests_per_second = rate;
+        Ok(())
+    }
+
+    /// Get the number of retries of a rate-limited or failed URL request
+    #[getter]
+    fn max_retries(&self) -> u32 {
+        self.inner.max_retries
+    }
+
+    /// Set the number of retries of a rate-limited or failed URL request
+    #[setter]
+    fn set_max_retries(&mut self, retries: u32) {
+        self.inner.max_retries = retries;
+    }
+
+    /// Get the delay before the first retry, in milliseconds
+    #[getter]
+    fn retry_backoff_ms(&self) -> u64 {
+        self.inner.retry_backoff_ms
+    }
+
+    /// Set the delay before the first retry, in milliseconds
+    #[setter]
+    fn set_retry_backoff_ms(&mut self, backoff: u64) {
+        self.inner.retry_backoff_ms = backoff;
+    }
+
+    /// Get the time limit of a `load_batch` call, `None` when unlimited
+    #[getter]
+    fn batch_timeout_ms(&self) -> Option<u64> {
+        self.inner.batch_timeout_ms
+    }
+
+    /// Set the time limit of a `load_batch` call, `None` to remove it
+    #[setter]
+    fn set_batch_timeout_ms(&mut self, timeout: Option<u64>) -> PyResult<()> {
+        if timeout == Some(0) {
+            return Err(invalid_value("batch_timeout_ms must be greater than 0"));
+        }
+        self.inner.batch_timeout_ms = timeout;
+        Ok(())
+    }
+
+    /// Get the largest URL download in bytes, `None` when `max_file_size` applies
+    #[getter]
+    fn max_download_size(&self) -> Option<usize> {
+        self.inner.max_download_size
+    }
+
+    /// Set the largest URL download in bytes, `None` to apply `max_file_size`
+    #[setter]
+    fn set_max_download_size(&mut self, size: Option<usize>) -> PyResult<()> {
+        if size == Some(0) {
+            return Err(invalid_value("max_download_size must be greater than 0"));
+        }
+        self.inner.max_download_size = size;
+        Ok(())
+    }
+
+    /// Get the number of times a dropped URL download is resumed or restarted
+    #[getter]
+    fn max_download_retries(&self) -> u32 {
+        self.inner.max_download_retries
+    }
+
+    /// Set the number of times a dropped URL download is resumed or restarted
+    #[setter]
+    fn set_max_download_retries(&mut self, retries: u32) {
+        self.inner.max_download_retries = retries;
+    }
+
+    /// Get the callable called with `(url, downloaded, total)` as URL downloads progress
+    #[getter]
+    fn download_progress(&self, py: Python<'_>) -> Option<PyObject> {
+        self.download_progress
+            .as_ref()
+            .map(|callback| callback.clone_ref(py))
+    }
+
+    /// Set the callable called with `(url, downloaded, total)` as URL downloads
+    /// progress; `total` is `None` when the server did not announce the size.
+    /// Exceptions it raises are reported as unraisable and do not stop the download
+    #[setter]
+    fn set_download_progress(&mut self, callback: Option<PyObject>) {
+        let callback = callback.map(Arc::new);
+        self.inner.download_progress = callback.clone().map(|callback| {
+            DownloadProgressCallback::new(move |progress| {
+                Python::with_gil(|py| {
+                    let args = (progress.url.as_str(), progress.downloaded, progress.total);
+                    if let Err(e) = callback.call1(py, args) {
+                        e.write_unraisable(py, None);
+                    }
+                });
+            })
+        });
+        self.download_progress = callback;
+    }
+
+    /// Get the number of documents parsed at the same time, `None` for the number of CPUs
+    #[getter]
+    fn max_parallel_parses(&self) -> Option<usize> {
+        self.inner.max_parallel_parses
+    }
+
+    /// Set the number of documents parsed at the same time, `None` for the number of CPUs
+    #[setter]
+    fn set_max_parallel_parses(&mut self, limit: Option<usize>) -> PyResult<()> {
+        if limit == Some(0) {
+            return Err(invalid_value("max_parallel_parses must be greater than 0"));
+        }
+        self.inner.max_parallel_parses = limit;
+        Ok(())
+    }
+
+    /// Get the bytes of documents parsed at the same time
+    #[getter]
+    fn max_parse_bytes(&self) -> usize {
+        self.inner.max_parse_bytes
+    }
+
+    /// Set the bytes of documents parsed at the same time; a larger document is parsed on its own
+    #[setter]
+    fn set_max_parse_bytes(&mut self, bytes: usize) -> PyResult<()> {
+        if bytes == 0 {
+            return Err(invalid_value("max_parse_bytes must be greater than 0"));
+        }
+        self.inner.max_parse_bytes = bytes;
+        Ok(())
+    }
+
     /// Get extraction settings as a dictionary
     #[getter]
     fn extraction_settings<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
@@ -238,7 +461,7 @@ impl PyDocumentContent {
 /// Python wrapper for DocumentLoader
 #[pyclass(name = "DocumentLoader")]
 pub struct PyDocumentLoader {
-    loader: DocumentLoader,
+    loader: Arc<DocumentLoader>,
 }
 
 #[pymethods]
@@ -251,37 +474,29 @@ impl PyDocumentLoader {
             Some(cfg) => DocumentLoader::with_config(cfg.inner),
             None => DocumentLoader::new(),
         };
-        Self { loader }
+        Self {
+            loader: Arc::new(loader),
+        }
     }
 
     /// Load and extract content from a document
     ///
     /// Args:
     ///     source_path: Path to the document file or URL
-    ///     document_type: Type of document (txt, pdf, docx, json, csv, xml, html)
+    ///     document_type: Type of document (txt, pdf, docx, json, csv, xml, html).
+    ///         Detected from the file extension when omitted
     ///
     /// Returns:
     ///     DocumentContent: The extracted content and metadata
+    #[pyo3(signature = (source_path, document_type=None))]
     fn load_document(
         &self,
         py: Python<'_>,
         source_path: String,
-        document_type: String,
+        document_type: Option<String>,
     ) -> PyResult<PyDocumentContent> {
         let rt = get_runtime();
-
-        // Validate inputs
-        if source_path.trim().is_empty() {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "source_path cannot be empty",
-            ));
-        }
-
-        if document_type.trim().is_empty() {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "document_type cannot be empty",
-            ));
-        }
+        let document_type = resolve_document_type(&source_path, document_type)?;
 
         let future = async {
             self.loader
@@ -297,6 +512,121 @@ impl PyDocumentLoader {
         }
     }
 
+    /// Load a document asynchronously, returning an awaitable `DocumentContent`
+    #[pyo3(signature = (source_path, document_type=None))]
+    fn load_document_async<'py>(
+        &self,
+        py: Python<'py>,
+        source_path: String,
+        document_type: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let document_type = resolve_document_type(&source_path, document_type)?;
+        let loader = Arc::clone(&self.loader);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            loader
+                .load_document(&source_path, &document_type)
+                .await
+                .map(|content| PyDocumentContent { inner: content })
+                .map_err(to_py_error)
+        })
+    }
+
+    /// Load every supported document in a directory
+    ///
+    /// Documents are parsed concurrently on a thread pool, largest first.
+    ///
+    /// Args:
+    ///     directory: Directory to load from
+    ///     recursive: Whether to descend into subdirectories
+    ///     return_stats: Whether to also return how the files were loaded
+    ///
+    /// Returns:
+    ///     List[DocumentContent]: One entry per supported file, in path order.
+    ///     Files with unsupported extensions are skipped. With `return_stats`,
+    ///     a `(documents, stats)` tuple whose `stats` dict holds `files` (one
+    ///     dict per file with `source`, `document_type`, `file_size`,
+    ///     `started_ms` and `parse_ms`, the latter two `None` for cached
+    ///     documents), `total_bytes`, `total_parse_ms` and `elapsed_ms`
+    #[pyo3(signature = (directory, recursive=false, return_stats=false))]
+    fn load_directory(
+        &self,
+        py: Python<'_>,
+        directory: &str,
+        recursive: bool,
+        return_stats: bool,
+    ) -> PyResult<PyObject> {
+        let rt = get_runtime();
+        let future = async {
+            self.loader
+                .load_directory_with_stats(directory, recursive)
+                .await
+        };
+
+        let loaded = py
+            .allow_threads(|| rt.block_on(future))
+            .map_err(to_py_error)?;
+        directory_result_to_py(py, loaded, return_stats)
+    }
+
+    /// Load a directory asynchronously, returning an awaitable list of
+    /// `DocumentContent`, or `(documents, stats)` with `return_stats`
+    #[pyo3(signature = (directory, recursive=false, return_stats=false))]
+    fn load_directory_async<'py>(
+        &self,
+        py: Python<'py>,
+        directory: String,
+        recursive: bool,
+        return_stats: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let loader = Arc::clone(&self.loader);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let result = loader
+                .load_directory_with_stats(&directory, recursive)
+                .await
+                .map_err(to_py_error)?;
+            Python::with_gil(|py| directory_result_to_py(py, result, return_stats))
+        })
+    }
+
+    /// Load files and URLs, detecting each source's type from its extension
+    ///
+    /// Args:
+    ///     sources: File paths and URLs to load
+    ///
+    /// Returns:
+    ///     List[dict]: One dict per source, in source order, with `source`,
+    ///     `document` (a DocumentContent, or None on failure) and `error`.
+    ///     A failing source does not abort the batch
+    fn load_batch(&self, py: Python<'_>, sources: Vec<String>) -> PyResult<Vec<PyObject>> {
+        let rt = get_runtime();
+        let results = py.allow_threads(|| rt.block_on(self.loader.load_batch(&sources)));
+        results
+            .into_iter()
+            .map(|result| batch_result_to_py(py, result))
+            .collect()
+    }
+
+    /// Load a batch asynchronously, returning an awaitable list of result dicts
+    fn load_batch_async<'py>(
+        &self,
+        py: Python<'py>,
+        sources: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let loader = Arc::clone(&self.loader);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let results = loader.load_batch(&sources).await;
+            Python::with_gil(|py| {
+                results
+                    .into_iter()
+                    .map(|result| batch_result_to_py(py, result))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+        })
+    }
+
     /// Get list of supported document types
     #[staticmethod]
     fn supported_types() -> Vec<String> {
@@ -331,6 +661,59 @@ impl PyDocumentLoader {
     }
 }
 
+/// Dict of one `load_batch` result
+fn batch_result_to_py(py: Python<'_>, result: BatchLoadResult) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("source", result.source)?;
+    dict.set_item(
+        "document",
+        result
+            .document
+            .map(|document| PyDocumentContent { inner: document }),
+    )?;
+    dict.set_item("error", result.error)?;
+    Ok(dict.into_any().unbind())
+}
+
+fn directory_result_to_py(
+    py: Python<'_>,
+    loaded: DirectoryLoadResult,
+    return_stats: bool,
+) -> PyResult<PyObject> {
+    let documents: Vec<PyDocumentContent> = loaded
+        .documents
+        .into_iter()
+        .map(|content| PyDocumentContent { inner: content })
+        .collect();
+    if !return_stats {
+        return Ok(documents.into_pyobject(py)?.into_any().unbind());
+    }
+    let stats = pythonize::pythonize(py, &loaded.stats)?;
+    Ok((documents, stats).into_pyobject(py)?.into_any().unbind())
+}
+
+/// Validate the source path and pick the document type, detecting it from the
+/// file extension when not given
+fn resolve_document_type(source_path: &str, document_type: Option<String>) -> PyResult<String> {
+    if source_path.trim().is_empty() {
+        return Err(invalid_value("source_path cannot be empty"));
+    }
+
+    match document_type {
+        Some(document_type) if document_type.trim().is_empty() => {
+            Err(invalid_value("document_type cannot be empty"))
+        }
+        Some(document_type) => Ok(document_type),
+        None => {
+            graphbit_core::document_loader::detect_document_type(source_path).ok_or_else(|| {
+                invalid_value(format!(
+                    "Cannot detect document type of '{source_path}'; pass document_type explicitly"
+                ))
+            })
+        }
+    }
+}
+
 /// Helper function to convert serde_json::Value to Python object
 fn serde_json_to_py_object(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
     match value {
@@ -384,9 +767,7 @@ fn py_object_to_serde_json(py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<s
         if let Some(n) = serde_json::Number::from_f64(f) {
             Ok(serde_json::Value::Number(n))
         } else {
-            Err(pyo3::exceptions::PyValueError::new_err(
-                "Invalid floating point number",
-            ))
+            Err(invalid_value("Invalid floating point number"))
         }
     } else if let Ok(s) = obj.extract::<String>() {
         Ok(serde_json::Value::String(s))
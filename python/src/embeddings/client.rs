@@ -1,7 +1,8 @@
 //! Embedding client for GraphBit Python bindings
 
 use graphbit_core::embeddings::{
-    EmbeddingBatchRequest, EmbeddingInput, EmbeddingRequest, EmbeddingService,
+    EmbeddingBatchRequest, EmbeddingInput, EmbeddingRequest, EmbeddingService, INPUT_TYPE_PARAM,
+    SimilarityMetric, similarity,
 };
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
@@ -9,7 +10,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use super::config::EmbeddingConfig;
-use crate::errors::to_py_runtime_error;
+use crate::errors::{invalid_value, to_py_error, to_py_runtime_error};
 use crate::runtime::get_runtime;
 
 /// Python client for generating text embeddings using various providers
@@ -30,26 +31,32 @@ impl EmbeddingClient {
     ///
     /// CRITICAL: This method releases the GIL during execution, enabling true parallelism
     /// from Python threads. Multiple threads can call this method simultaneously.
-    fn embed(&self, py: Python<'_>, text: String) -> PyResult<Vec<f32>> {
+    #[pyo3(signature = (text, input_type=None))]
+    fn embed(
+        &self,
+        py: Python<'_>,
+        text: String,
+        input_type: Option<String>,
+    ) -> PyResult<Vec<f32>> {
         // Validate input
         if text.is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Text input cannot be empty",
-            ));
+            return Err(invalid_value("Text input cannot be empty"));
         }
 
         let service = Arc::clone(&self.service);
         let rt = get_runtime();
+        let request = embedding_request(EmbeddingInput::Single(text), input_type);
 
         // CRITICAL FIX: Release GIL during async execution
         // This enables multiple Python threads to execute embed() in parallel
         py.allow_threads(|| {
             rt.block_on(async move {
-                let response = service
-                    .embed_text(&text)
-                    .await
-                    .map_err(to_py_runtime_error)?;
-                Ok(response)
+                let response = service.embed(request).await.map_err(to_py_runtime_error)?;
+                response
+                    .embeddings
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| to_py_runtime_error("No embeddings returned"))
             })
         })
     }
@@ -58,33 +65,37 @@ impl EmbeddingClient {
     ///
     /// CRITICAL: This method releases the GIL during execution, enabling true parallelism
     /// from Python threads. Multiple threads can call this method simultaneously.
-    fn embed_many(&self, py: Python<'_>, texts: Vec<String>) -> PyResult<Vec<Vec<f32>>> {
+    #[pyo3(signature = (texts, input_type=None))]
+    fn embed_many(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        input_type: Option<String>,
+    ) -> PyResult<Vec<Vec<f32>>> {
         // Validate input
         if texts.is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Text list cannot be empty",
-            ));
+            return Err(invalid_value("Text list cannot be empty"));
         }
 
         let service = Arc::clone(&self.service);
         let rt = get_runtime();
+        let request = embedding_request(EmbeddingInput::Multiple(texts), input_type);
 
         // CRITICAL FIX: Release GIL during async execution
         // This enables multiple Python threads to execute embed_many() in parallel
         py.allow_threads(|| {
             rt.block_on(async move {
-                let response = service
-                    .embed_texts(&texts)
-                    .await
-                    .map_err(to_py_runtime_error)?;
-                Ok(response)
+                let response = service.embed(request).await.map_err(to_py_runtime_error)?;
+                Ok(response.embeddings)
             })
         })
     }
 
-    /// Process a batch of embedding requests with lock-free parallel execution
+    /// Process a batch of embedding requests in parallel
+    ///
+    /// Failed requests are retried according to `EmbeddingConfig.retry`.
     ///
-    /// This method exposes GraphBit's lock-free parallel embedding engine to Python,
+    /// This method exposes GraphBit's parallel embedding engine to Python,
     /// enabling 10-50x speedup for batch embedding generation compared to sequential processing.
     ///
     /// # Arguments
@@ -95,7 +106,8 @@ impl EmbeddingClient {
     /// # Returns
     /// Dictionary with:
     /// - `embeddings`: List of embedding lists (Vec<Vec<Vec<f32>>>)
-    /// - `stats`: Batch processing statistics
+    /// - `stats`: Batch processing statistics, including `total_attempts` and
+    ///   `retried_requests`
     /// - `duration_ms`: Total processing time
     ///
     /// # Example
@@ -116,9 +128,7 @@ impl EmbeddingClient {
     ) -> PyResult<Py<PyDict>> {
         // Validate input
         if texts_batch.is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Batch cannot be empty",
-            ));
+            return Err(invalid_value("Batch cannot be empty"));
         }
 
         // Build batch request
@@ -140,8 +150,7 @@ impl EmbeddingClient {
         let service = Arc::clone(&self.service);
         let rt = get_runtime();
 
-        // CRITICAL: Release GIL during lock-free parallel execution
-        // This enables true parallelism with atomic operations for concurrency control
+        // CRITICAL: Release GIL during parallel execution, including retry backoff
         let batch_response = py.allow_threads(|| {
             rt.block_on(async move {
                 service
@@ -187,14 +196,81 @@ impl EmbeddingClient {
         )?;
         stats_dict.set_item("total_embeddings", batch_response.stats.total_embeddings)?;
         stats_dict.set_item("total_tokens", batch_response.stats.total_tokens)?;
+        stats_dict.set_item("total_attempts", batch_response.stats.total_attempts)?;
+        stats_dict.set_item("retried_requests", batch_response.stats.retried_requests)?;
 
         result_dict.set_item("stats", stats_dict)?;
 
         Ok(result_dict.unbind())
     }
 
+    /// Check that the provider is reachable, accepts the credentials and serves
+    /// the model; returns a dict with `reachable`, `authenticated`,
+    /// `model_available`, `latency_ms` and `error`
+    fn health_check(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let service = Arc::clone(&self.service);
+        let health = py
+            .allow_threads(|| get_runtime().block_on(async move { service.health_check().await }));
+        Ok(pythonize::pythonize(py, &health)?.unbind())
+    }
+
     #[staticmethod]
     fn similarity(a: Vec<f32>, b: Vec<f32>) -> PyResult<f32> {
         EmbeddingService::cosine_similarity(&a, &b).map_err(to_py_runtime_error)
     }
+
+    #[staticmethod]
+    fn dot_product(a: Vec<f32>, b: Vec<f32>) -> PyResult<f32> {
+        similarity::dot_product(&a, &b).map_err(to_py_runtime_error)
+    }
+
+    #[staticmethod]
+    fn euclidean_distance(a: Vec<f32>, b: Vec<f32>) -> PyResult<f32> {
+        similarity::euclidean_distance(&a, &b).map_err(to_py_runtime_error)
+    }
+
+    /// The `k` embeddings of `corpus` most similar to `query` under `metric`
+    /// ("cosine", "dot_product" or "euclidean"), as `(index, score)` pairs from
+    /// most to least similar
+    #[staticmethod]
+    #[pyo3(signature = (query, corpus, k, metric="cosine"))]
+    fn top_k(
+        py: Python<'_>,
+        query: Vec<f32>,
+        corpus: Vec<Vec<f32>>,
+        k: usize,
+        metric: &str,
+    ) -> PyResult<Vec<(usize, f32)>> {
+        let metric: SimilarityMetric = metric.parse().map_err(to_py_error)?;
+        py.allow_threads(|| similarity::top_k(&query, &corpus, k, metric))
+            .map_err(to_py_runtime_error)
+    }
+
+    /// Indices of up to `k` of `candidates` picked by maximal marginal
+    /// relevance, in selection order; `lambda_mult` trades relevance to
+    /// `query` (1.0) against diversity (0.0)
+    #[staticmethod]
+    #[pyo3(signature = (query, candidates, lambda_mult=0.5, k=4))]
+    fn mmr(
+        py: Python<'_>,
+        query: Vec<f32>,
+        candidates: Vec<Vec<f32>>,
+        lambda_mult: f32,
+        k: usize,
+    ) -> PyResult<Vec<usize>> {
+        py.allow_threads(|| similarity::mmr(&query, &candidates, lambda_mult, k))
+            .map_err(to_py_runtime_error)
+    }
+}
+
+/// Request embeddings of `input`, as `input_type` ("query" or "document")
+/// when given
+fn embedding_request(input: EmbeddingInput, input_type: Option<String>) -> EmbeddingRequest {
+    EmbeddingRequest {
+        input,
+        user: None,
+        params: input_type
+            .map(|input_type| HashMap::from([(INPUT_TYPE_PARAM.to_string(), input_type.into())]))
+            .unwrap_or_default(),
+    }
 }
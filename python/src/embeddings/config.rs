@@ -1,12 +1,19 @@
 //! Embedding configuration for GraphBit Python bindings
 
-use crate::validation::validate_api_key;
-use graphbit_core::embeddings::{EmbeddingConfig as CoreEmbeddingConfig, EmbeddingProvider};
+use crate::errors::{invalid_value, runtime_error, to_py_error};
+use crate::pickle::{Reduced, from_state, restore_fn, to_state};
+use crate::validation::{request_timeouts, validate_api_key};
+use crate::workflow::RetryPolicy;
+use graphbit_core::embeddings::{
+    EmbeddingConfig as CoreEmbeddingConfig, EmbeddingProvider, INPUT_TYPE_DOCUMENT,
+    INPUT_TYPE_QUERY,
+};
+use graphbit_core::http_client::RequestTimeouts;
 use pyo3::prelude::*;
 use std::collections::HashMap;
 
 /// Configuration for embedding providers and models
-#[pyclass]
+#[pyclass(module = "graphbit")]
 #[derive(Clone)]
 pub struct EmbeddingConfig {
     pub(crate) inner: CoreEmbeddingConfig,
@@ -15,34 +22,46 @@ pub struct EmbeddingConfig {
 #[pymethods]
 impl EmbeddingConfig {
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn openai(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn openai(
+        api_key: String,
+        model: Option<String>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "OpenAI")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreEmbeddingConfig {
                 provider: EmbeddingProvider::OpenAI,
                 api_key,
                 model: model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
-                base_url: None,
+                base_url,
                 timeout_seconds: None,
+                timeouts,
                 max_batch_size: None,
                 extra_params: HashMap::new(),
+                retry: None,
                 python_instance: None,
             },
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, deployment_name, endpoint, model=None, api_version=None))]
+    #[pyo3(signature = (api_key, deployment_name, endpoint, model=None, api_version=None, timeout_ms=None, connect_timeout_ms=None))]
     fn azure(
         api_key: String,
         deployment_name: String,
         endpoint: String,
         model: Option<String>,
         api_version: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
     ) -> PyResult<Self> {
         validate_api_key(&api_key, "Azure")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         let mut extra_params = HashMap::new();
         extra_params.insert(
@@ -62,13 +81,105 @@ impl EmbeddingConfig {
                 model: model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
                 base_url: None,
                 timeout_seconds: None,
+                timeouts,
                 max_batch_size: None,
                 extra_params,
+                retry: None,
                 python_instance: None,
             },
         })
     }
 
+    /// `Voyage AI` embeddings; `input_type` ("query" or "document") is the
+    /// default for requests that don't set one
+    #[staticmethod]
+    #[pyo3(signature = (api_key, model=None, input_type=None, dimensions=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn voyage(
+        api_key: String,
+        model: Option<String>,
+        input_type: Option<String>,
+        dimensions: Option<usize>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
+        validate_api_key(&api_key, "Voyage")?;
+        let config =
+            CoreEmbeddingConfig::voyage(api_key, model.unwrap_or_else(|| "voyage-3.5".to_string()));
+        Self::retrieval(
+            config,
+            input_type,
+            dimensions,
+            base_url,
+            timeout_ms,
+            connect_timeout_ms,
+        )
+    }
+
+    /// `Jina AI` embeddings; `input_type` ("query" or "document") is the
+    /// default for requests that don't set one
+    #[staticmethod]
+    #[pyo3(signature = (api_key, model=None, input_type=None, dimensions=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn jina(
+        api_key: String,
+        model: Option<String>,
+        input_type: Option<String>,
+        dimensions: Option<usize>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
+        validate_api_key(&api_key, "Jina")?;
+        let config = CoreEmbeddingConfig::jina(
+            api_key,
+            model.unwrap_or_else(|| "jina-embeddings-v3".to_string()),
+        );
+        Self::retrieval(
+            config,
+            input_type,
+            dimensions,
+            base_url,
+            timeout_ms,
+            connect_timeout_ms,
+        )
+    }
+
+    /// Local `sentence-transformers` model in `model_dir`, run on the CPU
+    /// with `threads` threads (one per core by default); needs a build with
+    /// the `local-embeddings` feature
+    #[staticmethod]
+    #[pyo3(signature = (model_dir, threads=None, max_batch_size=None))]
+    fn local(
+        model_dir: String,
+        threads: Option<usize>,
+        max_batch_size: Option<usize>,
+    ) -> PyResult<Self> {
+        #[cfg(feature = "local-embeddings")]
+        {
+            if !std::path::Path::new(&model_dir).is_dir() {
+                return Err(invalid_value(format!(
+                    "Local embedding model directory '{model_dir}' does not exist"
+                )));
+            }
+            let mut config = CoreEmbeddingConfig::local(model_dir);
+            if let Some(threads) = threads {
+                if threads == 0 {
+                    return Err(invalid_value("threads must be at least 1"));
+                }
+                config = config.with_threads(threads);
+            }
+            config.max_batch_size = max_batch_size;
+            Ok(Self { inner: config })
+        }
+        #[cfg(not(feature = "local-embeddings"))]
+        {
+            let _ = (model_dir, threads, max_batch_size);
+            Err(runtime_error(
+                "Local embeddings need GraphBit built with the `local-embeddings` feature",
+            ))
+        }
+    }
+
     #[staticmethod]
     #[pyo3(signature = (api_key, model=None))]
     fn huggingface(api_key: String, model: Option<String>) -> PyResult<Self> {
@@ -91,7 +202,7 @@ impl EmbeddingConfig {
 
             // Create instance with API key (token parameter)
             let hf_instance = hf_class.call1((api_key.clone(),)).map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                runtime_error(format!(
                     "Failed to create HuggingfaceEmbeddings instance: {e}"
                 ))
             })?;
@@ -104,8 +215,10 @@ impl EmbeddingConfig {
                         .unwrap_or_else(|| "sentence-transformers/all-MiniLM-L6-v2".to_string()),
                     base_url: None,
                     timeout_seconds: None,
+                    timeouts: RequestTimeouts::default(),
                     max_batch_size: None,
                     extra_params: HashMap::new(),
+                    retry: None,
                     python_instance: Some(std::sync::Arc::new(hf_instance.into())),
                 },
             })
@@ -135,9 +248,7 @@ impl EmbeddingConfig {
 
             // Create instance with API key
             let litellm_instance = litellm_class.call1((api_key.clone(),)).map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!(
-                    "Failed to create LiteLLMEmbeddings instance: {e}"
-                ))
+                runtime_error(format!("Failed to create LiteLLMEmbeddings instance: {e}"))
             })?;
 
             Ok(Self {
@@ -147,11 +258,102 @@ impl EmbeddingConfig {
                     model: model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
                     base_url: None,
                     timeout_seconds: None,
+                    timeouts: RequestTimeouts::default(),
                     max_batch_size: None,
                     extra_params: HashMap::new(),
+                    retry: None,
                     python_instance: Some(std::sync::Arc::new(litellm_instance.into())),
                 },
             })
         })
     }
+
+    /// Configuration from the `GRAPHBIT_EMBEDDING_*` environment variables
+    #[staticmethod]
+    fn from_env() -> PyResult<Self> {
+        CoreEmbeddingConfig::from_env()
+            .map(|inner| Self { inner })
+            .map_err(to_py_error)
+    }
+
+    /// Total request timeout in milliseconds, `None` for the provider default
+    #[getter]
+    const fn timeout_ms(&self) -> Option<u64> {
+        self.inner.timeouts.timeout_ms
+    }
+
+    /// Connection timeout in milliseconds, `None` for the default
+    #[getter]
+    const fn connect_timeout_ms(&self) -> Option<u64> {
+        self.inner.timeouts.connect_timeout_ms
+    }
+
+    /// Retry policy of failed requests in `EmbeddingClient.embed_batch_parallel`;
+    /// `None` sends every request once
+    #[getter]
+    fn retry(&self) -> Option<RetryPolicy> {
+        self.inner.retry.clone().map(|inner| RetryPolicy { inner })
+    }
+
+    #[setter]
+    fn set_retry(&mut self, retry: Option<PyRef<'_, RetryPolicy>>) {
+        self.inner.retry = retry.map(|policy| policy.inner.clone());
+    }
+
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<Reduced<'py, (String, Option<PyObject>)>> {
+        let config = slf.borrow();
+        let python_instance = config
+            .inner
+            .python_instance
+            .as_ref()
+            .map(|instance| instance.clone_ref(slf.py()));
+        Ok((
+            restore_fn(slf.as_any())?,
+            (to_state(&config.inner)?, python_instance),
+        ))
+    }
+
+    /// Rebuild a configuration pickled by `__reduce__`
+    #[staticmethod]
+    #[pyo3(signature = (state, python_instance=None))]
+    fn _from_state(state: &str, python_instance: Option<PyObject>) -> PyResult<Self> {
+        let mut inner: CoreEmbeddingConfig = from_state(state)?;
+        inner.python_instance = python_instance.map(std::sync::Arc::new);
+        Ok(Self { inner })
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+}
+
+impl EmbeddingConfig {
+    /// Apply the options shared by the retrieval providers to `config`
+    fn retrieval(
+        mut config: CoreEmbeddingConfig,
+        input_type: Option<String>,
+        dimensions: Option<usize>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
+        if let Some(input_type) = input_type {
+            if input_type != INPUT_TYPE_QUERY && input_type != INPUT_TYPE_DOCUMENT {
+                return Err(invalid_value(format!(
+                    "input_type must be '{INPUT_TYPE_QUERY}' or '{INPUT_TYPE_DOCUMENT}', got '{input_type}'"
+                )));
+            }
+            config = config.with_input_type(input_type);
+        }
+        if let Some(dimensions) = dimensions {
+            config = config.with_dimensions(dimensions);
+        }
+        if let Some(base_url) = base_url {
+            config = config.with_base_url(base_url);
+        }
+        config.timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
+        Ok(Self { inner: config })
+    }
 }
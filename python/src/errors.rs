@@ -2,11 +2,27 @@
 //!
 //! This module provides comprehensive error handling with proper error context,
 //! structured error types, and integration with logging systems.
+//!
+//! Every error surfaces in Python as a subclass of `graphbit.errors.GraphBitError`
+//! (defined in `python-src/graphbit/errors.py`), carrying structured attributes
+//! such as the provider, HTTP status code or offending field.
 
 use pyo3::prelude::*;
 use std::fmt;
 use tracing::{error, warn};
 
+/// Exception classes of the `graphbit.errors` Python module
+pub(crate) mod py_exceptions {
+    pyo3::import_exception!(graphbit.errors, GraphBitError);
+    pyo3::import_exception!(graphbit.errors, ConfigurationError);
+    pyo3::import_exception!(graphbit.errors, ProviderError);
+    pyo3::import_exception!(graphbit.errors, RateLimitError);
+    pyo3::import_exception!(graphbit.errors, ContentBlockedError);
+    pyo3::import_exception!(graphbit.errors, ValidationError);
+    pyo3::import_exception!(graphbit.errors, WorkflowExecutionError);
+    pyo3::import_exception!(graphbit.errors, TimeoutError);
+}
+
 /// Enhanced error types for better Python integration
 #[derive(Debug, Clone)]
 pub(crate) enum PythonBindingError {
@@ -22,12 +38,23 @@ pub(crate) enum PythonBindingError {
 
     /// Network error with retry information
     Network { message: String, retry_count: u32 },
+    /// LLM or embedding provider error
+    Provider {
+        /// Error message
+        message: String,
+        /// Optional provider name
+        provider: Option<String>,
+        /// HTTP status code returned by the provider, if any
+        status_code: Option<u16>,
+    },
     /// Authentication error with provider context
     Authentication {
         /// Error message
         message: String,
         /// Optional provider name
         provider: Option<String>,
+        /// HTTP status code returned by the provider, if any
+        status_code: Option<u16>,
     },
     /// Validation error with field-specific details
     Validation {
@@ -42,9 +69,27 @@ pub(crate) enum PythonBindingError {
     RateLimit {
         /// Error message
         message: String,
+        /// Optional provider name
+        provider: Option<String>,
         /// Optional retry after seconds
         retry_after: Option<u64>,
     },
+    /// Content the provider refused to produce for safety reasons
+    ContentBlocked {
+        /// Error message
+        message: String,
+        /// Provider name
+        provider: String,
+        /// Harm categories the provider flagged
+        categories: Vec<String>,
+    },
+    /// Workflow execution error
+    WorkflowExecution {
+        /// Error message
+        message: String,
+        /// Name of the node that caused the failure, if known
+        node_name: Option<String>,
+    },
     /// Timeout error with operation details
     Timeout {
         /// Error message
@@ -74,7 +119,18 @@ impl fmt::Display for PythonBindingError {
             } => {
                 write!(f, "Network error (attempt {}): {}", retry_count, message)
             }
-            PythonBindingError::Authentication { message, provider } => {
+            PythonBindingError::Provider {
+                message, provider, ..
+            } => {
+                if let Some(provider) = provider {
+                    write!(f, "Provider error with {}: {}", provider, message)
+                } else {
+                    write!(f, "Provider error: {}", message)
+                }
+            }
+            PythonBindingError::Authentication {
+                message, provider, ..
+            } => {
                 if let Some(provider) = provider {
                     write!(f, "Authentication error with {}: {}", provider, message)
                 } else {
@@ -99,6 +155,7 @@ impl fmt::Display for PythonBindingError {
             PythonBindingError::RateLimit {
                 message,
                 retry_after,
+                ..
             } => {
                 if let Some(retry_after) = retry_after {
                     write!(
@@ -110,6 +167,14 @@ impl fmt::Display for PythonBindingError {
                     write!(f, "Rate limit exceeded: {}", message)
                 }
             }
+            PythonBindingError::ContentBlocked {
+                message, provider, ..
+            } => {
+                write!(f, "Content blocked by {}: {}", provider, message)
+            }
+            PythonBindingError::WorkflowExecution { message, .. } => {
+                write!(f, "Workflow execution error: {}", message)
+            }
             PythonBindingError::Timeout {
                 message,
                 operation,
@@ -132,60 +197,119 @@ pub(crate) fn to_py_error(error: graphbit_core::errors::GraphBitError) -> PyErr
     // Log the error for debugging
     error!("GraphBit error in Python binding: {}", error);
 
+    let status_code = error.http_status();
     let python_error = match error {
-        GraphBitError::Network { message, .. } => PythonBindingError::Network {
-            message: message.clone(),
+        GraphBitError::Network { message } => PythonBindingError::Network {
+            message,
             retry_count: 1,
         },
-        GraphBitError::Authentication { message, .. } => PythonBindingError::Authentication {
-            message: message.clone(),
+        GraphBitError::LlmProvider { provider, message } => PythonBindingError::Provider {
+            message,
+            provider: Some(provider),
+            status_code,
+        },
+        GraphBitError::Llm { message } => PythonBindingError::Provider {
+            message,
             provider: None,
+            status_code,
+        },
+        GraphBitError::Authentication { provider, message } => PythonBindingError::Authentication {
+            message,
+            provider: Some(provider),
+            status_code,
         },
-        GraphBitError::RateLimit { .. } => PythonBindingError::RateLimit {
-            message: "Rate limit exceeded".to_string(),
-            retry_after: None,
+        GraphBitError::RateLimit {
+            provider,
+            retry_after_seconds,
+        } => PythonBindingError::RateLimit {
+            message: format!("{provider} rejected the request with HTTP 429"),
+            provider: Some(provider),
+            retry_after: Some(retry_after_seconds),
         },
-        GraphBitError::Validation { message, .. } => PythonBindingError::Validation {
-            message: message.clone(),
-            field: "unknown".to_string(),
+        GraphBitError::Timeout { .. } => {
+            return py_exceptions::TimeoutError::new_err(error.to_string());
+        }
+        GraphBitError::ContentBlocked {
+            provider,
+            reason,
+            categories,
+        } => PythonBindingError::ContentBlocked {
+            message: reason,
+            provider,
+            categories,
+        },
+        GraphBitError::Validation { field, message } => PythonBindingError::Validation {
+            message,
+            field,
             value: None,
         },
-        GraphBitError::Configuration { message, .. } => PythonBindingError::Configuration {
-            message: message.clone(),
+        GraphBitError::Configuration { message } => PythonBindingError::Configuration {
+            message,
             field: None,
         },
+        GraphBitError::WorkflowExecution { message } => PythonBindingError::WorkflowExecution {
+            node_name: node_name_from_message(&message),
+            message,
+        },
         _ => PythonBindingError::Core(error.to_string()),
     };
 
     python_error_to_py_err(python_error)
 }
 
-/// Convert Python binding errors to PyErr with appropriate exception types
+/// Convert Python binding errors to PyErr with the matching `graphbit.errors` exception
 pub(crate) fn python_error_to_py_err(error: PythonBindingError) -> PyErr {
-    match &error {
-        PythonBindingError::Network { .. } => {
-            PyErr::new::<pyo3::exceptions::PyConnectionError, _>(error.to_string())
-        }
-        PythonBindingError::Authentication { .. } => {
-            PyErr::new::<pyo3::exceptions::PyPermissionError, _>(error.to_string())
+    let message = error.to_string();
+    match error {
+        PythonBindingError::Core(_) => py_exceptions::GraphBitError::new_err(message),
+        PythonBindingError::Configuration { .. } => {
+            py_exceptions::ConfigurationError::new_err(message)
         }
-        PythonBindingError::Validation { .. } => {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(error.to_string())
+        PythonBindingError::Network { .. } => {
+            py_exceptions::ProviderError::new_err((message, None::<String>, None::<u16>))
         }
-        PythonBindingError::Configuration { .. } => {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(error.to_string())
+        PythonBindingError::Provider {
+            provider,
+            status_code,
+            ..
         }
-        PythonBindingError::RateLimit { .. } => {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(error.to_string())
+        | PythonBindingError::Authentication {
+            provider,
+            status_code,
+            ..
+        } => py_exceptions::ProviderError::new_err((message, provider, status_code)),
+        PythonBindingError::RateLimit {
+            provider,
+            retry_after,
+            ..
+        } => py_exceptions::RateLimitError::new_err((message, provider, 429, retry_after)),
+        PythonBindingError::ContentBlocked {
+            provider,
+            categories,
+            ..
+        } => py_exceptions::ContentBlockedError::new_err((
+            message,
+            provider,
+            None::<u16>,
+            categories,
+        )),
+        PythonBindingError::Validation { field, .. } => {
+            py_exceptions::ValidationError::new_err((message, field))
         }
-        PythonBindingError::Timeout { .. } => {
-            PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(error.to_string())
+        PythonBindingError::WorkflowExecution { node_name, .. } => {
+            py_exceptions::WorkflowExecutionError::new_err((message, node_name))
         }
-
-        _ => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(error.to_string()),
+        PythonBindingError::Timeout { .. } => py_exceptions::TimeoutError::new_err(message),
     }
 }
 
+/// Name of the node a core error message refers to (`Node '<name>' ...`), if any
+fn node_name_from_message(message: &str) -> Option<String> {
+    let (_, rest) = message.split_once("Node '")?;
+    let (name, _) = rest.split_once('\'')?;
+    Some(name.to_string())
+}
+
 /// Enhanced utility function to convert any error to PyErr with context
 pub(crate) fn to_py_runtime_error<E: std::fmt::Display>(error: E) -> PyErr {
     let error_msg = error.to_string();
@@ -196,17 +320,17 @@ pub(crate) fn to_py_runtime_error<E: std::fmt::Display>(error: E) -> PyErr {
 
     // Check for common error patterns and map to appropriate exceptions
     if error_msg.contains("timeout") || error_msg.contains("deadline") {
-        PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(error_msg)
-    } else if error_msg.contains("permission") || error_msg.contains("unauthorized") {
-        PyErr::new::<pyo3::exceptions::PyPermissionError, _>(error_msg)
-    } else if error_msg.contains("not found") || error_msg.contains("missing") {
-        PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(error_msg)
+        py_exceptions::TimeoutError::new_err(error_msg)
+    } else if error_msg.contains("permission")
+        || error_msg.contains("unauthorized")
+        || error_msg.contains("connection")
+        || error_msg.contains("network")
+    {
+        py_exceptions::ProviderError::new_err((error_msg, None::<String>, None::<u16>))
     } else if error_msg.contains("invalid") || error_msg.contains("malformed") {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(error_msg)
-    } else if error_msg.contains("connection") || error_msg.contains("network") {
-        PyErr::new::<pyo3::exceptions::PyConnectionError, _>(error_msg)
+        invalid_value(error_msg)
     } else {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(error_msg)
+        runtime_error(error_msg)
     }
 }
 
@@ -220,6 +344,16 @@ pub(crate) fn validation_error(field: &str, value: Option<&str>, message: &str)
     python_error_to_py_err(error)
 }
 
+/// Create a validation error for an invalid argument that is not tied to a named field
+pub(crate) fn invalid_value(message: impl Into<String>) -> PyErr {
+    py_exceptions::ValidationError::new_err((message.into(), None::<String>))
+}
+
+/// Create a generic GraphBit error for failures without a more specific category
+pub(crate) fn runtime_error(message: impl Into<String>) -> PyErr {
+    py_exceptions::GraphBitError::new_err(message.into())
+}
+
 /// Create timeout error with operation context
 pub(crate) fn timeout_error(operation: &str, duration_ms: u64, message: &str) -> PyErr {
     let error = PythonBindingError::Timeout {
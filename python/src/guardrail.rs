@@ -1,8 +1,8 @@
 //! GuardRail policy config exposed to Python as `GuardRailPolicyConfig`.
 //! Used with `Executor.execute(workflow, policy=...)` for PII masking/mapping.
 
+use crate::errors::invalid_value;
 use graphbit_core::GuardRailConfig;
-use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use std::sync::Arc;
 
@@ -25,7 +25,7 @@ impl GuardRailPolicyConfig {
     #[staticmethod]
     pub fn from_json(json_str: &str) -> PyResult<Self> {
         let config = GuardRailConfig::new(json_str)
-            .map_err(|e| PyValueError::new_err(format!("GuardRail config error: {}", e)))?;
+            .map_err(|e| invalid_value(format!("GuardRail config error: {}", e)))?;
         Ok(Self {
             inner: Arc::new(config),
         })
@@ -38,7 +38,7 @@ impl GuardRailPolicyConfig {
     #[staticmethod]
     pub fn from_file(path: &str) -> PyResult<Self> {
         let config = GuardRailConfig::from_file(std::path::Path::new(path))
-            .map_err(|e| PyValueError::new_err(format!("GuardRail config error: {}", e)))?;
+            .map_err(|e| invalid_value(format!("GuardRail config error: {}", e)))?;
         Ok(Self {
             inner: Arc::new(config),
         })
@@ -51,7 +51,7 @@ impl GuardRailPolicyConfig {
     #[staticmethod]
     pub fn from_url(url: &str) -> PyResult<Self> {
         let config = GuardRailConfig::from_url(url)
-            .map_err(|e| PyValueError::new_err(format!("GuardRail config error: {}", e)))?;
+            .map_err(|e| invalid_value(format!("GuardRail config error: {}", e)))?;
         Ok(Self {
             inner: Arc::new(config),
         })
@@ -23,19 +23,23 @@
 //! client = graphbit.LlmClient(config)
 //! ```
 
-use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
+use std::path::PathBuf;
 use std::sync::Once;
 use tracing::{error, info, warn};
+use crate::errors::invalid_value;
 
 // Module declarations
+mod cli;
 mod document_loader;
 mod embeddings;
 mod errors;
 mod guardrail;
 mod llm;
+mod logging;
 mod memory;
+mod pickle;
 mod runtime;
 mod text_splitter;
 mod tools;
@@ -46,15 +50,22 @@ mod workflow;
 pub use document_loader::{PyDocumentContent, PyDocumentLoader, PyDocumentLoaderConfig};
 pub use embeddings::{EmbeddingClient, EmbeddingConfig};
 pub use guardrail::GuardRailPolicyConfig;
-pub use llm::{LlmClient, LlmConfig, PyFinishReason, PyLlmResponse, PyLlmToolCall, PyLlmUsage};
+pub use llm::{
+    LlmClient, LlmConfig, PyFinishReason, PyLlmMiddleware, PyLlmResponse, PyLlmToolCall, PyLlmUsage,
+};
 pub use memory::{MemoryClient, PyMemory, PyMemoryConfig, PyMemoryHistory, PyScoredMemory};
 pub use text_splitter::{
     CharacterSplitter, RecursiveSplitter, SentenceSplitter, TextChunk, TextSplitterConfig,
     TokenSplitter,
 };
-pub use tools::{ToolDecorator, ToolExecutor, ToolRegistry, ToolResult};
+pub use tools::{
+    BuiltinTool, BuiltinTools, ToolContext, ToolDecorator, ToolExecutor, ToolRegistry, ToolResult,
+};
+pub use validation::PyValidationResult;
 pub use workflow::{
-    Executor, Node, Workflow, WorkflowContext, WorkflowResult, WorkflowStreamIterator,
+    Agent, AgentBuilder, Attachment, ExecutionHandle, Executor, Node, ResultComparison,
+    RetryPolicy, TemplateRegistry, Workflow, WorkflowContext, WorkflowResult,
+    WorkflowStreamIterator, WorkflowTemplate,
 };
 
 /// Global initialization flag to ensure init is called only once
@@ -68,48 +79,54 @@ static INIT: Once = Once::new();
 /// - Runtime configuration
 /// - Core library initialization
 /// - Resource management
+///
+/// Logs go to stderr, or are appended to `log_file`, as text or as one JSON
+/// object per line. Calling `init` again only updates the log level; use
+/// `set_log_level` to change it at any time.
+///
+/// Outbound HTTP honors `HTTPS_PROXY`/`NO_PROXY` by default; `proxy` and
+/// `no_proxy` override them, and `ca_bundle` adds the root certificates of a
+/// PEM file. These apply to clients created after the call.
 #[pyfunction]
-#[pyo3(signature = (log_level=None, enable_tracing=None, debug=None))]
+#[pyo3(signature = (log_level=None, enable_tracing=None, debug=None, log_format="text", log_file=None, proxy=None, no_proxy=None, ca_bundle=None))]
+#[allow(clippy::too_many_arguments)]
 fn init(
     log_level: Option<String>,
     enable_tracing: Option<bool>,
     debug: Option<bool>,
+    log_format: &str,
+    log_file: Option<&str>,
+    proxy: Option<String>,
+    no_proxy: Option<String>,
+    ca_bundle: Option<PathBuf>,
 ) -> PyResult<()> {
+    let level = logging::parse_level(log_level.as_deref().unwrap_or("warn"))?;
+    let format = logging::LogFormat::parse(log_format)?;
+
+    if proxy.is_some() || no_proxy.is_some() || ca_bundle.is_some() {
+        let factory = graphbit_core::HttpClientFactory::global();
+        let mut settings = factory.settings();
+        settings.proxy = proxy.or(settings.proxy);
+        settings.no_proxy = no_proxy.or(settings.no_proxy);
+        settings.ca_bundle = ca_bundle.or(settings.ca_bundle);
+        settings.validate().map_err(errors::to_py_error)?;
+        factory.set_settings(settings);
+    }
+
+    // Initialize logging if tracing is enabled
+    // Default to false for tracing to reduce debug output
+    let enable_tracing = enable_tracing.or(debug).unwrap_or(false);
+    if enable_tracing {
+        logging::init_tracing(level, format, log_file)?;
+        info!(
+            "GraphBit Python bindings - tracing initialized with level: {}",
+            level
+        );
+    }
+
     let mut init_result = Ok(());
 
     INIT.call_once(|| {
-        // Initialize logging if tracing is enabled
-        // Default to false for tracing to reduce debug output
-        let enable_tracing = enable_tracing.or(debug).unwrap_or(false);
-        if enable_tracing {
-            let log_level = log_level.unwrap_or_else(|| "warn".to_string());
-
-            // Initialize tracing subscriber for production logging
-            let subscriber = tracing_subscriber::FmtSubscriber::builder()
-                .with_max_level(match log_level.as_str() {
-                    "trace" => tracing::Level::TRACE,
-                    "debug" => tracing::Level::DEBUG,
-                    "info" => tracing::Level::INFO,
-                    "warn" => tracing::Level::WARN,
-                    "error" => tracing::Level::ERROR,
-                    _ => tracing::Level::WARN,
-                })
-                .with_thread_ids(false) // Disable thread IDs for cleaner output
-                .with_thread_names(false) // Disable thread names for cleaner output
-                .with_file(false) // Disable file info for performance
-                .with_line_number(false) // Disable line numbers for performance
-                .finish();
-
-            if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
-                eprintln!("Warning: Failed to set tracing subscriber: {}", e);
-            } else {
-                info!(
-                    "GraphBit Python bindings - tracing initialized with level: {}",
-                    log_level
-                );
-            }
-        }
-
         // Initialize the core library
         match graphbit_core::init() {
             Ok(_) => {
@@ -133,6 +150,14 @@ fn init(
     init_result
 }
 
+/// Change the log level of the tracing set up by `init(enable_tracing=True)`
+///
+/// Takes effect immediately for all threads, including running workflows.
+#[pyfunction]
+fn set_log_level(level: &str) -> PyResult<()> {
+    logging::set_level(logging::parse_level(level)?)
+}
+
 /// Get the current version of GraphBit
 ///
 /// Returns the version string of the GraphBit core library.
@@ -249,19 +274,23 @@ fn health_check(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
 
 /// Configure the global runtime with custom settings
 ///
-/// This is an advanced function that allows customization of the async runtime.
-/// It should be called before `init()` if custom configuration is needed.
+/// The runtime starts on `init()` or on first use, e.g. the first workflow
+/// execution, with these settings; settings left out keep their defaults.
+/// Raises `GraphBitError` once the runtime has started. `blocking_threads` is
+/// an alias of `max_blocking_threads`.
 #[pyfunction]
-#[pyo3(signature = (worker_threads=None, max_blocking_threads=None, thread_stack_size_mb=None))]
+#[pyo3(signature = (worker_threads=None, max_blocking_threads=None, thread_stack_size_mb=None, blocking_threads=None, thread_name_prefix=None))]
 fn configure_runtime(
     worker_threads: Option<i32>,
     max_blocking_threads: Option<i32>,
     thread_stack_size_mb: Option<i32>,
+    blocking_threads: Option<i32>,
+    thread_name_prefix: Option<String>,
 ) -> PyResult<()> {
     // Validate and convert worker_threads
     let validated_worker_threads = if let Some(threads) = worker_threads {
         if threads <= 0 {
-            return Err(PyValueError::new_err(format!(
+            return Err(invalid_value(format!(
                 "worker_threads must be positive, got: {}",
                 threads
             )));
@@ -272,11 +301,21 @@ fn configure_runtime(
     };
 
     // Validate and convert max_blocking_threads
-    let validated_max_blocking_threads = if let Some(threads) = max_blocking_threads {
+    if max_blocking_threads.is_some() && blocking_threads.is_some() {
+        return Err(invalid_value(
+            "Pass either blocking_threads or max_blocking_threads, not both",
+        ));
+    }
+    let (blocking_name, blocking) = if blocking_threads.is_some() {
+        ("blocking_threads", blocking_threads)
+    } else {
+        ("max_blocking_threads", max_blocking_threads)
+    };
+    let validated_max_blocking_threads = if let Some(threads) = blocking {
         if threads <= 0 {
-            return Err(PyValueError::new_err(format!(
-                "max_blocking_threads must be positive, got: {}",
-                threads
+            return Err(invalid_value(format!(
+                "{} must be positive, got: {}",
+                blocking_name, threads
             )));
         }
         Some(threads as usize)
@@ -287,7 +326,7 @@ fn configure_runtime(
     // Validate and convert thread_stack_size_mb
     let validated_thread_stack_size = if let Some(mb) = thread_stack_size_mb {
         if mb <= 0 {
-            return Err(PyValueError::new_err(format!(
+            return Err(invalid_value(format!(
                 "thread_stack_size_mb must be positive, got: {}",
                 mb
             )));
@@ -297,21 +336,127 @@ fn configure_runtime(
         None
     };
 
+    if let Some(prefix) = &thread_name_prefix {
+        if prefix.trim().is_empty() || prefix.contains('\0') {
+            return Err(invalid_value(format!(
+                "thread_name_prefix must be a non-empty name, got: {:?}",
+                prefix
+            )));
+        }
+    }
+
+    let defaults = runtime::RuntimeConfig::default();
     let config = runtime::RuntimeConfig {
-        worker_threads: validated_worker_threads,
-        thread_stack_size: validated_thread_stack_size,
-        enable_blocking_pool: true,
-        max_blocking_threads: validated_max_blocking_threads,
-        thread_keep_alive: Some(std::time::Duration::from_secs(10)),
-        thread_name_prefix: "graphbit-py".to_string(),
+        worker_threads: validated_worker_threads.or(defaults.worker_threads),
+        thread_stack_size: validated_thread_stack_size.or(defaults.thread_stack_size),
+        max_blocking_threads: validated_max_blocking_threads.or(defaults.max_blocking_threads),
+        thread_name_prefix: thread_name_prefix.unwrap_or(defaults.thread_name_prefix),
+        ..defaults
     };
 
-    runtime::init_runtime_with_config(config).map_err(errors::to_py_runtime_error)?;
+    runtime::set_runtime_config(config).map_err(|_| {
+        errors::runtime_error(
+            "The GraphBit runtime has already started in this process, so its configuration \
+             can no longer change. Call configure_runtime() before init() and before running \
+             any workflow or client call, e.g. right after importing graphbit.",
+        )
+    })?;
 
     info!("Runtime configured with custom settings");
     Ok(())
 }
 
+/// Describe the global runtime, for diagnostics
+///
+/// Returns whether the runtime has started, the process it started in, its
+/// uptime, thread settings, and the number of forks after which this process
+/// started a new runtime. Before the runtime starts, the thread settings are
+/// the ones it will start with.
+#[pyfunction]
+fn runtime_info(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new(py);
+    let config = runtime::runtime_config();
+    let stats = runtime::get_runtime_stats();
+
+    dict.set_item("started", stats.is_some())?;
+    dict.set_item("pid", stats.as_ref().map(|stats| stats.pid))?;
+    dict.set_item(
+        "uptime_seconds",
+        stats.as_ref().map(|stats| stats.uptime.as_secs_f64()),
+    )?;
+    dict.set_item(
+        "worker_threads",
+        stats.as_ref().map_or_else(
+            || config.worker_threads.unwrap_or_else(num_cpus::get),
+            |stats| stats.worker_threads,
+        ),
+    )?;
+    dict.set_item("blocking_threads", config.max_blocking_threads)?;
+    dict.set_item("thread_name_prefix", &config.thread_name_prefix)?;
+    dict.set_item("thread_stack_size", config.thread_stack_size)?;
+    dict.set_item("fork_restarts", runtime::fork_restarts())?;
+    Ok(dict)
+}
+
+/// Record executor metrics in memory
+///
+/// Installs the built-in recorder of node executions, LLM calls, concurrency
+/// permits, retries and circuit breaker transitions. Calling it again has no
+/// effect.
+#[pyfunction]
+fn enable_metrics() -> PyResult<()> {
+    graphbit_core::telemetry::install().map_err(errors::to_py_error)?;
+    Ok(())
+}
+
+/// Executor metrics in the Prometheus text format, empty until
+/// `enable_metrics` is called
+#[pyfunction]
+fn render_metrics() -> String {
+    graphbit_core::telemetry::installed()
+        .map(|recorder| recorder.render_prometheus())
+        .unwrap_or_default()
+}
+
+/// Push the executor metrics to `url`, e.g. the job URL of a Prometheus push
+/// gateway
+#[pyfunction]
+fn push_metrics(py: Python<'_>, url: &str) -> PyResult<()> {
+    let recorder = graphbit_core::telemetry::installed()
+        .ok_or_else(|| invalid_value("Metrics are not enabled; call enable_metrics() first"))?;
+    py.allow_threads(|| {
+        runtime::get_runtime().block_on(graphbit_core::telemetry::push(url, &recorder))
+    })
+    .map_err(errors::to_py_error)
+}
+
+/// Recover a JSON value from model output: a Markdown code fence is stripped,
+/// the value is taken out of surrounding prose, trailing commas are removed and
+/// an unterminated string and open brackets are closed.
+///
+/// Returns the value, or `(value, repairs)` with the names of the repairs made
+/// when `return_repairs` is true. Raises `ValueError` when no value is found.
+#[pyfunction]
+#[pyo3(signature = (text, return_repairs=false))]
+fn repair_json(py: Python<'_>, text: &str, return_repairs: bool) -> PyResult<PyObject> {
+    let repaired = graphbit_core::json_repair::repair_json(text)
+        .ok_or_else(|| invalid_value("No JSON value found in text"))?;
+    let value = pythonize::pythonize(py, &repaired.value)?.unbind();
+    if !return_repairs {
+        return Ok(value);
+    }
+    let repairs: Vec<&str> = repaired.repairs.iter().map(|r| r.as_str()).collect();
+    Ok((value, repairs).into_pyobject(py)?.into_any().unbind())
+}
+
+/// Forget the key an `api_key` setting resolved to, e.g. after rotating it:
+/// a `keyring:` entry is read again by the next provider created instead of
+/// being reused, and the key is no longer redacted unless resolved again
+#[pyfunction]
+fn forget_api_key(api_key: &str) {
+    graphbit_core::secret_ref::forget_api_key(api_key);
+}
+
 /// Gracefully shutdown the library (for testing and cleanup)
 ///
 /// This function cleans up resources and shuts down background threads.
@@ -371,13 +516,29 @@ fn graphbit(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Users should call graphbit.init() explicitly to control tracing.
     // If they don't call init(), we'll lazily initialize on first use.
 
+    // PythonCode nodes run in the interpreter that imported GraphBit
+    let executable: String = m.py().import("sys")?.getattr("executable")?.extract()?;
+    if !executable.is_empty() {
+        graphbit_core::python_code::set_python_executable(executable);
+    }
+
     // Core functions
     m.add_function(wrap_pyfunction!(init, m)?)?;
+    m.add_function(wrap_pyfunction!(set_log_level, m)?)?;
     m.add_function(wrap_pyfunction!(version, m)?)?;
     m.add_function(wrap_pyfunction!(get_system_info, m)?)?;
     m.add_function(wrap_pyfunction!(health_check, m)?)?;
     m.add_function(wrap_pyfunction!(configure_runtime, m)?)?;
+    m.add_function(wrap_pyfunction!(runtime_info, m)?)?;
     m.add_function(wrap_pyfunction!(shutdown, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(render_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(push_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(repair_json, m)?)?;
+    m.add_function(wrap_pyfunction!(forget_api_key, m)?)?;
+
+    // Command line interface, run by the `graphbit` console script
+    m.add_function(wrap_pyfunction!(cli::cli_main, m)?)?;
 
     // Document loader classes
     m.add_class::<PyDocumentLoaderConfig>()?;
@@ -387,6 +548,7 @@ fn graphbit(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // LLM classes
     m.add_class::<LlmConfig>()?;
     m.add_class::<LlmClient>()?;
+    m.add_class::<PyLlmMiddleware>()?;
     m.add_class::<PyLlmUsage>()?;
     m.add_class::<PyFinishReason>()?;
     m.add_class::<PyLlmToolCall>()?;
@@ -396,12 +558,20 @@ fn graphbit(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<GuardRailPolicyConfig>()?;
 
     // Workflow classes
+    m.add_class::<Agent>()?;
+    m.add_class::<AgentBuilder>()?;
     m.add_class::<Node>()?;
+    m.add_class::<RetryPolicy>()?;
     m.add_class::<Workflow>()?;
+    m.add_class::<WorkflowTemplate>()?;
+    m.add_class::<TemplateRegistry>()?;
     m.add_class::<WorkflowContext>()?;
     m.add_class::<WorkflowResult>()?;
+    m.add_class::<ResultComparison>()?;
+    m.add_class::<Attachment>()?;
     m.add_class::<WorkflowStreamIterator>()?;
     m.add_class::<Executor>()?;
+    m.add_class::<ExecutionHandle>()?;
 
     // Embedding classes
     m.add_class::<EmbeddingConfig>()?;
@@ -423,13 +593,20 @@ fn graphbit(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<RecursiveSplitter>()?;
     m.add_class::<text_splitter::splitter::TextSplitter>()?;
 
+    // Validation
+    m.add_class::<PyValidationResult>()?;
+    m.add_function(wrap_pyfunction!(validation::validate_json, m)?)?;
+
     // Tool system classes
     m.add_class::<ToolResult>()?;
     m.add_class::<ToolRegistry>()?;
+    m.add_class::<ToolContext>()?;
     m.add_class::<ToolDecorator>()?;
     m.add_class::<ToolExecutor>()?;
     m.add_class::<tools::executor::ExecutorConfig>()?;
     m.add_class::<tools::result::ToolResultCollection>()?;
+    m.add_class::<BuiltinTools>()?;
+    m.add_class::<BuiltinTool>()?;
 
     // Tool functions
     m.add_function(wrap_pyfunction!(tools::decorator::tool, m)?)?;
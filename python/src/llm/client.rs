@@ -10,7 +10,9 @@
 
 use futures::{Stream, StreamExt};
 use graphbit_core::errors::GraphBitResult;
-use graphbit_core::llm::{LlmMessage, LlmProviderTrait, LlmRequest};
+use graphbit_core::llm::{
+    LlmMessage, LlmMiddlewareStack, LlmProvider, LlmProviderTrait, LlmRequest, RerankConfig,
+};
 use pyo3::prelude::*;
 use pyo3::types::{PyAny, PyDict};
 use std::pin::Pin;
@@ -21,8 +23,9 @@ use tokio::time::timeout;
 use tracing::{debug, info, instrument, warn};
 
 use super::config::LlmConfig;
+use super::middleware::PyLlmMiddleware;
 use super::response::PyLlmResponse;
-use crate::errors::{timeout_error, to_py_error, validation_error};
+use crate::errors::{runtime_error, timeout_error, to_py_error, validation_error};
 use crate::runtime::get_runtime;
 
 /// Client configuration for production environments
@@ -169,6 +172,9 @@ pub struct LlmClient {
     config: ClientConfig,
     stats: Arc<RwLock<ClientStats>>,
     created_at: Instant,
+    /// Configuration and middleware the provider was created with, for re-ranking
+    llm_config: graphbit_core::llm::LlmConfig,
+    middleware: LlmMiddlewareStack,
     /// Cache for warmup to avoid repeated initialization overhead
     warmed_up: Arc<tokio::sync::OnceCell<()>>,
 }
@@ -176,8 +182,12 @@ pub struct LlmClient {
 #[pymethods]
 impl LlmClient {
     #[new]
-    #[pyo3(signature = (config, debug=None))]
-    fn new(config: LlmConfig, debug: Option<bool>) -> PyResult<Self> {
+    #[pyo3(signature = (config, debug=None, middleware=None))]
+    fn new(
+        config: LlmConfig,
+        debug: Option<bool>,
+        middleware: Option<Vec<PyRef<'_, PyLlmMiddleware>>>,
+    ) -> PyResult<Self> {
         let mut client_config = ClientConfig {
             debug: debug.unwrap_or(false),
             ..Default::default()
@@ -206,9 +216,16 @@ impl LlmClient {
             }
         }
 
-        let provider =
-            graphbit_core::llm::LlmProviderFactory::create_provider(config.inner.clone())
-                .map_err(to_py_error)?;
+        let middleware: LlmMiddlewareStack = middleware
+            .unwrap_or_default()
+            .iter()
+            .map(|layer| layer.inner.clone())
+            .collect();
+        let provider = graphbit_core::llm::LlmProviderFactory::create_provider_with_middleware(
+            config.inner.clone(),
+            middleware.clone(),
+        )
+        .map_err(to_py_error)?;
 
         let circuit_breaker = Arc::new(CircuitBreaker::new(client_config.clone()));
 
@@ -231,6 +248,8 @@ impl LlmClient {
             config: client_config,
             stats,
             created_at: Instant::now(),
+            llm_config: config.inner,
+            middleware,
             warmed_up: Arc::new(tokio::sync::OnceCell::new()),
         })
     }
@@ -806,6 +825,55 @@ impl LlmClient {
         Ok(())
     }
 
+    /// Check that the provider is reachable, accepts the credentials and serves
+    /// the model; returns a dict with `reachable`, `authenticated`,
+    /// `model_available`, `latency_ms` and `error`
+    fn health_check(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let provider = Arc::clone(&self.provider);
+        let health = py.allow_threads(|| {
+            get_runtime().block_on(async move { provider.read().await.health_check().await })
+        });
+        Ok(pythonize::pythonize(py, &health)?.unbind())
+    }
+
+    /// Order `documents` by their relevance to `query`, as scored by the LLM
+    ///
+    /// Documents are scored `batch_size` per prompt, with `model` instead of the
+    /// configured one if given. Returns a dict with `documents`, the `top_k` most
+    /// relevant (all if unset) as dicts of `index`, `document` and `score` from
+    /// 0.0 to 1.0, and the token `usage` of the scoring prompts.
+    #[pyo3(signature = (query, documents, top_k=None, model=None, batch_size=10))]
+    fn rerank(
+        &self,
+        py: Python<'_>,
+        query: String,
+        documents: Vec<String>,
+        top_k: Option<usize>,
+        model: Option<String>,
+        batch_size: usize,
+    ) -> PyResult<PyObject> {
+        let config = RerankConfig {
+            top_k,
+            model,
+            batch_size,
+        };
+        let llm_config = self.llm_config.clone();
+        let middleware = self.middleware.clone();
+        let reranking = py.allow_threads(|| {
+            get_runtime().block_on(async move {
+                let provider = LlmProvider::new(
+                    graphbit_core::llm::LlmProviderFactory::create_provider(llm_config.clone())?,
+                    llm_config,
+                );
+                provider
+                    .rerank_with(&middleware, &query, &documents, &config)
+                    .await
+            })
+        });
+        let reranking = reranking.map_err(to_py_error)?;
+        Ok(pythonize::pythonize(py, &reranking)?.unbind())
+    }
+
     /// Complete with full response object (synchronous)
     #[instrument(skip(self, py), fields(prompt_len = prompt.len()))]
     #[pyo3(signature = (prompt, max_tokens=None, temperature=None, enable_prompt_caching=false))]
@@ -1033,9 +1101,7 @@ impl LlmClient {
 
         // Check circuit breaker
         if !circuit_breaker.can_execute().await {
-            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                "Circuit breaker is open, request rejected",
-            ));
+            return Err(runtime_error("Circuit breaker is open, request rejected"));
         }
 
         let mut last_error = None;
@@ -1114,9 +1180,7 @@ impl LlmClient {
             stats_guard.failed_requests += 1;
         }
 
-        Err(last_error.unwrap_or_else(|| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("All retry attempts failed")
-        }))
+        Err(last_error.unwrap_or_else(|| runtime_error("All retry attempts failed")))
     }
 }
 
@@ -1229,9 +1293,7 @@ impl StreamIterator {
                 }
             } else {
                 // Should never happen after OnceCell initialization
-                Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                    "Stream not initialized",
-                ))
+                Err(runtime_error("Stream not initialized"))
             }
         })
     }
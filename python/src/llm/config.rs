@@ -1,13 +1,17 @@
 //! LLM configuration for GraphBit Python bindings
 
-use crate::validation::validate_api_key;
+use crate::errors::{invalid_value, runtime_error, to_py_error};
+use crate::pickle::{Reduced, from_state, restore_fn, to_state};
+use crate::validation::{request_timeouts, validate_api_key};
+use graphbit_core::env_config::{EnvConfig, provider_var_name};
 use graphbit_core::llm::LlmConfig as CoreLlmConfig;
 use graphbit_core::llm::providers::{register_python_instance, unregister_python_instance};
 use pyo3::prelude::*;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Configuration for LLM providers and models
-#[pyclass]
+#[pyclass(module = "graphbit")]
 #[derive(Clone)]
 pub struct LlmConfig {
     pub(crate) inner: CoreLlmConfig,
@@ -16,40 +20,61 @@ pub struct LlmConfig {
 #[pymethods]
 impl LlmConfig {
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn openai(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn openai(
+        api_key: String,
+        model: Option<String>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "OpenAI")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
+
+        let model = model.unwrap_or_else(|| "gpt-4o-mini".to_string());
+        let config = if let Some(base_url) = base_url {
+            CoreLlmConfig::openai_with_base_url(api_key, model, base_url)
+        } else {
+            CoreLlmConfig::openai(api_key, model)
+        };
 
         Ok(Self {
-            inner: CoreLlmConfig::openai(
-                api_key,
-                model.unwrap_or_else(|| "gpt-4o-mini".to_string()),
-            ),
+            inner: config.with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn anthropic(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn anthropic(
+        api_key: String,
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "Anthropic")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreLlmConfig::anthropic(
                 api_key,
                 model.unwrap_or_else(|| "claude-3-5-sonnet-20241022".to_string()),
-            ),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, deployment_name, endpoint, api_version=None))]
+    #[pyo3(signature = (api_key, deployment_name, endpoint, api_version=None, timeout_ms=None, connect_timeout_ms=None))]
     fn azurellm(
         api_key: String,
         deployment_name: String,
         endpoint: String,
         api_version: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
     ) -> PyResult<Self> {
         validate_api_key(&api_key, "Azure LLM")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreLlmConfig::azurellm(
@@ -57,18 +82,22 @@ impl LlmConfig {
                 deployment_name,
                 endpoint,
                 api_version.unwrap_or_else(|| "2024-10-21".to_string()),
-            ),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None, base_url=None))]
+    #[pyo3(signature = (api_key, model=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
     fn bytedance(
         api_key: String,
         model: Option<String>,
         base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
     ) -> PyResult<Self> {
         validate_api_key(&api_key, "ByteDance")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         let config = if let Some(base_url) = base_url {
             CoreLlmConfig::bytedance_with_base_url(
@@ -83,62 +112,95 @@ impl LlmConfig {
             )
         };
 
-        Ok(Self { inner: config })
+        Ok(Self {
+            inner: config.with_timeouts(timeouts),
+        })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn deepseek(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn deepseek(
+        api_key: String,
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "DeepSeek")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreLlmConfig::deepseek(
                 api_key,
                 model.unwrap_or_else(|| "deepseek-chat".to_string()),
-            ),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (model=None))]
-    fn ollama(model: Option<String>) -> Self {
-        Self {
-            inner: CoreLlmConfig::ollama(model.unwrap_or_else(|| "llama3.2".to_string())),
-        }
+    #[pyo3(signature = (model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn ollama(
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
+
+        Ok(Self {
+            inner: CoreLlmConfig::ollama(model.unwrap_or_else(|| "llama3.2".to_string()))
+                .with_timeouts(timeouts),
+        })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn perplexity(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn perplexity(
+        api_key: String,
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "Perplexity")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
-            inner: CoreLlmConfig::perplexity(api_key, model.unwrap_or_else(|| "sonar".to_string())),
+            inner: CoreLlmConfig::perplexity(api_key, model.unwrap_or_else(|| "sonar".to_string()))
+                .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn openrouter(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn openrouter(
+        api_key: String,
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "OpenRouter")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreLlmConfig::openrouter(
                 api_key,
                 model.unwrap_or_else(|| "openai/gpt-4o-mini".to_string()),
-            ),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None, site_url=None, site_name=None))]
+    #[pyo3(signature = (api_key, model=None, site_url=None, site_name=None, timeout_ms=None, connect_timeout_ms=None))]
     fn openrouter_with_site(
         api_key: String,
         model: Option<String>,
         site_url: Option<String>,
         site_name: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
     ) -> PyResult<Self> {
         validate_api_key(&api_key, "OpenRouter")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreLlmConfig::openrouter_with_site(
@@ -146,33 +208,47 @@ impl LlmConfig {
                 model.unwrap_or_else(|| "openai/gpt-4o-mini".to_string()),
                 site_url,
                 site_name,
-            ),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn fireworks(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn fireworks(
+        api_key: String,
+        model: Option<String>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "Fireworks")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
+
+        let model =
+            model.unwrap_or_else(|| "accounts/fireworks/models/llama-v3p1-8b-instruct".to_string());
+        let config = if let Some(base_url) = base_url {
+            CoreLlmConfig::fireworks_with_base_url(api_key, model, base_url)
+        } else {
+            CoreLlmConfig::fireworks(api_key, model)
+        };
 
         Ok(Self {
-            inner: CoreLlmConfig::fireworks(
-                api_key,
-                model.unwrap_or_else(|| {
-                    "accounts/fireworks/models/llama-v3p1-8b-instruct".to_string()
-                }),
-            ),
+            inner: config.with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None, organization=None))]
+    #[pyo3(signature = (api_key, model=None, organization=None, timeout_ms=None, connect_timeout_ms=None))]
     fn ai21(
         api_key: String,
         model: Option<String>,
         organization: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
     ) -> PyResult<Self> {
         validate_api_key(&api_key, "AI21")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         let config = if let Some(organization) = organization {
             CoreLlmConfig::ai21_with_organization(
@@ -184,17 +260,22 @@ impl LlmConfig {
             CoreLlmConfig::ai21(api_key, model.unwrap_or_else(|| "jamba-mini".to_string()))
         };
 
-        Ok(Self { inner: config })
+        Ok(Self {
+            inner: config.with_timeouts(timeouts),
+        })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None, version=None))]
+    #[pyo3(signature = (api_key, model=None, version=None, timeout_ms=None, connect_timeout_ms=None))]
     fn replicate(
         api_key: String,
         model: Option<String>,
         version: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
     ) -> PyResult<Self> {
         validate_api_key(&api_key, "Replicate")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         let config = if let Some(version) = version {
             CoreLlmConfig::replicate_with_version(
@@ -206,55 +287,104 @@ impl LlmConfig {
             CoreLlmConfig::replicate(api_key, model.unwrap_or_else(|| "openai/gpt-5".to_string()))
         };
 
-        Ok(Self { inner: config })
+        Ok(Self {
+            inner: config.with_timeouts(timeouts),
+        })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn togetherai(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn togetherai(
+        api_key: String,
+        model: Option<String>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "TogetherAI")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
+
+        let model = model.unwrap_or_else(|| "openai/gpt-oss-20b".to_string());
+        let config = if let Some(base_url) = base_url {
+            CoreLlmConfig::togetherai_with_base_url(api_key, model, base_url)
+        } else {
+            CoreLlmConfig::togetherai(api_key, model)
+        };
 
         Ok(Self {
-            inner: CoreLlmConfig::togetherai(
-                api_key,
-                model.unwrap_or_else(|| "openai/gpt-oss-20b".to_string()),
-            ),
+            inner: config.with_timeouts(timeouts),
         })
     }
 
+    /// Same as `togetherai`
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn xai(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, base_url=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn together(
+        api_key: String,
+        model: Option<String>,
+        base_url: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
+        Self::togetherai(api_key, model, base_url, timeout_ms, connect_timeout_ms)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (api_key, model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn xai(
+        api_key: String,
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "xAI")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
-            inner: CoreLlmConfig::xai(api_key, model.unwrap_or_else(|| "grok-4".to_string())),
+            inner: CoreLlmConfig::xai(api_key, model.unwrap_or_else(|| "grok-4".to_string()))
+                .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn mistralai(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn mistralai(
+        api_key: String,
+        model: Option<String>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "MistralAI")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
             inner: CoreLlmConfig::mistralai(
                 api_key,
                 model.unwrap_or_else(|| "mistral-large-latest".to_string()),
-            ),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
     #[staticmethod]
-    #[pyo3(signature = (api_key, model=None))]
-    fn gemini(api_key: String, model: Option<String>) -> PyResult<Self> {
+    #[pyo3(signature = (api_key, model=None, safety_settings=None, timeout_ms=None, connect_timeout_ms=None))]
+    fn gemini(
+        api_key: String,
+        model: Option<String>,
+        safety_settings: Option<HashMap<String, String>>,
+        timeout_ms: Option<u64>,
+        connect_timeout_ms: Option<u64>,
+    ) -> PyResult<Self> {
         validate_api_key(&api_key, "Gemini")?;
+        let timeouts = request_timeouts(timeout_ms, connect_timeout_ms)?;
 
         Ok(Self {
-            inner: CoreLlmConfig::gemini(
+            inner: CoreLlmConfig::gemini_with_safety_settings(
                 api_key,
                 model.unwrap_or_else(|| "gemini-2.5-flash".to_string()),
-            ),
+                safety_settings.unwrap_or_default(),
+            )
+            .with_timeouts(timeouts),
         })
     }
 
@@ -280,9 +410,7 @@ impl LlmConfig {
 
             // Create instance with API key (token parameter)
             let hf_instance = hf_class.call1((api_key,)).map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!(
-                    "Failed to create HuggingfaceLLM instance: {e}"
-                ))
+                runtime_error(format!("Failed to create HuggingfaceLLM instance: {e}"))
             })?;
 
             let py_object: PyObject = hf_instance.into();
@@ -326,15 +454,11 @@ impl LlmConfig {
             // Create instance with optional API key
             let litellm_instance = if let Some(key) = api_key {
                 litellm_class.call1((key,)).map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!(
-                        "Failed to create LiteLLMLLM instance: {e}"
-                    ))
+                    runtime_error(format!("Failed to create LiteLLMLLM instance: {e}"))
                 })?
             } else {
                 litellm_class.call0().map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!(
-                        "Failed to create LiteLLMLLM instance: {e}"
-                    ))
+                    runtime_error(format!("Failed to create LiteLLMLLM instance: {e}"))
                 })?
             };
 
@@ -348,6 +472,32 @@ impl LlmConfig {
         })
     }
 
+    /// Configuration of `provider` from its `GRAPHBIT_<PROVIDER>_*` environment
+    /// variables, of `GRAPHBIT_DEFAULT_PROVIDER` when no provider is given;
+    /// `model`, `api_key` and `base_url` take precedence over the variables
+    #[staticmethod]
+    #[pyo3(signature = (provider=None, model=None, api_key=None, base_url=None))]
+    fn from_env(
+        provider: Option<&str>,
+        model: Option<String>,
+        api_key: Option<String>,
+        base_url: Option<String>,
+    ) -> PyResult<Self> {
+        let env = EnvConfig::from_env();
+        let provider = Self::env_provider(&env, provider)?;
+        let env = [
+            ("MODEL", model),
+            ("API_KEY", api_key),
+            ("BASE_URL", base_url),
+        ]
+        .into_iter()
+        .fold(env, |env, (setting, value)| match value {
+            Some(value) => env.with_var(provider_var_name(&provider, setting), value),
+            None => env,
+        });
+        Self::from_env_config(&env, Some(&provider))
+    }
+
     fn provider(&self) -> String {
         self.inner.provider_name().to_string()
     }
@@ -356,6 +506,88 @@ impl LlmConfig {
         self.inner.model_name().to_string()
     }
 
+    /// Total request timeout in milliseconds, `None` for the provider default
+    fn timeout_ms(&self) -> Option<u64> {
+        self.inner.timeouts().timeout_ms
+    }
+
+    /// Connection timeout in milliseconds, `None` for the default
+    fn connect_timeout_ms(&self) -> Option<u64> {
+        self.inner.timeouts().connect_timeout_ms
+    }
+
+    /// Copy of this configuration with its API key replaced by `"[REDACTED]"`
+    ///
+    /// Use it to log or pickle a configuration without leaking credentials.
+    /// The copy cannot authenticate against the provider; Python-backed
+    /// providers lose their provider instance, which holds its own key.
+    fn redacted(&self) -> PyResult<Self> {
+        if let CoreLlmConfig::PythonBridge { model, .. } = &self.inner {
+            return Ok(Self {
+                inner: CoreLlmConfig::PythonBridge {
+                    python_instance: None,
+                    model: model.clone(),
+                    instance_id: None,
+                },
+            });
+        }
+
+        let redact_error =
+            |e: serde_json::Error| runtime_error(format!("Failed to redact LLM config: {e}"));
+        let mut value = serde_json::to_value(&self.inner).map_err(redact_error)?;
+        redact_api_key(&mut value);
+        if let Some(config) = value.get_mut("config") {
+            redact_api_key(config);
+        }
+        Ok(Self {
+            inner: serde_json::from_value(value).map_err(redact_error)?,
+        })
+    }
+
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<Reduced<'py, (String, Option<PyObject>)>> {
+        let config = slf.borrow();
+        let python_instance = match &config.inner {
+            CoreLlmConfig::PythonBridge {
+                python_instance: Some(instance),
+                ..
+            } => Some(instance.clone_ref(slf.py())),
+            _ => None,
+        };
+        Ok((
+            restore_fn(slf.as_any())?,
+            (to_state(&config.inner)?, python_instance),
+        ))
+    }
+
+    /// Rebuild a configuration pickled by `__reduce__`
+    #[staticmethod]
+    #[pyo3(signature = (state, python_instance=None))]
+    fn _from_state(state: &str, python_instance: Option<PyObject>) -> PyResult<Self> {
+        let mut inner: CoreLlmConfig = from_state(state)?;
+        if let (
+            CoreLlmConfig::PythonBridge {
+                python_instance: slot,
+                instance_id,
+                ..
+            },
+            Some(instance),
+        ) = (&mut inner, python_instance)
+        {
+            let instance = std::sync::Arc::new(instance);
+            if let Some(id) = instance_id {
+                register_python_instance(id.clone(), instance.clone());
+            }
+            *slot = Some(instance);
+        }
+        Ok(Self { inner })
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
     /// Cleanup the Python instance from the global registry
     ///
     /// Call this method when you're done using a HuggingFace or other PythonBridge
@@ -405,3 +637,31 @@ impl LlmConfig {
         false
     }
 }
+
+impl LlmConfig {
+    /// Configuration of `provider`, or of `GRAPHBIT_DEFAULT_PROVIDER`, from `env`
+    pub(crate) fn from_env_config(env: &EnvConfig, provider: Option<&str>) -> PyResult<Self> {
+        let provider = Self::env_provider(env, provider)?;
+        env.llm_config(&provider)
+            .map(|inner| Self { inner })
+            .map_err(to_py_error)
+    }
+
+    fn env_provider(env: &EnvConfig, provider: Option<&str>) -> PyResult<String> {
+        provider
+            .or_else(|| env.default_provider())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                invalid_value("No provider given and GRAPHBIT_DEFAULT_PROVIDER is not set")
+            })
+    }
+}
+
+/// Replace a non-empty `api_key` field of a serialized configuration
+fn redact_api_key(value: &mut serde_json::Value) {
+    if let Some(api_key) = value.get_mut("api_key") {
+        if api_key.as_str().is_some_and(|key| !key.is_empty()) {
+            *api_key = serde_json::Value::String("[REDACTED]".to_string());
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Python bindings for LLM middleware
+//!
+//! Middleware built from Python callables runs synchronously on GraphBit's
+//! runtime threads with the GIL held: hooks should return quickly and must not
+//! be coroutine functions.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use graphbit_core::errors::{GraphBitError, GraphBitResult};
+use graphbit_core::llm::{
+    LlmMiddleware as CoreLlmMiddleware, LlmRequest, LlmResponse, RedactionMiddleware,
+};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use crate::errors::{to_py_error, validation_error};
+
+/// Middleware calling Python hooks
+struct CallableMiddleware {
+    name: String,
+    before: Option<PyObject>,
+    after: Option<PyObject>,
+    on_error: Option<PyObject>,
+}
+
+impl CallableMiddleware {
+    /// Call `hook` with `request` (if any) and `value` as dicts. The hook either
+    /// mutates the `value` dict in place and returns `None`, or returns a
+    /// replacement dict.
+    fn call_hook<T: Serialize + DeserializeOwned>(
+        &self,
+        hook: &PyObject,
+        stage: &str,
+        request: Option<&LlmRequest>,
+        value: &T,
+    ) -> GraphBitResult<T> {
+        let fail = |message: String| {
+            GraphBitError::llm(format!(
+                "LLM middleware '{}' failed in {stage}: {message}",
+                self.name
+            ))
+        };
+        Python::with_gil(|py| {
+            let arg = pythonize::pythonize(py, value).map_err(|e| fail(e.to_string()))?;
+            let returned = match request {
+                Some(request) => {
+                    let request =
+                        pythonize::pythonize(py, request).map_err(|e| fail(e.to_string()))?;
+                    hook.call1(py, (request, &arg))
+                }
+                None => hook.call1(py, (&arg,)),
+            }
+            .map_err(|e| fail(e.to_string()))?
+            .into_bound(py);
+            if returned.hasattr("__await__").unwrap_or(false) {
+                let _ = returned.call_method0("close");
+                return Err(fail("hooks must be synchronous functions".to_string()));
+            }
+            let value = if returned.is_none() { arg } else { returned };
+            pythonize::depythonize(&value).map_err(|e| fail(format!("invalid {stage} result: {e}")))
+        })
+    }
+}
+
+#[async_trait]
+impl CoreLlmMiddleware for CallableMiddleware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn before_request(&self, request: &mut LlmRequest) -> GraphBitResult<()> {
+        if let Some(hook) = &self.before {
+            *request = self.call_hook(hook, "before", None, &*request)?;
+        }
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        request: &LlmRequest,
+        response: &mut LlmResponse,
+    ) -> GraphBitResult<()> {
+        if let Some(hook) = &self.after {
+            *response = self.call_hook(hook, "after", Some(request), &*response)?;
+        }
+        Ok(())
+    }
+
+    async fn on_error(&self, request: &LlmRequest, error: &GraphBitError) {
+        let Some(hook) = &self.on_error else {
+            return;
+        };
+        Python::with_gil(|py| {
+            let result = pythonize::pythonize(py, request)
+                .map_err(PyErr::from)
+                .and_then(|request| hook.call1(py, (request, error.to_string())));
+            if let Err(e) = result {
+                warn!("LLM middleware '{}' failed in on_error: {}", self.name, e);
+            }
+        });
+    }
+}
+
+/// Hooks run around every LLM call of an `LlmClient` or `Executor`
+///
+/// `before(request)` receives the request as a dict and `after(request, response)`
+/// the response as a dict; each either mutates the dict in place and returns
+/// `None` or returns a replacement. An exception raised by either fails the call.
+/// `on_error(request, message)` observes failures; its exceptions are logged and
+/// ignored. Hooks run in the order the middleware were passed.
+#[pyclass(name = "LlmMiddleware")]
+#[derive(Clone)]
+pub struct PyLlmMiddleware {
+    pub(crate) inner: Arc<dyn CoreLlmMiddleware>,
+}
+
+#[pymethods]
+impl PyLlmMiddleware {
+    /// Create middleware from synchronous callables
+    #[new]
+    #[pyo3(signature = (before=None, after=None, on_error=None, name=None))]
+    fn new(
+        py: Python<'_>,
+        before: Option<PyObject>,
+        after: Option<PyObject>,
+        on_error: Option<PyObject>,
+        name: Option<String>,
+    ) -> PyResult<Self> {
+        if before.is_none() && after.is_none() && on_error.is_none() {
+            return Err(validation_error(
+                "middleware",
+                None,
+                "At least one of before, after or on_error must be given",
+            ));
+        }
+        let inspect = py.import("inspect")?;
+        for (field, hook) in [
+            ("before", &before),
+            ("after", &after),
+            ("on_error", &on_error),
+        ] {
+            let Some(hook) = hook else {
+                continue;
+            };
+            let hook = hook.bind(py);
+            if !hook.is_callable() {
+                return Err(validation_error(
+                    field,
+                    None,
+                    "Middleware hooks must be callable",
+                ));
+            }
+            if inspect
+                .call_method1("iscoroutinefunction", (hook,))?
+                .is_truthy()?
+            {
+                return Err(validation_error(
+                    field,
+                    None,
+                    "Middleware hooks must be synchronous functions",
+                ));
+            }
+        }
+        Ok(Self {
+            inner: Arc::new(CallableMiddleware {
+                name: name.unwrap_or_else(|| "python".to_string()),
+                before,
+                after,
+                on_error,
+            }),
+        })
+    }
+
+    /// Built-in middleware replacing sensitive text in messages with `[REDACTED_<NAME>]`
+    ///
+    /// The default patterns cover email addresses, API keys, bearer tokens and card
+    /// numbers. `patterns` maps extra names to regular expressions.
+    #[staticmethod]
+    #[pyo3(signature = (patterns=None, include_defaults=true, redact_responses=false))]
+    fn redaction(
+        patterns: Option<&Bound<'_, PyDict>>,
+        include_defaults: bool,
+        redact_responses: bool,
+    ) -> PyResult<Self> {
+        let mut redaction = if include_defaults {
+            RedactionMiddleware::new()
+        } else {
+            RedactionMiddleware::empty()
+        };
+        if let Some(patterns) = patterns {
+            for (name, pattern) in patterns.iter() {
+                redaction = redaction
+                    .with_pattern(&name.extract::<String>()?, &pattern.extract::<String>()?)
+                    .map_err(to_py_error)?;
+            }
+        }
+        Ok(Self {
+            inner: Arc::new(redaction.with_response_redaction(redact_responses)),
+        })
+    }
+
+    /// Name of the middleware
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LlmMiddleware(name='{}')", self.inner.name())
+    }
+}
@@ -2,8 +2,10 @@
 
 pub(crate) mod client;
 pub(crate) mod config;
+pub(crate) mod middleware;
 pub(crate) mod response;
 
 pub use client::LlmClient;
 pub use config::LlmConfig;
+pub use middleware::PyLlmMiddleware;
 pub use response::{PyFinishReason, PyLlmResponse, PyLlmToolCall, PyLlmUsage};
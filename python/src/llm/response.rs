@@ -3,6 +3,7 @@
 //! This module provides PyO3 bindings for GraphBit's LLM response types,
 //! enabling comprehensive LLM tracing and observability from Python.
 
+use crate::errors::invalid_value;
 use graphbit_core::llm::{
     FinishReason as CoreFinishReason, LlmResponse as CoreLlmResponse,
     LlmToolCall as CoreLlmToolCall, LlmUsage as CoreLlmUsage,
@@ -267,9 +268,8 @@ impl PyLlmToolCall {
     /// Create new tool call
     #[new]
     fn new(id: String, name: String, parameters: String) -> PyResult<Self> {
-        let params_value: serde_json::Value = serde_json::from_str(&parameters).map_err(|e| {
-            pyo3::exceptions::PyValueError::new_err(format!("Invalid JSON parameters: {}", e))
-        })?;
+        let params_value: serde_json::Value = serde_json::from_str(&parameters)
+            .map_err(|e| invalid_value(format!("Invalid JSON parameters: {}", e)))?;
 
         Ok(Self {
             inner: CoreLlmToolCall {
@@ -447,9 +447,8 @@ impl PyLlmResponse {
 
     /// Add metadata (builder pattern) - accepts JSON string
     fn with_metadata(&mut self, key: String, value: String) -> PyResult<()> {
-        let json_value: serde_json::Value = serde_json::from_str(&value).map_err(|e| {
-            pyo3::exceptions::PyValueError::new_err(format!("Invalid JSON value: {}", e))
-        })?;
+        let json_value: serde_json::Value = serde_json::from_str(&value)
+            .map_err(|e| invalid_value(format!("Invalid JSON value: {}", e)))?;
         self.inner = self.inner.clone().with_metadata(key, json_value);
         Ok(())
     }
@@ -0,0 +1,119 @@
+//! Tracing subscriber setup for GraphBit Python bindings
+//!
+//! `init(enable_tracing=True)` installs a global subscriber once per process.
+//! Its level sits behind a reloadable filter, so later `init` calls and
+//! `set_log_level` can change it without reinstalling the subscriber.
+
+use crate::errors::{invalid_value, runtime_error};
+use pyo3::prelude::*;
+use std::fs::OpenOptions;
+use std::sync::Mutex;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{Layer, Registry, reload};
+
+/// Handle to the level filter of the installed subscriber, if any
+static LEVEL_HANDLE: Mutex<Option<reload::Handle<LevelFilter, Registry>>> = Mutex::new(None);
+
+/// Output format of log lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl LogFormat {
+    pub(crate) fn parse(format: &str) -> PyResult<Self> {
+        match format.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(invalid_value(format!(
+                "Invalid log format '{format}': expected 'text' or 'json'"
+            ))),
+        }
+    }
+}
+
+/// Parse a log level name such as `"info"` or `"DEBUG"`
+pub(crate) fn parse_level(level: &str) -> PyResult<LevelFilter> {
+    match level.to_lowercase().as_str() {
+        "trace" => Ok(LevelFilter::TRACE),
+        "debug" => Ok(LevelFilter::DEBUG),
+        "info" => Ok(LevelFilter::INFO),
+        "warn" | "warning" => Ok(LevelFilter::WARN),
+        "error" => Ok(LevelFilter::ERROR),
+        "off" => Ok(LevelFilter::OFF),
+        _ => Err(invalid_value(format!(
+            "Invalid log level '{level}': expected trace, debug, info, warn, error or off"
+        ))),
+    }
+}
+
+/// Install the global tracing subscriber, or update its level when one was
+/// already installed by an earlier call
+///
+/// The format and log file of the first installation stay in place.
+pub(crate) fn init_tracing(
+    level: LevelFilter,
+    format: LogFormat,
+    log_file: Option<&str>,
+) -> PyResult<()> {
+    let mut installed = LEVEL_HANDLE
+        .lock()
+        .map_err(|_| runtime_error("Tracing configuration lock poisoned"))?;
+    if let Some(handle) = installed.as_ref() {
+        return handle
+            .reload(level)
+            .map_err(|e| runtime_error(format!("Failed to update log level: {e}")));
+    }
+
+    let writer = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| invalid_value(format!("Cannot open log file '{path}': {e}")))?;
+            BoxMakeWriter::new(Mutex::new(file))
+        }
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(log_file.is_none())
+        .with_thread_ids(false) // Disable thread IDs for cleaner output
+        .with_thread_names(false) // Disable thread names for cleaner output
+        .with_file(false) // Disable file info for performance
+        .with_line_number(false); // Disable line numbers for performance
+    let fmt_layer = match format {
+        LogFormat::Text => fmt_layer.boxed(),
+        LogFormat::Json => fmt_layer.json().boxed(),
+    };
+
+    let (filter, handle) = reload::Layer::new(level);
+    let subscriber = Registry::default().with(fmt_layer.with_filter(filter));
+    if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+        eprintln!("Warning: Failed to set tracing subscriber: {e}");
+        return Ok(());
+    }
+    *installed = Some(handle);
+    Ok(())
+}
+
+/// Change the level of the subscriber installed by `init(enable_tracing=True)`
+pub(crate) fn set_level(level: LevelFilter) -> PyResult<()> {
+    let installed = LEVEL_HANDLE
+        .lock()
+        .map_err(|_| runtime_error("Tracing configuration lock poisoned"))?;
+    match installed.as_ref() {
+        Some(handle) => handle
+            .reload(level)
+            .map_err(|e| runtime_error(format!("Failed to update log level: {e}"))),
+        None => Err(runtime_error(
+            "Tracing is not enabled; call graphbit.init(enable_tracing=True) first",
+        )),
+    }
+}
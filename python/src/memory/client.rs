@@ -2,9 +2,12 @@
 
 use std::sync::Arc;
 
+use graphbit_core::llm::RerankConfig;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
-use crate::errors::to_py_runtime_error;
+use crate::embeddings::EmbeddingConfig;
+use crate::errors::{invalid_value, to_py_runtime_error, validation_error};
 use crate::runtime::get_runtime;
 
 use super::config::MemoryConfig;
@@ -89,7 +92,9 @@ impl MemoryClient {
     /// * `query` - The search query text.
     /// * `user_id` / `agent_id` / `run_id` - Optional scope filters.
     /// * `top_k` - Maximum number of results (default 10).
-    #[pyo3(signature = (query, user_id=None, agent_id=None, run_id=None, top_k=None))]
+    /// * `rerank` - Re-rank the results with the memory LLM: a dict with the
+    ///   optional keys `top_k`, `model` and `batch_size`.
+    #[pyo3(signature = (query, user_id=None, agent_id=None, run_id=None, top_k=None, rerank=None))]
     fn search(
         &self,
         py: Python<'_>,
@@ -98,7 +103,12 @@ impl MemoryClient {
         agent_id: Option<String>,
         run_id: Option<String>,
         top_k: Option<usize>,
+        rerank: Option<Bound<'_, PyDict>>,
     ) -> PyResult<Vec<PyScoredMemory>> {
+        let rerank: Option<RerankConfig> = rerank
+            .map(|config| pythonize::depythonize(&config))
+            .transpose()
+            .map_err(|e| invalid_value(format!("Invalid rerank config: {e}")))?;
         let scope = graphbit_core::memory::MemoryScope {
             user_id,
             agent_id,
@@ -112,7 +122,7 @@ impl MemoryClient {
         py.allow_threads(|| {
             rt.block_on(async move {
                 let results = service
-                    .search(&query, &scope, k)
+                    .search(&query, &scope, k, rerank.as_ref())
                     .await
                     .map_err(to_py_runtime_error)?;
                 Ok(results.into_iter().map(PyScoredMemory::from).collect())
@@ -230,6 +240,61 @@ impl MemoryClient {
             })
         })
     }
+
+    /// Re-embed every stored memory with another embedding model.
+    ///
+    /// Memories are embedded `batch_size` at a time; each batch is stored
+    /// transactionally with a checkpoint, so a re-embedding that was
+    /// interrupted resumes where it stopped when called again with the same
+    /// model. Until it finishes, `add`, `update` and `search` fail.
+    ///
+    /// # Arguments
+    /// * `embedding_config` - Embedding provider configuration of the new model.
+    /// * `batch_size` - Number of memories embedded per request (default 256).
+    /// * `progress` - Optional callable receiving `(processed, total)` after
+    ///   each batch. Raising from it stops the re-embedding.
+    ///
+    /// # Returns
+    /// The number of memories embedded by this call.
+    #[pyo3(signature = (embedding_config, batch_size=256, progress=None))]
+    fn reembed(
+        &self,
+        py: Python<'_>,
+        embedding_config: EmbeddingConfig,
+        batch_size: usize,
+        progress: Option<PyObject>,
+    ) -> PyResult<usize> {
+        if batch_size == 0 {
+            return Err(validation_error(
+                "batch_size",
+                Some("0"),
+                "Re-embedding batch size must be greater than 0",
+            ));
+        }
+        let service = Arc::clone(&self.service);
+        let rt = get_runtime();
+        let mut callback_error = None;
+
+        let result = py.allow_threads(|| {
+            rt.block_on(
+                service.reembed_all(embedding_config.inner, batch_size, |update| {
+                    let Some(progress) = &progress else {
+                        return Ok(());
+                    };
+                    Python::with_gil(|py| progress.call1(py, (update.processed, update.total)))
+                        .map(drop)
+                        .map_err(|e| {
+                            callback_error = Some(e);
+                            graphbit_core::errors::GraphBitError::memory("Progress callback failed")
+                        })
+                }),
+            )
+        });
+        if let Some(e) = callback_error {
+            return Err(e);
+        }
+        result.map_err(to_py_runtime_error)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -248,7 +313,6 @@ fn messages_to_llm(messages: Vec<(String, String)>) -> Vec<graphbit_core::llm::L
 }
 
 fn parse_memory_id(s: &str) -> PyResult<graphbit_core::memory::MemoryId> {
-    graphbit_core::memory::MemoryId::from_string(s).map_err(|e| {
-        pyo3::exceptions::PyValueError::new_err(format!("Invalid memory ID '{s}': {e}"))
-    })
+    graphbit_core::memory::MemoryId::from_string(s)
+        .map_err(|e| invalid_value(format!("Invalid memory ID '{s}': {e}")))
 }
@@ -0,0 +1,28 @@
+//! Pickle support for GraphBit Python bindings
+//!
+//! Picklable classes implement `__reduce__` by returning their private
+//! `_from_state` static method together with the serde JSON representation of
+//! the wrapped core value, so the state stays readable across GraphBit
+//! versions and processes.
+
+use crate::errors::invalid_value;
+use pyo3::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Return value of `__reduce__`: a callable and the arguments to call it with
+pub(crate) type Reduced<'py, A> = (Bound<'py, PyAny>, A);
+
+/// Serialize a core value into pickle state
+pub(crate) fn to_state<T: Serialize>(value: &T) -> PyResult<String> {
+    serde_json::to_string(value).map_err(|e| invalid_value(format!("Failed to pickle: {e}")))
+}
+
+/// Rebuild a core value from state produced by [`to_state`]
+pub(crate) fn from_state<T: DeserializeOwned>(state: &str) -> PyResult<T> {
+    serde_json::from_str(state).map_err(|e| invalid_value(format!("Invalid pickle state: {e}")))
+}
+
+/// The `_from_state` static method of `slf`'s class, returned by `__reduce__`
+pub(crate) fn restore_fn<'py>(slf: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    slf.get_type().getattr("_from_state")
+}
@@ -2,8 +2,13 @@
 //!
 //! This module provides a highly optimized, configurable async runtime with
 //! proper resource management, monitoring, and graceful shutdown capabilities.
+//!
+//! The runtime starts on first use, not on import, with the configuration set
+//! by `configure_runtime`. A process forked after the runtime started, e.g. a
+//! gunicorn worker, starts its own runtime on first use in the child.
 
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
 use std::time::Duration;
 use tokio::runtime::{Builder, Runtime};
 use tracing::{error, info, warn};
@@ -47,6 +52,7 @@ pub(crate) struct GraphBitRuntime {
     runtime: Runtime,
     config: RuntimeConfig,
     created_at: std::time::Instant,
+    pid: u32,
 }
 
 impl GraphBitRuntime {
@@ -67,8 +73,12 @@ impl GraphBitRuntime {
             builder.thread_stack_size(stack_size);
         }
 
-        // Configure thread naming
-        builder.thread_name(&config.thread_name_prefix);
+        // Configure thread naming: `<prefix>-<n>` for workers and blocking threads
+        let prefix = config.thread_name_prefix.clone();
+        let next_thread = AtomicUsize::new(0);
+        builder.thread_name_fn(move || {
+            format!("{}-{}", prefix, next_thread.fetch_add(1, Ordering::Relaxed))
+        });
 
         // Configure thread keep alive
         if let Some(keep_alive) = config.thread_keep_alive {
@@ -97,6 +107,7 @@ impl GraphBitRuntime {
             runtime,
             config,
             created_at: std::time::Instant::now(),
+            pid: std::process::id(),
         })
     }
 
@@ -110,12 +121,13 @@ impl GraphBitRuntime {
         self.created_at.elapsed()
     }
 
-    /// Get runtime statistics (placeholder for future metrics)
+    /// Get runtime statistics
     pub(crate) fn stats(&self) -> RuntimeStats {
         RuntimeStats {
             uptime: self.uptime(),
-            worker_threads: self.config.worker_threads.unwrap_or(0),
+            worker_threads: self.runtime.metrics().num_workers(),
             max_blocking_threads: self.config.max_blocking_threads.unwrap_or(0),
+            pid: self.pid,
         }
     }
 }
@@ -126,58 +138,172 @@ pub(crate) struct RuntimeStats {
     pub uptime: Duration,
     pub worker_threads: usize,
     pub max_blocking_threads: usize,
+    /// Process the runtime was started in
+    pub pid: u32,
 }
 
-/// Global runtime instance with lazy initialization and proper error handling
-static GRAPHBIT_RUNTIME: OnceLock<GraphBitRuntime> = OnceLock::new();
+/// Configuration set by `configure_runtime`, used when the runtime starts
+static RUNTIME_CONFIG: Mutex<Option<RuntimeConfig>> = Mutex::new(None);
+
+/// The started runtime, or null until first use. Runtimes are leaked so that
+/// `get_runtime` can hand out `&'static` references; a process only starts
+/// another one after a fork.
+static GRAPHBIT_RUNTIME: AtomicPtr<GraphBitRuntime> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Held while a thread starts the runtime. A spin flag rather than a mutex, so
+/// the fork handler can release it in the child.
+static STARTING: AtomicBool = AtomicBool::new(false);
+
+/// Number of forks after which this process discarded the runtime it inherited
+static FORK_RESTARTS: AtomicUsize = AtomicUsize::new(0);
 
-/// Get the optimized runtime instance with proper error handling
+/// Registers the fork handler once, when the first runtime starts
+static FORK_HANDLER: Once = Once::new();
+
+/// Get the runtime, starting it on first use with the configuration set by
+/// `configure_runtime`
 pub(crate) fn get_runtime() -> &'static Runtime {
-    GRAPHBIT_RUNTIME
-        .get_or_init(|| {
-            let config = RuntimeConfig::default();
-            match GraphBitRuntime::new(config) {
-                Ok(runtime) => runtime,
-                Err(e) => {
-                    error!("Critical error: Failed to create GraphBit runtime: {}", e);
-                    panic!("Failed to create GraphBit runtime: {}", e);
-                }
+    started_runtime().runtime()
+}
+
+fn started_runtime() -> &'static GraphBitRuntime {
+    let current = GRAPHBIT_RUNTIME.load(Ordering::Acquire);
+    if !current.is_null() {
+        // SAFETY: non-null pointers come from `Box::leak` and are never freed
+        return unsafe { &*current };
+    }
+
+    while STARTING
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        std::thread::yield_now();
+    }
+    let current = GRAPHBIT_RUNTIME.load(Ordering::Acquire);
+    let runtime = if current.is_null() {
+        let config = pending_config().unwrap_or_default();
+        let runtime = match GraphBitRuntime::new(config) {
+            Ok(runtime) => Box::leak(Box::new(runtime)),
+            Err(e) => {
+                STARTING.store(false, Ordering::Release);
+                error!("Critical error: Failed to create GraphBit runtime: {}", e);
+                panic!("Failed to create GraphBit runtime: {}", e);
             }
-        })
-        .runtime()
+        };
+        GRAPHBIT_RUNTIME.store(runtime, Ordering::Release);
+        FORK_HANDLER.call_once(register_fork_handler);
+        &*runtime
+    } else {
+        // SAFETY: as above
+        unsafe { &*current }
+    };
+    STARTING.store(false, Ordering::Release);
+    runtime
+}
+
+fn pending_config() -> Option<RuntimeConfig> {
+    RUNTIME_CONFIG
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
+
+/// Forget the inherited runtime in a forked child: its worker threads were not
+/// copied by `fork`, so the next use starts a new runtime. The old one is
+/// leaked, as shutting it down would wait for threads that do not exist.
+///
+/// Runs between `fork` and the child's return from it, so it only touches
+/// atomics.
+extern "C" fn reset_runtime_in_child() {
+    if !GRAPHBIT_RUNTIME
+        .swap(std::ptr::null_mut(), Ordering::AcqRel)
+        .is_null()
+    {
+        FORK_RESTARTS.fetch_add(1, Ordering::Relaxed);
+    }
+    STARTING.store(false, Ordering::Release);
+}
+
+#[cfg(unix)]
+fn register_fork_handler() {
+    // SAFETY: the handler only stores to atomics, which is async-signal-safe
+    let result = unsafe { libc::pthread_atfork(None, None, Some(reset_runtime_in_child)) };
+    if result != 0 {
+        warn!(
+            "Failed to register the GraphBit fork handler (error {}); do not use GraphBit in forked processes",
+            result
+        );
+    }
 }
 
-/// Initialize runtime with custom configuration
-pub(crate) fn init_runtime_with_config(config: RuntimeConfig) -> Result<(), std::io::Error> {
-    if GRAPHBIT_RUNTIME.get().is_some() {
-        warn!("Runtime already initialized, ignoring new configuration");
-        return Ok(());
+#[cfg(not(unix))]
+fn register_fork_handler() {}
+
+/// Run `future` to completion on the shared runtime, blocking the calling
+/// thread
+///
+/// Any number of threads may block on the runtime at once. A call from one of
+/// the runtime's own worker threads, e.g. from a Python callback invoked by a
+/// running workflow, blocks in place instead of panicking on a nested
+/// `block_on`. Callers holding the GIL should release it first with
+/// `Python::allow_threads`.
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        _ => get_runtime().block_on(future),
     }
+}
 
-    let runtime = GraphBitRuntime::new(config)?;
-    GRAPHBIT_RUNTIME.set(runtime).map_err(|_| {
-        std::io::Error::new(
+/// Set the configuration the runtime starts with. Fails once the runtime has
+/// started in this process; a forked child may configure it again before its
+/// first use.
+pub(crate) fn set_runtime_config(config: RuntimeConfig) -> Result<(), std::io::Error> {
+    if is_runtime_initialized() {
+        return Err(std::io::Error::new(
             std::io::ErrorKind::AlreadyExists,
             "Runtime already initialized",
-        )
-    })?;
-
+        ));
+    }
+    *RUNTIME_CONFIG
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(config);
     Ok(())
 }
 
+/// Configuration the runtime runs with, or starts with if it has not started
+pub(crate) fn runtime_config() -> RuntimeConfig {
+    current_runtime().map_or_else(
+        || pending_config().unwrap_or_default(),
+        |runtime| runtime.config.clone(),
+    )
+}
+
+fn current_runtime() -> Option<&'static GraphBitRuntime> {
+    let current = GRAPHBIT_RUNTIME.load(Ordering::Acquire);
+    // SAFETY: non-null pointers come from `Box::leak` and are never freed
+    (!current.is_null()).then(|| unsafe { &*current })
+}
+
 /// Get runtime statistics for monitoring
 pub(crate) fn get_runtime_stats() -> Option<RuntimeStats> {
-    GRAPHBIT_RUNTIME.get().map(|runtime| runtime.stats())
+    current_runtime().map(GraphBitRuntime::stats)
+}
+
+/// Number of forks after which this process started a new runtime
+pub(crate) fn fork_restarts() -> usize {
+    FORK_RESTARTS.load(Ordering::Relaxed)
 }
 
 /// Check if runtime is initialized
 pub(crate) fn is_runtime_initialized() -> bool {
-    GRAPHBIT_RUNTIME.get().is_some()
+    current_runtime().is_some()
 }
 
 /// Shutdown runtime gracefully (for testing and cleanup)
 pub(crate) fn shutdown_runtime() {
-    if GRAPHBIT_RUNTIME.get().is_some() {
+    if is_runtime_initialized() {
         info!("Shutting down GraphBit runtime gracefully");
         // Note: In production, runtime shutdown is handled automatically
         // This is primarily for testing scenarios
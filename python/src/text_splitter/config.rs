@@ -1,5 +1,6 @@
 //! Text splitter configuration for GraphBit Python bindings
 
+use crate::errors::invalid_value;
 use graphbit_core::text_splitter::{
     SplitterStrategy as CoreSplitterStrategy, TextSplitterConfig as CoreTextSplitterConfig,
 };
@@ -18,17 +19,13 @@ impl TextSplitterConfig {
     /// Create a character-based splitter configuration
     #[staticmethod]
     #[pyo3(signature = (chunk_size, chunk_overlap=0))]
-    fn character(chunk_size: usize, chunk_overlap: usize) -> PyResult<Self> {
+    pub(crate) fn character(chunk_size: usize, chunk_overlap: usize) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         if chunk_overlap >= chunk_size {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk overlap must be less than chunk size",
-            ));
+            return Err(invalid_value("Chunk overlap must be less than chunk size"));
         }
 
         Ok(Self {
@@ -48,21 +45,17 @@ impl TextSplitterConfig {
     /// Create a token-based splitter configuration
     #[staticmethod]
     #[pyo3(signature = (chunk_size, chunk_overlap=0, token_pattern=None))]
-    fn token(
+    pub(crate) fn token(
         chunk_size: usize,
         chunk_overlap: usize,
         token_pattern: Option<String>,
     ) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         if chunk_overlap >= chunk_size {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk overlap must be less than chunk size",
-            ));
+            return Err(invalid_value("Chunk overlap must be less than chunk size"));
         }
 
         Ok(Self {
@@ -82,16 +75,16 @@ impl TextSplitterConfig {
 
     /// Create a sentence-based splitter configuration
     #[staticmethod]
-    #[pyo3(signature = (chunk_size, chunk_overlap=0, sentence_endings=None))]
-    fn sentence(
+    #[pyo3(signature = (chunk_size, chunk_overlap=0, sentence_endings=None, abbreviations=None, unicode_segmentation=false))]
+    pub(crate) fn sentence(
         chunk_size: usize,
         chunk_overlap: usize,
         sentence_endings: Option<Vec<String>>,
+        abbreviations: Option<Vec<String>>,
+        unicode_segmentation: bool,
     ) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         Ok(Self {
@@ -100,6 +93,8 @@ impl TextSplitterConfig {
                     chunk_size,
                     chunk_overlap,
                     sentence_endings,
+                    abbreviations,
+                    unicode_segmentation,
                 },
                 preserve_word_boundaries: true,
                 trim_whitespace: true,
@@ -112,15 +107,13 @@ impl TextSplitterConfig {
     /// Create a recursive splitter configuration
     #[staticmethod]
     #[pyo3(signature = (chunk_size, chunk_overlap=0, separators=None))]
-    fn recursive(
+    pub(crate) fn recursive(
         chunk_size: usize,
         chunk_overlap: usize,
         separators: Option<Vec<String>>,
     ) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         Ok(Self {
@@ -147,9 +140,7 @@ impl TextSplitterConfig {
         min_paragraph_length: Option<usize>,
     ) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         Ok(Self {
@@ -172,9 +163,7 @@ impl TextSplitterConfig {
     #[pyo3(signature = (chunk_size, chunk_overlap=0, split_by_headers=true))]
     fn markdown(chunk_size: usize, chunk_overlap: usize, split_by_headers: bool) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         Ok(Self {
@@ -197,9 +186,7 @@ impl TextSplitterConfig {
     #[pyo3(signature = (chunk_size, chunk_overlap=0, language=None))]
     fn code(chunk_size: usize, chunk_overlap: usize, language: Option<String>) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         Ok(Self {
@@ -222,15 +209,11 @@ impl TextSplitterConfig {
     #[pyo3(signature = (pattern, chunk_size, chunk_overlap=0))]
     fn regex(pattern: String, chunk_size: usize, chunk_overlap: usize) -> PyResult<Self> {
         if chunk_size == 0 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Chunk size must be greater than 0",
-            ));
+            return Err(invalid_value("Chunk size must be greater than 0"));
         }
 
         if pattern.is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Pattern cannot be empty",
-            ));
+            return Err(invalid_value("Pattern cannot be empty"));
         }
 
         Ok(Self {
@@ -1,11 +1,12 @@
 //! Text splitter implementations for GraphBit Python bindings
 
+use crate::document_loader::PyDocumentContent;
 use crate::errors::to_py_runtime_error;
 use graphbit_core::text_splitter::{
-    CharacterSplitter as CoreCharacterSplitter, RecursiveSplitter as CoreRecursiveSplitter,
-    SentenceSplitter as CoreSentenceSplitter, TextChunk as CoreTextChunk,
-    TextSplitterFactory as CoreTextSplitterFactory, TextSplitterTrait,
-    TokenSplitter as CoreTokenSplitter,
+    CharacterSplitter as CoreCharacterSplitter, DocumentChunkConfig,
+    RecursiveSplitter as CoreRecursiveSplitter, SentenceSplitter as CoreSentenceSplitter,
+    TextChunk as CoreTextChunk, TextSplitterFactory as CoreTextSplitterFactory, TextSplitterTrait,
+    TokenSplitter as CoreTokenSplitter, chunk_document,
 };
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
@@ -238,11 +239,13 @@ pub struct SentenceSplitter {
 impl SentenceSplitter {
     /// Create a new sentence splitter
     #[new]
-    #[pyo3(signature = (chunk_size, chunk_overlap=0, sentence_endings=None))]
+    #[pyo3(signature = (chunk_size, chunk_overlap=0, sentence_endings=None, abbreviations=None, unicode_segmentation=false))]
     fn new(
         chunk_size: usize,
         chunk_overlap: usize,
         sentence_endings: Option<Vec<String>>,
+        abbreviations: Option<Vec<String>>,
+        unicode_segmentation: bool,
     ) -> PyResult<Self> {
         let splitter = if let Some(endings) = sentence_endings {
             let endings_refs: Vec<&str> = endings.iter().map(|s| s.as_str()).collect();
@@ -250,7 +253,9 @@ impl SentenceSplitter {
         } else {
             CoreSentenceSplitter::new(chunk_size, chunk_overlap)
         }
-        .map_err(to_py_runtime_error)?;
+        .map_err(to_py_runtime_error)?
+        .with_abbreviations(abbreviations.unwrap_or_default())
+        .with_unicode_segmentation(unicode_segmentation);
 
         Ok(Self {
             inner: Box::new(splitter),
@@ -409,31 +414,147 @@ impl TextSplitter {
         Ok(Self { inner: splitter })
     }
 
+    /// Create a character-based text splitter
+    #[staticmethod]
+    #[pyo3(signature = (chunk_size, chunk_overlap=0))]
+    fn character(chunk_size: usize, chunk_overlap: usize) -> PyResult<Self> {
+        Self::new(TextSplitterConfig::character(chunk_size, chunk_overlap)?)
+    }
+
+    /// Create a token-based text splitter
+    #[staticmethod]
+    #[pyo3(signature = (chunk_size, chunk_overlap=0, token_pattern=None))]
+    fn token(
+        chunk_size: usize,
+        chunk_overlap: usize,
+        token_pattern: Option<String>,
+    ) -> PyResult<Self> {
+        Self::new(TextSplitterConfig::token(
+            chunk_size,
+            chunk_overlap,
+            token_pattern,
+        )?)
+    }
+
+    /// Create a sentence-based text splitter
+    #[staticmethod]
+    #[pyo3(signature = (chunk_size, chunk_overlap=0, sentence_endings=None, abbreviations=None, unicode_segmentation=false))]
+    fn sentence(
+        chunk_size: usize,
+        chunk_overlap: usize,
+        sentence_endings: Option<Vec<String>>,
+        abbreviations: Option<Vec<String>>,
+        unicode_segmentation: bool,
+    ) -> PyResult<Self> {
+        Self::new(TextSplitterConfig::sentence(
+            chunk_size,
+            chunk_overlap,
+            sentence_endings,
+            abbreviations,
+            unicode_segmentation,
+        )?)
+    }
+
+    /// Create a recursive text splitter
+    #[staticmethod]
+    #[pyo3(signature = (chunk_size, chunk_overlap=0, separators=None))]
+    fn recursive(
+        chunk_size: usize,
+        chunk_overlap: usize,
+        separators: Option<Vec<String>>,
+    ) -> PyResult<Self> {
+        Self::new(TextSplitterConfig::recursive(
+            chunk_size,
+            chunk_overlap,
+            separators,
+        )?)
+    }
+
+    /// Split a loaded document with the splitter suited to its type:
+    /// Markdown for `md`, code for source files, sentences for `pdf`, `docx`
+    /// and `txt`, recursive otherwise. Chunks carry the document's `source`,
+    /// `document_type` and the chosen `splitter` in their metadata.
+    #[staticmethod]
+    #[pyo3(signature = (document, chunk_size=1000, chunk_overlap=200))]
+    fn auto(
+        document: PyRef<'_, PyDocumentContent>,
+        chunk_size: usize,
+        chunk_overlap: usize,
+        py: Python<'_>,
+    ) -> PyResult<Vec<TextChunk>> {
+        let document = &document.inner;
+        let config = DocumentChunkConfig {
+            chunk_size,
+            chunk_overlap,
+        };
+        py.allow_threads(|| {
+            let chunks = chunk_document(document, &config).map_err(to_py_runtime_error)?;
+
+            Ok(chunks
+                .into_iter()
+                .map(|chunk| TextChunk { inner: chunk })
+                .collect())
+        })
+    }
+
     /// Split text into chunks
-    fn split_text(&self, text: &str) -> PyResult<Vec<TextChunk>> {
-        let chunks = self.inner.split_text(text).map_err(to_py_runtime_error)?;
+    fn split_text(&self, text: &str, py: Python<'_>) -> PyResult<Vec<TextChunk>> {
+        py.allow_threads(|| {
+            let chunks = self.inner.split_text(text).map_err(to_py_runtime_error)?;
 
-        Ok(chunks
-            .into_iter()
-            .map(|chunk| TextChunk { inner: chunk })
-            .collect())
+            Ok(chunks
+                .into_iter()
+                .map(|chunk| TextChunk { inner: chunk })
+                .collect())
+        })
     }
 
     /// Split a list of texts
-    fn split_texts(&self, texts: Vec<String>) -> PyResult<Vec<Vec<TextChunk>>> {
+    fn split_texts(&self, texts: Vec<String>, py: Python<'_>) -> PyResult<Vec<Vec<TextChunk>>> {
         let mut all_chunks = Vec::new();
 
         for text in texts {
-            let chunks = self.split_text(&text)?;
+            let chunks = self.split_text(&text, py)?;
             all_chunks.push(chunks);
         }
 
         Ok(all_chunks)
     }
 
+    /// Split a loaded document, tagging every chunk with the document's
+    /// `source` and `document_type` in its metadata
+    fn split_document(
+        &self,
+        document: PyRef<'_, PyDocumentContent>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<TextChunk>> {
+        let document = &document.inner;
+        py.allow_threads(|| {
+            let chunks = self
+                .inner
+                .split_text(&document.content)
+                .map_err(to_py_runtime_error)?;
+
+            Ok(chunks
+                .into_iter()
+                .map(|chunk| TextChunk {
+                    inner: chunk
+                        .with_metadata(
+                            "source".to_string(),
+                            serde_json::Value::String(document.source.clone()),
+                        )
+                        .with_metadata(
+                            "document_type".to_string(),
+                            serde_json::Value::String(document.document_type.clone()),
+                        ),
+                })
+                .collect())
+        })
+    }
+
     /// Create chunks from text and return as list of dictionaries
-    fn create_documents(&self, text: &str) -> PyResult<Vec<HashMap<String, String>>> {
-        let chunks = self.split_text(text)?;
+    fn create_documents(&self, text: &str, py: Python<'_>) -> PyResult<Vec<HashMap<String, String>>> {
+        let chunks = self.split_text(text, py)?;
 
         Ok(chunks
             .into_iter()
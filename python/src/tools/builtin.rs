@@ -0,0 +1,130 @@
+//! Built-in Rust-native tools for GraphBit Python bindings
+//!
+//! Exposes the tools shipped with `graphbit-core` so they can be passed straight
+//! to `Node.agent(tools=[...])`, e.g. `tools=[BuiltinTools.http()]`.
+
+use crate::errors::{invalid_value, to_py_error};
+use crate::runtime::block_on;
+use graphbit_core::tools::{HttpRequestTool, HttpToolConfig, ToolMetadata};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Arc;
+
+/// Core tool wrapped by a [`BuiltinTool`]
+#[derive(Clone)]
+enum BuiltinToolKind {
+    Http(Arc<HttpRequestTool>),
+}
+
+/// A built-in tool that can be attached to agent nodes and called like a function
+#[pyclass]
+#[derive(Clone)]
+pub struct BuiltinTool {
+    kind: BuiltinToolKind,
+}
+
+impl BuiltinTool {
+    /// Metadata advertised to the LLM for this tool
+    pub(crate) fn metadata(&self) -> ToolMetadata {
+        match &self.kind {
+            BuiltinToolKind::Http(tool) => tool.metadata(),
+        }
+    }
+
+    fn run(
+        &self,
+        args: serde_json::Value,
+    ) -> graphbit_core::errors::GraphBitResult<serde_json::Value> {
+        let kind = self.kind.clone();
+        let future = async move {
+            match kind {
+                BuiltinToolKind::Http(tool) => tool.execute(args).await,
+            }
+        };
+
+        // Tool calls issued by the workflow executor run on a runtime worker thread
+        block_on(future)
+    }
+}
+
+#[pymethods]
+impl BuiltinTool {
+    /// Tool name exposed to the model
+    #[getter]
+    fn name(&self) -> String {
+        self.metadata().name
+    }
+
+    /// Tool description exposed to the model
+    #[getter]
+    fn description(&self) -> String {
+        self.metadata().description
+    }
+
+    /// JSON schema of the tool arguments
+    #[getter]
+    fn parameters_schema(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize::pythonize(py, &self.metadata().parameters)?.unbind())
+    }
+
+    /// Invoke the tool with keyword arguments, returning the JSON result as a dict
+    #[pyo3(signature = (**kwargs))]
+    fn __call__(&self, py: Python<'_>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+        let args: serde_json::Value = match kwargs {
+            Some(kwargs) => pythonize::depythonize(kwargs.as_any())?,
+            None => serde_json::Value::Object(serde_json::Map::new()),
+        };
+        let tool = self.clone();
+        let result = py
+            .allow_threads(move || tool.run(args))
+            .map_err(to_py_error)?;
+        Ok(pythonize::pythonize(py, &result)?.unbind())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("BuiltinTool(name='{}')", self.metadata().name)
+    }
+}
+
+/// Factory for the built-in tools
+#[pyclass]
+pub struct BuiltinTools;
+
+#[pymethods]
+impl BuiltinTools {
+    /// HTTP fetch tool (`http_request`) supporting GET/POST, response truncation,
+    /// HTML-to-text extraction and host allow/deny lists.
+    #[staticmethod]
+    #[pyo3(signature = (max_response_bytes=None, allowed_hosts=None, denied_hosts=None, timeout_seconds=None, extract_html_text=true))]
+    fn http(
+        max_response_bytes: Option<usize>,
+        allowed_hosts: Option<Vec<String>>,
+        denied_hosts: Option<Vec<String>>,
+        timeout_seconds: Option<u64>,
+        extract_html_text: bool,
+    ) -> PyResult<BuiltinTool> {
+        let mut config = HttpToolConfig::default().with_html_extraction(extract_html_text);
+        if let Some(max_bytes) = max_response_bytes {
+            if max_bytes == 0 {
+                return Err(invalid_value("max_response_bytes must be greater than 0"));
+            }
+            config = config.with_max_response_bytes(max_bytes);
+        }
+        if let Some(timeout) = timeout_seconds {
+            if timeout == 0 {
+                return Err(invalid_value("timeout_seconds must be greater than 0"));
+            }
+            config = config.with_timeout_seconds(timeout);
+        }
+        if let Some(hosts) = allowed_hosts {
+            config = config.with_allowed_hosts(hosts);
+        }
+        if let Some(hosts) = denied_hosts {
+            config = config.with_denied_hosts(hosts);
+        }
+
+        Ok(BuiltinTool {
+            kind: BuiltinToolKind::Http(Arc::new(HttpRequestTool::new(config))),
+        })
+    }
+}
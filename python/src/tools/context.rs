@@ -0,0 +1,123 @@
+//! Workflow context handed to Python tools declaring a `ctx` parameter
+
+use crate::errors::{invalid_value, to_py_error};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Name of the parameter through which a tool function receives its context
+pub(crate) const CONTEXT_PARAMETER: &str = "ctx";
+
+/// Node outputs and variables of the running workflow, and the scratch space
+/// tools write for later nodes to read as `{{scratch.NAME}}`
+#[pyclass(name = "ToolContext")]
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    pub(crate) inner: graphbit_core::tools::ToolContext,
+}
+
+impl ToolContext {
+    pub(crate) fn new(inner: graphbit_core::tools::ToolContext) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl ToolContext {
+    /// Empty context, for calling a tool outside of a workflow
+    #[new]
+    fn py_new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the agent node calling the tool
+    #[getter]
+    fn node_name(&self) -> &str {
+        self.inner.node_name()
+    }
+
+    /// Outputs of the nodes completed so far, keyed by node id and name
+    #[getter]
+    fn node_outputs<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        pythonize::pythonize(py, self.inner.node_outputs())
+            .map_err(|e| invalid_value(format!("Failed to convert node outputs: {e}")))
+    }
+
+    /// Workflow variables, including run-time inputs
+    #[getter]
+    fn variables<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        pythonize::pythonize(py, self.inner.variables())
+            .map_err(|e| invalid_value(format!("Failed to convert variables: {e}")))
+    }
+
+    /// Output of a node by id or name, or `None`
+    fn node_output<'py>(&self, node: &str, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.inner
+            .node_output(node)
+            .map(|output| pythonize::pythonize(py, output))
+            .transpose()
+            .map_err(|e| invalid_value(format!("Failed to convert node output: {e}")))
+    }
+
+    /// Workflow variable, or `None`
+    fn variable<'py>(&self, name: &str, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.inner
+            .variable(name)
+            .map(|value| pythonize::pythonize(py, value))
+            .transpose()
+            .map_err(|e| invalid_value(format!("Failed to convert variable: {e}")))
+    }
+
+    /// Value of a key such as `"scratch.summary"`, or `default`
+    #[pyo3(signature = (key, default=None))]
+    fn get<'py>(
+        &self,
+        key: &str,
+        default: Option<Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        match self.inner.get(key) {
+            Some(value) => pythonize::pythonize(py, &value)
+                .map(Some)
+                .map_err(|e| invalid_value(format!("Failed to convert '{key}': {e}"))),
+            None => Ok(default),
+        }
+    }
+
+    /// Write a JSON-serializable value to a key such as `"scratch.summary"`
+    fn set(&self, key: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let value = pythonize::depythonize::<serde_json::Value>(value).map_err(|e| {
+            invalid_value(format!("Value for '{key}' is not JSON-serializable: {e}"))
+        })?;
+        self.inner.set(key, value).map_err(to_py_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ToolContext(node_name='{}')", self.inner.node_name())
+    }
+}
+
+/// Whether `func` declares the [`CONTEXT_PARAMETER`]
+pub(crate) fn accepts_context(func: &Bound<'_, PyAny>, py: Python<'_>) -> bool {
+    py.import("inspect")
+        .and_then(|inspect| inspect.call_method1("signature", (func,)))
+        .and_then(|signature| signature.getattr("parameters"))
+        .and_then(|parameters| parameters.contains(CONTEXT_PARAMETER))
+        .unwrap_or(false)
+}
+
+/// Add `context` to the keyword arguments of `func` when it declares the
+/// [`CONTEXT_PARAMETER`]
+pub(crate) fn bind_context(
+    func: &Bound<'_, PyAny>,
+    kwargs: &Bound<'_, PyDict>,
+    context: Option<&graphbit_core::tools::ToolContext>,
+    py: Python<'_>,
+) -> PyResult<()> {
+    if accepts_context(func, py) {
+        kwargs.set_item(
+            CONTEXT_PARAMETER,
+            ToolContext::new(context.cloned().unwrap_or_default()),
+        )?;
+    }
+    Ok(())
+}
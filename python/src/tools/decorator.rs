@@ -3,7 +3,9 @@
 //! This module provides the @tool decorator that converts Python functions into
 //! executable tool calls for node agents with comprehensive introspection and validation.
 
+use crate::errors::{invalid_value, runtime_error};
 use crate::tools::registry::ToolRegistry;
+use crate::tools::schema::function_schema;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use std::sync::{Arc, Mutex, OnceLock};
@@ -75,12 +77,10 @@ impl ToolDecorator {
 
     /// Get the tool registry
     pub fn get_registry(&self) -> PyResult<ToolRegistry> {
-        let _registry_guard = self.registry.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire registry lock: {}",
-                e
-            ))
-        })?;
+        let _registry_guard = self
+            .registry
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?;
 
         // Return a new instance (simplified for now)
         Ok(ToolRegistry::new())
@@ -105,16 +105,13 @@ impl ToolDecorator {
         let desc = description.unwrap_or_else(|| "Manually registered tool".to_string());
 
         // Extract function schema
-        let func_bound = func.bind(py);
-        let params_schema = extract_function_schema(&func_bound, py)?;
-        let _ = func_bound; // Release the borrow
+        let schema = function_schema(func.bind(py), py)?;
+        let params_schema = pythonize::pythonize(py, &schema)?.downcast_into::<PyDict>()?;
 
-        let registry_guard = self.registry.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire registry lock: {}",
-                e
-            ))
-        })?;
+        let registry_guard = self
+            .registry
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?;
 
         registry_guard.register_tool(
             func_name,
@@ -122,6 +119,7 @@ impl ToolDecorator {
             func.clone_ref(py),
             &params_schema,
             return_type,
+            py,
         )?;
 
         Ok(())
@@ -129,24 +127,20 @@ impl ToolDecorator {
 
     /// List all registered tools
     pub fn list_tools(&self) -> PyResult<Vec<String>> {
-        let registry_guard = self.registry.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire registry lock: {}",
-                e
-            ))
-        })?;
+        let registry_guard = self
+            .registry
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?;
 
         registry_guard.list_tools()
     }
 
     /// Get tool metadata
     pub fn get_tool_info(&self, name: &str) -> PyResult<Option<String>> {
-        let registry_guard = self.registry.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire registry lock: {}",
-                e
-            ))
-        })?;
+        let registry_guard = self
+            .registry
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?;
 
         registry_guard.get_tool_metadata(name)
     }
@@ -163,92 +157,93 @@ impl Default for ToolDecorator {
     }
 }
 
-/// Extract function parameter schema using introspection
-fn extract_function_schema<'a>(
-    func: &'a Bound<'a, PyAny>,
-    py: Python<'a>,
-) -> PyResult<Bound<'a, PyDict>> {
-    let inspect = py.import("inspect")?;
-    let signature = inspect.call_method1("signature", (func,))?;
-    let parameters = signature.getattr("parameters")?;
-
-    let schema = PyDict::new(py);
-    schema.set_item("type", "object")?;
-
-    let properties = PyDict::new(py);
-    let required = pyo3::types::PyList::empty(py);
-
-    // Iterate through function parameters
-    for item in parameters.try_iter()? {
-        let (param_name, param) = item?.extract::<(String, PyObject)>()?;
-        let param_name_str = param_name;
-
-        // Skip 'self' and 'cls' parameters
-        if param_name_str == "self" || param_name_str == "cls" {
-            continue;
-        }
-
-        let param_info = PyDict::new(py);
-
-        // Get parameter annotation for type information
-        let param_bound = param.bind(py);
-        let annotation = param_bound.getattr("annotation")?;
-        let param_type =
-            if annotation.is_none() || annotation.to_string() == "<class 'inspect._empty'>" {
-                "string" // Default type
-            } else {
-                map_python_type_to_json_schema(&annotation.to_string())
-            };
-
-        param_info.set_item("type", param_type)?;
-
-        // Check if parameter has a default value
-        let default = param_bound.getattr("default")?;
-        if default.to_string() != "<class 'inspect._empty'>" {
-            param_info.set_item("default", default)?;
-        } else {
-            // Parameter is required
-            required.append(param_name_str.clone())?;
-        }
-
-        // Add description placeholder
-        param_info.set_item("description", format!("Parameter {}", param_name_str))?;
-
-        properties.set_item(param_name_str, param_info)?;
-    }
-
-    schema.set_item("properties", properties)?;
-    schema.set_item("required", required)?;
+/// Attribute set on decorated functions so agent nodes use the registered name and description
+pub(crate) const TOOL_ATTRIBUTE: &str = "__graphbit_tool__";
 
-    Ok(schema)
+/// Decorator returned by [`tool`]: registers the decorated function in the global registry
+#[pyclass]
+pub(crate) struct ToolRegistration {
+    name: Option<String>,
+    description: Option<String>,
+    return_type: Option<String>,
+    schema_override: Option<serde_json::Value>,
 }
 
-/// Map Python type annotations to JSON Schema types
-fn map_python_type_to_json_schema(type_str: &str) -> &'static str {
-    match type_str {
-        s if s.contains("int") => "integer",
-        s if s.contains("float") => "number",
-        s if s.contains("bool") => "boolean",
-        s if s.contains("str") => "string",
-        s if s.contains("list") || s.contains("List") => "array",
-        s if s.contains("dict") || s.contains("Dict") => "object",
-        _ => "string", // Default fallback
+#[pymethods]
+impl ToolRegistration {
+    fn __call__(&self, func: PyObject, py: Python<'_>) -> PyResult<PyObject> {
+        let func_bound = func.bind(py);
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => func_bound.getattr("__name__")?.extract::<String>()?,
+        };
+        let description = self
+            .description
+            .clone()
+            .or_else(|| {
+                func_bound
+                    .getattr("__doc__")
+                    .and_then(|doc| doc.extract::<Option<String>>())
+                    .ok()
+                    .flatten()
+            })
+            .unwrap_or_else(|| "Tool function".to_string());
+
+        let schema = match &self.schema_override {
+            Some(schema) => schema.clone(),
+            None => function_schema(func_bound, py).unwrap_or_else(
+                |_| serde_json::json!({"type": "object", "properties": {}, "required": []}),
+            ),
+        };
+        let params_schema = pythonize::pythonize(py, &schema)?.downcast_into::<PyDict>()?;
+
+        get_global_registry()
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?
+            .register_tool(
+                name.clone(),
+                description.clone(),
+                func.clone_ref(py),
+                &params_schema,
+                self.return_type.clone(),
+                py,
+            )?;
+
+        let info = PyDict::new(py);
+        info.set_item("name", name)?;
+        info.set_item("description", description)?;
+        info.set_item("parameters", &params_schema)?;
+        // Builtins and other objects without a writable __dict__ are still registered
+        let _ = func_bound.setattr(TOOL_ATTRIBUTE, info);
+
+        Ok(func)
     }
 }
 
-/// Convenience function to create a tool decorator
+/// Decorator registering a function as a tool in the global tool registry.
+///
+/// The parameter schema is generated from the function's type hints and
+/// docstring; `schema_override` replaces it with a JSON Schema of its own.
+/// `_description` is accepted as an alias of `description` for compatibility.
 #[pyfunction]
-#[pyo3(signature = (_description=None))]
-pub(crate) fn tool(_description: Option<String>, py: Python<'_>) -> PyResult<PyObject> {
-    // Create a simple decorator that just returns the function
-    // The actual registration happens when the node processes the tools
-    let decorator_func = py.eval(
-        c"lambda func: func", // Simple pass-through decorator
-        None,
-        None,
-    )?;
-
-    Ok(decorator_func.into_pyobject(py)?.into_any().unbind())
+#[pyo3(signature = (_description=None, name=None, description=None, return_type=None, schema_override=None))]
+pub(crate) fn tool(
+    _description: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    return_type: Option<String>,
+    schema_override: Option<&Bound<'_, PyDict>>,
+) -> PyResult<ToolRegistration> {
+    let schema_override = schema_override
+        .map(|schema| pythonize::depythonize::<serde_json::Value>(schema))
+        .transpose()
+        .map_err(|e| invalid_value(format!("schema_override must be a JSON Schema object: {e}")))?;
+    Ok(ToolRegistration {
+        name,
+        description: description.or(_description),
+        return_type,
+        schema_override,
+    })
 }
 
 /// Get the global tool registry
@@ -262,12 +257,9 @@ pub(crate) fn get_tool_registry() -> PyResult<ToolRegistry> {
 #[pyfunction]
 pub(crate) fn clear_tools() -> PyResult<()> {
     let registry = get_global_registry();
-    let _registry_guard = registry.lock().map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-            "Failed to acquire registry lock: {}",
-            e
-        ))
-    })?;
+    let _registry_guard = registry
+        .lock()
+        .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?;
 
     // Clear would need to be implemented in ToolRegistry
     Ok(())
@@ -3,6 +3,7 @@
 //! This module provides the core execution engine that handles sequential tool calls,
 //! stores results, and integrates with the LLM agent system.
 
+use crate::errors::{invalid_value, runtime_error};
 use crate::tools::registry::ToolRegistry;
 use crate::tools::result::{ToolResult, ToolResultCollection};
 use graphbit_core::llm::LlmToolCall;
@@ -49,30 +50,18 @@ impl std::error::Error for ToolExecutionError {}
 impl From<ToolExecutionError> for PyErr {
     fn from(err: ToolExecutionError) -> Self {
         match err {
-            ToolExecutionError::RegistryLockError(msg) => {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(msg)
-            }
-            ToolExecutionError::ContextLockError(msg) => {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(msg)
-            }
+            ToolExecutionError::RegistryLockError(msg) => runtime_error(msg),
+            ToolExecutionError::ContextLockError(msg) => runtime_error(msg),
             ToolExecutionError::ToolNotFound(msg) => {
                 PyErr::new::<pyo3::exceptions::PyKeyError, _>(msg)
             }
-            ToolExecutionError::InvalidParameters(msg) => {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(msg)
-            }
+            ToolExecutionError::InvalidParameters(msg) => invalid_value(msg),
             ToolExecutionError::ExecutionTimeout(msg) => {
-                PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(msg)
-            }
-            ToolExecutionError::MaxCallsExceeded(msg) => {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(msg)
-            }
-            ToolExecutionError::ValidationError(msg) => {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(msg)
-            }
-            ToolExecutionError::SerializationError(msg) => {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(msg)
+                crate::errors::py_exceptions::TimeoutError::new_err(msg)
             }
+            ToolExecutionError::MaxCallsExceeded(msg) => runtime_error(msg),
+            ToolExecutionError::ValidationError(msg) => invalid_value(msg),
+            ToolExecutionError::SerializationError(msg) => invalid_value(msg),
         }
     }
 }
@@ -337,12 +326,10 @@ impl ToolExecutor {
             if self.config.store_results {
                 results.add(result.clone());
 
-                let mut context = self.execution_context.lock().map_err(|e| {
-                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                        "Failed to acquire context lock: {}",
-                        e
-                    ))
-                })?;
+                let mut context = self
+                    .execution_context
+                    .lock()
+                    .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
                 context.add_result(result.clone());
 
                 // Store result as variable for next tools
@@ -376,41 +363,31 @@ impl ToolExecutor {
 
     /// Get execution results
     pub fn get_results(&self) -> PyResult<ToolResultCollection> {
-        let context = self.execution_context.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire context lock: {}",
-                e
-            ))
-        })?;
+        let context = self
+            .execution_context
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
 
         Ok(context.results.clone())
     }
 
     /// Get execution variables
     pub fn get_variables(&self) -> PyResult<String> {
-        let context = self.execution_context.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire context lock: {}",
-                e
-            ))
-        })?;
+        let context = self
+            .execution_context
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
 
-        serde_json::to_string(&context.variables).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to serialize variables: {}",
-                e
-            ))
-        })
+        serde_json::to_string(&context.variables)
+            .map_err(|e| invalid_value(format!("Failed to serialize variables: {}", e)))
     }
 
     /// Set execution variable
     pub fn set_variable(&self, key: String, value: &Bound<'_, PyAny>) -> PyResult<()> {
-        let mut context = self.execution_context.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire context lock: {}",
-                e
-            ))
-        })?;
+        let mut context = self
+            .execution_context
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
 
         let json_value = self.python_to_json_value(value)?;
         context.set_variable(key, json_value);
@@ -420,12 +397,10 @@ impl ToolExecutor {
 
     /// Get execution variable
     pub fn get_variable(&self, key: &str) -> PyResult<Option<String>> {
-        let context = self.execution_context.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire context lock: {}",
-                e
-            ))
-        })?;
+        let context = self
+            .execution_context
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
 
         if let Some(value) = context.get_variable(key) {
             Ok(Some(value.to_string()))
@@ -436,12 +411,10 @@ impl ToolExecutor {
 
     /// Clear execution context
     pub fn clear_context(&self) -> PyResult<()> {
-        let mut context = self.execution_context.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire context lock: {}",
-                e
-            ))
-        })?;
+        let mut context = self
+            .execution_context
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
 
         context.reset();
         Ok(())
@@ -449,12 +422,10 @@ impl ToolExecutor {
 
     /// Get execution statistics
     pub fn get_execution_stats(&self) -> PyResult<String> {
-        let context = self.execution_context.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire context lock: {}",
-                e
-            ))
-        })?;
+        let context = self
+            .execution_context
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire context lock: {}", e)))?;
 
         let elapsed_ms = context
             .start_time
@@ -471,12 +442,8 @@ impl ToolExecutor {
             "failed_results": context.results.failure_count()
         });
 
-        serde_json::to_string_pretty(&stats).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to serialize stats: {}",
-                e
-            ))
-        })
+        serde_json::to_string_pretty(&stats)
+            .map_err(|e| invalid_value(format!("Failed to serialize stats: {}", e)))
     }
 
     /// String representation
@@ -492,12 +459,10 @@ impl ToolExecutor {
         tool_call: LlmToolCall,
         py: Python<'_>,
     ) -> PyResult<ToolResult> {
-        let registry = self.registry.lock().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire registry lock: {}",
-                e
-            ))
-        })?;
+        let registry = self
+            .registry
+            .lock()
+            .map_err(|e| runtime_error(format!("Failed to acquire registry lock: {}", e)))?;
 
         // Convert parameters to PyDict
         let params = self.json_to_pydict(&tool_call.parameters, py)?;
@@ -605,12 +570,8 @@ impl ToolExecutor {
 
         // Try to extract as JSON string
         if let Ok(json_str) = tool_call_obj.extract::<String>() {
-            return serde_json::from_str(&json_str).map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to parse tool call JSON: {}",
-                    e
-                ))
-            });
+            return serde_json::from_str(&json_str)
+                .map_err(|e| invalid_value(format!("Failed to parse tool call JSON: {}", e)));
         }
 
         Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
@@ -686,9 +647,7 @@ impl ToolExecutor {
             if let Some(num) = serde_json::Number::from_f64(f) {
                 Ok(Value::Number(num))
             } else {
-                Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    "Invalid floating point number",
-                ))
+                Err(invalid_value("Invalid floating point number"))
             }
         } else if let Ok(s) = value.extract::<String>() {
             Ok(Value::String(s))
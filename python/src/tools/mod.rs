@@ -2,17 +2,23 @@
 //!
 //! This module provides a comprehensive tool decorator system that converts Python functions
 //! into executable tool calls for node agents. Features include:
-//! - Function introspection and JSON schema generation
+//! - JSON schema generation from type hints and docstrings
 //! - Sequential tool execution with result storage
 //! - Integration with LLM agents
 //! - Comprehensive error handling and validation
 //! - Thread-safe tool registry
+//! - Workflow context access for tools declaring a `ctx` parameter
 
+pub(crate) mod builtin;
+pub(crate) mod context;
 pub(crate) mod decorator;
 pub(crate) mod executor;
 pub(crate) mod registry;
 pub(crate) mod result;
+pub(crate) mod schema;
 
+pub use builtin::{BuiltinTool, BuiltinTools};
+pub use context::ToolContext;
 pub use decorator::ToolDecorator;
 pub use executor::ToolExecutor;
 pub use registry::ToolRegistry;
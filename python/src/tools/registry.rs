@@ -1,7 +1,15 @@
 //! Thread-safe tool registry for GraphBit Python bindings
+//!
+//! Registrations are forwarded to a [`graphbit_core::tools::ToolRegistry`], so
+//! Python functions and Rust-native tools live in the same registry; this type
+//! adds per-tool call statistics and an execution history on top.
 
+use crate::errors::{invalid_value, runtime_error, to_py_error};
+use crate::tools::context::bind_context;
 use crate::tools::result::ToolResult;
+use crate::tools::schema::coerce_arguments;
 use graphbit_core::llm::LlmTool;
+use graphbit_core::tools::{ContextToolHandler, ToolRegistry as CoreToolRegistry};
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use serde::{Deserialize, Serialize};
@@ -62,6 +70,7 @@ impl ToolMetadata {
 #[pyclass]
 #[derive(Clone)]
 pub struct ToolRegistry {
+    core: CoreToolRegistry,
     tools: Arc<RwLock<HashMap<String, PyObject>>>,
     metadata: Arc<RwLock<HashMap<String, ToolMetadata>>>,
     execution_history: Arc<RwLock<Vec<ToolResult>>>,
@@ -73,6 +82,7 @@ impl ToolRegistry {
     #[new]
     pub fn new() -> Self {
         Self {
+            core: CoreToolRegistry::new(),
             tools: Arc::new(RwLock::new(HashMap::new())),
             metadata: Arc::new(RwLock::new(HashMap::new())),
             execution_history: Arc::new(RwLock::new(Vec::new())),
@@ -89,6 +99,7 @@ impl ToolRegistry {
 
         // Return a clone that shares the same underlying data
         Self {
+            core: registry_guard.core.clone(),
             tools: registry_guard.tools.clone(),
             metadata: registry_guard.metadata.clone(),
             execution_history: registry_guard.execution_history.clone(),
@@ -103,22 +114,31 @@ impl ToolRegistry {
         function: PyObject,
         parameters_schema: &Bound<'_, PyDict>,
         return_type: Option<String>,
+        py: Python<'_>,
     ) -> PyResult<()> {
         // Validate inputs
         if name.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Tool name cannot be empty",
-            ));
+            return Err(invalid_value("Tool name cannot be empty"));
         }
 
         if description.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Tool description cannot be empty",
-            ));
+            return Err(invalid_value("Tool description cannot be empty"));
         }
 
         // Convert parameters schema to JSON
-        let schema_json = python_dict_to_json(parameters_schema)?;
+        let schema_json = pythonize::depythonize::<serde_json::Value>(parameters_schema.as_any())
+            .or_else(|_| python_dict_to_json(parameters_schema))?;
+
+        self.core
+            .register_context_tool(
+                graphbit_core::tools::ToolMetadata::new(
+                    name.clone(),
+                    description.clone(),
+                    schema_json.clone(),
+                ),
+                python_tool_handler(Arc::new(function.clone_ref(py))),
+            )
+            .map_err(to_py_error)?;
 
         // Create metadata
         let metadata = ToolMetadata::new(
@@ -130,22 +150,18 @@ impl ToolRegistry {
 
         // Store tool and metadata
         {
-            let mut tools = self.tools.write().map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                    "Failed to acquire tools lock: {}",
-                    e
-                ))
-            })?;
+            let mut tools = self
+                .tools
+                .write()
+                .map_err(|e| runtime_error(format!("Failed to acquire tools lock: {}", e)))?;
             tools.insert(name.clone(), function);
         }
 
         {
-            let mut meta = self.metadata.write().map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                    "Failed to acquire metadata lock: {}",
-                    e
-                ))
-            })?;
+            let mut meta = self
+                .metadata
+                .write()
+                .map_err(|e| runtime_error(format!("Failed to acquire metadata lock: {}", e)))?;
             meta.insert(name, metadata);
         }
 
@@ -154,64 +170,43 @@ impl ToolRegistry {
 
     /// Unregister a tool
     pub fn unregister_tool(&self, name: &str) -> PyResult<bool> {
-        let mut tools = self.tools.write().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire tools lock: {}",
-                e
-            ))
-        })?;
-
-        let mut metadata = self.metadata.write().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire metadata lock: {}",
-                e
-            ))
-        })?;
+        let mut tools = self
+            .tools
+            .write()
+            .map_err(|e| runtime_error(format!("Failed to acquire tools lock: {}", e)))?;
+
+        let mut metadata = self
+            .metadata
+            .write()
+            .map_err(|e| runtime_error(format!("Failed to acquire metadata lock: {}", e)))?;
 
+        let removed_core = self.core.unregister_tool(name);
         let removed_tool = tools.remove(name).is_some();
         let removed_meta = metadata.remove(name).is_some();
 
-        Ok(removed_tool || removed_meta)
+        Ok(removed_core || removed_tool || removed_meta)
     }
 
     /// Check if a tool is registered
     pub fn has_tool(&self, name: &str) -> PyResult<bool> {
-        let tools = self.tools.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire tools lock: {}",
-                e
-            ))
-        })?;
-        Ok(tools.contains_key(name))
+        Ok(self.core.has_tool(name))
     }
 
     /// Get list of registered tool names
     pub fn list_tools(&self) -> PyResult<Vec<String>> {
-        let tools = self.tools.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire tools lock: {}",
-                e
-            ))
-        })?;
-        Ok(tools.keys().cloned().collect())
+        Ok(self.core.tool_names())
     }
 
     /// Get tool metadata
     pub fn get_tool_metadata(&self, name: &str) -> PyResult<Option<String>> {
-        let metadata = self.metadata.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire metadata lock: {}",
-                e
-            ))
-        })?;
+        let metadata = self
+            .metadata
+            .read()
+            .map_err(|e| runtime_error(format!("Failed to acquire metadata lock: {}", e)))?;
 
         if let Some(meta) = metadata.get(name) {
-            let json = serde_json::to_string(meta).map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to serialize metadata: {}",
-                    e
-                ))
-            })?;
+            let json = serde_json::to_string(meta)
+                .map_err(|e| invalid_value(format!("Failed to serialize metadata: {}", e)))?;
             Ok(Some(json))
         } else {
             Ok(None)
@@ -220,12 +215,10 @@ impl ToolRegistry {
 
     /// Get all tools as LlmTool format for agent integration
     pub fn get_llm_tools(&self) -> PyResult<Vec<String>> {
-        let metadata = self.metadata.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire metadata lock: {}",
-                e
-            ))
-        })?;
+        let metadata = self
+            .metadata
+            .read()
+            .map_err(|e| runtime_error(format!("Failed to acquire metadata lock: {}", e)))?;
 
         let mut llm_tools = Vec::new();
         for meta in metadata.values() {
@@ -235,12 +228,8 @@ impl ToolRegistry {
                 meta.parameters_schema.clone(),
             );
 
-            let json = serde_json::to_string(&llm_tool).map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Failed to serialize LlmTool: {}",
-                    e
-                ))
-            })?;
+            let json = serde_json::to_string(&llm_tool)
+                .map_err(|e| invalid_value(format!("Failed to serialize LlmTool: {}", e)))?;
             llm_tools.push(json);
         }
 
@@ -253,17 +242,86 @@ impl ToolRegistry {
         name: &str,
         params: &Bound<'_, PyDict>,
         py: Python<'_>,
+    ) -> PyResult<ToolResult> {
+        self.execute_tool_with_context(name, params, None, py)
+    }
+
+    /// Get execution history
+    pub fn get_execution_history(&self) -> PyResult<Vec<ToolResult>> {
+        let history = self
+            .execution_history
+            .read()
+            .map_err(|e| runtime_error(format!("Failed to acquire history lock: {}", e)))?;
+        Ok(history.clone())
+    }
+
+    /// Clear execution history
+    pub fn clear_history(&self) -> PyResult<()> {
+        let mut history = self
+            .execution_history
+            .write()
+            .map_err(|e| runtime_error(format!("Failed to acquire history lock: {}", e)))?;
+        history.clear();
+        Ok(())
+    }
+
+    /// Get registry statistics
+    pub fn get_stats(&self) -> PyResult<String> {
+        let metadata = self
+            .metadata
+            .read()
+            .map_err(|e| runtime_error(format!("Failed to acquire metadata lock: {}", e)))?;
+
+        let history = self
+            .execution_history
+            .read()
+            .map_err(|e| runtime_error(format!("Failed to acquire history lock: {}", e)))?;
+
+        let total_tools = metadata.len();
+        let total_executions = history.len();
+        let successful_executions = history.iter().filter(|r| r.success).count();
+        let success_rate = if total_executions > 0 {
+            (successful_executions as f64 / total_executions as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let stats = serde_json::json!({
+            "total_tools": total_tools,
+            "total_executions": total_executions,
+            "successful_executions": successful_executions,
+            "success_rate": success_rate,
+            "tools": metadata.values().collect::<Vec<_>>()
+        });
+
+        serde_json::to_string_pretty(&stats)
+            .map_err(|e| invalid_value(format!("Failed to serialize stats: {}", e)))
+    }
+
+    /// String representation
+    pub fn __repr__(&self) -> PyResult<String> {
+        Ok(format!("ToolRegistry(tools={})", self.core.len()))
+    }
+}
+
+impl ToolRegistry {
+    /// Execute a tool by name with parameters, passing `context` to a tool
+    /// declaring a `ctx` parameter
+    pub(crate) fn execute_tool_with_context(
+        &self,
+        name: &str,
+        params: &Bound<'_, PyDict>,
+        context: Option<&graphbit_core::tools::ToolContext>,
+        py: Python<'_>,
     ) -> PyResult<ToolResult> {
         let start_time = Instant::now();
 
         // Get the tool function
         let function = {
-            let tools = self.tools.read().map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                    "Failed to acquire tools lock: {}",
-                    e
-                ))
-            })?;
+            let tools = self
+                .tools
+                .read()
+                .map_err(|e| runtime_error(format!("Failed to acquire tools lock: {}", e)))?;
 
             tools.get(name).map(|f| f.clone_ref(py)).ok_or_else(|| {
                 PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!("Tool '{}' not found", name))
@@ -273,8 +331,13 @@ impl ToolRegistry {
         // Convert parameters to string for logging
         let params_str = params.repr()?.to_string();
 
+        let kwargs = params.copy()?;
+        bind_context(function.bind(py), &kwargs, context, py)?;
+
         // Execute the tool
-        let result = match function.call(py, (), Some(params)) {
+        let result = match coerce_arguments(function.bind(py), &kwargs, py)
+            .and_then(|()| function.call(py, (), Some(&kwargs)))
+        {
             Ok(result) => {
                 let duration = start_time.elapsed().as_millis() as u64;
                 let output = result.to_string();
@@ -306,91 +369,12 @@ impl ToolRegistry {
         Ok(result)
     }
 
-    /// Get execution history
-    pub fn get_execution_history(&self) -> PyResult<Vec<ToolResult>> {
-        let history = self.execution_history.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire history lock: {}",
-                e
-            ))
-        })?;
-        Ok(history.clone())
-    }
-
-    /// Clear execution history
-    pub fn clear_history(&self) -> PyResult<()> {
-        let mut history = self.execution_history.write().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire history lock: {}",
-                e
-            ))
-        })?;
-        history.clear();
-        Ok(())
-    }
-
-    /// Get registry statistics
-    pub fn get_stats(&self) -> PyResult<String> {
-        let metadata = self.metadata.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire metadata lock: {}",
-                e
-            ))
-        })?;
-
-        let history = self.execution_history.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire history lock: {}",
-                e
-            ))
-        })?;
-
-        let total_tools = metadata.len();
-        let total_executions = history.len();
-        let successful_executions = history.iter().filter(|r| r.success).count();
-        let success_rate = if total_executions > 0 {
-            (successful_executions as f64 / total_executions as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let stats = serde_json::json!({
-            "total_tools": total_tools,
-            "total_executions": total_executions,
-            "successful_executions": successful_executions,
-            "success_rate": success_rate,
-            "tools": metadata.values().collect::<Vec<_>>()
-        });
-
-        serde_json::to_string_pretty(&stats).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to serialize stats: {}",
-                e
-            ))
-        })
-    }
-
-    /// String representation
-    pub fn __repr__(&self) -> PyResult<String> {
-        let tools = self.tools.read().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire tools lock: {}",
-                e
-            ))
-        })?;
-        Ok(format!("ToolRegistry(tools={})", tools.len()))
-    }
-}
-
-impl ToolRegistry {
     /// Record tool execution in metadata (internal method)
     fn record_tool_execution(&self, name: &str, duration_ms: u64) -> PyResult<()> {
-        let mut metadata = self.metadata.write().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire metadata lock: {}",
-                e
-            ))
-        })?;
+        let mut metadata = self
+            .metadata
+            .write()
+            .map_err(|e| runtime_error(format!("Failed to acquire metadata lock: {}", e)))?;
 
         if let Some(meta) = metadata.get_mut(name) {
             meta.record_call(duration_ms);
@@ -401,12 +385,10 @@ impl ToolRegistry {
 
     /// Add result to execution history (internal method)
     fn add_to_history(&self, result: ToolResult) -> PyResult<()> {
-        let mut history = self.execution_history.write().map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                "Failed to acquire history lock: {}",
-                e
-            ))
-        })?;
+        let mut history = self
+            .execution_history
+            .write()
+            .map_err(|e| runtime_error(format!("Failed to acquire history lock: {}", e)))?;
 
         history.push(result);
 
@@ -426,6 +408,40 @@ impl Default for ToolRegistry {
     }
 }
 
+/// Wrap a Python callable as a core tool handler.
+///
+/// The JSON arguments are passed as keyword arguments, along with the tool
+/// context when the function declares a `ctx` parameter; the return value is
+/// converted back to JSON, falling back to its string representation.
+fn python_tool_handler(function: Arc<PyObject>) -> ContextToolHandler {
+    Arc::new(move |args: serde_json::Value, context| {
+        let function = function.clone();
+        Box::pin(async move {
+            Python::with_gil(|py| {
+                let kwargs = match &args {
+                    serde_json::Value::Object(_) => {
+                        pythonize::pythonize(py, &args)?.downcast_into::<PyDict>()?
+                    }
+                    _ => PyDict::new(py),
+                };
+                bind_context(function.bind(py), &kwargs, Some(&context), py)?;
+                coerce_arguments(function.bind(py), &kwargs, py)?;
+                let result = function.call(py, (), Some(&kwargs))?;
+                let result = result.bind(py);
+                Ok::<_, PyErr>(
+                    pythonize::depythonize::<serde_json::Value>(result)
+                        .unwrap_or_else(|_| serde_json::Value::String(result.to_string())),
+                )
+            })
+            .map_err(|e| {
+                graphbit_core::errors::GraphBitError::workflow_execution(format!(
+                    "Tool execution failed: {e}"
+                ))
+            })
+        })
+    })
+}
+
 /// Helper function to convert Python dict to JSON
 fn python_dict_to_json(dict: &Bound<'_, PyDict>) -> PyResult<serde_json::Value> {
     let mut map = serde_json::Map::new();
@@ -1,5 +1,6 @@
 //! Tool execution result management for GraphBit Python bindings
 
+use crate::errors::invalid_value;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -124,23 +125,15 @@ impl ToolResult {
 
     /// Convert to JSON string representation
     pub fn to_json(&self) -> PyResult<String> {
-        serde_json::to_string(self).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to serialize tool result: {}",
-                e
-            ))
-        })
+        serde_json::to_string(self)
+            .map_err(|e| invalid_value(format!("Failed to serialize tool result: {}", e)))
     }
 
     /// Create from JSON string
     #[staticmethod]
     pub fn from_json(json_str: &str) -> PyResult<Self> {
-        serde_json::from_str(json_str).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to deserialize tool result: {}",
-                e
-            ))
-        })
+        serde_json::from_str(json_str)
+            .map_err(|e| invalid_value(format!("Failed to deserialize tool result: {}", e)))
     }
 
     /// String representation for debugging
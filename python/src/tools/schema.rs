@@ -0,0 +1,678 @@
+//! JSON Schema for Python tool parameters, built from type hints and docstrings
+//!
+//! Annotations are resolved with `typing.get_type_hints`, so string and
+//! `from __future__ import annotations` hints work. Dataclass and pydantic
+//! parameters become nested object schemas, and the dictionaries the model
+//! sends for them are turned back into instances by [`coerce_arguments`]
+//! before the tool runs.
+
+use crate::tools::context::CONTEXT_PARAMETER;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString, PyTuple, PyType};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// Nesting depth past which dataclasses are described as plain objects, so
+/// self-referencing types terminate
+const MAX_DEPTH: usize = 16;
+
+/// Parameter schema of `func`: one property per parameter, typed from its
+/// annotation and described by its docstring. Parameters without a default
+/// are required; `self`, `cls`, the tool context and `*args`/`**kwargs` are
+/// left out.
+pub(crate) fn function_schema(func: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<Value> {
+    let inspect = py.import("inspect")?;
+    let parameter = inspect.getattr("Parameter")?;
+    let empty = parameter.getattr("empty")?;
+    let variadic = [
+        parameter.getattr("VAR_POSITIONAL")?,
+        parameter.getattr("VAR_KEYWORD")?,
+    ];
+    let parameters = inspect
+        .call_method1("signature", (func,))?
+        .getattr("parameters")?;
+    let hints = type_hints(func, py);
+    let descriptions = parse_docstring_parameters(&docstring(func));
+
+    let mut builder = SchemaBuilder::new(py)?;
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for item in parameters.call_method0("items")?.try_iter()? {
+        let (name, param): (String, Bound<'_, PyAny>) = item?.extract()?;
+        let kind = param.getattr("kind")?;
+        if name == "self"
+            || name == "cls"
+            || name == CONTEXT_PARAMETER
+            || variadic.iter().any(|v| kind.eq(v).unwrap_or(false))
+        {
+            continue;
+        }
+
+        let annotation = match hints.get_item(&name)? {
+            Some(hint) => hint,
+            None => param.getattr("annotation")?,
+        };
+        let mut schema = if annotation.is(&empty) {
+            json!({"type": "string"})
+        } else {
+            builder.schema(&annotation)?
+        };
+        let description = descriptions
+            .get(&name)
+            .cloned()
+            .unwrap_or_else(|| format!("Parameter {name}"));
+        schema["description"] = Value::String(description);
+
+        let default = param.getattr("default")?;
+        if default.is(&empty) {
+            required.push(name.clone());
+        } else if let Ok(default) = pythonize::depythonize::<Value>(&default) {
+            schema["default"] = default;
+        }
+        properties.insert(name, schema);
+    }
+
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required
+    });
+    if !builder.defs.is_empty() {
+        schema["$defs"] = Value::Object(builder.defs);
+    }
+    Ok(schema)
+}
+
+/// Replace the arguments in `kwargs` annotated with a dataclass or pydantic
+/// model, also inside lists, dicts and `Optional`, by instances built from
+/// the dictionaries the model sent
+pub(crate) fn coerce_arguments(
+    func: &Bound<'_, PyAny>,
+    kwargs: &Bound<'_, PyDict>,
+    py: Python<'_>,
+) -> PyResult<()> {
+    let hints = type_hints(func, py);
+    if hints.is_empty() {
+        return Ok(());
+    }
+    let annotations = Annotations::new(py)?;
+    for (name, value) in kwargs.copy()?.iter() {
+        if let Some(annotation) = hints.get_item(&name)? {
+            kwargs.set_item(name, annotations.coerce(&value, &annotation)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolved type hints of `func`, or an empty dict when they cannot be
+/// resolved (the raw signature annotations are used instead)
+fn type_hints<'py>(func: &Bound<'py, PyAny>, py: Python<'py>) -> Bound<'py, PyDict> {
+    py.import("typing")
+        .and_then(|typing| typing.call_method1("get_type_hints", (func,)))
+        .and_then(|hints| Ok(hints.downcast_into::<PyDict>()?))
+        .unwrap_or_else(|_| PyDict::new(py))
+}
+
+fn docstring(obj: &Bound<'_, PyAny>) -> String {
+    obj.getattr("__doc__")
+        .and_then(|doc| doc.extract::<Option<String>>())
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// `typing` and builtins objects annotations are compared against
+struct Annotations<'py> {
+    py: Python<'py>,
+    typing: Bound<'py, PyModule>,
+    builtins: Bound<'py, PyModule>,
+    dataclasses: Bound<'py, PyModule>,
+    none_type: Bound<'py, PyAny>,
+    union_type: Option<Bound<'py, PyAny>>,
+    enum_type: Bound<'py, PyAny>,
+}
+
+impl<'py> Annotations<'py> {
+    fn new(py: Python<'py>) -> PyResult<Self> {
+        let builtins = py.import("builtins")?;
+        Ok(Self {
+            py,
+            typing: py.import("typing")?,
+            none_type: builtins.getattr("type")?.call1((py.None(),))?,
+            builtins,
+            dataclasses: py.import("dataclasses")?,
+            // `X | Y` unions only exist on Python 3.10+
+            union_type: py.import("types")?.getattr("UnionType").ok(),
+            enum_type: py.import("enum")?.getattr("Enum")?,
+        })
+    }
+
+    fn is_builtin(&self, annotation: &Bound<'py, PyAny>, name: &str) -> bool {
+        self.builtins
+            .getattr(name)
+            .is_ok_and(|builtin| annotation.is(&builtin))
+    }
+
+    fn is_typing(&self, annotation: &Bound<'py, PyAny>, name: &str) -> bool {
+        self.typing
+            .getattr(name)
+            .is_ok_and(|special| annotation.is(&special))
+    }
+
+    /// Origin and arguments of a generic alias such as `list[int]`
+    fn generic(
+        &self,
+        annotation: &Bound<'py, PyAny>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let origin = self.typing.call_method1("get_origin", (annotation,))?;
+        let args = self
+            .typing
+            .call_method1("get_args", (annotation,))?
+            .downcast_into::<PyTuple>()?;
+        Ok((origin, args))
+    }
+
+    fn is_union(&self, origin: &Bound<'py, PyAny>) -> bool {
+        self.is_typing(origin, "Union")
+            || self
+                .union_type
+                .as_ref()
+                .is_some_and(|union| origin.is(union))
+    }
+
+    fn is_dataclass(&self, annotation: &Bound<'py, PyAny>) -> bool {
+        annotation.is_instance_of::<PyType>()
+            && self
+                .dataclasses
+                .call_method1("is_dataclass", (annotation,))
+                .and_then(|result| result.is_truthy())
+                .unwrap_or(false)
+    }
+
+    fn is_pydantic_model(annotation: &Bound<'py, PyAny>) -> bool {
+        annotation.is_instance_of::<PyType>()
+            && annotation.hasattr("model_json_schema").unwrap_or(false)
+            && annotation.hasattr("model_validate").unwrap_or(false)
+    }
+
+    fn is_enum(&self, annotation: &Bound<'py, PyAny>) -> bool {
+        annotation
+            .downcast::<PyType>()
+            .is_ok_and(|ty| ty.is_subclass(&self.enum_type).unwrap_or(false))
+    }
+
+    /// `value` converted to the type `annotation` describes, where that type
+    /// is built from a dict (dataclasses, pydantic models) or a plain value
+    /// (enums); anything else is returned unchanged
+    fn coerce(
+        &self,
+        value: &Bound<'py, PyAny>,
+        annotation: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if value.is_none() {
+            return Ok(value.clone());
+        }
+
+        let (origin, args) = self.generic(annotation)?;
+        if !origin.is_none() {
+            if self.is_union(&origin) {
+                let members: Vec<_> = args.iter().filter(|a| !a.is(&self.none_type)).collect();
+                return match members.as_slice() {
+                    [member] => self.coerce(value, member),
+                    _ => Ok(value.clone()),
+                };
+            }
+            if self.is_builtin(&origin, "list") && args.len() == 1 {
+                if let Ok(items) = value.downcast::<PyList>() {
+                    let item_type = args.get_item(0)?;
+                    let coerced = items
+                        .iter()
+                        .map(|item| self.coerce(&item, &item_type))
+                        .collect::<PyResult<Vec<_>>>()?;
+                    return Ok(PyList::new(self.py, coerced)?.into_any());
+                }
+            }
+            if self.is_builtin(&origin, "dict") && args.len() == 2 {
+                if let Ok(entries) = value.downcast::<PyDict>() {
+                    let value_type = args.get_item(1)?;
+                    let coerced = PyDict::new(self.py);
+                    for (key, entry) in entries.iter() {
+                        coerced.set_item(key, self.coerce(&entry, &value_type)?)?;
+                    }
+                    return Ok(coerced.into_any());
+                }
+            }
+            return Ok(value.clone());
+        }
+
+        if let Ok(fields) = value.downcast::<PyDict>() {
+            if self.is_dataclass(annotation) {
+                let hints = type_hints(annotation, self.py);
+                let kwargs = fields.copy()?;
+                for (name, field) in fields.iter() {
+                    if let Some(field_type) = hints.get_item(&name)? {
+                        kwargs.set_item(name, self.coerce(&field, &field_type)?)?;
+                    }
+                }
+                return annotation.call((), Some(&kwargs));
+            }
+            if Self::is_pydantic_model(annotation) {
+                return annotation.call_method1("model_validate", (fields,));
+            }
+        }
+        if self.is_enum(annotation) && !value.is_instance(annotation)? {
+            return annotation.call1((value,));
+        }
+        Ok(value.clone())
+    }
+}
+
+/// Builds schemas for annotations, collecting the `$defs` of pydantic models
+/// so they can be placed at the root, where their `$ref`s point
+struct SchemaBuilder<'py> {
+    annotations: Annotations<'py>,
+    defs: Map<String, Value>,
+    depth: usize,
+}
+
+impl<'py> SchemaBuilder<'py> {
+    fn new(py: Python<'py>) -> PyResult<Self> {
+        Ok(Self {
+            annotations: Annotations::new(py)?,
+            defs: Map::new(),
+            depth: 0,
+        })
+    }
+
+    fn schema(&mut self, annotation: &Bound<'py, PyAny>) -> PyResult<Value> {
+        let t = &self.annotations;
+        if annotation.is_none() || annotation.is(&t.none_type) {
+            return Ok(json!({"type": "null"}));
+        }
+        if t.is_typing(annotation, "Any") {
+            return Ok(json!({}));
+        }
+        // bool first: it is a subclass of int
+        for (name, json_type) in [
+            ("bool", "boolean"),
+            ("int", "integer"),
+            ("float", "number"),
+            ("str", "string"),
+        ] {
+            if t.is_builtin(annotation, name) {
+                return Ok(json!({"type": json_type}));
+            }
+        }
+
+        let (origin, args) = t.generic(annotation)?;
+        if !origin.is_none() {
+            if t.is_typing(&origin, "Literal") {
+                return literal_schema(&args);
+            }
+            if t.is_union(&origin) {
+                return self.union_schema(&args);
+            }
+            if t.is_typing(&origin, "Annotated") {
+                return self.schema(&args.get_item(0)?);
+            }
+            if ["list", "tuple", "set", "frozenset"]
+                .iter()
+                .any(|name| t.is_builtin(&origin, name))
+            {
+                let mut schema = json!({"type": "array"});
+                let ellipsis = t.builtins.getattr("Ellipsis")?;
+                let homogeneous =
+                    args.len() == 1 || (args.len() == 2 && args.get_item(1)?.is(&ellipsis));
+                if homogeneous {
+                    schema["items"] = self.schema(&args.get_item(0)?)?;
+                }
+                return Ok(schema);
+            }
+            if t.is_builtin(&origin, "dict") {
+                let mut schema = json!({"type": "object"});
+                if args.len() == 2 {
+                    schema["additionalProperties"] = self.schema(&args.get_item(1)?)?;
+                }
+                return Ok(schema);
+            }
+            return self.schema(&origin);
+        }
+
+        if t.is_dataclass(annotation) {
+            return self.dataclass_schema(annotation);
+        }
+        if Annotations::is_pydantic_model(annotation) {
+            return self.pydantic_schema(annotation);
+        }
+        if t.is_enum(annotation) {
+            let values = annotation
+                .try_iter()?
+                .map(|member| {
+                    pythonize::depythonize::<Value>(&member?.getattr("value")?).map_err(PyErr::from)
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            return Ok(enum_schema(&values));
+        }
+
+        // Bare containers and unknown classes are matched by name
+        let name = match annotation.downcast::<PyString>() {
+            Ok(name) => name.to_string(),
+            Err(_) => annotation.str()?.to_string(),
+        };
+        Ok(json!({"type": map_python_type_to_json_schema(&name)}))
+    }
+
+    /// `Optional[X]` accepts `null` as well; other unions become `anyOf`
+    fn union_schema(&mut self, args: &Bound<'py, PyTuple>) -> PyResult<Value> {
+        let mut nullable = false;
+        let mut members = Vec::new();
+        for arg in args.iter() {
+            if arg.is(&self.annotations.none_type) {
+                nullable = true;
+            } else {
+                members.push(self.schema(&arg)?);
+            }
+        }
+        let mut schema = match members.len() {
+            1 => members.remove(0),
+            _ => json!({"anyOf": members}),
+        };
+        if !nullable {
+            return Ok(schema);
+        }
+        match schema
+            .get("type")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        {
+            Some(json_type) => {
+                schema["type"] = json!([json_type, "null"]);
+                if let Some(Value::Array(values)) = schema.get_mut("enum") {
+                    values.push(Value::Null);
+                }
+                Ok(schema)
+            }
+            None => match schema.get_mut("anyOf") {
+                Some(Value::Array(members)) => {
+                    members.push(json!({"type": "null"}));
+                    Ok(schema)
+                }
+                _ => Ok(json!({"anyOf": [schema, {"type": "null"}]})),
+            },
+        }
+    }
+
+    /// Object schema with one property per `__init__` field of a dataclass,
+    /// described by an `Attributes` section of its docstring
+    fn dataclass_schema(&mut self, class: &Bound<'py, PyAny>) -> PyResult<Value> {
+        if self.depth >= MAX_DEPTH {
+            return Ok(json!({"type": "object"}));
+        }
+        self.depth += 1;
+        let result = self.dataclass_properties(class);
+        self.depth -= 1;
+        let (properties, required) = result?;
+
+        let mut schema = json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false
+        });
+        if let Some(description) = summary(&docstring(class)) {
+            schema["description"] = Value::String(description);
+        }
+        Ok(schema)
+    }
+
+    fn dataclass_properties(
+        &mut self,
+        class: &Bound<'py, PyAny>,
+    ) -> PyResult<(Map<String, Value>, Vec<String>)> {
+        let py = class.py();
+        let dataclasses = self.annotations.dataclasses.clone();
+        let missing = dataclasses.getattr("MISSING")?;
+        let hints = type_hints(class, py);
+        let descriptions = parse_docstring_parameters(&docstring(class));
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for field in dataclasses.call_method1("fields", (class,))?.try_iter()? {
+            let field = field?;
+            if !field.getattr("init")?.is_truthy()? {
+                continue;
+            }
+            let name = field.getattr("name")?.extract::<String>()?;
+            let annotation = match hints.get_item(&name)? {
+                Some(hint) => hint,
+                None => field.getattr("type")?,
+            };
+            let mut schema = self.schema(&annotation)?;
+            if let Some(description) = descriptions.get(&name) {
+                schema["description"] = Value::String(description.clone());
+            }
+
+            let default = field.getattr("default")?;
+            if !default.is(&missing) {
+                if let Ok(default) = pythonize::depythonize::<Value>(&default) {
+                    schema["default"] = default;
+                }
+            } else if field.getattr("default_factory")?.is(&missing) {
+                required.push(name.clone());
+            }
+            properties.insert(name, schema);
+        }
+        Ok((properties, required))
+    }
+
+    /// Schema pydantic generates for a model, with its `$defs` moved to the root
+    fn pydantic_schema(&mut self, model: &Bound<'py, PyAny>) -> PyResult<Value> {
+        let schema = model.call_method0("model_json_schema")?;
+        let mut schema = pythonize::depythonize::<Value>(&schema)?;
+        if let Some(Value::Object(defs)) = schema
+            .as_object_mut()
+            .and_then(|schema| schema.remove("$defs"))
+        {
+            self.defs.extend(defs);
+        }
+        Ok(schema)
+    }
+}
+
+/// `Literal[...]` values as an enum, typed when all values share a type
+fn literal_schema(args: &Bound<'_, PyTuple>) -> PyResult<Value> {
+    let values = args
+        .iter()
+        .map(|arg| pythonize::depythonize::<Value>(&arg).map_err(PyErr::from))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(enum_schema(&values))
+}
+
+fn enum_schema(values: &[Value]) -> Value {
+    let json_type = |value: &Value| match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    let mut schema = json!({"enum": values});
+    if let Some((first, rest)) = values.split_first() {
+        if rest
+            .iter()
+            .all(|value| json_type(value) == json_type(first))
+        {
+            schema["type"] = Value::String(json_type(first).to_string());
+        }
+    }
+    schema
+}
+
+/// Map Python type annotations to JSON Schema types
+fn map_python_type_to_json_schema(type_str: &str) -> &'static str {
+    match type_str {
+        s if s.contains("int") => "integer",
+        s if s.contains("float") => "number",
+        s if s.contains("bool") => "boolean",
+        s if s.contains("str") => "string",
+        s if s.contains("list") || s.contains("List") => "array",
+        s if s.contains("dict") || s.contains("Dict") => "object",
+        _ => "string", // Default fallback
+    }
+}
+
+/// First paragraph of a docstring, unless it is the signature-like text
+/// dataclasses generate when no docstring is written
+fn summary(docstring: &str) -> Option<String> {
+    let summary = docstring
+        .trim()
+        .split("\n\n")
+        .next()
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())?;
+    let generated = summary.ends_with(')')
+        && summary
+            .split_once('(')
+            .is_some_and(|(name, _)| !name.contains(' '));
+    (!generated).then_some(summary)
+}
+
+/// Parse docstring to extract parameter descriptions
+/// Supports Google (`Args:`), NumPy (`Parameters` with an underline) and
+/// Sphinx (`:param name:`) formats; `Attributes` sections describe dataclass
+/// fields. Descriptions continued on more deeply indented lines are joined.
+pub(crate) fn parse_docstring_parameters(docstring: &str) -> HashMap<String, String> {
+    let lines: Vec<&str> = docstring.lines().collect();
+    let mut descriptions = HashMap::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let header = line.trim();
+        let underlined = lines[index + 1..]
+            .iter()
+            .find(|next| !next.trim().is_empty())
+            .is_some_and(|next| is_underline(next));
+
+        if matches!(
+            header,
+            "Args:" | "Arguments:" | "Parameters:" | "Attributes:"
+        ) {
+            // Google: the section ends at the next line indented no deeper than its header
+            let section = lines[index + 1..]
+                .iter()
+                .take_while(|l| l.trim().is_empty() || indent(l) > indent(line))
+                .copied();
+            parse_parameter_lines(section, split_google_line, &mut descriptions);
+        } else if matches!(header, "Parameters" | "Attributes") && underlined {
+            // NumPy: the section ends at the next underlined header
+            let body = &lines[index + 1..];
+            let section = body
+                .iter()
+                .enumerate()
+                .skip_while(|(_, l)| l.trim().is_empty() || is_underline(l))
+                .take_while(|(i, _)| {
+                    !body[i + 1..]
+                        .iter()
+                        .find(|next| !next.trim().is_empty())
+                        .is_some_and(|next| is_underline(next))
+                })
+                .map(|(_, l)| *l);
+            parse_parameter_lines(section, split_numpy_line, &mut descriptions);
+        }
+    }
+
+    // Sphinx: ":param name: description" or ":param type name: description"
+    let mut sphinx = lines.iter().peekable();
+    while let Some(line) = sphinx.next() {
+        let Some(rest) = line.trim().strip_prefix(":param ") else {
+            continue;
+        };
+        let Some((target, description)) = rest.split_once(':') else {
+            continue;
+        };
+        let Some(name) = target.split_whitespace().last() else {
+            continue;
+        };
+        let mut description = description.trim().to_string();
+        while let Some(next) = sphinx.next_if(|next| {
+            !next.trim().is_empty() && !next.trim().starts_with(':') && indent(next) > indent(line)
+        }) {
+            description.push(' ');
+            description.push_str(next.trim());
+        }
+        if !description.is_empty() {
+            descriptions.entry(name.to_string()).or_insert(description);
+        }
+    }
+
+    descriptions
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_underline(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && trimmed.chars().all(|c| c == '-')
+}
+
+/// Collect `name -> description` from the lines of a parameter section: each
+/// entry starts at the section's base indentation and continues on more
+/// deeply indented lines
+fn parse_parameter_lines<'a>(
+    section: impl Iterator<Item = &'a str>,
+    split: fn(&str) -> Option<(String, String)>,
+    descriptions: &mut HashMap<String, String>,
+) {
+    let mut base = None;
+    let mut current: Option<(String, Vec<String>)> = None;
+    let mut finish = |entry: Option<(String, Vec<String>)>| {
+        if let Some((name, parts)) = entry {
+            let description = parts.join(" ");
+            if !description.is_empty() {
+                descriptions.entry(name).or_insert(description);
+            }
+        }
+    };
+
+    for line in section.filter(|line| !line.trim().is_empty()) {
+        let base = *base.get_or_insert_with(|| indent(line));
+        if indent(line) > base {
+            if let Some((_, parts)) = current.as_mut() {
+                parts.push(line.trim().to_string());
+            }
+            continue;
+        }
+        finish(current.take());
+        current = split(line.trim()).map(|(name, first)| {
+            let parts = if first.is_empty() {
+                Vec::new()
+            } else {
+                vec![first]
+            };
+            (name, parts)
+        });
+    }
+    finish(current);
+}
+
+/// Split `name (type): description` or `name: description`
+fn split_google_line(line: &str) -> Option<(String, String)> {
+    let (target, description) = line.split_once(':')?;
+    let name = target.split('(').next()?.trim().trim_start_matches('*');
+    is_identifier(name).then(|| (name.to_string(), description.trim().to_string()))
+}
+
+/// Split `name : type` or a bare `name`; the description follows on the next lines
+fn split_numpy_line(line: &str) -> Option<(String, String)> {
+    let name = line.split(':').next()?.trim().trim_start_matches('*');
+    is_identifier(name).then(|| (name.to_string(), String::new()))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
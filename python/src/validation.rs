@@ -1,15 +1,27 @@
 //! Validation utilities for GraphBit Python bindings
 
+use crate::errors::{invalid_value, to_py_error};
+use graphbit_core::http_client::RequestTimeouts;
+use graphbit_core::secret_ref::SecretRef;
+use graphbit_core::validation::{TypeValidator, ValidationResult};
 use pyo3::prelude::*;
+use pyo3::types::PyString;
 
 /// Validate API key for different providers
+///
+/// References such as `env:OPENAI_API_KEY` are only checked for their form;
+/// they are resolved when the provider is created.
 pub(crate) fn validate_api_key(api_key: &str, provider: &str) -> PyResult<()> {
     if api_key.is_empty() {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+        return Err(invalid_value(format!(
             "{} API key cannot be empty",
             provider
         )));
     }
+    let secret = SecretRef::parse(api_key).map_err(|e| invalid_value(e.to_string()))?;
+    if secret.is_reference() {
+        return Ok(());
+    }
 
     let min_length = match provider.to_lowercase().as_str() {
         "openai" => 20,
@@ -19,11 +31,98 @@ pub(crate) fn validate_api_key(api_key: &str, provider: &str) -> PyResult<()> {
     };
 
     if api_key.len() < min_length {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "{} API key too short",
-            provider
-        )));
+        return Err(invalid_value(format!("{} API key too short", provider)));
     }
 
     Ok(())
 }
+
+/// Request timeouts from the `timeout_ms` and `connect_timeout_ms` arguments
+/// of a provider configuration, which must be positive when given
+pub(crate) fn request_timeouts(
+    timeout_ms: Option<u64>,
+    connect_timeout_ms: Option<u64>,
+) -> PyResult<RequestTimeouts> {
+    let timeouts = RequestTimeouts {
+        timeout_ms,
+        connect_timeout_ms,
+    };
+    timeouts.validate().map_err(to_py_error)?;
+    Ok(timeouts)
+}
+
+/// Result of validating data against a JSON schema
+#[pyclass(name = "ValidationResult")]
+#[derive(Clone)]
+pub struct PyValidationResult {
+    pub(crate) inner: ValidationResult,
+}
+
+#[pymethods]
+impl PyValidationResult {
+    /// Whether the data satisfied the schema
+    #[getter]
+    fn is_valid(&self) -> bool {
+        self.inner.is_valid
+    }
+
+    /// Validation errors as dictionaries with `field_path`, `message`,
+    /// `expected`, `actual` and `error_code`
+    #[getter]
+    fn errors(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize::pythonize(py, &self.inner.errors)?.into())
+    }
+
+    /// Validation metadata
+    #[getter]
+    fn metadata(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize::pythonize(py, &self.inner.metadata)?.into())
+    }
+
+    fn __bool__(&self) -> bool {
+        self.inner.is_valid
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ValidationResult(is_valid={}, errors={})",
+            if self.inner.is_valid { "True" } else { "False" },
+            self.inner.errors.len()
+        )
+    }
+}
+
+/// Validate data against a JSON Schema (draft-07)
+///
+/// `$ref` resolves within the schema document; keywords from later drafts
+/// are reported as `UNSUPPORTED_KEYWORD` errors.
+///
+/// Args:
+///     instance: Data to validate. A `str` is parsed as a JSON document
+///     schema: JSON schema as a dictionary or a boolean
+///
+/// Returns:
+///     ValidationResult: Whether the data is valid, with any errors
+#[pyfunction]
+pub(crate) fn validate_json(
+    py: Python<'_>,
+    instance: &Bound<'_, PyAny>,
+    schema: &Bound<'_, PyAny>,
+) -> PyResult<PyValidationResult> {
+    let schema = pythonize::depythonize::<serde_json::Value>(schema)
+        .map_err(|e| invalid_value(format!("Invalid schema: {e}")))?;
+    if !schema.is_object() && !schema.is_boolean() {
+        return Err(invalid_value("Schema must be a dictionary or a boolean"));
+    }
+
+    let validator = TypeValidator::new();
+    let result = if instance.is_instance_of::<PyString>() {
+        let document: String = instance.extract()?;
+        py.allow_threads(|| validator.validate_against_schema(&document, &schema))
+    } else {
+        let instance = pythonize::depythonize::<serde_json::Value>(instance)
+            .map_err(|e| invalid_value(format!("Instance is not JSON-compatible: {e}")))?;
+        py.allow_threads(|| validator.validate_value(&instance, &schema))
+    };
+    Ok(PyValidationResult { inner: result })
+}
@@ -0,0 +1,236 @@
+//! Reusable agents for GraphBit Python bindings
+//!
+//! An [`Agent`] is built once, registered on an `Executor`, and referenced by
+//! any number of `Node.agent(..., agent=...)` nodes.
+
+use crate::errors::{to_py_error, validation_error};
+use crate::llm::LlmConfig;
+use crate::runtime::block_on;
+use graphbit_core::agents::{Agent as CoreAgent, AgentBuilder as CoreAgentBuilder, AgentTrait};
+use graphbit_core::types::{AgentCapability, AgentId};
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Parse a capability name, mapping the built-in names to their variants
+fn capability_from_name(name: &str) -> AgentCapability {
+    match name {
+        "text_processing" => AgentCapability::TextProcessing,
+        "data_analysis" => AgentCapability::DataAnalysis,
+        "tool_execution" => AgentCapability::ToolExecution,
+        "decision_making" => AgentCapability::DecisionMaking,
+        other => AgentCapability::Custom(other.to_string()),
+    }
+}
+
+fn capability_name(capability: &AgentCapability) -> String {
+    match capability {
+        AgentCapability::TextProcessing => "text_processing".to_string(),
+        AgentCapability::DataAnalysis => "data_analysis".to_string(),
+        AgentCapability::ToolExecution => "tool_execution".to_string(),
+        AgentCapability::DecisionMaking => "decision_making".to_string(),
+        AgentCapability::Custom(name) => name.clone(),
+    }
+}
+
+/// An agent built once and shared by every node that references it
+#[pyclass]
+#[derive(Clone)]
+pub struct Agent {
+    pub(crate) inner: Arc<CoreAgent>,
+}
+
+impl Agent {
+    pub(crate) fn as_trait_object(&self) -> Arc<dyn AgentTrait> {
+        self.inner.clone()
+    }
+
+    pub(crate) fn agent_id(&self) -> &AgentId {
+        self.inner.id()
+    }
+}
+
+#[pymethods]
+impl Agent {
+    /// Start building an agent
+    #[staticmethod]
+    fn builder(name: String) -> PyResult<AgentBuilder> {
+        if name.trim().is_empty() {
+            return Err(validation_error("name", None, "Agent name cannot be empty"));
+        }
+        Ok(AgentBuilder {
+            name,
+            description: String::new(),
+            llm_config: None,
+            capabilities: Vec::new(),
+            system_prompt: None,
+            temperature: None,
+            max_tokens: None,
+            agent_id: None,
+        })
+    }
+
+    /// Agent ID, used by nodes to reference this agent
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.id().to_string()
+    }
+
+    /// Agent name
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.config().name.clone()
+    }
+
+    /// Agent description
+    #[getter]
+    fn description(&self) -> String {
+        self.inner.config().description.clone()
+    }
+
+    /// Capability names of the agent
+    #[getter]
+    fn capabilities(&self) -> Vec<String> {
+        self.inner
+            .config()
+            .capabilities
+            .iter()
+            .map(capability_name)
+            .collect()
+    }
+
+    /// System prompt of the agent, if any
+    #[getter]
+    fn system_prompt(&self) -> Option<String> {
+        let prompt = &self.inner.config().system_prompt;
+        (!prompt.is_empty()).then(|| prompt.clone())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Agent(id='{}', name='{}', model='{}')",
+            self.inner.id(),
+            self.inner.config().name,
+            self.inner.config().llm_config.model_name()
+        )
+    }
+}
+
+/// Fluent builder for [`Agent`]
+#[pyclass]
+#[derive(Clone)]
+pub struct AgentBuilder {
+    name: String,
+    description: String,
+    llm_config: Option<LlmConfig>,
+    capabilities: Vec<AgentCapability>,
+    system_prompt: Option<String>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+    agent_id: Option<String>,
+}
+
+#[pymethods]
+impl AgentBuilder {
+    /// Set the agent description
+    fn description(mut slf: PyRefMut<'_, Self>, description: String) -> PyRefMut<'_, Self> {
+        slf.description = description;
+        slf
+    }
+
+    /// Set the LLM configuration the agent runs with
+    fn llm(mut slf: PyRefMut<'_, Self>, config: LlmConfig) -> PyRefMut<'_, Self> {
+        slf.llm_config = Some(config);
+        slf
+    }
+
+    /// Add a capability, e.g. `"search"` or one of the built-in names
+    /// (`text_processing`, `data_analysis`, `tool_execution`, `decision_making`)
+    fn capability<'a>(mut slf: PyRefMut<'a, Self>, name: &str) -> PyResult<PyRefMut<'a, Self>> {
+        if name.trim().is_empty() {
+            return Err(validation_error(
+                "capability",
+                None,
+                "Capability name cannot be empty",
+            ));
+        }
+        let capability = capability_from_name(name);
+        if !slf.capabilities.contains(&capability) {
+            slf.capabilities.push(capability);
+        }
+        Ok(slf)
+    }
+
+    /// Set the agent's system prompt
+    fn system_prompt(mut slf: PyRefMut<'_, Self>, prompt: String) -> PyRefMut<'_, Self> {
+        slf.system_prompt = Some(prompt);
+        slf
+    }
+
+    /// Set the sampling temperature (0.0-2.0)
+    fn temperature(mut slf: PyRefMut<'_, Self>, temperature: f32) -> PyResult<PyRefMut<'_, Self>> {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err(validation_error(
+                "temperature",
+                Some(&temperature.to_string()),
+                "Temperature must be between 0.0 and 2.0",
+            ));
+        }
+        slf.temperature = Some(temperature);
+        Ok(slf)
+    }
+
+    /// Set the maximum number of tokens to generate
+    fn max_tokens(mut slf: PyRefMut<'_, Self>, max_tokens: u32) -> PyResult<PyRefMut<'_, Self>> {
+        if max_tokens == 0 {
+            return Err(validation_error(
+                "max_tokens",
+                Some("0"),
+                "max_tokens must be greater than 0",
+            ));
+        }
+        slf.max_tokens = Some(max_tokens);
+        Ok(slf)
+    }
+
+    /// Use a fixed agent ID instead of a generated one
+    fn id(mut slf: PyRefMut<'_, Self>, agent_id: String) -> PyRefMut<'_, Self> {
+        slf.agent_id = Some(agent_id);
+        slf
+    }
+
+    /// Build the agent, creating its LLM provider
+    fn build(&self, py: Python<'_>) -> PyResult<Agent> {
+        let llm_config = self.llm_config.as_ref().ok_or_else(|| {
+            validation_error(
+                "llm",
+                None,
+                "An LLM configuration is required; call .llm(config)",
+            )
+        })?;
+
+        let mut builder = CoreAgentBuilder::new(self.name.clone(), llm_config.inner.clone())
+            .description(self.description.clone())
+            .capabilities(self.capabilities.clone());
+        if let Some(prompt) = &self.system_prompt {
+            builder = builder.system_prompt(prompt.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            builder = builder.temperature(temperature);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            builder = builder.max_tokens(max_tokens);
+        }
+        if let Some(id) = &self.agent_id {
+            let agent_id = AgentId::from_string(id)
+                .map_err(|e| validation_error("id", Some(id), &format!("Invalid ID: {e}")))?;
+            builder = builder.with_id(agent_id);
+        }
+
+        let agent = py
+            .allow_threads(|| block_on(builder.build()))
+            .map_err(to_py_error)?;
+        Ok(Agent {
+            inner: Arc::new(agent),
+        })
+    }
+}
@@ -0,0 +1,78 @@
+//! Node attachments for GraphBit Python bindings
+
+use graphbit_core::attachment::Attachment as CoreAttachment;
+use graphbit_core::output_store::OutputSpill;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::errors::to_py_error;
+
+/// File, image or audio clip a node produced, such as the file a document
+/// loader node read or a binary HTTP response
+///
+/// Get them with `WorkflowResult.get_node_attachments(name)`.
+#[pyclass(module = "graphbit")]
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub(crate) inner: CoreAttachment,
+    /// Output store of the run, holding attachments too large for the context
+    pub(crate) spill: Option<OutputSpill>,
+}
+
+#[pymethods]
+impl Attachment {
+    /// Unique ID of the attachment
+    #[getter]
+    fn id(&self) -> &str {
+        &self.inner.id
+    }
+
+    /// MIME type of the content, e.g. `image/png`
+    #[getter]
+    fn mime_type(&self) -> &str {
+        &self.inner.mime_type
+    }
+
+    /// Original file name, when known
+    #[getter]
+    fn filename(&self) -> Option<&str> {
+        self.inner.filename.as_deref()
+    }
+
+    /// Size of the content in bytes
+    #[getter]
+    fn size_bytes(&self) -> u64 {
+        self.inner.size_bytes
+    }
+
+    /// SHA-256 hex digest of the content
+    #[getter]
+    fn sha256(&self) -> &str {
+        &self.inner.sha256
+    }
+
+    /// The content
+    fn bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = self.inner.read(self.spill.as_ref()).map_err(to_py_error)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Write the content to `path`
+    fn save(&self, path: std::path::PathBuf) -> PyResult<()> {
+        self.inner
+            .save(&path, self.spill.as_ref())
+            .map_err(to_py_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Attachment(mime_type='{}', filename={}, size_bytes={})",
+            self.inner.mime_type,
+            self.inner
+                .filename
+                .as_ref()
+                .map_or_else(|| "None".to_string(), |name| format!("'{name}'")),
+            self.inner.size_bytes
+        )
+    }
+}
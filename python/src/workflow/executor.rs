@@ -7,23 +7,47 @@
 //! - Detailed execution metrics and logging
 //! - Graceful error handling and recovery
 
+use graphbit_core::agents::AgentTrait;
+use graphbit_core::audit::{AuditCall, AuditCallKind, AuditContext, AuditOutcome};
+use graphbit_core::budget::{BudgetPolicy, ExecutionBudget};
+use graphbit_core::clock::{Clock, TestClock};
+use graphbit_core::env_config::EnvConfig;
+use graphbit_core::llm::profile::{profiles_from_file, profiles_from_value};
+use graphbit_core::llm::{
+    ConfigProfile, ContextWindow, HistorySummary, HistoryWindow, LlmMiddlewareStack,
+};
+use graphbit_core::pacing::LlmCallSpacing;
+use graphbit_core::run_history::{RunHistory, RunQuery, RunRetention, RunState};
 use graphbit_core::stream::{StreamEvent, StreamMode};
+use graphbit_core::template::is_yaml_file;
+use graphbit_core::types::{
+    CircuitBreakerConfig, ConcurrencyConfig, ConcurrencyManager, PayloadCaptureConfig,
+    PromptDefaults, RetryConfig, Secrets, TagFilter, ToolCallRecord, ToolCallTraceConfig,
+};
 use graphbit_core::workflow::WorkflowExecutor as CoreWorkflowExecutor;
-use graphbit_core::{DecodeContext, EncodeContext, Enforcer, GuardRail};
+use graphbit_core::{
+    AuditLog, AuditPolicy, DecodeContext, EncodeContext, Enforcer, ExternalEvents, GuardRail,
+    JsonlAuditSink, ResultRecord, ResultSink, ShutdownHandle,
+};
 use pyo3::exceptions::PyStopIteration;
 use pyo3::prelude::*;
-use std::collections::HashSet;
-use pyo3::types::{PyAny, PyDict, PyList};
+use std::collections::{HashMap, HashSet};
+use pyo3::types::{PyAny, PyDict, PyList, PyType};
 use serde::Serialize;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, instrument, warn};
 
-use super::{result::WorkflowResult, workflow::Workflow};
-use crate::errors::{timeout_error, to_py_runtime_error, validation_error};
+use super::template::load_yaml_file;
+use super::{
+    agent::Agent, handle::ExecutionHandle, result::WorkflowResult, retry::RetryPolicy,
+    workflow::Workflow,
+};
+use crate::errors::{invalid_value, timeout_error, to_py_error, validation_error};
 use crate::guardrail::GuardRailPolicyConfig;
 use crate::llm::config::LlmConfig;
-use crate::runtime::get_runtime;
+use crate::llm::middleware::PyLlmMiddleware;
+use crate::runtime::{block_on, get_runtime};
 
 type TimedStreamEvent = (StreamEvent, String);
 
@@ -32,10 +56,36 @@ type TimedStreamEvent = (StreamEvent, String);
 pub(crate) enum ExecutionMode {
     /// Balanced mode for general use
     Balanced,
+    /// Concurrency limits scaled to the number of CPUs
+    HighThroughput,
+    /// No retries, so failures are reported right away
+    LowLatency,
+    /// Few nodes in flight at once
+    MemoryOptimized,
+}
+
+impl ExecutionMode {
+    fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "balanced" => Some(Self::Balanced),
+            "high_throughput" => Some(Self::HighThroughput),
+            "low_latency" => Some(Self::LowLatency),
+            "memory_optimized" => Some(Self::MemoryOptimized),
+            _ => None,
+        }
+    }
+
+    fn concurrency_config(self) -> ConcurrencyConfig {
+        match self {
+            Self::Balanced | Self::LowLatency => ConcurrencyConfig::default(),
+            Self::HighThroughput => ConcurrencyConfig::high_throughput(),
+            Self::MemoryOptimized => ConcurrencyConfig::memory_optimized(),
+        }
+    }
 }
 
 /// Execution configuration for fine-tuning performance
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub(crate) struct ExecutionConfig {
     /// Execution mode
     pub mode: ExecutionMode,
@@ -47,6 +97,133 @@ pub(crate) struct ExecutionConfig {
     pub enable_metrics: bool,
     /// Enable execution tracing
     pub enable_tracing: bool,
+    /// Fail the node when a tool call's arguments do not match the tool schema
+    /// (instead of returning the validation error to the model)
+    pub strict_tool_args: bool,
+    /// Redaction and truncation applied to recorded tool call payloads
+    pub tool_call_trace: ToolCallTraceConfig,
+    /// Agents registered with `register_agent` and agents `warm_up` created for
+    /// agent nodes, shared by the nodes referencing them
+    pub agents: Vec<Arc<dyn AgentTrait>>,
+    /// Concurrency limits shared by every run of this executor
+    pub concurrency_manager: Arc<ConcurrencyManager>,
+    /// Fail the workflow as soon as a node fails
+    pub fail_fast: bool,
+    /// Time limit for nodes without their own `timeout_seconds`
+    pub max_node_execution_time_ms: Option<u64>,
+    /// Retry configuration for nodes without their own, instead of the mode's
+    pub retry: Option<RetryConfig>,
+    /// Circuit breaker configuration for agent nodes
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Middleware run around every LLM call
+    pub llm_middleware: LlmMiddlewareStack,
+    /// Run workflows containing shell command nodes
+    pub allow_shell_nodes: bool,
+    /// Run workflows containing Python code nodes
+    pub allow_python_code_nodes: bool,
+    /// Token and cost limits of each run
+    pub budget: ExecutionBudget,
+    /// Log receiving a record of every provider and tool call
+    pub audit_log: Option<AuditLog>,
+    /// Capture of each node's rendered prompt and raw provider payloads
+    pub capture_payloads: Option<PayloadCaptureConfig>,
+    /// System preamble, prompt suffix and globals applied to every node
+    pub prompt_defaults: PromptDefaults,
+    /// LLM profiles a run may select, keyed by name
+    pub profiles: HashMap<String, ConfigProfile>,
+    /// Mailboxes of the running executions, shared by every run
+    pub external_events: ExternalEvents,
+    /// Minimum interval between LLM calls, shared by every run
+    pub llm_call_spacing: Option<Arc<LlmCallSpacing>>,
+    /// Clock of every run instead of the system clock
+    pub clock: Option<Arc<dyn Clock>>,
+    /// Store recording every run when it starts and finishes
+    pub run_history: Option<RunHistory>,
+    /// Size limit of a single node attachment, instead of the core default
+    pub max_attachment_bytes: Option<u64>,
+    /// Running executions of every run and whether the executor is shutting down
+    pub shutdown: ShutdownHandle,
+}
+
+impl ExecutionConfig {
+    /// Build the core executor for one run from the mode preset and the overrides
+    fn core_executor(
+        &self,
+        llm_config: graphbit_core::llm::LlmConfig,
+        conditional_handlers: HashMap<String, graphbit_core::workflow::ConditionalRouteFn>,
+    ) -> CoreWorkflowExecutor {
+        let mut executor = match self.mode {
+            ExecutionMode::Balanced => CoreWorkflowExecutor::new(),
+            ExecutionMode::HighThroughput => CoreWorkflowExecutor::new_high_throughput(),
+            ExecutionMode::LowLatency => CoreWorkflowExecutor::new_low_latency(),
+            ExecutionMode::MemoryOptimized => CoreWorkflowExecutor::new_memory_optimized(),
+        }
+        .with_concurrency_manager(self.concurrency_manager.clone())
+        .with_fail_fast(self.fail_fast)
+        .with_default_llm_config(llm_config)
+        .with_conditional_handlers(conditional_handlers)
+        .with_agents(self.agents.iter().cloned())
+        .with_allow_shell_nodes(self.allow_shell_nodes)
+        .with_allow_python_code_nodes(self.allow_python_code_nodes);
+
+        if let Some(timeout_ms) = self.max_node_execution_time_ms {
+            executor = executor.with_max_node_execution_time(timeout_ms);
+        }
+        if let Some(retry) = &self.retry {
+            executor = executor.with_retry_config(retry.clone());
+        }
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            executor = executor.with_circuit_breaker_config(circuit_breaker.clone());
+        }
+        if !self.llm_middleware.is_empty() {
+            executor = executor.with_llm_middleware(Arc::new(self.llm_middleware.clone()));
+        }
+        if let Some(max_tokens) = self.budget.max_total_tokens {
+            executor = executor.with_max_total_tokens(max_tokens);
+        }
+        if let Some(max_cost_usd) = self.budget.max_cost_usd {
+            executor = executor.with_max_cost_usd(max_cost_usd);
+        }
+        if let Some(audit_log) = &self.audit_log {
+            executor = executor.with_audit_log(audit_log.clone());
+        }
+        if let Some(capture) = self.capture_payloads {
+            executor = executor.with_payload_capture_config(capture);
+        }
+        if let Some(spacing) = &self.llm_call_spacing {
+            executor = executor.with_llm_call_spacing(spacing.clone());
+        }
+        if let Some(clock) = &self.clock {
+            executor = executor.with_clock(clock.clone());
+        }
+        if let Some(history) = &self.run_history {
+            executor = executor.with_run_history(history.clone());
+        }
+        if let Some(max_bytes) = self.max_attachment_bytes {
+            executor = executor.with_max_attachment_bytes(max_bytes);
+        }
+        if let Some(preamble) = &self.prompt_defaults.system_preamble {
+            executor = executor.with_system_preamble(preamble.clone());
+        }
+        if let Some(suffix) = &self.prompt_defaults.prompt_suffix {
+            executor = executor.with_prompt_suffix(suffix.clone());
+        }
+        executor
+            .with_globals(self.prompt_defaults.globals.clone())
+            .with_profiles(self.profiles.clone())
+            .with_external_events(self.external_events.clone())
+            .with_shutdown_handle(self.shutdown.clone())
+            .with_budget_policy(self.budget.policy)
+    }
+
+    /// Run `future`, recording the provider and tool calls it makes for
+    /// `context` in the audit log, if any
+    async fn audited<F: std::future::Future>(&self, context: AuditContext, future: F) -> F::Output {
+        match &self.audit_log {
+            Some(audit_log) => audit_log.scope(context, future).await,
+            None => future.await,
+        }
+    }
 }
 
 impl Default for ExecutionConfig {
@@ -57,10 +234,56 @@ impl Default for ExecutionConfig {
             max_retries: 3,
             enable_metrics: true,
             enable_tracing: false, // Default to false to reduce debug output
+            strict_tool_args: false,
+            tool_call_trace: ToolCallTraceConfig::default(),
+            agents: Vec::new(),
+            concurrency_manager: Arc::new(ConcurrencyManager::new(ConcurrencyConfig::default())),
+            fail_fast: false,
+            max_node_execution_time_ms: None,
+            retry: None,
+            circuit_breaker: None,
+            llm_middleware: LlmMiddlewareStack::new(),
+            allow_shell_nodes: false,
+            allow_python_code_nodes: false,
+            budget: ExecutionBudget::default(),
+            audit_log: None,
+            capture_payloads: None,
+            prompt_defaults: PromptDefaults::default(),
+            profiles: HashMap::new(),
+            external_events: ExternalEvents::new(),
+            llm_call_spacing: None,
+            clock: None,
+            run_history: None,
+            max_attachment_bytes: None,
+            shutdown: ShutdownHandle::new(),
         }
     }
 }
 
+/// Grace period of a shutdown from a timeout in seconds
+fn grace_period(timeout: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(timeout).map_err(|_| {
+        invalid_value(format!(
+            "timeout must be a non-negative number of seconds, got {timeout}"
+        ))
+    })
+}
+
+/// LLM profiles keyed by name, from a dict or a YAML (`.yaml`, `.yml`) or JSON file
+fn executor_profiles(profiles: &Bound<'_, PyAny>) -> PyResult<HashMap<String, ConfigProfile>> {
+    let value = if profiles.is_instance_of::<PyDict>() {
+        pythonize::depythonize::<serde_json::Value>(profiles)
+            .map_err(|e| invalid_value(format!("Invalid profiles: {e}")))?
+    } else {
+        let path: std::path::PathBuf = profiles.extract()?;
+        if !is_yaml_file(&path) {
+            return profiles_from_file(&path).map_err(to_py_error);
+        }
+        load_yaml_file(&path, "LLM profile")?
+    };
+    profiles_from_value(value).map_err(to_py_error)
+}
+
 /// Execution statistics for monitoring
 #[derive(Debug, Clone)]
 pub(crate) struct ExecutionStats {
@@ -86,26 +309,63 @@ impl Default for ExecutionStats {
 }
 
 /// Production-grade workflow executor with comprehensive features
+///
+/// Thread safety: one executor may run workflows from several Python threads
+/// at once. `execute`, `execute_async`, `execute_batch`, `execute_streaming`
+/// and `run_async` give every run its own context, release the GIL while
+/// waiting on it and share only the concurrency limits, agents and statistics
+/// of the executor, which are safe under contention. `configure`,
+/// `register_agent` and `warm_up` change the executor itself and must not be
+/// called while runs are in progress.
 #[pyclass]
 pub struct Executor {
     /// Execution configuration
     config: ExecutionConfig,
     /// LLM configuration for auto-generating agents
     llm_config: LlmConfig,
-    /// Execution statistics
-    stats: ExecutionStats,
+    /// Execution statistics, updated by every execution running on this executor
+    stats: Mutex<ExecutionStats>,
 }
 
 #[pymethods]
 impl Executor {
     #[new]
-    #[pyo3(signature = (config, lightweight_mode=None, timeout_seconds=None, debug=None))]
+    #[pyo3(signature = (config, lightweight_mode=None, timeout_seconds=None, debug=None, strict_tool_args=false, redact_tool_calls=false, tool_call_max_chars=None, mode=None, max_concurrency=None, node_type_limits=None, fail_fast=false, max_node_execution_time_ms=None, retry=None, circuit_breaker=None, middleware=None, allow_shell_nodes=false, max_total_tokens=None, max_cost_usd=None, budget_policy="finish", audit_log=None, audit_policy="hash", globals=None, system_preamble=None, prompt_suffix=None, profiles=None, capture_payloads=false, capture_payload_max_chars=None, min_interval_between_llm_calls_ms=None, run_history=None, run_history_report_dir=None, max_attachment_bytes=None, allow_python_code_nodes=false))]
     #[allow(unused_variables)]
     fn new(
+        py: Python<'_>,
         config: LlmConfig,
         lightweight_mode: Option<bool>,
         timeout_seconds: Option<u64>,
         debug: Option<bool>,
+        strict_tool_args: bool,
+        redact_tool_calls: bool,
+        tool_call_max_chars: Option<usize>,
+        mode: Option<&str>,
+        max_concurrency: Option<usize>,
+        node_type_limits: Option<HashMap<String, usize>>,
+        fail_fast: bool,
+        max_node_execution_time_ms: Option<u64>,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        circuit_breaker: Option<&Bound<'_, PyDict>>,
+        middleware: Option<Vec<PyRef<'_, PyLlmMiddleware>>>,
+        allow_shell_nodes: bool,
+        max_total_tokens: Option<u64>,
+        max_cost_usd: Option<f64>,
+        budget_policy: &str,
+        audit_log: Option<std::path::PathBuf>,
+        audit_policy: &str,
+        globals: Option<&Bound<'_, PyDict>>,
+        system_preamble: Option<String>,
+        prompt_suffix: Option<String>,
+        profiles: Option<&Bound<'_, PyAny>>,
+        capture_payloads: bool,
+        capture_payload_max_chars: Option<usize>,
+        min_interval_between_llm_calls_ms: Option<u64>,
+        run_history: Option<std::path::PathBuf>,
+        run_history_report_dir: Option<std::path::PathBuf>,
+        max_attachment_bytes: Option<u64>,
+        allow_python_code_nodes: bool,
     ) -> PyResult<Self> {
         // Validate inputs
         if let Some(timeout) = timeout_seconds {
@@ -117,8 +377,194 @@ impl Executor {
                 ));
             }
         }
+        if tool_call_max_chars == Some(0) {
+            return Err(validation_error(
+                "tool_call_max_chars",
+                Some("0"),
+                "Tool call payload limit must be greater than 0",
+            ));
+        }
+        if capture_payload_max_chars == Some(0) {
+            return Err(validation_error(
+                "capture_payload_max_chars",
+                Some("0"),
+                "Captured payload limit must be greater than 0",
+            ));
+        }
 
-        let mut exec_config = ExecutionConfig::default();
+        let mode = match mode {
+            Some(name) => ExecutionMode::parse(name).ok_or_else(|| {
+                validation_error(
+                    "mode",
+                    Some(name),
+                    "Mode must be one of: balanced, high_throughput, low_latency, memory_optimized",
+                )
+            })?,
+            None => ExecutionMode::Balanced,
+        };
+        let mut concurrency = mode.concurrency_config();
+        if let Some(max_concurrency) = max_concurrency {
+            if max_concurrency == 0 {
+                return Err(validation_error(
+                    "max_concurrency",
+                    Some("0"),
+                    "Maximum concurrency must be greater than 0",
+                ));
+            }
+            concurrency.global_max_concurrency = max_concurrency;
+        }
+        for (node_type, limit) in node_type_limits.unwrap_or_default() {
+            if limit == 0 {
+                return Err(validation_error(
+                    "node_type_limits",
+                    Some(&node_type),
+                    "Node type limits must be greater than 0",
+                ));
+            }
+            concurrency.node_type_limits.insert(node_type, limit);
+        }
+        if max_node_execution_time_ms == Some(0) {
+            return Err(validation_error(
+                "max_node_execution_time_ms",
+                Some("0"),
+                "Maximum node execution time must be greater than 0",
+            ));
+        }
+        if max_attachment_bytes == Some(0) {
+            return Err(validation_error(
+                "max_attachment_bytes",
+                Some("0"),
+                "Maximum attachment size must be greater than 0",
+            ));
+        }
+        if max_total_tokens == Some(0) {
+            return Err(validation_error(
+                "max_total_tokens",
+                Some("0"),
+                "Maximum total tokens must be greater than 0",
+            ));
+        }
+        if let Some(max_cost_usd) = max_cost_usd {
+            if !(max_cost_usd.is_finite() && max_cost_usd > 0.0) {
+                return Err(validation_error(
+                    "max_cost_usd",
+                    Some(&max_cost_usd.to_string()),
+                    "Maximum cost must be a positive number",
+                ));
+            }
+        }
+        let policy = match budget_policy {
+            "finish" => BudgetPolicy::Finish,
+            "cancel" => BudgetPolicy::Cancel,
+            other => {
+                return Err(validation_error(
+                    "budget_policy",
+                    Some(other),
+                    "Budget policy must be one of: finish, cancel",
+                ))
+            }
+        };
+        let audit_policy = match audit_policy {
+            "full" => AuditPolicy::Full,
+            "hash" => AuditPolicy::Hash,
+            "redact" => AuditPolicy::redact(),
+            other => {
+                return Err(validation_error(
+                    "audit_policy",
+                    Some(other),
+                    "Audit policy must be one of: full, hash, redact",
+                ))
+            }
+        };
+        // Keys override the fields of core's default `CircuitBreakerConfig`
+        let circuit_breaker = circuit_breaker
+            .map(|overrides| {
+                let invalid = |e: String| invalid_value(format!("Invalid circuit_breaker: {e}"));
+                let overrides = pythonize::depythonize::<serde_json::Value>(overrides.as_any())
+                    .map_err(|e| invalid(e.to_string()))?;
+                let mut merged = serde_json::to_value(CircuitBreakerConfig::default())
+                    .map_err(|e| invalid(e.to_string()))?;
+                if let (Some(merged), Some(overrides)) =
+                    (merged.as_object_mut(), overrides.as_object())
+                {
+                    merged.extend(overrides.clone());
+                }
+                serde_json::from_value::<CircuitBreakerConfig>(merged)
+                    .map_err(|e| invalid(e.to_string()))
+            })
+            .transpose()?;
+        let globals = globals
+            .map(|globals| {
+                pythonize::depythonize::<HashMap<String, serde_json::Value>>(globals.as_any())
+                    .map_err(|e| invalid_value(format!("Invalid globals: {e}")))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let profiles = profiles
+            .map(executor_profiles)
+            .transpose()?
+            .unwrap_or_default();
+        if run_history.is_none() && run_history_report_dir.is_some() {
+            return Err(validation_error(
+                "run_history_report_dir",
+                None,
+                "Run reports are only written with a run_history database",
+            ));
+        }
+        let run_history = run_history
+            .map(|path| {
+                let history = RunHistory::open(&path).map_err(|e| {
+                    validation_error(
+                        "run_history",
+                        Some(&path.display().to_string()),
+                        &format!("Failed to open the run history: {e}"),
+                    )
+                })?;
+                Ok::<_, PyErr>(match run_history_report_dir {
+                    Some(dir) => history.with_report_dir(dir),
+                    None => history,
+                })
+            })
+            .transpose()?;
+
+        let mut exec_config = ExecutionConfig {
+            mode,
+            concurrency_manager: Arc::new(ConcurrencyManager::new(concurrency)),
+            fail_fast,
+            max_node_execution_time_ms,
+            retry: retry.map(|policy| policy.inner.clone()),
+            circuit_breaker,
+            llm_middleware: middleware
+                .unwrap_or_default()
+                .iter()
+                .map(|layer| layer.inner.clone())
+                .collect(),
+            allow_shell_nodes,
+            allow_python_code_nodes,
+            budget: ExecutionBudget {
+                max_total_tokens,
+                max_cost_usd,
+                policy,
+            },
+            audit_log: audit_log.map(|path| AuditLog::new(JsonlAuditSink::new(path), audit_policy)),
+            capture_payloads: capture_payloads.then(|| {
+                capture_payload_max_chars.map_or_else(PayloadCaptureConfig::default, |max_chars| {
+                    PayloadCaptureConfig::default().with_max_payload_chars(max_chars)
+                })
+            }),
+            prompt_defaults: PromptDefaults {
+                system_preamble,
+                prompt_suffix,
+                globals,
+            },
+            profiles,
+            llm_call_spacing: min_interval_between_llm_calls_ms
+                .filter(|interval_ms| *interval_ms > 0)
+                .map(|interval_ms| Arc::new(LlmCallSpacing::new(interval_ms))),
+            run_history,
+            max_attachment_bytes,
+            ..ExecutionConfig::default()
+        };
 
         // Set timeout if specified
         if let Some(timeout) = timeout_seconds {
@@ -127,6 +573,11 @@ impl Executor {
 
         // Set debug mode - defaults to false
         exec_config.enable_tracing = debug.unwrap_or(false);
+        exec_config.strict_tool_args = strict_tool_args;
+        exec_config.tool_call_trace = ToolCallTraceConfig {
+            redact_payloads: redact_tool_calls,
+            max_payload_chars: tool_call_max_chars,
+        };
 
         if exec_config.enable_tracing {
             info!(
@@ -138,23 +589,84 @@ impl Executor {
         Ok(Self {
             config: exec_config,
             llm_config: config,
-            stats: ExecutionStats::default(),
+            stats: Mutex::new(ExecutionStats::default()),
         })
     }
 
+    /// Create an executor with the settings of the `GRAPHBIT_*` environment
+    /// variables: `GRAPHBIT_MAX_CONCURRENCY`, `GRAPHBIT_FAIL_FAST`,
+    /// `GRAPHBIT_MAX_NODE_EXECUTION_TIME_MS`, `GRAPHBIT_MAX_TOTAL_TOKENS` and
+    /// `GRAPHBIT_MAX_COST_USD`. `config` defaults to `LlmConfig.from_env()`;
+    /// `config` and keyword arguments, those of the constructor, take
+    /// precedence over the variables.
+    #[classmethod]
+    #[pyo3(signature = (config=None, **kwargs))]
+    fn from_env<'py>(
+        cls: &Bound<'py, PyType>,
+        config: Option<LlmConfig>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let py = cls.py();
+        let env = EnvConfig::from_env();
+        let config = match config {
+            Some(config) => config,
+            None => LlmConfig::from_env_config(&env, None)?,
+        };
+        let defaults = env.executor_defaults().map_err(to_py_error)?;
+        let arguments = PyDict::new(py);
+        for (name, value) in pythonize::pythonize(py, &defaults)
+            .map_err(|e| invalid_value(format!("Invalid executor defaults: {e}")))?
+            .downcast::<PyDict>()?
+        {
+            if !value.is_none() {
+                arguments.set_item(name, value)?;
+            }
+        }
+        if let Some(kwargs) = kwargs {
+            arguments.update(kwargs.as_mapping())?;
+        }
+        cls.call((config,), Some(&arguments))
+    }
+
     /// Execute a workflow with comprehensive error handling and monitoring.
     ///
     /// `policy` is optional. When provided: encode before every LLM call, decode after every LLM call;
     /// before tool usage decode (so tools see real PII); after tool usage do nothing (no encode).
-    #[instrument(skip(self, py, workflow, policy), fields(workflow_name = %workflow.inner.name))]
-    #[pyo3(signature = (workflow, policy=None))]
+    ///
+    /// `inputs` seed the workflow variables (`{{input.NAME}}` in prompts, `vars.NAME` in
+    /// expressions). `secrets` fill `{{secret.NAME}}` references in node LLM configs and
+    /// HTTP request URLs and headers, and are redacted from the result.
+    ///
+    /// `profile` selects one of the executor's LLM `profiles` for this run.
+    ///
+    /// `only_tags` runs only the nodes carrying one of those tags and `skip_tags`
+    /// skips the nodes carrying any of them. The dependents of a skipped node are
+    /// skipped too, unless the node is tagged `passthrough_on_skip`: its input is
+    /// then passed on as its output.
+    ///
+    /// `warm_up` warms up the providers of the workflow, as `warm_up` does,
+    /// before its first node runs; the result's `get_warm_up_report()` returns
+    /// the report.
+    #[instrument(skip(self, py, workflow, policy, inputs, secrets), fields(workflow_name = %workflow.inner.name))]
+    #[pyo3(signature = (workflow, policy=None, inputs=None, secrets=None, profile=None, only_tags=None, skip_tags=None, warm_up=false))]
     fn execute(
-        &mut self,
+        &self,
         py: Python<'_>,
         workflow: &Workflow,
         policy: Option<&Bound<'_, GuardRailPolicyConfig>>,
+        inputs: Option<&Bound<'_, PyDict>>,
+        secrets: Option<HashMap<String, String>>,
+        profile: Option<String>,
+        only_tags: Option<Vec<String>>,
+        skip_tags: Option<Vec<String>>,
+        warm_up: bool,
     ) -> PyResult<WorkflowResult> {
         let start_time = Instant::now();
+        let inputs = workflow_inputs(inputs)?;
+        let secrets = Secrets::from(secrets.unwrap_or_default());
+        let tag_filter = TagFilter::default()
+            .with_only_tags(only_tags.unwrap_or_default())
+            .with_skip_tags(skip_tags.unwrap_or_default());
 
         // Validate workflow
         if workflow.inner.graph.node_count() == 0 {
@@ -196,7 +708,7 @@ impl Executor {
         // Release the GIL before entering the async runtime to prevent deadlocks
         // when the async code needs to call back into Python
         let result = py.allow_threads(|| {
-            get_runtime().block_on(async move {
+            block_on(async move {
                 // Apply timeout to the entire execution
                 tokio::time::timeout(timeout_duration, async move {
                     Self::execute_workflow_internal(
@@ -204,6 +716,12 @@ impl Executor {
                         workflow_clone,
                         config,
                         guardrail_enforcer,
+                        inputs,
+                        secrets,
+                        profile,
+                        tag_filter,
+                        warm_up,
+                        uuid::Uuid::new_v4(),
                     )
                     .await
                 })
@@ -228,7 +746,7 @@ impl Executor {
                 if debug {
                     error!("Workflow execution failed: {}", e);
                 }
-                Err(to_py_runtime_error(e))
+                Err(to_py_error(e))
             }
             Err(_) => {
                 if debug {
@@ -243,15 +761,90 @@ impl Executor {
         }
     }
 
+    /// Re-run the nodes `from_nodes`, given by name, and every node downstream
+    /// of them, reusing the outputs `result` recorded for the nodes upstream.
+    ///
+    /// `workflow` may be rebuilt after fixing a prompt; its nodes are matched to
+    /// the earlier run by name. Upstream nodes without an output in `result` run
+    /// again, and the result lists the reused nodes in `get_reused_nodes()`.
+    /// `inputs` of the earlier run are reused; `secrets` are never kept in a
+    /// result and are passed again.
+    #[instrument(skip(self, py, workflow, result, policy, secrets), fields(workflow_name = %workflow.inner.name))]
+    #[pyo3(signature = (workflow, result, from_nodes, policy=None, secrets=None))]
+    fn rerun(
+        &self,
+        py: Python<'_>,
+        workflow: &Workflow,
+        result: &WorkflowResult,
+        from_nodes: Vec<String>,
+        policy: Option<&Bound<'_, GuardRailPolicyConfig>>,
+        secrets: Option<HashMap<String, String>>,
+    ) -> PyResult<WorkflowResult> {
+        let start_time = Instant::now();
+        let from_nodes: Vec<&str> = from_nodes.iter().map(String::as_str).collect();
+        let context =
+            CoreWorkflowExecutor::reexecution_context(&workflow.inner, &result.inner, &from_nodes)
+                .map_err(to_py_error)?
+                .with_secrets(Secrets::from(secrets.unwrap_or_default()));
+
+        let llm_config = self.llm_config.inner.clone();
+        let workflow = workflow.inner.clone();
+        let config = self.config.clone();
+        let timeout_duration = config.timeout;
+        let guardrail_enforcer = policy.map(|p| {
+            Arc::new(GuardRail::enforcer_for(
+                p.borrow().get_inner(),
+                workflow.id.to_string(),
+            ))
+        });
+
+        let outcome = py.allow_threads(|| {
+            block_on(async move {
+                tokio::time::timeout(
+                    timeout_duration,
+                    Self::execute_workflow_with_context(
+                        llm_config,
+                        workflow,
+                        config,
+                        guardrail_enforcer,
+                        context,
+                    ),
+                )
+                .await
+            })
+        });
+        self.update_stats(matches!(outcome, Ok(Ok(_))), start_time.elapsed());
+        match outcome {
+            Ok(Ok(context)) => Ok(WorkflowResult::new(context)),
+            Ok(Err(e)) => Err(to_py_error(e)),
+            Err(_) => Err(timeout_error(
+                "workflow_execution",
+                start_time.elapsed().as_millis() as u64,
+                &format!("Workflow execution timed out after {:?}", timeout_duration),
+            )),
+        }
+    }
+
     /// Async execution with enhanced performance optimizations
-    #[instrument(skip(self, workflow, py, policy), fields(workflow_name = %workflow.inner.name))]
-    #[pyo3(signature = (workflow, policy=None))]
+    #[instrument(skip(self, workflow, py, policy, inputs, secrets), fields(workflow_name = %workflow.inner.name))]
+    #[pyo3(signature = (workflow, policy=None, inputs=None, secrets=None, profile=None, only_tags=None, skip_tags=None, warm_up=false))]
     fn run_async<'a>(
-        &mut self,
+        &self,
         workflow: &Workflow,
         py: Python<'a>,
         policy: Option<&Bound<'_, GuardRailPolicyConfig>>,
+        inputs: Option<&Bound<'_, PyDict>>,
+        secrets: Option<HashMap<String, String>>,
+        profile: Option<String>,
+        only_tags: Option<Vec<String>>,
+        skip_tags: Option<Vec<String>>,
+        warm_up: bool,
     ) -> PyResult<Bound<'a, PyAny>> {
+        let inputs = workflow_inputs(inputs)?;
+        let secrets = Secrets::from(secrets.unwrap_or_default());
+        let tag_filter = TagFilter::default()
+            .with_only_tags(only_tags.unwrap_or_default())
+            .with_skip_tags(skip_tags.unwrap_or_default());
         // Validate workflow
         if let Err(e) = workflow.inner.validate() {
             return Err(validation_error(
@@ -289,6 +882,12 @@ impl Executor {
                     workflow_clone,
                     config,
                     guardrail_enforcer,
+                    inputs,
+                    secrets,
+                    profile,
+                    tag_filter,
+                    warm_up,
+                    uuid::Uuid::new_v4(),
                 )
                 .await
             })
@@ -315,7 +914,7 @@ impl Executor {
                             duration, e
                         );
                     }
-                    Err(to_py_runtime_error(e))
+                    Err(to_py_error(e))
                 }
                 Err(_) => {
                     let duration = start_time.elapsed();
@@ -335,6 +934,208 @@ impl Executor {
         })
     }
 
+    /// Start executing `workflow` in the background and return its
+    /// `ExecutionHandle`, through which events are delivered to its external
+    /// event nodes and its result is awaited. The arguments are those of
+    /// `execute`.
+    #[instrument(skip(self, workflow, policy, inputs, secrets), fields(workflow_name = %workflow.inner.name))]
+    #[pyo3(signature = (workflow, policy=None, inputs=None, secrets=None, profile=None, only_tags=None, skip_tags=None, warm_up=false))]
+    fn execute_async(
+        &self,
+        workflow: &Workflow,
+        policy: Option<&Bound<'_, GuardRailPolicyConfig>>,
+        inputs: Option<&Bound<'_, PyDict>>,
+        secrets: Option<HashMap<String, String>>,
+        profile: Option<String>,
+        only_tags: Option<Vec<String>>,
+        skip_tags: Option<Vec<String>>,
+        warm_up: bool,
+    ) -> PyResult<ExecutionHandle> {
+        let inputs = workflow_inputs(inputs)?;
+        let secrets = Secrets::from(secrets.unwrap_or_default());
+        let tag_filter = TagFilter::default()
+            .with_only_tags(only_tags.unwrap_or_default())
+            .with_skip_tags(skip_tags.unwrap_or_default());
+        if let Err(e) = workflow.inner.validate() {
+            return Err(validation_error(
+                "workflow",
+                None,
+                &format!("Invalid workflow: {e}"),
+            ));
+        }
+
+        let workflow_clone = workflow.inner.clone();
+        let llm_config = self.llm_config.inner.clone();
+        let config = self.config.clone();
+        let timeout_duration = config.timeout;
+        let external_events = config.external_events.clone();
+        let guardrail_enforcer = policy.map(|p| {
+            let config = p.borrow().get_inner();
+            Arc::new(GuardRail::enforcer_for(
+                config,
+                workflow_clone.id.to_string(),
+            ))
+        });
+
+        let execution_id = uuid::Uuid::new_v4();
+        let task = get_runtime().spawn(async move {
+            tokio::time::timeout(
+                timeout_duration,
+                Box::pin(Self::execute_workflow_internal(
+                    llm_config,
+                    workflow_clone,
+                    config,
+                    guardrail_enforcer,
+                    inputs,
+                    secrets,
+                    profile,
+                    tag_filter,
+                    warm_up,
+                    execution_id,
+                )),
+            )
+            .await
+            .ok()
+        });
+        Ok(ExecutionHandle::new(
+            execution_id.to_string(),
+            external_events,
+            timeout_duration,
+            task,
+        ))
+    }
+
+    /// Execute a workflow once for each dict of `inputs`, returning one result
+    /// per dict in the same order.
+    ///
+    /// When `output_jsonl` is given, each execution's result is appended to that
+    /// file as one JSON line as soon as the execution completes: its execution
+    /// id, the position of its inputs, the outputs of its nodes keyed by name,
+    /// its token usage and its error. Every line is synced to disk before the
+    /// next is written, so the results finished before a crash are kept. With
+    /// `output_max_bytes` the file is rotated to `output_jsonl.1`,
+    /// `output_jsonl.2` and so on once it would grow past that size.
+    ///
+    /// An execution that fails or times out yields a failed result instead of
+    /// raising. The other arguments are those of `execute`.
+    #[instrument(skip(self, py, workflow, inputs, policy, secrets), fields(workflow_name = %workflow.inner.name))]
+    #[pyo3(signature = (workflow, inputs, output_jsonl=None, output_max_bytes=None, policy=None, secrets=None, profile=None, only_tags=None, skip_tags=None))]
+    fn execute_batch(
+        &self,
+        py: Python<'_>,
+        workflow: &Workflow,
+        inputs: Vec<Bound<'_, PyDict>>,
+        output_jsonl: Option<std::path::PathBuf>,
+        output_max_bytes: Option<u64>,
+        policy: Option<&Bound<'_, GuardRailPolicyConfig>>,
+        secrets: Option<HashMap<String, String>>,
+        profile: Option<String>,
+        only_tags: Option<Vec<String>>,
+        skip_tags: Option<Vec<String>>,
+    ) -> PyResult<Vec<WorkflowResult>> {
+        let inputs = inputs
+            .into_iter()
+            .map(|inputs| workflow_inputs(Some(&inputs)))
+            .collect::<PyResult<Vec<_>>>()?;
+        let secrets = Secrets::from(secrets.unwrap_or_default());
+        let tag_filter = TagFilter::default()
+            .with_only_tags(only_tags.unwrap_or_default())
+            .with_skip_tags(skip_tags.unwrap_or_default());
+        if workflow.inner.graph.node_count() == 0 {
+            return Err(validation_error(
+                "workflow",
+                None,
+                "Workflow cannot be empty",
+            ));
+        }
+        if let Err(e) = workflow.inner.validate() {
+            return Err(validation_error(
+                "workflow",
+                None,
+                &format!("Invalid workflow: {e}"),
+            ));
+        }
+        let sink = output_jsonl.map(|path| {
+            let sink = ResultSink::new(path);
+            Arc::new(match output_max_bytes {
+                Some(max_bytes) => sink.with_max_bytes(max_bytes),
+                None => sink,
+            })
+        });
+
+        let workflow_clone = workflow.inner.clone();
+        let llm_config = self.llm_config.inner.clone();
+        let config = self.config.clone();
+        let guardrail_enforcer = policy.map(|p| {
+            let config = p.borrow().get_inner();
+            Arc::new(GuardRail::enforcer_for(
+                config,
+                workflow_clone.id.to_string(),
+            ))
+        });
+
+        let results = py.allow_threads(|| {
+            block_on(async move {
+                use futures::StreamExt;
+
+                let limit = config
+                    .concurrency_manager
+                    .get_available_permits()
+                    .await
+                    .get("global")
+                    .copied()
+                    .unwrap_or(16)
+                    .max(1);
+                futures::stream::iter(inputs.into_iter().enumerate())
+                    .map(|(index, inputs)| {
+                        Self::execute_batch_item(
+                            llm_config.clone(),
+                            workflow_clone.clone(),
+                            config.clone(),
+                            guardrail_enforcer.clone(),
+                            inputs,
+                            secrets.clone(),
+                            profile.clone(),
+                            tag_filter.clone(),
+                            index,
+                            sink.clone(),
+                        )
+                    })
+                    .buffered(limit)
+                    .collect::<Vec<_>>()
+                    .await
+            })
+        });
+
+        Ok(results
+            .into_iter()
+            .map(|(context, duration)| {
+                let success = !matches!(
+                    context.state,
+                    graphbit_core::types::WorkflowState::Failed { .. }
+                );
+                self.update_stats(success, duration);
+                WorkflowResult::new(context)
+            })
+            .collect())
+    }
+
+    /// Deliver `event_name` with `payload` to the running execution
+    /// `execution_id`, resuming the external event node waiting for it
+    fn deliver_event(
+        &self,
+        execution_id: &str,
+        event_name: &str,
+        payload: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        let payload = pythonize::depythonize::<serde_json::Value>(payload)
+            .map_err(|e| invalid_value(format!("Invalid event payload: {e}")))?;
+        self.config
+            .external_events
+            .deliver(execution_id, event_name, payload)
+            .map_err(to_py_error)
+    }
+
     /// Configure the executor with new settings
     #[pyo3(signature = (timeout_seconds=None, max_retries=None, enable_metrics=None, debug=None))]
     fn configure(
@@ -389,37 +1190,308 @@ impl Executor {
         Ok(())
     }
 
+    /// Register a reusable agent so nodes created with `Node.agent(..., agent=...)`
+    /// share this instance instead of building their own. Registering an agent
+    /// with the same ID again replaces the previous registration.
+    fn register_agent(&mut self, agent: PyRef<'_, Agent>) {
+        let agent_id = agent.agent_id().clone();
+        self.config.agents.retain(|a| a.id() != &agent_id);
+        self.config.agents.push(agent.as_trait_object());
+
+        if self.config.enable_tracing {
+            info!("Registered agent '{}'", agent_id);
+        }
+    }
+
+    /// Run workflows on a frozen clock, on which delay nodes, retry backoff
+    /// and pacing take no real time. Meant for test harnesses.
+    fn _with_frozen_time(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf.config.clock = Some(Arc::new(TestClock::frozen(chrono::Utc::now())));
+        slf
+    }
+
+    /// Current executor metrics as a dict of `counters`, `gauges` and
+    /// `histograms`, each a list of samples with `name`, `labels` and `value`;
+    /// empty until `enable_metrics` is called
+    fn metrics_snapshot(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let snapshot = graphbit_core::telemetry::installed()
+            .map(|recorder| recorder.snapshot())
+            .unwrap_or_default();
+        Ok(pythonize::pythonize(py, &snapshot)?.unbind())
+    }
+
     /// Get comprehensive execution statistics
     fn get_stats<'a>(&self, py: Python<'a>) -> PyResult<Bound<'a, PyDict>> {
         let dict = PyDict::new(py);
+        let stats = self.lock_stats().clone();
 
-        dict.set_item("total_executions", self.stats.total_executions)?;
-        dict.set_item("successful_executions", self.stats.successful_executions)?;
-        dict.set_item("failed_executions", self.stats.failed_executions)?;
+        dict.set_item("total_executions", stats.total_executions)?;
+        dict.set_item("successful_executions", stats.successful_executions)?;
+        dict.set_item("failed_executions", stats.failed_executions)?;
         dict.set_item(
             "success_rate",
-            if self.stats.total_executions > 0 {
-                self.stats.successful_executions as f64 / self.stats.total_executions as f64
+            if stats.total_executions > 0 {
+                stats.successful_executions as f64 / stats.total_executions as f64
             } else {
                 0.0
             },
         )?;
-        dict.set_item("average_duration_ms", self.stats.average_duration_ms)?;
-        dict.set_item("total_duration_ms", self.stats.total_duration_ms)?;
-        dict.set_item("uptime_seconds", self.stats.created_at.elapsed().as_secs())?;
+        dict.set_item("average_duration_ms", stats.average_duration_ms)?;
+        dict.set_item("total_duration_ms", stats.total_duration_ms)?;
+        dict.set_item("uptime_seconds", stats.created_at.elapsed().as_secs())?;
 
         // Configuration info
         dict.set_item("execution_mode", format!("{:?}", self.config.mode))?;
         dict.set_item("timeout_seconds", self.config.timeout.as_secs())?;
         dict.set_item("max_retries", self.config.max_retries)?;
         dict.set_item("metrics_enabled", self.config.enable_metrics)?;
+        dict.set_item("strict_tool_args", self.config.strict_tool_args)?;
+        dict.set_item("allow_shell_nodes", self.config.allow_shell_nodes)?;
+        dict.set_item(
+            "allow_python_code_nodes",
+            self.config.allow_python_code_nodes,
+        )?;
+        dict.set_item("max_attachment_bytes", self.config.max_attachment_bytes)?;
+        dict.set_item("max_total_tokens", self.config.budget.max_total_tokens)?;
+        dict.set_item("max_cost_usd", self.config.budget.max_cost_usd)?;
+        dict.set_item(
+            "budget_policy",
+            match self.config.budget.policy {
+                BudgetPolicy::Finish => "finish",
+                BudgetPolicy::Cancel => "cancel",
+            },
+        )?;
+        dict.set_item(
+            "redact_tool_calls",
+            self.config.tool_call_trace.redact_payloads,
+        )?;
+        dict.set_item(
+            "tool_call_max_chars",
+            self.config.tool_call_trace.max_payload_chars,
+        )?;
 
         Ok(dict)
     }
 
+    /// Get concurrency statistics accumulated over every run of this executor:
+    /// permit acquisitions and wait times, and current and peak active nodes
+    fn stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let manager = self.config.concurrency_manager.clone();
+        let stats = py.allow_threads(|| block_on(manager.get_stats()));
+        Ok(pythonize::pythonize(py, &stats)?.into())
+    }
+
+    /// List the runs recorded in the executor's `run_history`, newest first,
+    /// as dicts with `execution_id`, `workflow_id`, `workflow_name`,
+    /// `workflow_version`, `state`, `error`, `started_at`, `completed_at`,
+    /// `stats` and `report_path`. `since` and `until` bound the start time and
+    /// take a `datetime` or an ISO 8601 string; naive times are read as UTC.
+    #[pyo3(signature = (limit=20, workflow_name=None, state=None, since=None, until=None))]
+    fn list_runs(
+        &self,
+        py: Python<'_>,
+        limit: usize,
+        workflow_name: Option<String>,
+        state: Option<&str>,
+        since: Option<&Bound<'_, PyAny>>,
+        until: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let history = self.require_run_history()?;
+        let mut query = RunQuery::new().with_limit(limit).started_between(
+            since.map(|time| run_time("since", time)).transpose()?,
+            until.map(|time| run_time("until", time)).transpose()?,
+        );
+        if let Some(name) = workflow_name {
+            query = query.with_workflow_name(name);
+        }
+        if let Some(state) = state {
+            query = query.with_state(RunState::from_str_opt(state).ok_or_else(|| {
+                validation_error(
+                    "state",
+                    Some(state),
+                    "State must be one of: running, completed, partially_completed, failed, cancelled",
+                )
+            })?);
+        }
+        let runs = py
+            .allow_threads(|| block_on(history.list(&query)))
+            .map_err(to_py_error)?;
+        Ok(pythonize::pythonize(py, &runs)?.unbind())
+    }
+
+    /// Remove the runs started more than `max_age_seconds` ago and all but the
+    /// `max_runs` most recent ones from the executor's `run_history`, with their
+    /// archived reports. Returns the number of runs removed.
+    #[pyo3(signature = (max_age_seconds=None, max_runs=None))]
+    fn prune_runs(
+        &self,
+        py: Python<'_>,
+        max_age_seconds: Option<f64>,
+        max_runs: Option<usize>,
+    ) -> PyResult<usize> {
+        let history = self.require_run_history()?;
+        let max_age = max_age_seconds
+            .map(|seconds| {
+                Duration::try_from_secs_f64(seconds).map_err(|_| {
+                    validation_error(
+                        "max_age_seconds",
+                        Some(&seconds.to_string()),
+                        "Maximum age must be a non-negative number of seconds",
+                    )
+                })
+            })
+            .transpose()?;
+        let retention = RunRetention { max_age, max_runs };
+        py.allow_threads(|| block_on(history.prune(retention)))
+            .map_err(to_py_error)
+    }
+
+    /// Add up the usage of the runs recorded in the executor's `run_history`
+    /// that started within the last `period_seconds`, to charge LLM spend back.
+    /// Returns a dict with `since`, the number of `runs`, their `total` usage,
+    /// `by_tag`, the usage by node tag, and `by_metadata`, the usage by the
+    /// value of each attributed workflow metadata key such as `team`. Each
+    /// usage is a dict with `usage`, the `prompt_tokens`, `completion_tokens`
+    /// and `total_tokens`, and the estimated `cost_usd`.
+    fn usage_summary(&self, py: Python<'_>, period_seconds: f64) -> PyResult<PyObject> {
+        let history = self.require_run_history()?;
+        let period = Duration::try_from_secs_f64(period_seconds)
+            .ok()
+            .and_then(|period| chrono::Duration::from_std(period).ok())
+            .ok_or_else(|| {
+                validation_error(
+                    "period_seconds",
+                    Some(&period_seconds.to_string()),
+                    "Period must be a non-negative number of seconds",
+                )
+            })?;
+        let summary = py
+            .allow_threads(|| block_on(history.usage_summary(chrono::Utc::now() - period)))
+            .map_err(to_py_error)?;
+        Ok(pythonize::pythonize(py, &summary)?.unbind())
+    }
+
+    /// Check a workflow without running it: validate it and health-check every
+    /// distinct provider and model its agent nodes would call. Returns a dict with
+    /// `ok`, `validation_error`, `providers`, one health dict per provider,
+    /// `prompts`, the rendered `system_prompt` and `prompt` of each agent node,
+    /// and `input_schema`, the workflow's declared input schema or `None`.
+    fn dry_run(&self, py: Python<'_>, workflow: &Workflow) -> PyResult<PyObject> {
+        let executor = self
+            .config
+            .core_executor(self.llm_config.inner.clone(), HashMap::new());
+        let workflow = workflow.inner.clone();
+        let report = py
+            .allow_threads(|| block_on(executor.dry_run(&workflow)))
+            .map_err(to_py_error)?;
+
+        let ok = report.is_ok();
+        let mut value = serde_json::to_value(&report).map_err(|e| to_py_error(e.into()))?;
+        value["ok"] = serde_json::Value::Bool(ok);
+        Ok(pythonize::pythonize(py, &value)?.unbind())
+    }
+
+    /// Warm up the providers `workflow` calls before running it, so that its
+    /// first LLM calls do not pay for DNS lookups, TLS handshakes or model
+    /// loading.
+    ///
+    /// Agent nodes without a registered agent get one created, which the
+    /// executor keeps for its later runs. Every distinct provider and model of
+    /// the agent nodes and model routers then gets one request: a one-token
+    /// completion for providers whose models load on first use (Ollama,
+    /// HuggingFace, Replicate) when `prime` is set, a health check otherwise.
+    /// `secrets` and `profile` are those the runs will use.
+    ///
+    /// Returns a dict with `ok`, `providers`, one dict per provider and model
+    /// with the health fields of `dry_run`, `primed` and the `nodes` calling it,
+    /// `agents_created` and `duration_ms`. Failed requests are reported there
+    /// rather than raised.
+    #[pyo3(signature = (workflow, secrets=None, profile=None, prime=true))]
+    fn warm_up(
+        &mut self,
+        py: Python<'_>,
+        workflow: &Workflow,
+        secrets: Option<HashMap<String, String>>,
+        profile: Option<String>,
+        prime: bool,
+    ) -> PyResult<PyObject> {
+        let executor = self
+            .config
+            .core_executor(self.llm_config.inner.clone(), HashMap::new())
+            .with_warm_up_priming(prime);
+        let mut context = graphbit_core::types::WorkflowContext::new(workflow.inner.id.clone())
+            .with_secrets(Secrets::from(secrets.unwrap_or_default()));
+        if let Some(profile) = profile {
+            context = context.with_profile(profile);
+        }
+        let workflow = workflow.inner.clone();
+        let (report, agents) = py
+            .allow_threads(|| {
+                block_on(async {
+                    let report = executor.warm_up_with_context(&workflow, &context).await?;
+                    let mut agents = Vec::with_capacity(report.agents_created.len());
+                    for agent_id in &report.agents_created {
+                        agents.extend(executor.agent(agent_id).await);
+                    }
+                    Ok::<_, graphbit_core::errors::GraphBitError>((report, agents))
+                })
+            })
+            .map_err(to_py_error)?;
+        self.config.agents.extend(agents);
+
+        let ok = report.is_ok();
+        let mut value = serde_json::to_value(&report).map_err(|e| to_py_error(e.into()))?;
+        value["ok"] = serde_json::Value::Bool(ok);
+        Ok(pythonize::pythonize(py, &value)?.unbind())
+    }
+
+    /// Shut the executor down: runs started afterwards fail, runs still going
+    /// after `timeout` seconds are cancelled and end in the "cancelled" state,
+    /// and the audit log is flushed. With `metrics_url`, the job URL of a
+    /// Prometheus push gateway, the metrics recorded since `enable_metrics`
+    /// are pushed there last.
+    ///
+    /// Cancelled runs are recorded in the run history, archived to its report
+    /// directory when one is set, so they can be resumed with `rerun`.
+    ///
+    /// Returns a dict with `completed`, the number of runs that finished within
+    /// the timeout, `interrupted`, one dict per cancelled run with its
+    /// `execution_id`, `workflow_name`, `cancelled_nodes`, `skipped_nodes` and
+    /// `checkpoint` file, `audit_log_flushed`, `metrics_pushed`, `errors` of
+    /// the flushes and `duration_ms`.
+    #[pyo3(signature = (timeout=30.0, metrics_url=None))]
+    fn shutdown(
+        &self,
+        py: Python<'_>,
+        timeout: f64,
+        metrics_url: Option<String>,
+    ) -> PyResult<PyObject> {
+        let grace_period = grace_period(timeout)?;
+        let mut executor = self
+            .config
+            .core_executor(self.llm_config.inner.clone(), HashMap::new());
+        if let Some(url) = metrics_url {
+            executor = executor.with_metrics_push_url(url);
+        }
+        let summary = py.allow_threads(|| block_on(executor.shutdown(grace_period)));
+        Ok(pythonize::pythonize(py, &summary)?.unbind())
+    }
+
+    /// Call `shutdown(timeout)` when the interpreter exits, through `atexit`
+    #[pyo3(signature = (timeout=30.0))]
+    fn shutdown_on_exit(slf: &Bound<'_, Self>, timeout: f64) -> PyResult<()> {
+        grace_period(timeout)?;
+        let py = slf.py();
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("timeout", timeout)?;
+        py.import("atexit")?
+            .call_method("register", (slf.getattr("shutdown")?,), Some(&kwargs))?;
+        Ok(())
+    }
+
     /// Reset execution statistics
-    fn reset_stats(&mut self) -> PyResult<()> {
-        self.stats = ExecutionStats::default();
+    fn reset_stats(&self) -> PyResult<()> {
+        *self.lock_stats() = ExecutionStats::default();
         if self.config.enable_tracing {
             info!("Execution statistics reset");
         }
@@ -437,6 +1509,10 @@ impl Executor {
     /// `StreamEvent`.  Iteration blocks until the next event is available,
     /// releasing the GIL between receives so other Python threads can run.
     ///
+    /// When `output_jsonl` is given, the result of each node is also appended to
+    /// that file as one JSON line, synced to disk, as the node completes; see
+    /// `execute_batch` for the line format and `output_max_bytes`.
+    ///
     /// Example
     /// -------
     /// ```python
@@ -446,12 +1522,14 @@ impl Executor {
     ///
     /// To inspect all event types and fields programmatically, use:
     /// `Executor.get_stream_event_schema()`.
-    #[pyo3(signature = (workflow, policy=None, stream_mode=None))]
+    #[pyo3(signature = (workflow, policy=None, stream_mode=None, output_jsonl=None, output_max_bytes=None))]
     fn execute_streaming(
-        &mut self,
+        &self,
         workflow: &Workflow,
         policy: Option<&Bound<'_, GuardRailPolicyConfig>>,
         stream_mode: Option<&str>,
+        output_jsonl: Option<std::path::PathBuf>,
+        output_max_bytes: Option<u64>,
     ) -> PyResult<WorkflowStreamIterator> {
         // Validate workflow
         if workflow.inner.graph.node_count() == 0 {
@@ -483,6 +1561,13 @@ impl Executor {
             Arc::new(GuardRail::enforcer_for(cfg, workflow_clone.id.to_string()))
         });
         let guardrail_for_iterator = guardrail_enforcer.clone();
+        let sink = output_jsonl.map(|path| {
+            let sink = ResultSink::new(path);
+            Arc::new(match output_max_bytes {
+                Some(max_bytes) => sink.with_max_bytes(max_bytes),
+                None => sink,
+            })
+        });
 
         // Internal channel from core executor -> processor
         let (core_event_tx, mut core_event_rx) = tokio::sync::mpsc::channel::<StreamEvent>(256);
@@ -491,6 +1576,9 @@ impl Executor {
 
         // Stream mode for follow-up LLM calls in the live tool loop (Messages/All → token stream).
         let tool_loop_stream_mode = mode;
+        let strict_tool_args = config.strict_tool_args;
+        let tool_call_trace = config.tool_call_trace.clone();
+        let execution_config = config.clone();
 
         // Spawn the streaming executor on the shared Tokio runtime
         get_runtime().spawn(async move {
@@ -509,18 +1597,21 @@ impl Executor {
                     }
                 };
 
-            let executor = CoreWorkflowExecutor::new()
-                .with_default_llm_config(llm_config.clone())
-                .with_conditional_handlers(conditional_handlers);
+            let executor = execution_config.core_executor(llm_config.clone(), conditional_handlers);
 
             let core_event_tx_for_execution = core_event_tx.clone();
+            let mut context = graphbit_core::types::WorkflowContext::new(workflow_clone.id.clone());
+            if let Some(sink) = sink {
+                context = context.with_result_sink(sink);
+            }
             let result = tokio::time::timeout(timeout_duration, async move {
                 executor
-                    .execute_streaming(
+                    .execute_with_context(
                         workflow_clone.clone(),
                         guardrail_enforcer,
-                        core_event_tx_for_execution,
+                        Some(core_event_tx_for_execution),
                         mode,
+                        context,
                     )
                     .await
             })
@@ -578,17 +1669,28 @@ impl Executor {
                             continue;
                         }
 
-                        match Executor::run_live_tool_loop_for_node(
-                            &node_id,
-                            &node_name,
-                            &output,
-                            &processor_workflow,
-                            processor_llm_config.clone(),
-                            guardrail_for_iterator.clone(),
-                            &event_tx,
-                            tool_loop_stream_mode,
-                        )
-                        .await
+                        let audit_context = AuditContext {
+                            node_id: Some(node_id.clone()),
+                            node_name: Some(node_name.clone()),
+                            ..AuditContext::default()
+                        };
+                        match config
+                            .audited(
+                                audit_context,
+                                Executor::run_live_tool_loop_for_node(
+                                    &node_id,
+                                    &node_name,
+                                    &output,
+                                    &processor_workflow,
+                                    processor_llm_config.clone(),
+                                    guardrail_for_iterator.clone(),
+                                    &event_tx,
+                                    tool_loop_stream_mode,
+                                    strict_tool_args,
+                                    &config.llm_middleware,
+                                ),
+                            )
+                            .await
                         {
                             Ok((final_output, executions, tools_used, finish_reason)) => {
                                 live_node_outcomes.insert(
@@ -641,6 +1743,7 @@ impl Executor {
                                 executions,
                                 tools_used,
                                 &finish_reason,
+                                &tool_call_trace,
                             );
                         }
 
@@ -654,6 +1757,9 @@ impl Executor {
                                 user_stream_mode,
                                 tool_loop_stream_mode,
                                 &event_tx,
+                                strict_tool_args,
+                                &tool_call_trace,
+                                &config,
                             )
                             .await
                             {
@@ -754,6 +1860,7 @@ impl Executor {
             StreamEvent::WorkflowCompleted { .. } => "workflow_completed",
             StreamEvent::WorkflowFailed { .. } => "workflow_failed",
             StreamEvent::Token { .. } => "token",
+            StreamEvent::NodeOutputDelta { .. } => "node_output_delta",
             StreamEvent::LlmCallStarted { .. } => "llm_call_started",
             StreamEvent::LlmCallCompleted { .. } => "llm_call_completed",
             StreamEvent::ToolCallStarted { .. } => "tool_call_started",
@@ -767,10 +1874,13 @@ impl Executor {
         use StreamEvent::*;
         match stream_mode {
             StreamMode::All => true,
-            StreamMode::Updates => !matches!(event, Token { .. }),
+            StreamMode::Updates => !matches!(event, Token { .. } | NodeOutputDelta { .. }),
             StreamMode::Messages => matches!(
                 event,
-                Token { .. } | WorkflowCompleted { .. } | WorkflowFailed { .. }
+                Token { .. }
+                    | NodeOutputDelta { .. }
+                    | WorkflowCompleted { .. }
+                    | WorkflowFailed { .. }
             ),
         }
     }
@@ -849,6 +1959,36 @@ impl Executor {
         Self::apply_node_llm_overrides(req, node_config)
     }
 
+    /// Limit the earlier exchanges a follow-up request of a tool loop replays to
+    /// the node's history window, extending `summary` when the window
+    /// summarizes the exchanges it leaves out
+    async fn fit_history_window(
+        req: &mut graphbit_core::llm::LlmRequest,
+        node_config: &std::collections::HashMap<String, serde_json::Value>,
+        llm_provider: &graphbit_core::llm::LlmProvider,
+        summary: &mut Option<HistorySummary>,
+    ) -> Result<(), graphbit_core::errors::GraphBitError> {
+        if let Some(window) = HistoryWindow::from_node_config(node_config)? {
+            window
+                .fit(req, summary, llm_provider, &LlmMiddlewareStack::default())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Apply the node's `context_strategy` to a follow-up request of a tool loop,
+    /// whose accumulated tool results may outgrow the model's context window
+    fn fit_context_window(
+        req: &mut graphbit_core::llm::LlmRequest,
+        node_config: &std::collections::HashMap<String, serde_json::Value>,
+        llm_provider: &graphbit_core::llm::LlmProvider,
+    ) -> Result<(), graphbit_core::errors::GraphBitError> {
+        if let Some(window) = ContextWindow::for_node(node_config, llm_provider.provider())? {
+            window.fit(req)?;
+        }
+        Ok(())
+    }
+
     async fn execute_llm_request_with_optional_stream(
         llm_provider: &graphbit_core::llm::LlmProvider,
         req: graphbit_core::llm::LlmRequest,
@@ -878,6 +2018,18 @@ impl Executor {
                         },
                     )
                     .await;
+                    // Tool-call fragments are not part of the node's output text
+                    if chunk.tool_calls.is_empty() {
+                        Self::send_stream_event(
+                            event_tx,
+                            StreamEvent::NodeOutputDelta {
+                                node_id: node_id.to_string(),
+                                node_name: node_name.to_string(),
+                                delta: chunk.content.clone(),
+                            },
+                        )
+                        .await;
+                    }
                     accumulated_content.push_str(&chunk.content);
                 }
                 last_response = Some(chunk);
@@ -929,6 +2081,7 @@ impl Executor {
             | StreamEvent::NodeCompleted { node_id, .. }
             | StreamEvent::NodeFailed { node_id, .. }
             | StreamEvent::Token { node_id, .. }
+            | StreamEvent::NodeOutputDelta { node_id, .. }
             | StreamEvent::LlmCallStarted { node_id, .. }
             | StreamEvent::LlmCallCompleted { node_id, .. }
             | StreamEvent::ToolCallStarted { node_id, .. }
@@ -993,6 +2146,7 @@ impl Executor {
             {
                 context.node_outputs.remove(node_name_value);
                 context.variables.remove(node_name_value);
+                context.tool_calls.remove(node_name_value);
                 context
                     .metadata
                     .remove(&format!("node_response_{node_name_value}"));
@@ -1009,6 +2163,9 @@ impl Executor {
         user_stream_mode: StreamMode,
         tool_loop_stream_mode: StreamMode,
         event_tx: &tokio::sync::mpsc::Sender<TimedStreamEvent>,
+        strict_tool_args: bool,
+        tool_call_trace: &ToolCallTraceConfig,
+        config: &ExecutionConfig,
     ) -> Result<graphbit_core::types::WorkflowContext, graphbit_core::errors::GraphBitError> {
         while !parent_node_ids.is_empty() {
             let downstream_nodes =
@@ -1025,12 +2182,11 @@ impl Executor {
             Self::clear_context_for_node_ids(&mut context, &downstream_nodes);
 
             let conditional_handlers = crate::workflow::node::build_core_conditional_handlers(workflow)?;
-            let executor = CoreWorkflowExecutor::new()
-                .with_default_llm_config(llm_config.clone())
-                .with_conditional_handlers(conditional_handlers);
+            let executor = config.core_executor(llm_config.clone(), conditional_handlers);
 
             let (rerun_core_tx, mut rerun_core_rx) =
                 tokio::sync::mpsc::channel::<StreamEvent>(256);
+            let execution_id = context.execution_id.to_string();
             let rerun_context_input = context;
             let workflow_clone = workflow.clone();
             let guardrail_for_rerun = guardrail_enforcer.clone();
@@ -1075,16 +2231,26 @@ impl Executor {
                             continue;
                         }
 
-                        let (final_output, executions, tools_used, finish_reason) =
-                            Self::run_live_tool_loop_for_node(
-                                &node_id,
-                                &node_name,
-                                &output,
-                                workflow,
-                                llm_config.clone(),
-                                guardrail_enforcer.clone(),
-                                event_tx,
-                                tool_loop_stream_mode,
+                        let audit_context = AuditContext {
+                            execution_id: Some(execution_id.clone()),
+                            node_id: Some(node_id.clone()),
+                            node_name: Some(node_name.clone()),
+                        };
+                        let (final_output, executions, tools_used, finish_reason) = config
+                            .audited(
+                                audit_context,
+                                Self::run_live_tool_loop_for_node(
+                                    &node_id,
+                                    &node_name,
+                                    &output,
+                                    workflow,
+                                    llm_config.clone(),
+                                    guardrail_enforcer.clone(),
+                                    event_tx,
+                                    tool_loop_stream_mode,
+                                    strict_tool_args,
+                                    &config.llm_middleware,
+                                ),
                             )
                             .await?;
                         rerun_live_outcomes.insert(
@@ -1161,6 +2327,7 @@ impl Executor {
                     executions,
                     tools_used,
                     &finish_reason,
+                    tool_call_trace,
                 );
             }
 
@@ -1184,11 +2351,13 @@ impl Executor {
         guardrail_enforcer: Option<Arc<Enforcer>>,
         event_tx: &tokio::sync::mpsc::Sender<TimedStreamEvent>,
         stream_mode: StreamMode,
+        strict_tool_args: bool,
+        llm_middleware: &LlmMiddlewareStack,
     ) -> Result<
         (String, Vec<serde_json::Value>, Vec<String>, String),
         graphbit_core::errors::GraphBitError,
     > {
-        use crate::workflow::node::execute_production_tool_calls;
+        use crate::workflow::node::execute_validated_tool_calls;
         use graphbit_core::llm::{LlmMessage, LlmProvider, LlmTool, LlmToolCall};
 
         let response_obj = initial_output;
@@ -1223,9 +2392,15 @@ impl Executor {
         let llm_tools: Vec<LlmTool> = Self::extract_llm_tools(&node.config);
         let max_iterations = Self::extract_max_iterations(&node.config, 10);
 
-        let llm_provider =
-            graphbit_core::llm::LlmProviderFactory::create_provider(llm_config.clone())
-                .map(|provider_trait| LlmProvider::new(provider_trait, llm_config.clone()))
+        // Honour per-node model / LLM config overrides
+        let llm_config = node
+            .resolve_llm_config(Some(&llm_config))
+            .unwrap_or(llm_config);
+        let llm_provider = graphbit_core::llm::LlmProviderFactory::create_provider_with_middleware(
+            llm_config.clone(),
+            llm_middleware.clone(),
+        )
+        .map(|provider_trait| LlmProvider::new(provider_trait, llm_config.clone()))
                 .map_err(|e| {
                     graphbit_core::errors::GraphBitError::workflow_execution(format!(
                         "Failed to create LLM provider for live streaming loop: {e}",
@@ -1241,6 +2416,7 @@ impl Executor {
         let mut tools_used: Vec<String> = Vec::new();
         let mut llm_iteration: u64 = 1;
         let mut loop_iteration: usize = 0;
+        let mut history_summary = None;
 
         loop {
             if loop_iteration >= max_iterations {
@@ -1302,7 +2478,14 @@ impl Executor {
             })?;
 
             let tool_results_json = Python::with_gil(|py| {
-                execute_production_tool_calls(py, tool_calls_json, node_tools.clone())
+                execute_validated_tool_calls(
+                    py,
+                    tool_calls_json,
+                    node_tools.clone(),
+                    &llm_tools,
+                    strict_tool_args,
+                    None,
+                )
             })
             .map_err(|e| {
                 graphbit_core::errors::GraphBitError::workflow_execution(format!(
@@ -1311,6 +2494,7 @@ impl Executor {
             })?;
             let tool_results: Vec<serde_json::Value> =
                 serde_json::from_str(&tool_results_json).unwrap_or_default();
+            audit_python_tool_calls(&python_tool_calls, &tool_results);
 
             if loop_iteration > 1 {
                 for tc in &python_tool_calls {
@@ -1441,8 +2625,11 @@ impl Executor {
             )
             .await;
 
-            let req =
+            let mut req =
                 Self::build_llm_request_with_tools(messages.clone(), &llm_tools, &node.config);
+            Self::fit_history_window(&mut req, &node.config, &llm_provider, &mut history_summary)
+                .await?;
+            Self::fit_context_window(&mut req, &node.config, &llm_provider)?;
 
             let llm_start = std::time::Instant::now();
             let next_response = Self::execute_llm_request_with_optional_stream(
@@ -1547,11 +2734,13 @@ impl Executor {
         executions_to_append: Vec<serde_json::Value>,
         tools_used_to_add: Vec<String>,
         final_finish_reason: &str,
+        tool_call_trace: &ToolCallTraceConfig,
     ) {
         context.node_outputs.insert(
             node_id.to_string(),
             serde_json::Value::String(final_output.to_string()),
         );
+        Self::record_tool_call_entries(context, node_name, &executions_to_append, tool_call_trace);
 
         for key in [
             format!("node_response_{node_id}"),
@@ -1636,6 +2825,56 @@ impl Executor {
         }
     }
 
+    /// Record the `tool_call` entries of a node's executions as [`ToolCallRecord`]s,
+    /// preferring guardrail-masked parameters and output when present.
+    fn record_tool_call_entries(
+        context: &mut graphbit_core::types::WorkflowContext,
+        node_name: &str,
+        entries: &[serde_json::Value],
+        tool_call_trace: &ToolCallTraceConfig,
+    ) {
+        for entry in entries
+            .iter()
+            .filter(|e| e.get("type").and_then(|v| v.as_str()) == Some("tool_call"))
+        {
+            let tool_name = entry
+                .get("tool_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let success = entry
+                .get("success")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let arguments = entry
+                .get("parameters_masked")
+                .or_else(|| entry.get("parameters"))
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            let output = if success {
+                entry.get("output_masked").or_else(|| entry.get("output"))
+            } else {
+                entry.get("error")
+            }
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+            let duration_ms = entry
+                .get("latency_ms")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+
+            context.record_tool_call(
+                node_name,
+                tool_call_trace.apply(ToolCallRecord::new(
+                    tool_name,
+                    arguments,
+                    output,
+                    success,
+                    duration_ms,
+                )),
+            );
+        }
+    }
+
     fn append_tool_calls_to_llm_output(
         content: &str,
         tool_calls: &[graphbit_core::llm::LlmToolCall],
@@ -1656,26 +2895,128 @@ impl Executor {
         }
     }
 
+    /// Run one execution of a batch, appending its result to `sink`. A failed or
+    /// timed out execution yields a failed context.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_batch_item(
+        llm_config: graphbit_core::llm::LlmConfig,
+        workflow: graphbit_core::workflow::Workflow,
+        config: ExecutionConfig,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        inputs: HashMap<String, serde_json::Value>,
+        secrets: Secrets,
+        profile: Option<String>,
+        tag_filter: TagFilter,
+        index: usize,
+        sink: Option<Arc<ResultSink>>,
+    ) -> (graphbit_core::types::WorkflowContext, Duration) {
+        let start_time = Instant::now();
+        let timeout_duration = config.timeout;
+        let workflow_id = workflow.id.clone();
+        let execution_id = uuid::Uuid::new_v4();
+        let result = tokio::time::timeout(
+            timeout_duration,
+            Box::pin(Self::execute_workflow_internal(
+                llm_config,
+                workflow,
+                config,
+                guardrail_enforcer,
+                inputs,
+                secrets,
+                profile,
+                tag_filter,
+                false,
+                execution_id,
+            )),
+        )
+        .await;
+        let context = match result {
+            Ok(Ok(context)) => context,
+            failure => {
+                let error = match failure {
+                    Ok(Err(e)) => e.to_string(),
+                    _ => format!("Workflow execution timed out after {timeout_duration:?}"),
+                };
+                let mut context = graphbit_core::types::WorkflowContext::new(workflow_id);
+                context.execution_id = execution_id;
+                context.fail(error);
+                context
+            }
+        };
+        if let Some(sink) = sink {
+            let record = ResultRecord::for_workflow(&context).with_index(index);
+            if let Err(e) = sink.append(&record).await {
+                error!(
+                    "Failed to append execution result to {}: {}",
+                    sink.path().display(),
+                    e
+                );
+            }
+        }
+        (context, start_time.elapsed())
+    }
+
     /// Internal workflow execution with mode-specific optimizations and tool call handling.
     /// When `guardrail_enforcer` is `Some`, the core encodes before LLM and decodes after LLM;
     /// we decode before tool usage only (no encode after tool).
-    async fn execute_workflow_internal(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn execute_workflow_internal(
         llm_config: graphbit_core::llm::LlmConfig,
         workflow: graphbit_core::workflow::Workflow,
         config: ExecutionConfig,
         guardrail_enforcer: Option<Arc<Enforcer>>,
+        inputs: HashMap<String, serde_json::Value>,
+        secrets: Secrets,
+        profile: Option<String>,
+        tag_filter: TagFilter,
+        warm_up: bool,
+        execution_id: uuid::Uuid,
+    ) -> Result<graphbit_core::types::WorkflowContext, graphbit_core::errors::GraphBitError> {
+        let mut context = graphbit_core::types::WorkflowContext::new(workflow.id.clone())
+            .with_inputs(inputs)
+            .with_secrets(secrets)
+            .with_tag_filter(tag_filter)
+            .with_warm_up(warm_up);
+        context.execution_id = execution_id;
+        if let Some(profile) = profile {
+            context = context.with_profile(profile);
+        }
+        Self::execute_workflow_with_context(
+            llm_config,
+            workflow,
+            config,
+            guardrail_enforcer,
+            context,
+        )
+        .await
+    }
+
+    /// Run `workflow` from a prepared `context`, with the tool call handling of
+    /// [`execute_workflow_internal`](Self::execute_workflow_internal)
+    pub(crate) async fn execute_workflow_with_context(
+        llm_config: graphbit_core::llm::LlmConfig,
+        mut workflow: graphbit_core::workflow::Workflow,
+        config: ExecutionConfig,
+        guardrail_enforcer: Option<Arc<Enforcer>>,
+        context: graphbit_core::types::WorkflowContext,
     ) -> Result<graphbit_core::types::WorkflowContext, graphbit_core::errors::GraphBitError> {
         let conditional_handlers =
             crate::workflow::node::build_core_conditional_handlers(&workflow)?;
-        let executor = match config.mode {
-            ExecutionMode::Balanced => CoreWorkflowExecutor::new()
-                .with_default_llm_config(llm_config.clone())
-                .with_conditional_handlers(conditional_handlers),
-        };
+        let executor = config.core_executor(llm_config.clone(), conditional_handlers);
+
+        // Resolve secret references up front so the tool loop below sees the same
+        // node LLM configs as the core executor
+        workflow.inject_secrets(&context.secrets)?;
 
         // Execute the workflow (core applies encode before LLM, decode after LLM when enforcer is Some)
         let mut context = executor
-            .execute(workflow.clone(), guardrail_enforcer.clone())
+            .execute_with_context(
+                workflow.clone(),
+                guardrail_enforcer.clone(),
+                None,
+                StreamMode::Updates,
+                context,
+            )
             .await?;
 
         // Store LLM config in context metadata for tool call handling
@@ -1695,12 +3036,22 @@ impl Executor {
         let mut context = context;
         let mut rerun_attempts = 0;
         loop {
-            let (ctx, nodes_with_tool_calls) = Self::handle_tool_calls_in_context(
-                context,
-                &workflow,
-                guardrail_enforcer.as_ref().map(|arc| arc.as_ref()),
-            )
-            .await?;
+            let audit_context = AuditContext {
+                execution_id: Some(context.execution_id.to_string()),
+                ..AuditContext::default()
+            };
+            let (ctx, nodes_with_tool_calls) = config
+                .audited(
+                    audit_context,
+                    Self::handle_tool_calls_in_context(
+                        context,
+                        &workflow,
+                        guardrail_enforcer.as_ref().map(|arc| arc.as_ref()),
+                        config.strict_tool_args,
+                        &config.tool_call_trace,
+                    ),
+                )
+                .await?;
             context = ctx;
 
             if nodes_with_tool_calls.is_empty() {
@@ -1755,15 +3106,14 @@ impl Executor {
                     {
                         context.node_outputs.remove(node_name_value);
                         context.variables.remove(node_name_value);
+                        context.tool_calls.remove(node_name_value);
                         context.metadata.remove(&format!("node_response_{}", node_name_value));
                     }
                 }
             }
 
             let conditional_handlers = crate::workflow::node::build_core_conditional_handlers(&workflow)?;
-            let executor_clone = CoreWorkflowExecutor::new()
-                .with_default_llm_config(llm_config.clone())
-                .with_conditional_handlers(conditional_handlers);
+            let executor_clone = config.core_executor(llm_config.clone(), conditional_handlers);
 
             context = executor_clone
                 .execute_with_context(workflow.clone(), guardrail_enforcer.clone(), None, StreamMode::Updates, context)
@@ -1778,6 +3128,13 @@ impl Executor {
             }
         }
 
+        if let Some(audit_log) = &config.audit_log {
+            if let Err(e) = audit_log.flush().await {
+                warn!("Failed to write the audit log: {e}");
+            }
+        }
+
+        context.redact_secrets();
         Ok(context)
     }
 
@@ -1798,8 +3155,10 @@ impl Executor {
         mut context: graphbit_core::types::WorkflowContext,
         workflow: &graphbit_core::workflow::Workflow,
         guardrail_enforcer: Option<&Enforcer>,
+        strict_tool_args: bool,
+        tool_call_trace: &ToolCallTraceConfig,
     ) -> Result<(graphbit_core::types::WorkflowContext, Vec<String>), graphbit_core::errors::GraphBitError> {
-        use crate::workflow::node::execute_production_tool_calls;
+        use crate::workflow::node::execute_validated_tool_calls;
         use graphbit_core::llm::{LlmMessage, LlmProvider, LlmRequest, LlmTool, LlmToolCall};
 
         // Check each node output for tool_calls_required responses
@@ -1888,11 +3247,19 @@ impl Executor {
                                     .and_then(|v| v.as_u64())
                                     .unwrap_or(10) as usize;
 
-                            // Get LLM config for subsequent calls
+                            // Get LLM config for subsequent calls, honouring
+                            // per-node model / LLM config overrides
                             let llm_config = context.metadata.get("llm_config").and_then(|v| {
                                 serde_json::from_value::<graphbit_core::llm::LlmConfig>(v.clone())
                                     .ok()
                             });
+                            // The selected profile's configuration comes below the node's own
+                            let llm_config = context
+                                .node_llm_configs
+                                .get(&node.id)
+                                .cloned()
+                                .or(llm_config);
+                            let llm_config = node.resolve_llm_config(llm_config.as_ref());
 
                             let llm_config = match llm_config {
                                 Some(cfg) => cfg,
@@ -1905,8 +3272,9 @@ impl Executor {
                             };
 
                             let llm_provider =
-                                match graphbit_core::llm::LlmProviderFactory::create_provider(
+                                match graphbit_core::llm::LlmProviderFactory::create_provider_with_middleware(
                                     llm_config.clone(),
+                                    context.llm_middleware.clone(),
                                 ) {
                                     Ok(provider_trait) => {
                                         LlmProvider::new(provider_trait, llm_config.clone())
@@ -1946,6 +3314,7 @@ impl Executor {
                             let mut all_tool_executions: Vec<serde_json::Value> = Vec::new();
                             let mut iteration: usize = 0;
                             let mut llm_calls_in_loop: usize = 0;
+                            let mut history_summary = None;
                             let mut final_content = current_content.clone();
                             let mut final_finish_reason = "tool_calls_required".to_string();
                             let mut final_raw_output_for_meta = final_content.clone();
@@ -2048,10 +3417,13 @@ impl Executor {
                                     })?;
 
                                 let tool_results_json = Python::with_gil(|py| {
-                                    execute_production_tool_calls(
+                                    execute_validated_tool_calls(
                                         py,
                                         tool_calls_json,
                                         node_tools.clone(),
+                                        &llm_tools,
+                                        strict_tool_args,
+                                        Some((&mut context, &node_name)),
                                     )
                                 })
                                 .map_err(|e| {
@@ -2066,6 +3438,7 @@ impl Executor {
                                 let tool_execution_results: Vec<serde_json::Value> =
                                     serde_json::from_str(&tool_results_json)
                                         .unwrap_or_else(|_| Vec::new());
+                                audit_python_tool_calls(&python_tool_calls, &tool_execution_results);
 
                                 // ---- Step 3: Append tool result messages to history ----
                                 for (i, result) in tool_execution_results.iter().enumerate() {
@@ -2203,6 +3576,12 @@ impl Executor {
                                         "latency_ms": latency_ms,
                                         "retries": []
                                     });
+                                    Self::record_tool_call_entries(
+                                        &mut context,
+                                        &node_name,
+                                        std::slice::from_ref(&entry),
+                                        tool_call_trace,
+                                    );
                                     executions.push(entry);
                                 }
 
@@ -2259,8 +3638,24 @@ impl Executor {
                                 }
 
                                 let llm_start = std::time::Instant::now();
-                                let next_response = match llm_provider.complete(next_request).await
-                                {
+                                let next_response = match Self::fit_history_window(
+                                    &mut next_request,
+                                    &node.config,
+                                    &llm_provider,
+                                    &mut history_summary,
+                                )
+                                .await
+                                .and_then(|()| {
+                                    Self::fit_context_window(
+                                        &mut next_request,
+                                        &node.config,
+                                        &llm_provider,
+                                    )
+                                }) {
+                                    Ok(()) => llm_provider.complete(next_request).await,
+                                    Err(e) => Err(e),
+                                };
+                                let next_response = match next_response {
                                     Ok(resp) => resp,
                                     Err(e) => {
                                         tracing::error!(
@@ -2627,25 +4022,68 @@ impl Executor {
     }
 
     /// Update execution statistics
-    fn update_stats(&mut self, success: bool, duration: Duration) {
+    fn update_stats(&self, success: bool, duration: Duration) {
         if !self.config.enable_metrics {
             return;
         }
+        let mut stats = self.lock_stats();
 
-        self.stats.total_executions += 1;
+        stats.total_executions += 1;
         let duration_ms = duration.as_millis() as u64;
-        self.stats.total_duration_ms += duration_ms;
+        stats.total_duration_ms += duration_ms;
 
         if success {
-            self.stats.successful_executions += 1;
+            stats.successful_executions += 1;
         } else {
-            self.stats.failed_executions += 1;
+            stats.failed_executions += 1;
         }
 
         // Update average duration (simple moving average)
-        self.stats.average_duration_ms =
-            self.stats.total_duration_ms as f64 / self.stats.total_executions as f64;
+        stats.average_duration_ms = stats.total_duration_ms as f64 / stats.total_executions as f64;
     }
+
+    /// Lock the execution statistics, even if an earlier holder panicked
+    fn lock_stats(&self) -> MutexGuard<'_, ExecutionStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// The executor's run history, or an error if it was created without one
+    fn require_run_history(&self) -> PyResult<RunHistory> {
+        self.config.run_history.clone().ok_or_else(|| {
+            invalid_value("Executor has no run history; create it with run_history=<path>")
+        })
+    }
+}
+
+/// Start time bound `name` of `list_runs`, from a `datetime` or an ISO 8601
+/// string; times without a UTC offset are read as UTC
+fn run_time(name: &str, value: &Bound<'_, PyAny>) -> PyResult<chrono::DateTime<chrono::Utc>> {
+    let text = match value.extract::<String>() {
+        Ok(text) => text,
+        Err(_) => value.call_method0("isoformat")?.extract::<String>()?,
+    };
+    chrono::DateTime::parse_from_rfc3339(&text)
+        .map(|time| time.with_timezone(&chrono::Utc))
+        .or_else(|_| {
+            text.parse::<chrono::NaiveDateTime>()
+                .map(|time| time.and_utc())
+        })
+        .map_err(|e| validation_error(name, Some(&text), &format!("Invalid time: {e}")))
+}
+
+/// Convert the `inputs` argument of `execute` into workflow variables
+fn workflow_inputs(
+    inputs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<HashMap<String, serde_json::Value>> {
+    inputs.map_or_else(
+        || Ok(HashMap::new()),
+        |inputs| {
+            pythonize::depythonize(inputs)
+                .map_err(|e| invalid_value(format!("Inputs must be JSON-compatible: {e}")))
+        },
+    )
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -2672,6 +4110,7 @@ fn event_category_phase(event: &StreamEvent) -> (&'static str, &'static str) {
         NodeStarted { .. } => ("node", "started"),
         NodeCompleted { .. } => ("node", "completed"),
         NodeFailed { .. } => ("node", "failed"),
+        NodeOutputDelta { .. } => ("node", "delta"),
         Token { .. } => ("llm", "token"),
         LlmCallStarted { .. } => ("llm", "started"),
         LlmCallCompleted { .. } => ("llm", "completed"),
@@ -2711,7 +4150,7 @@ fn make_event_schema<'py>(
     phase_field.set_item("type", "str")?;
     phase_field.set_item(
         "description",
-        "Generic lifecycle phase: started | completed | failed | token",
+        "Generic lifecycle phase: started | completed | failed | token | delta",
     )?;
     fields_list.append(phase_field)?;
 
@@ -2726,6 +4165,44 @@ fn make_event_schema<'py>(
     Ok(event_dict)
 }
 
+/// Record Python tool calls, and the results they produced in the same order,
+/// in the audit log of the current scope
+fn audit_python_tool_calls(calls: &[serde_json::Value], results: &[serde_json::Value]) {
+    for (call, result) in calls.iter().zip(results) {
+        let text = |value: Option<&serde_json::Value>| match value {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        };
+        let success = result.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
+        graphbit_core::audit::record(AuditCall {
+            kind: AuditCallKind::Tool,
+            provider: "python".to_string(),
+            model: result
+                .get("tool_name")
+                .or_else(|| call.get("tool_name"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            started_at: result
+                .get("start_time")
+                .and_then(|v| v.as_str())
+                .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+                .map_or_else(chrono::Utc::now, |time| time.with_timezone(&chrono::Utc)),
+            latency_ms: result.get("latency_ms").and_then(|v| v.as_u64()).unwrap_or(0),
+            prompt: text(call.get("parameters")),
+            completion: success.then(|| text(result.get("output"))),
+            usage: None,
+            outcome: if success {
+                AuditOutcome::Success
+            } else {
+                AuditOutcome::Error {
+                    message: text(result.get("error")),
+                }
+            },
+        });
+    }
+}
+
 fn stream_event_schema_dict<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
     let schema = PyDict::new(py);
     schema.set_item(
@@ -2744,7 +4221,7 @@ fn stream_event_schema_dict<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>
     )?;
     dimensions.set_item(
         "phase",
-        "Generic lifecycle state: started | completed | failed | token",
+        "Generic lifecycle state: started | completed | failed | token | delta",
     )?;
     schema.set_item("generic_dimensions", dimensions)?;
 
@@ -2755,7 +4232,7 @@ fn stream_event_schema_dict<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>
     )?;
     modes.set_item(
         "messages",
-        "Token streaming + terminal workflow event (workflow_completed/workflow_failed)",
+        "Token and node output delta streaming + terminal workflow event (workflow_completed/workflow_failed)",
     )?;
     modes.set_item(
         "all",
@@ -2767,7 +4244,10 @@ fn stream_event_schema_dict<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>
         "workflow",
         vec!["workflow_started", "workflow_completed", "workflow_failed"],
     )?;
-    event_groups.set_item("node", vec!["node_started", "node_completed", "node_failed"])?;
+    event_groups.set_item(
+        "node",
+        vec!["node_started", "node_completed", "node_failed", "node_output_delta"],
+    )?;
     event_groups.set_item("llm", vec!["token", "llm_call_started", "llm_call_completed"])?;
     event_groups.set_item(
         "tool",
@@ -2832,6 +4312,19 @@ fn stream_event_schema_dict<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyDict>
             ("error_type", "str", "Error class hint (e.g. runtime_error, timeout_error)"),
         ],
     )?)?;
+    events.append(make_event_schema(
+        py,
+        "node_output_delta",
+        "Incremental piece of an agent node's output text (tool-call fragments excluded)",
+        "node",
+        "delta",
+        &[
+            ("event", "str", "Event type"),
+            ("node_id", "str", "Node UUID"),
+            ("node_name", "str", "Node display name"),
+            ("delta", "str", "Newly generated output text"),
+        ],
+    )?)?;
     events.append(make_event_schema(
         py,
         "token",
@@ -3027,6 +4520,16 @@ fn stream_event_to_dict<'py>(
             dict.set_item("llm_call_id", llm_call_id)?;
             dict.set_item("content", content)?;
         }
+        NodeOutputDelta {
+            node_id,
+            node_name,
+            delta,
+        } => {
+            dict.set_item("event", "node_output_delta")?;
+            dict.set_item("node_id", node_id)?;
+            dict.set_item("node_name", node_name)?;
+            dict.set_item("delta", delta)?;
+        }
         ToolCallStarted {
             node_id,
             node_name,
@@ -3171,7 +4674,7 @@ impl WorkflowStreamIterator {
         // Block this thread (GIL released) until an event arrives or channel closes.
         // `block_on` on the shared runtime drives the async recv to completion.
         let maybe_event = py.allow_threads(move || {
-            get_runtime().block_on(async move {
+            block_on(async move {
                 let mut rx = receiver.lock().await;
                 rx.recv().await
             })
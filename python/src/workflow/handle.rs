@@ -0,0 +1,138 @@
+//! Handle to a workflow execution running in the background
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use graphbit_core::ExternalEvents;
+use graphbit_core::errors::{GraphBitError, GraphBitResult};
+use graphbit_core::types::WorkflowContext;
+use pyo3::prelude::*;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use super::result::WorkflowResult;
+use crate::errors::{invalid_value, timeout_error, to_py_error};
+use crate::runtime::block_on;
+
+/// Outcome of an execution; `None` when it ran past the executor's timeout
+type Outcome = Option<GraphBitResult<WorkflowContext>>;
+
+enum Execution {
+    Running(JoinHandle<Outcome>),
+    Finished(Box<Outcome>),
+}
+
+/// Workflow execution started with `Executor.execute_async`
+///
+/// External event nodes of the execution wait for events delivered with
+/// `deliver_event`; `result` waits for the execution to finish.
+#[pyclass]
+pub struct ExecutionHandle {
+    execution_id: String,
+    external_events: ExternalEvents,
+    /// Executor timeout, reported when the execution runs past it
+    timeout: Duration,
+    execution: Arc<Mutex<Execution>>,
+}
+
+impl ExecutionHandle {
+    pub(crate) fn new(
+        execution_id: String,
+        external_events: ExternalEvents,
+        timeout: Duration,
+        task: JoinHandle<Outcome>,
+    ) -> Self {
+        Self {
+            execution_id,
+            external_events,
+            timeout,
+            execution: Arc::new(Mutex::new(Execution::Running(task))),
+        }
+    }
+}
+
+#[pymethods]
+impl ExecutionHandle {
+    /// Id of the execution, as accepted by `Executor.deliver_event`
+    #[getter]
+    fn execution_id(&self) -> String {
+        self.execution_id.clone()
+    }
+
+    /// Deliver `event_name` with `payload` to the execution. The external event
+    /// node waiting for it outputs `payload`; an event delivered before the
+    /// node runs is kept until it does.
+    fn deliver_event(&self, event_name: &str, payload: &Bound<'_, PyAny>) -> PyResult<()> {
+        let payload = pythonize::depythonize::<serde_json::Value>(payload)
+            .map_err(|e| invalid_value(format!("Invalid event payload: {e}")))?;
+        self.external_events
+            .deliver(&self.execution_id, event_name, payload)
+            .map_err(to_py_error)
+    }
+
+    /// Names of the events external event nodes are waiting for, sorted
+    fn waiting_events(&self) -> Vec<String> {
+        self.external_events.waiting(&self.execution_id)
+    }
+
+    /// Whether the execution has finished
+    fn is_done(&self) -> bool {
+        self.execution
+            .try_lock()
+            .is_ok_and(|execution| match &*execution {
+                Execution::Running(task) => task.is_finished(),
+                Execution::Finished(_) => true,
+            })
+    }
+
+    /// Wait for the execution to finish and return its result, waiting at
+    /// most `timeout_seconds` when given
+    #[pyo3(signature = (timeout_seconds=None))]
+    fn result(&self, py: Python<'_>, timeout_seconds: Option<f64>) -> PyResult<WorkflowResult> {
+        let execution = Arc::clone(&self.execution);
+        let wait = timeout_seconds.map(Duration::from_secs_f64);
+        let outcome = py.allow_threads(|| {
+            block_on(async move {
+                let mut execution = execution.lock().await;
+                if let Execution::Running(task) = &mut *execution {
+                    let joined = match wait {
+                        Some(wait) => tokio::time::timeout(wait, task).await.ok()?,
+                        None => task.await,
+                    };
+                    let outcome = joined.unwrap_or_else(|e| {
+                        Some(Err(GraphBitError::workflow_execution(format!(
+                            "Workflow execution task failed: {e}"
+                        ))))
+                    });
+                    *execution = Execution::Finished(Box::new(outcome));
+                }
+                match &*execution {
+                    Execution::Finished(outcome) => Some(Outcome::clone(outcome)),
+                    Execution::Running(_) => None,
+                }
+            })
+        });
+        match outcome {
+            Some(Some(Ok(context))) => Ok(WorkflowResult::new(context)),
+            Some(Some(Err(e))) => Err(to_py_error(e)),
+            Some(None) => Err(timeout_error(
+                "async_workflow_execution",
+                self.timeout.as_millis() as u64,
+                &format!("Workflow execution timed out after {:?}", self.timeout),
+            )),
+            None => Err(timeout_error(
+                "execution_result",
+                wait.unwrap_or_default().as_millis() as u64,
+                "Workflow execution is still running",
+            )),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let state = if self.is_done() { "done" } else { "running" };
+        format!(
+            "ExecutionHandle(execution_id='{}', state='{state}')",
+            self.execution_id
+        )
+    }
+}
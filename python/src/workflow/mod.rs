@@ -1,13 +1,23 @@
 //! Workflow module for GraphBit Python bindings
 
+pub(crate) mod agent;
+pub(crate) mod attachment;
 pub(crate) mod context;
 pub(crate) mod executor;
+pub(crate) mod handle;
 pub(crate) mod node;
 pub(crate) mod result;
+pub(crate) mod retry;
+pub(crate) mod template;
 pub(crate) mod workflow;
 
+pub use agent::{Agent, AgentBuilder};
+pub use attachment::Attachment;
 pub use context::WorkflowContext;
 pub use executor::{Executor, WorkflowStreamIterator};
+pub use handle::ExecutionHandle;
 pub use node::Node;
-pub use result::WorkflowResult;
+pub use result::{ResultComparison, WorkflowResult};
+pub use retry::RetryPolicy;
+pub use template::{TemplateRegistry, WorkflowTemplate};
 pub use workflow::Workflow;
@@ -1,14 +1,23 @@
 //! Workflow node for GraphBit Python bindings
 
+use crate::errors::{invalid_value, runtime_error};
 use crate::llm::LlmConfig;
+use crate::pickle::{Reduced, from_state, restore_fn, to_state};
+use crate::tools::builtin::BuiltinTool;
 use crate::tools::ToolExecutor;
+use super::retry::RetryPolicy;
 use graphbit_core::{
-    graph::{AgentNodeConfig, NodeType, WorkflowNode},
-    types::AgentId,
+    graph::{AgentNodeConfig, JoinPolicy, NodeType, PendingBranches, WorkflowNode},
+    guardrail_node::{GuardrailAction, GuardrailCheck},
+    llm::{ContextStrategy, HistoryWindow},
+    pacing::NodePacing,
+    post_process::POST_PROCESS_KEY,
+    types::{AgentId, RetryConfig},
+    workflow::DEFAULT_IDEMPOTENCY_HEADER,
 };
 use graphbit_core::errors::GraphBitError;
 use pyo3::prelude::*;
-use pyo3::types::{PyAnyMethods, PyDict, PyList, PyTuple};
+use pyo3::types::{PyAnyMethods, PyDict, PyList, PyString, PyTuple};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Write;
@@ -53,7 +62,11 @@ pub(crate) fn build_core_conditional_handlers(
             )
         })?;
         for (_nid, node) in workflow.graph.get_nodes() {
-            if let NodeType::Condition { handler_id } = &node.node_type {
+            if let NodeType::Condition {
+                handler_id,
+                expression: None,
+            } = &node.node_type
+            {
                 let py_callable = registry.get(handler_id).ok_or_else(|| {
                     GraphBitError::workflow_execution(format!(
                         "Condition node '{}' (handler_id='{}'): callable not registered",
@@ -104,7 +117,7 @@ pub(crate) fn build_core_conditional_handlers(
 }
 
 /// A workflow node representing a single operation or step in a workflow
-#[pyclass]
+#[pyclass(module = "graphbit")]
 #[derive(Clone)]
 pub struct Node {
     pub(crate) inner: WorkflowNode,
@@ -113,7 +126,7 @@ pub struct Node {
 #[pymethods]
 impl Node {
     #[staticmethod]
-    #[pyo3(signature = (name, prompt, context=None, agent_id=None, output_name=None, tools=None, system_prompt=None, llm_config=None, temperature=None, max_tokens=None, max_iterations=None, enable_prompt_caching=false))]
+    #[pyo3(signature = (name, prompt, context=None, agent_id=None, output_name=None, tools=None, system_prompt=None, llm_config=None, temperature=None, max_tokens=None, max_iterations=None, enable_prompt_caching=false, model=None, output_schema=None, max_repair_attempts=None, strict_json=false, handoff_targets=None, agent=None, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, context_strategy=None, context_window=None, history_window=None, history_token_budget=None, summarize_overflow=false, globals=None, concurrency_key=None, post_process=None))]
     fn agent(
         name: String,
         prompt: String,
@@ -127,20 +140,43 @@ impl Node {
         max_tokens: Option<u32>,
         max_iterations: Option<u32>,
         enable_prompt_caching: bool,
+        model: Option<String>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        max_repair_attempts: Option<u32>,
+        strict_json: bool,
+        handoff_targets: Option<Vec<String>>,
+        agent: Option<PyRef<'_, super::agent::Agent>>,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        context_strategy: Option<String>,
+        context_window: Option<u32>,
+        history_window: Option<u32>,
+        history_token_budget: Option<u32>,
+        summarize_overflow: bool,
+        globals: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
     ) -> PyResult<Self> {
         // Validate required parameters
         if name.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Name cannot be empty",
-            ));
+            return Err(invalid_value("Name cannot be empty"));
         }
         if prompt.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Prompt cannot be empty",
-            ));
+            return Err(invalid_value("Prompt cannot be empty"));
         }
 
-        let id = agent_id.unwrap_or_else(|| {
+        // A registered agent is referenced by its own id
+        if agent.is_some() && agent_id.is_some() {
+            return Err(invalid_value("agent and agent_id cannot both be set"));
+        }
+        let registered_agent_id = agent.as_ref().map(|a| a.agent_id().to_string());
+        let id = registered_agent_id.clone().or(agent_id).unwrap_or_else(|| {
             format!(
                 "agent_{}",
                 std::time::SystemTime::now()
@@ -150,9 +186,7 @@ impl Node {
             )
         });
         let mut agent_config = AgentNodeConfig::new(
-            AgentId::from_string(&id).map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid ID: {}", e))
-            })?,
+            AgentId::from_string(&id).map_err(|e| invalid_value(format!("Invalid ID: {}", e)))?,
             prompt,
         );
         
@@ -172,6 +206,10 @@ impl Node {
             },
         );
 
+        if registered_agent_id.is_some() {
+            node = node.with_registered_agent();
+        }
+
         // Store output name in metadata if provided
         if let Some(output_name) = output_name {
             node.config.insert(
@@ -195,7 +233,7 @@ impl Node {
                     node.config.insert("llm_config".to_string(), config_value);
                 }
                 Err(e) => {
-                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    return Err(invalid_value(format!(
                         "Failed to serialize LLM config: {}",
                         e
                     )));
@@ -203,19 +241,28 @@ impl Node {
             }
         }
 
+        // Store model override in metadata if provided; applied on top of the
+        // node LLM config or, when absent, the executor default
+        if let Some(model) = model {
+            if model.trim().is_empty() {
+                return Err(invalid_value("Model cannot be empty"));
+            }
+            node.config
+                .insert("model".to_string(), serde_json::Value::String(model));
+        }
+
         // Store temperature in metadata if provided
         if let Some(temp) = temperature {
             // Validate temperature range (0.0 to 2.0 is typical for most LLMs)
             if temp < 0.0 || temp > 2.0 {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    "Temperature must be between 0.0 and 2.0",
-                ));
+                return Err(invalid_value("Temperature must be between 0.0 and 2.0"));
             }
             node.config.insert(
                 "temperature".to_string(),
-                serde_json::Value::Number(serde_json::Number::from_f64(temp as f64).ok_or_else(
-                    || PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid temperature value"),
-                )?),
+                serde_json::Value::Number(
+                    serde_json::Number::from_f64(temp as f64)
+                        .ok_or_else(|| invalid_value("Invalid temperature value"))?,
+                ),
             );
         }
 
@@ -223,9 +270,7 @@ impl Node {
         if let Some(tokens) = max_tokens {
             // Validate max_tokens range (must be positive, typically between 1 and 128000)
             if tokens == 0 {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    "max_tokens must be greater than 0",
-                ));
+                return Err(invalid_value("max_tokens must be greater than 0"));
             }
             node.config.insert(
                 "max_tokens".to_string(),
@@ -237,9 +282,7 @@ impl Node {
         // Controls how many tool-call → result → LLM cycles the agent can perform
         if let Some(iterations) = max_iterations {
             if iterations == 0 || iterations > 100 {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    "max_iterations must be between 1 and 100",
-                ));
+                return Err(invalid_value("max_iterations must be between 1 and 100"));
             }
             node.config.insert(
                 "max_iterations".to_string(),
@@ -247,6 +290,43 @@ impl Node {
             );
         }
 
+        // Oversized requests are truncated or rejected before they reach the provider
+        if let Some(strategy) = context_strategy {
+            let strategy = strategy
+                .parse::<ContextStrategy>()
+                .map_err(|e| invalid_value(e.to_string()))?;
+            node = node.with_context_strategy(strategy);
+        }
+        if let Some(tokens) = context_window {
+            if tokens == 0 {
+                return Err(invalid_value("context_window must be greater than 0"));
+            }
+            node = node.with_context_window(tokens);
+        }
+
+        // Tool-loop follow-up calls replay only the latest exchanges within these limits
+        if history_window.is_some() || history_token_budget.is_some() || summarize_overflow {
+            let mut window = HistoryWindow::default();
+            if let Some(exchanges) = history_window {
+                if exchanges == 0 {
+                    return Err(invalid_value("history_window must be greater than 0"));
+                }
+                window = window.with_max_exchanges(exchanges as usize);
+            }
+            if let Some(tokens) = history_token_budget {
+                if tokens == 0 {
+                    return Err(invalid_value("history_token_budget must be greater than 0"));
+                }
+                window = window.with_token_budget(tokens as usize);
+            }
+            if window.max_exchanges().is_none() && window.token_budget().is_none() {
+                return Err(invalid_value(
+                    "summarize_overflow requires history_window or history_token_budget",
+                ));
+            }
+            node = node.with_history_window(window.with_summarize_overflow(summarize_overflow));
+        }
+
         // Store enable_prompt_caching flag in metadata
         if enable_prompt_caching {
             node.config.insert(
@@ -255,6 +335,47 @@ impl Node {
             );
         }
 
+        // Output schema is enforced after the agent responds, with repair requests
+        // sent back to the model on validation failure
+        if let Some(schema) = output_schema {
+            let schema = pythonize::depythonize::<serde_json::Value>(schema.as_any())
+                .map_err(|e| invalid_value(format!("Invalid output_schema: {e}")))?;
+            node = node.with_output_schema(schema);
+        }
+        if let Some(attempts) = max_repair_attempts {
+            if attempts > 10 {
+                return Err(invalid_value(
+                    "max_repair_attempts must be between 0 and 10",
+                ));
+            }
+            node = node.with_max_repair_attempts(attempts);
+        }
+        if strict_json {
+            node = node.with_strict_json(true);
+        }
+
+        // Node globals take precedence over the executor's for this node
+        if let Some(globals) = globals {
+            let globals = pythonize::depythonize::<HashMap<String, serde_json::Value>>(
+                globals.as_any(),
+            )
+            .map_err(|e| invalid_value(format!("Invalid globals: {e}")))?;
+            node = node.with_globals(globals);
+        }
+
+        // Handoffs run inside the Rust tool loop, which cannot reach Python tools
+        if let Some(targets) = handoff_targets {
+            if tools.is_some() {
+                return Err(invalid_value(
+                    "handoff_targets cannot be combined with tools",
+                ));
+            }
+            if targets.iter().any(|t| t.trim().is_empty()) {
+                return Err(invalid_value("handoff_targets cannot contain empty names"));
+            }
+            node = node.with_handoff_targets(targets);
+        }
+
         // Store tools in metadata if provided
         if let Some(tools_list) = tools {
             println!("🔧 Processing {} tools for agent node", tools_list.len());
@@ -265,20 +386,53 @@ impl Node {
             let py = tools_list.py();
 
             for tool in tools_list.iter() {
+                // Built-in Rust tools carry their own metadata
+                if let Ok(builtin) = tool.downcast::<BuiltinTool>() {
+                    let metadata = builtin.borrow().metadata();
+                    tool_schemas.push(serde_json::json!({
+                        "name": metadata.name,
+                        "description": metadata.description,
+                        "parameters": metadata.parameters
+                    }));
+                    tool_names.push(metadata.name);
+                    continue;
+                }
+
+                // Name and description given to the @tool decorator take precedence
+                let registered = tool
+                    .getattr(crate::tools::decorator::TOOL_ATTRIBUTE)
+                    .ok()
+                    .and_then(|info| info.downcast_into::<PyDict>().ok());
+                let registered_str = |key: &str| {
+                    registered
+                        .as_ref()
+                        .and_then(|info| info.get_item(key).ok().flatten())
+                        .and_then(|value| value.extract::<String>().ok())
+                };
+
                 // Extract tool metadata
-                let tool_name = tool
-                    .getattr("__name__")
-                    .and_then(|name| name.extract::<String>())
-                    .unwrap_or_else(|_| "unknown_tool".to_string());
-
-                let tool_doc = tool
-                    .getattr("__doc__")
-                    .and_then(|doc| doc.extract::<Option<String>>())
-                    .unwrap_or(None)
-                    .unwrap_or_else(|| "Tool function".to_string());
-
-                // Extract comprehensive function schema using introspection
-                let parameters_schema = match extract_comprehensive_function_schema(&tool, py) {
+                let tool_name = registered_str("name").unwrap_or_else(|| {
+                    tool.getattr("__name__")
+                        .and_then(|name| name.extract::<String>())
+                        .unwrap_or_else(|_| "unknown_tool".to_string())
+                });
+
+                let tool_doc = registered_str("description").unwrap_or_else(|| {
+                    tool.getattr("__doc__")
+                        .and_then(|doc| doc.extract::<Option<String>>())
+                        .unwrap_or(None)
+                        .unwrap_or_else(|| "Tool function".to_string())
+                });
+
+                // Use the schema registered by @tool (including any schema_override),
+                // else build one from the type hints and docstring
+                let registered_schema = registered
+                    .as_ref()
+                    .and_then(|info| info.get_item("parameters").ok().flatten())
+                    .and_then(|schema| pythonize::depythonize::<serde_json::Value>(&schema).ok());
+                let parameters_schema = match registered_schema
+                    .map_or_else(|| crate::tools::schema::function_schema(&tool, py), Ok)
+                {
                     Ok(schema) => schema,
                     Err(e) => {
                         eprintln!(
@@ -330,7 +484,7 @@ impl Node {
                 use crate::tools::decorator::get_global_registry;
                 let global_registry = get_global_registry();
                 let global_guard = global_registry.lock().map_err(|e| {
-                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                    runtime_error(format!(
                         "Failed to acquire global tool registry lock: {}",
                         e
                     ))
@@ -339,10 +493,12 @@ impl Node {
                 TOOL_REGISTRY.with(|registry| {
                     let mut registry = registry.borrow_mut();
                     for (i, tool) in tools_list.iter().enumerate() {
-                        let tool_name = tool
-                            .getattr("__name__")
-                            .and_then(|name| name.extract::<String>())
-                            .unwrap_or_else(|_| format!("tool_{}", i));
+                        let tool_name = tool_schemas
+                            .get(i)
+                            .and_then(|schema| schema.get("name"))
+                            .and_then(|name| name.as_str())
+                            .map(str::to_string)
+                            .unwrap_or_else(|| format!("tool_{}", i));
                         let py_obj = tool.clone().into_pyobject(py).unwrap();
                         registry.insert(tool_name.clone(), py_obj.into_any().unbind());
 
@@ -354,7 +510,11 @@ impl Node {
                                 .and_then(|v| v.as_str())
                                 .unwrap_or("Tool function")
                                 .to_string();
-                            let params_dict = PyDict::new(py);
+                            let params_dict = schema
+                                .get("parameters")
+                                .and_then(|params| pythonize::pythonize(py, params).ok())
+                                .and_then(|params| params.downcast_into::<PyDict>().ok())
+                                .unwrap_or_else(|| PyDict::new(py));
 
                             let _ = global_guard.register_tool(
                                 tool_name.clone(),
@@ -362,6 +522,7 @@ impl Node {
                                 tool.clone().into_pyobject(py).unwrap().into_any().unbind(),
                                 &params_dict,
                                 None,
+                                py,
                             );
                         }
                     }
@@ -370,66 +531,762 @@ impl Node {
             })?;
         }
 
-        Ok(Self { inner: node })
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            None,
+            concurrency_key,
+            post_process,
+        )
     }
 
+    /// Agent node trying `models` in order, escalating to the next one while
+    /// `escalate_if` holds for an answer
     #[staticmethod]
-    fn transform(name: String, transformation: String) -> PyResult<Self> {
+    #[pyo3(signature = (name, prompt, models, escalate_if, context=None, agent_id=None, output_name=None, system_prompt=None, temperature=None, max_tokens=None, output_schema=None, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, concurrency_key=None, post_process=None))]
+    fn model_router(
+        name: String,
+        prompt: String,
+        models: Vec<LlmConfig>,
+        escalate_if: String,
+        context: Option<String>,
+        agent_id: Option<String>,
+        output_name: Option<String>,
+        system_prompt: Option<String>,
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        if prompt.trim().is_empty() {
+            return Err(invalid_value("Prompt cannot be empty"));
+        }
+        if models.is_empty() {
+            return Err(invalid_value("models cannot be empty"));
+        }
+
+        let id = agent_id.unwrap_or_else(|| {
+            format!(
+                "agent_{}",
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_nanos()
+            )
+        });
+        let mut agent_config = AgentNodeConfig::new(
+            AgentId::from_string(&id).map_err(|e| invalid_value(format!("Invalid ID: {e}")))?,
+            prompt,
+        );
+        if let Some(ctx) = context {
+            agent_config = agent_config.with_context(ctx);
+        }
+        if let Some(sys) = system_prompt {
+            agent_config = agent_config.with_system_prompt_override(sys);
+        }
+
+        let mut node = WorkflowNode::new(
+            name.clone(),
+            format!("Model router: {name}"),
+            NodeType::ModelRouter {
+                config: agent_config,
+                candidates: models.into_iter().map(|model| model.inner).collect(),
+                escalation_expression: escalate_if,
+            },
+        );
+        if let Some(output_name) = output_name {
+            node.config.insert(
+                "output_name".to_string(),
+                serde_json::Value::String(output_name),
+            );
+        }
+        if let Some(temp) = temperature {
+            if !(0.0..=2.0).contains(&temp) {
+                return Err(invalid_value("Temperature must be between 0.0 and 2.0"));
+            }
+            node.config.insert(
+                "temperature".to_string(),
+                serde_json::Value::Number(
+                    serde_json::Number::from_f64(f64::from(temp))
+                        .ok_or_else(|| invalid_value("Invalid temperature value"))?,
+                ),
+            );
+        }
+        if let Some(tokens) = max_tokens {
+            if tokens == 0 {
+                return Err(invalid_value("max_tokens must be greater than 0"));
+            }
+            node.config.insert(
+                "max_tokens".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(tokens)),
+            );
+        }
+
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Transform node applying a built-in transformation (`identity`,
+    /// `uppercase`, `lowercase`, `trim`, `json_parse`, `json_stringify`)
+    /// to its parent's output
+    #[staticmethod]
+    #[pyo3(signature = (name, transformation, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn transform(
+        name: String,
+        transformation: String,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
         // Validate required parameters
         if name.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Transform name cannot be empty",
-            ));
+            return Err(invalid_value("Transform name cannot be empty"));
         }
         if transformation.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Transform transformation cannot be empty",
-            ));
+            return Err(invalid_value("Transform transformation cannot be empty"));
         }
 
-        Ok(Self {
-            inner: WorkflowNode::new(
-                name.clone(),
-                format!("Transform: {}", name),
-                NodeType::Transform { transformation },
-            ),
-        })
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("Transform: {name}"),
+            NodeType::Transform { transformation },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
     }
 
-    /// Condition node: `handler` receives one argument, a **dict** with keys
+    /// Condition node routed either by an expression or by a Python callable.
+    ///
+    /// `expression` (or a `str` passed as `handler`) is evaluated against the
+    /// parent output; see the expression language guide for the grammar. A
+    /// callable `handler` receives one argument, a **dict** with keys
     /// `parent_node_id`, `parent_output`, `variables`, `node_outputs`, `metadata` (routing snapshot),
     /// and must return the next node **name** as `str`.
     #[staticmethod]
-    #[pyo3(signature = (name, handler))]
-    fn condition(name: String, handler: &Bound<'_, PyAny>) -> PyResult<Self> {
+    #[pyo3(signature = (name, handler=None, expression=None, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn condition(
+        name: String,
+        handler: Option<&Bound<'_, PyAny>>,
+        expression: Option<String>,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
         if name.trim().is_empty() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "Condition name cannot be empty",
-            ));
+            return Err(invalid_value("Condition name cannot be empty"));
         }
-        if !handler.is_callable() {
-            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                "Condition handler must be callable",
-            ));
+        let expression = match (handler, expression) {
+            (Some(handler), None) if handler.is_instance_of::<PyString>() => {
+                Some(handler.extract::<String>()?)
+            }
+            (Some(_), Some(_)) => {
+                return Err(invalid_value(
+                    "Condition takes either a handler or an expression, not both",
+                ));
+            }
+            (None, None) => {
+                return Err(invalid_value(
+                    "Condition needs a handler callable or an expression",
+                ));
+            }
+            (None, Some(expression)) => Some(expression),
+            (Some(_), None) => None,
+        };
+
+        let node_type = match (handler, expression) {
+            (_, Some(expression)) => {
+                if expression.trim().is_empty() {
+                    return Err(invalid_value("Condition expression cannot be empty"));
+                }
+                NodeType::Condition {
+                    handler_id: String::new(),
+                    expression: Some(expression),
+                }
+            }
+            (handler, None) => {
+                let handler = handler.filter(|h| h.is_callable()).ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                        "Condition handler must be callable",
+                    )
+                })?;
+                let handler_id = uuid::Uuid::new_v4().to_string();
+                let handler_py = handler.clone().unbind();
+                condition_handler_registry()
+                    .lock()
+                    .map_err(|e| runtime_error(format!("Condition handler registry lock: {e}")))?
+                    .insert(handler_id.clone(), Arc::new(handler_py));
+                NodeType::Condition {
+                    handler_id,
+                    expression: None,
+                }
+            }
+        };
+
+        let node = WorkflowNode::new(name.clone(), format!("Condition: {name}"), node_type);
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// HTTP request node. `body` is sent as-is when it is a string and as JSON
+    /// otherwise; the node outputs `{"status": ..., "body": ...}`. Every attempt
+    /// sends the node's idempotency key in `idempotency_header`, unless it is `None`.
+    ///
+    /// `multipart` sends a multipart form instead of `body`: a field whose value
+    /// is a reference like `{{nodes.loader.attachments[0]}}` uploads that
+    /// attachment as a file. A binary response, such as an image, becomes an
+    /// attachment of the node instead of its `body`.
+    #[staticmethod]
+    #[pyo3(signature = (name, url, method="GET", headers=None, body=None, idempotency_header=Some("Idempotency-Key"), retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None, multipart=None))]
+    fn http_request(
+        name: String,
+        url: String,
+        method: &str,
+        headers: Option<HashMap<String, String>>,
+        body: Option<&Bound<'_, PyAny>>,
+        idempotency_header: Option<&str>,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+        multipart: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Self> {
+        if url.trim().is_empty() {
+            return Err(invalid_value(format!(
+                "Invalid node '{name}': HTTP request url cannot be empty"
+            )));
         }
-        let handler_id = uuid::Uuid::new_v4().to_string();
-        let handler_py = handler.clone().unbind();
-        condition_handler_registry()
-            .lock()
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                    "Condition handler registry lock: {e}"
+        let mut node = WorkflowNode::new(
+            name.clone(),
+            format!("HTTP request: {name}"),
+            NodeType::HttpRequest {
+                url,
+                method: method.to_uppercase(),
+                headers: headers.unwrap_or_default(),
+            },
+        );
+        if let Some(body) = body {
+            let body = pythonize::depythonize::<serde_json::Value>(body).map_err(|e| {
+                invalid_value(format!(
+                    "Invalid node '{name}': body is not JSON-serializable: {e}"
                 ))
-            })?
-            .insert(handler_id.clone(), Arc::new(handler_py));
+            })?;
+            node.config.insert("body".to_string(), body);
+        }
+        if let Some(multipart) = multipart {
+            if body.is_some() {
+                return Err(invalid_value(format!(
+                    "Invalid node '{name}': set either body or multipart, not both"
+                )));
+            }
+            let multipart =
+                pythonize::depythonize::<serde_json::Value>(multipart).map_err(|e| {
+                    invalid_value(format!(
+                        "Invalid node '{name}': multipart is not JSON-serializable: {e}"
+                    ))
+                })?;
+            node.config.insert("multipart".to_string(), multipart);
+        }
+        if idempotency_header != Some(DEFAULT_IDEMPOTENCY_HEADER) {
+            node.config.insert(
+                "idempotency_header".to_string(),
+                idempotency_header.map_or(serde_json::Value::Null, serde_json::Value::from),
+            );
+        }
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
 
-        Ok(Self {
-            inner: WorkflowNode::new(
-                name.clone(),
-                format!("Condition: {}", name),
-                NodeType::Condition { handler_id },
-            ),
-        })
+    /// Delay node waiting `seconds` before passing its parent's output on
+    #[staticmethod]
+    #[pyo3(signature = (name, seconds, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn delay(
+        name: String,
+        seconds: u64,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("Delay: {name}"),
+            NodeType::Delay {
+                duration_seconds: seconds,
+            },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Python code node running `code` in a sandboxed subprocess
+    ///
+    /// `{{node.X}}` and `{{input.X}}` references in `code` are substituted before
+    /// it runs. The node outputs `{"stdout", "stderr", "value", "exit_status"}`,
+    /// where `value` is the value of a trailing expression. The code may only
+    /// import `allowed_imports`, may use `memory_limit_mb` MiB of memory and has
+    /// no network access unless `allow_network`. Only executors created with
+    /// `allow_python_code_nodes=True` run it; the sandbox limits mistakes in
+    /// generated code but is not a security boundary against hostile code.
+    #[staticmethod]
+    #[pyo3(signature = (name, code, timeout=30, allowed_imports=None, allow_network=false, memory_limit_mb=512, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn python_code(
+        name: String,
+        code: String,
+        timeout: u64,
+        allowed_imports: Option<Vec<String>>,
+        allow_network: bool,
+        memory_limit_mb: u64,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("Python code: {name}"),
+            NodeType::PythonCode {
+                code_template: code,
+                timeout_seconds: timeout,
+                memory_limit_mb,
+                allowed_imports: allowed_imports.unwrap_or_default(),
+                allow_network,
+            },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Shell command node running `command` with `sh -c`
+    ///
+    /// Values of `{{node.X}}` and `{{input.X}}` references in `command` are
+    /// passed to the command as environment variables rather than pasted into
+    /// it, and references must not be inside quotes. The node outputs
+    /// `{"stdout", "stderr", "exit_status", "truncated"}` and fails on a nonzero
+    /// exit status. Only executors created with `allow_shell_nodes=True` run it.
+    #[staticmethod]
+    #[pyo3(signature = (name, command, working_dir=None, env=None, timeout=60, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn shell(
+        name: String,
+        command: String,
+        working_dir: Option<String>,
+        env: Option<HashMap<String, String>>,
+        timeout: u64,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("Shell command: {name}"),
+            NodeType::ShellCommand {
+                command_template: command,
+                working_dir,
+                env: env.unwrap_or_default(),
+                timeout_seconds: timeout,
+            },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Guardrail node checking its input for personal data and unwanted text
+    ///
+    /// `checks` run in order. Each is `"pii"` (emails, phone numbers and credit
+    /// card numbers), `"email"`, `"phone"`, `"credit_card"`, or a dict:
+    /// `{"regex": pattern}`, `{"deny_list": [terms]}` or `{"classifier":
+    /// instructions}`, where the classifier asks an LLM, the executor's unless
+    /// given as `{"classifier": {"instructions": ..., "llm_config": ...}}`.
+    /// `on_violation` is "redact" (continue with matches masked as
+    /// `[REDACTED_<CHECK>]`), "block" (fail the node) or "route" (pass the input
+    /// on along the edges connected with `on_violation=True` only).
+    #[staticmethod]
+    #[pyo3(signature = (name, checks, on_violation="redact", retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn guardrail(
+        name: String,
+        checks: &Bound<'_, PyList>,
+        on_violation: &str,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let on_violation = on_violation
+            .parse::<GuardrailAction>()
+            .map_err(|e| invalid_value(e.to_string()))?;
+        let checks = checks
+            .iter()
+            .map(|check| Self::guardrail_check(&check))
+            .collect::<PyResult<Vec<_>>>()?;
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("Guardrail: {name}"),
+            NodeType::Guardrail {
+                checks,
+                on_violation,
+            },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// External event node pausing its branch until `event_name` is delivered
+    /// to the execution, then outputting the event's payload
+    ///
+    /// Deliver events with `ExecutionHandle.deliver_event` or
+    /// `Executor.deliver_event`. After `event_timeout` seconds without the
+    /// event, the node follows the edges connected with `on_timeout=True`, or
+    /// fails when there are none; without `event_timeout` it waits for good.
+    #[staticmethod]
+    #[pyo3(signature = (name, event_name, event_timeout=None, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn external_event(
+        name: String,
+        event_name: String,
+        event_timeout: Option<u64>,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("External event: {name}"),
+            NodeType::ExternalEvent {
+                event_name,
+                timeout_seconds: event_timeout,
+            },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Document loader node outputting the loaded document (`content`,
+    /// `metadata`, ...)
+    #[staticmethod]
+    #[pyo3(signature = (name, source, document_type, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn document_loader(
+        name: String,
+        source: String,
+        document_type: String,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let node = WorkflowNode::new(
+            name.clone(),
+            format!("Document loader: {name}"),
+            NodeType::DocumentLoader {
+                document_type,
+                source_path: source,
+                encoding: None,
+            },
+        );
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Split node passing its parent's output to every child, which then run
+    /// in parallel
+    #[staticmethod]
+    #[pyo3(signature = (name, retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn split(
+        name: String,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let node = WorkflowNode::new(name.clone(), format!("Split: {name}"), NodeType::Split);
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
+    }
+
+    /// Join node outputting a dict of its parents' outputs keyed by parent node name
+    ///
+    /// `policy` is "all" (wait for every parent), "any" (run once one parent
+    /// succeeds) or "quorum" (run once `quorum` parents succeed). With "any" and
+    /// "quorum" the dict holds only the parents that succeeded, and
+    /// `pending_branches` ("cancel" or "detach") decides what happens to parent
+    /// branches still running.
+    #[staticmethod]
+    #[pyo3(signature = (name, policy="all", quorum=None, pending_branches="cancel", retry=None, retry_config=None, timeout_seconds=None, tags=None, budget_exempt=false, pre_execution_delay_ms=None, post_execution_delay_ms=None, delay_jitter_ms=None, output_schema=None, concurrency_key=None, post_process=None))]
+    fn join(
+        name: String,
+        policy: &str,
+        quorum: Option<usize>,
+        pending_branches: &str,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        let policy = match (policy, quorum) {
+            ("all", None) => JoinPolicy::All,
+            ("any", None) => JoinPolicy::Any,
+            ("quorum", Some(0)) => return Err(invalid_value("quorum must be at least 1")),
+            ("quorum", Some(n)) => JoinPolicy::Quorum(n),
+            ("quorum", None) => return Err(invalid_value("policy \"quorum\" requires quorum")),
+            ("all" | "any", Some(_)) => {
+                return Err(invalid_value("quorum is only valid with policy \"quorum\""));
+            }
+            (other, _) => {
+                return Err(invalid_value(format!(
+                    "Unknown join policy '{other}', expected all, any or quorum"
+                )));
+            }
+        };
+        let pending_branches = pending_branches
+            .parse::<PendingBranches>()
+            .map_err(|e| invalid_value(e.to_string()))?;
+        let mut node = WorkflowNode::new(name.clone(), format!("Join: {name}"), NodeType::Join);
+        if policy != JoinPolicy::All {
+            node = node
+                .with_join_policy(policy)
+                .with_pending_branches(pending_branches);
+        }
+        Self::finish(
+            node,
+            retry,
+            retry_config,
+            timeout_seconds,
+            tags,
+            budget_exempt,
+            pre_execution_delay_ms,
+            post_execution_delay_ms,
+            delay_jitter_ms,
+            output_schema,
+            concurrency_key,
+            post_process,
+        )
     }
 
     fn id(&self) -> String {
@@ -482,6 +1339,7 @@ impl Node {
                         tool_func.clone_ref(py),
                         &params_dict,
                         None, // No specific return type
+                        py,
                     )?;
                 }
             }
@@ -494,9 +1352,7 @@ impl Node {
     /// Execute tools for this node with given tool calls
     fn execute_tools(&self, tool_calls: &Bound<'_, PyList>, py: Python<'_>) -> PyResult<String> {
         if !self.has_tools() {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                "This node does not have tools configured",
-            ));
+            return Err(invalid_value("This node does not have tools configured"));
         }
 
         let executor = self.create_tool_executor(py)?;
@@ -534,14 +1390,26 @@ impl Node {
 
     /// Get node configuration as JSON string
     fn get_config(&self) -> PyResult<String> {
-        serde_json::to_string_pretty(&self.inner.config).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to serialize node config: {}",
-                e
-            ))
+        serde_json::to_string_pretty(&self.inner.config)
+            .map_err(|e| invalid_value(format!("Failed to serialize node config: {}", e)))
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<Reduced<'py, (String,)>> {
+        Ok((restore_fn(slf.as_any())?, (to_state(&slf.borrow().inner)?,)))
+    }
+
+    /// Rebuild a node pickled by `__reduce__`
+    #[staticmethod]
+    fn _from_state(state: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: from_state(state)?,
         })
     }
 
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
     /// String representation
     fn __repr__(&self) -> String {
         format!(
@@ -550,6 +1418,7 @@ impl Node {
             self.inner.name,
             match &self.inner.node_type {
                 NodeType::Agent { .. } => "Agent",
+                NodeType::ModelRouter { .. } => "ModelRouter",
                 NodeType::Transform { .. } => "Transform",
                 NodeType::Condition { .. } => "Condition",
                 NodeType::Split => "Split",
@@ -558,266 +1427,158 @@ impl Node {
                 NodeType::HttpRequest { .. } => "HttpRequest",
                 NodeType::Custom { .. } => "Custom",
                 NodeType::DocumentLoader { .. } => "DocumentLoader",
+                NodeType::PythonCode { .. } => "PythonCode",
+                NodeType::ShellCommand { .. } => "ShellCommand",
+                NodeType::Guardrail { .. } => "Guardrail",
+                NodeType::ExternalEvent { .. } => "ExternalEvent",
             }
         )
     }
 }
 
-/// Extract comprehensive function parameter schema using introspection and docstring parsing
-fn extract_comprehensive_function_schema(
-    func: &Bound<'_, PyAny>,
-    py: Python<'_>,
-) -> PyResult<serde_json::Value> {
-    let inspect = py.import("inspect")?;
-    let signature = inspect.call_method1("signature", (func,))?;
-    let parameters = signature.getattr("parameters")?;
-
-    // Extract docstring for parameter descriptions
-    let docstring = func
-        .getattr("__doc__")
-        .and_then(|doc| doc.extract::<Option<String>>())
-        .unwrap_or(None)
-        .unwrap_or_else(|| String::new());
-
-    let param_descriptions = parse_docstring_parameters(&docstring);
-
-    let mut properties = serde_json::Map::new();
-    let mut required = Vec::new();
-
-    // Iterate through function parameters using Python dict iteration
-    let param_items = parameters.call_method0("items")?;
-    for item in param_items.try_iter()? {
-        let item_tuple = item?;
-        let param_name = item_tuple.get_item(0)?.extract::<String>()?;
-        let param_obj = item_tuple.get_item(1)?;
-
-        // Skip 'self' and 'cls' parameters
-        if param_name == "self" || param_name == "cls" {
-            continue;
-        }
-
-        // Get parameter annotation for type information
-        let annotation = param_obj.getattr("annotation")?;
-        let param_type =
-            if annotation.is_none() || annotation.to_string() == "<class 'inspect._empty'>" {
-                "string" // Default type
-            } else {
-                map_python_type_to_json_schema(&annotation.to_string())
+impl Node {
+    /// Guardrail check given as a name or a single-key dict
+    fn guardrail_check(check: &Bound<'_, PyAny>) -> PyResult<GuardrailCheck> {
+        if let Ok(name) = check.extract::<String>() {
+            return match name.as_str() {
+                "pii" => Ok(GuardrailCheck::Pii),
+                "email" => Ok(GuardrailCheck::Email),
+                "phone" => Ok(GuardrailCheck::Phone),
+                "credit_card" => Ok(GuardrailCheck::CreditCard),
+                other => Err(invalid_value(format!(
+                    "Unknown guardrail check '{other}', expected pii, email, phone or credit_card"
+                ))),
             };
-
-        let mut param_info = serde_json::Map::new();
-        param_info.insert(
-            "type".to_string(),
-            serde_json::Value::String(param_type.to_string()),
-        );
-
-        // Add description from docstring if available, otherwise use a default
-        let description = param_descriptions
-            .get(&param_name)
-            .cloned()
-            .unwrap_or_else(|| format!("Parameter {}", param_name));
-        param_info.insert(
-            "description".to_string(),
-            serde_json::Value::String(description),
-        );
-
-        // Check if parameter has a default value
-        let default = param_obj.getattr("default")?;
-        if default.to_string() != "<class 'inspect._empty'>" {
-            // Try to convert default value to JSON
-            if let Ok(default_str) = default.extract::<String>() {
-                param_info.insert(
-                    "default".to_string(),
-                    serde_json::Value::String(default_str),
-                );
-            } else if let Ok(default_int) = default.extract::<i64>() {
-                param_info.insert(
-                    "default".to_string(),
-                    serde_json::Value::Number(serde_json::Number::from(default_int)),
-                );
-            } else if let Ok(default_float) = default.extract::<f64>() {
-                if let Some(num) = serde_json::Number::from_f64(default_float) {
-                    param_info.insert("default".to_string(), serde_json::Value::Number(num));
-                }
-            } else if let Ok(default_bool) = default.extract::<bool>() {
-                param_info.insert("default".to_string(), serde_json::Value::Bool(default_bool));
-            }
-        } else {
-            // Parameter is required
-            required.push(param_name.clone());
         }
-
-        properties.insert(param_name, serde_json::Value::Object(param_info));
-    }
-
-    Ok(serde_json::json!({
-        "type": "object",
-        "properties": properties,
-        "required": required
-    }))
-}
-
-/// Parse docstring to extract parameter descriptions
-/// Supports Google, NumPy, and Sphinx docstring formats
-fn parse_docstring_parameters(docstring: &str) -> std::collections::HashMap<String, String> {
-    use std::collections::HashMap;
-
-    let mut param_descriptions = HashMap::new();
-
-    if docstring.is_empty() {
-        return param_descriptions;
-    }
-
-    // Try Google-style docstring format first
-    if let Some(args_section) = extract_args_section_google(docstring) {
-        for line in args_section.lines() {
-            if let Some((param_name, description)) = parse_google_param_line(line) {
-                param_descriptions.insert(param_name, description);
-            }
+        let invalid = || {
+            invalid_value(
+                "Guardrail checks are names or dicts with one key: regex, deny_list or classifier",
+            )
+        };
+        let check = check.downcast::<PyDict>().map_err(|_| invalid())?;
+        if check.len() != 1 {
+            return Err(invalid());
         }
-    }
-
-    // Try NumPy/Sphinx-style docstring format
-    if param_descriptions.is_empty() {
-        if let Some(params_section) = extract_parameters_section_numpy(docstring) {
-            for line in params_section.lines() {
-                if let Some((param_name, description)) = parse_numpy_param_line(line) {
-                    param_descriptions.insert(param_name, description);
+        let (kind, value) = check.iter().next().ok_or_else(invalid)?;
+        match kind.extract::<String>()?.as_str() {
+            "regex" => Ok(GuardrailCheck::Regex(value.extract()?)),
+            "deny_list" => Ok(GuardrailCheck::DenyList(value.extract()?)),
+            "classifier" => {
+                if let Ok(instructions) = value.extract::<String>() {
+                    return Ok(GuardrailCheck::Classifier {
+                        instructions,
+                        llm_config: None,
+                    });
                 }
+                let options = value.downcast::<PyDict>().map_err(|_| {
+                    invalid_value("classifier takes instructions or a dict with instructions")
+                })?;
+                let instructions = options
+                    .get_item("instructions")?
+                    .ok_or_else(|| invalid_value("classifier dict needs instructions"))?
+                    .extract()?;
+                let llm_config = options
+                    .get_item("llm_config")?
+                    .filter(|config| !config.is_none())
+                    .map(|config| config.extract::<LlmConfig>().map(|config| config.inner))
+                    .transpose()?;
+                Ok(GuardrailCheck::Classifier {
+                    instructions,
+                    llm_config,
+                })
             }
+            _ => Err(invalid()),
         }
     }
 
-    param_descriptions
-}
-
-/// Extract Args section from Google-style docstring
-fn extract_args_section_google(docstring: &str) -> Option<String> {
-    let lines: Vec<&str> = docstring.lines().collect();
-    let mut in_args_section = false;
-    let mut args_lines = Vec::new();
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        if trimmed == "Args:" || trimmed == "Arguments:" || trimmed == "Parameters:" {
-            in_args_section = true;
-            continue;
+    /// Apply the options shared by every node constructor, then validate the
+    /// node, surfacing core validation errors as `ValueError` naming the node.
+    ///
+    /// `retry` replaces the node's retry configuration, while `retry_config` keys
+    /// override the fields of core's default `RetryConfig`. `post_process` is
+    /// the list of operations applied to the node's output, as in core's
+    /// `post_process` module.
+    #[allow(clippy::too_many_arguments)]
+    fn finish(
+        mut node: WorkflowNode,
+        retry: Option<PyRef<'_, RetryPolicy>>,
+        retry_config: Option<&Bound<'_, PyDict>>,
+        timeout_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+        budget_exempt: bool,
+        pre_execution_delay_ms: Option<u64>,
+        post_execution_delay_ms: Option<u64>,
+        delay_jitter_ms: Option<u64>,
+        output_schema: Option<&Bound<'_, PyDict>>,
+        concurrency_key: Option<String>,
+        post_process: Option<&Bound<'_, PyList>>,
+    ) -> PyResult<Self> {
+        if node.name.trim().is_empty() {
+            return Err(invalid_value("Node name cannot be empty"));
         }
+        let name = node.name.clone();
+        let invalid = |message: String| invalid_value(format!("Invalid node '{name}': {message}"));
 
-        if in_args_section {
-            if trimmed.ends_with(':') && !trimmed.contains(' ') && trimmed.len() > 1 {
-                break;
-            }
-            args_lines.push(line);
+        if retry.is_some() && retry_config.is_some() {
+            return Err(invalid(
+                "retry and retry_config cannot both be set".to_string(),
+            ));
         }
-    }
-
-    if args_lines.is_empty() {
-        None
-    } else {
-        Some(args_lines.join("\n"))
-    }
-}
-
-/// Parse a single parameter line from Google-style docstring
-fn parse_google_param_line(line: &str) -> Option<(String, String)> {
-    let trimmed = line.trim();
-
-    // Look for pattern: "param_name (type): description" or "param_name: description"
-    if let Some(colon_pos) = trimmed.find(':') {
-        let param_part = trimmed[..colon_pos].trim();
-        let description = trimmed[colon_pos + 1..].trim();
-
-        // Extract parameter name (remove type annotation if present)
-        let param_name = if let Some(paren_pos) = param_part.find('(') {
-            param_part[..paren_pos].trim()
-        } else {
-            param_part
-        };
-
-        if !param_name.is_empty() && !description.is_empty() {
-            return Some((param_name.to_string(), description.to_string()));
+        if let Some(retry) = retry {
+            node = node.with_retry_config(retry.inner.clone());
         }
-    }
-
-    None
-}
-
-/// Extract Parameters section from NumPy/Sphinx-style docstring
-fn extract_parameters_section_numpy(docstring: &str) -> Option<String> {
-    let lines: Vec<&str> = docstring.lines().collect();
-    let mut in_params_section = false;
-    let mut params_lines = Vec::new();
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        if trimmed == "Parameters"
-            || trimmed == "Parameters:"
-            || trimmed.starts_with("Parameters\n")
-            || trimmed.starts_with("Parameters\r\n")
-        {
-            in_params_section = true;
-            continue;
-        }
-
-        if in_params_section {
-            // Check if we've reached another section
-            if trimmed == "Returns"
-                || trimmed == "Returns:"
-                || trimmed == "Raises"
-                || trimmed == "Raises:"
-                || trimmed == "Examples"
-                || trimmed == "Examples:"
+        if let Some(retry_config) = retry_config {
+            let overrides = pythonize::depythonize::<serde_json::Value>(retry_config.as_any())
+                .map_err(|e| invalid(format!("invalid retry_config: {e}")))?;
+            let mut merged = serde_json::to_value(RetryConfig::default())
+                .map_err(|e| invalid(format!("invalid retry_config: {e}")))?;
+            if let (Some(merged), Some(overrides)) = (merged.as_object_mut(), overrides.as_object())
             {
-                break;
+                merged.extend(overrides.clone());
             }
-            params_lines.push(line);
+            let retry_config = serde_json::from_value::<RetryConfig>(merged)
+                .map_err(|e| invalid(format!("invalid retry_config: {e}")))?;
+            node = node.with_retry_config(retry_config);
         }
-    }
-
-    if params_lines.is_empty() {
-        None
-    } else {
-        Some(params_lines.join("\n"))
-    }
-}
-
-/// Parse a single parameter line from NumPy/Sphinx-style docstring
-fn parse_numpy_param_line(line: &str) -> Option<(String, String)> {
-    let trimmed = line.trim();
-
-    // Look for pattern: "param_name : type" followed by description on next lines
-    // For simplicity, we'll look for lines that start with a word followed by space and colon
-    if let Some(colon_pos) = trimmed.find(" :") {
-        let param_name = trimmed[..colon_pos].trim();
-        let rest = trimmed[colon_pos + 2..].trim();
-
-        if !param_name.is_empty() {
-            // For NumPy style, description might be on the same line after type
-            let description = if rest.is_empty() {
-                format!("Parameter {}", param_name)
-            } else {
-                rest.to_string()
-            };
-            return Some((param_name.to_string(), description));
+        if let Some(timeout_seconds) = timeout_seconds {
+            if timeout_seconds == 0 {
+                return Err(invalid("timeout_seconds must be greater than 0".to_string()));
+            }
+            node = node.with_timeout(timeout_seconds);
+        }
+        if let Some(tags) = tags {
+            node = node.with_tags(tags);
+        }
+        if budget_exempt {
+            node = node.with_budget_exempt(true);
+        }
+        if pre_execution_delay_ms.is_some()
+            || post_execution_delay_ms.is_some()
+            || delay_jitter_ms.is_some()
+        {
+            node = node.with_pacing(NodePacing {
+                pre_execution_delay_ms: pre_execution_delay_ms.unwrap_or(0),
+                post_execution_delay_ms: post_execution_delay_ms.unwrap_or(0),
+                jitter_ms: delay_jitter_ms.unwrap_or(0),
+            });
+        }
+        if let Some(schema) = output_schema {
+            let schema = pythonize::depythonize::<serde_json::Value>(schema.as_any())
+                .map_err(|e| invalid(format!("invalid output_schema: {e}")))?;
+            node = node.with_output_schema(schema);
+        }
+        if let Some(key) = concurrency_key {
+            node = node.with_concurrency_key(key);
+        }
+        if let Some(post_process) = post_process {
+            let operations = pythonize::depythonize::<serde_json::Value>(post_process.as_any())
+                .map_err(|e| invalid(format!("invalid post_process: {e}")))?;
+            node.config.insert(POST_PROCESS_KEY.to_string(), operations);
         }
-    }
 
-    None
-}
+        node.validate().map_err(|e| invalid(e.to_string()))?;
 
-/// Map Python type annotations to JSON Schema types
-fn map_python_type_to_json_schema(type_str: &str) -> &'static str {
-    match type_str {
-        s if s.contains("int") => "integer",
-        s if s.contains("float") => "number",
-        s if s.contains("bool") => "boolean",
-        s if s.contains("str") => "string",
-        s if s.contains("list") || s.contains("List") => "array",
-        s if s.contains("dict") || s.contains("Dict") => "object",
-        _ => "string", // Default fallback
+        Ok(Self { inner: node })
     }
 }
 
@@ -876,7 +1637,7 @@ pub(crate) fn execute_tool(
             let args_tuple = PyTuple::new(py, args)?;
             tool_func.call1(py, args_tuple)
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            Err(invalid_value(format!(
                 "Tool '{}' not found in registry",
                 tool_name
             )))
@@ -900,20 +1661,14 @@ pub(crate) fn sync_global_tools_to_workflow(py: Python<'_>) -> PyResult<()> {
 
     // Get tools from the global registry
     let global_registry = get_global_registry();
-    let global_guard = global_registry.lock().map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-            "Failed to acquire global registry lock: {}",
-            e
-        ))
-    })?;
+    let global_guard = global_registry
+        .lock()
+        .map_err(|e| runtime_error(format!("Failed to acquire global registry lock: {}", e)))?;
 
     // Get the list of registered tools from the global registry
-    let global_tools = global_guard.list_tools().map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-            "Failed to list global tools: {}",
-            e
-        ))
-    })?;
+    let global_tools = global_guard
+        .list_tools()
+        .map_err(|e| runtime_error(format!("Failed to list global tools: {}", e)))?;
 
     // For each tool in the global registry, get the actual function and register it in thread-local
     TOOL_REGISTRY.with(|local_registry| {
@@ -944,28 +1699,23 @@ fn execute_tool_from_global_registry(
     py: Python<'_>,
     tool_name: &str,
     parameters: &serde_json::Map<String, serde_json::Value>,
+    context: Option<&graphbit_core::tools::ToolContext>,
 ) -> PyResult<String> {
     use crate::tools::decorator::get_global_registry;
 
     // Get the global registry
     let global_registry = get_global_registry();
-    let global_guard = global_registry.lock().map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-            "Failed to acquire global registry lock: {}",
-            e
-        ))
-    })?;
+    let global_guard = global_registry
+        .lock()
+        .map_err(|e| runtime_error(format!("Failed to acquire global registry lock: {}", e)))?;
 
     // Check if the tool exists in the global registry
-    let has_tool = global_guard.has_tool(tool_name).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-            "Failed to check tool existence: {}",
-            e
-        ))
-    })?;
+    let has_tool = global_guard
+        .has_tool(tool_name)
+        .map_err(|e| runtime_error(format!("Failed to check tool existence: {}", e)))?;
 
     if !has_tool {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+        return Err(invalid_value(format!(
             "Tool '{}' not found in global registry",
             tool_name
         )));
@@ -980,7 +1730,7 @@ fn execute_tool_from_global_registry(
                 params_dict.set_item(key, py_value)?;
             }
             Err(e) => {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                return Err(invalid_value(format!(
                     "Failed to convert parameter '{}': {}",
                     key, e
                 )));
@@ -989,7 +1739,7 @@ fn execute_tool_from_global_registry(
     }
 
     // Execute the tool through the global registry
-    match global_guard.execute_tool(tool_name, &params_dict, py) {
+    match global_guard.execute_tool_with_context(tool_name, &params_dict, context, py) {
         Ok(tool_result) => {
             if tool_result.success {
                 Ok(format!("{}: {}", tool_name, tool_result.output))
@@ -1018,13 +1768,8 @@ pub(crate) fn execute_workflow_tool_calls(
     node_tools: Vec<String>,
 ) -> PyResult<String> {
     // Parse the tool calls JSON
-    let tool_calls: Vec<serde_json::Value> =
-        serde_json::from_str(&tool_calls_json).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to parse tool calls JSON: {}",
-                e
-            ))
-        })?;
+    let tool_calls: Vec<serde_json::Value> = serde_json::from_str(&tool_calls_json)
+        .map_err(|e| invalid_value(format!("Failed to parse tool calls JSON: {}", e)))?;
 
     let mut results = Vec::new();
 
@@ -1087,7 +1832,7 @@ pub(crate) fn execute_workflow_tool_calls(
                 } else {
                     // Execute from global registry if not found in thread-local
                     Ok::<String, PyErr>(
-                        execute_tool_from_global_registry(py, tool_name, parameters)
+                        execute_tool_from_global_registry(py, tool_name, parameters, None)
                             .unwrap_or_else(|e| {
                                 format!(
                                     "{}: Error - Tool '{}' not found in any registry: {}",
@@ -1120,17 +1865,23 @@ pub(crate) fn execute_production_tool_calls(
     py: Python<'_>,
     tool_calls_json: String,
     node_tools: Vec<String>,
+) -> PyResult<String> {
+    execute_production_tool_calls_with_context(py, tool_calls_json, node_tools, None)
+}
+
+/// Like [`execute_production_tool_calls`], passing `context` to tools declaring
+/// a `ctx` parameter
+pub(crate) fn execute_production_tool_calls_with_context(
+    py: Python<'_>,
+    tool_calls_json: String,
+    node_tools: Vec<String>,
+    context: Option<&graphbit_core::tools::ToolContext>,
 ) -> PyResult<String> {
     use crate::tools::registry::ToolRegistry;
 
     // Parse tool calls
-    let tool_calls: Vec<serde_json::Value> =
-        serde_json::from_str(&tool_calls_json).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Failed to parse tool calls JSON: {}",
-                e
-            ))
-        })?;
+    let tool_calls: Vec<serde_json::Value> = serde_json::from_str(&tool_calls_json)
+        .map_err(|e| invalid_value(format!("Failed to parse tool calls JSON: {}", e)))?;
 
     // Create a tool registry with available tools
     let registry = ToolRegistry::new();
@@ -1147,6 +1898,7 @@ pub(crate) fn execute_production_tool_calls(
                     tool_func.clone_ref(py),
                     &params_dict,
                     None,
+                    py,
                 );
             }
         }
@@ -1201,7 +1953,7 @@ pub(crate) fn execute_production_tool_calls(
             // Tokio worker threads in streaming mode.
             let local_has_tool = registry.has_tool(tool_name).unwrap_or(false);
             let result_json = if local_has_tool {
-                match registry.execute_tool(tool_name, &params_dict, py) {
+                match registry.execute_tool_with_context(tool_name, &params_dict, context, py) {
                     Ok(tool_result) => {
                         let end_time = chrono::Utc::now();
                         let duration = start_instant.elapsed();
@@ -1237,7 +1989,7 @@ pub(crate) fn execute_production_tool_calls(
                 let end_time = chrono::Utc::now();
                 let duration = start_instant.elapsed();
                 let duration_ms = duration.as_millis() as u64;
-                match execute_tool_from_global_registry(py, tool_name, parameters) {
+                match execute_tool_from_global_registry(py, tool_name, parameters, context) {
                     Ok(output_text) => {
                         let looks_like_error =
                             output_text.contains("Error -") || output_text.contains("not found");
@@ -1277,10 +2029,101 @@ pub(crate) fn execute_production_tool_calls(
         }
     }
 
-    serde_json::to_string(&tool_execution_results).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "Failed to serialize tool results: {}",
-            e
-        ))
-    })
+    serde_json::to_string(&tool_execution_results)
+        .map_err(|e| invalid_value(format!("Failed to serialize tool results: {}", e)))
+}
+
+/// Execute tool calls after validating their arguments against the declared tool schemas.
+///
+/// A call whose arguments fail validation is not executed; its result carries a structured
+/// error message the model can use to correct itself on the next turn. With
+/// `strict_tool_args` the first invalid call fails instead.
+///
+/// Given the workflow context and the calling node's name, tools declaring a
+/// `ctx` parameter read the context, and the scratch writes of each successful
+/// call are merged into it before the next call runs.
+pub(crate) fn execute_validated_tool_calls(
+    py: Python<'_>,
+    tool_calls_json: String,
+    node_tools: Vec<String>,
+    tool_defs: &[graphbit_core::llm::LlmTool],
+    strict_tool_args: bool,
+    mut workflow_context: Option<(&mut graphbit_core::types::WorkflowContext, &str)>,
+) -> PyResult<String> {
+    use graphbit_core::tools::validate_tool_arguments;
+
+    let tool_calls: Vec<serde_json::Value> = serde_json::from_str(&tool_calls_json)
+        .map_err(|e| invalid_value(format!("Failed to parse tool calls JSON: {}", e)))?;
+
+    let mut results = Vec::with_capacity(tool_calls.len());
+    for tool_call in tool_calls {
+        let tool_name = tool_call
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        let parameters = tool_call
+            .get("parameters")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        let validation = tool_defs
+            .iter()
+            .find(|tool| tool.name == tool_name)
+            .map_or(Ok(()), |tool| {
+                validate_tool_arguments(tool_name, &tool.parameters, &parameters)
+            });
+
+        match validation {
+            Ok(()) => {
+                let single_call = serde_json::to_string(&[&tool_call])
+                    .map_err(|e| invalid_value(format!("Failed to serialize tool call: {}", e)))?;
+                let tool_context = workflow_context.as_ref().map(|(context, node_name)| {
+                    graphbit_core::tools::ToolContext::new(context, *node_name)
+                });
+                let output = execute_production_tool_calls_with_context(
+                    py,
+                    single_call,
+                    node_tools.clone(),
+                    tool_context.as_ref(),
+                )?;
+                let executed: Vec<serde_json::Value> =
+                    serde_json::from_str(&output).unwrap_or_default();
+                // Scratch writes of a failed call are dropped
+                if let (Some((context, node_name)), Some(tool_context)) =
+                    (workflow_context.as_mut(), tool_context)
+                {
+                    let succeeded = executed.iter().all(|result| {
+                        result.get("success") == Some(&serde_json::Value::Bool(true))
+                    });
+                    if succeeded {
+                        context.merge_node_scratch(
+                            node_name,
+                            &format!("'{tool_name}' of node '{node_name}'"),
+                            tool_context.take_writes(),
+                        );
+                    }
+                }
+                results.extend(executed);
+            }
+            Err(err) if strict_tool_args => {
+                return Err(invalid_value(err.to_string()));
+            }
+            Err(err) => {
+                let now = chrono::Utc::now().to_rfc3339();
+                results.push(serde_json::json!({
+                    "tool_name": tool_name,
+                    "output": "",
+                    "success": false,
+                    "error": err.to_tool_message(),
+                    "error_type": "invalid_tool_arguments",
+                    "start_time": now,
+                    "end_time": now,
+                    "latency_ms": 0
+                }));
+            }
+        }
+    }
+
+    serde_json::to_string(&results)
+        .map_err(|e| invalid_value(format!("Failed to serialize tool results: {}", e)))
 }
@@ -1,10 +1,16 @@
 //! Workflow result for GraphBit Python bindings
 
-use graphbit_core::types::{WorkflowContext, WorkflowState};
+use graphbit_core::compare::{CompareOptions, CompareThresholds, RunComparison};
+use graphbit_core::report::ReportConfig;
+use graphbit_core::types::{AbandonedNode, WorkflowContext, WorkflowState};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use serde_json;
 use std::collections::HashMap;
 
+use super::attachment::Attachment;
+use crate::errors::{invalid_value, to_py_error};
+
 /// Result of workflow execution containing output data and execution metadata
 #[pyclass]
 pub struct WorkflowResult {
@@ -63,6 +69,94 @@ fn sanitize_node_metadata(node: &mut serde_json::Value) {
     }
 }
 
+/// Report configuration from the Python keyword arguments
+fn report_config(
+    max_value_chars: Option<usize>,
+    redact_keys: Option<Vec<String>>,
+    include_payloads: bool,
+) -> ReportConfig {
+    redact_keys
+        .into_iter()
+        .flatten()
+        .fold(ReportConfig::default(), ReportConfig::with_redact_key)
+        .with_max_value_chars(max_value_chars)
+        .with_payloads(include_payloads)
+}
+
+/// Comparison of a result against an expected one, as returned by
+/// `WorkflowResult.compare`: read like a dict, with helpers checking it
+/// against thresholds
+#[pyclass]
+pub struct ResultComparison {
+    inner: RunComparison,
+    data: Py<PyDict>,
+}
+
+/// Thresholds from a dict with the fields of `CompareThresholds`; none allows
+/// no status or output change
+fn compare_thresholds(thresholds: Option<&Bound<'_, PyDict>>) -> PyResult<CompareThresholds> {
+    thresholds
+        .map(|thresholds| {
+            pythonize::depythonize(thresholds)
+                .map_err(|e| invalid_value(format!("Invalid thresholds: {e}")))
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+#[pymethods]
+impl ResultComparison {
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.data
+            .bind(py)
+            .get_item(key)?
+            .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err(key.to_string()))
+    }
+
+    fn __contains__(&self, py: Python<'_>, key: &str) -> PyResult<bool> {
+        self.data.bind(py).contains(key)
+    }
+
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.data.bind(py).len()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(self.data.bind(py).keys().as_any().try_iter()?.into_any())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("ResultComparison({})", self.data.bind(py).repr()?))
+    }
+
+    /// Keys of the comparison dict
+    fn keys<'py>(&self, py: Python<'py>) -> Bound<'py, pyo3::types::PyList> {
+        self.data.bind(py).keys()
+    }
+
+    /// Copy of the comparison as a plain dict
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.data.bind(py).copy()
+    }
+
+    /// Messages describing how the comparison exceeds `thresholds`, empty when it passes
+    ///
+    /// # Arguments
+    /// * `thresholds` - Dict with `max_changed_outputs` (default 0),
+    ///   `allow_status_changes` (default False), `max_duration_increase_ms`,
+    ///   `max_duration_increase_pct` and `max_token_increase`
+    #[pyo3(signature = (thresholds=None))]
+    fn violations(&self, thresholds: Option<&Bound<'_, PyDict>>) -> PyResult<Vec<String>> {
+        Ok(self.inner.violations(&compare_thresholds(thresholds)?))
+    }
+
+    /// Whether the comparison stays within `thresholds`, see `violations`
+    #[pyo3(signature = (thresholds=None))]
+    fn passes(&self, thresholds: Option<&Bound<'_, PyDict>>) -> PyResult<bool> {
+        Ok(self.inner.passes(&compare_thresholds(thresholds)?))
+    }
+}
+
 impl WorkflowResult {
     /// Create a new workflow result
     pub fn new(context: WorkflowContext) -> Self {
@@ -80,6 +174,38 @@ impl WorkflowResult {
         matches!(self.inner.state, WorkflowState::Failed { .. })
     }
 
+    /// Whether the executor shut down and cancelled the workflow
+    fn is_cancelled(&self) -> bool {
+        self.inner.state.is_cancelled()
+    }
+
+    /// Whether the workflow finished without failing fast although some
+    /// nodes failed
+    fn is_partial(&self) -> bool {
+        self.inner.state.is_partially_completed()
+    }
+
+    /// Names of the nodes that failed in a partially completed workflow
+    fn failed_nodes(&self) -> Vec<String> {
+        match &self.inner.state {
+            WorkflowState::PartiallyCompleted { failed_nodes } => failed_nodes.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Nodes skipped because a node they depend on failed, mapped to the
+    /// names of the failed nodes blocking them
+    fn skipped_nodes(&self) -> HashMap<String, Vec<String>> {
+        self.inner
+            .abandoned_nodes
+            .iter()
+            .filter_map(|(name, state)| match state {
+                AbandonedNode::Blocked { blocked_by } => Some((name.clone(), blocked_by.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn state(&self) -> String {
         format!("{:?}", self.inner.state)
     }
@@ -211,13 +337,355 @@ impl WorkflowResult {
         }
     }
 
+    /// Get the tool calls made by an agent node, in call order
+    ///
+    /// Each entry is a dictionary with `tool_name`, `arguments`, `output`
+    /// (the error message for failed calls), `success`, `duration_ms` and
+    /// `attempt` (1-based count of calls to that tool within the node).
+    ///
+    /// # Arguments
+    /// * `node_name` - Name of the agent node
+    fn get_tool_calls(&self, py: Python<'_>, node_name: &str) -> PyResult<PyObject> {
+        let records = self.inner.get_tool_calls(node_name);
+        Ok(pythonize::pythonize(py, records)?.into())
+    }
+
+    /// Get the output schema validation outcome of an agent node
+    ///
+    /// The dictionary has `repair_attempts` (number of repair requests sent to the
+    /// model) and `validation` (`is_valid`, `errors` and `metadata` of the final
+    /// output), plus `json_repairs` (names of the repairs `repair_json` made to
+    /// the final output) when JSON had to be recovered. Returns None for nodes
+    /// without an `output_schema`.
+    ///
+    /// # Arguments
+    /// * `node_name` - Name of the agent node
+    fn get_output_validation(&self, py: Python<'_>, node_name: &str) -> PyResult<Option<PyObject>> {
+        match self.inner.get_output_validation(node_name) {
+            Some(report) => Ok(Some(pythonize::pythonize(py, report)?.into())),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the report of a guardrail node
+    ///
+    /// The dictionary has the node's `action`, its `violations`, one per check
+    /// with the `check` name, number of `matches` and, for the classifier, the
+    /// `reason`, and the number of `redactions` made. Returns None for nodes
+    /// that are not guardrails or did not run.
+    ///
+    /// # Arguments
+    /// * `node_name` - Name of the guardrail node
+    fn get_guardrail_report(&self, py: Python<'_>, node_name: &str) -> PyResult<Option<PyObject>> {
+        self.inner
+            .get_guardrail_report(node_name)
+            .map(|report| Ok(pythonize::pythonize(py, report)?.into()))
+            .transpose()
+    }
+
+    /// Get how many attempts a node needed, retries included
+    ///
+    /// Returns None for nodes that did not run.
+    ///
+    /// # Arguments
+    /// * `node_name` - Name of the node
+    fn get_node_attempts(&self, node_name: &str) -> Option<u32> {
+        self.inner.get_node_attempts(node_name)
+    }
+
+    /// Get the names of the nodes whose output `Executor.rerun` reused from an
+    /// earlier run instead of running them, sorted
+    fn get_reused_nodes(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.inner.reused_nodes.iter().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Get the files, images and audio a node produced, in the order it produced them
+    fn get_node_attachments(&self, node_name: &str) -> Vec<Attachment> {
+        self.inner
+            .get_attachments(node_name)
+            .iter()
+            .map(|attachment| Attachment {
+                inner: attachment.clone(),
+                spill: self.inner.output_spill.clone(),
+            })
+            .collect()
+    }
+
+    /// Get the prompt and provider payloads captured for a node
+    ///
+    /// Only recorded when the executor was created with `capture_payloads=True`.
+    /// The dictionary has `rendered_prompt`, `system_prompt` and `exchanges`, one
+    /// per provider request with its `method`, `url`, `request_headers`,
+    /// `request_body`, `status` and raw `response_body`. Credentials are redacted.
+    /// For nodes with `post_process` operations it also has `raw`, the output
+    /// from before they ran. Returns None for nodes without captures.
+    ///
+    /// # Arguments
+    /// * `node_name` - Name of the node
+    fn get_node_debug(&self, py: Python<'_>, node_name: &str) -> PyResult<Option<PyObject>> {
+        self.inner
+            .get_node_debug(node_name)
+            .map(|debug| Ok(pythonize::pythonize(py, debug)?.into()))
+            .transpose()
+    }
+
+    /// Nodes a join with an "any" or "quorum" policy, the execution budget or
+    /// a shutdown stopped waiting for, nodes the tag filter excluded and nodes
+    /// on branches a condition node did not take, mapped to "skipped",
+    /// "cancelled" or "detached", and nodes a failed dependency blocked, mapped
+    /// to "blocked"
+    fn get_abandoned_nodes(&self) -> HashMap<String, String> {
+        self.inner
+            .abandoned_nodes
+            .iter()
+            .map(|(name, state)| {
+                let state = match state {
+                    AbandonedNode::Skipped { .. } => "skipped",
+                    AbandonedNode::Cancelled => "cancelled",
+                    AbandonedNode::Detached => "detached",
+                    AbandonedNode::Blocked { .. } => "blocked",
+                };
+                (name.clone(), state.to_string())
+            })
+            .collect()
+    }
+
+    /// Get why the workflow failed, when the failure has a known cause
+    ///
+    /// A run stopped by the executor's budget returns
+    /// `{"kind": "budget_exceeded", "usage": {...}, "cost_usd": ...}` with the
+    /// tokens and cost used. Returns None for successful runs and other failures.
+    fn get_failure_reason(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        match &self.inner.state {
+            WorkflowState::Failed {
+                reason: Some(reason),
+                ..
+            } => Ok(Some(pythonize::pythonize(py, reason)?.into())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Get the report of the warm-up of a run started with `warm_up=True`,
+    /// shaped like the dict `Executor.warm_up` returns; None for other runs
+    fn get_warm_up_report(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let Some(report) = &self.inner.warm_up_report else {
+            return Ok(None);
+        };
+        let mut value = serde_json::to_value(report).map_err(|e| to_py_error(e.into()))?;
+        value["ok"] = serde_json::Value::Bool(report.is_ok());
+        Ok(Some(pythonize::pythonize(py, &value)?.into()))
+    }
+
+    /// Compare this result against an expected one, e.g. a golden run loaded
+    /// with `WorkflowResult.from_json`, node by node
+    ///
+    /// Returns a mapping with the run's `status` change, `duration` and
+    /// `token_delta`, the `changed_outputs` and `status_changes` counts, and
+    /// per-node entries under `nodes`, each with its `status` change,
+    /// `output_diff` (values that differ, by JSON Pointer), `text_diff` (line
+    /// diff of text outputs), `duration` and `token_delta`. Nodes are matched
+    /// by name. The result reads like a dict; its `passes(thresholds)` and `violations(thresholds)`
+    /// check it for CI assertions.
+    ///
+    /// # Arguments
+    /// * `expected` - Result to compare against
+    /// * `ignore` - Volatile fields to leave out: `"timings"` (durations, and
+    ///   timestamps in outputs), `"tokens"` and `"ids"` (output keys named `id`
+    ///   or ending in `_id`)
+    /// * `ignore_keys` - Further output keys to leave out, at any depth
+    /// * `numeric_tolerance` - Numbers in outputs differing by at most this are equal
+    #[pyo3(signature = (expected, ignore=None, ignore_keys=None, numeric_tolerance=0.0))]
+    fn compare(
+        &self,
+        py: Python<'_>,
+        expected: PyRef<'_, WorkflowResult>,
+        ignore: Option<Vec<String>>,
+        ignore_keys: Option<Vec<String>>,
+        numeric_tolerance: f64,
+    ) -> PyResult<ResultComparison> {
+        let options = ignore
+            .into_iter()
+            .flatten()
+            .try_fold(CompareOptions::default(), |options, field| {
+                options.ignore(&field)
+            })
+            .map_err(to_py_error)?;
+        let options = ignore_keys
+            .into_iter()
+            .flatten()
+            .fold(options, CompareOptions::with_ignore_key)
+            .with_numeric_tolerance(numeric_tolerance);
+
+        let inner = self.inner.compare(&expected.inner, &options);
+        let data = pythonize::pythonize(py, &inner)?
+            .downcast_into::<PyDict>()?
+            .unbind();
+        Ok(ResultComparison { inner, data })
+    }
+
+    /// Build an execution report as a dictionary
+    ///
+    /// The report lists the executed nodes in order with their prompts, outputs,
+    /// tool calls, token usage, durations, attempts and errors, plus the skipped
+    /// nodes and the graph edges. Its structure is versioned by `report_version`.
+    ///
+    /// # Arguments
+    /// * `max_value_chars` - Truncate strings longer than this; None keeps them whole
+    /// * `redact_keys` - Additional object keys whose values are redacted
+    /// * `include_payloads` - Include the prompts and provider payloads captured
+    ///   by an executor created with `capture_payloads=True`
+    #[pyo3(signature = (max_value_chars=Some(ReportConfig::DEFAULT_MAX_VALUE_CHARS), redact_keys=None, include_payloads=false))]
+    fn to_report_dict(
+        &self,
+        py: Python<'_>,
+        max_value_chars: Option<usize>,
+        redact_keys: Option<Vec<String>>,
+        include_payloads: bool,
+    ) -> PyResult<PyObject> {
+        let report = self.inner.to_report_with(&report_config(
+            max_value_chars,
+            redact_keys,
+            include_payloads,
+        ));
+        Ok(pythonize::pythonize(py, &report)?.into())
+    }
+
+    /// Save the execution report to a file
+    ///
+    /// Paths ending in `.html` get a single-page HTML report with a collapsible
+    /// view per node and a Mermaid graph of the execution; any other path gets JSON.
+    ///
+    /// # Arguments
+    /// * `path` - Destination file
+    /// * `max_value_chars` - Truncate strings longer than this; None keeps them whole
+    /// * `redact_keys` - Additional object keys whose values are redacted
+    /// * `include_payloads` - Include the captured prompts and provider payloads,
+    ///   collapsed under each node in HTML reports
+    #[pyo3(signature = (path, max_value_chars=Some(ReportConfig::DEFAULT_MAX_VALUE_CHARS), redact_keys=None, include_payloads=false))]
+    fn save_report(
+        &self,
+        path: std::path::PathBuf,
+        max_value_chars: Option<usize>,
+        redact_keys: Option<Vec<String>>,
+        include_payloads: bool,
+    ) -> PyResult<()> {
+        self.inner
+            .to_report_with(&report_config(
+                max_value_chars,
+                redact_keys,
+                include_payloads,
+            ))
+            .save(path)
+            .map_err(to_py_error)
+    }
+
+    /// Render the conversations of the agent nodes as markdown
+    ///
+    /// Per agent node the transcript holds its system prompt and prompt, each
+    /// tool-loop iteration with the tool calls and their results, and the final
+    /// answer. Secrets and resolved API keys are redacted; tool results longer
+    /// than 600 characters are collapsed into a `<details>` block.
+    ///
+    /// # Arguments
+    /// * `max_value_chars` - Truncate strings longer than this; None keeps them whole
+    /// * `redact_keys` - Additional tool argument keys whose values are redacted
+    #[pyo3(signature = (max_value_chars=Some(ReportConfig::DEFAULT_MAX_VALUE_CHARS), redact_keys=None))]
+    fn to_transcript_markdown(
+        &self,
+        max_value_chars: Option<usize>,
+        redact_keys: Option<Vec<String>>,
+    ) -> String {
+        self.inner
+            .to_transcript_markdown_with(&report_config(max_value_chars, redact_keys, false))
+    }
+
+    /// Save the markdown transcript of the agent nodes to a file
+    ///
+    /// # Arguments
+    /// * `path` - Destination file
+    /// * `max_value_chars` - Truncate strings longer than this; None keeps them whole
+    /// * `redact_keys` - Additional tool argument keys whose values are redacted
+    #[pyo3(signature = (path, max_value_chars=Some(ReportConfig::DEFAULT_MAX_VALUE_CHARS), redact_keys=None))]
+    fn save_transcript(
+        &self,
+        path: std::path::PathBuf,
+        max_value_chars: Option<usize>,
+        redact_keys: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        std::fs::write(
+            path,
+            self.to_transcript_markdown(max_value_chars, redact_keys),
+        )
+        .map_err(|e| to_py_error(e.into()))
+    }
+
+    /// Serialize the full result to a JSON string
+    ///
+    /// The JSON is the serialized workflow context: state, node outputs, tool
+    /// calls, stats and metadata. Outputs spilled to an output store stay
+    /// references. Load it again with `WorkflowResult.from_json`.
+    ///
+    /// # Arguments
+    /// * `max_value_chars` - Truncate strings in node outputs and metadata
+    ///   longer than this; None keeps them whole
+    #[pyo3(signature = (max_value_chars=None))]
+    fn to_json(&self, max_value_chars: Option<usize>) -> PyResult<String> {
+        self.inner.to_json(max_value_chars).map_err(to_py_error)
+    }
+
+    /// Load a result from JSON produced by `to_json`
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = WorkflowContext::from_json(json).map_err(to_py_error)?;
+        Ok(Self::new(inner))
+    }
+
+    /// Get workflow execution statistics, including total and failed tool calls
+    ///
+    /// # Returns
+    /// Dictionary with execution statistics, or None if unavailable
+    fn get_stats(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        match self.inner.get_stats() {
+            Some(stats) => Ok(Some(pythonize::pythonize(py, stats)?.into())),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the tokens and estimated cost of the run's LLM calls by node tag, as a
+    /// dict mapping each tag to a dict with `usage` and `cost_usd`. A node with
+    /// several tags counts toward each.
+    fn get_usage_by_tag(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let usage = self
+            .inner
+            .get_stats()
+            .map(|stats| stats.usage_by_tag().clone())
+            .unwrap_or_default();
+        Ok(pythonize::pythonize(py, &usage)?.into())
+    }
+
+    /// Get the tokens and estimated cost of the run by the value of the workflow
+    /// metadata `key`, such as `team`, as a dict mapping the value to a dict with
+    /// `usage` and `cost_usd`; None if the run's usage was not attributed to `key`
+    fn get_usage_by_metadata(&self, py: Python<'_>, key: &str) -> PyResult<Option<PyObject>> {
+        match self
+            .inner
+            .get_stats()
+            .and_then(|stats| stats.usage_by_metadata(key))
+        {
+            Some(usage) => Ok(Some(pythonize::pythonize(py, usage)?.into())),
+            None => Ok(None),
+        }
+    }
+
     /// Get complete workflow execution metadata
     ///
     /// Returns the full workflow-level schema containing:
     /// - workflow_id, workflow_name
     /// - start_time, end_time, duration_ms
     /// - user_input, final_output (from first/last nodes)
-    /// - workflow_state: completed/failed/cancelled/paused
+    /// - workflow_state: completed/partially_completed/failed/cancelled/paused
     /// - nodes: array of per-node metadata objects (each with executions array)
     /// - total_usage: aggregated token usage across all nodes
     /// - total_tool_calls: sum of tool calls across all nodes
@@ -312,6 +780,7 @@ impl WorkflowResult {
         // Determine workflow_state from context state
         let workflow_state = match &self.inner.state {
             WorkflowState::Completed => "completed",
+            WorkflowState::PartiallyCompleted { .. } => "partially_completed",
             WorkflowState::Failed { .. } => "failed",
             WorkflowState::Cancelled => "cancelled",
             WorkflowState::Paused { .. } => "paused",
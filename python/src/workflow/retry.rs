@@ -0,0 +1,108 @@
+//! Per-node retry policy for GraphBit Python bindings
+
+use crate::errors::invalid_value;
+use crate::pickle::{Reduced, from_state, restore_fn, to_state};
+use graphbit_core::types::RetryConfig;
+use pyo3::prelude::*;
+
+/// Retry policy for a single workflow node
+///
+/// `max_attempts` counts every attempt, the first one included, so
+/// `RetryPolicy(max_attempts=1)` never retries. Delays start at `backoff_ms`
+/// and double after every attempt when `exponential` is set, up to
+/// `max_backoff_ms`; `jitter` randomizes each delay by up to that fraction.
+#[pyclass(module = "graphbit")]
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub(crate) inner: RetryConfig,
+}
+
+#[pymethods]
+impl RetryPolicy {
+    #[new]
+    #[pyo3(signature = (max_attempts=3, backoff_ms=1000, exponential=true, max_backoff_ms=None, jitter=0.1))]
+    fn new(
+        max_attempts: u32,
+        backoff_ms: u64,
+        exponential: bool,
+        max_backoff_ms: Option<u64>,
+        jitter: f64,
+    ) -> PyResult<Self> {
+        if max_attempts == 0 {
+            return Err(invalid_value("max_attempts must be at least 1"));
+        }
+        if !(0.0..=1.0).contains(&jitter) {
+            return Err(invalid_value("jitter must be between 0.0 and 1.0"));
+        }
+        let defaults = RetryConfig::default();
+        let max_backoff_ms = max_backoff_ms.unwrap_or(defaults.max_delay_ms);
+        if max_backoff_ms < backoff_ms {
+            return Err(invalid_value(
+                "max_backoff_ms cannot be lower than backoff_ms",
+            ));
+        }
+
+        let multiplier = if exponential { 2.0 } else { 1.0 };
+        let inner = RetryConfig::new(max_attempts - 1)
+            .with_exponential_backoff(backoff_ms, multiplier, max_backoff_ms)
+            .with_jitter(jitter);
+        Ok(Self { inner })
+    }
+
+    /// Total number of attempts, the first one included
+    #[getter]
+    fn max_attempts(&self) -> u32 {
+        self.inner.max_attempts + 1
+    }
+
+    /// Delay before the first retry in milliseconds
+    #[getter]
+    fn backoff_ms(&self) -> u64 {
+        self.inner.initial_delay_ms
+    }
+
+    /// Whether the delay doubles after every attempt
+    #[getter]
+    fn exponential(&self) -> bool {
+        self.inner.backoff_multiplier > 1.0
+    }
+
+    /// Upper bound for the delay between attempts in milliseconds
+    #[getter]
+    fn max_backoff_ms(&self) -> u64 {
+        self.inner.max_delay_ms
+    }
+
+    /// Fraction of each delay that is randomized
+    #[getter]
+    fn jitter(&self) -> f64 {
+        self.inner.jitter_factor
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<Reduced<'py, (String,)>> {
+        Ok((restore_fn(slf.as_any())?, (to_state(&slf.borrow().inner)?,)))
+    }
+
+    /// Rebuild a policy pickled by `__reduce__`
+    #[staticmethod]
+    fn _from_state(state: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: from_state(state)?,
+        })
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RetryPolicy(max_attempts={}, backoff_ms={}, exponential={}, max_backoff_ms={}, jitter={})",
+            self.max_attempts(),
+            self.backoff_ms(),
+            if self.exponential() { "True" } else { "False" },
+            self.max_backoff_ms(),
+            self.jitter()
+        )
+    }
+}
@@ -0,0 +1,231 @@
+//! Workflow templates for GraphBit Python bindings
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use graphbit_core::template::{
+    ParameterType, TemplateParameter, TemplateRegistry as CoreTemplateRegistry,
+    WorkflowTemplate as CoreWorkflowTemplate, parse_yaml,
+};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use super::workflow::Workflow;
+use crate::errors::{invalid_value, to_py_error};
+use crate::pickle::Reduced;
+
+/// Parse a YAML file; `what` names the file's contents in errors
+pub(crate) fn load_yaml_file(path: &Path, what: &str) -> PyResult<serde_json::Value> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| invalid_value(format!("Failed to read {what} {}: {e}", path.display())))?;
+    parse_yaml(&text, &format!("{what} {}", path.display())).map_err(to_py_error)
+}
+
+/// Convert keyword arguments into template parameter values
+fn template_params(
+    params: Option<&Bound<'_, PyDict>>,
+) -> PyResult<HashMap<String, serde_json::Value>> {
+    params.map_or_else(
+        || Ok(HashMap::new()),
+        |params| {
+            pythonize::depythonize(params).map_err(|e| {
+                invalid_value(format!("Template parameters must be JSON-compatible: {e}"))
+            })
+        },
+    )
+}
+
+/// A workflow definition with declared parameters
+///
+/// Strings in the workflow (prompts, node settings, names) reference parameters
+/// as `{{params.NAME}}`. `instantiate(**params)` fills in the declared defaults,
+/// substitutes the references and returns a validated `Workflow`.
+#[pyclass(module = "graphbit")]
+#[derive(Clone)]
+pub struct WorkflowTemplate {
+    pub(crate) inner: CoreWorkflowTemplate,
+}
+
+#[pymethods]
+impl WorkflowTemplate {
+    /// Create a template from a workflow
+    ///
+    /// `parameters` maps each parameter name to its type (`"string"`, `"number"`,
+    /// `"integer"`, `"boolean"`, `"array"` or `"object"`) or to a dict with
+    /// `type` and optional `default` and `description`. Parameters without a
+    /// default are required.
+    #[new]
+    #[pyo3(signature = (name, workflow, parameters=None, description=None))]
+    fn new(
+        name: String,
+        workflow: &Workflow,
+        parameters: Option<&Bound<'_, PyDict>>,
+        description: Option<String>,
+    ) -> PyResult<Self> {
+        let mut inner =
+            CoreWorkflowTemplate::from_workflow(name, &workflow.inner).map_err(to_py_error)?;
+        if let Some(description) = description {
+            inner = inner.with_description(description);
+        }
+        if let Some(parameters) = parameters {
+            for (name, spec) in parameters.iter() {
+                let name = name.extract::<String>()?;
+                let parameter = match spec.extract::<String>() {
+                    Ok(param_type) => TemplateParameter::new(
+                        pythonize::depythonize::<ParameterType>(&spec).map_err(|_| {
+                            invalid_value(format!(
+                                "Unknown type '{param_type}' for parameter '{name}'"
+                            ))
+                        })?,
+                    ),
+                    Err(_) => pythonize::depythonize::<TemplateParameter>(&spec).map_err(|e| {
+                        invalid_value(format!("Invalid declaration of parameter '{name}': {e}"))
+                    })?,
+                };
+                inner = inner.with_parameter(name, parameter);
+            }
+        }
+        inner.validate().map_err(to_py_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Load a template from a YAML (`.yaml`, `.yml`) or JSON file with `name`,
+    /// `description`, `parameters` and `workflow` keys
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let inner = CoreWorkflowTemplate::from_file(path).map_err(to_py_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Load a template from JSON produced by `to_json`
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = CoreWorkflowTemplate::from_json(json).map_err(to_py_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Serialize the template to a JSON string
+    fn to_json(&self) -> PyResult<String> {
+        self.inner.to_json().map_err(to_py_error)
+    }
+
+    /// Build a workflow with the given parameter values
+    ///
+    /// Raises `ValueError` for unknown, missing or mistyped parameters and for
+    /// an invalid resulting workflow
+    #[pyo3(signature = (**params))]
+    fn instantiate(&self, params: Option<&Bound<'_, PyDict>>) -> PyResult<Workflow> {
+        let inner = self
+            .inner
+            .instantiate(&template_params(params)?)
+            .map_err(to_py_error)?;
+        Ok(Workflow { inner })
+    }
+
+    /// Template name
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    /// Template description
+    #[getter]
+    fn description(&self) -> String {
+        self.inner.description.clone()
+    }
+
+    /// Declared parameters, each a dict with `type` and optional `default` and
+    /// `description`
+    #[getter]
+    fn parameters(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pythonize::pythonize(py, &self.inner.parameters)?.into())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<Reduced<'py, (String,)>> {
+        let state = slf.borrow().inner.to_json().map_err(to_py_error)?;
+        Ok((slf.get_type().getattr("from_json")?, (state,)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "WorkflowTemplate(name='{}', parameters=[{}])",
+            self.inner.name,
+            self.inner
+                .parameters
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Workflow templates by name
+#[pyclass(module = "graphbit")]
+#[derive(Clone, Default)]
+pub struct TemplateRegistry {
+    inner: CoreTemplateRegistry,
+}
+
+#[pymethods]
+impl TemplateRegistry {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a template under its name; names must be unique
+    fn register(&mut self, template: &WorkflowTemplate) -> PyResult<()> {
+        self.inner
+            .register(template.inner.clone())
+            .map_err(to_py_error)
+    }
+
+    /// Load a template file (see `WorkflowTemplate.from_file`) and register it,
+    /// returning its name
+    fn register_file(&mut self, path: &str) -> PyResult<String> {
+        self.inner.register_file(path).map_err(to_py_error)
+    }
+
+    /// Build a workflow from the template registered as `name`
+    #[pyo3(signature = (name, /, **params))]
+    fn instantiate(&self, name: &str, params: Option<&Bound<'_, PyDict>>) -> PyResult<Workflow> {
+        let inner = self
+            .inner
+            .instantiate(name, &template_params(params)?)
+            .map_err(to_py_error)?;
+        Ok(Workflow { inner })
+    }
+
+    /// The template registered as `name`, if any
+    fn get(&self, name: &str) -> Option<WorkflowTemplate> {
+        self.inner.get(name).map(|inner| WorkflowTemplate {
+            inner: inner.clone(),
+        })
+    }
+
+    /// Remove a template, returning whether it was registered
+    fn remove(&mut self, name: &str) -> bool {
+        self.inner.remove(name).is_some()
+    }
+
+    /// Names of the registered templates, sorted
+    fn names(&self) -> Vec<String> {
+        self.inner.names().into_iter().map(str::to_string).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.inner.get(name).is_some()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TemplateRegistry(templates=[{}])",
+            self.inner.names().join(", ")
+        )
+    }
+}
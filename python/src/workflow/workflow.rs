@@ -1,18 +1,64 @@
 //! Workflow implementation for GraphBit Python bindings
 
 use super::node::Node;
-use crate::errors::to_py_runtime_error;
-use graphbit_core::{graph::WorkflowEdge, types::NodeId, workflow::Workflow as CoreWorkflow};
+use crate::errors::{invalid_value, to_py_error, to_py_runtime_error};
+use crate::pickle::Reduced;
+use graphbit_core::{
+    graph::{NodeTypeKind, WorkflowEdge},
+    llm::LlmUsage,
+    types::NodeId,
+    workflow::Workflow as CoreWorkflow,
+};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// A workflow definition containing nodes and their execution flow
-#[pyclass]
+#[pyclass(module = "graphbit")]
 #[derive(Clone)]
 pub struct Workflow {
     pub(crate) inner: CoreWorkflow,
 }
 
+impl Workflow {
+    /// Resolve a node given by id or name; `role` names it in the error message
+    fn resolve_node_id(&self, id_or_name: &str, role: &str) -> PyResult<NodeId> {
+        // If the string is a literal UUID (`add_node` return values), parse it first so a node
+        // *name* that equals another node's UUID cannot hijack resolution. Otherwise resolve by
+        // human-readable node name. (NodeId::from_string accepts any string via v5 — do not use
+        // that path for names.)
+        if Uuid::parse_str(id_or_name.trim()).is_ok() {
+            NodeId::from_string(id_or_name).map_err(to_py_runtime_error)
+        } else if let Some(id) = self.inner.graph.get_node_id_by_name(id_or_name) {
+            Ok(id)
+        } else {
+            Err(invalid_value(format!("{role} node {id_or_name} not found")))
+        }
+    }
+
+    /// Sorted names of the nodes `ids`
+    fn node_names(&self, ids: impl IntoIterator<Item = NodeId>) -> Vec<String> {
+        let graph = &self.inner.graph;
+        let mut names: Vec<String> = ids
+            .into_iter()
+            .filter_map(|id| graph.get_node(&id).map(|node| node.name.clone()))
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// Unknown nodes and edges are `ValueError`s, other graph errors runtime errors
+fn graph_error_to_py(e: graphbit_core::errors::GraphBitError) -> PyErr {
+    let error_msg = e.to_string();
+    if error_msg.contains("not found") || error_msg.contains("Target node") {
+        invalid_value(error_msg)
+    } else {
+        to_py_runtime_error(e)
+    }
+}
+
 #[pymethods]
 impl Workflow {
     #[new]
@@ -30,59 +76,275 @@ impl Workflow {
         Ok(node_id.to_string())
     }
 
-    fn connect(&mut self, from_id: String, to_id: String) -> PyResult<()> {
-        // If the string is a literal UUID (`add_node` return values), parse it first so a node
-        // *name* that equals another node's UUID cannot hijack resolution. Otherwise resolve by
-        // human-readable node name. (NodeId::from_string accepts any string via v5 — do not use
-        // that path for names.)
-        let from_node_id = if Uuid::parse_str(from_id.trim()).is_ok() {
-            NodeId::from_string(&from_id).map_err(to_py_runtime_error)?
-        } else if let Some(id) = self.inner.graph.get_node_id_by_name(&from_id) {
-            id
-        } else {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Source node {} not found",
-                from_id
-            )));
+    /// Connect two nodes by id or name. `condition` is a boolean expression over
+    /// the source output that must hold for the target to run; `transform` is
+    /// an expression applied to the value flowing through the edge.
+    /// `on_violation` makes the edge one a guardrail node with the "route"
+    /// action follows, instead of its other edges, when its input violates a check.
+    /// `on_timeout` makes the edge one an external event node follows, instead
+    /// of its other edges, when its event does not arrive in time.
+    /// `on="failure"` makes the target a compensation node, run only when the
+    /// source fails, even with fail-fast on; it receives `{"node", "error"}`.
+    /// `branch="true"` or `branch="false"` makes the edge the one a condition
+    /// node follows when its boolean expression evaluates to that value.
+    #[pyo3(signature = (from_id, to_id, condition=None, transform=None, on_violation=false, on_timeout=false, on=None, branch=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn connect(
+        &mut self,
+        from_id: String,
+        to_id: String,
+        condition: Option<String>,
+        transform: Option<String>,
+        on_violation: bool,
+        on_timeout: bool,
+        on: Option<&str>,
+        branch: Option<&str>,
+    ) -> PyResult<()> {
+        let from_node_id = self.resolve_node_id(&from_id, "Source")?;
+        let to_node_id = self.resolve_node_id(&to_id, "Target")?;
+
+        let branch = match branch {
+            None => None,
+            Some("true") => Some(WorkflowEdge::on_true()),
+            Some("false") => Some(WorkflowEdge::on_false()),
+            Some(other) => {
+                return Err(invalid_value(format!(
+                    "Unknown branch '{other}'; expected \"true\" or \"false\""
+                )));
+            }
         };
+        if branch.is_some() && (condition.is_some() || on_violation || on_timeout || on.is_some()) {
+            return Err(invalid_value(
+                "A true/false edge cannot have a condition or be a violation, timeout or on-failure edge",
+            ));
+        }
 
-        let to_node_id = if Uuid::parse_str(to_id.trim()).is_ok() {
-            NodeId::from_string(&to_id).map_err(to_py_runtime_error)?
-        } else if let Some(id) = self.inner.graph.get_node_id_by_name(&to_id) {
-            id
+        let on_failure = match on {
+            None => false,
+            Some("failure") => true,
+            Some(other) => {
+                return Err(invalid_value(format!(
+                    "Unknown edge trigger '{other}'; expected \"failure\""
+                )));
+            }
+        };
+        if on_failure && (condition.is_some() || on_violation || on_timeout) {
+            return Err(invalid_value(
+                "An on-failure edge cannot have a condition or be a violation or timeout edge",
+            ));
+        }
+        let edge = if let Some(edge) = branch {
+            edge
+        } else if on_failure {
+            WorkflowEdge::on_failure()
         } else {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Target node {} not found",
-                to_id
-            )));
+            match (condition, on_violation, on_timeout) {
+                (_, true, true) => {
+                    return Err(invalid_value(
+                        "An edge cannot be both a violation and a timeout edge",
+                    ));
+                }
+                (Some(_), true, false) => {
+                    return Err(invalid_value("A violation edge cannot have a condition"));
+                }
+                (Some(_), false, true) => {
+                    return Err(invalid_value("A timeout edge cannot have a condition"));
+                }
+                (Some(condition), false, false) => WorkflowEdge::conditional(condition),
+                (None, true, false) => WorkflowEdge::violation(),
+                (None, false, true) => WorkflowEdge::timeout(),
+                (None, false, false) => WorkflowEdge::data_flow(),
+            }
+        };
+        let edge = match transform {
+            Some(transform) => edge.with_transform(transform),
+            None => edge,
         };
 
         self.inner
-            .connect_nodes(from_node_id, to_node_id, WorkflowEdge::data_flow())
-            .map_err(|e| {
-                let error_msg = e.to_string();
-                if error_msg.contains("not found") || error_msg.contains("Target node") {
-                    PyErr::new::<pyo3::exceptions::PyValueError, _>(error_msg)
-                } else {
-                    to_py_runtime_error(e)
-                }
-            })?;
+            .connect_nodes(from_node_id, to_node_id, edge)
+            .map_err(graph_error_to_py)?;
         Ok(())
     }
 
+    /// Replace a node, given by id or name, with `node`, keeping its incoming
+    /// and outgoing edges. Returns the id of the new node.
+    fn replace_node(&mut self, node_id: String, node: Node) -> PyResult<String> {
+        let old_id = self.resolve_node_id(&node_id, "Node")?;
+        let new_id = node.inner.id.clone();
+        self.inner
+            .graph
+            .replace_node(&old_id, node.inner)
+            .map_err(graph_error_to_py)?;
+        Ok(new_id.to_string())
+    }
+
+    /// Insert `node` into the edge between two nodes, given by id or name. The
+    /// edge keeps its condition and transform and now leads to `node`, which
+    /// connects on to the original target. Returns the id of the new node.
+    fn insert_between(&mut self, from_id: String, to_id: String, node: Node) -> PyResult<String> {
+        let from_node_id = self.resolve_node_id(&from_id, "Source")?;
+        let to_node_id = self.resolve_node_id(&to_id, "Target")?;
+        let new_id = node.inner.id.clone();
+        self.inner
+            .graph
+            .insert_between(&from_node_id, &to_node_id, node.inner)
+            .map_err(graph_error_to_py)?;
+        Ok(new_id.to_string())
+    }
+
     fn validate(&self) -> PyResult<()> {
         self.inner.validate().map_err(to_py_runtime_error)?;
         Ok(())
     }
 
+    /// Problems of a valid workflow worth a look, such as a condition node
+    /// with a true edge but no false edge
+    fn warnings(&self) -> Vec<String> {
+        self.inner.warnings()
+    }
+
     fn name(&self) -> String {
         self.inner.name.clone()
     }
 
+    /// Declare the JSON Schema the run-time `inputs` of an execution must match
+    ///
+    /// The inputs are checked as one object before any node runs: list them
+    /// under `properties`, the ones that must be given under `required`, and
+    /// reject undeclared inputs with `"additionalProperties": False`. Inputs that
+    /// do not match raise `ValidationError` listing every missing and invalid one
+    fn set_input_schema(&mut self, schema: &Bound<'_, PyDict>) -> PyResult<()> {
+        let schema: serde_json::Value = pythonize::depythonize(schema)
+            .map_err(|e| invalid_value(format!("Input schema must be JSON-compatible: {e}")))?;
+        self.inner.set_input_schema(schema).map_err(to_py_error)
+    }
+
+    /// The declared input schema, or `None`
+    fn input_schema(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        self.inner
+            .input_schema
+            .as_ref()
+            .map(|schema| Ok(pythonize::pythonize(py, schema)?.unbind()))
+            .transpose()
+    }
+
     fn node_count(&self) -> usize {
         self.inner.graph.node_count()
     }
 
+    fn edge_count(&self) -> usize {
+        self.inner.graph.edge_count()
+    }
+
+    /// Names of the nodes of the given `type` (e.g. `"agent"`) carrying `tag`,
+    /// in name order; every node when neither is given
+    #[pyo3(signature = (r#type=None, tag=None))]
+    fn find_nodes(&self, r#type: Option<&str>, tag: Option<&str>) -> PyResult<Vec<String>> {
+        let kind = r#type
+            .map(str::parse::<NodeTypeKind>)
+            .transpose()
+            .map_err(to_py_error)?;
+        let graph = &self.inner.graph;
+        let ids = graph.find_nodes(|node| {
+            kind.is_none_or(|kind| node.node_type.type_kind() == kind)
+                && tag.is_none_or(|tag| node.tags.iter().any(|t| t == tag))
+        });
+        Ok(self.node_names(ids))
+    }
+
+    /// Names of the nodes the node, given by id or name, depends on directly
+    /// or through other nodes, in name order
+    fn ancestors(&mut self, node: &str) -> PyResult<Vec<String>> {
+        let node_id = self.resolve_node_id(node, "Node")?;
+        let ids = self.inner.graph.ancestors(&node_id);
+        Ok(self.node_names(ids))
+    }
+
+    /// Names of the nodes depending on the node, given by id or name, directly
+    /// or through other nodes, in name order
+    fn descendants(&mut self, node: &str) -> PyResult<Vec<String>> {
+        let node_id = self.resolve_node_id(node, "Node")?;
+        let ids = self.inner.graph.descendants(&node_id);
+        Ok(self.node_names(ids))
+    }
+
+    /// Analyze the workflow graph without executing it
+    ///
+    /// Returns a dict with `depth` (nodes on the longest dependency chain),
+    /// `critical_path` (names of the nodes on that chain), `max_parallel_width`
+    /// and a `cost` estimate for running every agent node once with the given
+    /// average token counts. `prices` maps model names to
+    /// `(input_cost_per_token, output_cost_per_token)`.
+    #[pyo3(signature = (prices=None, avg_prompt_tokens=1000, avg_completion_tokens=500))]
+    fn analyze(
+        &self,
+        py: Python<'_>,
+        prices: Option<HashMap<String, (f64, f64)>>,
+        avg_prompt_tokens: u32,
+        avg_completion_tokens: u32,
+    ) -> PyResult<PyObject> {
+        let graph = &self.inner.graph;
+        let critical_path = graph
+            .critical_path()
+            .map_err(to_py_error)?
+            .iter()
+            .filter_map(|node_id| graph.get_node(node_id).map(|node| node.name.clone()))
+            .collect::<Vec<_>>();
+        let cost = self
+            .inner
+            .estimate_cost(
+                &prices.unwrap_or_default(),
+                &LlmUsage::new(avg_prompt_tokens, avg_completion_tokens),
+            )
+            .map_err(to_py_error)?;
+        let analysis = serde_json::json!({
+            "node_count": graph.node_count(),
+            "edge_count": graph.edge_count(),
+            "depth": critical_path.len(),
+            "critical_path": critical_path,
+            "max_parallel_width": graph.max_parallel_width().map_err(to_py_error)?,
+            "cost": cost,
+        });
+        Ok(pythonize::pythonize(py, &analysis)?.into())
+    }
+
+    /// Serialize the workflow to a JSON string
+    ///
+    /// The JSON carries a `schema_version` field and keeps node IDs, so it can be
+    /// stored and loaded again with `Workflow.from_json`
+    fn to_json(&self) -> PyResult<String> {
+        self.inner.to_json().map_err(to_py_error)
+    }
+
+    /// Load a workflow from JSON produced by `to_json`
+    ///
+    /// Raises `ValueError` when the `schema_version` is newer than this version
+    /// of GraphBit supports
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = CoreWorkflow::from_json(json).map_err(to_py_error)?;
+        Ok(Self { inner })
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<Reduced<'py, (String,)>> {
+        let state = slf.borrow().inner.to_json().map_err(to_py_error)?;
+        Ok((slf.get_type().getattr("from_json")?, (state,)))
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
+    /// Set the workflow metadata `key` to `value`, any JSON-compatible value.
+    /// Executors attribute the usage of a run to the value of its `team` key.
+    fn set_metadata(&mut self, key: String, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let value = pythonize::depythonize::<serde_json::Value>(value)
+            .map_err(|e| invalid_value(format!("Metadata must be JSON-compatible: {e}")))?;
+        self.inner.set_metadata(key, value);
+        Ok(())
+    }
+
     /// Set graph-level metadata key to a boolean value
     /// Exposes core graph.set_metadata for Python tests and configuration
     fn set_graph_metadata(&mut self, key: String, value: bool) -> PyResult<()> {
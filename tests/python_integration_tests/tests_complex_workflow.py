@@ -508,7 +508,7 @@ class TestDataPipelineWorkflows:
         stream_id = workflow.add_node(stream_agent)
 
         # Partitioning logic
-        partition_condition = graphbit.Node.condition("partition", "hash(key) % partition_count")
+        partition_condition = graphbit.Node.condition("partition", "'partition_' + str(len(key) % partition_count)")
         partition_id = workflow.add_node(partition_condition)
 
         # Parallel processing partitions
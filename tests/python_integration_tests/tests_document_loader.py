@@ -514,7 +514,7 @@ class TestDocumentLoader:
         """Test URL loading with unsupported document types."""
         loader = DocumentLoader()
 
-        # PDF and DOCX should not be supported for URL loading yet
+        # Nothing listens on port 9, so loading fails whatever the type
         with pytest.raises(Exception) as exc_info:
             loader.load_document("http://127.0.0.1:9/example.pdf", "pdf")
 
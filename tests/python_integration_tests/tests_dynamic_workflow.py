@@ -279,9 +279,9 @@ class TestDynamicDataTransformation:
         transformations = [
             ("uppercase", "uppercase"),
             ("lowercase", "lowercase"),
-            ("json_parse", "parse_json"),
-            ("format_date", "format_date('%Y-%m-%d')"),
-            ("extract_text", "extract_text(pattern='[a-zA-Z]+')"),
+            ("json_parse", "json_parse"),
+            ("format_date", "replace(input.date, '/', '-')"),
+            ("extract_text", "trim(lower(input.text))"),
         ]
 
         for name, operation in transformations:
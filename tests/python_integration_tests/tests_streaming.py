@@ -270,6 +270,21 @@ class TestStreamModeFiltering:
         assert len(token_events) == 0, \
             f"Updates mode emitted {len(token_events)} unexpected token events"
 
+    def test_updates_mode_emits_no_node_output_deltas(
+        self, llm_config: Any, simple_workflow: Any
+    ) -> None:
+        """stream_mode='updates' must not produce any 'node_output_delta' events."""
+        executor = Executor(llm_config)
+        events = list(executor.execute_streaming(simple_workflow, stream_mode="updates"))
+        assert not [e for e in events if e.get("event") == "node_output_delta"]
+
+    def test_schema_documents_node_output_delta(self) -> None:
+        """The stream schema must describe node_output_delta events."""
+        schema = Executor.get_stream_event_schema()
+        assert "node_output_delta" in schema["event_groups"]["node"]
+        delta = next(e for e in schema["events"] if e["event"] == "node_output_delta")
+        assert "delta" in {f["name"] for f in delta["fields"]}
+
     def test_node_events_present_in_updates_mode(
         self, llm_config: Any, simple_workflow: Any
     ) -> None:
@@ -344,6 +359,20 @@ class TestStreamingWithRealLlm:
         assert len(token_events) > 0, \
             "All mode must emit at least one token event"
 
+    def test_realllm_messages_mode_emits_node_output_deltas(
+        self, live_config: Any, live_workflow: Any
+    ) -> None:
+        """Concatenated node_output_delta events must match the node's output."""
+        executor = Executor(live_config)
+        events = list(
+            executor.execute_streaming(live_workflow, stream_mode="messages")
+        )
+
+        deltas = [e["delta"] for e in events if e.get("event") == "node_output_delta"]
+        assert deltas, "Messages mode must emit at least one node_output_delta event"
+        completed = next(e for e in events if e.get("event") == "workflow_completed")
+        assert completed["result"].get_node_output("Streamer") == "".join(deltas)
+
     def test_realllm_workflow_completed_has_outputs(
         self, live_config: Any, live_workflow: Any
     ) -> None:
@@ -171,6 +171,40 @@ class TestWorkflowComponents:
             condition = graphbit.Node.condition(name, expression)
             assert condition.name() == name
 
+    def test_condition_expression_arguments(self) -> None:
+        """Test condition nodes built from expressions and handlers."""
+        by_keyword = graphbit.Node.condition("route", expression="score > 0.8")
+        assert by_keyword.name() == "route"
+
+        with pytest.raises(ValueError):
+            graphbit.Node.condition("both", lambda state: "a", expression="true")
+        with pytest.raises(ValueError):
+            graphbit.Node.condition("neither")
+        with pytest.raises(ValueError):
+            graphbit.Node.condition("empty", expression="  ")
+        with pytest.raises(TypeError):
+            graphbit.Node.condition("not_callable", 42)
+
+    def test_expression_edges_validate(self) -> None:
+        """Test that validate() parses conditions and edge expressions."""
+        workflow = graphbit.Workflow("expression_edges")
+        source = workflow.add_node(graphbit.Node.transform("source", "{count: 3}"))
+        many = workflow.add_node(graphbit.Node.transform("many", "'many'"))
+        shaped = workflow.add_node(graphbit.Node.transform("shaped", "input"))
+        workflow.connect(source, many, condition="count > 1")
+        workflow.connect(source, shaped, transform="count * 2")
+        workflow.validate()
+
+        broken = graphbit.Workflow("broken_edge")
+        a = broken.add_node(graphbit.Node.transform("a", "1"))
+        b = broken.add_node(graphbit.Node.transform("b", "input"))
+        broken.connect(a, b, condition="input >")
+        with pytest.raises(Exception, match="invalid condition"):
+            broken.validate()
+
+        with pytest.raises(Exception, match="Unknown function 'shell'"):
+            graphbit.Node.condition("route", "shell('ls')")
+
     def test_transform_node_builder(self) -> None:
         """Test building transformation nodes."""
         transforms = [
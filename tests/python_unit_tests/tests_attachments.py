@@ -0,0 +1,73 @@
+"""Unit tests for node attachments."""
+
+import pytest
+
+from graphbit import Attachment, Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "4" * 48
+
+
+@pytest.fixture
+def executor():
+    """Executor that never reaches an LLM."""
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"), fail_fast=False)
+
+
+class TestAttachments:
+    """Test reading the attachments nodes produced."""
+
+    def test_document_loader_attaches_the_loaded_file(self, executor, tmp_path):
+        """Test the file a document loader read being available as an attachment."""
+        source = tmp_path / "notes.txt"
+        source.write_text("Quarterly notes")
+        workflow = Workflow("loader")
+        workflow.add_node(Node.document_loader("loader", str(source), "txt"))
+
+        result = executor.execute(workflow)
+
+        assert result.is_success(), result.state()
+        [attachment] = result.get_node_attachments("loader")
+        assert isinstance(attachment, Attachment)
+        assert attachment.mime_type == "text/plain"
+        assert attachment.filename == "notes.txt"
+        assert attachment.size_bytes == 15
+        assert len(attachment.sha256) == 64
+        assert attachment.bytes() == b"Quarterly notes"
+        copy = tmp_path / "copy.txt"
+        attachment.save(str(copy))
+        assert copy.read_bytes() == b"Quarterly notes"
+        assert "notes.txt" in repr(attachment)
+
+    def test_node_without_attachments(self, executor):
+        """Test nodes that attached nothing returning an empty list."""
+        workflow = Workflow("transform")
+        workflow.add_node(Node.transform("a", "'a'"))
+
+        result = executor.execute(workflow)
+
+        assert result.get_node_attachments("a") == []
+        assert result.get_node_attachments("missing") == []
+
+
+class TestAttachmentConfiguration:
+    """Test validation of attachment settings."""
+
+    def test_multipart_and_body_are_exclusive(self):
+        """Test an HTTP request node rejecting both a body and multipart fields."""
+        with pytest.raises(ValueError, match="multipart"):
+            Node.http_request("upload", "http://127.0.0.1:9", "POST", body={"a": 1}, multipart={"file": "x"})
+
+    def test_multipart_fields_are_accepted(self):
+        """Test an HTTP request node taking multipart fields."""
+        node = Node.http_request("upload", "http://127.0.0.1:9", "POST", multipart={"file": "{{nodes.fetch.attachments[0]}}", "purpose": "vision"})
+        assert node.name() == "upload"
+
+    def test_zero_attachment_limit_is_rejected(self):
+        """Test the executor rejecting a maximum attachment size of zero."""
+        with pytest.raises(ValueError, match="Maximum attachment size must be greater than 0"):
+            Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"), max_attachment_bytes=0)
+
+    def test_attachment_limit_is_reported(self):
+        """Test the executor stats reporting the maximum attachment size."""
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"), max_attachment_bytes=1024)
+        assert executor.get_stats()["max_attachment_bytes"] == 1024
@@ -0,0 +1,317 @@
+"""Unit tests for the graphbit command line interface."""
+
+import contextlib
+import io
+import json
+import os
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit.cli import main
+
+API_KEY = "sk-" + "0" * 48
+
+FIXTURES = os.path.join(os.path.dirname(__file__), "fixtures")
+WORKFLOW = os.path.join(FIXTURES, "cli_workflow.yaml")
+WORKFLOW_JSON = os.path.join(FIXTURES, "cli_workflow.json")
+INVALID_WORKFLOW = os.path.join(FIXTURES, "cli_invalid_workflow.yaml")
+PRICES = os.path.join(FIXTURES, "cli_prices.yaml")
+
+
+def run(*argv):
+    """Run the CLI and return its exit code, standard output and standard error."""
+    out, err = io.StringIO(), io.StringIO()
+    with contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+        code = main(list(argv))
+    return code, out.getvalue(), err.getvalue()
+
+
+@pytest.fixture
+def llm_server():
+    """Local OpenAI-compatible server recording prompts; prompts mentioning FAIL get an HTTP 400."""
+    prompts = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            prompt = body["messages"][-1]["content"]
+            prompts.append(prompt)
+            if "FAIL" in prompt:
+                status, reply = 400, {"error": {"message": "rejected"}}
+            else:
+                status, reply = 200, {
+                    "id": "chatcmpl-1",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Done"}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+                }
+            payload = json.dumps(reply).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", prompts
+    server.shutdown()
+
+
+def write_profiles(directory, url):
+    """Write a profiles.yaml with a `dev` profile pointing at the local server."""
+    path = directory / "profiles.yaml"
+    path.write_text(f"dev:\n  llm_config:\n    provider: OpenAI\n    api_key: {API_KEY}\n    model: gpt-4o-mini\n    base_url: {url}/v1\n")
+    return str(path)
+
+
+class TestCliUsage:
+    """Arguments the CLI rejects and its help text."""
+
+    def test_help_exits_zero(self):
+        """--help prints the usage to stdout."""
+        code, out, _ = run("--help")
+        assert code == 0
+        assert "usage: graphbit" in out
+
+    def test_no_command_is_usage_error(self):
+        """Running without a command prints the usage to stderr and exits with 2."""
+        code, out, err = run()
+        assert code == 2
+        assert out == ""
+        assert "usage: graphbit" in err
+
+    def test_unknown_command_is_usage_error(self):
+        """An unknown command exits with 2 and names the command."""
+        code, _, err = run("deploy", WORKFLOW)
+        assert code == 2
+        assert "unknown command 'deploy'" in err
+
+    def test_unknown_option_is_usage_error(self):
+        """An option the command does not take exits with 2."""
+        code, _, err = run("validate", WORKFLOW, "--strict")
+        assert code == 2
+        assert "unknown option '--strict'" in err
+
+    def test_missing_workflow_file_fails(self):
+        """A workflow file that does not exist exits with 1."""
+        code, _, err = run("graph", os.path.join(FIXTURES, "missing.yaml"))
+        assert code == 1
+        assert "cannot read" in err
+
+
+class TestCliValidate:
+    """graphbit validate."""
+
+    def test_valid_yaml_workflow(self):
+        """A valid YAML workflow passes."""
+        code, out, _ = run("validate", WORKFLOW)
+        assert code == 0
+        assert out == f"{WORKFLOW}: ok\n"
+
+    def test_valid_json_workflow(self):
+        """Workflows are also read from JSON files."""
+        code, out, _ = run("validate", WORKFLOW_JSON)
+        assert code == 0
+        assert out.endswith(": ok\n")
+
+    def test_invalid_workflow_lists_every_problem(self):
+        """Every invalid node and edge expression is reported, not just the first."""
+        code, out, _ = run("validate", INVALID_WORKFLOW)
+        assert code == 1
+        assert "2 problem(s)" in out
+        assert "node 'Extract'" in out
+        assert "edge 'Summarize' -> 'Reply': invalid condition" in out
+
+    def test_unreadable_workflow_is_a_problem(self, tmp_path):
+        """A file that is not a workflow is reported as a problem."""
+        path = tmp_path / "broken.yaml"
+        path.write_text("name: [unclosed\n")
+        code, out, _ = run("validate", str(path))
+        assert code == 1
+        assert "1 problem(s)" in out
+        assert "not valid YAML" in out
+
+
+class TestCliGraph:
+    """graphbit graph."""
+
+    def test_mermaid_is_the_default(self):
+        """The graph is printed as a Mermaid flowchart in dependency order."""
+        code, out, _ = run("graph", WORKFLOW)
+        assert code == 0
+        assert out.splitlines() == [
+            "flowchart TD",
+            '    n0["Extract"]',
+            '    n1("Summarize")',
+            '    n2("Reply")',
+            "    n0 --> n1",
+            "    n1 --> n2",
+        ]
+
+    def test_dot_format(self):
+        """--format dot prints a Graphviz digraph."""
+        code, out, _ = run("graph", WORKFLOW, "--format", "dot")
+        assert code == 0
+        assert out.startswith("digraph workflow {")
+        assert 'n1 [label="Summarize", shape=box, style=rounded];' in out
+        assert "n0 -> n1;" in out
+
+    def test_output_file(self, tmp_path):
+        """-o writes the graph to a file instead of stdout."""
+        path = tmp_path / "workflow.dot"
+        code, out, _ = run("graph", WORKFLOW, "--format=dot", "-o", str(path))
+        assert code == 0
+        assert out == ""
+        assert path.read_text().startswith("digraph workflow {")
+
+    def test_unknown_format_is_usage_error(self):
+        """Formats other than mermaid and dot are rejected."""
+        code, _, err = run("graph", WORKFLOW, "--format", "svg")
+        assert code == 2
+        assert "--format must be 'mermaid' or 'dot'" in err
+
+
+class TestCliCost:
+    """graphbit cost."""
+
+    def test_estimate_with_unpriced_node(self):
+        """Priced agent nodes are summed and agent nodes without a price are listed."""
+        code, out, _ = run("cost", WORKFLOW, "--model-prices", PRICES)
+        assert code == 0
+        assert "Summarize  gpt-4o-mini  $0.000450" in out
+        assert "Total: $0.000450 for 2000 prompt + 1000 completion tokens" in out
+        assert "Unpriced nodes: Reply" in out
+
+    def test_token_counts(self):
+        """--prompt-tokens and --completion-tokens set the tokens per agent node."""
+        code, out, _ = run(
+            "cost", WORKFLOW, "--model-prices", PRICES, "--prompt-tokens", "2000", "--completion-tokens=0"
+        )
+        assert code == 0
+        assert "Total: $0.000300 for 4000 prompt + 0 completion tokens" in out
+
+    def test_prices_are_required(self):
+        """cost without --model-prices is a usage error."""
+        code, _, err = run("cost", WORKFLOW)
+        assert code == 2
+        assert "--model-prices" in err
+
+    def test_invalid_token_count_is_usage_error(self):
+        """Token counts must be whole numbers."""
+        code, _, err = run("cost", WORKFLOW, "--model-prices", PRICES, "--prompt-tokens", "lots")
+        assert code == 2
+        assert "--prompt-tokens must be a whole number" in err
+
+    def test_malformed_prices_fail(self, tmp_path):
+        """A prices file not mapping models to two per-token costs exits with 1."""
+        path = tmp_path / "prices.json"
+        path.write_text('{"gpt-4o-mini": 0.1}')
+        code, _, err = run("cost", WORKFLOW, "--model-prices", str(path))
+        assert code == 1
+        assert "input_cost_per_token" in err
+
+
+class TestCliRun:
+    """graphbit run, against a local mock provider."""
+
+    def test_run_with_inputs_and_profile(self, llm_server, tmp_path):
+        """Inputs reach the workflow and every node is listed with its status."""
+        url, prompts = llm_server
+        code, out, _ = run("run", WORKFLOW, "--input", "ticket=printer on fire", "--profile", "dev", "--profiles", write_profiles(tmp_path, url))
+        assert code == 0
+        lines = out.splitlines()
+        assert lines[0].startswith("'support_triage' completed in ")
+        assert [line.split()[:2] for line in lines[1:]] == [["Extract", "succeeded"], ["Summarize", "succeeded"], ["Reply", "succeeded"]]
+        assert lines[2].endswith("6 tokens")
+        assert "Summarize: printer on fire" in prompts[0]
+
+    def test_input_flags_override_input_file(self, llm_server, tmp_path):
+        """--input values replace the values of the same name in --input-file."""
+        url, prompts = llm_server
+        inputs = tmp_path / "inputs.json"
+        inputs.write_text('{"ticket": "from the file"}')
+        code, _, _ = run(
+            "run", WORKFLOW, "--input-file", str(inputs), "--input", "ticket=from the flag",
+            "--profile", "dev", "--profiles", write_profiles(tmp_path, url),
+        )
+        assert code == 0
+        assert "Summarize: from the flag" in prompts[0]
+
+    def test_profiles_found_next_to_workflow(self, llm_server, tmp_path):
+        """--profile without --profiles reads profiles.yaml next to the workflow."""
+        url, _ = llm_server
+        workflow = tmp_path / "workflow.yaml"
+        workflow.write_text(open(WORKFLOW).read())
+        write_profiles(tmp_path, url)
+        code, _, _ = run("run", str(workflow), "--input", "ticket=hello", "--profile", "dev")
+        assert code == 0
+
+    def test_failing_node_is_named(self, llm_server, tmp_path):
+        """A node failure exits with 1 and names the node."""
+        url, _ = llm_server
+        code, out, err = run("run", WORKFLOW, "--input", "ticket=FAIL", "--profile", "dev", "--profiles", write_profiles(tmp_path, url))
+        assert code == 1
+        assert "Summarize  failed" in out
+        assert "graphbit run: node 'Summarize' failed: " in err
+        assert "HTTP 400" in err
+
+    def test_unconfigured_agent_nodes_are_named(self):
+        """Agent nodes with no LLM configuration are named before anything runs."""
+        code, out, err = run("run", WORKFLOW, "--input", "ticket=hello")
+        assert code == 1
+        assert out == ""
+        assert "agent node(s) 'Reply', 'Summarize' have no LLM configuration" in err
+
+    def test_unknown_profile_fails(self, llm_server, tmp_path):
+        """Selecting a profile the file does not define exits with 1."""
+        url, _ = llm_server
+        code, _, err = run("run", WORKFLOW, "--profile", "prod", "--profiles", write_profiles(tmp_path, url))
+        assert code == 1
+        assert "unknown profile 'prod', available profiles: dev" in err
+
+    def test_html_and_json_reports(self, llm_server, tmp_path):
+        """-o writes the execution report as HTML for .html files and JSON otherwise."""
+        url, _ = llm_server
+        profiles = write_profiles(tmp_path, url)
+        html, data = tmp_path / "report.html", tmp_path / "report.json"
+        assert run("run", WORKFLOW, "--input", "ticket=hello", "--profile", "dev", "--profiles", profiles, "-o", str(html))[0] == 0
+        assert run("run", WORKFLOW, "--input", "ticket=hello", "--profile", "dev", "--profiles", profiles, "-o", str(data))[0] == 0
+        assert "<html" in html.read_text()
+        report = json.loads(data.read_text())
+        assert report["status"] == "completed"
+        assert [node["node_name"] for node in report["nodes"]] == ["Extract", "Summarize", "Reply"]
+
+    def test_watch_reruns_on_change(self, llm_server, tmp_path):
+        """--watch runs again once a file in the watched directory changes."""
+        url, prompts = llm_server
+        data = tmp_path / "data"
+        data.mkdir()
+        argv = ["run", WORKFLOW, "--input", "ticket=hello", "--profile", "dev", "--profiles", write_profiles(tmp_path, url)]
+        argv += ["--watch", "--watch-dir", str(data), "--max-runs", "2"]
+        out, err, result = io.StringIO(), io.StringIO(), {}
+        with contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+            thread = threading.Thread(target=lambda: result.update(code=main(argv)))
+            thread.start()
+            deadline = time.monotonic() + 30
+            while "Watching for changes" not in out.getvalue() and time.monotonic() < deadline:
+                time.sleep(0.05)
+            (data / "tickets.txt").write_text("new ticket")
+            thread.join(30)
+        assert result == {"code": 0}, err.getvalue()
+        assert "[run 1] 'support_triage' completed" in out.getvalue()
+        assert "[run 2] 'support_triage' completed" in out.getvalue()
+        assert len(prompts) == 4
+
+    def test_bad_arguments_are_usage_errors(self, tmp_path):
+        """Malformed inputs, a profile without a profiles file and watch options without --watch exit with 2."""
+        assert run("run", WORKFLOW, "--input", "ticket")[0] == 2
+        workflow = tmp_path / "workflow.yaml"
+        workflow.write_text(open(WORKFLOW).read())
+        assert run("run", str(workflow), "--profile", "dev")[0] == 2
+        assert run("run", WORKFLOW, "--max-runs", "2")[0] == 2
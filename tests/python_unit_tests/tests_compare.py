@@ -0,0 +1,105 @@
+"""Unit tests for comparing workflow results."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow, WorkflowResult
+
+API_KEY = "sk-" + "0" * 48
+
+
+@pytest.fixture
+def server():
+    """Local OpenAI-compatible server answering every completion with `replies["content"]`."""
+    replies = {"content": "Prices stay the same.", "completion_tokens": 5}
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers["Content-Length"]))
+            payload = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "model": "gpt-4o-mini",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": replies["content"]}, "finish_reason": "stop"}],
+                    "usage": {
+                        "prompt_tokens": 10,
+                        "completion_tokens": replies["completion_tokens"],
+                        "total_tokens": 10 + replies["completion_tokens"],
+                    },
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    http_server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=http_server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{http_server.server_port}/v1", replies
+    http_server.shutdown()
+
+
+def run(base_url):
+    """Run a one-node workflow against the mock server, through JSON like a stored golden run."""
+    workflow = Workflow("compare")
+    workflow.add_node(Node.agent(name="draft", prompt="Draft the announcement"))
+    result = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url)).execute(workflow)
+    assert result.is_success()
+    return WorkflowResult.from_json(result.to_json())
+
+
+class TestResultCompare:
+    """Test WorkflowResult.compare and the passes helper."""
+
+    def test_identical_runs_pass(self, server):
+        """Test that two runs with the same outputs show no changes when timings are ignored."""
+        base_url, _ = server
+        golden = run(base_url)
+
+        diff = run(base_url).compare(golden, ignore=["timings"])
+
+        assert isinstance(diff.to_dict(), dict)
+        assert "nodes" in diff
+        assert diff["changed_outputs"] == 0
+        assert diff["status"] is None
+        assert diff["duration"] is None
+        assert diff["token_delta"]["total_tokens"] == 0
+        assert [node["node_name"] for node in diff["nodes"]] == ["draft"]
+        assert diff.passes()
+        assert diff.violations() == []
+
+    def test_changed_output(self, server):
+        """Test that a changed reply shows as a text diff and fails the default thresholds."""
+        base_url, replies = server
+        golden = run(base_url)
+        replies.update(content="Prices rise in May.", completion_tokens=8)
+
+        diff = run(base_url).compare(golden, ignore=["timings"])
+
+        node = diff["nodes"][0]
+        assert node["output_changed"]
+        assert node["text_diff"] == ["-Prices stay the same.", "+Prices rise in May."]
+        assert node["token_delta"]["completion_tokens"] == 3
+        assert diff["token_delta"]["total_tokens"] == 3
+        assert not diff.passes()
+        assert diff.violations() == ["1 node outputs changed, at most 0 allowed: draft"]
+        assert diff.passes({"max_changed_outputs": 1, "max_token_increase": 5})
+        assert not diff.passes({"max_changed_outputs": 1, "max_token_increase": 2})
+
+    def test_invalid_arguments(self, server):
+        """Test that unknown fields to ignore and unknown thresholds raise."""
+        base_url, _ = server
+        golden = run(base_url)
+
+        with pytest.raises(ValueError):
+            golden.compare(golden, ignore=["outputs"])
+        with pytest.raises(ValueError):
+            golden.compare(golden).passes({"max_changes": 1})
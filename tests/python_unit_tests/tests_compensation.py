@@ -0,0 +1,73 @@
+"""Unit tests for on-failure edges leading to compensation nodes."""
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "7" * 48
+
+
+def pipeline(write):
+    """Build `Fetch -> Write -> Publish`, with `Cleanup` compensating `Write`."""
+    workflow = Workflow("compensation")
+    workflow.add_node(Node.transform("Fetch", "'record'"))
+    workflow.add_node(Node.transform("Write", write))
+    workflow.add_node(Node.transform("Publish", "input + ' published'"))
+    workflow.add_node(Node.transform("Cleanup", "'deleted after ' + input.node"))
+    workflow.connect("Fetch", "Write")
+    workflow.connect("Write", "Publish")
+    workflow.connect("Write", "Cleanup", on="failure")
+    return workflow
+
+
+def execute(workflow, fail_fast=False):
+    """Run a workflow of transform nodes only."""
+    executor = Executor(
+        LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"),
+        fail_fast=fail_fast,
+    )
+    return executor.execute(workflow)
+
+
+class TestCompensation:
+    """Test Workflow.connect with on="failure"."""
+
+    @pytest.mark.parametrize("fail_fast", [False, True])
+    def test_compensation_never_runs_on_success(self, fail_fast):
+        """Test the compensation node being left out of a successful run."""
+        result = execute(pipeline("input + ' written'"), fail_fast=fail_fast)
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Publish") == "record written published"
+        assert result.get_node_output("Cleanup") is None
+
+    def test_compensation_runs_on_failure(self):
+        """Test the compensation node running with the failed node's error, keeping the failure."""
+        result = execute(pipeline("1 / 0"))
+
+        assert result.is_partial(), result.state()
+        assert result.failed_nodes() == ["Write"]
+        assert result.get_node_output("Cleanup") == "deleted after Write"
+        assert result.get_node_output("Publish") is None
+
+    def test_compensation_runs_under_fail_fast(self):
+        """Test fail-fast running the compensation node before failing the workflow."""
+        result = execute(pipeline("1 / 0"), fail_fast=True)
+
+        assert result.is_failed(), result.state()
+        assert result.get_node_output("Cleanup") == "deleted after Write"
+        assert result.get_node_output("Publish") is None
+
+    def test_unknown_trigger_raises(self):
+        """Test connect rejecting triggers other than "failure"."""
+        workflow = pipeline("input")
+
+        with pytest.raises(ValueError, match="Unknown edge trigger"):
+            workflow.connect("Fetch", "Publish", on="success")
+
+    def test_failure_edge_with_condition_raises(self):
+        """Test connect rejecting an on-failure edge that also has a condition."""
+        workflow = pipeline("input")
+
+        with pytest.raises(ValueError):
+            workflow.connect("Fetch", "Publish", condition="true", on="failure")
@@ -0,0 +1,91 @@
+"""Unit tests for serializing nodes that share a concurrency_key."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "9" * 48
+
+
+@pytest.fixture
+def barrier_server():
+    """Local server where every request waits up to 0.5s at a two-party barrier, recording whether it met another request."""
+    barrier = threading.Barrier(2, timeout=0.5)
+    observed = {"in_flight": 0, "peak": 0, "met": {}}
+    lock = threading.Lock()
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers.get("Content-Length") or 0))
+            with lock:
+                observed["in_flight"] += 1
+                observed["peak"] = max(observed["peak"], observed["in_flight"])
+            try:
+                barrier.wait()
+                met = True
+            except threading.BrokenBarrierError:
+                barrier.reset()
+                met = False
+            with lock:
+                observed["in_flight"] -= 1
+                observed["met"][self.path] = met
+            payload = json.dumps({"ok": True}).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", observed
+    server.shutdown()
+
+
+def run(url, first_key, second_key):
+    """Run two unconnected HTTP request nodes with the given concurrency keys, returning the executor."""
+    workflow = Workflow("sheet")
+    workflow.add_node(Node.http_request("first", f"{url}/first", method="POST", concurrency_key=first_key))
+    workflow.add_node(Node.http_request("second", f"{url}/second", method="POST", concurrency_key=second_key))
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+    result = executor.execute(workflow)
+    assert result.is_success(), result.state()
+    return executor
+
+
+class TestConcurrencyKey:
+    """Test Node(concurrency_key=...) serializing nodes on the same key only."""
+
+    def test_same_key_serializes_nodes(self, barrier_server):
+        """Test two parallel nodes on one key never being in flight together, with the key wait reported."""
+        url, observed = barrier_server
+
+        executor = run(url, "sheet:budget", "sheet:budget")
+
+        assert observed["peak"] == 1
+        assert observed["met"] == {"/first": False, "/second": False}
+        stats = executor.stats()
+        assert stats["keyed_lock_acquisitions"] == 2
+        assert stats["keyed_lock_wait_time_ms"] >= 400
+
+    def test_different_keys_run_in_parallel(self, barrier_server):
+        """Test nodes on different keys reaching the barrier together."""
+        url, observed = barrier_server
+
+        executor = run(url, "sheet:budget", "sheet:forecast")
+
+        assert observed["peak"] == 2
+        assert observed["met"] == {"/first": True, "/second": True}
+        assert executor.stats()["keyed_lock_acquisitions"] == 2
+
+    def test_invalid_key(self):
+        """Test an empty concurrency_key being rejected."""
+        with pytest.raises(ValueError, match="concurrency_key"):
+            Node.transform("write", "identity", concurrency_key=" ")
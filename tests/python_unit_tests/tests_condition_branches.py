@@ -0,0 +1,78 @@
+"""Unit tests for condition nodes routing along true/false edges."""
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "8" * 48
+
+
+def review(score, with_false=True):
+    """Build `Source -> IsHigh`, routing to `Publish -> Notify` when true and `Review` when false."""
+    workflow = Workflow("review")
+    workflow.add_node(Node.transform("Source", f"{{score: {score}}}"))
+    workflow.add_node(Node.condition("IsHigh", expression="score > 0.5"))
+    workflow.add_node(Node.transform("Publish", "'published'"))
+    workflow.add_node(Node.transform("Notify", "input + ' and notified'"))
+    workflow.add_node(Node.transform("Review", "'review'"))
+    workflow.connect("Source", "IsHigh")
+    workflow.connect("IsHigh", "Publish", branch="true")
+    workflow.connect("Publish", "Notify")
+    if with_false:
+        workflow.connect("IsHigh", "Review", branch="false")
+    return workflow
+
+
+def execute(workflow):
+    """Run a workflow of transform and condition nodes only."""
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+    return executor.execute(workflow)
+
+
+class TestConditionBranches:
+    """Test Workflow.connect with branch="true" and branch="false"."""
+
+    def test_true_branch(self):
+        """Test a true outcome running the true branch and skipping the false one."""
+        result = execute(review(0.9))
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Notify") == "published and notified"
+        assert result.get_node_output("Review") is None
+        assert result.get_abandoned_nodes() == {"Review": "skipped"}
+
+    def test_false_branch(self):
+        """Test a false outcome skipping the whole true subtree."""
+        result = execute(review(0.1))
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Review") == "review"
+        assert result.get_abandoned_nodes() == {"Publish": "skipped", "Notify": "skipped"}
+
+    def test_missing_branch(self):
+        """Test a missing false edge being reported and running nothing after the condition."""
+        workflow = review(0.1, with_false=False)
+
+        assert workflow.warnings() == [
+            "Condition node 'IsHigh' has no false edge; nothing after it runs when its expression is false"
+        ]
+        result = execute(workflow)
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Publish") is None
+
+    def test_duplicate_branch_is_invalid(self):
+        """Test validation rejecting two true edges."""
+        workflow = review(0.9, with_false=False)
+        workflow.connect("IsHigh", "Review", branch="true")
+
+        with pytest.raises(Exception, match="at most one is allowed"):
+            workflow.validate()
+
+    def test_invalid_branch_arguments(self):
+        """Test connect rejecting unknown labels and branches combined with other edge kinds."""
+        workflow = review(0.9)
+
+        with pytest.raises(ValueError, match="Unknown branch"):
+            workflow.connect("IsHigh", "Review", branch="maybe")
+        with pytest.raises(ValueError, match="cannot have a condition"):
+            workflow.connect("IsHigh", "Review", condition="true", branch="true")
@@ -0,0 +1,106 @@
+"""Unit tests for per-node context-window management."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+DOCUMENT = "DOCUMENT START " + "lorem ipsum " * 2000 + "DOCUMENT END"
+
+
+@pytest.fixture
+def chat_server():
+    """Local OpenAI-compatible server recording request bodies."""
+    bodies = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            bodies.append(body)
+            reply = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "summary"}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(reply)))
+            self.end_headers()
+            self.wfile.write(reply)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    config = LlmConfig.openai("sk-" + "0" * 48, "gpt-4o-mini", base_url=f"http://127.0.0.1:{server.server_port}/v1")
+    yield config, bodies
+    server.shutdown()
+
+
+def run_summary(config, **options):
+    """Run a single agent node summarizing the oversized document."""
+    workflow = Workflow("summarize")
+    workflow.add_node(Node.agent(name="summarize", prompt=f"Summarize: {DOCUMENT}", **options))
+    return Executor(config).execute(workflow)
+
+
+class TestContextWindow:
+    """Test context strategies applied to oversized agent prompts."""
+
+    def test_truncate_documents(self, chat_server):
+        """Test that the middle of an oversized prompt is cut before it reaches the provider."""
+        config, bodies = chat_server
+        result = run_summary(config, context_strategy="truncate_documents", context_window=1000, max_tokens=200)
+
+        assert result.is_success(), result.state()
+        sent = bodies[0]["messages"][-1]["content"]
+        assert len(sent) < len(DOCUMENT)
+        assert sent.startswith("Summarize: DOCUMENT START")
+        assert sent.endswith("DOCUMENT END")
+        assert "tokens truncated ...]" in sent
+
+        fit = result.get_node_response_metadata("summarize")["context_window"]
+        assert fit["strategy"] == "truncate_documents"
+        assert fit["context_window"] == 1000
+        assert fit["reserved_tokens"] == 200
+        assert fit["truncated"] is True
+        assert fit["final_tokens"] <= 800 < fit["estimated_tokens"]
+
+    def test_fail_strategy(self, chat_server):
+        """Test that the fail strategy stops the node before calling the provider."""
+        config, bodies = chat_server
+        result = run_summary(config, context_strategy="fail", context_window=1000)
+
+        assert bodies == []
+        assert result.get_node_output("summarize") is None
+        node = result.to_report_dict()["nodes"][0]
+        assert node["status"] == "failed"
+        assert "but only 1000 fit (1000 token context window, 0 reserved for the completion)" in node["error"]
+        assert "tokens over with strategy fail" in node["error"]
+
+    def test_prompts_that_fit_are_untouched(self, chat_server):
+        """Test that requests within the window are sent unchanged."""
+        config, bodies = chat_server
+        workflow = Workflow("short")
+        workflow.add_node(Node.agent(name="short", prompt="Say hi", context_strategy="fail"))
+        result = Executor(config).execute(workflow)
+
+        assert result.is_success(), result.state()
+        assert bodies[0]["messages"][-1]["content"] == "Say hi"
+        fit = result.get_node_response_metadata("short")["context_window"]
+        assert fit["truncated"] is False
+        assert fit["context_window"] == 128000
+
+    def test_invalid_configuration(self):
+        """Test that unknown strategies and empty windows are rejected."""
+        with pytest.raises(ValueError, match="Unknown context strategy"):
+            Node.agent(name="a", prompt="p", context_strategy="drop_everything")
+        with pytest.raises(ValueError, match="context_window"):
+            Node.agent(name="a", prompt="p", context_strategy="fail", context_window=0)
@@ -1,8 +1,13 @@
 """Unit tests for core GraphBit functions."""
 
+import json
+import subprocess
+import sys
+import textwrap
+
 import pytest
 
-from graphbit import configure_runtime, get_system_info, health_check, shutdown, version
+from graphbit import configure_runtime, get_system_info, health_check, init, set_log_level, shutdown, version
 
 
 class TestCoreInitialization:
@@ -42,9 +47,152 @@ class TestCoreInitialization:
             configure_runtime(thread_stack_size_mb=-5)
 
     def test_configure_runtime_valid_params(self):
-        """Test runtime configuration with valid parameters."""
-        # This should not raise an exception
-        configure_runtime(worker_threads=2, max_blocking_threads=4, thread_stack_size_mb=2)
+        """Test runtime configuration with valid parameters, before the runtime starts."""
+        result = _run_python(
+            """
+            from graphbit import configure_runtime, runtime_info
+            configure_runtime(worker_threads=2, max_blocking_threads=4, thread_stack_size_mb=2)
+            info = runtime_info()
+            assert (info["worker_threads"], info["blocking_threads"], info["thread_stack_size"]) == (2, 4, 2 * 1024 * 1024)
+            """
+        )
+        assert result.returncode == 0, result.stderr
+
+
+def _run_python(code):
+    """Run ``code`` in a fresh interpreter, since the tracing subscriber is process-wide."""
+    return subprocess.run([sys.executable, "-c", textwrap.dedent(code)], capture_output=True, text=True, timeout=120)
+
+
+class TestLogging:
+    """Test logging configuration through init() and set_log_level()."""
+
+    def test_invalid_options(self):
+        """Test that unknown levels and formats raise ValueError."""
+        with pytest.raises(ValueError, match="log level"):
+            init(log_level="loud")
+        with pytest.raises(ValueError, match="log format"):
+            init(log_format="xml")
+        with pytest.raises(ValueError, match="log level"):
+            set_log_level("loud")
+
+    def test_logs_only_when_enabled(self):
+        """Test that log lines reach stderr only with tracing enabled."""
+        disabled = _run_python(
+            """
+            import graphbit
+            graphbit.init(log_level="info")
+            graphbit.health_check()
+            """
+        )
+        assert disabled.returncode == 0, disabled.stderr
+        assert "Health check" not in disabled.stderr
+
+        enabled = _run_python(
+            """
+            import graphbit
+            graphbit.init(enable_tracing=True, log_level="info")
+            graphbit.health_check()
+            """
+        )
+        assert enabled.returncode == 0, enabled.stderr
+        assert "tracing initialized with level: info" in enabled.stderr
+        assert "Health check" in enabled.stderr
+
+    def test_json_log_file(self, tmp_path):
+        """Test JSON formatted logs written to a file instead of stderr."""
+        log_file = tmp_path / "graphbit.log"
+        result = _run_python(
+            f"""
+            import graphbit
+            graphbit.init(enable_tracing=True, log_level="info", log_format="json", log_file={str(log_file)!r})
+            graphbit.init(enable_tracing=True, log_level="info")
+            graphbit.health_check()
+            """
+        )
+        assert result.returncode == 0, result.stderr
+        assert "Health check" not in result.stderr
+
+        records = [json.loads(line) for line in log_file.read_text().splitlines()]
+        assert any("Health check" in record["fields"]["message"] for record in records)
+        assert all(record["level"] in ("INFO", "WARN") for record in records)
+
+    def test_set_log_level(self):
+        """Test changing the level at runtime."""
+        result = _run_python(
+            """
+            import graphbit
+            try:
+                graphbit.set_log_level("info")
+            except RuntimeError:
+                print("not enabled")
+            graphbit.init(enable_tracing=True, log_level="error")
+            graphbit.health_check()
+            graphbit.set_log_level("info")
+            graphbit.health_check()
+            """
+        )
+        assert result.returncode == 0, result.stderr
+        assert result.stdout.strip() == "not enabled"
+        assert result.stderr.count("Health check") == 1
+
+
+class TestHttpSettings:
+    """Test proxy and CA bundle configuration through init()."""
+
+    def test_invalid_ca_bundle(self, tmp_path):
+        """Test that unreadable or empty CA bundles are rejected up front."""
+        from graphbit.errors import ConfigurationError
+
+        with pytest.raises(ConfigurationError, match="Failed to read CA bundle"):
+            init(ca_bundle=tmp_path / "missing.pem")
+        empty = tmp_path / "empty.pem"
+        empty.write_text("not a certificate")
+        with pytest.raises(ConfigurationError, match="no PEM certificates"):
+            init(ca_bundle=str(empty))
+
+    def test_llm_requests_use_proxy(self):
+        """Test that LLM calls go through the proxy passed to init()."""
+        result = _run_python(
+            """
+            import json
+            import threading
+            from http.server import BaseHTTPRequestHandler, HTTPServer
+
+            import graphbit
+
+            seen = []
+
+            class Proxy(BaseHTTPRequestHandler):
+                def do_POST(self):
+                    self.rfile.read(int(self.headers["Content-Length"]))
+                    seen.append(self.path)
+                    body = json.dumps({
+                        "id": "chatcmpl-1",
+                        "object": "chat.completion",
+                        "choices": [{"index": 0, "message": {"role": "assistant", "content": "pong"}, "finish_reason": "stop"}],
+                        "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                    }).encode()
+                    self.send_response(200)
+                    self.send_header("Content-Type", "application/json")
+                    self.send_header("Content-Length", str(len(body)))
+                    self.end_headers()
+                    self.wfile.write(body)
+
+                def log_message(self, *args):
+                    pass
+
+            server = HTTPServer(("127.0.0.1", 0), Proxy)
+            threading.Thread(target=server.serve_forever, daemon=True).start()
+
+            graphbit.init(proxy=f"http://127.0.0.1:{server.server_port}")
+            config = graphbit.LlmConfig.openai("sk-" + "0" * 48, "gpt-4o-mini", base_url="http://api.openai.invalid/v1")
+            print(graphbit.LlmClient(config).complete("ping"))
+            print(seen)
+            """
+        )
+        assert result.returncode == 0, result.stderr
+        assert result.stdout.splitlines() == ["pong", "['http://api.openai.invalid/v1/chat/completions']"]
 
 
 class TestModuleAttributes:
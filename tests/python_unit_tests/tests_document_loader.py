@@ -1,11 +1,69 @@
 """Unit tests for document loading functionality."""
 
+import os
 import tempfile
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
 
 import pytest
 
 from graphbit import DocumentLoader, DocumentLoaderConfig
 
+SAMPLE_PDF = os.path.join(os.path.dirname(__file__), "fixtures", "sample.pdf")
+
+
+class _FlakyHandler(BaseHTTPRequestHandler):
+    """Answer 429 to the first two requests of each path, 503 to /down.txt."""
+
+    hits: dict = {}
+
+    def do_GET(self):  # noqa: N802
+        hits = self.hits[self.path] = self.hits.get(self.path, 0) + 1
+        if self.path == "/down.txt":
+            self.send_response(503)
+        elif hits <= 2:
+            self.send_response(429)
+            self.send_header("Retry-After", "0")
+        else:
+            body = f"body of {self.path}".encode()
+            self.send_response(200)
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+            return
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+    def log_message(self, *args):
+        pass
+
+
+class _DroppingHandler(BaseHTTPRequestHandler):
+    """Serve BODY with range support, cutting the first answer off halfway."""
+
+    BODY = b"".join(b"line %05d\n" % i for i in range(60_000))
+    ranges: list = []
+
+    def do_GET(self):  # noqa: N802
+        self.ranges.append(self.headers.get("Range"))
+        start = int(self.headers["Range"][len("bytes=") : -1]) if self.headers.get("Range") else 0
+        part = self.BODY[start:]
+        if start:
+            self.send_response(206)
+            self.send_header("Content-Range", f"bytes {start}-{len(self.BODY) - 1}/{len(self.BODY)}")
+        else:
+            self.send_response(200)
+        self.send_header("Accept-Ranges", "bytes")
+        self.send_header("ETag", '"v1"')
+        self.send_header("Content-Length", str(len(part)))
+        self.send_header("Connection", "close")
+        self.end_headers()
+        self.wfile.write(part if len(self.ranges) > 1 else part[: len(part) // 2])
+        self.close_connection = True
+
+    def log_message(self, *args):
+        pass
+
 
 class TestDocumentLoaderConfig:
     """Test document loader configuration."""
@@ -33,6 +91,64 @@ class TestDocumentLoaderConfig:
         with pytest.raises(ValueError):
             DocumentLoaderConfig(default_encoding="")
 
+    def test_document_loader_config_cache_dir(self):
+        """Test that the cache directory defaults to disabled and can be set."""
+        config = DocumentLoaderConfig()
+        assert config.cache_dir is None
+        config.cache_dir = "/tmp/graphbit-cache"
+        assert str(config.cache_dir) == "/tmp/graphbit-cache"
+        config.cache_dir = None
+        assert config.cache_dir is None
+
+    def test_document_loader_config_fetch_limits(self):
+        """Test the URL fetching limits and their validation."""
+        config = DocumentLoaderConfig(max_requests_per_host=2, requests_per_second=5.0, batch_timeout_ms=1000)
+        assert config.max_requests_per_host == 2
+        assert config.requests_per_second == 5.0
+        assert config.max_retries == 3
+        assert config.retry_backoff_ms == 500
+        assert config.batch_timeout_ms == 1000
+
+        with pytest.raises(ValueError):
+            DocumentLoaderConfig(max_requests_per_host=0)
+        with pytest.raises(ValueError):
+            config.requests_per_second = -1.0
+
+    def test_document_loader_config_download_settings(self):
+        """Test the URL download size, retries and progress callback."""
+        config = DocumentLoaderConfig()
+        assert config.max_download_size is None
+        assert config.max_download_retries == 3
+        assert config.download_progress is None
+
+        def progress(url, downloaded, total):
+            pass
+
+        config = DocumentLoaderConfig(max_download_size=1024, max_download_retries=5, download_progress=progress)
+        assert config.max_download_size == 1024
+        assert config.max_download_retries == 5
+        assert config.download_progress is progress
+        config.download_progress = None
+        assert config.download_progress is None
+
+        with pytest.raises(ValueError):
+            DocumentLoaderConfig(max_download_size=0)
+
+    def test_document_loader_config_parse_limits(self):
+        """Test the concurrent parse limits and their validation."""
+        config = DocumentLoaderConfig()
+        assert config.max_parallel_parses is None
+        assert config.max_parse_bytes == 64 * 1024 * 1024
+
+        config = DocumentLoaderConfig(max_parallel_parses=2, max_parse_bytes=1024)
+        assert config.max_parallel_parses == 2
+        assert config.max_parse_bytes == 1024
+
+        with pytest.raises(ValueError):
+            DocumentLoaderConfig(max_parallel_parses=0)
+        with pytest.raises(ValueError):
+            config.max_parse_bytes = 0
+
 
 class TestDocumentContent:
     """Test document content functionality."""
@@ -130,6 +246,113 @@ class TestDocumentLoader:
             assert isinstance(content.metadata, dict)
             assert "file_size" in content.metadata
 
+    def test_load_pdf_fixture(self):
+        """Test loading a PDF with the type detected from its extension."""
+        loader = DocumentLoader()
+        content = loader.load_document(SAMPLE_PDF)
+        assert content.document_type == "pdf"
+        assert "GraphBit sample document" in content.content
+
+    @pytest.mark.asyncio
+    async def test_load_pdf_fixture_async(self):
+        """Test loading a PDF asynchronously."""
+        loader = DocumentLoader()
+        content = await loader.load_document_async(SAMPLE_PDF, "pdf")
+        assert content.content == loader.load_document(SAMPLE_PDF, "pdf").content
+
+    def test_load_directory(self):
+        """Test loading every supported file in a directory."""
+        with tempfile.TemporaryDirectory() as directory:
+            for name, text in [("b.txt", "second"), ("a.txt", "first"), ("notes.unknown", "skipped")]:
+                with open(os.path.join(directory, name), "w") as f:
+                    f.write(text)
+            os.mkdir(os.path.join(directory, "nested"))
+            with open(os.path.join(directory, "nested", "c.txt"), "w") as f:
+                f.write("third")
+
+            loader = DocumentLoader()
+            assert [d.content for d in loader.load_directory(directory)] == ["first", "second"]
+            assert len(loader.load_directory(directory, recursive=True)) == 3
+
+    def test_load_directory_stats(self):
+        """Test the per-file parse durations reported with return_stats."""
+        with tempfile.TemporaryDirectory() as directory:
+            for name, text in [("small.txt", "tiny"), ("large.txt", "large " * 1000)]:
+                with open(os.path.join(directory, name), "w") as f:
+                    f.write(text)
+
+            loader = DocumentLoader(DocumentLoaderConfig(max_parallel_parses=1))
+            documents, stats = loader.load_directory(directory, return_stats=True)
+
+            assert [os.path.basename(d.source) for d in documents] == ["large.txt", "small.txt"]
+            assert [os.path.basename(f["source"]) for f in stats["files"]] == ["large.txt", "small.txt"]
+            assert stats["total_bytes"] == 6004
+            large, small = stats["files"]
+            assert large["parse_ms"] >= 0 and small["parse_ms"] >= 0
+            # Larger files are parsed first
+            assert large["started_ms"] <= small["started_ms"]
+            assert stats["elapsed_ms"] >= stats["total_parse_ms"]
+
+    def test_cache_serves_unchanged_file(self):
+        """Test that an unchanged file is served from the cache and a touched one is parsed again."""
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "doc.txt")
+            with open(path, "w") as f:
+                f.write("first")
+
+            loader = DocumentLoader(DocumentLoaderConfig(cache_dir=os.path.join(directory, "cache")))
+            assert loader.load_document(path, "txt").metadata["cache_hit"] is False
+            cached = loader.load_document(path, "txt")
+            assert cached.metadata["cache_hit"] is True
+            assert cached.content == "first"
+
+            with open(path, "w") as f:
+                f.write("again")
+            stat = os.stat(path)
+            os.utime(path, ns=(stat.st_atime_ns, stat.st_mtime_ns + 5_000_000_000))
+            reparsed = loader.load_document(path, "txt")
+            assert reparsed.metadata["cache_hit"] is False
+            assert reparsed.content == "again"
+
+    def test_load_batch_retries_and_reports_failures(self):
+        """Test that a batch retries throttled URLs and reports failing sources per entry."""
+        _FlakyHandler.hits = {}
+        server = ThreadingHTTPServer(("127.0.0.1", 0), _FlakyHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        base = f"http://127.0.0.1:{server.server_address[1]}"
+        try:
+            loader = DocumentLoader(DocumentLoaderConfig(max_requests_per_host=2, max_retries=2, retry_backoff_ms=1))
+            results = loader.load_batch([f"{base}/a.txt", f"{base}/down.txt", f"{base}/b.txt"])
+        finally:
+            server.shutdown()
+
+        assert [r["source"] for r in results] == [f"{base}/a.txt", f"{base}/down.txt", f"{base}/b.txt"]
+        assert results[0]["error"] is None
+        assert results[0]["document"].content == "body of /a.txt"
+        assert results[0]["document"].metadata["fetch_attempts"] == 3
+        assert results[1]["document"] is None
+        assert "503" in results[1]["error"]
+        assert results[2]["document"].content == "body of /b.txt"
+
+    def test_load_url_resumes_dropped_download(self):
+        """Test a download cut off halfway resuming with a range request, reporting progress."""
+        _DroppingHandler.ranges = []
+        server = ThreadingHTTPServer(("127.0.0.1", 0), _DroppingHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        url = f"http://127.0.0.1:{server.server_address[1]}/large.txt"
+        reports = []
+        try:
+            config = DocumentLoaderConfig(retry_backoff_ms=1, download_progress=lambda *report: reports.append(report))
+            document = DocumentLoader(config).load_document(url, "txt")
+        finally:
+            server.shutdown()
+
+        body = _DroppingHandler.BODY
+        assert document.content == body.decode()
+        assert document.metadata["download_retries"] == 1
+        assert _DroppingHandler.ranges == [None, f"bytes={len(body) // 2}-"]
+        assert reports[-1] == (url, len(body), len(body))
+
 
 class TestDocumentLoaderErrorHandling:
     """Test document loader error handling."""
@@ -161,3 +384,15 @@ class TestDocumentLoaderErrorHandling:
         loader = DocumentLoader()
         with pytest.raises(ValueError):
             loader.load_document("test.txt", "")
+
+    def test_load_undetectable_type(self):
+        """Test that a missing type is rejected when it cannot be detected."""
+        loader = DocumentLoader()
+        with pytest.raises(ValueError):
+            loader.load_document("test.unknown")
+
+    def test_load_missing_directory(self):
+        """Test loading a directory that does not exist."""
+        loader = DocumentLoader()
+        with pytest.raises(Exception):
+            loader.load_directory("/nonexistent/graphbit/directory")
@@ -0,0 +1,113 @@
+"""Unit tests for retries of failed requests in EmbeddingClient.embed_batch_parallel."""
+
+import copy
+import json
+import pickle
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import EmbeddingClient, EmbeddingConfig, RetryPolicy
+
+API_KEY = "sk-" + "7" * 48
+
+
+@pytest.fixture
+def flaky_embedder():
+    """Local OpenAI embedding server failing the first request of every third batch (`batch-2`, `batch-5`, ...) with a 500."""
+    received = []
+    lock = threading.Lock()
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            inputs = body["input"] if isinstance(body["input"], list) else [body["input"]]
+            with lock:
+                received.append(inputs[0])
+                attempt = received.count(inputs[0])
+            if int(inputs[0].split("-")[1]) % 3 == 2 and attempt == 1:
+                status, payload = 500, {"error": {"message": "upstream hiccup"}}
+            else:
+                data = [{"index": i, "embedding": [float(len(text)), 1.0]} for i, text in enumerate(inputs)]
+                status, payload = 200, {"data": data, "model": body["model"], "usage": {"prompt_tokens": 1, "total_tokens": 1}}
+            encoded = json.dumps(payload).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(encoded)))
+            self.end_headers()
+            self.wfile.write(encoded)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", received
+    server.shutdown()
+
+
+def batches(count):
+    return [[f"batch-{i}", f"batch-{i} second text"] for i in range(count)]
+
+
+class TestEmbeddingRetry:
+    """Test EmbeddingConfig.retry applied by embed_batch_parallel."""
+
+    def test_transient_failures_are_retried(self, flaky_embedder):
+        """Test a batch with a failing first attempt on every third request fully succeeding, with the attempts counted."""
+        url, received = flaky_embedder
+        config = EmbeddingConfig.openai(API_KEY, base_url=url)
+        config.retry = RetryPolicy(max_attempts=3, backoff_ms=10, jitter=0.0)
+
+        result = EmbeddingClient(config).embed_batch_parallel(batches(9), max_concurrency=3)
+
+        assert result["errors"] == []
+        assert [len(embeddings) for embeddings in result["embeddings"]] == [2] * 9
+        stats = result["stats"]
+        assert stats["successful_requests"] == 9
+        assert stats["failed_requests"] == 0
+        assert stats["total_attempts"] == 12
+        assert stats["retried_requests"] == 3
+        assert len(received) == 12
+
+    def test_single_attempt_policy_keeps_failures(self, flaky_embedder):
+        """Test RetryPolicy(max_attempts=1) reporting the failed requests instead of retrying them."""
+        url, received = flaky_embedder
+        config = EmbeddingConfig.openai(API_KEY, base_url=url)
+        config.retry = RetryPolicy(max_attempts=1)
+
+        result = EmbeddingClient(config).embed_batch_parallel(batches(6), max_concurrency=2)
+
+        assert len(result["errors"]) == 2
+        assert result["embeddings"][2] == []
+        assert result["stats"]["failed_requests"] == 2
+        assert result["stats"]["total_attempts"] == 6
+        assert result["stats"]["retried_requests"] == 0
+        assert len(received) == 6
+
+    def test_no_retries_without_policy(self, flaky_embedder):
+        """Test a config without a retry policy sending every request once."""
+        url, received = flaky_embedder
+        config = EmbeddingConfig.openai(API_KEY, base_url=url)
+
+        result = EmbeddingClient(config).embed_batch_parallel(batches(3), max_concurrency=3)
+
+        assert len(result["errors"]) == 1
+        assert result["stats"]["failed_requests"] == 1
+        assert result["stats"]["total_attempts"] == 3
+        assert result["stats"]["retried_requests"] == 0
+        assert len(received) == 3
+
+    def test_retry_property(self):
+        """Test EmbeddingConfig.retry defaulting to None and surviving pickle and deepcopy."""
+        config = EmbeddingConfig.openai(API_KEY)
+        assert config.retry is None
+
+        config.retry = RetryPolicy(max_attempts=4, backoff_ms=250)
+
+        for restored in (pickle.loads(pickle.dumps(config)), copy.deepcopy(config)):
+            assert restored.retry.max_attempts == 4
+            assert restored.retry.backoff_ms == 250
+        config.retry = None
+        assert config.retry is None
@@ -133,3 +133,42 @@ class TestEmbeddingClientErrorHandling:
 
         with pytest.raises((ValueError, TypeError)):
             client.embed_many([])
+
+
+class TestSimilarityFunctions:
+    """Test the similarity static methods against hand-computed values."""
+
+    CORPUS = [[0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [2.0, 0.0]]
+
+    def test_pairwise_scores(self):
+        """Test dot product, euclidean distance and cosine similarity."""
+        assert EmbeddingClient.dot_product([1.0, 2.0, 3.0], [4.0, 5.0, 6.0]) == pytest.approx(32.0)
+        assert EmbeddingClient.euclidean_distance([1.0, 2.0, 3.0], [4.0, 6.0, 3.0]) == pytest.approx(5.0)
+        assert EmbeddingClient.similarity([3.0, 4.0], [4.0, 3.0]) == pytest.approx(0.96)
+
+    def test_top_k(self):
+        """Test that top_k ranks by each metric, keeping corpus order for ties."""
+        assert [index for index, _ in EmbeddingClient.top_k([1.0, 0.0], self.CORPUS, 3)] == [1, 3, 2]
+        assert EmbeddingClient.top_k([1.0, 0.0], self.CORPUS, 2, metric="dot_product") == [(3, 2.0), (1, 1.0)]
+        assert EmbeddingClient.top_k([1.0, 0.0], self.CORPUS, 2, metric="euclidean") == [(1, 0.0), (2, 1.0)]
+        with pytest.raises(ValueError, match="Unknown similarity metric"):
+            EmbeddingClient.top_k([1.0, 0.0], self.CORPUS, 2, metric="manhattan")
+
+    def test_mmr(self):
+        """Test that MMR skips a duplicate in favour of a diverse candidate."""
+        candidates = [[1.0, 0.0], [1.0, 0.0], [0.0, 1.0]]
+        assert EmbeddingClient.mmr([4.0, 3.0], candidates, lambda_mult=0.5, k=3) == [0, 2, 1]
+        assert EmbeddingClient.mmr([4.0, 3.0], candidates, lambda_mult=1.0, k=3) == [0, 1, 2]
+        with pytest.raises(Exception, match="lambda"):
+            EmbeddingClient.mmr([4.0, 3.0], candidates, lambda_mult=1.5)
+
+    def test_dimension_mismatch(self):
+        """Test that embeddings of different dimensions are rejected."""
+        for call in (
+            lambda: EmbeddingClient.dot_product([1.0, 2.0, 3.0], [1.0, 2.0]),
+            lambda: EmbeddingClient.euclidean_distance([1.0, 2.0, 3.0], [1.0, 2.0]),
+            lambda: EmbeddingClient.top_k([1.0, 2.0, 3.0], [[1.0, 2.0]], 1),
+            lambda: EmbeddingClient.mmr([1.0, 2.0, 3.0], [[1.0, 2.0]]),
+        ):
+            with pytest.raises(Exception, match="dimensions must match"):
+                call()
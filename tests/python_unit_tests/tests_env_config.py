@@ -0,0 +1,130 @@
+"""Unit tests for configuration read from GRAPHBIT_* environment variables."""
+
+import contextlib
+import io
+import os
+
+import pytest
+
+from graphbit import EmbeddingConfig, Executor, LlmConfig, Node, Workflow
+from graphbit.cli import main
+
+API_KEY = "sk-" + "3" * 48
+
+
+@pytest.fixture(autouse=True)
+def clean_env(monkeypatch):
+    """Start every test without GRAPHBIT_* variables."""
+    for name in list(os.environ):
+        if name.startswith("GRAPHBIT_"):
+            monkeypatch.delenv(name)
+
+
+def doctor():
+    """Run `graphbit doctor` and return its exit code and output."""
+    out = io.StringIO()
+    with contextlib.redirect_stdout(out), contextlib.redirect_stderr(out):
+        code = main(["doctor"])
+    return code, out.getvalue()
+
+
+class TestLlmConfigFromEnv:
+    """Test LlmConfig.from_env."""
+
+    def test_default_provider_and_model(self, monkeypatch):
+        """Test the provider and model being read from the environment."""
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_PROVIDER", "anthropic")
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_MODEL", "claude-sonnet-4-5")
+        monkeypatch.setenv("GRAPHBIT_ANTHROPIC_API_KEY", API_KEY)
+
+        config = LlmConfig.from_env()
+
+        assert config.provider() == "anthropic"
+        assert config.model() == "claude-sonnet-4-5"
+
+    def test_arguments_win_over_environment(self, monkeypatch):
+        """Test explicit arguments taking precedence over variables."""
+        monkeypatch.setenv("GRAPHBIT_OPENAI_API_KEY", API_KEY)
+        monkeypatch.setenv("GRAPHBIT_OPENAI_MODEL", "gpt-4o")
+
+        assert LlmConfig.from_env("openai").model() == "gpt-4o"
+        assert LlmConfig.from_env("openai", model="gpt-4o-mini").model() == "gpt-4o-mini"
+
+    def test_missing_variables_are_named(self, monkeypatch):
+        """Test the errors naming the variable to set."""
+        with pytest.raises(ValueError, match="GRAPHBIT_DEFAULT_PROVIDER is not set"):
+            LlmConfig.from_env()
+
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_MODEL", "gpt-4o")
+        with pytest.raises(ValueError, match="GRAPHBIT_OPENAI_API_KEY is not set"):
+            LlmConfig.from_env("openai")
+
+    def test_embedding_config(self, monkeypatch):
+        """Test EmbeddingConfig.from_env using the provider key."""
+        monkeypatch.setenv("GRAPHBIT_EMBEDDING_MODEL", "text-embedding-3-small")
+        with pytest.raises(ValueError, match="GRAPHBIT_EMBEDDING_API_KEY"):
+            EmbeddingConfig.from_env()
+
+        monkeypatch.setenv("GRAPHBIT_OPENAI_API_KEY", API_KEY)
+        assert EmbeddingConfig.from_env() is not None
+
+
+class TestExecutorFromEnv:
+    """Test Executor.from_env."""
+
+    def test_executor_defaults_and_overrides(self, monkeypatch):
+        """Test executor settings from variables, overridden by keyword arguments."""
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_PROVIDER", "openai")
+        monkeypatch.setenv("GRAPHBIT_OPENAI_API_KEY", API_KEY)
+        monkeypatch.setenv("GRAPHBIT_OPENAI_MODEL", "gpt-4o-mini")
+        monkeypatch.setenv("GRAPHBIT_OPENAI_BASE_URL", "http://127.0.0.1:9/v1")
+        monkeypatch.setenv("GRAPHBIT_FAIL_FAST", "true")
+        workflow = Workflow("env")
+        workflow.add_node(Node.transform("Broken", "1 / 0"))
+        workflow.add_node(Node.transform("Other", "'ok'"))
+
+        assert Executor.from_env().execute(workflow).is_failed()
+        result = Executor.from_env(fail_fast=False).execute(workflow)
+        assert not result.is_failed()
+        assert not result.is_success()
+
+    def test_invalid_variable(self, monkeypatch):
+        """Test an invalid executor variable being rejected."""
+        monkeypatch.setenv("GRAPHBIT_MAX_CONCURRENCY", "many")
+        config = LlmConfig.openai(API_KEY, "gpt-4o-mini")
+
+        with pytest.raises(ValueError, match="GRAPHBIT_MAX_CONCURRENCY must be a whole number, got 'many'"):
+            Executor.from_env(config)
+
+
+class TestDoctor:
+    """Test `graphbit doctor`."""
+
+    def test_reports_problems(self, monkeypatch):
+        """Test missing and invalid variables failing the check and unknown ones being listed."""
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_PROVIDER", "openai")
+        monkeypatch.setenv("GRAPHBIT_OPENAI_API_KEY", API_KEY)
+        monkeypatch.setenv("GRAPHBIT_MAX_CONCURRENCY", "0")
+        monkeypatch.setenv("GRAPHBIT_OPENAI_MODLE", "gpt-4o")
+
+        code, output = doctor()
+
+        assert code != 0
+        assert API_KEY not in output
+        lines = {line.split()[0]: line for line in output.splitlines() if line.startswith("GRAPHBIT_")}
+        assert "found" in lines["GRAPHBIT_OPENAI_API_KEY"]
+        assert "missing" in lines["GRAPHBIT_OPENAI_MODEL"]
+        assert "invalid" in lines["GRAPHBIT_MAX_CONCURRENCY"]
+        assert "unknown" in lines["GRAPHBIT_OPENAI_MODLE"]
+        assert "environment: 2 problem(s)" in output
+
+    def test_complete_environment(self, monkeypatch):
+        """Test a complete environment passing the check."""
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_PROVIDER", "openai")
+        monkeypatch.setenv("GRAPHBIT_DEFAULT_MODEL", "gpt-4o-mini")
+        monkeypatch.setenv("GRAPHBIT_OPENAI_API_KEY", API_KEY)
+
+        code, output = doctor()
+
+        assert code == 0, output
+        assert "environment: ok" in output
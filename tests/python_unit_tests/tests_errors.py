@@ -0,0 +1,89 @@
+"""Tests for the graphbit.errors exception hierarchy."""
+
+import http.server
+import threading
+
+import pytest
+
+from graphbit import DocumentLoader, LlmClient, LlmConfig, Node, errors
+
+
+class _RateLimitHandler(http.server.BaseHTTPRequestHandler):
+    """Answer every request with HTTP 429 and a Retry-After header."""
+
+    def do_POST(self):
+        self.rfile.read(int(self.headers.get("Content-Length", 0)))
+        body = b'{"error": {"message": "Rate limit reached"}}'
+        self.send_response(429)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Retry-After", "7")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, format, *args):
+        pass
+
+
+@pytest.fixture
+def rate_limited_server():
+    """Run a local server that rate-limits every request."""
+    server = http.server.HTTPServer(("127.0.0.1", 0), _RateLimitHandler)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{server.server_address[1]}"
+    server.shutdown()
+
+
+class TestErrorHierarchy:
+    """Test the exception classes and how binding errors map onto them."""
+
+    def test_hierarchy_keeps_builtin_bases(self):
+        """Test that every error is a GraphBitError and keeps its builtin base."""
+        assert issubclass(errors.GraphBitError, RuntimeError)
+        assert issubclass(errors.ValidationError, ValueError)
+        assert issubclass(errors.ConfigurationError, ValueError)
+        assert issubclass(errors.TimeoutError, TimeoutError)
+        assert issubclass(errors.RateLimitError, errors.ProviderError)
+        for name in errors.__all__:
+            assert issubclass(getattr(errors, name), errors.GraphBitError)
+
+    def test_content_blocked_error_carries_categories(self):
+        """Test that ContentBlockedError is a ProviderError carrying the flagged categories."""
+        error = errors.ContentBlockedError("response blocked (SAFETY)", "gemini", None, ["HARM_CATEGORY_DANGEROUS_CONTENT"])
+
+        assert isinstance(error, errors.ProviderError)
+        assert error.provider == "gemini"
+        assert error.categories == ["HARM_CATEGORY_DANGEROUS_CONTENT"]
+        assert errors.ContentBlockedError("blocked").categories == []
+
+    def test_rate_limit_response_raises_rate_limit_error(self, rate_limited_server):
+        """Test that an HTTP 429 from the provider raises RateLimitError."""
+        config = LlmConfig.openai("sk-test-key-1234567890", base_url=rate_limited_server)
+        client = LlmClient(config)
+
+        with pytest.raises(errors.RateLimitError) as exc_info:
+            client.complete("Hello")
+
+        assert exc_info.value.provider == "openai"
+        assert exc_info.value.status_code == 429
+        assert exc_info.value.retry_after == 7
+
+    def test_validation_failure_raises_validation_error(self):
+        """Test that invalid arguments raise ValidationError."""
+        with pytest.raises(errors.ValidationError):
+            Node.agent(name="", prompt="Summarize")
+
+        with pytest.raises(errors.ValidationError) as exc_info:
+            Node.http_request(name="fetch", url="")
+        assert "fetch" in str(exc_info.value)
+
+    def test_missing_file_raises_validation_error(self, tmp_path):
+        """Test that loading a missing file raises ValidationError naming the field."""
+        missing = tmp_path / "missing.txt"
+
+        with pytest.raises(errors.ValidationError) as exc_info:
+            DocumentLoader().load_document(str(missing), "txt")
+
+        assert exc_info.value.field is not None
+        assert str(missing) in str(exc_info.value)
@@ -0,0 +1,99 @@
+"""Unit tests for external event nodes suspending a workflow until an event is delivered."""
+
+import json
+import time
+
+import pytest
+
+from graphbit import ExecutionHandle, Executor, LlmConfig, Node, Workflow
+from graphbit.errors import ValidationError
+
+API_KEY = "sk-" + "5" * 48
+
+
+def build(event_timeout=None, on_timeout=False):
+    """Workflow waiting for a `payment` event before `Paid`, with a timeout edge to `Expired` if asked."""
+    workflow = Workflow("payment")
+    workflow.add_node(Node.transform("Source", "'order'"))
+    workflow.add_node(Node.external_event("wait", "payment", event_timeout=event_timeout))
+    workflow.add_node(Node.transform("Paid", "input"))
+    workflow.connect("Source", "wait")
+    workflow.connect("wait", "Paid")
+    if on_timeout:
+        workflow.add_node(Node.transform("Expired", "'expired'"))
+        workflow.connect("wait", "Expired", on_timeout=True)
+    return workflow
+
+
+def executor():
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+
+
+def until_waiting(handle, event_name):
+    for _ in range(200):
+        if handle.waiting_events() == [event_name]:
+            return
+        time.sleep(0.01)
+    raise AssertionError(f"execution never waited for {event_name!r}")
+
+
+class TestExternalEventNode:
+    """Test Node.external_event with Executor.execute_async."""
+
+    def test_suspends_until_the_event_is_delivered(self):
+        """Test the node waiting for its event, then outputting the delivered payload."""
+        handle = executor().execute_async(build())
+        assert isinstance(handle, ExecutionHandle)
+
+        until_waiting(handle, "payment")
+        assert not handle.is_done()
+        handle.deliver_event("payment", {"amount": 42, "currency": "EUR"})
+
+        result = handle.result(timeout_seconds=10)
+        assert result.is_success(), result.state()
+        assert json.loads(result.get_node_output("Paid")) == {"amount": 42, "currency": "EUR"}
+        assert handle.is_done()
+        assert handle.waiting_events() == []
+
+    def test_deliver_through_the_executor(self):
+        """Test delivering an event by execution id, and the errors once the execution has finished."""
+        ex = executor()
+        handle = ex.execute_async(build())
+        until_waiting(handle, "payment")
+
+        ex.deliver_event(handle.execution_id, "payment", "paid")
+
+        assert handle.result(timeout_seconds=10).get_node_output("Paid") == "paid"
+        with pytest.raises(ValidationError, match="No running execution"):
+            ex.deliver_event(handle.execution_id, "payment", "again")
+        with pytest.raises(ValidationError, match="No running execution"):
+            handle.deliver_event("payment", "again")
+
+    def test_timeout_follows_the_timeout_edge(self):
+        """Test a missing event routing the execution along the on_timeout edge."""
+        result = executor().execute_async(build(event_timeout=1, on_timeout=True)).result(timeout_seconds=10)
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Expired") == "expired"
+        assert result.get_node_output("Paid") is None
+
+    def test_result_wait_can_time_out(self):
+        """Test result giving up after timeout_seconds while the execution keeps running."""
+        handle = executor().execute_async(build())
+        until_waiting(handle, "payment")
+
+        with pytest.raises(TimeoutError):
+            handle.result(timeout_seconds=0.1)
+
+        handle.deliver_event("payment", "late")
+        assert handle.result(timeout_seconds=10).get_node_output("Paid") == "late"
+
+    def test_timeout_edge_validation(self):
+        """Test the edge kinds being exclusive and timeout edges needing an event timeout."""
+        workflow = build()
+        with pytest.raises(ValueError, match="timeout edge cannot have a condition"):
+            workflow.connect("wait", "Paid", condition="true", on_timeout=True)
+        with pytest.raises(ValueError, match="both a violation and a timeout edge"):
+            workflow.connect("wait", "Paid", on_violation=True, on_timeout=True)
+        with pytest.raises(Exception, match="has timeout edges but no timeout"):
+            build(on_timeout=True).validate()
@@ -0,0 +1,92 @@
+"""Unit tests for running workflows on a frozen clock with Executor._with_frozen_time()."""
+
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, RetryPolicy, Workflow
+
+API_KEY = "sk-" + "5" * 48
+
+
+@pytest.fixture
+def flaky_server():
+    """Local server answering 503 to the first two requests and 200 afterwards, counting requests."""
+    requests = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            requests.append(self.path)
+            status, payload = (503, b"unavailable") if len(requests) <= 2 else (200, b'{"ok": true}')
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}/data", requests
+    server.shutdown()
+
+
+def frozen_executor():
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o"))._with_frozen_time()
+
+
+def report_node(result, name):
+    return next(node for node in result.to_report_dict()["nodes"] if node["node_name"] == name)
+
+
+class TestFrozenTime:
+    """Test waits passing on a frozen clock instead of in real time."""
+
+    def test_returns_the_executor(self):
+        """Test _with_frozen_time returning the executor it configured."""
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o"))
+        assert executor._with_frozen_time() is executor
+
+    def test_delay_node_takes_no_real_time(self):
+        """Test an hour-long delay node finishing at once, with its duration on the frozen clock."""
+        workflow = Workflow("delayed")
+        workflow.add_node(Node.delay("wait", 3600))
+
+        started = time.monotonic()
+        result = frozen_executor().execute(workflow)
+
+        assert result.is_success(), result.state()
+        assert time.monotonic() - started < 5
+        assert report_node(result, "wait")["duration_ms"] == 3_600_000
+
+    def test_pacing_delays_take_no_real_time(self):
+        """Test pacing delays of several minutes passing at once."""
+        workflow = Workflow("paced")
+        workflow.add_node(Node.delay("pause", 1, pre_execution_delay_ms=300_000, post_execution_delay_ms=300_000))
+
+        started = time.monotonic()
+        result = frozen_executor().execute(workflow)
+
+        assert result.is_success(), result.state()
+        assert time.monotonic() - started < 5
+        assert report_node(result, "pause")["pacing"]["pre_execution_ms"] == 300_000
+
+    def test_retry_backoff_takes_no_real_time(self, flaky_server):
+        """Test retries with long backoffs finishing at once while still retrying."""
+        url, requests = flaky_server
+        workflow = Workflow("flaky")
+        workflow.add_node(Node.http_request("fetch", url, retry=RetryPolicy(max_attempts=4, backoff_ms=20_000, jitter=0.0)))
+
+        started = time.monotonic()
+        result = frozen_executor().execute(workflow)
+
+        assert result.is_success(), result.state()
+        assert time.monotonic() - started < 5
+        assert len(requests) == 3
+        report = report_node(result, "fetch")
+        assert report["attempts"] == 3
+        assert report["duration_ms"] >= 40_000
@@ -0,0 +1,65 @@
+"""Unit tests for finding workflow nodes by type and tag and walking their dependencies."""
+
+import pytest
+
+from graphbit import Node, Workflow
+
+
+def build():
+    """Workflow `loader -> clean -> {summarize, extract} -> report`, plus an unconnected `audit`."""
+    workflow = Workflow("graph_query")
+    workflow.add_node(Node.transform("loader", "'document'", tags=["io"]))
+    workflow.add_node(Node.transform("clean", "input", tags=[]))
+    workflow.add_node(Node.agent("summarize", "Summarize {{input}}", agent_id="summarizer", tags=["expensive"]))
+    workflow.add_node(Node.agent("extract", "Extract entities from {{input}}", agent_id="extractor", tags=["cheap"]))
+    workflow.add_node(Node.agent("report", "Report on {{input}}", agent_id="reporter", tags=["expensive"]))
+    workflow.add_node(Node.transform("audit", "'ok'", tags=["io"]))
+    workflow.connect("loader", "clean")
+    workflow.connect("clean", "summarize")
+    workflow.connect("clean", "extract")
+    workflow.connect("summarize", "report")
+    workflow.connect("extract", "report")
+    return workflow
+
+
+class TestGraphQuery:
+    """Test find_nodes, ancestors and descendants."""
+
+    def test_find_nodes_by_type_and_tag(self):
+        """Test both filters having to match."""
+        workflow = build()
+
+        assert workflow.find_nodes(type="agent") == ["extract", "report", "summarize"]
+        assert workflow.find_nodes(tag="io") == ["audit", "loader"]
+        assert workflow.find_nodes(type="agent", tag="expensive") == ["report", "summarize"]
+        assert workflow.find_nodes(type="transform", tag="expensive") == []
+        assert len(workflow.find_nodes()) == 6
+
+    def test_find_nodes_rejects_unknown_type(self):
+        """Test an unknown node type raising ValueError."""
+        with pytest.raises(ValueError, match="Unknown node type 'robot'"):
+            build().find_nodes(type="robot")
+
+    def test_descendants_and_ancestors(self):
+        """Test the transitive dependents and dependencies of nodes."""
+        workflow = build()
+
+        assert workflow.descendants("loader") == ["clean", "extract", "report", "summarize"]
+        assert workflow.descendants("report") == []
+        assert workflow.ancestors("report") == ["clean", "extract", "loader", "summarize"]
+        assert workflow.ancestors("audit") == []
+
+    def test_descendants_follow_new_edges(self):
+        """Test the cached answers being refreshed when the graph changes."""
+        workflow = build()
+        assert workflow.descendants("report") == []
+
+        workflow.connect("report", "audit")
+
+        assert workflow.descendants("report") == ["audit"]
+        assert "loader" in workflow.ancestors("audit")
+
+    def test_unknown_node_raises(self):
+        """Test asking about a node not in the workflow."""
+        with pytest.raises(ValueError, match="not found"):
+            build().descendants("missing")
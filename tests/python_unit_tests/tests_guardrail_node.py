@@ -0,0 +1,130 @@
+"""Unit tests for guardrail nodes checking content for PII and custom patterns."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "3" * 48
+
+PII_TEXT = "Mail jane@example.com or call 555-123-4567, card 4111 1111 1111 1111"
+
+
+@pytest.fixture
+def classifier_server():
+    """Local server answering every request with an OpenAI chat completion holding a violation verdict."""
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers["Content-Length"]))
+            verdict = json.dumps({"violation": True, "reason": "Threatens a person"})
+            payload = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": verdict}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}"
+    server.shutdown()
+
+
+def build(guard, review=False):
+    """Workflow passing the `text` input through `guard` to `Publish`, and to `Review` along a violation edge if asked."""
+    workflow = Workflow("guardrail")
+    workflow.add_node(Node.transform("Source", "vars.text"))
+    workflow.add_node(guard)
+    workflow.add_node(Node.transform("Publish", "input"))
+    workflow.connect("Source", guard.name())
+    workflow.connect(guard.name(), "Publish")
+    if review:
+        workflow.add_node(Node.transform("Review", "input"))
+        workflow.connect(guard.name(), "Review", on_violation=True)
+    return workflow
+
+
+def run(workflow, text, url="http://127.0.0.1:9"):
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=f"{url}/v1"), fail_fast=True)
+    return executor.execute(workflow, inputs={"text": text})
+
+
+class TestGuardrailNode:
+    """Test Node.guardrail with the redact, block and route actions."""
+
+    def test_redact_masks_pii(self):
+        """Test matches being masked and the masked text reaching the next node."""
+        result = run(build(Node.guardrail("guard", checks=["pii"])), PII_TEXT)
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Publish") == "Mail [REDACTED_EMAIL] or call [REDACTED_PHONE], card [REDACTED_CREDIT_CARD]"
+        report = result.get_guardrail_report("guard")
+        assert report["action"] == "redact"
+        assert report["redactions"] == 3
+        assert [v["check"] for v in report["violations"]] == ["email", "credit_card", "phone"]
+
+    def test_custom_checks(self):
+        """Test regex and deny list checks."""
+        guard = Node.guardrail("guard", checks=[{"regex": r"ORD-\d+"}, {"deny_list": ["secret project"]}])
+        result = run(build(guard), "ORD-12 mentions the Secret Project")
+
+        assert result.get_node_output("Publish") == "[REDACTED_REGEX] mentions the [REDACTED_DENY_LIST]"
+        assert result.get_guardrail_report("guard")["violations"] == [{"check": "regex", "matches": 1}, {"check": "deny_list", "matches": 1}]
+
+    def test_block_fails_node(self):
+        """Test a violation failing the node when the action is block, and clean input passing."""
+        result = run(build(Node.guardrail("guard", checks=["email"], on_violation="block")), "Write to jane@example.com")
+
+        assert result.is_failed()
+        assert "blocked its input: email (1)" in result.state()
+        assert result.get_guardrail_report("guard")["violations"] == [{"check": "email", "matches": 1}]
+
+        result = run(build(Node.guardrail("guard", checks=["email"], on_violation="block")), "Nothing to see")
+        assert result.is_success(), result.state()
+        assert result.get_guardrail_report("guard")["violations"] == []
+
+    def test_route_follows_violation_edge(self):
+        """Test a violation sending the unchanged input along the violation edge only."""
+        guard = Node.guardrail("guard", checks=["pii"], on_violation="route")
+        result = run(build(guard, review=True), PII_TEXT)
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Review") == PII_TEXT
+        assert result.get_node_output("Publish") is None
+
+        result = run(build(Node.guardrail("guard", checks=["pii"], on_violation="route"), review=True), "Nothing to see")
+        assert result.get_node_output("Publish") == "Nothing to see"
+        assert result.get_node_output("Review") is None
+
+    def test_classifier_redacts_whole_text(self, classifier_server):
+        """Test a classifier violation replacing the text, with the classifier's reason in the report."""
+        guard = Node.guardrail("guard", checks=[{"classifier": "No threats of violence"}])
+        result = run(build(guard), "I will hurt you", url=classifier_server)
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Publish") == "[REDACTED_CLASSIFIER]"
+        assert result.get_guardrail_report("guard")["violations"] == [{"check": "classifier", "matches": 1, "reason": "Threatens a person"}]
+
+    def test_invalid_configuration(self):
+        """Test unknown checks and actions, and route without a violation edge, being rejected."""
+        with pytest.raises(ValueError, match="Unknown guardrail check"):
+            Node.guardrail("guard", checks=["ssn"])
+        with pytest.raises(ValueError, match="Unknown guardrail action"):
+            Node.guardrail("guard", checks=["pii"], on_violation="drop")
+        with pytest.raises(ValueError, match="invalid regex check"):
+            Node.guardrail("guard", checks=[{"regex": "(unclosed"}])
+        with pytest.raises(RuntimeError, match="no violation edge"):
+            build(Node.guardrail("guard", checks=["pii"], on_violation="route")).validate()
@@ -0,0 +1,178 @@
+"""Unit tests for provider health checks and executor dry runs."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import EmbeddingClient, EmbeddingConfig, Executor, LlmClient, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "0" * 48
+BAD_API_KEY = "sk-" + "1" * 48
+
+
+@pytest.fixture
+def base_url():
+    """Local OpenAI-compatible server accepting only API_KEY and serving only gpt-4o-mini and text-embedding-3-small."""
+
+    class Handler(BaseHTTPRequestHandler):
+        def reply(self, status, body):
+            payload = json.dumps(body).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def authorized(self):
+            if self.headers.get("Authorization") == f"Bearer {API_KEY}":
+                return True
+            self.reply(401, {"error": {"message": "Incorrect API key provided", "code": "invalid_api_key"}})
+            return False
+
+        def model_not_found(self):
+            self.reply(404, {"error": {"message": "The model does not exist", "code": "model_not_found"}})
+
+        def do_GET(self):
+            if not self.authorized():
+                return
+            if self.path.endswith("/models/gpt-4o-mini"):
+                self.reply(200, {"id": "gpt-4o-mini", "object": "model", "owned_by": "openai"})
+            else:
+                self.model_not_found()
+
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            if not self.authorized():
+                return
+            if body.get("model") != "text-embedding-3-small":
+                self.model_not_found()
+                return
+            self.reply(
+                200,
+                {
+                    "object": "list",
+                    "data": [{"object": "embedding", "index": 0, "embedding": [0.1, 0.2]}],
+                    "model": "text-embedding-3-small",
+                    "usage": {"prompt_tokens": 1, "total_tokens": 1},
+                },
+            )
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}/v1"
+    server.shutdown()
+
+
+class TestLlmHealthCheck:
+    """Test LlmClient.health_check against mocked responses."""
+
+    def test_healthy(self, base_url):
+        """Test that a reachable provider serving the model is healthy."""
+        health = LlmClient(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url)).health_check()
+        assert health["provider"] == "openai"
+        assert health["model"] == "gpt-4o-mini"
+        assert health["reachable"] and health["authenticated"] and health["model_available"]
+        assert health["error"] is None
+        assert health["latency_ms"] >= 0
+
+    def test_unauthorized(self, base_url):
+        """Test that a rejected API key is reported as unauthenticated."""
+        health = LlmClient(LlmConfig.openai(BAD_API_KEY, "gpt-4o-mini", base_url=base_url)).health_check()
+        assert health["reachable"]
+        assert not health["authenticated"]
+        assert "401" in health["error"]
+
+    def test_missing_model(self, base_url):
+        """Test that an unknown model is reported as unavailable."""
+        health = LlmClient(LlmConfig.openai(API_KEY, "gpt-unknown", base_url=base_url)).health_check()
+        assert health["reachable"] and health["authenticated"]
+        assert not health["model_available"]
+
+
+class TestEmbeddingHealthCheck:
+    """Test EmbeddingClient.health_check against mocked responses."""
+
+    def test_healthy(self, base_url):
+        """Test that a reachable provider serving the model is healthy."""
+        health = EmbeddingClient(EmbeddingConfig.openai(API_KEY, base_url=base_url)).health_check()
+        assert health["reachable"] and health["authenticated"] and health["model_available"]
+
+    def test_unauthorized(self, base_url):
+        """Test that a rejected API key is reported as unauthenticated."""
+        health = EmbeddingClient(EmbeddingConfig.openai(BAD_API_KEY, base_url=base_url)).health_check()
+        assert health["reachable"]
+        assert not health["authenticated"]
+
+    def test_missing_model(self, base_url):
+        """Test that an unknown model is reported as unavailable."""
+        health = EmbeddingClient(EmbeddingConfig.openai(API_KEY, "text-embedding-unknown", base_url=base_url)).health_check()
+        assert health["reachable"] and health["authenticated"]
+        assert not health["model_available"]
+
+
+class TestExecutorDryRun:
+    """Test Executor.dry_run."""
+
+    def test_probes_each_distinct_model_once(self, base_url):
+        """Test that nodes sharing a model are checked once and an unknown model fails the dry run."""
+        workflow = Workflow("dry run")
+        first = workflow.add_node(Node.agent(name="first", prompt="Draft"))
+        second = workflow.add_node(Node.agent(name="second", prompt="Review"))
+        third = workflow.add_node(Node.agent(name="third", prompt="Polish", model="gpt-unknown"))
+        workflow.connect(first, second)
+        workflow.connect(second, third)
+
+        report = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url)).dry_run(workflow)
+
+        assert not report["ok"]
+        assert report["validation_error"] is None
+        assert [(health["model"], health["model_available"]) for health in report["providers"]] == [
+            ("gpt-4o-mini", True),
+            ("gpt-unknown", False),
+        ]
+
+    def test_healthy_workflow(self, base_url):
+        """Test that a valid workflow with healthy providers passes."""
+        workflow = Workflow("dry run")
+        workflow.add_node(Node.agent(name="answer", prompt="Answer"))
+
+        report = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url)).dry_run(workflow)
+
+        assert report["ok"]
+        assert len(report["providers"]) == 1
+
+    def test_renders_prompts_with_executor_defaults(self, base_url):
+        """Test that the dry run shows prompts with the preamble, suffix and merged globals."""
+        workflow = Workflow("dry run")
+        first = workflow.add_node(
+            Node.agent(
+                name="draft",
+                prompt="Draft a reply about {{input.topic}} for {{globals.company_name}}",
+                system_prompt="Be brief.",
+                globals={"company_name": "Acme Labs"},
+            )
+        )
+        second = workflow.add_node(Node.agent(name="review", prompt="Review it as {{globals.company_name}}"))
+        workflow.connect(first, second)
+
+        executor = Executor(
+            LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url),
+            globals={"company_name": "Acme"},
+            system_preamble="You work for {{globals.company_name}}.",
+            prompt_suffix="Sign as {{globals.company_name}}.",
+        )
+        prompts = {prompt["node"]: prompt for prompt in executor.dry_run(workflow)["prompts"]}
+
+        assert prompts["draft"]["system_prompt"] == "You work for Acme Labs.\n\nBe brief."
+        assert prompts["draft"]["prompt"] == "Draft a reply about {{input.topic}} for Acme Labs\n\nSign as Acme Labs."
+        assert prompts["review"]["prompt"] == "Review it as Acme\n\nSign as Acme."
+
+    def test_rejects_invalid_globals(self):
+        """Test that globals must be a dict with string keys."""
+        with pytest.raises(Exception, match="globals"):
+            Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"), globals={1: "one"})
@@ -0,0 +1,109 @@
+"""Unit tests for history windows of agent tool loops."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow, tool
+
+API_KEY = "sk-" + "0" * 48
+TOOL_ROUNDS = 3
+
+
+@pytest.fixture
+def llm_server():
+    """Local OpenAI-compatible server asking for `lookup` with steps 1 to 3, then answering, and summarizing when asked to."""
+    requests = []
+    state = {"rounds": 0, "summaries": 0}
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            requests.append(body)
+            message = {"role": "assistant", "content": "Done"}
+            finish_reason = "stop"
+            if "summarization assistant" in body["messages"][0]["content"]:
+                state["summaries"] += 1
+                message["content"] = f"Summary {state['summaries']}"
+            elif state["rounds"] < TOOL_ROUNDS:
+                state["rounds"] += 1
+                call = {"id": f"call_{state['rounds']}", "type": "function", "function": {"name": "lookup", "arguments": json.dumps({"step": state["rounds"]})}}
+                message = {"role": "assistant", "content": None, "tool_calls": [call]}
+                finish_reason = "tool_calls"
+            payload = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", requests
+    server.shutdown()
+
+
+@tool(description="Look up a step")
+def lookup(step: int) -> str:
+    """Return the result of `step`."""
+    return f"result {step}"
+
+
+def run(url, **options):
+    workflow = Workflow("history")
+    workflow.add_node(Node.agent("research", "Research the topic", tools=[lookup], **options))
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=f"{url}/v1"))
+    return executor.execute(workflow)
+
+
+def tool_results(body):
+    return [m["content"] for m in body["messages"] if m["role"] == "tool"]
+
+
+class TestHistoryWindow:
+    """Test the tool exchanges replayed by agent tool loops."""
+
+    def test_window_keeps_latest_exchange_and_summarizes_the_rest(self, llm_server):
+        """Test that only the latest exchange is replayed, after a summary of the earlier ones."""
+        url, requests = llm_server
+        result = run(url, history_window=1, summarize_overflow=True)
+
+        assert result.is_success(), result.state()
+        summaries = [body for body in requests if "summarization assistant" in body["messages"][0]["content"]]
+        follow_ups = [body for body in requests[1:] if body not in summaries]
+        assert [tool_results(body) for body in follow_ups] == [["result 1"], ["result 2"], ["result 3"]]
+        assert len(summaries) == 2
+        assert "result 1" in summaries[0]["messages"][1]["content"]
+        assert "Earlier summary:\nSummary 1" in summaries[1]["messages"][1]["content"]
+        assert "result 1" not in summaries[1]["messages"][1]["content"]
+        note = follow_ups[2]["messages"][1]
+        assert note["role"] == "system"
+        assert note["content"].endswith("Summary 2")
+
+    def test_default_replays_every_exchange(self, llm_server):
+        """Test that without a window every exchange is replayed."""
+        url, requests = llm_server
+        result = run(url)
+
+        assert result.is_success(), result.state()
+        assert tool_results(requests[-1]) == ["result 1", "result 2", "result 3"]
+
+    def test_invalid_configuration(self):
+        """Test that empty windows and summaries without a window are rejected."""
+        with pytest.raises(ValueError, match="history_window"):
+            Node.agent(name="a", prompt="p", history_window=0)
+        with pytest.raises(ValueError, match="history_token_budget"):
+            Node.agent(name="a", prompt="p", history_token_budget=0)
+        with pytest.raises(ValueError, match="summarize_overflow requires"):
+            Node.agent(name="a", prompt="p", summarize_overflow=True)
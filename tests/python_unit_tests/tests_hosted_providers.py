@@ -0,0 +1,118 @@
+"""Unit tests for the Fireworks AI and TogetherAI providers against mocked endpoints."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmClient, LlmConfig, Node, Workflow
+
+API_KEY = "fw-" + "0" * 40
+
+CATALOG = [
+    {"id": "openai/gpt-oss-20b", "object": "model", "type": "chat", "organization": "OpenAI", "context_length": 131072},
+    {"id": "accounts/fireworks/models/llama-v3p1-8b-instruct", "object": "model", "owned_by": "fireworks", "context_length": 131072},
+]
+
+
+@pytest.fixture
+def server():
+    """Local server answering with `responses[path]`, a (status, body) pair, and recording request bodies."""
+    responses = {}
+    requests = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def reply(self):
+            status, body = responses.get(self.path, (404, {"error": "Not Found"}))
+            payload = (body if isinstance(body, str) else json.dumps(body)).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def do_GET(self):
+            requests.append((self.path, None))
+            self.reply()
+
+        def do_POST(self):
+            requests.append((self.path, json.loads(self.rfile.read(int(self.headers["Content-Length"])))))
+            self.reply()
+
+        def log_message(self, *args):
+            pass
+
+    http_server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=http_server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{http_server.server_port}/v1", responses, requests
+    http_server.shutdown()
+
+
+def completion(content):
+    """Chat completion answering `content`."""
+    return {
+        "id": "cmpl-1",
+        "object": "chat.completion",
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": content}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 5, "completion_tokens": 3, "total_tokens": 8},
+    }
+
+
+class TestHostedProviders:
+    """Test LlmConfig.fireworks and LlmConfig.together with a custom base_url."""
+
+    def test_fireworks_completion(self, server):
+        """Test that bare Fireworks model names are expanded to full model IDs."""
+        base_url, responses, requests = server
+        responses["/v1/chat/completions"] = (200, completion("Hello from Fireworks"))
+
+        client = LlmClient(LlmConfig.fireworks(API_KEY, "llama-v3p1-8b-instruct", base_url=base_url))
+
+        assert client.complete("Hi") == "Hello from Fireworks"
+        assert requests[0][1]["model"] == "accounts/fireworks/models/llama-v3p1-8b-instruct"
+
+    def test_together_completion(self, server):
+        """Test that together is the same provider as togetherai."""
+        base_url, responses, requests = server
+        responses["/v1/chat/completions"] = (200, completion("Hello from Together"))
+
+        config = LlmConfig.together(API_KEY, "openai/gpt-oss-20b", base_url=base_url)
+
+        assert config.provider() == "togetherai"
+        assert LlmClient(config).complete("Hi") == "Hello from Together"
+        assert requests[0][1]["model"] == "openai/gpt-oss-20b"
+
+    @pytest.mark.parametrize(
+        "status, body, message",
+        [
+            (404, {"error": {"object": "error", "type": "invalid_request_error", "message": "Model not found"}}, "Model not found (invalid_request_error)"),
+            (400, {"error": {"message": "max_tokens too large", "type": "invalid_request_error", "code": "invalid_parameter"}}, "max_tokens too large (invalid_parameter)"),
+            (503, {"error": "Service overloaded"}, "Service overloaded"),
+            (422, {"detail": "Field required: messages"}, "Field required: messages"),
+        ],
+    )
+    def test_error_envelopes(self, server, status, body, message):
+        """Test that the message of each provider's error shape is surfaced."""
+        base_url, responses, _ = server
+        responses["/v1/chat/completions"] = (status, body)
+
+        for config in (LlmConfig.fireworks(API_KEY, base_url=base_url), LlmConfig.together(API_KEY, base_url=base_url)):
+            with pytest.raises(Exception) as error:
+                LlmClient(config).complete("Hi")
+            assert message in str(error.value)
+            assert f"HTTP {status}" in str(error.value)
+
+    def test_dry_run_checks_model_names(self, server):
+        """Test that a model missing from the catalog is reported before a run."""
+        base_url, responses, requests = server
+        responses["/v1/models"] = (200, CATALOG)
+        workflow = Workflow("hosted")
+        workflow.add_node(Node.agent(name="answer", prompt="Answer"))
+
+        known = Executor(LlmConfig.together(API_KEY, "openai/gpt-oss-20b", base_url=base_url)).dry_run(workflow)
+        unknown = Executor(LlmConfig.together(API_KEY, "openai/gpt-oss-2b", base_url=base_url)).dry_run(workflow)
+
+        assert known["ok"]
+        assert not unknown["ok"]
+        assert all(path == "/v1/models" for path, _ in requests)
@@ -0,0 +1,80 @@
+"""Unit tests for declaring a workflow input schema and validating inputs against it."""
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+from graphbit.errors import ValidationError
+
+API_KEY = "sk-" + "3" * 48
+
+SCHEMA = {
+    "type": "object",
+    "properties": {
+        "question": {"type": "string"},
+        "doc_path": {"type": "string"},
+    },
+    "required": ["question", "doc_path"],
+    "additionalProperties": False,
+}
+
+
+def build(schema=SCHEMA):
+    """Workflow with one transform node reading the `question` and `doc_path` inputs."""
+    workflow = Workflow("qa")
+    workflow.add_node(Node.transform("Answer", "vars.question + ' in ' + vars.doc_path"))
+    if schema is not None:
+        workflow.set_input_schema(schema)
+    return workflow
+
+
+def executor():
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+
+
+class TestInputSchema:
+    """Test input schemas declared with Workflow.set_input_schema."""
+
+    def test_matching_inputs_run(self):
+        """Test inputs matching the schema running the workflow."""
+        result = executor().execute(build(), inputs={"question": "Who?", "doc_path": "a.pdf"})
+
+        assert result.get_node_output("Answer") == "Who? in a.pdf"
+
+    def test_missing_required_inputs_raise(self):
+        """Test every missing required input being listed in one ValidationError."""
+        with pytest.raises(ValidationError) as error:
+            executor().execute(build(), inputs={})
+
+        message = str(error.value)
+        assert "Required property 'question' is missing" in message
+        assert "Required property 'doc_path' is missing" in message
+
+    def test_wrong_type_raises(self):
+        """Test an input of the wrong type raising a ValidationError naming it."""
+        with pytest.raises(ValidationError, match="question: Type mismatch"):
+            executor().execute(build(), inputs={"question": 42, "doc_path": "a.pdf"})
+
+    def test_extra_inputs_follow_additional_properties(self):
+        """Test undeclared inputs rejected by additionalProperties false and accepted otherwise."""
+        inputs = {"question": "Who?", "doc_path": "a.pdf", "locale": "en"}
+        with pytest.raises(ValidationError, match="Unknown property 'locale'"):
+            executor().execute(build(), inputs=inputs)
+
+        open_schema = {key: value for key, value in SCHEMA.items() if key != "additionalProperties"}
+        result = executor().execute(build(open_schema), inputs=inputs)
+        assert result.is_success()
+
+    def test_invalid_schema_is_rejected(self):
+        """Test a schema that does not describe an object being rejected."""
+        workflow = Workflow("qa")
+        with pytest.raises(ValidationError):
+            workflow.set_input_schema({"type": "string"})
+        assert workflow.input_schema() is None
+
+    def test_schema_in_json_and_dry_run(self):
+        """Test the schema surviving to_json and appearing in the dry-run report."""
+        workflow = Workflow.from_json(build().to_json())
+
+        assert workflow.input_schema() == SCHEMA
+        assert executor().dry_run(workflow)["input_schema"] == SCHEMA
+        assert executor().dry_run(build(None))["input_schema"] is None
@@ -0,0 +1,115 @@
+"""Unit tests for recovering JSON from malformed model output."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow, repair_json
+
+PERSON_SCHEMA = {
+    "type": "object",
+    "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+    "required": ["name", "age"],
+}
+
+FIXTURES = [
+    ('{"a": 1}', {"a": 1}, []),
+    ('```json\n{"a": 1}\n```', {"a": 1}, ["stripped_code_fence"]),
+    ('Sure, here it is:\n```\n[1, 2]\n```\nAnything else?', [1, 2], ["stripped_code_fence"]),
+    ('The answer is {"a": {"b": [1]}}.', {"a": {"b": [1]}}, ["extracted_from_prose"]),
+    ('{"a": [1, 2,], "b": 3,}', {"a": [1, 2], "b": 3}, ["removed_trailing_commas"]),
+    ('{"a": "x,]",}', {"a": "x,]"}, ["removed_trailing_commas"]),
+    ('{"summary": "cut off', {"summary": "cut off"}, ["closed_string", "closed_brackets"]),
+    ('[{"a": 1}, {"b": 2', [{"a": 1}, {"b": 2}], ["closed_brackets"]),
+    ('{"a": 1, "b":', {"a": 1, "b": None}, ["closed_brackets"]),
+    ('```json\n{"tags": ["math",],}\n```', {"tags": ["math"]}, ["stripped_code_fence", "removed_trailing_commas"]),
+]
+
+
+@pytest.fixture
+def chat_server():
+    """Local OpenAI-compatible server answering with scripted replies, recording request bodies."""
+    replies = []
+    bodies = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            bodies.append(json.loads(self.rfile.read(int(self.headers["Content-Length"]))))
+            reply = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": replies.pop(0)}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(reply)))
+            self.end_headers()
+            self.wfile.write(reply)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    config = LlmConfig.openai("sk-" + "0" * 48, "gpt-4o-mini", base_url=f"http://127.0.0.1:{server.server_port}/v1")
+    yield config, replies, bodies
+    server.shutdown()
+
+
+def run_extraction(config, **options):
+    """Run a single agent node extracting a person with PERSON_SCHEMA."""
+    workflow = Workflow("extraction")
+    workflow.add_node(Node.agent(name="extract", prompt="Extract the person from: Ada, 36", output_schema=PERSON_SCHEMA, **options))
+    return Executor(config).execute(workflow)
+
+
+class TestRepairJson:
+    """Test graphbit.repair_json on malformed model output."""
+
+    def test_fixtures(self):
+        """Test each fixture recovering the expected value and reporting the repairs made."""
+        for text, expected, repairs in FIXTURES:
+            assert repair_json(text) == expected, text
+            assert repair_json(text, return_repairs=True) == (expected, repairs), text
+
+    def test_unrecoverable(self):
+        """Test text without a recoverable value raising ValueError."""
+        for text in ["", "no json here", "[citation needed]", "{'a': 1}"]:
+            with pytest.raises(ValueError):
+                repair_json(text)
+
+
+class TestAgentJsonRecovery:
+    """Test agent nodes with an output_schema recovering JSON from their output."""
+
+    def test_fenced_output_recovered(self, chat_server):
+        """Test fenced output with a trailing comma satisfying the schema without a repair request."""
+        config, replies, bodies = chat_server
+        replies.append('```json\n{"name": "Ada", "age": 36,}\n```')
+
+        result = run_extraction(config)
+
+        assert result.is_success(), result.state()
+        assert json.loads(result.get_node_output("extract")) == {"name": "Ada", "age": 36}
+        assert len(bodies) == 1
+        report = result.get_output_validation("extract")
+        assert report["repair_attempts"] == 0
+        assert report["json_repairs"] == ["stripped_code_fence", "removed_trailing_commas"]
+
+    def test_strict_json(self, chat_server):
+        """Test strict_json=True sending fenced output back to the model instead of recovering it."""
+        config, replies, bodies = chat_server
+        replies.extend(['```json\n{"name": "Ada", "age": 36}\n```', '{"name": "Ada", "age": 36}'])
+
+        result = run_extraction(config, strict_json=True)
+
+        assert result.is_success(), result.state()
+        assert len(bodies) == 2
+        report = result.get_output_validation("extract")
+        assert report["repair_attempts"] == 1
+        assert "json_repairs" not in report
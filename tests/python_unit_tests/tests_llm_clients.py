@@ -81,6 +81,19 @@ class TestLlmConfig:
         assert config.provider() == "mistralai"
         assert config.model() == "mistral-large-latest"
 
+    def test_llm_config_gemini_safety_settings(self):
+        """Test creating Gemini LLM configuration with safety settings."""
+        config = LlmConfig.gemini(
+            api_key="AIza" + "x" * 35,
+            model="gemini-2.5-pro",
+            safety_settings={"HARM_CATEGORY_DANGEROUS_CONTENT": "BLOCK_ONLY_HIGH"},
+        )
+        assert config.provider() == "gemini"
+        assert config.model() == "gemini-2.5-pro"
+
+        with pytest.raises(TypeError):
+            LlmConfig.gemini(api_key="AIza" + "x" * 35, safety_settings={"HARM_CATEGORY_DANGEROUS_CONTENT": 3})
+
     def test_llm_config_perplexity(self):
         """Test creating Perplexity LLM configuration."""
         api_key = get_api_key("perplexity")
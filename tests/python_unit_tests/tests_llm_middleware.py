@@ -0,0 +1,146 @@
+"""Unit tests for LLM middleware."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmClient, LlmConfig, LlmMiddleware, Node, Workflow
+
+
+@pytest.fixture
+def chat_server():
+    """Local OpenAI-compatible server echoing the last message and recording request bodies."""
+    bodies = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            bodies.append(body)
+            reply = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "choices": [
+                        {
+                            "index": 0,
+                            "message": {"role": "assistant", "content": f"echo: {body['messages'][-1]['content']}"},
+                            "finish_reason": "stop",
+                        }
+                    ],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(reply)))
+            self.end_headers()
+            self.wfile.write(reply)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    config = LlmConfig.openai("sk-" + "0" * 48, "gpt-4o-mini", base_url=f"http://127.0.0.1:{server.server_port}/v1")
+    yield config, bodies
+    server.shutdown()
+
+
+def tagging(name, log):
+    """Middleware tagging requests and responses with its name and logging its hook calls."""
+
+    def before(request):
+        log.append(f"{name}.before")
+        request["messages"][-1]["content"] += f" [{name}]"
+
+    def after(request, response):
+        log.append(f"{name}.after")
+        return {**response, "content": response["content"] + f" <{name}>"}
+
+    def on_error(request, message):
+        log.append(f"{name}.error")
+
+    return LlmMiddleware(before=before, after=after, on_error=on_error, name=name)
+
+
+class TestLlmMiddleware:
+    """Test middleware registered on LlmClient and Executor."""
+
+    def test_hooks_run_in_order_and_mutate_requests(self, chat_server):
+        """Test that hooks run in registration order and their changes reach the provider and caller."""
+        config, bodies = chat_server
+        log = []
+
+        def set_temperature(request):
+            return {**request, "temperature": 0.25}
+
+        client = LlmClient(
+            config,
+            middleware=[tagging("first", log), tagging("second", log), LlmMiddleware(before=set_temperature)],
+        )
+        assert client.complete("ping") == "echo: ping [first] [second] <first> <second>"
+
+        assert log == ["first.before", "second.before", "first.after", "second.after"]
+        assert bodies[0]["messages"][-1]["content"] == "ping [first] [second]"
+        assert bodies[0]["temperature"] == 0.25
+
+    def test_failing_hook_aborts_the_call(self, chat_server):
+        """Test that an exception in a hook fails the call and reaches on_error."""
+        config, bodies = chat_server
+        log = []
+
+        def reject(request):
+            raise ValueError("rejected by policy")
+
+        client = LlmClient(config, middleware=[tagging("audit", log), LlmMiddleware(before=reject, name="guard")])
+        with pytest.raises(Exception, match="rejected by policy"):
+            client.complete("ping")
+
+        assert bodies == []
+        assert log[0] == "audit.before"
+        assert "audit.error" in log
+
+    def test_redaction(self, chat_server):
+        """Test that the built-in redaction middleware masks default and custom patterns."""
+        config, bodies = chat_server
+        redaction = LlmMiddleware.redaction(patterns={"ticket": r"TICKET-\d+"})
+        assert redaction.name == "redaction"
+
+        client = LlmClient(config, middleware=[redaction])
+        reply = client.complete("Mail ada@example.com about TICKET-42")
+
+        assert bodies[0]["messages"][-1]["content"] == "Mail [REDACTED_EMAIL] about [REDACTED_TICKET]"
+        assert reply == "echo: Mail [REDACTED_EMAIL] about [REDACTED_TICKET]"
+
+        with pytest.raises(Exception, match="Invalid redaction pattern"):
+            LlmMiddleware.redaction(patterns={"bad": "("})
+
+    def test_executor_middleware(self, chat_server):
+        """Test that executor middleware wraps the LLM calls of agent nodes."""
+        config, bodies = chat_server
+        log = []
+        workflow = Workflow("support")
+        workflow.add_node(Node.agent(name="answer", prompt="Customer ada@example.com asks for a refund"))
+
+        executor = Executor(config, middleware=[LlmMiddleware.redaction(), tagging("audit", log)])
+        result = executor.execute(workflow)
+
+        assert result.is_success(), result.state()
+        assert bodies[0]["messages"][-1]["content"] == "Customer [REDACTED_EMAIL] asks for a refund [audit]"
+        assert log == ["audit.before", "audit.after"]
+        assert result.get_node_output("answer") == "echo: Customer [REDACTED_EMAIL] asks for a refund [audit] <audit>"
+
+    def test_invalid_hooks(self):
+        """Test that missing, non-callable and coroutine hooks are rejected."""
+
+        async def before(request):
+            return request
+
+        with pytest.raises(ValueError):
+            LlmMiddleware()
+        with pytest.raises(ValueError):
+            LlmMiddleware(before="not callable")
+        with pytest.raises(ValueError, match="synchronous"):
+            LlmMiddleware(before=before)
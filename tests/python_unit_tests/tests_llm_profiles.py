@@ -0,0 +1,137 @@
+"""Unit tests for executor LLM profiles selected at execution time."""
+
+import asyncio
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+from graphbit.errors import ConfigurationError, ValidationError
+
+API_KEY = "sk-" + "0" * 48
+
+
+@pytest.fixture
+def llm_server():
+    """Local server speaking the OpenAI chat completions and Ollama chat APIs, recording (path, model, prompt) per request."""
+    received = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def reply(self, body):
+            payload = json.dumps(body).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def do_GET(self):
+            # Ollama checks the model is pulled before its first call
+            self.reply({"models": [{"name": "llama3.2"}, {"name": "llama3.2:1b"}]})
+
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            prompt = body["messages"][-1]["content"]
+            received.append((self.path, body["model"], prompt))
+            if self.path == "/api/chat":
+                self.reply({"message": {"role": "assistant", "content": "Done"}, "done": True})
+            else:
+                self.reply(
+                    {
+                        "id": "chatcmpl-1",
+                        "choices": [{"index": 0, "message": {"role": "assistant", "content": "Done"}, "finish_reason": "stop"}],
+                        "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+                    }
+                )
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", received
+    server.shutdown()
+
+
+def model_for(received, task):
+    """Path and model of the last request made for the node prompted with `task`."""
+    return next((path, model) for path, model, prompt in reversed(received) if f"Task: {task}" in prompt)
+
+
+def profiles(url):
+    return {
+        "dev": {
+            "llm_config": {"provider": "Ollama", "model": "llama3.2", "base_url": url},
+            "tags": {"fast": {"model": "llama3.2:1b"}},
+        },
+        "prod": {"nodes": {"Review": {"model": "gpt-4o-mini"}}},
+    }
+
+
+def workflow(url):
+    workflow = Workflow("profiles")
+    workflow.add_node(Node.agent("Summarize", "Task: Summarize", tags=["fast"]))
+    workflow.add_node(Node.agent("Review", "Task: Review"))
+    workflow.add_node(Node.agent("Pinned", "Task: Pinned", llm_config=LlmConfig.openai(API_KEY, "gpt-4.1", base_url=f"{url}/v1")))
+    return workflow
+
+
+class TestLlmProfiles:
+    """Test selecting LLM profiles per run."""
+
+    def test_profile_selects_the_model_of_each_node(self, llm_server):
+        """Test that each profile gives nodes its models, below node-level configuration."""
+        url, received = llm_server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"), profiles=profiles(url))
+
+        assert executor.execute(workflow(url), profile="dev").is_success()
+        assert model_for(received, "Summarize") == ("/api/chat", "llama3.2:1b")
+        assert model_for(received, "Review") == ("/api/chat", "llama3.2")
+        assert model_for(received, "Pinned") == ("/v1/chat/completions", "gpt-4.1")
+
+        assert executor.execute(workflow(url), profile="prod").is_success()
+        assert model_for(received, "Summarize") == ("/v1/chat/completions", "gpt-4o")
+        assert model_for(received, "Review") == ("/v1/chat/completions", "gpt-4o-mini")
+        assert model_for(received, "Pinned") == ("/v1/chat/completions", "gpt-4.1")
+
+    def test_profiles_load_from_yaml(self, llm_server, tmp_path):
+        """Test loading profiles from a YAML file and selecting one with run_async."""
+        url, received = llm_server
+        path = tmp_path / "profiles.yaml"
+        path.write_text(f"dev:\n  llm_config:\n    provider: Ollama\n    model: llama3.2\n    base_url: {url}\n")
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"), profiles=path)
+
+        workflow = Workflow("yaml")
+        workflow.add_node(Node.agent("Review", "Task: Review"))
+
+        async def run():
+            return await executor.run_async(workflow, profile="dev")
+
+        assert asyncio.run(run()).is_success()
+        assert model_for(received, "Review") == ("/api/chat", "llama3.2")
+
+    def test_without_profile_the_executor_config_applies(self, llm_server):
+        """Test that runs without a profile use the executor's configuration."""
+        url, received = llm_server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"), profiles=profiles(url))
+
+        workflow = Workflow("plain")
+        workflow.add_node(Node.agent("Summarize", "Task: Summarize", tags=["fast"]))
+        assert executor.execute(workflow).is_success()
+        assert model_for(received, "Summarize") == ("/v1/chat/completions", "gpt-4o")
+
+    def test_unknown_profile_fails(self, llm_server):
+        """Test that selecting an unknown profile fails before any node runs."""
+        url, received = llm_server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"), profiles=profiles(url))
+
+        with pytest.raises(ValidationError, match="Unknown profile 'staging'"):
+            executor.execute(workflow(url), profile="staging")
+        assert received == []
+
+    def test_invalid_profiles_are_rejected(self):
+        """Test that malformed profiles fail when the executor is created."""
+        with pytest.raises(ConfigurationError, match="Invalid LLM profiles"):
+            Executor(LlmConfig.openai(API_KEY, "gpt-4o"), profiles={"dev": {"llm_config": {"provider": "Nope"}}})
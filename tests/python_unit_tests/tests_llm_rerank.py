@@ -0,0 +1,140 @@
+"""Unit tests for re-ranking documents and memories by LLM-scored relevance."""
+
+import json
+import re
+import sqlite3
+import threading
+import uuid
+from datetime import datetime, timezone
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import EmbeddingConfig, LlmClient, LlmConfig, LlmMiddleware, MemoryClient, MemoryConfig
+from graphbit.errors import ValidationError
+
+API_KEY = "sk-" + "4" * 48
+
+DOCUMENTS = [
+    "Paris is the capital of France",
+    "Bananas are rich in potassium",
+    "France borders Spain and Italy",
+    "The Eiffel Tower is in Paris",
+]
+
+SCORES = {DOCUMENTS[0]: 10, DOCUMENTS[2]: 4, DOCUMENTS[3]: 6}
+
+
+@pytest.fixture
+def server():
+    """Local OpenAI-compatible server scoring documents with `SCORES` and embedding every text alike, recording request bodies."""
+    bodies = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            bodies.append(body)
+            if "input" in body:
+                inputs = body["input"] if isinstance(body["input"], list) else [body["input"]]
+                data = [{"index": i, "embedding": [1.0, 0.0]} for i in range(len(inputs))]
+                payload = {"data": data, "model": body["model"], "usage": {"prompt_tokens": 1, "total_tokens": 1}}
+            else:
+                numbered = re.findall(r"^\[(\d+)\] (.*)$", body["messages"][-1]["content"], re.MULTILINE)
+                verdict = json.dumps({n: SCORES.get(document, 0) for n, document in numbered})
+                payload = {
+                    "id": "chatcmpl-1",
+                    "model": body["model"],
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": verdict}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 30, "completion_tokens": 10, "total_tokens": 40},
+                }
+            reply = json.dumps(payload).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(reply)))
+            self.end_headers()
+            self.wfile.write(reply)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", bodies
+    server.shutdown()
+
+
+def llm_config(url):
+    return LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=f"{url}/v1")
+
+
+def chat_models(bodies):
+    return [body["model"] for body in bodies if "messages" in body]
+
+
+class TestLlmClientRerank:
+    """Test LlmClient.rerank."""
+
+    def test_orders_documents_by_score(self, server):
+        """Test documents being ordered by normalized score and cut to top_k, scored batch_size per prompt."""
+        url, bodies = server
+        client = LlmClient(llm_config(url))
+
+        ranked = client.rerank("What is the capital of France?", DOCUMENTS, top_k=3, batch_size=2)
+
+        assert [(d["index"], d["score"]) for d in ranked["documents"]] == [(0, 1.0), (3, 0.6), (2, 0.4)]
+        assert ranked["documents"][0]["document"] == DOCUMENTS[0]
+        assert chat_models(bodies) == ["gpt-4o-mini", "gpt-4o-mini"]
+        assert ranked["usage"]["total_tokens"] == 80
+
+    def test_scoring_goes_through_middleware_with_model_override(self, server):
+        """Test the scoring calls reaching the client's middleware and using the given model."""
+        url, bodies = server
+        usage = []
+        middleware = LlmMiddleware(after=lambda request, response: usage.append(response["usage"]["total_tokens"]))
+        client = LlmClient(llm_config(url), middleware=[middleware])
+
+        ranked = client.rerank("Paris", DOCUMENTS, model="gpt-4o")
+
+        assert len(ranked["documents"]) == 4
+        assert usage == [40]
+        assert chat_models(bodies) == ["gpt-4o"]
+
+    def test_rejects_empty_batch_size(self, server):
+        """Test a batch size of zero being rejected before any call."""
+        url, bodies = server
+        with pytest.raises(Exception, match="batch size"):
+            LlmClient(llm_config(url)).rerank("Paris", DOCUMENTS, batch_size=0)
+        assert bodies == []
+
+
+class TestMemorySearchRerank:
+    """Test MemoryClient.search with a rerank config."""
+
+    def open_client(self, url, db_path):
+        config = MemoryConfig(llm_config(url), EmbeddingConfig.openai(API_KEY, "embed-v1", base_url=url), db_path=str(db_path))
+        return MemoryClient(config)
+
+    def test_search_reranks_memories(self, server, tmp_path):
+        """Test memories found by similarity being re-ranked by the memory LLM."""
+        url, bodies = server
+        db_path = tmp_path / "memory.db"
+        self.open_client(url, db_path)
+        now = datetime.now(timezone.utc).isoformat()
+        with sqlite3.connect(db_path) as conn:
+            conn.executemany(
+                "INSERT INTO memories (id, content, hash, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+                [(str(uuid.uuid4()), document, document, now, now) for document in DOCUMENTS],
+            )
+        client = self.open_client(url, db_path)
+
+        results = client.search("Capital of France", top_k=4, rerank={"top_k": 2})
+
+        assert [(r.memory.content, r.score) for r in results] == [(DOCUMENTS[0], 1.0), (DOCUMENTS[3], 0.6)]
+        assert chat_models(bodies) == ["gpt-4o-mini"]
+
+    def test_invalid_rerank_config(self, server, tmp_path):
+        """Test an unusable rerank config being rejected."""
+        url, _ = server
+        client = self.open_client(url, tmp_path / "memory.db")
+        with pytest.raises(ValidationError, match="Invalid rerank config"):
+            client.search("Paris", rerank={"top_k": "two"})
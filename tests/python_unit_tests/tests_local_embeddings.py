@@ -0,0 +1,50 @@
+"""Unit tests for local embeddings against the tiny BERT model bundled with the Rust tests."""
+
+import math
+import os
+
+import pytest
+
+from graphbit import EmbeddingClient, EmbeddingConfig
+
+MODEL_DIR = os.path.join(os.path.dirname(__file__), "..", "rust_unit_tests", "fixtures", "local-embedding-model")
+
+HELLO_WORLD = [0.148480, -0.165277, -0.647780, 0.485316, -0.247488, -0.075142, 0.149237, 0.454222]
+CAT_ON_MAT = [0.323639, -0.081758, -0.527280, 0.125321, -0.427304, -0.078389, 0.579675, 0.264740]
+
+
+@pytest.fixture
+def config():
+    """Local embedding configuration, skipping builds without the local-embeddings feature."""
+    try:
+        return EmbeddingConfig.local(MODEL_DIR, threads=1)
+    except Exception as error:
+        if "local-embeddings" in str(error):
+            pytest.skip("GraphBit built without the local-embeddings feature")
+        raise
+
+
+class TestLocalEmbeddings:
+    """Test EmbeddingConfig.local."""
+
+    def test_deterministic_vectors(self, config):
+        """Test that embeddings match the reference model, alone and in a padded batch."""
+        client = EmbeddingClient(config)
+
+        single = client.embed("Hello world")
+        batch = client.embed_many(["Hello world", "The cat sat on the mat."])
+
+        assert single == pytest.approx(HELLO_WORLD, abs=1e-4)
+        assert batch[0] == pytest.approx(HELLO_WORLD, abs=1e-4)
+        assert batch[1] == pytest.approx(CAT_ON_MAT, abs=1e-4)
+        assert math.sqrt(sum(x * x for x in batch[1])) == pytest.approx(1.0, abs=1e-5)
+
+    def test_missing_directory(self, config):
+        """Test that a model directory that does not exist is rejected."""
+        with pytest.raises(Exception, match="does not exist"):
+            EmbeddingConfig.local(os.path.join(MODEL_DIR, "missing"))
+
+    def test_invalid_threads(self, config):
+        """Test that zero threads are rejected."""
+        with pytest.raises(Exception, match="threads"):
+            EmbeddingConfig.local(MODEL_DIR, threads=0)
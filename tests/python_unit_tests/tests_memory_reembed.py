@@ -0,0 +1,127 @@
+"""Unit tests for re-embedding stored memories with another embedding model."""
+
+import json
+import sqlite3
+import threading
+import uuid
+from datetime import datetime, timezone
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import EmbeddingConfig, LlmConfig, MemoryClient, MemoryConfig
+from graphbit.errors import ValidationError
+
+API_KEY = "sk-" + "0" * 48
+
+FACTS = ["User lives in Munich", "User prefers tea", "User works on Rust", "User owns a cat", "User speaks German"]
+
+
+def fake_embedding(model, text):
+    """Word counts hashed into as many buckets as the model name is long, so different models make incomparable vectors."""
+    vector = [0.0] * len(model)
+    for word in text.split():
+        vector[sum(word.encode()) % len(vector)] += 1.0
+    return vector
+
+
+@pytest.fixture
+def embedder():
+    """Local server answering OpenAI embedding requests with `fake_embedding`, recording (model, input count) per request."""
+    received = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            inputs = body["input"] if isinstance(body["input"], list) else [body["input"]]
+            received.append((body["model"], len(inputs)))
+            data = [{"index": i, "embedding": fake_embedding(body["model"], text)} for i, text in enumerate(inputs)]
+            payload = json.dumps({"data": data, "model": body["model"], "usage": {"prompt_tokens": 1, "total_tokens": 1}}).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", received
+    server.shutdown()
+
+
+def embedding_config(url, model):
+    return EmbeddingConfig.openai(API_KEY, model, base_url=url)
+
+
+def open_client(db_path, url, model):
+    return MemoryClient(MemoryConfig(LlmConfig.openai(API_KEY), embedding_config(url, model), db_path=str(db_path)))
+
+
+def seed(db_path, url):
+    """Store the facts in a sqlite database embedded with the `small-v1` model."""
+    open_client(db_path, url, "small-v1")
+    now = datetime.now(timezone.utc).isoformat()
+    with sqlite3.connect(db_path) as conn:
+        conn.executemany(
+            "INSERT INTO memories (id, content, hash, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+            [(str(uuid.uuid4()), fact, fact, now, now) for fact in FACTS],
+        )
+    open_client(db_path, url, "small-v1")
+
+
+def embedded(received, model):
+    return sum(count for m, count in received if m == model)
+
+
+class TestMemoryReembed:
+    """Test MemoryClient.reembed against the sqlite store."""
+
+    def test_reembed_switches_model(self, embedder, tmp_path):
+        """Test every memory being re-embedded in batches and searchable with the new model."""
+        url, _ = embedder
+        db_path = tmp_path / "memory.db"
+        seed(db_path, url)
+        client = open_client(db_path, url, "large-model-v2")
+        with pytest.raises(RuntimeError, match="small-v1"):
+            client.search("User prefers tea")
+
+        updates = []
+        assert client.reembed(embedding_config(url, "large-model-v2"), batch_size=2, progress=lambda done, total: updates.append((done, total))) == 5
+
+        assert updates == [(2, 5), (4, 5), (5, 5)]
+        assert [scored.memory.content for scored in client.search("User prefers tea", top_k=1)] == ["User prefers tea"]
+        with sqlite3.connect(db_path) as conn:
+            assert conn.execute("SELECT value FROM memory_settings WHERE key = 'embedding_model'").fetchone() == ("large-model-v2",)
+            assert conn.execute("SELECT DISTINCT model FROM memory_embeddings").fetchall() == [("large-model-v2",)]
+
+    def test_interrupted_reembed_resumes(self, embedder, tmp_path):
+        """Test a re-embedding stopped by its progress callback continuing after the last stored batch."""
+        url, received = embedder
+        db_path = tmp_path / "memory.db"
+        seed(db_path, url)
+        client = open_client(db_path, url, "small-v1")
+
+        def stop(done, total):
+            raise ValueError("stopped")
+
+        with pytest.raises(ValueError, match="stopped"):
+            client.reembed(embedding_config(url, "large-model-v2"), batch_size=2, progress=stop)
+        with pytest.raises(RuntimeError, match="unfinished"):
+            client.search("User prefers tea")
+
+        updates = []
+        assert client.reembed(embedding_config(url, "large-model-v2"), batch_size=2, progress=lambda done, total: updates.append(done)) == 3
+        assert updates == [4, 5]
+        assert embedded(received, "large-model-v2") == 5
+        assert client.search("User owns a cat", top_k=1)[0].memory.content == "User owns a cat"
+
+    def test_reembed_rejects_empty_batch(self, embedder, tmp_path):
+        """Test a batch size of 0 being rejected."""
+        url, _ = embedder
+        client = open_client(tmp_path / "memory.db", url, "small-v1")
+
+        with pytest.raises(ValidationError, match="greater than 0"):
+            client.reembed(embedding_config(url, "large-model-v2"), batch_size=0)
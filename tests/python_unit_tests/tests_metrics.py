@@ -0,0 +1,63 @@
+"""Unit tests for the executor metrics and their Prometheus rendering."""
+
+from graphbit import Executor, LlmConfig, Node, Workflow, enable_metrics, render_metrics
+
+API_KEY = "sk-" + "5" * 48
+
+
+def executor():
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+
+
+def transform_workflow():
+    """Workflow of two transform nodes."""
+    workflow = Workflow("metrics")
+    workflow.add_node(Node.transform("Source", "'hello'"))
+    workflow.add_node(Node.transform("Upper", "uppercase"))
+    workflow.connect("Source", "Upper")
+    return workflow
+
+
+def transform_successes(snapshot):
+    return sum(
+        sample["value"]
+        for sample in snapshot["counters"]
+        if sample["name"] == "graphbit_node_executions_total"
+        and sample["labels"] == {"node_type": "transform", "outcome": "success"}
+    )
+
+
+class TestMetrics:
+    """Test enable_metrics, Executor.metrics_snapshot and render_metrics."""
+
+    def test_snapshot_counts_node_executions(self):
+        """Test the node execution counter growing with a run, with permit waits recorded."""
+        enable_metrics()
+        enable_metrics()
+        ex = executor()
+        before = transform_successes(ex.metrics_snapshot())
+
+        result = ex.execute(transform_workflow())
+
+        assert result.is_success(), result.state()
+        snapshot = ex.metrics_snapshot()
+        assert set(snapshot) == {"counters", "gauges", "histograms"}
+        assert transform_successes(snapshot) >= before + 2
+        waits = [
+            sample["value"]
+            for sample in snapshot["histograms"]
+            if sample["name"] == "graphbit_permit_wait_seconds" and sample["labels"] == {"node_type": "transform"}
+        ]
+        assert waits and waits[0]["count"] >= 2
+        assert waits[0]["buckets"][-1][1] == waits[0]["count"]
+
+    def test_render_metrics_in_prometheus_format(self):
+        """Test the Prometheus text exposition of the recorded metrics."""
+        enable_metrics()
+        executor().execute(transform_workflow())
+
+        text = render_metrics()
+
+        assert "# TYPE graphbit_node_executions_total counter" in text
+        assert 'graphbit_node_executions_total{node_type="transform",outcome="success"}' in text
+        assert "# TYPE graphbit_node_duration_seconds histogram" in text
@@ -0,0 +1,100 @@
+"""Unit tests for model router nodes escalating between models."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "0" * 48
+
+ANSWERS = {
+    "gpt-4o-mini": '{"answer": "maybe", "confidence": 0.4}',
+    "gpt-4o": '{"answer": "yes", "confidence": 0.95}',
+}
+
+
+@pytest.fixture
+def llm_server():
+    """Local server speaking the OpenAI chat completions API, answering per model and recording the models asked."""
+    asked = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            asked.append(body["model"])
+            payload = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "model": body["model"],
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": ANSWERS[body["model"]]}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 100, "completion_tokens": 10, "total_tokens": 110},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}/v1", asked
+    server.shutdown()
+
+
+def models(url):
+    """A cheap and an expensive model served by the local server."""
+    return [LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=url), LlmConfig.openai(API_KEY, "gpt-4o", base_url=url)]
+
+
+def run(node):
+    """Execute a workflow made of `node` alone."""
+    workflow = Workflow("routing")
+    workflow.add_node(node)
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1")).execute(workflow)
+
+
+class TestModelRouter:
+    """Test model router nodes."""
+
+    def test_low_confidence_escalates(self, llm_server):
+        """Test an answer below the confidence threshold being escalated to the next model."""
+        url, asked = llm_server
+
+        result = run(Node.model_router("answer", "Is the sky blue?", models=models(url), escalate_if="confidence < 0.7"))
+
+        assert result.is_success(), result.state()
+        assert asked == ["gpt-4o-mini", "gpt-4o"]
+        assert json.loads(result.get_node_output("answer"))["answer"] == "yes"
+        routing = result.get_node_response_metadata("answer")["model_router"]
+        assert routing["answered_by"] == "gpt-4o"
+        assert [attempt["model"] for attempt in routing["attempts"]] == ["gpt-4o-mini", "gpt-4o"]
+        assert routing["total_cost_usd"] == pytest.approx(sum(attempt["cost_usd"] for attempt in routing["attempts"]))
+
+    def test_confident_answer_is_kept(self, llm_server):
+        """Test the first model's answer being kept when the expression does not hold."""
+        url, asked = llm_server
+
+        result = run(Node.model_router("answer", "Is the sky blue?", models=models(url), escalate_if="confidence < 0.3"))
+
+        assert result.is_success(), result.state()
+        assert asked == ["gpt-4o-mini"]
+        assert result.get_node_response_metadata("answer")["model_router"]["answered_by"] == "gpt-4o-mini"
+
+    def test_invalid_routers_are_rejected(self):
+        """Test a router without models or with an invalid expression being rejected."""
+        with pytest.raises(ValueError, match="models cannot be empty"):
+            Node.model_router("answer", "Is the sky blue?", models=[], escalate_if="confidence < 0.7")
+        with pytest.raises(ValueError, match="invalid escalation expression"):
+            Node.model_router("answer", "Is the sky blue?", models=models("http://127.0.0.1:9/v1"), escalate_if="confidence <")
+
+    def test_repr_names_the_node_type(self):
+        """Test the node representation naming the model router type."""
+        node = Node.model_router("answer", "Is the sky blue?", models=models("http://127.0.0.1:9/v1"), escalate_if="confidence < 0.7")
+        assert "type='ModelRouter'" in repr(node)
@@ -0,0 +1,122 @@
+"""Unit tests for node pacing delays and the minimum interval between LLM calls."""
+
+import json
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "5" * 48
+
+COMPLETION = {
+    "id": "chatcmpl-1",
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Done"}, "finish_reason": "stop"}],
+    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+}
+
+
+@pytest.fixture
+def llm_server():
+    """Local server answering every request with an OpenAI chat completion and recording when each prompt arrived."""
+    arrivals = {}
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            arrivals[body["messages"][-1]["content"]] = time.monotonic()
+            payload = json.dumps(COMPLETION).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", arrivals
+    server.shutdown()
+
+
+def executor(url, **options):
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"), **options)
+
+
+def arrival(arrivals, prompt):
+    return next(at for content, at in arrivals.items() if prompt in content)
+
+
+def report_node(result, name):
+    return next(node for node in result.to_report_dict()["nodes"] if node["node_name"] == name)
+
+
+class TestNodePacing:
+    """Test delays before and after nodes."""
+
+    def test_kwargs_set_the_node_config(self):
+        """Test the pacing kwargs being stored in the node configuration."""
+        node = Node.transform("clean", "trim", pre_execution_delay_ms=250, post_execution_delay_ms=100, delay_jitter_ms=20)
+
+        config = json.loads(node.get_config())
+        assert config["pre_execution_delay_ms"] == 250
+        assert config["post_execution_delay_ms"] == 100
+        assert config["delay_jitter_ms"] == 20
+        assert "pre_execution_delay_ms" not in json.loads(Node.transform("clean", "trim").get_config())
+
+    def test_jitter_needs_a_delay(self):
+        """Test delay_jitter_ms being rejected on a node without delays."""
+        with pytest.raises(ValueError, match="delay_jitter_ms"):
+            Node.agent("Fetch", "Fetch", delay_jitter_ms=50)
+
+    def test_delays_space_dependent_nodes(self, llm_server):
+        """Test the post-execution delay of a node holding back its dependent, outside the node's duration."""
+        url, arrivals = llm_server
+        workflow = Workflow("paced")
+        fetch = workflow.add_node(Node.agent("Fetch", "Fetch the page", post_execution_delay_ms=300))
+        parse = workflow.add_node(Node.agent("Parse", "Parse the page", pre_execution_delay_ms=100))
+        workflow.connect(fetch, parse)
+
+        result = executor(url).execute(workflow)
+
+        assert result.is_success(), result.state()
+        assert arrival(arrivals, "Parse the page") - arrival(arrivals, "Fetch the page") >= 0.4
+        fetch_report = report_node(result, "Fetch")
+        assert fetch_report["pacing"] == {"pre_execution_ms": 0, "post_execution_ms": 300, "llm_spacing_ms": 0}
+        assert fetch_report["duration_ms"] < 300
+        assert report_node(result, "Parse")["pacing"]["pre_execution_ms"] == 100
+
+
+class TestLlmCallSpacing:
+    """Test the executor's min_interval_between_llm_calls_ms."""
+
+    def test_concurrent_calls_are_spaced(self, llm_server):
+        """Test independent agent nodes starting their LLM calls at least the interval apart."""
+        url, arrivals = llm_server
+        workflow = Workflow("fan-out")
+        for name in ["a", "b", "c"]:
+            workflow.add_node(Node.agent(name, f"Summarize {name}"))
+
+        result = executor(url, min_interval_between_llm_calls_ms=150).execute(workflow)
+
+        assert result.is_success(), result.state()
+        starts = sorted(arrivals.values())
+        assert all(later - earlier >= 0.14 for earlier, later in zip(starts, starts[1:])), starts
+        waits = sorted((report_node(result, name).get("pacing") or {}).get("llm_spacing_ms", 0) for name in ["a", "b", "c"])
+        assert waits[0] == 0
+        assert waits[2] >= 250
+
+    def test_spacing_carries_over_between_runs(self, llm_server):
+        """Test the interval applying across consecutive executions of the same executor."""
+        url, arrivals = llm_server
+        spaced = executor(url, min_interval_between_llm_calls_ms=300)
+        for name in ["first", "second"]:
+            workflow = Workflow(name)
+            workflow.add_node(Node.agent(name, f"Run {name}"))
+            assert spaced.execute(workflow).is_success()
+
+        assert arrival(arrivals, "Run second") - arrival(arrivals, "Run first") >= 0.29
@@ -0,0 +1,102 @@
+"""Unit tests for capturing rendered prompts and raw provider payloads per node."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+from graphbit.errors import ValidationError
+
+API_KEY = "sk-" + "7" * 48
+
+COMPLETION = {
+    "id": "chatcmpl-1",
+    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Done"}, "finish_reason": "stop"}],
+    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+}
+
+
+@pytest.fixture
+def llm_server():
+    """Local server answering every request with an OpenAI chat completion."""
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers["Content-Length"]))
+            payload = json.dumps(COMPLETION).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}"
+    server.shutdown()
+
+
+def run(url, **options):
+    workflow = Workflow("capture")
+    workflow.add_node(Node.agent("Summarize", "Summarize {{globals.topic}}"))
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"), globals={"topic": "rust"}, **options)
+    result = executor.execute(workflow)
+    assert result.is_success(), result.state()
+    return result
+
+
+class TestPayloadCapture:
+    """Test the executor's capture_payloads debug mode."""
+
+    def test_capture_records_prompt_and_payloads(self, llm_server):
+        """Test the rendered prompt, request body and raw response body being recorded for the node."""
+        debug = run(llm_server, capture_payloads=True).get_node_debug("Summarize")
+
+        assert debug["rendered_prompt"] == "Summarize rust"
+        [exchange] = debug["exchanges"]
+        assert exchange["method"] == "POST"
+        assert exchange["url"] == f"{llm_server}/v1/chat/completions"
+        assert exchange["status"] == 200
+        assert json.loads(exchange["response_body"]) == COMPLETION
+        assert json.loads(exchange["request_body"])["model"] == "gpt-4o"
+
+    def test_capture_redacts_authorization_header(self, llm_server):
+        """Test the API key being left out of the captured request."""
+        [exchange] = run(llm_server, capture_payloads=True).get_node_debug("Summarize")["exchanges"]
+
+        assert exchange["request_headers"]["authorization"] == "[REDACTED]"
+        assert API_KEY not in json.dumps(exchange)
+
+    def test_nothing_captured_by_default(self, llm_server):
+        """Test executors without capture_payloads recording nothing."""
+        result = run(llm_server)
+
+        assert result.get_node_debug("Summarize") is None
+        assert "debug" not in result.to_report_dict(include_payloads=True)["nodes"][0]
+
+    def test_payload_limit(self, llm_server):
+        """Test captured payloads being cut to capture_payload_max_chars."""
+        [exchange] = run(llm_server, capture_payloads=True, capture_payload_max_chars=10).get_node_debug("Summarize")["exchanges"]
+
+        assert exchange["truncated"]
+        assert exchange["response_body"].startswith(json.dumps(COMPLETION)[:10])
+        with pytest.raises(ValidationError, match="greater than 0"):
+            Executor(LlmConfig.openai(API_KEY), capture_payloads=True, capture_payload_max_chars=0)
+
+    def test_report_includes_payloads_on_request(self, llm_server, tmp_path):
+        """Test reports only showing the captured payloads when include_payloads is set."""
+        result = run(llm_server, capture_payloads=True)
+
+        assert "debug" not in result.to_report_dict()["nodes"][0]
+        assert result.to_report_dict(include_payloads=True)["nodes"][0]["debug"]["rendered_prompt"] == "Summarize rust"
+
+        path = tmp_path / "report.html"
+        result.save_report(path, include_payloads=True)
+        html = path.read_text()
+        assert "Debug payloads" in html
+        assert API_KEY not in html
@@ -0,0 +1,81 @@
+"""Unit tests for Node(post_process=...) reshaping node outputs."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "9" * 48
+LISTING = {"items": [{"title": "First Post"}, {"title": "Second Post"}], "summary": "Two posts"}
+
+
+@pytest.fixture
+def listing_server():
+    """Local server answering every GET with the LISTING JSON."""
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            payload = json.dumps(LISTING).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}/posts"
+    server.shutdown()
+
+
+def run(url, post_process, **options):
+    """Run one HTTP request node with the given post_process list, returning the result."""
+    workflow = Workflow("posts")
+    workflow.add_node(Node.http_request("fetch", url, post_process=post_process))
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"), **options)
+    return executor.execute(workflow)
+
+
+class TestPostProcess:
+    """Test node outputs reshaped by post_process before they are stored."""
+
+    def test_operations_apply_in_order(self, listing_server):
+        """Test a path, a template and lowercasing applied one after the other."""
+        result = run(listing_server, [{"json_path": "$.body.items[-1].title"}, {"template": "Latest: {{output}}"}, "to_lowercase"])
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("fetch") == "latest: second post"
+
+    def test_raw_output_is_captured(self, listing_server):
+        """Test get_node_debug() keeping the output from before post-processing."""
+        result = run(listing_server, [{"json_path": "$.body.summary"}], capture_payloads=True)
+
+        assert result.get_node_output("fetch") == "Two posts"
+        assert result.get_node_debug("fetch")["raw"] == {"status": 200, "body": LISTING}
+
+    def test_unmatched_path_fails_node(self, listing_server):
+        """Test a path matching nothing failing the node."""
+        result = run(listing_server, [{"json_path": "$.body.next"}])
+
+        assert result.is_failed()
+        assert result.failed_nodes() == ["fetch"]
+
+    @pytest.mark.parametrize(
+        "post_process",
+        [
+            [{"reverse": True}],
+            [{"json_path": "body"}],
+            [{"regex_extract": {"pattern": "(\\d+)", "group": 2}}],
+            [{"truncate": 0}],
+        ],
+    )
+    def test_invalid_operations_raise_at_build_time(self, post_process):
+        """Test unknown operations and invalid arguments raising ValueError naming the node."""
+        with pytest.raises(ValueError, match="Invalid node 'fetch'"):
+            Node.http_request("fetch", "http://127.0.0.1:9/posts", post_process=post_process)
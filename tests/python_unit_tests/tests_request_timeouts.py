@@ -0,0 +1,68 @@
+"""Unit tests for request and connection timeouts set on LLM and embedding configurations."""
+
+import socket
+import threading
+import time
+
+import pytest
+
+from graphbit import EmbeddingConfig, LlmClient, LlmConfig
+from graphbit.errors import TimeoutError as GraphBitTimeoutError
+
+API_KEY = "sk-" + "0" * 48
+
+
+@pytest.fixture
+def stalling_server():
+    """Local server accepting connections without ever answering them."""
+    listener = socket.socket()
+    listener.bind(("127.0.0.1", 0))
+    listener.listen()
+    connections = []
+    stop = threading.Event()
+
+    def accept():
+        listener.settimeout(0.1)
+        while not stop.is_set():
+            try:
+                connections.append(listener.accept()[0])
+            except OSError:
+                continue
+
+    threading.Thread(target=accept, daemon=True).start()
+    yield f"http://127.0.0.1:{listener.getsockname()[1]}/v1"
+    stop.set()
+    for connection in connections:
+        connection.close()
+    listener.close()
+
+
+class TestRequestTimeouts:
+    """Test request and connection timeouts."""
+
+    def test_stalled_request_raises_timeout(self, stalling_server):
+        """Test a request outliving its timeout raising the timeout error."""
+        client = LlmClient(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=stalling_server, timeout_ms=200))
+
+        started = time.monotonic()
+        with pytest.raises(GraphBitTimeoutError, match="timed out"):
+            client.complete("ping")
+        assert time.monotonic() - started < 5
+
+    def test_timeouts_are_kept_on_configurations(self):
+        """Test the timeouts being readable back from the configurations."""
+        config = LlmConfig.anthropic(API_KEY, timeout_ms=1500, connect_timeout_ms=250)
+        assert config.timeout_ms() == 1500
+        assert config.connect_timeout_ms() == 250
+        assert LlmConfig.openai(API_KEY).timeout_ms() is None
+
+        embeddings = EmbeddingConfig.openai(API_KEY, timeout_ms=2000)
+        assert embeddings.timeout_ms == 2000
+        assert embeddings.connect_timeout_ms is None
+
+    def test_zero_timeouts_are_rejected(self):
+        """Test a zero timeout being rejected when the configuration is built."""
+        with pytest.raises(ValueError, match="greater than zero"):
+            LlmConfig.openai(API_KEY, timeout_ms=0)
+        with pytest.raises(ValueError, match="greater than zero"):
+            EmbeddingConfig.openai(API_KEY, connect_timeout_ms=0)
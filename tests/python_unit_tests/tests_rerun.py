@@ -0,0 +1,67 @@
+"""Unit tests for re-running part of a workflow with Executor.rerun."""
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "3" * 48
+
+
+def chain(c="input + 'c'", b="input + 'b'"):
+    """Build a -> b -> c -> d, each node appending its letter to its input."""
+    workflow = Workflow("chain")
+    ids = [
+        workflow.add_node(Node.transform("a", "'a'")),
+        workflow.add_node(Node.transform("b", b)),
+        workflow.add_node(Node.transform("c", c)),
+        workflow.add_node(Node.transform("d", "input + 'd'")),
+    ]
+    for parent, child in zip(ids, ids[1:]):
+        workflow.connect(parent, child)
+    return workflow
+
+
+@pytest.fixture
+def executor():
+    """Executor that never reaches an LLM."""
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"), fail_fast=False)
+
+
+def executed(result):
+    """Names of the nodes of the chain that ran in `result`."""
+    return [name for name in "abcd" if result.get_node_attempts(name) is not None]
+
+
+class TestRerun:
+    """Test Executor.rerun running only the invalidated part of a workflow."""
+
+    def test_rerun_from_middle_of_chain(self, executor):
+        """Test re-running from c running c and d only, with a and b reused."""
+        result = executor.execute(chain())
+        assert result.get_node_output("d") == "abcd"
+
+        rerun = executor.rerun(chain(c="input + 'C'"), result, from_nodes=["c"])
+
+        assert rerun.is_success(), rerun.state()
+        assert executed(rerun) == ["c", "d"]
+        assert rerun.get_reused_nodes() == ["a", "b"]
+        assert rerun.get_node_output("b") == "ab"
+        assert rerun.get_node_output("d") == "abCd"
+
+    def test_failed_node_runs_again(self, executor):
+        """Test a node without an output in the earlier result running again with its dependents."""
+        result = executor.execute(chain(b="1 / 0"))
+        assert not result.is_success()
+
+        rerun = executor.rerun(chain(), result, from_nodes=["d"])
+
+        assert rerun.is_success(), rerun.state()
+        assert executed(rerun) == ["b", "c", "d"]
+        assert rerun.get_reused_nodes() == ["a"]
+
+    def test_invalid_from_nodes(self, executor):
+        """Test an unknown node in from_nodes being rejected."""
+        result = executor.execute(chain())
+
+        with pytest.raises(Exception, match="no node 'e'"):
+            executor.rerun(chain(), result, from_nodes=["e"])
@@ -0,0 +1,102 @@
+"""Unit tests for archiving workflow results as JSON and reloading them."""
+
+import json
+import os
+import subprocess
+import sys
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow, WorkflowResult
+
+API_KEY = "sk-" + "6" * 48
+
+# Reload an archived result in a fresh interpreter and print what it exposes
+RELOAD_SCRIPT = """
+import json, sys
+from graphbit import WorkflowResult
+
+with open(sys.argv[1], encoding="utf-8") as file:
+    result = WorkflowResult.from_json(file.read())
+print(json.dumps({
+    "success": result.is_success(),
+    "outputs": result.get_all_node_outputs(),
+    "stats": result.get_stats(),
+    "variables": result.get_all_variables(),
+}))
+"""
+
+
+def build():
+    """Workflow `Source -> Shout -> Count` with `Source` reading the `item` input."""
+    workflow = Workflow("archive")
+    workflow.add_node(Node.transform("Source", "vars.item"))
+    workflow.add_node(Node.transform("Shout", "input + '!'"))
+    workflow.add_node(Node.transform("Count", "{'length': len(input)}"))
+    workflow.connect("Source", "Shout")
+    workflow.connect("Shout", "Count")
+    return workflow
+
+
+def execute(item):
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+    result = executor.execute(build(), inputs={"item": item})
+    assert result.is_success(), result.state()
+    return result
+
+
+class TestWorkflowResultJson:
+    """Test WorkflowResult.to_json and WorkflowResult.from_json."""
+
+    def test_archived_run_reloads_in_a_fresh_process(self, tmp_path):
+        """Test a result written to disk and read back by another interpreter keeping its outputs and stats."""
+        result = execute("archive me")
+        path = tmp_path / "run.json"
+        path.write_text(result.to_json(), encoding="utf-8")
+
+        env = dict(os.environ, PYTHONPATH=os.pathsep.join(p for p in sys.path if p))
+        completed = subprocess.run(
+            [sys.executable, "-c", RELOAD_SCRIPT, str(path)],
+            capture_output=True,
+            text=True,
+            env=env,
+            timeout=120,
+        )
+        assert completed.returncode == 0, completed.stderr
+        reloaded = json.loads(completed.stdout)
+
+        assert reloaded["success"] is True
+        assert reloaded["outputs"] == result.get_all_node_outputs()
+        assert reloaded["outputs"]["Shout"] == "archive me!"
+        assert reloaded["stats"] == result.get_stats()
+        assert reloaded["stats"]["total_nodes"] == 3
+        assert reloaded["variables"] == result.get_all_variables()
+
+    def test_round_trip_in_process(self):
+        """Test from_json restoring the state, outputs and report of a result."""
+        result = execute("hello")
+
+        reloaded = WorkflowResult.from_json(result.to_json())
+
+        assert reloaded.is_success()
+        assert reloaded.state() == result.state()
+        assert reloaded.get_node_output("Count") == result.get_node_output("Count")
+        assert reloaded.execution_time_ms() == result.execution_time_ms()
+        assert reloaded.to_report_dict()["nodes"] == result.to_report_dict()["nodes"]
+        assert json.loads(reloaded.to_json()) == json.loads(result.to_json())
+
+    def test_max_value_chars_truncates_long_outputs(self):
+        """Test to_json truncating strings in node outputs longer than max_value_chars."""
+        result = execute("x" * 500)
+
+        archived = json.loads(result.to_json(max_value_chars=50))
+
+        shout = WorkflowResult.from_json(json.dumps(archived)).get_node_output("Shout")
+        assert shout.startswith("x" * 50)
+        assert shout.endswith("[truncated 451 chars]")
+        assert len(json.loads(result.to_json())["node_outputs"]) == len(archived["node_outputs"])
+
+    def test_invalid_json_raises(self):
+        """Test from_json rejecting text that is not a serialized result."""
+        with pytest.raises(RuntimeError):
+            WorkflowResult.from_json('{"state": 3}')
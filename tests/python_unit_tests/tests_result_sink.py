@@ -0,0 +1,73 @@
+"""Unit tests for streaming execution results to JSONL files."""
+
+import json
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "3" * 48
+
+
+def build():
+    """Workflow `Source -> Shout` with `Source` reading the `item` input."""
+    workflow = Workflow("result_sink")
+    workflow.add_node(Node.transform("Source", "vars.item"))
+    workflow.add_node(Node.transform("Shout", "input + '!'"))
+    workflow.connect("Source", "Shout")
+    return workflow
+
+
+def executor():
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+
+
+def read_lines(path):
+    with open(path, encoding="utf-8") as file:
+        return [json.loads(line) for line in file]
+
+
+class TestResultSink:
+    """Test the output_jsonl option of execute_batch and execute_streaming."""
+
+    def test_execute_batch_writes_one_line_per_execution(self, tmp_path):
+        """Test each execution of a batch appending one line with its outputs."""
+        path = tmp_path / "results.jsonl"
+        items = ["a", "b", "c"]
+
+        results = executor().execute_batch(build(), [{"item": item} for item in items], output_jsonl=str(path))
+
+        assert [result.get_node_output("Shout") for result in results] == ["a!", "b!", "c!"]
+        lines = sorted(read_lines(path), key=lambda line: line["index"])
+        assert [line["index"] for line in lines] == [0, 1, 2]
+        for line, item in zip(lines, items):
+            assert line["success"] is True
+            assert line["output"] == {"Source": item, "Shout": item + "!"}
+            assert line["execution_id"]
+
+    def test_execute_batch_without_output_file(self):
+        """Test a batch without output_jsonl returning results in input order."""
+        results = executor().execute_batch(build(), [{"item": "x"}, {"item": "y"}])
+
+        assert [result.get_node_output("Source") for result in results] == ["x", "y"]
+        assert all(result.is_success() for result in results)
+
+    def test_torn_line_from_a_crash_is_repaired(self, tmp_path):
+        """Test an incomplete last line being cut off before new results are appended."""
+        path = tmp_path / "results.jsonl"
+        path.write_text('{"execution_id": "crashed", "succ', encoding="utf-8")
+
+        executor().execute_batch(build(), [{"item": "a"}], output_jsonl=str(path))
+
+        lines = read_lines(path)
+        assert len(lines) == 1
+        assert lines[0]["output"]["Shout"] == "a!"
+
+    def test_execute_streaming_writes_one_line_per_node(self, tmp_path):
+        """Test a streamed run appending one line per completed node."""
+        path = tmp_path / "nodes.jsonl"
+
+        events = list(executor().execute_streaming(build(), output_jsonl=str(path)))
+
+        assert events[-1]["event"] in ("workflow_completed", "workflow_failed")
+        lines = read_lines(path)
+        assert [line["node_name"] for line in lines] == ["Source", "Shout"]
+        assert len({line["execution_id"] for line in lines}) == 1
@@ -0,0 +1,89 @@
+"""Unit tests for the Voyage AI and Jina AI embedding providers against a mocked endpoint."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import EmbeddingClient, EmbeddingConfig
+
+API_KEY = "pa-" + "0" * 40
+
+
+@pytest.fixture
+def server():
+    """Local server answering each embeddings request with a 4-dimensional embedding per input, recording request bodies."""
+    requests = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            requests.append(body)
+            data = [{"object": "embedding", "index": index, "embedding": [float(index)] * 4} for index in range(len(body["input"]))]
+            payload = json.dumps({"object": "list", "data": data, "model": body["model"], "usage": {"total_tokens": 5}}).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    http_server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=http_server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{http_server.server_port}/v1", requests
+    http_server.shutdown()
+
+
+class TestRetrievalEmbeddings:
+    """Test EmbeddingConfig.voyage and EmbeddingConfig.jina with a custom base_url."""
+
+    def test_voyage_batches_at_1000_texts(self, server):
+        """Test that Voyage requests are split at 1,000 texts."""
+        base_url, requests = server
+        client = EmbeddingClient(EmbeddingConfig.voyage(API_KEY, base_url=base_url))
+
+        embeddings = client.embed_many(["hello"] * 2500)
+
+        assert len(embeddings) == 2500
+        assert [len(body["input"]) for body in requests] == [1000, 1000, 500]
+        assert requests[0]["model"] == "voyage-3.5"
+
+    def test_jina_batches_at_2048_texts(self, server):
+        """Test that Jina requests are split at 2,048 texts."""
+        base_url, requests = server
+        client = EmbeddingClient(EmbeddingConfig.jina(API_KEY, base_url=base_url))
+
+        client.embed_many(["hello"] * 2500)
+
+        assert [len(body["input"]) for body in requests] == [2048, 452]
+        assert requests[0]["model"] == "jina-embeddings-v3"
+
+    def test_input_type(self, server):
+        """Test that the configured input type is sent unless a call overrides it."""
+        base_url, requests = server
+        voyage = EmbeddingClient(EmbeddingConfig.voyage(API_KEY, input_type="document", dimensions=256, base_url=base_url))
+        jina = EmbeddingClient(EmbeddingConfig.jina(API_KEY, input_type="document", base_url=base_url))
+
+        voyage.embed("a document")
+        voyage.embed("a query", input_type="query")
+        jina.embed_many(["a document"])
+        jina.embed_many(["a query"], input_type="query")
+
+        assert requests[0]["input_type"] == "document"
+        assert requests[0]["output_dimension"] == 256
+        assert requests[1]["input_type"] == "query"
+        assert requests[2]["task"] == "retrieval.passage"
+        assert requests[3]["task"] == "retrieval.query"
+
+    def test_invalid_input_type(self, server):
+        """Test that input types other than query and document are rejected."""
+        base_url, requests = server
+
+        with pytest.raises(Exception, match="input_type"):
+            EmbeddingConfig.voyage(API_KEY, input_type="passage", base_url=base_url)
+        with pytest.raises(Exception, match="input_type"):
+            EmbeddingClient(EmbeddingConfig.jina(API_KEY, base_url=base_url)).embed("text", input_type="passage")
+        assert requests == []
@@ -0,0 +1,94 @@
+"""Unit tests for recording executor runs in a run history database."""
+
+import datetime
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow, WorkflowResult
+
+API_KEY = "sk-" + "8" * 48
+
+
+def build(name, transformation="'done'"):
+    """Workflow named `name` with a single transform node."""
+    workflow = Workflow(name)
+    workflow.add_node(Node.transform("Step", transformation))
+    return workflow
+
+
+def executor(**kwargs):
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"), **kwargs)
+
+
+class TestRunHistory:
+    """Test Executor(run_history=...), list_runs and prune_runs."""
+
+    def test_runs_persist_across_executors(self, tmp_path):
+        """Test runs recorded by one executor being listed, newest first, by another opening the same database."""
+        path = tmp_path / "runs.db"
+        first = executor(run_history=path)
+        first.execute(build("ingest"))
+        first.execute(build("ingest"))
+
+        second = executor(run_history=str(path))
+        second.execute(build("report"))
+        runs = second.list_runs()
+
+        assert [run["workflow_name"] for run in runs] == ["report", "ingest", "ingest"]
+        assert all(run["state"] == "completed" for run in runs)
+        assert runs[0]["stats"]["total_nodes"] == 1
+        assert runs[0]["error"] is None
+        assert runs[0]["completed_at"] >= runs[0]["started_at"]
+        assert [run["execution_id"] for run in first.list_runs(limit=2)] == [run["execution_id"] for run in runs[:2]]
+
+    def test_list_runs_filters(self, tmp_path):
+        """Test list_runs filtering by workflow name, state and start time."""
+        history = executor(run_history=tmp_path / "runs.db", fail_fast=True)
+        history.execute(build("ingest"))
+        history.execute(build("report"))
+        history.execute(build("broken", "reverse"))
+
+        assert [run["workflow_name"] for run in history.list_runs(workflow_name="ingest")] == ["ingest"]
+        failed = history.list_runs(state="failed")
+        assert [run["workflow_name"] for run in failed] == ["broken"]
+        assert failed[0]["error"]
+
+        an_hour_ago = datetime.datetime.now(datetime.timezone.utc) - datetime.timedelta(hours=1)
+        assert len(history.list_runs(since=an_hour_ago)) == 3
+        assert history.list_runs(until=an_hour_ago) == []
+        assert len(history.list_runs(since=an_hour_ago.replace(tzinfo=None).isoformat(), limit=2)) == 2
+
+        with pytest.raises(ValueError):
+            history.list_runs(state="done")
+        with pytest.raises(ValueError):
+            history.list_runs(since="yesterday")
+
+    def test_prune_runs_removes_reports(self, tmp_path):
+        """Test prune_runs keeping the most recent runs and deleting the archived reports of the others."""
+        reports = tmp_path / "reports"
+        history = executor(run_history=tmp_path / "runs.db", run_history_report_dir=reports)
+        results = [history.execute(build("ingest", f"'{index}'")) for index in range(3)]
+
+        runs = history.list_runs()
+        paths = [run["report_path"] for run in runs]
+        assert sorted(p.name for p in reports.iterdir()) == sorted(f"{run['execution_id']}.json" for run in runs)
+        with open(paths[0], encoding="utf-8") as file:
+            assert WorkflowResult.from_json(file.read()).get_node_output("Step") == results[-1].get_node_output("Step")
+
+        assert history.prune_runs(max_runs=1) == 2
+        assert [run["execution_id"] for run in history.list_runs()] == [runs[0]["execution_id"]]
+        assert [p.name for p in reports.iterdir()] == [f"{runs[0]['execution_id']}.json"]
+        assert history.prune_runs(max_age_seconds=3600) == 0
+        assert history.prune_runs(max_age_seconds=0) == 1
+
+    def test_without_run_history(self, tmp_path):
+        """Test list_runs and prune_runs raising on an executor without run_history, and a report dir requiring it."""
+        plain = executor()
+        plain.execute(build("ingest"))
+
+        with pytest.raises(ValueError):
+            plain.list_runs()
+        with pytest.raises(ValueError):
+            plain.prune_runs(max_runs=1)
+        with pytest.raises(ValueError):
+            executor(run_history_report_dir=tmp_path)
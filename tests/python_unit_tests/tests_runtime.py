@@ -0,0 +1,182 @@
+"""Unit tests for the lazily started, configurable runtime and its fork safety."""
+
+import os
+import subprocess
+import sys
+import textwrap
+
+import pytest
+
+from graphbit import configure_runtime
+
+RUN_WORKFLOW = """
+import os
+
+from graphbit import Executor, LlmConfig, Node, Workflow, configure_runtime, init, runtime_info
+
+
+def run():
+    workflow = Workflow("runtime")
+    workflow.add_node(Node.transform("answer", "'ok'"))
+    result = Executor(LlmConfig.openai("sk-" + "0" * 48, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1")).execute(workflow)
+    assert result.get_node_output("answer") == "ok", result.state()
+
+
+def thread_names(prefix):
+    names = []
+    for task in os.listdir("/proc/self/task"):
+        with open(f"/proc/self/task/{task}/comm") as comm:
+            names.append(comm.read().strip())
+    return sorted(name for name in names if name.startswith(prefix + "-"))
+"""
+
+
+def run_python(code):
+    """Run ``code`` after the RUN_WORKFLOW helpers in a fresh interpreter, since the runtime is process-wide."""
+    script = RUN_WORKFLOW + textwrap.dedent(code)
+    result = subprocess.run([sys.executable, "-c", script], capture_output=True, text=True, timeout=120)
+    assert result.returncode == 0, result.stdout + result.stderr
+    return result.stdout
+
+
+class TestConfigureRuntime:
+    """Test configure_runtime applying before the runtime starts, and only then."""
+
+    def test_runtime_starts_lazily_with_configuration(self):
+        """Test importing graphbit starting no threads, and the runtime starting with the configured threads."""
+        run_python(
+            """
+            assert runtime_info()["started"] is False
+            configure_runtime(worker_threads=3, blocking_threads=5, thread_name_prefix="gbtest")
+            assert thread_names("gbtest") == []
+
+            run()
+
+            info = runtime_info()
+            assert info["started"] is True
+            assert info["pid"] == os.getpid()
+            assert (info["worker_threads"], info["blocking_threads"], info["thread_name_prefix"]) == (3, 5, "gbtest")
+            assert info["fork_restarts"] == 0
+            if os.path.isdir("/proc/self/task"):
+                assert thread_names("gbtest")[:3] == ["gbtest-0", "gbtest-1", "gbtest-2"]
+            """
+        )
+
+    def test_configure_after_init_raises(self):
+        """Test configure_runtime after init() raising with guidance, keeping the running configuration."""
+        out = run_python(
+            """
+            from graphbit.errors import GraphBitError
+
+            configure_runtime(worker_threads=2)
+            init()
+            try:
+                configure_runtime(worker_threads=4)
+            except GraphBitError as error:
+                print(error)
+            assert runtime_info()["worker_threads"] == 2
+            """
+        )
+        assert "already started" in out
+        assert "Call configure_runtime() before init()" in out
+
+    def test_configure_after_first_use_raises(self):
+        """Test configure_runtime after a workflow ran raising, since the workflow started the runtime."""
+        out = run_python(
+            """
+            from graphbit.errors import GraphBitError
+
+            run()
+            try:
+                configure_runtime(thread_name_prefix="late")
+            except GraphBitError as error:
+                print(type(error).__name__)
+            assert runtime_info()["thread_name_prefix"] == "graphbit-py"
+            """
+        )
+        assert out.strip() == "GraphBitError"
+
+    def test_last_configuration_before_start_wins(self):
+        """Test configuring the runtime twice before it starts."""
+        run_python(
+            """
+            configure_runtime(worker_threads=2)
+            configure_runtime(worker_threads=3)
+            run()
+            assert runtime_info()["worker_threads"] == 3
+            """
+        )
+
+    def test_invalid_options(self):
+        """Test invalid options being rejected whether or not the runtime has started."""
+        with pytest.raises(ValueError, match="not both"):
+            configure_runtime(blocking_threads=4, max_blocking_threads=4)
+        with pytest.raises(ValueError, match="blocking_threads must be positive"):
+            configure_runtime(blocking_threads=0)
+        with pytest.raises(ValueError, match="thread_name_prefix"):
+            configure_runtime(thread_name_prefix=" ")
+
+
+@pytest.mark.skipif(not hasattr(os, "fork"), reason="os.fork is not available")
+class TestFork:
+    """Test forked worker processes starting their own runtime."""
+
+    def test_preloaded_master_forks_workers(self):
+        """Test gunicorn-style workers forked after the master ran a workflow running workflows on a new runtime."""
+        run_python(
+            """
+            configure_runtime(worker_threads=2, thread_name_prefix="gbfork")
+            init()
+            run()
+            master_pid = runtime_info()["pid"]
+
+            workers = []
+            for _ in range(2):
+                pid = os.fork()
+                if pid == 0:
+                    code = 1
+                    try:
+                        assert runtime_info()["started"] is False
+                        run()
+                        info = runtime_info()
+                        assert info["pid"] == os.getpid() != master_pid
+                        assert (info["worker_threads"], info["thread_name_prefix"]) == (2, "gbfork")
+                        assert info["fork_restarts"] == 1
+                        code = 0
+                    finally:
+                        os._exit(code)
+                workers.append(pid)
+
+            for pid in workers:
+                _, status = os.waitpid(pid, 0)
+                assert os.waitstatus_to_exitcode(status) == 0
+
+            # The master keeps its runtime
+            run()
+            assert runtime_info()["pid"] == master_pid
+            assert runtime_info()["fork_restarts"] == 0
+            """
+        )
+
+    def test_fork_before_first_use(self):
+        """Test a child forked before the runtime started starting it normally, with the configuration set before the fork."""
+        run_python(
+            """
+            configure_runtime(worker_threads=2)
+            pid = os.fork()
+            if pid == 0:
+                code = 1
+                try:
+                    run()
+                    info = runtime_info()
+                    assert info["pid"] == os.getpid()
+                    assert info["worker_threads"] == 2
+                    assert info["fork_restarts"] == 0
+                    code = 0
+                finally:
+                    os._exit(code)
+            _, status = os.waitpid(pid, 0)
+            assert os.waitstatus_to_exitcode(status) == 0
+            assert runtime_info()["started"] is False
+            """
+        )
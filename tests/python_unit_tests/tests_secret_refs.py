@@ -0,0 +1,123 @@
+"""Unit tests for API key references and redaction of resolved keys."""
+
+import os
+import pickle
+import stat
+import sys
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import EmbeddingConfig, Executor, LlmConfig, Node, Workflow, forget_api_key
+
+SENTINEL = "sk-sentinel-" + "5" * 40
+
+
+@pytest.fixture
+def echoing_server():
+    """Local server rejecting every request with a 401 echoing its Authorization header, recording the headers."""
+    seen = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers.get("Content-Length") or 0))
+            authorization = self.headers.get("Authorization", "")
+            seen.append(authorization)
+            payload = ('{"error": {"message": "Invalid credentials: %s"}}' % authorization).encode()
+            self.send_response(401)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}/v1", seen
+    server.shutdown()
+
+
+class TestSecretRefs:
+    """Test api_key references such as env:NAME being resolved lazily and never stored."""
+
+    def test_references_skip_key_length_checks(self):
+        """Test references being accepted where a literal key of that length would be too short."""
+        LlmConfig.openai(api_key="env:OPENAI_API_KEY")
+        LlmConfig.anthropic("file:/run/secrets/anthropic")
+        EmbeddingConfig.openai("keyring:graphbit/openai")
+
+        with pytest.raises(ValueError, match="too short"):
+            LlmConfig.openai("sk-short")
+        with pytest.raises(ValueError, match="keyring:service/user"):
+            LlmConfig.openai("keyring:graphbit")
+
+    def test_pickled_config_keeps_the_reference(self, monkeypatch):
+        """Test a pickled config holding the reference rather than the key."""
+        monkeypatch.setenv("GRAPHBIT_TEST_SENTINEL_KEY", SENTINEL)
+        data = pickle.dumps(LlmConfig.openai("env:GRAPHBIT_TEST_SENTINEL_KEY", "gpt-4o-mini"))
+
+        assert b"env:GRAPHBIT_TEST_SENTINEL_KEY" in data
+        assert SENTINEL.encode() not in data
+        assert pickle.loads(data).model() == "gpt-4o-mini"
+
+    def test_sentinel_key_never_leaves_the_reference(self, monkeypatch, echoing_server):
+        """Test the key reaching the provider but not results, reports or errors, even when the provider echoes it."""
+        monkeypatch.setenv("GRAPHBIT_TEST_SENTINEL_KEY", SENTINEL)
+        url, seen = echoing_server
+        config = LlmConfig.openai("env:GRAPHBIT_TEST_SENTINEL_KEY", "gpt-4o-mini", base_url=url)
+        workflow = Workflow("sentinel")
+        workflow.add_node(Node.agent("Summarize", "Summarize the news"))
+
+        result = Executor(config, capture_payloads=True).execute(workflow)
+
+        assert seen and seen[0] == f"Bearer {SENTINEL}"
+        assert not result.is_success()
+        for text in [
+            result.to_json(),
+            str(result.to_report_dict(include_payloads=True)),
+            str(result.get_failure_reason()),
+            result.state(),
+            pickle.dumps(workflow).decode("latin-1"),
+        ]:
+            assert SENTINEL not in text
+
+    def test_unset_variable_fails_at_execution(self):
+        """Test a reference to an unset variable failing the run with a message naming it."""
+        config = LlmConfig.openai("env:GRAPHBIT_TEST_UNSET_KEY", "gpt-4o-mini", base_url="http://127.0.0.1:9/v1")
+        workflow = Workflow("unset")
+        workflow.add_node(Node.agent("Summarize", "Summarize the news"))
+
+        try:
+            result = Executor(config).execute(workflow)
+        except Exception as error:
+            message = str(error)
+        else:
+            assert not result.is_success()
+            message = result.to_json()
+        assert "'GRAPHBIT_TEST_UNSET_KEY' is not set" in message
+
+    @pytest.mark.skipif(sys.platform == "win32", reason="the keyring is read through a shell tool")
+    def test_keyring_key_is_reused_until_forgotten(self, monkeypatch, tmp_path, echoing_server):
+        """Test a keyring key being read once for several runs and again after forget_api_key."""
+        calls = tmp_path / "calls"
+        tool = tmp_path / ("security" if sys.platform == "darwin" else "secret-tool")
+        tool.write_text(f"#!/bin/sh\necho call >> '{calls}'\necho '{SENTINEL}'\n")
+        tool.chmod(tool.stat().st_mode | stat.S_IEXEC)
+        monkeypatch.setenv("PATH", f"{tmp_path}{os.pathsep}{os.environ.get('PATH', '')}")
+        url, seen = echoing_server
+        reference = "keyring:graphbit-python-test/openai"
+        config = LlmConfig.openai(reference, "gpt-4o-mini", base_url=url)
+        workflow = Workflow("keyring")
+        workflow.add_node(Node.agent("Summarize", "Summarize the news"))
+
+        for _ in range(2):
+            Executor(config).execute(workflow)
+        assert len(calls.read_text().splitlines()) == 1
+
+        forget_api_key(reference)
+        Executor(config).execute(workflow)
+        assert len(calls.read_text().splitlines()) == 2
+        assert seen and all(header == f"Bearer {SENTINEL}" for header in seen)
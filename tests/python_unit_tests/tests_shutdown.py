@@ -0,0 +1,98 @@
+"""Unit tests for the graceful shutdown of an executor."""
+
+import threading
+import time
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "4" * 48
+
+
+def delayed_workflow(seconds):
+    """Build a workflow waiting `seconds` between two transform steps."""
+    workflow = Workflow("nightly")
+    prepare = workflow.add_node(Node.transform("prepare", "'ready'"))
+    wait = workflow.add_node(Node.delay("wait", seconds))
+    publish = workflow.add_node(Node.transform("publish", "'published'"))
+    workflow.connect(prepare, wait)
+    workflow.connect(wait, publish)
+    return workflow
+
+
+def run_in_thread(executor, workflow):
+    """Start executing `workflow` in a thread and return the thread and its result holder."""
+    results = []
+    worker = threading.Thread(target=lambda: results.append(executor.execute(workflow)))
+    worker.start()
+    time.sleep(0.3)
+    return worker, results
+
+
+class TestExecutorShutdown:
+    """Test Executor.shutdown."""
+
+    def test_running_execution_is_cancelled(self, tmp_path):
+        """Test a run outlasting the timeout being cancelled and archived."""
+        executor = Executor(
+            LlmConfig.openai(API_KEY, "gpt-4o-mini"),
+            run_history=str(tmp_path / "runs.db"),
+            run_history_report_dir=str(tmp_path / "runs"),
+        )
+        worker, results = run_in_thread(executor, delayed_workflow(60))
+
+        summary = executor.shutdown(timeout=0.1)
+        worker.join(timeout=10)
+
+        result = results[0]
+        assert result.is_cancelled()
+        assert not result.is_failed()
+        assert result.state() == "Cancelled"
+        assert result.get_abandoned_nodes() == {"wait": "cancelled", "publish": "skipped"}
+        assert summary["completed"] == 0
+        [interrupted] = summary["interrupted"]
+        assert interrupted["workflow_name"] == "nightly"
+        assert interrupted["cancelled_nodes"] == ["wait"]
+        assert interrupted["skipped_nodes"] == ["publish"]
+        assert interrupted["checkpoint"].startswith(str(tmp_path / "runs"))
+        assert executor.list_runs()[0]["state"] == "cancelled"
+
+    def test_execution_within_timeout_completes(self):
+        """Test a run finishing within the timeout being counted as completed."""
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"))
+        worker, results = run_in_thread(executor, delayed_workflow(1))
+
+        summary = executor.shutdown(timeout=10)
+        worker.join(timeout=10)
+
+        assert results[0].is_success()
+        assert summary["completed"] == 1
+        assert summary["interrupted"] == []
+
+    def test_runs_after_shutdown_are_rejected(self):
+        """Test executions started after the shutdown raising."""
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"))
+        executor.shutdown(timeout=0)
+
+        with pytest.raises(Exception, match="shutting down"):
+            executor.execute(delayed_workflow(0))
+
+    def test_invalid_timeout(self):
+        """Test a negative timeout being rejected."""
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"))
+
+        with pytest.raises(ValueError, match="non-negative number of seconds"):
+            executor.shutdown(timeout=-1)
+        with pytest.raises(ValueError, match="non-negative number of seconds"):
+            executor.shutdown_on_exit(timeout=-1)
+
+    def test_shutdown_on_exit_registers_handler(self, monkeypatch):
+        """Test shutdown_on_exit registering the shutdown with atexit."""
+        registered = []
+        monkeypatch.setattr("atexit.register", lambda func, **kwargs: registered.append(kwargs))
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini"))
+
+        executor.shutdown_on_exit(timeout=5)
+
+        assert registered == [{"timeout": 5}]
@@ -0,0 +1,131 @@
+"""Tests keeping the graphbit.pyi type stubs in sync with the compiled module."""
+
+import ast
+import inspect
+import re
+from pathlib import Path
+
+import pytest
+
+import graphbit
+from graphbit import graphbit as native
+
+STUB_PATH = Path(graphbit.__file__).with_name("graphbit.pyi")
+
+# Dunder methods that carry user-visible behavior and therefore need a stub entry
+_STUBBED_DUNDERS = {"__call__", "__bool__", "__enter__", "__exit__", "__add__", "__iadd__", "__iter__", "__next__", "__aiter__", "__anext__"}
+
+
+def _parameters(args):
+    """Return (name, has_default) pairs of an ast.arguments, without self."""
+    positional = args.posonlyargs + args.args
+    defaults = [False] * (len(positional) - len(args.defaults)) + [True] * len(args.defaults)
+    params = [(arg.arg, has_default) for arg, has_default in zip(positional, defaults)]
+    params += [(arg.arg, default is not None) for arg, default in zip(args.kwonlyargs, args.kw_defaults)]
+    if args.vararg:
+        params.append(("*" + args.vararg.arg, False))
+    if args.kwarg:
+        params.append(("**" + args.kwarg.arg, False))
+    return [p for p in params if p[0] != "self"]
+
+
+def _runtime_parameters(text_signature):
+    """Parse a pyo3 ``__text_signature__`` the same way as a stub signature."""
+    signature = re.sub(r"\$\w+,?\s*", "", text_signature)
+    signature = re.sub(r"(,\s*)?/\s*(?=[,)])", "", signature).replace("(, ", "(")
+    function = ast.parse(f"def f{signature}: pass").body[0]
+    return _parameters(function.args)
+
+
+def _load_stub():
+    tree = ast.parse(STUB_PATH.read_text())
+    functions, classes = {}, {}
+    for node in tree.body:
+        if isinstance(node, ast.FunctionDef):
+            functions[node.name] = _parameters(node.args)
+        elif isinstance(node, ast.ClassDef):
+            members = {}
+            for item in node.body:
+                if isinstance(item, ast.FunctionDef):
+                    members[item.name] = _parameters(item.args)
+                elif isinstance(item, ast.AnnAssign):
+                    members[item.target.id] = None
+            classes[node.name] = members
+        elif isinstance(node, ast.AnnAssign):
+            functions[node.target.id] = None
+    return functions, classes
+
+
+def _runtime_members(cls):
+    for name, value in vars(cls).items():
+        if name.startswith("_") and name not in _STUBBED_DUNDERS:
+            continue
+        yield name, getattr(cls, name), value
+
+
+@pytest.fixture(scope="module")
+def stub():
+    return _load_stub()
+
+
+class TestTypeStubs:
+    """Every public class, method and keyword argument must have a stub entry."""
+
+    def test_stub_ships_with_package(self):
+        """Test that the stub and py.typed marker live next to the package."""
+        assert STUB_PATH.is_file()
+        assert STUB_PATH.with_name("py.typed").is_file()
+
+    def test_module_level_names_are_stubbed(self, stub):
+        """Test that every public function, class and attribute has a stub."""
+        functions, classes = stub
+        for name in dir(native):
+            if name.startswith("_") and name not in ("__version__", "__author__", "__description__"):
+                continue
+            obj = getattr(native, name)
+            if inspect.ismodule(obj):
+                continue
+            if inspect.isclass(obj):
+                assert name in classes, f"class {name} has no stub"
+            else:
+                assert name in functions, f"{name} has no stub"
+                if callable(obj) and obj.__text_signature__:
+                    assert functions[name] == _runtime_parameters(obj.__text_signature__), f"stub signature of {name}() is out of date"
+
+    def test_stubs_do_not_reference_removed_names(self, stub):
+        """Test that the stub does not declare public names missing from the module."""
+        functions, classes = stub
+        for name in list(functions) + list(classes):
+            if not name.startswith("_"):
+                assert hasattr(native, name), f"stub declares {name}, which the module does not define"
+
+    def test_class_members_are_stubbed(self, stub):
+        """Test that every method, property and keyword argument of every class is stubbed."""
+        _, classes = stub
+        for class_name in dir(native):
+            cls = getattr(native, class_name)
+            if not inspect.isclass(cls) or class_name.startswith("_"):
+                continue
+            members = classes[class_name]
+
+            if cls.__text_signature__ is not None:
+                assert "__init__" in members, f"{class_name}.__init__ has no stub"
+                assert members["__init__"] == _runtime_parameters(cls.__text_signature__), f"stub signature of {class_name}() is out of date"
+
+            for name, bound, raw in _runtime_members(cls):
+                assert name in members, f"{class_name}.{name} has no stub"
+                text_signature = getattr(bound, "__text_signature__", None)
+                # Properties and slot wrappers (``__add__`` etc.) have no meaningful parameter names
+                is_data = type(raw).__name__ in ("getset_descriptor", "member_descriptor")
+                is_slot = type(raw).__name__ == "wrapper_descriptor"
+                if is_data or is_slot or text_signature is None or "*args" in text_signature:
+                    continue
+                assert members[name] == _runtime_parameters(text_signature), f"stub signature of {class_name}.{name}() is out of date"
+
+            for name in members:
+                if not name.startswith("_"):
+                    assert hasattr(cls, name), f"stub declares {class_name}.{name}, which the class does not define"
+
+    def test_error_classes_are_typed(self):
+        """Test that the pure-Python exception hierarchy is importable from the package."""
+        assert graphbit.errors.RateLimitError.__init__.__annotations__["retry_after"]
@@ -0,0 +1,62 @@
+"""Unit tests for selecting and skipping workflow nodes by tag at execution time."""
+
+import asyncio
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "3" * 48
+
+
+def build(enrich_tags):
+    """Workflow `Source -> Enrich -> Report` with `Enrich` carrying `enrich_tags`."""
+    workflow = Workflow("tag_filter")
+    workflow.add_node(Node.transform("Source", "'order'", tags=["core"]))
+    workflow.add_node(Node.transform("Enrich", "input + ' with customer data'", tags=enrich_tags))
+    workflow.add_node(Node.transform("Report", "'report of ' + input", tags=["core"]))
+    workflow.connect("Source", "Enrich")
+    workflow.connect("Enrich", "Report")
+    return workflow
+
+
+def executor():
+    return Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+
+
+class TestTagFilter:
+    """Test the only_tags and skip_tags execution options."""
+
+    def test_skip_tags_skips_the_node_and_its_dependents(self):
+        """Test a skipped middle node skipping the node depending on it."""
+        result = executor().execute(build(["expensive"]), skip_tags=["expensive"])
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Source") == "order"
+        assert result.get_node_output("Report") is None
+        assert result.get_abandoned_nodes() == {"Enrich": "skipped", "Report": "skipped"}
+
+    def test_passthrough_on_skip_passes_the_input_on(self):
+        """Test a skipped middle node tagged passthrough_on_skip handing its input to its dependent."""
+        workflow = build(["expensive", "passthrough_on_skip"])
+        result = executor().execute(workflow, skip_tags=["expensive"])
+
+        assert result.is_success(), result.state()
+        assert result.get_node_output("Enrich") == "order"
+        assert result.get_node_output("Report") == "report of order"
+        assert result.get_abandoned_nodes() == {"Enrich": "skipped"}
+
+    def test_only_tags_runs_the_selected_nodes(self):
+        """Test only_tags excluding the untagged node on run_async."""
+        async def run():
+            return await executor().run_async(build(["passthrough_on_skip"]), only_tags=["core"])
+
+        result = asyncio.run(run())
+
+        assert result.get_node_output("Report") == "report of order"
+        assert result.get_abandoned_nodes() == {"Enrich": "skipped"}
+
+    def test_no_tag_filter_runs_every_node(self):
+        """Test tags having no effect without only_tags or skip_tags."""
+        result = executor().execute(build(["expensive"]))
+
+        assert result.get_node_output("Report") == "report of order with customer data"
+        assert result.get_abandoned_nodes() == {}
@@ -2,7 +2,9 @@
 
 import pytest  # noqa: F401
 
-from graphbit import CharacterSplitter, RecursiveSplitter, SentenceSplitter, TextChunk, TextSplitter, TextSplitterConfig, TokenSplitter
+import os
+
+from graphbit import CharacterSplitter, DocumentLoader, RecursiveSplitter, SentenceSplitter, TextChunk, TextSplitter, TextSplitterConfig, TokenSplitter, validate_json
 
 
 class TestTextSplitterConfig:
@@ -165,6 +167,21 @@ class TestSentenceSplitter:
             sentences = [s.strip() for s in chunk.content.split(".") if s.strip()]
             assert len(sentences) <= 2
 
+    def test_abbreviations_and_decimals_do_not_end_sentences(self):
+        """Test titles, added abbreviations and decimal numbers being kept inside their sentence."""
+        splitter = SentenceSplitter(chunk_size=1, sentence_endings=["\\.", "!", "\\?"], abbreviations=["cl."])
+        chunks = splitter.split_text("Dr. Smith cites cl. 4 of the lease. The rate is 2.5 percent!")
+
+        assert [chunk.content for chunk in chunks] == ["Dr. Smith cites cl. 4 of the lease.", "The rate is 2.5 percent!"]
+
+    def test_unicode_segmentation(self):
+        """Test Unicode segmentation finding boundaries the ending patterns do not cover."""
+        text = "यह पहला वाक्य है। यह दूसरा वाक्य है।"
+        assert len(SentenceSplitter(chunk_size=1).split_text(text)) == 1
+
+        splitter = TextSplitter.sentence(chunk_size=1, unicode_segmentation=True)
+        assert [chunk.content for chunk in splitter.split_text(text)] == ["यह पहला वाक्य है।", "यह दूसरा वाक्य है।"]
+
 
 class TestRecursiveSplitter:
     """Test recursive splitter functionality."""
@@ -256,3 +273,137 @@ class TestTextSplitter:
             assert "start_index" in doc
             assert "end_index" in doc
             assert "chunk_index" in doc
+
+    def test_text_splitter_factories(self):
+        """Test the strategy shortcuts on TextSplitter."""
+        text = "First sentence here. Second sentence here. Third one."
+        for splitter in [
+            TextSplitter.character(20, 5),
+            TextSplitter.recursive(20),
+            TextSplitter.sentence(30),
+            TextSplitter.token(4, 1),
+        ]:
+            chunks = splitter.split_text(text)
+            assert len(chunks) > 1
+            assert all(isinstance(chunk, TextChunk) for chunk in chunks)
+
+        with pytest.raises(ValueError):
+            TextSplitter.character(0)
+
+    def test_text_splitter_round_trip(self):
+        """Test that non-overlapping chunks cover the original text."""
+        text = "GraphBit splits text. Every chunk keeps its offsets. Nothing is lost."
+        chunks = TextSplitter.sentence(25).split_text(text)
+        assert [chunk.chunk_index for chunk in chunks] == list(range(len(chunks)))
+        for chunk in chunks:
+            assert text[chunk.start_index : chunk.end_index].strip() == chunk.content
+        assert " ".join(chunk.content for chunk in chunks) == text
+
+    def test_split_document(self):
+        """Test splitting a loaded document keeps its source in chunk metadata."""
+        path = os.path.join(os.path.dirname(__file__), "fixtures", "sample.pdf")
+        document = DocumentLoader().load_document(path)
+        chunks = TextSplitter.character(20).split_document(document)
+
+        assert len(chunks) > 1
+        for chunk in chunks:
+            assert chunk.metadata["source"] == path
+            assert chunk.metadata["document_type"] == "pdf"
+
+    def test_auto(self):
+        """Test that TextSplitter.auto picks the splitter by document type and records it in chunk metadata."""
+        fixtures = os.path.dirname(__file__)
+        documents = os.path.join(fixtures, "..", "rust_unit_tests", "fixtures", "documents")
+        cases = [
+            (os.path.join(fixtures, "fixtures", "sample.pdf"), "pdf", "sentence"),
+            (os.path.join(documents, "report.txt"), "txt", "sentence"),
+            (os.path.join(documents, "records.json"), "json", "recursive"),
+        ]
+
+        for path, document_type, splitter in cases:
+            document = DocumentLoader().load_document(path, document_type)
+            chunks = TextSplitter.auto(document, chunk_size=100, chunk_overlap=0)
+
+            assert chunks
+            for chunk in chunks:
+                assert chunk.metadata["splitter"] == splitter
+                assert chunk.metadata["source"] == path
+                assert chunk.metadata["document_type"] == document_type
+
+    def test_markdown_and_code_configs(self):
+        """Test that Markdown and code configurations build working splitters."""
+        markdown = TextSplitter(TextSplitterConfig.markdown(100, 0, True))
+        chunks = markdown.split_text("# Intro\n\nHello.\n\n## Usage\n\nRun it.")
+        assert [chunk.metadata["heading"] for chunk in chunks] == ["Intro", "Usage"]
+
+        code = TextSplitter(TextSplitterConfig.code(40, 0, "python"))
+        chunks = code.split_text("def first():\n    return 1\n\n\ndef second():\n    return 2\n")
+        assert [chunk.content.strip() for chunk in chunks] == ["def first():\n    return 1", "def second():\n    return 2"]
+        assert chunks[0].metadata["language"] == "python"
+
+
+class TestValidation:
+    """Test JSON schema validation."""
+
+    def test_validate_json(self):
+        """Test validating JSON against a schema."""
+        schema = {"type": "object", "properties": {"name": {"type": "string"}}, "required": ["name"]}
+        assert validate_json('{"name": "Ada"}', schema).is_valid
+
+        result = validate_json('{"name": 42}', schema)
+        assert not result
+        assert result.errors[0]["error_code"] == "TYPE_MISMATCH"
+        assert result.errors[0]["field_path"] == "root.name"
+
+        assert not validate_json("not json", schema).is_valid
+
+    def test_validate_json_nested_objects(self):
+        """Test failure paths inside nested objects."""
+        schema = {
+            "type": "object",
+            "properties": {
+                "user": {
+                    "type": "object",
+                    "properties": {"email": {"type": "string", "pattern": "@"}, "age": {"type": "integer", "minimum": 0}},
+                    "required": ["email"],
+                    "additionalProperties": False,
+                }
+            },
+        }
+        assert validate_json({"user": {"email": "a@b.c", "age": 3}}, schema)
+
+        result = validate_json({"user": {"age": -1, "nick": "x"}}, schema)
+        failures = {(e["field_path"], e["error_code"]) for e in result.errors}
+        assert failures == {
+            ("root.user.email", "MISSING_REQUIRED_PROPERTY"),
+            ("root.user.age", "NUMBER_TOO_SMALL"),
+            ("root.user.nick", "UNKNOWN_PROPERTY"),
+        }
+
+    def test_validate_json_array_constraints(self):
+        """Test item, length and uniqueness constraints on arrays."""
+        schema = {"type": "array", "items": {"type": "string"}, "minItems": 1, "maxItems": 3, "uniqueItems": True}
+        assert validate_json(["a", "b"], schema).is_valid
+
+        assert [e["error_code"] for e in validate_json([], schema).errors] == ["ARRAY_TOO_SHORT"]
+        result = validate_json(["a", 1, "a", "b"], schema)
+        failures = {(e["field_path"], e["error_code"]) for e in result.errors}
+        assert failures == {("root", "ARRAY_TOO_LONG"), ("root[2]", "DUPLICATE_ITEMS"), ("root[1]", "TYPE_MISMATCH")}
+
+    def test_validate_json_refs_and_unsupported_keywords(self):
+        """Test local references and errors for keywords outside draft-07."""
+        schema = {
+            "definitions": {"tag": {"type": "string", "maxLength": 3}},
+            "type": "object",
+            "properties": {"tags": {"type": "array", "items": {"$ref": "#/definitions/tag"}}},
+        }
+        assert validate_json({"tags": ["abc"]}, schema)
+        result = validate_json({"tags": ["abcd"]}, schema)
+        assert [(e["field_path"], e["error_code"]) for e in result.errors] == [("root.tags[0]", "STRING_TOO_LONG")]
+
+        result = validate_json([], {"prefixItems": [{"type": "string"}]})
+        assert result.errors[0]["error_code"] == "UNSUPPORTED_KEYWORD"
+        assert "prefixItems" in result.errors[0]["message"]
+
+        with pytest.raises(ValueError):
+            validate_json({}, ["not", "a", "schema"])
@@ -0,0 +1,136 @@
+"""Unit tests for running workflows on one executor from several Python threads."""
+
+import json
+import re
+import threading
+import time
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "5" * 48
+
+THREADS = 8
+RUNS_PER_THREAD = 20
+STEPS = ["outline", "draft", "review", "polish", "summary"]
+
+
+@pytest.fixture
+def echo_server():
+    """Local OpenAI-compatible server echoing each prompt back and recording the peak of requests in flight."""
+    lock = threading.Lock()
+    state = {"in_flight": 0, "peak": 0}
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            with lock:
+                state["in_flight"] += 1
+                state["peak"] = max(state["peak"], state["in_flight"])
+            time.sleep(0.01)
+            completion = {
+                "id": "chatcmpl-1",
+                "choices": [{"index": 0, "message": {"role": "assistant", "content": body["messages"][-1]["content"]}, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 5, "total_tokens": 10},
+            }
+            payload = json.dumps(completion).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+            with lock:
+                state["in_flight"] -= 1
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    server.daemon_threads = True
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", state
+    server.shutdown()
+
+
+def five_step_workflow():
+    workflow = Workflow("pipeline")
+    previous = None
+    for step in STEPS:
+        node = workflow.add_node(Node.agent(step, f"{step} the piece on {{{{input.topic}}}}"))
+        if previous is not None:
+            workflow.connect(previous, node)
+        previous = node
+    return workflow
+
+
+def run_threads(target):
+    errors = []
+
+    def guarded(index):
+        try:
+            target(index)
+        except BaseException as error:  # noqa: B036 - reported to the main thread
+            errors.append(error)
+
+    threads = [threading.Thread(target=guarded, args=(index,)) for index in range(THREADS)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join(timeout=300)
+    assert not any(thread.is_alive() for thread in threads)
+    assert not errors, errors[0]
+
+
+class TestThreadSafety:
+    """Test executions from several threads sharing one executor."""
+
+    def test_stress_parallel_executions_stay_isolated(self, echo_server):
+        """Test 8 threads each running a 5-node workflow 20 times, every run seeing only its own inputs."""
+        url, _ = echo_server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"))
+        workflow = five_step_workflow()
+
+        def worker(index):
+            for run in range(RUNS_PER_THREAD):
+                topic = f"topic-{index}-{run}"
+                result = executor.execute(workflow, inputs={"topic": topic})
+                assert result.is_success(), result.state()
+                for step in STEPS:
+                    output = result.get_node_output(step)
+                    assert set(re.findall(r"topic-\d+-\d+", output)) == {topic}, output
+
+        run_threads(worker)
+
+        stats = executor.get_stats()
+        assert stats["total_executions"] == THREADS * RUNS_PER_THREAD
+        assert stats["successful_executions"] == THREADS * RUNS_PER_THREAD
+        assert executor.stats()["current_active_tasks"] == 0
+
+    def test_executions_run_in_parallel(self, echo_server):
+        """Test threads executing at the same time, the GIL being released while they wait."""
+        url, state = echo_server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"))
+        workflow = five_step_workflow()
+
+        run_threads(lambda index: executor.execute(workflow, inputs={"topic": f"topic-{index}-0"}))
+
+        assert state["peak"] > 1
+
+    def test_batch_and_single_executions_share_an_executor(self, echo_server):
+        """Test execute_batch on one thread while execute runs on others."""
+        url, _ = echo_server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o", base_url=f"{url}/v1"))
+        workflow = five_step_workflow()
+
+        def worker(index):
+            if index == 0:
+                results = executor.execute_batch(workflow, [{"topic": f"topic-0-{run}"} for run in range(4)])
+                assert all(result.is_success() for result in results)
+            else:
+                assert executor.execute(workflow, inputs={"topic": f"topic-{index}-0"}).is_success()
+
+        run_threads(worker)
+
+        assert executor.get_stats()["total_executions"] == 4 + THREADS - 1
@@ -0,0 +1,122 @@
+"""Unit tests for tools reading and writing workflow state through a ToolContext."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, ToolContext, Workflow, tool
+from graphbit.errors import ValidationError
+
+API_KEY = "sk-" + "0" * 48
+
+
+@pytest.fixture
+def llm_server():
+    """Local OpenAI-compatible server asking Research nodes for the scripted tool calls, recording every prompt."""
+    received = []
+    scripted = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            prompt = next(m["content"] for m in body["messages"] if m["role"] == "user")
+            received.append(prompt)
+            called_tools = any(m["role"] == "tool" for m in body["messages"])
+            if "Task: Research" in prompt and not called_tools:
+                calls = [
+                    {"id": f"call_{i}", "type": "function", "function": {"name": name, "arguments": json.dumps(arguments)}}
+                    for i, (name, arguments) in enumerate(scripted)
+                ]
+                message = {"role": "assistant", "content": None, "tool_calls": calls}
+                finish_reason = "tool_calls"
+            else:
+                message = {"role": "assistant", "content": "Done"}
+                finish_reason = "stop"
+            payload = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", received, scripted
+    server.shutdown()
+
+
+@tool(description="Remember a summary for later nodes")
+def remember_summary(value: str, ctx: ToolContext) -> str:
+    """Store `value`, prefixed with the topic input, as scratch.summary."""
+    ctx.set("scratch.summary", f"{ctx.variable('topic')}: {value}")
+    assert ctx.get("scratch.summary") == f"{ctx.variable('topic')}: {value}"
+    return "remembered"
+
+
+@tool(description="Fail after writing")
+def fail_after_writing(ctx: ToolContext) -> str:
+    """Write scratch.summary, then fail."""
+    ctx.set("scratch.summary", "partial")
+    raise RuntimeError("boom")
+
+
+def run(url, tools):
+    workflow = Workflow("report")
+    research = workflow.add_node(Node.agent("Research", "Task: Research", tools=tools))
+    write = workflow.add_node(Node.agent("Write", "Task: Write about {{scratch.summary}}"))
+    workflow.connect(research, write)
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=f"{url}/v1"))
+    return executor.execute(workflow, inputs={"topic": "GraphBit"})
+
+
+class TestToolContext:
+    """Test tools reading and writing workflow state."""
+
+    def test_tool_writes_reach_later_prompts(self, llm_server):
+        """Test that a value a tool writes is substituted into a later node's prompt."""
+        url, received, scripted = llm_server
+        scripted.append(("remember_summary", {"value": "fast"}))
+
+        result = run(url, [remember_summary])
+        assert result.is_success()
+        assert any(prompt.endswith("Task: Write about GraphBit: fast") for prompt in received), received
+
+    def test_last_tool_call_wins(self, llm_server):
+        """Test that the last of several writes to a key wins."""
+        url, received, scripted = llm_server
+        scripted.extend([("remember_summary", {"value": "fast"}), ("remember_summary", {"value": "typed"})])
+
+        assert run(url, [remember_summary]).is_success()
+        assert any(prompt.endswith("Task: Write about GraphBit: typed") for prompt in received), received
+
+    def test_failed_tool_writes_are_dropped(self, llm_server):
+        """Test that the writes of a failing tool call are not merged."""
+        url, received, scripted = llm_server
+        scripted.append(("fail_after_writing", {}))
+
+        assert run(url, [fail_after_writing]).is_success()
+        assert not any("partial" in prompt for prompt in received), received
+
+    def test_context_parameter_is_not_in_the_schema(self):
+        """Test that the ctx parameter is hidden from the model."""
+        node = Node.agent("Research", "Task: Research", tools=[remember_summary])
+        schemas = json.loads(node.get_config())["tool_schemas"]
+        schema = next(s for s in schemas if s["name"] == "remember_summary")
+        assert list(schema["parameters"]["properties"]) == ["value"]
+        assert schema["parameters"]["required"] == ["value"]
+
+    def test_keys_must_be_namespaced(self):
+        """Test that writes outside the scratch namespace are rejected."""
+        with pytest.raises(ValidationError, match="scratch.NAME"):
+            ToolContext().set("summary", "value")
@@ -0,0 +1,214 @@
+"""Unit tests for tool parameter schemas generated from type hints and docstrings."""
+
+import json
+import threading
+from dataclasses import dataclass, field
+from enum import Enum
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from typing import Dict, List, Literal, Optional
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow, get_tool_registry, tool
+
+API_KEY = "sk-" + "0" * 48
+
+
+class Priority(Enum):
+    """Shipping priority."""
+
+    LOW = "low"
+    HIGH = "high"
+
+
+@dataclass
+class Address:
+    """Postal address.
+
+    Attributes:
+        street: Street and house number.
+        zip_code: Postal code, if known.
+    """
+
+    street: str
+    zip_code: Optional[str] = None
+
+
+@dataclass
+class Order:
+    """Order to ship."""
+
+    sku: str
+    quantity: int
+    address: Address
+    gift_note: Optional[str] = None
+    tags: List[str] = field(default_factory=list)
+
+
+received_orders = []
+
+
+@tool(description="Ship an order")
+def ship(order: Order, priority: Priority = Priority.LOW, express: bool = False) -> str:
+    """Ship an order.
+
+    Args:
+        order: The order to ship.
+        priority: How urgently to ship it.
+        express: Whether to use express delivery,
+            at extra cost.
+    """
+    received_orders.append(order)
+    return f"{type(order).__name__} to {type(order.address).__name__} {order.address.street}"
+
+
+@tool(description="Search the catalog")
+def search(query: str, limit: int = 10, sort: Literal["price", "rating"] = "price", filters: Optional[Dict[str, float]] = None) -> str:
+    """Search the catalog.
+
+    Parameters
+    ----------
+    query : str
+        Free-text query,
+        matched against titles.
+    limit : int
+        Maximum number of results.
+
+    Returns
+    -------
+    str
+        Matching titles.
+    """
+    return query
+
+
+def tool_schema(func, name):
+    """Return the parameter schema an agent node sends for `func`."""
+    node = Node.agent("Research", "Task: Research", tools=[func])
+    schemas = json.loads(node.get_config())["tool_schemas"]
+    return next(s for s in schemas if s["name"] == name)["parameters"]
+
+
+@pytest.fixture
+def llm_server():
+    """Local OpenAI-compatible server asking for the scripted tool call once, recording tool messages."""
+    tool_messages = []
+    scripted = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+            results = [m["content"] for m in body["messages"] if m["role"] == "tool"]
+            tool_messages.extend(results)
+            if not results:
+                calls = [
+                    {"id": f"call_{i}", "type": "function", "function": {"name": name, "arguments": json.dumps(arguments)}}
+                    for i, (name, arguments) in enumerate(scripted)
+                ]
+                message = {"role": "assistant", "content": None, "tool_calls": calls}
+                finish_reason = "tool_calls"
+            else:
+                message = {"role": "assistant", "content": "Done"}
+                finish_reason = "stop"
+            payload = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{server.server_port}", tool_messages, scripted
+    server.shutdown()
+
+
+class TestToolSchema:
+    """Test schemas generated for decorated tools."""
+
+    def test_nested_dataclass_with_optional_fields(self):
+        """Test that dataclass parameters become nested objects with nullable optional fields."""
+        schema = tool_schema(ship, "ship")
+        assert schema["required"] == ["order"]
+
+        order = schema["properties"]["order"]
+        assert order["type"] == "object"
+        assert order["description"] == "The order to ship."
+        assert order["required"] == ["sku", "quantity", "address"]
+        assert order["additionalProperties"] is False
+        assert order["properties"]["quantity"] == {"type": "integer"}
+        assert order["properties"]["gift_note"] == {"type": ["string", "null"], "default": None}
+        assert order["properties"]["tags"] == {"type": "array", "items": {"type": "string"}}
+
+        address = order["properties"]["address"]
+        assert address["description"] == "Postal address."
+        assert address["required"] == ["street"]
+        assert address["properties"]["street"] == {"type": "string", "description": "Street and house number."}
+        assert address["properties"]["zip_code"]["type"] == ["string", "null"]
+        assert address["properties"]["zip_code"]["description"] == "Postal code, if known."
+
+    def test_enum_bool_and_multiline_google_descriptions(self):
+        """Test enum and bool parameters and descriptions continued over several lines."""
+        properties = tool_schema(ship, "ship")["properties"]
+        assert properties["priority"]["enum"] == ["low", "high"]
+        assert properties["priority"]["type"] == "string"
+        assert properties["priority"]["description"] == "How urgently to ship it."
+        assert properties["express"] == {
+            "type": "boolean",
+            "description": "Whether to use express delivery, at extra cost.",
+            "default": False,
+        }
+
+    def test_literal_dict_and_numpy_descriptions(self):
+        """Test Literal and dict parameters and NumPy-style descriptions."""
+        schema = tool_schema(search, "search")
+        properties = schema["properties"]
+        assert schema["required"] == ["query"]
+        assert properties["query"]["description"] == "Free-text query, matched against titles."
+        assert properties["limit"]["description"] == "Maximum number of results."
+        assert properties["limit"]["default"] == 10
+        assert properties["sort"]["enum"] == ["price", "rating"]
+        assert properties["filters"]["type"] == ["object", "null"]
+        assert properties["filters"]["additionalProperties"] == {"type": "number"}
+
+    def test_schema_override(self):
+        """Test that schema_override replaces the generated schema."""
+        override = {"type": "object", "properties": {"text": {"type": "string", "maxLength": 20}}, "required": ["text"]}
+
+        @tool(description="Echo text", schema_override=override)
+        def echo_override(**kwargs) -> str:
+            return kwargs["text"]
+
+        assert tool_schema(echo_override, "echo_override") == override
+
+    def test_dataclass_arguments_are_instantiated(self):
+        """Test that dict arguments for dataclass parameters reach the tool as instances."""
+        result = get_tool_registry().execute_tool(
+            "ship", {"order": {"sku": "A1", "quantity": 2, "address": {"street": "1 Main St"}}, "priority": "high"}
+        )
+        assert result.success, result.error
+        assert result.output == "Order to Address 1 Main St"
+        assert received_orders[-1] == Order("A1", 2, Address("1 Main St"))
+
+    def test_generated_schema_validates_arguments(self, llm_server):
+        """Test that arguments violating the nested schema are reported back to the model."""
+        url, tool_messages, scripted = llm_server
+        scripted.append(("ship", {"order": {"sku": "A1", "quantity": "two", "address": {"street": "1 Main St"}}}))
+        calls_before = len(received_orders)
+
+        workflow = Workflow("orders")
+        workflow.add_node(Node.agent("Ship", "Task: Ship", tools=[ship]))
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=f"{url}/v1"))
+        assert executor.execute(workflow).is_success()
+
+        assert any("invalid_tool_arguments" in message and "order.quantity" in message for message in tool_messages), tool_messages
+        assert len(received_orders) == calls_before
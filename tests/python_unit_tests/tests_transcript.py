@@ -0,0 +1,58 @@
+"""Unit tests for markdown transcripts of workflow results."""
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+from graphbit.errors import GraphBitError
+
+API_KEY = "sk-" + "7" * 48
+
+
+def execute():
+    """Run a workflow of transform nodes only."""
+    workflow = Workflow("transcript")
+    workflow.add_node(Node.transform("Source", "vars.item"))
+    workflow.add_node(Node.transform("Shout", "input + '!'"))
+    workflow.connect("Source", "Shout")
+    executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url="http://127.0.0.1:9/v1"))
+    result = executor.execute(workflow, inputs={"item": "hello"})
+    assert result.is_success(), result.state()
+    return result
+
+
+class TestTranscript:
+    """Test WorkflowResult.to_transcript_markdown and WorkflowResult.save_transcript."""
+
+    def test_run_without_agent_nodes(self):
+        """Test the transcript of a run without agent nodes saying so."""
+        markdown = execute().to_transcript_markdown()
+
+        assert markdown.startswith("# Transcript of workflow run `")
+        assert markdown.rstrip().endswith("No agent nodes ran.")
+
+    def test_save_transcript_writes_markdown(self, tmp_path):
+        """Test save_transcript writing the same markdown as to_transcript_markdown."""
+        result = execute()
+        path = tmp_path / "run.md"
+
+        result.save_transcript(path)
+
+        assert path.read_text(encoding="utf-8") == result.to_transcript_markdown()
+
+    def test_save_transcript_to_missing_directory_raises(self, tmp_path):
+        """Test save_transcript raising when the file cannot be written."""
+        result = execute()
+
+        with pytest.raises(GraphBitError):
+            result.save_transcript(tmp_path / "missing" / "run.md")
+
+    def test_html_report_has_transcript_tab(self, tmp_path):
+        """Test the HTML report offering a Transcript tab next to the nodes."""
+        path = tmp_path / "run.html"
+
+        execute().save_report(path)
+
+        html = path.read_text(encoding="utf-8")
+        assert '<label for="tab-nodes">Nodes</label>' in html
+        assert '<label for="tab-transcript">Transcript</label>' in html
+        assert "No agent nodes ran." in html
@@ -0,0 +1,124 @@
+"""Unit tests for token usage and cost attributed to node tags and teams."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+
+@pytest.fixture
+def llm_config():
+    """Local OpenAI-compatible server answering as gpt-4 with 100 prompt and 50 completion tokens."""
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            self.rfile.read(int(self.headers["Content-Length"]))
+            reply = json.dumps(
+                {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "model": "gpt-4",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "done"}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 100, "completion_tokens": 50, "total_tokens": 150},
+                }
+            ).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(reply)))
+            self.end_headers()
+            self.wfile.write(reply)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    yield LlmConfig.openai("sk-" + "9" * 48, "gpt-4", base_url=f"http://127.0.0.1:{server.server_port}/v1")
+    server.shutdown()
+
+
+def tagged_workflow(team):
+    """Build `search` (tagged search) -> `rank` (search and ranking) -> the untagged `write`, for `team`."""
+    workflow = Workflow("answer")
+    workflow.set_metadata("team", team)
+    workflow.add_node(Node.agent(name="search", prompt="Find sources", tags=["search"]))
+    workflow.add_node(Node.agent(name="rank", prompt="Rank sources", tags=["search", "ranking"]))
+    workflow.add_node(Node.agent(name="write", prompt="Write the answer"))
+    workflow.connect("search", "rank")
+    workflow.connect("rank", "write")
+    return workflow
+
+
+# Each call costs 100 * 0.00003 + 50 * 0.00006 = 0.006 at the gpt-4 price
+CALL_COST = 0.006
+
+
+class TestUsageAttribution:
+    """Test WorkflowResult.get_usage_by_tag, get_usage_by_metadata and Executor.usage_summary."""
+
+    def test_usage_by_tag_and_team(self, llm_config):
+        """Test the tokens and cost of each call counting toward every tag of its node and the team."""
+        result = Executor(llm_config).execute(tagged_workflow("growth"))
+
+        assert result.is_success(), result.state()
+        by_tag = result.get_usage_by_tag()
+        assert sorted(by_tag) == ["ranking", "search"]
+        assert by_tag["search"]["usage"]["total_tokens"] == 300
+        assert by_tag["search"]["usage"]["prompt_tokens"] == 200
+        assert by_tag["search"]["cost_usd"] == pytest.approx(2 * CALL_COST)
+        assert by_tag["ranking"]["usage"]["total_tokens"] == 150
+        assert by_tag["ranking"]["cost_usd"] == pytest.approx(CALL_COST)
+
+        by_team = result.get_usage_by_metadata("team")
+        assert list(by_team) == ["growth"]
+        assert by_team["growth"]["usage"]["total_tokens"] == 450
+        assert by_team["growth"]["cost_usd"] == pytest.approx(3 * CALL_COST)
+        assert result.get_usage_by_metadata("cost_center") is None
+        assert result.get_stats()["usage"]["total"]["usage"]["total_tokens"] == 450
+
+    def test_report_includes_breakdown(self, llm_config, tmp_path):
+        """Test the JSON and HTML reports carrying the usage by tag and team."""
+        result = Executor(llm_config).execute(tagged_workflow("growth"))
+
+        report = result.to_report_dict()
+        assert report["stats"]["usage"]["by_tag"]["search"]["usage"]["total_tokens"] == 300
+        path = tmp_path / "report.html"
+        result.save_report(str(path))
+        html = path.read_text()
+        assert "<h2>Usage</h2>" in html
+        assert "<tr><td>growth</td>" in html
+
+    def test_usage_summary(self, llm_config, tmp_path):
+        """Test usage_summary adding up the runs of the period by tag and team."""
+        executor = Executor(llm_config, run_history=str(tmp_path / "runs.db"))
+        for team in ["growth", "search", "growth"]:
+            assert executor.execute(tagged_workflow(team)).is_success()
+
+        summary = executor.usage_summary(3600)
+
+        assert summary["runs"] == 3
+        assert summary["total"]["usage"]["total_tokens"] == 1350
+        assert summary["by_tag"]["search"]["usage"]["total_tokens"] == 900
+        assert summary["by_tag"]["ranking"]["cost_usd"] == pytest.approx(3 * CALL_COST)
+        by_team = summary["by_metadata"]["team"]
+        assert by_team["growth"]["usage"]["total_tokens"] == 900
+        assert by_team["growth"]["cost_usd"] == pytest.approx(6 * CALL_COST)
+        assert by_team["search"]["usage"]["total_tokens"] == 450
+
+    def test_usage_summary_errors(self, llm_config, tmp_path):
+        """Test usage_summary needing a run history and a non-negative period."""
+        with pytest.raises(ValueError, match="no run history"):
+            Executor(llm_config).usage_summary(3600)
+        executor = Executor(llm_config, run_history=str(tmp_path / "runs.db"))
+        with pytest.raises(ValueError, match="non-negative number of seconds"):
+            executor.usage_summary(-1)
+
+    def test_metadata_must_be_json_compatible(self):
+        """Test set_metadata rejecting values that are not JSON-compatible."""
+        workflow = Workflow("answer")
+
+        with pytest.raises(ValueError, match="JSON-compatible"):
+            workflow.set_metadata("team", object())
@@ -0,0 +1,125 @@
+"""Unit tests for executor warm-up."""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from graphbit import Executor, LlmConfig, Node, Workflow
+
+API_KEY = "sk-" + "0" * 48
+BAD_API_KEY = "sk-" + "1" * 48
+
+
+@pytest.fixture
+def server():
+    """Local OpenAI-compatible server accepting only API_KEY, recording the paths it is asked for."""
+    paths = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def reply(self, status, body):
+            payload = json.dumps(body).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def authorized(self):
+            paths.append(self.path)
+            if self.headers.get("Authorization") == f"Bearer {API_KEY}":
+                return True
+            self.reply(401, {"error": {"message": "Incorrect API key provided", "code": "invalid_api_key"}})
+            return False
+
+        def do_GET(self):
+            if self.authorized():
+                self.reply(200, {"id": "gpt-4o-mini", "object": "model", "owned_by": "openai"})
+
+        def do_POST(self):
+            self.rfile.read(int(self.headers["Content-Length"]))
+            if not self.authorized():
+                return
+            self.reply(
+                200,
+                {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "model": "gpt-4o-mini",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Done"}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+                },
+            )
+
+        def log_message(self, *args):
+            pass
+
+    http_server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=http_server.serve_forever, daemon=True).start()
+    yield f"http://127.0.0.1:{http_server.server_port}/v1", paths
+    http_server.shutdown()
+
+
+def two_step_workflow():
+    """Workflow `draft -> review` of agent nodes."""
+    workflow = Workflow("warm up")
+    draft = workflow.add_node(Node.agent(name="draft", prompt="Draft"))
+    review = workflow.add_node(Node.agent(name="review", prompt="Review"))
+    workflow.connect(draft, review)
+    return workflow
+
+
+class TestExecutorWarmUp:
+    """Test Executor.warm_up and execute(warm_up=True)."""
+
+    def test_checks_each_provider_once_and_creates_agents(self, server):
+        """Test that nodes sharing a model are checked once and their agents are kept."""
+        base_url, paths = server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url))
+
+        report = executor.warm_up(two_step_workflow())
+
+        assert report["ok"]
+        assert len(report["agents_created"]) == 2
+        assert len(report["providers"]) == 1
+        assert report["providers"][0]["model"] == "gpt-4o-mini"
+        assert report["providers"][0]["nodes"] == ["draft", "review"]
+        # OpenAI has no cold start, so it is health-checked rather than primed
+        assert not report["providers"][0]["primed"]
+        assert paths == ["/v1/models/gpt-4o-mini"]
+
+    def test_reports_failures_without_raising(self, server):
+        """Test that a rejected API key shows in the report instead of raising."""
+        base_url, _ = server
+        executor = Executor(LlmConfig.openai(BAD_API_KEY, "gpt-4o-mini", base_url=base_url))
+
+        report = executor.warm_up(two_step_workflow())
+
+        assert not report["ok"]
+        assert not report["providers"][0]["authenticated"]
+
+    def test_execute_with_warm_up(self, server):
+        """Test that execute(warm_up=True) attaches the warm-up report to the result."""
+        base_url, _ = server
+        executor = Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini", base_url=base_url))
+        workflow = two_step_workflow()
+
+        result = executor.execute(workflow, warm_up=True)
+        assert result.is_success()
+        report = result.get_warm_up_report()
+        assert report["ok"]
+        assert report["providers"][0]["nodes"] == ["draft", "review"]
+
+        assert executor.execute(workflow).get_warm_up_report() is None
+
+    def test_rejects_invalid_workflow(self):
+        """Test that a workflow failing validation raises."""
+        workflow = Workflow("cyclic")
+        first = workflow.add_node(Node.agent(name="first", prompt="First"))
+        second = workflow.add_node(Node.agent(name="second", prompt="Second"))
+        workflow.connect(first, second)
+        workflow.connect(second, first)
+
+        with pytest.raises(Exception):
+            Executor(LlmConfig.openai(API_KEY, "gpt-4o-mini")).warm_up(workflow)
@@ -1,10 +1,17 @@
 """Unit tests for workflow functionality."""
 
+import copy
+import http.server
+import json
+import multiprocessing
 import os
+import pickle
+import threading
+import time
 
 import pytest
 
-from graphbit import Executor, LlmConfig, Node, Workflow
+from graphbit import Agent, EmbeddingClient, EmbeddingConfig, Executor, LlmConfig, Node, RetryPolicy, Workflow
 
 
 def get_api_key(provider: str) -> str:
@@ -28,7 +35,7 @@ class TestNode:
 
     def test_transform_node_creation(self):
         """Test creating transform node."""
-        node = Node.transform(name="test_transform", transformation="lambda x: x.upper()")
+        node = Node.transform(name="test_transform", transformation="upper(input)")
         assert node is not None
         assert node.name() == "test_transform"
         assert node.id() is not None
@@ -91,6 +98,421 @@ class TestWorkflow:
         workflow = Workflow("test_workflow")
         assert workflow.validate() is None
 
+    def test_workflow_json_roundtrip(self):
+        """Workflows survive to_json/from_json with nodes, edges and name intact."""
+        workflow = Workflow("roundtrip")
+        id1 = workflow.add_node(Node.agent(name="agent1", prompt="First step", agent_id="a1"))
+        id2 = workflow.add_node(Node.agent(name="agent2", prompt="Second step", agent_id="a2"))
+        workflow.connect(id1, id2)
+
+        data = workflow.to_json()
+        assert json.loads(data)["schema_version"] == 1
+
+        restored = Workflow.from_json(data)
+        assert restored.name() == "roundtrip"
+        assert restored.node_count() == workflow.node_count() == 2
+        assert restored.edge_count() == workflow.edge_count() == 1
+        assert restored.validate() is None
+        assert json.loads(restored.to_json()) == json.loads(data)
+
+    def test_workflow_json_rejects_newer_schema(self):
+        """JSON from a newer GraphBit version is rejected."""
+        data = json.loads(Workflow("future").to_json())
+        data["schema_version"] = 99
+        with pytest.raises(ValueError, match="upgrade GraphBit"):
+            Workflow.from_json(json.dumps(data))
+
+    def test_workflow_analyze(self):
+        """Analysis reports the critical path, width and expected cost of a diamond."""
+        workflow = Workflow("diamond")
+        workflow.add_node(Node.transform(name="load", transformation="input"))
+        workflow.add_node(Node.agent(name="pros", prompt="List pros", model="gpt-4o-mini"))
+        workflow.add_node(Node.agent(name="cons", prompt="List cons"))
+        workflow.add_node(Node.agent(name="decide", prompt="Decide", model="gpt-4o-mini"))
+        for source, target in [("load", "pros"), ("load", "cons"), ("pros", "decide"), ("cons", "decide")]:
+            workflow.connect(source, target)
+
+        analysis = workflow.analyze(prices={"gpt-4o-mini": (0.000001, 0.000002)}, avg_prompt_tokens=100, avg_completion_tokens=50)
+
+        assert analysis["node_count"] == 4
+        assert analysis["edge_count"] == 4
+        assert analysis["depth"] == 3
+        assert analysis["critical_path"] == ["load", "cons", "decide"]
+        assert analysis["max_parallel_width"] == 2
+        cost = analysis["cost"]
+        assert [node["node_name"] for node in cost["nodes"]] in (["pros", "cons", "decide"], ["cons", "pros", "decide"])
+        assert cost["total_cost"] == pytest.approx(0.0004)
+        assert cost["unpriced_nodes"] == ["cons"]
+        assert cost["token_usage"]["prompt_tokens"] == 300
+
+        # Without prices every agent node is unpriced
+        cost = workflow.analyze()["cost"]
+        assert sorted(cost["unpriced_nodes"]) == ["cons", "decide", "pros"]
+        assert cost["total_cost"] == 0
+
+    def test_workflow_replace_node(self):
+        """Replacing a node keeps its edges, so the critical path runs through the replacement."""
+        workflow = Workflow("replace")
+        for name in ["load", "draft", "publish"]:
+            workflow.add_node(Node.transform(name=name, transformation="input"))
+        workflow.connect("load", "draft")
+        workflow.connect("draft", "publish")
+
+        new_id = workflow.replace_node("draft", Node.transform(name="rewrite", transformation="input"))
+
+        assert json.loads(workflow.to_json())["graph"]["nodes"][new_id]["name"] == "rewrite"
+        assert workflow.node_count() == 3
+        assert workflow.edge_count() == 2
+        assert workflow.analyze()["critical_path"] == ["load", "rewrite", "publish"]
+        with pytest.raises(ValueError):
+            workflow.replace_node("draft", Node.transform(name="again", transformation="input"))
+
+    def test_workflow_insert_between(self):
+        """Inserting a node splits an edge, keeping its condition on the first half."""
+        workflow = Workflow("insert")
+        draft = workflow.add_node(Node.transform(name="draft", transformation="input"))
+        publish = workflow.add_node(Node.transform(name="publish", transformation="input"))
+        workflow.connect(draft, publish, condition="ready == true")
+
+        review = workflow.insert_between(draft, "publish", Node.transform(name="review", transformation="input"))
+
+        assert workflow.edge_count() == 2
+        assert workflow.analyze()["critical_path"] == ["draft", "review", "publish"]
+        edges = json.loads(workflow.to_json())["graph"]["edges"]
+        assert [(source, target, edge["condition"]) for source, target, edge in edges] == [
+            (draft, review, "ready == true"),
+            (review, publish, None),
+        ]
+        with pytest.raises(ValueError):
+            workflow.insert_between(draft, publish, Node.transform(name="late", transformation="input"))
+
+
+class TestRegisteredAgent:
+    """Test reusable agents shared by several nodes."""
+
+    def _agent(self):
+        config = LlmConfig.openai("sk-test-key-1234567890")
+        return Agent.builder("researcher").description("Finds sources").llm(config).capability("search").system_prompt("Be concise").build()
+
+    def test_agent_builder(self):
+        """Test building an agent."""
+        agent = self._agent()
+        assert agent.name == "researcher"
+        assert agent.description == "Finds sources"
+        assert agent.capabilities == ["search"]
+        assert agent.system_prompt == "Be concise"
+        assert agent.id
+
+    def test_agent_builder_requires_llm(self):
+        """Test that building without an LLM config fails."""
+        with pytest.raises(ValueError):
+            Agent.builder("researcher").build()
+
+    def test_nodes_share_registered_agent(self):
+        """Test two nodes referencing one registered agent."""
+        agent = self._agent()
+        workflow = Workflow("shared_agent")
+        id1 = workflow.add_node(Node.agent(name="draft", prompt="Draft {input}", agent=agent))
+        id2 = workflow.add_node(Node.agent(name="review", prompt="Review the draft", agent=agent))
+        workflow.connect(id1, id2)
+        assert workflow.validate() is None
+
+        executor = Executor(LlmConfig.openai("sk-test-key-1234567890"))
+        executor.register_agent(agent)
+
+    def test_agent_and_agent_id_are_exclusive(self):
+        """Test that a node cannot set both agent and agent_id."""
+        with pytest.raises(ValueError):
+            Node.agent(name="n", prompt="p", agent=self._agent(), agent_id="other")
+
+
+class _EchoHandler(http.server.BaseHTTPRequestHandler):
+    """Answer POST requests with the JSON body they carried."""
+
+    def do_POST(self):
+        length = int(self.headers.get("Content-Length", 0))
+        body = json.dumps({"echo": json.loads(self.rfile.read(length))}).encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, *args):
+        pass
+
+
+class TestNodeTypes:
+    """Test constructors for non-agent node types."""
+
+    def test_constructors(self):
+        """Test every node type constructor with the shared options."""
+        nodes = [
+            Node.http_request("fetch", "https://example.com", method="post", headers={"X-Key": "k"}, body={"q": 1}),
+            Node.delay("wait", 2, timeout_seconds=10),
+            Node.document_loader("load", "notes.txt", "txt", tags=["io"]),
+            Node.split("fan_out", retry_config={"max_attempts": 5}),
+            Node.join("fan_in", output_schema={"type": "object"}),
+            Node.transform("upper", "uppercase", tags=["text"]),
+        ]
+        assert [n.name() for n in nodes] == ["fetch", "wait", "load", "fan_out", "fan_in", "upper"]
+        assert "HttpRequest" in repr(nodes[0])
+        assert json.loads(nodes[0].get_config())["body"] == {"q": 1}
+
+    def test_validation_errors_name_the_node(self):
+        """Test that invalid node configuration raises ValueError naming the node."""
+        with pytest.raises(ValueError, match="'load'"):
+            Node.document_loader("load", "notes.exe", "exe")
+        with pytest.raises(ValueError, match="'fetch'"):
+            Node.http_request("fetch", "")
+        with pytest.raises(ValueError, match="'fan_out'"):
+            Node.split("fan_out", retry_config={"max_attempts": "many"})
+        with pytest.raises(ValueError):
+            Node.delay("", 1)
+
+    def test_execute_mixed_workflow(self, tmp_path):
+        """Test executing a workflow mixing loader, split, HTTP, delay, join and transform nodes."""
+        server = http.server.ThreadingHTTPServer(("127.0.0.1", 0), _EchoHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        source = tmp_path / "note.txt"
+        source.write_text("hello graphbit")
+
+        try:
+            workflow = Workflow("mixed")
+            load = workflow.add_node(Node.document_loader("load", str(source), "txt"))
+            fan_out = workflow.add_node(Node.split("fan_out"))
+            post = workflow.add_node(Node.http_request("post", f"http://127.0.0.1:{server.server_port}/echo", method="POST", body={"q": 1}))
+            wait = workflow.add_node(Node.delay("wait", 0))
+            fan_in = workflow.add_node(Node.join("fan_in"))
+            stringify = workflow.add_node(Node.transform("stringify", "json_stringify"))
+            for parent, child in [(load, fan_out), (fan_out, post), (fan_out, wait), (post, fan_in), (wait, fan_in), (fan_in, stringify)]:
+                workflow.connect(parent, child)
+            workflow.validate()
+
+            result = Executor(LlmConfig.openai("sk-test-key-1234567890")).execute(workflow)
+        finally:
+            server.shutdown()
+
+        assert result.is_success()
+        joined = json.loads(result.get_node_output("fan_in"))
+        assert joined["post"] == {"status": 200, "body": {"echo": {"q": 1}}}
+        assert joined["wait"]["content"] == "hello graphbit"
+        assert json.loads(result.get_node_output("stringify")) == joined
+
+    def test_join_policies(self):
+        """Test a join with policy "any" continuing without waiting for its slow parent."""
+        workflow = Workflow("race")
+        fast = workflow.add_node(Node.transform("fast", "'first'"))
+        slow = workflow.add_node(Node.delay("slow", 5))
+        fastest = workflow.add_node(Node.join("fastest", policy="any"))
+        workflow.connect(fast, fastest)
+        workflow.connect(slow, fastest)
+
+        started = time.monotonic()
+        result = Executor(LlmConfig.openai("sk-test-key-1234567890")).execute(workflow)
+        assert time.monotonic() - started < 3
+        assert result.is_success()
+        assert json.loads(result.get_node_output("fastest")) == {"fast": "first"}
+        assert result.get_abandoned_nodes() == {"slow": "cancelled"}
+
+        Node.join("two", policy="quorum", quorum=2, pending_branches="detach")
+        with pytest.raises(ValueError, match="requires quorum"):
+            Node.join("two", policy="quorum")
+        with pytest.raises(ValueError, match="Unknown join policy"):
+            Node.join("two", policy="first")
+        with pytest.raises(ValueError, match="Unknown pending branch handling"):
+            Node.join("two", policy="any", pending_branches="abandon")
+
+    def test_partial_failure(self):
+        """Test a failing branch keeping the other branch's output and skipping the nodes after the join."""
+        workflow = Workflow("diamond")
+        source = workflow.add_node(Node.transform("source", "'question'"))
+        left = workflow.add_node(Node.transform("left", "input + '!'"))
+        right = workflow.add_node(Node.transform("right", "1 / 0"))
+        join = workflow.add_node(Node.join("join"))
+        after = workflow.add_node(Node.transform("after", "input"))
+        for parent, child in [(source, left), (source, right), (left, join), (right, join), (join, after)]:
+            workflow.connect(parent, child)
+
+        result = Executor(LlmConfig.openai("sk-test-key-1234567890"), fail_fast=False).execute(workflow)
+        assert result.is_partial()
+        assert not result.is_success() and not result.is_failed()
+        assert result.failed_nodes() == ["right"]
+        assert result.skipped_nodes() == {"join": ["right"], "after": ["right"]}
+        assert result.get_abandoned_nodes() == {"join": "blocked", "after": "blocked"}
+        assert result.get_node_output("left") == "question!"
+        stats = result.get_stats()
+        assert (stats["successful_nodes"], stats["failed_nodes"], stats["skipped_nodes"]) == (2, 1, 2)
+
+        result = Executor(LlmConfig.openai("sk-test-key-1234567890"), fail_fast=True).execute(workflow)
+        assert result.is_failed()
+        assert result.failed_nodes() == [] and not result.is_partial()
+
+
+class _AuthEchoHandler(http.server.BaseHTTPRequestHandler):
+    """Answer GET requests with the Authorization header they carried."""
+
+    received = []
+
+    def do_GET(self):
+        authorization = self.headers.get("Authorization", "")
+        type(self).received.append(authorization)
+        body = json.dumps({"authorization": authorization}).encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, *args):
+        pass
+
+
+class TestInputsAndSecrets:
+    """Test run-time inputs and secrets passed to Executor.execute."""
+
+    TOKEN = "sk-live-4f9a2c7e1b"
+
+    def _run(self, header, **kwargs):
+        _AuthEchoHandler.received = []
+        server = http.server.ThreadingHTTPServer(("127.0.0.1", 0), _AuthEchoHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            workflow = Workflow("secrets")
+            fetch = workflow.add_node(Node.http_request("fetch", f"http://127.0.0.1:{server.server_port}/", headers={"Authorization": header}))
+            shout = workflow.add_node(Node.transform("shout", "upper(vars.question)"))
+            workflow.connect(fetch, shout)
+            return Executor(LlmConfig.openai("sk-test-key-1234567890")).execute(workflow, **kwargs)
+        finally:
+            server.shutdown()
+
+    def test_secret_is_sent_and_redacted(self):
+        """Test that a secret reaches the HTTP header but not the result."""
+        result = self._run("Bearer {{secret.API_TOKEN}}", inputs={"question": "why"}, secrets={"API_TOKEN": self.TOKEN})
+
+        assert result.is_success()
+        assert _AuthEchoHandler.received == [f"Bearer {self.TOKEN}"]
+        assert json.loads(result.get_node_output("fetch"))["body"] == {"authorization": "Bearer [REDACTED]"}
+        assert result.get_node_output("shout") == "WHY"
+        assert result.get_variable("question") == "why"
+        assert self.TOKEN not in json.dumps(result.get_all_node_outputs())
+        assert self.TOKEN not in json.dumps(result.get_all_variables())
+
+    def test_missing_secret_fails_before_running(self):
+        """Test that referencing a secret that was not supplied raises before any request."""
+        with pytest.raises(Exception, match="API_TOKEN"):
+            self._run("Bearer {{secret.API_TOKEN}}", inputs={"question": "why"})
+        assert _AuthEchoHandler.received == []
+
+    def test_inputs_must_be_json_compatible(self):
+        """Test that inputs that cannot be converted to JSON are rejected."""
+        with pytest.raises(ValueError, match="Inputs"):
+            self._run("none", inputs={"question": object()})
+
+
+class TestExecutionReport:
+    """Test execution reports exported from a workflow result."""
+
+    def _run(self):
+        workflow = Workflow("report")
+        shout = workflow.add_node(Node.transform("shout", "upper(vars.question)"))
+        length = workflow.add_node(Node.transform("length", "len(input)"))
+        workflow.connect(shout, length)
+        return Executor(LlmConfig.openai("sk-test-key-1234567890")).execute(workflow, inputs={"question": "why"})
+
+    def test_report_dict_lists_nodes_in_order(self):
+        """Test that the report dict records the executed nodes and the graph."""
+        report = self._run().to_report_dict()
+
+        assert report["report_version"] == 1
+        assert report["status"] == "completed"
+        assert [node["node_name"] for node in report["nodes"]] == ["shout", "length"]
+        assert [node["step"] for node in report["nodes"]] == [1, 2]
+        assert report["nodes"][0]["output"] == "WHY"
+        assert report["nodes"][1]["output"] == 3
+        assert report["nodes"][1]["attempts"] == 1
+        assert report["edges"] == [{"from": "shout", "to": "length"}]
+        assert report["skipped_nodes"] == []
+
+    def test_report_truncation(self):
+        """Test that long strings are truncated to max_value_chars."""
+        report = self._run().to_report_dict(max_value_chars=1)
+        assert report["nodes"][0]["output"] == "W... [truncated 2 chars]"
+
+    def test_save_report_as_html_and_json(self, tmp_path):
+        """Test that the file extension selects the report format."""
+        result = self._run()
+        result.save_report(tmp_path / "run.html")
+        result.save_report(str(tmp_path / "run.json"))
+
+        html = (tmp_path / "run.html").read_text()
+        assert html.startswith("<!DOCTYPE html>")
+        assert '<pre class="mermaid">' in html
+        assert "<details" in html
+        assert json.loads((tmp_path / "run.json").read_text())["nodes"][0]["node_name"] == "shout"
+
+
+class _FlakyHandler(http.server.BaseHTTPRequestHandler):
+    """Answer GET requests with HTTP 503 until the third request."""
+
+    requests = 0
+
+    def do_GET(self):
+        type(self).requests += 1
+        status, body = (503, b"unavailable") if self.requests < 3 else (200, b'{"ok": true}')
+        self.send_response(status)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, *args):
+        pass
+
+
+class TestNodeRetry:
+    """Test per-node retry policies."""
+
+    def test_retry_policy(self):
+        """Test RetryPolicy defaults, accessors and validation."""
+        policy = RetryPolicy(max_attempts=5, backoff_ms=500, exponential=True)
+        assert (policy.max_attempts, policy.backoff_ms, policy.exponential, policy.jitter) == (5, 500, True, 0.1)
+        assert "max_attempts=5" in repr(policy)
+
+        with pytest.raises(ValueError):
+            RetryPolicy(max_attempts=0)
+        with pytest.raises(ValueError):
+            RetryPolicy(jitter=1.5)
+        with pytest.raises(ValueError, match="'fetch'"):
+            Node.http_request("fetch", "https://example.com", retry=policy, retry_config={"max_attempts": 1})
+
+    def _run_flaky(self, retry):
+        _FlakyHandler.requests = 0
+        server = http.server.ThreadingHTTPServer(("127.0.0.1", 0), _FlakyHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            workflow = Workflow("flaky")
+            workflow.add_node(Node.http_request("scrape", f"http://127.0.0.1:{server.server_port}/", retry=retry, timeout_seconds=30, tags=["scraping"]))
+            return Executor(LlmConfig.openai("sk-test-key-1234567890")).execute(workflow)
+        finally:
+            server.shutdown()
+
+    def test_node_succeeds_on_third_attempt(self):
+        """Test that a node retries under its own policy and reports the attempts used."""
+        result = self._run_flaky(RetryPolicy(max_attempts=5, backoff_ms=10, exponential=False, jitter=0.0))
+
+        assert result.is_success()
+        assert json.loads(result.get_node_output("scrape"))["body"] == {"ok": True}
+        assert result.get_node_attempts("scrape") == 3
+        assert result.get_node_attempts("missing") is None
+
+    def test_node_gives_up_after_max_attempts(self):
+        """Test that a node stops retrying once its policy is exhausted."""
+        result = self._run_flaky(RetryPolicy(max_attempts=2, backoff_ms=10, jitter=0.0))
+
+        assert result.get_node_attempts("scrape") == 2
+        assert _FlakyHandler.requests == 2
+        assert result.get_stats()["failed_nodes"] == 1
+
 
 class TestExecutor:
     """Test workflow executor functionality."""
@@ -198,6 +620,180 @@ class TestExecutor:
         assert stats["failed_executions"] == 0
 
 
+class _BarrierHandler(http.server.BaseHTTPRequestHandler):
+    """Hold every GET request for a while, recording the peak number in flight."""
+
+    lock = threading.Lock()
+    in_flight = 0
+    peak = 0
+
+    def do_GET(self):
+        cls = type(self)
+        with cls.lock:
+            cls.in_flight += 1
+            cls.peak = max(cls.peak, cls.in_flight)
+        time.sleep(0.2)
+        with cls.lock:
+            cls.in_flight -= 1
+        body = b"{}"
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, *args):
+        pass
+
+
+class TestExecutorTuning:
+    """Test executor presets and concurrency limits."""
+
+    def _run_parallel(self, executor, nodes=6):
+        _BarrierHandler.in_flight = _BarrierHandler.peak = 0
+        server = http.server.ThreadingHTTPServer(("127.0.0.1", 0), _BarrierHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            workflow = Workflow("parallel")
+            for i in range(nodes):
+                workflow.add_node(Node.http_request(f"fetch_{i}", f"http://127.0.0.1:{server.server_port}/{i}"))
+            result = executor.execute(workflow)
+        finally:
+            server.shutdown()
+        assert result.is_success()
+        return _BarrierHandler.peak
+
+    def test_modes(self):
+        """Test that every preset is accepted and unknown modes are rejected."""
+        config = LlmConfig.openai("sk-test-key-1234567890")
+        for mode, name in [("balanced", "Balanced"), ("high_throughput", "HighThroughput"), ("low_latency", "LowLatency"), ("memory_optimized", "MemoryOptimized")]:
+            assert Executor(config, mode=mode).get_execution_mode() == name
+
+        with pytest.raises(ValueError):
+            Executor(config, mode="turbo")
+        with pytest.raises(ValueError):
+            Executor(config, max_concurrency=0)
+        with pytest.raises(ValueError):
+            Executor(config, node_type_limits={"http_request": 0})
+        with pytest.raises(ValueError):
+            Executor(config, circuit_breaker={"failure_threshold": "many"})
+
+    def test_node_type_limit_bounds_parallelism(self):
+        """Test that a node type limit caps how many of those nodes run at once."""
+        executor = Executor(LlmConfig.openai("sk-test-key-1234567890"), node_type_limits={"http_request": 2})
+
+        assert self._run_parallel(executor) == 2
+
+        stats = executor.stats()
+        assert stats["total_permit_acquisitions"] == 6
+        assert stats["peak_active_tasks"] == 2
+        assert stats["current_active_tasks"] == 0
+
+    def test_max_concurrency_bounds_parallelism(self):
+        """Test that max_concurrency caps running nodes across all node types."""
+        executor = Executor(
+            LlmConfig.openai("sk-test-key-1234567890"),
+            mode="high_throughput",
+            max_concurrency=1,
+            fail_fast=True,
+            retry=RetryPolicy(max_attempts=1),
+            circuit_breaker={"failure_threshold": 2},
+        )
+
+        assert self._run_parallel(executor, nodes=3) == 1
+        assert executor.stats()["peak_active_tasks"] == 1
+
+    def test_max_node_execution_time(self):
+        """Test that max_node_execution_time_ms bounds nodes without their own timeout."""
+        executor = Executor(LlmConfig.openai("sk-test-key-1234567890"), max_node_execution_time_ms=100)
+        workflow = Workflow("slow")
+        workflow.add_node(Node.delay("wait", 5))
+
+        start = time.monotonic()
+        result = executor.execute(workflow)
+
+        assert time.monotonic() - start < 3
+        assert result.get_stats()["failed_nodes"] == 1
+
+
+def _execute_received_workflow(conn):
+    """Run in a child process: execute the pickled workflow received on ``conn``."""
+    workflow, llm_config = conn.recv()
+    result = Executor(llm_config).execute(workflow)
+    conn.send((result.is_success(), result.get_node_output("post")))
+    conn.close()
+
+
+class TestPickling:
+    """Test pickling and copying workflows, nodes and configuration objects."""
+
+    def test_roundtrip(self):
+        """Test that every picklable class survives a pickle roundtrip."""
+        policy = pickle.loads(pickle.dumps(RetryPolicy(max_attempts=4, backoff_ms=50, exponential=False)))
+        assert (policy.max_attempts, policy.backoff_ms, policy.exponential) == (4, 50, False)
+
+        node = Node.http_request("fetch", "https://example.com", method="POST", body={"q": 1}, retry=policy, tags=["io"])
+        restored = pickle.loads(pickle.dumps(node))
+        assert restored.id() == node.id()
+        assert restored.get_config() == node.get_config()
+        assert repr(restored) == repr(node)
+
+        config = pickle.loads(pickle.dumps(LlmConfig.anthropic("sk-ant-test-key-123456", "claude-3-5-haiku-latest")))
+        assert (config.provider(), config.model()) == ("anthropic", "claude-3-5-haiku-latest")
+
+        embedding = pickle.loads(pickle.dumps(EmbeddingConfig.openai("sk-test-key-1234567890")))
+        EmbeddingClient(embedding)
+
+    def test_api_keys_survive_unless_redacted(self):
+        """Test that pickles keep API keys and redacted() strips them."""
+        config = LlmConfig.openai("sk-test-key-1234567890", "gpt-4o-mini")
+        assert b"sk-test-key-1234567890" in pickle.dumps(config)
+        assert b"sk-test-key-1234567890" in pickle.dumps(EmbeddingConfig.openai("sk-test-key-1234567890"))
+
+        redacted = config.redacted()
+        assert (redacted.provider(), redacted.model()) == ("openai", "gpt-4o-mini")
+        assert b"sk-test-key-1234567890" not in pickle.dumps(redacted)
+        assert b"[REDACTED]" in pickle.dumps(redacted)
+
+    def test_deepcopy(self):
+        """Test that a deep copy of a workflow is independent of the original."""
+        workflow = Workflow("original")
+        first = workflow.add_node(Node.delay("wait", 0))
+        clone = copy.deepcopy(workflow)
+        second = clone.add_node(Node.transform("upper", "uppercase"))
+        clone.connect(first, second)
+
+        assert (workflow.node_count(), workflow.edge_count()) == (1, 0)
+        assert (clone.node_count(), clone.edge_count()) == (2, 1)
+        assert copy.deepcopy(RetryPolicy(max_attempts=2)).max_attempts == 2
+        assert copy.deepcopy(LlmConfig.openai("sk-test-key-1234567890")).provider() == "openai"
+
+    def test_execute_in_child_process(self):
+        """Test sending a workflow through a multiprocessing Pipe and executing it on the other side."""
+        server = http.server.ThreadingHTTPServer(("127.0.0.1", 0), _EchoHandler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        workflow = Workflow("remote")
+        post = workflow.add_node(Node.http_request("post", f"http://127.0.0.1:{server.server_port}/echo", method="POST", body={"q": 1}, retry=RetryPolicy(max_attempts=2)))
+        stringify = workflow.add_node(Node.transform("stringify", "json_stringify"))
+        workflow.connect(post, stringify)
+
+        context = multiprocessing.get_context("spawn")
+        parent_conn, child_conn = context.Pipe()
+        process = context.Process(target=_execute_received_workflow, args=(child_conn,))
+        try:
+            process.start()
+            parent_conn.send((workflow, LlmConfig.openai("sk-test-key-1234567890")))
+            assert parent_conn.poll(60), "child process did not answer"
+            success, output = parent_conn.recv()
+            process.join(10)
+        finally:
+            if process.is_alive():
+                process.kill()
+            server.shutdown()
+
+        assert success
+        assert json.loads(output) == {"status": 200, "body": {"echo": {"q": 1}}}
+
+
 class TestWorkflowErrorHandling:
     """Test workflow error handling."""
 
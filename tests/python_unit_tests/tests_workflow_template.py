@@ -0,0 +1,113 @@
+"""Unit tests for workflow templates and the template registry."""
+
+import json
+import pickle
+
+import pytest
+import yaml
+
+from graphbit import Node, TemplateRegistry, Workflow, WorkflowTemplate
+
+PARAMETERS = {
+    "topic": "string",
+    "model": {"type": "string", "default": "gpt-4o-mini"},
+    "sentences": {"type": "integer", "default": 3, "description": "Length of the summary"},
+}
+
+
+def summarize_template():
+    """Template summarizing a document, with the model and summary length as parameters."""
+    workflow = Workflow("Summarize {{params.topic}}")
+    workflow.add_node(
+        Node.agent(
+            name="summarize",
+            prompt="Summarize this {{params.topic}} document in {{params.sentences}} sentences",
+            model="{{params.model}}",
+        )
+    )
+    return WorkflowTemplate("summarize_doc", workflow, parameters=PARAMETERS, description="Summarize a document")
+
+
+def summarize_node(workflow):
+    """The summarize node of an instantiated workflow, as serialized JSON."""
+    nodes = json.loads(workflow.to_json())["graph"]["nodes"].values()
+    return next(node for node in nodes if node["name"] == "summarize")
+
+
+class TestWorkflowTemplate:
+    """Test template declaration, instantiation and loading."""
+
+    def test_instantiate_with_defaults(self):
+        """Test that defaults fill in parameters that are not supplied."""
+        workflow = summarize_template().instantiate(topic="legal")
+
+        assert workflow.name() == "Summarize legal"
+        node = summarize_node(workflow)
+        assert node["node_type"]["prompt_template"] == "Summarize this legal document in 3 sentences"
+        assert node["config"]["model"] == "gpt-4o-mini"
+
+        workflow = summarize_template().instantiate(topic="finance", model="gpt-4o", sentences=5)
+        node = summarize_node(workflow)
+        assert node["node_type"]["prompt_template"] == "Summarize this finance document in 5 sentences"
+        assert node["config"]["model"] == "gpt-4o"
+
+    def test_parameter_errors(self):
+        """Test that missing, unknown and mistyped parameters are rejected."""
+        template = summarize_template()
+        with pytest.raises(ValueError, match="Missing required parameters: topic"):
+            template.instantiate()
+        with pytest.raises(ValueError, match="Unknown parameters: temperature"):
+            template.instantiate(topic="legal", temperature=0.2)
+        with pytest.raises(ValueError, match="'sentences' must be of type integer"):
+            template.instantiate(topic="legal", sentences="three")
+
+    def test_invalid_declarations(self):
+        """Test that undeclared references and bad parameter types are rejected."""
+        workflow = Workflow("Summarize")
+        workflow.add_node(Node.agent(name="summarize", prompt="Summarize {{params.topic}}"))
+        with pytest.raises(ValueError, match="undeclared parameter"):
+            WorkflowTemplate("summarize_doc", workflow)
+        with pytest.raises(ValueError, match="Unknown type 'text'"):
+            WorkflowTemplate("summarize_doc", workflow, parameters={"topic": "text"})
+
+    def test_load_yaml_and_json_files(self, tmp_path):
+        """Test that templates load from YAML and JSON files."""
+        template = summarize_template()
+        yaml_path = tmp_path / "summarize.yaml"
+        yaml_path.write_text(yaml.safe_dump(json.loads(template.to_json())))
+        json_path = tmp_path / "summarize.json"
+        json_path.write_text(template.to_json())
+
+        for path in (yaml_path, json_path):
+            loaded = WorkflowTemplate.from_file(str(path))
+            assert loaded.name == "summarize_doc"
+            assert loaded.description == "Summarize a document"
+            assert loaded.parameters["sentences"] == {"type": "integer", "default": 3, "description": "Length of the summary"}
+            assert loaded.parameters["topic"] == {"type": "string"}
+            assert summarize_node(loaded.instantiate(topic="legal"))["config"]["model"] == "gpt-4o-mini"
+
+        assert pickle.loads(pickle.dumps(template)).to_json() == template.to_json()
+
+    def test_registry(self, tmp_path):
+        """Test registering and instantiating templates by name."""
+        registry = TemplateRegistry()
+        registry.register(summarize_template())
+        path = tmp_path / "summarize.yml"
+        definition = json.loads(summarize_template().to_json())
+        path.write_text(yaml.safe_dump({**definition, "name": "summarize_brief"}))
+        assert registry.register_file(str(path)) == "summarize_brief"
+
+        assert registry.names() == ["summarize_brief", "summarize_doc"]
+        assert len(registry) == 2
+        assert "summarize_doc" in registry
+        workflow = registry.instantiate("summarize_doc", topic="legal", sentences=1)
+        assert summarize_node(workflow)["node_type"]["prompt_template"] == "Summarize this legal document in 1 sentences"
+
+        with pytest.raises(ValueError, match="already registered"):
+            registry.register(summarize_template())
+        with pytest.raises(ValueError, match="Unknown workflow template 'translate'"):
+            registry.instantiate("translate")
+
+        assert registry.remove("summarize_brief")
+        assert registry.get("summarize_brief") is None
+        assert registry.get("summarize_doc").name == "summarize_doc"
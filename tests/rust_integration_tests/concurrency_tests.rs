@@ -161,12 +161,18 @@ async fn test_workflow_execution_stats() {
         total_nodes: 10,
         successful_nodes: 8,
         failed_nodes: 2,
+        skipped_nodes: 0,
         avg_execution_time_ms: 150.5,
         max_concurrent_nodes: 5,
         total_execution_time_ms: 3000,
         peak_memory_usage_mb: Some(256.8),
         semaphore_acquisitions: 25,
         avg_semaphore_wait_ms: 12.3,
+        total_tool_calls: 0,
+        failed_tool_calls: 0,
+        spilled_outputs: 0,
+        spilled_bytes: 0,
+        usage: Default::default(),
     };
 
     assert_eq!(stats.total_nodes, 10);
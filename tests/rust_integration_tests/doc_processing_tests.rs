@@ -216,6 +216,17 @@ async fn test_custom_document_loader_config() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: true,
         extraction_settings: std::collections::HashMap::new(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let loader = DocumentLoader::with_config(config);
@@ -679,6 +690,17 @@ async fn test_document_size_limits_and_configuration() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: true,
         extraction_settings: std::collections::HashMap::new(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let loader = DocumentLoader::with_config(config);
@@ -810,6 +832,17 @@ async fn test_document_content_preservation() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: true,
         extraction_settings: std::collections::HashMap::new(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let loader = DocumentLoader::with_config(config);
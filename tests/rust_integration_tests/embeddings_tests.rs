@@ -16,7 +16,9 @@ fn create_test_embedding_config() -> EmbeddingConfig {
         timeout_seconds: Some(30),
         max_batch_size: Some(32),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     }
 }
 
@@ -70,7 +72,9 @@ async fn test_openai_embedding_provider_creation() {
         timeout_seconds: Some(30),
         max_batch_size: Some(16),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let provider_result = EmbeddingProviderFactory::create_provider(config);
@@ -232,7 +236,9 @@ async fn test_openai_real_embeddings() {
         timeout_seconds: Some(30),
         max_batch_size: Some(16),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).expect("Failed to create service");
@@ -280,7 +286,9 @@ async fn test_openai_real_batch_embeddings() {
         timeout_seconds: Some(30),
         max_batch_size: Some(16),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).expect("Failed to create service");
@@ -355,7 +363,9 @@ async fn test_huggingface_real_embeddings() {
         timeout_seconds: Some(60), // `HuggingFace` can be slower
         max_batch_size: Some(32),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).expect("Failed to create service");
@@ -405,7 +415,9 @@ async fn test_semantic_search_with_real_embeddings() {
         timeout_seconds: Some(30),
         max_batch_size: Some(16),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).expect("Failed to create service");
@@ -499,7 +511,9 @@ async fn test_embedding_provider_comparison() {
             timeout_seconds: Some(30),
             max_batch_size: Some(16),
             extra_params: HashMap::new(),
+            retry: None,
             python_instance: None,
+            timeouts: Default::default(),
         };
 
         let service = EmbeddingService::new(config).expect("Failed to create OpenAI service");
@@ -519,7 +533,9 @@ async fn test_embedding_provider_comparison() {
             timeout_seconds: Some(60),
             max_batch_size: Some(32),
             extra_params: HashMap::new(),
+            retry: None,
             python_instance: None,
+            timeouts: Default::default(),
         };
 
         let service = EmbeddingService::new(config).expect("Failed to create HuggingFace service");
@@ -559,7 +575,9 @@ async fn test_embedding_provider_capabilities() {
             timeout_seconds: None,
             max_batch_size: None,
             extra_params: HashMap::new(),
+            retry: None,
             python_instance: None,
+            timeouts: Default::default(),
         },
         EmbeddingConfig {
             provider: EmbeddingProvider::HuggingFace,
@@ -569,7 +587,9 @@ async fn test_embedding_provider_capabilities() {
             timeout_seconds: None,
             max_batch_size: None,
             extra_params: HashMap::new(),
+            retry: None,
             python_instance: None,
+            timeouts: Default::default(),
         },
     ];
 
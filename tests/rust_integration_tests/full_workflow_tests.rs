@@ -162,6 +162,7 @@ async fn test_workflow_with_conditions() {
         "Checks if text is long",
         NodeType::Condition {
             handler_id: "input_length_gt_100".to_string(),
+            expression: None,
         },
     );
 
@@ -350,12 +351,18 @@ async fn test_workflow_execution_stats() {
         total_nodes: 5,
         successful_nodes: 4,
         failed_nodes: 1,
+        skipped_nodes: 0,
         avg_execution_time_ms: 250.0,
         max_concurrent_nodes: 3,
         total_execution_time_ms: 1250,
         peak_memory_usage_mb: Some(128.0),
         semaphore_acquisitions: 10,
         avg_semaphore_wait_ms: 15.5,
+        total_tool_calls: 0,
+        failed_tool_calls: 0,
+        spilled_outputs: 0,
+        spilled_bytes: 0,
+        usage: Default::default(),
     };
 
     context.set_stats(stats);
@@ -825,7 +832,9 @@ async fn test_real_embeddings_workflow() {
         timeout_seconds: Some(30),
         max_batch_size: Some(16),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service_result = EmbeddingService::new(embedding_config);
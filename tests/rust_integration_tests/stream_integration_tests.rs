@@ -38,6 +38,7 @@ fn event_tag(e: &StreamEvent) -> &'static str {
         StreamEvent::WorkflowCompleted { .. } => "workflow_completed",
         StreamEvent::WorkflowFailed { .. } => "workflow_failed",
         StreamEvent::Token { .. } => "token",
+        StreamEvent::NodeOutputDelta { .. } => "node_output_delta",
         StreamEvent::LlmCallStarted { .. } => "llm_call_started",
         StreamEvent::LlmCallCompleted { .. } => "llm_call_completed",
         StreamEvent::ToolCallStarted { .. } => "tool_call_started",
@@ -212,6 +212,7 @@ async fn test_conditional_workflow_execution() {
         "Check condition",
         NodeType::Condition {
             handler_id: "value_gt_10".to_string(),
+            expression: None,
         },
     );
     let true_path_node = WorkflowNode::new(
@@ -513,10 +514,11 @@ async fn test_workflow_state_transitions() {
     // Test failure state
     context.state = WorkflowState::Failed {
         error: "Processing failed".to_string(),
+        reason: None,
     };
 
     match &context.state {
-        WorkflowState::Failed { error } => {
+        WorkflowState::Failed { error, .. } => {
             assert_eq!(error, "Processing failed");
         }
         _ => panic!("Expected Failed state"),
@@ -540,6 +542,7 @@ async fn test_complex_workflow_validation() {
                     },
                     2 => NodeType::Condition {
                         handler_id: format!("condition{i}"),
+                        expression: None,
                     },
                     3 => NodeType::Join,
                     _ => unreachable!(),
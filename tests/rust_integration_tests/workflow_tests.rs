@@ -456,6 +456,7 @@ async fn test_workflow_execution_with_conditions() {
         "A conditional node",
         NodeType::Condition {
             handler_id: "length_gt_5".to_string(),
+            expression: None,
         },
     );
 
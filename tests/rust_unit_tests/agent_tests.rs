@@ -94,6 +94,7 @@ async fn test_agent_creation() {
         model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
 
     let agent = AgentBuilder::new("test_agent", llm_config)
@@ -156,6 +157,7 @@ fn test_agent_config_capabilities() {
         model: "model".into(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
     let config = AgentConfig::new("name", "desc", llm_config)
         .with_capabilities(vec![
@@ -237,6 +239,7 @@ async fn test_agent_validation() {
         model: "model".into(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
     let config = AgentConfig::new("name", "desc", llm_config)
         .with_max_tokens(64)
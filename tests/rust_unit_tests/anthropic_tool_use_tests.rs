@@ -0,0 +1,270 @@
+//! `Anthropic` tool use: tools sent in `Anthropic`'s format, `tool_use` blocks
+//! parsed into tool calls, `tool_result` blocks sent back and `max_tokens`
+//! defaulted from the model, against a scripted `/v1/messages` server
+
+use super::test_helpers::{MockRequest, MockRequests, MockResponse, MockServer};
+use futures::StreamExt;
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{FinishReason, LlmConfig, LlmProviderFactory, LlmRequest, LlmTool},
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+const MODEL: &str = "claude-sonnet-4-20250514";
+
+/// Start a server answering each request with the next scripted response,
+/// recording the request bodies. A string response is sent as a server-sent
+/// event stream, anything else as a JSON message.
+async fn spawn_anthropic_server(responses: Vec<Value>) -> (String, MockRequests) {
+    let server = MockServer::replaying(
+        responses
+            .into_iter()
+            .map(|response| match response {
+                Value::String(events) => {
+                    MockResponse::new(200, events).with_header("Content-Type", "text/event-stream")
+                }
+                message => MockResponse::json(200, message),
+            })
+            .collect(),
+    )
+    .await;
+    (format!("{}/v1", server.url()), server.requests())
+}
+
+fn anthropic(base_url: String) -> LlmConfig {
+    LlmConfig::Anthropic {
+        api_key: "sk-ant-test".to_string(),
+        model: MODEL.to_string(),
+        base_url: Some(base_url),
+        timeouts: Default::default(),
+    }
+}
+
+fn message(content: Value, stop_reason: &str) -> Value {
+    json!({
+        "id": "msg_1",
+        "type": "message",
+        "role": "assistant",
+        "content": content,
+        "stop_reason": stop_reason,
+        "usage": {"input_tokens": 12, "output_tokens": 4}
+    })
+}
+
+fn tool_use(id: &str, city: &str) -> Value {
+    json!({"type": "tool_use", "id": id, "name": "get_weather", "input": {"city": city}})
+}
+
+fn weather_tool() -> ToolMetadata {
+    ToolMetadata::new(
+        "get_weather",
+        "Current weather in a city",
+        json!({
+            "type": "object",
+            "properties": {"city": {"type": "string"}},
+            "required": ["city"]
+        }),
+    )
+}
+
+/// Run a `Weather` agent node with the `get_weather` tool against `url`
+async fn run_weather_node(url: String) -> graphbit_core::types::WorkflowContext {
+    let handler: ToolHandler = Arc::new(|args: Value| {
+        Box::pin(async move {
+            Ok(json!(format!(
+                "Sunny in {}",
+                args["city"].as_str().unwrap()
+            )))
+        })
+    });
+    let registry = ToolRegistry::new();
+    registry.register_tool(weather_tool(), handler).unwrap();
+
+    let mut workflow = Workflow::new("weather", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "Weather",
+                "",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(AgentId::new(), "What is the weather?"),
+                },
+            )
+            .with_tools(["get_weather"]),
+        )
+        .unwrap();
+
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(anthropic(url))
+        .with_tool_registry(registry)
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_two_round_tool_conversation() {
+    let (url, requests) = spawn_anthropic_server(vec![
+        message(
+            json!([{"type": "text", "text": "Let me check."}, tool_use("toolu_1", "Paris")]),
+            "tool_use",
+        ),
+        message(
+            json!([{"type": "text", "text": "It is sunny in Paris."}]),
+            "end_turn",
+        ),
+    ])
+    .await;
+
+    let context = run_weather_node(url).await;
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(
+        context.get_node_output("Weather"),
+        Some(&json!("It is sunny in Paris."))
+    );
+
+    let requests: Vec<Value> = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(MockRequest::json)
+        .collect();
+    assert_eq!(requests.len(), 2);
+
+    // Tools go out in Anthropic's format and max_tokens defaults to the model's limit
+    let first = &requests[0];
+    assert_eq!(first["max_tokens"], 64_000);
+    assert_eq!(first["tools"][0]["name"], "get_weather");
+    assert_eq!(
+        first["tools"][0]["input_schema"]["properties"]["city"]["type"],
+        "string"
+    );
+    assert!(first["tools"][0].get("parameters").is_none());
+
+    // The follow-up replays the tool_use block and answers it with a tool_result
+    let messages = requests[1]["messages"].as_array().unwrap();
+    let assistant = &messages[messages.len() - 2];
+    assert_eq!(assistant["role"], "assistant");
+    assert_eq!(assistant["content"][0]["text"], "Let me check.");
+    assert_eq!(assistant["content"][1]["type"], "tool_use");
+    assert_eq!(assistant["content"][1]["id"], "toolu_1");
+    assert_eq!(assistant["content"][1]["input"], json!({"city": "Paris"}));
+    let result = &messages[messages.len() - 1];
+    assert_eq!(result["role"], "user");
+    assert_eq!(result["content"][0]["type"], "tool_result");
+    assert_eq!(result["content"][0]["tool_use_id"], "toolu_1");
+    assert!(
+        result["content"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("Sunny in Paris")
+    );
+}
+
+#[tokio::test]
+async fn test_parallel_tool_results_share_one_user_message() {
+    let (url, requests) = spawn_anthropic_server(vec![
+        message(
+            json!([tool_use("toolu_1", "Paris"), tool_use("toolu_2", "Oslo")]),
+            "tool_use",
+        ),
+        message(json!([{"type": "text", "text": "Done."}]), "end_turn"),
+    ])
+    .await;
+
+    let context = run_weather_node(url).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let requests: Vec<Value> = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(MockRequest::json)
+        .collect();
+    let messages = requests[1]["messages"].as_array().unwrap();
+    let results = &messages[messages.len() - 1];
+    assert_eq!(results["role"], "user");
+    let ids: Vec<_> = results["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|block| block["tool_use_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["toolu_1", "toolu_2"]);
+}
+
+#[tokio::test]
+async fn test_tool_use_response_is_parsed() {
+    let (url, requests) = spawn_anthropic_server(vec![message(
+        json!([tool_use("toolu_1", "Paris")]),
+        "tool_use",
+    )])
+    .await;
+    let provider = LlmProviderFactory::create_provider(anthropic(url)).unwrap();
+
+    let request = LlmRequest::new("What is the weather?")
+        .with_max_tokens(256)
+        .with_tool(LlmTool::new(
+            "get_weather",
+            "Current weather in a city",
+            json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        ));
+    let response = provider.complete(request).await.unwrap();
+
+    assert!(matches!(response.finish_reason, FinishReason::ToolCalls));
+    assert_eq!(response.tool_calls.len(), 1);
+    assert_eq!(response.tool_calls[0].id, "toolu_1");
+    assert_eq!(response.tool_calls[0].name, "get_weather");
+    assert_eq!(response.tool_calls[0].parameters, json!({"city": "Paris"}));
+    // An explicit max_tokens is sent unchanged
+    assert_eq!(requests.lock().unwrap()[0].json()["max_tokens"], 256);
+}
+
+#[tokio::test]
+async fn test_streamed_tool_use_arrives_in_the_final_chunk() {
+    let events = [
+        json!({"type": "message_start", "message": {"id": "msg_1"}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Checking."}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"city\": "}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "\"Paris\"}"}}),
+        json!({"type": "content_block_stop", "index": 1}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use", "stop_sequence": null}, "usage": {"output_tokens": 9}}),
+        json!({"type": "message_stop"}),
+    ]
+    .iter()
+    .map(|event| format!("event: {}\ndata: {event}\n\n", event["type"].as_str().unwrap()))
+    .collect::<Vec<_>>()
+    .concat();
+    let (url, _) = spawn_anthropic_server(vec![Value::String(events)]).await;
+    let provider = LlmProviderFactory::create_provider(anthropic(url)).unwrap();
+
+    let chunks: Vec<_> = provider
+        .stream(LlmRequest::new("What is the weather?"))
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].content, "Checking.");
+    assert!(chunks[0].tool_calls.is_empty());
+    let last = &chunks[1];
+    assert!(matches!(last.finish_reason, FinishReason::ToolCalls));
+    assert_eq!(last.tool_calls.len(), 1);
+    assert_eq!(last.tool_calls[0].id, "toolu_1");
+    assert_eq!(last.tool_calls[0].parameters, json!({"city": "Paris"}));
+}
@@ -0,0 +1,369 @@
+//! Binary attachments: produced by document loader and HTTP nodes, moved to the
+//! output store when large, uploaded as multipart fields and sent to models as images
+
+use super::test_helpers::{MockRequests, MockResponse, MockServer};
+use graphbit_core::{
+    attachment::{self, Attachment, AttachmentData},
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::LlmConfig,
+    output_store::{FileOutputStore, OutputSpill},
+    types::{AgentId, WorkflowContext, WorkflowId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const IMAGE: &[u8] = b"\x89PNG fake image";
+
+/// Start a server answering each request with the next `(content type, body)`
+/// response, repeating the last one
+async fn spawn_server(responses: Vec<(&'static str, Vec<u8>)>) -> (String, MockRequests) {
+    let server = MockServer::replaying(
+        responses
+            .into_iter()
+            .map(|(content_type, body)| {
+                MockResponse::new(200, body).with_header("Content-Type", content_type)
+            })
+            .collect(),
+    )
+    .await;
+    (server.url(), server.requests())
+}
+
+fn http_node(name: &str, url: String, method: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::HttpRequest {
+            url,
+            method: method.to_string(),
+            headers: HashMap::new(),
+        },
+    )
+}
+
+/// Workflow with the given nodes, each one feeding the next
+fn chain(nodes: Vec<WorkflowNode>) -> Workflow {
+    let mut workflow = Workflow::new("attachments", "");
+    let ids: Vec<_> = nodes
+        .into_iter()
+        .map(|node| workflow.add_node(node).unwrap())
+        .collect();
+    for pair in ids.windows(2) {
+        workflow
+            .connect_nodes(pair[0].clone(), pair[1].clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+fn node_error(context: &WorkflowContext, name: &str) -> String {
+    context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == name)
+        .and_then(|record| record.error.clone())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_inline_bytes_serialize_as_base64() {
+    let attachment = Attachment::from_bytes(IMAGE.to_vec(), "image/png").with_filename("chart.png");
+
+    let value = serde_json::to_value(&attachment).unwrap();
+    assert_eq!(value["data"]["storage"], "inline");
+    assert_eq!(value["data"]["bytes"], "iVBORyBmYWtlIGltYWdl");
+    assert_eq!(value["size_bytes"], IMAGE.len());
+
+    let restored: Attachment = serde_json::from_value(value).unwrap();
+    assert_eq!(restored, attachment);
+    assert_eq!(restored.read(None).unwrap(), IMAGE);
+    assert!(restored.local_file_name().ends_with("-chart.png"));
+}
+
+#[test]
+fn test_large_attachments_move_to_the_output_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let spill = OutputSpill::new(Arc::new(FileOutputStore::new(dir.path()).unwrap()), 8);
+    let mut context = WorkflowContext::new(WorkflowId::new()).with_output_spill(spill.clone());
+
+    context
+        .set_attachments(
+            "render",
+            vec![
+                Attachment::from_bytes(IMAGE.to_vec(), "image/png"),
+                Attachment::from_bytes(b"tiny".to_vec(), "text/plain"),
+            ],
+        )
+        .unwrap();
+
+    let [large, small] = context.get_attachments("render") else {
+        panic!("expected two attachments");
+    };
+    assert_eq!(large.data, AttachmentData::Stored);
+    assert!(matches!(small.data, AttachmentData::Inline { .. }));
+    assert_eq!(context.read_attachment(large).unwrap(), IMAGE);
+    assert_eq!(large.read(Some(&spill)).unwrap(), IMAGE);
+
+    // A deserialized context reads stored attachments once the spill is attached
+    let restored = WorkflowContext::from_json(&context.to_json(None).unwrap()).unwrap();
+    let large = restored.get_attachments("render")[0].clone();
+    assert!(large.read(None).is_err());
+    let restored = restored.with_output_spill(spill);
+    assert_eq!(restored.read_attachment(&large).unwrap(), IMAGE);
+
+    context.max_attachment_bytes = 4;
+    let error = context
+        .set_attachments(
+            "render",
+            vec![Attachment::from_bytes(IMAGE.to_vec(), "image/png")],
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("over the 4 byte limit"), "{error}");
+}
+
+#[tokio::test]
+async fn test_document_loader_attaches_the_loaded_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.txt");
+    std::fs::write(&path, "Quarterly notes").unwrap();
+    let workflow = chain(vec![WorkflowNode::new(
+        "loader",
+        "",
+        NodeType::DocumentLoader {
+            document_type: "txt".to_string(),
+            source_path: path.to_string_lossy().into_owned(),
+            encoding: None,
+        },
+    )]);
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let [attachment] = context.get_attachments("loader") else {
+        panic!("expected one attachment");
+    };
+    assert_eq!(attachment.mime_type, "text/plain");
+    assert_eq!(attachment.filename.as_deref(), Some("notes.txt"));
+    assert_eq!(attachment.data, AttachmentData::File { path });
+    assert_eq!(
+        context.read_attachment(attachment).unwrap(),
+        b"Quarterly notes"
+    );
+}
+
+#[tokio::test]
+async fn test_binary_response_is_attached_and_rendered_as_a_temporary_file() {
+    let (url, _) = spawn_server(vec![("image/png", IMAGE.to_vec())]).await;
+    let workflow = chain(vec![
+        http_node("fetch", format!("{url}/chart"), "GET"),
+        WorkflowNode::new(
+            "measure",
+            "",
+            NodeType::ShellCommand {
+                command_template: "wc -c < {{nodes.fetch.attachments[0]}}".to_string(),
+                working_dir: None,
+                env: HashMap::new(),
+                timeout_seconds: 10,
+            },
+        ),
+    ]);
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_allow_shell_nodes(true)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    let output = context.get_node_output("fetch").unwrap();
+    assert_eq!(output["status"], 200);
+    assert_eq!(output["body"], Value::Null);
+    assert_eq!(output["attachment"]["mime_type"], "image/png");
+    let attachment = &context.get_attachments("fetch")[0];
+    assert_eq!(context.read_attachment(attachment).unwrap(), IMAGE);
+
+    let measured = context.get_node_output("measure").unwrap()["stdout"].clone();
+    assert_eq!(measured.as_str().unwrap().trim(), IMAGE.len().to_string());
+    // The local copy the shell command read is gone once the execution finished
+    assert!(!attachment::temp_dir(context.execution_id).exists());
+}
+
+#[tokio::test]
+async fn test_attachments_over_the_limit_fail_the_node() {
+    let (url, _) = spawn_server(vec![("image/png", IMAGE.to_vec())]).await;
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_max_attachment_bytes(4)
+        .execute(chain(vec![http_node("fetch", url, "GET")]), None)
+        .await
+        .unwrap();
+
+    assert!(!matches!(context.state, WorkflowState::Completed));
+    let error = node_error(&context, "fetch");
+    assert!(error.contains("over the 4 byte limit"), "{error}");
+}
+
+#[tokio::test]
+async fn test_multipart_fields_upload_attachments() {
+    let (url, requests) = spawn_server(vec![
+        ("image/png", IMAGE.to_vec()),
+        ("application/json", br#"{"uploaded":true}"#.to_vec()),
+    ])
+    .await;
+    let mut upload = http_node("upload", format!("{url}/files"), "POST");
+    upload.config.insert(
+        "multipart".to_string(),
+        json!({"file": "{{nodes.fetch.attachments[0]}}", "purpose": "vision"}),
+    );
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(
+            chain(vec![
+                http_node("fetch", format!("{url}/chart"), "GET"),
+                upload,
+            ]),
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(
+        context.get_node_output("upload"),
+        Some(&json!({"status": 200, "body": {"uploaded": true}}))
+    );
+    let requests = requests.lock().unwrap();
+    let body = String::from_utf8_lossy(&requests[1].body);
+    assert!(body.contains("name=\"file\"; filename=\""), "{body}");
+    assert!(body.contains("Content-Type: image/png"), "{body}");
+    assert!(body.contains("PNG fake image"), "{body}");
+    assert!(body.contains("name=\"purpose\"\r\n\r\nvision"), "{body}");
+}
+
+#[tokio::test]
+async fn test_multipart_reference_to_a_missing_attachment_fails() {
+    let (url, _) = spawn_server(vec![("application/json", b"{}".to_vec())]).await;
+    let mut upload = http_node("upload", url, "POST");
+    upload.config.insert(
+        "multipart".to_string(),
+        json!({"file": "{{nodes.fetch.attachments[0]}}"}),
+    );
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(chain(vec![upload]), None)
+        .await
+        .unwrap();
+
+    let error = node_error(&context, "upload");
+    assert!(
+        error.contains("node 'fetch' has no such attachment"),
+        "{error}"
+    );
+}
+
+fn describe_node() -> WorkflowNode {
+    WorkflowNode::new(
+        "describe",
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(
+                AgentId::new(),
+                "Describe {{nodes.fetch.attachments[0]}} in one line",
+            ),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_prompt_images_are_sent_to_the_model() {
+    let (files, _) = spawn_server(vec![("image/png", IMAGE.to_vec())]).await;
+    let reply = json!({
+        "id": "msg_1",
+        "type": "message",
+        "role": "assistant",
+        "content": [{"type": "text", "text": "A rising line chart"}],
+        "stop_reason": "end_turn",
+        "usage": {"input_tokens": 12, "output_tokens": 4}
+    });
+    let (anthropic, requests) =
+        spawn_server(vec![("application/json", reply.to_string().into_bytes())]).await;
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(LlmConfig::Anthropic {
+            api_key: "sk-ant-test".to_string(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            base_url: Some(format!("{anthropic}/v1")),
+            timeouts: Default::default(),
+        })
+        .execute(
+            chain(vec![
+                http_node("fetch", format!("{files}/chart"), "GET"),
+                describe_node(),
+            ]),
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    let request = requests.lock().unwrap()[0].json();
+    let blocks = request["messages"][0]["content"].as_array().unwrap();
+    assert_eq!(
+        blocks[0],
+        json!({
+            "type": "image",
+            "source": {"type": "base64", "media_type": "image/png", "data": "iVBORyBmYWtlIGltYWdl"}
+        })
+    );
+    let text = blocks[1]["text"].as_str().unwrap();
+    assert!(text.contains("Describe [image 1: "), "{text}");
+    assert!(!text.contains("attachments[0]"), "{text}");
+}
+
+#[tokio::test]
+async fn test_prompt_images_need_a_provider_accepting_them() {
+    let (files, _) = spawn_server(vec![("image/png", IMAGE.to_vec())]).await;
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(LlmConfig::ollama_with_base_url(
+            "llama3.2",
+            "http://127.0.0.1:9",
+        ))
+        .execute(
+            chain(vec![
+                http_node("fetch", format!("{files}/chart"), "GET"),
+                describe_node(),
+            ]),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let error = node_error(&context, "describe");
+    assert!(error.contains("does not accept image inputs"), "{error}");
+}
@@ -0,0 +1,337 @@
+//! Audit log: one chained record per LLM, tool, HTTP and embedding call, the
+//! prompt policies and rotation of the JSONL file
+
+use super::test_helpers::{MockResponse, MockServer, ScriptedProvider, TestAgent};
+use async_trait::async_trait;
+use graphbit_core::{
+    AuditLog, AuditPolicy, AuditSink, JsonlAuditSink,
+    agents::AgentConfig,
+    audit::{AuditCall, AuditCallKind, AuditContext, AuditOutcome, AuditRecord, verify_chain},
+    embeddings::{EmbeddingConfig, EmbeddingProvider, EmbeddingService},
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmToolCall, LlmUsage},
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Sink keeping records in memory
+#[derive(Clone, Default)]
+struct MemorySink {
+    records: Arc<Mutex<Vec<AuditRecord>>>,
+}
+
+#[async_trait]
+impl AuditSink for MemorySink {
+    async fn write(&self, record: &AuditRecord) -> graphbit_core::GraphBitResult<()> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+impl MemorySink {
+    fn records(&self) -> Vec<AuditRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+fn call(prompt: &str) -> AuditCall {
+    AuditCall {
+        kind: AuditCallKind::Llm,
+        provider: "openai".to_string(),
+        model: Some("gpt-4o-mini".to_string()),
+        started_at: chrono::Utc::now(),
+        latency_ms: 12,
+        prompt: prompt.to_string(),
+        completion: Some("Done".to_string()),
+        usage: Some(LlmUsage::new(10, 2)),
+        outcome: AuditOutcome::Success,
+    }
+}
+
+#[tokio::test]
+async fn test_agent_node_records_its_llm_and_tool_calls() {
+    let registry = ToolRegistry::new();
+    let handler: ToolHandler =
+        Arc::new(|args: serde_json::Value| Box::pin(async move { Ok(json!({"echo": args})) }));
+    registry
+        .register_tool(
+            ToolMetadata::new("lookup", "Look up an order", json!({"type": "object"})),
+            handler,
+        )
+        .unwrap();
+
+    let agent_id = AgentId::new();
+    let provider = ScriptedProvider::replaying(vec![
+        LlmResponse::new("", "scripted-model").with_tool_calls(vec![LlmToolCall {
+            id: "call_lookup".to_string(),
+            name: "lookup".to_string(),
+            parameters: json!({"order": 42}),
+        }]),
+        LlmResponse::new("Order 42 shipped", "scripted-model").with_usage(LlmUsage::new(30, 5)),
+    ]);
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_tool_registry(registry);
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Support", "", LlmConfig::default()).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::Full);
+
+    let mut workflow = Workflow::new("support", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "support",
+                "",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(agent_id, "Where is order 42?"),
+                },
+            )
+            .with_tools(["lookup"]),
+        )
+        .unwrap();
+    let context = executor
+        .with_audit_log(log.clone())
+        .execute(workflow, None)
+        .await
+        .unwrap();
+    log.flush().await.unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let records = sink.records();
+    let kinds: Vec<_> = records.iter().map(|record| record.kind).collect();
+    assert_eq!(
+        kinds,
+        [AuditCallKind::Llm, AuditCallKind::Tool, AuditCallKind::Llm]
+    );
+    let execution_id = context.execution_id.to_string();
+    for record in &records {
+        assert_eq!(record.execution_id.as_deref(), Some(execution_id.as_str()));
+        assert_eq!(record.node_name.as_deref(), Some("support"));
+        assert_eq!(record.outcome, AuditOutcome::Success);
+    }
+
+    let first = &records[0];
+    assert_eq!(first.provider, "scripted");
+    assert_eq!(first.model.as_deref(), Some("scripted-model"));
+    assert!(
+        first.prompt.contains("Where is order 42?"),
+        "{}",
+        first.prompt
+    );
+    assert!(first.completion.as_deref().unwrap().contains("call_lookup"));
+
+    let tool = &records[1];
+    assert_eq!(tool.model.as_deref(), Some("lookup"));
+    assert_eq!(tool.prompt, r#"{"order":42}"#);
+    assert_eq!(tool.completion.as_deref(), Some(r#"{"echo":{"order":42}}"#));
+
+    let last = &records[2];
+    assert_eq!(last.completion.as_deref(), Some("Order 42 shipped"));
+    assert_eq!(last.usage.as_ref().unwrap().total_tokens, 35);
+    verify_chain(&records).unwrap();
+}
+
+#[tokio::test]
+async fn test_http_request_node_is_recorded_without_headers() {
+    let url = format!(
+        "{}/orders",
+        MockServer::responding(MockResponse::json(200, r#"{"id":7}"#))
+            .await
+            .url()
+    );
+    let mut workflow = Workflow::new("orders", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "create_order",
+                "",
+                NodeType::HttpRequest {
+                    url: url.clone(),
+                    method: "post".to_string(),
+                    headers: HashMap::from([(
+                        "Authorization".to_string(),
+                        "Bearer secret-token".to_string(),
+                    )]),
+                },
+            )
+            .with_config("body".to_string(), json!({"item": "book"})),
+        )
+        .unwrap();
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::Full);
+
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_audit_log(log.clone())
+        .execute(workflow, None)
+        .await
+        .unwrap();
+    log.flush().await.unwrap();
+
+    let records = sink.records();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.kind, AuditCallKind::Http);
+    assert_eq!(record.node_name.as_deref(), Some("create_order"));
+    assert_eq!(
+        record.prompt,
+        format!("POST {url}\n\n{{\"item\":\"book\"}}")
+    );
+    assert!(!record.prompt.contains("secret-token"));
+    assert_eq!(
+        record.completion.as_deref(),
+        Some(r#"{"body":{"id":7},"status":200}"#)
+    );
+}
+
+#[tokio::test]
+async fn test_embedding_requests_are_recorded_inside_a_scope() {
+    let base_url = MockServer::responding(MockResponse::json(
+        200,
+        r#"{"data":[{"embedding":[0.1,0.2]}],"model":"text-embedding-3-small","usage":{"prompt_tokens":4,"total_tokens":4}}"#,
+    ))
+    .await
+    .url();
+    let service = EmbeddingService::new(EmbeddingConfig {
+        provider: EmbeddingProvider::OpenAI,
+        api_key: "test".to_string(),
+        model: "text-embedding-3-small".to_string(),
+        base_url: Some(base_url),
+        timeout_seconds: Some(5),
+        max_batch_size: None,
+        extra_params: HashMap::new(),
+        retry: None,
+        python_instance: None,
+        timeouts: Default::default(),
+    })
+    .unwrap();
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::Full);
+
+    // Outside a scope nothing is recorded
+    service.embed_text("unaudited").await.unwrap();
+    let context = AuditContext {
+        execution_id: Some("run-1".to_string()),
+        ..AuditContext::default()
+    };
+    log.scope(context, service.embed_text("audited text"))
+        .await
+        .unwrap();
+    log.flush().await.unwrap();
+
+    let records = sink.records();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.kind, AuditCallKind::Embedding);
+    assert_eq!(record.provider, "openai");
+    assert_eq!(record.execution_id.as_deref(), Some("run-1"));
+    assert_eq!(record.prompt, "audited text");
+    assert_eq!(record.usage.as_ref().unwrap().prompt_tokens, 4);
+}
+
+#[tokio::test]
+async fn test_redact_policy_removes_api_keys_and_emails() {
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::redact());
+
+    log.record(
+        AuditContext::default(),
+        call("Use key sk-abcdefghijklmnopqrstuvwx to email jane.doe@example.com"),
+    );
+    log.flush().await.unwrap();
+
+    let prompt = &sink.records()[0].prompt;
+    assert_eq!(
+        prompt,
+        "Use key [REDACTED_API_KEY] to email [REDACTED_EMAIL]"
+    );
+}
+
+#[tokio::test]
+async fn test_hash_policy_keeps_only_hashes() {
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::Hash);
+
+    log.record(
+        AuditContext::default(),
+        call("My card is 4111 1111 1111 1111"),
+    );
+    log.flush().await.unwrap();
+
+    let record = &sink.records()[0];
+    assert!(record.prompt.starts_with("sha256:"));
+    assert!(!record.prompt.contains("4111"));
+    assert_eq!(
+        record.prompt,
+        AuditPolicy::Hash.apply("My card is 4111 1111 1111 1111")
+    );
+    assert_eq!(record.completion, Some(AuditPolicy::Hash.apply("Done")));
+}
+
+fn read_records(path: &std::path::Path) -> Vec<AuditRecord> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_jsonl_sink_rotates_and_the_chain_detects_tampering() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let log = AuditLog::new(
+        JsonlAuditSink::new(&path).with_max_bytes(1_500),
+        AuditPolicy::Full,
+    );
+    for i in 0..6 {
+        log.record(AuditContext::default(), call(&format!("prompt {i}")));
+    }
+    log.flush().await.unwrap();
+    drop(log);
+
+    // A new log on the same file continues the chain
+    let log = AuditLog::new(
+        JsonlAuditSink::new(&path).with_max_bytes(1_500),
+        AuditPolicy::Full,
+    );
+    log.record(AuditContext::default(), call("prompt 6"));
+    log.flush().await.unwrap();
+
+    let mut rotated = 1;
+    while dir.path().join(format!("audit.jsonl.{rotated}")).exists() {
+        rotated += 1;
+    }
+    assert!(rotated > 2, "expected the file to be rotated");
+    let mut records = Vec::new();
+    for index in (1..rotated).rev() {
+        records.extend(read_records(
+            &dir.path().join(format!("audit.jsonl.{index}")),
+        ));
+    }
+    records.extend(read_records(&path));
+    assert_eq!(
+        records
+            .iter()
+            .map(|record| record.sequence)
+            .collect::<Vec<_>>(),
+        (0..7).collect::<Vec<_>>()
+    );
+    verify_chain(&records).unwrap();
+
+    let mut edited = records.clone();
+    edited[3].prompt = "something else".to_string();
+    assert!(verify_chain(&edited).is_err());
+    let mut removed = records;
+    removed.remove(3);
+    assert!(verify_chain(&removed).is_err());
+}
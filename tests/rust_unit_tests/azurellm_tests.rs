@@ -12,6 +12,7 @@ async fn test_azurellm_provider_creation() {
         deployment_name: "gpt-4o-mini".to_string(),
         endpoint: "https://test.openai.azure.com".to_string(),
         api_version: "2024-10-21".to_string(),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -50,6 +51,7 @@ async fn test_azurellm_config_helpers() {
             deployment_name,
             endpoint,
             api_version,
+            ..
         } => {
             assert_eq!(api_key, "test-key");
             assert_eq!(deployment_name, "gpt-4o-mini");
@@ -72,6 +74,7 @@ async fn test_azurellm_config_helpers() {
             deployment_name,
             endpoint,
             api_version,
+            ..
         } => {
             assert_eq!(api_key, "test-key");
             assert_eq!(deployment_name, "gpt-4o");
@@ -89,6 +92,7 @@ async fn test_azurellm_message_formatting() {
         deployment_name: "gpt-4o-mini".to_string(),
         endpoint: "https://test.openai.azure.com".to_string(),
         api_version: "2024-10-21".to_string(),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -125,6 +129,7 @@ async fn test_azurellm_with_tools() {
         deployment_name: "gpt-4o".to_string(),
         endpoint: "https://test.openai.azure.com".to_string(),
         api_version: "2024-10-21".to_string(),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -184,6 +189,7 @@ async fn test_azurellm_different_api_versions() {
             deployment_name: "gpt-4o-mini".to_string(),
             endpoint: "https://test.openai.azure.com".to_string(),
             api_version: version.to_string(),
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -209,6 +215,7 @@ async fn test_azurellm_different_deployments() {
             deployment_name: deployment.to_string(),
             endpoint: "https://test.openai.azure.com".to_string(),
             api_version: "2024-10-21".to_string(),
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -237,6 +244,7 @@ async fn test_azurellm_provider_capabilities() {
         deployment_name: "gpt-4o-mini".to_string(),
         endpoint: "https://test.openai.azure.com".to_string(),
         api_version: "2024-10-21".to_string(),
+        timeouts: Default::default(),
     };
 
     let provider = LlmProviderFactory::create_provider(config).unwrap();
@@ -256,6 +264,7 @@ async fn test_azurellm_error_handling() {
         deployment_name: "gpt-4o-mini".to_string(),
         endpoint: "https://test.openai.azure.com".to_string(),
         api_version: "2024-10-21".to_string(),
+        timeouts: Default::default(),
     })
     .unwrap();
 
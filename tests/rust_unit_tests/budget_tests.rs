@@ -0,0 +1,233 @@
+//! Execution budgets: halting at the node that uses up the budget, exemptions
+//! and what happens to nodes still running
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    BudgetPolicy, FailureReason,
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmUsage},
+    types::{AbandonedNode, AgentId, SkipReason, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::time::{Duration, Instant};
+
+/// Executor with one agent reporting 40 prompt and 60 completion tokens per
+/// call, taking 200ms to answer prompts mentioning "soon" and two seconds for
+/// "slow"
+async fn executor(agent_id: &AgentId) -> WorkflowExecutor {
+    let provider = ScriptedProvider::new(|_| {
+        Ok(LlmResponse::new("answer", "scripted-model").with_usage(LlmUsage::new(40, 60)))
+    })
+    .with_latency(|request| {
+        let mentions = |word: &str| request.messages.iter().any(|m| m.content.contains(word));
+        if mentions("soon") {
+            Duration::from_millis(200)
+        } else if mentions("slow") {
+            Duration::from_secs(2)
+        } else {
+            Duration::ZERO
+        }
+    })
+    .with_cost_per_token(0.001, 0.002);
+    let executor = WorkflowExecutor::new().without_retries();
+    executor
+        .register_agent(std::sync::Arc::new(TestAgent::new(
+            AgentConfig::new("Worker", "", LlmConfig::default()).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+    executor
+}
+
+fn agent_node(agent_id: &AgentId, name: &str, prompt: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), prompt),
+        },
+    )
+    .with_registered_agent()
+}
+
+/// Workflow running `nodes` one after the other
+fn chain(nodes: Vec<WorkflowNode>) -> Workflow {
+    let mut workflow = Workflow::new("budget", "");
+    let ids: Vec<_> = nodes
+        .into_iter()
+        .map(|node| workflow.add_node(node).unwrap())
+        .collect();
+    for pair in ids.windows(2) {
+        workflow
+            .connect_nodes(pair[0].clone(), pair[1].clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+/// Usage and cost the run failed with
+fn budget_failure(context: &WorkflowContext) -> (LlmUsage, f64) {
+    match &context.state {
+        WorkflowState::Failed {
+            reason: Some(FailureReason::BudgetExceeded { usage, cost_usd }),
+            ..
+        } => (usage.clone(), *cost_usd),
+        state => panic!("expected a budget failure, got {state:?}"),
+    }
+}
+
+fn ran(context: &WorkflowContext, name: &str) -> bool {
+    context
+        .get_node_executions()
+        .iter()
+        .any(|record| record.node_name == name && record.error.is_none())
+}
+
+#[tokio::test]
+async fn test_run_halts_at_the_node_using_up_the_token_budget() {
+    let agent_id = AgentId::new();
+    let workflow = chain(
+        ["first", "second", "third", "fourth"]
+            .iter()
+            .map(|name| agent_node(&agent_id, name, "Do the work"))
+            .collect(),
+    );
+
+    let context = executor(&agent_id)
+        .await
+        .with_max_total_tokens(250)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    let (usage, _) = budget_failure(&context);
+    assert_eq!(usage.total_tokens, 300);
+    assert!(ran(&context, "first") && ran(&context, "second") && ran(&context, "third"));
+    assert!(!ran(&context, "fourth"));
+    assert_eq!(
+        context.abandoned_nodes.get("fourth"),
+        Some(&AbandonedNode::Skipped {
+            reason: SkipReason::Budget
+        })
+    );
+    let WorkflowState::Failed { error, .. } = &context.state else {
+        unreachable!()
+    };
+    assert!(error.contains("300 of 250 tokens"), "{error}");
+}
+
+#[tokio::test]
+async fn test_cost_budget_uses_the_provider_prices() {
+    let agent_id = AgentId::new();
+    let workflow = chain(
+        ["first", "second", "third"]
+            .iter()
+            .map(|name| agent_node(&agent_id, name, "Do the work"))
+            .collect(),
+    );
+
+    // Each call costs 40 * 0.001 + 60 * 0.002 = 0.16
+    let context = executor(&agent_id)
+        .await
+        .with_max_cost_usd(0.3)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    let (usage, cost_usd) = budget_failure(&context);
+    assert_eq!(usage.total_tokens, 200);
+    assert!((cost_usd - 0.32).abs() < 1e-9, "{cost_usd}");
+    assert!(ran(&context, "second"));
+    assert!(!ran(&context, "third"));
+}
+
+#[tokio::test]
+async fn test_budget_exempt_cleanup_node_still_runs() {
+    let agent_id = AgentId::new();
+    let workflow = chain(vec![
+        agent_node(&agent_id, "first", "Do the work"),
+        agent_node(&agent_id, "second", "Do more work"),
+        agent_node(&agent_id, "cleanup", "Clean up").with_budget_exempt(true),
+    ]);
+
+    let context = executor(&agent_id)
+        .await
+        .with_max_total_tokens(100)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    let (usage, _) = budget_failure(&context);
+    assert_eq!(usage.total_tokens, 200);
+    assert!(!ran(&context, "second"));
+    assert!(ran(&context, "cleanup"));
+}
+
+/// Workflow with a fast and a slow node running side by side, where the fast
+/// one uses up the budget while the slow one is waiting for its answer
+fn fast_and_slow(agent_id: &AgentId) -> Workflow {
+    let mut workflow = Workflow::new("budget", "");
+    workflow
+        .add_node(agent_node(agent_id, "fast", "Answer soon"))
+        .unwrap();
+    workflow
+        .add_node(agent_node(agent_id, "slow", "Answer slow"))
+        .unwrap();
+    workflow
+}
+
+#[tokio::test]
+async fn test_cancel_policy_stops_running_nodes() {
+    let agent_id = AgentId::new();
+    let started = Instant::now();
+
+    let context = executor(&agent_id)
+        .await
+        .with_max_total_tokens(100)
+        .with_budget_policy(BudgetPolicy::Cancel)
+        .execute(fast_and_slow(&agent_id), None)
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    let (usage, _) = budget_failure(&context);
+    assert_eq!(usage.total_tokens, 100);
+    assert_eq!(
+        context.abandoned_nodes.get("slow"),
+        Some(&AbandonedNode::Cancelled)
+    );
+}
+
+#[tokio::test]
+async fn test_finish_policy_lets_running_nodes_finish() {
+    let agent_id = AgentId::new();
+
+    let context = executor(&agent_id)
+        .await
+        .with_max_total_tokens(100)
+        .execute(fast_and_slow(&agent_id), None)
+        .await
+        .unwrap();
+
+    let (usage, _) = budget_failure(&context);
+    assert_eq!(usage.total_tokens, 200);
+    assert!(ran(&context, "slow"));
+    assert!(context.abandoned_nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_run_within_budget_completes() {
+    let agent_id = AgentId::new();
+    let workflow = chain(vec![agent_node(&agent_id, "only", "Do the work")]);
+
+    let context = executor(&agent_id)
+        .await
+        .with_max_total_tokens(1_000)
+        .with_max_cost_usd(10.0)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+}
@@ -0,0 +1,124 @@
+//! Clocks: the frozen and paused test clocks, and an executor timing its
+//! delays and retries on an injected clock
+
+use graphbit_core::{
+    clock::{self, Clock, SystemClock, TestClock},
+    graph::{NodeType, WorkflowNode},
+    types::{RetryConfig, RetryableErrorType, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn start() -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+        .unwrap()
+        .into()
+}
+
+#[tokio::test]
+async fn test_frozen_clock_moves_only_when_advanced_or_slept() {
+    let clock = TestClock::frozen(start());
+    let origin = clock.instant();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(clock.now(), start());
+    assert_eq!(clock.instant(), origin);
+
+    // An hour-long sleep returns at once and moves the clock by an hour
+    let real = std::time::Instant::now();
+    clock.sleep(Duration::from_secs(3600)).await;
+    assert!(real.elapsed() < Duration::from_secs(1));
+    assert_eq!(clock.now(), start() + chrono::Duration::hours(1));
+
+    clock.advance(Duration::from_millis(1500)).await;
+    assert_eq!(clock.elapsed(), Duration::from_millis(3_601_500));
+    assert_eq!(clock.instant() - origin, Duration::from_millis(3_601_500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_paused_clock_follows_the_tokio_clock() {
+    let clock = TestClock::paused(start());
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert_eq!(clock.now(), start() + chrono::Duration::seconds(10));
+
+    clock.sleep(Duration::from_secs(5)).await;
+    clock.advance(Duration::from_secs(5)).await;
+    assert_eq!(clock.elapsed(), Duration::from_secs(20));
+    assert_eq!(clock.instant(), tokio::time::Instant::now());
+}
+
+#[tokio::test]
+async fn test_system_clock_is_current_outside_an_execution() {
+    let before = chrono::Utc::now();
+    let now = clock::now();
+    assert!(now >= before && now <= chrono::Utc::now());
+    assert!(SystemClock.now() >= now);
+}
+
+#[tokio::test]
+async fn test_delay_node_passes_on_the_executor_clock() {
+    let mut workflow = Workflow::new("delayed", "");
+    workflow
+        .add_node(WorkflowNode::new(
+            "wait",
+            "",
+            NodeType::Delay {
+                duration_seconds: 3600,
+            },
+        ))
+        .unwrap();
+
+    let clock = Arc::new(TestClock::frozen(start()));
+    let real = std::time::Instant::now();
+    let context = WorkflowExecutor::new()
+        .with_clock(clock.clone())
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(real.elapsed() < Duration::from_secs(5));
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(context.get_node_executions()[0].duration_ms, 3_600_000);
+    assert_eq!(clock.elapsed(), Duration::from_secs(3600));
+}
+
+#[tokio::test]
+async fn test_retry_backoff_passes_on_the_executor_clock() {
+    // An unknown transformation fails every attempt
+    let mut workflow = Workflow::new("failing", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "reverse",
+                "",
+                NodeType::Transform {
+                    transformation: "reverse".to_string(),
+                },
+            )
+            .with_retry_config(
+                RetryConfig::new(2)
+                    .with_exponential_backoff(30_000, 2.0, 300_000)
+                    .with_jitter(0.0)
+                    .with_retryable_errors(vec![RetryableErrorType::Other]),
+            ),
+        )
+        .unwrap();
+
+    let clock = Arc::new(TestClock::frozen(start()));
+    let real = std::time::Instant::now();
+    let context = WorkflowExecutor::new()
+        .with_clock(clock.clone())
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(real.elapsed() < Duration::from_secs(5));
+    assert_eq!(context.get_node_attempts("reverse"), Some(3));
+    // Backoff of 30s, then 60s
+    assert_eq!(clock.elapsed(), Duration::from_secs(90));
+    assert_eq!(context.get_node_executions()[0].duration_ms, 90_000);
+}
@@ -0,0 +1,339 @@
+//! Comparison of a run against a golden run
+
+use chrono::{DateTime, Duration, Utc};
+use graphbit_core::{
+    compare::{CompareOptions, CompareThresholds, DurationDelta, StatusChange, TokenDelta},
+    types::{NodeExecutionRecord, NodeId, WorkflowContext, WorkflowId, WorkflowState},
+};
+use serde_json::{Value, json};
+
+fn at(millis: i64) -> DateTime<Utc> {
+    "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::milliseconds(millis)
+}
+
+/// One node of a synthetic run
+struct Step {
+    name: &'static str,
+    success: bool,
+    output: Value,
+    duration_ms: u64,
+    tokens: Option<(u32, u32)>,
+}
+
+fn step(name: &'static str, output: Value, duration_ms: u64, tokens: Option<(u32, u32)>) -> Step {
+    Step {
+        name,
+        success: true,
+        output,
+        duration_ms,
+        tokens,
+    }
+}
+
+/// Context of a finished run executing `steps` one after the other; node IDs
+/// are fresh, as in a separate execution
+fn run(steps: Vec<Step>) -> WorkflowContext {
+    let mut context = WorkflowContext::new(WorkflowId::new());
+    context.started_at = at(0);
+    let mut elapsed = 0;
+    let mut failed = None;
+    for (index, step) in steps.into_iter().enumerate() {
+        let node_id = NodeId::new();
+        if let Some((prompt, completion)) = step.tokens {
+            context.set_metadata(
+                format!("node_response_{node_id}"),
+                json!({"total_usage": {
+                    "prompt_tokens": prompt,
+                    "completion_tokens": completion,
+                    "total_tokens": prompt + completion,
+                }}),
+            );
+        }
+        context.set_node_output(&node_id, step.output);
+        context.record_node_execution(NodeExecutionRecord {
+            node_id,
+            node_name: step.name.to_string(),
+            step: index + 1,
+            success: step.success,
+            error: (!step.success).then(|| format!("Node '{}' failed", step.name)),
+            started_at: at(elapsed),
+            duration_ms: step.duration_ms,
+            retry_count: 0,
+            idempotency_key: None,
+            pacing: None,
+        });
+        elapsed += i64::try_from(step.duration_ms).unwrap();
+        if !step.success {
+            failed = Some(step.name);
+        }
+    }
+    context.state = match failed {
+        Some(name) => WorkflowState::Failed {
+            error: format!("Node '{name}' failed"),
+            reason: None,
+        },
+        None => WorkflowState::Completed,
+    };
+    context.completed_at = Some(at(elapsed));
+    context
+}
+
+/// `plan` (JSON text), `draft` (text) and `score` (JSON) all succeed
+fn golden() -> WorkflowContext {
+    run(vec![
+        step(
+            "plan",
+            json!(
+                r#"{"request_id": "a1", "topics": ["pricing", "roadmap"], "created_at": "2026-01-01T00:00:00Z"}"#
+            ),
+            1000,
+            Some((100, 40)),
+        ),
+        step(
+            "draft",
+            json!("Dear team,\nPrices stay the same.\nThanks"),
+            2000,
+            Some((300, 120)),
+        ),
+        step("score", json!({"score": 0.82, "id": 7}), 10, None),
+    ])
+}
+
+/// The golden run with a changed draft, slower and costlier, and volatile
+/// values changed elsewhere
+fn candidate() -> WorkflowContext {
+    run(vec![
+        step(
+            "plan",
+            json!(
+                r#"{"request_id": "b2", "topics": ["pricing", "roadmap"], "created_at": "2026-03-04T05:06:07Z"}"#
+            ),
+            1100,
+            Some((100, 40)),
+        ),
+        step(
+            "draft",
+            json!("Dear team,\nPrices rise in May.\nThanks"),
+            2500,
+            Some((320, 150)),
+        ),
+        step("score", json!({"score": 0.8201, "id": 8}), 12, None),
+    ])
+}
+
+#[test]
+fn test_compare_identical_runs() {
+    let golden = golden();
+    let comparison = golden.compare(&golden, &CompareOptions::default());
+
+    assert!(comparison.status.is_none());
+    assert_eq!(comparison.changed_outputs, 0);
+    assert_eq!(comparison.status_changes, 0);
+    assert!(comparison.nodes.iter().all(|node| !node.is_changed()));
+    assert_eq!(comparison.token_delta, Some(TokenDelta::default()));
+    assert!(comparison.passes(&CompareThresholds::default()));
+}
+
+#[test]
+fn test_compare_reports_node_differences() {
+    let comparison = candidate().compare(&golden(), &CompareOptions::default());
+    let names: Vec<&str> = comparison
+        .nodes
+        .iter()
+        .map(|node| node.node_name.as_str())
+        .collect();
+    assert_eq!(names, ["plan", "draft", "score"]);
+    assert_eq!(comparison.changed_outputs, 3);
+
+    // JSON text is compared value by value
+    let plan = &comparison.nodes[0];
+    let paths: Vec<&str> = plan
+        .output_diff
+        .iter()
+        .map(|change| change.path.as_str())
+        .collect();
+    assert_eq!(paths, ["/created_at", "/request_id"]);
+    assert_eq!(plan.output_diff[1].expected, Some(json!("a1")));
+    assert_eq!(plan.output_diff[1].actual, Some(json!("b2")));
+    assert!(plan.text_diff.is_none());
+
+    let draft = &comparison.nodes[1];
+    assert_eq!(draft.output_diff.len(), 1);
+    assert_eq!(draft.output_diff[0].path, "");
+    assert_eq!(
+        draft.text_diff.as_deref().unwrap(),
+        [
+            " Dear team,",
+            "-Prices stay the same.",
+            "+Prices rise in May.",
+            " Thanks"
+        ]
+    );
+    assert_eq!(
+        draft.duration,
+        Some(DurationDelta {
+            expected_ms: 2000,
+            actual_ms: 2500,
+            delta_ms: 500
+        })
+    );
+    assert_eq!(
+        draft.token_delta,
+        Some(TokenDelta {
+            prompt_tokens: 20,
+            completion_tokens: 30,
+            total_tokens: 50
+        })
+    );
+
+    // Nodes without token usage have no token delta
+    assert!(comparison.nodes[2].token_delta.is_none());
+
+    assert_eq!(comparison.duration.unwrap().delta_ms, 602);
+    assert_eq!(comparison.token_delta.unwrap().total_tokens, 50);
+}
+
+#[test]
+fn test_compare_ignores_volatile_values() {
+    let options = CompareOptions::default()
+        .ignore("timings")
+        .unwrap()
+        .ignore("ids")
+        .unwrap()
+        .with_numeric_tolerance(0.001);
+    let comparison = candidate().compare(&golden(), &options);
+
+    // Only the draft's text changed
+    let changed: Vec<&str> = comparison
+        .nodes
+        .iter()
+        .filter(|node| node.output_changed)
+        .map(|node| node.node_name.as_str())
+        .collect();
+    assert_eq!(changed, ["draft"]);
+    assert!(comparison.duration.is_none());
+    assert!(comparison.nodes.iter().all(|node| node.duration.is_none()));
+    assert!(comparison.token_delta.is_some());
+
+    let options = options.ignore("tokens").unwrap();
+    let comparison = candidate().compare(&golden(), &options);
+    assert!(comparison.token_delta.is_none());
+    assert!(
+        comparison
+            .nodes
+            .iter()
+            .all(|node| node.token_delta.is_none())
+    );
+
+    assert!(CompareOptions::default().ignore("outputs").is_err());
+}
+
+#[test]
+fn test_compare_ignore_keys() {
+    let options = CompareOptions::default()
+        .with_ignore_key("request_id")
+        .with_ignore_key("created_at");
+    let comparison = candidate().compare(&golden(), &options);
+    assert!(!comparison.nodes[0].output_changed);
+    // Numbers outside the tolerance and other keys still differ
+    let score = &comparison.nodes[2];
+    let paths: Vec<&str> = score
+        .output_diff
+        .iter()
+        .map(|change| change.path.as_str())
+        .collect();
+    assert_eq!(paths, ["/id", "/score"]);
+}
+
+#[test]
+fn test_compare_status_changes() {
+    let golden = golden();
+    let mut failing = vec![
+        step("plan", json!("{}"), 1000, None),
+        step("draft", Value::Null, 2000, None),
+        step("review", json!("ok"), 10, None),
+    ];
+    failing[1].success = false;
+    let comparison = run(failing).compare(&golden, &CompareOptions::default());
+
+    assert_eq!(
+        comparison.status,
+        Some(StatusChange {
+            expected: "completed".to_string(),
+            actual: "failed".to_string()
+        })
+    );
+    let status = |name: &str| {
+        comparison
+            .nodes
+            .iter()
+            .find(|node| node.node_name == name)
+            .unwrap()
+            .status
+            .clone()
+            .map(|change| (change.expected, change.actual))
+    };
+    assert_eq!(status("plan"), None);
+    assert_eq!(
+        status("draft"),
+        Some(("succeeded".to_string(), "failed".to_string()))
+    );
+    // Nodes that ran in only one run
+    assert_eq!(
+        status("review"),
+        Some(("not_run".to_string(), "succeeded".to_string()))
+    );
+    assert_eq!(
+        status("score"),
+        Some(("succeeded".to_string(), "not_run".to_string()))
+    );
+    assert_eq!(comparison.status_changes, 3);
+    // Actual nodes first, then the ones only the golden run executed
+    assert_eq!(comparison.nodes.last().unwrap().node_name, "score");
+    assert!(comparison.nodes.last().unwrap().duration.is_none());
+}
+
+#[test]
+fn test_compare_thresholds() {
+    let comparison = candidate().compare(
+        &golden(),
+        &CompareOptions::default()
+            .ignore("ids")
+            .unwrap()
+            .with_ignore_key("request_id")
+            .with_ignore_key("created_at")
+            .with_numeric_tolerance(0.001),
+    );
+    assert_eq!(comparison.changed_outputs, 1);
+
+    let violations = comparison.violations(&CompareThresholds::default());
+    assert_eq!(
+        violations,
+        ["1 node outputs changed, at most 0 allowed: draft"]
+    );
+
+    let thresholds: CompareThresholds = serde_json::from_value(json!({
+        "max_changed_outputs": 1,
+        "max_duration_increase_ms": 1000,
+        "max_token_increase": 100,
+    }))
+    .unwrap();
+    assert!(comparison.passes(&thresholds));
+
+    let strict = CompareThresholds {
+        max_changed_outputs: 1,
+        max_duration_increase_pct: Some(10.0),
+        max_token_increase: Some(10),
+        ..CompareThresholds::default()
+    };
+    assert_eq!(
+        comparison.violations(&strict),
+        [
+            "Run duration increased by 20.0%, at most 10% allowed",
+            "Total tokens increased by 50, at most 10 allowed",
+        ]
+    );
+
+    // Unknown threshold names are rejected rather than silently ignored
+    assert!(serde_json::from_value::<CompareThresholds>(json!({"max_changes": 1})).is_err());
+}
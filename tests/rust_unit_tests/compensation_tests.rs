@@ -0,0 +1,285 @@
+//! On-failure edges leading to compensation nodes
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::{AbandonedNode, SkipReason, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+/// Workflow with the given nodes, data flow edges and on-failure edges
+/// between node names
+fn workflow(
+    nodes: Vec<WorkflowNode>,
+    edges: &[(&str, &str)],
+    failure_edges: &[(&str, &str)],
+) -> Workflow {
+    let mut workflow = Workflow::new("compensation", "");
+    for node in nodes {
+        workflow.add_node(node).unwrap();
+    }
+    let connections = edges
+        .iter()
+        .map(|edge| (edge, WorkflowEdge::data_flow()))
+        .chain(
+            failure_edges
+                .iter()
+                .map(|edge| (edge, WorkflowEdge::on_failure())),
+        );
+    for ((from, to), edge) in connections {
+        let from = workflow.graph.get_node_id_by_name(from).unwrap();
+        let to = workflow.graph.get_node_id_by_name(to).unwrap();
+        workflow.connect_nodes(from, to, edge).unwrap();
+    }
+    workflow
+}
+
+/// `fetch -> write -> publish`, with `cleanup -> notify` compensating `write`
+fn pipeline(write: &str) -> Workflow {
+    workflow(
+        vec![
+            transform("fetch", "'record'"),
+            transform("write", write),
+            transform("publish", "input + ' published'"),
+            transform(
+                "cleanup",
+                "'deleted after ' + input.node + ': ' + input.error",
+            ),
+            transform("notify", "input + '!'"),
+        ],
+        &[
+            ("fetch", "write"),
+            ("write", "publish"),
+            ("cleanup", "notify"),
+        ],
+        &[("write", "cleanup")],
+    )
+}
+
+async fn run(workflow: Workflow, fail_fast: bool) -> WorkflowContext {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_fail_fast(fail_fast)
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+/// Times `name` ran
+fn runs(context: &WorkflowContext, name: &str) -> usize {
+    context
+        .node_executions
+        .iter()
+        .filter(|record| record.node_name == name)
+        .count()
+}
+
+#[tokio::test]
+async fn test_compensation_never_runs_on_success() {
+    for fail_fast in [false, true] {
+        let context = run(pipeline("input + ' written'"), fail_fast).await;
+
+        assert!(matches!(context.state, WorkflowState::Completed));
+        assert_eq!(
+            context.get_node_output("publish"),
+            Some(&json!("record written published"))
+        );
+        assert_eq!(runs(&context, "cleanup"), 0);
+        assert_eq!(runs(&context, "notify"), 0);
+        assert_eq!(context.get_node_output("cleanup"), None);
+    }
+}
+
+#[tokio::test]
+async fn test_compensation_runs_once_and_failure_is_kept() {
+    let context = run(pipeline("1 / 0"), false).await;
+
+    match &context.state {
+        WorkflowState::PartiallyCompleted { failed_nodes } => {
+            assert_eq!(failed_nodes, &["write".to_string()]);
+        }
+        state => panic!("expected a partially completed workflow, got {state:?}"),
+    }
+    assert_eq!(runs(&context, "cleanup"), 1);
+    assert_eq!(runs(&context, "notify"), 1);
+    let cleanup = context.get_node_output("cleanup").unwrap();
+    assert!(
+        cleanup
+            .as_str()
+            .unwrap()
+            .starts_with("deleted after write: "),
+        "{cleanup}"
+    );
+    assert_eq!(runs(&context, "publish"), 0);
+    assert!(matches!(
+        context.abandoned_nodes.get("publish"),
+        Some(AbandonedNode::Blocked { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_compensation_runs_under_fail_fast() {
+    let nodes = vec![
+        transform("fetch", "'record'"),
+        transform("write", "1 / 0"),
+        transform("publish", "input + ' published'"),
+        transform(
+            "cleanup",
+            "'deleted after ' + input.node + ': ' + input.error",
+        ),
+        transform("notify", "input + '!'"),
+        transform("index", "input + ' indexed'"),
+        transform("archive", "input + ' archived'"),
+    ];
+    let workflow = workflow(
+        nodes,
+        &[
+            ("fetch", "write"),
+            ("write", "publish"),
+            ("cleanup", "notify"),
+            ("fetch", "index"),
+            ("index", "archive"),
+        ],
+        &[("write", "cleanup")],
+    );
+
+    let context = run(workflow, true).await;
+
+    // The workflow fails with the error of `write`, after its compensation ran
+    let WorkflowState::Failed { error, .. } = &context.state else {
+        panic!("expected a failed workflow, got {:?}", context.state);
+    };
+    let write_error = context
+        .node_executions
+        .iter()
+        .find(|record| record.node_name == "write")
+        .and_then(|record| record.error.clone())
+        .unwrap();
+    assert_eq!(error, &write_error);
+    assert_eq!(runs(&context, "cleanup"), 1);
+    assert_eq!(
+        context.get_node_output("notify"),
+        Some(&json!(format!("deleted after write: {write_error}!")))
+    );
+    // Nodes that had not started yet were skipped
+    for name in ["publish", "archive"] {
+        assert_eq!(runs(&context, name), 0, "{name} ran");
+        assert_eq!(
+            context.abandoned_nodes.get(name),
+            Some(&AbandonedNode::Skipped {
+                reason: SkipReason::FailFast
+            })
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_failing_compensation_keeps_original_failure() {
+    let workflow = workflow(
+        vec![
+            transform("write", "1 / 0"),
+            transform("cleanup", "undefined_name"),
+            transform("alert", "'cleanup failed: ' + input.error"),
+        ],
+        &[],
+        &[("write", "cleanup"), ("cleanup", "alert")],
+    );
+
+    let context = run(workflow, true).await;
+
+    let WorkflowState::Failed { error, .. } = &context.state else {
+        panic!("expected a failed workflow, got {:?}", context.state);
+    };
+    assert!(!error.contains("undefined_name"), "{error}");
+    assert_eq!(runs(&context, "cleanup"), 1);
+    assert_eq!(runs(&context, "alert"), 1);
+}
+
+#[tokio::test]
+async fn test_compensation_chains_stop_at_max_depth() {
+    // Every compensation node fails in turn
+    let chain = || {
+        workflow(
+            vec![
+                transform("write", "1 / 0"),
+                transform("first", "1 / 0"),
+                transform("second", "1 / 0"),
+                transform("third", "1 / 0"),
+            ],
+            &[],
+            &[("write", "first"), ("first", "second"), ("second", "third")],
+        )
+    };
+
+    let context = run(chain(), false).await;
+    assert_eq!(
+        ["first", "second", "third"].map(|name| runs(&context, name)),
+        [1, 1, 1]
+    );
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_max_compensation_depth(2)
+        .execute(chain(), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        ["first", "second", "third"].map(|name| runs(&context, name)),
+        [1, 1, 0]
+    );
+}
+
+#[test]
+fn test_compensation_subtrees_must_be_terminal_and_acyclic() {
+    let nodes = || {
+        vec![
+            transform("write", "'record'"),
+            transform("publish", "input"),
+            transform("cleanup", "input"),
+            transform("notify", "input"),
+        ]
+    };
+
+    // A compensation subtree ending on its own is valid
+    let valid = workflow(
+        nodes(),
+        &[("write", "publish"), ("cleanup", "notify")],
+        &[("write", "cleanup")],
+    );
+    assert!(valid.validate().is_ok());
+
+    // The rest of the workflow cannot lead into a compensation subtree
+    let rejoined = workflow(
+        nodes(),
+        &[
+            ("write", "publish"),
+            ("cleanup", "notify"),
+            ("publish", "notify"),
+        ],
+        &[("write", "cleanup")],
+    );
+    let error = rejoined.validate().unwrap_err().to_string();
+    assert!(
+        error.contains("'publish' leads to compensation node 'notify'"),
+        "{error}"
+    );
+
+    // Nor can a compensation subtree loop
+    let cyclic = workflow(
+        nodes(),
+        &[("cleanup", "notify"), ("notify", "cleanup")],
+        &[("write", "cleanup")],
+    );
+    let error = cyclic.validate().unwrap_err().to_string();
+    assert!(error.contains("is part of a cycle"), "{error}");
+}
@@ -7,7 +7,8 @@ use graphbit_core::{
     errors::GraphBitError,
     graph::{NodeType, WorkflowGraph, WorkflowNode},
     types::{
-        CircuitBreaker, CircuitBreakerConfig, ConcurrencyStats, NodeId, WorkflowContext, WorkflowId,
+        CircuitBreaker, CircuitBreakerConfig, ConcurrencyConfig, ConcurrencyManager,
+        ConcurrencyStats, NodeId, TaskInfo, WorkflowContext, WorkflowId,
     },
 };
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
@@ -340,6 +341,36 @@ fn test_concurrency_stats_thread_safety() {
     assert!(final_stats.avg_wait_time_ms > 0.0);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrency_manager_stats_under_contention() {
+    let manager = Arc::new(ConcurrencyManager::new(ConcurrencyConfig::default()));
+
+    let handles: Vec<_> = (0..64)
+        .map(|i| {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                let task = if i % 2 == 0 {
+                    TaskInfo::http_task(NodeId::new())
+                } else {
+                    TaskInfo::transform_task(NodeId::new())
+                };
+                let permits = manager.acquire_permits(&task).await.unwrap();
+                tokio::task::yield_now().await;
+                drop(permits);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    // Every dropped permit is counted, none lost to a contended lock
+    let stats = manager.get_stats().await;
+    assert_eq!(stats.total_permit_acquisitions, 64);
+    assert_eq!(stats.current_active_tasks, 0);
+    assert!(stats.peak_active_tasks >= 1);
+}
+
 #[tokio::test]
 async fn test_concurrent_error_handling() {
     let error_count = Arc::new(AtomicU32::new(0));
@@ -0,0 +1,176 @@
+//! Concurrency keys: nodes sharing a key never run at the same time, while
+//! nodes with different keys still run in parallel
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    graph::{NodeType, WorkflowNode},
+    types::{ConcurrencyConfig, ConcurrencyManager, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// What the barrier server saw: the peak number of requests in flight and,
+/// per request path, whether the request met another one at the barrier
+#[derive(Default)]
+struct Observed {
+    arrivals: usize,
+    in_flight: usize,
+    peak_in_flight: usize,
+    met: HashMap<String, bool>,
+}
+
+/// Start an HTTP server where every request waits up to 300ms at a barrier
+/// for a second request before answering. A request meets another one if it
+/// found one waiting, or one arrived while it waited.
+async fn spawn_barrier_server() -> (String, Arc<Mutex<Observed>>) {
+    let observed = Arc::new(Mutex::new(Observed::default()));
+    let recorded = observed.clone();
+    let server = MockServer::new(move |request| {
+        let observed = recorded.clone();
+        async move {
+            let (arrival, mut met) = {
+                let mut observed = observed.lock().unwrap();
+                observed.arrivals += 1;
+                observed.in_flight += 1;
+                observed.peak_in_flight = observed.peak_in_flight.max(observed.in_flight);
+                (observed.arrivals, observed.in_flight > 1)
+            };
+            for _ in 0..30 {
+                if met {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                met = observed.lock().unwrap().arrivals > arrival;
+            }
+            {
+                let mut observed = observed.lock().unwrap();
+                observed.in_flight -= 1;
+                observed.met.insert(request.path, met);
+            }
+            MockResponse::new(200, r#"{"ok":true}"#)
+        }
+    })
+    .await;
+    (server.url(), observed)
+}
+
+/// Two unconnected HTTP request nodes, `first` and `second`, with the given
+/// concurrency keys
+fn parallel_workflow(url: &str, keys: [Option<&str>; 2]) -> Workflow {
+    let mut workflow = Workflow::new("sheet", "");
+    for (name, key) in ["first", "second"].into_iter().zip(keys) {
+        let node = WorkflowNode::new(
+            name,
+            "",
+            NodeType::HttpRequest {
+                url: format!("{url}/{name}"),
+                method: "POST".to_string(),
+                headers: HashMap::new(),
+            },
+        );
+        let node = match key {
+            Some(key) => node.with_concurrency_key(key),
+            None => node,
+        };
+        workflow.add_node(node).unwrap();
+    }
+    workflow
+}
+
+async fn run(keys: [Option<&str>; 2]) -> (Arc<Mutex<Observed>>, WorkflowExecutor) {
+    let (url, observed) = spawn_barrier_server().await;
+    let executor = WorkflowExecutor::new().without_retries();
+    let context = executor
+        .execute(parallel_workflow(&url, keys), None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    (observed, executor)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_same_key_serializes_nodes() {
+    let (observed, executor) = run([Some("sheet:budget"), Some("sheet:budget")]).await;
+    let stats = executor.get_concurrency_stats().await;
+
+    let observed = observed.lock().unwrap();
+    assert_eq!(observed.peak_in_flight, 1);
+    assert_eq!(observed.met.len(), 2);
+    assert!(observed.met.values().all(|met| !met), "{:?}", observed.met);
+
+    assert_eq!(stats.keyed_lock_acquisitions, 2);
+    // The second node waited for the first one, barrier timeout included
+    assert!(
+        stats.keyed_lock_wait_time_ms >= 250,
+        "{}",
+        stats.keyed_lock_wait_time_ms
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_different_keys_run_in_parallel() {
+    let (observed, executor) = run([Some("sheet:budget"), Some("sheet:forecast")]).await;
+    let stats = executor.get_concurrency_stats().await;
+
+    let observed = observed.lock().unwrap();
+    assert_eq!(observed.peak_in_flight, 2);
+    assert!(observed.met.values().all(|met| *met), "{:?}", observed.met);
+    assert_eq!(stats.keyed_lock_acquisitions, 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_unkeyed_node_runs_beside_keyed_node() {
+    let (observed, executor) = run([Some("sheet:budget"), None]).await;
+
+    assert_eq!(observed.lock().unwrap().peak_in_flight, 2);
+    let stats = executor.get_concurrency_stats().await;
+    assert_eq!(stats.keyed_lock_acquisitions, 1);
+    assert_eq!(stats.keyed_lock_wait_time_ms, 0);
+}
+
+#[tokio::test]
+async fn test_acquire_key_waits_for_release() {
+    let manager = Arc::new(ConcurrencyManager::new(ConcurrencyConfig::default()));
+    let held = manager.acquire_key("sheet:budget").await;
+    // Other keys are free
+    drop(manager.acquire_key("sheet:forecast").await);
+
+    let waiter = tokio::spawn({
+        let manager = manager.clone();
+        async move { drop(manager.acquire_key("sheet:budget").await) }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!waiter.is_finished());
+
+    drop(held);
+    waiter.await.unwrap();
+    // A released key can be taken again right away
+    drop(manager.acquire_key("sheet:budget").await);
+    assert_eq!(manager.get_stats().await.keyed_lock_acquisitions, 4);
+}
+
+#[test]
+fn test_concurrency_key_validation() {
+    let node = WorkflowNode::new(
+        "write",
+        "",
+        NodeType::Transform {
+            transformation: "identity".to_string(),
+        },
+    );
+    assert_eq!(node.concurrency_key(), None);
+
+    let keyed = node.clone().with_concurrency_key("sheet:budget");
+    assert_eq!(keyed.concurrency_key(), Some("sheet:budget"));
+    assert!(keyed.validate().is_ok());
+
+    let blank = node.with_concurrency_key("  ");
+    let error = blank.validate().unwrap_err().to_string();
+    assert!(error.contains("concurrency_key"), "{error}");
+}
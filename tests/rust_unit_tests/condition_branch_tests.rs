@@ -0,0 +1,266 @@
+//! Condition nodes routing along true/false edges: taking one branch, skipping
+//! the other, missing branches, nesting and validation
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::{AbandonedNode, NodeId, SkipReason, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+fn condition(name: &str, expression: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Condition {
+            handler_id: String::new(),
+            expression: Some(expression.to_string()),
+        },
+    )
+}
+
+async fn run(workflow: Workflow) -> WorkflowContext {
+    workflow.validate().unwrap();
+    WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+fn skipped_by_condition(context: &WorkflowContext, name: &str) -> bool {
+    context.abandoned_nodes.get(name)
+        == Some(&AbandonedNode::Skipped {
+            reason: SkipReason::Condition,
+        })
+}
+
+/// `source` scoring `score`, routed by `is_high` to `publish` and then
+/// `notify` when it holds and to `review` otherwise; `with_false` adds the
+/// false edge
+fn review_workflow(score: f64, with_false: bool) -> Workflow {
+    let mut workflow = Workflow::new("review", "");
+    let source = workflow
+        .add_node(transform("source", &format!("{{score: {score}}}")))
+        .unwrap();
+    let is_high = workflow
+        .add_node(condition("is_high", "score > 0.5"))
+        .unwrap();
+    let publish = workflow
+        .add_node(transform("publish", "'published'"))
+        .unwrap();
+    let notify = workflow
+        .add_node(transform("notify", "'notified'"))
+        .unwrap();
+    let review = workflow.add_node(transform("review", "'review'")).unwrap();
+    workflow
+        .connect_nodes(source, is_high.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(is_high.clone(), publish.clone(), WorkflowEdge::on_true())
+        .unwrap();
+    workflow
+        .connect_nodes(publish, notify, WorkflowEdge::data_flow())
+        .unwrap();
+    if with_false {
+        workflow
+            .connect_nodes(is_high, review, WorkflowEdge::on_false())
+            .unwrap();
+    }
+    workflow
+}
+
+#[tokio::test]
+async fn test_true_branch_runs_and_false_branch_is_skipped() {
+    let context = run(review_workflow(0.9, true)).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("is_high"), Some(&json!(true)));
+    assert_eq!(
+        context.get_node_output("publish"),
+        Some(&json!("published"))
+    );
+    assert_eq!(context.get_node_output("notify"), Some(&json!("notified")));
+    assert_eq!(context.get_node_output("review"), None);
+    assert!(skipped_by_condition(&context, "review"));
+    assert_eq!(context.abandoned_nodes.len(), 1);
+}
+
+#[tokio::test]
+async fn test_false_branch_runs_and_true_subtree_is_skipped() {
+    let context = run(review_workflow(0.1, true)).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("is_high"), Some(&json!(false)));
+    assert_eq!(context.get_node_output("review"), Some(&json!("review")));
+    assert_eq!(context.get_node_output("publish"), None);
+    assert_eq!(context.get_node_output("notify"), None);
+    assert!(skipped_by_condition(&context, "publish"));
+    assert!(skipped_by_condition(&context, "notify"));
+    assert_eq!(context.stats.unwrap().skipped_nodes, 2);
+}
+
+#[tokio::test]
+async fn test_missing_branch_runs_nothing_after_the_condition() {
+    let workflow = review_workflow(0.1, false);
+    assert_eq!(
+        workflow.warnings(),
+        [
+            "Condition node 'is_high' has no false edge; nothing after it runs when its expression is false"
+        ]
+    );
+
+    let context = run(workflow).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("is_high"), Some(&json!(false)));
+    assert!(skipped_by_condition(&context, "publish"));
+    assert!(skipped_by_condition(&context, "notify"));
+
+    // The branch that exists still runs
+    let context = run(review_workflow(0.9, false)).await;
+    assert_eq!(context.get_node_output("notify"), Some(&json!("notified")));
+    assert!(context.abandoned_nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_nested_conditions() {
+    async fn route(amount: u32, urgent: bool) -> WorkflowContext {
+        let mut workflow = Workflow::new("nested", "");
+        let source = workflow
+            .add_node(transform(
+                "source",
+                &format!("{{amount: {amount}, urgent: {urgent}}}"),
+            ))
+            .unwrap();
+        let large = workflow
+            .add_node(condition("large", "amount > 100"))
+            .unwrap();
+        // The outer condition passes on its outcome, so the inner one reads the source
+        let is_urgent = workflow
+            .add_node(condition("is_urgent", "nodes.source.urgent"))
+            .unwrap();
+        let escalate = workflow
+            .add_node(transform("escalate", "'escalate'"))
+            .unwrap();
+        let queue = workflow.add_node(transform("queue", "'queue'")).unwrap();
+        let approve = workflow
+            .add_node(transform("approve", "'approve'"))
+            .unwrap();
+        let edges: [(&NodeId, &NodeId, WorkflowEdge); 5] = [
+            (&source, &large, WorkflowEdge::data_flow()),
+            (&large, &is_urgent, WorkflowEdge::on_true()),
+            (&large, &approve, WorkflowEdge::on_false()),
+            (&is_urgent, &escalate, WorkflowEdge::on_true()),
+            (&is_urgent, &queue, WorkflowEdge::on_false()),
+        ];
+        for (from, to, edge) in edges {
+            workflow
+                .connect_nodes(from.clone(), to.clone(), edge)
+                .unwrap();
+        }
+        let context = run(workflow).await;
+        assert!(matches!(context.state, WorkflowState::Completed));
+        context
+    }
+
+    let ran = |context: &WorkflowContext| {
+        let mut ran: Vec<&str> = ["escalate", "queue", "approve"]
+            .into_iter()
+            .filter(|name| context.get_node_output(name).is_some())
+            .collect();
+        ran.sort_unstable();
+        ran
+    };
+
+    let context = route(500, true).await;
+    assert_eq!(ran(&context), ["escalate"]);
+    assert!(skipped_by_condition(&context, "queue"));
+    assert!(skipped_by_condition(&context, "approve"));
+
+    let context = route(500, false).await;
+    assert_eq!(ran(&context), ["queue"]);
+
+    let context = route(20, true).await;
+    assert_eq!(ran(&context), ["approve"]);
+    for name in ["is_urgent", "escalate", "queue"] {
+        assert!(skipped_by_condition(&context, name), "{name}");
+    }
+}
+
+#[test]
+fn test_validation_of_true_false_edges() {
+    let build = |edges: &[(&str, &str, WorkflowEdge)]| {
+        let mut workflow = Workflow::new("invalid", "");
+        let source = workflow.add_node(transform("source", "1")).unwrap();
+        let check = workflow.add_node(condition("check", "input > 0")).unwrap();
+        let yes = workflow.add_node(transform("yes", "'yes'")).unwrap();
+        let no = workflow.add_node(transform("no", "'no'")).unwrap();
+        workflow
+            .connect_nodes(source.clone(), check.clone(), WorkflowEdge::data_flow())
+            .unwrap();
+        let id = |name: &str| match name {
+            "source" => source.clone(),
+            "check" => check.clone(),
+            "yes" => yes.clone(),
+            _ => no.clone(),
+        };
+        for (from, to, edge) in edges {
+            workflow
+                .connect_nodes(id(from), id(to), edge.clone())
+                .unwrap();
+        }
+        workflow.validate().map(|()| workflow.warnings())
+    };
+
+    let warnings = build(&[
+        ("check", "yes", WorkflowEdge::on_true()),
+        ("check", "no", WorkflowEdge::on_false()),
+    ])
+    .unwrap();
+    assert!(warnings.is_empty());
+
+    let error = build(&[
+        ("check", "yes", WorkflowEdge::on_true()),
+        ("check", "no", WorkflowEdge::on_true()),
+    ])
+    .unwrap_err();
+    assert!(error.to_string().contains("has 2 true edges"), "{error}");
+
+    let error = build(&[
+        ("check", "yes", WorkflowEdge::on_true()),
+        ("check", "no", WorkflowEdge::data_flow()),
+    ])
+    .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("true/false edges and other outgoing edges"),
+        "{error}"
+    );
+
+    let error = build(&[("source", "yes", WorkflowEdge::on_false())]).unwrap_err();
+    assert!(
+        error.to_string().contains("is not a condition node"),
+        "{error}"
+    );
+
+    // On-failure edges may leave a condition node with true/false edges
+    let warnings = build(&[
+        ("check", "yes", WorkflowEdge::on_true()),
+        ("check", "no", WorkflowEdge::on_failure()),
+    ])
+    .unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("has no false edge"));
+}
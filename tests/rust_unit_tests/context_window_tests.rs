@@ -0,0 +1,283 @@
+//! Context-window estimation, truncation strategies and their use by agent nodes
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{
+        ContextStrategy, ContextWindow, LlmConfig, LlmMessage, LlmRequest, LlmRole, LlmToolCall,
+        context_window::{estimate_request_tokens, estimate_tokens},
+    },
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Provider with a 300-token context window answering "ok"
+fn small_window_provider() -> ScriptedProvider {
+    ScriptedProvider::replying("ok").with_max_context_length(300)
+}
+
+/// Run a single agent node whose prompt far exceeds the 300-token window
+async fn run_oversized_node(
+    configure: impl FnOnce(WorkflowNode) -> WorkflowNode,
+) -> (WorkflowContext, Vec<LlmRequest>) {
+    let provider = small_window_provider();
+    let requests = provider.requests();
+    let agent_id = AgentId::new();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Reader", "Reads documents", LlmConfig::default())
+            .with_id(agent_id.clone()),
+        provider,
+    ));
+    let executor = WorkflowExecutor::new().without_retries();
+    executor.register_agent(agent).await;
+
+    let prompt = format!(
+        "DOCUMENT START {} DOCUMENT END. Summarize the document.",
+        "lorem ipsum ".repeat(400)
+    );
+    let node = WorkflowNode::new(
+        "summarize",
+        "Summarize a document",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, prompt),
+        },
+    );
+    let mut workflow = Workflow::new("oversized", "Oversized prompt");
+    workflow.add_node(configure(node)).unwrap();
+    let context = executor.execute(workflow, None).await.unwrap();
+    let requests = requests.lock().unwrap().clone();
+    (context, requests)
+}
+
+fn tool_call(id: &str) -> LlmToolCall {
+    LlmToolCall {
+        id: id.to_string(),
+        name: "search".to_string(),
+        parameters: json!({"query": "weather"}),
+    }
+}
+
+#[test]
+fn test_token_estimates() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("abcd"), 1);
+    assert_eq!(estimate_tokens("abcde"), 2);
+    // Characters outside ASCII count as a token each
+    assert_eq!(estimate_tokens("日本語"), 3);
+
+    let request = LlmRequest::with_messages(vec![
+        LlmMessage::system("You are terse."),
+        LlmMessage::user("Hello there"),
+    ]);
+    let base = estimate_request_tokens(&request);
+    assert!(base > estimate_tokens("You are terse.Hello there"));
+    let with_tool = request.with_tool(graphbit_core::llm::LlmTool::new(
+        "search",
+        "Search the web",
+        json!({"type": "object", "properties": {"query": {"type": "string"}}}),
+    ));
+    assert!(estimate_request_tokens(&with_tool) > base);
+}
+
+#[test]
+fn test_requests_that_fit_are_untouched() {
+    let mut request = LlmRequest::new("short prompt");
+    let fit = ContextWindow::new(1000, ContextStrategy::Fail)
+        .fit(&mut request)
+        .unwrap();
+    assert!(!fit.truncated);
+    assert_eq!(fit.estimated_tokens, fit.final_tokens);
+    assert_eq!(request.messages[0].content, "short prompt");
+}
+
+#[test]
+fn test_fail_strategy_states_the_overflow() {
+    let mut request = LlmRequest::new("x".repeat(4000)).with_max_tokens(100);
+    let estimated = estimate_request_tokens(&request);
+    let err = ContextWindow::new(500, ContextStrategy::Fail)
+        .fit(&mut request)
+        .unwrap_err()
+        .to_string();
+
+    let over = estimated - 400;
+    assert!(
+        err.contains(&format!(
+            "needs an estimated {estimated} prompt tokens but only 400 fit"
+        )),
+        "{err}"
+    );
+    assert!(
+        err.contains("500 token context window, 100 reserved"),
+        "{err}"
+    );
+    assert!(err.contains(&format!("{over} tokens over")), "{err}");
+}
+
+#[test]
+fn test_truncate_history_drops_oldest_messages() {
+    let filler = "history ".repeat(100);
+    let mut request = LlmRequest::with_messages(vec![
+        LlmMessage::system("Be helpful."),
+        LlmMessage::user(format!("old question {filler}")),
+        LlmMessage::assistant(format!("old answer {filler}")),
+        LlmMessage::user("What is the weather?"),
+        LlmMessage::assistant("").with_tool_calls(vec![tool_call("call_1")]),
+        LlmMessage::tool("call_1", format!("stale result {filler}")),
+        LlmMessage::assistant("").with_tool_calls(vec![tool_call("call_2")]),
+        LlmMessage::tool("call_2", "Sunny"),
+    ]);
+
+    let fit = ContextWindow::new(120, ContextStrategy::TruncateHistory)
+        .fit(&mut request)
+        .unwrap();
+
+    assert!(fit.truncated);
+    assert_eq!(fit.removed_messages, 4);
+    assert!(fit.final_tokens <= 120);
+    // The earlier tool call is dropped together with its result
+    let kept: Vec<(LlmRole, &str)> = request
+        .messages
+        .iter()
+        .map(|m| (m.role.clone(), m.content.as_str()))
+        .collect();
+    assert_eq!(
+        kept,
+        [
+            (LlmRole::System, "Be helpful."),
+            (LlmRole::User, "What is the weather?"),
+            (LlmRole::Assistant, ""),
+            (LlmRole::Tool, "Sunny"),
+        ]
+    );
+    assert_eq!(request.messages[2].tool_calls[0].id, "call_2");
+
+    // Without history to drop, the request still cannot fit
+    let mut request = LlmRequest::new("x".repeat(4000));
+    let err = ContextWindow::new(120, ContextStrategy::TruncateHistory)
+        .fit(&mut request)
+        .unwrap_err();
+    assert!(err.to_string().contains("tokens over"), "{err}");
+}
+
+#[test]
+fn test_truncate_documents_cuts_the_middle() {
+    let document = format!("BEGIN {} END", "filler text ".repeat(500));
+    let mut request = LlmRequest::with_messages(vec![
+        LlmMessage::system("Summarize."),
+        LlmMessage::user(document),
+    ]);
+
+    let fit = ContextWindow::new(200, ContextStrategy::TruncateDocuments)
+        .fit(&mut request)
+        .unwrap();
+
+    assert!(fit.truncated);
+    assert!(fit.final_tokens <= 200);
+    assert_eq!(fit.removed_messages, 0);
+    let content = &request.messages[1].content;
+    assert!(content.starts_with("BEGIN "), "{content}");
+    assert!(content.ends_with(" END"), "{content}");
+    assert!(content.contains("tokens truncated ...]"), "{content}");
+    assert_eq!(request.messages[0].content, "Summarize.");
+}
+
+#[test]
+fn test_node_context_configuration() {
+    let provider = small_window_provider();
+    let mut config = HashMap::new();
+    assert_eq!(ContextWindow::for_node(&config, &provider).unwrap(), None);
+
+    config.insert("context_strategy".to_string(), json!("truncate_history"));
+    let window = ContextWindow::for_node(&config, &provider)
+        .unwrap()
+        .unwrap();
+    assert_eq!(window.limit(), 300);
+    assert_eq!(window.strategy(), ContextStrategy::TruncateHistory);
+
+    config.insert("context_window".to_string(), json!(1000));
+    let window = ContextWindow::for_node(&config, &provider)
+        .unwrap()
+        .unwrap();
+    assert_eq!(window.limit(), 1000);
+
+    config.insert("context_window".to_string(), json!(0));
+    assert!(ContextWindow::for_node(&config, &provider).is_err());
+
+    // Unknown strategies are rejected when the workflow is validated
+    let node = WorkflowNode::new(
+        "agent",
+        "Agent",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), "Prompt"),
+        },
+    );
+    assert!(
+        node.clone()
+            .with_context_strategy(ContextStrategy::Fail)
+            .validate()
+            .is_ok()
+    );
+    let mut invalid = node;
+    invalid
+        .config
+        .insert("context_strategy".to_string(), json!("drop_everything"));
+    let err = invalid.validate().unwrap_err();
+    assert!(
+        err.to_string().contains("Unknown context strategy"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_agent_node_truncates_oversized_prompt() {
+    let (context, requests) =
+        run_oversized_node(|node| node.with_context_strategy(ContextStrategy::TruncateDocuments))
+            .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let sent = &requests[0].messages.last().unwrap().content;
+    assert!(sent.starts_with("DOCUMENT START"), "{sent}");
+    assert!(sent.ends_with("Summarize the document."), "{sent}");
+    assert!(estimate_request_tokens(&requests[0]) <= 300);
+
+    let fit = &context.metadata["node_response_summarize"]["context_window"];
+    assert_eq!(fit["truncated"], json!(true));
+    assert_eq!(fit["strategy"], json!("truncate_documents"));
+    assert_eq!(fit["context_window"], json!(300));
+}
+
+#[tokio::test]
+async fn test_agent_node_fails_on_overflow() {
+    let (context, requests) = run_oversized_node(|node| {
+        node.with_context_strategy(ContextStrategy::Fail)
+            .with_context_window(250)
+            .with_max_tokens(50)
+    })
+    .await;
+
+    assert!(requests.is_empty());
+    let record = &context.get_node_executions()[0];
+    assert!(!record.success);
+    let error = record.error.as_deref().unwrap();
+    assert!(error.contains("but only 200 fit"), "{error}");
+    assert!(
+        error.contains("250 token context window, 50 reserved"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_nodes_without_strategy_are_unchanged() {
+    let (context, requests) = run_oversized_node(|node| node).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert!(estimate_request_tokens(&requests[0]) > 300);
+    assert_eq!(
+        context.metadata["node_response_summarize"]["context_window"],
+        json!(null)
+    );
+}
@@ -1,4 +1,7 @@
-use graphbit_core::document_loader::{DocumentLoader, DocumentLoaderConfig};
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::document_loader::{
+    CACHE_HIT_KEY, DocumentLoader, DocumentLoaderConfig, DownloadProgress, DownloadProgressCallback,
+};
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -337,3 +340,445 @@ async fn test_file_size_limit_validation() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_load_directory_detects_types_and_skips_unsupported() {
+    let dir = tempfile::tempdir().expect("tmp dir");
+    std::fs::write(dir.path().join("b.txt"), "plain text").unwrap();
+    std::fs::write(dir.path().join("a.json"), "{\"k\": 1}").unwrap();
+    std::fs::write(dir.path().join("notes.md"), "# skipped").unwrap();
+    std::fs::create_dir(dir.path().join("nested")).unwrap();
+    std::fs::write(dir.path().join("nested").join("c.txt"), "nested text").unwrap();
+
+    let loader = DocumentLoader::new();
+    let root = dir.path().to_str().unwrap();
+
+    let documents = loader.load_directory(root, false).await.unwrap();
+    let types: Vec<_> = documents.iter().map(|d| d.document_type.as_str()).collect();
+    assert_eq!(types, ["json", "txt"]);
+    assert_eq!(documents[1].content, "plain text");
+
+    let documents = loader.load_directory(root, true).await.unwrap();
+    assert_eq!(documents.len(), 3);
+    assert!(documents.iter().any(|d| d.content == "nested text"));
+
+    let missing = dir.path().join("missing");
+    assert!(loader
+        .load_directory(missing.to_str().unwrap(), false)
+        .await
+        .is_err());
+}
+
+fn cached_loader(cache_dir: &std::path::Path) -> DocumentLoader {
+    DocumentLoader::with_config(DocumentLoaderConfig {
+        cache_dir: Some(cache_dir.to_path_buf()),
+        ..Default::default()
+    })
+}
+
+fn cache_hit(document: &graphbit_core::document_loader::DocumentContent) -> bool {
+    document.metadata[CACHE_HIT_KEY].as_bool().unwrap()
+}
+
+#[tokio::test]
+async fn test_cache_serves_unchanged_file_and_reparses_touched_file() {
+    let dir = tempfile::tempdir().expect("tmp dir");
+    let path = dir.path().join("doc.json");
+    std::fs::write(&path, "{\"k\": 1}").unwrap();
+    let source = path.to_str().unwrap();
+    let loader = cached_loader(&dir.path().join("cache"));
+
+    let first = loader.load_document(source, "json").await.unwrap();
+    assert!(!cache_hit(&first));
+    assert!(first.content.contains("\"k\": 1"));
+
+    // Same size and modification time: the cached extraction is served even
+    // though the bytes changed, proving the file was not parsed again
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    std::fs::write(&path, "{\"k\": 2}").unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(modified).unwrap();
+    let second = loader.load_document(source, "json").await.unwrap();
+    assert!(cache_hit(&second));
+    assert_eq!(second.content, first.content);
+    assert_eq!(second.extracted_at, first.extracted_at);
+
+    // Touching the file invalidates the entry
+    file.set_modified(modified + std::time::Duration::from_secs(5))
+        .unwrap();
+    let third = loader.load_document(source, "json").await.unwrap();
+    assert!(!cache_hit(&third));
+    assert!(third.content.contains("\"k\": 2"));
+
+    // Entries are not shared across document types or extraction settings
+    let as_text = loader.load_document(source, "txt").await.unwrap();
+    assert!(!cache_hit(&as_text));
+    let mut config = DocumentLoaderConfig {
+        cache_dir: Some(dir.path().join("cache")),
+        ..Default::default()
+    };
+    config
+        .extraction_settings
+        .insert("mode".to_string(), serde_json::json!("fast"));
+    let other = DocumentLoader::with_config(config)
+        .load_document(source, "json")
+        .await
+        .unwrap();
+    assert!(!cache_hit(&other));
+}
+
+#[tokio::test]
+async fn test_documents_are_not_flagged_without_a_cache() {
+    let mut tmp = NamedTempFile::new().expect("create temp");
+    write!(tmp, "plain text").unwrap();
+    let document = DocumentLoader::new()
+        .load_document(tmp.path().to_str().unwrap(), "txt")
+        .await
+        .unwrap();
+    assert!(!document.metadata.contains_key(CACHE_HIT_KEY));
+}
+
+/// `If-None-Match` header of every request a mock server received
+type SeenValidators = std::sync::Arc<std::sync::Mutex<Vec<Option<String>>>>;
+
+/// Serve `body` with an ETag, answering 304 to requests that present it
+async fn spawn_etag_server(body: &'static str) -> (String, SeenValidators) {
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let server = MockServer::new(move |request| {
+        let if_none_match = request.header("if-none-match").map(str::to_string);
+        let response = if if_none_match.as_deref() == Some("\"v1\"") {
+            MockResponse::new(304, "")
+        } else {
+            MockResponse::json(200, body)
+        };
+        recorded.lock().unwrap().push(if_none_match);
+        std::future::ready(response.with_header("ETag", "\"v1\""))
+    })
+    .await;
+    (format!("{}/doc.json", server.url()), requests)
+}
+
+#[tokio::test]
+async fn test_cache_revalidates_urls_with_etag() {
+    let dir = tempfile::tempdir().expect("tmp dir");
+    let (url, requests) = spawn_etag_server("{\"k\": 1}").await;
+    let loader = cached_loader(dir.path());
+
+    let first = loader.load_document(&url, "json").await.unwrap();
+    assert!(!cache_hit(&first));
+    let second = loader.load_document(&url, "json").await.unwrap();
+    assert!(cache_hit(&second));
+    assert_eq!(second.content, first.content);
+
+    assert_eq!(
+        *requests.lock().unwrap(),
+        [None, Some("\"v1\"".to_string())]
+    );
+}
+
+/// Requests per path and the most connections a mock server handled at once
+#[derive(Default)]
+struct ServerLog {
+    attempts: std::collections::HashMap<String, u32>,
+    active: usize,
+    max_active: usize,
+}
+
+/// Serve JSON after two `429` answers per path, the first with `Retry-After: 0`;
+/// paths starting with `/down` always answer `503` and `/slow` never answers
+async fn spawn_flaky_server() -> (String, std::sync::Arc<std::sync::Mutex<ServerLog>>) {
+    let log = std::sync::Arc::new(std::sync::Mutex::new(ServerLog::default()));
+    let server_log = log.clone();
+    let server = MockServer::new(move |request| {
+        let log = server_log.clone();
+        async move {
+            let path = request.path;
+            let attempt = {
+                let mut log = log.lock().unwrap();
+                log.active += 1;
+                log.max_active = log.max_active.max(log.active);
+                let attempt = log.attempts.entry(path.clone()).or_default();
+                *attempt += 1;
+                *attempt
+            };
+            let delay = if path.starts_with("/slow") {
+                10_000
+            } else {
+                30
+            };
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+
+            let response = if path.starts_with("/down") {
+                MockResponse::new(503, "")
+            } else if attempt == 1 {
+                MockResponse::new(429, "").with_header("Retry-After", "0")
+            } else if attempt == 2 {
+                MockResponse::new(429, "")
+            } else {
+                MockResponse::json(200, format!("{{\"path\": \"{path}\"}}"))
+            };
+            log.lock().unwrap().active -= 1;
+            response
+        }
+    })
+    .await;
+    (server.url(), log)
+}
+
+fn polite_loader(config: DocumentLoaderConfig) -> DocumentLoader {
+    DocumentLoader::with_config(DocumentLoaderConfig {
+        retry_backoff_ms: 10,
+        ..config
+    })
+}
+
+#[tokio::test]
+async fn test_url_fetch_retries_rate_limited_requests() {
+    let (base, log) = spawn_flaky_server().await;
+    let loader = polite_loader(DocumentLoaderConfig::default());
+
+    let document = loader
+        .load_document(&format!("{base}/doc.json"), "json")
+        .await
+        .unwrap();
+    assert!(document.content.contains("/doc.json"));
+    assert_eq!(document.metadata["fetch_attempts"], 3);
+    assert_eq!(log.lock().unwrap().attempts["/doc.json"], 3);
+
+    // Without retries the first 429 is the answer
+    let error = polite_loader(DocumentLoaderConfig {
+        max_retries: 0,
+        ..Default::default()
+    })
+    .load_document(&format!("{base}/other.json"), "json")
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("429"), "{error}");
+}
+
+#[tokio::test]
+async fn test_load_batch_limits_hosts_and_reports_failures_per_document() {
+    let (base, log) = spawn_flaky_server().await;
+    let loader = polite_loader(DocumentLoaderConfig {
+        max_requests_per_host: 2,
+        max_retries: 2,
+        ..Default::default()
+    });
+    let mut sources: Vec<String> = (0..5).map(|i| format!("{base}/doc{i}.json")).collect();
+    sources.push(format!("{base}/down.json"));
+    sources.push("/nonexistent/graphbit/missing.txt".to_string());
+
+    let results = loader.load_batch(&sources).await;
+
+    assert_eq!(
+        results
+            .iter()
+            .map(|r| r.source.as_str())
+            .collect::<Vec<_>>(),
+        sources.iter().map(String::as_str).collect::<Vec<_>>()
+    );
+    for result in &results[..5] {
+        let document = result.document.as_ref().expect("loaded after retries");
+        assert_eq!(document.metadata["fetch_attempts"], 3);
+        assert!(result.error.is_none());
+    }
+    let down = results[5].error.as_deref().unwrap();
+    assert!(
+        down.contains("503") && down.contains("after 3 attempts"),
+        "{down}"
+    );
+    assert!(
+        results[6]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("File not found")
+    );
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.attempts["/down.json"], 3);
+    assert_eq!(log.max_active, 2);
+}
+
+#[tokio::test]
+async fn test_load_batch_rate_limit_and_deadline() {
+    let (base, _log) = spawn_flaky_server().await;
+    let loader = polite_loader(DocumentLoaderConfig {
+        requests_per_second: Some(20.0),
+        batch_timeout_ms: Some(1_000),
+        ..Default::default()
+    });
+    let sources = vec![
+        format!("{base}/a.json"),
+        format!("{base}/b.json?version=2"),
+        format!("{base}/slow.json"),
+    ];
+
+    let started = std::time::Instant::now();
+    let results = loader.load_batch(&sources).await;
+    let elapsed = started.elapsed();
+
+    // Seven requests at 20 per second, the first one free
+    assert!(results[0].document.is_some(), "{:?}", results[0].error);
+    assert_eq!(results[1].document.as_ref().unwrap().document_type, "json");
+    let slow = results[2].error.as_deref().unwrap();
+    assert!(slow.contains("timed out"), "{slow}");
+    assert!(
+        elapsed >= std::time::Duration::from_millis(250),
+        "{elapsed:?}"
+    );
+    assert!(elapsed < std::time::Duration::from_secs(5), "{elapsed:?}");
+}
+
+/// `Range` and `If-Range` headers of every request a mock server received
+type SeenRanges = std::sync::Arc<std::sync::Mutex<Vec<(Option<String>, Option<String>)>>>;
+
+/// About 600 KiB of text
+fn large_text() -> String {
+    (0..60_000).map(|i| format!("line {i:05}\n")).collect()
+}
+
+/// Serve `body` with an ETag, honoring `Range` requests when `ranges` is set.
+/// The first `drops` answers close the connection halfway through the body.
+async fn spawn_dropping_server(body: String, ranges: bool, drops: usize) -> (String, SeenRanges) {
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let server = MockServer::new(move |request| {
+        let header = |name: &str| request.header(name).map(str::to_string);
+        let (range, if_range) = (header("range"), header("if-range"));
+        let start = range
+            .as_deref()
+            .filter(|_| ranges)
+            .and_then(|range| range.strip_prefix("bytes="))
+            .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+        let drop_connection = {
+            let mut seen = recorded.lock().unwrap();
+            seen.push((range, if_range));
+            seen.len() <= drops
+        };
+
+        let mut response = match start {
+            Some(start) => MockResponse::new(206, &body.as_bytes()[start..]).with_header(
+                "Content-Range",
+                format!("bytes {start}-{}/{}", body.len() - 1, body.len()),
+            ),
+            None => MockResponse::new(200, body.as_bytes()),
+        }
+        .with_header("Content-Type", "text/plain")
+        .with_header("ETag", "\"v1\"");
+        if ranges {
+            response = response.with_header("Accept-Ranges", "bytes");
+        }
+        if drop_connection {
+            response = response.cut_off();
+        }
+        std::future::ready(response)
+    })
+    .await;
+    (format!("{}/large.txt", server.url()), seen)
+}
+
+#[tokio::test]
+async fn test_url_download_resumes_with_range_requests() {
+    let body = large_text();
+    let (url, seen) = spawn_dropping_server(body.clone(), true, 2).await;
+    let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::<DownloadProgress>::new()));
+    let reports = progress.clone();
+    let loader = polite_loader(DocumentLoaderConfig {
+        download_progress: Some(DownloadProgressCallback::new(move |progress| {
+            reports.lock().unwrap().push(progress.clone());
+        })),
+        ..Default::default()
+    });
+
+    let document = loader.load_document(&url, "txt").await.unwrap();
+
+    assert_eq!(document.content, body);
+    assert_eq!(document.file_size, body.len());
+    assert_eq!(document.metadata["download_retries"], 2);
+    let first_drop = body.len() / 2;
+    let second_drop = first_drop + (body.len() - first_drop) / 2;
+    let etag = Some("\"v1\"".to_string());
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            (None, None),
+            (Some(format!("bytes={first_drop}-")), etag.clone()),
+            (Some(format!("bytes={second_drop}-")), etag),
+        ]
+    );
+
+    let progress = progress.lock().unwrap();
+    let total = Some(body.len() as u64);
+    assert!(progress.len() > 1);
+    assert!(progress.iter().all(|p| p.url == url && p.total == total));
+    assert!(
+        progress
+            .windows(2)
+            .all(|w| w[0].downloaded <= w[1].downloaded)
+    );
+    assert_eq!(progress.last().unwrap().downloaded, body.len() as u64);
+}
+
+#[tokio::test]
+async fn test_url_download_starts_over_without_range_support() {
+    let body = large_text();
+    let (url, seen) = spawn_dropping_server(body.clone(), false, 1).await;
+
+    let document = polite_loader(DocumentLoaderConfig::default())
+        .load_document(&url, "txt")
+        .await
+        .unwrap();
+
+    assert_eq!(document.content, body);
+    assert_eq!(document.metadata["download_retries"], 1);
+    assert_eq!(*seen.lock().unwrap(), [(None, None), (None, None)]);
+}
+
+#[tokio::test]
+async fn test_url_download_gives_up_after_max_download_retries() {
+    let (url, seen) = spawn_dropping_server(large_text(), true, usize::MAX).await;
+    let loader = polite_loader(DocumentLoaderConfig {
+        max_download_retries: 1,
+        ..Default::default()
+    });
+
+    let error = loader.load_document(&url, "txt").await.unwrap_err();
+
+    assert!(
+        error
+            .to_string()
+            .contains("Failed to read response body after 2 attempts"),
+        "{error}"
+    );
+    assert_eq!(seen.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_url_download_size_limit() {
+    let (url, _seen) = spawn_dropping_server(large_text(), true, 0).await;
+
+    // Above `max_file_size`, but within `max_download_size`
+    let document = polite_loader(DocumentLoaderConfig {
+        max_file_size: 1024,
+        max_download_size: Some(1024 * 1024),
+        ..Default::default()
+    })
+    .load_document(&url, "txt")
+    .await
+    .unwrap();
+    assert_eq!(document.metadata["download_retries"], 0);
+
+    let error = polite_loader(DocumentLoaderConfig {
+        max_download_size: Some(1024),
+        ..Default::default()
+    })
+    .load_document(&url, "txt")
+    .await
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("exceeds maximum allowed size"),
+        "{error}"
+    );
+}
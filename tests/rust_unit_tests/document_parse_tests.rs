@@ -0,0 +1,289 @@
+//! Parsing documents on the blocking pool, largest first, within the byte budget
+
+use graphbit_core::document_loader::{DocumentLoader, DocumentLoaderConfig, FileLoadStats};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// Build a PDF of `pages` pages, each holding `lines` lines of text
+fn generate_pdf(title: &str, pages: usize, lines: usize) -> Vec<u8> {
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {pages} >>",
+            (0..pages)
+                .map(|page| format!("{} 0 R", 4 + 2 * page))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    for page in 0..pages {
+        let mut stream = String::from("BT /F1 10 Tf 40 792 Td\n");
+        for line in 0..lines {
+            writeln!(
+                stream,
+                "0 -12 Td ({title} page {page} line {line} of the report) Tj"
+            )
+            .unwrap();
+        }
+        stream.push_str("ET");
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            5 + 2 * page
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            stream.len()
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        write!(pdf, "{} 0 obj\n{object}\nendobj\n", index + 1).unwrap();
+    }
+    let xref = pdf.len();
+    write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).unwrap();
+    for offset in offsets {
+        write!(pdf, "{offset:010} 00000 n \n").unwrap();
+    }
+    write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    )
+    .unwrap();
+    pdf.into_bytes()
+}
+
+/// Write PDFs `report-00.pdf`, `report-01.pdf`, ... of the given page counts
+fn write_pdfs(dir: &Path, page_counts: &[usize]) {
+    for (index, &pages) in page_counts.iter().enumerate() {
+        let name = format!("report-{index:02}");
+        std::fs::write(
+            dir.join(format!("{name}.pdf")),
+            generate_pdf(&name, pages, 40),
+        )
+        .unwrap();
+    }
+}
+
+/// The end of a file's parse, from the start of the load
+fn finished_ms(file: &FileLoadStats) -> f64 {
+    file.started_ms.unwrap() + file.parse_ms.unwrap()
+}
+
+#[tokio::test]
+async fn test_generated_pdf_is_extracted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("single.pdf");
+    std::fs::write(&path, generate_pdf("single", 2, 3)).unwrap();
+
+    let document = DocumentLoader::new()
+        .load_document(path.to_str().unwrap(), "pdf")
+        .await
+        .unwrap();
+    assert!(document.content.contains("single page 0 line 0"));
+    assert!(document.content.contains("single page 1 line 2"));
+}
+
+// The read of a FIFO blocks until its writer is released, which only happens
+// once the test task runs again. On this single-threaded runtime that proves
+// the parse waits on the blocking pool rather than on the runtime thread; if
+// it did not, the writer gives up waiting and the test fails instead of hanging.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_runtime_runs_while_a_parse_is_blocked() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("gated.txt");
+    let status = std::process::Command::new("mkfifo")
+        .arg(&path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let (release, released) = std::sync::mpsc::channel::<()>();
+    let writer = {
+        let path = path.clone();
+        std::thread::spawn(move || {
+            let stalled = released.recv_timeout(Duration::from_secs(30)).is_err();
+            std::fs::write(path, "released").unwrap();
+            stalled
+        })
+    };
+
+    let load = tokio::spawn(async move {
+        DocumentLoader::new()
+            .load_document(path.to_str().unwrap(), "txt")
+            .await
+    });
+    // Let the load run up to its parse, which cannot finish before the release
+    tokio::task::yield_now().await;
+    assert!(!load.is_finished());
+    release.send(()).unwrap();
+
+    let document = load.await.unwrap().unwrap();
+    assert_eq!(document.content, "released");
+    assert!(!writer.join().unwrap(), "the parse held the runtime thread");
+}
+
+#[tokio::test]
+async fn test_directory_parse_stats() {
+    let dir = tempfile::tempdir().unwrap();
+    write_pdfs(dir.path(), &[40, 120, 20, 80, 60, 120, 40, 80]);
+    let loader = DocumentLoader::with_config(DocumentLoaderConfig {
+        max_parallel_parses: Some(2),
+        ..Default::default()
+    });
+
+    let loaded = loader
+        .load_directory_with_stats(dir.path().to_str().unwrap(), false)
+        .await
+        .unwrap();
+
+    let stats = &loaded.stats;
+    assert_eq!(loaded.documents.len(), 8);
+    let longest_parse_ms = stats
+        .files
+        .iter()
+        .filter_map(|file| file.parse_ms)
+        .fold(0.0, f64::max);
+    assert!(stats.files.iter().all(|file| file.parse_ms.is_some()));
+    assert!(stats.total_parse_ms > 0.0);
+    assert!(stats.elapsed_ms >= longest_parse_ms);
+}
+
+#[tokio::test]
+async fn test_larger_files_parse_first() {
+    let dir = tempfile::tempdir().unwrap();
+    write_pdfs(dir.path(), &[2, 20, 5, 12]);
+    let loader = DocumentLoader::with_config(DocumentLoaderConfig {
+        max_parallel_parses: Some(1),
+        ..Default::default()
+    });
+
+    let loaded = loader
+        .load_directory_with_stats(dir.path().to_str().unwrap(), false)
+        .await
+        .unwrap();
+
+    // Documents and stats stay in path order
+    let sources: Vec<String> = loaded
+        .documents
+        .iter()
+        .map(|document| {
+            Path::new(&document.source)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    assert_eq!(
+        sources,
+        [
+            "report-00.pdf",
+            "report-01.pdf",
+            "report-02.pdf",
+            "report-03.pdf"
+        ]
+    );
+    assert!(
+        loaded
+            .stats
+            .files
+            .iter()
+            .zip(&loaded.documents)
+            .all(|(file, document)| file.source == document.source)
+    );
+
+    // With one parse at a time, files start in decreasing size
+    let mut by_start: Vec<&FileLoadStats> = loaded.stats.files.iter().collect();
+    by_start.sort_by(|a, b| a.started_ms.unwrap().total_cmp(&b.started_ms.unwrap()));
+    let sizes: Vec<usize> = by_start.iter().map(|file| file.file_size).collect();
+    let mut descending = sizes.clone();
+    descending.sort_by(|a, b| b.cmp(a));
+    assert_eq!(sizes, descending);
+    assert_eq!(
+        loaded.stats.total_bytes,
+        sizes.iter().sum::<usize>(),
+        "{:?}",
+        loaded.stats
+    );
+}
+
+#[tokio::test]
+async fn test_parse_bytes_bound_concurrent_parses() {
+    let dir = tempfile::tempdir().unwrap();
+    write_pdfs(dir.path(), &[10, 10, 10, 10]);
+    let largest = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().metadata().unwrap().len())
+        .max()
+        .unwrap();
+    // Room for one file at a time, however many parse slots there are
+    let loader = DocumentLoader::with_config(DocumentLoaderConfig {
+        max_parallel_parses: Some(4),
+        max_parse_bytes: usize::try_from(largest).unwrap(),
+        ..Default::default()
+    });
+
+    let loaded = loader
+        .load_directory_with_stats(dir.path().to_str().unwrap(), false)
+        .await
+        .unwrap();
+
+    let mut files: Vec<&FileLoadStats> = loaded.stats.files.iter().collect();
+    files.sort_by(|a, b| a.started_ms.unwrap().total_cmp(&b.started_ms.unwrap()));
+    for pair in files.windows(2) {
+        assert!(
+            pair[1].started_ms.unwrap() >= finished_ms(pair[0]),
+            "{} started before {} finished",
+            pair[1].source,
+            pair[0].source
+        );
+    }
+
+    // A file larger than the whole budget is still parsed, on its own
+    let small = DocumentLoader::with_config(DocumentLoaderConfig {
+        max_parse_bytes: 1,
+        ..Default::default()
+    });
+    let documents = small
+        .load_directory(dir.path().to_str().unwrap(), false)
+        .await
+        .unwrap();
+    assert_eq!(documents.len(), 4);
+}
+
+#[tokio::test]
+async fn test_cached_files_report_no_parse_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let docs = dir.path().join("docs");
+    std::fs::create_dir(&docs).unwrap();
+    write_pdfs(&docs, &[3, 6]);
+    let loader = DocumentLoader::with_config(DocumentLoaderConfig {
+        cache_dir: Some(dir.path().join("cache")),
+        ..Default::default()
+    });
+    let docs = docs.to_str().unwrap();
+
+    let first = loader.load_directory_with_stats(docs, false).await.unwrap();
+    assert!(first.stats.files.iter().all(|file| file.parse_ms.is_some()));
+
+    let second = loader.load_directory_with_stats(docs, false).await.unwrap();
+    assert!(
+        second
+            .stats
+            .files
+            .iter()
+            .all(|file| file.parse_ms.is_none())
+    );
+    assert_eq!(second.stats.total_parse_ms, 0.0);
+    assert_eq!(
+        first.documents[1].content, second.documents[1].content,
+        "cached content differs"
+    );
+}
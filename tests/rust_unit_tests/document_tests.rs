@@ -11,6 +11,17 @@ async fn test_document_loader_creation() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: false,
         extraction_settings: Default::default(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let _loader = DocumentLoader::with_config(config);
@@ -76,6 +87,17 @@ async fn test_file_size_limits() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: false,
         extraction_settings: Default::default(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let loader = DocumentLoader::with_config(config);
@@ -154,6 +176,17 @@ async fn test_encoding_handling() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: true,
         extraction_settings: Default::default(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let loader = DocumentLoader::with_config(config);
@@ -384,6 +417,11 @@ async fn test_detect_document_type_utility() {
     );
     assert_eq!(detect_document_type("markup.xml"), Some("xml".to_string()));
     assert_eq!(detect_document_type("page.html"), Some("html".to_string()));
+    assert_eq!(
+        detect_document_type("https://example.com/data.json?version=2"),
+        Some("json".to_string())
+    );
+    assert_eq!(detect_document_type("https://example.com/"), None);
     assert_eq!(
         detect_document_type("document.docx"),
         Some("docx".to_string())
@@ -451,6 +489,17 @@ async fn test_document_loader_config_edge_cases() {
         default_encoding: "iso-8859-1".to_string(),
         preserve_formatting: true,
         extraction_settings,
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let loader = DocumentLoader::with_config(config.clone());
@@ -0,0 +1,136 @@
+//! Edge transforms: values reshaped between nodes, stored apart from node outputs
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmRole},
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Transform data",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+async fn run(executor: &WorkflowExecutor, workflow: Workflow) -> WorkflowContext {
+    workflow.validate().unwrap();
+    executor.execute(workflow, None).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_json_field_extraction_feeds_the_target_prompt() {
+    let provider = ScriptedProvider::replying("A title");
+    let requests = provider.requests();
+    let agent_id = AgentId::new();
+    let agent = TestAgent::new(
+        AgentConfig::new("Titler", "", LlmConfig::default()).with_id(agent_id.clone()),
+        provider,
+    );
+    let executor = WorkflowExecutor::new().without_retries();
+    executor.register_agent(Arc::new(agent)).await;
+
+    let mut workflow = Workflow::new("edge_transform", "");
+    let article = workflow
+        .add_node(transform(
+            "article",
+            "{summary: 'GraphBit ships', body: 'the full article text'}",
+        ))
+        .unwrap();
+    let title = workflow
+        .add_node(WorkflowNode::new(
+            "title",
+            "",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(agent_id, "Title for: {{node.article}}"),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(
+            article.clone(),
+            title.clone(),
+            WorkflowEdge::data_flow().with_transform("summary"),
+        )
+        .unwrap();
+
+    let context = run(&executor, workflow).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let prompt = requests[0]
+        .messages
+        .iter()
+        .filter(|message| matches!(message.role, LlmRole::User))
+        .map(|message| message.content.clone())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(prompt.contains("Title for: GraphBit ships"), "{prompt}");
+    assert!(!prompt.contains("full article text"), "{prompt}");
+
+    // The article's own output is untouched; the edge value is stored beside it
+    assert_eq!(
+        context.get_node_output("article"),
+        Some(&json!({"summary": "GraphBit ships", "body": "the full article text"}))
+    );
+    assert_eq!(
+        context.get_edge_output("article", &title),
+        Some(&json!("GraphBit ships"))
+    );
+    assert_eq!(
+        context.get_edge_output(&article.to_string(), &title),
+        Some(&json!("GraphBit ships"))
+    );
+}
+
+#[tokio::test]
+async fn test_string_concatenation() {
+    let mut workflow = Workflow::new("edge_transform", "");
+    let first = workflow.add_node(transform("first", "'ada'")).unwrap();
+    let full = workflow
+        .add_node(transform("full", "upper(input)"))
+        .unwrap();
+    workflow
+        .connect_nodes(
+            first,
+            full,
+            WorkflowEdge::data_flow().with_transform("input + ' lovelace'"),
+        )
+        .unwrap();
+
+    let context = run(&WorkflowExecutor::new().without_retries(), workflow).await;
+    assert_eq!(context.get_node_output("first"), Some(&json!("ada")));
+    assert_eq!(
+        context.get_node_output("full"),
+        Some(&json!("ADA LOVELACE"))
+    );
+}
+
+#[test]
+fn test_invalid_transform_is_rejected_at_build_time() {
+    let mut workflow = Workflow::new("edge_transform", "");
+    let a = workflow.add_node(transform("a", "1")).unwrap();
+    let b = workflow.add_node(transform("b", "input")).unwrap();
+    workflow
+        .connect_nodes(
+            a,
+            b,
+            WorkflowEdge::data_flow().with_transform("upper(input"),
+        )
+        .unwrap();
+
+    let err = workflow.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("Edge 'a' -> 'b' has an invalid transform"),
+        "{err}"
+    );
+}
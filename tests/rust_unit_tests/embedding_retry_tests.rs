@@ -0,0 +1,233 @@
+//! Retries of failed requests in `EmbeddingService::process_batch`
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    embeddings::{
+        EmbeddingBatchRequest, EmbeddingConfig, EmbeddingInput, EmbeddingProvider,
+        EmbeddingRequest, EmbeddingService,
+    },
+    types::RetryConfig,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How the mock answers the `attempt`-th request (counting from 1) for a text
+#[derive(Clone, Copy)]
+enum Fault {
+    /// Succeed
+    None,
+    /// `500` on the first attempt
+    FailFirst,
+    /// `500` on every attempt
+    FailAlways,
+    /// `429` with `Retry-After: 1` on the first attempt
+    RateLimitFirst,
+}
+
+/// Input texts in the order the mock received them
+type Received = Arc<Mutex<Vec<String>>>;
+
+/// Start a server answering `OpenAI` embedding requests, failing as `fault`
+/// decides for each input text
+async fn spawn_flaky_server(fault: fn(&str) -> Fault) -> (String, Received) {
+    let received = Received::default();
+    let recorded = Arc::clone(&received);
+    let server = MockServer::new(move |request| {
+        let text = request.json()["input"].as_str().unwrap().to_string();
+        let attempt = {
+            let mut recorded = recorded.lock().unwrap();
+            recorded.push(text.clone());
+            recorded.iter().filter(|seen| **seen == text).count()
+        };
+        let response = match (fault(&text), attempt) {
+            (Fault::FailFirst, 1) | (Fault::FailAlways, _) => {
+                MockResponse::json(500, json!({"error": {"message": "upstream hiccup"}}))
+            }
+            (Fault::RateLimitFirst, 1) => {
+                MockResponse::json(429, json!({"error": {"message": "slow down"}}))
+                    .with_header("Retry-After", "1")
+            }
+            _ => MockResponse::json(
+                200,
+                json!({
+                    "data": [{"index": 0, "embedding": [text.len() as f32, 1.0]}],
+                    "model": "text-embedding-3-small",
+                    "usage": {"prompt_tokens": 2, "total_tokens": 2}
+                }),
+            ),
+        };
+        std::future::ready(response)
+    })
+    .await;
+    (server.url(), received)
+}
+
+fn service(url: &str, retry: Option<RetryConfig>) -> EmbeddingService {
+    EmbeddingService::new(EmbeddingConfig {
+        provider: EmbeddingProvider::OpenAI,
+        api_key: "test".to_string(),
+        model: "text-embedding-3-small".to_string(),
+        base_url: Some(url.to_string()),
+        timeout_seconds: Some(5),
+        max_batch_size: None,
+        extra_params: HashMap::new(),
+        retry,
+        python_instance: None,
+        timeouts: Default::default(),
+    })
+    .unwrap()
+}
+
+/// Short deterministic backoff so the tests stay fast
+fn fast_retry(max_attempts: u32) -> RetryConfig {
+    RetryConfig::new(max_attempts)
+        .with_exponential_backoff(10, 2.0, 100)
+        .with_jitter(0.0)
+}
+
+fn batch(texts: &[&str], max_concurrency: usize) -> EmbeddingBatchRequest {
+    EmbeddingBatchRequest {
+        requests: texts
+            .iter()
+            .map(|text| EmbeddingRequest {
+                input: EmbeddingInput::Single((*text).to_string()),
+                user: None,
+                params: HashMap::new(),
+            })
+            .collect(),
+        max_concurrency: Some(max_concurrency),
+        timeout_ms: Some(30_000),
+    }
+}
+
+/// Every third request (`text-2`, `text-5`, ...) fails its first attempt
+fn every_third(text: &str) -> Fault {
+    let index: usize = text.trim_start_matches("text-").parse().unwrap();
+    if index % 3 == 2 {
+        Fault::FailFirst
+    } else {
+        Fault::None
+    }
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried() {
+    let (url, received) = spawn_flaky_server(every_third).await;
+    let texts: Vec<String> = (0..12).map(|i| format!("text-{i}")).collect();
+    let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+
+    let response = service(&url, Some(fast_retry(3)))
+        .process_batch(batch(&texts, 4))
+        .await
+        .unwrap();
+
+    for (text, result) in texts.iter().zip(&response.responses) {
+        let embedding = &result.as_ref().unwrap().embeddings[0];
+        assert_eq!(embedding[0], text.len() as f32, "{text}");
+    }
+    assert_eq!(response.stats.successful_requests, 12);
+    assert_eq!(response.stats.failed_requests, 0);
+    assert_eq!(response.stats.total_embeddings, 12);
+    assert_eq!(response.stats.total_attempts, 16);
+    assert_eq!(response.stats.retried_requests, 4);
+    assert_eq!(received.lock().unwrap().len(), 16);
+}
+
+#[tokio::test]
+async fn test_no_retries_without_retry_config() {
+    let (url, received) = spawn_flaky_server(every_third).await;
+
+    let response = service(&url, None)
+        .process_batch(batch(&["text-0", "text-1", "text-2"], 3))
+        .await
+        .unwrap();
+
+    assert_eq!(response.stats.successful_requests, 2);
+    assert_eq!(response.stats.failed_requests, 1);
+    assert_eq!(response.stats.total_attempts, 3);
+    assert_eq!(response.stats.retried_requests, 0);
+    assert_eq!(received.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_request_fails_once_attempts_are_exhausted() {
+    let (url, received) = spawn_flaky_server(|text| {
+        if text == "broken" {
+            Fault::FailAlways
+        } else {
+            Fault::None
+        }
+    })
+    .await;
+
+    let response = service(&url, Some(fast_retry(2)))
+        .process_batch(batch(&["fine", "broken"], 2))
+        .await
+        .unwrap();
+
+    assert!(response.responses[0].is_ok());
+    let error = response.responses[1].as_ref().unwrap_err();
+    assert_eq!(error.http_status(), Some(500));
+    assert_eq!(response.stats.successful_requests, 1);
+    assert_eq!(response.stats.failed_requests, 1);
+    assert_eq!(response.stats.total_attempts, 4);
+    assert_eq!(response.stats.retried_requests, 1);
+    let received = received.lock().unwrap();
+    assert_eq!(received.iter().filter(|text| *text == "broken").count(), 3);
+}
+
+#[tokio::test]
+async fn test_no_retries_when_disabled() {
+    let (url, received) = spawn_flaky_server(every_third).await;
+
+    let response = service(&url, Some(RetryConfig::new(0)))
+        .process_batch(batch(&["text-0", "text-1", "text-2"], 3))
+        .await
+        .unwrap();
+
+    assert_eq!(response.stats.successful_requests, 2);
+    assert_eq!(response.stats.failed_requests, 1);
+    assert_eq!(response.stats.total_attempts, 3);
+    assert_eq!(response.stats.retried_requests, 0);
+    assert_eq!(received.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_rate_limit_waits_for_retry_after() {
+    let (url, _) = spawn_flaky_server(|_| Fault::RateLimitFirst).await;
+    // Rate limits are retried even when no error type is listed as retryable
+    let retry = fast_retry(1).with_retryable_errors(vec![]);
+
+    let started = Instant::now();
+    let response = service(&url, Some(retry))
+        .process_batch(batch(&["limited"], 1))
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(response.stats.successful_requests, 1);
+    assert_eq!(response.stats.total_attempts, 2);
+}
+
+#[tokio::test]
+async fn test_waiting_retry_frees_its_concurrency_slot() {
+    let (url, received) = spawn_flaky_server(|text| {
+        if text == "first" {
+            Fault::RateLimitFirst
+        } else {
+            Fault::None
+        }
+    })
+    .await;
+
+    let response = service(&url, Some(fast_retry(1)))
+        .process_batch(batch(&["first", "second"], 1))
+        .await
+        .unwrap();
+
+    assert_eq!(response.stats.successful_requests, 2);
+    // `second` is sent while `first` waits for its retry
+    assert_eq!(*received.lock().unwrap(), ["first", "second", "first"]);
+}
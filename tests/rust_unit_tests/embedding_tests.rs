@@ -16,7 +16,9 @@ async fn test_embedding_service_creation() {
         timeout_seconds: None,
         max_batch_size: Some(1),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config);
@@ -75,7 +77,9 @@ async fn test_openai_embeddings() {
         timeout_seconds: None,
         max_batch_size: Some(1),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).unwrap();
@@ -99,7 +103,9 @@ async fn test_huggingface_embeddings() {
         timeout_seconds: None,
         max_batch_size: Some(1),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).unwrap();
@@ -147,7 +153,9 @@ async fn test_embedding_error_handling() {
         timeout_seconds: None,
         max_batch_size: Some(1),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config);
@@ -201,7 +209,9 @@ async fn test_embedding_provider_factory_edge_cases() {
         timeout_seconds: Some(60),
         max_batch_size: Some(50),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let provider = EmbeddingProviderFactory::create_provider(openai_config);
@@ -219,7 +229,9 @@ async fn test_embedding_provider_factory_edge_cases() {
         timeout_seconds: Some(120),
         max_batch_size: Some(25),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let provider = EmbeddingProviderFactory::create_provider(hf_config);
@@ -243,7 +255,9 @@ async fn test_openai_provider_config_validation() {
         timeout_seconds: None,
         max_batch_size: None,
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let result = OpenAIEmbeddingProvider::new(invalid_config);
@@ -265,7 +279,9 @@ async fn test_huggingface_provider_config_validation() {
         timeout_seconds: None,
         max_batch_size: None,
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let result = HuggingFaceEmbeddingProvider::new(invalid_config);
@@ -286,7 +302,9 @@ async fn test_embedding_batch_processing_edge_cases() {
         timeout_seconds: Some(30),
         max_batch_size: Some(2),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).unwrap();
@@ -360,7 +378,9 @@ async fn test_embedding_service_concurrency_limits() {
         timeout_seconds: Some(30),
         max_batch_size: Some(10),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     // Test service creation (no custom concurrency method available, use regular constructor)
@@ -381,7 +401,9 @@ async fn test_embedding_service_error_handling() {
         timeout_seconds: Some(5),
         max_batch_size: Some(1),
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let service = EmbeddingService::new(config).unwrap();
@@ -440,7 +462,9 @@ async fn test_embedding_dimensions_for_different_models() {
         timeout_seconds: None,
         max_batch_size: None,
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let provider = EmbeddingProviderFactory::create_provider(ada_config).unwrap();
@@ -458,7 +482,9 @@ async fn test_embedding_dimensions_for_different_models() {
         timeout_seconds: None,
         max_batch_size: None,
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let provider = EmbeddingProviderFactory::create_provider(small_config).unwrap();
@@ -475,7 +501,9 @@ async fn test_embedding_dimensions_for_different_models() {
         timeout_seconds: None,
         max_batch_size: None,
         extra_params: HashMap::new(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
 
     let provider = EmbeddingProviderFactory::create_provider(large_config).unwrap();
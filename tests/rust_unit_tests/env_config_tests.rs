@@ -0,0 +1,322 @@
+//! Configuration read from `GRAPHBIT_*` environment variables
+
+use graphbit_core::{
+    embeddings::{EmbeddingConfig, EmbeddingProvider},
+    env_config::{EnvConfig, EnvVarStatus, ExecutorEnvDefaults},
+    graph::{NodeType, WorkflowNode},
+    llm::LlmConfig,
+    types::WorkflowState,
+    workflow::{Workflow, WorkflowExecutor},
+};
+
+fn env(vars: &[(&str, &str)]) -> EnvConfig {
+    EnvConfig::from_vars(vars.iter().copied())
+}
+
+fn base_url(config: &LlmConfig) -> Option<String> {
+    serde_json::to_value(config).unwrap()["base_url"]
+        .as_str()
+        .map(str::to_string)
+}
+
+#[test]
+fn test_llm_config_from_provider_variables() {
+    let vars = env(&[
+        ("GRAPHBIT_OPENAI_API_KEY", "sk-env-0a1b2c3d4e5f"),
+        ("GRAPHBIT_OPENAI_MODEL", "gpt-4o"),
+        ("GRAPHBIT_OPENAI_BASE_URL", "http://127.0.0.1:9/v1"),
+        ("GRAPHBIT_DEFAULT_MODEL", "default-model"),
+        ("GRAPHBIT_ANTHROPIC_API_KEY", "sk-ant-env"),
+        ("OPENAI_API_KEY", "not-read"),
+    ]);
+
+    let openai = vars.llm_config("openai").unwrap();
+    assert_eq!(openai.provider_name(), "openai");
+    assert_eq!(openai.model_name(), "gpt-4o");
+    assert_eq!(openai.api_key(), Some("sk-env-0a1b2c3d4e5f"));
+    assert_eq!(base_url(&openai).as_deref(), Some("http://127.0.0.1:9/v1"));
+
+    // Providers without their own model use the default model
+    let anthropic = vars.llm_config("Anthropic").unwrap();
+    assert_eq!(anthropic.model_name(), "default-model");
+    assert_eq!(base_url(&anthropic), None);
+
+    // Ollama needs no key
+    let ollama = env(&[("GRAPHBIT_OLLAMA_MODEL", "llama3.2")])
+        .llm_config("ollama")
+        .unwrap();
+    assert_eq!(ollama.model_name(), "llama3.2");
+
+    let azure = env(&[
+        ("GRAPHBIT_AZURELLM_API_KEY", "azure-key"),
+        ("GRAPHBIT_AZURELLM_MODEL", "gpt-4o-deployment"),
+        (
+            "GRAPHBIT_AZURELLM_ENDPOINT",
+            "https://example.openai.azure.com",
+        ),
+    ])
+    .llm_config("azurellm")
+    .unwrap();
+    let azure = serde_json::to_value(&azure).unwrap();
+    assert_eq!(azure["deployment_name"], "gpt-4o-deployment");
+    assert_eq!(azure["endpoint"], "https://example.openai.azure.com");
+    assert_eq!(azure["api_version"], "2024-10-21");
+}
+
+#[test]
+fn test_llm_config_names_missing_variables() {
+    let error = |vars: &[(&str, &str)], provider: &str| {
+        env(vars).llm_config(provider).unwrap_err().to_string()
+    };
+
+    let missing_model = error(&[("GRAPHBIT_OPENAI_API_KEY", "sk-env")], "openai");
+    assert!(
+        missing_model.contains("Neither GRAPHBIT_OPENAI_MODEL nor GRAPHBIT_DEFAULT_MODEL is set"),
+        "{missing_model}"
+    );
+    let missing_key = error(&[("GRAPHBIT_DEFAULT_MODEL", "gpt-4o")], "openai");
+    assert!(
+        missing_key.contains("GRAPHBIT_OPENAI_API_KEY is not set"),
+        "{missing_key}"
+    );
+    let unknown = error(&[], "openapi");
+    assert!(
+        unknown.contains("Unknown LLM provider 'openapi'"),
+        "{unknown}"
+    );
+    // Empty variables count as unset
+    let empty = error(
+        &[
+            ("GRAPHBIT_OPENAI_API_KEY", "  "),
+            ("GRAPHBIT_DEFAULT_MODEL", "gpt-4o"),
+        ],
+        "openai",
+    );
+    assert!(
+        empty.contains("GRAPHBIT_OPENAI_API_KEY is not set"),
+        "{empty}"
+    );
+}
+
+#[test]
+fn test_embedding_config_falls_back_to_provider_key() {
+    let config = env(&[
+        ("GRAPHBIT_EMBEDDING_MODEL", "text-embedding-3-small"),
+        ("GRAPHBIT_OPENAI_API_KEY", "sk-openai"),
+    ])
+    .embedding_config()
+    .unwrap();
+    assert_eq!(config.provider, EmbeddingProvider::OpenAI);
+    assert_eq!(config.model, "text-embedding-3-small");
+    assert_eq!(config.api_key, "sk-openai");
+
+    let config = env(&[
+        ("GRAPHBIT_EMBEDDING_PROVIDER", "voyage"),
+        ("GRAPHBIT_EMBEDDING_MODEL", "voyage-3.5"),
+        ("GRAPHBIT_EMBEDDING_API_KEY", "voyage-key"),
+        ("GRAPHBIT_VOYAGE_API_KEY", "other-key"),
+        ("GRAPHBIT_EMBEDDING_BASE_URL", "http://127.0.0.1:9"),
+    ])
+    .embedding_config()
+    .unwrap();
+    assert_eq!(config.provider, EmbeddingProvider::Voyage);
+    assert_eq!(config.api_key, "voyage-key");
+    assert_eq!(config.base_url.as_deref(), Some("http://127.0.0.1:9"));
+
+    let azure = env(&[
+        ("GRAPHBIT_EMBEDDING_PROVIDER", "azure"),
+        ("GRAPHBIT_EMBEDDING_MODEL", "text-embedding-3-small"),
+        ("GRAPHBIT_AZURELLM_API_KEY", "azure-key"),
+    ])
+    .embedding_config()
+    .unwrap_err()
+    .to_string();
+    assert!(
+        azure.contains("GRAPHBIT_EMBEDDING_BASE_URL must be set to the Azure endpoint"),
+        "{azure}"
+    );
+}
+
+#[test]
+fn test_executor_defaults_are_parsed_and_checked() {
+    assert_eq!(
+        env(&[]).executor_defaults().unwrap(),
+        ExecutorEnvDefaults::default()
+    );
+    assert_eq!(
+        env(&[
+            ("GRAPHBIT_MAX_CONCURRENCY", "8"),
+            ("GRAPHBIT_FAIL_FAST", "Yes"),
+            ("GRAPHBIT_MAX_NODE_EXECUTION_TIME_MS", "30000"),
+            ("GRAPHBIT_MAX_TOTAL_TOKENS", "1000"),
+            ("GRAPHBIT_MAX_COST_USD", "2.5"),
+        ])
+        .executor_defaults()
+        .unwrap(),
+        ExecutorEnvDefaults {
+            max_concurrency: Some(8),
+            fail_fast: Some(true),
+            max_node_execution_time_ms: Some(30_000),
+            max_total_tokens: Some(1000),
+            max_cost_usd: Some(2.5),
+        }
+    );
+
+    for (name, value, expected) in [
+        (
+            "GRAPHBIT_MAX_CONCURRENCY",
+            "0",
+            "GRAPHBIT_MAX_CONCURRENCY must be greater than 0, got '0'",
+        ),
+        (
+            "GRAPHBIT_MAX_TOTAL_TOKENS",
+            "lots",
+            "GRAPHBIT_MAX_TOTAL_TOKENS must be a whole number, got 'lots'",
+        ),
+        (
+            "GRAPHBIT_FAIL_FAST",
+            "maybe",
+            "GRAPHBIT_FAIL_FAST must be true or false, got 'maybe'",
+        ),
+        (
+            "GRAPHBIT_MAX_COST_USD",
+            "-1",
+            "GRAPHBIT_MAX_COST_USD must be a positive number, got '-1'",
+        ),
+    ] {
+        let error = env(&[(name, value)])
+            .executor_defaults()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn test_doctor_lists_found_missing_and_unknown_variables() {
+    let checks = env(&[
+        ("GRAPHBIT_DEFAULT_PROVIDER", "openai"),
+        ("GRAPHBIT_OPENAI_API_KEY", "sk-secret-9f8e7d6c5b4a"),
+        ("GRAPHBIT_MAX_CONCURRENCY", "0"),
+        ("GRAPHBIT_OPENAI_MODLE", "gpt-4o"),
+    ])
+    .doctor();
+    let status = |name: &str| {
+        checks
+            .iter()
+            .find(|check| check.name == name)
+            .unwrap_or_else(|| panic!("{name} not checked"))
+    };
+
+    assert_eq!(
+        status("GRAPHBIT_DEFAULT_PROVIDER").status,
+        EnvVarStatus::Found("openai".to_string())
+    );
+    assert_eq!(
+        status("GRAPHBIT_OPENAI_API_KEY").status,
+        EnvVarStatus::Found("[REDACTED]".to_string())
+    );
+    let model = status("GRAPHBIT_OPENAI_MODEL");
+    assert_eq!(model.status, EnvVarStatus::Missing);
+    assert!(model.required && model.is_problem());
+    assert!(!status("GRAPHBIT_OPENAI_BASE_URL").is_problem());
+    assert!(matches!(
+        status("GRAPHBIT_MAX_CONCURRENCY").status,
+        EnvVarStatus::Invalid(_)
+    ));
+    assert_eq!(
+        status("GRAPHBIT_OPENAI_MODLE").status,
+        EnvVarStatus::Unrecognized
+    );
+    // Providers without variables are left out
+    assert!(checks.iter().all(|check| !check.name.contains("ANTHROPIC")));
+    assert!(
+        checks
+            .iter()
+            .all(|check| !format!("{check:?}").contains("sk-secret"))
+    );
+    assert_eq!(checks.iter().filter(|check| check.is_problem()).count(), 2);
+}
+
+fn failing_workflow() -> Workflow {
+    let mut workflow = Workflow::new("env", "");
+    for (name, transformation) in [("broken", "1 / 0"), ("other", "'ok'")] {
+        workflow
+            .add_node(WorkflowNode::new(
+                name,
+                "",
+                NodeType::Transform {
+                    transformation: transformation.to_string(),
+                },
+            ))
+            .unwrap();
+    }
+    workflow
+}
+
+// The only test reading the process environment, so no other test sees the
+// variables it sets
+#[tokio::test]
+async fn test_process_variables_and_precedence() {
+    unsafe {
+        std::env::set_var("GRAPHBIT_XAI_API_KEY", "xai-env-4d3c2b1a");
+        std::env::set_var("GRAPHBIT_XAI_MODEL", "grok-env");
+        std::env::set_var("GRAPHBIT_FAIL_FAST", "true");
+        std::env::set_var("GRAPHBIT_EMBEDDING_PROVIDER", "jina");
+        std::env::set_var("GRAPHBIT_EMBEDDING_MODEL", "jina-embeddings-v3");
+        std::env::set_var("GRAPHBIT_JINA_API_KEY", "jina-env");
+    }
+
+    let config = LlmConfig::from_env("xai").unwrap();
+    assert_eq!(config.model_name(), "grok-env");
+    assert_eq!(config.api_key(), Some("xai-env-4d3c2b1a"));
+    // Settings made in code win
+    assert_eq!(config.with_model("grok-code").model_name(), "grok-code");
+    let embedding = EmbeddingConfig::from_env().unwrap();
+    assert_eq!(embedding.provider, EmbeddingProvider::Jina);
+    assert_eq!(embedding.api_key, "jina-env");
+
+    let context = WorkflowExecutor::from_env()
+        .unwrap()
+        .without_retries()
+        .execute(failing_workflow(), None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(context.state, WorkflowState::Failed { .. }),
+        "{:?}",
+        context.state
+    );
+    let context = WorkflowExecutor::from_env()
+        .unwrap()
+        .without_retries()
+        .with_fail_fast(false)
+        .execute(failing_workflow(), None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(context.state, WorkflowState::PartiallyCompleted { .. }),
+        "{:?}",
+        context.state
+    );
+
+    unsafe {
+        std::env::remove_var("GRAPHBIT_XAI_MODEL");
+        std::env::set_var("GRAPHBIT_FAIL_FAST", "sometimes");
+    }
+    let error = LlmConfig::from_env("xai").unwrap_err().to_string();
+    assert!(error.contains("GRAPHBIT_XAI_MODEL"), "{error}");
+    assert!(WorkflowExecutor::from_env().is_err());
+
+    unsafe {
+        for name in [
+            "GRAPHBIT_XAI_API_KEY",
+            "GRAPHBIT_FAIL_FAST",
+            "GRAPHBIT_EMBEDDING_PROVIDER",
+            "GRAPHBIT_EMBEDDING_MODEL",
+            "GRAPHBIT_JINA_API_KEY",
+        ] {
+            std::env::remove_var(name);
+        }
+    }
+}
@@ -125,6 +125,35 @@ fn test_error_from_conversions() {
     assert!(matches!(graphbit_err, GraphBitError::Internal { .. }));
 }
 
+#[test]
+fn test_error_from_http_status() {
+    let rate_limited = GraphBitError::from_http_status("openai", 429, Some(7), "slow down");
+    assert!(matches!(
+        rate_limited,
+        GraphBitError::RateLimit {
+            retry_after_seconds: 7,
+            ..
+        }
+    ));
+    assert_eq!(rate_limited.http_status(), Some(429));
+    assert_eq!(
+        GraphBitError::from_http_status("openai", 429, None, "").retry_delay(),
+        Some(1)
+    );
+
+    let unauthorized = GraphBitError::from_http_status("openai", 401, None, "bad key");
+    assert!(matches!(unauthorized, GraphBitError::Authentication { .. }));
+    assert_eq!(unauthorized.http_status(), Some(401));
+
+    let server_error = GraphBitError::from_http_status("openai", 503, None, "unavailable");
+    assert!(matches!(server_error, GraphBitError::LlmProvider { .. }));
+    assert!(server_error.is_retryable());
+    assert_eq!(server_error.http_status(), Some(503));
+    assert!(server_error.to_string().contains("unavailable"));
+
+    assert_eq!(GraphBitError::llm_provider("openai", "boom").http_status(), None);
+}
+
 #[test]
 fn test_retryable_error_type_classification() {
     let network_err = GraphBitError::Network {
@@ -0,0 +1,266 @@
+//! Execution report export
+
+use chrono::{DateTime, Duration, Utc};
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    report::{REPORT_VERSION, ReportConfig},
+    types::{
+        NodeExecutionRecord, NodeId, Secrets, ToolCallRecord, WorkflowContext, WorkflowId,
+        WorkflowState,
+    },
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Set `UPDATE_SNAPSHOTS=1` to rewrite the snapshot after an intended change
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/rust_unit_tests/snapshots")
+        .join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing snapshot {}: {e}", path.display()));
+    assert_eq!(
+        actual.trim_end(),
+        expected.trim_end(),
+        "report does not match {}; rerun with UPDATE_SNAPSHOTS=1 if the change is intended",
+        path.display()
+    );
+}
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::seconds(seconds)
+}
+
+/// Context of a finished run, built by hand so every field is deterministic:
+/// `research` (an agent with a tool call) succeeds, `publish` fails after two
+/// retries and the `archive` branch never runs
+fn mock_run() -> WorkflowContext {
+    let research = NodeId::from_string("research").unwrap();
+    let publish = NodeId::from_string("publish").unwrap();
+    let archive = NodeId::from_string("archive").unwrap();
+
+    let mut context = WorkflowContext::new(
+        WorkflowId::from_string("6f1c2a9e-3b4d-4e5f-8a7b-9c0d1e2f3a4b").unwrap(),
+    );
+    context.started_at = at(0);
+    context.set_metadata(
+        "node_id_to_name".to_string(),
+        json!({
+            research.to_string(): "research",
+            publish.to_string(): "publish",
+            archive.to_string(): "archive",
+        }),
+    );
+    context.set_metadata(
+        "node_dependencies".to_string(),
+        json!({
+            research.to_string(): [],
+            publish.to_string(): [research.to_string()],
+            archive.to_string(): [research.to_string()],
+        }),
+    );
+    context.set_metadata(
+        format!("node_response_{research}"),
+        json!({
+            "user_input": "Summarize the release notes for version 2.4 in three bullet points",
+            "total_usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2},
+            "executions": [
+                {
+                    "type": "llm_call",
+                    "model": "mock-model",
+                    "usage": {"prompt_tokens": 120, "completion_tokens": 30, "total_tokens": 150}
+                },
+                {"type": "tool_call", "tool_name": "search"},
+                {
+                    "type": "llm_call",
+                    "model": "mock-model",
+                    "usage": {"prompt_tokens": 200, "completion_tokens": 45, "total_tokens": 245}
+                }
+            ]
+        }),
+    );
+    context.set_node_output_by_name(
+        "research",
+        json!({"summary": "Faster startup", "api_key": "sk-test-123"}),
+    );
+    context.set_node_output(
+        &research,
+        json!({"summary": "Faster startup", "api_key": "sk-test-123"}),
+    );
+    context.record_tool_call(
+        "research",
+        ToolCallRecord::new(
+            "search",
+            json!({"query": "release notes 2.4"}),
+            json!("2.4: faster startup, smaller wheels, new streaming API and many fixes"),
+            true,
+            12.0,
+        ),
+    );
+    context.record_node_attempts("research", 1);
+    context.record_node_execution(NodeExecutionRecord {
+        node_id: research,
+        node_name: "research".to_string(),
+        step: 1,
+        success: true,
+        error: None,
+        started_at: at(0),
+        duration_ms: 1200,
+        retry_count: 0,
+        idempotency_key: None,
+        pacing: None,
+    });
+    context.record_node_attempts("publish", 3);
+    context.record_node_execution(NodeExecutionRecord {
+        node_id: publish,
+        node_name: "publish".to_string(),
+        step: 2,
+        success: false,
+        error: Some("HTTP request node 'publish' received status 503: unavailable".to_string()),
+        started_at: at(1),
+        duration_ms: 1300,
+        retry_count: 2,
+        idempotency_key: None,
+        pacing: None,
+    });
+    context.state = WorkflowState::Failed {
+        error: "Node 'publish' failed".to_string(),
+        reason: None,
+    };
+    context.completed_at = Some(at(2) + Duration::milliseconds(500));
+    context
+}
+
+#[test]
+fn test_report_json_snapshot() {
+    let report = mock_run().to_report_with(&ReportConfig::default().with_max_value_chars(Some(40)));
+    assert_eq!(report.report_version, REPORT_VERSION);
+    assert_snapshot("execution_report.json", &report.to_json().unwrap());
+}
+
+#[test]
+fn test_report_mermaid_graph() {
+    assert_eq!(
+        mock_run().to_report().to_mermaid(),
+        "flowchart TD\n\
+         \x20   n0[\"archive\"]:::skipped\n\
+         \x20   n1[\"2. publish\"]:::failed\n\
+         \x20   n2[\"1. research\"]:::succeeded\n\
+         \x20   n2 --> n0\n\
+         \x20   n2 --> n1\n\
+         \x20   classDef succeeded fill:#dcfce7,stroke:#16a34a\n\
+         \x20   classDef failed fill:#fee2e2,stroke:#dc2626\n\
+         \x20   classDef skipped fill:#f3f4f6,stroke:#9ca3af,stroke-dasharray: 4 4\n"
+    );
+}
+
+#[test]
+fn test_report_html_is_escaped_and_collapsible() {
+    let mut context = mock_run();
+    context.set_node_output(
+        &NodeId::from_string("research").unwrap(),
+        json!("<script>alert('x')</script>"),
+    );
+    let html = context.to_report().to_html();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+    assert!(!html.contains("<script>alert"));
+    // Failed nodes start expanded, succeeded ones collapsed
+    assert!(html.contains("<details class=\"node failed\" open>"));
+    assert!(html.contains("<details class=\"node succeeded\">"));
+    assert!(html.contains("<pre class=\"mermaid\">\nflowchart TD"));
+    assert!(html.contains("n2 --&gt; n1"));
+}
+
+#[test]
+fn test_report_redaction_and_truncation() {
+    let mut context = mock_run().with_secrets(Secrets::from_iter([("TOKEN", "hunter2")]));
+    context.set_node_output(
+        &NodeId::from_string("research").unwrap(),
+        json!({"note": "token hunter2", "password": "p4ss", "body": "y".repeat(50)}),
+    );
+
+    let report = context.to_report_with(
+        &ReportConfig::default()
+            .with_max_value_chars(Some(10))
+            .with_redact_key("note"),
+    );
+    assert_eq!(
+        report.nodes[0].output,
+        json!({"note": "[REDACTED]", "password": "[REDACTED]", "body": "yyyyyyyyyy... [truncated 40 chars]"})
+    );
+
+    context.set_node_output(
+        &NodeId::from_string("research").unwrap(),
+        json!({"note": "token hunter2"}),
+    );
+    let report = context.to_report_with(&ReportConfig::default().with_max_value_chars(None));
+    assert_eq!(report.nodes[0].output, json!({"note": "token [REDACTED]"}));
+}
+
+#[tokio::test]
+async fn test_report_of_executed_workflow() {
+    let mut workflow = Workflow::new("report", "Report a run");
+    let mut add = |name: &str, transformation: &str| {
+        workflow
+            .add_node(WorkflowNode::new(
+                name,
+                name,
+                NodeType::Transform {
+                    transformation: transformation.to_string(),
+                },
+            ))
+            .unwrap()
+    };
+    let question = add("question", "upper(vars.question)");
+    let length = add("length", "len(input)");
+    let broken = add("broken", "int(input)");
+    workflow
+        .connect_nodes(question.clone(), length, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(question, broken, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_inputs(
+            workflow,
+            None,
+            HashMap::from([("question".to_string(), json!("why"))]),
+            Secrets::new(),
+        )
+        .await
+        .unwrap();
+    let report = context.to_report();
+
+    assert_eq!(report.nodes.len(), 3);
+    assert_eq!(report.nodes[0].node_name, "question");
+    assert_eq!(report.nodes[0].step, 1);
+    assert_eq!(report.nodes[0].output, json!("WHY"));
+    let by_name = |name: &str| report.nodes.iter().find(|n| n.node_name == name).unwrap();
+    assert_eq!(by_name("length").step, 2);
+    assert_eq!(by_name("length").output, json!(3));
+    assert_eq!(by_name("length").status, "succeeded");
+    assert_eq!(by_name("broken").status, "failed");
+    assert!(by_name("broken").error.is_some());
+    assert_eq!(by_name("broken").attempts, 1);
+    assert!(report.skipped_nodes.is_empty());
+    assert_eq!(report.edges.len(), 2);
+    assert_eq!(
+        report.nodes.iter().map(|n| n.sequence).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+
+    // The report round-trips through its JSON form
+    let restored: graphbit_core::ExecutionReport =
+        serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(restored.nodes.len(), 3);
+}
@@ -0,0 +1,498 @@
+//! Execution service: workflow submission, background runs and the HTTP API
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    errors::GraphBitError,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmResponse},
+    server::{ExecutionService, ExecutionStatus, ServerConfig, serve, serve_with_config},
+    stream::{StreamEvent, StreamMode},
+    types::AgentId,
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Service whose executor runs the agents `agent_ids` on a provider answering
+/// with the prompt it received, failing on prompts containing "fail"
+async fn echo_service(agent_ids: &[AgentId]) -> ExecutionService {
+    let executor = WorkflowExecutor::new().without_retries();
+    for agent_id in agent_ids {
+        let provider = ScriptedProvider::new(|request| {
+            let prompt = request
+                .messages
+                .last()
+                .map(|message| message.content.clone())
+                .unwrap_or_default();
+            if prompt.contains("fail") {
+                return Err(GraphBitError::llm("model overloaded"));
+            }
+            Ok(LlmResponse::new(format!("echo: {prompt}"), "echo-model"))
+        });
+        let agent = Arc::new(TestAgent::new(
+            AgentConfig::new("Echo", "Echoes prompts", LlmConfig::default())
+                .with_id(agent_id.clone()),
+            provider,
+        ));
+        executor.register_agent(agent).await;
+    }
+    ExecutionService::new(executor)
+}
+
+/// Summarize-then-title workflow on two echo agents
+fn summarize_workflow(agent_ids: &[AgentId], prompt: &str) -> Workflow {
+    let mut workflow = Workflow::new("summarize", "Summarize and title");
+    let summarize = workflow
+        .add_node(WorkflowNode::new(
+            "summarize",
+            "Summarize the topic",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(agent_ids[0].clone(), prompt),
+            },
+        ))
+        .unwrap();
+    let title = workflow
+        .add_node(WorkflowNode::new(
+            "title",
+            "Title the summary",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(agent_ids[1].clone(), "Title this summary"),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(summarize, title, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+/// Start an HTTP server for `service` on a free local port, returning its base URL
+async fn start_server(service: ExecutionService) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(serve(listener, service));
+    url
+}
+
+/// Parse a server-sent event stream into `(event, data)` pairs
+fn parse_events(body: &str) -> Vec<(String, Value)> {
+    body.split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let mut lines = block.lines();
+            let event = lines.next().unwrap().strip_prefix("event: ").unwrap();
+            let data = lines.next().unwrap().strip_prefix("data: ").unwrap();
+            (event.to_string(), serde_json::from_str(data).unwrap())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_http_submit_and_execute() {
+    let agent_ids = [AgentId::new(), AgentId::new()];
+    let url = start_server(echo_service(&agent_ids).await).await;
+    let client = reqwest::Client::new();
+
+    let workflow = summarize_workflow(&agent_ids, "Summarize {{input.topic}}");
+    let response = client
+        .post(format!("{url}/workflows"))
+        .body(workflow.to_json().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+    let submitted: Value = response.json().await.unwrap();
+    assert_eq!(submitted["id"], json!(workflow.id.to_string()));
+    assert_eq!(submitted["name"], json!("summarize"));
+    assert_eq!(submitted["node_count"], json!(2));
+
+    let response = client
+        .post(format!("{url}/executions"))
+        .json(&json!({"workflow_id": workflow.id.to_string(), "inputs": {"topic": "tides"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+    let started: Value = response.json().await.unwrap();
+    assert_eq!(started["status"], json!("running"));
+    let execution_id = started["id"].as_str().unwrap().to_string();
+
+    // The event stream ends, and the connection closes, when the execution finishes
+    let response = client
+        .get(format!("{url}/executions/{execution_id}/events"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let events = parse_events(&response.text().await.unwrap());
+    let names: Vec<&str> = events.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names.first(), Some(&"workflow_started"));
+    assert_eq!(names.last(), Some(&"workflow_completed"));
+    let completed: Vec<&Value> = events
+        .iter()
+        .filter(|(name, _)| name == "node_completed")
+        .map(|(_, data)| data)
+        .collect();
+    assert_eq!(completed.len(), 2);
+    assert_eq!(completed[0]["node_name"], json!("summarize"));
+    assert_eq!(completed[0]["output"], json!("echo: Summarize tides"));
+    assert!(
+        events
+            .iter()
+            .all(|(name, data)| data["event"] == json!(name))
+    );
+
+    let snapshot: Value = client
+        .get(format!("{url}/executions/{execution_id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(snapshot["id"], json!(execution_id));
+    assert_eq!(snapshot["workflow_id"], json!(workflow.id.to_string()));
+    assert_eq!(snapshot["status"], json!("completed"));
+    assert_eq!(snapshot["event_count"], json!(events.len()));
+    let report = &snapshot["report"];
+    assert_eq!(report["status"], json!("completed"));
+    assert_eq!(report["stats"]["successful_nodes"], json!(2));
+    let nodes = report["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0]["node_name"], json!("summarize"));
+    assert_eq!(nodes[0]["status"], json!("succeeded"));
+    assert_eq!(nodes[1]["node_name"], json!("title"));
+}
+
+#[tokio::test]
+async fn test_http_errors() {
+    let agent_ids = [AgentId::new(), AgentId::new()];
+    let url = start_server(echo_service(&agent_ids).await).await;
+    let client = reqwest::Client::new();
+    let error = |response: reqwest::Response| async move {
+        let status = response.status().as_u16();
+        let body: Value = response.json().await.unwrap();
+        (status, body["error"].as_str().unwrap().to_string())
+    };
+
+    let (status, message) = error(
+        client
+            .post(format!("{url}/workflows"))
+            .body("{\"name\": ")
+            .send()
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert!(message.starts_with("Invalid workflow"), "{message}");
+
+    let (status, message) = error(
+        client
+            .post(format!("{url}/executions"))
+            .json(&json!({"workflow_id": "missing"}))
+            .send()
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, 404);
+    assert_eq!(message, "Unknown workflow 'missing'");
+
+    let workflow = summarize_workflow(&agent_ids, "Summarize");
+    client
+        .post(format!("{url}/workflows"))
+        .body(workflow.to_json().unwrap())
+        .send()
+        .await
+        .unwrap();
+    let (status, message) = error(
+        client
+            .post(format!("{url}/executions"))
+            .json(&json!({"workflow_id": workflow.id.to_string(), "stream_mode": "tokens"}))
+            .send()
+            .await
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert!(
+        message.contains("Invalid stream_mode 'tokens'"),
+        "{message}"
+    );
+
+    for path in ["executions/missing", "executions/missing/events"] {
+        let (status, message) =
+            error(client.get(format!("{url}/{path}")).send().await.unwrap()).await;
+        assert_eq!(status, 404);
+        assert_eq!(message, "Unknown execution 'missing'");
+    }
+
+    let (status, _) = error(client.get(format!("{url}/workflows")).send().await.unwrap()).await;
+    assert_eq!(status, 405);
+    let (status, _) = error(client.get(format!("{url}/health")).send().await.unwrap()).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_service_node_failure() {
+    let agent_ids = [AgentId::new(), AgentId::new()];
+    let service = echo_service(&agent_ids).await;
+    let workflow_id = service
+        .submit_workflow(summarize_workflow(&agent_ids, "Please fail"))
+        .await
+        .unwrap();
+
+    let execution_id = service
+        .execute_stored(&workflow_id, HashMap::new(), StreamMode::Updates)
+        .await
+        .unwrap();
+    let snapshot = service.wait(&execution_id).await.unwrap();
+    // A failed agent node does not fail the workflow; the report records it
+    assert_eq!(snapshot.status, ExecutionStatus::Completed);
+    let report = snapshot.report.unwrap();
+    let summarize = report
+        .nodes
+        .iter()
+        .find(|node| node.node_name == "summarize")
+        .unwrap();
+    assert_eq!(summarize.status, "failed");
+    assert!(
+        summarize
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("model overloaded"),
+        "{:?}",
+        summarize.error
+    );
+
+    // Events stay available for replay after the execution has finished
+    let mut events = service.events(&execution_id).await.unwrap();
+    let mut failed_nodes = Vec::new();
+    while let Some(event) = events.next().await {
+        if let StreamEvent::NodeFailed { node_name, .. } = event {
+            failed_nodes.push(node_name);
+        }
+    }
+    assert_eq!(failed_nodes, ["summarize"]);
+
+    assert!(
+        service
+            .execute_stored("missing", HashMap::new(), StreamMode::Updates)
+            .await
+            .is_none()
+    );
+    assert!(service.execution("missing").await.is_none());
+}
+
+#[tokio::test]
+async fn test_service_workflow_failure() {
+    let service = echo_service(&[]).await;
+    let mut workflow = Workflow::new("routing", "Condition on a missing field");
+    let source = workflow
+        .add_node(WorkflowNode::new(
+            "source",
+            "Source",
+            NodeType::Transform {
+                transformation: "{a: 1}".to_string(),
+            },
+        ))
+        .unwrap();
+    let check = workflow
+        .add_node(WorkflowNode::new(
+            "check",
+            "Route",
+            NodeType::Condition {
+                handler_id: String::new(),
+                expression: Some("b > 0".to_string()),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(source, check.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    for branch in ["yes", "no"] {
+        let branch = workflow
+            .add_node(WorkflowNode::new(
+                branch,
+                "Branch",
+                NodeType::Transform {
+                    transformation: format!("'{branch}'"),
+                },
+            ))
+            .unwrap();
+        workflow
+            .connect_nodes(check.clone(), branch, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+
+    let execution_id = service
+        .execute(workflow, HashMap::new(), StreamMode::Updates)
+        .await;
+    let snapshot = service.wait(&execution_id).await.unwrap();
+    assert_eq!(snapshot.status, ExecutionStatus::Failed);
+    let error = snapshot.error.unwrap();
+    assert!(error.contains("Unknown name 'b'"), "{error}");
+    assert_eq!(snapshot.report.unwrap().status, "failed");
+}
+
+#[tokio::test]
+async fn test_submit_rejects_invalid_workflow() {
+    let agent_ids = [AgentId::new(), AgentId::new()];
+    let service = echo_service(&agent_ids).await;
+    let mut workflow = Workflow::new("normalize", "Normalize the input");
+    workflow
+        .add_node(WorkflowNode::new(
+            "normalize",
+            "Normalize",
+            NodeType::Transform {
+                transformation: "lower(input".to_string(),
+            },
+        ))
+        .unwrap();
+
+    let err = service.submit_workflow(workflow.clone()).await.unwrap_err();
+    assert!(err.to_string().contains("invalid transformation"), "{err}");
+    assert!(service.workflow(&workflow.id.to_string()).await.is_none());
+}
+
+/// Start an HTTP server for `service` with `config`, returning its address
+async fn start_server_with(service: ExecutionService, config: ServerConfig) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(serve_with_config(listener, service, config));
+    address
+}
+
+/// Send `request` on a raw connection and read until the server closes it
+async fn raw_exchange(address: SocketAddr, request: &[u8]) -> String {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    stream.write_all(request).await.unwrap();
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(10), stream.read_to_end(&mut response))
+        .await
+        .expect("the server kept a stalled connection open")
+        .unwrap();
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+#[tokio::test]
+async fn test_http_accepts_yaml_workflows() {
+    let agent_ids = [AgentId::new(), AgentId::new()];
+    let url = start_server(echo_service(&agent_ids).await).await;
+    let client = reqwest::Client::new();
+
+    let workflow = summarize_workflow(&agent_ids, "Summarize {{input.topic}}");
+    let value: Value = serde_json::from_str(&workflow.to_json().unwrap()).unwrap();
+    let yaml = serde_yaml::to_string(&value).unwrap();
+    let response = client
+        .post(format!("{url}/workflows"))
+        .header("content-type", "application/yaml")
+        .body(yaml)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+    let submitted: Value = response.json().await.unwrap();
+    assert_eq!(submitted["id"], json!(workflow.id.to_string()));
+    assert_eq!(submitted["node_count"], json!(2));
+
+    let response = client
+        .post(format!("{url}/workflows"))
+        .header("content-type", "text/yaml; charset=utf-8")
+        .body("name: [unclosed")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_http_decodes_path_segments() {
+    let url = start_server(echo_service(&[]).await).await;
+    let response = reqwest::get(format!("{url}/executions/missing%20run"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], json!("Unknown execution 'missing run'"));
+}
+
+#[tokio::test]
+async fn test_http_bearer_token() {
+    let config = ServerConfig::new().with_auth_token("s3cret-token");
+    assert!(!format!("{config:?}").contains("s3cret-token"));
+    let address = start_server_with(echo_service(&[]).await, config).await;
+    let url = format!("http://{address}/executions");
+    let client = reqwest::Client::new();
+
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+    let response = client
+        .get(&url)
+        .bearer_auth("s3cret-tokem")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 401);
+    let response = client
+        .get(&url)
+        .bearer_auth("s3cret-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn test_http_body_size_limit() {
+    let config = ServerConfig::new().with_max_body_bytes(64);
+    let address = start_server_with(echo_service(&[]).await, config).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("http://{address}/workflows"))
+        .body("x".repeat(65))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 413);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"], json!("Request body exceeds 64 bytes"));
+}
+
+#[tokio::test]
+async fn test_http_drops_stalled_clients() {
+    let config = ServerConfig::new()
+        .with_header_read_timeout(Duration::from_millis(200))
+        .with_body_read_timeout(Duration::from_millis(200));
+    let address = start_server_with(echo_service(&[]).await, config).await;
+
+    // Headers that never end: the connection is closed without a response
+    let response = raw_exchange(
+        address,
+        b"GET /executions HTTP/1.1\r\nHost: localhost\r\nX-Slow: 1\r\n",
+    )
+    .await;
+    assert!(!response.contains("200 OK"), "{response}");
+
+    // A body shorter than its Content-Length times out
+    let response = raw_exchange(
+        address,
+        b"POST /workflows HTTP/1.1\r\nHost: localhost\r\nContent-Length: 100\r\n\r\n{\"name\"",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 408"), "{response}");
+}
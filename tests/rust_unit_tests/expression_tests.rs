@@ -0,0 +1,507 @@
+use graphbit_core::{
+    expression::{Expression, ExpressionErrorKind, ExpressionScope},
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::WorkflowState,
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+fn eval_in(source: &str, scope: &ExpressionScope) -> Value {
+    Expression::parse(source)
+        .unwrap_or_else(|e| panic!("{source:?} failed to parse: {e}"))
+        .evaluate(scope)
+        .unwrap_or_else(|e| panic!("{source:?} failed to evaluate: {e}"))
+}
+
+fn eval(source: &str, input: Value) -> Value {
+    eval_in(source, &ExpressionScope::new(input))
+}
+
+/// Kind and position of the error raised while parsing or evaluating `source`
+fn error(source: &str, input: Value) -> (ExpressionErrorKind, usize) {
+    let err = match Expression::parse(source) {
+        Ok(expression) => expression
+            .evaluate(&ExpressionScope::new(input))
+            .expect_err(&format!("{source:?} should fail")),
+        Err(e) => e,
+    };
+    (err.kind, err.position)
+}
+
+#[test]
+fn test_arithmetic_and_precedence() {
+    assert_eq!(eval("1 + 2 * 3", Value::Null), json!(7));
+    assert_eq!(eval("(1 + 2) * 3", Value::Null), json!(9));
+    assert_eq!(eval("8 / 2", Value::Null), json!(4));
+    assert_eq!(eval("7 / 2", Value::Null), json!(3.5));
+    assert_eq!(eval("7 % 3", Value::Null), json!(1));
+    assert_eq!(eval("-2 - -3", Value::Null), json!(1));
+    assert_eq!(eval("0.5 + 0.25", Value::Null), json!(0.75));
+    assert_eq!(eval("2e3", Value::Null), json!(2000.0));
+    assert_eq!(
+        eval("9223372036854775807 + 1", Value::Null),
+        json!(9_223_372_036_854_775_808.0)
+    );
+}
+
+#[test]
+fn test_concatenation() {
+    assert_eq!(eval("'ab' + \"cd\"", Value::Null), json!("abcd"));
+    assert_eq!(eval("[1, 2] + [3]", Value::Null), json!([1, 2, 3]));
+    assert_eq!(eval(r#"'it\'s\n'"#, Value::Null), json!("it's\n"));
+}
+
+#[test]
+fn test_comparisons() {
+    assert_eq!(eval("1 < 2", Value::Null), json!(true));
+    assert_eq!(eval("2 <= 2.0", Value::Null), json!(true));
+    assert_eq!(eval("'b' > 'a'", Value::Null), json!(true));
+    assert_eq!(eval("1 == 1.0", Value::Null), json!(true));
+    assert_eq!(
+        eval("[1, {a: 2}] == [1.0, {'a': 2}]", Value::Null),
+        json!(true)
+    );
+    assert_eq!(eval("'1' == 1", Value::Null), json!(false));
+    assert_eq!(eval("null != false", Value::Null), json!(true));
+}
+
+#[test]
+fn test_boolean_logic() {
+    assert_eq!(eval("true and not false", Value::Null), json!(true));
+    assert_eq!(eval("false || true && false", Value::Null), json!(false));
+    assert_eq!(eval("!(1 > 2)", Value::Null), json!(true));
+    // Short-circuiting skips the right-hand side entirely
+    assert_eq!(eval("false and missing_name", Value::Null), json!(false));
+    assert_eq!(eval("true or 1 / 0", Value::Null), json!(true));
+}
+
+#[test]
+fn test_in_operator() {
+    assert_eq!(eval("'ell' in 'hello'", Value::Null), json!(true));
+    assert_eq!(eval("2 in [1, 2.0, 3]", Value::Null), json!(true));
+    assert_eq!(eval("'a' in {a: 1}", Value::Null), json!(true));
+    assert_eq!(eval("'b' in {a: 1}", Value::Null), json!(false));
+}
+
+#[test]
+fn test_paths_into_input() {
+    let input = json!({
+        "user": {"name": "Ada", "tags": ["admin", "ops"]},
+        "items": [10, 20, 30],
+    });
+    assert_eq!(eval("input.user.name", input.clone()), json!("Ada"));
+    assert_eq!(eval("user.tags[1]", input.clone()), json!("ops"));
+    assert_eq!(eval("items[-1]", input.clone()), json!(30));
+    assert_eq!(eval("input['user']['name']", input.clone()), json!("Ada"));
+    assert_eq!(eval("items[1 + 1]", input.clone()), json!(30));
+    // Missing fields and indexes are null rather than errors
+    assert_eq!(eval("user.email", input.clone()), json!(null));
+    assert_eq!(eval("items[10]", input.clone()), json!(null));
+    assert_eq!(eval("user.email.domain", input), json!(null));
+}
+
+#[test]
+fn test_paths_into_json_text() {
+    let input = json!(r#"{"decision": "approve", "score": 0.8}"#);
+    assert_eq!(eval("decision", input.clone()), json!("approve"));
+    assert_eq!(eval("input.score > 0.5", input), json!(true));
+    assert_eq!(eval("'héllo'[1]", Value::Null), json!("é"));
+}
+
+#[test]
+fn test_nodes_and_variables() {
+    let scope = ExpressionScope::new(json!({"limit": 1}))
+        .with_nodes(HashMap::from([(
+            "fetch".to_string(),
+            json!({"status": 200, "body": {"count": 3}}),
+        )]))
+        .with_variables(HashMap::from([
+            ("limit".to_string(), json!(5)),
+            ("region".to_string(), json!("eu")),
+        ]));
+    assert_eq!(eval_in("nodes.fetch.body.count", &scope), json!(3));
+    assert_eq!(eval_in("vars.limit", &scope), json!(5));
+    // Bare names prefer input fields over variables
+    assert_eq!(eval_in("limit", &scope), json!(1));
+    assert_eq!(eval_in("region", &scope), json!("eu"));
+    assert_eq!(eval_in("nodes.absent", &scope), json!(null));
+}
+
+#[test]
+fn test_string_functions() {
+    let input = json!("  Hello World  ");
+    assert_eq!(eval("trim(input)", input.clone()), json!("Hello World"));
+    assert_eq!(
+        eval("lower(trim(input))", input.clone()),
+        json!("hello world")
+    );
+    assert_eq!(eval("upper('abc')", Value::Null), json!("ABC"));
+    assert_eq!(eval("contains(input, 'World')", input.clone()), json!(true));
+    assert_eq!(
+        eval("starts_with(trim(input), 'Hell')", input.clone()),
+        json!(true)
+    );
+    assert_eq!(eval("ends_with(input, 'x')", input), json!(false));
+    assert_eq!(
+        eval("replace('a-b-c', '-', '+')", Value::Null),
+        json!("a+b+c")
+    );
+    assert_eq!(eval("split('a,b', ',')", Value::Null), json!(["a", "b"]));
+    assert_eq!(eval("join(['a', 'b'], ', ')", Value::Null), json!("a, b"));
+    assert_eq!(eval("len('héllo')", Value::Null), json!(5));
+}
+
+#[test]
+fn test_collection_and_type_functions() {
+    let input = json!({"b": 2, "a": 1});
+    assert_eq!(eval("keys(input)", input.clone()), json!(["a", "b"]));
+    assert_eq!(eval("values(input)", input.clone()), json!([1, 2]));
+    assert_eq!(eval("len(input)", input.clone()), json!(2));
+    assert_eq!(eval("contains([1, 2], 2)", Value::Null), json!(true));
+    assert_eq!(eval("type(input)", input.clone()), json!("object"));
+    assert_eq!(eval("type(null)", Value::Null), json!("null"));
+    assert_eq!(eval("str(12)", Value::Null), json!("12"));
+    assert_eq!(
+        eval("json_stringify(input)", input),
+        json!(r#"{"a":1,"b":2}"#)
+    );
+    assert_eq!(
+        eval("json_parse('[1, true]')", Value::Null),
+        json!([1, true])
+    );
+    assert_eq!(
+        eval("default(input.missing, 'n/a')", json!({})),
+        json!("n/a")
+    );
+    assert_eq!(eval("default(0, 'n/a')", Value::Null), json!(0));
+}
+
+#[test]
+fn test_numeric_functions() {
+    assert_eq!(eval("num('42')", Value::Null), json!(42));
+    assert_eq!(eval("num(' 2.5 ')", Value::Null), json!(2.5));
+    assert_eq!(eval("int('7.9')", Value::Null), json!(7));
+    assert_eq!(eval("int(-7.9)", Value::Null), json!(-7));
+    assert_eq!(eval("abs(-3)", Value::Null), json!(3));
+    assert_eq!(eval("round(2.5)", Value::Null), json!(3));
+    assert_eq!(eval("floor(-1.5)", Value::Null), json!(-2));
+    assert_eq!(eval("ceil(1.2)", Value::Null), json!(2));
+    assert_eq!(eval("min(3, 1, 2)", Value::Null), json!(1));
+    assert_eq!(eval("max([1, 5.5, 2])", Value::Null), json!(5.5));
+    assert_eq!(eval("max('a', 'c', 'b')", Value::Null), json!("c"));
+}
+
+#[test]
+fn test_syntax_errors_report_positions() {
+    use ExpressionErrorKind::Syntax;
+    assert_eq!(error("", Value::Null), (Syntax, 0));
+    assert_eq!(error("1 +", Value::Null), (Syntax, 3));
+    assert_eq!(error("a = 1", Value::Null), (Syntax, 2));
+    assert_eq!(error("'open", Value::Null), (Syntax, 0));
+    assert_eq!(error("(1 + 2", Value::Null), (Syntax, 6));
+    assert_eq!(error("1 2", Value::Null), (Syntax, 2));
+    assert_eq!(error("a.", Value::Null), (Syntax, 2));
+    assert_eq!(error("[1, 2", Value::Null), (Syntax, 5));
+    assert_eq!(error("{a 1}", Value::Null), (Syntax, 3));
+    assert_eq!(error("1 # 2", Value::Null), (Syntax, 2));
+    assert_eq!(error(r"'\q'", Value::Null), (Syntax, 1));
+    // Positions count characters, not bytes
+    assert_eq!(error("'é' +", Value::Null), (Syntax, 5));
+    assert_eq!(error(&"(".repeat(200), Value::Null).0, Syntax);
+
+    let err = Expression::parse("a = 1").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unexpected '='; use '==' to compare at position 2"
+    );
+}
+
+#[test]
+fn test_functions_are_checked_at_parse_time() {
+    assert_eq!(
+        error("1 + exec('rm')", Value::Null),
+        (ExpressionErrorKind::UnknownFunction, 4)
+    );
+    assert_eq!(
+        error("lower('a', 'b')", Value::Null),
+        (ExpressionErrorKind::ArgumentCount, 0)
+    );
+    assert_eq!(
+        error("replace('a')", Value::Null),
+        (ExpressionErrorKind::ArgumentCount, 0)
+    );
+    assert_eq!(
+        error("min()", Value::Null),
+        (ExpressionErrorKind::ArgumentCount, 0)
+    );
+}
+
+#[test]
+fn test_type_mismatches() {
+    use ExpressionErrorKind::TypeMismatch;
+    assert_eq!(error("1 + 'a'", Value::Null), (TypeMismatch, 2));
+    assert_eq!(error("'a' < 1", Value::Null), (TypeMismatch, 4));
+    assert_eq!(error("not 1", Value::Null), (TypeMismatch, 0));
+    assert_eq!(error("1 and true", Value::Null), (TypeMismatch, 2));
+    assert_eq!(error("-'a'", Value::Null), (TypeMismatch, 0));
+    assert_eq!(error("lower(5)", Value::Null), (TypeMismatch, 0));
+    assert_eq!(error("input.a", json!(3)), (TypeMismatch, 0));
+    assert_eq!(error("1 in 5", Value::Null), (TypeMismatch, 2));
+    assert_eq!(error("[1][true]", Value::Null), (TypeMismatch, 0));
+    assert_eq!(error("min(1, 'a')", Value::Null), (TypeMismatch, 0));
+    assert_eq!(error("join([1], ',')", Value::Null), (TypeMismatch, 0));
+
+    let err = Expression::parse("input.count")
+        .unwrap()
+        .evaluate_bool(&ExpressionScope::new(json!({"count": 2})))
+        .unwrap_err();
+    assert_eq!(err.kind, TypeMismatch);
+}
+
+#[test]
+fn test_missing_variables() {
+    assert_eq!(
+        error("status == 'ok'", json!({"state": "ok"})),
+        (ExpressionErrorKind::MissingVariable, 0)
+    );
+    assert_eq!(
+        error("1 + unknown", Value::Null),
+        (ExpressionErrorKind::MissingVariable, 4)
+    );
+    assert_eq!(
+        error("name", json!("plain text")),
+        (ExpressionErrorKind::MissingVariable, 0)
+    );
+}
+
+#[test]
+fn test_invalid_values() {
+    assert_eq!(
+        error("1 / 0", Value::Null),
+        (ExpressionErrorKind::DivisionByZero, 2)
+    );
+    assert_eq!(
+        error("5 % 0.0", Value::Null),
+        (ExpressionErrorKind::DivisionByZero, 2)
+    );
+    assert_eq!(
+        error("num('abc')", Value::Null),
+        (ExpressionErrorKind::InvalidValue, 0)
+    );
+    assert_eq!(
+        error("json_parse('{')", Value::Null),
+        (ExpressionErrorKind::InvalidValue, 0)
+    );
+    assert_eq!(
+        error("[1][0.5]", Value::Null),
+        (ExpressionErrorKind::InvalidValue, 0)
+    );
+    assert_eq!(
+        error("split('a', '')", Value::Null),
+        (ExpressionErrorKind::InvalidValue, 0)
+    );
+}
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Transform data",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+fn condition(name: &str, expression: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Route",
+        NodeType::Condition {
+            handler_id: String::new(),
+            expression: Some(expression.to_string()),
+        },
+    )
+}
+
+async fn run(workflow: Workflow) -> graphbit_core::types::WorkflowContext {
+    workflow.validate().unwrap();
+    WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_transform_nodes_evaluate_expressions() {
+    let mut workflow = Workflow::new("transforms", "Expression transforms");
+    let source = workflow
+        .add_node(transform("source", "{name: ' Ada ', scores: [3, 4]}"))
+        .unwrap();
+    let summary = workflow
+        .add_node(transform(
+            "summary",
+            "upper(trim(name)) + ': ' + str(scores[0] + scores[1])",
+        ))
+        .unwrap();
+    let legacy = workflow.add_node(transform("legacy", "lowercase")).unwrap();
+    workflow
+        .connect_nodes(source, summary.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(summary, legacy, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let context = run(workflow).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("summary"), Some(&json!("ADA: 7")));
+    assert_eq!(context.get_node_output("legacy"), Some(&json!("ada: 7")));
+}
+
+#[tokio::test]
+async fn test_condition_expression_routes_by_boolean_and_name() {
+    let mut workflow = Workflow::new("routing", "Expression conditions");
+    let source = workflow
+        .add_node(transform("source", "{score: 0.9, kind: 'billing'}"))
+        .unwrap();
+    let is_high = workflow
+        .add_node(condition("is_high", "score > 0.5"))
+        .unwrap();
+    let high = workflow.add_node(transform("high", "'high'")).unwrap();
+    let low = workflow.add_node(transform("low", "'low'")).unwrap();
+    let by_kind = workflow.add_node(condition("by_kind", "kind")).unwrap();
+    let billing = workflow
+        .add_node(transform("billing", "'billing'"))
+        .unwrap();
+    let support = workflow
+        .add_node(transform("support", "'support'"))
+        .unwrap();
+    for (from, to) in [
+        (&source, &is_high),
+        (&is_high, &high),
+        (&is_high, &low),
+        (&source, &by_kind),
+        (&by_kind, &billing),
+        (&by_kind, &support),
+    ] {
+        workflow
+            .connect_nodes(from.clone(), to.clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+
+    let context = run(workflow).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("is_high"), Some(&json!("high")));
+    assert_eq!(context.get_node_output("high"), Some(&json!("high")));
+    assert_eq!(context.get_node_output("low"), None);
+    assert_eq!(context.get_node_output("billing"), Some(&json!("billing")));
+    assert_eq!(context.get_node_output("support"), None);
+}
+
+#[tokio::test]
+async fn test_condition_expression_error_fails_workflow() {
+    let mut workflow = Workflow::new("routing", "Expression condition on a missing field");
+    let source = workflow.add_node(transform("source", "{a: 1}")).unwrap();
+    let check = workflow.add_node(condition("check", "b > 0")).unwrap();
+    let yes = workflow.add_node(transform("yes", "'yes'")).unwrap();
+    let no = workflow.add_node(transform("no", "'no'")).unwrap();
+    for (from, to) in [(&source, &check), (&check, &yes), (&check, &no)] {
+        workflow
+            .connect_nodes(from.clone(), to.clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+
+    let context = run(workflow).await;
+    match &context.state {
+        WorkflowState::Failed { error, .. } => {
+            assert!(error.contains("Unknown name 'b'"), "{error}")
+        }
+        other => panic!("expected failure, got {other:?}"),
+    }
+    assert_eq!(context.get_node_output("yes"), None);
+    assert_eq!(context.get_node_output("no"), None);
+}
+
+#[tokio::test]
+async fn test_conditional_edges_skip_targets() {
+    let mut workflow = Workflow::new("edges", "Conditional edges");
+    let source = workflow
+        .add_node(transform("source", "{count: 3}"))
+        .unwrap();
+    let many = workflow.add_node(transform("many", "'many'")).unwrap();
+    let none = workflow.add_node(transform("none", "'none'")).unwrap();
+    let after_none = workflow
+        .add_node(transform("after_none", "'after'"))
+        .unwrap();
+    workflow
+        .connect_nodes(source.clone(), many, WorkflowEdge::conditional("count > 1"))
+        .unwrap();
+    workflow
+        .connect_nodes(
+            source,
+            none.clone(),
+            WorkflowEdge::conditional("count == 0"),
+        )
+        .unwrap();
+    workflow
+        .connect_nodes(none, after_none, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let context = run(workflow).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("many"), Some(&json!("many")));
+    assert_eq!(context.get_node_output("none"), None);
+    assert_eq!(context.get_node_output("after_none"), None);
+}
+
+#[tokio::test]
+async fn test_edge_transforms_reshape_values() {
+    let mut workflow = Workflow::new("edge_transform", "Edge transforms");
+    let source = workflow
+        .add_node(transform("source", "{user: {name: 'ada'}}"))
+        .unwrap();
+    let greet = workflow
+        .add_node(transform("greet", "'hello ' + input"))
+        .unwrap();
+    workflow
+        .connect_nodes(
+            source,
+            greet,
+            WorkflowEdge::data_flow().with_transform("upper(user.name)"),
+        )
+        .unwrap();
+
+    let context = run(workflow).await;
+    assert_eq!(context.get_node_output("greet"), Some(&json!("hello ADA")));
+}
+
+#[test]
+fn test_validate_rejects_invalid_expressions() {
+    let mut workflow = Workflow::new("invalid", "Bad expressions");
+    workflow
+        .add_node(transform("bad_transform", "upper(input"))
+        .unwrap();
+    let err = workflow.validate().unwrap_err().to_string();
+    assert!(err.contains("Transform node 'bad_transform'"), "{err}");
+    assert!(err.contains("at position 11"), "{err}");
+
+    let mut workflow = Workflow::new("invalid", "Bad condition");
+    workflow
+        .add_node(condition("check", "shell('ls')"))
+        .unwrap();
+    let err = workflow.validate().unwrap_err().to_string();
+    assert!(err.contains("Unknown function 'shell'"), "{err}");
+
+    let mut workflow = Workflow::new("invalid", "Bad edge");
+    let a = workflow.add_node(transform("a", "1")).unwrap();
+    let b = workflow.add_node(transform("b", "input")).unwrap();
+    workflow
+        .connect_nodes(a, b, WorkflowEdge::conditional("input >"))
+        .unwrap();
+    let err = workflow.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("Edge 'a' -> 'b' has an invalid condition"),
+        "{err}"
+    );
+}
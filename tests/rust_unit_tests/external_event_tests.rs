@@ -0,0 +1,302 @@
+//! External event nodes: suspension until an event is delivered, timeout edges
+//! and delivery over the execution service
+
+use graphbit_core::{
+    errors::GraphBitError,
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    server::{ExecutionService, serve},
+    stream::StreamMode,
+    types::{WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+fn external_event(event_name: &str, timeout_seconds: Option<u64>) -> WorkflowNode {
+    WorkflowNode::new(
+        "wait",
+        "",
+        NodeType::ExternalEvent {
+            event_name: event_name.to_string(),
+            timeout_seconds,
+        },
+    )
+}
+
+/// `Source` -> `wait` (for `payment`) -> `Paid`, with a timeout edge
+/// `wait` -> `Expired` when `timeout_edge` is set
+fn payment_workflow(timeout_seconds: Option<u64>, timeout_edge: bool) -> Workflow {
+    let mut workflow = Workflow::new("payment", "");
+    let source = workflow.add_node(transform("Source", "'order'")).unwrap();
+    let wait = workflow
+        .add_node(external_event("payment", timeout_seconds))
+        .unwrap();
+    let paid = workflow.add_node(transform("Paid", "input")).unwrap();
+    workflow
+        .connect_nodes(source, wait.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(wait.clone(), paid, WorkflowEdge::data_flow())
+        .unwrap();
+    if timeout_edge {
+        let expired = workflow
+            .add_node(transform("Expired", "'expired'"))
+            .unwrap();
+        workflow
+            .connect_nodes(wait, expired, WorkflowEdge::timeout())
+            .unwrap();
+    }
+    workflow
+}
+
+/// Start `workflow` in the background, returning its execution id
+fn start(
+    executor: &Arc<WorkflowExecutor>,
+    workflow: Workflow,
+) -> (
+    String,
+    tokio::task::JoinHandle<graphbit_core::GraphBitResult<WorkflowContext>>,
+) {
+    let context = WorkflowContext::new(workflow.id.clone());
+    let execution_id = context.execution_id.to_string();
+    let executor = Arc::clone(executor);
+    let run = tokio::spawn(async move {
+        executor
+            .execute_with_context(workflow, None, None, StreamMode::Updates, context)
+            .await
+    });
+    (execution_id, run)
+}
+
+/// Wait until the execution waits for `event_name`
+async fn until_waiting(executor: &WorkflowExecutor, execution_id: &str, event_name: &str) {
+    for _ in 0..200 {
+        if executor.external_events().waiting(execution_id) == [event_name] {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("execution never waited for '{event_name}'");
+}
+
+#[tokio::test]
+async fn test_node_suspends_until_the_event_is_delivered() {
+    let executor = Arc::new(WorkflowExecutor::new().without_retries());
+    let (execution_id, run) = start(&executor, payment_workflow(None, false));
+
+    until_waiting(&executor, &execution_id, "payment").await;
+    assert!(!run.is_finished());
+    let payment = json!({"amount": 42, "currency": "EUR"});
+    executor
+        .deliver_event(&execution_id, "payment", payment.clone())
+        .unwrap();
+
+    let context = run.await.unwrap().unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("wait"), Some(&payment));
+    assert_eq!(context.get_node_output("Paid"), Some(&payment));
+    // The mailbox closes with the execution
+    assert!(executor.external_events().waiting(&execution_id).is_empty());
+    let error = executor
+        .deliver_event(&execution_id, "payment", json!({}))
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::Validation { .. }));
+}
+
+#[tokio::test]
+async fn test_event_delivered_before_the_node_runs_is_kept() {
+    let executor = Arc::new(WorkflowExecutor::new().without_retries());
+    let mut workflow = Workflow::new("payment", "");
+    let delay = workflow
+        .add_node(WorkflowNode::new(
+            "Delay",
+            "",
+            NodeType::Delay {
+                duration_seconds: 1,
+            },
+        ))
+        .unwrap();
+    let wait = workflow.add_node(external_event("payment", None)).unwrap();
+    workflow
+        .connect_nodes(delay, wait, WorkflowEdge::data_flow())
+        .unwrap();
+    let (execution_id, run) = start(&executor, workflow);
+
+    // Deliverable once the execution has started, before the node waits
+    let mut delivered = false;
+    for _ in 0..50 {
+        if executor
+            .deliver_event(&execution_id, "payment", json!("early"))
+            .is_ok()
+        {
+            delivered = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(delivered);
+    let error = executor
+        .deliver_event(&execution_id, "payment", json!("again"))
+        .unwrap_err();
+    assert!(error.to_string().contains("already delivered"), "{error}");
+
+    let context = run.await.unwrap().unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("wait"), Some(&json!("early")));
+}
+
+#[tokio::test]
+async fn test_timeout_follows_the_timeout_edges() {
+    let executor = Arc::new(WorkflowExecutor::new().without_retries());
+    let (_, run) = start(&executor, payment_workflow(Some(1), true));
+
+    let context = run.await.unwrap().unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert!(context.event_timed_out("wait"));
+    assert_eq!(context.get_node_output("Expired"), Some(&json!("expired")));
+    assert!(context.get_node_output("Paid").is_none());
+
+    // In time, the timeout edges are not followed
+    let (execution_id, run) = start(&executor, payment_workflow(Some(30), true));
+    until_waiting(&executor, &execution_id, "payment").await;
+    executor
+        .deliver_event(&execution_id, "payment", json!("paid"))
+        .unwrap();
+    let context = run.await.unwrap().unwrap();
+    assert!(!context.event_timed_out("wait"));
+    assert_eq!(context.get_node_output("Paid"), Some(&json!("paid")));
+    assert!(context.get_node_output("Expired").is_none());
+}
+
+#[tokio::test]
+async fn test_timeout_without_timeout_edges_fails_the_node() {
+    let executor = Arc::new(WorkflowExecutor::new().without_retries());
+    let (_, run) = start(&executor, payment_workflow(Some(1), false));
+
+    let context = run.await.unwrap().unwrap();
+    assert!(!matches!(context.state, WorkflowState::Completed));
+    let error = context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == "wait")
+        .and_then(|record| record.error.clone())
+        .unwrap_or_default();
+    assert!(
+        error.contains("received no 'payment' event within 1s"),
+        "{error}"
+    );
+    assert!(context.get_node_output("Paid").is_none());
+}
+
+#[test]
+fn test_validation_of_external_event_nodes() {
+    let mut workflow = Workflow::new("payment", "");
+    workflow.add_node(external_event("", None)).unwrap();
+    let error = workflow.validate().unwrap_err();
+    assert!(error.to_string().contains("must name its event"), "{error}");
+
+    let error = payment_workflow(None, true).validate().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("has timeout edges but no timeout"),
+        "{error}"
+    );
+
+    let mut workflow = Workflow::new("payment", "");
+    let source = workflow.add_node(transform("Source", "1")).unwrap();
+    let target = workflow.add_node(transform("Target", "input")).unwrap();
+    workflow
+        .connect_nodes(source, target, WorkflowEdge::timeout())
+        .unwrap();
+    let error = workflow.validate().unwrap_err();
+    assert!(
+        error.to_string().contains("is not an external event node"),
+        "{error}"
+    );
+
+    assert!(payment_workflow(Some(5), true).validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_http_event_delivery() {
+    let service = ExecutionService::new(WorkflowExecutor::new().without_retries());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(serve(listener, service.clone()));
+    let client = reqwest::Client::new();
+
+    let workflow_id = service
+        .submit_workflow(payment_workflow(None, false))
+        .await
+        .unwrap();
+    let execution_id = service
+        .execute_stored(&workflow_id, HashMap::new(), StreamMode::Updates)
+        .await
+        .unwrap();
+    let mut waiting = false;
+    for _ in 0..200 {
+        let snapshot: Value = client
+            .get(format!("{url}/executions/{execution_id}"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if snapshot["waiting_for"] == json!(["payment"]) {
+            waiting = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(waiting);
+
+    let response = client
+        .post(format!("{url}/executions/{execution_id}/events"))
+        .json(&json!({"name": "payment", "payload": {"amount": 42}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+
+    let snapshot = service.wait(&execution_id).await.unwrap();
+    let report = serde_json::to_value(snapshot.report.unwrap()).unwrap();
+    assert_eq!(report["status"], json!("completed"));
+    assert!(snapshot.waiting_for.is_empty());
+
+    // The execution has finished
+    let response = client
+        .post(format!("{url}/executions/{execution_id}/events"))
+        .json(&json!({"name": "payment", "payload": {}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 409);
+    let response = client
+        .post(format!("{url}/executions/missing/events"))
+        .json(&json!({"name": "payment"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client
+        .post(format!("{url}/executions/{execution_id}/events"))
+        .body("{\"payload\": 1}")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,18 @@
+"""Ingestion pipeline."""
+
+import os
+from dataclasses import dataclass
+
+
+def load(path):
+    """Read a file."""
+    with open(path) as handle:
+        return handle.read()
+
+
+@dataclass
+class Pipeline:
+    """Load and chunk documents."""
+
+    def run(self, paths):
+        return [load(path) for path in paths]
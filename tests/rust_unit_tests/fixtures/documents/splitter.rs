@@ -0,0 +1,27 @@
+//! Chunk helpers
+
+use std::collections::HashMap;
+
+/// Count the words of a chunk
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Chunks of one document
+#[derive(Debug, Default)]
+pub struct Chunks {
+    items: Vec<String>,
+    metadata: HashMap<String, String>,
+}
+
+impl Chunks {
+    /// Add a chunk
+    pub fn push(&mut self, chunk: String) {
+        self.items.push(chunk);
+    }
+
+    /// Number of chunks
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+}
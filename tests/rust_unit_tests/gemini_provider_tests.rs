@@ -0,0 +1,336 @@
+//! `Gemini` function calling and safety settings: tools sent as
+//! `functionDeclarations`, `functionCall` parts parsed into tool calls,
+//! `functionResponse` parts sent back, `safetySettings` forwarded and blocked
+//! responses surfaced as errors, against a server replaying recorded responses
+
+use super::test_helpers::{MockRequest, MockRequests, MockResponse, MockServer};
+use futures::StreamExt;
+use graphbit_core::{
+    errors::GraphBitError,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{FinishReason, LlmConfig, LlmProviderFactory, LlmRequest, LlmTool},
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const FUNCTION_CALL: &str = include_str!("fixtures/gemini/function_call.json");
+const SAFETY_BLOCK: &str = include_str!("fixtures/gemini/safety_block.json");
+const PROMPT_BLOCKED: &str = include_str!("fixtures/gemini/prompt_blocked.json");
+
+/// Start a server answering each request with the next scripted response,
+/// recording the request bodies. A string response is sent as a server-sent
+/// event stream, anything else as JSON.
+async fn spawn_gemini_server(responses: Vec<Value>) -> (String, MockRequests) {
+    let server = MockServer::replaying(
+        responses
+            .into_iter()
+            .map(|response| match response {
+                Value::String(events) => {
+                    MockResponse::new(200, events).with_header("Content-Type", "text/event-stream")
+                }
+                message => MockResponse::json(200, message),
+            })
+            .collect(),
+    )
+    .await;
+    (format!("{}/v1beta", server.url()), server.requests())
+}
+
+fn fixture(json: &str) -> Value {
+    serde_json::from_str(json).unwrap()
+}
+
+fn gemini(base_url: String, safety_settings: HashMap<String, String>) -> LlmConfig {
+    LlmConfig::Gemini {
+        api_key: "gemini-test-key".to_string(),
+        model: "gemini-2.5-flash".to_string(),
+        base_url: Some(base_url),
+        safety_settings,
+        timeouts: Default::default(),
+    }
+}
+
+fn text_response(text: &str) -> Value {
+    json!({
+        "candidates": [{
+            "content": {"parts": [{"text": text}], "role": "model"},
+            "finishReason": "STOP",
+            "index": 0
+        }],
+        "usageMetadata": {"promptTokenCount": 80, "candidatesTokenCount": 7, "totalTokenCount": 87}
+    })
+}
+
+fn weather_tool() -> LlmTool {
+    LlmTool::new(
+        "get_weather",
+        "Current weather in a city",
+        json!({
+            "type": "object",
+            "properties": {"city": {"type": "string"}},
+            "required": ["city"],
+            "additionalProperties": false
+        }),
+    )
+}
+
+fn sse(chunks: &[Value]) -> Value {
+    Value::String(
+        chunks
+            .iter()
+            .map(|chunk| format!("data: {chunk}\r\n\r\n"))
+            .collect::<Vec<_>>()
+            .concat(),
+    )
+}
+
+#[tokio::test]
+async fn test_function_call_fixture_is_parsed() {
+    let (url, requests) = spawn_gemini_server(vec![fixture(FUNCTION_CALL)]).await;
+    let provider = LlmProviderFactory::create_provider(gemini(url, HashMap::new())).unwrap();
+
+    let response = provider
+        .complete(LlmRequest::new("What is the weather in Paris?").with_tool(weather_tool()))
+        .await
+        .unwrap();
+
+    assert!(matches!(response.finish_reason, FinishReason::ToolCalls));
+    assert_eq!(response.tool_calls.len(), 1);
+    assert_eq!(response.tool_calls[0].name, "get_weather");
+    assert_eq!(response.tool_calls[0].parameters, json!({"city": "Paris"}));
+    assert!(response.tool_calls[0].id.starts_with("call_"));
+    assert_eq!(response.usage.prompt_tokens, 58);
+
+    // Tools go out as functionDeclarations, translated to Gemini's schema dialect
+    let request = requests.lock().unwrap()[0].json();
+    let declaration = &request["tools"][0]["functionDeclarations"][0];
+    assert_eq!(declaration["name"], "get_weather");
+    assert_eq!(
+        declaration["parameters"]["properties"]["city"]["type"],
+        "string"
+    );
+    assert!(
+        declaration["parameters"]
+            .get("additionalProperties")
+            .is_none()
+    );
+    assert!(request.get("safetySettings").is_none());
+}
+
+#[tokio::test]
+async fn test_tool_result_is_sent_back_as_function_response() {
+    let (url, requests) = spawn_gemini_server(vec![
+        fixture(FUNCTION_CALL),
+        text_response("Sunny in Paris."),
+    ])
+    .await;
+    let handler: ToolHandler = Arc::new(|args: Value| {
+        Box::pin(async move {
+            Ok(json!(format!(
+                "Sunny in {}",
+                args["city"].as_str().unwrap()
+            )))
+        })
+    });
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(
+            ToolMetadata::new(
+                "get_weather",
+                "Current weather in a city",
+                weather_tool().parameters,
+            ),
+            handler,
+        )
+        .unwrap();
+
+    let mut workflow = Workflow::new("weather", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "Weather",
+                "",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(AgentId::new(), "What is the weather in Paris?"),
+                },
+            )
+            .with_tools(["get_weather"]),
+        )
+        .unwrap();
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(gemini(url, HashMap::new()))
+        .with_tool_registry(registry)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(
+        context.get_node_output("Weather"),
+        Some(&json!("Sunny in Paris."))
+    );
+
+    let requests: Vec<Value> = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(MockRequest::json)
+        .collect();
+    assert_eq!(requests.len(), 2);
+    let contents = requests[1]["contents"].as_array().unwrap();
+    let call = &contents[contents.len() - 2];
+    assert_eq!(call["role"], "model");
+    assert_eq!(call["parts"][0]["functionCall"]["name"], "get_weather");
+    assert_eq!(
+        call["parts"][0]["functionCall"]["args"],
+        json!({"city": "Paris"})
+    );
+    let result = &contents[contents.len() - 1];
+    assert_eq!(result["role"], "user");
+    assert_eq!(
+        result["parts"][0]["functionResponse"]["name"],
+        "get_weather"
+    );
+    assert!(
+        result["parts"][0]["functionResponse"]["response"]["result"]
+            .as_str()
+            .unwrap()
+            .contains("Sunny in Paris")
+    );
+}
+
+#[tokio::test]
+async fn test_safety_settings_are_forwarded() {
+    let (url, requests) =
+        spawn_gemini_server(vec![text_response("Take 400mg every 6 hours.")]).await;
+    let safety_settings = HashMap::from([
+        (
+            "HARM_CATEGORY_HARASSMENT".to_string(),
+            "BLOCK_MEDIUM_AND_ABOVE".to_string(),
+        ),
+        (
+            "HARM_CATEGORY_DANGEROUS_CONTENT".to_string(),
+            "BLOCK_ONLY_HIGH".to_string(),
+        ),
+    ]);
+    let provider = LlmProviderFactory::create_provider(gemini(url, safety_settings)).unwrap();
+
+    provider
+        .complete(LlmRequest::new("What is the maximum ibuprofen dose?"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        requests.lock().unwrap()[0].json()["safetySettings"],
+        json!([
+            {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_ONLY_HIGH"},
+            {"category": "HARM_CATEGORY_HARASSMENT", "threshold": "BLOCK_MEDIUM_AND_ABOVE"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_safety_block_fixture_is_content_blocked_error() {
+    let (url, _) = spawn_gemini_server(vec![fixture(SAFETY_BLOCK)]).await;
+    let provider = LlmProviderFactory::create_provider(gemini(url, HashMap::new())).unwrap();
+
+    let error = provider
+        .complete(LlmRequest::new("How do I synthesize it?"))
+        .await
+        .unwrap_err();
+
+    match &error {
+        GraphBitError::ContentBlocked {
+            provider,
+            reason,
+            categories,
+        } => {
+            assert_eq!(provider, "gemini");
+            assert!(reason.contains("SAFETY"), "{reason}");
+            assert_eq!(categories, &["HARM_CATEGORY_DANGEROUS_CONTENT"]);
+        }
+        other => panic!("expected ContentBlocked, got {other:?}"),
+    }
+    assert!(!error.is_retryable());
+}
+
+#[tokio::test]
+async fn test_blocked_prompt_is_content_blocked_error() {
+    let (url, _) = spawn_gemini_server(vec![fixture(PROMPT_BLOCKED)]).await;
+    let provider = LlmProviderFactory::create_provider(gemini(url, HashMap::new())).unwrap();
+
+    let error = provider
+        .complete(LlmRequest::new("Insult my coworker"))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            GraphBitError::ContentBlocked { reason, categories, .. }
+                if reason.contains("prompt") && categories == &["HARM_CATEGORY_HARASSMENT"]
+        ),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_yields_function_calls_and_ends_on_safety_block() {
+    let (url, _) = spawn_gemini_server(vec![
+        sse(&[text_response("Let me check."), fixture(FUNCTION_CALL)]),
+        sse(&[text_response("Step one is"), fixture(SAFETY_BLOCK)]),
+    ])
+    .await;
+    let provider = LlmProviderFactory::create_provider(gemini(url, HashMap::new())).unwrap();
+
+    let chunks: Vec<_> = provider
+        .stream(LlmRequest::new("What is the weather in Paris?").with_tool(weather_tool()))
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].content, "Let me check.");
+    assert!(matches!(chunks[1].finish_reason, FinishReason::ToolCalls));
+    assert_eq!(chunks[1].tool_calls[0].name, "get_weather");
+    assert_eq!(chunks[1].tool_calls[0].parameters, json!({"city": "Paris"}));
+
+    let results: Vec<_> = provider
+        .stream(LlmRequest::new("How do I synthesize it?"))
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().content, "Step one is");
+    assert!(matches!(
+        results[1],
+        Err(GraphBitError::ContentBlocked { .. })
+    ));
+}
+
+#[test]
+fn test_safety_settings_default_when_missing() {
+    let config: LlmConfig = serde_json::from_value(json!({
+        "provider": "Gemini",
+        "api_key": "gemini-test-key",
+        "model": "gemini-2.5-flash",
+        "base_url": null
+    }))
+    .unwrap();
+
+    assert!(matches!(
+        config,
+        LlmConfig::Gemini { safety_settings, .. } if safety_settings.is_empty()
+    ));
+}
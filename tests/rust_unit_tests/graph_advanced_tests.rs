@@ -327,6 +327,7 @@ fn test_node_type_variants() {
     };
     let condition_node = NodeType::Condition {
         handler_id: "x_gt_0".to_string(),
+        expression: None,
     };
     let transform_node = NodeType::Transform {
         transformation: "uppercase".to_string(),
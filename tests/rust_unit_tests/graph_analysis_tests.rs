@@ -0,0 +1,241 @@
+//! Graph analysis: depth, critical path, parallel width, cost estimates and
+//! Mermaid and DOT exports
+
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmUsage},
+    types::{AgentId, NodeId},
+    workflow::Workflow,
+};
+use std::collections::HashMap;
+
+fn transform(name: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: "input".to_string(),
+        },
+    )
+}
+
+fn agent(name: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), format!("Run {name}")),
+        },
+    )
+}
+
+/// Workflow with the given nodes and `(from, to)` edges between node names
+fn workflow(nodes: Vec<WorkflowNode>, edges: &[(&str, &str)]) -> Workflow {
+    let mut workflow = Workflow::new("analysis", "");
+    for node in nodes {
+        workflow.add_node(node).unwrap();
+    }
+    for (from, to) in edges {
+        let from = workflow.graph.get_node_id_by_name(from).unwrap();
+        let to = workflow.graph.get_node_id_by_name(to).unwrap();
+        workflow
+            .connect_nodes(from, to, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+fn names(workflow: &Workflow, path: &[NodeId]) -> Vec<String> {
+    path.iter()
+        .map(|id| workflow.graph.get_node(id).unwrap().name.clone())
+        .collect()
+}
+
+#[test]
+fn test_chain() {
+    let chain = workflow(
+        ["a", "b", "c", "d"].map(transform).into(),
+        &[("a", "b"), ("b", "c"), ("c", "d")],
+    );
+    let graph = &chain.graph;
+    assert_eq!(graph.depth().unwrap(), 4);
+    assert_eq!(graph.max_parallel_width().unwrap(), 1);
+    assert_eq!(
+        names(&chain, &graph.critical_path().unwrap()),
+        ["a", "b", "c", "d"]
+    );
+}
+
+#[test]
+fn test_diamond() {
+    let diamond = workflow(
+        ["a", "b", "c", "d"].map(transform).into(),
+        &[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")],
+    );
+    let graph = &diamond.graph;
+    assert_eq!(graph.depth().unwrap(), 3);
+    assert_eq!(graph.max_parallel_width().unwrap(), 2);
+    // Equally long branches: the one through the smallest name wins
+    assert_eq!(
+        names(&diamond, &graph.critical_path().unwrap()),
+        ["a", "b", "d"]
+    );
+
+    // Lengthening one branch moves the critical path onto it
+    let uneven = workflow(
+        ["a", "b", "c", "c2", "d"].map(transform).into(),
+        &[("a", "b"), ("a", "c"), ("c", "c2"), ("b", "d"), ("c2", "d")],
+    );
+    let graph = &uneven.graph;
+    assert_eq!(graph.depth().unwrap(), 4);
+    assert_eq!(graph.max_parallel_width().unwrap(), 2);
+    assert_eq!(
+        names(&uneven, &graph.critical_path().unwrap()),
+        ["a", "c", "c2", "d"]
+    );
+}
+
+#[test]
+fn test_wide_and_disconnected_graphs() {
+    // Fan-out to three workers, plus an unrelated two-node chain
+    let wide = workflow(
+        ["fetch", "w1", "w2", "w3", "merge", "x", "y"]
+            .map(transform)
+            .into(),
+        &[
+            ("fetch", "w1"),
+            ("fetch", "w2"),
+            ("fetch", "w3"),
+            ("w1", "merge"),
+            ("w2", "merge"),
+            ("w3", "merge"),
+            ("x", "y"),
+        ],
+    );
+    let graph = &wide.graph;
+    assert_eq!(graph.depth().unwrap(), 3);
+    // w1, w2, w3 and y all sit on the second level
+    assert_eq!(graph.max_parallel_width().unwrap(), 4);
+
+    let empty = Workflow::new("empty", "");
+    assert_eq!(empty.graph.depth().unwrap(), 0);
+    assert_eq!(empty.graph.max_parallel_width().unwrap(), 0);
+    assert!(empty.graph.critical_path().unwrap().is_empty());
+}
+
+#[test]
+fn test_cycles_are_rejected() {
+    let cyclic = workflow(["a", "b"].map(transform).into(), &[("a", "b"), ("b", "a")]);
+    assert!(cyclic.graph.depth().is_err());
+    assert!(cyclic.graph.critical_path().is_err());
+    assert!(
+        cyclic
+            .estimate_cost(&HashMap::new(), &LlmUsage::new(1, 1))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_estimate_cost() {
+    let pipeline = workflow(
+        vec![
+            transform("load"),
+            agent("summarize").with_model("gpt-4o-mini"),
+            agent("review").with_llm_config(&LlmConfig::anthropic("key", "claude-sonnet")),
+            agent("title"),
+            agent("translate").with_model("unknown-model"),
+        ],
+        &[
+            ("load", "summarize"),
+            ("summarize", "review"),
+            ("review", "title"),
+            ("title", "translate"),
+        ],
+    );
+    let prices = HashMap::from([
+        ("gpt-4o-mini".to_string(), (0.000_001, 0.000_002)),
+        ("claude-sonnet".to_string(), (0.000_003, 0.000_015)),
+    ]);
+
+    let estimate = pipeline
+        .estimate_cost(&prices, &LlmUsage::new(1000, 500))
+        .unwrap();
+
+    let nodes: Vec<(&str, Option<&str>)> = estimate
+        .nodes
+        .iter()
+        .map(|node| (node.node_name.as_str(), node.model.as_deref()))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("summarize", Some("gpt-4o-mini")),
+            ("review", Some("claude-sonnet")),
+            ("title", None),
+            ("translate", Some("unknown-model")),
+        ]
+    );
+    assert!((estimate.nodes[0].cost.unwrap() - 0.002).abs() < 1e-12);
+    assert!((estimate.nodes[1].cost.unwrap() - 0.0105).abs() < 1e-12);
+    assert!((estimate.total_cost - 0.0125).abs() < 1e-12);
+    assert_eq!(estimate.unpriced_nodes, ["title", "translate"]);
+    assert_eq!(estimate.token_usage.prompt_tokens, 4000);
+    assert_eq!(estimate.token_usage.completion_tokens, 2000);
+}
+
+#[test]
+fn test_export_mermaid_and_dot() {
+    let mut routed = workflow(
+        vec![
+            transform("load \"raw\""),
+            WorkflowNode::new(
+                "route",
+                "",
+                NodeType::Condition {
+                    handler_id: String::new(),
+                    expression: Some("input.kind".to_string()),
+                },
+            ),
+            agent("answer"),
+            transform("audit"),
+        ],
+        &[("load \"raw\"", "route")],
+    );
+    let id = |name| routed.graph.get_node_id_by_name(name).unwrap();
+    let (route, answer, audit) = (id("route"), id("answer"), id("audit"));
+    routed
+        .connect_nodes(
+            route,
+            answer.clone(),
+            WorkflowEdge::conditional("input.kind == \"question\""),
+        )
+        .unwrap();
+    routed
+        .connect_nodes(answer, audit, WorkflowEdge::control_flow())
+        .unwrap();
+
+    assert_eq!(
+        routed.graph.to_mermaid(),
+        "flowchart TD\n\
+         \x20   n0[\"load #quot;raw#quot;\"]\n\
+         \x20   n1{\"route\"}\n\
+         \x20   n2(\"answer\")\n\
+         \x20   n3[\"audit\"]\n\
+         \x20   n0 --> n1\n\
+         \x20   n1 -->|\"input.kind == #quot;question#quot;\"| n2\n\
+         \x20   n2 -.-> n3\n"
+    );
+    assert_eq!(
+        routed.graph.to_dot(),
+        "digraph workflow {\n\
+         \x20   rankdir=TB;\n\
+         \x20   n0 [label=\"load \\\"raw\\\"\", shape=box];\n\
+         \x20   n1 [label=\"route\", shape=diamond];\n\
+         \x20   n2 [label=\"answer\", shape=box, style=rounded];\n\
+         \x20   n3 [label=\"audit\", shape=box];\n\
+         \x20   n0 -> n1;\n\
+         \x20   n1 -> n2 [label=\"input.kind == \\\"question\\\"\"];\n\
+         \x20   n2 -> n3 [style=dashed];\n\
+         }\n"
+    );
+}
@@ -0,0 +1,309 @@
+//! Graph mutations: replacing nodes, splitting and updating edges, merging graphs
+
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowGraph, WorkflowNode},
+    types::{AgentId, NodeId},
+};
+use std::collections::BTreeSet;
+
+fn transform(name: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: "input".to_string(),
+        },
+    )
+}
+
+fn agent(name: &str, agent_id: AgentId) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, format!("Run {name}")),
+        },
+    )
+}
+
+/// Graph with the given nodes and `(from, to)` data flow edges between node names
+fn graph(nodes: Vec<WorkflowNode>, edges: &[(&str, &str)]) -> WorkflowGraph {
+    let mut graph = WorkflowGraph::new();
+    for node in nodes {
+        graph.add_node(node).unwrap();
+    }
+    for (from, to) in edges {
+        graph
+            .add_edge(id(&graph, from), id(&graph, to), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    graph
+}
+
+fn id(graph: &WorkflowGraph, name: &str) -> NodeId {
+    graph.get_node_id_by_name(name).unwrap()
+}
+
+fn names(graph: &WorkflowGraph, ids: &[NodeId]) -> BTreeSet<String> {
+    ids.iter()
+        .map(|id| graph.get_node(id).unwrap().name.clone())
+        .collect()
+}
+
+/// Edges as `(from, to)` node names, in serialization order
+fn edge_names(graph: &WorkflowGraph) -> Vec<(String, String)> {
+    graph
+        .get_edges()
+        .iter()
+        .map(|(from, to, _)| {
+            (
+                graph.get_node(from).unwrap().name.clone(),
+                graph.get_node(to).unwrap().name.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Fill the root, leaf, dependency and dependent caches
+fn warm_caches(graph: &mut WorkflowGraph) {
+    graph.get_root_nodes();
+    graph.get_leaf_nodes();
+    for node_id in graph.get_nodes().keys().cloned().collect::<Vec<_>>() {
+        graph.get_dependencies(&node_id);
+        graph.get_dependents(&node_id);
+    }
+}
+
+/// Names of the nodes `view` returns, e.g. through one of the graph's caches
+fn view_names(
+    graph: &mut WorkflowGraph,
+    view: impl FnOnce(&mut WorkflowGraph) -> Vec<NodeId>,
+) -> BTreeSet<String> {
+    let ids = view(graph);
+    names(graph, &ids)
+}
+
+/// Compare the cached views of a mutated graph with those of a graph freshly
+/// rebuilt from its serialized form, which also checks the serialization
+fn assert_caches_consistent(graph: &mut WorkflowGraph) {
+    let mut fresh: WorkflowGraph =
+        serde_json::from_str(&serde_json::to_string(graph).unwrap()).unwrap();
+    fresh.rebuild_graph().unwrap();
+    assert_eq!(edge_names(graph), edge_names(&fresh));
+
+    assert_eq!(
+        view_names(graph, WorkflowGraph::get_root_nodes),
+        view_names(&mut fresh, WorkflowGraph::get_root_nodes)
+    );
+    assert_eq!(
+        view_names(graph, WorkflowGraph::get_leaf_nodes),
+        view_names(&mut fresh, WorkflowGraph::get_leaf_nodes)
+    );
+    for node_id in graph.get_nodes().keys().cloned().collect::<Vec<_>>() {
+        assert_eq!(
+            view_names(graph, |g| g.get_dependencies(&node_id)),
+            view_names(&mut fresh, |g| g.get_dependencies(&node_id))
+        );
+        assert_eq!(
+            view_names(graph, |g| g.get_dependents(&node_id)),
+            view_names(&mut fresh, |g| g.get_dependents(&node_id))
+        );
+    }
+    assert_eq!(
+        graph.topological_sort().unwrap().len(),
+        graph.node_count(),
+        "the petgraph structure is out of sync"
+    );
+}
+
+#[test]
+fn test_replace_node_keeps_incident_edges() {
+    let mut g = graph(
+        ["a", "b", "c"].map(transform).into(),
+        &[("a", "b"), ("b", "c")],
+    );
+    warm_caches(&mut g);
+    let old_b = id(&g, "b");
+
+    let replacement = transform("b2");
+    let new_b = replacement.id.clone();
+    g.replace_node(&old_b, replacement).unwrap();
+
+    assert!(g.get_node(&old_b).is_none());
+    assert_eq!(g.get_node_id_by_name("b"), None);
+    assert_eq!(id(&g, "b2"), new_b);
+    assert_eq!(
+        edge_names(&g),
+        [("a".into(), "b2".into()), ("b2".into(), "c".into())]
+    );
+    assert_eq!(
+        view_names(&mut g, |g| g.get_dependencies(&new_b)),
+        BTreeSet::from(["a".into()])
+    );
+    assert_caches_consistent(&mut g);
+}
+
+#[test]
+fn test_replace_node_in_place_and_errors() {
+    let mut g = graph(["a", "b"].map(transform).into(), &[("a", "b")]);
+    warm_caches(&mut g);
+    let b = id(&g, "b");
+
+    let mut updated = transform("renamed");
+    updated.id = b.clone();
+    g.replace_node(&b, updated).unwrap();
+    assert_eq!(g.get_node(&b).unwrap().name, "renamed");
+    assert_eq!(id(&g, "renamed"), b);
+    assert_caches_consistent(&mut g);
+
+    let a = id(&g, "a");
+    let mut duplicate = transform("dup");
+    duplicate.id = a.clone();
+    assert!(g.replace_node(&b, duplicate).is_err());
+    assert!(g.replace_node(&NodeId::new(), transform("x")).is_err());
+    assert_eq!(g.node_count(), 2);
+}
+
+#[test]
+fn test_insert_between_splits_edge() {
+    let mut g = graph(
+        ["a", "b", "c"].map(transform).into(),
+        &[("a", "c"), ("b", "c")],
+    );
+    let (a, c) = (id(&g, "a"), id(&g, "c"));
+    g.update_edge(&a, &c, WorkflowEdge::conditional("score > 1"))
+        .unwrap();
+    warm_caches(&mut g);
+
+    let middle = transform("m");
+    let m = middle.id.clone();
+    g.insert_between(&a, &c, middle).unwrap();
+
+    // The split edge keeps its place and its condition on the first half
+    assert_eq!(
+        edge_names(&g),
+        [
+            ("a".into(), "m".into()),
+            ("m".into(), "c".into()),
+            ("b".into(), "c".into())
+        ]
+    );
+    assert_eq!(
+        g.get_edge(&a, &m).unwrap().condition.as_deref(),
+        Some("score > 1")
+    );
+    assert!(g.get_edge(&m, &c).unwrap().condition.is_none());
+    assert!(g.get_edge(&a, &c).is_none());
+    assert_eq!(g.depth().unwrap(), 3);
+    assert_caches_consistent(&mut g);
+}
+
+#[test]
+fn test_insert_between_errors_leave_graph_unchanged() {
+    let mut g = graph(["a", "b"].map(transform).into(), &[("a", "b")]);
+    let (a, b) = (id(&g, "a"), id(&g, "b"));
+
+    assert!(g.insert_between(&b, &a, transform("m")).is_err());
+    let mut existing = transform("m");
+    existing.id = a.clone();
+    assert!(g.insert_between(&a, &b, existing).is_err());
+
+    assert_eq!(g.node_count(), 2);
+    assert_eq!(edge_names(&g), [("a".into(), "b".into())]);
+}
+
+#[test]
+fn test_update_edge() {
+    let mut g = graph(["a", "b"].map(transform).into(), &[("a", "b")]);
+    warm_caches(&mut g);
+    let (a, b) = (id(&g, "a"), id(&g, "b"));
+
+    g.update_edge(
+        &a,
+        &b,
+        WorkflowEdge::data_flow().with_transform("upper(input)"),
+    )
+    .unwrap();
+    assert_eq!(
+        g.get_edge(&a, &b).unwrap().transform.as_deref(),
+        Some("upper(input)")
+    );
+    assert_eq!(g.edge_count(), 1);
+    assert_caches_consistent(&mut g);
+
+    assert!(g.update_edge(&b, &a, WorkflowEdge::data_flow()).is_err());
+}
+
+#[test]
+fn test_merge_prefixes_names_and_remaps_colliding_ids() {
+    let shared_agent = AgentId::new();
+    let mut g = graph(
+        vec![transform("start"), agent("writer", shared_agent.clone())],
+        &[("start", "writer")],
+    );
+    warm_caches(&mut g);
+
+    // Merging a copy of the graph into itself collides on every id
+    let other = g.clone();
+    let ids = g.merge(&other, "sub.").unwrap();
+
+    assert_eq!(g.node_count(), 4);
+    assert_eq!(ids.len(), 2);
+    for (old, new) in &ids {
+        assert_ne!(old, new);
+        assert_eq!(
+            g.get_node(new).unwrap().name,
+            format!("sub.{}", other.get_node(old).unwrap().name)
+        );
+    }
+    assert_eq!(
+        edge_names(&g),
+        [
+            ("start".into(), "writer".into()),
+            ("sub.start".into(), "sub.writer".into())
+        ]
+    );
+    let NodeType::Agent { config } = &g.get_node(&id(&g, "sub.writer")).unwrap().node_type else {
+        panic!("expected an agent node");
+    };
+    assert_ne!(config.agent_id, shared_agent);
+    g.validate().unwrap();
+    assert_caches_consistent(&mut g);
+}
+
+#[test]
+fn test_merge_keeps_free_ids_and_renames_handoff_targets() {
+    let mut g = graph(vec![transform("start")], &[]);
+    let other = graph(
+        vec![
+            agent("triage", AgentId::new()).with_handoff_targets(["billing"]),
+            agent("billing", AgentId::new()),
+        ],
+        &[],
+    );
+    let triage = id(&other, "triage");
+
+    let ids = g.merge(&other, "support_").unwrap();
+
+    assert_eq!(ids[&triage], triage);
+    assert_eq!(
+        g.get_node(&triage).unwrap().handoff_targets(),
+        ["support_billing"]
+    );
+    g.validate().unwrap();
+}
+
+#[test]
+fn test_merge_name_collision_leaves_graph_unchanged() {
+    let mut g = graph(["a", "sub_b"].map(transform).into(), &[("a", "sub_b")]);
+    let other = graph(["b", "c"].map(transform).into(), &[("b", "c")]);
+
+    let error = g.merge(&other, "sub_").unwrap_err();
+    assert!(error.to_string().contains("sub_b"), "{error}");
+    assert_eq!(g.node_count(), 2);
+    assert_eq!(g.edge_count(), 1);
+
+    g.merge(&other, "other_").unwrap();
+    assert_eq!(g.node_count(), 4);
+    assert_caches_consistent(&mut g);
+}
@@ -0,0 +1,318 @@
+//! Graph queries: finding nodes by type, tag and predicate, transitive
+//! ancestors and descendants, and subgraph extraction
+
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, NodeTypeKind, WorkflowEdge, WorkflowGraph, WorkflowNode},
+    types::{AgentId, NodeId},
+};
+use std::collections::HashSet;
+
+fn node(name: &str, node_type: NodeType, tags: &[&str]) -> WorkflowNode {
+    WorkflowNode::new(name, "", node_type).with_tags(tags.iter().map(|t| t.to_string()).collect())
+}
+
+fn transform(name: &str, tags: &[&str]) -> WorkflowNode {
+    node(
+        name,
+        NodeType::Transform {
+            transformation: "input".to_string(),
+        },
+        tags,
+    )
+}
+
+fn agent(name: &str, prompt: &str, tags: &[&str]) -> WorkflowNode {
+    node(
+        name,
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), prompt),
+        },
+        tags,
+    )
+}
+
+/// 15 nodes: two sources feeding a pipeline that fans out after `validate`,
+/// joins again at `review` and ends in `notify` and `archive`, plus the
+/// unconnected `audit`
+fn fixture() -> WorkflowGraph {
+    let nodes = vec![
+        transform("loader", &["io"]),
+        transform("fetch_prices", &["io"]),
+        transform("clean", &[]),
+        node(
+            "validate",
+            NodeType::Condition {
+                handler_id: String::new(),
+                expression: Some("'summarize'".to_string()),
+            },
+            &["cheap"],
+        ),
+        agent("summarize", "Summarize {{input}}", &["expensive"]),
+        agent("classify", "Classify {{input}}", &["expensive", "core"]),
+        agent("extract", "Extract entities from {{input}}", &["core"]),
+        transform("merge", &[]),
+        transform("score", &["core"]),
+        agent("review", "Review {{input}}", &["expensive"]),
+        node(
+            "wait",
+            NodeType::Delay {
+                duration_seconds: 30,
+            },
+            &[],
+        ),
+        transform("notify", &["io"]),
+        agent("report", "Write a report on {{input}}", &[]),
+        transform("archive", &["io"]),
+        node(
+            "audit",
+            NodeType::Delay {
+                duration_seconds: 5,
+            },
+            &["cheap"],
+        ),
+    ];
+    let edges = [
+        ("loader", "clean"),
+        ("fetch_prices", "clean"),
+        ("clean", "validate"),
+        ("validate", "summarize"),
+        ("validate", "classify"),
+        ("clean", "extract"),
+        ("summarize", "merge"),
+        ("classify", "merge"),
+        ("extract", "score"),
+        ("merge", "review"),
+        ("score", "review"),
+        ("review", "wait"),
+        ("wait", "notify"),
+        ("review", "report"),
+        ("report", "archive"),
+    ];
+
+    let mut graph = WorkflowGraph::new();
+    for node in nodes {
+        graph.add_node(node).unwrap();
+    }
+    for (from, to) in edges {
+        let from = id(&graph, from);
+        let to = id(&graph, to);
+        graph.add_edge(from, to, WorkflowEdge::data_flow()).unwrap();
+    }
+    graph
+}
+
+fn id(graph: &WorkflowGraph, name: &str) -> NodeId {
+    graph.get_node_id_by_name(name).unwrap()
+}
+
+fn names<'a>(graph: &WorkflowGraph, ids: impl IntoIterator<Item = &'a NodeId>) -> Vec<String> {
+    ids.into_iter()
+        .map(|id| graph.get_node(id).unwrap().name.clone())
+        .collect()
+}
+
+fn sorted_names(graph: &WorkflowGraph, ids: &HashSet<NodeId>) -> Vec<String> {
+    let mut names = names(graph, ids);
+    names.sort();
+    names
+}
+
+#[test]
+fn test_fixture_is_valid() {
+    let graph = fixture();
+    assert_eq!(graph.node_count(), 15);
+    assert_eq!(graph.edge_count(), 15);
+    graph.validate().unwrap();
+}
+
+#[test]
+fn test_find_nodes_by_type() {
+    let graph = fixture();
+    let agents = graph.find_nodes_by_type(NodeTypeKind::Agent);
+    assert_eq!(
+        names(&graph, &agents),
+        ["classify", "extract", "report", "review", "summarize"]
+    );
+    let delays = graph.find_nodes_by_type(NodeTypeKind::Delay);
+    assert_eq!(names(&graph, &delays), ["audit", "wait"]);
+    assert!(graph.find_nodes_by_type(NodeTypeKind::Join).is_empty());
+}
+
+#[test]
+fn test_find_nodes_by_tag() {
+    let graph = fixture();
+    let expensive = graph.find_nodes_by_tag("expensive");
+    assert_eq!(
+        names(&graph, &expensive),
+        ["classify", "review", "summarize"]
+    );
+    let io = graph.find_nodes_by_tag("io");
+    assert_eq!(
+        names(&graph, &io),
+        ["archive", "fetch_prices", "loader", "notify"]
+    );
+    assert!(graph.find_nodes_by_tag("missing").is_empty());
+}
+
+#[test]
+fn test_find_nodes_by_config_predicate() {
+    let graph = fixture();
+    let long_delays = graph.find_nodes(|node| {
+        matches!(node.node_type, NodeType::Delay { duration_seconds } if duration_seconds >= 10)
+    });
+    assert_eq!(names(&graph, &long_delays), ["wait"]);
+
+    let entity_agents = graph.find_nodes(|node| match &node.node_type {
+        NodeType::Agent { config } => config.prompt_template.contains("entities"),
+        _ => false,
+    });
+    assert_eq!(names(&graph, &entity_agents), ["extract"]);
+
+    let core_agents = graph.find_nodes(|node| {
+        node.node_type.type_kind() == NodeTypeKind::Agent && node.tags.iter().any(|t| t == "core")
+    });
+    assert_eq!(names(&graph, &core_agents), ["classify", "extract"]);
+    assert_eq!(graph.find_nodes(|_| true).len(), 15);
+}
+
+#[test]
+fn test_node_type_kind_names() {
+    for kind in NodeTypeKind::ALL {
+        assert_eq!(kind.as_str().parse::<NodeTypeKind>().unwrap(), kind);
+    }
+    assert_eq!(
+        "http_request".parse::<NodeTypeKind>().unwrap(),
+        NodeTypeKind::HttpRequest
+    );
+    let error = "robot".parse::<NodeTypeKind>().unwrap_err();
+    assert!(error.to_string().contains("Unknown node type 'robot'"));
+}
+
+#[test]
+fn test_descendants() {
+    let mut graph = fixture();
+    let loader = id(&graph, "loader");
+    let descendants = graph.descendants(&loader);
+    assert_eq!(
+        sorted_names(&graph, &descendants),
+        [
+            "archive",
+            "classify",
+            "clean",
+            "extract",
+            "merge",
+            "notify",
+            "report",
+            "review",
+            "score",
+            "summarize",
+            "validate",
+            "wait",
+        ]
+    );
+
+    let extract = id(&graph, "extract");
+    let descendants = graph.descendants(&extract);
+    assert_eq!(
+        sorted_names(&graph, &descendants),
+        ["archive", "notify", "report", "review", "score", "wait"]
+    );
+
+    for leaf in ["archive", "notify", "audit"] {
+        let leaf = id(&graph, leaf);
+        assert!(graph.descendants(&leaf).is_empty());
+    }
+}
+
+#[test]
+fn test_ancestors() {
+    let mut graph = fixture();
+    let review = id(&graph, "review");
+    let ancestors = graph.ancestors(&review);
+    assert_eq!(
+        sorted_names(&graph, &ancestors),
+        [
+            "classify",
+            "clean",
+            "extract",
+            "fetch_prices",
+            "loader",
+            "merge",
+            "score",
+            "summarize",
+            "validate",
+        ]
+    );
+
+    let notify = id(&graph, "notify");
+    assert_eq!(graph.ancestors(&notify).len(), 11);
+
+    for root in ["loader", "fetch_prices", "audit"] {
+        let root = id(&graph, root);
+        assert!(graph.ancestors(&root).is_empty());
+    }
+}
+
+#[test]
+fn test_transitive_caches_follow_graph_changes() {
+    let mut graph = fixture();
+    let archive = id(&graph, "archive");
+    let audit = id(&graph, "audit");
+    assert!(graph.descendants(&archive).is_empty());
+    assert!(graph.ancestors(&audit).is_empty());
+
+    graph
+        .add_edge(archive.clone(), audit.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    let descendants = graph.descendants(&archive);
+    assert_eq!(sorted_names(&graph, &descendants), ["audit"]);
+    let ancestors = graph.ancestors(&audit);
+    assert_eq!(ancestors.len(), 12);
+    assert!(ancestors.contains(&archive));
+}
+
+#[test]
+fn test_subgraph() {
+    let graph = fixture();
+    let ids: Vec<NodeId> = ["summarize", "classify", "merge", "review"]
+        .iter()
+        .map(|name| id(&graph, name))
+        .collect();
+    let mut subgraph = graph.subgraph(&ids).unwrap();
+
+    assert_eq!(subgraph.node_count(), 4);
+    assert_eq!(subgraph.edge_count(), 3);
+    subgraph.validate().unwrap();
+    assert_eq!(id(&subgraph, "merge"), id(&graph, "merge"));
+
+    let roots = subgraph.get_root_nodes();
+    let mut roots = names(&subgraph, &roots);
+    roots.sort();
+    assert_eq!(roots, ["classify", "summarize"]);
+    let review = id(&subgraph, "review");
+    assert_eq!(subgraph.ancestors(&review).len(), 3);
+    assert!(subgraph.get_node_id_by_name("score").is_none());
+}
+
+#[test]
+fn test_subgraph_round_trips_through_json() {
+    let graph = fixture();
+    let ids = graph.find_nodes_by_tag("core");
+    let subgraph = graph.subgraph(&ids).unwrap();
+    assert_eq!(subgraph.node_count(), 3);
+    assert_eq!(subgraph.edge_count(), 1);
+
+    let json = serde_json::to_string(&subgraph).unwrap();
+    let mut restored: WorkflowGraph = serde_json::from_str(&json).unwrap();
+    restored.rebuild_graph().unwrap();
+    assert_eq!(restored.node_count(), 3);
+    assert_eq!(restored.edge_count(), 1);
+    restored.validate().unwrap();
+}
+
+#[test]
+fn test_subgraph_of_unknown_node_fails() {
+    let graph = fixture();
+    let error = graph.subgraph(&[NodeId::new()]).unwrap_err();
+    assert!(error.to_string().contains("not found"));
+}
@@ -0,0 +1,370 @@
+//! Guardrail nodes: pattern and classifier checks with the redact, block and
+//! route actions
+
+use super::test_helpers::{MockRequests, MockResponse, MockServer};
+use async_trait::async_trait;
+use graphbit_core::{
+    AuditLog, AuditPolicy, AuditSink,
+    audit::{AuditCallKind, AuditRecord},
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    guardrail_node::{GuardrailAction, GuardrailCheck, GuardrailReport, GuardrailViolation},
+    llm::LlmConfig,
+    types::{Secrets, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const PII_TEXT: &str = "Mail jane@example.com or call 555-123-4567, card 4111 1111 1111 1111";
+
+/// Sink keeping records in memory
+#[derive(Clone, Default)]
+struct MemorySink {
+    records: Arc<Mutex<Vec<AuditRecord>>>,
+}
+
+#[async_trait]
+impl AuditSink for MemorySink {
+    async fn write(&self, record: &AuditRecord) -> graphbit_core::GraphBitResult<()> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+fn guardrail(checks: Vec<GuardrailCheck>, on_violation: GuardrailAction) -> WorkflowNode {
+    WorkflowNode::new(
+        "guard",
+        "",
+        NodeType::Guardrail {
+            checks,
+            on_violation,
+        },
+    )
+}
+
+/// `Source` (the `text` input) -> `guard` -> `Publish`
+fn workflow(guard: WorkflowNode) -> Workflow {
+    let mut workflow = Workflow::new("guardrail", "");
+    let source = workflow.add_node(transform("Source", "vars.text")).unwrap();
+    let guard = workflow.add_node(guard).unwrap();
+    let publish = workflow.add_node(transform("Publish", "input")).unwrap();
+    workflow
+        .connect_nodes(source, guard.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(guard, publish, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+async fn run_with(executor: WorkflowExecutor, workflow: Workflow, text: &str) -> WorkflowContext {
+    let inputs = HashMap::from([("text".to_string(), json!(text))]);
+    executor
+        .without_retries()
+        .execute_with_inputs(workflow, None, inputs, Secrets::default())
+        .await
+        .unwrap()
+}
+
+async fn run(workflow: Workflow, text: &str) -> WorkflowContext {
+    run_with(WorkflowExecutor::new(), workflow, text).await
+}
+
+fn violation(check: &str, matches: usize) -> GuardrailViolation {
+    GuardrailViolation {
+        check: check.to_string(),
+        matches,
+        reason: None,
+    }
+}
+
+#[tokio::test]
+async fn test_redact_masks_pii_and_continues() {
+    let context = run(
+        workflow(guardrail(
+            vec![GuardrailCheck::Pii],
+            GuardrailAction::Redact,
+        )),
+        PII_TEXT,
+    )
+    .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let masked =
+        json!("Mail [REDACTED_EMAIL] or call [REDACTED_PHONE], card [REDACTED_CREDIT_CARD]");
+    assert_eq!(context.get_node_output("guard"), Some(&masked));
+    assert_eq!(context.get_node_output("Publish"), Some(&masked));
+    assert_eq!(
+        context.get_guardrail_report("guard"),
+        Some(&GuardrailReport {
+            action: GuardrailAction::Redact,
+            violations: vec![
+                violation("email", 1),
+                violation("credit_card", 1),
+                violation("phone", 1)
+            ],
+            redactions: 3,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_custom_regex_and_deny_list_checks() {
+    let checks = vec![
+        GuardrailCheck::Regex(r"ORD-\d+".to_string()),
+        GuardrailCheck::DenyList(vec!["secret project".to_string()]),
+    ];
+    let context = run(
+        workflow(guardrail(checks, GuardrailAction::Redact)),
+        "ORD-12 and ORD-34 mention the Secret Project",
+    )
+    .await;
+
+    assert_eq!(
+        context.get_node_output("Publish"),
+        Some(&json!(
+            "[REDACTED_REGEX] and [REDACTED_REGEX] mention the [REDACTED_DENY_LIST]"
+        ))
+    );
+    let report = context.get_guardrail_report("guard").unwrap();
+    assert_eq!(
+        report.violations,
+        [violation("regex", 2), violation("deny_list", 1)]
+    );
+    assert_eq!(report.redactions, 3);
+}
+
+#[tokio::test]
+async fn test_block_fails_the_node_on_a_violation() {
+    let guard = || guardrail(vec![GuardrailCheck::Email], GuardrailAction::Block);
+
+    let context = run(workflow(guard()), "Write to jane@example.com").await;
+    assert!(matches!(
+        context.state,
+        WorkflowState::PartiallyCompleted { .. }
+    ));
+    let error = context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == "guard")
+        .and_then(|record| record.error.clone())
+        .unwrap_or_default();
+    assert!(error.contains("blocked its input: email (1)"), "{error}");
+    assert!(context.get_node_output("Publish").is_none());
+    let report = context.get_guardrail_report("guard").unwrap();
+    assert_eq!(report.violations, [violation("email", 1)]);
+    assert_eq!(report.redactions, 0);
+
+    let context = run(workflow(guard()), "Nothing to see").await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("Publish"),
+        Some(&json!("Nothing to see"))
+    );
+    assert!(context.get_guardrail_report("guard").unwrap().is_clean());
+}
+
+/// `Source` -> `guard` -> `Publish`, with a violation edge `guard` -> `Review`
+fn routing_workflow() -> Workflow {
+    let mut workflow = workflow(guardrail(vec![GuardrailCheck::Pii], GuardrailAction::Route));
+    let guard = workflow.graph.get_node_id_by_name("guard").unwrap();
+    let review = workflow.add_node(transform("Review", "input")).unwrap();
+    workflow
+        .connect_nodes(guard, review, WorkflowEdge::violation())
+        .unwrap();
+    workflow
+}
+
+#[tokio::test]
+async fn test_route_follows_violation_edges_on_a_violation() {
+    let context = run(routing_workflow(), PII_TEXT).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("Review"), Some(&json!(PII_TEXT)));
+    assert!(context.get_node_output("Publish").is_none());
+    assert_eq!(context.get_guardrail_report("guard").unwrap().redactions, 0);
+
+    let context = run(routing_workflow(), "Nothing to see").await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("Publish"),
+        Some(&json!("Nothing to see"))
+    );
+    assert!(context.get_node_output("Review").is_none());
+}
+
+#[test]
+fn test_violation_edges_need_a_routing_guardrail() {
+    let routing = workflow(guardrail(vec![GuardrailCheck::Pii], GuardrailAction::Route));
+    let error = routing.validate().unwrap_err();
+    assert!(
+        error.to_string().contains("has no violation edge"),
+        "{error}"
+    );
+
+    let mut redacting = workflow(guardrail(
+        vec![GuardrailCheck::Pii],
+        GuardrailAction::Redact,
+    ));
+    let guard = redacting.graph.get_node_id_by_name("guard").unwrap();
+    let review = redacting.add_node(transform("Review", "input")).unwrap();
+    redacting
+        .connect_nodes(guard, review, WorkflowEdge::violation())
+        .unwrap();
+    let error = redacting.validate().unwrap_err();
+    assert!(error.to_string().contains("route action"), "{error}");
+
+    assert!(routing_workflow().validate().is_ok());
+}
+
+#[test]
+fn test_invalid_checks_are_rejected() {
+    let cases = [
+        (vec![], "at least one check"),
+        (
+            vec![GuardrailCheck::Regex("(unclosed".to_string())],
+            "invalid regex check",
+        ),
+        (
+            vec![GuardrailCheck::DenyList(vec![" ".to_string()])],
+            "deny list check without terms",
+        ),
+        (
+            vec![GuardrailCheck::Classifier {
+                instructions: String::new(),
+                llm_config: None,
+            }],
+            "classifier check without instructions",
+        ),
+    ];
+    for (checks, message) in cases {
+        let error = guardrail(checks, GuardrailAction::Redact)
+            .validate()
+            .unwrap_err();
+        assert!(error.to_string().contains(message), "{error}");
+    }
+}
+
+#[test]
+fn test_checks_serialize_as_names_and_single_key_objects() {
+    let checks: Vec<GuardrailCheck> = serde_json::from_value(json!([
+        "pii",
+        "credit_card",
+        {"regex": "ORD-\\d+"},
+        {"deny_list": ["secret"]},
+        {"classifier": {"instructions": "No threats"}}
+    ]))
+    .unwrap();
+    assert!(matches!(checks[0], GuardrailCheck::Pii));
+    assert!(matches!(checks[1], GuardrailCheck::CreditCard));
+    assert!(matches!(&checks[2], GuardrailCheck::Regex(pattern) if pattern == r"ORD-\d+"));
+    assert!(matches!(&checks[3], GuardrailCheck::DenyList(terms) if terms == &["secret"]));
+    assert!(matches!(
+        &checks[4],
+        GuardrailCheck::Classifier { instructions, llm_config: None } if instructions == "No threats"
+    ));
+
+    let node: NodeType = serde_json::from_value(json!({
+        "type": "Guardrail",
+        "checks": ["email"]
+    }))
+    .unwrap();
+    assert!(matches!(
+        node,
+        NodeType::Guardrail {
+            on_violation: GuardrailAction::Redact,
+            ..
+        }
+    ));
+}
+
+/// Start a server answering every request with an `OpenAI` chat completion of
+/// `content`, recording the request bodies
+async fn spawn_llm_server(content: &'static str) -> (String, MockRequests) {
+    let server = MockServer::responding(MockResponse::json(
+        200,
+        json!({
+            "id": "chatcmpl-1",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": content},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6}
+        }),
+    ))
+    .await;
+    (server.url(), server.requests())
+}
+
+#[tokio::test]
+async fn test_classifier_uses_the_executor_llm_and_is_audited() {
+    let (url, requests) =
+        spawn_llm_server(r#"{"violation": true, "reason": "Threatens a person"}"#).await;
+    let checks = vec![GuardrailCheck::Classifier {
+        instructions: "No threats of violence".to_string(),
+        llm_config: None,
+    }];
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::Full);
+    let executor = WorkflowExecutor::new()
+        .with_default_llm_config(LlmConfig::openai_with_base_url(
+            "sk-test",
+            "gpt-4o-mini",
+            format!("{url}/v1"),
+        ))
+        .with_audit_log(log.clone());
+
+    let context = run_with(
+        executor,
+        workflow(guardrail(checks, GuardrailAction::Redact)),
+        "I will hurt you",
+    )
+    .await;
+    log.flush().await.unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("Publish"),
+        Some(&json!("[REDACTED_CLASSIFIER]"))
+    );
+    let report = context.get_guardrail_report("guard").unwrap();
+    assert_eq!(
+        report.violations,
+        [GuardrailViolation {
+            check: "classifier".to_string(),
+            matches: 1,
+            reason: Some("Threatens a person".to_string()),
+        }]
+    );
+
+    let request = requests.lock().unwrap()[0].json();
+    assert!(
+        request["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("No threats of violence")
+    );
+    assert_eq!(request["messages"][1]["content"], "I will hurt you");
+
+    let records = sink.records.lock().unwrap().clone();
+    let kinds: Vec<_> = records.iter().map(|record| record.kind).collect();
+    assert_eq!(kinds, [AuditCallKind::Llm, AuditCallKind::Guardrail]);
+    let record = &records[1];
+    assert_eq!(record.node_name.as_deref(), Some("guard"));
+    assert_eq!(record.prompt, "I will hurt you");
+    let completion: GuardrailReport =
+        serde_json::from_str(record.completion.as_deref().unwrap()).unwrap();
+    assert_eq!(&completion, report);
+}
@@ -0,0 +1,200 @@
+use super::test_helpers::{Requests, ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmRole, LlmToolCall},
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Register an agent replaying `responses`, returning its id and captured requests
+async fn register_scripted_agent(
+    executor: &WorkflowExecutor,
+    name: &str,
+    responses: Vec<LlmResponse>,
+) -> (AgentId, Requests) {
+    let provider = ScriptedProvider::replaying(responses).with_function_calling();
+    let requests = provider.requests();
+    let agent_id = AgentId::new();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new(name, "Scripted agent", LlmConfig::default())
+            .with_id(agent_id.clone())
+            .with_system_prompt(format!("You are the {name}")),
+        provider,
+    ));
+    executor.register_agent(agent).await;
+    (agent_id, requests)
+}
+
+fn agent_node(name: &str, agent_id: &AgentId, prompt: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Agent node",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), prompt),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_supervisor_hands_off_to_worker() {
+    let executor = WorkflowExecutor::new().without_retries();
+    let (supervisor_id, supervisor_requests) = register_scripted_agent(
+        &executor,
+        "supervisor",
+        vec![
+            LlmResponse::new("Let me ask the researcher.", "scripted-model").with_tool_calls(vec![
+                LlmToolCall {
+                    id: "call_1".to_string(),
+                    name: "handoff".to_string(),
+                    parameters: json!({
+                        "target": "researcher",
+                        "message": "Find the boiling point of water"
+                    }),
+                },
+            ]),
+            LlmResponse::new("Water boils at 100 C.", "scripted-model"),
+        ],
+    )
+    .await;
+    let (worker_id, worker_requests) = register_scripted_agent(
+        &executor,
+        "researcher",
+        vec![LlmResponse::new("100 degrees Celsius", "scripted-model")],
+    )
+    .await;
+    let (summary_id, summary_requests) = register_scripted_agent(
+        &executor,
+        "summary",
+        vec![LlmResponse::new("done", "scripted-model")],
+    )
+    .await;
+
+    let mut workflow = Workflow::new("handoff", "Supervisor with one worker");
+    workflow
+        .add_node(
+            agent_node(
+                "supervisor",
+                &supervisor_id,
+                "At what temperature does water boil?",
+            )
+            .with_handoff_targets(["researcher"]),
+        )
+        .unwrap();
+    let worker = workflow
+        .add_node(agent_node("researcher", &worker_id, "Answer precisely."))
+        .unwrap();
+    let summary = workflow
+        .add_node(agent_node(
+            "summary",
+            &summary_id,
+            "Summarize {{node.researcher}}",
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(worker, summary, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let context = executor.execute(workflow, None).await.unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+
+    // The supervisor is offered the handoff tool and gets the worker's answer back
+    let supervisor_requests = supervisor_requests.lock().unwrap();
+    assert_eq!(supervisor_requests.len(), 2);
+    assert!(
+        supervisor_requests[0]
+            .tools
+            .iter()
+            .any(|t| t.name == "handoff")
+    );
+    let tool_result = supervisor_requests[1]
+        .messages
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::Tool))
+        .unwrap();
+    assert_eq!(tool_result.content, "100 degrees Celsius");
+    assert_eq!(
+        context.get_node_output("supervisor"),
+        Some(&json!("Water boils at 100 C."))
+    );
+
+    // The worker runs once, with the supervisor's conversation carried over
+    let worker_requests = worker_requests.lock().unwrap();
+    assert_eq!(worker_requests.len(), 1);
+    let messages = &worker_requests[0].messages;
+    assert!(matches!(messages[0].role, LlmRole::System));
+    assert_eq!(messages[0].content, "You are the researcher");
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.content.contains("At what temperature does water boil?"))
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.content == "Let me ask the researcher.")
+    );
+    let instruction = &messages.last().unwrap().content;
+    assert!(instruction.starts_with("Answer precisely."));
+    assert!(instruction.ends_with("Find the boiling point of water"));
+
+    assert_eq!(
+        context.get_node_output("researcher"),
+        Some(&json!("100 degrees Celsius"))
+    );
+    let record = context.metadata.get("handoff_researcher").unwrap();
+    assert_eq!(record["from"], "supervisor");
+    assert_eq!(record["message"], "Find the boiling point of water");
+
+    // Nodes downstream of the worker see its handed-off answer
+    let summary_requests = summary_requests.lock().unwrap();
+    assert_eq!(summary_requests.len(), 1);
+    assert!(
+        summary_requests[0]
+            .messages
+            .last()
+            .unwrap()
+            .content
+            .contains("100 degrees Celsius")
+    );
+}
+
+#[test]
+fn test_handoff_targets_are_validated() {
+    let supervisor_id = AgentId::new();
+    let worker_id = AgentId::new();
+
+    let mut workflow = Workflow::new("unknown", "Unknown handoff target");
+    workflow
+        .add_node(agent_node("supervisor", &supervisor_id, "p").with_handoff_targets(["missing"]))
+        .unwrap();
+    let err = workflow.validate().unwrap_err().to_string();
+    assert!(err.contains("unknown node 'missing'"), "{err}");
+
+    let mut workflow = Workflow::new("non_agent", "Non-agent handoff target");
+    workflow
+        .add_node(agent_node("supervisor", &supervisor_id, "p").with_handoff_targets(["upper"]))
+        .unwrap();
+    workflow
+        .add_node(WorkflowNode::new(
+            "upper",
+            "Transform node",
+            NodeType::Transform {
+                transformation: "uppercase".to_string(),
+            },
+        ))
+        .unwrap();
+    let err = workflow.validate().unwrap_err().to_string();
+    assert!(err.contains("is not an agent node"), "{err}");
+
+    let mut workflow = Workflow::new("valid", "Valid handoff target");
+    workflow
+        .add_node(agent_node("supervisor", &supervisor_id, "p").with_handoff_targets(["worker"]))
+        .unwrap();
+    workflow
+        .add_node(agent_node("worker", &worker_id, "p"))
+        .unwrap();
+    assert!(workflow.validate().is_ok());
+}
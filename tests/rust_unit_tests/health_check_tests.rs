@@ -0,0 +1,199 @@
+//! Provider health checks and the executor dry run, against mocked provider responses
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    embeddings::{EmbeddingConfig, EmbeddingProvider, EmbeddingService},
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmProvider, LlmProviderFactory, ProviderHealth},
+    types::AgentId,
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::collections::HashMap;
+use tokio::net::TcpListener;
+
+/// Start an HTTP server answering every request with `status` and `body`,
+/// returning its base URL
+async fn spawn_server(status: u16, body: &'static str) -> String {
+    let server = MockServer::responding(MockResponse::json(status, body)).await;
+    format!("{}/v1", server.url())
+}
+
+async fn spawn_model_server() -> String {
+    spawn_server(
+        200,
+        r#"{"id":"gpt-4o-mini","object":"model","owned_by":"openai"}"#,
+    )
+    .await
+}
+
+async fn spawn_unauthorized_server() -> String {
+    spawn_server(
+        401,
+        r#"{"error":{"message":"Incorrect API key provided","code":"invalid_api_key"}}"#,
+    )
+    .await
+}
+
+async fn spawn_missing_model_server() -> String {
+    spawn_server(
+        404,
+        r#"{"error":{"message":"The model does not exist","code":"model_not_found"}}"#,
+    )
+    .await
+}
+
+async fn llm_health(base_url: String) -> ProviderHealth {
+    let config = LlmConfig::openai_with_base_url("sk-test", "gpt-4o-mini", base_url);
+    let provider = LlmProviderFactory::create_provider(config.clone()).unwrap();
+    LlmProvider::new(provider, config).health_check().await
+}
+
+async fn embedding_health(base_url: String) -> ProviderHealth {
+    let service = EmbeddingService::new(EmbeddingConfig {
+        provider: EmbeddingProvider::OpenAI,
+        api_key: "sk-test".to_string(),
+        model: "text-embedding-3-small".to_string(),
+        base_url: Some(base_url),
+        timeout_seconds: None,
+        max_batch_size: None,
+        extra_params: HashMap::new(),
+        retry: None,
+        python_instance: None,
+        timeouts: Default::default(),
+    })
+    .unwrap();
+    service.health_check().await
+}
+
+#[tokio::test]
+async fn test_llm_health_check_success() {
+    let health = llm_health(spawn_model_server().await).await;
+    assert!(health.is_healthy(), "{health:?}");
+    assert_eq!(health.provider, "openai");
+    assert_eq!(health.model, "gpt-4o-mini");
+    assert!(health.error.is_none());
+}
+
+#[tokio::test]
+async fn test_llm_health_check_unauthorized() {
+    let health = llm_health(spawn_unauthorized_server().await).await;
+    assert!(health.reachable);
+    assert!(!health.authenticated);
+    assert!(!health.model_available);
+    assert!(health.error.unwrap().contains("401"));
+}
+
+#[tokio::test]
+async fn test_llm_health_check_missing_model() {
+    let health = llm_health(spawn_missing_model_server().await).await;
+    assert!(health.reachable);
+    assert!(health.authenticated);
+    assert!(!health.model_available);
+    assert!(!health.is_healthy());
+}
+
+#[tokio::test]
+async fn test_llm_health_check_unreachable() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let health = llm_health(format!("http://{addr}/v1")).await;
+    assert!(!health.reachable);
+    assert!(!health.authenticated);
+    assert!(health.error.is_some());
+}
+
+#[tokio::test]
+async fn test_embedding_health_check_success() {
+    let base_url = spawn_server(
+        200,
+        r#"{"object":"list","data":[{"object":"embedding","index":0,"embedding":[0.1,0.2]}],
+            "model":"text-embedding-3-small","usage":{"prompt_tokens":1,"total_tokens":1}}"#,
+    )
+    .await;
+    let health = embedding_health(base_url).await;
+    assert!(health.is_healthy(), "{health:?}");
+    assert_eq!(health.model, "text-embedding-3-small");
+}
+
+#[tokio::test]
+async fn test_embedding_health_check_unauthorized() {
+    let health = embedding_health(spawn_unauthorized_server().await).await;
+    assert!(health.reachable);
+    assert!(!health.authenticated);
+}
+
+#[tokio::test]
+async fn test_embedding_health_check_missing_model() {
+    let health = embedding_health(spawn_missing_model_server().await).await;
+    assert!(health.reachable);
+    assert!(health.authenticated);
+    assert!(!health.model_available);
+}
+
+fn agent_node(name: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Agent node",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), format!("Prompt for {name}")),
+        },
+    )
+}
+
+/// Two agent nodes in sequence, both relying on the executor's LLM configuration
+fn two_agent_workflow() -> Workflow {
+    let mut workflow = Workflow::new("dry run", "Two agents");
+    let draft = workflow.add_node(agent_node("draft")).unwrap();
+    let review = workflow.add_node(agent_node("review")).unwrap();
+    workflow
+        .connect_nodes(draft, review, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+#[tokio::test]
+async fn test_dry_run_probes_each_distinct_provider_once() {
+    let base_url = spawn_model_server().await;
+    let executor = WorkflowExecutor::new().with_default_llm_config(
+        LlmConfig::openai_with_base_url("sk-test", "gpt-4o-mini", base_url),
+    );
+
+    let report = executor.dry_run(&two_agent_workflow()).await.unwrap();
+    assert!(report.is_ok(), "{report:?}");
+    assert!(report.validation_error.is_none());
+    assert_eq!(report.providers.len(), 1);
+    assert_eq!(report.providers[0].model, "gpt-4o-mini");
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_unhealthy_provider() {
+    let base_url = spawn_unauthorized_server().await;
+    let executor = WorkflowExecutor::new().with_default_llm_config(
+        LlmConfig::openai_with_base_url("sk-test", "gpt-4o-mini", base_url),
+    );
+
+    let report = executor.dry_run(&two_agent_workflow()).await.unwrap();
+    assert!(!report.is_ok());
+    assert!(report.validation_error.is_none());
+    assert!(!report.providers[0].authenticated);
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_invalid_workflow_without_probing() {
+    let mut workflow = Workflow::new("cyclic", "Two agents in a cycle");
+    let a = workflow.add_node(agent_node("a")).unwrap();
+    let b = workflow.add_node(agent_node("b")).unwrap();
+    workflow
+        .connect_nodes(a.clone(), b.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(b, a, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let report = WorkflowExecutor::new().dry_run(&workflow).await.unwrap();
+    assert!(!report.is_ok());
+    assert!(report.validation_error.is_some());
+    assert!(report.providers.is_empty());
+}
@@ -0,0 +1,342 @@
+//! History windows limiting the tool exchanges an agent's tool loop replays
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{
+        HistoryWindow, LlmConfig, LlmRequest, LlmResponse, LlmRole, LlmToolCall,
+        context_window::estimate_request_tokens,
+    },
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Tool rounds the scripted model asks for before answering
+const TOOL_ROUNDS: usize = 4;
+
+fn is_summary_request(request: &LlmRequest) -> bool {
+    request.messages[0]
+        .content
+        .contains("conversation summarization assistant")
+}
+
+/// Registry with a `lookup` tool returning `result {step}`, padded with `pad` dots
+fn registry() -> ToolRegistry {
+    let handler: ToolHandler = Arc::new(|args: serde_json::Value| {
+        Box::pin(async move {
+            let pad = usize::try_from(args["pad"].as_u64().unwrap_or(0)).unwrap();
+            Ok(json!(format!("result {}{}", args["step"], ".".repeat(pad))))
+        })
+    });
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(
+            ToolMetadata::new(
+                "lookup",
+                "Look up a step",
+                json!({"type": "object", "properties": {"step": {"type": "integer"}, "pad": {"type": "integer"}}}),
+            ),
+            handler,
+        )
+        .unwrap();
+    registry
+}
+
+/// Provider asking for `lookup` with steps 1 to [`TOOL_ROUNDS`], passing
+/// `pad` along, then answering, and summarizing when asked to
+fn researcher(pad: usize) -> ScriptedProvider {
+    let tool_rounds = Mutex::new(0);
+    let summaries = Mutex::new(0);
+    ScriptedProvider::new(move |request| {
+        if is_summary_request(request) {
+            let mut summaries = summaries.lock().unwrap();
+            *summaries += 1;
+            return Ok(LlmResponse::new(
+                format!("Summary {summaries}"),
+                "scripted-model",
+            ));
+        }
+        let mut rounds = tool_rounds.lock().unwrap();
+        if *rounds == TOOL_ROUNDS {
+            return Ok(LlmResponse::new("Done", "scripted-model"));
+        }
+        *rounds += 1;
+        Ok(
+            LlmResponse::new("", "scripted-model").with_tool_calls(vec![LlmToolCall {
+                id: format!("call_{rounds}"),
+                name: "lookup".to_string(),
+                parameters: json!({"step": *rounds, "pad": pad}),
+            }]),
+        )
+    })
+}
+
+/// Run a `research` agent limited by `window`, with tool results padded by `pad`
+/// dots, returning the context and the requests sent
+async fn run(window: Option<HistoryWindow>, pad: usize) -> (WorkflowContext, Vec<LlmRequest>) {
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_capture_payloads(true)
+        .with_tool_registry(registry());
+    let agent_id = AgentId::new();
+    let provider = researcher(pad);
+    let requests = provider.requests();
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Researcher", "", LlmConfig::default()).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+
+    let mut node = WorkflowNode::new(
+        "research",
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, "Research the topic"),
+        },
+    )
+    .with_tools(["lookup"]);
+    if let Some(window) = window {
+        node = node.with_history_window(window);
+    }
+    let mut workflow = Workflow::new("history", "");
+    workflow.add_node(node).unwrap();
+
+    let context = executor.execute(workflow, None).await.unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let requests = requests.lock().unwrap().clone();
+    (context, requests)
+}
+
+/// Steps of the tool results in `request`, in order
+fn tool_steps(request: &LlmRequest) -> Vec<String> {
+    request
+        .messages
+        .iter()
+        .filter(|message| message.role == LlmRole::Tool)
+        .map(|message| message.content.trim_end_matches('.').to_string())
+        .collect()
+}
+
+/// Follow-up requests of the tool loop, without the initial and summary requests
+fn follow_ups(requests: &[LlmRequest]) -> Vec<&LlmRequest> {
+    requests
+        .iter()
+        .skip(1)
+        .filter(|request| !is_summary_request(request))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_without_window_every_exchange_is_replayed() {
+    let (context, requests) = run(None, 0).await;
+
+    let steps: Vec<Vec<String>> = follow_ups(&requests).into_iter().map(tool_steps).collect();
+    assert_eq!(
+        steps,
+        vec![
+            vec!["result 1"],
+            vec!["result 1", "result 2"],
+            vec!["result 1", "result 2", "result 3"],
+            vec!["result 1", "result 2", "result 3", "result 4"],
+        ]
+    );
+    let debug = context.get_node_debug("research").unwrap();
+    assert_eq!(debug.histories.len(), TOOL_ROUNDS);
+    assert!(
+        debug
+            .histories
+            .iter()
+            .all(|history| history.dropped_exchanges == 0)
+    );
+}
+
+#[tokio::test]
+async fn test_window_keeps_the_latest_exchanges() {
+    let (context, requests) = run(Some(HistoryWindow::default().with_max_exchanges(2)), 0).await;
+
+    let follow_ups = follow_ups(&requests);
+    let steps: Vec<Vec<String>> = follow_ups
+        .iter()
+        .map(|request| tool_steps(request))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            vec!["result 1"],
+            vec!["result 1", "result 2"],
+            vec!["result 2", "result 3"],
+            vec!["result 3", "result 4"],
+        ]
+    );
+    // The prompt is always sent, and no summary is made without summarize_overflow
+    for request in &follow_ups {
+        assert_eq!(request.messages[0].role, LlmRole::User);
+        assert_eq!(request.messages[0].content, "Research the topic");
+        assert!(
+            request
+                .messages
+                .iter()
+                .all(|message| message.role != LlmRole::System)
+        );
+    }
+
+    // The capture shows what each iteration sent
+    let debug = context.get_node_debug("research").unwrap();
+    let dropped: Vec<usize> = debug
+        .histories
+        .iter()
+        .map(|history| history.dropped_exchanges)
+        .collect();
+    assert_eq!(dropped, vec![0, 0, 1, 2]);
+    let last = &debug.histories[3];
+    assert_eq!(last.iteration, 4);
+    assert_eq!(last.messages.len(), 5);
+    assert_eq!(last.messages[0], "User: Research the topic");
+    assert_eq!(last.messages[2], "Tool: result 3");
+    assert!(last.messages[3].contains("[calls lookup("), "{last:?}");
+
+    let executions = context.metadata["node_response_research"]["executions"]
+        .as_array()
+        .unwrap();
+    let history = executions
+        .iter()
+        .filter(|execution| execution["type"] == "llm_call")
+        .last()
+        .unwrap()["history"]
+        .clone();
+    assert_eq!(history["exchanges"], 4);
+    assert_eq!(history["dropped_exchanges"], 2);
+}
+
+#[tokio::test]
+async fn test_overflow_is_summarized_once_per_exchange() {
+    let window = HistoryWindow::default()
+        .with_max_exchanges(1)
+        .with_summarize_overflow(true);
+    let (context, requests) = run(Some(window), 0).await;
+
+    // Each exchange leaving the window is summarized, extending the summary so far
+    let summaries: Vec<&LlmRequest> = requests
+        .iter()
+        .filter(|request| is_summary_request(request))
+        .collect();
+    assert_eq!(summaries.len(), 3);
+    let first = &summaries[0].messages[1].content;
+    assert!(first.contains("Tool: result 1"), "{first}");
+    assert!(!first.contains("Earlier summary"), "{first}");
+    let second = &summaries[1].messages[1].content;
+    assert!(second.contains("Earlier summary:\nSummary 1"), "{second}");
+    assert!(second.contains("Tool: result 2"), "{second}");
+    assert!(!second.contains("result 1"), "{second}");
+
+    let follow_ups = follow_ups(&requests);
+    assert!(
+        follow_ups[0]
+            .messages
+            .iter()
+            .all(|m| m.role != LlmRole::System)
+    );
+    for (n, request) in follow_ups.iter().enumerate().skip(1) {
+        // The prompt, the summary note and the latest exchange
+        assert_eq!(request.messages.len(), 4);
+        let note = &request.messages[1];
+        assert_eq!(note.role, LlmRole::System);
+        assert!(
+            note.content.ends_with(&format!(
+                "earlier tool exchanges of this conversation:\nSummary {n}"
+            )),
+            "{note:?}"
+        );
+        assert_eq!(tool_steps(request), vec![format!("result {}", n + 1)]);
+    }
+
+    let debug = context.get_node_debug("research").unwrap();
+    let summarized: Vec<usize> = debug
+        .histories
+        .iter()
+        .map(|history| history.summarized_exchanges)
+        .collect();
+    assert_eq!(summarized, vec![0, 1, 2, 3]);
+    assert!(debug.histories[3].messages[1].starts_with("System: Summary of the 3 earlier"));
+}
+
+#[tokio::test]
+async fn test_token_budget_drops_the_oldest_exchanges() {
+    // Each tool result is about 100 tokens
+    let budget = 300;
+    let window = HistoryWindow::default().with_token_budget(budget);
+    let (context, requests) = run(Some(window), 400).await;
+
+    let follow_ups = follow_ups(&requests);
+    for request in &follow_ups {
+        assert!(estimate_request_tokens(request) <= budget);
+    }
+    let steps: Vec<Vec<String>> = follow_ups
+        .iter()
+        .map(|request| tool_steps(request))
+        .collect();
+    assert_eq!(steps[0], vec!["result 1"]);
+    assert_eq!(steps[3].last().unwrap(), "result 4");
+    assert!(steps[3].len() < 4, "{steps:?}");
+
+    let debug = context.get_node_debug("research").unwrap();
+    assert!(debug.histories[3].dropped_exchanges > 0);
+}
+
+#[test]
+fn test_history_window_from_node_config() {
+    let config = |pairs: &[(&str, serde_json::Value)]| -> HashMap<String, serde_json::Value> {
+        pairs
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), value.clone()))
+            .collect()
+    };
+
+    assert_eq!(HistoryWindow::from_node_config(&config(&[])).unwrap(), None);
+    assert_eq!(
+        HistoryWindow::from_node_config(&config(&[("summarize_overflow", json!(false))])).unwrap(),
+        None
+    );
+    let window = HistoryWindow::from_node_config(&config(&[
+        ("history_window", json!(3)),
+        ("history_token_budget", json!(2000)),
+        ("summarize_overflow", json!(true)),
+    ]))
+    .unwrap()
+    .unwrap();
+    assert_eq!(window.max_exchanges(), Some(3));
+    assert_eq!(window.token_budget(), Some(2000));
+    assert!(window.summarize_overflow());
+
+    for invalid in [
+        config(&[("history_window", json!(0))]),
+        config(&[("history_token_budget", json!("many"))]),
+        config(&[
+            ("history_window", json!(2)),
+            ("summarize_overflow", json!("yes")),
+        ]),
+        config(&[("summarize_overflow", json!(true))]),
+    ] {
+        assert!(
+            HistoryWindow::from_node_config(&invalid).is_err(),
+            "{invalid:?}"
+        );
+    }
+
+    // Nodes with an invalid window fail validation
+    let node = WorkflowNode::new(
+        "research",
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), "Research"),
+        },
+    )
+    .with_history_window(HistoryWindow::default().with_summarize_overflow(true));
+    assert!(node.validate().is_err());
+}
@@ -0,0 +1,438 @@
+//! `Fireworks AI` and `TogetherAI` providers against mocked endpoints: chat
+//! completions with tools, model catalogs and their error responses
+
+use super::test_helpers::{MockRequests, MockResponse, MockServer};
+use graphbit_core::errors::GraphBitError;
+use graphbit_core::llm::fireworks::FireworksProvider;
+use graphbit_core::llm::togetherai::TogetherAiProvider;
+use graphbit_core::llm::{
+    FinishReason, LlmConfig, LlmMessage, LlmProviderFactory, LlmProviderTrait, LlmRequest, LlmTool,
+    LlmToolCall, ModelInfo,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Start an HTTP server answering each path with a fixed status and body (404
+/// for other paths), recording every request it receives; returns the base URL
+/// of its `/v1` API
+async fn spawn_mock_server(routes: HashMap<&'static str, (u16, String)>) -> (String, MockRequests) {
+    let server = MockServer::new(move |request| {
+        let (status, body) = routes
+            .get(request.path.as_str())
+            .cloned()
+            .unwrap_or_else(|| (404, r#"{"error": "Not Found"}"#.to_string()));
+        std::future::ready(MockResponse::json(status, body))
+    })
+    .await;
+    (format!("{}/v1", server.url()), server.requests())
+}
+
+/// Server answering chat completions with `status` and `body`
+async fn spawn_completion_server(status: u16, body: impl ToString) -> (String, MockRequests) {
+    spawn_mock_server(HashMap::from([(
+        "/v1/chat/completions",
+        (status, body.to_string()),
+    )]))
+    .await
+}
+
+fn weather_tool() -> LlmTool {
+    LlmTool::new(
+        "get_weather",
+        "Current weather in a city",
+        json!({
+            "type": "object",
+            "properties": {"city": {"type": "string"}},
+            "required": ["city"],
+        }),
+    )
+}
+
+/// `Fireworks AI` catalog: an `OpenAI` style list
+fn fireworks_catalog() -> String {
+    json!({
+        "object": "list",
+        "data": [
+            {
+                "id": "accounts/fireworks/models/deepseek-v3p1",
+                "object": "model",
+                "owned_by": "fireworks",
+                "created": 1_755_000_000,
+                "kind": "HF_BASE_MODEL",
+                "supports_chat": true,
+                "supports_image_input": false,
+                "supports_tools": true,
+                "context_length": 163_840,
+            },
+            {
+                "id": "accounts/fireworks/models/llama-v3p1-8b-instruct",
+                "object": "model",
+                "owned_by": "fireworks",
+                "kind": "HF_BASE_MODEL",
+                "supports_tools": false,
+                "context_length": 131_072,
+            },
+        ],
+    })
+    .to_string()
+}
+
+/// `TogetherAI` catalog: a bare array with display names and pricing
+fn togetherai_catalog() -> String {
+    json!([
+        {
+            "id": "openai/gpt-oss-20b",
+            "object": "model",
+            "created": 1_754_000_000,
+            "type": "chat",
+            "display_name": "OpenAI GPT-OSS 20B",
+            "organization": "OpenAI",
+            "context_length": 131_072,
+            "pricing": {"input": 0.05, "output": 0.2},
+        },
+        {
+            "id": "BAAI/bge-large-en-v1.5",
+            "object": "model",
+            "type": "embedding",
+            "display_name": "BAAI-Bge-Large-1p5",
+            "organization": "BAAI",
+            "context_length": null,
+        },
+    ])
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_fireworks_completion_with_tools() {
+    let (base_url, received) = spawn_completion_server(
+        200,
+        json!({
+            "id": "fw-1",
+            "object": "chat.completion",
+            "model": "accounts/fireworks/models/llama-v3p1-8b-instruct",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"},
+                    }],
+                },
+                "finish_reason": "tool_calls",
+            }],
+            "usage": {"prompt_tokens": 42, "completion_tokens": 9, "total_tokens": 51},
+        }),
+    )
+    .await;
+
+    // Bare model names refer to the models Fireworks serves itself
+    let provider = LlmProviderFactory::create_provider(LlmConfig::fireworks_with_base_url(
+        "fw-key",
+        "llama-v3p1-8b-instruct",
+        base_url,
+    ))
+    .unwrap();
+    assert_eq!(
+        provider.model_name(),
+        "accounts/fireworks/models/llama-v3p1-8b-instruct"
+    );
+
+    let response = provider
+        .complete(LlmRequest::new("Weather in Paris?").with_tool(weather_tool()))
+        .await
+        .unwrap();
+    assert!(matches!(response.finish_reason, FinishReason::ToolCalls));
+    assert_eq!(response.tool_calls.len(), 1);
+    assert_eq!(response.tool_calls[0].id, "call_1");
+    assert_eq!(response.tool_calls[0].name, "get_weather");
+    assert_eq!(response.tool_calls[0].parameters, json!({"city": "Paris"}));
+    assert_eq!(response.usage.total_tokens, 51);
+
+    let requests = received.lock().unwrap();
+    let request = &requests[0];
+    let body = request.json();
+    assert_eq!(request.header("authorization"), Some("Bearer fw-key"));
+    assert_eq!(
+        body["model"],
+        "accounts/fireworks/models/llama-v3p1-8b-instruct"
+    );
+    assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+    assert_eq!(body["tool_choice"], "auto");
+}
+
+#[tokio::test]
+async fn test_togetherai_completion_with_tool_results() {
+    let (base_url, received) = spawn_completion_server(
+        200,
+        json!({
+            "id": "tg-1",
+            "object": "chat.completion",
+            "model": "openai/gpt-oss-20b",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "It is sunny in Paris."},
+                "finish_reason": "stop",
+            }],
+            "usage": {"prompt_tokens": 60, "completion_tokens": 7, "total_tokens": 67},
+        }),
+    )
+    .await;
+
+    let provider = LlmProviderFactory::create_provider(LlmConfig::togetherai_with_base_url(
+        "tg-key",
+        "openai/gpt-oss-20b",
+        base_url,
+    ))
+    .unwrap();
+    let request = LlmRequest::with_messages(vec![
+        LlmMessage::user("Weather in Paris?"),
+        LlmMessage::assistant("").with_tool_calls(vec![LlmToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            parameters: json!({"city": "Paris"}),
+        }]),
+        LlmMessage::tool("call_1", "sunny"),
+    ])
+    .with_tool(weather_tool());
+    let response = provider.complete(request).await.unwrap();
+    assert_eq!(response.content, "It is sunny in Paris.");
+    assert!(matches!(response.finish_reason, FinishReason::Stop));
+    assert_eq!(response.id.as_deref(), Some("tg-1"));
+
+    // Tool calls and results are passed through in the OpenAI format
+    let requests = received.lock().unwrap();
+    let messages = &requests[0].json()["messages"];
+    assert_eq!(
+        messages[1]["tool_calls"][0]["function"]["arguments"],
+        r#"{"city":"Paris"}"#
+    );
+    assert_eq!(messages[2]["role"], "tool");
+    assert_eq!(messages[2]["tool_call_id"], "call_1");
+}
+
+#[tokio::test]
+async fn test_fireworks_list_models() {
+    let (base_url, received) =
+        spawn_mock_server(HashMap::from([("/v1/models", (200, fireworks_catalog()))])).await;
+    let provider = FireworksProvider::with_base_url(
+        "fw-key".to_string(),
+        "deepseek-v3p1".to_string(),
+        base_url,
+    )
+    .unwrap();
+    assert_eq!(provider.max_context_length(), None);
+
+    let models = provider.list_models().await.unwrap();
+    assert_eq!(
+        models[0],
+        ModelInfo {
+            id: "accounts/fireworks/models/deepseek-v3p1".to_string(),
+            display_name: None,
+            organization: Some("fireworks".to_string()),
+            model_type: Some("HF_BASE_MODEL".to_string()),
+            context_length: Some(163_840),
+            supports_tools: Some(true),
+        }
+    );
+    assert_eq!(models[1].supports_tools, Some(false));
+    assert_eq!(
+        received.lock().unwrap()[0].header("authorization"),
+        Some("Bearer fw-key")
+    );
+
+    // The catalog's context window replaces the built-in estimate
+    assert_eq!(provider.max_context_length(), Some(163_840));
+}
+
+#[tokio::test]
+async fn test_togetherai_list_models() {
+    let (base_url, _) =
+        spawn_mock_server(HashMap::from([("/v1/models", (200, togetherai_catalog()))])).await;
+    let provider = TogetherAiProvider::with_base_url(
+        "tg-key".to_string(),
+        "openai/gpt-oss-20b".to_string(),
+        base_url,
+    )
+    .unwrap();
+    assert_eq!(provider.max_context_length(), Some(8192));
+
+    let models = provider.list_models().await.unwrap();
+    let ids: Vec<&str> = models.iter().map(|model| model.id.as_str()).collect();
+    assert_eq!(ids, ["openai/gpt-oss-20b", "BAAI/bge-large-en-v1.5"]);
+    assert_eq!(
+        models[0].display_name.as_deref(),
+        Some("OpenAI GPT-OSS 20B")
+    );
+    assert_eq!(models[0].organization.as_deref(), Some("OpenAI"));
+    assert_eq!(models[1].model_type.as_deref(), Some("embedding"));
+    assert_eq!(models[1].context_length, None);
+    assert_eq!(provider.max_context_length(), Some(131_072));
+}
+
+#[tokio::test]
+async fn test_health_check_validates_model_names() {
+    let (base_url, received) =
+        spawn_mock_server(HashMap::from([("/v1/models", (200, togetherai_catalog()))])).await;
+
+    let health = TogetherAiProvider::with_base_url(
+        "tg-key".to_string(),
+        "openai/gpt-oss-20b".to_string(),
+        base_url.clone(),
+    )
+    .unwrap()
+    .health_check()
+    .await;
+    assert!(health.is_healthy());
+    assert_eq!(health.provider, "togetherai");
+
+    let health = TogetherAiProvider::with_base_url(
+        "tg-key".to_string(),
+        "openai/gpt-oss-2b".to_string(),
+        base_url,
+    )
+    .unwrap()
+    .health_check()
+    .await;
+    assert!(health.reachable && health.authenticated);
+    assert!(!health.model_available);
+    assert!(health.error.unwrap().contains("'openai/gpt-oss-2b'"));
+
+    // Both checks only looked at the catalog
+    assert!(
+        received
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|request| request.path == "/v1/models")
+    );
+
+    let (base_url, _) = spawn_mock_server(HashMap::from([(
+        "/v1/models",
+        (
+            401,
+            r#"{"error": {"message": "Invalid API key provided", "type": "invalid_request_error", "code": "invalid_api_key"}}"#
+                .to_string(),
+        ),
+    )]))
+    .await;
+    let health = FireworksProvider::with_base_url(
+        "fw-bad".to_string(),
+        "deepseek-v3p1".to_string(),
+        base_url,
+    )
+    .unwrap()
+    .health_check()
+    .await;
+    assert!(health.reachable);
+    assert!(!health.authenticated);
+}
+
+#[tokio::test]
+async fn test_error_envelopes() {
+    let fireworks = |base_url: String| {
+        LlmProviderFactory::create_provider(LlmConfig::fireworks_with_base_url(
+            "fw-key",
+            "llama-v3p1-8b-instruct",
+            base_url,
+        ))
+        .unwrap()
+    };
+    let togetherai = |base_url: String| {
+        LlmProviderFactory::create_provider(LlmConfig::togetherai_with_base_url(
+            "tg-key",
+            "openai/gpt-oss-20b",
+            base_url,
+        ))
+        .unwrap()
+    };
+
+    // Fireworks: an OpenAI style error object with a type but no code
+    let (base_url, _) = spawn_completion_server(
+        404,
+        json!({"error": {
+            "object": "error",
+            "type": "invalid_request_error",
+            "message": "Model not found, inaccessible, and/or not deployed",
+        }}),
+    )
+    .await;
+    let error = fireworks(base_url)
+        .complete(LlmRequest::new("Hi"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::LlmProvider { .. }));
+    assert_eq!(error.http_status(), Some(404));
+    assert!(error.to_string().contains(
+        "API error (HTTP 404): Model not found, inaccessible, and/or not deployed (invalid_request_error)"
+    ));
+
+    // Fireworks: a plain message on rejected keys
+    let (base_url, _) = spawn_completion_server(401, json!({"message": "unauthorized"})).await;
+    let error = fireworks(base_url)
+        .complete(LlmRequest::new("Hi"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::Authentication { .. }));
+    assert!(error.to_string().contains("unauthorized"));
+
+    // Together: an error object whose code wins over its type
+    let (base_url, _) = spawn_completion_server(
+        400,
+        json!({
+            "id": "tg-err",
+            "error": {
+                "message": "Input validation error: max_tokens must be at least 1",
+                "type": "invalid_request_error",
+                "param": "max_tokens",
+                "code": "invalid_parameter",
+            },
+        }),
+    )
+    .await;
+    let error = togetherai(base_url)
+        .complete(LlmRequest::new("Hi"))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains(
+        "API error (HTTP 400): Input validation error: max_tokens must be at least 1 (invalid_parameter)"
+    ));
+
+    // Together: the error as a plain string, and throttling
+    let (base_url, _) = spawn_completion_server(
+        429,
+        json!({"error": "You have exceeded the rate limit of 60 requests per minute"}),
+    )
+    .await;
+    let error = togetherai(base_url)
+        .complete(LlmRequest::new("Hi"))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::RateLimit { .. }));
+
+    // Gateways answering with a `detail` or with no JSON at all
+    let (base_url, _) =
+        spawn_completion_server(422, json!({"detail": "Field required: messages"})).await;
+    let error = togetherai(base_url)
+        .complete(LlmRequest::new("Hi"))
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("API error (HTTP 422): Field required: messages")
+    );
+
+    let (base_url, _) = spawn_completion_server(502, "upstream connect error").await;
+    let error = fireworks(base_url)
+        .complete(LlmRequest::new("Hi"))
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("API error (HTTP 502): upstream connect error")
+    );
+}
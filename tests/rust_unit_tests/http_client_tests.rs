@@ -0,0 +1,319 @@
+//! Shared HTTP clients and connection reuse
+//!
+//! Tests only touch global factory settings under component names no other
+//! test sends requests with, since all tests share the process-wide factory.
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::http_client::{HttpClientFactory, HttpSettings};
+use graphbit_core::llm::{LlmConfig, LlmProviderFactory, LlmRequest};
+use graphbit_core::workflow::WorkflowExecutor;
+use serde_json::json;
+use std::time::Duration;
+
+/// Start a server answering every request with `body` after `delay`
+async fn spawn_delayed_server(body: String, delay: Duration) -> MockServer {
+    MockServer::new(move |_| {
+        let body = body.clone();
+        async move {
+            tokio::time::sleep(delay).await;
+            MockResponse::json(200, body)
+        }
+    })
+    .await
+}
+
+/// Self-signed root certificate
+const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBjTCCATOgAwIBAgIUYskDiH07IsUWoG7ul88YSGb1r4gwCgYIKoZIzj0EAwIw
+GzEZMBcGA1UEAwwQR3JhcGhCaXQgVGVzdCBDQTAgFw0yNjEwMTcxMTUyMDRaGA8y
+MTI2MDkyMzExNTIwNFowGzEZMBcGA1UEAwwQR3JhcGhCaXQgVGVzdCBDQTBZMBMG
+ByqGSM49AgEGCCqGSM49AwEHA0IABEx0AfA7YWVeohsdGUAfZOEkEoCRq3zKW2Lx
+aVLrW71bDtoNH3e2825LsHrKfO011fM6zIzVDFOA0uHqlJQPPk2jUzBRMB0GA1Ud
+DgQWBBQ2Qfg5H1zkDig9kcwSc/w3TFbtxTAfBgNVHSMEGDAWgBQ2Qfg5H1zkDig9
+kcwSc/w3TFbtxTAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIE4L
+STKJ37+KvrxgSCGEbDPI4T3VUFEloLBLt7qQZh73AiEA5NdGK6qccObbCHF9hNM5
+HiIuxShyJ+0p1OG5MoEe/gU=
+-----END CERTIFICATE-----
+";
+
+fn chat_completion() -> String {
+    json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "pong"},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+    })
+    .to_string()
+}
+
+#[test]
+fn test_clients_are_shared_per_settings() {
+    let factory = HttpClientFactory::new(HttpSettings::default());
+    let minute = Some(Duration::from_secs(60));
+
+    factory.client("openai", minute, None).unwrap();
+    factory.client("openai", minute, None).unwrap();
+    factory.client("deepseek", minute, None).unwrap();
+    assert_eq!(factory.clients_built(), 1);
+
+    // A different timeout or user agent needs its own client
+    factory
+        .client("gemini", Some(Duration::from_secs(120)), None)
+        .unwrap();
+    factory
+        .client(
+            "document_loader",
+            minute,
+            Some("GraphBit Document Loader/1.0"),
+        )
+        .unwrap();
+    assert_eq!(factory.clients_built(), 3);
+
+    // Overrides take precedence over the component's defaults
+    factory.set_provider_settings(
+        "openai",
+        HttpSettings::default().with_timeout(Duration::from_secs(5)),
+    );
+    assert_eq!(
+        factory.settings_for("openai", minute, None).timeout,
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(
+        factory.settings_for("deepseek", minute, None).timeout,
+        minute
+    );
+    assert!(factory.clear_provider_settings("openai"));
+    assert_eq!(factory.settings_for("openai", minute, None).timeout, minute);
+
+    // Factory-wide settings apply to every component, keeping their default timeouts
+    factory.set_settings(
+        HttpSettings::default()
+            .with_pool_max_idle_per_host(32)
+            .with_default_header("X-Team", "search"),
+    );
+    let settings = factory.settings_for("openai", minute, None);
+    assert_eq!(settings.pool_max_idle_per_host, 32);
+    assert_eq!(settings.default_headers["X-Team"], "search");
+    assert_eq!(settings.timeout, minute);
+}
+
+#[test]
+fn test_invalid_settings_are_config_errors() {
+    let factory = HttpClientFactory::new(HttpSettings::default());
+    let err = factory
+        .client_with(&HttpSettings::default().with_default_header("bad header", "x"))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Invalid default header name"),
+        "{err}"
+    );
+    assert_eq!(factory.clients_built(), 0);
+}
+
+#[tokio::test]
+async fn test_providers_reuse_pooled_connections() {
+    let server = spawn_delayed_server(chat_completion(), Duration::ZERO).await;
+    let url = server.url();
+
+    // Both providers default to a 60 second timeout, so they share one client
+    let configs = [
+        LlmConfig::OpenAI {
+            api_key: "test".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            base_url: Some(url.clone()),
+            organization: None,
+            timeouts: Default::default(),
+        },
+        LlmConfig::DeepSeek {
+            api_key: "test".to_string(),
+            model: "deepseek-chat".to_string(),
+            base_url: Some(url.clone()),
+            timeouts: Default::default(),
+        },
+    ];
+    for config in configs {
+        let provider = LlmProviderFactory::create_provider(config).unwrap();
+        for _ in 0..3 {
+            let response = provider.complete(LlmRequest::new("ping")).await.unwrap();
+            assert_eq!(response.content, "pong");
+        }
+    }
+
+    assert_eq!(server.connections(), 1);
+}
+
+#[tokio::test]
+async fn test_timeouts_apply_per_component() {
+    let url = spawn_delayed_server("{}".to_string(), Duration::from_millis(500))
+        .await
+        .url();
+    let factory = HttpClientFactory::new(HttpSettings::default());
+
+    let impatient = factory
+        .client("impatient", Some(Duration::from_millis(100)), None)
+        .unwrap();
+    let err = impatient.get(&url).send().await.unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+
+    let patient = factory
+        .client("patient", Some(Duration::from_secs(10)), None)
+        .unwrap();
+    assert!(
+        patient
+            .get(&url)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .is_success()
+    );
+
+    // An override's timeout replaces the component's default
+    factory.set_provider_settings(
+        "patient",
+        HttpSettings::default().with_timeout(Duration::from_millis(100)),
+    );
+    let overridden = factory
+        .client("patient", Some(Duration::from_secs(10)), None)
+        .unwrap();
+    assert!(overridden.get(&url).send().await.unwrap_err().is_timeout());
+}
+
+#[test]
+fn test_executor_configures_global_factory() {
+    let _executor = WorkflowExecutor::new().with_provider_http_settings(
+        "http_client_tests.mock",
+        HttpSettings::default()
+            .with_timeout(Duration::from_secs(7))
+            .with_proxy("http://proxy.internal:3128"),
+    );
+
+    let settings = HttpClientFactory::global().settings_for("http_client_tests.mock", None, None);
+    assert_eq!(settings.timeout, Some(Duration::from_secs(7)));
+    assert_eq!(
+        settings.proxy.as_deref(),
+        Some("http://proxy.internal:3128")
+    );
+    assert!(
+        HttpClientFactory::global()
+            .client("http_client_tests.mock", None, None)
+            .is_ok()
+    );
+    assert!(HttpClientFactory::global().clear_provider_settings("http_client_tests.mock"));
+}
+
+#[tokio::test]
+async fn test_provider_requests_go_through_proxy() {
+    let proxy = MockServer::responding(MockResponse::json(200, chat_completion())).await;
+    let requests = proxy.requests();
+    let proxy = proxy.url();
+
+    // The API host does not resolve, so only the proxy can answer
+    HttpClientFactory::global()
+        .set_provider_settings("xai", HttpSettings::default().with_proxy(proxy.as_str()));
+    let provider = LlmProviderFactory::create_provider(LlmConfig::Xai {
+        api_key: "test".to_string(),
+        model: "grok-4".to_string(),
+        base_url: Some("http://api.x.invalid/v1".to_string()),
+        timeouts: Default::default(),
+    });
+    HttpClientFactory::global().clear_provider_settings("xai");
+
+    let response = provider
+        .unwrap()
+        .complete(LlmRequest::new("ping"))
+        .await
+        .unwrap();
+    assert_eq!(response.content, "pong");
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "http://api.x.invalid/v1/chat/completions");
+}
+
+#[tokio::test]
+async fn test_no_proxy_hosts_bypass_proxy() {
+    let server = spawn_delayed_server("{}".to_string(), Duration::ZERO).await;
+    let url = server.url();
+    let proxy = MockServer::responding(MockResponse::json(200, "{}")).await;
+    let requests = proxy.requests();
+    let proxy = proxy.url();
+    let factory = HttpClientFactory::new(HttpSettings::default());
+
+    let bypassing = factory
+        .client_with(
+            &HttpSettings::default()
+                .with_proxy(proxy.as_str())
+                .with_no_proxy("localhost,127.0.0.1"),
+        )
+        .unwrap();
+    assert!(
+        bypassing
+            .get(&url)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .is_success()
+    );
+    assert_eq!(server.connections(), 1);
+    assert!(requests.lock().unwrap().is_empty());
+
+    let proxied = factory
+        .client_with(
+            &HttpSettings::default()
+                .with_proxy(proxy.as_str())
+                .with_no_proxy("example.com"),
+        )
+        .unwrap();
+    assert!(
+        proxied
+            .get(&url)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .is_success()
+    );
+    assert_eq!(server.connections(), 1);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_ca_bundle_loading() {
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = dir.path().join("ca.pem");
+    std::fs::write(&bundle, TEST_CA).unwrap();
+    let settings = HttpSettings::default().with_ca_bundle(&bundle);
+    settings.validate().unwrap();
+    assert!(
+        HttpClientFactory::new(HttpSettings::default())
+            .client_with(&settings)
+            .is_ok()
+    );
+
+    let missing = dir.path().join("missing.pem");
+    let err = HttpSettings::default()
+        .with_ca_bundle(&missing)
+        .validate()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Failed to read CA bundle"),
+        "{err}"
+    );
+
+    let empty = dir.path().join("empty.pem");
+    std::fs::write(&empty, "not a certificate").unwrap();
+    let err = HttpSettings::default()
+        .with_ca_bundle(&empty)
+        .validate()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("contains no PEM certificates"),
+        "{err}"
+    );
+}
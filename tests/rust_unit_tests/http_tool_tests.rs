@@ -0,0 +1,140 @@
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::tools::{BuiltinTools, HttpToolConfig};
+
+/// Start an HTTP server that answers every request with `body`
+async fn spawn_mock_server(content_type: &'static str, body: String) -> String {
+    MockServer::responding(MockResponse::new(200, body).with_header("Content-Type", content_type))
+        .await
+        .url()
+}
+
+/// Start an HTTP server that redirects every request to `location`
+async fn spawn_redirect_server(location: String) -> String {
+    MockServer::responding(MockResponse::new(302, "").with_header("Location", location))
+        .await
+        .url()
+}
+
+#[test]
+fn test_http_tool_metadata_schema() {
+    let tool = BuiltinTools::http(HttpToolConfig::default());
+    let metadata = tool.metadata();
+    assert_eq!(metadata.name, "http_request");
+    assert_eq!(metadata.parameters["type"], "object");
+    assert_eq!(metadata.parameters["required"][0], "url");
+    for (_, property) in metadata.parameters["properties"].as_object().unwrap() {
+        assert!(property.get("type").is_some());
+    }
+
+    let llm_tool = metadata.to_llm_tool();
+    assert_eq!(llm_tool.name, "http_request");
+    assert_eq!(llm_tool.parameters, metadata.parameters);
+}
+
+#[tokio::test]
+async fn test_http_tool_allowlist_rejection() {
+    let url = spawn_mock_server("text/plain", "hello".to_string()).await;
+    let tool = BuiltinTools::http(
+        HttpToolConfig::default().with_allowed_hosts(vec!["example.com".to_string()]),
+    );
+
+    let err = tool
+        .execute(serde_json::json!({ "url": url }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("allowlist"));
+
+    assert!(tool.check_url("https://example.com/page").is_ok());
+    assert!(tool.check_url("https://api.example.com/page").is_ok());
+    assert!(tool.check_url("https://notexample.com/page").is_err());
+    assert!(tool.check_url("ftp://example.com/file").is_err());
+}
+
+#[tokio::test]
+async fn test_http_tool_denylist_takes_precedence() {
+    let tool = BuiltinTools::http(
+        HttpToolConfig::default()
+            .with_allowed_hosts(vec!["127.0.0.1".to_string()])
+            .with_denied_hosts(vec!["127.0.0.1".to_string()]),
+    );
+    let err = tool.check_url("http://127.0.0.1:8080/").unwrap_err();
+    assert!(err.to_string().contains("denied"));
+}
+
+#[tokio::test]
+async fn test_http_tool_truncates_response() {
+    let url = spawn_mock_server("text/plain", "a".repeat(1000)).await;
+    let tool = BuiltinTools::http(HttpToolConfig::default().with_max_response_bytes(100));
+
+    let result = tool
+        .execute(serde_json::json!({ "url": url, "method": "GET" }))
+        .await
+        .unwrap();
+    assert_eq!(result["status"], 200);
+    assert_eq!(result["truncated"], true);
+    assert_eq!(result["body"].as_str().unwrap().len(), 100);
+}
+
+#[tokio::test]
+async fn test_http_tool_extracts_html_text() {
+    let html = "<html><head><title>Mock Page</title></head><body><p>Hello from the mock server</p></body></html>";
+    let url = spawn_mock_server("text/html; charset=utf-8", html.to_string()).await;
+    let tool = BuiltinTools::http(HttpToolConfig::default());
+
+    let result = tool
+        .execute(serde_json::json!({ "url": url }))
+        .await
+        .unwrap();
+    let body = result["body"].as_str().unwrap();
+    assert_eq!(result["truncated"], false);
+    assert!(body.contains("Mock Page"));
+    assert!(body.contains("Hello from the mock server"));
+    assert!(!body.contains("<p>"));
+}
+
+#[tokio::test]
+async fn test_http_tool_rejects_unsupported_method() {
+    let tool = BuiltinTools::http(HttpToolConfig::default());
+    let err = tool
+        .execute(serde_json::json!({ "url": "http://127.0.0.1:1/", "method": "DELETE" }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Unsupported HTTP method"));
+}
+
+#[tokio::test]
+async fn test_http_tool_checks_redirects_against_host_lists() {
+    let target = spawn_mock_server("text/plain", "internal".to_string()).await;
+    let port = target.rsplit(':').next().unwrap();
+    let tool = BuiltinTools::http(
+        HttpToolConfig::default()
+            .with_allowed_hosts(vec!["127.0.0.1".to_string(), "localhost".to_string()])
+            .with_denied_hosts(vec!["localhost".to_string()]),
+    );
+
+    // An allowed host redirecting to a denied one fails without reaching it
+    let url = spawn_redirect_server(format!("http://localhost:{port}/")).await;
+    let err = tool
+        .execute(serde_json::json!({ "url": url }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("'localhost' is denied"), "{err}");
+
+    // Redirects to hosts outside the allowlist fail as well
+    let url = spawn_redirect_server("http://example.com/".to_string()).await;
+    let err = tool
+        .execute(serde_json::json!({ "url": url }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("allowlist"), "{err}");
+
+    // Redirects to allowed hosts are followed
+    let url = spawn_redirect_server(format!("{target}/")).await;
+    let result = tool
+        .execute(serde_json::json!({ "url": url }))
+        .await
+        .unwrap();
+    assert_eq!(result["status"], 200);
+    assert_eq!(result["body"], "internal");
+    assert!(result["url"].as_str().unwrap().starts_with(&target));
+}
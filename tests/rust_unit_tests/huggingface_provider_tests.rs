@@ -0,0 +1,244 @@
+//! `HuggingFace` provider against a mocked Inference Providers router and TGI server
+
+use super::test_helpers::{MockRequests, MockResponse, MockServer};
+use graphbit_core::errors::GraphBitError;
+use graphbit_core::llm::huggingface::HuggingFaceProvider;
+use graphbit_core::llm::{
+    FinishReason, LlmConfig, LlmProviderFactory, LlmProviderTrait, LlmRequest,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Start an HTTP server answering each path with a fixed status and JSON body
+/// (404 for other paths), recording every request it receives
+async fn spawn_mock_server(routes: HashMap<&'static str, (u16, Value)>) -> (String, MockRequests) {
+    let server = MockServer::new(move |request| {
+        let (status, body) = routes
+            .get(request.path.as_str())
+            .cloned()
+            .unwrap_or_else(|| (404, json!({"error": "Not Found"})));
+        std::future::ready(MockResponse::json(status, body))
+    })
+    .await;
+    (server.url(), server.requests())
+}
+
+fn chat_completion(content: &str) -> Value {
+    json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+    })
+}
+
+#[tokio::test]
+async fn test_router_chat_completion_with_provider_hint() {
+    let (url, received) = spawn_mock_server(HashMap::from([
+        ("/v1/chat/completions", (200, chat_completion("Paris"))),
+        (
+            "/meta-llama/Llama-3.1-8B-Instruct/resolve/main/config.json",
+            (200, json!({"max_position_embeddings": 131_072})),
+        ),
+    ]))
+    .await;
+
+    let provider = HuggingFaceProvider::with_base_url(
+        "hf_test".to_string(),
+        "meta-llama/Llama-3.1-8B-Instruct".to_string(),
+        format!("{url}/v1"),
+    )
+    .unwrap()
+    .with_inference_provider("together")
+    .with_hub_url(&url);
+    // Until the model card is read the context length is a guess
+    assert_eq!(provider.max_context_length(), Some(4096));
+    assert!(provider.supports_function_calling());
+
+    let response = provider
+        .complete(
+            LlmRequest::new("Capital of France?")
+                .with_max_tokens(16)
+                .with_temperature(0.5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.content, "Paris");
+    assert_eq!(response.model, "meta-llama/Llama-3.1-8B-Instruct");
+    assert_eq!(response.usage.prompt_tokens, 12);
+    assert_eq!(response.usage.completion_tokens, 3);
+    assert!(matches!(response.finish_reason, FinishReason::Stop));
+    assert_eq!(provider.max_context_length(), Some(131_072));
+
+    let received = received.lock().unwrap().clone();
+    let chat = received
+        .iter()
+        .find(|r| r.path == "/v1/chat/completions")
+        .unwrap();
+    assert_eq!(chat.header("authorization"), Some("Bearer hf_test"));
+    assert_eq!(
+        chat.json()["model"],
+        json!("meta-llama/Llama-3.1-8B-Instruct:together")
+    );
+    assert_eq!(
+        chat.json()["messages"],
+        json!([{"role": "user", "content": "Capital of France?"}])
+    );
+    assert_eq!(chat.json()["max_tokens"], json!(16));
+}
+
+#[tokio::test]
+async fn test_unsupported_model_error_names_task() {
+    let (url, _) = spawn_mock_server(HashMap::from([(
+        "/v1/chat/completions",
+        (
+            400,
+            json!({"error": {
+                "message": "Model distilgpt2 is not supported for task text-generation and provider hf-inference. Supported task: fill-mask.",
+                "type": "invalid_request_error",
+                "code": "model_not_supported"
+            }}),
+        ),
+    )]))
+    .await;
+
+    let provider = HuggingFaceProvider::with_base_url(
+        "hf_test".to_string(),
+        "distilgpt2".to_string(),
+        format!("{url}/v1"),
+    )
+    .unwrap()
+    .with_inference_provider("hf-inference")
+    .with_hub_url(&url);
+    let err = provider
+        .complete(LlmRequest::new("Hello"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, GraphBitError::LlmProvider { .. }), "{err:?}");
+    let message = err.to_string();
+    assert!(
+        message.contains("Model 'distilgpt2' is not supported for task 'text-generation'"),
+        "{message}"
+    );
+    assert!(
+        message.contains("inference provider 'hf-inference'"),
+        "{message}"
+    );
+    assert!(message.contains("text_generation"), "{message}");
+}
+
+#[tokio::test]
+async fn test_other_api_errors_pass_through() {
+    let (url, _) = spawn_mock_server(HashMap::from([(
+        "/v1/chat/completions",
+        (
+            401,
+            json!({"error": "Invalid credentials in Authorization header"}),
+        ),
+    )]))
+    .await;
+
+    let provider = HuggingFaceProvider::with_base_url(
+        "bad".to_string(),
+        "Qwen/Qwen2.5-7B-Instruct".to_string(),
+        format!("{url}/v1"),
+    )
+    .unwrap()
+    .with_hub_url(&url);
+    let message = provider
+        .complete(LlmRequest::new("Hello"))
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(
+        message.contains("API error (401 Unauthorized): Invalid credentials"),
+        "{message}"
+    );
+    // A missing model card leaves the fallback context length in place
+    assert_eq!(provider.max_context_length(), Some(2048));
+}
+
+#[tokio::test]
+async fn test_tgi_text_generation_endpoint() {
+    let (url, received) = spawn_mock_server(HashMap::from([
+        (
+            "/generate",
+            (
+                200,
+                json!({
+                    "generated_text": " Hello from TGI",
+                    "details": {"finish_reason": "length", "generated_tokens": 7}
+                }),
+            ),
+        ),
+        (
+            "/info",
+            (200, json!({"model_id": "gpt2", "max_total_tokens": 1024})),
+        ),
+    ]))
+    .await;
+
+    let provider =
+        LlmProviderFactory::create_provider(LlmConfig::huggingface_tgi("", "gpt2", &url)).unwrap();
+    assert!(!provider.supports_function_calling());
+
+    let response = provider
+        .complete(LlmRequest::new("Say hello").with_max_tokens(7))
+        .await
+        .unwrap();
+    assert_eq!(response.content, "Hello from TGI");
+    assert_eq!(response.usage.completion_tokens, 7);
+    assert!(matches!(response.finish_reason, FinishReason::Length));
+    assert_eq!(provider.max_context_length(), Some(1024));
+
+    let received = received.lock().unwrap().clone();
+    let generate = received.iter().find(|r| r.path == "/generate").unwrap();
+    // No API key, no Authorization header
+    assert_eq!(generate.header("authorization"), None);
+    assert_eq!(
+        generate.json()["inputs"],
+        json!("User: Say hello\nAssistant: ")
+    );
+    assert_eq!(generate.json()["parameters"]["max_new_tokens"], json!(7));
+    assert_eq!(
+        generate.json()["parameters"]["return_full_text"],
+        json!(false)
+    );
+}
+
+#[test]
+fn test_text_generation_requires_base_url() {
+    let config = LlmConfig::HuggingFace {
+        api_key: "hf_test".to_string(),
+        model: "gpt2".to_string(),
+        base_url: None,
+        inference_provider: None,
+        text_generation: true,
+        timeouts: Default::default(),
+    };
+    let err = LlmProviderFactory::create_provider(config).err().unwrap();
+    assert!(err.to_string().contains("requires a base_url"), "{err}");
+
+    // Configurations saved before the router fields existed still load
+    let config: LlmConfig = serde_json::from_value(json!({
+        "provider": "HuggingFace",
+        "api_key": "hf_test",
+        "model": "gpt2",
+        "base_url": null
+    }))
+    .unwrap();
+    assert!(matches!(
+        config,
+        LlmConfig::HuggingFace {
+            inference_provider: None,
+            text_generation: false,
+            ..
+        }
+    ));
+}
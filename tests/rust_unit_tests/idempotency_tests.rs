@@ -0,0 +1,215 @@
+//! Idempotency keys: stable across a node's retries, distinct across nodes and executions
+
+use super::test_helpers::{MockRequests, MockResponse, MockServer, ScriptedProvider, TestAgent};
+use graphbit_core::{
+    GraphBitError,
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmToolCall},
+    tools::{ToolCallContext, ToolHandler, ToolMetadata, ToolRegistry},
+    types::{
+        AgentId, NodeExecutionResult, NodeId, RetryConfig, RetryableErrorType, WorkflowContext,
+        WorkflowState,
+    },
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Start an HTTP server answering with 503 until the third request
+async fn spawn_recording_server() -> (String, MockRequests) {
+    let server = MockServer::replaying(vec![
+        MockResponse::new(503, "unavailable"),
+        MockResponse::new(503, "unavailable"),
+        MockResponse::new(200, r#"{"ok":true}"#),
+    ])
+    .await;
+    (format!("{}/charge", server.url()), server.requests())
+}
+
+fn retry_config(retryable: RetryableErrorType) -> RetryConfig {
+    RetryConfig::new(4)
+        .with_exponential_backoff(10, 1.0, 10)
+        .with_jitter(0.0)
+        .with_retryable_errors(vec![retryable])
+}
+
+fn http_node(name: &str, url: String) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Charge a card",
+        NodeType::HttpRequest {
+            url,
+            method: "POST".to_string(),
+            headers: HashMap::new(),
+        },
+    )
+    .with_retry_config(retry_config(RetryableErrorType::TemporaryUnavailable))
+}
+
+/// Idempotency key recorded for the node `name`
+fn recorded_key(context: &WorkflowContext, name: &str) -> String {
+    context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == name)
+        .and_then(|record| record.idempotency_key.clone())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_http_retries_send_the_same_idempotency_key() {
+    let (url, requests) = spawn_recording_server().await;
+    let mut workflow = Workflow::new("charge", "");
+    workflow.add_node(http_node("charge", url)).unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let key = recorded_key(&context, "charge");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    for request in requests.iter() {
+        assert_eq!(request.header("idempotency-key"), Some(key.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn test_http_idempotency_header_is_configurable() {
+    let (url, requests) = spawn_recording_server().await;
+    let mut workflow = Workflow::new("charge", "");
+    let mut node = http_node("charge", url).with_retry_config(RetryConfig::new(0));
+    node.config
+        .insert("idempotency_header".to_string(), json!("X-Request-Key"));
+    workflow.add_node(node).unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    let key = recorded_key(&context, "charge");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0].header("x-request-key"), Some(key.as_str()));
+    assert!(requests[0].header("idempotency-key").is_none());
+}
+
+#[tokio::test]
+async fn test_keys_differ_across_nodes_and_executions() {
+    let mut workflow = Workflow::new("keys", "");
+    for name in ["first", "second"] {
+        workflow
+            .add_node(WorkflowNode::new(
+                name,
+                "",
+                NodeType::Transform {
+                    transformation: "1".to_string(),
+                },
+            ))
+            .unwrap();
+    }
+    let executor = WorkflowExecutor::new();
+
+    let run_one = executor.execute(workflow.clone(), None).await.unwrap();
+    let run_two = executor.execute(workflow, None).await.unwrap();
+
+    let keys = [
+        recorded_key(&run_one, "first"),
+        recorded_key(&run_one, "second"),
+        recorded_key(&run_two, "first"),
+        recorded_key(&run_two, "second"),
+    ];
+    for (i, key) in keys.iter().enumerate() {
+        assert!(!keys[i + 1..].contains(key), "{keys:?}");
+    }
+}
+
+#[test]
+fn test_previous_attempt_key_is_only_set_after_a_retry() {
+    let first = NodeExecutionResult::success(json!(1), NodeId::new()).with_idempotency_key("key");
+    assert_eq!(first.previous_attempt_key(), None);
+
+    let retried = first.with_retry_count(1);
+    assert_eq!(retried.previous_attempt_key(), Some("key"));
+}
+
+fn charge_call() -> LlmResponse {
+    LlmResponse::new("", "scripted-model").with_tool_calls(vec![LlmToolCall {
+        id: "call_charge".to_string(),
+        name: "charge".to_string(),
+        parameters: json!({}),
+    }])
+}
+
+#[tokio::test]
+async fn test_tools_see_the_node_key_on_every_attempt() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let registry = ToolRegistry::new();
+    let recorder = seen.clone();
+    let handler: ToolHandler = Arc::new(move |_args: serde_json::Value| {
+        let recorder = recorder.clone();
+        Box::pin(async move {
+            recorder.lock().unwrap().push(ToolCallContext::current());
+            Ok(json!("charged"))
+        })
+    });
+    registry
+        .register_tool(
+            ToolMetadata::new("charge", "Charge a card", json!({"type": "object"})),
+            handler,
+        )
+        .unwrap();
+
+    // The first attempt fails after its tool call, so the node is retried
+    let agent_id = AgentId::new();
+    let provider = ScriptedProvider::replaying_results(vec![
+        Ok(charge_call()),
+        Err(GraphBitError::llm_provider("scripted", "connection reset")),
+        Ok(charge_call()),
+        Ok(LlmResponse::new("charged once", "scripted-model")),
+    ]);
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Cashier", "Charges cards", LlmConfig::default())
+            .with_id(agent_id.clone()),
+        provider,
+    ));
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_tool_registry(registry);
+    executor.register_agent(agent).await;
+
+    let mut workflow = Workflow::new("cashier", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "cashier",
+                "",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(agent_id, "Charge the card"),
+                },
+            )
+            .with_tools(["charge"])
+            .with_retry_config(retry_config(RetryableErrorType::NetworkError)),
+        )
+        .unwrap();
+    let context = executor.execute(workflow, None).await.unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let key = recorded_key(&context, "cashier");
+    let seen = seen.lock().unwrap();
+    assert_eq!(
+        *seen,
+        vec![
+            Some(ToolCallContext::new("cashier", key.clone(), 0)),
+            Some(ToolCallContext::new("cashier", key.clone(), 1)),
+        ]
+    );
+    assert_eq!(seen[1].as_ref().unwrap().previous_attempt_key, Some(key));
+    assert_eq!(ToolCallContext::current(), None);
+}
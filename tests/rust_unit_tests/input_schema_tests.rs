@@ -0,0 +1,167 @@
+//! Workflow input schemas: inputs checked before any node runs, extra inputs,
+//! and the schema in the builder, JSON and dry-run report
+
+use graphbit_core::{
+    errors::GraphBitError,
+    graph::{NodeType, WorkflowNode},
+    types::{Secrets, WorkflowContext},
+    workflow::{Workflow, WorkflowBuilder, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn schema(additional_properties: bool) -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "question": {"type": "string"},
+            "doc_path": {"type": "string"}
+        },
+        "required": ["question", "doc_path"],
+        "additionalProperties": additional_properties
+    })
+}
+
+fn workflow(schema: serde_json::Value) -> Workflow {
+    let (builder, _) = WorkflowBuilder::new("qa")
+        .input_schema(schema)
+        .add_node(WorkflowNode::new(
+            "answer",
+            "",
+            NodeType::Transform {
+                transformation: "vars.question + ' in ' + vars.doc_path".to_string(),
+            },
+        ))
+        .unwrap();
+    builder.build().unwrap()
+}
+
+async fn run(
+    workflow: Workflow,
+    inputs: serde_json::Value,
+) -> Result<WorkflowContext, GraphBitError> {
+    let inputs: HashMap<String, serde_json::Value> = serde_json::from_value(inputs).unwrap();
+    WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_inputs(workflow, None, inputs, Secrets::default())
+        .await
+}
+
+fn validation_message(result: Result<WorkflowContext, GraphBitError>) -> String {
+    match result {
+        Err(GraphBitError::Validation { field, message }) => {
+            assert_eq!(field, "inputs");
+            message
+        }
+        other => panic!("expected an input validation error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_matching_inputs_run() {
+    let context = run(
+        workflow(schema(false)),
+        json!({"question": "Who?", "doc_path": "a.pdf"}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(context.load_node_outputs()["answer"], "Who? in a.pdf");
+}
+
+#[tokio::test]
+async fn test_missing_required_inputs_are_listed() {
+    let message = validation_message(run(workflow(schema(false)), json!({})).await);
+    assert!(message.contains("workflow 'qa'"), "{message}");
+    assert!(
+        message.contains("question: Required property 'question' is missing"),
+        "{message}"
+    );
+    assert!(
+        message.contains("doc_path: Required property 'doc_path' is missing"),
+        "{message}"
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_types_are_listed() {
+    let message = validation_message(
+        run(
+            workflow(schema(false)),
+            json!({"question": 42, "doc_path": "a.pdf"}),
+        )
+        .await,
+    );
+    assert!(
+        message.contains("question: Type mismatch (expected string, got "),
+        "{message}"
+    );
+    assert!(!message.contains("doc_path"), "{message}");
+}
+
+#[tokio::test]
+async fn test_extra_inputs_follow_additional_properties() {
+    let inputs = json!({"question": "Who?", "doc_path": "a.pdf", "locale": "en"});
+
+    let message = validation_message(run(workflow(schema(false)), inputs.clone()).await);
+    assert!(
+        message.contains("locale: Unknown property 'locale'"),
+        "{message}"
+    );
+
+    // Undeclared inputs are accepted unless the schema forbids them
+    assert!(run(workflow(schema(true)), inputs.clone()).await.is_ok());
+    let mut open = schema(true);
+    open.as_object_mut().unwrap().remove("additionalProperties");
+    assert!(run(workflow(open), inputs).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_workflow_without_schema_accepts_any_inputs() {
+    let mut workflow = workflow(schema(false));
+    workflow.input_schema = None;
+    assert!(
+        run(workflow, json!({"question": 1, "doc_path": 2}))
+            .await
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_input_schema_must_be_an_object_schema() {
+    let mut workflow = Workflow::new("qa", "");
+    assert!(matches!(
+        workflow.set_input_schema(json!(["question"])),
+        Err(GraphBitError::Validation { .. })
+    ));
+    assert!(matches!(
+        workflow.set_input_schema(json!({"type": "string"})),
+        Err(GraphBitError::Validation { .. })
+    ));
+    assert!(workflow.input_schema.is_none());
+
+    let (builder, _) = WorkflowBuilder::new("qa")
+        .input_schema(json!({"type": "array"}))
+        .add_node(WorkflowNode::new(
+            "answer",
+            "",
+            NodeType::Transform {
+                transformation: "'yes'".to_string(),
+            },
+        ))
+        .unwrap();
+    assert!(builder.build().is_err());
+}
+
+#[tokio::test]
+async fn test_schema_survives_json_and_appears_in_dry_run() {
+    let workflow = workflow(schema(false));
+    let loaded = Workflow::from_json(&workflow.to_json().unwrap()).unwrap();
+    assert_eq!(loaded.input_schema, Some(schema(false)));
+
+    let report = WorkflowExecutor::new().dry_run(&loaded).await.unwrap();
+    assert_eq!(report.input_schema, Some(schema(false)));
+
+    // Workflows without a schema serialize without the field
+    let plain = Workflow::new("plain", "");
+    assert!(!plain.to_json().unwrap().contains("input_schema"));
+}
@@ -0,0 +1,244 @@
+//! Join policies: waiting for all, any or a quorum of parents
+
+use graphbit_core::{
+    graph::{JoinPolicy, NodeType, PendingBranches, WorkflowEdge, WorkflowNode},
+    types::{AbandonedNode, SkipReason, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+fn delay(name: &str, seconds: u64) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Delay {
+            duration_seconds: seconds,
+        },
+    )
+}
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+fn join(policy: JoinPolicy, pending_branches: PendingBranches) -> WorkflowNode {
+    WorkflowNode::new("join", "", NodeType::Join)
+        .with_join_policy(policy)
+        .with_pending_branches(pending_branches)
+}
+
+/// Workflow with the given nodes and `(from, to)` edges between node names
+fn workflow(nodes: Vec<WorkflowNode>, edges: &[(&str, &str)]) -> Workflow {
+    let mut workflow = Workflow::new("join_policy", "");
+    for node in nodes {
+        workflow.add_node(node).unwrap();
+    }
+    for (from, to) in edges {
+        let from = workflow.graph.get_node_id_by_name(from).unwrap();
+        let to = workflow.graph.get_node_id_by_name(to).unwrap();
+        workflow
+            .connect_nodes(from, to, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+/// Run `workflow`, returning its context and how long the run took
+async fn run(workflow: Workflow) -> (WorkflowContext, Duration) {
+    let started = Instant::now();
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+    (context, started.elapsed())
+}
+
+#[tokio::test]
+async fn test_any_cancels_the_slow_branch() {
+    let race = workflow(
+        vec![
+            transform("source", "'question'"),
+            delay("fast", 0),
+            delay("slow", 5),
+            delay("slow_next", 0),
+            join(JoinPolicy::Any, PendingBranches::Cancel),
+            transform("after", "input.fast + '!'"),
+        ],
+        &[
+            ("source", "fast"),
+            ("source", "slow"),
+            ("slow", "slow_next"),
+            ("fast", "join"),
+            ("slow_next", "join"),
+            ("join", "after"),
+        ],
+    );
+
+    let (context, elapsed) = run(race).await;
+    assert!(elapsed < Duration::from_secs(3), "took {elapsed:?}");
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("join"),
+        Some(&json!({"fast": "question"}))
+    );
+    assert_eq!(context.get_node_output("after"), Some(&json!("question!")));
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([
+            ("slow".to_string(), AbandonedNode::Cancelled),
+            (
+                "slow_next".to_string(),
+                AbandonedNode::Skipped {
+                    reason: SkipReason::Join
+                }
+            ),
+        ])
+    );
+    assert!(context.get_node_output("slow").is_none());
+}
+
+#[tokio::test]
+async fn test_quorum_continues_once_enough_parents_succeed() {
+    let vote = workflow(
+        vec![
+            delay("first", 0),
+            delay("second", 1),
+            delay("third", 6),
+            join(JoinPolicy::Quorum(2), PendingBranches::Cancel),
+        ],
+        &[("first", "join"), ("second", "join"), ("third", "join")],
+    );
+
+    let (context, elapsed) = run(vote).await;
+    assert!(elapsed >= Duration::from_secs(1), "took {elapsed:?}");
+    assert!(elapsed < Duration::from_secs(4), "took {elapsed:?}");
+    assert_eq!(
+        context.get_node_output("join"),
+        Some(&json!({"first": null, "second": null}))
+    );
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([("third".to_string(), AbandonedNode::Cancelled)])
+    );
+}
+
+#[tokio::test]
+async fn test_detached_branches_are_not_waited_for() {
+    let race = workflow(
+        vec![
+            delay("fast", 0),
+            delay("slow", 5),
+            join(JoinPolicy::Any, PendingBranches::Detach),
+        ],
+        &[("fast", "join"), ("slow", "join")],
+    );
+
+    let (context, elapsed) = run(race).await;
+    assert!(elapsed < Duration::from_secs(3), "took {elapsed:?}");
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([("slow".to_string(), AbandonedNode::Detached)])
+    );
+    assert!(context.get_node_output("slow").is_none());
+    assert!(
+        context
+            .get_node_executions()
+            .iter()
+            .all(|record| record.node_name != "slow")
+    );
+}
+
+#[tokio::test]
+async fn test_all_waits_for_every_parent() {
+    let both = workflow(
+        vec![
+            delay("fast", 0),
+            delay("slow", 1),
+            WorkflowNode::new("join", "", NodeType::Join),
+        ],
+        &[("fast", "join"), ("slow", "join")],
+    );
+
+    let (context, elapsed) = run(both).await;
+    assert!(elapsed >= Duration::from_secs(1), "took {elapsed:?}");
+    assert_eq!(
+        context.get_node_output("join"),
+        Some(&json!({"fast": null, "slow": null}))
+    );
+    assert!(context.abandoned_nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_parents_do_not_count_towards_the_quorum() {
+    let vote = workflow(
+        vec![
+            transform("good", "'yes'"),
+            transform("bad", "missing_name"),
+            join(JoinPolicy::Quorum(2), PendingBranches::Cancel),
+        ],
+        &[("good", "join"), ("bad", "join")],
+    );
+
+    let (context, _) = run(vote).await;
+    let join = context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == "join")
+        .unwrap();
+    assert!(!join.success);
+    let error = join.error.as_deref().unwrap();
+    assert!(
+        error.contains("needs 2 successful parents, 1 succeeded"),
+        "{error}"
+    );
+}
+
+#[test]
+fn test_invalid_policies_are_rejected() {
+    let too_few = workflow(
+        vec![
+            delay("only", 0),
+            join(JoinPolicy::Quorum(2), PendingBranches::Cancel),
+        ],
+        &[("only", "join")],
+    );
+    let err = too_few.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("Join node 'join' has 1 parents, fewer than its policy requires (2)"),
+        "{err}"
+    );
+
+    let zero = workflow(
+        vec![
+            delay("only", 0),
+            join(JoinPolicy::Quorum(0), PendingBranches::Cancel),
+        ],
+        &[("only", "join")],
+    );
+    let err = zero.validate().unwrap_err().to_string();
+    assert!(err.contains("A quorum must be at least 1"), "{err}");
+
+    let unknown = workflow(
+        vec![
+            delay("only", 0),
+            WorkflowNode::new("join", "", NodeType::Join)
+                .with_config("pending_branches".to_string(), json!("abandon")),
+        ],
+        &[("only", "join")],
+    );
+    let err = unknown.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("Unknown pending branch handling 'abandon'"),
+        "{err}"
+    );
+}
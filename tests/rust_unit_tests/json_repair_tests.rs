@@ -0,0 +1,319 @@
+//! JSON recovery from malformed model output: a fixture for each kind of
+//! damage, checked for the recovered value and the repairs reported
+
+use graphbit_core::json_repair::{JsonRepair, repair_json};
+use serde_json::{Value, json};
+
+use JsonRepair::{
+    ClosedBrackets, ClosedString, ExtractedFromProse, RemovedTrailingCommas, StrippedCodeFence,
+};
+
+/// Model output, with the value it should be recovered as and the repairs
+/// reported along the way
+struct Fixture {
+    name: &'static str,
+    input: &'static str,
+    expected: Value,
+    repairs: &'static [JsonRepair],
+}
+
+fn fixture(
+    name: &'static str,
+    input: &'static str,
+    expected: Value,
+    repairs: &'static [JsonRepair],
+) -> Fixture {
+    Fixture {
+        name,
+        input,
+        expected,
+        repairs,
+    }
+}
+
+fn valid_fixtures() -> Vec<Fixture> {
+    vec![
+        fixture("object", r#"{"a": 1}"#, json!({"a": 1}), &[]),
+        fixture("padded array", "  [1, 2, 3]\n", json!([1, 2, 3]), &[]),
+        fixture("string", r#""just text""#, json!("just text"), &[]),
+        fixture("number", "42", json!(42), &[]),
+        fixture(
+            "commas and brackets inside strings",
+            r#"{"a": "x,]", "b": "{,}"}"#,
+            json!({"a": "x,]", "b": "{,}"}),
+            &[],
+        ),
+    ]
+}
+
+fn fence_fixtures() -> Vec<Fixture> {
+    vec![
+        fixture(
+            "json fence",
+            "```json\n{\"a\": 1}\n```",
+            json!({"a": 1}),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "bare fence",
+            "```\n[1, 2]\n```",
+            json!([1, 2]),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "uppercase info string and CRLF",
+            "```JSON\r\n{\"a\": 1}\r\n```",
+            json!({"a": 1}),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "fence left open",
+            "```json\n{\"a\": 1}\n",
+            json!({"a": 1}),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "fence between prose",
+            "Here is the result:\n```json\n{\"a\": 1}\n```\nLet me know if you need more!",
+            json!({"a": 1}),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "fenced string",
+            "```json\n\"ok\"\n```",
+            json!("ok"),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "fence on one line",
+            "```{\"a\": 1}```",
+            json!({"a": 1}),
+            &[StrippedCodeFence],
+        ),
+        fixture(
+            "fenced trailing comma",
+            "```json\n{\"tags\": [\"math\",],}\n```",
+            json!({"tags": ["math"]}),
+            &[StrippedCodeFence, RemovedTrailingCommas],
+        ),
+        fixture(
+            "fenced truncation",
+            "```json\n{\"a\": [1, 2",
+            json!({"a": [1, 2]}),
+            &[StrippedCodeFence, ClosedBrackets],
+        ),
+    ]
+}
+
+fn prose_fixtures() -> Vec<Fixture> {
+    vec![
+        fixture(
+            "object in a sentence",
+            r#"The answer is {"a": 1}."#,
+            json!({"a": 1}),
+            &[ExtractedFromProse],
+        ),
+        fixture(
+            "array before prose",
+            "[1, 2] as requested",
+            json!([1, 2]),
+            &[ExtractedFromProse],
+        ),
+        fixture(
+            "nested value",
+            r#"Sure! {"a": {"b": [1, {"c": null}]}} Hope that helps"#,
+            json!({"a": {"b": [1, {"c": null}]}}),
+            &[ExtractedFromProse],
+        ),
+        fixture(
+            "brackets in prose before the value",
+            r#"See [note] below: {"a": 1}"#,
+            json!({"a": 1}),
+            &[ExtractedFromProse],
+        ),
+        fixture(
+            "brackets inside strings",
+            r#"Output: {"a": "}{", "b": "]"} done"#,
+            json!({"a": "}{", "b": "]"}),
+            &[ExtractedFromProse],
+        ),
+        fixture(
+            "first of two values",
+            r#"{"a": 1} and then {"b": 2}"#,
+            json!({"a": 1}),
+            &[ExtractedFromProse],
+        ),
+        fixture(
+            "non-ASCII prose",
+            r#"Résumé parsed: {"name": "Zoë",}"#,
+            json!({"name": "Zoë"}),
+            &[ExtractedFromProse, RemovedTrailingCommas],
+        ),
+        fixture(
+            "truncated after prose",
+            r#"Here: {"a": [1, 2"#,
+            json!({"a": [1, 2]}),
+            &[ExtractedFromProse, ClosedBrackets],
+        ),
+    ]
+}
+
+fn trailing_comma_fixtures() -> Vec<Fixture> {
+    vec![
+        fixture(
+            "object",
+            r#"{"a": 1,}"#,
+            json!({"a": 1}),
+            &[RemovedTrailingCommas],
+        ),
+        fixture(
+            "array",
+            "[1, 2, 3,]",
+            json!([1, 2, 3]),
+            &[RemovedTrailingCommas],
+        ),
+        fixture(
+            "nested",
+            r#"{"a": [1, 2,], "b": {"c": 3,},}"#,
+            json!({"a": [1, 2], "b": {"c": 3}}),
+            &[RemovedTrailingCommas],
+        ),
+        fixture(
+            "whitespace around the comma",
+            "{\"a\": 1 ,\n\t}",
+            json!({"a": 1}),
+            &[RemovedTrailingCommas],
+        ),
+        fixture(
+            "comma inside a string kept",
+            r#"{"a": "x,]", "b": 2,}"#,
+            json!({"a": "x,]", "b": 2}),
+            &[RemovedTrailingCommas],
+        ),
+    ]
+}
+
+fn truncation_fixtures() -> Vec<Fixture> {
+    vec![
+        fixture(
+            "open array",
+            r#"{"a": 1, "b": [1, 2"#,
+            json!({"a": 1, "b": [1, 2]}),
+            &[ClosedBrackets],
+        ),
+        fixture(
+            "open string",
+            r#"{"a": "cut off"#,
+            json!({"a": "cut off"}),
+            &[ClosedString, ClosedBrackets],
+        ),
+        fixture(
+            "after a comma",
+            r#"{"a": 1,"#,
+            json!({"a": 1}),
+            &[RemovedTrailingCommas, ClosedBrackets],
+        ),
+        fixture(
+            "after a colon",
+            r#"{"a": 1, "b":"#,
+            json!({"a": 1, "b": null}),
+            &[ClosedBrackets],
+        ),
+        fixture(
+            "inside a key",
+            r#"{"a": 1, "bo"#,
+            json!({"a": 1, "bo": null}),
+            &[ClosedString, ClosedBrackets],
+        ),
+        fixture(
+            "dangling escape",
+            r#"["x", "y\"#,
+            json!(["x", "y"]),
+            &[ClosedString, ClosedBrackets],
+        ),
+        fixture(
+            "escaped quotes in the open string",
+            r#"{"a": "say \"hi\""#,
+            json!({"a": "say \"hi\""}),
+            &[ClosedString, ClosedBrackets],
+        ),
+        fixture(
+            "array of objects",
+            r#"[{"a": 1}, {"b": 2"#,
+            json!([{"a": 1}, {"b": 2}]),
+            &[ClosedBrackets],
+        ),
+        fixture(
+            "deeply nested",
+            r#"{"a": {"b": {"c": ["d""#,
+            json!({"a": {"b": {"c": ["d"]}}}),
+            &[ClosedBrackets],
+        ),
+    ]
+}
+
+fn check(fixtures: Vec<Fixture>) {
+    for fixture in fixtures {
+        let repaired = repair_json(fixture.input)
+            .unwrap_or_else(|| panic!("{}: nothing recovered", fixture.name));
+        assert_eq!(repaired.value, fixture.expected, "{}", fixture.name);
+        assert_eq!(repaired.repairs, fixture.repairs, "{}", fixture.name);
+    }
+}
+
+#[test]
+fn test_valid_json_is_unchanged() {
+    check(valid_fixtures());
+}
+
+#[test]
+fn test_code_fences() {
+    check(fence_fixtures());
+}
+
+#[test]
+fn test_value_in_prose() {
+    check(prose_fixtures());
+}
+
+#[test]
+fn test_trailing_commas() {
+    check(trailing_comma_fixtures());
+}
+
+#[test]
+fn test_truncated_output() {
+    check(truncation_fixtures());
+}
+
+#[test]
+fn test_unrecoverable_output() {
+    for input in [
+        "",
+        "   ",
+        "no json here",
+        "[citation needed]",
+        "{not: json}",
+        "{'a': 1}",
+        "```\nstill nothing\n```",
+        "Use the { character",
+    ] {
+        assert_eq!(repair_json(input), None, "{input:?}");
+    }
+}
+
+#[test]
+fn test_repair_names() {
+    for repair in [
+        StrippedCodeFence,
+        ExtractedFromProse,
+        RemovedTrailingCommas,
+        ClosedString,
+        ClosedBrackets,
+    ] {
+        assert_eq!(
+            serde_json::to_value(repair).unwrap(),
+            json!(repair.as_str())
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Middleware stacked on providers and on the workflow executor
+
+use super::test_helpers::{Requests, ScriptedProvider, TestAgent};
+use async_trait::async_trait;
+use graphbit_core::{
+    agents::AgentConfig,
+    errors::{GraphBitError, GraphBitResult},
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{
+        LlmConfig, LlmMessage, LlmMiddleware, LlmMiddlewareStack, LlmProviderFactory,
+        LlmProviderTrait, LlmRequest, LlmResponse, MiddlewareProvider, RedactionMiddleware,
+    },
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// Middleware recording its hook calls in a shared log and tagging requests
+/// and responses with its name
+struct Tagging {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+    reject: bool,
+}
+
+#[async_trait]
+impl LlmMiddleware for Tagging {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn before_request(&self, request: &mut LlmRequest) -> GraphBitResult<()> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{}.before", self.name));
+        if self.reject {
+            return Err(GraphBitError::validation("request", "rejected by policy"));
+        }
+        let message = request.messages.last_mut().unwrap();
+        message.content.push_str(&format!(" [{}]", self.name));
+        request
+            .extra_params
+            .insert("seen_by".to_string(), json!(self.name));
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        _request: &LlmRequest,
+        response: &mut LlmResponse,
+    ) -> GraphBitResult<()> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{}.after", self.name));
+        response.content.push_str(&format!(" <{}>", self.name));
+        Ok(())
+    }
+
+    async fn on_error(&self, _request: &LlmRequest, error: &GraphBitError) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{}.error: {error}", self.name));
+    }
+}
+
+fn tagging(
+    names: &[&'static str],
+    reject: Option<&str>,
+) -> (LlmMiddlewareStack, Arc<Mutex<Vec<String>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let stack = names
+        .iter()
+        .map(|name| -> Arc<dyn LlmMiddleware> {
+            Arc::new(Tagging {
+                name,
+                log: log.clone(),
+                reject: reject == Some(*name),
+            })
+        })
+        .collect();
+    (stack, log)
+}
+
+/// Provider echoing the last message of each request, or failing every
+/// request when `fail` is set
+fn echo_provider(fail: bool) -> (ScriptedProvider, Requests) {
+    let provider = ScriptedProvider::new(move |request| {
+        if fail {
+            return Err(GraphBitError::llm_provider("echo", "upstream unavailable"));
+        }
+        let content = &request.messages.last().unwrap().content;
+        Ok(LlmResponse::new(format!("echo: {content}"), "echo-model"))
+    });
+    let requests = provider.requests();
+    (provider, requests)
+}
+
+#[tokio::test]
+async fn test_middleware_runs_in_registration_order() {
+    let (provider, requests) = echo_provider(false);
+    let (stack, log) = tagging(&["first", "second"], None);
+    let provider = MiddlewareProvider::new(Box::new(provider), stack);
+
+    let response = provider.complete(LlmRequest::new("hello")).await.unwrap();
+
+    assert_eq!(
+        log.lock().unwrap().as_slice(),
+        [
+            "first.before",
+            "second.before",
+            "first.after",
+            "second.after"
+        ]
+    );
+    // The provider sees the request as modified by every middleware in turn
+    let sent = requests.lock().unwrap()[0].clone();
+    assert_eq!(sent.messages[0].content, "hello [first] [second]");
+    assert_eq!(sent.extra_params["seen_by"], json!("second"));
+    assert_eq!(
+        response.content,
+        "echo: hello [first] [second] <first> <second>"
+    );
+}
+
+#[tokio::test]
+async fn test_rejecting_middleware_aborts_the_call() {
+    let (provider, requests) = echo_provider(false);
+    let (stack, log) = tagging(&["first", "guard", "last"], Some("guard"));
+
+    let err = stack
+        .complete(&provider, LlmRequest::new("hello"))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("rejected by policy"), "{err}");
+    assert!(requests.lock().unwrap().is_empty());
+    let log = log.lock().unwrap().clone();
+    assert_eq!(&log[..2], ["first.before", "guard.before"]);
+    // Every middleware observes the error, including those after the failing one
+    assert_eq!(log.len(), 5);
+    assert!(log[2..].iter().all(|entry| entry.contains(".error: ")));
+    assert!(log[4].starts_with("last.error"));
+}
+
+#[tokio::test]
+async fn test_provider_errors_reach_on_error() {
+    let (provider, _) = echo_provider(true);
+    let (stack, log) = tagging(&["audit"], None);
+
+    let err = stack
+        .complete(&provider, LlmRequest::new("hello"))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("upstream unavailable"), "{err}");
+    let log = log.lock().unwrap().clone();
+    assert_eq!(log[0], "audit.before");
+    assert!(
+        log[1].starts_with("audit.error: ") && log[1].contains("upstream unavailable"),
+        "{log:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_redaction_middleware() {
+    let (provider, requests) = echo_provider(false);
+    let redaction = RedactionMiddleware::new()
+        .with_pattern("ticket", r"TICKET-\d+")
+        .unwrap();
+    let stack = LlmMiddlewareStack::new().with(Arc::new(redaction));
+
+    let request = LlmRequest::with_messages(vec![
+        LlmMessage::system("Reply to ada@example.com"),
+        LlmMessage::user(
+            "Key sk-abcdefghijklmnopqrstuvwx, card 4111 1111 1111 1111, TICKET-42, Bearer eyJhbGciOiJIUzI1NiJ9.e30",
+        ),
+    ]);
+    let response = stack.complete(&provider, request).await.unwrap();
+
+    let sent = requests.lock().unwrap()[0].clone();
+    assert_eq!(sent.messages[0].content, "Reply to [REDACTED_EMAIL]");
+    assert_eq!(
+        sent.messages[1].content,
+        "Key [REDACTED_API_KEY], card [REDACTED_CARD_NUMBER], [REDACTED_TICKET], [REDACTED_BEARER_TOKEN]"
+    );
+    assert!(response.content.starts_with("echo: Key [REDACTED_API_KEY]"));
+
+    // Responses are only redacted when asked to
+    let redaction = RedactionMiddleware::empty()
+        .with_pattern("name", "Ada")
+        .unwrap();
+    assert_eq!(redaction.redact("Ada Lovelace"), "[REDACTED_NAME] Lovelace");
+    let stack = LlmMiddlewareStack::new().with(Arc::new(redaction.with_response_redaction(true)));
+    let (provider, _) = echo_provider(false);
+    let provider = MiddlewareProvider::new(Box::new(provider), stack);
+    let response = provider.complete(LlmRequest::new("Ada")).await.unwrap();
+    assert_eq!(response.content, "echo: [REDACTED_NAME]");
+
+    assert!(RedactionMiddleware::new().with_pattern("bad", "(").is_err());
+}
+
+#[test]
+fn test_factory_wraps_providers_in_middleware() {
+    let (stack, _) = tagging(&["audit"], None);
+    let provider = LlmProviderFactory::create_provider_with_middleware(
+        LlmConfig::openai("sk-test", "gpt-4o-mini"),
+        stack,
+    )
+    .unwrap();
+    assert_eq!(provider.provider_name(), "openai");
+    assert_eq!(provider.model_name(), "gpt-4o-mini");
+    assert!(provider.supports_function_calling());
+}
+
+#[tokio::test]
+async fn test_executor_middleware_wraps_agent_nodes() {
+    let (provider, requests) = echo_provider(false);
+    let agent_id = AgentId::new();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Support", "Answers tickets", LlmConfig::default())
+            .with_id(agent_id.clone()),
+        provider,
+    ));
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_llm_middleware(Arc::new(RedactionMiddleware::new()))
+        .with_llm_middleware(Arc::new(Tagging {
+            name: "audit",
+            log: log.clone(),
+            reject: false,
+        }));
+    executor.register_agent(agent).await;
+
+    let mut workflow = Workflow::new("support", "Answer a ticket");
+    workflow
+        .add_node(WorkflowNode::new(
+            "answer",
+            "Answer the ticket",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(
+                    agent_id,
+                    "Customer ada@example.com asks for a refund",
+                ),
+            },
+        ))
+        .unwrap();
+    let context = executor.execute(workflow, None).await.unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    // Redaction ran before the audit middleware saw and tagged the request
+    let sent = requests.lock().unwrap()[0].clone();
+    assert_eq!(
+        sent.messages.last().unwrap().content,
+        "Customer [REDACTED_EMAIL] asks for a refund [audit]"
+    );
+    assert_eq!(
+        log.lock().unwrap().as_slice(),
+        ["audit.before", "audit.after"]
+    );
+    assert_eq!(
+        context.get_node_output("answer"),
+        Some(&json!(
+            "echo: Customer [REDACTED_EMAIL] asks for a refund [audit] <audit>"
+        ))
+    );
+}
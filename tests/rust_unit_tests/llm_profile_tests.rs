@@ -0,0 +1,252 @@
+//! Executor-level LLM profiles selected at execution time
+
+use super::test_helpers::{MockRequests, MockResponse, MockServer};
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{ConfigProfile, LlmConfig, LlmOverride, profile::profiles_from_value},
+    types::{AgentId, Secrets, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+
+/// Start a server speaking the `OpenAI` chat completions and `Ollama` chat
+/// APIs, recording every request it receives
+async fn spawn_llm_server() -> (String, MockRequests) {
+    let server = MockServer::new(|request| {
+        let body = match request.path.as_str() {
+            "/v1/chat/completions" => json!({
+                "id": "chatcmpl-1",
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "Done"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6}
+            }),
+            "/api/tags" => json!({
+                "models": [{"name": "llama3.2"}, {"name": "llama3.2:1b"}]
+            }),
+            "/api/chat" => json!({
+                "message": {"role": "assistant", "content": "Done"},
+                "done": true
+            }),
+            _ => json!({}),
+        };
+        std::future::ready(MockResponse::json(200, body))
+    })
+    .await;
+    (server.url(), server.requests())
+}
+
+/// Path and model of the request made for the node prompted with `task`
+fn model_for(received: &MockRequests, task: &str) -> (String, String) {
+    let received = received.lock().unwrap();
+    let request = received
+        .iter()
+        .rev()
+        .find(|r| String::from_utf8_lossy(&r.body).contains(&format!("Task: {task}")))
+        .unwrap_or_else(|| panic!("no request for {task}"));
+    (
+        request.path.clone(),
+        request.json()["model"].as_str().unwrap().to_string(),
+    )
+}
+
+fn agent_node(task: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        task,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), format!("Task: {task}")),
+        },
+    )
+}
+
+/// Workflow with a `Summarize` node tagged `fast`, a `Review` node and a
+/// `Pinned` node with its own LLM configuration
+fn workflow(url: &str) -> Workflow {
+    let mut workflow = Workflow::new("profiles", "");
+    workflow
+        .add_node(agent_node("Summarize").with_tags(vec!["fast".to_string()]))
+        .unwrap();
+    workflow.add_node(agent_node("Review")).unwrap();
+    let mut pinned = agent_node("Pinned");
+    pinned.config.insert(
+        "llm_config".to_string(),
+        serde_json::to_value(LlmConfig::openai_with_base_url(
+            "sk-pinned",
+            "gpt-4.1",
+            format!("{url}/v1"),
+        ))
+        .unwrap(),
+    );
+    workflow.add_node(pinned).unwrap();
+    workflow
+}
+
+fn executor(url: &str) -> WorkflowExecutor {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(LlmConfig::openai_with_base_url(
+            "sk-prod",
+            "gpt-4o",
+            format!("{url}/v1"),
+        ))
+        .with_profile(
+            "prod",
+            ConfigProfile::default()
+                .with_node_override("Review", LlmOverride::model("gpt-4o-mini")),
+        )
+        .with_profile(
+            "dev",
+            ConfigProfile::new(LlmConfig::ollama_with_base_url("llama3.2", url))
+                .with_tag_override("fast", LlmOverride::model("llama3.2:1b")),
+        )
+}
+
+#[tokio::test]
+async fn test_profiles_select_the_model_of_each_node() {
+    let (url, received) = spawn_llm_server().await;
+    let executor = executor(&url);
+
+    let context = executor
+        .execute_with_profile(workflow(&url), None, "dev")
+        .await
+        .unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.profile.as_deref(), Some("dev"));
+    assert_eq!(
+        model_for(&received, "Summarize"),
+        ("/api/chat".to_string(), "llama3.2:1b".to_string())
+    );
+    assert_eq!(
+        model_for(&received, "Review"),
+        ("/api/chat".to_string(), "llama3.2".to_string())
+    );
+    // Node-level configuration takes precedence over the profile
+    assert_eq!(
+        model_for(&received, "Pinned"),
+        ("/v1/chat/completions".to_string(), "gpt-4.1".to_string())
+    );
+
+    // The same executor switches models with the selected profile
+    let context = executor
+        .execute_with_profile(workflow(&url), None, "prod")
+        .await
+        .unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        model_for(&received, "Summarize"),
+        ("/v1/chat/completions".to_string(), "gpt-4o".to_string())
+    );
+    assert_eq!(
+        model_for(&received, "Review"),
+        (
+            "/v1/chat/completions".to_string(),
+            "gpt-4o-mini".to_string()
+        )
+    );
+    assert_eq!(
+        model_for(&received, "Pinned"),
+        ("/v1/chat/completions".to_string(), "gpt-4.1".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_without_a_profile_the_executor_config_applies() {
+    let (url, received) = spawn_llm_server().await;
+    let mut workflow = Workflow::new("plain", "");
+    workflow.add_node(agent_node("Summarize")).unwrap();
+
+    executor(&url).execute(workflow, None).await.unwrap();
+    assert_eq!(
+        model_for(&received, "Summarize"),
+        ("/v1/chat/completions".to_string(), "gpt-4o".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_profile_fails_before_execution() {
+    let (url, received) = spawn_llm_server().await;
+    let error = executor(&url)
+        .execute_with_profile(workflow(&url), None, "staging")
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(error.contains("Unknown profile 'staging'"), "{error}");
+    assert!(error.contains(r#"["dev", "prod"]"#), "{error}");
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_profiles_from_json_resolve_secrets() {
+    let (url, received) = spawn_llm_server().await;
+    let profiles = profiles_from_value(json!({
+        "dev": {
+            "llm_config": {
+                "provider": "OpenAI",
+                "api_key": "{{secret.DEV_KEY}}",
+                "model": "gpt-4o-mini",
+                "base_url": format!("{url}/v1")
+            },
+            "nodes": {"Review": {"model": "o3-mini"}}
+        }
+    }))
+    .unwrap();
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_profiles(profiles);
+
+    let mut workflow = Workflow::new("secrets", "");
+    workflow.add_node(agent_node("Review")).unwrap();
+    let context = WorkflowContext::new(workflow.id.clone())
+        .with_profile("dev")
+        .with_secrets(Secrets::from_iter([("DEV_KEY", "sk-dev")]));
+    executor
+        .execute_with_context(
+            workflow,
+            None,
+            None,
+            graphbit_core::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        model_for(&received, "Review"),
+        ("/v1/chat/completions".to_string(), "o3-mini".to_string())
+    );
+    let received = received.lock().unwrap();
+    assert_eq!(received[0].header("authorization"), Some("Bearer sk-dev"));
+}
+
+#[test]
+fn test_profile_resolution_order() {
+    let base = LlmConfig::openai("sk", "gpt-4o");
+    let profile = ConfigProfile::default()
+        .with_model("gpt-4o-mini")
+        .with_tag_override("local", LlmOverride::config(LlmConfig::ollama("llama3.2")))
+        .with_tag_override("tiny", LlmOverride::model("llama3.2:1b"))
+        .with_node_override("Named", LlmOverride::model("qwen2.5"));
+
+    let plain = agent_node("Plain");
+    let resolved = profile.resolve(&plain, Some(&base)).unwrap();
+    assert_eq!(resolved.provider_name(), "openai");
+    assert_eq!(resolved.model_name(), "gpt-4o-mini");
+
+    // Tags apply in order, then the node's name
+    let tagged = agent_node("Named").with_tags(vec!["local".to_string(), "tiny".to_string()]);
+    let resolved = profile.resolve(&tagged, Some(&base)).unwrap();
+    assert_eq!(resolved.provider_name(), "ollama");
+    assert_eq!(resolved.model_name(), "qwen2.5");
+
+    // A model alone has nothing to apply to without a configuration
+    assert!(profile.resolve(&plain, None).is_none());
+    assert!(
+        ConfigProfile::default()
+            .resolve(&plain, Some(&base))
+            .is_none()
+    );
+}
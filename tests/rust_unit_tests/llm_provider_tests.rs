@@ -8,6 +8,7 @@ async fn test_deepseek_provider_creation() {
         api_key: "test-key".to_string(),
         model: "deepseek-chat".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -28,6 +29,7 @@ async fn test_deepseek_message_formatting() {
         api_key: "test-key".to_string(),
         model: "deepseek-chat".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -63,6 +65,7 @@ async fn test_fireworks_provider_creation() {
         api_key: "test-key".to_string(),
         model: "accounts/fireworks/models/llama-v3p1-8b-instruct".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -87,6 +90,7 @@ async fn test_togetherai_provider_creation() {
         api_key: "test-key".to_string(),
         model: "openai/gpt-oss-20b".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -127,6 +131,7 @@ async fn test_togetherai_model_configs() {
             api_key: "test-key".to_string(),
             model: model.to_string(),
             base_url: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -181,6 +186,7 @@ async fn test_fireworks_model_configs() {
             api_key: "test-key".to_string(),
             model: model.to_string(),
             base_url: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -195,6 +201,7 @@ async fn test_fireworks_message_formatting() {
         api_key: "test-key".to_string(),
         model: "accounts/fireworks/models/llama-v3p1-8b-instruct".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -227,6 +234,7 @@ async fn test_fireworks_with_custom_base_url() {
         api_key: "test-key".to_string(),
         model: "accounts/fireworks/models/llama-v3p1-8b-instruct".to_string(),
         base_url: Some("https://custom.fireworks.ai/v1".to_string()),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -244,6 +252,7 @@ async fn test_xai_provider_creation() {
         api_key: "test-key".to_string(),
         model: "grok-4".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -287,6 +296,7 @@ async fn test_xai_model_configs() {
             api_key: "test-key".to_string(),
             model: model.to_string(),
             base_url: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -301,6 +311,7 @@ async fn test_xai_message_formatting() {
         api_key: "test-key".to_string(),
         model: "grok-4".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -333,6 +344,7 @@ async fn test_xai_with_custom_base_url() {
         api_key: "test-key".to_string(),
         model: "grok-4".to_string(),
         base_url: Some("https://custom.x.ai/v1".to_string()),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -347,6 +359,7 @@ async fn test_perplexity_provider_creation() {
         api_key: "test-key".to_string(),
         model: "pplx-7b-online".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -385,6 +398,7 @@ async fn test_perplexity_model_configs() {
             api_key: "test-key".to_string(),
             model: model.to_string(),
             base_url: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -401,6 +415,7 @@ async fn test_perplexity_message_formatting() {
         api_key: "test-key".to_string(),
         model: "pplx-7b-online".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -435,6 +450,7 @@ async fn test_provider_factory_error_handling() {
         api_key: "test-key".to_string(),
         model: "invalid-model".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
     let provider = LlmProviderFactory::create_provider(config).unwrap();
     assert!(provider.cost_per_token().is_none());
@@ -445,6 +461,7 @@ async fn test_provider_factory_error_handling() {
         api_key: "test-key".to_string(),
         model: "pplx-7b-online".to_string(),
         base_url: Some("invalid-url".to_string()),
+        timeouts: Default::default(),
     };
     let provider = LlmProviderFactory::create_provider(config).unwrap();
     let request = LlmRequest::new("test");
@@ -486,11 +503,13 @@ async fn test_provider_common_functionality() {
             api_key: "test-key".to_string(),
             model: "deepseek-chat".to_string(),
             base_url: None,
+            timeouts: Default::default(),
         },
         LlmConfig::Perplexity {
             api_key: "test-key".to_string(),
             model: "pplx-7b-online".to_string(),
             base_url: None,
+            timeouts: Default::default(),
         },
     ];
 
@@ -522,6 +541,7 @@ async fn test_openrouter_provider_creation() {
         base_url: None,
         site_url: None,
         site_name: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -544,6 +564,7 @@ async fn test_openrouter_provider_with_site_info() {
         base_url: None,
         site_url: Some("https://example.com".to_string()),
         site_name: Some("Test App".to_string()),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -566,6 +587,7 @@ async fn test_openrouter_message_formatting() {
         base_url: None,
         site_url: None,
         site_name: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -606,6 +628,7 @@ async fn test_openrouter_config_helpers() {
             base_url,
             site_url,
             site_name,
+            ..
         } => {
             assert_eq!(api_key, "test-key");
             assert_eq!(model, "openai/gpt-4o-mini");
@@ -630,6 +653,7 @@ async fn test_openrouter_config_helpers() {
             base_url,
             site_url,
             site_name,
+            ..
         } => {
             assert_eq!(api_key, "test-key");
             assert_eq!(model, "anthropic/claude-3-5-sonnet");
@@ -660,6 +684,7 @@ async fn test_openrouter_model_context_lengths() {
             base_url: None,
             site_url: None,
             site_name: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -675,6 +700,7 @@ async fn test_replicate_provider_creation() {
         model: "lucataco/glaive-function-calling-v1".to_string(),
         base_url: None,
         version: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -693,6 +719,7 @@ async fn test_replicate_provider_with_version() {
         version: Some(
             "2288c783ba83e28b9ac4906e2dfa8004e3eda67f11ffc7a6a80bd927e46bc6c9".to_string(),
         ),
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -709,6 +736,7 @@ async fn test_replicate_message_formatting() {
         model: "lucataco/glaive-function-calling-v1".to_string(),
         base_url: None,
         version: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -740,6 +768,7 @@ async fn test_replicate_tool_calling_support() {
         model: "lucataco/glaive-function-calling-v1".to_string(),
         base_url: None,
         version: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -752,6 +781,7 @@ async fn test_replicate_tool_calling_support() {
         model: "some/other-model".to_string(),
         base_url: None,
         version: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -795,6 +825,7 @@ async fn test_ai21labs_provider_creation() {
         model: "jamba-mini".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -827,6 +858,7 @@ async fn test_ai21labs_model_configs() {
             model: model.to_string(),
             base_url: None,
             organization: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -842,6 +874,7 @@ async fn test_ai21labs_message_formatting() {
         model: "jamba-mini".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -875,6 +908,7 @@ async fn test_bytedance_provider_creation() {
         api_key: "test-key".to_string(),
         model: "seed-1-6-flash-250715".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -910,6 +944,7 @@ async fn test_bytedance_model_configs() {
             api_key: "test-key".to_string(),
             model: model.to_string(),
             base_url: None,
+            timeouts: Default::default(),
         })
         .unwrap();
 
@@ -924,6 +959,7 @@ async fn test_bytedance_message_formatting() {
         api_key: "test-key".to_string(),
         model: "seed-1-6-flash-250715".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     })
     .unwrap();
 
@@ -956,6 +992,7 @@ async fn test_bytedance_with_custom_base_url() {
         api_key: "test-key".to_string(),
         model: "seed-1-6-flash-250715".to_string(),
         base_url: Some("https://custom.bytedance.com/api/v3".to_string()),
+        timeouts: Default::default(),
     })
     .unwrap();
 
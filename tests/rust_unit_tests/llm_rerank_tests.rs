@@ -0,0 +1,276 @@
+//! Re-ranking retrieved documents by LLM-scored relevance
+
+use super::test_helpers::{MockResponse, MockServer, Requests, ScriptedProvider};
+use chrono::Utc;
+use graphbit_core::{
+    budget::{BudgetTracker, ExecutionBudget},
+    embeddings::{EmbeddingConfig, EmbeddingProvider},
+    errors::GraphBitError,
+    llm::{LlmConfig, LlmMiddlewareStack, LlmProvider, LlmResponse, LlmUsage, RerankConfig},
+    memory::{Memory, MemoryConfig, MemoryId, MemoryScope, MemoryService, store::MetadataStore},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const DOCUMENTS: [&str; 5] = [
+    "Paris is the capital of France",
+    "Bananas are rich in potassium",
+    "France borders Spain and Italy",
+    "The Eiffel Tower is in Paris",
+    "Rust has no garbage collector",
+];
+
+/// Fixed score out of 10 for each document
+fn fixed_score(document: &str) -> u32 {
+    match document {
+        "Paris is the capital of France" => 10,
+        "The Eiffel Tower is in Paris" => 6,
+        "France borders Spain and Italy" => 4,
+        _ => 0,
+    }
+}
+
+/// Reply scoring the numbered documents of a scoring prompt with [`fixed_score`]
+fn scores_reply(prompt: &str) -> String {
+    let scores: serde_json::Map<String, Value> = prompt
+        .lines()
+        .filter_map(|line| line.strip_prefix('['))
+        .filter_map(|line| line.split_once("] "))
+        .map(|(n, document)| (n.to_string(), json!(fixed_score(document))))
+        .collect();
+    format!("Scores: {}", Value::Object(scores))
+}
+
+/// Provider answering scoring prompts with [`scores_reply`], or `reply` when
+/// set, with the requests it receives
+fn scoring_provider(reply: Option<&str>) -> (LlmProvider, Requests) {
+    let reply = reply.map(ToString::to_string);
+    let scoring = ScriptedProvider::new(move |request| {
+        let prompt = &request.messages.last().unwrap().content;
+        let reply = reply.clone().unwrap_or_else(|| scores_reply(prompt));
+        Ok(LlmResponse::new(reply, "scoring-model").with_usage(LlmUsage::new(30, 10)))
+    })
+    .with_model("scoring-model");
+    let requests = scoring.requests();
+    let provider = LlmProvider::new(
+        Box::new(scoring),
+        LlmConfig::openai("test", "scoring-model"),
+    );
+    (provider, requests)
+}
+
+fn documents() -> Vec<String> {
+    DOCUMENTS.iter().map(ToString::to_string).collect()
+}
+
+#[tokio::test]
+async fn test_rerank_orders_documents_by_score() {
+    let (provider, requests) = scoring_provider(None);
+
+    let reranking = provider
+        .rerank(
+            "What is the capital of France?",
+            &documents(),
+            &RerankConfig::default().with_top_k(3).with_batch_size(2),
+        )
+        .await
+        .unwrap();
+
+    let ranked: Vec<(usize, f64)> = reranking
+        .documents
+        .iter()
+        .map(|document| (document.index, document.score))
+        .collect();
+    assert_eq!(ranked, [(0, 1.0), (3, 0.6), (2, 0.4)]);
+    assert_eq!(reranking.documents[1].document, DOCUMENTS[3]);
+    // Five documents two at a time take three prompts
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    assert_eq!(
+        (
+            reranking.usage.prompt_tokens,
+            reranking.usage.completion_tokens
+        ),
+        (90, 30)
+    );
+}
+
+#[tokio::test]
+async fn test_rerank_keeps_every_document_without_top_k() {
+    let (provider, requests) = scoring_provider(None);
+
+    let reranking = provider
+        .rerank("Paris", &documents(), &RerankConfig::default())
+        .await
+        .unwrap();
+
+    let order: Vec<usize> = reranking.documents.iter().map(|d| d.index).collect();
+    // Equal scores keep their input order
+    assert_eq!(order, [0, 3, 2, 1, 4]);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_rerank_usage_reaches_the_budget_tracker() {
+    let (provider, _) = scoring_provider(None);
+    let tracker = BudgetTracker::new(ExecutionBudget::default(), HashMap::new());
+    let middleware = LlmMiddlewareStack::new().with(Arc::new(tracker.clone()));
+
+    provider
+        .rerank_with(
+            &middleware,
+            "Paris",
+            &documents(),
+            &RerankConfig::default().with_batch_size(3),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(tracker.usage().total_tokens, 80);
+}
+
+#[tokio::test]
+async fn test_rerank_rejects_bad_replies_and_batch_sizes() {
+    let (provider, _) = scoring_provider(Some(r#"{"1": 3}"#));
+    let error = provider
+        .rerank("Paris", &documents()[..2], &RerankConfig::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::Llm { .. }));
+    assert!(
+        error.to_string().contains("no score for document 2"),
+        "{error}"
+    );
+
+    let (provider, _) = scoring_provider(Some("Both look relevant"));
+    let error = provider
+        .rerank("Paris", &documents(), &RerankConfig::default())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("not a score object"), "{error}");
+
+    let error = provider
+        .rerank(
+            "Paris",
+            &documents(),
+            &RerankConfig::default().with_batch_size(0),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::Validation { .. }));
+}
+
+#[tokio::test]
+async fn test_rerank_of_no_documents_makes_no_call() {
+    let (provider, requests) = scoring_provider(None);
+
+    let reranking = provider
+        .rerank("Paris", &[], &RerankConfig::default())
+        .await
+        .unwrap();
+
+    assert!(reranking.documents.is_empty());
+    assert_eq!(requests.lock().unwrap().len(), 0);
+}
+
+/// Models named in requests to the server
+type Models = Arc<Mutex<Vec<String>>>;
+
+/// Start a server answering `OpenAI` embedding requests with one-hot vectors
+/// (every text alike) and chat completions with [`scores_reply`]
+async fn spawn_openai_server() -> (String, Models) {
+    let models = Models::default();
+    let recorded = Arc::clone(&models);
+    let server = MockServer::new(move |request| {
+        let request = request.json();
+    let model = request["model"].as_str().unwrap().to_string();
+    recorded.lock().unwrap().push(model.clone());
+    let body = match request.get("input") {
+        Some(Value::Array(inputs)) => {
+            let data: Vec<Value> = (0..inputs.len())
+                .map(|index| json!({"index": index, "embedding": [1.0, 0.0]}))
+                .collect();
+            json!({"data": data, "model": model, "usage": {"prompt_tokens": 1, "total_tokens": 1}})
+        }
+        Some(_) => json!({
+            "data": [{"index": 0, "embedding": [1.0, 0.0]}],
+            "model": model,
+            "usage": {"prompt_tokens": 1, "total_tokens": 1}
+        }),
+        None => {
+            let prompt = request["messages"][1]["content"].as_str().unwrap();
+            json!({
+                "id": "chatcmpl-1",
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": scores_reply(prompt)},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 30, "completion_tokens": 10, "total_tokens": 40}
+            })
+        }
+    };
+        std::future::ready(MockResponse::json(200, body))
+    })
+    .await;
+    (server.url(), models)
+}
+
+#[tokio::test]
+async fn test_memory_search_reranks_with_the_memory_llm() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("memory.db");
+    let db_path = db_path.to_str().unwrap();
+    let store = MetadataStore::new(db_path).unwrap();
+    for document in DOCUMENTS {
+        store
+            .insert_memory(&Memory {
+                id: MemoryId::new(),
+                content: document.to_string(),
+                scope: MemoryScope::default(),
+                metadata: HashMap::new(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                hash: document.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+    drop(store);
+    let (url, models) = spawn_openai_server().await;
+    let config = MemoryConfig::new(
+        LlmConfig::openai_with_base_url("test", "gpt-4o-mini", &url),
+        EmbeddingConfig {
+            provider: EmbeddingProvider::OpenAI,
+            api_key: "test".to_string(),
+            model: "embed-v1".to_string(),
+            base_url: Some(url.clone()),
+            timeout_seconds: Some(5),
+            max_batch_size: None,
+            extra_params: HashMap::new(),
+            retry: None,
+            python_instance: None,
+            timeouts: Default::default(),
+        },
+    )
+    .with_db_path(db_path);
+    let service = MemoryService::new(config).await.unwrap();
+
+    let results = service
+        .search(
+            "Capital of France",
+            &MemoryScope::default(),
+            5,
+            Some(&RerankConfig::default().with_top_k(2).with_model("gpt-4o")),
+        )
+        .await
+        .unwrap();
+
+    let ranked: Vec<(&str, f64)> = results
+        .iter()
+        .map(|result| (result.memory.content.as_str(), result.score))
+        .collect();
+    assert_eq!(ranked, [(DOCUMENTS[0], 1.0), (DOCUMENTS[3], 0.6)]);
+    assert_eq!(models.lock().unwrap().last().unwrap(), "gpt-4o");
+}
@@ -13,6 +13,7 @@ async fn test_openai_llm() {
         model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -35,6 +36,7 @@ async fn test_openai_llm_failure() {
         model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -55,6 +57,7 @@ async fn test_anthropic_llm() {
         api_key: std::env::var("ANTHROPIC_API_KEY").unwrap(),
         model: "claude-3-5-sonnet-20241022".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -82,6 +85,7 @@ async fn test_anthropic_llm_failure() {
         api_key: "invalid-key".to_string(),
         model: "claude-3-5-sonnet-20241022".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -102,6 +106,9 @@ async fn test_huggingface_llm() {
         api_key: std::env::var("HUGGINGFACE_API_KEY").unwrap(),
         model: "mistralai/Mixtral-8x7B-Instruct-v0.1".to_string(),
         base_url: None,
+        inference_provider: None,
+        text_generation: false,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -123,6 +130,9 @@ async fn test_huggingface_llm_failure() {
         api_key: "invalid-key".to_string(),
         model: "mistralai/Mixtral-8x7B-Instruct-v0.1".to_string(),
         base_url: None,
+        inference_provider: None,
+        text_generation: false,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -143,6 +153,7 @@ async fn test_perplexity_llm() {
         api_key: std::env::var("PERPLEXITY_API_KEY").unwrap(),
         model: "sonar".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -173,6 +184,7 @@ async fn test_perplexity_llm_failure() {
         api_key: "invalid-key".to_string(),
         model: "sonar".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -193,6 +205,7 @@ async fn test_deepseek_llm() {
         api_key: std::env::var("DEEPSEEK_API_KEY").unwrap(),
         model: "deepseek-chat".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -220,6 +233,7 @@ async fn test_deepseek_llm_failure() {
         api_key: "invalid-key".to_string(),
         model: "deepseek-chat".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -239,6 +253,7 @@ async fn test_ollama_llm() {
     let config = llm::LlmConfig::Ollama {
         base_url: Some("http://localhost:11434".to_string()),
         model: "llama3.2:latest".to_string(),
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -264,6 +279,7 @@ async fn test_togetherai_llm() {
         api_key: std::env::var("TOGETHER_API_KEY").unwrap(),
         model: "openai/gpt-oss-20b".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -285,6 +301,7 @@ async fn test_togetherai_llm_failure() {
         api_key: "invalid-key".to_string(),
         model: "openai/gpt-oss-20b".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -300,6 +317,7 @@ fn test_togetherai_config_creation() {
         api_key: "test-key".to_string(),
         model: "openai/gpt-oss-20b".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     match config {
@@ -307,6 +325,7 @@ fn test_togetherai_config_creation() {
             api_key,
             model,
             base_url,
+            ..
         } => {
             assert_eq!(api_key, "test-key");
             assert_eq!(model, "openai/gpt-oss-20b");
@@ -322,6 +341,7 @@ fn test_togetherai_config_with_custom_base_url() {
         api_key: "test-key".to_string(),
         model: "openai/gpt-oss-20b".to_string(),
         base_url: Some("https://custom.together.xyz/v1".to_string()),
+        timeouts: Default::default(),
     };
 
     match config {
@@ -329,6 +349,7 @@ fn test_togetherai_config_with_custom_base_url() {
             api_key,
             model,
             base_url,
+            ..
         } => {
             assert_eq!(api_key, "test-key");
             assert_eq!(model, "openai/gpt-oss-20b");
@@ -349,6 +370,7 @@ async fn test_togetherai_function_calling() {
         api_key: std::env::var("TOGETHER_API_KEY").unwrap(),
         model: "openai/gpt-oss-20b".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let provider = llm::LlmProviderFactory::create_provider(config).unwrap();
@@ -0,0 +1,159 @@
+//! Local embedding provider against the tiny BERT model in
+//! `fixtures/local-embedding-model` (hidden size 8, two layers, inputs of at
+//! most 16 tokens). Expected vectors come from a reference implementation of
+//! the same forward pass, mean pooling and normalization.
+
+use graphbit_core::embeddings::{
+    EmbeddingConfig, EmbeddingInput, EmbeddingProvider, EmbeddingProviderTrait, EmbeddingRequest,
+    EmbeddingService, LocalEmbeddingProvider,
+};
+use graphbit_core::errors::GraphBitError;
+use std::collections::HashMap;
+
+const MODEL_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/rust_unit_tests/fixtures/local-embedding-model"
+);
+
+const HELLO_WORLD: [f32; 8] = [
+    0.148_480, -0.165_277, -0.647_780, 0.485_316, -0.247_488, -0.075_142, 0.149_237, 0.454_222,
+];
+const CAT_ON_MAT: [f32; 8] = [
+    0.323_639, -0.081_758, -0.527_280, 0.125_321, -0.427_304, -0.078_389, 0.579_675, 0.264_740,
+];
+const WORD_PIECES: [f32; 8] = [
+    0.545_516, 0.536_751, -0.273_397, 0.085_650, -0.016_423, 0.025_842, -0.558_178, -0.140_458,
+];
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+    }
+}
+
+fn service(config: EmbeddingConfig) -> EmbeddingService {
+    EmbeddingService::new(config).unwrap()
+}
+
+#[tokio::test]
+async fn test_embeddings_match_the_reference_model() {
+    let service = service(EmbeddingConfig::local(MODEL_DIR));
+
+    assert_close(
+        &service.embed_text("Hello world").await.unwrap(),
+        &HELLO_WORLD,
+    );
+    assert_close(
+        &service.embed_text("The cat sat on the mat.").await.unwrap(),
+        &CAT_ON_MAT,
+    );
+    // "graph ##bit run ##s local embedding ##s"
+    assert_close(
+        &service
+            .embed_text("Graphbit runs local embeddings")
+            .await
+            .unwrap(),
+        &WORD_PIECES,
+    );
+}
+
+#[tokio::test]
+async fn test_batches_are_padded_without_changing_embeddings() {
+    let service = service(EmbeddingConfig::local(MODEL_DIR));
+    let texts = vec![
+        "Hello world".to_string(),
+        "The cat sat on the mat.".to_string(),
+        "Graphbit runs local embeddings".to_string(),
+    ];
+
+    let embeddings = service.embed_texts(&texts).await.unwrap();
+
+    assert_close(&embeddings[0], &HELLO_WORLD);
+    assert_close(&embeddings[1], &CAT_ON_MAT);
+    assert_close(&embeddings[2], &WORD_PIECES);
+    for embedding in &embeddings {
+        let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5, "norm {norm}");
+    }
+}
+
+#[tokio::test]
+async fn test_batch_size_and_threads_do_not_change_embeddings() {
+    let mut config = EmbeddingConfig::local(MODEL_DIR).with_threads(1);
+    config.max_batch_size = Some(2);
+    let service = service(config);
+    let texts = vec![
+        "Hello world".to_string(),
+        "The cat sat on the mat.".to_string(),
+        "Hello world".to_string(),
+    ];
+
+    let response = service
+        .embed(EmbeddingRequest {
+            input: EmbeddingInput::Multiple(texts),
+            user: None,
+            params: HashMap::new(),
+        })
+        .await
+        .unwrap();
+
+    assert_close(&response.embeddings[0], &HELLO_WORLD);
+    assert_close(&response.embeddings[1], &CAT_ON_MAT);
+    assert_close(&response.embeddings[2], &HELLO_WORLD);
+    // [CLS] and [SEP] count: 4 + 9 + 4
+    assert_eq!(response.usage.prompt_tokens, 17);
+    assert_eq!(response.usage.total_tokens, 17);
+    assert_eq!(response.model, MODEL_DIR);
+}
+
+#[tokio::test]
+async fn test_long_inputs_are_truncated_to_the_max_sequence_length() {
+    let service = service(EmbeddingConfig::local(MODEL_DIR));
+    // max_seq_length is 16: [CLS], 14 words and [SEP]
+    let words = "the cat sat on the mat a dog sat on a mat the cat";
+    let longer = format!("{words} ran fast to the air gap model");
+
+    assert_eq!(
+        service.embed_text(words).await.unwrap(),
+        service.embed_text(&longer).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_dimensions_come_from_the_model_config() {
+    let provider = LocalEmbeddingProvider::new(EmbeddingConfig::local(MODEL_DIR)).unwrap();
+
+    assert_eq!(provider.get_embedding_dimensions().await.unwrap(), 8);
+    assert_eq!(provider.provider_name(), "local");
+    assert!(provider.health_check().await.model_available);
+}
+
+#[test]
+fn test_missing_model_directory_is_a_config_error() {
+    let missing = EmbeddingConfig::local("/nonexistent/graphbit-model");
+    let error = EmbeddingService::new(missing).err().unwrap();
+    assert!(
+        matches!(error, GraphBitError::Configuration { .. }),
+        "{error:?}"
+    );
+    assert!(error.to_string().contains("/nonexistent/graphbit-model"));
+
+    let incomplete = tempfile::tempdir().unwrap();
+    let error = EmbeddingService::new(EmbeddingConfig::local(incomplete.path().to_string_lossy()))
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("config.json"), "{error}");
+
+    let error = LocalEmbeddingProvider::new(EmbeddingConfig::local(MODEL_DIR).with_threads(0))
+        .err()
+        .unwrap();
+    assert!(
+        matches!(error, GraphBitError::Validation { .. }),
+        "{error:?}"
+    );
+
+    let mut other = EmbeddingConfig::local(MODEL_DIR);
+    other.provider = EmbeddingProvider::Jina;
+    assert!(LocalEmbeddingProvider::new(other).is_err());
+}
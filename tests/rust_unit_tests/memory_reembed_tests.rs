@@ -0,0 +1,270 @@
+//! Persisted memory embeddings and migration to another embedding model
+
+use super::test_helpers::{MockResponse, MockServer};
+use chrono::Utc;
+use graphbit_core::{
+    embeddings::{EmbeddingConfig, EmbeddingProvider},
+    errors::GraphBitError,
+    llm::LlmConfig,
+    memory::{
+        Memory, MemoryConfig, MemoryId, MemoryScope, MemoryService, ReembedProgress,
+        store::MetadataStore,
+    },
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const FACTS: [&str; 5] = [
+    "User lives in Munich",
+    "User prefers tea",
+    "User works on Rust",
+    "User owns a cat",
+    "User speaks German",
+];
+
+/// Texts embedded per request, by model
+type Requests = Arc<Mutex<Vec<(String, usize)>>>;
+
+/// Deterministic embedding of `text`: word counts hashed into as many buckets
+/// as the model name is long, so different models make incomparable vectors
+fn fake_embedding(model: &str, text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; model.len()];
+    for word in text.split_whitespace() {
+        let bucket = word.bytes().map(usize::from).sum::<usize>() % vector.len();
+        vector[bucket] += 1.0;
+    }
+    vector
+}
+
+/// Start a server answering `OpenAI` embedding requests with [`fake_embedding`]
+async fn spawn_embedding_server() -> (String, Requests) {
+    let requests = Requests::default();
+    let recorded = Arc::clone(&requests);
+    let server = MockServer::new(move |request| {
+        let request = request.json();
+        let model = request["model"].as_str().unwrap().to_string();
+        let inputs: Vec<String> = match &request["input"] {
+            Value::String(text) => vec![text.clone()],
+            inputs => serde_json::from_value(inputs.clone()).unwrap(),
+        };
+        recorded.lock().unwrap().push((model.clone(), inputs.len()));
+        let data: Vec<Value> = inputs
+            .iter()
+            .enumerate()
+            .map(|(index, text)| json!({"index": index, "embedding": fake_embedding(&model, text)}))
+            .collect();
+        let body =
+            json!({"data": data, "model": model, "usage": {"prompt_tokens": 1, "total_tokens": 1}});
+        std::future::ready(MockResponse::json(200, body))
+    })
+    .await;
+    (server.url(), requests)
+}
+
+fn embedding_config(url: &str, model: &str) -> EmbeddingConfig {
+    EmbeddingConfig {
+        provider: EmbeddingProvider::OpenAI,
+        api_key: "test".to_string(),
+        model: model.to_string(),
+        base_url: Some(url.to_string()),
+        timeout_seconds: Some(5),
+        max_batch_size: None,
+        extra_params: HashMap::new(),
+        retry: None,
+        python_instance: None,
+        timeouts: Default::default(),
+    }
+}
+
+async fn open(db_path: &str, url: &str, model: &str) -> MemoryService {
+    let config = MemoryConfig::new(
+        LlmConfig::openai("test", "gpt-4o-mini"),
+        embedding_config(url, model),
+    )
+    .with_db_path(db_path);
+    MemoryService::new(config).await.unwrap()
+}
+
+/// Write the facts to a new database, as stored before embeddings were kept
+async fn seed(db_path: &str) {
+    let store = MetadataStore::new(db_path).unwrap();
+    for fact in FACTS {
+        store
+            .insert_memory(&Memory {
+                id: MemoryId::new(),
+                content: fact.to_string(),
+                scope: MemoryScope::default(),
+                metadata: HashMap::new(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                hash: fact.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+}
+
+fn embedded(requests: &Requests, model: &str) -> usize {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(m, _)| m == model)
+        .map(|(_, count)| count)
+        .sum()
+}
+
+async fn top_match(service: &MemoryService, query: &str) -> String {
+    let results = service
+        .search(query, &MemoryScope::default(), 1, None)
+        .await
+        .unwrap();
+    results[0].memory.content.clone()
+}
+
+#[tokio::test]
+async fn test_embeddings_are_stored_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("memory.db");
+    let db_path = db_path.to_str().unwrap();
+    let (url, requests) = spawn_embedding_server().await;
+    seed(db_path).await;
+
+    let service = open(db_path, &url, "small-v1").await;
+    assert_eq!(embedded(&requests, "small-v1"), 5);
+    assert_eq!(
+        top_match(&service, "User prefers tea").await,
+        "User prefers tea"
+    );
+    drop(service);
+
+    // Reopening loads the stored vectors instead of embedding every memory again
+    let service = open(db_path, &url, "small-v1").await;
+    assert_eq!(embedded(&requests, "small-v1"), 6);
+    assert_eq!(
+        top_match(&service, "User owns a cat").await,
+        "User owns a cat"
+    );
+}
+
+#[tokio::test]
+async fn test_vectors_of_another_model_are_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("memory.db");
+    let db_path = db_path.to_str().unwrap();
+    let (url, requests) = spawn_embedding_server().await;
+    seed(db_path).await;
+    drop(open(db_path, &url, "small-v1").await);
+
+    let service = open(db_path, &url, "large-model-v2").await;
+    assert_eq!(embedded(&requests, "large-model-v2"), 0);
+    let error = service
+        .search("User prefers tea", &MemoryScope::default(), 1, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::Memory { .. }));
+    assert!(error.to_string().contains("small-v1"), "{error}");
+    assert!(service.add(&[], &MemoryScope::default()).await.is_err());
+
+    // Memories can still be read
+    let memories = service.get_all(&MemoryScope::default()).await.unwrap();
+    assert_eq!(memories.len(), 5);
+}
+
+#[tokio::test]
+async fn test_reembed_all_switches_the_embedding_model() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("memory.db");
+    let db_path = db_path.to_str().unwrap();
+    let (url, requests) = spawn_embedding_server().await;
+    seed(db_path).await;
+    let service = open(db_path, &url, "small-v1").await;
+
+    let mut updates = Vec::new();
+    let count = service
+        .reembed_all(embedding_config(&url, "large-model-v2"), 2, |update| {
+            updates.push(update);
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(count, 5);
+    assert_eq!(
+        updates,
+        [2, 4, 5].map(|processed| ReembedProgress {
+            processed,
+            total: 5
+        })
+    );
+    assert_eq!(service.embedding_model().await, "large-model-v2");
+    assert_eq!(
+        top_match(&service, "User works on Rust").await,
+        "User works on Rust"
+    );
+    drop(service);
+
+    // The stored marker now matches the new model
+    let service = open(db_path, &url, "large-model-v2").await;
+    assert_eq!(embedded(&requests, "large-model-v2"), 6);
+    assert_eq!(
+        top_match(&service, "User speaks German").await,
+        "User speaks German"
+    );
+}
+
+#[tokio::test]
+async fn test_interrupted_reembed_resumes_after_the_last_batch() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("memory.db");
+    let db_path = db_path.to_str().unwrap();
+    let (url, requests) = spawn_embedding_server().await;
+    seed(db_path).await;
+    let service = open(db_path, &url, "small-v1").await;
+
+    let result = service
+        .reembed_all(embedding_config(&url, "large-model-v2"), 2, |_| {
+            Err(GraphBitError::memory("stopped"))
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(service.embedding_model().await, "small-v1");
+    drop(service);
+
+    // Neither model is usable until the re-embedding finishes
+    let service = open(db_path, &url, "large-model-v2").await;
+    let error = service
+        .search("User prefers tea", &MemoryScope::default(), 1, None)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("unfinished"), "{error}");
+
+    let mut updates = Vec::new();
+    let count = service
+        .reembed_all(embedding_config(&url, "large-model-v2"), 2, |update| {
+            updates.push(update.processed);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(updates, [4, 5]);
+    assert_eq!(embedded(&requests, "large-model-v2"), 5);
+    assert_eq!(
+        top_match(&service, "User owns a cat").await,
+        "User owns a cat"
+    );
+}
+
+#[tokio::test]
+async fn test_reembed_all_rejects_an_empty_batch_size() {
+    let (url, _) = spawn_embedding_server().await;
+    let service = open(":memory:", &url, "small-v1").await;
+
+    let error = service
+        .reembed_all(embedding_config(&url, "large-model-v2"), 0, |_| Ok(()))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GraphBitError::Validation { .. }));
+}
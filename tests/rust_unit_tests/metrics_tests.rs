@@ -0,0 +1,196 @@
+//! Executor metrics: counters over a mocked run and the Prometheus exporter
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    errors::GraphBitError,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmUsage},
+    telemetry::{self, MetricsSnapshot},
+    types::{AgentId, CircuitBreakerConfig, RetryConfig, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::sync::Arc;
+
+/// Executor retrying at once, whose circuit breakers open on the first failure
+/// and close on the next success
+fn executor() -> WorkflowExecutor {
+    WorkflowExecutor::new()
+        .with_retry_config(
+            RetryConfig::new(2)
+                .with_exponential_backoff(1, 1.0, 1)
+                .with_jitter(0.0),
+        )
+        .with_circuit_breaker_config(CircuitBreakerConfig {
+            failure_threshold: 1,
+            recovery_timeout_ms: 0,
+            success_threshold: 1,
+            ..CircuitBreakerConfig::default()
+        })
+}
+
+/// Register an agent on `model` whose first call times out and whose second
+/// answers with 12 prompt and 5 completion tokens
+async fn register_flaky_agent(executor: &WorkflowExecutor, model: &str) -> AgentId {
+    let agent_id = AgentId::new();
+    let provider = ScriptedProvider::replaying_results(vec![
+        Err(GraphBitError::llm("request timed out")),
+        Ok(LlmResponse::new("Paris", model).with_usage(LlmUsage::new(12, 5))),
+    ])
+    .with_model(model);
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("flaky", "Flaky agent", LlmConfig::default()).with_id(agent_id.clone()),
+        provider,
+    ));
+    executor.register_agent(agent).await;
+    agent_id
+}
+
+/// `Source` -> agent node `Ask`
+fn workflow(agent_id: &AgentId) -> Workflow {
+    let mut workflow = Workflow::new("metrics", "");
+    let source = workflow
+        .add_node(WorkflowNode::new(
+            "Source",
+            "",
+            NodeType::Transform {
+                transformation: "'France'".to_string(),
+            },
+        ))
+        .unwrap();
+    let ask = workflow
+        .add_node(WorkflowNode::new(
+            "Ask",
+            "",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(agent_id.clone(), "Capital of {{node.Source}}?"),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(source, ask, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+fn llm_labels(model: &str) -> [(&str, &str); 2] {
+    [("provider", "scripted"), ("model", model)]
+}
+
+#[tokio::test]
+async fn test_mocked_run_increments_counters() {
+    telemetry::install().unwrap();
+    let executor = executor();
+    let model = "metrics-run-model";
+    let agent_id = register_flaky_agent(&executor, model).await;
+    let agent = agent_id.to_string();
+    let before = executor.metrics_snapshot();
+
+    let context = executor.execute(workflow(&agent_id), None).await.unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let after = executor.metrics_snapshot();
+
+    // Counters shared with other tests grow by at least this run's share
+    let delta = |name: &str, labels: &[(&str, &str)]| {
+        after.counter(name, labels) - before.counter(name, labels)
+    };
+    let agent_success = [("node_type", "agent"), ("outcome", "success")];
+    assert!(delta(telemetry::NODE_EXECUTIONS, &agent_success) >= 1);
+    let transform_success = [("node_type", "transform"), ("outcome", "success")];
+    assert!(delta(telemetry::NODE_EXECUTIONS, &transform_success) >= 1);
+    assert!(delta(telemetry::NODE_RETRIES, &[("node_type", "agent")]) >= 1);
+    let waits = |snapshot: &MetricsSnapshot| {
+        snapshot
+            .histogram(telemetry::PERMIT_WAIT, &[("node_type", "agent")])
+            .map_or(0, |histogram| histogram.count)
+    };
+    assert!(waits(&after) - waits(&before) >= 1);
+    assert!(after.gauge(telemetry::ACTIVE_TASKS, &[]).is_some());
+
+    // Metrics labelled with this test's model and agent are exact
+    let [provider, model_label] = llm_labels(model);
+    let requests = |outcome| {
+        after.counter(
+            telemetry::LLM_REQUESTS,
+            &[provider, model_label, ("outcome", outcome)],
+        )
+    };
+    assert_eq!((requests("success"), requests("failure")), (1, 1));
+    let tokens = |kind| {
+        after.counter(
+            telemetry::LLM_TOKENS,
+            &[provider, model_label, ("kind", kind)],
+        )
+    };
+    assert_eq!((tokens("prompt"), tokens("completion")), (12, 5));
+    let latency = after
+        .histogram(telemetry::LLM_DURATION, &llm_labels(model))
+        .unwrap();
+    assert_eq!(latency.count, 2);
+    assert!(latency.p50 <= latency.p99);
+
+    let transitions = |to| {
+        after.counter(
+            telemetry::BREAKER_TRANSITIONS,
+            &[("agent", &agent), ("to", to)],
+        )
+    };
+    assert_eq!(
+        (
+            transitions("open"),
+            transitions("half_open"),
+            transitions("closed")
+        ),
+        (1, 1, 1)
+    );
+    assert_eq!(
+        after.gauge(telemetry::BREAKER_STATE, &[("agent", &agent)]),
+        Some(0.0)
+    );
+}
+
+#[tokio::test]
+async fn test_prometheus_exporter_serves_the_metrics() {
+    let recorder = telemetry::install().unwrap();
+    // Installing again hands out the same recorder
+    assert_eq!(
+        telemetry::install().unwrap().snapshot().counters.len(),
+        recorder.snapshot().counters.len()
+    );
+    let executor = executor();
+    let model = "metrics-export-model";
+    let agent_id = register_flaky_agent(&executor, model).await;
+    executor.execute(workflow(&agent_id), None).await.unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(telemetry::serve(listener, recorder));
+    let client = reqwest::Client::new();
+
+    let response = client.get(format!("{url}/metrics")).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4")
+    );
+    let text = response.text().await.unwrap();
+    assert!(text.contains("# TYPE graphbit_llm_tokens_total counter"));
+    assert!(text.contains("# HELP graphbit_llm_tokens_total LLM prompt and completion tokens"));
+    assert!(text.contains(&format!(
+        "graphbit_llm_tokens_total{{kind=\"prompt\",model=\"{model}\",provider=\"scripted\"}} 12"
+    )));
+    assert!(text.contains(&format!(
+        "graphbit_llm_request_duration_seconds_count{{model=\"{model}\",provider=\"scripted\"}} 2"
+    )));
+    assert!(text.contains(&format!(
+        "graphbit_llm_request_duration_seconds_bucket{{model=\"{model}\",provider=\"scripted\",le=\"+Inf\"}} 2"
+    )));
+    assert!(text.contains("# TYPE graphbit_permit_wait_seconds histogram"));
+
+    let response = client.get(format!("{url}/other")).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client.post(format!("{url}/metrics")).send().await.unwrap();
+    assert_eq!(response.status(), 405);
+}
@@ -1,23 +1,99 @@
 mod agent_comprehensive_tests;
 mod agent_tests;
+mod anthropic_tool_use_tests;
+mod audit_tests;
 mod azurellm_tests;
+mod budget_tests;
+mod clock_tests;
+mod compensation_tests;
+mod compare_tests;
 mod concurrency_comprehensive_tests;
+mod condition_branch_tests;
+mod context_window_tests;
 mod document_loader_unit_tests;
 mod document_tests;
+mod edge_transform_tests;
 mod embedding_tests;
 mod error_comprehensive_tests;
 mod error_tests;
+mod execution_report_tests;
+mod execution_service_tests;
+mod expression_tests;
+mod external_event_tests;
+mod gemini_provider_tests;
 mod graph_advanced_tests;
+mod graph_analysis_tests;
+mod graph_mutation_tests;
+mod graph_query_tests;
+mod guardrail_node_tests;
+mod handoff_tests;
+mod health_check_tests;
+mod history_window_tests;
+mod hosted_provider_tests;
+mod http_client_tests;
+mod http_tool_tests;
+mod idempotency_tests;
+mod join_policy_tests;
+mod llm_middleware_tests;
+mod llm_profile_tests;
 mod llm_provider_tests;
+mod llm_rerank_tests;
 mod llm_tests;
+mod local_embedding_tests;
+mod memory_reembed_tests;
+mod metrics_tests;
+mod node_output_delta_tests;
+mod node_outputs_tests;
+mod pacing_tests;
+mod partial_failure_tests;
+mod payload_capture_tests;
 mod python_bindings_tests;
 mod serialization_comprehensive_tests;
 mod stream_tests;
 mod text_splitter_tests;
+mod tool_argument_validation_tests;
+mod tool_context_tests;
+mod tool_registry_tests;
+mod transcript_tests;
 mod type_tests;
 mod types_comprehensive_tests;
 mod validation_tests;
 mod workflow_tests;
 
+mod attachment_tests;
+mod concurrency_key_tests;
+mod document_parse_tests;
+mod embedding_retry_tests;
+mod env_config_tests;
+mod huggingface_provider_tests;
+mod input_schema_tests;
+mod json_repair_tests;
+mod model_router_tests;
+mod node_llm_override_tests;
+mod node_type_execution_tests;
+mod output_repair_tests;
+mod output_store_tests;
+mod post_process_tests;
+mod prompt_defaults_tests;
+mod python_code_tests;
+mod rate_limit_tests;
+mod reexecution_tests;
+mod registered_agent_tests;
+mod request_timeout_tests;
+mod result_sink_tests;
+mod retrieval_embedding_tests;
+mod run_history_tests;
+mod secret_ref_tests;
+mod shell_command_tests;
+mod shutdown_tests;
+mod similarity_tests;
+mod split_branch_tests;
 mod system_prompt_tests;
+mod tag_filter_tests;
 mod test_helpers;
+mod tool_schema_tests;
+mod usage_attribution_tests;
+mod warm_up_tests;
+mod workflow_inputs_tests;
+mod workflow_result_json_tests;
+mod workflow_template_tests;
@@ -0,0 +1,207 @@
+//! Model router nodes escalating from cheap to expensive models
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::LlmConfig,
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+
+const CHEAP: &str = "gpt-4o-mini";
+const EXPENSIVE: &str = "gpt-4o";
+
+/// Start a server speaking the `OpenAI` chat completions API that answers
+/// with the script entry for the requested model and records the models
+/// asked. A model without an entry gets a server error.
+async fn spawn_llm_server(
+    script: Vec<(&'static str, &'static str)>,
+) -> (String, Arc<Mutex<Vec<String>>>) {
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let log = asked.clone();
+    let server = MockServer::new(move |request| {
+        let model = request.json()["model"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        log.lock().unwrap().push(model.clone());
+        let response = match script.iter().find(|(name, _)| *name == model) {
+            Some((_, content)) => MockResponse::json(
+                200,
+                json!({
+                    "id": "chatcmpl-1",
+                    "model": model,
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": content},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 100, "completion_tokens": 10, "total_tokens": 110}
+                }),
+            ),
+            None => MockResponse::json(500, json!({"error": {"message": "model unavailable"}})),
+        };
+        std::future::ready(response)
+    })
+    .await;
+    (format!("{}/v1", server.url()), asked)
+}
+
+fn router(url: &str, escalation_expression: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        "answer",
+        "",
+        NodeType::ModelRouter {
+            config: AgentNodeConfig::new(AgentId::new(), "Is the sky blue?"),
+            candidates: vec![
+                LlmConfig::openai_with_base_url("sk-test", CHEAP, url),
+                LlmConfig::openai_with_base_url("sk-test", EXPENSIVE, url),
+            ],
+            escalation_expression: escalation_expression.to_string(),
+        },
+    )
+}
+
+async fn run(node: WorkflowNode) -> WorkflowContext {
+    let mut workflow = Workflow::new("routing", "");
+    workflow.add_node(node).unwrap();
+    WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+/// Routing record of the `answer` node
+fn routing(context: &WorkflowContext) -> &Value {
+    &context.metadata["node_response_answer"]["model_router"]
+}
+
+#[tokio::test]
+async fn test_low_confidence_escalates_to_the_next_model() {
+    let (url, asked) = spawn_llm_server(vec![
+        (CHEAP, r#"{"answer": "maybe", "confidence": 0.4}"#),
+        (EXPENSIVE, r#"{"answer": "yes", "confidence": 0.95}"#),
+    ])
+    .await;
+
+    let context = run(router(&url, "confidence < 0.7")).await;
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(*asked.lock().unwrap(), vec![CHEAP, EXPENSIVE]);
+    assert_eq!(context.get_node_output("answer").unwrap()["answer"], "yes");
+
+    let routing = routing(&context);
+    assert_eq!(routing["answered_by"], EXPENSIVE);
+    let attempts = routing["attempts"].as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0]["model"], CHEAP);
+    assert_eq!(attempts[0]["escalated"], true);
+    assert_eq!(attempts[1]["escalated"], false);
+    let cheap_cost = attempts[0]["cost_usd"].as_f64().unwrap();
+    let expensive_cost = attempts[1]["cost_usd"].as_f64().unwrap();
+    assert!((cheap_cost - 100.0f64.mul_add(0.000_000_15, 10.0 * 0.000_000_6)).abs() < 1e-12);
+    assert!((expensive_cost - 100.0f64.mul_add(0.000_005, 10.0 * 0.000_015)).abs() < 1e-12);
+    let total_cost = routing["total_cost_usd"].as_f64().unwrap();
+    assert!((total_cost - (cheap_cost + expensive_cost)).abs() < 1e-12);
+
+    // Both attempts' tokens count towards the node, each call tagged with its attempt
+    let response = &context.metadata["node_response_answer"];
+    let calls = response["executions"].as_array().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0]["attempt"], 0);
+    assert_eq!(calls[1]["attempt"], 1);
+    let report = context.to_report();
+    let node = &report.nodes[0];
+    assert_eq!(node.model.as_deref(), Some(EXPENSIVE));
+    assert_eq!(node.token_usage.as_ref().unwrap().total_tokens, 220);
+}
+
+#[tokio::test]
+async fn test_confident_answer_stops_at_the_first_model() {
+    let (url, asked) = spawn_llm_server(vec![
+        (CHEAP, r#"{"answer": "yes", "confidence": 0.9}"#),
+        (EXPENSIVE, r#"{"answer": "yes", "confidence": 0.95}"#),
+    ])
+    .await;
+
+    let context = run(router(&url, "confidence < 0.7")).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(*asked.lock().unwrap(), vec![CHEAP]);
+    let routing = routing(&context);
+    assert_eq!(routing["answered_by"], CHEAP);
+    assert_eq!(routing["attempts"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_and_unjudgeable_answers_escalate() {
+    // The cheap model fails outright
+    let (url, asked) = spawn_llm_server(vec![(EXPENSIVE, "Yes")]).await;
+    let context = run(router(&url, "confidence < 0.7")).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(*asked.lock().unwrap(), vec![CHEAP, EXPENSIVE]);
+    let routing = routing(&context);
+    assert!(routing["attempts"][0]["error"].is_string());
+    assert_eq!(routing["answered_by"], EXPENSIVE);
+
+    // Plain text has no confidence to compare, and the last model answers anyway
+    let (url, asked) = spawn_llm_server(vec![(CHEAP, "Yes"), (EXPENSIVE, "Yes")]).await;
+    let context = run(router(&url, "confidence < 0.7")).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(*asked.lock().unwrap(), vec![CHEAP, EXPENSIVE]);
+    assert_eq!(context.get_node_output("answer"), Some(&json!("Yes")));
+}
+
+#[tokio::test]
+async fn test_schema_validity_and_finish_reason_can_be_judged() {
+    let (url, asked) = spawn_llm_server(vec![
+        (CHEAP, "It is blue"),
+        (
+            EXPENSIVE,
+            r#"```json
+{"answer": "yes"}
+```"#,
+        ),
+    ])
+    .await;
+    let node =
+        router(&url, "not schema_valid or finish_reason != 'stop'").with_output_schema(json!({
+            "type": "object",
+            "properties": {"answer": {"type": "string"}},
+            "required": ["answer"]
+        }));
+
+    let context = run(node).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(*asked.lock().unwrap(), vec![CHEAP, EXPENSIVE]);
+    assert_eq!(
+        context.get_node_output("answer"),
+        Some(&json!({"answer": "yes"}))
+    );
+}
+
+#[test]
+fn test_invalid_routers_are_rejected() {
+    let url = "http://127.0.0.1:9/v1";
+    let mut node = router(url, "confidence < 0.7");
+    if let NodeType::ModelRouter { candidates, .. } = &mut node.node_type {
+        candidates.clear();
+    }
+    let error = node.validate().unwrap_err().to_string();
+    assert!(error.contains("at least one candidate"), "{error}");
+
+    let error = router(url, "confidence <")
+        .validate()
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("invalid escalation expression"), "{error}");
+    assert!(router(url, "confidence < 0.7").validate().is_ok());
+}
@@ -0,0 +1,128 @@
+use super::test_helpers::{Requests, ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmRequest, LlmRole},
+    types::AgentId,
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::sync::Arc;
+
+fn agent_node(name: &str, agent_id: &AgentId) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Agent node",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), format!("Prompt for {name}")),
+        },
+    )
+}
+
+fn system_message(request: &LlmRequest) -> Option<&str> {
+    request
+        .messages
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::System))
+        .map(|m| m.content.as_str())
+}
+
+/// Register an agent with its own sampling settings, returning the requests
+/// it receives
+async fn register_capturing_agent(executor: &WorkflowExecutor) -> (AgentId, Requests) {
+    let agent_id = AgentId::new();
+    let provider = ScriptedProvider::replying("ok");
+    let requests = provider.requests();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Agent", "Capturing agent", LlmConfig::default())
+            .with_id(agent_id.clone())
+            .with_system_prompt("Agent system prompt")
+            .with_temperature(0.7)
+            .with_max_tokens(500),
+        provider,
+    ));
+    executor.register_agent(agent).await;
+    (agent_id, requests)
+}
+
+#[tokio::test]
+async fn test_node_overrides_reach_the_llm_request() {
+    let executor = WorkflowExecutor::new().without_retries();
+    let (extract_agent, extract_requests) = register_capturing_agent(&executor).await;
+    let (synthesize_agent, synthesize_requests) = register_capturing_agent(&executor).await;
+
+    let mut workflow = Workflow::new("overrides", "Per-node LLM settings");
+    let extract = workflow
+        .add_node(
+            agent_node("extract", &extract_agent)
+                .with_system_prompt("Extract entities")
+                .with_temperature(0.0)
+                .with_max_tokens(64),
+        )
+        .unwrap();
+    let synthesize = workflow
+        .add_node(agent_node("synthesize", &synthesize_agent))
+        .unwrap();
+    workflow
+        .connect_nodes(
+            extract,
+            synthesize,
+            graphbit_core::graph::WorkflowEdge::data_flow(),
+        )
+        .unwrap();
+
+    executor.execute(workflow, None).await.unwrap();
+
+    let extract_requests = extract_requests.lock().unwrap();
+    let synthesize_requests = synthesize_requests.lock().unwrap();
+    assert_eq!(extract_requests.len(), 1);
+    assert_eq!(synthesize_requests.len(), 1);
+
+    // Node-level settings win over the agent configuration
+    let extract_request = &extract_requests[0];
+    assert_eq!(system_message(extract_request), Some("Extract entities"));
+    assert_eq!(extract_request.temperature, Some(0.0));
+    assert_eq!(extract_request.max_tokens, Some(64));
+
+    // Without node overrides the agent configuration applies
+    let synthesize_request = &synthesize_requests[0];
+    assert_eq!(
+        system_message(synthesize_request),
+        Some("Agent system prompt")
+    );
+    assert_eq!(synthesize_request.temperature, Some(0.7));
+    assert_eq!(synthesize_request.max_tokens, Some(500));
+}
+
+#[test]
+fn test_resolve_llm_config_priority() {
+    let executor_default = LlmConfig::openai("sk-executor", "gpt-4o");
+    let node = agent_node("n", &AgentId::new());
+
+    // Executor default when the node sets nothing
+    let resolved = node.resolve_llm_config(Some(&executor_default)).unwrap();
+    assert_eq!(resolved.provider_name(), "openai");
+    assert_eq!(resolved.model_name(), "gpt-4o");
+    assert!(node.resolve_llm_config(None).is_none());
+
+    // Model override keeps the fallback provider
+    let node = node.with_model("gpt-4o-mini");
+    let resolved = node.resolve_llm_config(Some(&executor_default)).unwrap();
+    assert_eq!(resolved.provider_name(), "openai");
+    assert_eq!(resolved.model_name(), "gpt-4o-mini");
+
+    // Full node config wins, with the model override applied on top
+    let node = node.with_llm_config(&LlmConfig::anthropic("sk-ant", "claude-3-5-sonnet"));
+    let resolved = node.resolve_llm_config(Some(&executor_default)).unwrap();
+    assert_eq!(resolved.provider_name(), "anthropic");
+    assert_eq!(resolved.model_name(), "gpt-4o-mini");
+}
+
+#[test]
+fn test_llm_config_with_model() {
+    let azure = LlmConfig::azurellm_with_defaults("key", "old-deployment", "https://x.azure.com")
+        .with_model("new-deployment");
+    assert_eq!(azure.model_name(), "new-deployment");
+
+    let ollama = LlmConfig::ollama("llama3").with_model("qwen2");
+    assert_eq!(ollama.model_name(), "qwen2");
+}
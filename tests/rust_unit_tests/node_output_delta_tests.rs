@@ -0,0 +1,179 @@
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmToolCall},
+    stream::{StreamEvent, StreamMode},
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowContext},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Ten text chunks spelling out a sentence
+fn ten_chunks() -> Vec<LlmResponse> {
+    (0..10)
+        .map(|i| LlmResponse::new(format!("word{i} "), "chunked-model"))
+        .collect()
+}
+
+/// Run a single agent node against scripted streams, collecting every event
+async fn run_streaming(
+    calls: Vec<Vec<LlmResponse>>,
+    stream_mode: StreamMode,
+    registry: Option<ToolRegistry>,
+) -> (WorkflowContext, Vec<StreamEvent>) {
+    let agent_id = AgentId::new();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Writer", "Streams its answer", LlmConfig::default())
+            .with_id(agent_id.clone()),
+        ScriptedProvider::streaming(calls),
+    ));
+
+    let mut executor = WorkflowExecutor::new().without_retries();
+    let mut node = WorkflowNode::new(
+        "writer",
+        "Writes an answer",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, "Write ten words"),
+        },
+    );
+    if let Some(registry) = registry {
+        executor = executor.with_tool_registry(registry);
+        node = node.with_tools(["add"]);
+    }
+    executor.register_agent(agent).await;
+
+    let mut workflow = Workflow::new("streaming", "Streamed agent output");
+    workflow.add_node(node).unwrap();
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let collector = tokio::spawn(async move {
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    });
+    let context = executor
+        .execute_streaming(workflow, None, tx, stream_mode)
+        .await
+        .unwrap();
+    (context, collector.await.unwrap())
+}
+
+fn deltas(events: &[StreamEvent]) -> Vec<&str> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            StreamEvent::NodeOutputDelta {
+                node_name, delta, ..
+            } => {
+                assert_eq!(node_name, "writer");
+                Some(delta.as_str())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn token_count(events: &[StreamEvent]) -> usize {
+    events
+        .iter()
+        .filter(|e| matches!(e, StreamEvent::Token { .. }))
+        .count()
+}
+
+#[tokio::test]
+async fn test_agent_output_is_streamed_as_deltas() {
+    let (context, events) = run_streaming(vec![ten_chunks()], StreamMode::Messages, None).await;
+
+    let deltas = deltas(&events);
+    assert_eq!(deltas.len(), 10);
+    assert_eq!(deltas[0], "word0 ");
+    assert_eq!(token_count(&events), 10);
+
+    // The full response is still assembled for the context
+    let expected: String = deltas.concat();
+    assert_eq!(context.get_node_output("writer"), Some(&json!(expected)));
+
+    // Deltas arrive before the node completes
+    let last_delta = events
+        .iter()
+        .rposition(|e| matches!(e, StreamEvent::NodeOutputDelta { .. }))
+        .unwrap();
+    let completed = events
+        .iter()
+        .position(|e| matches!(e, StreamEvent::NodeCompleted { .. }))
+        .unwrap();
+    assert!(last_delta < completed);
+}
+
+#[tokio::test]
+async fn test_tool_call_fragments_are_not_deltas() {
+    let registry = ToolRegistry::new();
+    let handler: ToolHandler = Arc::new(|args: serde_json::Value| {
+        Box::pin(async move {
+            Ok(json!(
+                args["a"].as_i64().unwrap_or_default() + args["b"].as_i64().unwrap_or_default()
+            ))
+        })
+    });
+    registry
+        .register_tool(
+            ToolMetadata::new(
+                "add",
+                "Add two integers",
+                json!({
+                    "type": "object",
+                    "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+                    "required": ["a", "b"]
+                }),
+            ),
+            handler,
+        )
+        .unwrap();
+
+    let tool_chunk = LlmResponse::new("[tool_call add {\"a\":2,\"b\":3}]", "chunked-model")
+        .with_tool_calls(vec![LlmToolCall {
+            id: "call_add".to_string(),
+            name: "add".to_string(),
+            parameters: json!({"a": 2, "b": 3}),
+        }]);
+    let (context, events) = run_streaming(
+        vec![vec![tool_chunk], ten_chunks()],
+        StreamMode::All,
+        Some(registry),
+    )
+    .await;
+
+    // The tool-call fragment is a token but never a delta
+    assert_eq!(token_count(&events), 11);
+    let deltas = deltas(&events);
+    assert_eq!(deltas.len(), 10);
+    assert!(deltas.iter().all(|d| !d.contains("tool_call")));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        StreamEvent::ToolCallCompleted { tool_name, .. } if tool_name == "add"
+    )));
+    assert_eq!(
+        context.get_node_output("writer"),
+        Some(&json!(deltas.concat()))
+    );
+}
+
+#[tokio::test]
+async fn test_updates_mode_emits_no_deltas() {
+    let (context, events) = run_streaming(vec![ten_chunks()], StreamMode::Updates, None).await;
+
+    assert!(deltas(&events).is_empty());
+    assert_eq!(token_count(&events), 0);
+    assert!(
+        context
+            .get_node_output("writer")
+            .and_then(|v| v.as_str())
+            .is_some_and(|output| output.starts_with("word0 ") && output.ends_with("word9 "))
+    );
+}
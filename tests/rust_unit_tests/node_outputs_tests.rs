@@ -0,0 +1,226 @@
+//! Node outputs shared between a workflow context and its snapshots: the map
+//! API, snapshot isolation, concurrent writers and sharing on wide graphs
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    output_store::{OutputRef, OutputSpill},
+    tools::ToolContext,
+    types::{NodeId, NodeOutputs, Secrets, WorkflowContext, WorkflowId},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[test]
+fn test_behaves_like_a_map() {
+    let mut outputs = NodeOutputs::new();
+    assert!(outputs.is_empty());
+
+    assert!(outputs.insert("a".to_string(), json!(1)).is_none());
+    assert!(
+        outputs
+            .insert("b".to_string(), json!({"x": [1, 2]}))
+            .is_none()
+    );
+    assert_eq!(
+        *outputs.insert("a".to_string(), json!(2)).unwrap(),
+        json!(1)
+    );
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs.get("a"), Some(&json!(2)));
+    assert!(outputs.contains_key("b"));
+    assert!(outputs.get("missing").is_none());
+
+    let mut keys: Vec<_> = outputs.keys().cloned().collect();
+    keys.sort();
+    assert_eq!(keys, ["a", "b"]);
+
+    let json = serde_json::to_value(&outputs).unwrap();
+    assert_eq!(json, json!({"a": 2, "b": {"x": [1, 2]}}));
+    let restored: NodeOutputs = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.len(), 2);
+    assert_eq!(restored.get("b"), outputs.get("b"));
+
+    assert_eq!(*outputs.remove("a").unwrap(), json!(2));
+    assert!(outputs.remove("a").is_none());
+    assert_eq!(outputs.len(), 1);
+    let owned: HashMap<String, serde_json::Value> = outputs.into_iter().collect();
+    assert_eq!(
+        owned,
+        HashMap::from([("b".to_string(), json!({"x": [1, 2]}))])
+    );
+}
+
+#[test]
+fn test_snapshots_share_values_and_stay_unchanged() {
+    let mut context = WorkflowContext::new(WorkflowId::new());
+    let node = NodeId::new();
+    context.set_node_output(&node, json!({"text": "x".repeat(1024)}));
+    context.set_node_output_by_name("keep", json!("kept"));
+
+    let snapshot = context.node_outputs.clone();
+    let key = node.to_string();
+    assert!(Arc::ptr_eq(
+        &snapshot.get_shared(&key).unwrap(),
+        &context.node_outputs.get_shared(&key).unwrap()
+    ));
+
+    // Writes after the snapshot, including in-place redaction, leave it alone
+    context.secrets = Secrets::from(HashMap::from([("token".to_string(), "kept".to_string())]));
+    context.set_node_output_by_name("later", json!("new"));
+    context.node_outputs.remove(&key);
+    context.redact_secrets();
+
+    assert_eq!(
+        context.get_node_output("keep"),
+        Some(&json!(Secrets::REDACTED))
+    );
+    assert!(context.get_node_output(&key).is_none());
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot.get("keep"), Some(&json!("kept")));
+    assert!(snapshot.get("later").is_none());
+    assert_eq!(
+        snapshot.get(&key).unwrap()["text"].as_str().unwrap().len(),
+        1024
+    );
+}
+
+#[test]
+fn test_snapshot_reads_spilled_outputs_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut context = WorkflowContext::new(WorkflowId::new())
+        .with_output_spill(OutputSpill::to_directory(dir.path()).unwrap());
+    let large = json!({"text": "x".repeat(OutputSpill::DEFAULT_THRESHOLD_BYTES)});
+    context.set_node_output_by_name("large", large.clone());
+    context.set_node_output_by_name("small", json!("small"));
+
+    assert!(OutputRef::from_value(context.get_node_output("large").unwrap()).is_some());
+    let snapshot = context.snapshot_node_outputs();
+    assert_eq!(snapshot.get("large"), Some(&large));
+    assert!(Arc::ptr_eq(
+        &snapshot.get_shared("small").unwrap(),
+        &context.node_outputs.get_shared("small").unwrap()
+    ));
+
+    let tool_context = ToolContext::new(&context, "reader");
+    assert_eq!(tool_context.node_output("large"), Some(&large));
+    assert_eq!(tool_context.node_outputs().len(), 2);
+}
+
+/// Writers store outputs through a shared context while readers take
+/// snapshots under the lock and check them after releasing it
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_writers_and_snapshot_readers() {
+    const WRITERS: usize = 8;
+    const OUTPUTS_PER_WRITER: usize = 50;
+
+    for _ in 0..20 {
+        let context = Arc::new(Mutex::new(WorkflowContext::new(WorkflowId::new())));
+        let mut tasks = Vec::new();
+        for writer in 0..WRITERS {
+            let context = Arc::clone(&context);
+            tasks.push(tokio::spawn(async move {
+                for i in 0..OUTPUTS_PER_WRITER {
+                    let name = format!("w{writer}-{i}");
+                    let output = json!({"writer": writer, "i": i});
+                    context.lock().await.set_node_output_by_name(&name, output);
+
+                    let snapshot = context.lock().await.node_outputs.clone();
+                    // A snapshot is consistent: its length matches its entries, every
+                    // value belongs to its key, and this writer's outputs are all there
+                    assert_eq!(snapshot.iter().count(), snapshot.len());
+                    for (key, value) in &snapshot {
+                        let expected = format!("w{}-{}", value["writer"], value["i"]);
+                        assert_eq!(key, &expected);
+                    }
+                    for seen in 0..=i {
+                        assert!(snapshot.contains_key(&format!("w{writer}-{seen}")));
+                    }
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(
+            context.lock().await.node_outputs.len(),
+            WRITERS * OUTPUTS_PER_WRITER
+        );
+    }
+}
+
+/// `source` fans a large payload out to `width` transforms reading it
+fn wide_workflow(width: usize) -> Workflow {
+    let mut workflow = Workflow::new("wide", "");
+    let source = WorkflowNode::new(
+        "source",
+        "",
+        NodeType::Transform {
+            transformation: "vars.payload".to_string(),
+        },
+    );
+    let source_id = source.id.clone();
+    workflow.add_node(source).unwrap();
+    for i in 0..width {
+        let branch = WorkflowNode::new(
+            format!("branch_{i}"),
+            "",
+            NodeType::Transform {
+                transformation: "input.id".to_string(),
+            },
+        );
+        let branch_id = branch.id.clone();
+        workflow.add_node(branch).unwrap();
+        workflow
+            .connect_nodes(source_id.clone(), branch_id, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+#[tokio::test]
+async fn test_wide_graph_shares_outputs() {
+    const WIDTH: usize = 64;
+    let payload = json!({"id": "report", "text": "x".repeat(256 * 1024)});
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_inputs(
+            wide_workflow(WIDTH),
+            None,
+            HashMap::from([("payload".to_string(), payload.clone())]),
+            Secrets::default(),
+        )
+        .await
+        .unwrap();
+    for i in 0..WIDTH {
+        assert_eq!(
+            context.get_node_output(&format!("branch_{i}")),
+            Some(&json!("report"))
+        );
+    }
+
+    // A snapshot shares every shard and every output with the original
+    let mut outputs: NodeOutputs = (0..WIDTH)
+        .map(|i| (format!("branch_{i}"), payload.clone()))
+        .collect();
+    let snapshot = outputs.clone();
+    assert_eq!(outputs.unshared_shards(&snapshot), 0);
+
+    // Writing after the snapshot copies the keys of one shard, never the outputs
+    outputs.insert("late".to_string(), json!("late"));
+    assert_eq!(outputs.unshared_shards(&snapshot), 1);
+    outputs.insert("later".to_string(), json!("later"));
+    assert!(outputs.unshared_shards(&snapshot) <= 2);
+    assert!(!snapshot.contains_key("late"));
+    for i in 0..WIDTH {
+        let key = format!("branch_{i}");
+        assert!(Arc::ptr_eq(
+            &outputs.get_shared(&key).unwrap(),
+            &snapshot.get_shared(&key).unwrap()
+        ));
+    }
+}
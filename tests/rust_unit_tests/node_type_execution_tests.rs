@@ -0,0 +1,281 @@
+use super::test_helpers::{MockRequests, MockResponse, MockServer};
+use graphbit_core::{
+    clock::TestClock,
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::{ConcurrencyConfig, ConcurrencyManager, RetryConfig, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Start an HTTP server answering every request with `status` and a JSON `body`
+async fn spawn_json_server(status: u16, body: &'static str) -> String {
+    let server = MockServer::responding(MockResponse::json(status, body)).await;
+    format!("{}/data", server.url())
+}
+
+/// Start an HTTP server answering with 503 until the third request, recording requests
+async fn spawn_flaky_server() -> (String, MockRequests) {
+    let server = MockServer::replaying(vec![
+        MockResponse::new(503, "unavailable"),
+        MockResponse::new(503, "unavailable"),
+        MockResponse::new(200, r#"{"ok":true}"#),
+    ])
+    .await;
+    (format!("{}/data", server.url()), server.requests())
+}
+
+/// Server answering every request after 100ms, recording the peak number of
+/// requests in flight at once.
+async fn spawn_barrier_server() -> (String, Arc<AtomicUsize>) {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let peak_seen = peak.clone();
+    let server = MockServer::new(move |_| {
+        let in_flight = in_flight.clone();
+        let peak = peak.clone();
+        async move {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            MockResponse::new(200, r#"{"ok":true}"#)
+        }
+    })
+    .await;
+    (format!("{}/data", server.url()), peak_seen)
+}
+
+fn parallel_http_workflow(url: &str, count: usize) -> Workflow {
+    let mut workflow = Workflow::new("parallel", "Independent HTTP nodes");
+    for i in 0..count {
+        workflow
+            .add_node(http_node(&format!("fetch_{i}"), url.to_string()))
+            .unwrap();
+    }
+    workflow
+}
+
+fn http_node(name: &str, url: String) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Fetch data",
+        NodeType::HttpRequest {
+            url,
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+        },
+    )
+}
+
+fn transform_node(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Transform data",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_mixed_node_types_execute() {
+    let url = spawn_json_server(200, r#"{"message":"hello"}"#).await;
+
+    let mut workflow = Workflow::new("mixed", "Non-agent node types");
+    let fetch = workflow.add_node(http_node("fetch", url)).unwrap();
+    let split = workflow
+        .add_node(WorkflowNode::new("split", "Fan out", NodeType::Split))
+        .unwrap();
+    let stringify = workflow
+        .add_node(transform_node("stringify", "json_stringify"))
+        .unwrap();
+    let wait = workflow
+        .add_node(WorkflowNode::new(
+            "wait",
+            "Pause",
+            NodeType::Delay {
+                duration_seconds: 0,
+            },
+        ))
+        .unwrap();
+    let upper = workflow
+        .add_node(transform_node("upper", "uppercase"))
+        .unwrap();
+    let join = workflow
+        .add_node(WorkflowNode::new("join", "Fan in", NodeType::Join))
+        .unwrap();
+    for (from, to) in [
+        (&fetch, &split),
+        (&split, &stringify),
+        (&split, &wait),
+        (&stringify, &upper),
+        (&upper, &join),
+        (&wait, &join),
+    ] {
+        workflow
+            .connect_nodes(from.clone(), to.clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow.validate().unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let fetched = json!({"status": 200, "body": {"message": "hello"}});
+    assert_eq!(context.get_node_output("split"), Some(&fetched));
+    assert_eq!(context.get_node_output("wait"), Some(&fetched));
+    assert_eq!(
+        context.get_node_output("upper"),
+        Some(&json!(r#"{"BODY":{"MESSAGE":"HELLO"},"STATUS":200}"#))
+    );
+    assert_eq!(
+        context.get_node_output("join"),
+        Some(&json!({
+            "upper": r#"{"BODY":{"MESSAGE":"HELLO"},"STATUS":200}"#,
+            "wait": fetched,
+        }))
+    );
+}
+
+#[tokio::test]
+async fn test_http_request_node_fails_on_error_status() {
+    let url = spawn_json_server(404, r#"{"error":"missing"}"#).await;
+
+    let mut workflow = Workflow::new("http_error", "HTTP node hitting a 404");
+    workflow.add_node(http_node("fetch", url)).unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(context.get_stats().unwrap().failed_nodes, 1);
+    assert_eq!(context.get_node_output("fetch"), Some(&json!(null)));
+}
+
+#[tokio::test]
+async fn test_unsupported_transformation_fails() {
+    let mut workflow = Workflow::new("bad_transform", "Unknown transformation");
+    workflow
+        .add_node(transform_node("reverse", "reverse"))
+        .unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(context.get_stats().unwrap().failed_nodes, 1);
+    assert_eq!(context.get_node_output("reverse"), Some(&json!(null)));
+}
+
+#[tokio::test]
+async fn test_node_timeout_bounds_execution() {
+    let mut workflow = Workflow::new("timeout", "Delay longer than its timeout");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "slow",
+                "Pause",
+                NodeType::Delay {
+                    duration_seconds: 30,
+                },
+            )
+            .with_timeout(1),
+        )
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let context = WorkflowExecutor::new()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(start.elapsed().as_secs() < 5);
+    assert_eq!(context.get_stats().unwrap().failed_nodes, 1);
+}
+
+#[tokio::test]
+async fn test_node_retry_config_overrides_executor() {
+    let (url, requests) = spawn_flaky_server().await;
+
+    let mut workflow = Workflow::new("flaky", "HTTP node failing twice");
+    workflow
+        .add_node(
+            http_node("fetch", url).with_retry_config(
+                RetryConfig::new(4)
+                    .with_exponential_backoff(5_000, 2.0, 60_000)
+                    .with_jitter(0.0),
+            ),
+        )
+        .unwrap();
+
+    // The node's own retry configuration applies even with executor retries
+    // disabled; its backoff passes on a frozen clock, taking no real time
+    let started = std::time::Instant::now();
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_clock(Arc::new(TestClock::frozen(chrono::Utc::now())))
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(started.elapsed().as_secs() < 5);
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_stats().unwrap().failed_nodes, 0);
+    assert_eq!(context.get_node_attempts("fetch"), Some(3));
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    // Backoff of 5s, then 10s
+    let record = &context.get_node_executions()[0];
+    assert_eq!(record.duration_ms, 15_000);
+}
+
+#[tokio::test]
+async fn test_node_type_limit_bounds_parallelism() {
+    let (url, peak) = spawn_barrier_server().await;
+
+    let mut config = ConcurrencyConfig::high_throughput();
+    config
+        .node_type_limits
+        .insert("http_request".to_string(), 2);
+    let manager = Arc::new(ConcurrencyManager::new(config));
+
+    let context = WorkflowExecutor::new()
+        .with_concurrency_manager(manager.clone())
+        .execute(parallel_http_workflow(&url, 6), None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+    let stats = manager.get_stats().await;
+    assert_eq!(stats.total_permit_acquisitions, 6);
+    assert_eq!(stats.peak_active_tasks, 2);
+    assert_eq!(stats.current_active_tasks, 0);
+}
+
+#[tokio::test]
+async fn test_global_limit_bounds_parallelism() {
+    let (url, peak) = spawn_barrier_server().await;
+
+    let mut config = ConcurrencyConfig::high_throughput();
+    config.global_max_concurrency = 3;
+    let context = WorkflowExecutor::new_high_throughput()
+        .with_concurrency_config(config)
+        .execute(parallel_http_workflow(&url, 8), None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(peak.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,220 @@
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    json_repair::JsonRepair,
+    llm::{LlmConfig, LlmRequest, LlmResponse, LlmRole},
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+fn person_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "name": {"type": "string"},
+            "age": {"type": "integer"}
+        },
+        "required": ["name", "age"]
+    })
+}
+
+/// Run a single agent node with `person_schema()` against scripted responses
+async fn run_extraction(
+    responses: &[&str],
+    max_repair_attempts: Option<u32>,
+) -> (WorkflowContext, Vec<LlmRequest>) {
+    run_extraction_with(responses, |node| match max_repair_attempts {
+        Some(attempts) => node.with_max_repair_attempts(attempts),
+        None => node,
+    })
+    .await
+}
+
+/// Like `run_extraction`, with the node adjusted by `configure`
+async fn run_extraction_with(
+    responses: &[&str],
+    configure: impl FnOnce(WorkflowNode) -> WorkflowNode,
+) -> (WorkflowContext, Vec<LlmRequest>) {
+    let provider = ScriptedProvider::replaying(
+        responses
+            .iter()
+            .map(|content| LlmResponse::new(*content, "scripted-model"))
+            .collect(),
+    );
+    let requests = provider.requests();
+    let agent_id = AgentId::new();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Extractor", "Extracts people", LlmConfig::default())
+            .with_id(agent_id.clone()),
+        provider,
+    ));
+
+    let executor = WorkflowExecutor::new().without_retries();
+    executor.register_agent(agent).await;
+
+    let node = configure(
+        WorkflowNode::new(
+            "extract",
+            "Extract a person",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(agent_id, "Extract the person from: Ada, 36"),
+            },
+        )
+        .with_output_schema(person_schema()),
+    );
+
+    let mut workflow = Workflow::new("extraction", "Schema-validated extraction");
+    workflow.add_node(node).unwrap();
+    let context = executor.execute(workflow, None).await.unwrap();
+    let requests = requests.lock().unwrap().clone();
+    (context, requests)
+}
+
+#[tokio::test]
+async fn test_valid_output_needs_no_repair() {
+    let (context, requests) = run_extraction(&[r#"{"name": "Ada", "age": 36}"#], None).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(requests.len(), 1);
+    let report = context.get_output_validation("extract").unwrap();
+    assert_eq!(report.repair_attempts, 0);
+    assert!(report.is_valid());
+}
+
+#[tokio::test]
+async fn test_invalid_output_is_repaired() {
+    let (context, requests) = run_extraction(
+        &[r#"{"name": "Ada"}"#, r#"{"name": "Ada", "age": 36}"#],
+        None,
+    )
+    .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("extract"),
+        Some(&json!({"name": "Ada", "age": 36}))
+    );
+
+    let report = context.get_output_validation("extract").unwrap();
+    assert_eq!(report.repair_attempts, 1);
+    assert!(report.is_valid());
+
+    // The repair request replays the invalid output and the validation errors
+    assert_eq!(requests.len(), 2);
+    let repair = &requests[1].messages;
+    let assistant = repair
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::Assistant))
+        .unwrap();
+    assert_eq!(assistant.content, r#"{"name":"Ada"}"#);
+    let instruction = &repair.last().unwrap().content;
+    assert!(instruction.contains("Fix your output"));
+    assert!(instruction.contains("age"));
+}
+
+#[tokio::test]
+async fn test_node_fails_when_repairs_run_out() {
+    let (context, requests) =
+        run_extraction(&["not json", r#"{"name": 42, "age": 36}"#], Some(1)).await;
+
+    let stats = context.get_stats().unwrap();
+    assert_eq!(stats.successful_nodes, 0);
+    assert_eq!(stats.failed_nodes, 1);
+    assert_eq!(requests.len(), 2);
+
+    let report = context.get_output_validation("extract").unwrap();
+    assert_eq!(report.repair_attempts, 1);
+    assert!(!report.is_valid());
+    assert!(!report.validation.errors.is_empty());
+}
+
+#[tokio::test]
+async fn test_fenced_output_is_recovered_without_repair_request() {
+    let (context, requests) = run_extraction(
+        &["Sure! Here it is:\n```json\n{\"name\": \"Ada\", \"age\": 36,}\n```"],
+        None,
+    )
+    .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        context.get_node_output("extract"),
+        Some(&json!({"name": "Ada", "age": 36}))
+    );
+    let report = context.get_output_validation("extract").unwrap();
+    assert_eq!(report.repair_attempts, 0);
+    assert_eq!(
+        report.json_repairs,
+        [
+            JsonRepair::StrippedCodeFence,
+            JsonRepair::RemovedTrailingCommas
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(report).unwrap()["json_repairs"],
+        json!(["stripped_code_fence", "removed_trailing_commas"])
+    );
+}
+
+#[tokio::test]
+async fn test_repair_response_is_recovered() {
+    let (context, requests) = run_extraction(
+        &["not json", "```json\n{\"name\": \"Ada\", \"age\": 36}\n```"],
+        None,
+    )
+    .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(requests.len(), 2);
+    let report = context.get_output_validation("extract").unwrap();
+    assert_eq!(report.repair_attempts, 1);
+    assert_eq!(report.json_repairs, [JsonRepair::StrippedCodeFence]);
+}
+
+#[tokio::test]
+async fn test_strict_json_skips_recovery() {
+    let fenced = "```json\n{\"name\": \"Ada\", \"age\": 36}\n```";
+    let (context, requests) =
+        run_extraction_with(&[fenced, r#"{"name": "Ada", "age": 36}"#], |node| {
+            node.with_strict_json(true)
+        })
+        .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    // The fenced output went back to the model instead
+    assert_eq!(requests.len(), 2);
+    let report = context.get_output_validation("extract").unwrap();
+    assert_eq!(report.repair_attempts, 1);
+    assert!(report.json_repairs.is_empty());
+    assert!(
+        serde_json::to_value(report)
+            .unwrap()
+            .get("json_repairs")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_valid_string_output_is_not_recovered() {
+    // Output that already satisfies the schema is kept as returned
+    let (context, _) = run_extraction_with(&["Ada is [36] years old"], |node| {
+        node.with_output_schema(json!({"type": "string"}))
+    })
+    .await;
+
+    assert_eq!(
+        context.get_node_output("extract"),
+        Some(&json!("Ada is [36] years old"))
+    );
+    assert!(
+        context
+            .get_output_validation("extract")
+            .unwrap()
+            .json_repairs
+            .is_empty()
+    );
+}
@@ -0,0 +1,197 @@
+//! Spilling large node outputs to a content-addressed output store
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    output_store::{OutputRef, OutputSpill},
+    types::{WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Length of the text in the synthetic response, about 4 MB
+const TEXT_LEN: usize = 4 * 1024 * 1024;
+
+/// Start an HTTP server answering every request with the same multi-MB JSON body
+async fn spawn_large_body_server() -> String {
+    let body = json!({ "id": "report-7", "text": "x".repeat(TEXT_LEN) });
+    let server = MockServer::responding(MockResponse::json(200, body)).await;
+    format!("{}/report", server.url())
+}
+
+/// `fetch` downloads the large report and `measure` reads its text length
+fn report_workflow(url: String) -> Workflow {
+    let mut workflow = Workflow::new("spill", "Large output");
+    let fetch = workflow
+        .add_node(WorkflowNode::new(
+            "fetch",
+            "Download the report",
+            NodeType::HttpRequest {
+                url,
+                method: "GET".to_string(),
+                headers: HashMap::new(),
+            },
+        ))
+        .unwrap();
+    let measure = workflow
+        .add_node(WorkflowNode::new(
+            "measure",
+            "Measure the report",
+            NodeType::Transform {
+                transformation: "{'id': input.body.id, 'length': len(input.body.text)}".to_string(),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(fetch, measure, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+async fn run(url: &str, spill: Option<OutputSpill>) -> WorkflowContext {
+    let mut executor = WorkflowExecutor::new().without_retries();
+    if let Some(spill) = spill {
+        executor = executor.with_output_spill(spill);
+    }
+    let context = executor
+        .execute(report_workflow(url.to_string()), None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    context
+}
+
+fn stored_files(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+#[test]
+fn test_spill_threshold_and_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let spill = OutputSpill::to_directory(dir.path()).unwrap();
+    assert_eq!(
+        spill.threshold_bytes(),
+        OutputSpill::DEFAULT_THRESHOLD_BYTES
+    );
+
+    assert_eq!(spill.spill(&json!({"small": true})).unwrap(), None);
+    assert!(stored_files(dir.path()).is_empty());
+
+    let large = json!({ "text": "y".repeat(2 * 1024 * 1024) });
+    let output_ref = spill.spill(&large).unwrap().expect("expected a spill");
+    assert_eq!(output_ref.sha256.len(), 64);
+    assert_eq!(
+        output_ref.size_bytes,
+        serde_json::to_vec(&large).unwrap().len() as u64
+    );
+    assert_eq!(spill.load(&output_ref).unwrap(), large);
+
+    let marker = output_ref.to_value();
+    assert_eq!(OutputRef::from_value(&marker), Some(output_ref.clone()));
+    assert_eq!(OutputRef::from_value(&json!({"sha256": "abc"})), None);
+    // A reference is never spilled again
+    assert_eq!(spill.spill(&marker).unwrap(), None);
+    assert_eq!(
+        stored_files(dir.path()),
+        vec![format!("{}.json", output_ref.sha256)]
+    );
+}
+
+#[test]
+fn test_load_rejects_modified_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let spill = OutputSpill::to_directory(dir.path()).unwrap();
+    let output_ref = spill
+        .spill(&json!("z".repeat(2 * 1024 * 1024)))
+        .unwrap()
+        .unwrap();
+
+    let path = dir.path().join(format!("{}.json", output_ref.sha256));
+    std::fs::write(&path, "\"tampered\"").unwrap();
+    let err = spill.load(&output_ref).unwrap_err();
+    assert!(err.to_string().contains("content hash"), "{err}");
+}
+
+#[tokio::test]
+async fn test_spilled_output_is_transparent_downstream() {
+    let url = spawn_large_body_server().await;
+    let dir = tempfile::tempdir().unwrap();
+    let spill = OutputSpill::to_directory(dir.path()).unwrap();
+    let context = run(&url, Some(spill)).await;
+
+    // The context holds only a reference to the large output...
+    let stored = context.get_node_output("fetch").unwrap();
+    let output_ref = OutputRef::from_value(stored).expect("expected a spilled output");
+    assert!(output_ref.size_bytes > TEXT_LEN as u64);
+    assert_eq!(context.get_variable("fetch"), Some(stored));
+
+    // ...while downstream nodes, templates and loads see the full value
+    assert_eq!(
+        context.get_node_output("measure"),
+        Some(&json!({"id": "report-7", "length": TEXT_LEN}))
+    );
+    let full = context.load_node_output("fetch").unwrap();
+    assert_eq!(full["status"], json!(200));
+    assert_eq!(full["body"]["text"].as_str().unwrap().len(), TEXT_LEN);
+    assert_eq!(
+        WorkflowExecutor::resolve_template_variables(
+            "Summarize {{node.fetch.body.id}} ({{node.measure.length}} chars)",
+            &context,
+        ),
+        format!("Summarize report-7 ({TEXT_LEN} chars)")
+    );
+
+    let stats = context.get_stats().unwrap();
+    assert_eq!(stats.spilled_outputs, 1);
+    assert_eq!(stats.spilled_bytes, output_ref.size_bytes);
+}
+
+#[tokio::test]
+async fn test_spilling_shrinks_serialized_context() {
+    let url = spawn_large_body_server().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let in_memory = serde_json::to_string(&run(&url, None).await).unwrap();
+    let spilled_context = run(&url, Some(OutputSpill::to_directory(dir.path()).unwrap())).await;
+    let spilled = serde_json::to_string(&spilled_context).unwrap();
+
+    // Without spilling the output is stored under the node id and name and
+    // again in the variables; with spilling none of the copies remain
+    assert!(in_memory.len() > 2 * TEXT_LEN, "{}", in_memory.len());
+    assert!(spilled.len() < 64 * 1024, "{}", spilled.len());
+
+    // A restored context reads its spilled outputs once the store is attached again
+    let restored: WorkflowContext = serde_json::from_str(&spilled).unwrap();
+    assert!(restored.output_spill.is_none());
+    let restored = restored.with_output_spill(OutputSpill::to_directory(dir.path()).unwrap());
+    assert_eq!(
+        restored.load_node_output("fetch").unwrap()["body"]["id"],
+        json!("report-7")
+    );
+}
+
+#[tokio::test]
+async fn test_spilled_outputs_are_deduplicated_across_runs() {
+    let url = spawn_large_body_server().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let first = run(&url, Some(OutputSpill::to_directory(dir.path()).unwrap())).await;
+    let second = run(&url, Some(OutputSpill::to_directory(dir.path()).unwrap())).await;
+
+    assert_eq!(
+        first.get_node_output("fetch"),
+        second.get_node_output("fetch")
+    );
+    let files = stored_files(dir.path());
+    assert_eq!(files.len(), 1, "{files:?}");
+    assert!(files[0].ends_with(".json"));
+}
@@ -0,0 +1,297 @@
+//! Pacing: delays before and after nodes, their jitter, the minimum interval
+//! between LLM calls and the timing breakdown of the execution report. The
+//! executor waits on the Tokio clock, so these tests run in paused time.
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmMiddleware, LlmRequest, LlmResponse},
+    pacing::{LlmCallSpacing, NodePacing, PacingDelays},
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Start of every LLM call on the Tokio clock, with its prompt
+type Calls = Arc<Mutex<Vec<(String, Instant)>>>;
+
+/// Executor with one agent answering every node at once and recording in
+/// `calls` when each call reached it
+async fn executor(agent_id: &AgentId, calls: &Calls) -> WorkflowExecutor {
+    let calls = calls.clone();
+    let provider = ScriptedProvider::new(move |request| {
+        let prompt = request
+            .messages
+            .last()
+            .map(|message| message.content.clone())
+            .unwrap_or_default();
+        calls.lock().unwrap().push((prompt, Instant::now()));
+        Ok(LlmResponse::new("answer", "scripted-model"))
+    });
+    let executor = WorkflowExecutor::new().without_retries();
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Worker", "", LlmConfig::default()).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+    executor
+}
+
+fn agent_node(agent_id: &AgentId, name: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), format!("Work as {name}")),
+        },
+    )
+    .with_registered_agent()
+}
+
+/// Start of the call made by node `name`
+fn call_of(calls: &Calls, name: &str) -> Instant {
+    calls
+        .lock()
+        .unwrap()
+        .iter()
+        .find_map(|(prompt, at)| prompt.contains(&format!("Work as {name}")).then_some(*at))
+        .unwrap_or_else(|| panic!("node '{name}' made no LLM call"))
+}
+
+fn record_pacing(context: &WorkflowContext, name: &str) -> Option<PacingDelays> {
+    context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == name)
+        .unwrap_or_else(|| panic!("node '{name}' did not run"))
+        .pacing
+}
+
+const fn paced(pre_execution_delay_ms: u64, post_execution_delay_ms: u64) -> NodePacing {
+    NodePacing {
+        pre_execution_delay_ms,
+        post_execution_delay_ms,
+        jitter_ms: 0,
+    }
+}
+
+#[test]
+fn test_node_pacing_reads_and_validates_the_node_config() {
+    let node = WorkflowNode::new("fetch", "", NodeType::Split).with_pacing(NodePacing {
+        pre_execution_delay_ms: 250,
+        post_execution_delay_ms: 0,
+        jitter_ms: 50,
+    });
+    assert_eq!(node.config.get("pre_execution_delay_ms"), Some(&json!(250)));
+    assert!(!node.config.contains_key("post_execution_delay_ms"));
+    assert_eq!(
+        node.pacing(),
+        Some(NodePacing {
+            pre_execution_delay_ms: 250,
+            post_execution_delay_ms: 0,
+            jitter_ms: 50,
+        })
+    );
+    assert!(node.validate().is_ok());
+
+    assert_eq!(NodePacing::from_node_config(&HashMap::new()).unwrap(), None);
+    let config = |key: &str, value: serde_json::Value| HashMap::from([(key.to_string(), value)]);
+    assert!(NodePacing::from_node_config(&config("post_execution_delay_ms", json!(-5))).is_err());
+    assert!(NodePacing::from_node_config(&config("pre_execution_delay_ms", json!("1s"))).is_err());
+    // Jitter only extends delays
+    let mut node = WorkflowNode::new("fetch", "", NodeType::Split);
+    node.config
+        .insert("delay_jitter_ms".to_string(), json!(100));
+    let error = node.validate().unwrap_err().to_string();
+    assert!(error.contains("delay_jitter_ms"), "{error}");
+}
+
+#[test]
+fn test_jitter_extends_each_delay_within_its_range() {
+    let pacing = NodePacing {
+        pre_execution_delay_ms: 100,
+        post_execution_delay_ms: 0,
+        jitter_ms: 50,
+    };
+    for _ in 0..50 {
+        let delay = pacing.pre_execution_delay();
+        assert!(
+            delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150),
+            "{delay:?}"
+        );
+        assert_eq!(pacing.post_execution_delay(), Duration::ZERO);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_delays_space_nodes_outside_their_duration() {
+    let agent_id = AgentId::new();
+    let calls = Calls::default();
+    let mut workflow = Workflow::new("paced", "");
+    let fetch = workflow
+        .add_node(agent_node(&agent_id, "fetch").with_pacing(paced(0, 500)))
+        .unwrap();
+    let parse = workflow
+        .add_node(agent_node(&agent_id, "parse").with_pacing(paced(300, 0)))
+        .unwrap();
+    workflow
+        .connect_nodes(fetch, parse, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let started = Instant::now();
+    let context = executor(&agent_id, &calls)
+        .await
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(call_of(&calls, "fetch"), started);
+    // The post-execution delay of the parent and the pre-execution delay of
+    // the child add up
+    assert_eq!(
+        call_of(&calls, "parse") - call_of(&calls, "fetch"),
+        Duration::from_millis(800)
+    );
+    assert_eq!(
+        record_pacing(&context, "fetch"),
+        Some(PacingDelays {
+            pre_execution_ms: 0,
+            post_execution_ms: 500,
+            llm_spacing_ms: 0,
+        })
+    );
+    assert_eq!(
+        record_pacing(&context, "parse"),
+        Some(PacingDelays {
+            pre_execution_ms: 300,
+            post_execution_ms: 0,
+            llm_spacing_ms: 0,
+        })
+    );
+    // The waits are not part of the measured durations
+    for record in context.get_node_executions() {
+        assert!(record.duration_ms < 300, "{record:?}");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_min_interval_spaces_concurrent_llm_calls() {
+    let agent_id = AgentId::new();
+    let calls = Calls::default();
+    let mut workflow = Workflow::new("fan-out", "");
+    for name in ["a", "b", "c"] {
+        workflow.add_node(agent_node(&agent_id, name)).unwrap();
+    }
+
+    let started = Instant::now();
+    let context = executor(&agent_id, &calls)
+        .await
+        .with_min_interval_between_llm_calls(1000)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    let mut starts: Vec<Instant> = calls.lock().unwrap().iter().map(|(_, at)| *at).collect();
+    starts.sort();
+    assert_eq!(
+        starts,
+        vec![
+            started,
+            started + Duration::from_secs(1),
+            started + Duration::from_secs(2)
+        ]
+    );
+    // The nodes waited 0, 1000 and 2000ms for their turn, within their duration
+    let mut waits: Vec<u64> = ["a", "b", "c"]
+        .iter()
+        .map(|name| record_pacing(&context, name).map_or(0, |pacing| pacing.llm_spacing_ms))
+        .collect();
+    waits.sort_unstable();
+    assert_eq!(waits, vec![0, 1000, 2000]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_llm_call_spacing_carries_over_between_calls() {
+    let spacing = LlmCallSpacing::new(200);
+    let started = Instant::now();
+    let mut request = LlmRequest::new("ping");
+    for _ in 0..3 {
+        spacing.before_request(&mut request).await.unwrap();
+    }
+    assert_eq!(started.elapsed(), Duration::from_millis(400));
+
+    // A call after a quiet period does not wait
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let quiet = Instant::now();
+    spacing.before_request(&mut request).await.unwrap();
+    assert_eq!(quiet.elapsed(), Duration::ZERO);
+    assert_eq!(spacing.interval(), Duration::from_millis(200));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_report_shows_the_timing_breakdown() {
+    let agent_id = AgentId::new();
+    let calls = Calls::default();
+    let mut workflow = Workflow::new("paced", "");
+    workflow
+        .add_node(agent_node(&agent_id, "fetch").with_pacing(paced(100, 200)))
+        .unwrap();
+    workflow.add_node(agent_node(&agent_id, "idle")).unwrap();
+
+    let report = executor(&agent_id, &calls)
+        .await
+        .execute(workflow, None)
+        .await
+        .unwrap()
+        .to_report();
+
+    let fetch = report
+        .nodes
+        .iter()
+        .find(|node| node.node_name == "fetch")
+        .unwrap();
+    assert_eq!(fetch.pacing.map(PacingDelays::total_ms), Some(300));
+    let idle = report
+        .nodes
+        .iter()
+        .find(|node| node.node_name == "idle")
+        .unwrap();
+    assert_eq!(idle.pacing, None);
+
+    let json = serde_json::to_value(&report).unwrap();
+    let nodes = json["nodes"].as_array().unwrap();
+    let fetch = nodes
+        .iter()
+        .find(|node| node["node_name"] == "fetch")
+        .unwrap();
+    assert_eq!(fetch["pacing"]["pre_execution_ms"], 100);
+    assert_eq!(fetch["pacing"]["post_execution_ms"], 200);
+    let idle = nodes
+        .iter()
+        .find(|node| node["node_name"] == "idle")
+        .unwrap();
+    assert!(idle.get("pacing").is_none());
+    assert!(
+        report
+            .to_html()
+            .contains("waited 100 ms before, 200 ms after"),
+        "{}",
+        report.to_html()
+    );
+}
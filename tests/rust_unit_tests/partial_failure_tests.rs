@@ -0,0 +1,190 @@
+//! Failed nodes without fail-fast: successes kept, dependents skipped
+
+use graphbit_core::{
+    graph::{JoinPolicy, NodeType, PendingBranches, WorkflowEdge, WorkflowNode},
+    types::{AbandonedNode, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+/// Workflow with the given nodes and `(from, to)` edges between node names
+fn workflow(nodes: Vec<WorkflowNode>, edges: &[(&str, &str)]) -> Workflow {
+    let mut workflow = Workflow::new("partial_failure", "");
+    for node in nodes {
+        workflow.add_node(node).unwrap();
+    }
+    for (from, to) in edges {
+        let from = workflow.graph.get_node_id_by_name(from).unwrap();
+        let to = workflow.graph.get_node_id_by_name(to).unwrap();
+        workflow
+            .connect_nodes(from, to, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+/// `source` feeding a `left` and a `right` branch that meet in `join`,
+/// followed by `after`
+fn diamond(left: &str, right: &str) -> Workflow {
+    workflow(
+        vec![
+            transform("source", "'question'"),
+            transform("left", left),
+            transform("right", right),
+            WorkflowNode::new("join", "", NodeType::Join),
+            transform("after", "input"),
+        ],
+        &[
+            ("source", "left"),
+            ("source", "right"),
+            ("left", "join"),
+            ("right", "join"),
+            ("join", "after"),
+        ],
+    )
+}
+
+async fn run(workflow: Workflow, fail_fast: bool) -> WorkflowContext {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_fail_fast(fail_fast)
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+fn blocked_by(names: &[&str]) -> AbandonedNode {
+    AbandonedNode::Blocked {
+        blocked_by: names.iter().map(|name| (*name).to_string()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_failed_branch_keeps_other_outputs_and_skips_join() {
+    let context = run(diamond("input + '!'", "1 / 0"), false).await;
+
+    match &context.state {
+        WorkflowState::PartiallyCompleted { failed_nodes } => {
+            assert_eq!(failed_nodes, &["right".to_string()]);
+        }
+        state => panic!("expected a partially completed workflow, got {state:?}"),
+    }
+    assert!(context.state.is_terminal());
+    assert_eq!(context.get_node_output("source"), Some(&json!("question")));
+    assert_eq!(context.get_node_output("left"), Some(&json!("question!")));
+    assert_eq!(context.get_node_output("join"), None);
+    assert_eq!(context.get_node_output("after"), None);
+
+    // Nodes downstream of the join are blocked through it
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([
+            ("join".to_string(), blocked_by(&["right"])),
+            ("after".to_string(), blocked_by(&["right"])),
+        ])
+    );
+
+    let stats = context.stats.clone().unwrap();
+    assert_eq!(stats.successful_nodes, 2);
+    assert_eq!(stats.failed_nodes, 1);
+    assert_eq!(stats.skipped_nodes, 2);
+
+    let failed: Vec<_> = context
+        .get_node_executions()
+        .iter()
+        .filter(|record| !record.success)
+        .map(|record| record.node_name.as_str())
+        .collect();
+    assert_eq!(failed, ["right"]);
+}
+
+#[tokio::test]
+async fn test_blocked_nodes_list_every_failed_dependency() {
+    let context = run(diamond("1 / 0", "num('x')"), false).await;
+
+    let WorkflowState::PartiallyCompleted { mut failed_nodes } = context.state.clone() else {
+        panic!(
+            "expected a partially completed workflow, got {:?}",
+            context.state
+        );
+    };
+    failed_nodes.sort();
+    assert_eq!(failed_nodes, ["left", "right"]);
+    assert_eq!(
+        context.abandoned_nodes.get("after"),
+        Some(&blocked_by(&["left", "right"]))
+    );
+    assert_eq!(context.stats.unwrap().skipped_nodes, 2);
+}
+
+#[tokio::test]
+async fn test_fail_fast_fails_the_workflow() {
+    let context = run(diamond("input + '!'", "1 / 0"), true).await;
+
+    match &context.state {
+        WorkflowState::Failed { error, .. } => assert!(error.contains("right"), "{error}"),
+        state => panic!("expected a failed workflow, got {state:?}"),
+    }
+    assert_eq!(context.get_node_output("after"), None);
+}
+
+#[tokio::test]
+async fn test_any_join_runs_with_the_successful_branch() {
+    let race = workflow(
+        vec![
+            transform("source", "'question'"),
+            WorkflowNode::new(
+                "slow",
+                "",
+                NodeType::Delay {
+                    duration_seconds: 1,
+                },
+            ),
+            transform("broken", "1 / 0"),
+            WorkflowNode::new("join", "", NodeType::Join)
+                .with_join_policy(JoinPolicy::Any)
+                .with_pending_branches(PendingBranches::Cancel),
+        ],
+        &[
+            ("source", "slow"),
+            ("source", "broken"),
+            ("slow", "join"),
+            ("broken", "join"),
+        ],
+    );
+    let context = run(race, false).await;
+    assert!(
+        context.state.is_partially_completed(),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(
+        context.get_node_output("join"),
+        Some(&json!({"slow": "question"}))
+    );
+    assert!(context.abandoned_nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_workflow_without_failures_completes() {
+    let context = run(diamond("input + '!'", "input + '?'"), false).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("after"),
+        Some(&json!({"left": "question!", "right": "question?"}))
+    );
+    let stats = context.stats.unwrap();
+    assert_eq!((stats.failed_nodes, stats.skipped_nodes), (0, 0));
+}
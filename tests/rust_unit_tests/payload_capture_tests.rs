@@ -0,0 +1,190 @@
+//! Capture of rendered prompts and raw provider payloads per node
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::LlmConfig,
+    report::ReportConfig,
+    types::{AgentId, PayloadCaptureConfig, Secrets, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+const API_KEY: &str = "sk-capture-secret-1234";
+
+fn completion() -> String {
+    json!({
+        "id": "chatcmpl-1",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "Done"},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6}
+    })
+    .to_string()
+}
+
+/// Start a server answering every request with an `OpenAI` chat completion
+async fn spawn_llm_server() -> String {
+    MockServer::responding(MockResponse::json(200, completion()))
+        .await
+        .url()
+}
+
+fn workflow() -> Workflow {
+    let mut workflow = Workflow::new("capture", "");
+    workflow
+        .add_node(WorkflowNode::new(
+            "Summarize",
+            "",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(AgentId::new(), "Summarize {{globals.topic}}"),
+            },
+        ))
+        .unwrap();
+    workflow
+}
+
+fn executor(url: &str) -> WorkflowExecutor {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(LlmConfig::openai_with_base_url(
+            API_KEY,
+            "gpt-4o",
+            format!("{url}/v1"),
+        ))
+        .with_globals(HashMap::from([("topic".to_string(), json!("rust"))]))
+}
+
+async fn run(executor: WorkflowExecutor) -> WorkflowContext {
+    let context = executor.execute(workflow(), None).await.unwrap();
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    context
+}
+
+#[tokio::test]
+async fn test_capture_records_rendered_prompt_and_raw_payloads() {
+    let url = spawn_llm_server().await;
+    let context = run(executor(&url).with_capture_payloads(true)).await;
+
+    // The provider still parses the response the capture consumed
+    assert_eq!(context.get_node_output("Summarize"), Some(&json!("Done")));
+
+    let debug = context.get_node_debug("Summarize").unwrap();
+    assert_eq!(debug.rendered_prompt.as_deref(), Some("Summarize rust"));
+    assert_eq!(debug.exchanges.len(), 1);
+
+    let exchange = &debug.exchanges[0];
+    assert_eq!(exchange.provider, "openai");
+    assert_eq!(exchange.method, "POST");
+    assert_eq!(exchange.url, format!("{url}/v1/chat/completions"));
+    assert_eq!(exchange.status, Some(200));
+    assert_eq!(
+        exchange.response_body.as_deref(),
+        Some(completion().as_str())
+    );
+    assert!(!exchange.truncated);
+
+    let body: Value = serde_json::from_str(&exchange.request_body).unwrap();
+    assert_eq!(body["model"], "gpt-4o");
+    assert!(body["messages"].to_string().contains("Summarize rust"));
+}
+
+#[tokio::test]
+async fn test_capture_redacts_authorization_headers() {
+    let url = spawn_llm_server().await;
+    let context = run(executor(&url).with_capture_payloads(true)).await;
+
+    let exchange = &context.get_node_debug("Summarize").unwrap().exchanges[0];
+    assert_eq!(
+        exchange
+            .request_headers
+            .get("authorization")
+            .map(String::as_str),
+        Some(Secrets::REDACTED)
+    );
+    assert_eq!(
+        exchange
+            .request_headers
+            .get("content-type")
+            .map(String::as_str),
+        Some("application/json")
+    );
+    let captured = serde_json::to_string(exchange).unwrap();
+    assert!(!captured.contains(API_KEY), "{captured}");
+}
+
+#[tokio::test]
+async fn test_nothing_is_captured_by_default() {
+    let url = spawn_llm_server().await;
+    let context = run(executor(&url)).await;
+
+    assert!(context.get_node_debug("Summarize").is_none());
+    assert!(context.node_debug.nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_captures_are_serialized_only_when_persisted() {
+    let url = spawn_llm_server().await;
+
+    let context = run(executor(&url).with_capture_payloads(true)).await;
+    assert!(context.get_node_debug("Summarize").is_some());
+    let serialized = serde_json::to_value(&context).unwrap();
+    assert!(serialized.get("node_debug").is_none());
+
+    let persisted = PayloadCaptureConfig::default().with_persist(true);
+    let context = run(executor(&url).with_payload_capture_config(persisted)).await;
+    let restored: WorkflowContext =
+        serde_json::from_value(serde_json::to_value(&context).unwrap()).unwrap();
+    assert_eq!(
+        restored.get_node_debug("Summarize"),
+        context.get_node_debug("Summarize")
+    );
+}
+
+#[tokio::test]
+async fn test_captured_payloads_are_size_capped() {
+    let url = spawn_llm_server().await;
+    let config = PayloadCaptureConfig::default().with_max_payload_chars(10);
+    let context = run(executor(&url).with_payload_capture_config(config)).await;
+
+    let debug = context.get_node_debug("Summarize").unwrap();
+    assert_eq!(
+        debug.rendered_prompt.as_deref(),
+        Some("Summarize ... [truncated 4 chars]")
+    );
+    let exchange = &debug.exchanges[0];
+    assert!(exchange.truncated);
+    assert!(exchange.request_body.ends_with("chars]"));
+    assert!(
+        exchange
+            .response_body
+            .as_deref()
+            .unwrap()
+            .starts_with(&completion()[..10])
+    );
+}
+
+#[tokio::test]
+async fn test_report_includes_payloads_when_enabled() {
+    let url = spawn_llm_server().await;
+    let context = run(executor(&url).with_capture_payloads(true)).await;
+
+    let report = context.to_report();
+    assert!(report.nodes[0].debug.is_none());
+    assert!(!report.to_html().contains("Debug payloads"));
+
+    let report = context.to_report_with(&ReportConfig::default().with_payloads(true));
+    let debug = report.nodes[0].debug.as_ref().unwrap();
+    assert_eq!(debug.rendered_prompt.as_deref(), Some("Summarize rust"));
+    let html = report.to_html();
+    assert!(html.contains("<details class=\"debug\">"));
+    assert!(html.contains("Rendered prompt"));
+    assert!(!html.contains(API_KEY));
+}
@@ -0,0 +1,260 @@
+//! Post-processing of node outputs before they are stored
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    graph::{NodeType, WorkflowNode},
+    post_process::{JsonPath, PostProcessor, RegexGroup, apply_all},
+    types::{WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+const LISTING: &str =
+    r#"{"items":[{"title":"First Post"},{"title":"Second Post"}],"summary":"Two posts"}"#;
+
+/// Start an HTTP server answering every request with the `LISTING` JSON
+async fn spawn_listing_server() -> String {
+    let server = MockServer::responding(MockResponse::json(200, LISTING)).await;
+    format!("{}/posts", server.url())
+}
+
+fn fetch_node(url: String) -> WorkflowNode {
+    WorkflowNode::new(
+        "fetch",
+        "Fetch posts",
+        NodeType::HttpRequest {
+            url,
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+        },
+    )
+}
+
+fn apply(operations: Value, output: Value) -> graphbit_core::GraphBitResult<Value> {
+    let config = HashMap::from([("post_process".to_string(), operations)]);
+    let processors = PostProcessor::from_node_config(&config)?;
+    apply_all(&processors, output, &|template| template.to_string())
+}
+
+fn node_error(context: &WorkflowContext, name: &str) -> String {
+    context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == name)
+        .and_then(|record| record.error.clone())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_json_path_selects_keys_and_indexes() {
+    let listing: Value = serde_json::from_str(LISTING).unwrap();
+    for (path, expected) in [
+        ("$", listing.clone()),
+        ("$.summary", json!("Two posts")),
+        ("$.items[0].title", json!("First Post")),
+        ("$.items[-1]['title']", json!("Second Post")),
+    ] {
+        let selected = JsonPath::parse(path).unwrap().select(&listing).cloned();
+        assert_eq!(selected, Some(expected), "{path}");
+    }
+    assert_eq!(
+        JsonPath::parse("$.items[2]").unwrap().select(&listing),
+        None
+    );
+
+    for invalid in ["summary", "$.", "$.items[x]", "$['open"] {
+        assert!(JsonPath::parse(invalid).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_json_path_parses_text_outputs() {
+    let output = json!("Here you go:\n```json\n{\"summary\": \"Short\"}\n```");
+    assert_eq!(
+        apply(json!([{"json_path": "$.summary"}]), output).unwrap(),
+        json!("Short")
+    );
+
+    let error = apply(
+        json!([{"json_path": "$.missing"}]),
+        json!({"summary": "Short"}),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("'$.missing' matched nothing"), "{error}");
+}
+
+#[test]
+fn test_regex_extract_keeps_groups() {
+    let output = json!("Order #4521 shipped on 2024-05-01");
+    assert_eq!(
+        apply(json!([{"regex_extract": "#\\d+"}]), output.clone()).unwrap(),
+        json!("#4521")
+    );
+    assert_eq!(
+        apply(
+            json!([{"regex_extract": {"pattern": "#(\\d+)", "group": 1}}]),
+            output.clone()
+        )
+        .unwrap(),
+        json!("4521")
+    );
+    assert_eq!(
+        apply(
+            json!([{"regex_extract": {"pattern": "on (?P<date>[\\d-]+)", "group": "date"}}]),
+            output.clone()
+        )
+        .unwrap(),
+        json!("2024-05-01")
+    );
+    assert!(apply(json!([{"regex_extract": "refund"}]), output).is_err());
+
+    assert!(PostProcessor::regex_extract("#(\\d+)", RegexGroup::Index(2)).is_err());
+    assert!(PostProcessor::regex_extract("(", RegexGroup::Index(0)).is_err());
+}
+
+#[test]
+fn test_text_operations() {
+    assert_eq!(
+        apply(json!([{"truncate": 5}]), json!("Grüße aus Berlin")).unwrap(),
+        json!("Grüße")
+    );
+    assert_eq!(
+        apply(json!(["to_lowercase"]), json!("Hello World")).unwrap(),
+        json!("hello world")
+    );
+    // JSON values are turned into their JSON text
+    assert_eq!(
+        apply(json!(["to_lowercase"]), json!({"Key": "Value"})).unwrap(),
+        json!(r#"{"key":"value"}"#)
+    );
+
+    let markdown = "# Release *notes*\n\n> **Bold** claim with [a link](https://example.com)\n\n- first `item`\n- second item\n\n---\n\n```\n# not a heading\n```";
+    assert_eq!(
+        apply(json!(["strip_markdown"]), json!(markdown)).unwrap(),
+        json!(
+            "Release notes\n\nBold claim with a link\n\nfirst item\nsecond item\n\n\n# not a heading"
+        )
+    );
+}
+
+#[test]
+fn test_operations_apply_in_order() {
+    let config = HashMap::from([(
+        "post_process".to_string(),
+        json!([
+            {"json_path": "$.title"},
+            {"template": "Title: {{output}} ({{node.fetch}})"},
+            "to_lowercase",
+        ]),
+    )]);
+    let processors = PostProcessor::from_node_config(&config).unwrap();
+    let output = apply_all(&processors, json!({"title": "Launch"}), &|template| {
+        template.replace("{{node.fetch}}", "Fetched")
+    })
+    .unwrap();
+    assert_eq!(output, json!("title: launch (fetched)"));
+}
+
+#[test]
+fn test_invalid_operations_are_rejected() {
+    for (operations, message) in [
+        (json!([{"reverse": true}]), "Unknown operation 'reverse'"),
+        (json!([{"truncate": 0}]), "operation 0"),
+        (json!(["json_path"]), "operation 0"),
+        (
+            json!(["to_lowercase", {"json_path": "items"}]),
+            "operation 1",
+        ),
+        (json!({"truncate": 10}), "Expected a list of operations"),
+    ] {
+        let error = apply(operations.clone(), json!("text"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(message), "{operations}: {error}");
+    }
+
+    let node = WorkflowNode::new("shout", "", NodeType::Split);
+    let mut invalid = node.clone();
+    invalid
+        .config
+        .insert("post_process".to_string(), json!(["shout"]));
+    let error = invalid.validate().unwrap_err().to_string();
+    assert!(
+        error.contains("Node 'shout' has an invalid post_process list"),
+        "{error}"
+    );
+
+    let valid = node.with_post_process([
+        PostProcessor::json_path("$.items[0]").unwrap(),
+        PostProcessor::truncate(100).unwrap(),
+        PostProcessor::StripMarkdown,
+    ]);
+    valid.validate().unwrap();
+    let names: Vec<&str> = valid
+        .post_processors()
+        .iter()
+        .map(PostProcessor::name)
+        .collect();
+    assert_eq!(names, ["json_path", "truncate", "strip_markdown"]);
+}
+
+#[tokio::test]
+async fn test_executor_stores_post_processed_output() {
+    let url = spawn_listing_server().await;
+    let mut workflow = Workflow::new("post_process", "");
+    workflow
+        .add_node(fetch_node(url).with_post_process([
+            PostProcessor::json_path("$.body.items[-1].title").unwrap(),
+            PostProcessor::template("Latest: {{output}}"),
+            PostProcessor::ToLowercase,
+        ]))
+        .unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_capture_payloads(true)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(
+        context.get_node_output("fetch"),
+        Some(&json!("latest: second post"))
+    );
+    let debug = context.get_node_debug("fetch").unwrap();
+    assert_eq!(
+        debug.raw,
+        Some(json!({"status": 200, "body": serde_json::from_str::<Value>(LISTING).unwrap()}))
+    );
+}
+
+#[tokio::test]
+async fn test_executor_fails_node_when_post_processing_fails() {
+    let url = spawn_listing_server().await;
+    let mut workflow = Workflow::new("post_process", "");
+    workflow
+        .add_node(
+            fetch_node(url).with_post_process([PostProcessor::json_path("$.body.next").unwrap()]),
+        )
+        .unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(context.get_stats().unwrap().failed_nodes, 1);
+    assert_eq!(
+        node_error(&context, "fetch"),
+        "Workflow execution error: Post-processing the output of node 'fetch' failed: json_path: '$.body.next' matched nothing"
+    );
+    assert!(context.get_node_debug("fetch").is_none());
+}
@@ -0,0 +1,230 @@
+//! Executor-level system preamble, prompt suffix and `{{globals.NAME}}`
+//! template variables
+
+use super::test_helpers::{Requests, ScriptedProvider, TestAgent};
+use async_trait::async_trait;
+use graphbit_core::{
+    AuditLog, AuditPolicy, AuditSink,
+    agents::AgentConfig,
+    audit::AuditRecord,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmRequest, LlmRole},
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct MemorySink {
+    records: Arc<Mutex<Vec<AuditRecord>>>,
+}
+
+#[async_trait]
+impl AuditSink for MemorySink {
+    async fn write(&self, record: &AuditRecord) -> graphbit_core::GraphBitResult<()> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+/// Register an agent with `system_prompt` on `executor`, returning its id and
+/// the requests it receives
+async fn register_agent(executor: &WorkflowExecutor, system_prompt: &str) -> (AgentId, Requests) {
+    let provider = ScriptedProvider::replying("Done");
+    let requests = provider.requests();
+    let agent_id = AgentId::new();
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Agent", "", LlmConfig::default())
+                .with_id(agent_id.clone())
+                .with_system_prompt(system_prompt),
+            provider,
+        )))
+        .await;
+    (agent_id, requests)
+}
+
+fn agent_node(name: &str, agent_id: AgentId, prompt: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, prompt),
+        },
+    )
+    .with_registered_agent()
+}
+
+/// Content of the system and user messages of `request`
+fn messages(request: &LlmRequest) -> (Option<String>, String) {
+    let content = |role: LlmRole| {
+        request
+            .messages
+            .iter()
+            .find(|message| message.role == role)
+            .map(|message| message.content.clone())
+    };
+    (content(LlmRole::System), content(LlmRole::User).unwrap())
+}
+
+fn globals(values: serde_json::Value) -> HashMap<String, serde_json::Value> {
+    serde_json::from_value(values).unwrap()
+}
+
+#[tokio::test]
+async fn test_preamble_suffix_and_globals_reach_the_provider_and_audit_log() {
+    let sink = MemorySink::default();
+    let log = AuditLog::new(sink.clone(), AuditPolicy::Full);
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_globals(globals(
+            json!({"company_name": "Acme", "support": {"email": "help@acme.test"}}),
+        ))
+        .with_system_preamble("You work for {{globals.company_name}}.")
+        .with_prompt_suffix("Escalate to {{globals.support.email}}.")
+        .with_audit_log(log.clone());
+    let (agent_id, requests) = register_agent(&executor, "Be brief.").await;
+
+    let mut workflow = Workflow::new("support", "");
+    workflow
+        .add_node(agent_node(
+            "reply",
+            agent_id,
+            "Greet the customer on behalf of {{globals.company_name}}.",
+        ))
+        .unwrap();
+    let context = executor.execute(workflow, None).await.unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+
+    let (system, user) = messages(&requests.lock().unwrap()[0]);
+    assert_eq!(system.as_deref(), Some("You work for Acme.\n\nBe brief."));
+    assert_eq!(
+        user,
+        "Greet the customer on behalf of Acme.\n\nEscalate to help@acme.test."
+    );
+
+    log.flush().await.unwrap();
+    let records = sink.records.lock().unwrap();
+    assert!(
+        records[0].prompt.contains("system: You work for Acme."),
+        "{}",
+        records[0].prompt
+    );
+    assert!(records[0].prompt.contains("Escalate to help@acme.test."));
+}
+
+#[tokio::test]
+async fn test_node_globals_take_precedence_over_executor_globals() {
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_globals(globals(json!({"company_name": "Acme", "tone": "formal"})))
+        .with_system_preamble("Tone: {{globals.tone}}.");
+    let (agent_id, requests) = register_agent(&executor, "").await;
+
+    let mut workflow = Workflow::new("tones", "");
+    let first = workflow
+        .add_node(
+            agent_node(
+                "casual",
+                agent_id.clone(),
+                "{{globals.company_name}} {{globals.unknown}}",
+            )
+            .with_globals(globals(json!({"tone": "casual"}))),
+        )
+        .unwrap();
+    let second = workflow
+        .add_node(agent_node(
+            "default",
+            agent_id,
+            "{{globals.company_name}} {{globals.tone}}",
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(first, second, WorkflowEdge::control_flow())
+        .unwrap();
+    executor.execute(workflow, None).await.unwrap();
+
+    let requests = requests.lock().unwrap();
+    let (system, user) = messages(&requests[0]);
+    assert_eq!(system.as_deref(), Some("Tone: casual."));
+    // Unknown globals are left in place, like unknown inputs
+    assert_eq!(user, "Acme {{globals.unknown}}");
+
+    // Node globals do not leak into other nodes
+    let (system, user) = messages(&requests[1]);
+    assert_eq!(system.as_deref(), Some("Tone: formal."));
+    assert!(user.ends_with("Acme formal"), "{user}");
+}
+
+#[tokio::test]
+async fn test_dry_run_renders_prompts_with_defaults() {
+    let executor = WorkflowExecutor::new()
+        .with_default_llm_config(LlmConfig::openai_with_base_url(
+            "sk-test",
+            "gpt-4o-mini",
+            "http://127.0.0.1:1",
+        ))
+        .with_globals(globals(json!({"company_name": "Acme"})))
+        .with_system_preamble("You work for {{globals.company_name}}.")
+        .with_prompt_suffix("Sign as {{globals.company_name}}.");
+
+    let mut workflow = Workflow::new("dry run", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "summarize",
+                "",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(AgentId::new(), "Summarize {{input.topic}}")
+                        .with_system_prompt_override("Use bullet points."),
+                },
+            )
+            .with_globals(globals(json!({"company_name": "Acme Labs"}))),
+        )
+        .unwrap();
+    workflow
+        .add_node(agent_node("plain", AgentId::new(), "Say hello"))
+        .unwrap();
+
+    let report = executor.dry_run(&workflow).await.unwrap();
+    let mut prompts = report.prompts;
+    prompts.sort_by(|a, b| a.node.cmp(&b.node));
+    assert_eq!(prompts.len(), 2);
+
+    assert_eq!(prompts[0].node, "plain");
+    assert_eq!(
+        prompts[0].system_prompt.as_deref(),
+        Some("You work for Acme.")
+    );
+    assert_eq!(prompts[0].prompt, "Say hello\n\nSign as Acme.");
+
+    // Inputs are unknown during a dry run and stay unresolved
+    assert_eq!(prompts[1].node, "summarize");
+    assert_eq!(
+        prompts[1].system_prompt.as_deref(),
+        Some("You work for Acme Labs.\n\nUse bullet points.")
+    );
+    assert_eq!(
+        prompts[1].prompt,
+        "Summarize {{input.topic}}\n\nSign as Acme Labs."
+    );
+}
+
+#[tokio::test]
+async fn test_without_defaults_prompts_are_unchanged() {
+    let executor = WorkflowExecutor::new().without_retries();
+    let (agent_id, requests) = register_agent(&executor, "").await;
+
+    let mut workflow = Workflow::new("plain", "");
+    workflow
+        .add_node(agent_node("plain", agent_id, "Say hello"))
+        .unwrap();
+    executor.execute(workflow, None).await.unwrap();
+
+    let (system, user) = messages(&requests.lock().unwrap()[0]);
+    assert_eq!(system, None);
+    assert_eq!(user, "Say hello");
+    assert!(executor.prompt_defaults().is_empty());
+}
@@ -14,6 +14,17 @@ fn test_document_loader() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: false,
         extraction_settings: Default::default(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
 
     let _loader = DocumentLoader::with_config(config);
@@ -134,6 +145,17 @@ fn test_type_conversions() {
         default_encoding: "utf-8".to_string(),
         preserve_formatting: false,
         extraction_settings: Default::default(),
+        cache_dir: None,
+        max_requests_per_host: 4,
+        requests_per_second: None,
+        max_retries: 3,
+        retry_backoff_ms: 500,
+        batch_timeout_ms: None,
+        max_download_size: None,
+        max_download_retries: 3,
+        download_progress: None,
+        max_parallel_parses: None,
+        max_parse_bytes: 64 * 1024 * 1024,
     };
     assert_eq!(config.max_file_size, 1024 * 1024);
     assert_eq!(config.default_encoding, "utf-8");
@@ -0,0 +1,159 @@
+//! Python code nodes: sandboxed subprocess execution of generated code, only
+//! on executors that allow it
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::{WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+fn python_code(code: &str, timeout_seconds: u64, allowed_imports: &[&str]) -> WorkflowNode {
+    WorkflowNode::new(
+        "code",
+        "",
+        NodeType::PythonCode {
+            code_template: code.to_string(),
+            timeout_seconds,
+            memory_limit_mb: 512,
+            allowed_imports: allowed_imports.iter().map(ToString::to_string).collect(),
+            allow_network: false,
+        },
+    )
+}
+
+/// Workflow feeding the output of a `scores` transform into `code`
+fn workflow(code: WorkflowNode) -> Workflow {
+    let mut workflow = Workflow::new("python_code", "");
+    let scores = workflow
+        .add_node(WorkflowNode::new(
+            "scores",
+            "",
+            NodeType::Transform {
+                transformation: "[3, 4, 5]".to_string(),
+            },
+        ))
+        .unwrap();
+    let code = workflow.add_node(code).unwrap();
+    workflow
+        .connect_nodes(scores, code, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+async fn run(workflow: Workflow) -> WorkflowContext {
+    workflow.validate().unwrap();
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_allow_python_code_nodes(true)
+        .execute(workflow, None)
+        .await
+        .unwrap()
+}
+
+/// Error recorded for the `code` node
+fn code_error(context: &WorkflowContext) -> String {
+    context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == "code")
+        .and_then(|record| record.error.clone())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_captures_stdout_and_the_final_value() {
+    let code = python_code(
+        "import json\nscores = {{node.scores}}\nprint('total', sum(scores))\njson.dumps({'best': max(scores)})",
+        30,
+        &["json"],
+    );
+
+    let context = run(workflow(code)).await;
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("code"),
+        Some(&json!({
+            "stdout": "total 12\n",
+            "stderr": "",
+            "value": "{\"best\": 5}",
+            "exit_status": 0,
+        }))
+    );
+}
+
+#[tokio::test]
+async fn test_runaway_code_is_killed_at_the_timeout() {
+    let started = Instant::now();
+    let context = run(workflow(python_code("while True:\n    pass", 1, &[]))).await;
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let error = code_error(&context);
+    assert!(error.contains("timed out after 1s"), "{error}");
+}
+
+#[tokio::test]
+async fn test_imports_outside_the_allow_list_fail() {
+    let context = run(workflow(python_code(
+        "import socket\nsocket.gethostname()",
+        30,
+        &["json"],
+    )))
+    .await;
+
+    let error = code_error(&context);
+    assert!(
+        error.contains("ImportError: import of 'socket' is not allowed"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_memory_limit_comes_from_the_node() {
+    let mut code = python_code("len(bytearray(256 * 1024 * 1024))", 30, &[]);
+    if let NodeType::PythonCode {
+        memory_limit_mb, ..
+    } = &mut code.node_type
+    {
+        *memory_limit_mb = 128;
+    }
+
+    let context = run(workflow(code)).await;
+    let error = code_error(&context);
+    assert!(error.contains("MemoryError"), "{error}");
+}
+
+#[tokio::test]
+async fn test_python_code_nodes_require_opt_in() {
+    let result = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow(python_code("1", 30, &[])), None)
+        .await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("does not allow Python code nodes"), "{err}");
+}
+
+#[test]
+fn test_zero_memory_limit_is_rejected() {
+    let mut code = python_code("1", 30, &[]);
+    if let NodeType::PythonCode {
+        memory_limit_mb, ..
+    } = &mut code.node_type
+    {
+        *memory_limit_mb = 0;
+    }
+
+    let err = workflow(code).validate().unwrap_err().to_string();
+    assert!(err.contains("memory limit"), "{err}");
+}
+
+#[test]
+fn test_zero_timeout_is_rejected() {
+    let err = workflow(python_code("1", 0, &[]))
+        .validate()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("timeout"), "{err}");
+}
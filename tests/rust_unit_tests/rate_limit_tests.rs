@@ -0,0 +1,36 @@
+//! Token bucket rate limiting
+
+use graphbit_core::clock::{Clock, TestClock};
+use graphbit_core::rate_limit::TokenBucket;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_token_bucket_allows_bursts_up_to_capacity() {
+    let bucket = TokenBucket::new(1.0, 3).unwrap();
+    for _ in 0..3 {
+        assert!(bucket.try_acquire().is_ok());
+    }
+    let wait = bucket.try_acquire().unwrap_err();
+    assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+}
+
+#[test]
+fn test_token_bucket_rejects_invalid_rates() {
+    assert!(TokenBucket::new(0.0, 1).is_none());
+    assert!(TokenBucket::new(-1.0, 1).is_none());
+    assert!(TokenBucket::new(f64::NAN, 1).is_none());
+    assert_eq!(TokenBucket::new(2.5, 0).unwrap().rate_per_second(), 2.5);
+}
+
+#[tokio::test]
+async fn test_token_bucket_spaces_requests() {
+    let clock = Arc::new(TestClock::frozen(chrono::Utc::now()));
+    let bucket = TokenBucket::new(2.0, 1).unwrap().with_clock(clock.clone());
+    let started = clock.instant();
+    for _ in 0..6 {
+        bucket.acquire().await;
+    }
+    // The first token is available at once, the other five 500ms apart
+    assert_eq!(clock.instant() - started, Duration::from_millis(2500));
+}
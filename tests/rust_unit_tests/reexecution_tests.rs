@@ -0,0 +1,237 @@
+//! Re-execution from chosen nodes: only the invalidated subgraph runs, the
+//! nodes upstream of it keep their prior outputs
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    types::{Secrets, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+fn transform(name: &str, transformation: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+}
+
+fn condition(name: &str, expression: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Condition {
+            handler_id: String::new(),
+            expression: Some(expression.to_string()),
+        },
+    )
+}
+
+/// Workflow with the given nodes and `(from, to)` edges between node names.
+/// Every call builds new node ids.
+fn workflow(nodes: Vec<WorkflowNode>, edges: &[(&str, &str)]) -> Workflow {
+    let mut workflow = Workflow::new("reexecution", "");
+    for node in nodes {
+        workflow.add_node(node).unwrap();
+    }
+    for (from, to) in edges {
+        let from = workflow.graph.get_node_id_by_name(from).unwrap();
+        let to = workflow.graph.get_node_id_by_name(to).unwrap();
+        workflow
+            .connect_nodes(from, to, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+/// `a -> b -> c -> d`, each node appending its transformation to its input
+fn chain(b: &str, c: &str) -> Workflow {
+    workflow(
+        vec![
+            transform("a", "'a'"),
+            transform("b", b),
+            transform("c", c),
+            transform("d", "input + 'd'"),
+        ],
+        &[("a", "b"), ("b", "c"), ("c", "d")],
+    )
+}
+
+fn executor() -> WorkflowExecutor {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_fail_fast(false)
+}
+
+async fn run(workflow: Workflow) -> WorkflowContext {
+    executor().execute(workflow, None).await.unwrap()
+}
+
+fn executed(context: &WorkflowContext) -> Vec<&str> {
+    context
+        .get_node_executions()
+        .iter()
+        .map(|record| record.node_name.as_str())
+        .collect()
+}
+
+fn names(names: &[&str]) -> HashSet<String> {
+    names.iter().map(ToString::to_string).collect()
+}
+
+#[tokio::test]
+async fn test_reexecute_from_middle_of_chain() {
+    let prior = run(chain("input + 'b'", "input + 'c'")).await;
+    assert_eq!(prior.get_node_output("d"), Some(&json!("abcd")));
+
+    // The prompt of `c` was fixed: only `c` and `d` run again
+    let context = executor()
+        .reexecute_from(chain("input + 'b'", "input + 'C'"), None, &prior, &["c"])
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(executed(&context), ["c", "d"]);
+    assert_eq!(context.reused_nodes, names(&["a", "b"]));
+    assert!(context.is_reused("b"));
+    assert!(!context.is_reused("c"));
+    assert_eq!(context.get_node_output("b"), Some(&json!("ab")));
+    assert_eq!(context.get_node_output("d"), Some(&json!("abCd")));
+}
+
+#[tokio::test]
+async fn test_reexecute_from_first_node_runs_everything() {
+    let prior = run(chain("input + 'b'", "input + 'c'")).await;
+
+    let context = executor()
+        .reexecute_from(chain("input + 'b'", "input + 'c'"), None, &prior, &["a"])
+        .await
+        .unwrap();
+
+    assert_eq!(executed(&context), ["a", "b", "c", "d"]);
+    assert!(context.reused_nodes.is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_upstream_node_runs_again() {
+    let prior = run(chain("1 / 0", "input + 'c'")).await;
+    assert!(matches!(
+        prior.state,
+        WorkflowState::PartiallyCompleted { .. }
+    ));
+
+    // `b` has no prior output, so it runs again along with everything after it
+    let context = executor()
+        .reexecute_from(chain("input + 'b'", "input + 'c'"), None, &prior, &["d"])
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(executed(&context), ["b", "c", "d"]);
+    assert_eq!(context.reused_nodes, names(&["a"]));
+    assert_eq!(context.get_node_output("d"), Some(&json!("abcd")));
+}
+
+#[tokio::test]
+async fn test_reused_condition_keeps_its_route() {
+    let routing = || {
+        workflow(
+            vec![
+                transform("source", "{score: 0.9}"),
+                condition("is_high", "score > 0.5"),
+                transform("high", "'high'"),
+                transform("low", "'low'"),
+            ],
+            &[
+                ("source", "is_high"),
+                ("is_high", "high"),
+                ("is_high", "low"),
+            ],
+        )
+    };
+    let prior = run(routing()).await;
+    assert_eq!(prior.get_node_output("low"), None);
+
+    let context = executor()
+        .reexecute_from(routing(), None, &prior, &["high"])
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(executed(&context), ["high"]);
+    assert_eq!(context.reused_nodes, names(&["source", "is_high"]));
+    assert_eq!(context.get_node_output("high"), Some(&json!("high")));
+    assert_eq!(context.get_node_output("low"), None);
+}
+
+#[tokio::test]
+async fn test_prior_variables_seed_the_reexecution() {
+    let build = || {
+        workflow(
+            vec![
+                transform("greeting", "'Hello'"),
+                transform("message", "input + ', ' + vars.name"),
+            ],
+            &[("greeting", "message")],
+        )
+    };
+    let prior = executor()
+        .execute_with_inputs(
+            build(),
+            None,
+            HashMap::from([("name".to_string(), json!("Ada"))]),
+            Secrets::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(prior.get_node_output("message"), Some(&json!("Hello, Ada")));
+
+    let context = executor()
+        .reexecute_from(build(), None, &prior, &["message"])
+        .await
+        .unwrap();
+
+    assert_eq!(executed(&context), ["message"]);
+    assert_eq!(
+        context.get_node_output("message"),
+        Some(&json!("Hello, Ada"))
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_reexecutions_are_rejected() {
+    let prior = run(chain("input + 'b'", "input + 'c'")).await;
+    let error = |workflow: Workflow, prior: &WorkflowContext, start: &[&str]| {
+        WorkflowExecutor::reexecution_context(&workflow, prior, start)
+            .unwrap_err()
+            .to_string()
+    };
+
+    let message = error(chain("input + 'b'", "input + 'c'"), &prior, &["e"]);
+    assert!(message.contains("no node 'e'"), "{message}");
+
+    let message = error(chain("input + 'b'", "input + 'c'"), &prior, &[]);
+    assert!(message.contains("start node"), "{message}");
+
+    // `c` now reads from `a`, but only `d` is re-executed
+    let rewired = workflow(
+        vec![
+            transform("a", "'a'"),
+            transform("b", "input + 'b'"),
+            transform("c", "input + 'c'"),
+            transform("d", "input + 'd'"),
+        ],
+        &[("a", "b"), ("a", "c"), ("c", "d")],
+    );
+    let message = error(rewired, &prior, &["d"]);
+    assert!(message.contains("Node 'c' has other parents"), "{message}");
+
+    // A context that never ran records no nodes to reuse
+    let workflow = chain("input + 'b'", "input + 'c'");
+    let empty = WorkflowContext::new(workflow.id.clone());
+    let message = error(workflow, &empty, &["c"]);
+    assert!(message.contains("does not record the nodes"), "{message}");
+}
@@ -0,0 +1,124 @@
+use super::test_helpers::{Requests, ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::{AgentConfig, AgentTrait},
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::LlmConfig,
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Build the shared agent, counting every construction in `constructions`,
+/// with the requests it receives
+fn shared_agent(agent_id: AgentId, constructions: &AtomicUsize) -> (TestAgent, Requests) {
+    constructions.fetch_add(1, Ordering::SeqCst);
+    let provider = ScriptedProvider::replying("shared answer");
+    let requests = provider.requests();
+    let config = AgentConfig::new(
+        "Shared",
+        "Agent shared by several nodes",
+        LlmConfig::default(),
+    )
+    .with_id(agent_id);
+    (TestAgent::new(config, provider), requests)
+}
+
+fn agent_node(name: &str, agent_id: &AgentId) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "Agent node",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), format!("Prompt for {name}")),
+        },
+    )
+}
+
+/// Two nodes referencing the same registered agent, connected in sequence
+fn shared_agent_workflow(agent_id: &AgentId) -> Workflow {
+    let mut workflow = Workflow::new("shared", "Nodes sharing a registered agent");
+    let draft = workflow
+        .add_node(agent_node("draft", agent_id).with_registered_agent())
+        .unwrap();
+    let review = workflow
+        .add_node(agent_node("review", agent_id).with_registered_agent())
+        .unwrap();
+    workflow
+        .connect_nodes(draft, review, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+#[tokio::test]
+async fn test_nodes_share_one_registered_agent() {
+    let constructions = AtomicUsize::new(0);
+    let agent_id = AgentId::new();
+    let (agent, requests) = shared_agent(agent_id.clone(), &constructions);
+    let agent: Arc<dyn AgentTrait> = Arc::new(agent);
+
+    let workflow = shared_agent_workflow(&agent_id);
+    workflow.validate().unwrap();
+
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_agents([agent]);
+    let context = executor.execute(workflow, None).await.unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(constructions.load(Ordering::SeqCst), 1);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(
+        context.get_node_output("draft"),
+        Some(&json!("shared answer"))
+    );
+    assert_eq!(
+        context.get_node_output("review"),
+        Some(&json!("shared answer"))
+    );
+}
+
+#[test]
+fn test_duplicate_agent_ids_still_rejected_without_registration() {
+    let agent_id = AgentId::new();
+    let mut workflow = Workflow::new("duplicates", "Inline agents with one ID");
+    workflow.add_node(agent_node("a", &agent_id)).unwrap();
+    workflow.add_node(agent_node("b", &agent_id)).unwrap();
+
+    let err = workflow.validate().unwrap_err();
+    assert!(err.to_string().contains("Duplicate agent_id"));
+}
+
+#[tokio::test]
+async fn test_missing_registered_agent_fails() {
+    let workflow = shared_agent_workflow(&AgentId::new());
+
+    let err = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("was not registered with the executor")
+    );
+}
+
+#[test]
+fn test_registered_agent_reference_survives_serialization() {
+    let agent_id = AgentId::new();
+    let workflow = shared_agent_workflow(&agent_id);
+
+    let serialized = serde_json::to_value(&workflow).unwrap();
+    let restored: Workflow = serde_json::from_value(serialized.clone()).unwrap();
+    restored.validate().unwrap();
+    assert_eq!(serde_json::to_value(&restored).unwrap(), serialized);
+
+    for node in restored.graph.get_nodes().values() {
+        assert!(node.uses_registered_agent());
+        match &node.node_type {
+            NodeType::Agent { config } => assert_eq!(config.agent_id, agent_id),
+            other => panic!("unexpected node type {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Request and connection timeouts set on LLM and embedding configurations
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    embeddings::{
+        EmbeddingConfig, EmbeddingInput, EmbeddingProvider, EmbeddingProviderFactory,
+        EmbeddingRequest,
+    },
+    errors::GraphBitError,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    http_client::{DEFAULT_CONNECT_TIMEOUT, HttpClientFactory, HttpSettings, RequestTimeouts},
+    llm::{LlmConfig, LlmProviderFactory, LlmRequest},
+    types::{AgentId, RetryConfig, RetryableErrorType, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Start a server that leaves the first `stalls` requests unanswered and
+/// answers the rest with a chat completion, counting the requests it gets
+async fn spawn_stalling_server(stalls: usize) -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let server = MockServer::new(move |_| {
+        let stall = counter.fetch_add(1, Ordering::SeqCst) < stalls;
+        async move {
+            if stall {
+                // Hold the connection open without answering
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            MockResponse::json(
+                200,
+                json!({
+                    "id": "chatcmpl-1",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "pong"},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+                }),
+            )
+        }
+    })
+    .await;
+    (format!("{}/v1", server.url()), requests)
+}
+
+fn openai(url: &str, timeouts: RequestTimeouts) -> LlmConfig {
+    LlmConfig::openai_with_base_url("sk-test", "gpt-4o-mini", url).with_timeouts(timeouts)
+}
+
+fn agent_workflow() -> Workflow {
+    let mut workflow = Workflow::new("timeouts", "");
+    workflow
+        .add_node(WorkflowNode::new(
+            "answer",
+            "",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(AgentId::new(), "ping"),
+            },
+        ))
+        .unwrap();
+    workflow
+}
+
+/// Error the `answer` node failed with
+fn node_error(context: &WorkflowContext) -> String {
+    assert!(
+        matches!(&context.state, WorkflowState::PartiallyCompleted { failed_nodes } if failed_nodes == &["answer"]),
+        "{:?}",
+        context.state
+    );
+    context.to_report().nodes[0].error.clone().unwrap()
+}
+
+#[tokio::test]
+async fn test_stalled_llm_request_fails_with_retryable_timeout() {
+    let (url, _) = spawn_stalling_server(usize::MAX).await;
+    let provider = LlmProviderFactory::create_provider(openai(
+        &url,
+        RequestTimeouts::default().with_timeout_ms(200),
+    ))
+    .unwrap();
+
+    let started = Instant::now();
+    let error = provider
+        .complete(LlmRequest::new("ping"))
+        .await
+        .unwrap_err();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(
+        matches!(&error, GraphBitError::Timeout { provider, .. } if provider == "openai"),
+        "{error:?}"
+    );
+    assert!(error.is_retryable());
+    assert_eq!(
+        RetryableErrorType::from_error(&error),
+        RetryableErrorType::TimeoutError
+    );
+}
+
+#[tokio::test]
+async fn test_stalled_embedding_request_fails_with_timeout() {
+    let (url, _) = spawn_stalling_server(usize::MAX).await;
+    let provider = EmbeddingProviderFactory::create_provider(EmbeddingConfig {
+        provider: EmbeddingProvider::OpenAI,
+        api_key: "test".to_string(),
+        model: "text-embedding-3-small".to_string(),
+        base_url: Some(url),
+        timeout_seconds: Some(30),
+        timeouts: RequestTimeouts::default().with_timeout_ms(200),
+        max_batch_size: None,
+        extra_params: HashMap::new(),
+        retry: None,
+        python_instance: None,
+    })
+    .unwrap();
+
+    let started = Instant::now();
+    let error = provider
+        .generate_embeddings(EmbeddingRequest {
+            input: EmbeddingInput::Single("ping".to_string()),
+            user: None,
+            params: HashMap::new(),
+        })
+        .await
+        .unwrap_err();
+
+    // The timeout in milliseconds takes precedence over `timeout_seconds`
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(matches!(error, GraphBitError::Timeout { .. }), "{error:?}");
+}
+
+#[tokio::test]
+async fn test_timed_out_node_request_is_retried() {
+    let (url, requests) = spawn_stalling_server(1).await;
+    let executor = WorkflowExecutor::new()
+        .with_default_llm_config(openai(
+            &url,
+            RequestTimeouts::default().with_timeout_ms(200),
+        ))
+        .with_retry_config(
+            RetryConfig::new(2)
+                .with_exponential_backoff(10, 2.0, 100)
+                .with_jitter(0.0),
+        );
+
+    let context = executor.execute(agent_workflow(), None).await.unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    assert_eq!(context.get_node_output("answer"), Some(&json!("pong")));
+}
+
+#[tokio::test]
+async fn test_node_timeout_bounds_request_timeout() {
+    let (url, _) = spawn_stalling_server(usize::MAX).await;
+    let executor = WorkflowExecutor::new()
+        .with_default_llm_config(openai(
+            &url,
+            RequestTimeouts::default().with_timeout_ms(30_000),
+        ))
+        .with_max_node_execution_time(300)
+        .without_retries();
+
+    let started = Instant::now();
+    let context = executor.execute(agent_workflow(), None).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let error = node_error(&context);
+    assert!(error.contains("timed out after 300ms"), "{error}");
+}
+
+#[test]
+fn test_timeouts_round_trip_through_configurations() {
+    let config = openai(
+        "http://127.0.0.1:9/v1",
+        RequestTimeouts::default()
+            .with_timeout_ms(1500)
+            .with_connect_timeout_ms(250),
+    );
+    let value = serde_json::to_value(&config).unwrap();
+    assert_eq!(value["timeout_ms"], 1500);
+    assert_eq!(value["connect_timeout_ms"], 250);
+    let restored: LlmConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(restored.timeouts(), config.timeouts());
+
+    // Configurations saved without timeouts leave them to the HTTP settings
+    let restored: LlmConfig = serde_json::from_value(json!({
+        "provider": "Anthropic",
+        "api_key": "test",
+        "model": "claude-3-5-haiku-latest",
+        "base_url": null
+    }))
+    .unwrap();
+    assert!(restored.timeouts().is_empty());
+    let value = serde_json::to_value(&restored).unwrap();
+    assert!(value.get("timeout_ms").is_none());
+
+    // Providers without an HTTP client of their own have no timeouts to set
+    let custom = LlmConfig::Custom {
+        provider_type: "mine".to_string(),
+        config: HashMap::new(),
+    }
+    .with_timeouts(RequestTimeouts::default().with_timeout_ms(10));
+    assert!(custom.timeouts().is_empty());
+}
+
+#[test]
+fn test_zero_timeouts_are_rejected() {
+    for timeouts in [
+        RequestTimeouts::default().with_timeout_ms(0),
+        RequestTimeouts::default().with_connect_timeout_ms(0),
+    ] {
+        let error = LlmProviderFactory::create_provider(openai("http://127.0.0.1:9/v1", timeouts))
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, GraphBitError::Validation { message, .. } if message.contains("greater than zero")),
+            "{error:?}"
+        );
+    }
+}
+
+#[test]
+fn test_connect_timeout_defaults_when_unset() {
+    let factory = HttpClientFactory::new(HttpSettings::default());
+    let settings = factory.settings_for("request_timeout_tests", None, None);
+    assert_eq!(settings.connect_timeout, Some(DEFAULT_CONNECT_TIMEOUT));
+
+    factory.set_provider_settings(
+        "request_timeout_tests",
+        HttpSettings::default().with_connect_timeout(Duration::from_secs(3)),
+    );
+    let settings = factory.settings_for("request_timeout_tests", None, None);
+    assert_eq!(settings.connect_timeout, Some(Duration::from_secs(3)));
+
+    // Configuration timeouts replace the component's
+    let mut settings = settings;
+    RequestTimeouts::default()
+        .with_timeout_ms(1500)
+        .with_connect_timeout_ms(250)
+        .apply(&mut settings);
+    assert_eq!(settings.timeout, Some(Duration::from_millis(1500)));
+    assert_eq!(settings.connect_timeout, Some(Duration::from_millis(250)));
+}
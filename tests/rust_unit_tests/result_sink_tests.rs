@@ -0,0 +1,222 @@
+//! Result sinks: one JSON line per completed node or batch execution, torn
+//! lines left by a crash, rotation by size and deduplication on read
+
+use graphbit_core::{
+    ResultRecord, ResultSink,
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    result_sink::read_results,
+    stream::StreamMode,
+    types::WorkflowContext,
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// `source -> shout`, with `source` reading the `item` input
+fn pipeline() -> Workflow {
+    let mut workflow = Workflow::new("result_sink", "");
+    let source = WorkflowNode::new(
+        "source",
+        "",
+        NodeType::Transform {
+            transformation: "vars.item".to_string(),
+        },
+    );
+    let shout = WorkflowNode::new(
+        "shout",
+        "",
+        NodeType::Transform {
+            transformation: "input + '!'".to_string(),
+        },
+    );
+    let (source_id, shout_id) = (source.id.clone(), shout.id.clone());
+    workflow.add_node(source).unwrap();
+    workflow.add_node(shout).unwrap();
+    workflow
+        .connect_nodes(source_id, shout_id, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+fn inputs(item: &str) -> HashMap<String, serde_json::Value> {
+    HashMap::from([("item".to_string(), json!(item))])
+}
+
+fn lines(path: &std::path::Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn node_record(execution_id: &str, node: &str, output: serde_json::Value) -> ResultRecord {
+    ResultRecord {
+        execution_id: execution_id.to_string(),
+        index: None,
+        node_id: Some(node.to_string()),
+        node_name: Some(node.to_string()),
+        success: true,
+        output,
+        error: None,
+        usage: None,
+        recorded_at: chrono::Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_one_line_per_completed_node() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.jsonl");
+    let workflow = pipeline();
+    let context = WorkflowContext::new(workflow.id.clone())
+        .with_inputs(inputs("hello"))
+        .with_result_sink(Arc::new(ResultSink::new(&path)));
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_context(workflow, None, None, StreamMode::Updates, context)
+        .await
+        .unwrap();
+
+    let lines = lines(&path);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["node_name"], "source");
+    assert_eq!(lines[0]["output"], "hello");
+    assert_eq!(lines[1]["node_name"], "shout");
+    assert_eq!(lines[1]["output"], "hello!");
+    for line in &lines {
+        assert_eq!(line["execution_id"], context.execution_id.to_string());
+        assert_eq!(line["success"], true);
+        assert!(line.get("index").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_batch_writes_one_line_per_execution() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.jsonl");
+    let sink = Arc::new(ResultSink::new(&path));
+
+    let results = WorkflowExecutor::new()
+        .without_retries()
+        .execute_batch(
+            pipeline(),
+            None,
+            vec![inputs("a"), inputs("b"), inputs("c")],
+            Some(sink),
+        )
+        .await;
+
+    let outputs: Vec<_> = results
+        .iter()
+        .map(|result| result.as_ref().unwrap().load_node_outputs()["shout"].clone())
+        .collect();
+    assert_eq!(outputs, [json!("a!"), json!("b!"), json!("c!")]);
+
+    let mut lines = lines(&path);
+    assert_eq!(lines.len(), 3);
+    lines.sort_by_key(|line| line["index"].as_u64());
+    for (index, (line, result)) in lines.iter().zip(&results).enumerate() {
+        assert_eq!(line["index"], index);
+        assert_eq!(
+            line["execution_id"],
+            result.as_ref().unwrap().execution_id.to_string()
+        );
+        assert_eq!(line["success"], true);
+        assert_eq!(line["output"]["shout"], outputs[index]);
+        assert!(line.get("node_name").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_torn_last_line_is_cut_off_before_appending() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.jsonl");
+    let complete = serde_json::to_string(&node_record("run", "source", json!("done"))).unwrap();
+    std::fs::write(
+        &path,
+        format!("{complete}\n{{\"execution_id\":\"run\",\"out"),
+    )
+    .unwrap();
+
+    // Reading skips the torn line without touching the file
+    let records = read_results(&path).await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].output, json!("done"));
+
+    ResultSink::new(&path)
+        .append(&node_record("run", "shout", json!("done!")))
+        .await
+        .unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with('\n'));
+    let lines = lines(&path);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["node_name"], "source");
+    assert_eq!(lines[1]["node_name"], "shout");
+}
+
+#[tokio::test]
+async fn test_rotation_by_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.jsonl");
+    let sink = ResultSink::new(&path).with_max_bytes(64);
+
+    for node in ["first", "second", "third"] {
+        sink.append(&node_record("run", node, json!(node)))
+            .await
+            .unwrap();
+    }
+
+    // Every line is over the limit, so each gets a file of its own
+    assert_eq!(
+        lines(&dir.path().join("results.jsonl.1"))[0]["node_name"],
+        "first"
+    );
+    assert_eq!(
+        lines(&dir.path().join("results.jsonl.2"))[0]["node_name"],
+        "second"
+    );
+    assert_eq!(lines(&path)[0]["node_name"], "third");
+
+    let names: Vec<_> = read_results(&path)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter_map(|record| record.node_name)
+        .collect();
+    assert_eq!(names, ["first", "second", "third"]);
+}
+
+#[tokio::test]
+async fn test_read_keeps_the_last_record_of_each_node() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.jsonl");
+    let sink = ResultSink::new(&path);
+
+    // A resumed execution repeats the node it was interrupted in
+    sink.append(&node_record("run", "source", json!("first try")))
+        .await
+        .unwrap();
+    sink.append(&node_record("other", "source", json!("other run")))
+        .await
+        .unwrap();
+    sink.append(&node_record("run", "source", json!("second try")))
+        .await
+        .unwrap();
+
+    let records = read_results(&path).await.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].execution_id, "run");
+    assert_eq!(records[0].output, json!("second try"));
+    assert_eq!(records[1].output, json!("other run"));
+    assert!(
+        read_results(dir.path().join("missing.jsonl"))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
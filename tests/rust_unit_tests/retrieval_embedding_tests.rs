@@ -0,0 +1,263 @@
+//! `Voyage AI` and `Jina AI` embedding providers against a mocked endpoint:
+//! batching at each API's limits, input types and dimension reporting
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::embeddings::{
+    EmbeddingConfig, EmbeddingInput, EmbeddingRequest, EmbeddingService, JinaEmbeddingProvider,
+    VoyageEmbeddingProvider,
+};
+use graphbit_core::errors::GraphBitError;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Start an HTTP server answering every embeddings request with one
+/// `dimensions`-long embedding per input text, listed in reverse order, and
+/// `usage`; returns the base URL of its `/v1` API and the request bodies it
+/// received
+async fn spawn_embeddings_server(
+    dimensions: usize,
+    usage: Value,
+) -> (String, Arc<Mutex<Vec<Value>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    let server = MockServer::new(move |request| {
+        let request = request.json();
+        let count = request["input"].as_array().map_or(0, Vec::len);
+        let data: Vec<Value> = (0..count)
+            .rev()
+            .map(|index| json!({"object": "embedding", "index": index, "embedding": vec![index as f32; dimensions]}))
+            .collect();
+        let body = json!({"object": "list", "data": data, "model": request["model"], "usage": usage});
+        log.lock().unwrap().push(request);
+        std::future::ready(MockResponse::json(200, body))
+    })
+    .await;
+    (format!("{}/v1", server.url()), received)
+}
+
+fn texts(count: usize, text: &str) -> Vec<String> {
+    vec![text.to_string(); count]
+}
+
+fn batch_sizes(received: &Mutex<Vec<Value>>) -> Vec<usize> {
+    received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|body| body["input"].as_array().unwrap().len())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_voyage_batches_at_the_text_limit() {
+    let (base_url, received) = spawn_embeddings_server(4, json!({"total_tokens": 10})).await;
+    let service = EmbeddingService::new(
+        EmbeddingConfig::voyage("pa-test", "voyage-3.5").with_base_url(base_url),
+    )
+    .unwrap();
+
+    let embeddings = service.embed_texts(&texts(2500, "hello")).await.unwrap();
+
+    assert_eq!(batch_sizes(&received), vec![1000, 1000, 500]);
+    assert_eq!(embeddings.len(), 2500);
+    // Each batch is put back in input order
+    assert_eq!(embeddings[999], vec![999.0; 4]);
+    assert_eq!(embeddings[1000], vec![0.0; 4]);
+}
+
+#[tokio::test]
+async fn test_voyage_batches_at_the_token_limit() {
+    let (base_url, received) = spawn_embeddings_server(4, json!({"total_tokens": 10})).await;
+    let service = EmbeddingService::new(
+        EmbeddingConfig::voyage("pa-test", "voyage-3-large").with_base_url(base_url),
+    )
+    .unwrap();
+
+    // About 1,000 tokens each, against voyage-3-large's 120K per request
+    let long_text = "abcd".repeat(1000);
+    service.embed_texts(&texts(250, &long_text)).await.unwrap();
+
+    assert_eq!(batch_sizes(&received), vec![120, 120, 10]);
+    assert_eq!(
+        VoyageEmbeddingProvider::max_batch_tokens("voyage-3.5-lite"),
+        1_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_jina_batches_at_the_text_limit() {
+    let (base_url, received) = spawn_embeddings_server(4, json!({"total_tokens": 10})).await;
+    let service = EmbeddingService::new(
+        EmbeddingConfig::jina("jina_test", "jina-embeddings-v3").with_base_url(base_url),
+    )
+    .unwrap();
+
+    let embeddings = service.embed_texts(&texts(2500, "hello")).await.unwrap();
+
+    assert_eq!(batch_sizes(&received), vec![2048, 452]);
+    assert_eq!(embeddings.len(), 2500);
+}
+
+#[tokio::test]
+async fn test_input_type_sets_voyage_input_type_and_jina_task() {
+    let (base_url, received) = spawn_embeddings_server(4, json!({"total_tokens": 3})).await;
+    let voyage = EmbeddingService::new(
+        EmbeddingConfig::voyage("pa-test", "voyage-3.5")
+            .with_base_url(base_url.clone())
+            .with_input_type("document")
+            .with_dimensions(256),
+    )
+    .unwrap();
+    let jina = EmbeddingService::new(
+        EmbeddingConfig::jina("jina_test", "jina-embeddings-v3")
+            .with_base_url(base_url.clone())
+            .with_input_type("document"),
+    )
+    .unwrap();
+    let query = |text: &str| EmbeddingRequest {
+        input: EmbeddingInput::Single(text.to_string()),
+        user: None,
+        params: HashMap::from([("input_type".to_string(), json!("query"))]),
+    };
+
+    voyage.embed_text("a document").await.unwrap();
+    let response = voyage.embed(query("a query")).await.unwrap();
+    assert_eq!(response.metadata["input_type"], json!("query"));
+    jina.embed_text("a document").await.unwrap();
+    jina.embed(query("a query")).await.unwrap();
+
+    let bodies = received.lock().unwrap().clone();
+    assert_eq!(bodies[0]["input_type"], json!("document"));
+    assert_eq!(bodies[0]["output_dimension"], json!(256));
+    assert!(bodies[0].get("dimensions").is_none());
+    assert_eq!(bodies[1]["input_type"], json!("query"));
+    assert_eq!(bodies[2]["task"], json!("retrieval.passage"));
+    assert!(bodies[2].get("input_type").is_none());
+    assert_eq!(bodies[3]["task"], json!("retrieval.query"));
+
+    // Jina's v2 models take no task
+    let v2 = EmbeddingService::new(
+        EmbeddingConfig::jina("jina_test", "jina-embeddings-v2-base-en")
+            .with_base_url(base_url)
+            .with_input_type("query"),
+    )
+    .unwrap();
+    v2.embed_text("a query").await.unwrap();
+    assert!(received.lock().unwrap()[4].get("task").is_none());
+}
+
+#[tokio::test]
+async fn test_invalid_input_type_is_rejected() {
+    let (base_url, received) = spawn_embeddings_server(4, json!({"total_tokens": 3})).await;
+    let service = EmbeddingService::new(
+        EmbeddingConfig::voyage("pa-test", "voyage-3.5")
+            .with_base_url(base_url)
+            .with_input_type("passage"),
+    )
+    .unwrap();
+
+    let error = service.embed_text("text").await.unwrap_err();
+
+    assert!(
+        matches!(&error, GraphBitError::Validation { field, .. } if field == "input_type"),
+        "{error:?}"
+    );
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_usage_is_normalized_and_summed_across_batches() {
+    // Voyage reports only total tokens
+    let (base_url, _) = spawn_embeddings_server(4, json!({"total_tokens": 7})).await;
+    let voyage = EmbeddingService::new(
+        EmbeddingConfig::voyage("pa-test", "voyage-3.5").with_base_url(base_url),
+    )
+    .unwrap();
+    let request = EmbeddingRequest {
+        input: EmbeddingInput::Multiple(texts(1500, "hello")),
+        user: None,
+        params: HashMap::new(),
+    };
+
+    let response = voyage.embed(request.clone()).await.unwrap();
+
+    assert_eq!(response.usage.prompt_tokens, 14);
+    assert_eq!(response.usage.total_tokens, 14);
+    assert_eq!(response.metadata["batches"], json!(2));
+    assert_eq!(response.model, "voyage-3.5");
+
+    // Jina reports both
+    let (base_url, _) =
+        spawn_embeddings_server(4, json!({"prompt_tokens": 5, "total_tokens": 5})).await;
+    let jina = EmbeddingService::new(
+        EmbeddingConfig::jina("jina_test", "jina-embeddings-v3").with_base_url(base_url),
+    )
+    .unwrap();
+
+    let response = jina.embed(request).await.unwrap();
+
+    assert_eq!(response.usage.prompt_tokens, 5);
+    assert_eq!(response.usage.total_tokens, 5);
+}
+
+#[tokio::test]
+async fn test_dimensions_per_model() {
+    let dimensions = |config: EmbeddingConfig| async move {
+        EmbeddingService::new(config)
+            .unwrap()
+            .get_dimensions()
+            .await
+            .unwrap()
+    };
+
+    assert_eq!(
+        dimensions(EmbeddingConfig::voyage("pa-test", "voyage-3.5")).await,
+        1024
+    );
+    assert_eq!(
+        dimensions(EmbeddingConfig::voyage("pa-test", "voyage-3-lite")).await,
+        512
+    );
+    assert_eq!(
+        dimensions(EmbeddingConfig::voyage("pa-test", "voyage-code-2")).await,
+        1536
+    );
+    assert_eq!(
+        dimensions(EmbeddingConfig::jina("jina_test", "jina-embeddings-v4")).await,
+        2048
+    );
+    assert_eq!(
+        dimensions(EmbeddingConfig::jina("jina_test", "jina-embeddings-v3")).await,
+        1024
+    );
+    assert_eq!(
+        dimensions(EmbeddingConfig::jina(
+            "jina_test",
+            "jina-embeddings-v2-small-en"
+        ))
+        .await,
+        512
+    );
+    assert_eq!(
+        JinaEmbeddingProvider::model_dimensions("jina-embeddings-v2-base-de"),
+        Some(768)
+    );
+
+    // Shortened embeddings report the configured size
+    assert_eq!(
+        dimensions(EmbeddingConfig::voyage("pa-test", "voyage-3-large").with_dimensions(256)).await,
+        256
+    );
+    assert_eq!(
+        dimensions(EmbeddingConfig::jina("jina_test", "jina-embeddings-v3").with_dimensions(64))
+            .await,
+        64
+    );
+
+    // Unknown models are measured with a test request
+    let (base_url, received) = spawn_embeddings_server(3, json!({"total_tokens": 1})).await;
+    let config = EmbeddingConfig::jina("jina_test", "jina-embeddings-v5").with_base_url(base_url);
+    assert_eq!(dimensions(config).await, 3);
+    assert_eq!(received.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,318 @@
+//! Run history: recording executions, querying and pruning the store, and the
+//! `GET /executions` endpoint
+
+use chrono::{Duration as ChronoDuration, SecondsFormat, SubsecRound, Utc};
+use graphbit_core::{
+    graph::{NodeType, WorkflowNode},
+    run_history::{RunHistory, RunQuery, RunRecord, RunRetention, RunState},
+    server::{ExecutionService, serve},
+    stream::StreamMode,
+    types::{WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Single transform node workflow named `name`
+fn transform_workflow(name: &str, transformation: &str) -> Workflow {
+    let mut workflow = Workflow::new(name, "");
+    workflow
+        .add_node(WorkflowNode::new(
+            "step",
+            "",
+            NodeType::Transform {
+                transformation: transformation.to_string(),
+            },
+        ))
+        .unwrap();
+    workflow
+}
+
+fn executor(history: &RunHistory) -> WorkflowExecutor {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_run_history(history.clone())
+}
+
+/// Finished record of a run of `workflow_name` started `age` ago
+fn finished_record(workflow_name: &str, age: ChronoDuration) -> RunRecord {
+    let workflow = Workflow::new(workflow_name, "");
+    let mut context = WorkflowContext::new(workflow.id.clone());
+    context.started_at = Utc::now() - age;
+    let mut record = RunRecord::started(&workflow, &context);
+    context.state = WorkflowState::Completed;
+    record.finish(&Ok(context));
+    record
+}
+
+#[tokio::test]
+async fn test_runs_persist_across_executors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("runs.db");
+
+    let mut ingest = transform_workflow("ingest", "'done'");
+    ingest.metadata.insert("version".to_string(), json!("1.4"));
+    let first = {
+        let history = RunHistory::open(&path).unwrap();
+        let executor = executor(&history);
+        let first = executor.execute(ingest.clone(), None).await.unwrap();
+        executor.execute(ingest, None).await.unwrap();
+        first
+    };
+
+    // A second executor opening the same database sees the earlier runs
+    let history = RunHistory::open(&path).unwrap();
+    let latest = executor(&history)
+        .execute(transform_workflow("report", "'done'"), None)
+        .await
+        .unwrap();
+
+    let runs = history.recent(10).await.unwrap();
+    let names: Vec<&str> = runs.iter().map(|run| run.workflow_name.as_str()).collect();
+    assert_eq!(names, ["report", "ingest", "ingest"]);
+    assert_eq!(runs[0].execution_id, latest.execution_id.to_string());
+    assert!(
+        runs.iter()
+            .all(|run| run.state == RunState::Completed && run.completed_at.is_some())
+    );
+
+    let run = history
+        .get(&first.execution_id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.workflow_version.as_deref(), Some("1.4"));
+    // Times are stored with microsecond precision
+    assert_eq!(run.started_at, first.started_at.trunc_subsecs(6));
+    assert_eq!(run.stats.unwrap().total_nodes, 1);
+    assert!(run.error.is_none());
+    assert!(history.get("missing").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_failed_run_records_error() {
+    let history = RunHistory::in_memory().unwrap();
+    let executor = executor(&history).with_fail_fast(true);
+
+    let context = executor
+        .execute(transform_workflow("broken", "reverse"), None)
+        .await
+        .unwrap();
+    assert!(matches!(context.state, WorkflowState::Failed { .. }));
+
+    let runs = history
+        .list(&RunQuery::new().with_state(RunState::Failed))
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].workflow_name, "broken");
+    assert!(runs[0].error.is_some());
+}
+
+#[tokio::test]
+async fn test_query_by_name_state_and_time() {
+    let history = RunHistory::in_memory().unwrap();
+    for (name, hours) in [
+        ("ingest", 30),
+        ("report", 20),
+        ("ingest", 10),
+        ("ingest", 1),
+    ] {
+        history
+            .record(&finished_record(name, ChronoDuration::hours(hours)))
+            .await
+            .unwrap();
+    }
+    let running = Workflow::new("ingest", "");
+    history
+        .record(&RunRecord::started(
+            &running,
+            &WorkflowContext::new(running.id.clone()),
+        ))
+        .await
+        .unwrap();
+
+    let ingest = history
+        .list(&RunQuery::new().with_workflow_name("ingest"))
+        .await
+        .unwrap();
+    assert_eq!(ingest.len(), 4);
+    assert_eq!(ingest[0].state, RunState::Running);
+    assert!(
+        ingest
+            .windows(2)
+            .all(|pair| pair[0].started_at >= pair[1].started_at)
+    );
+
+    let window = RunQuery::new().started_between(
+        Some(Utc::now() - ChronoDuration::hours(24)),
+        Some(Utc::now() - ChronoDuration::hours(5)),
+    );
+    let in_window = history.list(&window).await.unwrap();
+    let names: Vec<&str> = in_window
+        .iter()
+        .map(|run| run.workflow_name.as_str())
+        .collect();
+    assert_eq!(names, ["ingest", "report"]);
+
+    let completed = history
+        .list(
+            &RunQuery::new()
+                .with_workflow_name("ingest")
+                .with_state(RunState::Completed)
+                .with_limit(2),
+        )
+        .await
+        .unwrap();
+    assert_eq!(completed.len(), 2);
+    assert!(completed.iter().all(|run| run.state == RunState::Completed));
+    assert_eq!(history.recent(1).await.unwrap()[0].state, RunState::Running);
+}
+
+#[tokio::test]
+async fn test_prune_by_count_removes_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let reports = dir.path().join("reports");
+    let history = RunHistory::open(dir.path().join("runs.db"))
+        .unwrap()
+        .with_report_dir(&reports);
+    let executor = executor(&history);
+    for _ in 0..3 {
+        executor
+            .execute(transform_workflow("ingest", "'done'"), None)
+            .await
+            .unwrap();
+    }
+
+    let runs = history.recent(10).await.unwrap();
+    let paths: Vec<_> = runs
+        .iter()
+        .map(|run| run.report_path.clone().unwrap())
+        .collect();
+    assert!(paths.iter().all(|path| path.starts_with(&reports)));
+    let archived = std::fs::read_to_string(&paths[0]).unwrap();
+    let context = WorkflowContext::from_json(&archived).unwrap();
+    assert_eq!(context.execution_id.to_string(), runs[0].execution_id);
+
+    let removed = history
+        .prune(RunRetention {
+            max_age: None,
+            max_runs: Some(1),
+        })
+        .await
+        .unwrap();
+    assert_eq!(removed, 2);
+    let remaining = history.recent(10).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].execution_id, runs[0].execution_id);
+    assert!(paths[0].exists());
+    assert!(!paths[1].exists() && !paths[2].exists());
+}
+
+#[tokio::test]
+async fn test_prune_by_age() {
+    let history = RunHistory::in_memory().unwrap();
+    for hours in [72, 49, 2] {
+        history
+            .record(&finished_record("ingest", ChronoDuration::hours(hours)))
+            .await
+            .unwrap();
+    }
+
+    let retention = RunRetention {
+        max_age: Some(Duration::from_secs(48 * 3600)),
+        max_runs: None,
+    };
+    assert_eq!(history.prune(retention).await.unwrap(), 2);
+    assert_eq!(history.prune(retention).await.unwrap(), 0);
+    assert_eq!(history.recent(10).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_retention_applies_after_each_run() {
+    let history = RunHistory::in_memory()
+        .unwrap()
+        .with_retention(RunRetention {
+            max_age: None,
+            max_runs: Some(2),
+        });
+    let executor = executor(&history);
+    let mut last = None;
+    for _ in 0..4 {
+        last = Some(
+            executor
+                .execute(transform_workflow("ingest", "'done'"), None)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let runs = history.recent(10).await.unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].execution_id, last.unwrap().execution_id.to_string());
+}
+
+#[tokio::test]
+async fn test_http_list_executions() {
+    let history = RunHistory::in_memory().unwrap();
+    let service = ExecutionService::new(
+        WorkflowExecutor::new()
+            .without_retries()
+            .with_run_history(history.clone()),
+    );
+    for name in ["ingest", "report", "ingest"] {
+        let workflow_id = service
+            .submit_workflow(transform_workflow(name, "'done'"))
+            .await
+            .unwrap();
+        let execution_id = service
+            .execute_stored(&workflow_id, HashMap::new(), StreamMode::Updates)
+            .await
+            .unwrap();
+        service.wait(&execution_id).await.unwrap();
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(serve(listener, service));
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{url}/executions"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    let runs = body["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 3);
+    assert_eq!(runs[0]["workflow_name"], json!("ingest"));
+    assert_eq!(runs[1]["workflow_name"], json!("report"));
+    assert_eq!(runs[0]["state"], json!("completed"));
+    assert_eq!(runs[0]["stats"]["total_nodes"], json!(1));
+
+    let since = (Utc::now() - ChronoDuration::hours(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let body: Value = client
+        .get(format!(
+            "{url}/executions?workflow_name=ingest&state=completed&since={since}&limit=1"
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let runs = body["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["workflow_name"], json!("ingest"));
+
+    for query in ["state=done", "since=yesterday", "limit=many"] {
+        let response = client
+            .get(format!("{url}/executions?{query}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400, "{query}");
+    }
+}
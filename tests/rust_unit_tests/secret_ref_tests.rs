@@ -0,0 +1,263 @@
+//! API key references and redaction of resolved keys
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    embeddings::{EmbeddingConfig, EmbeddingProviderFactory},
+    errors::GraphBitError,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmProviderFactory},
+    secret_ref::{self, REDACTED, SecretRef},
+    types::{AgentId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Start a server rejecting every request with a 401 whose body echoes the
+/// request's `Authorization` header, as some gateways do, recording the headers
+async fn spawn_echoing_server() -> (String, Arc<Mutex<Vec<String>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let server = MockServer::new(move |request| {
+        let authorization = request
+            .header("authorization")
+            .unwrap_or_default()
+            .to_string();
+        recorded.lock().unwrap().push(authorization.clone());
+        std::future::ready(MockResponse::json(
+            401,
+            format!(r#"{{"error":{{"message":"Invalid credentials: {authorization}"}}}}"#),
+        ))
+    })
+    .await;
+    (format!("{}/v1", server.url()), seen)
+}
+
+#[test]
+fn test_parse_references() {
+    assert_eq!(
+        SecretRef::parse("env:OPENAI_API_KEY").unwrap(),
+        SecretRef::Env("OPENAI_API_KEY".to_string())
+    );
+    assert_eq!(
+        SecretRef::parse("file:/run/secrets/openai").unwrap(),
+        SecretRef::File(PathBuf::from("/run/secrets/openai"))
+    );
+    assert_eq!(
+        SecretRef::parse("keyring:graphbit/openai").unwrap(),
+        SecretRef::Keyring {
+            service: "graphbit".to_string(),
+            user: "openai".to_string(),
+        }
+    );
+    let literal = SecretRef::parse("sk-literal-key").unwrap();
+    assert!(!literal.is_reference());
+
+    // References show as written, literal keys never do
+    assert_eq!(
+        SecretRef::parse("env:OPENAI_API_KEY").unwrap().to_string(),
+        "env:OPENAI_API_KEY"
+    );
+    assert_eq!(literal.to_string(), REDACTED);
+    assert_eq!(format!("{literal:?}"), "SecretRef([REDACTED])");
+
+    for invalid in ["env:", "file: ", "keyring:graphbit", "keyring:/openai"] {
+        assert!(
+            matches!(
+                SecretRef::parse(invalid),
+                Err(GraphBitError::Configuration { .. })
+            ),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn test_resolve_env_and_file_references() {
+    let key = "sk-env-3f9a1c2b7d4e5f60";
+    unsafe { std::env::set_var("GRAPHBIT_SECRET_REF_TEST_ENV_KEY", format!("{key}\n")) };
+    assert_eq!(
+        secret_ref::resolve_api_key("env:GRAPHBIT_SECRET_REF_TEST_ENV_KEY").unwrap(),
+        key
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("openai");
+    std::fs::write(&path, "sk-file-8c7b6a5d4e3f2a10\n").unwrap();
+    assert_eq!(
+        secret_ref::resolve_api_key(&format!("file:{}", path.display())).unwrap(),
+        "sk-file-8c7b6a5d4e3f2a10"
+    );
+
+    let missing = secret_ref::resolve_api_key("env:GRAPHBIT_SECRET_REF_TEST_UNSET")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        missing.contains("'GRAPHBIT_SECRET_REF_TEST_UNSET' is not set"),
+        "{missing}"
+    );
+    let unreadable =
+        secret_ref::resolve_api_key(&format!("file:{}", dir.path().join("none").display()))
+            .unwrap_err()
+            .to_string();
+    assert!(
+        unreadable.contains("Cannot read API key file"),
+        "{unreadable}"
+    );
+}
+
+#[test]
+fn test_resolved_keys_are_redacted() {
+    let key = "sk-redact-5e4d3c2b1a0f9e8d";
+    let config = LlmConfig::openai(key, "gpt-4o-mini");
+    assert!(!format!("{config:?}").contains(key));
+    assert!(format!("{config:?}").contains(REDACTED));
+    let referenced = LlmConfig::openai("env:OPENAI_API_KEY", "gpt-4o-mini");
+    assert!(format!("{referenced:?}").contains("env:OPENAI_API_KEY"));
+    let embedding = EmbeddingConfig::voyage(key, "voyage-3.5");
+    assert!(!format!("{embedding:?}").contains(key));
+
+    // Once a provider was created with the key, errors echoing it are redacted
+    LlmProviderFactory::create_provider(config).unwrap();
+    let error = GraphBitError::llm_provider("openai", format!("Rejected key {key}"));
+    assert_eq!(
+        error.to_string(),
+        format!("LLM provider error: openai - Rejected key {REDACTED}")
+    );
+    assert_eq!(
+        secret_ref::redact(&format!("Bearer {key}")),
+        format!("Bearer {REDACTED}")
+    );
+
+    // Short placeholder keys are left alone
+    LlmProviderFactory::create_provider(LlmConfig::openai("short-key", "gpt-4o-mini")).unwrap();
+    assert_eq!(secret_ref::redact("short-key"), "short-key");
+}
+
+#[test]
+fn test_embedding_factory_resolves_references() {
+    let config =
+        EmbeddingConfig::voyage("env:GRAPHBIT_SECRET_REF_TEST_UNSET_EMBEDDING", "voyage-3.5");
+    let error = EmbeddingProviderFactory::create_provider(config)
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(
+        error.contains("'GRAPHBIT_SECRET_REF_TEST_UNSET_EMBEDDING' is not set"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_sentinel_key_never_leaves_the_reference() {
+    const SENTINEL: &str = "sk-sentinel-7a1b2c3d4e5f60718293";
+    unsafe { std::env::set_var("GRAPHBIT_SECRET_REF_TEST_SENTINEL", SENTINEL) };
+    let (url, seen) = spawn_echoing_server().await;
+
+    let config = LlmConfig::openai_with_base_url(
+        "env:GRAPHBIT_SECRET_REF_TEST_SENTINEL",
+        "gpt-4o-mini",
+        url,
+    );
+    let mut workflow = Workflow::new("sentinel", "");
+    workflow
+        .add_node(
+            WorkflowNode::new(
+                "Summarize",
+                "",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(AgentId::new(), "Summarize the news"),
+                },
+            )
+            .with_llm_config(&config),
+        )
+        .unwrap();
+
+    let serialized_workflow = serde_json::to_string(&workflow).unwrap();
+    assert!(serialized_workflow.contains("env:GRAPHBIT_SECRET_REF_TEST_SENTINEL"));
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .with_default_llm_config(config.clone())
+        .with_capture_payloads(true)
+        .execute(workflow, None)
+        .await
+        .unwrap();
+
+    // The key was resolved for the request itself
+    assert_eq!(
+        seen.lock().unwrap().first().map(String::as_str),
+        Some(&*format!("Bearer {SENTINEL}"))
+    );
+
+    assert!(!matches!(context.state, WorkflowState::Completed));
+    let node_errors: Vec<String> = context
+        .get_node_executions()
+        .iter()
+        .filter_map(|record| record.error.clone())
+        .collect();
+    assert!(!node_errors.is_empty());
+
+    for (what, text) in [
+        ("workflow", serialized_workflow),
+        ("context", serde_json::to_string(&context).unwrap()),
+        (
+            "report",
+            serde_json::to_string(&context.to_report()).unwrap(),
+        ),
+        ("state", format!("{:?}", context.state)),
+        ("node errors", node_errors.join("\n")),
+        ("config debug", format!("{config:?}")),
+    ] {
+        assert!(!text.contains(SENTINEL), "{what} leaks the key: {text}");
+    }
+}
+
+/// Put a fake keyring tool on `PATH` that answers with `key` and counts its
+/// calls in the returned file
+#[cfg(unix)]
+fn fake_keyring_tool(dir: &std::path::Path, key: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let calls = dir.join("calls");
+    let tool = dir.join(if cfg!(target_os = "macos") {
+        "security"
+    } else {
+        "secret-tool"
+    });
+    std::fs::write(
+        &tool,
+        format!(
+            "#!/bin/sh\necho call >> '{}'\necho '{key}'\n",
+            calls.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = std::env::var("PATH").unwrap_or_default();
+    unsafe { std::env::set_var("PATH", format!("{}:{path}", dir.display())) };
+    calls
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_keyring_keys_are_cached_until_forgotten() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = "sk-keyring-7a6b5c4d3e2f1a0b";
+    let calls = fake_keyring_tool(dir.path(), key);
+    let reference = "keyring:graphbit-secret-ref-test/openai";
+    let call_count = || std::fs::read_to_string(&calls).unwrap().lines().count();
+
+    // Resolved on a runtime worker, the tool runs once and its key is reused
+    for _ in 0..3 {
+        assert_eq!(secret_ref::resolve_api_key(reference).unwrap(), key);
+    }
+    assert_eq!(call_count(), 1);
+    assert_eq!(secret_ref::redact(key), REDACTED);
+
+    secret_ref::forget_api_key(reference);
+    assert_eq!(secret_ref::redact(key), key);
+    assert_eq!(secret_ref::resolve_api_key(reference).unwrap(), key);
+    assert_eq!(call_count(), 2);
+    assert_eq!(secret_ref::redact(key), REDACTED);
+}
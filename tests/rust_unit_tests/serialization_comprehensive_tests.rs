@@ -10,6 +10,7 @@ use graphbit_core::{
         AgentId, AgentMessage, MessageContent, NodeId, RetryConfig, WorkflowContext, WorkflowId,
         WorkflowState,
     },
+    workflow::{WORKFLOW_SCHEMA_VERSION, Workflow},
 };
 use serde_json::json;
 use std::collections::HashMap;
@@ -36,6 +37,7 @@ fn test_workflow_graph_serialization_roundtrip() {
         "Check condition",
         NodeType::Condition {
             handler_id: "value_gt_10".to_string(),
+            expression: None,
         },
     );
 
@@ -579,3 +581,83 @@ fn test_large_data_serialization_performance() {
     println!("Deserialization took: {deserialize_duration:?}");
     println!("Serialized size: {} bytes", serialized.len());
 }
+
+#[test]
+fn test_workflow_json_roundtrip() {
+    let mut workflow = Workflow::new("Pipeline", "Extract then summarize");
+    let extract = workflow
+        .add_node(WorkflowNode::new(
+            "extract",
+            "Extract facts",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(AgentId::new(), "Extract: {input}"),
+            },
+        ))
+        .unwrap();
+    let summarize = workflow
+        .add_node(WorkflowNode::new(
+            "summarize",
+            "Summarize facts",
+            NodeType::Transform {
+                transformation: "uppercase".to_string(),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(
+            extract.clone(),
+            summarize.clone(),
+            WorkflowEdge::data_flow(),
+        )
+        .unwrap();
+    workflow.set_metadata("owner".to_string(), json!("research"));
+    workflow
+        .graph
+        .set_metadata("layout".to_string(), json!("dag"));
+
+    let json = workflow.to_json().unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"], json!(WORKFLOW_SCHEMA_VERSION));
+
+    let mut restored = Workflow::from_json(&json).unwrap();
+    assert_eq!(restored.id, workflow.id);
+    assert_eq!(restored.name, "Pipeline");
+    assert_eq!(restored.metadata.get("owner"), Some(&json!("research")));
+    assert_eq!(restored.graph.get_metadata("layout"), Some(&json!("dag")));
+    assert_eq!(restored.graph.node_count(), 2);
+    assert_eq!(restored.graph.edge_count(), 1);
+    restored.validate().unwrap();
+
+    // Node ids survive and the rebuilt graph knows the edge
+    assert_eq!(
+        restored.graph.get_node_id_by_name("extract"),
+        Some(extract.clone())
+    );
+    assert_eq!(restored.graph.get_dependencies(&summarize), vec![extract]);
+}
+
+#[test]
+fn test_workflow_json_schema_version() {
+    let workflow = Workflow::new("Versioned", "");
+    let mut value: serde_json::Value = serde_json::from_str(&workflow.to_json().unwrap()).unwrap();
+
+    // Unversioned JSON is read as version 1
+    value.as_object_mut().unwrap().remove("schema_version");
+    assert!(Workflow::from_json(&value.to_string()).is_ok());
+
+    value["schema_version"] = json!(WORKFLOW_SCHEMA_VERSION + 1);
+    let err = Workflow::from_json(&value.to_string()).unwrap_err();
+    assert!(matches!(err, GraphBitError::Validation { .. }));
+    assert!(err.to_string().contains("upgrade GraphBit"));
+}
+
+#[test]
+fn test_workflow_json_rejects_schema_version_zero() {
+    let workflow = Workflow::new("Versioned", "");
+    let mut value: serde_json::Value = serde_json::from_str(&workflow.to_json().unwrap()).unwrap();
+
+    value["schema_version"] = json!(0);
+    let err = Workflow::from_json(&value.to_string()).unwrap_err();
+    assert!(matches!(err, GraphBitError::Validation { .. }));
+    assert!(err.to_string().contains("positive integer"));
+}
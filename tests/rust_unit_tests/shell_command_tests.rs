@@ -0,0 +1,183 @@
+//! Shell command nodes: output capture, substituted values, failures and the
+//! executor gate
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowNode},
+    types::{RetryConfig, RetryableErrorType, Secrets, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+fn shell(command: &str, timeout_seconds: u64) -> WorkflowNode {
+    WorkflowNode::new(
+        "shell",
+        "",
+        NodeType::ShellCommand {
+            command_template: command.to_string(),
+            working_dir: None,
+            env: HashMap::from([("GREETING".to_string(), "hello".to_string())]),
+            timeout_seconds,
+        },
+    )
+}
+
+fn workflow(node: WorkflowNode) -> Workflow {
+    let mut workflow = Workflow::new("shell", "");
+    workflow.add_node(node).unwrap();
+    workflow
+}
+
+async fn run(workflow: Workflow, inputs: HashMap<String, serde_json::Value>) -> WorkflowContext {
+    WorkflowExecutor::new()
+        .without_retries()
+        .with_allow_shell_nodes(true)
+        .execute_with_inputs(workflow, None, inputs, Secrets::default())
+        .await
+        .unwrap()
+}
+
+/// Error recorded for the `shell` node
+fn shell_error(context: &WorkflowContext) -> String {
+    context
+        .get_node_executions()
+        .iter()
+        .find(|record| record.node_name == "shell")
+        .and_then(|record| record.error.clone())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_captures_output_and_quotes_substituted_values() {
+    let inputs = HashMap::from([("name".to_string(), json!("world'; echo injected"))]);
+    let context = run(
+        workflow(shell(
+            "echo \"$GREETING\" {{input.name}}; echo oops >&2",
+            10,
+        )),
+        inputs,
+    )
+    .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(
+        context.get_node_output("shell"),
+        Some(&json!({
+            "stdout": "hello world'; echo injected\n",
+            "stderr": "oops\n",
+            "exit_status": 0,
+            "truncated": false,
+        }))
+    );
+}
+
+#[tokio::test]
+async fn test_substituted_values_cannot_run_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    let value = format!(
+        "$(touch {0}) `touch {0}` \"; touch {0}; \" %PATH% ^& *",
+        marker.display()
+    );
+    let inputs = HashMap::from([("value".to_string(), json!(value))]);
+    let context = run(workflow(shell("printf %s {{input.value}}", 10)), inputs).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("shell").unwrap()["stdout"], value);
+    assert!(!marker.exists());
+}
+
+#[tokio::test]
+async fn test_references_inside_quotes_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    let inputs = HashMap::from([(
+        "x".to_string(),
+        json!(format!("$(id; touch {})", marker.display())),
+    )]);
+
+    for command in [
+        "echo \"{{input.x}}\"",
+        "echo '{{input.x}}'",
+        "echo \"value: {{input.x}}",
+    ] {
+        let err = WorkflowExecutor::new()
+            .with_allow_shell_nodes(true)
+            .execute_with_inputs(
+                workflow(shell(command, 10)),
+                None,
+                inputs.clone(),
+                Secrets::default(),
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("reference '{{input.x}}' inside quotes"),
+            "{command}: {err}"
+        );
+    }
+    assert!(!marker.exists());
+
+    // Escaped quotes and references next to quoted text are fine
+    for command in [
+        "echo \\\"{{input.x}}\\\"",
+        "echo \"a\" {{input.x}} 'b'",
+        "echo \"it's\" {{input.x}}",
+    ] {
+        workflow(shell(command, 10)).validate().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_runaway_command_is_killed_at_the_timeout() {
+    let started = Instant::now();
+    let context = run(workflow(shell("sleep 30", 1)), HashMap::new()).await;
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let error = shell_error(&context);
+    assert!(error.contains("timed out after 1s"), "{error}");
+}
+
+#[tokio::test]
+async fn test_nonzero_exit_fails_after_the_node_retries() {
+    let node = shell("echo broken >&2; exit 3", 10).with_retry_config(
+        RetryConfig::new(2)
+            .with_exponential_backoff(10, 1.0, 10)
+            .with_jitter(0.0)
+            .with_retryable_errors(vec![RetryableErrorType::Other]),
+    );
+
+    let context = run(workflow(node), HashMap::new()).await;
+
+    let error = shell_error(&context);
+    assert!(error.contains("exit status: 3"), "{error}");
+    assert!(error.contains("broken"), "{error}");
+    assert_eq!(context.get_node_attempts("shell"), Some(3));
+}
+
+#[tokio::test]
+async fn test_shell_nodes_are_refused_unless_allowed() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    let command = format!("touch '{}'", marker.display());
+
+    let err = WorkflowExecutor::new()
+        .execute(workflow(shell(&command, 10)), None)
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("does not allow shell nodes"), "{err}");
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_zero_timeout_is_rejected() {
+    let err = workflow(shell("true", 0))
+        .validate()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("timeout"), "{err}");
+}
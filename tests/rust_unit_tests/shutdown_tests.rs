@@ -0,0 +1,209 @@
+//! Graceful shutdown: draining running executions, cancelling those left after
+//! the grace period, and recording and flushing what they leave behind
+
+use async_trait::async_trait;
+use graphbit_core::{
+    AuditLog, AuditPolicy, AuditSink,
+    audit::{AuditCall, AuditCallKind, AuditContext, AuditOutcome, AuditRecord},
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    run_history::{RunHistory, RunState},
+    server::{ExecutionService, ExecutionStatus},
+    stream::StreamMode,
+    types::{AbandonedNode, SkipReason, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Sink keeping records in memory, slowly
+#[derive(Clone, Default)]
+struct SlowSink {
+    records: Arc<Mutex<Vec<AuditRecord>>>,
+}
+
+#[async_trait]
+impl AuditSink for SlowSink {
+    async fn write(&self, record: &AuditRecord) -> graphbit_core::GraphBitResult<()> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+/// `prepare` followed by a `wait` of `delay_seconds` and a `publish` step
+fn delayed_workflow(delay_seconds: u64) -> Workflow {
+    let mut workflow = Workflow::new("nightly", "");
+    let prepare = workflow
+        .add_node(WorkflowNode::new(
+            "prepare",
+            "",
+            NodeType::Transform {
+                transformation: "'ready'".to_string(),
+            },
+        ))
+        .unwrap();
+    let wait = workflow
+        .add_node(WorkflowNode::new(
+            "wait",
+            "",
+            NodeType::Delay {
+                duration_seconds: delay_seconds,
+            },
+        ))
+        .unwrap();
+    let publish = workflow
+        .add_node(WorkflowNode::new(
+            "publish",
+            "",
+            NodeType::Transform {
+                transformation: "'published'".to_string(),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(prepare, wait.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(wait, publish, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+fn audit_call() -> AuditCall {
+    AuditCall {
+        kind: AuditCallKind::Llm,
+        provider: "openai".to_string(),
+        model: Some("gpt-4o-mini".to_string()),
+        started_at: chrono::Utc::now(),
+        latency_ms: 12,
+        prompt: "Summarize".to_string(),
+        completion: Some("Done".to_string()),
+        usage: None,
+        outcome: AuditOutcome::Success,
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_cancels_executions_after_grace_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = RunHistory::in_memory()
+        .unwrap()
+        .with_report_dir(dir.path().join("reports"));
+    let sink = SlowSink::default();
+    let audit_log = AuditLog::new(sink.clone(), AuditPolicy::default());
+    let executor = Arc::new(
+        WorkflowExecutor::new()
+            .without_retries()
+            .with_run_history(history.clone())
+            .with_audit_log(audit_log.clone()),
+    );
+
+    let running = tokio::spawn({
+        let executor = executor.clone();
+        async move { executor.execute(delayed_workflow(60), None).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(executor.shutdown_handle().running(), 1);
+    audit_log.record(AuditContext::default(), audit_call());
+
+    let summary = executor.shutdown(Duration::from_millis(50)).await;
+    let context = running.await.unwrap().unwrap();
+
+    assert!(context.state.is_cancelled());
+    assert_eq!(
+        context.abandoned_nodes.get("wait"),
+        Some(&AbandonedNode::Cancelled)
+    );
+    assert_eq!(
+        context.abandoned_nodes.get("publish"),
+        Some(&AbandonedNode::Skipped {
+            reason: SkipReason::Shutdown
+        })
+    );
+    assert!(context.get_node_output("prepare").is_some());
+
+    assert_eq!(summary.completed, 0);
+    assert_eq!(summary.interrupted.len(), 1);
+    let interrupted = &summary.interrupted[0];
+    assert_eq!(interrupted.execution_id, context.execution_id.to_string());
+    assert_eq!(interrupted.workflow_name, "nightly");
+    assert_eq!(interrupted.cancelled_nodes, ["wait"]);
+    assert_eq!(interrupted.skipped_nodes, ["publish"]);
+    let checkpoint = interrupted.checkpoint.as_ref().unwrap();
+    let archived =
+        WorkflowContext::from_json(&std::fs::read_to_string(checkpoint).unwrap()).unwrap();
+    assert!(archived.state.is_cancelled());
+
+    let run = history
+        .get(&context.execution_id.to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.state, RunState::Cancelled);
+
+    assert!(summary.audit_log_flushed);
+    assert!(summary.errors.is_empty());
+    assert_eq!(sink.records.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_executions_finishing_within_grace_period_complete() {
+    let executor = Arc::new(WorkflowExecutor::new().without_retries());
+    let running = tokio::spawn({
+        let executor = executor.clone();
+        async move { executor.execute(delayed_workflow(1), None).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let summary = executor.shutdown(Duration::from_secs(10)).await;
+    let context = running.await.unwrap().unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(summary.completed, 1);
+    assert!(summary.interrupted.is_empty());
+    assert!(!summary.audit_log_flushed);
+}
+
+#[tokio::test]
+async fn test_no_execution_starts_after_shutdown() {
+    let executor = WorkflowExecutor::new();
+    assert!(!executor.shutdown_handle().is_shutting_down());
+
+    let summary = executor.shutdown(Duration::from_secs(1)).await;
+    assert_eq!(summary.completed, 0);
+    assert!(summary.interrupted.is_empty());
+    assert!(executor.shutdown_handle().is_shutting_down());
+
+    let error = executor
+        .execute(delayed_workflow(0), None)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("shutting down"), "{error}");
+}
+
+#[tokio::test]
+async fn test_executors_sharing_a_handle_shut_down_together() {
+    let first = WorkflowExecutor::new();
+    let second = WorkflowExecutor::new().with_shutdown_handle(first.shutdown_handle().clone());
+
+    first.shutdown(Duration::from_secs(1)).await;
+
+    assert!(second.execute(delayed_workflow(0), None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_service_reports_cancelled_executions() {
+    let service = ExecutionService::new(WorkflowExecutor::new().without_retries());
+    let id = service
+        .execute(delayed_workflow(60), HashMap::new(), StreamMode::Updates)
+        .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let summary = service.shutdown(Duration::from_millis(10)).await;
+    let execution = service.wait(&id).await.unwrap();
+
+    assert_eq!(summary.interrupted.len(), 1);
+    assert_eq!(execution.status, ExecutionStatus::Cancelled);
+    assert!(execution.error.is_none());
+}
@@ -0,0 +1,164 @@
+//! Similarity functions over embeddings, against hand-computed fixtures
+
+use graphbit_core::GraphBitError;
+use graphbit_core::embeddings::{
+    EmbeddingService, SimilarityMetric, cosine_similarity, dot_product, euclidean_distance, mmr,
+    top_k,
+};
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "expected {expected}, got {actual}"
+    );
+}
+
+fn assert_ranking(actual: &[(usize, f32)], expected: &[(usize, f32)]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?}");
+    for (&(index, score), &(expected_index, expected_score)) in actual.iter().zip(expected) {
+        assert_eq!(index, expected_index, "{actual:?}");
+        assert_close(score, expected_score);
+    }
+}
+
+fn assert_dimension_error<T: std::fmt::Debug>(result: Result<T, GraphBitError>) {
+    match result {
+        Err(GraphBitError::Validation { field, message }) => {
+            assert_eq!(field, "dimensions");
+            assert!(message.contains("got 3 and 2"), "{message}");
+        }
+        other => panic!("expected a dimension error, got {other:?}"),
+    }
+}
+
+/// Query [1, 0] against four 2-d embeddings
+const QUERY: [f32; 2] = [1.0, 0.0];
+const CORPUS: [[f32; 2]; 4] = [[0.0, 1.0], [1.0, 0.0], [1.0, 1.0], [2.0, 0.0]];
+
+#[test]
+fn test_pairwise_scores() {
+    assert_close(
+        dot_product(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap(),
+        32.0,
+    );
+    assert_close(
+        euclidean_distance(&[1.0, 2.0, 3.0], &[4.0, 6.0, 3.0]).unwrap(),
+        5.0,
+    );
+    assert_close(
+        cosine_similarity(&[1.0, 0.0], &[1.0, 1.0]).unwrap(),
+        std::f32::consts::FRAC_1_SQRT_2,
+    );
+    assert_close(cosine_similarity(&[3.0, 4.0], &[-3.0, -4.0]).unwrap(), -1.0);
+    assert_close(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]).unwrap(), 0.0);
+    assert_close(
+        EmbeddingService::cosine_similarity(&[3.0, 4.0], &[4.0, 3.0]).unwrap(),
+        0.96,
+    );
+}
+
+#[test]
+fn test_dimension_mismatch() {
+    let a = [1.0, 2.0, 3.0];
+    let b = [1.0, 2.0];
+    assert_dimension_error(dot_product(&a, &b));
+    assert_dimension_error(cosine_similarity(&a, &b));
+    assert_dimension_error(euclidean_distance(&a, &b));
+    assert_dimension_error(top_k(&a, &[b], 1, SimilarityMetric::Cosine));
+    assert_dimension_error(mmr(&a, &[b], 0.5, 1));
+}
+
+#[test]
+fn test_top_k_cosine() {
+    // Scores 0, 1, 1/√2, 1: ties keep their corpus order
+    let ranked = top_k(&QUERY, &CORPUS, 3, SimilarityMetric::Cosine).unwrap();
+    assert_ranking(
+        &ranked,
+        &[(1, 1.0), (3, 1.0), (2, std::f32::consts::FRAC_1_SQRT_2)],
+    );
+}
+
+#[test]
+fn test_top_k_dot_product() {
+    // Scores 0, 1, 1, 2
+    let ranked = top_k(&QUERY, &CORPUS, 2, SimilarityMetric::DotProduct).unwrap();
+    assert_ranking(&ranked, &[(3, 2.0), (1, 1.0)]);
+}
+
+#[test]
+fn test_top_k_euclidean_prefers_smaller_distances() {
+    // Distances √2, 0, 1, 1
+    let ranked = top_k(&QUERY, &CORPUS, 3, SimilarityMetric::Euclidean).unwrap();
+    assert_ranking(&ranked, &[(1, 0.0), (2, 1.0), (3, 1.0)]);
+}
+
+#[test]
+fn test_top_k_bounds() {
+    let all = top_k(&QUERY, &CORPUS, 10, SimilarityMetric::DotProduct).unwrap();
+    assert_eq!(
+        all.iter().map(|&(index, _)| index).collect::<Vec<_>>(),
+        [3, 1, 2, 0]
+    );
+    assert!(
+        top_k(&QUERY, &CORPUS, 0, SimilarityMetric::Cosine)
+            .unwrap()
+            .is_empty()
+    );
+    let empty: [Vec<f32>; 0] = [];
+    assert!(
+        top_k(&QUERY, &empty, 3, SimilarityMetric::Cosine)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_similarity_metric_names() {
+    for metric in [
+        SimilarityMetric::Cosine,
+        SimilarityMetric::DotProduct,
+        SimilarityMetric::Euclidean,
+    ] {
+        assert_eq!(metric.as_str().parse::<SimilarityMetric>().unwrap(), metric);
+    }
+    assert_eq!(
+        "dot".parse::<SimilarityMetric>().unwrap(),
+        SimilarityMetric::DotProduct
+    );
+    assert!("manhattan".parse::<SimilarityMetric>().is_err());
+}
+
+/// Query [4, 3] against a duplicated embedding and an orthogonal one:
+/// relevance 0.8, 0.8 and 0.6; similarity 1 between the duplicates, 0 otherwise
+const MMR_QUERY: [f32; 2] = [4.0, 3.0];
+const MMR_CANDIDATES: [[f32; 2]; 3] = [[1.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+
+#[test]
+fn test_mmr_prefers_diverse_candidates() {
+    // Second pick: the duplicate scores 0.5·0.8 − 0.5·1 = −0.1, the
+    // orthogonal candidate 0.5·0.6 − 0.5·0 = 0.3
+    assert_eq!(mmr(&MMR_QUERY, &MMR_CANDIDATES, 0.5, 3).unwrap(), [0, 2, 1]);
+    assert_eq!(mmr(&MMR_QUERY, &MMR_CANDIDATES, 0.5, 2).unwrap(), [0, 2]);
+}
+
+#[test]
+fn test_mmr_lambda_one_ranks_by_relevance() {
+    assert_eq!(mmr(&MMR_QUERY, &MMR_CANDIDATES, 1.0, 3).unwrap(), [0, 1, 2]);
+}
+
+#[test]
+fn test_mmr_bounds() {
+    assert_eq!(mmr(&MMR_QUERY, &MMR_CANDIDATES, 0.5, 10).unwrap().len(), 3);
+    assert!(mmr(&MMR_QUERY, &MMR_CANDIDATES, 0.5, 0).unwrap().is_empty());
+    assert!(
+        mmr(&MMR_QUERY, &[] as &[Vec<f32>], 0.5, 3)
+            .unwrap()
+            .is_empty()
+    );
+    for lambda in [-0.1, 1.5, f32::NAN] {
+        assert!(matches!(
+            mmr(&MMR_QUERY, &MMR_CANDIDATES, lambda, 2),
+            Err(GraphBitError::Validation { ref field, .. }) if field == "lambda"
+        ));
+    }
+}
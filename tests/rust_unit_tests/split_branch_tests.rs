@@ -0,0 +1,293 @@
+//! Branches of split fan-outs: `{{index}}`, `{{item}}` and scratch spaces
+//! isolated per branch, collected by the join
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowGraph, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmRole, LlmToolCall},
+    tools::{ContextToolHandler, ToolContext, ToolMetadata, ToolRegistry},
+    types::{AgentId, BranchScope, NodeId, WorkflowContext, WorkflowId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const BRANCHES: usize = 20;
+
+/// Registry with a `remember` tool storing its `value` argument as
+/// `scratch.value`, and the value it saw there before as `scratch.before`
+fn registry() -> ToolRegistry {
+    let handler: ContextToolHandler = Arc::new(|args: serde_json::Value, ctx: ToolContext| {
+        Box::pin(async move {
+            let before = ctx.get("scratch.value").unwrap_or(serde_json::Value::Null);
+            ctx.set("scratch.before", before)?;
+            ctx.set("scratch.value", args["value"].clone())?;
+            Ok(json!("remembered"))
+        })
+    });
+    let registry = ToolRegistry::new();
+    registry
+        .register_context_tool(
+            ToolMetadata::new(
+                "remember",
+                "Remember a value for later nodes of the branch",
+                json!({"type": "object", "properties": {"value": {"type": "string"}}}),
+            ),
+            handler,
+        )
+        .unwrap();
+    registry
+}
+
+fn agent_node(agent_id: &AgentId, name: &str, prompt: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), prompt),
+        },
+    )
+}
+
+/// Register an agent making prompts ending in a `Remember` task call the
+/// `remember` tool with that task, and recording every prompt in `prompts`
+async fn register_agent(executor: &WorkflowExecutor, prompts: &Arc<Mutex<Vec<String>>>) -> AgentId {
+    let agent_id = AgentId::new();
+    let prompts = prompts.clone();
+    let provider = ScriptedProvider::new(move |request| {
+        let prompt = request
+            .messages
+            .iter()
+            .find(|message| matches!(message.role, LlmRole::User))
+            .map(|message| message.content.clone())
+            .unwrap_or_default();
+        prompts.lock().unwrap().push(prompt.clone());
+        let called_tools = request
+            .messages
+            .iter()
+            .any(|message| matches!(message.role, LlmRole::Tool));
+        let task = prompt.lines().last().unwrap_or_default();
+        if task.starts_with("Remember") && !called_tools {
+            let call = LlmToolCall {
+                id: "call_1".to_string(),
+                name: "remember".to_string(),
+                parameters: json!({"value": task}),
+            };
+            return Ok(LlmResponse::new("", "scripted-model").with_tool_calls(vec![call]));
+        }
+        Ok(LlmResponse::new("Done", "scripted-model"))
+    });
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Worker", "", LlmConfig::default()).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+    agent_id
+}
+
+fn connect(workflow: &mut Workflow, from: &NodeId, to: &NodeId) {
+    workflow
+        .connect_nodes(from.clone(), to.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_parallel_branches_keep_their_scratch_writes_apart() {
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_tool_registry(registry());
+
+    // items -> fan -> (remember_i -> check_i) x 20 -> merge -> report
+    let mut workflow = Workflow::new("fan out", "");
+    let items = workflow
+        .add_node(WorkflowNode::new(
+            "items",
+            "",
+            NodeType::Transform {
+                transformation: "vars.cities".to_string(),
+            },
+        ))
+        .unwrap();
+    let fan = workflow
+        .add_node(WorkflowNode::new("fan", "", NodeType::Split))
+        .unwrap();
+    let merge = workflow
+        .add_node(WorkflowNode::new("merge", "", NodeType::Join))
+        .unwrap();
+    let report = workflow
+        .add_node(agent_node(
+            &register_agent(&executor, &prompts).await,
+            "report",
+            "Report {{scratch.merge.3.value}} ({{scratch.value}})",
+        ))
+        .unwrap();
+    connect(&mut workflow, &items, &fan);
+    for index in 0..BRANCHES {
+        let remember = workflow
+            .add_node(
+                agent_node(
+                    &register_agent(&executor, &prompts).await,
+                    &format!("remember_{index}"),
+                    "Remember {{item.name}} at {{index}}",
+                )
+                .with_tools(["remember"]),
+            )
+            .unwrap();
+        let check = workflow
+            .add_node(agent_node(
+                &register_agent(&executor, &prompts).await,
+                &format!("check_{index}"),
+                "Check {{index}}: {{scratch.value}} / {{scratch.shared}}",
+            ))
+            .unwrap();
+        connect(&mut workflow, &fan, &remember);
+        connect(&mut workflow, &remember, &check);
+        connect(&mut workflow, &check, &merge);
+    }
+    connect(&mut workflow, &merge, &report);
+
+    let cities: Vec<_> = (0..BRANCHES)
+        .map(|index| json!({"name": format!("city_{index}")}))
+        .collect();
+    let mut context = WorkflowContext::new(workflow.id.clone())
+        .with_inputs(HashMap::from([("cities".to_string(), json!(cities))]));
+    context.merge_scratch("seed", vec![("shared".to_string(), json!("global"))]);
+    let context = executor
+        .execute_with_context(
+            workflow,
+            None,
+            None,
+            graphbit_core::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    let prompts = prompts.lock().unwrap().clone();
+    for index in 0..BRANCHES {
+        let written = format!("Remember city_{index} at {index}");
+        let expected = format!("Check {index}: {written} / global");
+        assert!(
+            prompts.iter().any(|prompt| prompt.ends_with(&expected)),
+            "missing {expected:?} in {prompts:?}"
+        );
+    }
+
+    // Branch writes never reached the shared scratch space; the join collected them in order
+    assert_eq!(context.get_scratch("value"), None);
+    let collected = context.get_scratch("merge").unwrap().as_array().unwrap();
+    assert_eq!(collected.len(), BRANCHES);
+    for (index, branch) in collected.iter().enumerate() {
+        assert_eq!(
+            branch,
+            &json!({"before": null, "value": format!("Remember city_{index} at {index}")})
+        );
+    }
+    assert!(
+        prompts
+            .iter()
+            .any(|prompt| prompt.ends_with("Report Remember city_3 at 3 ({{scratch.value}})")),
+        "{prompts:?}"
+    );
+}
+
+#[test]
+fn test_branches_end_at_joins_and_shared_nodes() {
+    let mut graph = WorkflowGraph::new();
+    let mut add = |name: &str, node_type: NodeType| {
+        let node = WorkflowNode::new(name, "", node_type);
+        let id = node.id.clone();
+        graph.add_node(node).unwrap();
+        id
+    };
+    let transform = || NodeType::Transform {
+        transformation: "input".to_string(),
+    };
+    let outer = add("outer", NodeType::Split);
+    let a = add("a", transform());
+    let b = add("b", transform());
+    let inner = add("inner", NodeType::Split);
+    let b0 = add("b0", transform());
+    let b1 = add("b1", transform());
+    let shared = add("shared", transform());
+    let join = add("join", NodeType::Join);
+    let after = add("after", transform());
+    for (from, to) in [
+        (&outer, &a),
+        (&outer, &b),
+        (&b, &inner),
+        (&inner, &b0),
+        (&inner, &b1),
+        (&a, &shared),
+        (&b0, &shared),
+        (&a, &join),
+        (&b1, &join),
+        (&join, &after),
+    ] {
+        graph
+            .add_edge(from.clone(), to.clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+
+    let branches = graph.split_branches();
+
+    assert_eq!(branches.get(&a), Some(&(outer.clone(), 0)));
+    assert_eq!(branches.get(&b), Some(&(outer.clone(), 1)));
+    assert_eq!(branches.get(&inner), Some(&(outer.clone(), 1)));
+    assert_eq!(branches.get(&b0), Some(&(inner.clone(), 0)));
+    assert_eq!(branches.get(&b1), Some(&(inner, 1)));
+    // Reached from both branches of the outer split
+    assert_eq!(branches.get(&shared), None);
+    assert_eq!(branches.get(&join), None);
+    assert_eq!(branches.get(&after), None);
+    assert_eq!(branches.get(&outer), None);
+}
+
+#[test]
+fn test_branch_items_and_scratch_lookup() {
+    let mut context = WorkflowContext::new(WorkflowId::new());
+    context.set_node_output_by_name("fan", json!(["first", "second"]));
+    context.node_branches.insert(
+        "worker".to_string(),
+        BranchScope {
+            split: "fan".to_string(),
+            index: 1,
+        },
+    );
+
+    let branch = context.node_branch("worker").unwrap().clone();
+    assert_eq!(context.branch_item(&branch), Some(json!("second")));
+
+    context.merge_scratch("seed", vec![("tone".to_string(), json!("calm"))]);
+    context.merge_node_scratch(
+        "worker",
+        "remember",
+        vec![("tone".to_string(), json!("bold"))],
+    );
+    assert_eq!(
+        context.get_node_scratch("worker", "tone"),
+        Some(&json!("bold"))
+    );
+    assert_eq!(
+        context.get_node_scratch("other", "tone"),
+        Some(&json!("calm"))
+    );
+    assert_eq!(context.get_scratch("tone"), Some(&json!("calm")));
+    assert_eq!(context.node_scratch("worker")["tone"], json!("bold"));
+
+    context.collect_branch_scratch("merge", &["worker".to_string(), "other".to_string()]);
+    assert_eq!(
+        context.get_scratch("merge"),
+        Some(&json!([{"tone": "bold"}]))
+    );
+    assert_eq!(context.get_scratch("merge.0.tone"), Some(&json!("bold")));
+}
@@ -122,6 +122,19 @@ fn test_token_event() {
     assert_eq!(json["content"], "The");
 }
 
+#[test]
+fn test_node_output_delta_event() {
+    let event = StreamEvent::NodeOutputDelta {
+        node_id: "node-1".to_string(),
+        node_name: "researcher".to_string(),
+        delta: "The".to_string(),
+    };
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["event"], "node_output_delta");
+    assert_eq!(json["node_name"], "researcher");
+    assert_eq!(json["delta"], "The");
+}
+
 #[test]
 fn test_tool_call_started_event() {
     let event = StreamEvent::ToolCallStarted {
@@ -1,82 +1,23 @@
 use graphbit_core::{
-    agents::{AgentConfig, AgentTrait},
+    agents::AgentConfig,
     graph::{AgentNodeConfig, NodeType, WorkflowNode},
-    llm::{LlmConfig, LlmProvider, LlmRequest, LlmResponse, LlmUsage, FinishReason, LlmProviderTrait, LlmRole},
-    types::{AgentId, AgentMessage, MessageContent, WorkflowContext, WorkflowId},
+    llm::{LlmConfig, LlmRole},
+    types::AgentId,
     workflow::{WorkflowExecutor, Workflow},
-    validation::ValidationResult,
 };
-use async_trait::async_trait;
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-
-// Mock LLM Provider to capture requests
-struct MockLlmProvider {
-    captured_requests: Arc<Mutex<Vec<LlmRequest>>>,
-}
-
-#[async_trait]
-impl LlmProviderTrait for MockLlmProvider {
-    fn provider_name(&self) -> &str { "mock" }
-    fn model_name(&self) -> &str { "mock-model" }
-    async fn complete(&self, request: LlmRequest) -> graphbit_core::GraphBitResult<LlmResponse> {
-        self.captured_requests.lock().unwrap().push(request);
-        Ok(LlmResponse {
-            content: "Mock response".to_string(),
-            usage: LlmUsage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
-            finish_reason: FinishReason::Stop,
-            model: "mock-model".to_string(),
-            tool_calls: vec![],
-            metadata: HashMap::new(),
-            id: None,
-        })
-    }
-}
-
-// Manual Agent implementation for testing
-struct TestAgent {
-    config: AgentConfig,
-    provider: LlmProvider,
-}
-
-#[async_trait]
-impl AgentTrait for TestAgent {
-    fn id(&self) -> &AgentId { &self.config.id }
-    fn config(&self) -> &AgentConfig { &self.config }
-    fn llm_provider(&self) -> &LlmProvider { &self.provider }
-    
-    async fn process_message(&self, _m: AgentMessage, _c: &mut WorkflowContext) -> graphbit_core::GraphBitResult<AgentMessage> {
-        Ok(AgentMessage::new(self.config.id.clone(), None, MessageContent::Text("Mock".to_string())))
-    }
-
-    async fn execute(&self, _message: AgentMessage) -> graphbit_core::GraphBitResult<serde_json::Value> {
-        Ok(serde_json::json!({"mock": true}))
-    }
-
-    async fn validate_output(&self, _output: &str, _schema: &serde_json::Value) -> ValidationResult {
-        ValidationResult::success()
-    }
-}
+use std::sync::Arc;
+use super::test_helpers::{ScriptedProvider, TestAgent};
 
 #[tokio::test]
 async fn test_system_prompt_inheritance_and_override() {
-    let captured_requests = Arc::new(Mutex::new(Vec::new()));
-    
+    let provider = ScriptedProvider::replying("Mock response");
+    let captured_requests = provider.requests();
+
     let agent_id = AgentId::new();
-    let llm_config = LlmConfig::default();
-    let mock_provider_trait = MockLlmProvider {
-        captured_requests: captured_requests.clone(),
-    };
-    
-    let agent_config = AgentConfig::new("Test Agent", "desc", llm_config.clone())
+    let agent_config = AgentConfig::new("Test Agent", "desc", LlmConfig::default())
         .with_id(agent_id.clone())
         .with_system_prompt("Agent Default System Prompt");
-    
-    let llm_provider = LlmProvider::new(Box::new(mock_provider_trait), llm_config);
-    let agent = Arc::new(TestAgent {
-        config: agent_config,
-        provider: llm_provider,
-    });
+    let agent = Arc::new(TestAgent::new(agent_config, provider));
     
     let executor = WorkflowExecutor::new();
     executor.register_agent(agent).await;
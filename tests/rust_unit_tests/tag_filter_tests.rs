@@ -0,0 +1,142 @@
+//! Tag filters: selecting and skipping nodes by tag, and passing the input of
+//! skipped nodes through
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    stream::StreamMode,
+    types::{AbandonedNode, SkipReason, TagFilter, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn transform(name: &str, transformation: &str, tags: &[&str]) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Transform {
+            transformation: transformation.to_string(),
+        },
+    )
+    .with_tags(tags.iter().map(ToString::to_string).collect())
+}
+
+/// `source -> enrich -> report`, with `enrich` tagged `enrich_tags` and the
+/// other nodes `tags`
+fn pipeline(tags: &[&str], enrich_tags: &[&str]) -> Workflow {
+    let mut workflow = Workflow::new("tag_filter", "");
+    let nodes = [
+        transform("source", "'order'", tags),
+        transform("enrich", "input + ' with customer data'", enrich_tags),
+        transform("report", "'report of ' + input", tags),
+    ];
+    let ids: Vec<_> = nodes.iter().map(|node| node.id.clone()).collect();
+    for node in nodes {
+        workflow.add_node(node).unwrap();
+    }
+    for pair in ids.windows(2) {
+        workflow
+            .connect_nodes(pair[0].clone(), pair[1].clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+async fn run(workflow: Workflow, filter: TagFilter) -> WorkflowContext {
+    let context = WorkflowContext::new(workflow.id.clone()).with_tag_filter(filter);
+    WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_context(workflow, None, None, StreamMode::Updates, context)
+        .await
+        .unwrap()
+}
+
+fn skipped_by_tag() -> AbandonedNode {
+    AbandonedNode::Skipped {
+        reason: SkipReason::Tag,
+    }
+}
+
+fn skip(tags: &[&str]) -> TagFilter {
+    TagFilter::default().with_skip_tags(tags.iter().map(ToString::to_string).collect())
+}
+
+#[tokio::test]
+async fn test_skipped_middle_node_skips_its_dependents() {
+    let context = run(pipeline(&[], &["expensive"]), skip(&["expensive"])).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("source"), Some(&json!("order")));
+    assert_eq!(context.get_node_output("enrich"), None);
+    assert_eq!(context.get_node_output("report"), None);
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([
+            ("enrich".to_string(), skipped_by_tag()),
+            ("report".to_string(), skipped_by_tag()),
+        ])
+    );
+    let executed: Vec<&str> = context
+        .node_executions
+        .iter()
+        .map(|record| record.node_name.as_str())
+        .collect();
+    assert_eq!(executed, ["source"]);
+}
+
+#[tokio::test]
+async fn test_skipped_middle_node_passes_its_input_through() {
+    let context = run(
+        pipeline(&[], &["expensive", "passthrough_on_skip"]),
+        skip(&["expensive"]),
+    )
+    .await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("enrich"), Some(&json!("order")));
+    assert_eq!(
+        context.get_node_output("report"),
+        Some(&json!("report of order"))
+    );
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([("enrich".to_string(), skipped_by_tag())])
+    );
+}
+
+#[tokio::test]
+async fn test_only_tags_excludes_untagged_nodes() {
+    let only_core = || TagFilter::default().with_only_tags(vec!["core".to_string()]);
+    let context = run(pipeline(&["core"], &["passthrough_on_skip"]), only_core()).await;
+
+    assert_eq!(
+        context.get_node_output("report"),
+        Some(&json!("report of order"))
+    );
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([("enrich".to_string(), skipped_by_tag())])
+    );
+
+    // Dependents of an excluded node are skipped even when selected themselves
+    let context = run(pipeline(&[], &["core"]), only_core()).await;
+    assert_eq!(
+        context.abandoned_nodes,
+        HashMap::from([
+            ("source".to_string(), skipped_by_tag()),
+            ("enrich".to_string(), skipped_by_tag()),
+            ("report".to_string(), skipped_by_tag()),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_no_filter_runs_every_node() {
+    let context = run(pipeline(&[], &["expensive"]), TagFilter::default()).await;
+
+    assert_eq!(
+        context.get_node_output("report"),
+        Some(&json!("report of order with customer data"))
+    );
+    assert!(context.abandoned_nodes.is_empty());
+}
@@ -1,14 +1,28 @@
+use async_trait::async_trait;
 use graphbit_core::{
-    agents::{Agent, AgentBuilder},
+    GraphBitResult,
+    agents::{Agent, AgentBuilder, AgentConfig, AgentTrait},
     embeddings::{
         EmbeddingConfig, EmbeddingProvider, EmbeddingProviderFactory, EmbeddingProviderTrait,
     },
-    llm::{LlmConfig, LlmProviderFactory, LlmProviderTrait},
-    types::AgentCapability,
+    llm::{
+        LlmConfig, LlmProvider, LlmProviderFactory, LlmProviderTrait, LlmRequest, LlmResponse,
+        ProviderHealth,
+    },
+    types::{AgentCapability, AgentId, AgentMessage, MessageContent, WorkflowContext},
+    validation::ValidationResult,
     workflow::{WorkflowBuilder, WorkflowExecutor},
 };
+use futures::future::BoxFuture;
+use std::collections::{HashMap, VecDeque};
 use std::env;
-use std::sync::Arc;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::runtime::Runtime;
 
 // API Key Helpers
@@ -64,6 +78,7 @@ pub(super) fn create_test_llm_provider() -> Box<dyn LlmProviderTrait> {
         model: "test_model".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
     LlmProviderFactory::create_provider(config).unwrap()
 }
@@ -79,7 +94,9 @@ pub(super) fn create_test_embedding_provider() -> Box<dyn EmbeddingProviderTrait
         timeout_seconds: Some(5),
         max_batch_size: Some(1),
         extra_params: Default::default(),
+        retry: None,
         python_instance: None,
+        timeouts: Default::default(),
     };
     EmbeddingProviderFactory::create_provider(config).unwrap()
 }
@@ -92,6 +109,7 @@ pub(super) async fn create_test_agent() -> Arc<Agent> {
         model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
 
     Arc::new(
@@ -193,6 +211,7 @@ pub(super) fn create_mock_llm_config() -> graphbit_core::llm::LlmConfig {
         model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     }
 }
 
@@ -283,3 +302,483 @@ where
 }
 
 // Skip macro intentionally removed to avoid duplicate definitions across modules
+
+/// Requests a [`ScriptedProvider`] received, in order
+#[allow(dead_code)]
+pub(super) type Requests = Arc<Mutex<Vec<LlmRequest>>>;
+
+/// Answer of a [`ScriptedProvider`] to one request, as the chunks it streams
+type Script = dyn Fn(&LlmRequest) -> GraphBitResult<Vec<LlmResponse>> + Send + Sync;
+
+/// Delay of a [`ScriptedProvider`] before answering a request
+type Latency = dyn Fn(&LlmRequest) -> Duration + Send + Sync;
+
+#[allow(dead_code)]
+/// Mock LLM provider answering every request through a script and recording
+/// the requests it receives
+pub(super) struct ScriptedProvider {
+    model: String,
+    script: Box<Script>,
+    requests: Requests,
+    latency: Option<Box<Latency>>,
+    streaming: bool,
+    function_calling: bool,
+    max_context_length: Option<u32>,
+    cost_per_token: Option<(f64, f64)>,
+    cold_start: bool,
+    health_checks: Arc<AtomicUsize>,
+}
+
+#[allow(dead_code)]
+impl ScriptedProvider {
+    /// Provider answering each request with what `respond` returns for it
+    pub(super) fn new(
+        respond: impl Fn(&LlmRequest) -> GraphBitResult<LlmResponse> + Send + Sync + 'static,
+    ) -> Self {
+        Self::with_script(Box::new(move |request| {
+            respond(request).map(|response| vec![response])
+        }))
+    }
+
+    fn with_script(script: Box<Script>) -> Self {
+        Self {
+            model: "scripted-model".to_string(),
+            script,
+            requests: Requests::default(),
+            latency: None,
+            streaming: false,
+            function_calling: false,
+            max_context_length: None,
+            cost_per_token: None,
+            cold_start: false,
+            health_checks: Arc::default(),
+        }
+    }
+
+    /// Provider answering every request with `content`
+    pub(super) fn replying(content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self::new(move |_| Ok(LlmResponse::new(content.clone(), "scripted-model")))
+    }
+
+    /// Provider answering with `results` in order, then with "done"
+    pub(super) fn replaying_results(results: Vec<GraphBitResult<LlmResponse>>) -> Self {
+        let results = Mutex::new(VecDeque::from(results));
+        Self::new(move |_| {
+            results
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| Ok(LlmResponse::new("done", "scripted-model")))
+        })
+    }
+
+    /// Provider answering with `responses` in order, then with "done"
+    pub(super) fn replaying(responses: Vec<LlmResponse>) -> Self {
+        Self::replaying_results(responses.into_iter().map(Ok).collect())
+    }
+
+    /// Streaming provider sending one list of chunks per request, in order;
+    /// a completion answers with the chunks joined
+    pub(super) fn streaming(calls: Vec<Vec<LlmResponse>>) -> Self {
+        let calls = Mutex::new(VecDeque::from(calls));
+        let mut provider = Self::with_script(Box::new(move |_| {
+            Ok(calls
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("no scripted call left"))
+        }));
+        provider.streaming = true;
+        provider
+    }
+
+    /// Report `model` as the model name
+    pub(super) fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Wait for `latency(request)` before answering a request
+    pub(super) fn with_latency(
+        mut self,
+        latency: impl Fn(&LlmRequest) -> Duration + Send + Sync + 'static,
+    ) -> Self {
+        self.latency = Some(Box::new(latency));
+        self
+    }
+
+    /// Report support for function calling
+    pub(super) fn with_function_calling(mut self) -> Self {
+        self.function_calling = true;
+        self
+    }
+
+    /// Report a context window of `tokens`
+    pub(super) fn with_max_context_length(mut self, tokens: u32) -> Self {
+        self.max_context_length = Some(tokens);
+        self
+    }
+
+    /// Report prices per prompt and completion token
+    pub(super) fn with_cost_per_token(mut self, prompt: f64, completion: f64) -> Self {
+        self.cost_per_token = Some((prompt, completion));
+        self
+    }
+
+    /// Report a cold start, so that warm-ups prime the model
+    pub(super) fn with_cold_start(mut self) -> Self {
+        self.cold_start = true;
+        self
+    }
+
+    /// Requests received so far, shared with the provider
+    pub(super) fn requests(&self) -> Requests {
+        self.requests.clone()
+    }
+
+    /// Number of health checks run so far, shared with the provider
+    pub(super) fn health_checks(&self) -> Arc<AtomicUsize> {
+        self.health_checks.clone()
+    }
+
+    async fn answer(&self, request: LlmRequest) -> GraphBitResult<Vec<LlmResponse>> {
+        if let Some(latency) = &self.latency {
+            tokio::time::sleep(latency(&request)).await;
+        }
+        let chunks = (self.script)(&request);
+        self.requests.lock().unwrap().push(request);
+        chunks
+    }
+}
+
+#[async_trait]
+impl LlmProviderTrait for ScriptedProvider {
+    fn provider_name(&self) -> &str {
+        "scripted"
+    }
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+    async fn complete(&self, request: LlmRequest) -> GraphBitResult<LlmResponse> {
+        let mut chunks = self.answer(request).await?;
+        if chunks.len() == 1 {
+            return Ok(chunks.remove(0));
+        }
+        let content: String = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+        Ok(LlmResponse::new(content, self.model.clone()))
+    }
+    async fn stream(
+        &self,
+        request: LlmRequest,
+    ) -> GraphBitResult<Box<dyn futures::Stream<Item = GraphBitResult<LlmResponse>> + Unpin + Send>>
+    {
+        let chunks = self.answer(request).await?;
+        Ok(Box::new(futures::stream::iter(chunks.into_iter().map(Ok))))
+    }
+    fn supports_streaming(&self) -> bool {
+        self.streaming
+    }
+    fn supports_function_calling(&self) -> bool {
+        self.function_calling
+    }
+    fn max_context_length(&self) -> Option<u32> {
+        self.max_context_length
+    }
+    fn cost_per_token(&self) -> Option<(f64, f64)> {
+        self.cost_per_token
+    }
+    fn has_cold_start(&self) -> bool {
+        self.cold_start
+    }
+    async fn health_check(&self) -> ProviderHealth {
+        self.health_checks.fetch_add(1, Ordering::SeqCst);
+        ProviderHealth::from_probe("scripted", &self.model, 3, &Ok(()))
+    }
+}
+
+/// A request a [`MockServer`] received
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub(super) struct MockRequest {
+    pub method: String,
+    /// Request target: the path and query, or the absolute URL sent to a proxy
+    pub path: String,
+    /// Headers, with lowercase names
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl MockRequest {
+    /// Value of the header `name`, in any case
+    pub(super) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Body parsed as JSON
+    pub(super) fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body is not JSON")
+    }
+}
+
+/// Requests a [`MockServer`] received, in order
+#[allow(dead_code)]
+pub(super) type MockRequests = Arc<Mutex<Vec<MockRequest>>>;
+
+/// Answer of a [`MockServer`] to one request
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub(super) struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    cut_off: bool,
+}
+
+#[allow(dead_code)]
+impl MockResponse {
+    /// Response with `status` and `body`
+    pub(super) fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            cut_off: false,
+        }
+    }
+
+    /// JSON response with `status` and `body`
+    pub(super) fn json(status: u16, body: impl std::fmt::Display) -> Self {
+        Self::new(status, body.to_string()).with_header("Content-Type", "application/json")
+    }
+
+    /// Add the header `name`
+    pub(super) fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Close the connection halfway through the body
+    pub(super) const fn cut_off(mut self) -> Self {
+        self.cut_off = true;
+        self
+    }
+}
+
+/// Handler producing a [`MockServer`]'s answers
+type Respond = dyn Fn(MockRequest) -> BoxFuture<'static, MockResponse> + Send + Sync;
+
+#[allow(dead_code)]
+/// HTTP/1.1 server on a free local port answering every request through a
+/// handler and recording the requests it receives
+///
+/// Each request is read in full: its head up to the blank line, then
+/// `Content-Length` bytes of body. Connections are kept alive and served in
+/// their own tasks, so requests are handled concurrently.
+pub(super) struct MockServer {
+    address: SocketAddr,
+    requests: MockRequests,
+    connections: Arc<AtomicUsize>,
+}
+
+#[allow(dead_code)]
+impl MockServer {
+    /// Server answering each request with what `respond` returns for it
+    pub(super) async fn new<F, Fut>(respond: F) -> Self
+    where
+        F: Fn(MockRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = MockResponse> + Send + 'static,
+    {
+        let respond: Arc<Respond> = Arc::new(move |request| Box::pin(respond(request)));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = Self {
+            address: listener.local_addr().unwrap(),
+            requests: MockRequests::default(),
+            connections: Arc::default(),
+        };
+        let requests = server.requests.clone();
+        let connections = server.connections.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve_mock_connection(
+                    stream,
+                    respond.clone(),
+                    requests.clone(),
+                ));
+            }
+        });
+        server
+    }
+
+    /// Server answering every request with `response`
+    pub(super) async fn responding(response: MockResponse) -> Self {
+        Self::new(move |_| std::future::ready(response.clone())).await
+    }
+
+    /// Server answering with `responses` in order, repeating the last one
+    pub(super) async fn replaying(responses: Vec<MockResponse>) -> Self {
+        let responses = Mutex::new(VecDeque::from(responses));
+        Self::new(move |_| {
+            let mut responses = responses.lock().unwrap();
+            let response = if responses.len() > 1 {
+                responses.pop_front()
+            } else {
+                responses.front().cloned()
+            };
+            std::future::ready(response.expect("no scripted response"))
+        })
+        .await
+    }
+
+    /// Base URL of the server, without a trailing slash
+    pub(super) fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Requests received so far, shared with the server
+    pub(super) fn requests(&self) -> MockRequests {
+        self.requests.clone()
+    }
+
+    /// Number of connections accepted so far
+    pub(super) fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+/// Answer requests on `stream` until the client closes it
+async fn serve_mock_connection(
+    mut stream: TcpStream,
+    respond: Arc<Respond>,
+    requests: MockRequests,
+) {
+    let mut buffer = Vec::new();
+    while let Some(request) = read_mock_request(&mut stream, &mut buffer).await {
+        requests.lock().unwrap().push(request.clone());
+        let response = respond(request).await;
+
+        let reason = reqwest::StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("Mock");
+        let headers: String = response
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}\r\n"))
+            .collect();
+        let head = format!(
+            "HTTP/1.1 {} {reason}\r\n{headers}Content-Length: {}\r\n\r\n",
+            response.status,
+            response.body.len()
+        );
+        let body = if response.cut_off {
+            &response.body[..response.body.len() / 2]
+        } else {
+            &response.body
+        };
+        if stream.write_all(head.as_bytes()).await.is_err()
+            || stream.write_all(body).await.is_err()
+            || response.cut_off
+        {
+            let _ = stream.shutdown().await;
+            return;
+        }
+    }
+}
+
+/// Read the next request from `stream`, keeping bytes of the request after
+/// it in `buffer`; `None` once the client closes the connection
+async fn read_mock_request(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<MockRequest> {
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(position) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break position + 4;
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let length: usize = headers
+        .get("content-length")
+        .map_or(0, |length| length.parse().unwrap());
+
+    while buffer.len() < head_end + length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+    }
+    let body = buffer[head_end..head_end + length].to_vec();
+    buffer.drain(..head_end + length);
+    Some(MockRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+#[allow(dead_code)]
+/// Agent answering every node through its provider
+pub(super) struct TestAgent {
+    config: AgentConfig,
+    provider: LlmProvider,
+}
+
+#[allow(dead_code)]
+impl TestAgent {
+    /// Agent configured by `config` whose LLM calls go to `provider`
+    pub(super) fn new(config: AgentConfig, provider: impl LlmProviderTrait + 'static) -> Self {
+        let provider = LlmProvider::new(Box::new(provider), config.llm_config.clone());
+        Self { config, provider }
+    }
+}
+
+#[async_trait]
+impl AgentTrait for TestAgent {
+    fn id(&self) -> &AgentId {
+        &self.config.id
+    }
+    fn config(&self) -> &AgentConfig {
+        &self.config
+    }
+    fn llm_provider(&self) -> &LlmProvider {
+        &self.provider
+    }
+    async fn process_message(
+        &self,
+        _message: AgentMessage,
+        _context: &mut WorkflowContext,
+    ) -> GraphBitResult<AgentMessage> {
+        Ok(AgentMessage::new(
+            self.config.id.clone(),
+            None,
+            MessageContent::Text("unused".to_string()),
+        ))
+    }
+    async fn execute(&self, _message: AgentMessage) -> GraphBitResult<serde_json::Value> {
+        Ok(serde_json::json!({}))
+    }
+    async fn validate_output(
+        &self,
+        _output: &str,
+        _schema: &serde_json::Value,
+    ) -> ValidationResult {
+        ValidationResult::success()
+    }
+}
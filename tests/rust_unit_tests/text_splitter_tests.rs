@@ -1,7 +1,23 @@
+use graphbit_core::document_loader::DocumentContent;
 use graphbit_core::text_splitter::{
-    CharacterSplitter, RecursiveSplitter, SentenceSplitter, SplitterStrategy, TextSplitterConfig,
-    TextSplitterFactory, TextSplitterTrait, TokenSplitter,
+    CharacterSplitter, CodeSplitter, DocumentChunkConfig, MarkdownSplitter, RecursiveSplitter,
+    SentenceSplitter, SplitterStrategy, TextChunk, TextSplitterConfig, TextSplitterFactory,
+    TextSplitterTrait, TokenSplitter, chunk_document,
 };
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Tricky sentences with their expected boundaries, split with the ending
+/// patterns and with Unicode segmentation respectively
+const PUNCTUATION_CORPUS: &str = include_str!("fixtures/sentences/punctuation.json");
+const UNICODE_CORPUS: &str = include_str!("fixtures/sentences/unicode.json");
+
+/// One document per type [`TextSplitterFactory::for_document`] tells apart
+const MARKDOWN_DOCUMENT: &str = include_str!("fixtures/documents/guide.md");
+const RUST_DOCUMENT: &str = include_str!("fixtures/documents/splitter.rs");
+const PYTHON_DOCUMENT: &str = include_str!("fixtures/documents/pipeline.py");
+const PROSE_DOCUMENT: &str = include_str!("fixtures/documents/report.txt");
+const JSON_DOCUMENT: &str = include_str!("fixtures/documents/records.json");
 
 #[test]
 #[ignore = "Flaky/slow in CI; not critical for coverage"]
@@ -71,6 +87,8 @@ fn test_splitter_factory() {
             chunk_size: 50,
             chunk_overlap: 10,
             sentence_endings: None,
+            abbreviations: None,
+            unicode_segmentation: false,
         },
         ..config.clone()
     })
@@ -111,22 +129,6 @@ fn test_splitter_factory_unsupported_strategies() {
     };
     assert!(TextSplitterFactory::create_splitter(config.clone()).is_err());
 
-    // Markdown unsupported
-    config.strategy = SplitterStrategy::Markdown {
-        chunk_size: 100,
-        chunk_overlap: 10,
-        split_by_headers: true,
-    };
-    assert!(TextSplitterFactory::create_splitter(config.clone()).is_err());
-
-    // Code unsupported
-    config.strategy = SplitterStrategy::Code {
-        chunk_size: 100,
-        chunk_overlap: 10,
-        language: Some("rs".into()),
-    };
-    assert!(TextSplitterFactory::create_splitter(config.clone()).is_err());
-
     // Regex unsupported in factory path
     config.strategy = SplitterStrategy::Regex {
         pattern: "\\w+".into(),
@@ -422,3 +424,327 @@ fn test_splitter_with_very_long_text() {
         assert!(chunk.content.len() <= 100);
     }
 }
+
+/// Sentences `splitter` finds in `text`, one chunk each
+fn sentences_of(splitter: &dyn TextSplitterTrait, text: &str) -> Vec<String> {
+    splitter
+        .split_text(text)
+        .unwrap()
+        .into_iter()
+        .map(|chunk| chunk.content)
+        .collect()
+}
+
+fn check_corpus(corpus: &str, splitter: &dyn TextSplitterTrait) {
+    let cases: Vec<Value> = serde_json::from_str(corpus).unwrap();
+    for case in cases {
+        let expected: Vec<String> = serde_json::from_value(case["sentences"].clone()).unwrap();
+        assert_eq!(
+            sentences_of(splitter, case["text"].as_str().unwrap()),
+            expected,
+            "{}",
+            case["name"]
+        );
+    }
+}
+
+#[test]
+fn test_sentence_boundaries_in_punctuation_corpus() {
+    check_corpus(PUNCTUATION_CORPUS, &SentenceSplitter::new(1, 0).unwrap());
+}
+
+#[test]
+fn test_sentence_boundaries_in_unicode_corpus() {
+    let splitter = SentenceSplitter::new(1, 0)
+        .unwrap()
+        .with_unicode_segmentation(true);
+    check_corpus(UNICODE_CORPUS, &splitter);
+}
+
+#[test]
+fn test_sentence_abbreviations_extend_the_defaults() {
+    let text = "See cl. 4 of the lease. Dr. Lee agreed.";
+    let splitter = SentenceSplitter::new(1, 0).unwrap();
+    assert_eq!(sentences_of(&splitter, text).len(), 3);
+
+    let splitter = splitter.with_abbreviations(["Cl."]);
+    assert_eq!(
+        sentences_of(&splitter, text),
+        ["See cl. 4 of the lease.", "Dr. Lee agreed."]
+    );
+}
+
+#[test]
+fn test_custom_sentence_endings_keep_boundary_rules() {
+    let splitter = SentenceSplitter::with_endings(1, 0, vec![r"\.", "!", r"\?"]).unwrap();
+    assert_eq!(
+        sentences_of(&splitter, "Pi is about 3.14 here. Dr. Who knows!"),
+        ["Pi is about 3.14 here.", "Dr. Who knows!"]
+    );
+
+    // Endings alone decide where sentences may end
+    let splitter = SentenceSplitter::with_endings(1, 0, vec![";"]).unwrap();
+    assert_eq!(
+        sentences_of(&splitter, "One. Two; three"),
+        ["One. Two;", "three"]
+    );
+}
+
+#[test]
+fn test_sentence_options_from_configuration() {
+    let config: TextSplitterConfig = serde_json::from_value(json!({
+        "strategy": {
+            "type": "Sentence",
+            "chunk_size": 1,
+            "chunk_overlap": 0,
+            "sentence_endings": null,
+            "abbreviations": ["cl"],
+            "unicode_segmentation": true
+        },
+        "preserve_word_boundaries": true,
+        "trim_whitespace": true,
+        "include_metadata": true,
+        "extra_params": {}
+    }))
+    .unwrap();
+    let splitter = TextSplitterFactory::create_splitter(config).unwrap();
+    assert_eq!(
+        sentences_of(splitter.as_ref(), "See cl. 4 of the lease. यह दूसरा वाक्य है।"),
+        ["See cl. 4 of the lease.", "यह दूसरा वाक्य है।"]
+    );
+    let SplitterStrategy::Sentence {
+        abbreviations,
+        unicode_segmentation,
+        ..
+    } = &splitter.config().strategy
+    else {
+        panic!("not a sentence splitter");
+    };
+    assert_eq!(abbreviations.as_deref(), Some(&["cl".to_string()][..]));
+    assert!(unicode_segmentation);
+
+    // Configurations saved without the options keep the defaults
+    let config: SplitterStrategy = serde_json::from_value(json!({
+        "type": "Sentence",
+        "chunk_size": 10,
+        "chunk_overlap": 0,
+        "sentence_endings": null
+    }))
+    .unwrap();
+    assert!(matches!(
+        config,
+        SplitterStrategy::Sentence {
+            abbreviations: None,
+            unicode_segmentation: false,
+            ..
+        }
+    ));
+}
+
+fn document(source: &str, document_type: &str, content: &str) -> DocumentContent {
+    DocumentContent {
+        source: source.to_string(),
+        document_type: document_type.to_string(),
+        content: content.to_string(),
+        metadata: HashMap::new(),
+        file_size: content.len(),
+        extracted_at: chrono::Utc::now(),
+    }
+}
+
+fn metadata_str<'a>(chunk: &'a TextChunk, key: &str) -> Option<&'a str> {
+    chunk.metadata.get(key).and_then(Value::as_str)
+}
+
+#[test]
+fn test_chunk_document_picks_the_splitter_by_type() {
+    let config = DocumentChunkConfig {
+        chunk_size: 200,
+        chunk_overlap: 0,
+    };
+    let cases = [
+        ("docs/guide.md", "md", MARKDOWN_DOCUMENT, "markdown"),
+        ("src/splitter.rs", "rs", RUST_DOCUMENT, "code"),
+        ("ingest/pipeline.py", "py", PYTHON_DOCUMENT, "code"),
+        ("reports/q3.pdf", "pdf", PROSE_DOCUMENT, "sentence"),
+        ("reports/q3.docx", "docx", PROSE_DOCUMENT, "sentence"),
+        ("reports/q3.txt", "txt", PROSE_DOCUMENT, "sentence"),
+        ("data/records.json", "json", JSON_DOCUMENT, "recursive"),
+    ];
+
+    for (source, document_type, content, splitter) in cases {
+        let chunks = chunk_document(&document(source, document_type, content), &config).unwrap();
+
+        assert!(!chunks.is_empty(), "{source}");
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(metadata_str(chunk, "splitter"), Some(splitter), "{source}");
+            assert_eq!(metadata_str(chunk, "source"), Some(source));
+            assert_eq!(metadata_str(chunk, "document_type"), Some(document_type));
+            assert_eq!(chunk.chunk_index, index);
+            // Sentence chunks end at the first sentence reaching the size
+            if splitter != "sentence" {
+                assert!(chunk.content.len() <= 200, "{source}: {:?}", chunk.content);
+            }
+        }
+    }
+
+    // Extensions are matched without the dot and case
+    let splitter = TextSplitterFactory::for_document(".TS", 100, 10).unwrap();
+    assert!(matches!(
+        &splitter.config().strategy,
+        SplitterStrategy::Code { language: Some(language), .. } if language == "typescript"
+    ));
+    let splitter = TextSplitterFactory::for_document("markdown", 100, 10).unwrap();
+    assert_eq!(splitter.config().strategy.name(), "markdown");
+}
+
+#[test]
+fn test_markdown_chunks_follow_sections() {
+    let chunks = chunk_document(
+        &document("guide.md", "md", MARKDOWN_DOCUMENT),
+        &DocumentChunkConfig::default(),
+    )
+    .unwrap();
+
+    // The `#` comment inside the fenced block is not a heading
+    let headings: Vec<_> = chunks
+        .iter()
+        .map(|chunk| metadata_str(chunk, "heading"))
+        .collect();
+    assert_eq!(
+        headings,
+        [
+            Some("Getting started"),
+            Some("Installation"),
+            Some("Your first workflow")
+        ]
+    );
+    assert!(chunks[1].content.starts_with("## Installation"));
+    assert!(chunks[1].content.ends_with("```"));
+    for chunk in &chunks {
+        assert_eq!(
+            &MARKDOWN_DOCUMENT[chunk.start_index..chunk.end_index],
+            chunk.content
+        );
+    }
+
+    // Small chunks still cut around the fence before inside it
+    let splitter = MarkdownSplitter::new(120, 0, true).unwrap();
+    let chunks = splitter.split_text(MARKDOWN_DOCUMENT).unwrap();
+    assert!(
+        chunks
+            .iter()
+            .any(|chunk| chunk.content.starts_with("```bash"))
+    );
+    assert!(chunks.iter().all(|chunk| chunk.content.len() <= 120));
+
+    // Without header splitting small sections share chunks
+    let splitter = MarkdownSplitter::new(1000, 0, false).unwrap();
+    let chunks = splitter.split_text(MARKDOWN_DOCUMENT).unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert!(!chunks[0].metadata.contains_key("heading"));
+    let splitter = MarkdownSplitter::new(350, 0, false).unwrap();
+    let chunks = splitter.split_text(MARKDOWN_DOCUMENT).unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[1].content.starts_with("## Your first workflow"));
+
+    // Prose overlaps by whole sentences
+    let splitter = TextSplitterFactory::for_document("txt", 100, 50).unwrap();
+    assert!(matches!(
+        splitter.config().strategy,
+        SplitterStrategy::Sentence {
+            chunk_overlap: 1,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_code_chunks_start_at_items() {
+    let chunks = chunk_document(
+        &document("splitter.rs", "rs", RUST_DOCUMENT),
+        &DocumentChunkConfig {
+            chunk_size: 250,
+            chunk_overlap: 0,
+        },
+    )
+    .unwrap();
+
+    let starts: Vec<_> = chunks
+        .iter()
+        .map(|chunk| chunk.content.lines().next().unwrap())
+        .collect();
+    assert_eq!(
+        starts,
+        [
+            "//! Chunk helpers",
+            "/// Chunks of one document",
+            "impl Chunks {"
+        ]
+    );
+    assert!(
+        chunks
+            .iter()
+            .all(|chunk| metadata_str(chunk, "language") == Some("rust"))
+    );
+
+    let splitter = CodeSplitter::new(120, 0, Some("Python".to_string())).unwrap();
+    let chunks = splitter.split_text(PYTHON_DOCUMENT).unwrap();
+    assert!(
+        chunks
+            .iter()
+            .any(|chunk| chunk.content.trim_start().starts_with("def load(path):"))
+    );
+    assert!(chunks.iter().any(|chunk| {
+        chunk
+            .content
+            .trim_start()
+            .starts_with("@dataclass\nclass Pipeline:")
+    }));
+    assert!(
+        chunks
+            .iter()
+            .all(|chunk| metadata_str(chunk, "language") == Some("python"))
+    );
+
+    // Overlapping chunks reach back into the chunk before them
+    let splitter = CodeSplitter::new(200, 20, Some("rust".to_string())).unwrap();
+    let chunks = splitter.split_text(RUST_DOCUMENT).unwrap();
+    for pair in chunks.windows(2) {
+        assert!(pair[1].start_index < pair[0].end_index);
+        assert_eq!(
+            &RUST_DOCUMENT[pair[1].start_index..pair[1].end_index],
+            pair[1].content
+        );
+    }
+}
+
+#[test]
+fn test_factory_creates_markdown_and_code_splitters() {
+    for strategy in [
+        SplitterStrategy::Markdown {
+            chunk_size: 100,
+            chunk_overlap: 10,
+            split_by_headers: true,
+        },
+        SplitterStrategy::Code {
+            chunk_size: 100,
+            chunk_overlap: 10,
+            language: Some("go".to_string()),
+        },
+    ] {
+        let name = strategy.name();
+        let splitter = TextSplitterFactory::create_splitter(TextSplitterConfig {
+            strategy,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(splitter.config().strategy.name(), name);
+        assert!(splitter.validate_config().is_ok());
+    }
+
+    assert_eq!(CodeSplitter::language_for_extension("hpp"), Some("cpp"));
+    assert_eq!(CodeSplitter::language_for_extension("md"), None);
+    assert!(MarkdownSplitter::new(0, 0, true).is_err());
+    assert!(CodeSplitter::new(10, 10, None).is_err());
+}
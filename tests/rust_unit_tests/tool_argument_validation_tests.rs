@@ -0,0 +1,189 @@
+use super::test_helpers::ScriptedProvider;
+use graphbit_core::errors::GraphBitError;
+use graphbit_core::llm::{
+    LlmMessage, LlmProviderTrait, LlmRequest, LlmResponse, LlmRole, LlmTool, LlmToolCall,
+};
+use graphbit_core::tools::validate_tool_arguments;
+use serde_json::json;
+
+fn weather_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "city": {"type": "string"},
+            "days": {"type": "integer"},
+            "units": {"type": "string", "enum": ["metric", "imperial"]}
+        },
+        "required": ["city"]
+    })
+}
+
+#[test]
+fn test_valid_arguments_pass() {
+    let args = json!({"city": "Berlin", "days": 3, "units": "metric"});
+    assert!(validate_tool_arguments("get_weather", &weather_schema(), &args).is_ok());
+}
+
+#[test]
+fn test_missing_required_argument() {
+    let err =
+        validate_tool_arguments("get_weather", &weather_schema(), &json!({"days": 2})).unwrap_err();
+    assert_eq!(err.tool_name, "get_weather");
+    assert_eq!(err.errors.len(), 1);
+    assert_eq!(err.errors[0].error_code, "MISSING_REQUIRED_PROPERTY");
+    assert!(err.to_string().contains("city"));
+}
+
+#[test]
+fn test_wrong_argument_types() {
+    let args = json!({"city": 42, "days": "three"});
+    let err = validate_tool_arguments("get_weather", &weather_schema(), &args).unwrap_err();
+    assert_eq!(err.errors.len(), 2);
+    assert!(err.errors.iter().all(|e| e.error_code == "TYPE_MISMATCH"));
+}
+
+#[test]
+fn test_integer_arguments() {
+    let schema = weather_schema();
+    assert!(
+        validate_tool_arguments("get_weather", &schema, &json!({"city": "a", "days": 2.0})).is_ok()
+    );
+    assert!(
+        validate_tool_arguments("get_weather", &schema, &json!({"city": "a", "days": 2.5}))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_enum_mismatch() {
+    let args = json!({"city": "Oslo", "units": "kelvin"});
+    let err = validate_tool_arguments("get_weather", &weather_schema(), &args).unwrap_err();
+    assert_eq!(err.errors[0].error_code, "ENUM_MISMATCH");
+}
+
+fn with_additional_properties(additional: serde_json::Value) -> serde_json::Value {
+    let mut schema = weather_schema();
+    schema["additionalProperties"] = additional;
+    schema
+}
+
+#[test]
+fn test_unknown_argument_allowed_without_additional_properties() {
+    let args = json!({"city": "Oslo", "country": "NO"});
+    assert!(validate_tool_arguments("get_weather", &weather_schema(), &args).is_ok());
+}
+
+#[test]
+fn test_unknown_argument_allowed_when_additional_properties_true() {
+    let args = json!({"city": "Oslo", "country": "NO"});
+    let schema = with_additional_properties(json!(true));
+    assert!(validate_tool_arguments("get_weather", &schema, &args).is_ok());
+}
+
+#[test]
+fn test_unknown_argument_rejected_when_additional_properties_false() {
+    let args = json!({"city": "Oslo", "country": "NO"});
+    let schema = with_additional_properties(json!(false));
+    let err = validate_tool_arguments("get_weather", &schema, &args).unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    assert_eq!(err.errors[0].error_code, "UNKNOWN_PROPERTY");
+    assert_eq!(err.errors[0].message, "Unknown argument 'country'");
+    assert_eq!(err.errors[0].expected.as_deref(), Some("city, days, units"));
+}
+
+#[test]
+fn test_unknown_argument_validated_against_additional_properties_schema() {
+    let schema = with_additional_properties(json!({"type": "string"}));
+    let args = json!({"city": "Oslo", "country": "NO"});
+    assert!(validate_tool_arguments("get_weather", &schema, &args).is_ok());
+
+    let args = json!({"city": "Oslo", "population": 700_000});
+    let err = validate_tool_arguments("get_weather", &schema, &args).unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    assert_eq!(err.errors[0].field_path, "root.population");
+    assert_eq!(err.errors[0].error_code, "TYPE_MISMATCH");
+}
+
+#[test]
+fn test_tool_message_is_structured_json() {
+    let err =
+        validate_tool_arguments("get_weather", &weather_schema(), &json!({"city": 1})).unwrap_err();
+    let message: serde_json::Value = serde_json::from_str(&err.to_tool_message()).unwrap();
+    assert_eq!(message["error"], "invalid_tool_arguments");
+    assert_eq!(message["tool"], "get_weather");
+    assert_eq!(message["details"][0]["argument"], "city");
+    assert_eq!(message["details"][0]["expected"], "string");
+}
+
+#[test]
+fn test_strict_mode_error_conversion() {
+    let err = validate_tool_arguments("get_weather", &weather_schema(), &json!({})).unwrap_err();
+    let graphbit_err: GraphBitError = err.into();
+    assert!(
+        matches!(graphbit_err, GraphBitError::Validation { ref field, .. } if field == "tool_arguments")
+    );
+    assert!(!graphbit_err.is_retryable());
+}
+
+fn tool_call(id: &str, parameters: serde_json::Value) -> LlmToolCall {
+    LlmToolCall {
+        id: id.to_string(),
+        name: "get_weather".to_string(),
+        parameters,
+    }
+}
+
+#[tokio::test]
+async fn test_model_corrects_arguments_after_validation_error() {
+    // First asks for the tool with bad arguments, then (after seeing the validation
+    // error) with corrected ones, then answers
+    let provider = ScriptedProvider::replaying(vec![
+        LlmResponse::new("", "scripted-model").with_tool_calls(vec![tool_call(
+            "call_1",
+            json!({"city": "Paris", "days": "two"}),
+        )]),
+        LlmResponse::new("", "scripted-model").with_tool_calls(vec![tool_call(
+            "call_2",
+            json!({"city": "Paris", "days": 2}),
+        )]),
+        LlmResponse::new("Sunny for two days", "scripted-model"),
+    ]);
+    let requests = provider.requests();
+    let tool = LlmTool::new("get_weather", "Weather forecast", weather_schema());
+
+    let mut messages = vec![LlmMessage::user("Weather in Paris for two days?")];
+    let mut executed = Vec::new();
+    let final_answer = loop {
+        let request = LlmRequest::with_messages(messages.clone()).with_tools(vec![tool.clone()]);
+        let response = provider.complete(request).await.unwrap();
+        if response.tool_calls.is_empty() {
+            break response.content;
+        }
+        messages.push(LlmMessage::assistant("").with_tool_calls(response.tool_calls.clone()));
+        for call in &response.tool_calls {
+            let result =
+                match validate_tool_arguments(&call.name, &tool.parameters, &call.parameters) {
+                    Ok(()) => {
+                        executed.push(call.parameters.clone());
+                        "18C".to_string()
+                    }
+                    Err(err) => err.to_tool_message(),
+                };
+            messages.push(LlmMessage::tool(&call.id, result));
+        }
+    };
+
+    assert_eq!(final_answer, "Sunny for two days");
+    assert_eq!(executed, vec![json!({"city": "Paris", "days": 2})]);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    let error_message = requests[1]
+        .messages
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::Tool))
+        .unwrap();
+    let error: serde_json::Value = serde_json::from_str(&error_message.content).unwrap();
+    assert_eq!(error["error"], "invalid_tool_arguments");
+    assert_eq!(error["details"][0]["argument"], "days");
+}
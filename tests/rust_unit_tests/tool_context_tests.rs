@@ -0,0 +1,232 @@
+//! Tools reading and writing workflow state through their `ToolContext`
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmRole, LlmToolCall},
+    tools::{ContextToolHandler, ToolContext, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowContext, WorkflowId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Provider asking for `tool_calls` on `Research` prompts until the node has
+/// called tools, and recording every prompt in `prompts`
+fn researching_provider(
+    tool_calls: Vec<LlmToolCall>,
+    prompts: &Arc<Mutex<Vec<String>>>,
+) -> ScriptedProvider {
+    let prompts = prompts.clone();
+    ScriptedProvider::new(move |request| {
+        let prompt = request
+            .messages
+            .iter()
+            .find(|message| matches!(message.role, LlmRole::User))
+            .map(|message| message.content.clone())
+            .unwrap_or_default();
+        prompts.lock().unwrap().push(prompt.clone());
+
+        let called_tools = request
+            .messages
+            .iter()
+            .any(|message| matches!(message.role, LlmRole::Tool));
+        Ok(if prompt.contains("Task: Research") && !called_tools {
+            LlmResponse::new("", "scripted-model").with_tool_calls(tool_calls.clone())
+        } else {
+            LlmResponse::new("Done", "scripted-model")
+        })
+    })
+}
+
+fn remember_call(id: &str, value: &str) -> LlmToolCall {
+    LlmToolCall {
+        id: id.to_string(),
+        name: "remember".to_string(),
+        parameters: json!({"value": value}),
+    }
+}
+
+/// Registry with a `remember` tool storing its `value` argument, prefixed
+/// with the `topic` input, as `scratch.summary`
+fn registry() -> ToolRegistry {
+    let handler: ContextToolHandler = Arc::new(|args: serde_json::Value, ctx: ToolContext| {
+        Box::pin(async move {
+            let topic = ctx.variable("topic").and_then(|v| v.as_str()).unwrap_or("");
+            let value = args["value"].as_str().unwrap_or("");
+            ctx.set("scratch.summary", json!(format!("{topic}: {value}")))?;
+            Ok(json!("remembered"))
+        })
+    });
+    let registry = ToolRegistry::new();
+    registry
+        .register_context_tool(
+            ToolMetadata::new(
+                "remember",
+                "Remember a summary for later nodes",
+                json!({"type": "object", "properties": {"value": {"type": "string"}}}),
+            ),
+            handler,
+        )
+        .unwrap();
+    registry
+}
+
+fn agent_node(agent_id: &AgentId, name: &str, prompt: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), prompt),
+        },
+    )
+}
+
+/// Run a `Research` node calling `remember` as scripted by `tool_calls`,
+/// followed by a `Write` node, returning the context and the prompts sent
+async fn run(tool_calls: Vec<LlmToolCall>) -> (WorkflowContext, Vec<String>) {
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_tool_registry(registry());
+    let mut agent_ids = Vec::new();
+    for name in ["Researcher", "Writer"] {
+        let agent_id = AgentId::new();
+        let agent = Arc::new(TestAgent::new(
+            AgentConfig::new(name, "", LlmConfig::default()).with_id(agent_id.clone()),
+            researching_provider(tool_calls.clone(), &prompts),
+        ));
+        executor.register_agent(agent).await;
+        agent_ids.push(agent_id);
+    }
+
+    let mut workflow = Workflow::new("report", "");
+    let research = workflow
+        .add_node(agent_node(&agent_ids[0], "Research", "Task: Research").with_tools(["remember"]))
+        .unwrap();
+    let write = workflow
+        .add_node(agent_node(
+            &agent_ids[1],
+            "Write",
+            "Task: Write about {{scratch.summary}}",
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(research, write, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let context = WorkflowContext::new(workflow.id.clone())
+        .with_inputs(HashMap::from([("topic".to_string(), json!("GraphBit"))]));
+    let context = executor
+        .execute_with_context(
+            workflow,
+            None,
+            None,
+            graphbit_core::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+        .unwrap();
+    let prompts = prompts.lock().unwrap().clone();
+    (context, prompts)
+}
+
+#[tokio::test]
+async fn test_tool_writes_reach_later_prompts() {
+    let (context, prompts) = run(vec![remember_call("call_1", "fast")]).await;
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert!(
+        prompts
+            .iter()
+            .any(|prompt| prompt.ends_with("Task: Write about GraphBit: fast")),
+        "{prompts:?}"
+    );
+    assert_eq!(
+        context.get_scratch("summary"),
+        Some(&json!("GraphBit: fast"))
+    );
+    assert_eq!(
+        context.metadata["scratch"],
+        json!({"summary": "GraphBit: fast"})
+    );
+}
+
+#[tokio::test]
+async fn test_last_tool_call_wins() {
+    let (context, prompts) = run(vec![
+        remember_call("call_1", "fast"),
+        remember_call("call_2", "typed"),
+    ])
+    .await;
+
+    assert!(
+        prompts
+            .iter()
+            .any(|prompt| prompt.ends_with("Task: Write about GraphBit: typed")),
+        "{prompts:?}"
+    );
+    assert_eq!(
+        context.get_scratch("summary"),
+        Some(&json!("GraphBit: typed"))
+    );
+}
+
+#[test]
+fn test_tool_context_reads_and_buffers_writes() {
+    let mut workflow_context = WorkflowContext::new(WorkflowId::new());
+    workflow_context.set_node_output_by_name("Research", json!({"facts": 3}));
+    workflow_context.set_variable("topic".to_string(), json!("GraphBit"));
+    workflow_context.merge_scratch("seed", vec![("draft".to_string(), json!("v1"))]);
+
+    let ctx = ToolContext::new(&workflow_context, "Write");
+    assert_eq!(ctx.node_name(), "Write");
+    assert_eq!(ctx.node_output("Research"), Some(&json!({"facts": 3})));
+    assert_eq!(ctx.variable("topic"), Some(&json!("GraphBit")));
+    assert_eq!(ctx.get("scratch.draft"), Some(json!("v1")));
+
+    // Writes are visible to the tool at once and to the workflow once merged
+    ctx.set("scratch.draft", json!("v2")).unwrap();
+    ctx.set("scratch.notes", json!({"tone": "dry"})).unwrap();
+    assert_eq!(ctx.get("scratch.draft"), Some(json!("v2")));
+    assert_eq!(workflow_context.get_scratch("draft"), Some(&json!("v1")));
+
+    workflow_context.merge_scratch("Write", ctx.take_writes());
+    assert_eq!(workflow_context.get_scratch("draft"), Some(&json!("v2")));
+    assert_eq!(
+        workflow_context.get_scratch("notes.tone"),
+        Some(&json!("dry"))
+    );
+    assert!(ctx.take_writes().is_empty());
+}
+
+#[test]
+fn test_tool_context_keys_must_be_namespaced() {
+    let ctx = ToolContext::default();
+    for key in ["summary", "scratch.", "scratch", "vars.summary"] {
+        let error = ctx.set(key, json!(1)).unwrap_err().to_string();
+        assert!(error.contains("scratch.NAME"), "{key}: {error}");
+    }
+    assert_eq!(ctx.get("summary"), None);
+}
+
+#[tokio::test]
+async fn test_registry_execute_passes_an_empty_context() {
+    let output = registry()
+        .execute("remember", json!({"value": "fast"}))
+        .await
+        .unwrap();
+    assert_eq!(output, json!("remembered"));
+
+    let ctx = ToolContext::default();
+    registry()
+        .execute_with_context("remember", json!({"value": "fast"}), ctx.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        ctx.take_writes(),
+        vec![("summary".to_string(), json!(": fast"))]
+    );
+}
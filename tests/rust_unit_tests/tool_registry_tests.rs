@@ -0,0 +1,401 @@
+use super::test_helpers::{Requests, ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmRole, LlmToolCall},
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, ToolCallTraceConfig},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn add_tool_metadata() -> ToolMetadata {
+    ToolMetadata::new(
+        "add",
+        "Add two integers",
+        json!({
+            "type": "object",
+            "properties": {"a": {"type": "integer"}, "b": {"type": "integer"}},
+            "required": ["a", "b"]
+        }),
+    )
+}
+
+fn add_tool_handler(calls: Arc<AtomicUsize>) -> ToolHandler {
+    Arc::new(move |args: serde_json::Value| {
+        let calls = calls.clone();
+        Box::pin(async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            let a = args["a"].as_i64().unwrap_or_default();
+            let b = args["b"].as_i64().unwrap_or_default();
+            Ok(json!(a + b))
+        })
+    })
+}
+
+fn add_call(parameters: serde_json::Value) -> LlmResponse {
+    LlmResponse::new("", "scripted-model").with_tool_calls(vec![LlmToolCall {
+        id: "call_add".to_string(),
+        name: "add".to_string(),
+        parameters,
+    }])
+}
+
+/// `slow_double` tool that sleeps before answering so its duration is measurable
+fn register_slow_double(registry: &ToolRegistry) {
+    let metadata = ToolMetadata::new(
+        "slow_double",
+        "Double an integer, slowly",
+        json!({
+            "type": "object",
+            "properties": {"x": {"type": "integer"}},
+            "required": ["x"]
+        }),
+    );
+    let handler: ToolHandler = Arc::new(|args: serde_json::Value| {
+        Box::pin(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+            Ok(json!(args["x"].as_i64().unwrap_or_default() * 2))
+        })
+    });
+    registry.register_tool(metadata, handler).unwrap();
+}
+
+async fn executor_with_agent(
+    responses: Vec<LlmResponse>,
+    registry: ToolRegistry,
+) -> (WorkflowExecutor, AgentId, Requests) {
+    let provider = ScriptedProvider::replaying(responses);
+    let requests = provider.requests();
+    let agent_id = AgentId::new();
+    let agent = Arc::new(TestAgent::new(
+        AgentConfig::new("Calculator", "Adds numbers", LlmConfig::default())
+            .with_id(agent_id.clone()),
+        provider,
+    ));
+
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_tool_registry(registry);
+    executor.register_agent(agent).await;
+    (executor, agent_id, requests)
+}
+
+fn calculator_workflow(agent_id: AgentId) -> Workflow {
+    let node = WorkflowNode::new(
+        "calculator",
+        "Adds numbers with a tool",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, "What is 2 + 3?"),
+        },
+    )
+    .with_tools(["add"]);
+
+    let mut workflow = Workflow::new("tool registry", "Rust closure tool");
+    workflow.add_node(node).unwrap();
+    workflow
+}
+
+#[tokio::test]
+async fn test_registry_register_execute_unregister() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(add_tool_metadata(), add_tool_handler(calls.clone()))
+        .unwrap();
+
+    assert!(registry.has_tool("add"));
+    assert_eq!(registry.tool_names(), vec!["add".to_string()]);
+    assert_eq!(
+        registry
+            .llm_tools(&["add".to_string(), "missing".to_string()])
+            .len(),
+        1
+    );
+    assert_eq!(
+        registry
+            .execute("add", json!({"a": 1, "b": 2}))
+            .await
+            .unwrap(),
+        json!(3)
+    );
+    assert!(registry.execute("missing", json!({})).await.is_err());
+
+    assert!(registry.unregister_tool("add"));
+    assert!(registry.is_empty());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_registry_rejects_empty_name() {
+    let registry = ToolRegistry::new();
+    let metadata = ToolMetadata::new(" ", "nameless", json!({"type": "object"}));
+    assert!(
+        registry
+            .register_tool(metadata, add_tool_handler(Arc::new(AtomicUsize::new(0))))
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_registry_extend_copies_and_replaces_tools() {
+    let base_calls = Arc::new(AtomicUsize::new(0));
+    let base = ToolRegistry::new();
+    base.register_tool(add_tool_metadata(), add_tool_handler(base_calls.clone()))
+        .unwrap();
+    register_slow_double(&base);
+
+    let override_calls = Arc::new(AtomicUsize::new(0));
+    let overrides = ToolRegistry::new();
+    overrides
+        .register_tool(
+            add_tool_metadata(),
+            add_tool_handler(override_calls.clone()),
+        )
+        .unwrap();
+
+    let merged = ToolRegistry::new();
+    merged.extend(&base).unwrap();
+    merged.extend(&overrides).unwrap();
+    let mut names = merged.tool_names();
+    names.sort();
+    assert_eq!(names, vec!["add".to_string(), "slow_double".to_string()]);
+
+    merged
+        .execute("add", json!({"a": 1, "b": 2}))
+        .await
+        .unwrap();
+    assert_eq!(base_calls.load(Ordering::SeqCst), 0);
+    assert_eq!(override_calls.load(Ordering::SeqCst), 1);
+
+    // Extending a registry with itself leaves it unchanged
+    merged.extend(&merged).unwrap();
+    assert_eq!(merged.len(), 2);
+}
+
+#[tokio::test]
+async fn test_agent_calls_rust_closure_tool() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(add_tool_metadata(), add_tool_handler(calls.clone()))
+        .unwrap();
+
+    let (executor, agent_id, requests) = executor_with_agent(
+        vec![
+            add_call(json!({"a": 2, "b": 3})),
+            LlmResponse::new("2 + 3 = 5", "scripted-model"),
+        ],
+        registry,
+    )
+    .await;
+
+    let context = executor
+        .execute(calculator_workflow(agent_id), None)
+        .await
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        context.get_node_output("calculator"),
+        Some(&json!("2 + 3 = 5"))
+    );
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].tools[0].name, "add");
+
+    // The tool result round-trips into the conversation sent with the follow-up call
+    let follow_up = &requests[1].messages;
+    let assistant = follow_up
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::Assistant))
+        .unwrap();
+    assert_eq!(assistant.tool_calls[0].name, "add");
+    let tool_message = follow_up
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::Tool))
+        .unwrap();
+    assert_eq!(tool_message.tool_call_id.as_deref(), Some("call_add"));
+    assert_eq!(tool_message.content, "5");
+
+    let metadata = &context.metadata["node_response_calculator"];
+    assert_eq!(metadata["total_tool_calls"], 1);
+    assert_eq!(metadata["tools_used"], json!(["add"]));
+    assert_eq!(metadata["final_output"], "2 + 3 = 5");
+}
+
+#[tokio::test]
+async fn test_invalid_arguments_returned_to_model() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(add_tool_metadata(), add_tool_handler(calls.clone()))
+        .unwrap();
+
+    let (executor, agent_id, requests) = executor_with_agent(
+        vec![
+            add_call(json!({"a": "two", "b": 3})),
+            add_call(json!({"a": 2, "b": 3})),
+            LlmResponse::new("5", "scripted-model"),
+        ],
+        registry,
+    )
+    .await;
+
+    executor
+        .execute(calculator_workflow(agent_id), None)
+        .await
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    let error = requests[1]
+        .messages
+        .iter()
+        .find(|m| matches!(m.role, LlmRole::Tool))
+        .unwrap();
+    assert!(error.content.contains("invalid_tool_arguments"));
+}
+
+#[tokio::test]
+async fn test_strict_tool_args_fail_the_node() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(add_tool_metadata(), add_tool_handler(calls.clone()))
+        .unwrap();
+
+    let (executor, agent_id, requests) =
+        executor_with_agent(vec![add_call(json!({"a": 2}))], registry).await;
+    let executor = executor.with_strict_tool_args(true);
+
+    let context = executor
+        .execute(calculator_workflow(agent_id), None)
+        .await
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    assert_ne!(context.get_node_output("calculator"), Some(&json!("done")));
+}
+
+#[tokio::test]
+async fn test_tool_calls_recorded_in_order() {
+    let registry = ToolRegistry::new();
+    registry
+        .register_tool(
+            add_tool_metadata(),
+            add_tool_handler(Arc::new(AtomicUsize::new(0))),
+        )
+        .unwrap();
+    register_slow_double(&registry);
+
+    let (executor, agent_id, _) = executor_with_agent(
+        vec![
+            LlmResponse::new("", "scripted-model").with_tool_calls(vec![
+                LlmToolCall {
+                    id: "call_1".to_string(),
+                    name: "add".to_string(),
+                    parameters: json!({"a": 2, "b": 3}),
+                },
+                LlmToolCall {
+                    id: "call_2".to_string(),
+                    name: "slow_double".to_string(),
+                    parameters: json!({"x": 5}),
+                },
+            ]),
+            add_call(json!({"a": "ten", "b": 1})),
+            LlmResponse::new("10", "scripted-model"),
+        ],
+        registry,
+    )
+    .await;
+
+    let context = executor
+        .execute(calculator_workflow(agent_id), None)
+        .await
+        .unwrap();
+
+    let calls = context.get_tool_calls("calculator");
+    let names: Vec<&str> = calls.iter().map(|c| c.tool_name.as_str()).collect();
+    assert_eq!(names, vec!["add", "slow_double", "add"]);
+
+    assert_eq!(calls[0].arguments, json!({"a": 2, "b": 3}));
+    assert_eq!(calls[0].output, json!("5"));
+    assert!(calls[0].success);
+    assert_eq!(calls[0].attempt, 1);
+
+    assert_eq!(calls[1].output, json!("10"));
+    assert!(calls[1].duration_ms >= 25.0);
+    assert!(calls[1].duration_ms >= calls[0].duration_ms);
+
+    // The retried call with invalid arguments is recorded as a failed second attempt
+    assert!(!calls[2].success);
+    assert_eq!(calls[2].attempt, 2);
+
+    let stats = context.get_stats().unwrap();
+    assert_eq!(stats.total_tool_calls, 3);
+    assert_eq!(stats.failed_tool_calls, 1);
+    assert!(context.get_tool_calls("unknown").is_empty());
+}
+
+#[tokio::test]
+async fn test_tool_call_trace_truncates_payloads() {
+    let registry = ToolRegistry::new();
+    register_slow_double(&registry);
+
+    let (executor, agent_id, _) = executor_with_agent(
+        vec![
+            LlmResponse::new("", "scripted-model").with_tool_calls(vec![LlmToolCall {
+                id: "call_1".to_string(),
+                name: "slow_double".to_string(),
+                parameters: json!({"x": 123_456_789}),
+            }]),
+            LlmResponse::new("done", "scripted-model"),
+        ],
+        registry,
+    )
+    .await;
+    let executor = executor.with_tool_call_trace(ToolCallTraceConfig::truncated(4));
+
+    let node = WorkflowNode::new(
+        "doubler",
+        "Doubles numbers with a tool",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, "Double 123456789"),
+        },
+    )
+    .with_tools(["slow_double"]);
+    let mut workflow = Workflow::new("trace", "Truncated tool trace");
+    workflow.add_node(node).unwrap();
+
+    let context = executor.execute(workflow, None).await.unwrap();
+    let call = &context.get_tool_calls("doubler")[0];
+    assert_eq!(call.arguments, json!("{\"x\"... [truncated 11 chars]"));
+    assert_eq!(call.output, json!("2469... [truncated 5 chars]"));
+}
+
+#[test]
+fn test_tool_call_trace_redacts_payloads() {
+    let record = graphbit_core::types::ToolCallRecord::new(
+        "login",
+        json!({"password": "hunter2"}),
+        json!("token"),
+        true,
+        1.0,
+    );
+    let redacted = ToolCallTraceConfig::redacted().apply(record.clone());
+    assert_eq!(redacted.arguments, json!(ToolCallTraceConfig::REDACTED));
+    assert_eq!(redacted.output, json!(ToolCallTraceConfig::REDACTED));
+    assert_eq!(redacted.tool_name, "login");
+
+    // Payloads within the limit are kept as-is
+    assert_eq!(
+        ToolCallTraceConfig::truncated(64).apply(record.clone()),
+        record
+    );
+}
@@ -0,0 +1,258 @@
+//! Tool schema translation: one set of tool definitions in every provider's
+//! format, keyword downgrades for Gemini and tool calls parsed back
+
+use graphbit_core::{
+    errors::GraphBitError,
+    llm::{LlmTool, SchemaMode, ToolDialect, ToolSchemaTranslator},
+};
+use serde_json::{Value, json};
+
+/// A tool without parameters and one whose schema uses keywords Gemini rejects
+fn tools() -> Vec<LlmTool> {
+    vec![
+        LlmTool::new("get_time", "Current time", Value::Null),
+        LlmTool::new(
+            "convert",
+            "Convert an amount between currencies",
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "amount": {"type": "number", "exclusiveMinimum": 0},
+                    "currency": {"oneOf": [{"const": "USD"}, {"const": "EUR"}]},
+                    "note": {"type": ["string", "null"]}
+                },
+                "required": ["amount", "currency"],
+                "additionalProperties": false
+            }),
+        ),
+    ]
+}
+
+fn translate(dialect: ToolDialect) -> Value {
+    ToolSchemaTranslator::new(dialect)
+        .translate(&tools())
+        .unwrap()
+        .tools
+}
+
+fn empty_object() -> Value {
+    json!({"type": "object", "properties": {}})
+}
+
+#[test]
+fn test_openai_format() {
+    assert_eq!(
+        translate(ToolDialect::OpenAi),
+        json!([
+            {
+                "type": "function",
+                "function": {
+                    "name": "get_time",
+                    "description": "Current time",
+                    "parameters": empty_object()
+                }
+            },
+            {
+                "type": "function",
+                "function": {
+                    "name": "convert",
+                    "description": "Convert an amount between currencies",
+                    "parameters": tools()[1].parameters
+                }
+            }
+        ])
+    );
+}
+
+#[test]
+fn test_anthropic_format() {
+    assert_eq!(
+        translate(ToolDialect::Anthropic),
+        json!([
+            {
+                "name": "get_time",
+                "description": "Current time",
+                "input_schema": empty_object()
+            },
+            {
+                "name": "convert",
+                "description": "Convert an amount between currencies",
+                "input_schema": tools()[1].parameters
+            }
+        ])
+    );
+}
+
+#[test]
+fn test_gemini_format_downgrades_unsupported_keywords() {
+    let translated = ToolSchemaTranslator::new(ToolDialect::Gemini)
+        .translate(&tools())
+        .unwrap();
+
+    assert_eq!(
+        translated.tools,
+        json!([{
+            "functionDeclarations": [
+                {
+                    "name": "get_time",
+                    "description": "Current time",
+                    "parameters": empty_object()
+                },
+                {
+                    "name": "convert",
+                    "description": "Convert an amount between currencies",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "amount": {"type": "number", "minimum": 0},
+                            "currency": {"anyOf": [{"enum": ["USD"]}, {"enum": ["EUR"]}]},
+                            "note": {"type": "string", "nullable": true}
+                        },
+                        "required": ["amount", "currency"]
+                    }
+                }
+            ]
+        }])
+    );
+    let mut paths: Vec<&str> = translated
+        .warnings
+        .iter()
+        .map(|warning| warning.path.as_str())
+        .collect();
+    paths.sort_unstable();
+    assert_eq!(
+        paths,
+        [
+            "/$schema",
+            "/additionalProperties",
+            "/properties/amount/exclusiveMinimum",
+            "/properties/currency/anyOf/0/const",
+            "/properties/currency/anyOf/1/const",
+            "/properties/currency/oneOf",
+            "/properties/note/type",
+        ]
+    );
+    assert!(
+        translated
+            .warnings
+            .iter()
+            .all(|warning| warning.tool == "convert")
+    );
+}
+
+#[test]
+fn test_strict_mode_rejects_unsupported_keywords() {
+    let strict = ToolSchemaTranslator::new(ToolDialect::Gemini).with_mode(SchemaMode::Strict);
+    match strict.translate(&tools()) {
+        Err(GraphBitError::Validation { field, message }) => {
+            assert_eq!(field, "tools.convert.parameters");
+            assert!(message.contains("Gemini does not support"), "{message}");
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+
+    // Schemas within the subset translate unchanged
+    let supported = LlmTool::new(
+        "search",
+        "Search",
+        json!({"type": "object", "properties": {"query": {"type": "string"}}}),
+    );
+    let translated = strict.translate(&[supported.clone()]).unwrap();
+    assert_eq!(
+        translated.tools[0]["functionDeclarations"][0]["parameters"],
+        supported.parameters
+    );
+    assert!(translated.warnings.is_empty());
+
+    // Other providers accept the full schema in strict mode
+    let strict_openai =
+        ToolSchemaTranslator::new(ToolDialect::OpenAi).with_mode(SchemaMode::Strict);
+    assert!(
+        strict_openai
+            .translate(&tools())
+            .unwrap()
+            .warnings
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_non_object_parameters_are_rejected() {
+    let tool = LlmTool::new("echo", "Echo", json!({"type": "string"}));
+    for dialect in [
+        ToolDialect::OpenAi,
+        ToolDialect::Anthropic,
+        ToolDialect::Gemini,
+    ] {
+        assert!(matches!(
+            ToolSchemaTranslator::new(dialect).translate(&[tool.clone()]),
+            Err(GraphBitError::Validation { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_tool_calls_are_parsed_from_every_format() {
+    let openai = json!({"choices": [{"message": {"role": "assistant", "tool_calls": [{
+        "id": "call_1",
+        "type": "function",
+        "function": {"name": "convert", "arguments": "{\"amount\": 5, \"currency\": \"EUR\"}"}
+    }]}}]});
+    let anthropic = json!({"content": [
+        {"type": "text", "text": "Converting"},
+        {"type": "tool_use", "id": "call_1", "name": "convert", "input": {"amount": 5, "currency": "EUR"}}
+    ]});
+    let gemini = json!({"candidates": [{"content": {"role": "model", "parts": [
+        {"functionCall": {"name": "convert", "args": {"amount": 5, "currency": "EUR"}}}
+    ]}}]});
+
+    for (dialect, response) in [
+        (ToolDialect::OpenAi, openai),
+        (ToolDialect::Anthropic, anthropic),
+        (ToolDialect::Gemini, gemini),
+    ] {
+        let calls = ToolSchemaTranslator::new(dialect)
+            .parse_tool_calls(&response)
+            .unwrap();
+        assert_eq!(calls.len(), 1, "{dialect:?}");
+        assert_eq!(calls[0].name, "convert");
+        assert_eq!(calls[0].parameters, json!({"amount": 5, "currency": "EUR"}));
+        if dialect == ToolDialect::Gemini {
+            assert!(calls[0].id.starts_with("call_"));
+        } else {
+            assert_eq!(calls[0].id, "call_1");
+        }
+    }
+}
+
+#[test]
+fn test_invalid_openai_arguments() {
+    let response = json!({"choices": [{"message": {"tool_calls": [{
+        "id": "call_1",
+        "type": "function",
+        "function": {"name": "convert", "arguments": "{amount: 5"}
+    }]}}]});
+
+    let calls = ToolSchemaTranslator::new(ToolDialect::OpenAi)
+        .parse_tool_calls(&response)
+        .unwrap();
+    assert_eq!(calls[0].parameters, json!({"raw_arguments": "{amount: 5"}));
+
+    assert!(
+        ToolSchemaTranslator::new(ToolDialect::OpenAi)
+            .with_mode(SchemaMode::Strict)
+            .parse_tool_calls(&response)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_dialect_for_provider() {
+    assert_eq!(
+        ToolDialect::for_provider("anthropic"),
+        ToolDialect::Anthropic
+    );
+    assert_eq!(ToolDialect::for_provider("gemini"), ToolDialect::Gemini);
+    assert_eq!(ToolDialect::for_provider("mistralai"), ToolDialect::OpenAi);
+}
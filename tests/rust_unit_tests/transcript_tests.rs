@@ -0,0 +1,185 @@
+//! Markdown transcripts of the conversations of agent nodes
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmToolCall},
+    tools::{ToolHandler, ToolMetadata, ToolRegistry},
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/rust_unit_tests/snapshots")
+        .join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing snapshot {}: {e}", path.display()));
+    assert_eq!(
+        actual.trim_end(),
+        expected.trim_end(),
+        "transcript does not match {}; rerun with UPDATE_SNAPSHOTS=1 if the change is intended",
+        path.display()
+    );
+}
+
+fn tool(registry: &ToolRegistry, name: &str, result: serde_json::Value) {
+    let handler: ToolHandler = Arc::new(move |_args: serde_json::Value| {
+        let result = result.clone();
+        Box::pin(async move { Ok(result) })
+    });
+    registry
+        .register_tool(
+            ToolMetadata::new(name, name, json!({"type": "object"})),
+            handler,
+        )
+        .unwrap();
+}
+
+/// Registry with `search`, returning one hit, and `fetch_page`, returning a
+/// page long enough to be collapsed
+fn registry() -> ToolRegistry {
+    let registry = ToolRegistry::new();
+    tool(
+        &registry,
+        "search",
+        json!([{"title": "Tokio benchmarks", "url": "https://example.com/bench"}]),
+    );
+    let page: String = (1..=20)
+        .map(|line| format!("Benchmark line {line}: 60000 requests per second\n"))
+        .collect();
+    tool(&registry, "fetch_page", json!(page));
+    registry
+}
+
+/// Run a `research` agent with a system prompt through the scripted conversation
+async fn run() -> WorkflowContext {
+    let executor = WorkflowExecutor::new()
+        .without_retries()
+        .with_tool_registry(registry());
+    // Searching, then fetching the page found, then answering
+    let provider = ScriptedProvider::replaying(vec![
+        LlmResponse::new("Let me search for recent benchmarks.", "scripted-model").with_tool_calls(
+            vec![LlmToolCall {
+                id: "call_1".to_string(),
+                name: "search".to_string(),
+                parameters: json!({"query": "tokio benchmarks", "api_key": "sk-live-123"}),
+            }],
+        ),
+        LlmResponse::new("", "scripted-model").with_tool_calls(vec![LlmToolCall {
+            id: "call_2".to_string(),
+            name: "fetch_page".to_string(),
+            parameters: json!({"url": "https://example.com/bench"}),
+        }]),
+        LlmResponse::new(
+            "Tokio handles 1.2M requests per second in the\n`hyper` benchmark.",
+            "scripted-model",
+        ),
+    ]);
+    let agent_id = AgentId::new();
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Researcher", "", LlmConfig::default())
+                .with_id(agent_id.clone())
+                .with_system_prompt("You are a careful researcher.\nCite your sources."),
+            provider,
+        )))
+        .await;
+
+    let node = WorkflowNode::new(
+        "research",
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, "How fast is tokio?"),
+        },
+    )
+    .with_tools(["search", "fetch_page"]);
+    let mut workflow = Workflow::new("transcript", "");
+    workflow.add_node(node).unwrap();
+
+    let context = executor.execute(workflow, None).await.unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    context
+}
+
+#[tokio::test]
+async fn test_transcript_markdown_matches_snapshot() {
+    let context = run().await;
+
+    let markdown = context
+        .to_transcript_markdown()
+        .replace(&context.workflow_id.to_string(), "<workflow-id>");
+    assert!(!markdown.contains("sk-live-123"));
+    assert_snapshot("transcript.md", &markdown);
+}
+
+#[tokio::test]
+async fn test_transcript_records_iterations() {
+    let context = run().await;
+
+    let report = context.to_report();
+    assert_eq!(report.transcript.len(), 1);
+    let transcript = &report.transcript[0];
+    assert_eq!(transcript.node_name, "research");
+    assert_eq!(transcript.model.as_deref(), Some("scripted-model"));
+    assert_eq!(
+        transcript.system_prompt.as_deref(),
+        Some("You are a careful researcher.\nCite your sources.")
+    );
+    assert_eq!(transcript.prompt.as_deref(), Some("How fast is tokio?"));
+
+    let tools: Vec<(u32, &str)> = transcript
+        .iterations
+        .iter()
+        .flat_map(|iteration| {
+            iteration
+                .tool_calls
+                .iter()
+                .map(|call| (iteration.iteration, call.tool_name.as_str()))
+        })
+        .collect();
+    assert_eq!(tools, [(1, "search"), (2, "fetch_page")]);
+    assert_eq!(
+        transcript.iterations[0].reply.as_deref(),
+        Some("Let me search for recent benchmarks.")
+    );
+    assert_eq!(transcript.iterations[1].reply, None);
+    assert_eq!(
+        transcript.iterations[0].tool_calls[0].arguments["api_key"],
+        "[REDACTED]"
+    );
+    assert!(transcript.answer.as_deref().unwrap().starts_with("Tokio"));
+    assert_eq!(transcript.error, None);
+}
+
+#[tokio::test]
+async fn test_html_report_has_transcript_tab() {
+    let context = run().await;
+
+    let html = context.to_report().to_html();
+    assert!(html.contains("<label for=\"tab-transcript\">Transcript</label>"));
+    assert!(html.contains("<section class=\"tab transcript\">"));
+    assert!(html.contains("You are a careful researcher."));
+    assert!(html.contains("<summary>Result ("));
+    assert!(!html.contains("sk-live-123"));
+}
+
+#[test]
+fn test_transcript_without_agent_nodes() {
+    let context = WorkflowContext::new(graphbit_core::types::WorkflowId::new());
+
+    assert!(
+        context
+            .to_transcript_markdown()
+            .ends_with("\n\nNo agent nodes ran.\n")
+    );
+    assert!(context.to_report().transcript.is_empty());
+}
@@ -1,6 +1,9 @@
+use graphbit_core::clock::TestClock;
 use graphbit_core::errors::GraphBitError;
 use graphbit_core::types::*;
 use graphbit_core::{AgentId, MessageContent, NodeId, WorkflowId};
+use std::sync::Arc;
+use std::time::Duration;
 
 // ID Types Tests
 #[test]
@@ -86,21 +89,25 @@ fn test_retry_config_should_retry_classification() {
 }
 
 // Circuit Breaker Tests
-#[test]
-fn test_circuit_breaker_transitions() {
+#[tokio::test]
+async fn test_circuit_breaker_transitions() {
+    let clock = Arc::new(TestClock::frozen(chrono::Utc::now()));
     let mut cb = CircuitBreaker::new(CircuitBreakerConfig {
         failure_threshold: 2,
-        recovery_timeout_ms: 100, // Increase timeout to ensure it passes
+        recovery_timeout_ms: 100,
         success_threshold: 1,
         failure_window_ms: 1_000,
-    });
+    })
+    .with_clock(clock.clone());
     // Two failures -> open
     cb.record_failure();
     cb.record_failure();
     assert!(matches!(cb.state, CircuitBreakerState::Open { .. }));
 
-    // Wait for recovery timeout
-    std::thread::sleep(std::time::Duration::from_millis(150));
+    // Still open just before the recovery timeout
+    clock.advance(Duration::from_millis(99)).await;
+    assert!(!cb.should_allow_request());
+    clock.advance(Duration::from_millis(1)).await;
 
     // After timeout allow request -> half-open
     assert!(cb.should_allow_request());
@@ -3,8 +3,10 @@
 // Tests for all core types, ID generation, message handling,
 // workflow context, and type serialization.
 
+use graphbit_core::clock::TestClock;
 use graphbit_core::types::*;
 use serde_json::json;
+use std::sync::Arc;
 // use uuid::Uuid; // Not needed, using graphbit_core types
 
 #[test]
@@ -197,6 +199,7 @@ fn test_workflow_state_variants() {
         WorkflowState::Completed,
         WorkflowState::Failed {
             error: "Test error".to_string(),
+            reason: None,
         },
         WorkflowState::Cancelled,
     ];
@@ -240,8 +243,8 @@ fn test_retry_config_builder_pattern() {
     assert!((160..=240).contains(&delay2)); // 200 ± 20% jitter
 }
 
-#[test]
-fn test_circuit_breaker_state_machine() {
+#[tokio::test]
+async fn test_circuit_breaker_state_machine() {
     let config = CircuitBreakerConfig {
         failure_threshold: 3,
         recovery_timeout_ms: 1000,
@@ -249,7 +252,8 @@ fn test_circuit_breaker_state_machine() {
         failure_window_ms: 5000,
     };
 
-    let mut breaker = CircuitBreaker::new(config);
+    let clock = Arc::new(TestClock::frozen(chrono::Utc::now()));
+    let mut breaker = CircuitBreaker::new(config).with_clock(clock.clone());
 
     // Initially closed
     assert!(matches!(breaker.state, CircuitBreakerState::Closed));
@@ -267,7 +271,7 @@ fn test_circuit_breaker_state_machine() {
     assert!(!breaker.should_allow_request());
 
     // After timeout, should transition to half-open
-    std::thread::sleep(std::time::Duration::from_millis(1001));
+    clock.advance(std::time::Duration::from_millis(1001)).await;
     assert!(breaker.should_allow_request());
     assert!(matches!(breaker.state, CircuitBreakerState::HalfOpen));
 
@@ -303,12 +307,18 @@ fn test_workflow_execution_stats() {
         total_nodes: 0,
         successful_nodes: 0,
         failed_nodes: 0,
+        skipped_nodes: 0,
         avg_execution_time_ms: 0.0,
         max_concurrent_nodes: 0,
         total_execution_time_ms: 0,
         peak_memory_usage_mb: None,
         semaphore_acquisitions: 0,
         avg_semaphore_wait_ms: 0.0,
+        total_tool_calls: 0,
+        failed_tool_calls: 0,
+        spilled_outputs: 0,
+        spilled_bytes: 0,
+        usage: Default::default(),
     };
 
     // Test timing operations
@@ -343,6 +353,11 @@ fn test_node_execution_result() {
         completed_at: Some(chrono::Utc::now()),
         retry_count: 2,
         node_id: node_id.clone(),
+        output_validation: None,
+        idempotency_key: None,
+        debug: None,
+        guardrail: None,
+        pacing: None,
     };
 
     assert_eq!(result.node_id, node_id);
@@ -362,6 +377,11 @@ fn test_node_execution_result() {
         completed_at: Some(chrono::Utc::now()),
         retry_count: 0,
         node_id: node_id.clone(),
+        output_validation: None,
+        idempotency_key: None,
+        debug: None,
+        guardrail: None,
+        pacing: None,
     };
 
     assert!(error_result.error.is_some());
@@ -0,0 +1,219 @@
+//! Token usage and cost attributed to node tags and workflow metadata, in the
+//! stats and reports of a run and summed over the run history
+
+use super::test_helpers::{ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::{LlmConfig, LlmResponse, LlmUsage},
+    run_history::RunHistory,
+    types::{AgentId, WorkflowState},
+    usage::UsageCost,
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `executor` with one agent reporting 10 prompt and 20 completion tokens for
+/// prompts asking for something "brief" and 40 and 60 otherwise, priced when
+/// `priced`
+async fn with_agent(
+    executor: WorkflowExecutor,
+    agent_id: &AgentId,
+    priced: bool,
+) -> WorkflowExecutor {
+    let mut provider = ScriptedProvider::new(|request| {
+        let brief = request.messages.iter().any(|m| m.content.contains("brief"));
+        let usage = if brief {
+            LlmUsage::new(10, 20)
+        } else {
+            LlmUsage::new(40, 60)
+        };
+        Ok(LlmResponse::new("answer", "scripted-model").with_usage(usage))
+    });
+    if priced {
+        provider = provider.with_cost_per_token(0.001, 0.002);
+    }
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Worker", "", LlmConfig::default()).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+    executor
+}
+
+fn agent_node(agent_id: &AgentId, name: &str, prompt: &str, tags: &[&str]) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id.clone(), prompt),
+        },
+    )
+    .with_registered_agent()
+    .with_tags(tags.iter().map(|tag| tag.to_string()).collect())
+}
+
+/// `search` (tagged `search`), `rank` (`search` and `ranking`), `write`
+/// (`content`, brief) and the untagged `format`, one after the other, for `team`
+fn tagged_workflow(agent_id: &AgentId, team: &str) -> Workflow {
+    let mut workflow = Workflow::new("answer", "");
+    workflow.set_metadata("team".to_string(), json!(team));
+    let ids: Vec<_> = [
+        agent_node(agent_id, "search", "Find sources", &["search"]),
+        agent_node(agent_id, "rank", "Rank sources", &["search", "ranking"]),
+        agent_node(agent_id, "write", "Write a brief answer", &["content"]),
+        agent_node(agent_id, "format", "Format the answer", &[]),
+    ]
+    .into_iter()
+    .map(|node| workflow.add_node(node).unwrap())
+    .collect();
+    for pair in ids.windows(2) {
+        workflow
+            .connect_nodes(pair[0].clone(), pair[1].clone(), WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+/// Assert `usage` used `total_tokens` tokens and cost `cost_usd`
+fn assert_usage(usage: &UsageCost, total_tokens: u32, cost_usd: f64) {
+    assert_eq!(usage.usage.total_tokens, total_tokens);
+    assert!(
+        (usage.cost_usd - cost_usd).abs() < 1e-9,
+        "{} != {cost_usd}",
+        usage.cost_usd
+    );
+}
+
+#[tokio::test]
+async fn test_usage_is_grouped_by_tag_and_team() {
+    let agent_id = AgentId::new();
+    let executor = with_agent(WorkflowExecutor::new().without_retries(), &agent_id, true).await;
+
+    let context = executor
+        .execute(tagged_workflow(&agent_id, "growth"), None)
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    let stats = context.stats.as_ref().unwrap();
+    // A full call costs 40 * 0.001 + 60 * 0.002 = 0.16, a brief one
+    // 10 * 0.001 + 20 * 0.002 = 0.05
+    assert_usage(&stats.usage.total, 330, 0.53);
+    let by_tag = stats.usage_by_tag();
+    assert_eq!(
+        by_tag.keys().collect::<Vec<_>>(),
+        ["content", "ranking", "search"]
+    );
+    assert_usage(&by_tag["search"], 200, 0.32);
+    assert_usage(&by_tag["ranking"], 100, 0.16);
+    assert_usage(&by_tag["content"], 30, 0.05);
+    assert_eq!(by_tag["search"].usage.prompt_tokens, 80);
+    assert_eq!(by_tag["search"].usage.completion_tokens, 120);
+
+    let by_team = stats.usage_by_metadata("team").unwrap();
+    assert_eq!(by_team.len(), 1);
+    assert_usage(&by_team["growth"], 330, 0.53);
+
+    let report = context.to_report();
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(
+        json["stats"]["usage"]["by_tag"]["search"]["usage"]["total_tokens"],
+        200
+    );
+    assert_eq!(
+        json["stats"]["usage"]["by_metadata"]["team"]["growth"]["usage"]["total_tokens"],
+        330
+    );
+    let html = report.to_html();
+    assert!(html.contains("<tr><th>Cost</th><td>$0.5300</td></tr>"));
+    assert!(html.contains("<h2>Usage</h2>"));
+    assert!(
+        html.contains(
+            "<tr><td>search</td><td>80</td><td>120</td><td>200</td><td>$0.3200</td></tr>"
+        )
+    );
+    assert!(html.contains("<tr><th>team</th>"));
+}
+
+#[tokio::test]
+async fn test_metadata_keys_and_unpriced_models() {
+    let agent_id = AgentId::new();
+    let executor = with_agent(
+        WorkflowExecutor::new()
+            .without_retries()
+            .with_usage_metadata_keys(["cost_center"]),
+        &agent_id,
+        false,
+    )
+    .await;
+    let mut workflow = tagged_workflow(&agent_id, "growth");
+    workflow.set_metadata("cost_center".to_string(), json!(4021));
+
+    let context = executor.execute(workflow, None).await.unwrap();
+
+    let stats = context.stats.as_ref().unwrap();
+    // Calls to models without a price count their tokens at no cost
+    assert_usage(&stats.usage.total, 330, 0.0);
+    assert_usage(&stats.usage_by_tag()["search"], 200, 0.0);
+    assert!(stats.usage_by_metadata("team").is_none());
+    assert_usage(
+        &stats.usage_by_metadata("cost_center").unwrap()["4021"],
+        330,
+        0.0,
+    );
+    assert!(!context.to_report().to_html().contains("<tr><th>Cost</th>"));
+}
+
+#[tokio::test]
+async fn test_usage_summary_adds_up_past_runs() {
+    let agent_id = AgentId::new();
+    let history = RunHistory::in_memory().unwrap();
+    let executor = with_agent(
+        WorkflowExecutor::new()
+            .without_retries()
+            .with_run_history(history.clone()),
+        &agent_id,
+        true,
+    )
+    .await;
+    for team in ["growth", "search", "growth"] {
+        executor
+            .execute(tagged_workflow(&agent_id, team), None)
+            .await
+            .unwrap();
+    }
+
+    let summary = executor
+        .usage_summary(Duration::from_secs(3600))
+        .await
+        .unwrap();
+
+    assert_eq!(summary.runs, 3);
+    assert_usage(&summary.usage.total, 990, 1.59);
+    assert_usage(&summary.usage.by_tag["search"], 600, 0.96);
+    assert_usage(&summary.usage.by_tag["content"], 90, 0.15);
+    let by_team = &summary.usage.by_metadata["team"];
+    assert_usage(&by_team["growth"], 660, 1.06);
+    assert_usage(&by_team["search"], 330, 0.53);
+
+    // Runs started before the period are left out
+    let summary = history
+        .usage_summary(chrono::Utc::now() + chrono::Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(summary.runs, 0);
+    assert!(summary.usage.by_tag.is_empty());
+}
+
+#[tokio::test]
+async fn test_usage_summary_needs_a_run_history() {
+    let error = WorkflowExecutor::new()
+        .usage_summary(Duration::from_secs(60))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("run history"), "{error}");
+}
@@ -302,6 +302,283 @@ fn test_invalid_schema() {
         .any(|e| e.error_code == "INVALID_SCHEMA"));
 }
 
+fn error_codes(result: &validation::ValidationResult) -> Vec<(&str, &str)> {
+    result
+        .errors
+        .iter()
+        .map(|e| (e.field_path.as_str(), e.error_code.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_json_schema_nested_objects_report_paths() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "user": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string", "minLength": 1},
+                    "address": {
+                        "type": "object",
+                        "properties": {
+                            "zip": {"type": "string", "pattern": "^[0-9]{5}$"},
+                            "city": {"type": "string"}
+                        },
+                        "required": ["zip", "city"],
+                        "additionalProperties": false
+                    }
+                },
+                "required": ["name"]
+            }
+        }
+    });
+
+    let valid = serde_json::json!({"user": {"name": "Ada", "address": {"zip": "12345", "city": "x"}}});
+    let result = validator.validate_value(&valid, &schema);
+    assert!(result.is_valid, "{:?}", result.errors);
+
+    let invalid = serde_json::json!({"user": {"name": "", "address": {"zip": "12a", "street": "x"}}});
+    let result = validator.validate_value(&invalid, &schema);
+    assert_eq!(
+        error_codes(&result),
+        vec![
+            ("root.user.address.city", "MISSING_REQUIRED_PROPERTY"),
+            ("root.user.address.street", "UNKNOWN_PROPERTY"),
+            ("root.user.address.zip", "PATTERN_MISMATCH"),
+            ("root.user.name", "STRING_TOO_SHORT"),
+        ]
+    );
+}
+
+#[test]
+fn test_json_schema_array_constraints() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "type": "array",
+        "items": {"type": "integer", "minimum": 0},
+        "minItems": 2,
+        "maxItems": 4,
+        "uniqueItems": true,
+        "contains": {"const": 7}
+    });
+
+    assert!(validator.validate_value(&serde_json::json!([1, 7, 3]), &schema).is_valid);
+
+    let result = validator.validate_value(&serde_json::json!([1]), &schema);
+    assert_eq!(
+        error_codes(&result),
+        vec![("root", "ARRAY_TOO_SHORT"), ("root", "CONTAINS_MISMATCH")]
+    );
+
+    let result = validator.validate_value(&serde_json::json!([7, 1, 2, 3, 1.0]), &schema);
+    assert_eq!(
+        error_codes(&result),
+        vec![("root", "ARRAY_TOO_LONG"), ("root[4]", "DUPLICATE_ITEMS")]
+    );
+
+    let result = validator.validate_value(&serde_json::json!([7, -1, "x"]), &schema);
+    assert_eq!(
+        error_codes(&result),
+        vec![("root[1]", "NUMBER_TOO_SMALL"), ("root[2]", "TYPE_MISMATCH")]
+    );
+}
+
+#[test]
+fn test_json_schema_tuple_items() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "type": "array",
+        "items": [{"type": "string"}, {"type": "number"}],
+        "additionalItems": false
+    });
+
+    assert!(validator.validate_value(&serde_json::json!(["a", 1]), &schema).is_valid);
+    let result = validator.validate_value(&serde_json::json!([1, 1, true]), &schema);
+    assert_eq!(
+        error_codes(&result),
+        vec![("root[0]", "TYPE_MISMATCH"), ("root[2]", "ADDITIONAL_ITEMS")]
+    );
+}
+
+#[test]
+fn test_json_schema_local_refs() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "definitions": {
+            "node": {
+                "type": "object",
+                "properties": {
+                    "value": {"type": "integer"},
+                    "children": {"type": "array", "items": {"$ref": "#/definitions/node"}}
+                },
+                "required": ["value"]
+            }
+        },
+        "$ref": "#/definitions/node"
+    });
+
+    let tree = serde_json::json!({"value": 1, "children": [{"value": 2, "children": [{"value": 3}]}]});
+    assert!(validator.validate_value(&tree, &schema).is_valid);
+
+    let tree = serde_json::json!({"value": 1, "children": [{"value": 2, "children": [{"value": "3"}]}]});
+    let result = validator.validate_value(&tree, &schema);
+    assert_eq!(
+        error_codes(&result),
+        vec![("root.children[0].children[0].value", "TYPE_MISMATCH")]
+    );
+
+    let result =
+        validator.validate_value(&serde_json::json!(1), &serde_json::json!({"$ref": "#/definitions/missing"}));
+    assert_eq!(error_codes(&result), vec![("root", "UNRESOLVED_REF")]);
+
+    let result = validator.validate_value(
+        &serde_json::json!(1),
+        &serde_json::json!({"$ref": "https://example.com/schema.json"}),
+    );
+    assert_eq!(error_codes(&result), vec![("root", "UNSUPPORTED_REF")]);
+
+    let result = validator.validate_value(&serde_json::json!(1), &serde_json::json!({"$ref": "#"}));
+    assert_eq!(error_codes(&result), vec![("root", "REF_CYCLE")]);
+}
+
+#[test]
+fn test_json_schema_combinators() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "anyOf": [{"type": "string"}, {"type": "integer"}],
+        "not": {"const": 13}
+    });
+    assert!(validator.validate_value(&serde_json::json!("x"), &schema).is_valid);
+    assert!(validator.validate_value(&serde_json::json!(4), &schema).is_valid);
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!(13), &schema)),
+        vec![("root", "NOT_MISMATCH")]
+    );
+    let result = validator.validate_value(&serde_json::json!(1.5), &schema);
+    assert_eq!(error_codes(&result), vec![("root", "ANY_OF_MISMATCH")]);
+    assert!(result.errors[0].message.contains("#0: root: Type mismatch"));
+
+    let schema = serde_json::json!({"oneOf": [{"type": "number"}, {"type": "integer"}]});
+    assert!(validator.validate_value(&serde_json::json!(1.5), &schema).is_valid);
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!(2), &schema)),
+        vec![("root", "ONE_OF_MISMATCH")]
+    );
+
+    let schema = serde_json::json!({
+        "type": "object",
+        "if": {"properties": {"kind": {"const": "card"}}},
+        "then": {"required": ["card_number"]},
+        "else": {"required": ["iban"]},
+        "dependencies": {"card_number": ["expiry"]}
+    });
+    assert!(validator.validate_value(&serde_json::json!({"kind": "bank", "iban": "x"}), &schema).is_valid);
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!({"kind": "card"}), &schema)),
+        vec![("root.card_number", "MISSING_REQUIRED_PROPERTY")]
+    );
+    assert_eq!(
+        error_codes(
+            &validator.validate_value(&serde_json::json!({"kind": "card", "card_number": "1"}), &schema)
+        ),
+        vec![("root.expiry", "MISSING_DEPENDENCY")]
+    );
+}
+
+#[test]
+fn test_json_schema_object_keywords() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {"id": {"type": "integer"}},
+        "patternProperties": {"^x-": {"type": "string"}},
+        "additionalProperties": {"type": "boolean"},
+        "propertyNames": {"maxLength": 8},
+        "minProperties": 1,
+        "maxProperties": 3
+    });
+
+    let valid = serde_json::json!({"id": 1, "x-tag": "a", "flag": true});
+    assert!(validator.validate_value(&valid, &schema).is_valid);
+
+    let invalid = serde_json::json!({"id": 1, "x-tag": 2, "flag": "yes", "long_name": true});
+    assert_eq!(
+        error_codes(&validator.validate_value(&invalid, &schema)),
+        vec![
+            ("root", "TOO_MANY_PROPERTIES"),
+            ("root.flag", "TYPE_MISMATCH"),
+            ("root.long_name", "STRING_TOO_LONG"),
+            ("root.x-tag", "TYPE_MISMATCH"),
+        ]
+    );
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!({}), &schema)),
+        vec![("root", "TOO_FEW_PROPERTIES")]
+    );
+}
+
+#[test]
+fn test_json_schema_numbers_and_boolean_schemas() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "type": ["number", "null"],
+        "exclusiveMinimum": 0,
+        "exclusiveMaximum": 1,
+        "multipleOf": 0.1
+    });
+    assert!(validator.validate_value(&serde_json::json!(0.3), &schema).is_valid);
+    assert!(validator.validate_value(&serde_json::json!(null), &schema).is_valid);
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!(0), &schema)),
+        vec![("root", "NUMBER_TOO_SMALL")]
+    );
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!(0.35), &schema)),
+        vec![("root", "NOT_MULTIPLE_OF")]
+    );
+    let result = validator.validate_value(&serde_json::json!("1"), &schema);
+    assert_eq!(result.errors[0].expected.as_deref(), Some("number or null"));
+
+    let schema = serde_json::json!({"properties": {"open": true, "closed": false}});
+    assert!(validator.validate_value(&serde_json::json!({"open": 1}), &schema).is_valid);
+    assert_eq!(
+        error_codes(&validator.validate_value(&serde_json::json!({"closed": 1}), &schema)),
+        vec![("root.closed", "FALSE_SCHEMA")]
+    );
+}
+
+#[test]
+fn test_json_schema_unsupported_keywords() {
+    let validator = TypeValidator::new();
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "tags": {"type": "array", "prefixItems": [{"type": "string"}]}
+        },
+        "unevaluatedProperties": false
+    });
+
+    let result = validator.validate_value(&serde_json::json!({"tags": []}), &schema);
+    assert_eq!(error_codes(&result), vec![("root", "UNSUPPORTED_KEYWORD")]);
+    assert!(result.errors[0].message.contains("unevaluatedProperties"));
+
+    let schema = serde_json::json!({"properties": {"tags": {"prefixItems": []}}});
+    let result = validator.validate_value(&serde_json::json!({"tags": []}), &schema);
+    assert_eq!(error_codes(&result), vec![("root.tags", "UNSUPPORTED_KEYWORD")]);
+
+    // Annotations and `$defs` containers are accepted
+    let schema = serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Item",
+        "format": "email",
+        "$defs": {"name": {"type": "string"}},
+        "properties": {"name": {"$ref": "#/$defs/name"}}
+    });
+    assert!(validator.validate_value(&serde_json::json!({"name": "a"}), &schema).is_valid);
+}
+
 // Mock custom validator for testing
 struct MockValidator {
     name: String,
@@ -0,0 +1,249 @@
+//! Executor warm-up: priming providers with cold starts, health-checking the
+//! others and creating the agents of agent nodes ahead of execution
+
+use super::test_helpers::{MockResponse, MockServer, Requests, ScriptedProvider, TestAgent};
+use graphbit_core::{
+    agents::AgentConfig,
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    llm::LlmConfig,
+    types::{AgentId, WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Calls the provider of an agent received
+struct Calls {
+    requests: Requests,
+    health_checks: Arc<AtomicUsize>,
+}
+
+impl Calls {
+    fn completions(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    fn health_checks(&self) -> usize {
+        self.health_checks.load(Ordering::SeqCst)
+    }
+}
+
+/// Register an agent whose provider serves `model`, returning its id and the
+/// calls its provider receives
+async fn register_agent(
+    executor: &WorkflowExecutor,
+    llm_config: LlmConfig,
+    model: &'static str,
+    cold_start: bool,
+) -> (AgentId, Calls) {
+    let mut provider = ScriptedProvider::replying("Done").with_model(model);
+    if cold_start {
+        provider = provider.with_cold_start();
+    }
+    let calls = Calls {
+        requests: provider.requests(),
+        health_checks: provider.health_checks(),
+    };
+    let agent_id = AgentId::new();
+    executor
+        .register_agent(Arc::new(TestAgent::new(
+            AgentConfig::new("Agent", "", llm_config).with_id(agent_id.clone()),
+            provider,
+        )))
+        .await;
+    (agent_id, calls)
+}
+
+fn agent_node(name: &str, agent_id: AgentId) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(agent_id, format!("Prompt for {name}")),
+        },
+    )
+    .with_registered_agent()
+}
+
+/// `draft -> review -> publish`; `draft` and `review` share one agent
+fn workflow(shared: AgentId, other: Option<AgentId>) -> Workflow {
+    let mut workflow = Workflow::new("warm up", "");
+    let draft = workflow
+        .add_node(agent_node("draft", shared.clone()))
+        .unwrap();
+    let review = workflow.add_node(agent_node("review", shared)).unwrap();
+    workflow
+        .connect_nodes(draft, review.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    if let Some(other) = other {
+        let publish = workflow.add_node(agent_node("publish", other)).unwrap();
+        workflow
+            .connect_nodes(review, publish, WorkflowEdge::data_flow())
+            .unwrap();
+    }
+    workflow
+}
+
+#[tokio::test]
+async fn test_warm_up_primes_cold_start_providers_once() {
+    let executor = WorkflowExecutor::new();
+    let (cold, cold_calls) =
+        register_agent(&executor, LlmConfig::ollama("llama3"), "llama3", true).await;
+    let (warm, warm_calls) = register_agent(
+        &executor,
+        LlmConfig::openai("sk-test", "gpt-4o-mini"),
+        "gpt-4o-mini",
+        false,
+    )
+    .await;
+
+    let report = executor.warm_up(&workflow(cold, Some(warm))).await.unwrap();
+
+    assert!(report.is_ok(), "{report:?}");
+    assert!(report.agents_created.is_empty());
+    assert_eq!(cold_calls.completions(), 1);
+    assert_eq!(cold_calls.health_checks(), 0);
+    assert_eq!(warm_calls.completions(), 0);
+    assert_eq!(warm_calls.health_checks(), 1);
+
+    assert_eq!(report.providers.len(), 2);
+    let primed = &report.providers[0];
+    assert!(primed.primed);
+    assert_eq!(primed.health.model, "llama3");
+    assert_eq!(primed.nodes, ["draft", "review"]);
+    let checked = &report.providers[1];
+    assert!(!checked.primed);
+    assert_eq!(checked.health.model, "gpt-4o-mini");
+    assert_eq!(checked.health.latency_ms, 3);
+    assert_eq!(checked.nodes, ["publish"]);
+}
+
+#[tokio::test]
+async fn test_warm_up_without_priming_only_health_checks() {
+    let executor = WorkflowExecutor::new().with_warm_up_priming(false);
+    let (cold, calls) =
+        register_agent(&executor, LlmConfig::ollama("llama3"), "llama3", true).await;
+
+    let report = executor.warm_up(&workflow(cold, None)).await.unwrap();
+
+    assert!(!report.providers[0].primed);
+    assert_eq!(calls.completions(), 0);
+    assert_eq!(calls.health_checks(), 1);
+}
+
+#[tokio::test]
+async fn test_execution_warms_up_when_asked() {
+    let executor = WorkflowExecutor::new().without_retries();
+    let (cold, calls) =
+        register_agent(&executor, LlmConfig::ollama("llama3"), "llama3", true).await;
+    let workflow = workflow(cold, None);
+
+    let context = WorkflowContext::new(workflow.id.clone()).with_warm_up(true);
+    let context = executor
+        .execute_with_context(
+            workflow.clone(),
+            None,
+            None,
+            graphbit_core::stream::StreamMode::Updates,
+            context,
+        )
+        .await
+        .unwrap();
+    assert!(matches!(context.state, WorkflowState::Completed));
+    // One priming call, then one call per node
+    assert_eq!(calls.completions(), 3);
+    let report = context.warm_up_report.as_ref().unwrap();
+    assert!(report.providers[0].primed);
+    assert_eq!(report.providers[0].nodes, ["draft", "review"]);
+
+    // The report survives serialization of the context
+    let json = serde_json::to_string(&context).unwrap();
+    let restored: WorkflowContext = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.warm_up_report.unwrap().providers.len(), 1);
+
+    let context = executor.execute(workflow, None).await.unwrap();
+    assert!(context.warm_up_report.is_none());
+    assert_eq!(calls.completions(), 5);
+}
+
+fn unregistered_agent_node(name: &str) -> WorkflowNode {
+    WorkflowNode::new(
+        name,
+        "",
+        NodeType::Agent {
+            config: AgentNodeConfig::new(AgentId::new(), format!("Prompt for {name}")),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_warm_up_creates_agents_for_agent_nodes() {
+    let server = MockServer::responding(MockResponse::json(
+        200,
+        r#"{"id":"gpt-4o-mini","object":"model","owned_by":"openai"}"#,
+    ))
+    .await;
+    let requests = server.requests();
+    let executor = WorkflowExecutor::new().with_default_llm_config(
+        LlmConfig::openai_with_base_url("sk-test", "gpt-4o-mini", format!("{}/v1", server.url())),
+    );
+    let mut workflow = Workflow::new("auto agents", "");
+    let first = workflow.add_node(unregistered_agent_node("first")).unwrap();
+    let second = workflow
+        .add_node(unregistered_agent_node("second"))
+        .unwrap();
+    workflow
+        .connect_nodes(first, second, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let report = executor.warm_up(&workflow).await.unwrap();
+    assert!(report.is_ok(), "{report:?}");
+    assert_eq!(report.agents_created.len(), 2);
+    for agent_id in &report.agents_created {
+        assert!(executor.agent(agent_id).await.is_some());
+    }
+    // Both agents call the same provider and model, which is checked once
+    assert_eq!(report.providers.len(), 1);
+    assert_eq!(report.providers[0].nodes, ["first", "second"]);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // The agents are kept, so a second warm-up creates none
+    let report = executor.warm_up(&workflow).await.unwrap();
+    assert!(report.agents_created.is_empty());
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_warm_up_reports_failures_without_failing() {
+    let server = MockServer::responding(MockResponse::json(
+        401,
+        r#"{"error":{"message":"Incorrect API key provided","code":"invalid_api_key"}}"#,
+    ))
+    .await;
+    let executor = WorkflowExecutor::new().with_default_llm_config(
+        LlmConfig::openai_with_base_url("sk-test", "gpt-4o-mini", format!("{}/v1", server.url())),
+    );
+    let mut workflow = Workflow::new("bad key", "");
+    workflow.add_node(unregistered_agent_node("only")).unwrap();
+
+    let report = executor.warm_up(&workflow).await.unwrap();
+    assert!(!report.is_ok());
+    assert!(!report.providers[0].health.authenticated);
+    assert!(report.providers[0].health.error.is_some());
+}
+
+#[tokio::test]
+async fn test_warm_up_rejects_invalid_workflow() {
+    let executor = WorkflowExecutor::new();
+    let mut workflow = Workflow::new("cyclic", "");
+    let a = workflow.add_node(unregistered_agent_node("a")).unwrap();
+    let b = workflow.add_node(unregistered_agent_node("b")).unwrap();
+    workflow
+        .connect_nodes(a.clone(), b.clone(), WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+        .connect_nodes(b, a, WorkflowEdge::data_flow())
+        .unwrap();
+
+    assert!(executor.warm_up(&workflow).await.is_err());
+}
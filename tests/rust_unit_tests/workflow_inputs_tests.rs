@@ -0,0 +1,269 @@
+//! Run-time workflow inputs and secret injection
+
+use super::test_helpers::{MockResponse, MockServer};
+use graphbit_core::{
+    errors::GraphBitError,
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    stream::{StreamEvent, StreamMode},
+    types::{Secrets, WorkflowContext, WorkflowId, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+const TOKEN: &str = "sk-live-4f9a2c7e1b";
+
+/// Start an HTTP server that answers with `status` and echoes the request's
+/// `Authorization` header back in a JSON body, recording the headers it received
+async fn spawn_echo_server(status: u16) -> (String, Arc<Mutex<Vec<String>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = received.clone();
+    let server = MockServer::new(move |request| {
+        let authorization = request
+            .header("authorization")
+            .unwrap_or_default()
+            .to_string();
+        log.lock().unwrap().push(authorization.clone());
+        std::future::ready(MockResponse::json(
+            status,
+            json!({ "authorization": authorization }),
+        ))
+    })
+    .await;
+    (format!("{}/echo", server.url()), received)
+}
+
+fn authorized_fetch_workflow(url: String, header: &str) -> Workflow {
+    let mut workflow = Workflow::new("secrets", "Authorized fetch");
+    workflow
+        .add_node(WorkflowNode::new(
+            "fetch",
+            "Fetch with a token",
+            NodeType::HttpRequest {
+                url,
+                method: "GET".to_string(),
+                headers: HashMap::from([("Authorization".to_string(), header.to_string())]),
+            },
+        ))
+        .unwrap();
+    workflow
+}
+
+fn secrets() -> Secrets {
+    Secrets::from_iter([("API_TOKEN", TOKEN)])
+}
+
+/// Writer collecting formatted tracing output
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).to_string()
+    }
+}
+
+#[test]
+fn test_input_references_resolve_in_templates() {
+    let context = WorkflowContext::new(WorkflowId::new()).with_inputs(HashMap::from([
+        ("question".to_string(), json!("What changed?")),
+        (
+            "document".to_string(),
+            json!({"path": "/tmp/report.pdf", "pages": 12}),
+        ),
+    ]));
+
+    let resolved = WorkflowExecutor::resolve_template_variables(
+        "Answer {{input.question}} using {{input.document.path}} ({{input.document.pages}} pages). {{input.missing}}",
+        &context,
+    );
+    assert_eq!(
+        resolved,
+        "Answer What changed? using /tmp/report.pdf (12 pages). {{input.missing}}"
+    );
+}
+
+#[tokio::test]
+async fn test_inputs_are_available_to_expressions() {
+    let mut workflow = Workflow::new("inputs", "Inputs in expressions");
+    workflow
+        .add_node(WorkflowNode::new(
+            "shout",
+            "Upper-case the question",
+            NodeType::Transform {
+                transformation: "upper(vars.question) + '?'".to_string(),
+            },
+        ))
+        .unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_inputs(
+            workflow,
+            None,
+            HashMap::from([("question".to_string(), json!("why"))]),
+            Secrets::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(context.get_node_output("shout"), Some(&json!("WHY?")));
+    assert_eq!(context.get_variable("question"), Some(&json!("why")));
+}
+
+#[tokio::test]
+async fn test_secret_is_sent_but_never_exported() {
+    let (url, received) = spawn_echo_server(200).await;
+    let workflow = authorized_fetch_workflow(url, "Bearer {{secret.API_TOKEN}}");
+
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_inputs(workflow, None, HashMap::new(), secrets())
+        .await
+        .unwrap();
+
+    assert!(matches!(context.state, WorkflowState::Completed));
+    assert_eq!(*received.lock().unwrap(), vec![format!("Bearer {TOKEN}")]);
+    assert_eq!(
+        context.get_node_output("fetch"),
+        Some(&json!({"status": 200, "body": {"authorization": "Bearer [REDACTED]"}}))
+    );
+
+    let serialized = serde_json::to_string(&context).unwrap();
+    assert!(!serialized.contains(TOKEN));
+    assert!(!format!("{context:?}").contains(TOKEN));
+
+    let restored: WorkflowContext = serde_json::from_str(&serialized).unwrap();
+    assert!(restored.secrets.is_empty());
+
+    let logs = logs.contents();
+    assert!(logs.contains("fetch"), "expected execution to be traced");
+    assert!(!logs.contains(TOKEN));
+}
+
+#[tokio::test]
+async fn test_secret_is_redacted_from_streamed_failures() {
+    let (url, _) = spawn_echo_server(401).await;
+    let workflow = authorized_fetch_workflow(url, "Bearer {{secret.API_TOKEN}}");
+    let context = WorkflowContext::new(workflow.id.clone()).with_secrets(secrets());
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_context(workflow, None, Some(tx), StreamMode::Updates, context)
+        .await
+        .unwrap();
+
+    let mut node_error = None;
+    while let Ok(event) = rx.try_recv() {
+        assert!(!serde_json::to_string(&event).unwrap().contains(TOKEN));
+        if let StreamEvent::NodeFailed { error, .. } = event {
+            node_error = Some(error);
+        }
+    }
+    let node_error = node_error.expect("expected a NodeFailed event");
+    assert!(node_error.contains("Bearer [REDACTED]"), "{node_error}");
+    assert_eq!(context.get_stats().unwrap().failed_nodes, 1);
+}
+
+#[tokio::test]
+async fn test_missing_secret_fails_before_execution() {
+    let (url, received) = spawn_echo_server(200).await;
+    let workflow = authorized_fetch_workflow(url, "Bearer {{secret.API_TOKEN}}");
+
+    let err = WorkflowExecutor::new()
+        .without_retries()
+        .execute(workflow, None)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, GraphBitError::Configuration { .. }), "{err}");
+    assert!(err.to_string().contains("API_TOKEN"));
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_secrets_debug_lists_names_only() {
+    let secrets = Secrets::from_iter([("API_TOKEN", TOKEN), ("DB_PASSWORD", "hunter2")]);
+    assert_eq!(
+        format!("{secrets:?}"),
+        r#"{"API_TOKEN": "[REDACTED]", "DB_PASSWORD": "[REDACTED]"}"#
+    );
+
+    let context = WorkflowContext::new(WorkflowId::new()).with_secrets(secrets);
+    let debug = format!("{context:?}");
+    assert!(!debug.contains(TOKEN) && !debug.contains("hunter2"));
+    assert!(
+        !serde_json::to_string(&context)
+            .unwrap()
+            .contains("API_TOKEN")
+    );
+}
+
+#[test]
+fn test_secret_injection_and_redaction() {
+    let secrets = Secrets::from_iter([("KEY", "abc"), ("LONG_KEY", "abcdef")]);
+    assert_eq!(
+        secrets
+            .inject("{{secret.KEY}}-{{secret.LONG_KEY}}")
+            .unwrap(),
+        "abc-abcdef"
+    );
+    assert!(secrets.inject("{{secret.OTHER}}").is_err());
+    assert_eq!(secrets.redact("abcdef abc"), "[REDACTED] [REDACTED]");
+
+    let mut value = json!({"nested": ["token abcdef", 1, null]});
+    secrets.redact_value(&mut value);
+    assert_eq!(value, json!({"nested": ["token [REDACTED]", 1, null]}));
+}
+
+#[tokio::test]
+async fn test_secret_injection_leaves_workflow_edges_intact() {
+    let (url, received) = spawn_echo_server(200).await;
+    let mut workflow = authorized_fetch_workflow(url, "Token {{secret.API_TOKEN}}");
+    let fetch = workflow.graph.get_nodes().keys().next().unwrap().clone();
+    let status = workflow
+        .add_node(WorkflowNode::new(
+            "status",
+            "Read the status",
+            NodeType::Transform {
+                transformation: "input.status".to_string(),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(fetch, status, WorkflowEdge::data_flow())
+        .unwrap();
+
+    let context = WorkflowExecutor::new()
+        .without_retries()
+        .execute_with_inputs(workflow, None, HashMap::new(), secrets())
+        .await
+        .unwrap();
+
+    assert_eq!(*received.lock().unwrap(), vec![format!("Token {TOKEN}")]);
+    assert_eq!(context.get_node_output("status"), Some(&json!(200)));
+}
@@ -0,0 +1,142 @@
+//! Archiving a finished run with `WorkflowContext::to_json` and inspecting it
+//! again after `WorkflowContext::from_json`
+
+use graphbit_core::{
+    graph::{NodeType, WorkflowEdge, WorkflowNode},
+    output_store::{FileOutputStore, OutputRef, OutputSpill},
+    types::{WorkflowContext, WorkflowState},
+    workflow::{Workflow, WorkflowExecutor},
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// `draft` produces a title and a body of `body_len` characters, `measure` reads them back
+fn draft_workflow(body_len: usize) -> Workflow {
+    let mut workflow = Workflow::new("archive", "Archived run");
+    let draft = workflow
+        .add_node(WorkflowNode::new(
+            "draft",
+            "Write the draft",
+            NodeType::Transform {
+                transformation: format!(
+                    "{{'title': 'Graph archives', 'body': '{}'}}",
+                    "lorem ".repeat(body_len / 6)
+                ),
+            },
+        ))
+        .unwrap();
+    let measure = workflow
+        .add_node(WorkflowNode::new(
+            "measure",
+            "Measure the draft",
+            NodeType::Transform {
+                transformation: "{'title': input.title, 'length': len(input.body)}".to_string(),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(draft, measure, WorkflowEdge::data_flow())
+        .unwrap();
+    workflow
+}
+
+async fn run(executor: WorkflowExecutor, body_len: usize) -> WorkflowContext {
+    let context = executor
+        .without_retries()
+        .execute(draft_workflow(body_len), None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(context.state, WorkflowState::Completed),
+        "{:?}",
+        context.state
+    );
+    context
+}
+
+#[tokio::test]
+async fn test_round_trip_keeps_outputs_stats_and_metadata() {
+    let context = run(WorkflowExecutor::new(), 60).await;
+
+    let archived = WorkflowContext::from_json(&context.to_json(None).unwrap()).unwrap();
+
+    assert!(matches!(archived.state, WorkflowState::Completed));
+    assert_eq!(archived.workflow_id, context.workflow_id);
+    assert_eq!(archived.execution_id, context.execution_id);
+    assert_eq!(archived.started_at, context.started_at);
+    assert_eq!(archived.completed_at, context.completed_at);
+    for node in ["draft", "measure"] {
+        assert_eq!(
+            archived.get_node_output(node),
+            context.get_node_output(node),
+            "{node}"
+        );
+    }
+    assert_eq!(
+        archived.get_node_output("measure"),
+        Some(&json!({"title": "Graph archives", "length": 60}))
+    );
+    assert_eq!(
+        serde_json::to_value(archived.get_stats()).unwrap(),
+        serde_json::to_value(context.get_stats()).unwrap()
+    );
+    assert!(archived.get_stats().is_some());
+    assert_eq!(archived.metadata, context.metadata);
+    assert_eq!(archived.node_attempts, context.node_attempts);
+    assert_eq!(
+        archived.get_node_executions().len(),
+        context.get_node_executions().len()
+    );
+    // Serializing the reloaded context gives the same JSON again
+    assert_eq!(
+        archived.to_json(None).unwrap(),
+        WorkflowContext::from_json(&archived.to_json(None).unwrap())
+            .unwrap()
+            .to_json(None)
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_max_value_chars_truncates_outputs() {
+    let context = run(WorkflowExecutor::new(), 600).await;
+
+    let archived = WorkflowContext::from_json(&context.to_json(Some(20)).unwrap()).unwrap();
+
+    let draft = archived.get_node_output("draft").unwrap();
+    assert_eq!(draft["title"], "Graph archives");
+    let body = draft["body"].as_str().unwrap();
+    assert!(body.starts_with("lorem lorem lorem lo"), "{body}");
+    assert!(body.ends_with("... [truncated 580 chars]"), "{body}");
+    // Numbers and other non-string values are kept
+    assert_eq!(archived.get_node_output("measure").unwrap()["length"], 600);
+}
+
+#[tokio::test]
+async fn test_spilled_outputs_stay_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let spill = OutputSpill::new(Arc::new(FileOutputStore::new(dir.path()).unwrap()), 256);
+    let context = run(
+        WorkflowExecutor::new().with_output_spill(spill.clone()),
+        600,
+    )
+    .await;
+
+    // Truncation never shortens a reference's digest
+    let json = context.to_json(Some(10)).unwrap();
+    assert!(!json.contains(&"lorem ".repeat(10)));
+    let archived = WorkflowContext::from_json(&json).unwrap();
+    let output_ref = OutputRef::from_value(archived.get_node_output("draft").unwrap())
+        .expect("draft output should be spilled");
+    assert_eq!(output_ref.sha256.len(), 64);
+
+    let archived = archived.with_output_spill(spill);
+    let draft = archived.load_node_output("draft").unwrap();
+    assert_eq!(draft["body"].as_str().unwrap().len(), 600);
+}
+
+#[test]
+fn test_invalid_json_is_rejected() {
+    assert!(WorkflowContext::from_json("{\"state\": 3}").is_err());
+    assert!(WorkflowContext::from_json("not json").is_err());
+}
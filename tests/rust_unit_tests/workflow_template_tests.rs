@@ -0,0 +1,303 @@
+//! Parameterized workflow templates and the template registry
+
+use graphbit_core::{
+    graph::{AgentNodeConfig, NodeType, WorkflowEdge, WorkflowNode},
+    template::{ParameterType, TemplateParameter, TemplateRegistry, WorkflowTemplate},
+    types::AgentId,
+    workflow::Workflow,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Summarize-then-title workflow whose model, topic and token limit are parameters
+fn summarize_template() -> WorkflowTemplate {
+    let mut workflow = Workflow::new("Summarize {{params.topic}}", "Summarize a document");
+    let summarize = workflow
+        .add_node(
+            WorkflowNode::new(
+                "summarize",
+                "Summarize the document",
+                NodeType::Agent {
+                    config: AgentNodeConfig::new(
+                        AgentId::new(),
+                        "Summarize this {{params.topic}} document in {{params.sentences}} sentences",
+                    ),
+                },
+            )
+            .with_model("{{params.model}}")
+            .with_config("max_tokens".to_string(), json!("{{params.max_tokens}}")),
+        )
+        .unwrap();
+    let title = workflow
+        .add_node(WorkflowNode::new(
+            "title",
+            "Title the summary",
+            NodeType::Agent {
+                config: AgentNodeConfig::new(AgentId::new(), "Title this summary"),
+            },
+        ))
+        .unwrap();
+    workflow
+        .connect_nodes(summarize, title, WorkflowEdge::data_flow())
+        .unwrap();
+
+    WorkflowTemplate::from_workflow("summarize_doc", &workflow)
+        .unwrap()
+        .with_parameter("topic", TemplateParameter::new(ParameterType::String))
+        .with_parameter(
+            "model",
+            TemplateParameter::new(ParameterType::String).with_default("gpt-4o-mini"),
+        )
+        .with_parameter(
+            "max_tokens",
+            TemplateParameter::new(ParameterType::Integer).with_default(500),
+        )
+        .with_parameter(
+            "sentences",
+            TemplateParameter::new(ParameterType::Integer)
+                .with_default(3)
+                .with_description("Length of the summary"),
+        )
+}
+
+fn params(values: serde_json::Value) -> HashMap<String, serde_json::Value> {
+    serde_json::from_value(values).unwrap()
+}
+
+fn node<'a>(workflow: &'a Workflow, name: &str) -> &'a WorkflowNode {
+    let id = workflow.graph.get_node_id_by_name(name).unwrap();
+    workflow.graph.get_node(&id).unwrap()
+}
+
+fn prompt(node: &WorkflowNode) -> &str {
+    match &node.node_type {
+        NodeType::Agent { config } => &config.prompt_template,
+        other => panic!("expected an agent node, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_instantiate_substitutes_parameters() {
+    let template = summarize_template();
+    let workflow = template
+        .instantiate(&params(
+            json!({"topic": "legal", "model": "gpt-4o", "max_tokens": 1200}),
+        ))
+        .unwrap();
+
+    assert_eq!(workflow.name, "Summarize legal");
+    assert_eq!(workflow.graph.node_count(), 2);
+    assert_eq!(workflow.graph.edge_count(), 1);
+    let summarize = node(&workflow, "summarize");
+    assert_eq!(
+        prompt(summarize),
+        "Summarize this legal document in 3 sentences"
+    );
+    assert_eq!(summarize.config["model"], json!("gpt-4o"));
+    // A lone reference keeps the parameter's type
+    assert_eq!(summarize.config["max_tokens"], json!(1200));
+    assert_eq!(
+        workflow.metadata["template"],
+        json!({
+            "name": "summarize_doc",
+            "parameters": {"topic": "legal", "model": "gpt-4o", "max_tokens": 1200, "sentences": 3}
+        })
+    );
+}
+
+#[test]
+fn test_defaults_apply_and_instances_are_independent() {
+    let template = summarize_template();
+    let first = template
+        .instantiate(&params(json!({"topic": "finance"})))
+        .unwrap();
+    let second = template
+        .instantiate(&params(json!({"topic": "medical", "sentences": 5})))
+        .unwrap();
+
+    let summarize = node(&first, "summarize");
+    assert_eq!(summarize.config["model"], json!("gpt-4o-mini"));
+    assert_eq!(summarize.config["max_tokens"], json!(500));
+    assert_eq!(
+        prompt(summarize),
+        "Summarize this finance document in 3 sentences"
+    );
+    assert_eq!(
+        prompt(node(&second, "summarize")),
+        "Summarize this medical document in 5 sentences"
+    );
+    assert_ne!(first.id, second.id);
+    // The template itself is left untouched
+    assert!(template.workflow.to_string().contains("{{params.topic}}"));
+}
+
+#[test]
+fn test_parameter_errors() {
+    let template = summarize_template();
+
+    let err = template
+        .instantiate(&HashMap::new())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Template 'summarize_doc': Missing required parameters: topic"),
+        "{err}"
+    );
+
+    let err = template
+        .instantiate(&params(json!({"topic": "legal", "temperature": 0.2})))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Unknown parameters: temperature"), "{err}");
+
+    let err = template
+        .instantiate(&params(json!({"topic": "legal", "max_tokens": "many"})))
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Parameter 'max_tokens' must be of type integer, got \"many\""),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_template_validation() {
+    let undeclared = summarize_template();
+    let mut undeclared = undeclared;
+    undeclared.parameters.remove("model");
+    let err = undeclared.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("references undeclared parameter '{{params.model}}'"),
+        "{err}"
+    );
+
+    let bad_default = summarize_template().with_parameter(
+        "sentences",
+        TemplateParameter::new(ParameterType::Integer).with_default("three"),
+    );
+    let err = bad_default.validate().unwrap_err().to_string();
+    assert!(err.contains("Default of parameter 'sentences'"), "{err}");
+
+    // The instantiated workflow is validated too
+    let mut workflow = Workflow::new("normalize", "Normalize the input");
+    workflow
+        .add_node(WorkflowNode::new(
+            "normalize",
+            "Normalize",
+            NodeType::Transform {
+                transformation: "{{params.expression}}".to_string(),
+            },
+        ))
+        .unwrap();
+    let template = WorkflowTemplate::from_workflow("normalize", &workflow)
+        .unwrap()
+        .with_parameter(
+            "expression",
+            TemplateParameter::new(ParameterType::String).with_default("lower(input"),
+        );
+    let err = template.instantiate(&HashMap::new()).unwrap_err();
+    assert!(err.to_string().contains("invalid transformation"), "{err}");
+    assert!(
+        template
+            .instantiate(&params(json!({"expression": "trim(input)"})))
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_json_round_trip_and_files() {
+    let template = summarize_template();
+    let json = template.to_json().unwrap();
+    assert_eq!(WorkflowTemplate::from_json(&json).unwrap(), template);
+
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["parameters"]["topic"], json!({"type": "string"}));
+    assert_eq!(
+        value["parameters"]["sentences"],
+        json!({"type": "integer", "default": 3, "description": "Length of the summary"})
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("summarize_doc.json");
+    std::fs::write(&path, &json).unwrap();
+    let mut registry = TemplateRegistry::new();
+    assert_eq!(registry.register_file(&path).unwrap(), "summarize_doc");
+    std::fs::remove_file(&path).unwrap();
+
+    let err = WorkflowTemplate::from_file(&path).unwrap_err();
+    assert!(
+        err.to_string().contains("Failed to read workflow template"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_yaml_files() {
+    let workflow = Workflow::new("Greet {{params.who}}", "").to_json().unwrap();
+    let yaml = format!(
+        "# Greeting template\n\
+         name: greet\n\
+         description: Greets someone\n\
+         parameters:\n  \
+           who:\n    \
+             type: string\n    \
+             default: world\n\
+         workflow: {workflow}\n"
+    );
+    let template = WorkflowTemplate::from_yaml(&yaml).unwrap();
+    assert_eq!(template.description, "Greets someone");
+    let workflow = template.instantiate(&HashMap::new()).unwrap();
+    assert_eq!(workflow.name, "Greet world");
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry = TemplateRegistry::new();
+    for file in ["greet.yaml", "greet.YML"] {
+        let path = dir.path().join(file);
+        std::fs::write(&path, &yaml).unwrap();
+        assert_eq!(WorkflowTemplate::from_file(&path).unwrap(), template);
+    }
+    assert_eq!(
+        registry
+            .register_file(dir.path().join("greet.yaml"))
+            .unwrap(),
+        "greet"
+    );
+
+    let path = dir.path().join("broken.yml");
+    std::fs::write(&path, "name: [unclosed").unwrap();
+    let err = WorkflowTemplate::from_file(&path).unwrap_err();
+    assert!(
+        err.to_string().contains("Invalid workflow template"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_registry() {
+    let mut registry = TemplateRegistry::new();
+    assert!(registry.is_empty());
+    registry.register(summarize_template()).unwrap();
+    let other = WorkflowTemplate::from_workflow("empty", &Workflow::new("empty", "")).unwrap();
+    registry.register(other).unwrap();
+    assert_eq!(registry.names(), ["empty", "summarize_doc"]);
+
+    let err = registry.register(summarize_template()).unwrap_err();
+    assert!(err.to_string().contains("already registered"), "{err}");
+
+    let workflow = registry
+        .instantiate("summarize_doc", &params(json!({"topic": "legal"})))
+        .unwrap();
+    assert_eq!(workflow.name, "Summarize legal");
+
+    let err = registry
+        .instantiate("translate", &HashMap::new())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Unknown workflow template 'translate' (registered: empty, summarize_doc)"),
+        "{err}"
+    );
+
+    assert!(registry.remove("empty").is_some());
+    assert_eq!(registry.len(), 1);
+}
@@ -59,6 +59,8 @@ impl graphbit_core::llm::LlmProviderTrait for DummyLlmProvider {
                 prompt_tokens: 10,
                 completion_tokens: 5,
                 total_tokens: 15,
+                cache_read_tokens: None,
+                cache_creation_tokens: None,
             },
             finish_reason: graphbit_core::llm::FinishReason::Stop,
             model: "dummy-model".to_string(),
@@ -426,6 +428,7 @@ async fn test_workflow_with_llm() {
         model: "gpt-3.5-turbo".to_string(),
         base_url: None,
         organization: None,
+        timeouts: Default::default(),
     };
 
     let agent_id = AgentId::new();
@@ -473,6 +476,7 @@ async fn test_workflow_with_anthropic() {
         api_key: std::env::var("ANTHROPIC_API_KEY").unwrap(),
         model: "claude-3-5-sonnet-20241022".to_string(),
         base_url: None,
+        timeouts: Default::default(),
     };
 
     let agent_id = AgentId::new();
@@ -522,6 +526,7 @@ async fn test_workflow_with_ollama() {
     let llm_config = llm::LlmConfig::Ollama {
         model: model.to_string(),
         base_url: Some(base_url.to_string()),
+        timeouts: Default::default(),
     };
 
     let agent_id = AgentId::new();
@@ -564,14 +569,14 @@ async fn test_workflow_graph_operations() {
         "node1".to_string(),
         "Node 1".to_string(),
         NodeType::Transform {
-            transformation: "return input;".to_string(),
+            transformation: "input".to_string(),
         },
     );
     let node2 = WorkflowNode::new(
         "node2".to_string(),
         "Node 2".to_string(),
         NodeType::Transform {
-            transformation: "return input;".to_string(),
+            transformation: "input".to_string(),
         },
     );
 
@@ -773,6 +778,7 @@ fn test_workflow_builder_metadata_and_build_errors() {
         description: "".into(),
         graph: g,
         metadata: Default::default(),
+        input_schema: None,
     };
     let err = wf.validate().unwrap_err();
     assert!(format!("{err}")
@@ -0,0 +1,61 @@
+"""Unit tests for the built-in Rust-native tools."""
+
+import http.server
+import threading
+
+import pytest
+
+from graphbit import BuiltinTool, BuiltinTools, Node
+
+
+class _MockHandler(http.server.BaseHTTPRequestHandler):
+    body = b"x" * 500
+
+    def do_GET(self):  # noqa: N802
+        self.send_response(200)
+        self.send_header("Content-Type", "text/plain")
+        self.send_header("Content-Length", str(len(self.body)))
+        self.end_headers()
+        self.wfile.write(self.body)
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def mock_server():
+    server = http.server.HTTPServer(("127.0.0.1", 0), _MockHandler)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    yield f"http://127.0.0.1:{server.server_address[1]}"
+    server.shutdown()
+
+
+class TestBuiltinHttpTool:
+    """Test cases for BuiltinTools.http()."""
+
+    def test_metadata(self):
+        tool = BuiltinTools.http()
+        assert isinstance(tool, BuiltinTool)
+        assert tool.name == "http_request"
+        assert tool.parameters_schema["required"] == ["url"]
+
+    def test_truncation(self, mock_server):
+        tool = BuiltinTools.http(max_response_bytes=100)
+        result = tool(url=mock_server)
+        assert result["status"] == 200
+        assert result["truncated"] is True
+        assert len(result["body"]) == 100
+
+    def test_allowlist_rejection(self, mock_server):
+        tool = BuiltinTools.http(allowed_hosts=["example.com"])
+        with pytest.raises(Exception, match="allowlist"):
+            tool(url=mock_server)
+
+    def test_invalid_arguments(self):
+        with pytest.raises(ValueError):
+            BuiltinTools.http(max_response_bytes=0)
+
+    def test_agent_node_accepts_builtin_tool(self):
+        node = Node.agent(name="fetcher", prompt="Fetch the page", tools=[BuiltinTools.http()])
+        assert node.get_tool_names() == ["http_request"]
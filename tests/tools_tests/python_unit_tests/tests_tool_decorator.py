@@ -819,5 +819,30 @@ def test_tool_decorator_registry_types(registry_type):
         pytest.skip(f"ToolDecorator registry type testing not available: {e}")
 
 
+
+class TestToolFunctionRegistration:
+    """The @tool decorator registers functions into the shared registry."""
+
+    def test_tool_registers_into_global_registry(self):
+        @tool(description="Multiply two integers", name="registry_multiply")
+        def multiply(a: int, b: int) -> int:
+            return a * b
+
+        assert multiply(2, 3) == 6
+        registry = get_tool_registry()
+        assert registry.has_tool("registry_multiply")
+        assert "registry_multiply" in registry.list_tools()
+        assert registry.unregister_tool("registry_multiply")
+        assert not registry.has_tool("registry_multiply")
+
+    def test_tool_description_alias(self):
+        @tool(_description="Echo the input")
+        def registry_echo(text: str) -> str:
+            return text
+
+        metadata = get_tool_registry().get_tool_metadata("registry_echo")
+        assert metadata is not None
+        assert "Echo the input" in metadata
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v", "--tb=short"])